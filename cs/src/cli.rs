@@ -69,6 +69,17 @@ pub enum Command {
         radius: usize,
     },
 
+    /// Print the source file and line of an offset in a chunk
+    Locate {
+        /// Chunk ID (context_id/chunk_num)
+        #[arg(required = true)]
+        chunk_id: String,
+
+        /// Offset in bytes within the chunk
+        #[arg(required = true)]
+        offset: usize,
+    },
+
     /// Show statistics for a context
     Stats {
         /// Context ID
@@ -85,4 +96,22 @@ pub enum Command {
         #[arg(required = true)]
         context_id: String,
     },
+
+    /// Store identical chunks once (migrates older stores) and report the savings
+    Dedupe,
+
+    /// Serve the store as a JSON HTTP API (OpenAPI description at /openapi.json)
+    Serve {
+        /// Port to listen on
+        #[arg(short, long, default_value_t = crate::server::DEFAULT_PORT)]
+        port: u16,
+
+        /// Address to bind
+        #[arg(short, long, default_value = "127.0.0.1")]
+        bind: String,
+
+        /// File holding the bearer token clients must send (default: $CS_SERVE_TOKEN; unset: no auth)
+        #[arg(short, long)]
+        token_file: Option<PathBuf>,
+    },
 }
@@ -7,14 +7,33 @@
 //!
 //! ```text
 //! .contextstore/
+//! ├── store.lock           # writer lock
+//! ├── objects/             # chunk bodies, stored once per distinct content
+//! │   └── 3f/
+//! │       └── 3fa2…e1.txt  # named by SHA-256
 //! └── {context_id}/
-//!     ├── index.jsonl      # chunk metadata
-//!     └── chunks/
-//!         ├── 0001.txt
-//!         ├── 0002.txt
-//!         └── ...
+//!     └── index.jsonl      # chunk metadata, referencing objects by hash
 //! ```
 //!
+//! Overlapping ingests of the same documents share their chunk bodies.
+//! Stores created before content addressing keep a `chunks/` directory per
+//! context; they stay readable, and `cs dedupe` migrates them.
+//!
+//! # Concurrency
+//!
+//! Any number of readers plus writers, across threads or processes, may share
+//! one store. Writers (`ingest`, `delete`, `dedupe`) serialize on an advisory
+//! lock on `store.lock`, which the OS releases if the holder dies, so there are
+//! no stale locks to clean up. Readers take no lock: objects and indexes are
+//! written to a temp file and renamed into place, so a reader sees either the
+//! old or the new version of a file, never a partial one, and a context is
+//! listed only once its index exists.
+//!
+//! # HTTP API
+//!
+//! `cs serve` exposes ingest, search, chunks, windows and stats as JSON over
+//! HTTP for agents outside Rust; see [`server`].
+//!
 //! # Example
 //!
 //! ```ignore
@@ -28,9 +47,12 @@
 
 pub mod cli;
 pub mod config;
+pub mod server;
 mod store;
 
-pub use store::{ChunkMeta, ContextId, ContextStore, IngestOptions, SearchMatch, SearchOptions};
+pub use store::{
+    ChunkMeta, ContextId, ContextStore, DedupeReport, IngestOptions, SearchMatch, SearchOptions, SourceLocation,
+};
 
 /// Default chunk size (32KB)
 pub const DEFAULT_CHUNK_SIZE: usize = 32 * 1024;
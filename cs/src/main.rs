@@ -54,9 +54,10 @@ fn main() -> Result<()> {
             )?;
             for m in matches {
                 println!(
-                    "{}:{} {}",
+                    "{}:{} {} {}",
                     m.chunk_id.yellow(),
                     m.offset.to_string().dimmed(),
+                    m.location.to_string().cyan(),
                     m.snippet
                 );
             }
@@ -75,6 +76,10 @@ fn main() -> Result<()> {
             let content = store.get_window(&chunk_id, offset, radius)?;
             println!("{}", content);
         }
+        contextstore::cli::Command::Locate { chunk_id, offset } => {
+            let store = ContextStore::open(&config.store_path)?;
+            println!("{}", store.locate(&chunk_id, offset)?);
+        }
         contextstore::cli::Command::Stats { context_id } => {
             let store = ContextStore::open(&config.store_path)?;
             let stats = store.stats(&context_id)?;
@@ -99,6 +104,51 @@ fn main() -> Result<()> {
             store.delete(&context_id)?;
             println!("{} Deleted context: {}", "✓".green(), context_id);
         }
+        contextstore::cli::Command::Dedupe => {
+            let store = ContextStore::open(&config.store_path)?;
+            let report = store.dedupe()?;
+            println!(
+                "{} Deduplicated {} chunks in {} contexts",
+                "✓".green(),
+                report.chunks,
+                report.contexts
+            );
+            println!("  Migrated chunks: {}", report.migrated);
+            println!("  Stored objects: {}", report.objects);
+            println!("  Removed unreferenced objects: {}", report.removed_objects);
+            println!(
+                "  Bytes: {} referenced, {} stored ({} saved)",
+                report.bytes_referenced,
+                report.bytes_stored,
+                report.saved_bytes().to_string().cyan()
+            );
+        }
+        contextstore::cli::Command::Serve { port, bind, token_file } => {
+            let store = ContextStore::open(&config.store_path)?;
+            let token = match token_file {
+                Some(path) => Some(
+                    std::fs::read_to_string(&path)
+                        .context(format!("Failed to read token from {}", path.display()))?
+                        .trim()
+                        .to_string(),
+                ),
+                None => std::env::var(contextstore::server::TOKEN_ENV).ok(),
+            };
+            let listener = std::net::TcpListener::bind((bind.as_str(), port))
+                .context(format!("Failed to bind {}:{}", bind, port))?;
+            println!(
+                "{} Serving {} on http://{}{}",
+                "✓".green(),
+                config.store_path.display(),
+                listener.local_addr()?.to_string().cyan(),
+                if token.as_deref().is_some_and(|t| !t.is_empty()) {
+                    " (token required)"
+                } else {
+                    ""
+                }
+            );
+            std::sync::Arc::new(contextstore::server::Server::new(store, token)).serve(listener)?;
+        }
     }
 
     Ok(())
@@ -0,0 +1,695 @@
+//! HTTP API for `cs serve`
+//!
+//! Exposes the store to agents that cannot link the crate (Python scripts,
+//! other LLM runtimes) as JSON over HTTP:
+//!
+//! ```text
+//! GET  /openapi.json                          OpenAPI 3 description of this API
+//! GET  /contexts                              list context IDs
+//! POST /contexts                              ingest {"paths": [...], "chunk_size"?, "overlap"?}
+//! GET  /contexts/{context_id}/stats           context statistics
+//! POST /contexts/{context_id}/search          search {"pattern", "max_results"?, "case_insensitive"?}
+//! GET  /chunks/{context_id}/{chunk}           full chunk content
+//! GET  /chunks/{context_id}/{chunk}/window    text around ?offset=N&radius=M
+//! GET  /chunks/{context_id}/{chunk}/locate    source file and line of ?offset=N
+//! ```
+//!
+//! Ingest paths are resolved on the server's filesystem. When a token is set,
+//! every route except `/openapi.json` requires `Authorization: Bearer <token>`.
+//!
+//! The server speaks just enough HTTP/1.1 for local tooling: one request per
+//! connection, `Content-Length` bodies only. Bind it to loopback or put it
+//! behind a reverse proxy for TLS.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::time::Duration;
+
+use eyre::{Context, Result};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{debug, info, warn};
+
+use crate::store::{ContextStore, IngestOptions, SearchOptions};
+
+/// Default port for `cs serve`
+pub const DEFAULT_PORT: u16 = 7700;
+
+/// Environment variable holding the API token
+pub const TOKEN_ENV: &str = "CS_SERVE_TOKEN";
+
+/// Longest accepted request line plus headers
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+
+/// Largest accepted request body
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// How long a client may take to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Default radius for window requests, matching `cs window`
+const DEFAULT_WINDOW_RADIUS: usize = 500;
+
+/// Body of `POST /contexts`
+#[derive(Debug, Deserialize)]
+struct IngestRequest {
+    paths: Vec<String>,
+    #[serde(default)]
+    chunk_size: Option<usize>,
+    #[serde(default)]
+    overlap: Option<usize>,
+}
+
+/// Body of `POST /contexts/{context_id}/search`
+#[derive(Debug, Deserialize)]
+struct SearchRequest {
+    pattern: String,
+    #[serde(default)]
+    max_results: Option<usize>,
+    #[serde(default)]
+    case_insensitive: bool,
+}
+
+/// A parsed HTTP request
+#[derive(Debug, Clone, Default)]
+pub struct HttpRequest {
+    pub method: String,
+    /// Path without the query string
+    pub path: String,
+    /// Query parameters, not percent-decoded
+    pub query: HashMap<String, String>,
+    /// Header values by lowercased name
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+/// A response to send back
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: serde_json::Value,
+}
+
+impl HttpResponse {
+    fn json(status: u16, body: serde_json::Value) -> Self {
+        Self { status, body }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self::json(status, json!({ "error": message.into() }))
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            201 => "Created",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            413 => "Payload Too Large",
+            _ => "Internal Server Error",
+        }
+    }
+
+    /// Serialize as an HTTP/1.1 response that closes the connection
+    fn to_bytes(&self) -> Vec<u8> {
+        let body = self.body.to_string();
+        let mut bytes = format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            self.status,
+            self.reason(),
+            body.len()
+        )
+        .into_bytes();
+        bytes.extend_from_slice(body.as_bytes());
+        bytes
+    }
+}
+
+/// Serves a store over HTTP
+pub struct Server {
+    store: ContextStore,
+    token: Option<String>,
+}
+
+impl Server {
+    /// Serve `store`, requiring `token` as a bearer token when set
+    pub fn new(store: ContextStore, token: Option<String>) -> Self {
+        Self {
+            store,
+            token: token.filter(|t| !t.is_empty()),
+        }
+    }
+
+    /// Accept connections forever, one thread per connection
+    pub fn serve(self: Arc<Self>, listener: TcpListener) -> Result<()> {
+        info!(addr = ?listener.local_addr()?, auth = self.token.is_some(), "ContextStore API listening");
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    warn!(error = %e, "Accept failed");
+                    continue;
+                }
+            };
+            let server = self.clone();
+            std::thread::spawn(move || {
+                if let Err(e) = server.handle_connection(stream) {
+                    debug!(error = %e, "Server::serve: connection failed");
+                }
+            });
+        }
+        Ok(())
+    }
+
+    fn handle_connection(&self, mut stream: TcpStream) -> Result<()> {
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        let response = match read_request(&mut stream)? {
+            Ok(request) => self.handle(&request),
+            Err(response) => response,
+        };
+        stream.write_all(&response.to_bytes())?;
+        stream.flush()?;
+        Ok(())
+    }
+
+    /// Answer one request
+    pub fn handle(&self, request: &HttpRequest) -> HttpResponse {
+        debug!(method = %request.method, path = %request.path, "Server::handle: called");
+        if request.path == "/openapi.json" {
+            return match request.method.as_str() {
+                "GET" => HttpResponse::json(200, openapi()),
+                _ => HttpResponse::error(405, "Only GET is accepted"),
+            };
+        }
+        if let Some(token) = &self.token {
+            let presented = request
+                .headers
+                .get("authorization")
+                .and_then(|v| v.strip_prefix("Bearer "))
+                .unwrap_or("");
+            if !constant_time_eq(presented.trim(), token) {
+                return HttpResponse::error(401, "Missing or invalid bearer token");
+            }
+        }
+
+        let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+        let result = match (request.method.as_str(), segments.as_slice()) {
+            ("GET", ["contexts"]) => self.list(),
+            ("POST", ["contexts"]) => self.ingest(&request.body),
+            ("GET", ["contexts", id, "stats"]) => self.stats(id),
+            ("POST", ["contexts", id, "search"]) => self.search(id, &request.body),
+            ("GET", ["chunks", id, chunk]) => self.chunk(id, chunk),
+            ("GET", ["chunks", id, chunk, "window"]) => self.window(id, chunk, &request.query),
+            ("GET", ["chunks", id, chunk, "locate"]) => self.locate(id, chunk, &request.query),
+            (
+                _,
+                ["contexts"] | ["contexts", _, "stats" | "search"] | ["chunks", _, _] | ["chunks", _, _, "window" | "locate"],
+            ) => {
+                Ok(HttpResponse::error(405, format!("{} not allowed on {}", request.method, request.path)))
+            }
+            _ => Ok(HttpResponse::error(404, format!("No route {}", request.path))),
+        };
+        result.unwrap_or_else(store_error)
+    }
+
+    fn list(&self) -> Result<HttpResponse> {
+        Ok(HttpResponse::json(200, json!({ "contexts": self.store.list_contexts()? })))
+    }
+
+    fn ingest(&self, body: &[u8]) -> Result<HttpResponse> {
+        let request: IngestRequest = match serde_json::from_slice(body) {
+            Ok(request) => request,
+            Err(e) => return Ok(HttpResponse::error(400, format!("Invalid body: {}", e))),
+        };
+        if request.paths.is_empty() {
+            return Ok(HttpResponse::error(400, "No paths to ingest"));
+        }
+        let defaults = IngestOptions::default();
+        let options = IngestOptions {
+            chunk_size: request.chunk_size.unwrap_or(defaults.chunk_size),
+            overlap: request.overlap.unwrap_or(defaults.overlap),
+        };
+        if options.chunk_size == 0 || options.overlap >= options.chunk_size {
+            return Ok(HttpResponse::error(400, "chunk_size must be positive and larger than overlap"));
+        }
+        let context_id = self.store.ingest(&request.paths, options)?;
+        info!(%context_id, "Ingested over HTTP");
+        Ok(HttpResponse::json(201, json!({ "context_id": context_id })))
+    }
+
+    fn stats(&self, context_id: &str) -> Result<HttpResponse> {
+        let stats = self.store.stats(context_id)?;
+        Ok(HttpResponse::json(
+            200,
+            json!({
+                "context_id": context_id,
+                "chunk_count": stats.chunk_count,
+                "total_bytes": stats.total_bytes,
+                "source_count": stats.source_count,
+            }),
+        ))
+    }
+
+    fn search(&self, context_id: &str, body: &[u8]) -> Result<HttpResponse> {
+        let request: SearchRequest = match serde_json::from_slice(body) {
+            Ok(request) => request,
+            Err(e) => return Ok(HttpResponse::error(400, format!("Invalid body: {}", e))),
+        };
+        let defaults = SearchOptions::default();
+        let matches = self.store.search(
+            context_id,
+            &request.pattern,
+            SearchOptions {
+                max_results: request.max_results.unwrap_or(defaults.max_results),
+                case_insensitive: request.case_insensitive,
+            },
+        )?;
+        let matches: Vec<_> = matches
+            .into_iter()
+            .map(|m| {
+                json!({
+                    "chunk_id": format!("{}/{}", context_id, m.chunk_id),
+                    "offset": m.offset,
+                    "snippet": m.snippet,
+                    "location": m.location,
+                })
+            })
+            .collect();
+        Ok(HttpResponse::json(200, json!({ "matches": matches })))
+    }
+
+    fn chunk(&self, context_id: &str, chunk: &str) -> Result<HttpResponse> {
+        let chunk_id = format!("{}/{}", context_id, chunk);
+        let content = self.store.get_chunk(&chunk_id)?;
+        Ok(HttpResponse::json(200, json!({ "chunk_id": chunk_id, "content": content })))
+    }
+
+    fn window(&self, context_id: &str, chunk: &str, query: &HashMap<String, String>) -> Result<HttpResponse> {
+        let param = |name: &str| query.get(name).map(|v| v.parse::<usize>());
+        let offset = match offset_param(query) {
+            Ok(offset) => offset,
+            Err(response) => return Ok(response),
+        };
+        let radius = match param("radius") {
+            Some(Ok(radius)) => radius,
+            Some(Err(_)) => return Ok(HttpResponse::error(400, "Invalid radius")),
+            None => DEFAULT_WINDOW_RADIUS,
+        };
+        let chunk_id = format!("{}/{}", context_id, chunk);
+        let content = self.store.get_window(&chunk_id, offset, radius)?;
+        Ok(HttpResponse::json(
+            200,
+            json!({ "chunk_id": chunk_id, "offset": offset, "radius": radius, "content": content }),
+        ))
+    }
+
+    fn locate(&self, context_id: &str, chunk: &str, query: &HashMap<String, String>) -> Result<HttpResponse> {
+        let offset = match offset_param(query) {
+            Ok(offset) => offset,
+            Err(response) => return Ok(response),
+        };
+        let location = self.store.locate(&format!("{}/{}", context_id, chunk), offset)?;
+        Ok(HttpResponse::json(200, serde_json::to_value(location)?))
+    }
+}
+
+/// The required `offset` query parameter
+fn offset_param(query: &HashMap<String, String>) -> std::result::Result<usize, HttpResponse> {
+    match query.get("offset").map(|v| v.parse::<usize>()) {
+        Some(Ok(offset)) => Ok(offset),
+        Some(Err(_)) => Err(HttpResponse::error(400, "Invalid offset")),
+        None => Err(HttpResponse::error(400, "Missing offset")),
+    }
+}
+
+/// Map a store error to a response: unknown IDs are 404, bad patterns 400
+fn store_error(e: eyre::Report) -> HttpResponse {
+    let message = format!("{:#}", e);
+    if e.downcast_ref::<regex::Error>().is_some() || message.contains("past the end") {
+        HttpResponse::error(400, message)
+    } else if message.contains("not found") {
+        HttpResponse::error(404, message)
+    } else {
+        warn!(error = %message, "ContextStore API request failed");
+        HttpResponse::error(500, message)
+    }
+}
+
+/// Compare two strings without exiting early on the first difference
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Read one request; Ok(Err(response)) for requests to reject without routing
+fn read_request(stream: &mut TcpStream) -> Result<std::result::Result<HttpRequest, HttpResponse>> {
+    let mut reader = BufReader::new(stream);
+    let mut request = HttpRequest::default();
+    let mut head_bytes = 0;
+
+    let mut line = String::new();
+    head_bytes += reader.read_line(&mut line).context("Failed to read request line")?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Ok(Err(HttpResponse::error(400, "Malformed request line")));
+    };
+    request.method = method.to_string();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    request.path = path.to_string();
+    request.query = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line).context("Failed to read header")?;
+        head_bytes += read;
+        if head_bytes > MAX_HEAD_BYTES {
+            return Ok(Err(HttpResponse::error(400, "Headers too large")));
+        }
+        let header = line.trim_end();
+        if read == 0 || header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            request
+                .headers
+                .insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let length = match request.headers.get("content-length").map(|v| v.parse::<usize>()) {
+        Some(Ok(length)) => length,
+        Some(Err(_)) => return Ok(Err(HttpResponse::error(400, "Invalid Content-Length"))),
+        None => 0,
+    };
+    if length > MAX_BODY_BYTES {
+        return Ok(Err(HttpResponse::error(413, "Body too large")));
+    }
+    request.body = vec![0; length];
+    reader.read_exact(&mut request.body).context("Failed to read body")?;
+    Ok(Ok(request))
+}
+
+/// OpenAPI 3 description of the API, served at `/openapi.json`
+pub fn openapi() -> serde_json::Value {
+    let context_id = json!({ "name": "context_id", "in": "path", "required": true, "schema": { "type": "string" } });
+    let chunk = json!({
+        "name": "chunk", "in": "path", "required": true,
+        "description": "Chunk number within the context, e.g. 0001",
+        "schema": { "type": "string" }
+    });
+    let error = json!({
+        "description": "Error",
+        "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } }
+    });
+    let ok = |schema: &str| {
+        json!({
+            "description": "OK",
+            "content": { "application/json": { "schema": { "$ref": format!("#/components/schemas/{}", schema) } } }
+        })
+    };
+    let body = |schema: &str| {
+        json!({
+            "required": true,
+            "content": { "application/json": { "schema": { "$ref": format!("#/components/schemas/{}", schema) } } }
+        })
+    };
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "ContextStore API",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "RLM-style external context store: ingest files, search them, and fetch chunks."
+        },
+        "security": [{ "bearer": [] }],
+        "paths": {
+            "/contexts": {
+                "get": {
+                    "summary": "List contexts",
+                    "operationId": "listContexts",
+                    "responses": { "200": ok("ContextList"), "401": error }
+                },
+                "post": {
+                    "summary": "Ingest files (paths on the server) into a new context",
+                    "operationId": "ingest",
+                    "requestBody": body("IngestRequest"),
+                    "responses": { "201": ok("Ingested"), "400": error, "401": error }
+                }
+            },
+            "/contexts/{context_id}/stats": {
+                "get": {
+                    "summary": "Context statistics",
+                    "operationId": "stats",
+                    "parameters": [context_id],
+                    "responses": { "200": ok("Stats"), "401": error, "404": error }
+                }
+            },
+            "/contexts/{context_id}/search": {
+                "post": {
+                    "summary": "Search a context with a regex",
+                    "operationId": "search",
+                    "parameters": [context_id],
+                    "requestBody": body("SearchRequest"),
+                    "responses": { "200": ok("SearchResults"), "400": error, "401": error, "404": error }
+                }
+            },
+            "/chunks/{context_id}/{chunk}": {
+                "get": {
+                    "summary": "Full content of a chunk",
+                    "operationId": "getChunk",
+                    "parameters": [context_id, chunk],
+                    "responses": { "200": ok("Chunk"), "401": error, "404": error }
+                }
+            },
+            "/chunks/{context_id}/{chunk}/window": {
+                "get": {
+                    "summary": "Text around an offset in a chunk",
+                    "operationId": "window",
+                    "parameters": [
+                        context_id,
+                        chunk,
+                        { "name": "offset", "in": "query", "required": true, "schema": { "type": "integer", "minimum": 0 } },
+                        { "name": "radius", "in": "query", "schema": { "type": "integer", "minimum": 0, "default": DEFAULT_WINDOW_RADIUS } }
+                    ],
+                    "responses": { "200": ok("Window"), "400": error, "401": error, "404": error }
+                }
+            },
+            "/chunks/{context_id}/{chunk}/locate": {
+                "get": {
+                    "summary": "Source file and line of an offset in a chunk",
+                    "operationId": "locate",
+                    "parameters": [
+                        context_id,
+                        chunk,
+                        { "name": "offset", "in": "query", "required": true, "schema": { "type": "integer", "minimum": 0 } }
+                    ],
+                    "responses": { "200": ok("SourceLocation"), "400": error, "401": error, "404": error }
+                }
+            }
+        },
+        "components": {
+            "securitySchemes": { "bearer": { "type": "http", "scheme": "bearer" } },
+            "schemas": {
+                "Error": {
+                    "type": "object",
+                    "properties": { "error": { "type": "string" } }
+                },
+                "ContextList": {
+                    "type": "object",
+                    "properties": { "contexts": { "type": "array", "items": { "type": "string" } } }
+                },
+                "IngestRequest": {
+                    "type": "object",
+                    "required": ["paths"],
+                    "properties": {
+                        "paths": { "type": "array", "items": { "type": "string" }, "description": "File paths or glob patterns" },
+                        "chunk_size": { "type": "integer", "default": crate::DEFAULT_CHUNK_SIZE },
+                        "overlap": { "type": "integer", "default": crate::DEFAULT_OVERLAP }
+                    }
+                },
+                "Ingested": {
+                    "type": "object",
+                    "properties": { "context_id": { "type": "string" } }
+                },
+                "Stats": {
+                    "type": "object",
+                    "properties": {
+                        "context_id": { "type": "string" },
+                        "chunk_count": { "type": "integer" },
+                        "total_bytes": { "type": "integer" },
+                        "source_count": { "type": "integer" }
+                    }
+                },
+                "SearchRequest": {
+                    "type": "object",
+                    "required": ["pattern"],
+                    "properties": {
+                        "pattern": { "type": "string", "description": "Regular expression" },
+                        "max_results": { "type": "integer", "default": 10 },
+                        "case_insensitive": { "type": "boolean", "default": false }
+                    }
+                },
+                "SearchResults": {
+                    "type": "object",
+                    "properties": {
+                        "matches": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "chunk_id": { "type": "string", "description": "context_id/chunk" },
+                                    "offset": { "type": "integer" },
+                                    "snippet": { "type": "string" },
+                                    "location": { "$ref": "#/components/schemas/SourceLocation" }
+                                }
+                            }
+                        }
+                    }
+                },
+                "SourceLocation": {
+                    "type": "object",
+                    "properties": {
+                        "source": { "type": "string", "description": "Source file path, as ingested" },
+                        "line": {
+                            "type": "integer",
+                            "nullable": true,
+                            "description": "1-based; null for chunks ingested before line tracking"
+                        },
+                        "byte": { "type": "integer", "description": "Byte offset in the source file" }
+                    }
+                },
+                "Chunk": {
+                    "type": "object",
+                    "properties": { "chunk_id": { "type": "string" }, "content": { "type": "string" } }
+                },
+                "Window": {
+                    "type": "object",
+                    "properties": {
+                        "chunk_id": { "type": "string" },
+                        "offset": { "type": "integer" },
+                        "radius": { "type": "integer" },
+                        "content": { "type": "string" }
+                    }
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn request(method: &str, path: &str, body: &str, token: Option<&str>) -> HttpRequest {
+        let (path, query) = path.split_once('?').unwrap_or((path, ""));
+        HttpRequest {
+            method: method.to_string(),
+            path: path.to_string(),
+            query: query
+                .split('&')
+                .filter_map(|pair| pair.split_once('='))
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            headers: token
+                .map(|t| HashMap::from([("authorization".to_string(), format!("Bearer {}", t))]))
+                .unwrap_or_default(),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_routes_and_auth() {
+        let temp = TempDir::new().unwrap();
+        let doc = temp.path().join("doc.md");
+        fs::write(&doc, "The recursive language model reads chunks on demand.").unwrap();
+        let store = ContextStore::open(temp.path().join("store")).unwrap();
+        let server = Server::new(store, Some("t0ken".to_string()));
+        let call = |method: &str, path: &str, body: &str| server.handle(&request(method, path, body, Some("t0ken")));
+
+        assert_eq!(server.handle(&request("GET", "/contexts", "", None)).status, 401);
+        assert_eq!(server.handle(&request("GET", "/contexts", "", Some("wrong"))).status, 401);
+        assert_eq!(server.handle(&request("GET", "/openapi.json", "", None)).status, 200);
+
+        let body = json!({ "paths": [doc.to_string_lossy()] }).to_string();
+        let response = call("POST", "/contexts", &body);
+        assert_eq!(response.status, 201);
+        let id = response.body["context_id"].as_str().unwrap().to_string();
+        assert_eq!(call("GET", "/contexts", "").body["contexts"], json!([id]));
+
+        let stats = call("GET", &format!("/contexts/{}/stats", id), "");
+        assert_eq!(stats.body["chunk_count"], 1);
+
+        let found = call("POST", &format!("/contexts/{}/search", id), r#"{"pattern": "language"}"#);
+        assert_eq!(found.status, 200);
+        let chunk_id = found.body["matches"][0]["chunk_id"].as_str().unwrap().to_string();
+        assert_eq!(chunk_id, format!("{}/0001", id));
+        assert_eq!(found.body["matches"][0]["offset"], 14);
+
+        let chunk = call("GET", &format!("/chunks/{}", chunk_id), "");
+        assert_eq!(chunk.body["content"], "The recursive language model reads chunks on demand.");
+        let window = call("GET", &format!("/chunks/{}/window?offset=14&radius=5", chunk_id), "");
+        assert_eq!(window.body["content"], "sive langu");
+        let located = call("GET", &format!("/chunks/{}/locate?offset=14", chunk_id), "");
+        assert_eq!(located.body["line"], 1);
+        assert_eq!(located.body["byte"], 14);
+        assert_eq!(found.body["matches"][0]["location"], located.body);
+        assert_eq!(call("GET", &format!("/chunks/{}/locate?offset=999", chunk_id), "").status, 400);
+
+        assert_eq!(call("GET", "/contexts/nope/stats", "").status, 404);
+        assert_eq!(call("GET", "/chunks/nope/0001", "").status, 404);
+        assert_eq!(call("POST", &format!("/contexts/{}/search", id), r#"{"pattern": "("}"#).status, 400);
+        assert_eq!(call("GET", &format!("/chunks/{}/window", chunk_id), "").status, 400);
+        assert_eq!(call("DELETE", "/contexts", "").status, 405);
+        assert_eq!(call("GET", "/nowhere", "").status, 404);
+    }
+
+    #[test]
+    fn test_openapi_describes_every_route() {
+        let spec = openapi();
+        let paths = spec["paths"].as_object().unwrap();
+        for path in [
+            "/contexts",
+            "/contexts/{context_id}/stats",
+            "/contexts/{context_id}/search",
+            "/chunks/{context_id}/{chunk}",
+            "/chunks/{context_id}/{chunk}/window",
+            "/chunks/{context_id}/{chunk}/locate",
+        ] {
+            assert!(paths.contains_key(path), "{}", path);
+        }
+        assert_eq!(spec["openapi"], "3.0.3");
+    }
+
+    #[test]
+    fn test_serve_over_tcp() {
+        let temp = TempDir::new().unwrap();
+        let store = ContextStore::open(temp.path().join("store")).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = Arc::new(Server::new(store, None));
+        std::thread::spawn(move || server.serve(listener));
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        let body = r#"{"paths": []}"#;
+        let raw = format!(
+            "POST /contexts?verbose=1 HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        stream.write_all(raw.as_bytes()).unwrap();
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).unwrap();
+        assert!(reply.starts_with("HTTP/1.1 400 Bad Request\r\n"), "{}", reply);
+        let json: serde_json::Value = serde_json::from_str(reply.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        assert_eq!(json["error"], "No paths to ingest");
+    }
+}
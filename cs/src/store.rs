@@ -1,13 +1,22 @@
 //! Core ContextStore implementation
 
 use eyre::{Context, Result};
+use fs2::FileExt;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
 use std::fs;
 use std::io::{BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
 use tracing::{debug, info};
 use uuid::Uuid;
 
+/// Directory holding chunk bodies named by content hash, shared by all contexts
+const OBJECTS_DIR: &str = "objects";
+
+/// Lock file serializing writers (ingest, delete, dedupe)
+const LOCK_FILE: &str = "store.lock";
+
 /// Unique identifier for a context
 pub type ContextId = String;
 
@@ -25,12 +34,52 @@ pub struct ChunkMeta {
     pub byte_start: u64,
     /// Byte end in source file
     pub byte_end: u64,
-    /// Content hash for staleness detection
+    /// SHA-256 of the chunk body; names its file under `objects/`
     pub content_hash: String,
+    /// Line of the source file the chunk starts on (1-based; 0 in indexes written before line tracking)
+    #[serde(default)]
+    pub line_start: u64,
+    /// Number of source lines the chunk touches
+    #[serde(default)]
+    pub line_count: u64,
     /// Creation timestamp (unix ms)
     pub created_at: i64,
 }
 
+impl ChunkMeta {
+    /// Where `offset` (bytes into this chunk, whose body is `content`) lies in the source file
+    pub fn locate(&self, content: &str, offset: usize) -> SourceLocation {
+        let offset = offset.min(content.len());
+        let line = (self.line_start > 0)
+            .then(|| self.line_start + count_newlines(&content.as_bytes()[..offset]));
+        SourceLocation {
+            source: self.source.clone(),
+            line,
+            byte: self.byte_start + offset as u64,
+        }
+    }
+}
+
+/// A position in an ingested source file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceLocation {
+    /// Source file path, as ingested
+    pub source: String,
+    /// Line number (1-based); None for chunks ingested before line tracking
+    pub line: Option<u64>,
+    /// Byte offset in the source file
+    pub byte: u64,
+}
+
+impl std::fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}", self.source, line),
+            None => write!(f, "{} (byte {})", self.source, self.byte),
+        }
+    }
+}
+
 /// Options for ingesting content
 #[derive(Debug, Clone)]
 pub struct IngestOptions {
@@ -76,6 +125,8 @@ pub struct SearchMatch {
     pub offset: usize,
     /// Snippet of matching text
     pub snippet: String,
+    /// Where the match lies in its source file
+    pub location: SourceLocation,
 }
 
 /// Statistics for a context
@@ -89,6 +140,32 @@ pub struct ContextStats {
     pub source_count: usize,
 }
 
+/// Result of `ContextStore::dedupe`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DedupeReport {
+    /// Contexts scanned
+    pub contexts: usize,
+    /// Chunk references across all contexts
+    pub chunks: usize,
+    /// Chunks moved from a context's `chunks/` directory into `objects/`
+    pub migrated: usize,
+    /// Distinct chunk bodies stored
+    pub objects: usize,
+    /// Objects no context referenced any more, removed
+    pub removed_objects: usize,
+    /// Bytes the chunks take when every context stores its own copy
+    pub bytes_referenced: u64,
+    /// Bytes stored under `objects/`
+    pub bytes_stored: u64,
+}
+
+impl DedupeReport {
+    /// Bytes saved by storing identical chunks once
+    pub fn saved_bytes(&self) -> u64 {
+        self.bytes_referenced.saturating_sub(self.bytes_stored)
+    }
+}
+
 /// The main context store
 pub struct ContextStore {
     /// Base path for storage
@@ -105,84 +182,73 @@ impl ContextStore {
     }
 
     /// Ingest files matching the given patterns into a new context
+    ///
+    /// The context becomes visible to readers only once its index is complete.
     pub fn ingest(&self, patterns: &[String], options: IngestOptions) -> Result<ContextId> {
+        let _lock = self.lock()?;
         let context_id = Uuid::now_v7().to_string();
         let ctx_path = self.base_path.join(&context_id);
-        let chunks_path = ctx_path.join("chunks");
-        fs::create_dir_all(&chunks_path)?;
-
-        let index_path = ctx_path.join("index.jsonl");
-        let mut index_file = fs::File::create(&index_path)?;
+        fs::create_dir_all(&ctx_path)?;
 
-        let mut chunk_num = 0u32;
-
-        for pattern in patterns {
+        let mut chunks = Vec::new();
+        let ingested = patterns.iter().try_for_each(|pattern| {
             // Expand glob pattern
             let paths = glob::glob(pattern).context(format!("Invalid glob pattern: {}", pattern))?;
 
             for entry in paths {
                 let path = entry?;
                 if path.is_file() {
-                    chunk_num = self.ingest_file(&path, &chunks_path, &mut index_file, chunk_num, &options)?;
+                    self.ingest_file(&path, &mut chunks, &options)?;
                 }
             }
+            Ok::<_, eyre::Report>(())
+        });
+        if let Err(e) = ingested {
+            let _ = fs::remove_dir_all(&ctx_path);
+            return Err(e);
         }
+        self.write_index(&context_id, &chunks)?;
 
-        info!(context_id, chunk_count = chunk_num, "Ingestion complete");
+        info!(context_id, chunk_count = chunks.len(), "Ingestion complete");
         Ok(context_id)
     }
 
-    fn ingest_file(
-        &self,
-        path: &Path,
-        chunks_path: &Path,
-        index_file: &mut fs::File,
-        mut chunk_num: u32,
-        options: &IngestOptions,
-    ) -> Result<u32> {
+    fn ingest_file(&self, path: &Path, chunks: &mut Vec<ChunkMeta>, options: &IngestOptions) -> Result<()> {
         let content = fs::read_to_string(path).context(format!("Failed to read file: {}", path.display()))?;
         let content_bytes = content.as_bytes();
         let source = path.to_string_lossy().to_string();
+        let mut line = 1u64;
 
         let mut offset = 0usize;
         while offset < content_bytes.len() {
             let end = (offset + options.chunk_size).min(content_bytes.len());
             let chunk_content = &content_bytes[offset..end];
 
-            chunk_num += 1;
-            let chunk_id = format!("{:04}", chunk_num);
-            let chunk_path = chunks_path.join(format!("{}.txt", chunk_id));
-
-            fs::write(&chunk_path, chunk_content)?;
+            let chunk_id = format!("{:04}", chunks.len() + 1);
+            let content_hash = self.write_object(chunk_content)?;
 
-            let meta = ChunkMeta {
-                chunk_id: chunk_id.clone(),
+            chunks.push(ChunkMeta {
+                chunk_id,
                 source: source.clone(),
                 byte_start: offset as u64,
                 byte_end: end as u64,
-                content_hash: format!("{:x}", md5_hash(chunk_content)),
+                content_hash,
+                line_start: line,
+                line_count: count_newlines(chunk_content.strip_suffix(b"\n").unwrap_or(chunk_content)) + 1,
                 created_at: chrono::Utc::now().timestamp_millis(),
-            };
-
-            let line = serde_json::to_string(&meta)?;
-            writeln!(index_file, "{}", line)?;
+            });
 
             // Move forward, accounting for overlap
-            offset = if end >= content_bytes.len() { end } else { end - options.overlap };
+            let next = if end >= content_bytes.len() { end } else { end - options.overlap };
+            line += count_newlines(&content_bytes[offset..next]);
+            offset = next;
         }
 
-        Ok(chunk_num)
+        Ok(())
     }
 
     /// Search for a pattern within a context
     pub fn search(&self, context_id: &str, pattern: &str, options: SearchOptions) -> Result<Vec<SearchMatch>> {
-        let ctx_path = self.base_path.join(context_id);
-        let chunks_path = ctx_path.join("chunks");
-
-        if !ctx_path.exists() {
-            return Err(eyre::eyre!("Context not found: {}", context_id));
-        }
-
         let regex = if options.case_insensitive {
             regex::RegexBuilder::new(pattern).case_insensitive(true).build()?
         } else {
@@ -191,28 +257,23 @@ impl ContextStore {
 
         let mut matches = Vec::new();
 
-        for entry in fs::read_dir(&chunks_path)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.extension().map(|e| e == "txt").unwrap_or(false) {
-                let chunk_id = path.file_stem().and_then(|s| s.to_str()).unwrap_or("").to_string();
-
-                let content = fs::read_to_string(&path)?;
+        for meta in self.chunks(context_id)? {
+            let content = self.read_chunk(context_id, &meta)?;
 
-                for m in regex.find_iter(&content) {
-                    let start = m.start().saturating_sub(30);
-                    let end = (m.end() + 30).min(content.len());
-                    let snippet = content[start..end].to_string();
+            for m in regex.find_iter(&content) {
+                let start = floor_char_boundary(&content, m.start().saturating_sub(30));
+                let end = ceil_char_boundary(&content, (m.end() + 30).min(content.len()));
+                let snippet = content[start..end].to_string();
 
-                    matches.push(SearchMatch {
-                        chunk_id: chunk_id.clone(),
-                        offset: m.start(),
-                        snippet,
-                    });
+                matches.push(SearchMatch {
+                    chunk_id: meta.chunk_id.clone(),
+                    offset: m.start(),
+                    snippet,
+                    location: meta.locate(&content, m.start()),
+                });
 
-                    if matches.len() >= options.max_results {
-                        return Ok(matches);
-                    }
+                if matches.len() >= options.max_results {
+                    return Ok(matches);
                 }
             }
         }
@@ -222,22 +283,61 @@ impl ContextStore {
 
     /// Get the full content of a chunk
     pub fn get_chunk(&self, chunk_id: &str) -> Result<String> {
-        // chunk_id format: "context_id/chunk_num" or just "chunk_num" if context known
-        let (context_id, chunk_num) = if chunk_id.contains('/') {
-            let parts: Vec<&str> = chunk_id.splitn(2, '/').collect();
-            (parts[0], parts[1])
-        } else {
-            // Search all contexts for this chunk
+        let (context_id, meta) = self.chunk_meta(chunk_id)?;
+        self.read_chunk(&context_id, &meta)
+            .context(format!("Chunk not found: {}", chunk_id))
+    }
+
+    /// Map a byte offset within a chunk back to its source file and line
+    pub fn locate(&self, chunk_id: &str, offset: usize) -> Result<SourceLocation> {
+        let (context_id, meta) = self.chunk_meta(chunk_id)?;
+        let content = self
+            .read_chunk(&context_id, &meta)
+            .context(format!("Chunk not found: {}", chunk_id))?;
+        if offset > content.len() {
+            return Err(eyre::eyre!(
+                "Offset {} is past the end of chunk {} ({} bytes)",
+                offset,
+                chunk_id,
+                content.len()
+            ));
+        }
+        Ok(meta.locate(&content, offset))
+    }
+
+    /// Split a `context_id/chunk_num` ID and look up the chunk's metadata
+    fn chunk_meta(&self, chunk_id: &str) -> Result<(ContextId, ChunkMeta)> {
+        let Some((context_id, chunk_num)) = chunk_id.split_once('/') else {
             return Err(eyre::eyre!("Chunk ID must include context: context_id/chunk_num"));
         };
 
-        let chunk_path = self
-            .base_path
-            .join(context_id)
-            .join("chunks")
-            .join(format!("{}.txt", chunk_num));
+        let meta = self
+            .chunks(context_id)?
+            .into_iter()
+            .find(|meta| meta.chunk_id == chunk_num)
+            .ok_or_else(|| eyre::eyre!("Chunk not found: {}", chunk_id))?;
+        Ok((context_id.to_string(), meta))
+    }
 
-        fs::read_to_string(&chunk_path).context(format!("Chunk not found: {}", chunk_id))
+    /// Read a chunk body: its object, or the context's `chunks/` file in stores not yet deduplicated
+    fn read_chunk(&self, context_id: &str, meta: &ChunkMeta) -> Result<String> {
+        let object = self.object_path(&meta.content_hash);
+        if object.exists() {
+            return Ok(fs::read_to_string(object)?);
+        }
+        let legacy = self.legacy_chunk_path(context_id, &meta.chunk_id);
+        if let Ok(content) = fs::read_to_string(&legacy) {
+            return Ok(content);
+        }
+        // `dedupe` may have migrated the chunk since `meta` was read
+        let current = self
+            .chunks(context_id)?
+            .into_iter()
+            .find(|m| m.chunk_id == meta.chunk_id && m.content_hash != meta.content_hash);
+        match current {
+            Some(current) => Ok(fs::read_to_string(self.object_path(&current.content_hash))?),
+            None => Err(eyre::eyre!("Missing chunk body: {}/{}", context_id, meta.chunk_id)),
+        }
     }
 
     /// Get a window of text around an offset
@@ -282,13 +382,32 @@ impl ContextStore {
         })
     }
 
+    /// Chunk metadata of a context, in index order
+    pub fn chunks(&self, context_id: &str) -> Result<Vec<ChunkMeta>> {
+        let index_path = self.base_path.join(context_id).join("index.jsonl");
+        if !index_path.exists() {
+            return Err(eyre::eyre!("Context not found: {}", context_id));
+        }
+
+        let reader = BufReader::new(fs::File::open(&index_path)?);
+        let mut chunks = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                chunks.push(serde_json::from_str(&line)?);
+            }
+        }
+        Ok(chunks)
+    }
+
     /// List all context IDs
     pub fn list_contexts(&self) -> Result<Vec<ContextId>> {
         let mut contexts = Vec::new();
 
         for entry in fs::read_dir(&self.base_path)? {
             let entry = entry?;
-            if entry.path().is_dir()
+            // A context without an index is still being ingested (or was interrupted)
+            if entry.path().join("index.jsonl").is_file()
                 && let Some(name) = entry.file_name().to_str()
             {
                 contexts.push(name.to_string());
@@ -298,23 +417,188 @@ impl ContextStore {
         Ok(contexts)
     }
 
-    /// Delete a context and all its data
+    /// Delete a context and the chunk bodies no other context references
     pub fn delete(&self, context_id: &str) -> Result<()> {
+        let _lock = self.lock()?;
         let ctx_path = self.base_path.join(context_id);
         if ctx_path.exists() {
             fs::remove_dir_all(&ctx_path)?;
-            info!(context_id, "Deleted context");
+            let removed = self.remove_unreferenced_objects()?;
+            info!(context_id, removed_objects = removed, "Deleted context");
+        }
+        Ok(())
+    }
+
+    /// Move chunk bodies into content-addressed storage and drop unreferenced ones
+    ///
+    /// Stores written before chunks were content-addressed keep a `chunks/`
+    /// directory per context; each of its files is moved to `objects/` (once
+    /// per distinct body) and the index is rewritten with the SHA-256 hashes.
+    pub fn dedupe(&self) -> Result<DedupeReport> {
+        debug!(base_path = ?self.base_path, "ContextStore::dedupe: called");
+        let _lock = self.lock()?;
+        let mut report = DedupeReport::default();
+
+        for context_id in self.list_contexts()? {
+            let Ok(mut chunks) = self.chunks(&context_id) else {
+                continue;
+            };
+            report.contexts += 1;
+            let mut changed = false;
+            for meta in &mut chunks {
+                report.chunks += 1;
+                let legacy = self.legacy_chunk_path(&context_id, &meta.chunk_id);
+                if legacy.exists() {
+                    let content = fs::read(&legacy)?;
+                    meta.content_hash = self.write_object(&content)?;
+                    report.migrated += 1;
+                    changed = true;
+                }
+                report.bytes_referenced += fs::metadata(self.object_path(&meta.content_hash))
+                    .map(|m| m.len())
+                    .unwrap_or(0);
+            }
+            if changed {
+                self.write_index(&context_id, &chunks)?;
+                fs::remove_dir_all(self.base_path.join(&context_id).join("chunks"))?;
+                info!(context_id, "Migrated context to content-addressed chunks");
+            }
+        }
+
+        report.removed_objects = self.remove_unreferenced_objects()?;
+        for path in self.object_files()? {
+            report.objects += 1;
+            report.bytes_stored += fs::metadata(&path)?.len();
+        }
+        info!(
+            objects = report.objects,
+            saved_bytes = report.saved_bytes(),
+            "Dedupe complete"
+        );
+        Ok(report)
+    }
+
+    /// Take the writer lock; released when the returned file is dropped
+    ///
+    /// This is an advisory `flock`, which the OS drops when the holder exits,
+    /// so a crashed writer never leaves a stale lock behind.
+    fn lock(&self) -> Result<fs::File> {
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.base_path.join(LOCK_FILE))
+            .context("Failed to open store lock file")?;
+        file.lock_exclusive().context("Failed to acquire store lock")?;
+        Ok(file)
+    }
+
+    /// Store a chunk body under its hash (once) and return the hash
+    fn write_object(&self, content: &[u8]) -> Result<String> {
+        let hash = content_hash(content);
+        let path = self.object_path(&hash);
+        if !path.exists() {
+            fs::create_dir_all(path.parent().expect("object path has a parent"))?;
+            // Write then rename, so a crash never leaves a truncated object under a valid hash
+            let tmp = path.with_extension(format!("tmp.{}", Uuid::now_v7()));
+            fs::write(&tmp, content)?;
+            fs::rename(&tmp, &path)?;
         }
+        Ok(hash)
+    }
+
+    /// `objects/ab/abcdef….txt`
+    fn object_path(&self, hash: &str) -> PathBuf {
+        let prefix = hash.get(..2).unwrap_or(hash);
+        self.base_path
+            .join(OBJECTS_DIR)
+            .join(prefix)
+            .join(format!("{}.txt", hash))
+    }
+
+    /// Chunk file of a context written before chunks were content-addressed
+    fn legacy_chunk_path(&self, context_id: &str, chunk_id: &str) -> PathBuf {
+        self.base_path
+            .join(context_id)
+            .join("chunks")
+            .join(format!("{}.txt", chunk_id))
+    }
+
+    /// Replace a context's index atomically (temp file + rename)
+    fn write_index(&self, context_id: &str, chunks: &[ChunkMeta]) -> Result<()> {
+        let index_path = self.base_path.join(context_id).join("index.jsonl");
+        let tmp = index_path.with_extension("jsonl.tmp");
+        let mut file = fs::File::create(&tmp)?;
+        for meta in chunks {
+            writeln!(file, "{}", serde_json::to_string(meta)?)?;
+        }
+        file.sync_all()?;
+        fs::rename(&tmp, &index_path)?;
         Ok(())
     }
+
+    /// All files under `objects/`
+    fn object_files(&self) -> Result<Vec<PathBuf>> {
+        let objects = self.base_path.join(OBJECTS_DIR);
+        if !objects.exists() {
+            return Ok(Vec::new());
+        }
+        let mut files = Vec::new();
+        for prefix in fs::read_dir(&objects)? {
+            let prefix = prefix?.path();
+            if prefix.is_dir() {
+                for entry in fs::read_dir(&prefix)? {
+                    let path = entry?.path();
+                    if path.extension().is_some_and(|e| e == "txt") {
+                        files.push(path);
+                    }
+                }
+            }
+        }
+        Ok(files)
+    }
+
+    /// Remove objects no context's index references; returns how many
+    fn remove_unreferenced_objects(&self) -> Result<usize> {
+        let mut referenced = HashSet::new();
+        for context_id in self.list_contexts()? {
+            for meta in self.chunks(&context_id).unwrap_or_default() {
+                referenced.insert(meta.content_hash);
+            }
+        }
+        let mut removed = 0;
+        for path in self.object_files()? {
+            let hash = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+            if !referenced.contains(hash) {
+                fs::remove_file(&path)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
 }
 
-/// Simple hash for content (not cryptographic, just for change detection)
-fn md5_hash(data: &[u8]) -> u64 {
-    use std::hash::{Hash, Hasher};
-    let mut hasher = std::collections::hash_map::DefaultHasher::new();
-    data.hash(&mut hasher);
-    hasher.finish()
+fn count_newlines(bytes: &[u8]) -> u64 {
+    bytes.iter().filter(|&&b| b == b'\n').count() as u64
+}
+
+/// SHA-256 of a chunk body, hex encoded
+fn content_hash(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn floor_char_boundary(s: &str, mut index: usize) -> usize {
+    while !s.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+fn ceil_char_boundary(s: &str, mut index: usize) -> usize {
+    while !s.is_char_boundary(index) {
+        index += 1;
+    }
+    index
 }
 
 #[cfg(test)]
@@ -339,6 +623,46 @@ mod tests {
         let matches = store.search(&ctx_id, "RLM", SearchOptions::default()).unwrap();
         assert!(!matches.is_empty());
         assert!(matches[0].snippet.contains("RLM"));
+
+        let chunks = store.chunks(&ctx_id).unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].chunk_id, matches[0].chunk_id);
+        assert_eq!(chunks[0].source, test_file.to_string_lossy());
+    }
+
+    #[test]
+    fn test_lines_mapped_across_overlapping_chunks() {
+        let temp = TempDir::new().unwrap();
+        let store = ContextStore::open(temp.path().join("store")).unwrap();
+        let doc = temp.path().join("notes.md");
+        let text: String = (1..=40).map(|n| format!("line {:02}\n", n)).collect();
+        fs::write(&doc, &text).unwrap();
+
+        // 8-byte lines, 100-byte chunks overlapping by 20 bytes
+        let options = IngestOptions {
+            chunk_size: 100,
+            overlap: 20,
+        };
+        let ctx_id = store.ingest(&[doc.to_string_lossy().to_string()], options).unwrap();
+        let chunks = store.chunks(&ctx_id).unwrap();
+        assert_eq!(chunks.len(), 4);
+        assert_eq!((chunks[0].line_start, chunks[0].line_count), (1, 13));
+        assert_eq!((chunks[1].line_start, chunks[1].line_count), (11, 13));
+        assert_eq!(chunks[3].line_start, 31);
+
+        let matches = store.search(&ctx_id, "line 27", SearchOptions::default()).unwrap();
+        assert_eq!(matches.len(), 1);
+        let location = &matches[0].location;
+        assert_eq!(location.source, doc.to_string_lossy());
+        assert_eq!((location.line, location.byte), (Some(27), 208));
+
+        let chunk_id = format!("{}/{}", ctx_id, matches[0].chunk_id);
+        assert_eq!(&store.locate(&chunk_id, matches[0].offset).unwrap(), location);
+        assert_eq!(
+            store.locate(&format!("{}/0002", ctx_id), 0).unwrap().to_string(),
+            format!("{}:11", doc.display())
+        );
+        assert!(store.locate(&chunk_id, 1000).is_err());
     }
 
     #[test]
@@ -361,4 +685,120 @@ mod tests {
         let contexts = store.list_contexts().unwrap();
         assert!(!contexts.contains(&ctx_id));
     }
+
+    #[test]
+    fn test_identical_chunks_stored_once_and_legacy_migrated() {
+        let temp = TempDir::new().unwrap();
+        let store = ContextStore::open(temp.path().join("store")).unwrap();
+        let doc = temp.path().join("doc.md");
+        fs::write(&doc, "shared documentation body").unwrap();
+        let patterns = [doc.to_string_lossy().to_string()];
+
+        let first = store.ingest(&patterns, IngestOptions::default()).unwrap();
+        let second = store.ingest(&patterns, IngestOptions::default()).unwrap();
+        assert_eq!(store.object_files().unwrap().len(), 1);
+        assert_eq!(store.list_contexts().unwrap().len(), 2);
+        assert_eq!(
+            store.get_chunk(&format!("{}/0001", second)).unwrap(),
+            "shared documentation body"
+        );
+
+        // A context written in the old layout: bodies under chunks/, non-SHA hashes
+        let legacy = "0190a0a0-0000-7000-8000-000000000000";
+        let legacy_dir = temp.path().join("store").join(legacy);
+        fs::create_dir_all(legacy_dir.join("chunks")).unwrap();
+        fs::write(legacy_dir.join("chunks").join("0001.txt"), "shared documentation body").unwrap();
+        fs::write(
+            legacy_dir.join("index.jsonl"),
+            r#"{"chunk_id":"0001","source":"doc.md","byte_start":0,"byte_end":25,"content_hash":"9f1c","created_at":0}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            store.search(legacy, "shared", SearchOptions::default()).unwrap().len(),
+            1
+        );
+
+        let report = store.dedupe().unwrap();
+        assert_eq!((report.contexts, report.chunks, report.migrated), (3, 3, 1));
+        assert_eq!(report.objects, 1);
+        assert_eq!(report.bytes_referenced, 75);
+        assert_eq!(report.saved_bytes(), 50);
+        assert!(!legacy_dir.join("chunks").exists());
+        // Indexes from before line tracking still locate, by byte only
+        assert_eq!(
+            store.locate(&format!("{}/0001", legacy), 7).unwrap().to_string(),
+            "doc.md (byte 7)"
+        );
+        assert_eq!(
+            store.chunks(legacy).unwrap()[0].content_hash,
+            store.chunks(&first).unwrap()[0].content_hash
+        );
+        assert_eq!(
+            store.get_chunk(&format!("{}/0001", legacy)).unwrap(),
+            "shared documentation body"
+        );
+
+        // Objects go once the last context referencing them does
+        store.delete(&first).unwrap();
+        store.delete(legacy).unwrap();
+        assert_eq!(store.object_files().unwrap().len(), 1);
+        store.delete(&second).unwrap();
+        assert!(store.object_files().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parallel_ingest_delete_and_search() {
+        let temp = TempDir::new().unwrap();
+        let store_path = temp.path().join("store");
+        let doc = temp.path().join("doc.md");
+        fs::write(&doc, "shared body for concurrent access").unwrap();
+        let patterns = [doc.to_string_lossy().to_string()];
+        let keeper = ContextStore::open(&store_path)
+            .unwrap()
+            .ingest(&patterns, IngestOptions::default())
+            .unwrap();
+
+        // Each thread opens its own handle, like a separate process would
+        let writers: Vec<_> = (0..4)
+            .map(|_| {
+                let (store_path, patterns) = (store_path.clone(), patterns.clone());
+                std::thread::spawn(move || {
+                    let store = ContextStore::open(&store_path).unwrap();
+                    for _ in 0..10 {
+                        let id = store.ingest(&patterns, IngestOptions::default()).unwrap();
+                        store.delete(&id).unwrap();
+                    }
+                })
+            })
+            .collect();
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let (store_path, keeper) = (store_path.clone(), keeper.clone());
+                std::thread::spawn(move || {
+                    let store = ContextStore::open(&store_path).unwrap();
+                    for _ in 0..50 {
+                        for id in store.list_contexts().unwrap() {
+                            // Contexts deleted mid-loop are gone, but listed ones are never partial
+                            if let Ok(chunks) = store.chunks(&id) {
+                                assert_eq!(chunks.len(), 1);
+                            }
+                        }
+                        let matches = store.search(&keeper, "concurrent", SearchOptions::default()).unwrap();
+                        assert_eq!(matches.len(), 1);
+                        assert_eq!(
+                            store.get_chunk(&format!("{}/0001", keeper)).unwrap(),
+                            "shared body for concurrent access"
+                        );
+                    }
+                })
+            })
+            .collect();
+        for handle in writers.into_iter().chain(readers) {
+            handle.join().unwrap();
+        }
+
+        let store = ContextStore::open(&store_path).unwrap();
+        assert_eq!(store.list_contexts().unwrap(), [keeper]);
+        assert_eq!(store.object_files().unwrap().len(), 1);
+    }
 }
@@ -0,0 +1,335 @@
+//! Python bindings for TaskDaemon
+//!
+//! Built with maturin (`maturin develop -m py/Cargo.toml`) into the `taskdaemon`
+//! Python module, so loop orchestration can be scripted without the CLI:
+//!
+//! ```python
+//! import taskdaemon
+//!
+//! client = taskdaemon.Client()                  # same config lookup as `td`
+//! exec_id = client.submit("ralph", "Fix the login redirect", context={"issue": "412"})
+//! print(client.status(exec_id)["status"])
+//!
+//! async for event in client.events(exec_id):   # needs the daemon running
+//!     print(event["type"], event)
+//!
+//! store = taskdaemon.ContextStore(".contextstore")
+//! ctx = store.ingest(["docs/**/*.md"])
+//! for match in store.search(ctx, "RLM.*recursive"):
+//!     print(match["source"], match["line"], match["snippet"])
+//! ```
+//!
+//! Executions and events come back as plain dicts with the same fields as
+//! `td exec describe --json` and the event log. Submitting writes to the
+//! TaskStore and wakes the daemon over IPC, exactly like `td run`; a daemon
+//! that is not running picks the execution up when it starts.
+
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+use contextstore::{ContextStore, IngestOptions, SearchOptions};
+use pyo3::exceptions::{PyRuntimeError, PyStopAsyncIteration, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use taskdaemon::batch::task_execution;
+use taskdaemon::config::Config;
+use taskdaemon::domain::Submitter;
+use taskdaemon::ipc::{DaemonClient, EventStream};
+use taskdaemon::r#loop::LoopLoader;
+use taskdaemon::state::StateManager;
+use tokio::sync::Mutex;
+use tracing::debug;
+
+/// Tag added to every execution submitted from Python
+pub const PYTHON_TAG: &str = "python";
+
+fn runtime() -> &'static tokio::runtime::Runtime {
+    pyo3_async_runtimes::tokio::get_runtime()
+}
+
+fn runtime_error(e: impl std::fmt::Display) -> PyErr {
+    PyRuntimeError::new_err(e.to_string())
+}
+
+/// Convert a JSON value to Python objects via the `json` module
+fn to_python(py: Python<'_>, value: &serde_json::Value) -> PyResult<PyObject> {
+    let json = py.import("json")?;
+    Ok(json.call_method1("loads", (value.to_string(),))?.unbind())
+}
+
+/// Convert a Python dict to a JSON object via the `json` module
+fn from_python(dict: &Bound<'_, PyDict>) -> PyResult<serde_json::Map<String, serde_json::Value>> {
+    let json = dict.py().import("json")?;
+    let text: String = json.call_method1("dumps", (dict,))?.extract()?;
+    serde_json::from_str(&text).map_err(|e| PyValueError::new_err(format!("context is not a JSON object: {}", e)))
+}
+
+/// Handle on the TaskStore and loop types a `td` with the same config would use
+#[pyclass(module = "taskdaemon")]
+pub struct Client {
+    state: StateManager,
+    loader: Arc<RwLock<LoopLoader>>,
+    /// Recorded on submitted executions
+    submitter: Option<Submitter>,
+}
+
+#[pymethods]
+impl Client {
+    /// Open the TaskStore named by the config (or `store_dir`)
+    #[new]
+    #[pyo3(signature = (config = None, store_dir = None))]
+    fn new(config: Option<PathBuf>, store_dir: Option<PathBuf>) -> PyResult<Self> {
+        debug!(?config, ?store_dir, "Client::new: called");
+        let config = Config::load(config.as_ref()).map_err(runtime_error)?;
+        let store_dir = store_dir.unwrap_or_else(|| PathBuf::from(&config.storage.taskstore_dir));
+        let loader = LoopLoader::new(&config.loops).map_err(runtime_error)?;
+        // The actor task lives on the shared runtime
+        let _guard = runtime().enter();
+        let state = StateManager::spawn(&store_dir).map_err(runtime_error)?;
+        Ok(Self {
+            state,
+            loader: Arc::new(RwLock::new(loader)),
+            submitter: config.user.submitter(),
+        })
+    }
+
+    /// Queue an execution of `loop_type` for `task`; returns its ID
+    #[pyo3(signature = (loop_type, task, context = None, tags = Vec::new()))]
+    fn submit(
+        &self,
+        py: Python<'_>,
+        loop_type: &str,
+        task: &str,
+        context: Option<&Bound<'_, PyDict>>,
+        tags: Vec<String>,
+    ) -> PyResult<String> {
+        debug!(%loop_type, "Client::submit: called");
+        let context = context.map(from_python).transpose()?.unwrap_or_default();
+        let mut all_tags = vec![PYTHON_TAG.to_string()];
+        all_tags.extend(tags);
+        let exec = {
+            let loader = self.loader.read().expect("loop loader poisoned");
+            task_execution(&loader, loop_type, task, context, &all_tags)
+                .map_err(|problems| PyValueError::new_err(problems.join("; ")))?
+                .with_submitter(self.submitter.clone())
+        };
+        let state = self.state.clone();
+        py.allow_threads(|| runtime().block_on(state.create_execution(exec)))
+            .map_err(runtime_error)
+    }
+
+    /// The execution as a dict, or None if there is no such execution
+    fn status(&self, py: Python<'_>, id: &str) -> PyResult<Option<PyObject>> {
+        debug!(%id, "Client::status: called");
+        let state = self.state.clone();
+        let exec = py
+            .allow_threads(|| runtime().block_on(state.get_execution(id)))
+            .map_err(runtime_error)?;
+        exec.map(|exec| to_python(py, &serde_json::to_value(&exec).map_err(runtime_error)?))
+            .transpose()
+    }
+
+    /// Executions as dicts, optionally only those with `status` and/or `loop_type`
+    #[pyo3(signature = (status = None, loop_type = None))]
+    fn list(&self, py: Python<'_>, status: Option<String>, loop_type: Option<String>) -> PyResult<Vec<PyObject>> {
+        debug!(?status, ?loop_type, "Client::list: called");
+        let state = self.state.clone();
+        let executions = py
+            .allow_threads(|| runtime().block_on(state.list_executions(status, loop_type)))
+            .map_err(runtime_error)?;
+        executions
+            .iter()
+            .map(|exec| to_python(py, &serde_json::to_value(exec).map_err(runtime_error)?))
+            .collect()
+    }
+
+    /// Names of the loaded loop types
+    fn loop_types(&self) -> Vec<String> {
+        let loader = self.loader.read().expect("loop loader poisoned");
+        let mut names: Vec<String> = loader.names().map(str::to_string).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Async iterator over daemon events, optionally for one execution only
+    #[pyo3(signature = (execution_id = None))]
+    fn events(&self, execution_id: Option<String>) -> Events {
+        Events {
+            execution_id,
+            stream: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+/// Live daemon events; connects on the first `__anext__`
+#[pyclass(module = "taskdaemon")]
+pub struct Events {
+    execution_id: Option<String>,
+    stream: Arc<Mutex<Option<EventStream>>>,
+}
+
+#[pymethods]
+impl Events {
+    fn __aiter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __anext__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let execution_id = self.execution_id.clone();
+        let stream = self.stream.clone();
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            let mut stream = stream.lock().await;
+            if stream.is_none() {
+                debug!(?execution_id, "Events::__anext__: subscribing");
+                let subscribed = DaemonClient::new()
+                    .subscribe(execution_id.as_deref())
+                    .await
+                    .map_err(runtime_error)?;
+                *stream = Some(subscribed);
+            }
+            let next = stream.as_mut().expect("subscribed above").next().await;
+            match next.map_err(runtime_error)? {
+                Some(event) => {
+                    let value = serde_json::to_value(&event).map_err(runtime_error)?;
+                    Python::with_gil(|py| to_python(py, &value))
+                }
+                None => Err(PyStopAsyncIteration::new_err("event stream closed")),
+            }
+        })
+    }
+}
+
+/// A ContextStore directory (see the `cs` CLI)
+#[pyclass(name = "ContextStore", module = "taskdaemon")]
+pub struct PyContextStore {
+    store: ContextStore,
+}
+
+#[pymethods]
+impl PyContextStore {
+    #[new]
+    fn new(path: PathBuf) -> PyResult<Self> {
+        debug!(?path, "PyContextStore::new: called");
+        Ok(Self {
+            store: ContextStore::open(&path).map_err(runtime_error)?,
+        })
+    }
+
+    /// Ingest files matching `patterns`; returns the new context ID
+    #[pyo3(signature = (patterns, chunk_size = contextstore::DEFAULT_CHUNK_SIZE, overlap = contextstore::DEFAULT_OVERLAP))]
+    fn ingest(&self, py: Python<'_>, patterns: Vec<String>, chunk_size: usize, overlap: usize) -> PyResult<String> {
+        debug!(?patterns, chunk_size, overlap, "PyContextStore::ingest: called");
+        let options = IngestOptions { chunk_size, overlap };
+        py.allow_threads(|| self.store.ingest(&patterns, options))
+            .map_err(runtime_error)
+    }
+
+    /// Regex search in a context; matches as dicts with chunk_id, offset, snippet, source and line
+    #[pyo3(signature = (context_id, pattern, max_results = 10, case_insensitive = false))]
+    fn search(
+        &self,
+        py: Python<'_>,
+        context_id: &str,
+        pattern: &str,
+        max_results: usize,
+        case_insensitive: bool,
+    ) -> PyResult<Vec<PyObject>> {
+        debug!(%context_id, %pattern, "PyContextStore::search: called");
+        let options = SearchOptions {
+            max_results,
+            case_insensitive,
+        };
+        let matches = py
+            .allow_threads(|| self.store.search(context_id, pattern, options))
+            .map_err(runtime_error)?;
+        matches
+            .iter()
+            .map(|m| {
+                let value = serde_json::json!({
+                    "chunk_id": m.chunk_id,
+                    "offset": m.offset,
+                    "snippet": m.snippet,
+                    "source": m.location.source,
+                    "line": m.location.line,
+                });
+                to_python(py, &value)
+            })
+            .collect()
+    }
+
+    /// Full text of a chunk
+    fn get_chunk(&self, chunk_id: &str) -> PyResult<String> {
+        self.store.get_chunk(chunk_id).map_err(runtime_error)
+    }
+
+    /// IDs of the contexts in the store
+    fn list_contexts(&self) -> PyResult<Vec<String>> {
+        self.store.list_contexts().map_err(runtime_error)
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "taskdaemon")]
+fn taskdaemon_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Client>()?;
+    m.add_class::<Events>()?;
+    m.add_class::<PyContextStore>()?;
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::PyList;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_submit_and_status() {
+        pyo3::prepare_freethreaded_python();
+        let temp = tempdir().unwrap();
+        let client = Client::new(None, Some(temp.path().join("store"))).unwrap();
+        Python::with_gil(|py| {
+            let context = PyDict::new(py);
+            context.set_item("issue", "412").unwrap();
+            let id = client
+                .submit(py, "ralph", "Fix the login redirect", Some(&context), vec!["Data".to_string()])
+                .unwrap();
+
+            let status = client.status(py, &id).unwrap().unwrap();
+            let status = status.bind(py).downcast::<PyDict>().unwrap();
+            let value: String = status.get_item("status").unwrap().unwrap().extract().unwrap();
+            assert_eq!(value, "pending");
+            let tags: Vec<String> = status.get_item("tags").unwrap().unwrap().extract().unwrap();
+            assert_eq!(tags, vec!["python".to_string(), "data".to_string()]);
+
+            assert!(client.status(py, "missing").unwrap().is_none());
+            assert_eq!(client.list(py, Some("pending".to_string()), None).unwrap().len(), 1);
+            let error = client.submit(py, "nope", "Anything", None, vec![]).unwrap_err();
+            assert!(error.is_instance_of::<PyValueError>(py));
+        });
+    }
+
+    #[test]
+    fn test_context_store_ingest_and_search() {
+        pyo3::prepare_freethreaded_python();
+        let temp = tempdir().unwrap();
+        let doc = temp.path().join("notes.md");
+        std::fs::write(&doc, "intro\nRLM is recursive\n").unwrap();
+        let store = PyContextStore::new(temp.path().join("cs")).unwrap();
+        Python::with_gil(|py| {
+            let ctx = store
+                .ingest(py, vec![doc.display().to_string()], 1024, 0)
+                .unwrap();
+            assert_eq!(store.list_contexts().unwrap(), vec![ctx.clone()]);
+
+            let matches = store.search(py, &ctx, "RLM.*recursive", 10, false).unwrap();
+            assert_eq!(matches.len(), 1);
+            let found = PyList::new(py, &matches).unwrap();
+            let first = found.get_item(0).unwrap();
+            let first = first.downcast::<PyDict>().unwrap();
+            let line: u64 = first.get_item("line").unwrap().unwrap().extract().unwrap();
+            assert_eq!(line, 2);
+        });
+    }
+}
@@ -20,4 +20,17 @@ fn main() {
     println!("cargo:rustc-env=GIT_DESCRIBE={}", git_describe);
     println!("cargo:rerun-if-changed=.git/HEAD");
     println!("cargo:rerun-if-changed=.git/refs/");
+
+    #[cfg(feature = "grpc")]
+    compile_protos();
+}
+
+/// Generate the gRPC service from proto/taskdaemon.proto with the vendored protoc
+#[cfg(feature = "grpc")]
+fn compile_protos() {
+    let protoc = protoc_bin_vendored::protoc_bin_path().expect("No vendored protoc for this platform");
+    // SAFETY: build scripts are single-threaded
+    unsafe { std::env::set_var("PROTOC", protoc) };
+    tonic_prost_build::compile_protos("proto/taskdaemon.proto").expect("Failed to compile proto/taskdaemon.proto");
+    println!("cargo:rerun-if-changed=proto/taskdaemon.proto");
 }
@@ -0,0 +1,580 @@
+//! Question answering over past executions (`td ask`)
+//!
+//! `td ask "why did the payments loop fail last week?"` picks the executions
+//! most relevant to the question from the TaskStore, gathers what explains
+//! them (the task, the plan or other artifact, failure events from the event
+//! log, and the output of the last failing validation), and asks the LLM to
+//! answer from that evidence alone, citing execution IDs.
+//!
+//! Retrieval is deliberately simple: keyword overlap between the question and
+//! each execution's ID, type, title, task and last error, weighted towards
+//! failed executions when the question is about failure, and restricted to a
+//! time window when the question names one ("yesterday", "last week",
+//! "last 3 days") or `--since` is given.
+
+use std::path::Path;
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use eyre::Result;
+use tracing::debug;
+
+use crate::domain::{LoopExecution, LoopExecutionStatus};
+use crate::events::{Event, EventLogEntry, IterationOutcome};
+use crate::llm::{CompletionRequest, LlmClient, Message};
+
+/// Executions handed to the LLM by default
+pub const DEFAULT_LIMIT: usize = 5;
+
+/// Characters of an artifact (plan, spec) included per execution
+const MAX_ARTIFACT_CHARS: usize = 4000;
+
+/// Failure events included per execution (the most recent)
+const MAX_FAILURE_EVENTS: usize = 10;
+
+/// Lines of validation output included per execution
+const MAX_VALIDATION_LINES: usize = 40;
+
+/// Tokens allowed for the answer
+const MAX_ANSWER_TOKENS: u32 = 2048;
+
+/// Words too common to say anything about which execution is meant
+const STOPWORDS: &[&str] = &[
+    "the",
+    "and",
+    "for",
+    "was",
+    "were",
+    "did",
+    "does",
+    "why",
+    "what",
+    "when",
+    "how",
+    "which",
+    "who",
+    "that",
+    "this",
+    "with",
+    "from",
+    "into",
+    "last",
+    "week",
+    "month",
+    "day",
+    "days",
+    "today",
+    "yesterday",
+    "loop",
+    "loops",
+    "execution",
+    "executions",
+    "run",
+    "runs",
+    "ago",
+    "are",
+    "has",
+    "have",
+    "had",
+    "not",
+    "any",
+    "all",
+    "our",
+    "its",
+];
+
+/// Words that mark a question about failures
+const FAILURE_WORDS: &[&str] = &["fail", "error", "broke", "break", "stuck", "crash", "wrong", "block"];
+
+/// Time window named in the question, as the earliest time of interest
+pub fn time_window(question: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    debug!(%question, "time_window: called");
+    let q = question.to_lowercase();
+    let start_of_today = Utc.from_utc_datetime(&now.date_naive().and_hms_opt(0, 0, 0).unwrap_or_default());
+
+    // "last 3 days", "past 12 hours", "last 2 weeks"
+    let words: Vec<&str> = q
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    for window in words.windows(3) {
+        if matches!(window[0], "last" | "past")
+            && let Ok(amount) = window[1].parse::<i64>()
+        {
+            let age = match window[2].trim_end_matches('s') {
+                "hour" => Duration::hours(amount),
+                "day" => Duration::days(amount),
+                "week" => Duration::weeks(amount),
+                "month" => Duration::days(30 * amount),
+                _ => continue,
+            };
+            return Some(now - age);
+        }
+    }
+
+    if q.contains("today") {
+        Some(start_of_today)
+    } else if q.contains("yesterday") {
+        Some(start_of_today - Duration::days(1))
+    } else if q.contains("last week") || q.contains("past week") || q.contains("this week") {
+        Some(now - Duration::weeks(1))
+    } else if q.contains("last month") || q.contains("past month") || q.contains("this month") {
+        Some(now - Duration::days(30))
+    } else {
+        None
+    }
+}
+
+/// Crude stem so "payments" matches "payment" and "failed" matches "failing"
+fn stem(word: &str) -> &str {
+    for suffix in ["ing", "ed", "es", "s"] {
+        if let Some(stem) = word.strip_suffix(suffix)
+            && stem.len() >= 3
+        {
+            return stem;
+        }
+    }
+    word
+}
+
+/// Distinctive words of the question, stemmed
+fn keywords(question: &str) -> Vec<String> {
+    let mut keywords: Vec<String> = question
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.len() >= 3 && !STOPWORDS.contains(w) && !w.chars().all(|c| c.is_ascii_digit()))
+        .map(|w| stem(w).to_string())
+        .collect();
+    keywords.sort();
+    keywords.dedup();
+    keywords
+}
+
+/// Whether the question asks about failures
+fn about_failure(keywords: &[String]) -> bool {
+    keywords.iter().any(|k| FAILURE_WORDS.iter().any(|f| k.starts_with(f)))
+}
+
+/// Whether an execution ended badly
+fn is_failure(exec: &LoopExecution) -> bool {
+    matches!(
+        exec.status,
+        LoopExecutionStatus::Failed | LoopExecutionStatus::Blocked | LoopExecutionStatus::Stopped
+    ) || exec.last_error.is_some()
+}
+
+/// Relevance of an execution to the question's keywords
+fn score(exec: &LoopExecution, keywords: &[String], failure_question: bool) -> u32 {
+    let named = format!(
+        "{} {} {}",
+        exec.id,
+        exec.loop_type,
+        exec.title.as_deref().unwrap_or_default()
+    )
+    .to_lowercase();
+    let context = exec.context.to_string().to_lowercase();
+    let error = exec.last_error.as_deref().unwrap_or_default().to_lowercase();
+
+    let mut score = 0;
+    for keyword in keywords {
+        // Failure words are handled by the status boost, not as topic matches
+        if FAILURE_WORDS.iter().any(|f| keyword.starts_with(f)) {
+            continue;
+        }
+        if named.contains(keyword.as_str()) {
+            score += 3;
+        }
+        if error.contains(keyword.as_str()) {
+            score += 2;
+        }
+        if context.contains(keyword.as_str()) {
+            score += 1;
+        }
+    }
+    if failure_question && is_failure(exec) {
+        score += 2;
+    }
+    score
+}
+
+/// The executions most relevant to the question, best first
+///
+/// Only executions updated at or after `since` are considered. If nothing
+/// matches the question's topic, the most recent executions in the window are
+/// returned instead (failures first for failure questions).
+pub fn rank<'a>(
+    executions: &'a [LoopExecution],
+    question: &str,
+    since: Option<DateTime<Utc>>,
+    limit: usize,
+) -> Vec<&'a LoopExecution> {
+    debug!(count = executions.len(), %question, ?since, limit, "rank: called");
+    let keywords = keywords(question);
+    let failure_question = about_failure(&keywords);
+    let since_ms = since.map(|t| t.timestamp_millis());
+
+    let mut scored: Vec<(u32, &LoopExecution)> = executions
+        .iter()
+        .filter(|e| since_ms.is_none_or(|since| e.updated_at >= since))
+        .map(|e| (score(e, &keywords, failure_question), e))
+        .collect();
+    let topical = scored
+        .iter()
+        .any(|(s, e)| *s > if failure_question && is_failure(e) { 2 } else { 0 });
+    if topical {
+        scored.retain(|(s, e)| *s > if failure_question && is_failure(e) { 2 } else { 0 });
+    }
+    scored.sort_by(|(sa, a), (sb, b)| sb.cmp(sa).then(b.updated_at.cmp(&a.updated_at)));
+    scored.truncate(limit);
+    debug!(selected = scored.len(), topical, "rank: complete");
+    scored.into_iter().map(|(_, e)| e).collect()
+}
+
+/// What is known about one execution
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Evidence {
+    pub id: String,
+    /// Record summary: type, status, title, times, task, last error
+    pub record: String,
+    /// Artifact content (plan, spec), truncated
+    pub artifact: Option<String>,
+    /// Failure-related events, oldest first
+    pub failures: Vec<String>,
+    /// Output of the last failing validation run
+    pub validation: Vec<String>,
+}
+
+impl Evidence {
+    /// Collect the evidence for an execution from its record, event log and artifact
+    pub fn gather(exec: &LoopExecution, events: &[EventLogEntry], repo_root: &Path) -> Self {
+        debug!(exec_id = %exec.id, events = events.len(), "Evidence::gather: called");
+        let mut record = format!(
+            "type: {}\nstatus: {}\ntitle: {}\ncreated: {}\nupdated: {}\niterations: {}\n",
+            exec.loop_type,
+            exec.status,
+            exec.title.as_deref().unwrap_or("-"),
+            format_ms(exec.created_at),
+            format_ms(exec.updated_at),
+            exec.iteration
+        );
+        if let Some(task) = exec
+            .context
+            .get("task")
+            .or_else(|| exec.context.get("task-description"))
+            .or_else(|| exec.context.get("question"))
+            .and_then(|v| v.as_str())
+        {
+            record.push_str(&format!("task: {}\n", task.trim()));
+        }
+        if let Some(error) = &exec.last_error {
+            record.push_str(&format!("last error: {}\n", error.trim()));
+        }
+
+        let artifact = exec
+            .artifact_path
+            .as_ref()
+            .map(|p| repo_root.join(p))
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .map(|content| clip(content.trim(), MAX_ARTIFACT_CHARS));
+
+        let mut failures: Vec<String> = events.iter().filter_map(describe_failure).collect();
+        if failures.len() > MAX_FAILURE_EVENTS {
+            failures.drain(..failures.len() - MAX_FAILURE_EVENTS);
+        }
+
+        Self {
+            id: exec.id.clone(),
+            record,
+            artifact,
+            failures,
+            validation: last_failing_validation(events),
+        }
+    }
+
+    /// Render as a prompt section
+    fn render(&self) -> String {
+        let mut out = format!("=== execution {} ===\n{}", self.id, self.record);
+        if let Some(artifact) = &self.artifact {
+            out.push_str(&format!("\n--- artifact ---\n{}\n", artifact));
+        }
+        if !self.failures.is_empty() {
+            out.push_str("\n--- failure events ---\n");
+            for failure in &self.failures {
+                out.push_str(&format!("- {}\n", failure));
+            }
+        }
+        if !self.validation.is_empty() {
+            out.push_str("\n--- last failing validation output ---\n");
+            out.push_str(&self.validation.join("\n"));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// One line for an event that explains a failure (None for everything else)
+fn describe_failure(entry: &EventLogEntry) -> Option<String> {
+    let ts = entry.timestamp.format("%Y-%m-%d %H:%M");
+    let text = match &entry.event {
+        Event::Error { context, message, .. } => format!("error in {}: {}", context, message),
+        Event::Warning { context, message, .. } => format!("warning in {}: {}", context, message),
+        Event::LoopStuck {
+            iteration,
+            unchanged_iterations,
+            action,
+            ..
+        } => format!(
+            "stuck at iteration {}: no progress for {} iterations ({})",
+            iteration, unchanged_iterations, action
+        ),
+        Event::IterationCompleted { iteration, outcome, .. } => match outcome {
+            IterationOutcome::ValidationPassed => return None,
+            IterationOutcome::ValidationFailed { exit_code } => {
+                format!(
+                    "iteration {}: validation failed with exit code {}",
+                    iteration, exit_code
+                )
+            }
+            IterationOutcome::MaxTurnsReached => format!("iteration {}: max turns reached", iteration),
+            IterationOutcome::ToolError { tool, error } => {
+                format!("iteration {}: {} failed: {}", iteration, tool, error)
+            }
+            IterationOutcome::LlmError { error, .. } => format!("iteration {}: LLM error: {}", iteration, error),
+            IterationOutcome::TimedOut { cause } => format!("iteration {}: {}", iteration, cause),
+        },
+        Event::ToolCallCompleted {
+            iteration,
+            tool_name,
+            success: false,
+            result_summary,
+            ..
+        } => format!("iteration {}: {} failed: {}", iteration, tool_name, result_summary),
+        Event::ResourceLimitExceeded {
+            iteration,
+            tool_name,
+            resource,
+            limit,
+            ..
+        } => format!(
+            "iteration {}: {} hit the {} limit ({})",
+            iteration, tool_name, resource, limit
+        ),
+        Event::NetworkRequest {
+            iteration,
+            tool_name,
+            target,
+            allowed: false,
+            ..
+        } => format!("iteration {}: {} was blocked from {} by the network policy", iteration, tool_name, target),
+        Event::LoopCompleted {
+            success: false,
+            total_iterations,
+            ..
+        } => format!("loop ended without success after {} iterations", total_iterations),
+        _ => return None,
+    };
+    Some(format!("{} {}", ts, text))
+}
+
+/// Tail of the output of the last validation run that failed
+fn last_failing_validation(events: &[EventLogEntry]) -> Vec<String> {
+    let failed_iteration = events.iter().rev().find_map(|entry| match &entry.event {
+        Event::ValidationCompleted {
+            iteration, exit_code, ..
+        } if *exit_code != 0 => Some(*iteration),
+        _ => None,
+    });
+    let Some(failed_iteration) = failed_iteration else {
+        return Vec::new();
+    };
+
+    // Only the last run of that iteration, which starts at its last ValidationStarted
+    let start = events
+        .iter()
+        .rposition(|entry| {
+            matches!(&entry.event, Event::ValidationStarted { iteration, .. } if *iteration == failed_iteration)
+        })
+        .unwrap_or(0);
+    let lines: Vec<String> = events[start..]
+        .iter()
+        .take_while(|entry| !matches!(&entry.event, Event::ValidationCompleted { iteration, .. } if *iteration == failed_iteration))
+        .filter_map(|entry| match &entry.event {
+            Event::ValidationOutput { iteration, line, .. } if *iteration == failed_iteration => Some(line.clone()),
+            _ => None,
+        })
+        .collect();
+    let skip = lines.len().saturating_sub(MAX_VALIDATION_LINES);
+    lines.into_iter().skip(skip).collect()
+}
+
+fn format_ms(ms: i64) -> String {
+    DateTime::<Utc>::from_timestamp_millis(ms)
+        .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn clip(s: &str, max_chars: usize) -> String {
+    if s.chars().count() <= max_chars {
+        s.to_string()
+    } else {
+        let cut: String = s.chars().take(max_chars).collect();
+        format!("{}\n[... truncated]", cut)
+    }
+}
+
+/// Build the completion request for a question and its evidence
+pub fn build_request(question: &str, evidence: &[Evidence], now: DateTime<Utc>) -> CompletionRequest {
+    debug!(%question, executions = evidence.len(), "build_request: called");
+    let system_prompt = format!(
+        "You answer questions about past runs of TaskDaemon, an orchestrator of autonomous coding loops.\n\
+         Answer only from the execution records below; if they don't contain the answer, say so.\n\
+         Cite the executions your answer relies on by ID in square brackets, e.g. [{}].\n\
+         Be concise: lead with the answer, then the supporting details.\n\
+         The current time is {}.",
+        evidence.first().map(|e| e.id.as_str()).unwrap_or("execution-id"),
+        now.format("%Y-%m-%d %H:%M UTC")
+    );
+    let mut user = String::from("Execution records:\n\n");
+    for item in evidence {
+        user.push_str(&item.render());
+        user.push('\n');
+    }
+    user.push_str(&format!("Question: {}", question));
+
+    CompletionRequest {
+        system_prompt,
+        messages: vec![Message::user(user)],
+        tools: vec![],
+        max_tokens: MAX_ANSWER_TOKENS,
+    }
+}
+
+/// IDs of the given executions that the answer cites, in citation order
+pub fn cited<'a>(answer: &str, evidence: &'a [Evidence]) -> Vec<&'a str> {
+    let mut cited: Vec<(usize, &str)> = evidence
+        .iter()
+        .filter_map(|e| answer.find(e.id.as_str()).map(|pos| (pos, e.id.as_str())))
+        .collect();
+    cited.sort();
+    cited.into_iter().map(|(_, id)| id).collect()
+}
+
+/// Ask the LLM the question over the evidence
+pub async fn answer(llm: &dyn LlmClient, question: &str, evidence: &[Evidence]) -> Result<String> {
+    debug!(%question, executions = evidence.len(), "answer: called");
+    let response = llm.complete(build_request(question, evidence, Utc::now())).await?;
+    Ok(response.content.unwrap_or_default().trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn exec(id: &str, title: &str, status: LoopExecutionStatus, updated_at: DateTime<Utc>) -> LoopExecution {
+        let mut exec = LoopExecution::new("ralph", title);
+        exec.id = id.to_string();
+        exec.title = Some(title.to_string());
+        exec.status = status;
+        exec.context = json!({ "task": title });
+        exec.updated_at = updated_at.timestamp_millis();
+        exec
+    }
+
+    fn entry(event: Event) -> EventLogEntry {
+        EventLogEntry::new(event)
+    }
+
+    #[test]
+    fn test_time_window() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 10, 15, 30, 0).unwrap();
+        assert_eq!(
+            time_window("why did it fail last week?", now),
+            Some(now - Duration::weeks(1))
+        );
+        assert_eq!(
+            time_window("what broke yesterday", now),
+            Some(Utc.with_ymd_and_hms(2026, 3, 9, 0, 0, 0).unwrap())
+        );
+        assert_eq!(
+            time_window("failures in the last 3 days", now),
+            Some(now - Duration::days(3))
+        );
+        assert_eq!(time_window("how does the payments loop work", now), None);
+    }
+
+    #[test]
+    fn test_rank() {
+        let now = Utc::now();
+        let executions = vec![
+            exec("a-payments", "Add payments webhook", LoopExecutionStatus::Complete, now),
+            exec(
+                "b-payments",
+                "Fix payment retries",
+                LoopExecutionStatus::Failed,
+                now - Duration::days(2),
+            ),
+            exec("c-auth", "Refactor auth middleware", LoopExecutionStatus::Failed, now),
+            exec(
+                "d-payments",
+                "Payments ledger",
+                LoopExecutionStatus::Failed,
+                now - Duration::days(20),
+            ),
+        ];
+
+        let ids = |ranked: Vec<&LoopExecution>| ranked.into_iter().map(|e| e.id.clone()).collect::<Vec<_>>();
+        let since = time_window("why did the payments loop fail last week?", now);
+        let ranked = rank(&executions, "why did the payments loop fail last week?", since, 5);
+        // Topic matches only (c-auth failed but is unrelated), failures first, d is too old
+        assert_eq!(ids(ranked), vec!["b-payments", "a-payments"]);
+
+        // Nothing on topic: the most recent in the window
+        let ranked = rank(&executions, "what happened today?", None, 2);
+        assert_eq!(ranked.len(), 2);
+    }
+
+    #[test]
+    fn test_gather_and_request() {
+        let now = Utc::now();
+        let mut failed = exec("b-payments", "Fix payment retries", LoopExecutionStatus::Failed, now);
+        failed.last_error = Some("Max iterations (10) exceeded".to_string());
+        let id = failed.id.clone();
+        let events = vec![
+            entry(Event::ValidationStarted {
+                execution_id: id.clone(),
+                iteration: 3,
+                command: "cargo test".to_string(),
+            }),
+            entry(Event::ValidationOutput {
+                execution_id: id.clone(),
+                iteration: 3,
+                line: "test retries::backoff ... FAILED".to_string(),
+                is_stderr: false,
+            }),
+            entry(Event::ValidationCompleted {
+                execution_id: id.clone(),
+                iteration: 3,
+                exit_code: 101,
+                duration_ms: 900,
+            }),
+            entry(Event::IterationCompleted {
+                execution_id: id.clone(),
+                iteration: 3,
+                outcome: IterationOutcome::ValidationFailed { exit_code: 101 },
+            }),
+        ];
+
+        let evidence = Evidence::gather(&failed, &events, Path::new("/nonexistent"));
+        assert!(evidence.record.contains("last error: Max iterations (10) exceeded"));
+        assert_eq!(evidence.failures.len(), 1);
+        assert!(evidence.failures[0].ends_with("iteration 3: validation failed with exit code 101"));
+        assert_eq!(evidence.validation, vec!["test retries::backoff ... FAILED"]);
+
+        let request = build_request("why did it fail?", std::slice::from_ref(&evidence), now);
+        assert!(request.system_prompt.contains("[b-payments]"));
+        assert!(request.tools.is_empty());
+
+        let answer = "It failed on the backoff test [b-payments].";
+        assert_eq!(cited(answer, &[evidence]), vec!["b-payments"]);
+    }
+}
@@ -0,0 +1,456 @@
+//! Batch submission manifests
+//!
+//! `td exec submit batch.yaml` queues many executions at once. The manifest
+//! lists tasks with a loop type, a priority, and dependencies on other entries
+//! (by `name`) or on executions that already exist (by ID). The task is
+//! available to prompts as `{{task}}`, and each entry's context is checked
+//! against its loop type's declared variables:
+//!
+//! ```yaml
+//! tasks:
+//!   - name: extract-config
+//!     loop-type: ralph
+//!     task: Move config parsing into its own module
+//!     priority: high
+//!   - loop-type: ralph
+//!     task: Switch the CLI to the new config module
+//!     depends-on: [extract-config]
+//!     paths: [src/cli.rs]
+//! ```
+//!
+//! `paths` declares the files or directories an entry will change; they are
+//! locked when it starts so other loops are warned (or wait) before touching
+//! them.
+//!
+//! The whole manifest is validated before anything is created, so a typo in
+//! entry 27 doesn't leave 26 orphaned executions behind.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use eyre::{Context, Result};
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::domain::{LoopExecution, Priority};
+use crate::r#loop::{LoopLoader, validate_submission};
+
+/// Maximum title length derived from a task description
+const MAX_TITLE_CHARS: usize = 60;
+
+/// Context keys that loop types use for a free-form task description
+pub(crate) const TASK_INPUTS: [&str; 2] = ["task-description", "user-request"];
+
+/// A batch of executions to submit together
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct BatchManifest {
+    pub tasks: Vec<BatchEntry>,
+}
+
+/// One execution in a batch manifest
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct BatchEntry {
+    /// Local name other entries use in `depends-on` (also the display title)
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Loop type to run (must be a loaded type)
+    #[serde(alias = "loop_type")]
+    pub loop_type: String,
+
+    /// Task description handed to the loop
+    pub task: String,
+
+    /// Scheduling priority
+    #[serde(default)]
+    pub priority: Priority,
+
+    /// Entry names or existing execution IDs that must complete first
+    #[serde(default, alias = "depends_on")]
+    pub depends_on: Vec<String>,
+
+    /// Files or directories the task will change (locked while it runs)
+    #[serde(default)]
+    pub paths: Vec<String>,
+}
+
+impl BatchEntry {
+    /// Label used in validation errors: the name, or the 1-based position
+    fn label(&self, index: usize) -> String {
+        match &self.name {
+            Some(name) => format!("'{}'", name),
+            None => format!("#{}", index + 1),
+        }
+    }
+
+    /// Display title: the name, or the first line of the task, shortened
+    fn title(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => task_title(&self.task),
+        }
+    }
+}
+
+/// Title for an execution created from a free-form task: its first line, shortened
+pub(crate) fn task_title(task: &str) -> String {
+    let first_line = task.lines().next().unwrap_or_default().trim();
+    if first_line.chars().count() <= MAX_TITLE_CHARS {
+        first_line.to_string()
+    } else {
+        let truncated: String = first_line.chars().take(MAX_TITLE_CHARS - 3).collect();
+        format!("{}...", truncated.trim_end())
+    }
+}
+
+/// Pending execution for a free-form task, checked against its loop type's variables
+///
+/// This is how executions submitted from outside the CLI (webhooks, gRPC,
+/// Python) are built: the task becomes the title and `{{task}}` plus any
+/// task-description input the type declares, on top of `context`.
+pub fn task_execution(
+    loader: &LoopLoader,
+    loop_type: &str,
+    task: &str,
+    context: serde_json::Map<String, serde_json::Value>,
+    tags: &[String],
+) -> std::result::Result<LoopExecution, Vec<String>> {
+    debug!(%loop_type, "task_execution: called");
+    let Some(type_def) = loader.get(loop_type) else {
+        return Err(vec![format!("Unknown loop type '{}'", loop_type)]);
+    };
+    if task.trim().is_empty() {
+        return Err(vec!["Empty task".to_string()]);
+    }
+
+    let mut exec = LoopExecution::new(loop_type, task_title(task));
+    exec.set_title(task_title(task));
+    exec.context = serde_json::Value::Object(context);
+    exec = exec.with_context_value("task", task);
+    for input in TASK_INPUTS
+        .iter()
+        .filter(|key| type_def.inputs.iter().any(|i| i == *key))
+    {
+        exec = exec.with_context_value(input, task);
+    }
+    validate_submission(&type_def.variables, &exec.context)?;
+    exec.add_tags(tags);
+    Ok(exec)
+}
+
+impl BatchManifest {
+    /// Load a manifest from a YAML file
+    pub fn load(path: &Path) -> Result<Self> {
+        debug!(?path, "BatchManifest::load: called");
+        let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&content).with_context(|| format!("Invalid manifest {}", path.display()))
+    }
+
+    /// Parse a manifest from YAML
+    pub fn parse(content: &str) -> Result<Self> {
+        debug!(len = content.len(), "BatchManifest::parse: called");
+        Ok(serde_yaml::from_str(content)?)
+    }
+
+    /// Validate the manifest and build its executions
+    ///
+    /// `existing` holds the IDs of executions already in the store, which
+    /// `depends-on` may reference directly. Executions are returned in
+    /// dependency order (every execution after the ones it depends on) and in
+    /// Pending status. All problems are reported at once.
+    pub fn to_executions(
+        &self,
+        loader: &LoopLoader,
+        existing: &HashSet<String>,
+    ) -> std::result::Result<Vec<LoopExecution>, Vec<String>> {
+        debug!(count = self.tasks.len(), "BatchManifest::to_executions: called");
+        let mut errors = Vec::new();
+        if self.tasks.is_empty() {
+            errors.push("Manifest has no tasks".to_string());
+            return Err(errors);
+        }
+
+        // Names must be unique and must not shadow an existing execution
+        let mut names: HashMap<&str, usize> = HashMap::new();
+        for (i, entry) in self.tasks.iter().enumerate() {
+            let Some(name) = entry.name.as_deref() else {
+                continue;
+            };
+            if let Some(&first) = names.get(name) {
+                errors.push(format!(
+                    "Task #{}: name '{}' already used by task #{}",
+                    i + 1,
+                    name,
+                    first + 1
+                ));
+            } else {
+                names.insert(name, i);
+            }
+            if existing.contains(name) {
+                errors.push(format!("Task #{}: name '{}' is an existing execution ID", i + 1, name));
+            }
+        }
+
+        // Per-entry checks, resolving dependencies to entry indices or external IDs
+        let mut local_deps: Vec<Vec<usize>> = vec![Vec::new(); self.tasks.len()];
+        let mut external_deps: Vec<Vec<String>> = vec![Vec::new(); self.tasks.len()];
+        for (i, entry) in self.tasks.iter().enumerate() {
+            let label = entry.label(i);
+            if loader.get(&entry.loop_type).is_none() {
+                let mut available: Vec<&str> = loader.names().collect();
+                available.sort_unstable();
+                errors.push(format!(
+                    "Task {}: unknown loop type '{}' (available: {})",
+                    label,
+                    entry.loop_type,
+                    available.join(", ")
+                ));
+            }
+            if entry.task.trim().is_empty() {
+                errors.push(format!("Task {}: task description is empty", label));
+            }
+            for dep in &entry.depends_on {
+                if let Some(&j) = names.get(dep.as_str()) {
+                    if j == i {
+                        errors.push(format!("Task {}: depends on itself", label));
+                    } else {
+                        local_deps[i].push(j);
+                    }
+                } else if existing.contains(dep) {
+                    external_deps[i].push(dep.clone());
+                } else {
+                    errors.push(format!(
+                        "Task {}: depends on unknown task or execution '{}'",
+                        label, dep
+                    ));
+                }
+            }
+        }
+
+        let order = match dependency_order(&local_deps) {
+            Ok(order) => order,
+            Err(cycle) => {
+                let path: Vec<String> = cycle.iter().map(|&i| self.tasks[i].label(i)).collect();
+                errors.push(format!("Dependency cycle: {}", path.join(" -> ")));
+                Vec::new()
+            }
+        };
+
+        if !errors.is_empty() {
+            debug!(
+                error_count = errors.len(),
+                "BatchManifest::to_executions: invalid manifest"
+            );
+            return Err(errors);
+        }
+
+        // Build in dependency order so every dep already has its generated ID
+        let mut ids: Vec<Option<String>> = vec![None; self.tasks.len()];
+        let mut seen_ids: HashMap<String, usize> = HashMap::new();
+        let mut executions = Vec::with_capacity(self.tasks.len());
+        for i in order {
+            let entry = &self.tasks[i];
+            let mut deps: Vec<String> = local_deps[i].iter().filter_map(|&j| ids[j].clone()).collect();
+            deps.extend(external_deps[i].iter().cloned());
+
+            let mut exec = LoopExecution::new(&entry.loop_type, entry.title())
+                .with_priority(entry.priority)
+                .with_deps(deps)
+                .with_context_value("task", &entry.task);
+            exec.set_title(entry.title());
+            if !entry.paths.is_empty() {
+                exec.context["paths"] = serde_json::json!(entry.paths);
+            }
+            if let Some(loop_type) = loader.get(&entry.loop_type) {
+                for input in TASK_INPUTS
+                    .iter()
+                    .filter(|key| loop_type.inputs.iter().any(|i| i == *key))
+                {
+                    exec = exec.with_context_value(input, &entry.task);
+                }
+                if let Err(problems) = validate_submission(&loop_type.variables, &exec.context) {
+                    let label = entry.label(i);
+                    errors.extend(problems.into_iter().map(|p| format!("Task {}: {}", label, p)));
+                }
+            }
+
+            // IDs are slugged from the title; identical tasks of one type would collide
+            if let Some(first) = seen_ids.insert(exec.id.clone(), i) {
+                errors.push(format!(
+                    "Task {}: duplicates task {} (give one of them a distinct name)",
+                    entry.label(i),
+                    self.tasks[first].label(first)
+                ));
+            }
+            ids[i] = Some(exec.id.clone());
+            executions.push(exec);
+        }
+
+        if !errors.is_empty() {
+            debug!(
+                error_count = errors.len(),
+                "BatchManifest::to_executions: duplicate executions"
+            );
+            return Err(errors);
+        }
+        debug!(count = executions.len(), "BatchManifest::to_executions: complete");
+        Ok(executions)
+    }
+}
+
+/// Order entries so each comes after its dependencies (stable for independent entries)
+///
+/// Returns the entries of a cycle (first entry repeated at the end) if there is one.
+fn dependency_order(deps: &[Vec<usize>]) -> std::result::Result<Vec<usize>, Vec<usize>> {
+    debug!(count = deps.len(), "dependency_order: called");
+    // 0 = unvisited, 1 = on the current path, 2 = done
+    let mut state = vec![0u8; deps.len()];
+    let mut order = Vec::with_capacity(deps.len());
+    let mut path = Vec::new();
+
+    fn visit(
+        node: usize,
+        deps: &[Vec<usize>],
+        state: &mut [u8],
+        order: &mut Vec<usize>,
+        path: &mut Vec<usize>,
+    ) -> Option<Vec<usize>> {
+        match state[node] {
+            2 => return None,
+            1 => {
+                let start = path.iter().position(|&n| n == node).unwrap_or(0);
+                let mut cycle = path[start..].to_vec();
+                cycle.push(node);
+                return Some(cycle);
+            }
+            _ => {}
+        }
+        state[node] = 1;
+        path.push(node);
+        for &dep in &deps[node] {
+            if let Some(cycle) = visit(dep, deps, state, order, path) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        state[node] = 2;
+        order.push(node);
+        None
+    }
+
+    for node in 0..deps.len() {
+        if let Some(cycle) = visit(node, deps, &mut state, &mut order, &mut path) {
+            debug!(?cycle, "dependency_order: cycle found");
+            return Err(cycle);
+        }
+    }
+    Ok(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LoopsConfig;
+
+    fn loader() -> LoopLoader {
+        LoopLoader::new(&LoopsConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn test_manifest_builds_linked_executions_in_order() {
+        let manifest = BatchManifest::parse(
+            r#"
+tasks:
+  - loop-type: ralph
+    task: Switch the CLI to the new config module
+    depends-on: [extract-config, 0190ab-ralph-existing]
+    paths: [src/cli.rs, src/main.rs]
+  - name: extract-config
+    loop_type: ralph
+    task: Move config parsing into its own module
+    priority: high
+"#,
+        )
+        .unwrap();
+        let existing = HashSet::from(["0190ab-ralph-existing".to_string()]);
+
+        let executions = manifest.to_executions(&loader(), &existing).unwrap();
+        assert_eq!(executions.len(), 2);
+
+        // The dependency is created first, and the dependent links to its ID
+        let (extract, switch) = (&executions[0], &executions[1]);
+        assert_eq!(extract.title.as_deref(), Some("extract-config"));
+        assert_eq!(extract.priority, Priority::High);
+        assert_eq!(switch.priority, Priority::Normal);
+        assert_eq!(
+            switch.deps,
+            vec![extract.id.clone(), "0190ab-ralph-existing".to_string()]
+        );
+        assert_eq!(
+            switch.context["task-description"],
+            "Switch the CLI to the new config module"
+        );
+        assert_eq!(switch.context["task"], "Switch the CLI to the new config module");
+        assert_eq!(switch.declared_paths(), ["src/cli.rs", "src/main.rs"]);
+        assert!(extract.declared_paths().is_empty());
+    }
+
+    #[test]
+    fn test_manifest_reports_all_errors() {
+        let manifest = BatchManifest::parse(
+            r#"
+tasks:
+  - name: a
+    loop-type: nope
+    task: ""
+    depends-on: [b, missing]
+  - name: b
+    loop-type: ralph
+    task: Second
+    depends-on: [a]
+  - name: b
+    loop-type: ralph
+    task: Third
+"#,
+        )
+        .unwrap();
+
+        let errors = manifest.to_executions(&loader(), &HashSet::new()).unwrap_err();
+        let text = errors.join("\n");
+        assert!(text.contains("name 'b' already used by task #2"), "{}", text);
+        assert!(text.contains("unknown loop type 'nope'"), "{}", text);
+        assert!(text.contains("task description is empty"), "{}", text);
+        assert!(text.contains("unknown task or execution 'missing'"), "{}", text);
+        assert!(text.contains("Dependency cycle: 'a' -> "), "{}", text);
+    }
+
+    #[test]
+    fn test_task_execution_fills_task_inputs() {
+        let mut context = serde_json::Map::new();
+        context.insert("issue".to_string(), serde_json::json!("412"));
+        let exec = task_execution(
+            &loader(),
+            "ralph",
+            "Fix the login redirect\nIt loops forever",
+            context,
+            &["Platform".to_string()],
+        )
+        .unwrap();
+        assert_eq!(exec.title.as_deref(), Some("Fix the login redirect"));
+        assert_eq!(exec.context["task"], "Fix the login redirect\nIt loops forever");
+        assert_eq!(exec.context["issue"], "412");
+        assert_eq!(exec.tags, vec!["platform".to_string()]);
+
+        let errors = task_execution(&loader(), "nope", "Anything", Default::default(), &[]).unwrap_err();
+        assert_eq!(errors, vec!["Unknown loop type 'nope'".to_string()]);
+        assert!(task_execution(&loader(), "ralph", "  ", Default::default(), &[]).is_err());
+    }
+
+    #[test]
+    fn test_manifest_rejects_unknown_fields() {
+        let err = BatchManifest::parse("tasks:\n  - loop-type: ralph\n    task: x\n    prio: high\n").unwrap_err();
+        assert!(err.to_string().contains("prio"));
+    }
+}
@@ -0,0 +1,230 @@
+//! Bulk actions on many executions at once
+//!
+//! `td exec cancel --filter status=failed,type=plan` selects executions with
+//! an [`ExecFilter`] and applies one [`BulkAction`] to each of them. Without
+//! `--yes` the command only previews the affected IDs. The TUI applies the
+//! same actions to the executions marked with space.
+//!
+//! Executions an action doesn't apply to (pausing one that isn't running,
+//! say) are skipped rather than reported as failures.
+
+use std::fmt;
+use std::str::FromStr;
+
+use eyre::{Result, bail};
+use tracing::debug;
+
+use crate::domain::{LoopExecution, Submitter};
+use crate::state::{StateManager, StateResponse};
+
+/// An action that can be applied to many executions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkAction {
+    Pause,
+    Resume,
+    Cancel,
+    Delete,
+}
+
+impl BulkAction {
+    /// Whether the action applies to an execution in `status` (as displayed, e.g. "running")
+    pub fn applies_to_status(self, status: &str) -> bool {
+        match self {
+            Self::Pause => status == "running",
+            Self::Resume => matches!(status, "paused" | "blocked" | "parked" | "waiting_provider"),
+            Self::Cancel => !matches!(status, "complete" | "failed" | "stopped"),
+            Self::Delete => true,
+        }
+    }
+
+    /// Whether the action applies to `exec`
+    pub fn applies_to(self, exec: &LoopExecution) -> bool {
+        self.applies_to_status(&exec.status.to_string())
+    }
+
+    /// Capitalized name for prompts ("Pause 3 executions?")
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Pause => "Pause",
+            Self::Resume => "Resume",
+            Self::Cancel => "Cancel",
+            Self::Delete => "Delete",
+        }
+    }
+
+    /// Past tense for reports ("Paused 3 executions")
+    pub fn past_tense(self) -> &'static str {
+        match self {
+            Self::Pause => "Paused",
+            Self::Resume => "Resumed",
+            Self::Cancel => "Cancelled",
+            Self::Delete => "Deleted",
+        }
+    }
+
+    /// Apply the action to one execution
+    pub async fn apply(self, state: &StateManager, id: &str) -> StateResponse<()> {
+        debug!(action = %self, %id, "BulkAction::apply: called");
+        match self {
+            Self::Pause => state.pause_execution(id).await,
+            Self::Resume => state.resume_execution(id).await,
+            Self::Cancel => state.cancel_execution(id).await,
+            Self::Delete => state.delete_execution(id).await,
+        }
+    }
+}
+
+impl fmt::Display for BulkAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Pause => "pause",
+            Self::Resume => "resume",
+            Self::Cancel => "cancel",
+            Self::Delete => "delete",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Execution selector: `status=failed,type=plan,tag=backend,user=alice`
+///
+/// Every given field must match; `tag` may repeat. `user=me` selects the
+/// executions the current OS user submitted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecFilter {
+    pub status: Option<String>,
+    pub loop_type: Option<String>,
+    pub tags: Vec<String>,
+    pub user: Option<String>,
+}
+
+impl ExecFilter {
+    /// Whether `exec` matches every field of the filter
+    pub fn matches(&self, exec: &LoopExecution) -> bool {
+        self.status
+            .as_ref()
+            .is_none_or(|status| exec.status.to_string().eq_ignore_ascii_case(status))
+            && self
+                .loop_type
+                .as_ref()
+                .is_none_or(|loop_type| exec.loop_type.eq_ignore_ascii_case(loop_type))
+            && self.tags.iter().all(|tag| exec.has_tag(tag))
+            && self.user.as_ref().is_none_or(|user| exec.submitted_by_user(user))
+    }
+}
+
+impl FromStr for ExecFilter {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        debug!(%s, "ExecFilter::from_str: called");
+        let mut filter = Self::default();
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let Some((key, value)) = part.split_once('=') else {
+                bail!("Invalid filter '{}': expected key=value", part);
+            };
+            let value = value.trim().to_string();
+            if value.is_empty() {
+                bail!("Invalid filter '{}': empty value", part);
+            }
+            match key.trim() {
+                "status" => filter.status = Some(value),
+                "type" | "loop-type" => filter.loop_type = Some(value),
+                "tag" => filter.tags.push(value),
+                "user" if value == "me" => match Submitter::current(None) {
+                    Some(me) => filter.user = Some(me.user),
+                    None => bail!("Invalid filter '{}': can't tell who you are (set $USER)", part),
+                },
+                "user" => filter.user = Some(value),
+                other => bail!("Unknown filter key '{}' (expected status, type, tag or user)", other),
+            }
+        }
+        if filter == Self::default() {
+            bail!("Empty filter: give at least one of status=, type=, tag= or user=");
+        }
+        Ok(filter)
+    }
+}
+
+/// What happened when a bulk action ran
+#[derive(Debug, Default)]
+pub struct BulkOutcome {
+    /// IDs the action succeeded on
+    pub done: Vec<String>,
+    /// IDs the action failed on, with the error
+    pub failed: Vec<(String, String)>,
+}
+
+/// Apply `action` to every ID, continuing past failures
+pub async fn apply_all(state: &StateManager, action: BulkAction, ids: &[String]) -> BulkOutcome {
+    debug!(%action, count = ids.len(), "apply_all: called");
+    let mut outcome = BulkOutcome::default();
+    for id in ids {
+        match action.apply(state, id).await {
+            Ok(()) => outcome.done.push(id.clone()),
+            Err(e) => {
+                debug!(%id, error = %e, "apply_all: action failed");
+                outcome.failed.push((id.clone(), e.to_string()));
+            }
+        }
+    }
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::LoopExecutionStatus;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_exec_filter_parse_and_match() {
+        let filter: ExecFilter = "status=failed, type=plan,tag=backend".parse().unwrap();
+        assert_eq!(filter.status.as_deref(), Some("failed"));
+        assert_eq!(filter.loop_type.as_deref(), Some("plan"));
+        assert_eq!(filter.tags, ["backend"]);
+
+        let mut exec = LoopExecution::new("plan", "api");
+        exec.set_status(LoopExecutionStatus::Failed);
+        assert!(!filter.matches(&exec));
+        exec.add_tags(&["backend".to_string()]);
+        assert!(filter.matches(&exec));
+
+        let filter: ExecFilter = "user=alice".parse().unwrap();
+        assert!(!filter.matches(&exec));
+        let exec = exec.with_submitter(Some(Submitter {
+            user: "alice".to_string(),
+            name: None,
+        }));
+        assert!(filter.matches(&exec));
+        assert!("user=me".parse::<ExecFilter>().unwrap().user.is_some());
+
+        assert!("status".parse::<ExecFilter>().is_err());
+        assert!("owner=me".parse::<ExecFilter>().is_err());
+        assert!("".parse::<ExecFilter>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_apply_all_reports_failures() {
+        let temp = tempdir().unwrap();
+        let state = StateManager::spawn(temp.path()).unwrap();
+        let mut running = LoopExecution::with_id("running-exec", "ralph");
+        running.set_status(LoopExecutionStatus::Running);
+        state.create_execution(running).await.unwrap();
+        state
+            .create_execution(LoopExecution::with_id("pending-exec", "ralph"))
+            .await
+            .unwrap();
+
+        let ids = vec!["running-exec".to_string(), "pending-exec".to_string()];
+        let outcome = apply_all(&state, BulkAction::Pause, &ids).await;
+        assert_eq!(outcome.done, ["running-exec"]);
+        assert_eq!(outcome.failed.len(), 1);
+        assert!(!BulkAction::Pause.applies_to_status("pending"));
+
+        let paused = state.get_execution("running-exec").await.unwrap().unwrap();
+        assert_eq!(paused.status, LoopExecutionStatus::Paused);
+
+        state.shutdown().await.unwrap();
+    }
+}
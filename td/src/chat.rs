@@ -0,0 +1,634 @@
+//! Slack/Discord chat bridge
+//!
+//! With `chat.platform` set, the daemon posts the transitions it would show as
+//! desktop notifications (complete, failed, waiting for approval) to a channel
+//! through its incoming-webhook URL. With `chat.command-path` set, the webhook
+//! server also takes commands from the channel:
+//!
+//! ```text
+//! status [id]          overview, or one execution
+//! pause <id>           resume <id>           cancel <id>
+//! nudge <id> <text>    message for the loop's next iteration
+//! ```
+//!
+//! Slack delivers commands as a slash command or an `app_mention` event,
+//! signed with the app's signing secret. Discord bots (or any other relay)
+//! POST `{"user": "...", "text": "..."}` signed like webhook triggers
+//! (`X-TaskDaemon-Signature`). Pause, resume and cancel go through the same
+//! [`BulkAction`]s as the TUI; every command except `help` must be allowed
+//! for the user under `chat.allow`.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use eyre::{Result, bail};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+use crate::bulk::BulkAction;
+use crate::config::{ChatConfig, NotificationsConfig};
+use crate::coordinator::{CoordRequest, NUDGE_SHARE_TYPE};
+use crate::domain::{IdResolver, LoopExecutionStatus};
+use crate::notify::Notifier;
+use crate::state::{StateEvent, StateManager};
+use crate::webhook::{self, HttpRequest, HttpResponse, SIGNATURE_HEADER};
+
+/// `from_exec_id` of shares sent by the chat bridge
+const CHAT_SENDER: &str = "chat";
+
+/// Oldest Slack request timestamp accepted, in seconds (replay protection)
+const SLACK_MAX_AGE_SECS: i64 = 300;
+
+/// Running executions listed by `status`
+const MAX_STATUS_LINES: usize = 10;
+
+const USAGE: &str = "Commands: status [id], pause <id>, resume <id>, cancel <id>, nudge <id> <message>, help";
+
+/// Chat service the bridge talks to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatPlatform {
+    Slack,
+    Discord,
+}
+
+impl fmt::Display for ChatPlatform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Slack => write!(f, "slack"),
+            Self::Discord => write!(f, "discord"),
+        }
+    }
+}
+
+/// A command parsed from a chat message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChatCommand {
+    Help,
+    /// Overview, or one execution
+    Status(Option<String>),
+    /// Pause, resume or cancel an execution
+    Action(BulkAction, String),
+    /// Message for an execution's next iteration
+    Nudge(String, String),
+}
+
+impl ChatCommand {
+    /// Parse a message, ignoring leading mentions (`<@U123>`, `@taskdaemon`)
+    pub fn parse(text: &str) -> std::result::Result<Self, String> {
+        debug!(%text, "ChatCommand::parse: called");
+        let mut words = text
+            .split_whitespace()
+            .skip_while(|word| (word.starts_with("<@") && word.ends_with('>')) || word.starts_with('@'));
+        let Some(command) = words.next() else {
+            return Ok(Self::Help);
+        };
+        let id = words.next().map(str::to_string);
+        let require_id = |id: Option<String>| id.ok_or_else(|| format!("'{}' needs an execution ID", command));
+        match command.to_lowercase().as_str() {
+            "help" => Ok(Self::Help),
+            "status" => Ok(Self::Status(id)),
+            "pause" => Ok(Self::Action(BulkAction::Pause, require_id(id)?)),
+            "resume" => Ok(Self::Action(BulkAction::Resume, require_id(id)?)),
+            "cancel" => Ok(Self::Action(BulkAction::Cancel, require_id(id)?)),
+            "nudge" => {
+                let id = require_id(id)?;
+                let message = words.collect::<Vec<_>>().join(" ");
+                if message.is_empty() {
+                    return Err("'nudge' needs a message".to_string());
+                }
+                Ok(Self::Nudge(id, message))
+            }
+            other => Err(format!("Unknown command '{}'", other)),
+        }
+    }
+
+    /// Name used in `chat.allow`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Help => "help",
+            Self::Status(_) => "status",
+            Self::Action(BulkAction::Pause, _) => "pause",
+            Self::Action(BulkAction::Resume, _) => "resume",
+            Self::Action(BulkAction::Cancel, _) => "cancel",
+            Self::Action(BulkAction::Delete, _) => "delete",
+            Self::Nudge(..) => "nudge",
+        }
+    }
+}
+
+/// Posts notifications to a chat channel and runs the commands it sends
+pub struct ChatBridge {
+    platform: ChatPlatform,
+    config: ChatConfig,
+    webhook_url: Option<String>,
+    signing_secret: Option<String>,
+    state: StateManager,
+    coordinator: Option<mpsc::Sender<CoordRequest>>,
+    http: reqwest::Client,
+}
+
+impl ChatBridge {
+    /// Build the bridge from config; None when no platform is configured
+    pub fn new(config: &ChatConfig, state: StateManager) -> Result<Option<Self>> {
+        let Some(platform) = config.platform else {
+            debug!("ChatBridge::new: no platform configured");
+            return Ok(None);
+        };
+        debug!(%platform, "ChatBridge::new: called");
+        let read_env = |var: &Option<String>| {
+            var.as_ref()
+                .and_then(|v| std::env::var(v).ok())
+                .filter(|v| !v.is_empty())
+        };
+        let webhook_url = read_env(&config.webhook_url_env);
+        if webhook_url.is_none() {
+            warn!(%platform, "Chat bridge has no webhook URL; notifications are not posted");
+        }
+        let signing_secret = read_env(&config.signing_secret_env);
+        if let Some(path) = &config.command_path {
+            if !path.starts_with('/') {
+                bail!("chat.command-path must start with '/': {}", path);
+            }
+            if signing_secret.is_none() {
+                bail!("chat.command-path is set but no signing secret was found (signing-secret-env)");
+            }
+        }
+        Ok(Some(Self {
+            platform,
+            config: config.clone(),
+            webhook_url,
+            signing_secret,
+            state,
+            coordinator: None,
+            http: reqwest::Client::new(),
+        }))
+    }
+
+    /// Deliver `nudge` messages through the coordinator
+    pub fn with_coordinator(mut self, coordinator: mpsc::Sender<CoordRequest>) -> Self {
+        self.coordinator = Some(coordinator);
+        self
+    }
+
+    /// Path on the webhook server that receives commands
+    pub fn command_path(&self) -> Option<&str> {
+        self.config.command_path.as_deref()
+    }
+
+    /// Whether `user` may run `command`
+    pub fn allowed(&self, user: &str, command: &ChatCommand) -> bool {
+        if *command == ChatCommand::Help {
+            return true;
+        }
+        self.config
+            .allow
+            .get(command.name())
+            .is_some_and(|users| users.iter().any(|u| u == "*" || u == user))
+    }
+
+    /// Run a command message from `user` and return the reply
+    pub async fn execute(&self, user: &str, text: &str) -> String {
+        let command = match ChatCommand::parse(text) {
+            Ok(command) => command,
+            Err(e) => return format!("{}. {}", e, USAGE),
+        };
+        debug!(%user, command = command.name(), "ChatBridge::execute: called");
+        if !self.allowed(user, &command) {
+            warn!(%user, command = command.name(), "Chat command not allowed");
+            return format!("You are not allowed to run '{}'.", command.name());
+        }
+        info!(%user, command = command.name(), "Chat command");
+
+        match self.run(user, command).await {
+            Ok(reply) => reply,
+            Err(e) => format!("Failed: {}", e),
+        }
+    }
+
+    async fn run(&self, user: &str, command: ChatCommand) -> Result<String> {
+        match command {
+            ChatCommand::Help => Ok(USAGE.to_string()),
+            ChatCommand::Status(None) => self.overview().await,
+            ChatCommand::Status(Some(reference)) => {
+                let id = self.resolve(&reference).await?;
+                let exec = self
+                    .state
+                    .get_execution(&id)
+                    .await?
+                    .ok_or_else(|| eyre::eyre!("Execution {} not found", id))?;
+                let mut reply = format!(
+                    "{} ({}): {}, iteration {}",
+                    exec.id, exec.loop_type, exec.status, exec.iteration
+                );
+                if let Some(submitter) = &exec.submitted_by {
+                    reply.push_str(&format!("\nSubmitted by: {}", submitter));
+                }
+                if let Some(error) = &exec.last_error {
+                    reply.push_str(&format!("\nLast error: {}", error));
+                }
+                Ok(reply)
+            }
+            ChatCommand::Action(action, reference) => {
+                let id = self.resolve(&reference).await?;
+                let exec = self
+                    .state
+                    .get_execution(&id)
+                    .await?
+                    .ok_or_else(|| eyre::eyre!("Execution {} not found", id))?;
+                if !action.applies_to(&exec) {
+                    return Ok(format!("Cannot {} {}: it is {}", action, id, exec.status));
+                }
+                action.apply(&self.state, &id).await?;
+                Ok(format!("{} {}", action.past_tense(), id))
+            }
+            ChatCommand::Nudge(reference, message) => {
+                let id = self.resolve(&reference).await?;
+                let exec = self
+                    .state
+                    .get_execution(&id)
+                    .await?
+                    .ok_or_else(|| eyre::eyre!("Execution {} not found", id))?;
+                if exec.status != LoopExecutionStatus::Running {
+                    return Ok(format!("Cannot nudge {}: it is {}", id, exec.status));
+                }
+                let Some(coordinator) = &self.coordinator else {
+                    bail!("nudges are not available");
+                };
+                coordinator
+                    .send(CoordRequest::Share {
+                        from_exec_id: CHAT_SENDER.to_string(),
+                        target_exec_id: id.clone(),
+                        share_type: NUDGE_SHARE_TYPE.to_string(),
+                        data: serde_json::json!({ "message": message, "from": user }),
+                    })
+                    .await
+                    .map_err(|_| eyre::eyre!("coordinator is not running"))?;
+                Ok(format!("Nudged {}; the message goes into its next iteration", id))
+            }
+        }
+    }
+
+    /// Counts by status and the running executions
+    async fn overview(&self) -> Result<String> {
+        let executions = self.state.list_executions(None, None).await?;
+        let mut counts: Vec<(String, usize)> = Vec::new();
+        for exec in &executions {
+            let status = exec.status.to_string();
+            match counts.iter_mut().find(|(s, _)| *s == status) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((status, 1)),
+            }
+        }
+        if counts.is_empty() {
+            return Ok("No executions.".to_string());
+        }
+        let mut reply = counts
+            .iter()
+            .map(|(status, count)| format!("{} {}", count, status))
+            .collect::<Vec<_>>()
+            .join(", ");
+        for exec in executions
+            .iter()
+            .filter(|e| e.status == LoopExecutionStatus::Running)
+            .take(MAX_STATUS_LINES)
+        {
+            let title = exec.title.as_deref().unwrap_or(&exec.loop_type);
+            reply.push_str(&format!("\n• {} {} (iteration {})", exec.id, title, exec.iteration));
+        }
+        Ok(reply)
+    }
+
+    /// Resolve a (partial) execution ID
+    async fn resolve(&self, reference: &str) -> Result<String> {
+        let ids: HashMap<String, String> = self
+            .state
+            .list_executions(None, None)
+            .await?
+            .into_iter()
+            .map(|exec| {
+                let name = exec.title.clone().unwrap_or_else(|| exec.loop_type.clone());
+                (exec.id, name)
+            })
+            .collect();
+        match IdResolver::new(&ids).resolve(reference) {
+            Ok(Some(id)) => Ok(id),
+            Ok(None) => bail!("no execution matches '{}'", reference),
+            Err(candidates) => bail!("'{}' is ambiguous: {}", reference, candidates.join(", ")),
+        }
+    }
+
+    /// Answer a command request from the webhook server
+    pub async fn handle(&self, request: &HttpRequest) -> HttpResponse {
+        debug!(platform = %self.platform, path = %request.path, "ChatBridge::handle: called");
+        if request.method != "POST" {
+            return HttpResponse::error(405, "Only POST is accepted");
+        }
+        let Some(secret) = &self.signing_secret else {
+            return HttpResponse::error(404, "Chat commands are not enabled");
+        };
+        match self.platform {
+            ChatPlatform::Slack => {
+                if !verify_slack(secret, request, chrono::Utc::now().timestamp()) {
+                    warn!("Slack request signature rejected");
+                    return HttpResponse::error(401, "Missing or invalid signature");
+                }
+                self.handle_slack(request).await
+            }
+            ChatPlatform::Discord => {
+                let signature = request.headers.get(SIGNATURE_HEADER).map(String::as_str).unwrap_or("");
+                if !webhook::verify(secret, &request.body, signature) {
+                    warn!("Chat command signature rejected");
+                    return HttpResponse::error(401, "Missing or invalid signature");
+                }
+                let message: RelayedMessage = match serde_json::from_slice(&request.body) {
+                    Ok(message) => message,
+                    Err(e) => return HttpResponse::error(400, format!("Invalid body: {}", e)),
+                };
+                let reply = self.execute(&message.user, &message.text).await;
+                HttpResponse::json(200, serde_json::json!({ "content": reply }))
+            }
+        }
+    }
+
+    async fn handle_slack(&self, request: &HttpRequest) -> HttpResponse {
+        let content_type = request.headers.get("content-type").map(String::as_str).unwrap_or("");
+        if content_type.starts_with("application/x-www-form-urlencoded") {
+            // Slash command: reply in the response
+            let form = form_decode(&String::from_utf8_lossy(&request.body));
+            let user = form.get("user_id").map(String::as_str).unwrap_or_default();
+            let text = form.get("text").map(String::as_str).unwrap_or_default();
+            let reply = self.execute(user, text).await;
+            return HttpResponse::json(200, serde_json::json!({ "response_type": "in_channel", "text": reply }));
+        }
+
+        let payload: serde_json::Value = match serde_json::from_slice(&request.body) {
+            Ok(payload) => payload,
+            Err(e) => return HttpResponse::error(400, format!("Invalid body: {}", e)),
+        };
+        match payload["type"].as_str() {
+            Some("url_verification") => {
+                HttpResponse::json(200, serde_json::json!({ "challenge": payload["challenge"] }))
+            }
+            Some("event_callback") if payload["event"]["type"] == "app_mention" => {
+                // Mention: reply in the channel
+                let user = payload["event"]["user"].as_str().unwrap_or_default();
+                let text = payload["event"]["text"].as_str().unwrap_or_default();
+                let reply = self.execute(user, text).await;
+                self.post(&reply).await;
+                HttpResponse::json(200, serde_json::json!({}))
+            }
+            _ => HttpResponse::json(200, serde_json::json!({})),
+        }
+    }
+
+    /// Post a message to the channel
+    pub async fn post(&self, text: &str) {
+        let Some(url) = &self.webhook_url else {
+            debug!("ChatBridge::post: no webhook URL");
+            return;
+        };
+        let payload = match self.platform {
+            ChatPlatform::Slack => serde_json::json!({ "text": text }),
+            ChatPlatform::Discord => serde_json::json!({ "content": text }),
+        };
+        match self.http.post(url).json(&payload).send().await {
+            Ok(response) if response.status().is_success() => debug!("ChatBridge::post: sent"),
+            Ok(response) => warn!(status = %response.status(), "Chat webhook rejected the message"),
+            Err(e) => warn!(error = %e, "Failed to post to chat webhook"),
+        }
+    }
+
+    /// Post notifications for execution transitions until the state channel closes
+    pub async fn watch(self: Arc<Self>, notifications: NotificationsConfig) {
+        debug!("ChatBridge::watch: called");
+        let mut notifier = Notifier::new(notifications);
+        let mut events = self.state.subscribe_events();
+        match self.state.list_executions(None, None).await {
+            Ok(executions) => {
+                notifier.observe(&executions);
+            }
+            Err(e) => warn!(error = %e, "Chat bridge: failed to list executions"),
+        }
+
+        loop {
+            match events.recv().await {
+                Ok(StateEvent::ExecutionCreated { .. } | StateEvent::ExecutionUpdated { .. }) => {
+                    let executions = match self.state.list_executions(None, None).await {
+                        Ok(executions) => executions,
+                        Err(e) => {
+                            warn!(error = %e, "Chat bridge: failed to list executions");
+                            continue;
+                        }
+                    };
+                    for notification in notifier.observe(&executions) {
+                        self.post(&format!("{}: {}", notification.summary(), notification.body()))
+                            .await;
+                    }
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!(skipped, "ChatBridge::watch: lagged, will catch up on next update");
+                }
+                Err(RecvError::Closed) => {
+                    debug!("ChatBridge::watch: state events closed");
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Command relayed by a Discord bot or other integration
+#[derive(Debug, Deserialize)]
+struct RelayedMessage {
+    user: String,
+    text: String,
+}
+
+/// Check Slack's `v0` request signature and timestamp
+fn verify_slack(secret: &str, request: &HttpRequest, now_secs: i64) -> bool {
+    let (Some(timestamp), Some(signature)) = (
+        request.headers.get("x-slack-request-timestamp"),
+        request.headers.get("x-slack-signature"),
+    ) else {
+        return false;
+    };
+    let Ok(sent_at) = timestamp.parse::<i64>() else {
+        return false;
+    };
+    if (now_secs - sent_at).abs() > SLACK_MAX_AGE_SECS {
+        return false;
+    }
+    let mut base = format!("v0:{}:", timestamp).into_bytes();
+    base.extend_from_slice(&request.body);
+    let expected = format!(
+        "v0={}",
+        webhook::to_hex(&webhook::hmac_sha256(secret.as_bytes(), &base))
+    );
+    webhook::constant_time_eq(&expected, signature)
+}
+
+/// Decode an `application/x-www-form-urlencoded` body
+fn form_decode(body: &str) -> HashMap<String, String> {
+    body.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (percent_decode(key), percent_decode(value)))
+        .collect()
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => match s.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                Some(byte) => {
+                    out.push(byte);
+                    i += 2;
+                }
+                None => out.push(b'%'),
+            },
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::LoopExecution;
+    use std::collections::BTreeMap;
+    use tempfile::tempdir;
+
+    fn bridge(platform: ChatPlatform, state: StateManager) -> ChatBridge {
+        let config = ChatConfig {
+            platform: Some(platform),
+            command_path: Some("/chat".to_string()),
+            allow: BTreeMap::from([
+                ("status".to_string(), vec!["*".to_string()]),
+                ("pause".to_string(), vec!["U1".to_string()]),
+                ("nudge".to_string(), vec!["U1".to_string()]),
+            ]),
+            ..Default::default()
+        };
+        ChatBridge {
+            platform,
+            config,
+            webhook_url: None,
+            signing_secret: Some("s3cret".to_string()),
+            state,
+            coordinator: None,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_commands_and_slack_signature() {
+        assert_eq!(ChatCommand::parse("<@U0BOT> status"), Ok(ChatCommand::Status(None)));
+        assert_eq!(
+            ChatCommand::parse("@taskdaemon PAUSE abc"),
+            Ok(ChatCommand::Action(BulkAction::Pause, "abc".to_string()))
+        );
+        assert_eq!(
+            ChatCommand::parse("nudge abc  try the   other test"),
+            Ok(ChatCommand::Nudge("abc".to_string(), "try the other test".to_string()))
+        );
+        assert_eq!(ChatCommand::parse(""), Ok(ChatCommand::Help));
+        assert!(ChatCommand::parse("nudge abc").is_err());
+        assert!(ChatCommand::parse("cancel").is_err());
+        assert!(ChatCommand::parse("delete abc").unwrap_err().contains("Unknown"));
+
+        assert_eq!(form_decode("text=pause+ab%2Fc&user_id=U1")["text"], "pause ab/c");
+        assert_eq!(percent_decode("100%"), "100%");
+
+        let body = b"token=x&text=status".to_vec();
+        let signature = format!(
+            "v0={}",
+            webhook::to_hex(&webhook::hmac_sha256(b"s3cret", b"v0:1000:token=x&text=status"))
+        );
+        let request = HttpRequest {
+            method: "POST".to_string(),
+            path: "/chat".to_string(),
+            headers: HashMap::from([
+                ("x-slack-request-timestamp".to_string(), "1000".to_string()),
+                ("x-slack-signature".to_string(), signature),
+            ]),
+            body,
+        };
+        assert!(verify_slack("s3cret", &request, 1100));
+        assert!(!verify_slack("s3cret", &request, 1000 + SLACK_MAX_AGE_SECS + 1));
+        assert!(!verify_slack("other", &request, 1100));
+    }
+
+    #[tokio::test]
+    async fn test_commands_authorized_and_applied() {
+        let temp = tempdir().unwrap();
+        let state = StateManager::spawn(temp.path()).unwrap();
+        let mut exec = LoopExecution::new("ralph", "fix the flaky test");
+        exec.status = LoopExecutionStatus::Running;
+        let id = state.create_execution(exec).await.unwrap();
+        let (tx, mut rx) = mpsc::channel(4);
+        let bridge = bridge(ChatPlatform::Discord, state.clone()).with_coordinator(tx);
+
+        let reply = bridge.execute("U2", "status").await;
+        assert!(reply.contains("1 running"), "{}", reply);
+        assert!(reply.contains(&id));
+        assert!(
+            bridge
+                .execute("U2", &format!("pause {}", id))
+                .await
+                .contains("not allowed")
+        );
+        assert!(
+            bridge
+                .execute("U1", &format!("resume {}", id))
+                .await
+                .contains("not allowed")
+        );
+
+        let reply = bridge
+            .execute("U1", &format!("nudge {} check the logs first", id))
+            .await;
+        assert!(reply.starts_with("Nudged"), "{}", reply);
+        match rx.recv().await.unwrap() {
+            CoordRequest::Share {
+                target_exec_id,
+                share_type,
+                data,
+                ..
+            } => {
+                assert_eq!(target_exec_id, id);
+                assert_eq!(share_type, NUDGE_SHARE_TYPE);
+                assert_eq!(data["message"], "check the logs first");
+                assert_eq!(data["from"], "U1");
+            }
+            other => panic!("unexpected request: {:?}", other),
+        }
+
+        // Discord relay: signed JSON in, `content` out
+        let body = serde_json::to_vec(&serde_json::json!({ "user": "U1", "text": format!("pause {}", id) })).unwrap();
+        let mut request = HttpRequest {
+            method: "POST".to_string(),
+            path: "/chat".to_string(),
+            headers: HashMap::new(),
+            body: body.clone(),
+        };
+        assert_eq!(bridge.handle(&request).await.status, 401);
+        request
+            .headers
+            .insert(SIGNATURE_HEADER.to_string(), webhook::sign("s3cret", &body));
+        let response = bridge.handle(&request).await;
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body["content"], format!("Paused {}", id));
+        let exec = state.get_execution(&id).await.unwrap().unwrap();
+        assert_eq!(exec.status, LoopExecutionStatus::Paused);
+    }
+}
@@ -0,0 +1,600 @@
+//! CI mode for `td run --ci`
+//!
+//! In CI there is nobody to read prose or watch a TUI, so a CI run prints its
+//! progress as JSON lines on stdout (the same tagged [`Event`]s the event log
+//! stores, minus streaming tokens and raw validation output), ends with a
+//! `RunFinished` line, and can write a summary of its validation runs as JUnit
+//! XML or SARIF for the pipeline to display. The exit code says how the loop
+//! ended (see [`CiOutcome::exit_code`]) so a job can gate on it.
+
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::json;
+use tracing::debug;
+
+use crate::events::Event;
+use crate::r#loop::{IterationResult, TestReport};
+
+/// Lines of stdout/stderr kept per validation run (the tail)
+const MAX_OUTPUT_LINES: usize = 1000;
+
+/// Lines of output shown in a failure message
+const FAILURE_OUTPUT_LINES: usize = 50;
+
+/// SARIF schema the report conforms to
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Format of the `--report` file
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CiReportFormat {
+    #[default]
+    Junit,
+    Sarif,
+}
+
+impl CiReportFormat {
+    /// Format implied by a report path: `.sarif` / `.json` is SARIF, anything else JUnit
+    pub fn from_path(path: &Path) -> Self {
+        debug!(?path, "CiReportFormat::from_path: called");
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("sarif") || ext.eq_ignore_ascii_case("json") => Self::Sarif,
+            _ => Self::Junit,
+        }
+    }
+}
+
+impl std::str::FromStr for CiReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        debug!(%s, "CiReportFormat::from_str: called");
+        match s.to_lowercase().as_str() {
+            "junit" | "xml" => Ok(Self::Junit),
+            "sarif" => Ok(Self::Sarif),
+            _ => Err(format!("Unknown report format: {}. Use: junit or sarif", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for CiReportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Junit => write!(f, "junit"),
+            Self::Sarif => write!(f, "sarif"),
+        }
+    }
+}
+
+/// How a CI run ended
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CiOutcome {
+    /// Validation passed
+    Complete,
+    /// The loop errored, ran out of iterations, or a hook blocked it
+    Failed,
+    /// The loop stopped making progress
+    Stuck,
+    /// The loop stopped for another reason (rate limits)
+    Incomplete,
+    /// Stopped by a signal or user request
+    Interrupted,
+}
+
+impl CiOutcome {
+    /// Outcome of an engine run
+    pub fn from_result(result: &IterationResult) -> Self {
+        match result {
+            IterationResult::Complete { .. } => Self::Complete,
+            IterationResult::Error { .. } | IterationResult::Blocked { .. } => Self::Failed,
+            IterationResult::Stuck { .. } => Self::Stuck,
+            IterationResult::Interrupted { .. } => Self::Interrupted,
+            IterationResult::Continue { .. }
+            | IterationResult::RateLimited { .. }
+            | IterationResult::ProviderUnavailable { .. } => Self::Incomplete,
+        }
+    }
+
+    /// Process exit code: 0 complete, 1 failed, 2 stuck, 3 incomplete, 130 interrupted
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Self::Complete => 0,
+            Self::Failed => 1,
+            Self::Stuck => 2,
+            Self::Incomplete => 3,
+            Self::Interrupted => 130,
+        }
+    }
+}
+
+impl std::fmt::Display for CiOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Complete => write!(f, "complete"),
+            Self::Failed => write!(f, "failed"),
+            Self::Stuck => write!(f, "stuck"),
+            Self::Incomplete => write!(f, "incomplete"),
+            Self::Interrupted => write!(f, "interrupted"),
+        }
+    }
+}
+
+/// One validation command run
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationRun {
+    pub iteration: u32,
+    pub command: String,
+    /// None if the run never completed
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+    /// Tail of stdout, one entry per line
+    pub stdout: Vec<String>,
+    /// Tail of stderr, one entry per line
+    pub stderr: Vec<String>,
+}
+
+impl ValidationRun {
+    /// Whether the command exited with `success_exit_code`
+    pub fn passed(&self, success_exit_code: i32) -> bool {
+        self.exit_code == Some(success_exit_code)
+    }
+
+    /// Last lines of output for a failure message (stderr if there is any)
+    fn output_tail(&self) -> String {
+        let lines = if self.stderr.iter().any(|l| !l.trim().is_empty()) {
+            &self.stderr
+        } else {
+            &self.stdout
+        };
+        let start = lines.len().saturating_sub(FAILURE_OUTPUT_LINES);
+        lines[start..].join("\n")
+    }
+}
+
+/// Collects validation runs from the event stream
+#[derive(Debug, Default)]
+pub struct CiCollector {
+    runs: Vec<ValidationRun>,
+}
+
+impl CiCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take in one event
+    pub fn record(&mut self, event: &Event) {
+        match event {
+            Event::ValidationStarted { iteration, command, .. } => {
+                self.runs.push(ValidationRun {
+                    iteration: *iteration,
+                    command: command.clone(),
+                    ..Default::default()
+                });
+            }
+            Event::ValidationOutput {
+                iteration,
+                line,
+                is_stderr,
+                ..
+            } => {
+                if let Some(run) = self.current(*iteration) {
+                    let lines = if *is_stderr { &mut run.stderr } else { &mut run.stdout };
+                    if lines.len() == MAX_OUTPUT_LINES {
+                        lines.remove(0);
+                    }
+                    lines.push(line.clone());
+                }
+            }
+            Event::ValidationCompleted {
+                iteration,
+                exit_code,
+                duration_ms,
+                ..
+            } => {
+                if let Some(run) = self.current(*iteration) {
+                    run.exit_code = Some(*exit_code);
+                    run.duration_ms = *duration_ms;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The open run of an iteration
+    fn current(&mut self, iteration: u32) -> Option<&mut ValidationRun> {
+        self.runs
+            .last_mut()
+            .filter(|run| run.iteration == iteration && run.exit_code.is_none())
+    }
+
+    /// The runs seen, in order
+    pub fn into_runs(self) -> Vec<ValidationRun> {
+        self.runs
+    }
+}
+
+/// Whether an event goes into the JSON progress stream
+///
+/// Streaming tokens and validation output lines are left out; the output is
+/// summarized in the report instead.
+pub fn is_progress_event(event: &Event) -> bool {
+    !matches!(event, Event::TokenReceived { .. } | Event::ValidationOutput { .. })
+}
+
+/// Everything the CI report is built from
+#[derive(Debug, Clone)]
+pub struct CiSummary {
+    pub exec_id: String,
+    pub loop_type: String,
+    pub outcome: CiOutcome,
+    /// Why the loop didn't complete
+    pub message: Option<String>,
+    /// Code of the error that failed the loop (see [`crate::error`])
+    pub error_code: Option<String>,
+    pub iterations: u32,
+    pub success_exit_code: i32,
+    pub runs: Vec<ValidationRun>,
+}
+
+impl CiSummary {
+    /// Test results of the last completed validation run, if its output was recognized
+    pub fn final_tests(&self) -> Option<TestReport> {
+        let run = self.runs.iter().rev().find(|r| r.exit_code.is_some())?;
+        TestReport::parse(&run.stdout.join("\n"), &run.stderr.join("\n"))
+    }
+
+    /// The final `RunFinished` progress line
+    pub fn finished_line(&self) -> serde_json::Value {
+        json!({
+            "type": "RunFinished",
+            "execution_id": self.exec_id,
+            "loop_type": self.loop_type,
+            "outcome": self.outcome,
+            "message": self.message,
+            "error_code": self.error_code,
+            "iterations": self.iterations,
+            "validation_runs": self.runs.len(),
+            "exit_code": self.outcome.exit_code(),
+        })
+    }
+
+    /// Render the report in `format`
+    pub fn render(&self, format: CiReportFormat) -> String {
+        debug!(%format, "CiSummary::render: called");
+        match format {
+            CiReportFormat::Junit => self.render_junit(),
+            CiReportFormat::Sarif => {
+                serde_json::to_string_pretty(&self.render_sarif()).expect("SARIF is always serializable")
+            }
+        }
+    }
+
+    /// JUnit XML: one suite of validation runs and the loop outcome, and one
+    /// suite of the individual tests from the last run
+    pub fn render_junit(&self) -> String {
+        debug!(exec_id = %self.exec_id, runs = self.runs.len(), "CiSummary::render_junit: called");
+        let mut suites = Vec::new();
+
+        // Validation runs, plus a final case for the loop itself
+        let mut cases = Vec::new();
+        for run in &self.runs {
+            let name = format!("iteration {}: {}", run.iteration, run.command);
+            let failure = match run.exit_code {
+                Some(_) if run.passed(self.success_exit_code) => None,
+                Some(code) => Some((format!("exit code {}", code), run.output_tail())),
+                None => Some(("did not complete".to_string(), run.output_tail())),
+            };
+            cases.push(junit_case("validation", &name, run.duration_ms, failure));
+        }
+        let loop_failure = (self.outcome != CiOutcome::Complete).then(|| {
+            (
+                format!("loop {}", self.outcome),
+                self.message.clone().unwrap_or_default(),
+            )
+        });
+        let total_ms = self.runs.iter().map(|r| r.duration_ms).sum();
+        cases.push(junit_case("loop", &self.loop_type, total_ms, loop_failure));
+        suites.push(junit_suite(&format!("{} {}", self.loop_type, self.exec_id), &cases));
+
+        // Individual tests of the final run, when the runner's output was recognized
+        if let Some(report) = self.final_tests() {
+            let mut cases: Vec<(bool, String)> = report
+                .passed
+                .iter()
+                .map(|name| junit_case(&report.framework.to_string(), name, 0, None))
+                .collect();
+            cases.extend(report.failed.iter().map(|test| {
+                junit_case(
+                    &report.framework.to_string(),
+                    &test.name,
+                    0,
+                    Some(("test failed".to_string(), test.message.clone())),
+                )
+            }));
+            suites.push(junit_suite(&format!("{} tests", self.exec_id), &cases));
+        }
+
+        let tests: usize = suites.iter().map(|(tests, _, _)| tests).sum();
+        let failures: usize = suites.iter().map(|(_, failures, _)| failures).sum();
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str(&format!(
+            "<testsuites name=\"taskdaemon\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">\n",
+            tests,
+            failures,
+            total_ms as f64 / 1000.0
+        ));
+        for (_, _, suite) in suites {
+            xml.push_str(&suite);
+        }
+        xml.push_str("</testsuites>\n");
+        xml
+    }
+
+    /// SARIF 2.1.0: one result per failing test of the last run, the last
+    /// failing validation run if its output wasn't recognized, and the loop
+    /// outcome if it didn't complete
+    pub fn render_sarif(&self) -> serde_json::Value {
+        debug!(exec_id = %self.exec_id, "CiSummary::render_sarif: called");
+        let mut results = Vec::new();
+
+        let last = self.runs.iter().rev().find(|r| r.exit_code.is_some());
+        let failed_tests = self.final_tests().map(|report| report.failed).unwrap_or_default();
+        for test in &failed_tests {
+            results.push(sarif_result(
+                "test-failed",
+                "error",
+                &format!("{} failed\n{}", test.name, test.message),
+            ));
+        }
+        if let Some(run) = last
+            && !run.passed(self.success_exit_code)
+            && failed_tests.is_empty()
+        {
+            results.push(sarif_result(
+                "validation-failed",
+                "error",
+                &format!(
+                    "`{}` exited with {} in iteration {}\n{}",
+                    run.command,
+                    run.exit_code.unwrap_or_default(),
+                    run.iteration,
+                    run.output_tail()
+                ),
+            ));
+        }
+        if self.outcome != CiOutcome::Complete {
+            results.push(sarif_result(
+                "loop-incomplete",
+                if self.outcome == CiOutcome::Interrupted { "warning" } else { "error" },
+                &format!(
+                    "{} loop {} {} after {} iterations{}",
+                    self.loop_type,
+                    self.exec_id,
+                    self.outcome,
+                    self.iterations,
+                    self.message.as_deref().map(|m| format!(": {}", m)).unwrap_or_default()
+                ),
+            ));
+        }
+
+        json!({
+            "$schema": SARIF_SCHEMA,
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "taskdaemon",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": [
+                            sarif_rule("test-failed", "A test failed in the final validation run"),
+                            sarif_rule("validation-failed", "The final validation command failed"),
+                            sarif_rule("loop-incomplete", "The loop ended without passing validation"),
+                        ],
+                    }
+                },
+                "invocations": [{
+                    "executionSuccessful": self.outcome == CiOutcome::Complete,
+                    "exitCode": self.outcome.exit_code(),
+                }],
+                "properties": {
+                    "executionId": self.exec_id,
+                    "loopType": self.loop_type,
+                    "iterations": self.iterations,
+                    "validationRuns": self.runs.len(),
+                },
+                "results": results,
+            }]
+        })
+    }
+}
+
+/// A `<testcase>`, with an optional (message, details) failure
+fn junit_case(classname: &str, name: &str, duration_ms: u64, failure: Option<(String, String)>) -> (bool, String) {
+    let open = format!(
+        "    <testcase classname=\"{}\" name=\"{}\" time=\"{:.3}\"",
+        escape_xml(classname),
+        escape_xml(name),
+        duration_ms as f64 / 1000.0
+    );
+    match failure {
+        None => (false, format!("{} />\n", open)),
+        Some((message, details)) => (
+            true,
+            format!(
+                "{}>\n      <failure message=\"{}\">{}</failure>\n    </testcase>\n",
+                open,
+                escape_xml(&message),
+                escape_xml(&details)
+            ),
+        ),
+    }
+}
+
+/// A `<testsuite>` of cases: (tests, failures, xml)
+fn junit_suite(name: &str, cases: &[(bool, String)]) -> (usize, usize, String) {
+    let failures = cases.iter().filter(|(failed, _)| *failed).count();
+    let mut xml = format!(
+        "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">\n",
+        escape_xml(name),
+        cases.len(),
+        failures
+    );
+    for (_, case) in cases {
+        xml.push_str(case);
+    }
+    xml.push_str("  </testsuite>\n");
+    (cases.len(), failures, xml)
+}
+
+fn sarif_rule(id: &str, description: &str) -> serde_json::Value {
+    json!({ "id": id, "shortDescription": { "text": description } })
+}
+
+fn sarif_result(rule_id: &str, level: &str, message: &str) -> serde_json::Value {
+    json!({ "ruleId": rule_id, "level": level, "message": { "text": message } })
+}
+
+/// Escape text for XML attributes and content, dropping characters XML 1.0 can't carry
+fn escape_xml(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if (c as u32) < 0x20 => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(iteration: u32, lines: &[&str], exit_code: i32) -> Vec<Event> {
+        let id = "exec-1".to_string();
+        let mut events = vec![Event::ValidationStarted {
+            execution_id: id.clone(),
+            iteration,
+            command: "cargo test".to_string(),
+        }];
+        events.extend(lines.iter().map(|line| Event::ValidationOutput {
+            execution_id: id.clone(),
+            iteration,
+            line: line.to_string(),
+            is_stderr: false,
+        }));
+        events.push(Event::ValidationCompleted {
+            execution_id: id,
+            iteration,
+            exit_code,
+            duration_ms: 1500,
+        });
+        events
+    }
+
+    fn summary(outcome: CiOutcome, runs: Vec<ValidationRun>) -> CiSummary {
+        CiSummary {
+            exec_id: "exec-1".to_string(),
+            loop_type: "ralph".to_string(),
+            outcome,
+            message: (outcome != CiOutcome::Complete).then(|| "Max iterations (2) exceeded".to_string()),
+            error_code: None,
+            iterations: 2,
+            success_exit_code: 0,
+            runs,
+        }
+    }
+
+    const CARGO_FAILURE: [&str; 9] = [
+        "running 2 tests",
+        "test tests::ok ... ok",
+        "test tests::bad ... FAILED",
+        "",
+        "failures:",
+        "",
+        "---- tests::bad stdout ----",
+        "assertion failed: 1 < 0",
+        "test result: FAILED. 1 passed; 1 failed; 0 ignored",
+    ];
+
+    fn collect(events: impl IntoIterator<Item = Event>) -> Vec<ValidationRun> {
+        let mut collector = CiCollector::new();
+        for event in events {
+            collector.record(&event);
+        }
+        collector.into_runs()
+    }
+
+    #[test]
+    fn test_collector_and_outcomes() {
+        let runs = collect(events(1, &CARGO_FAILURE, 101).into_iter().chain(events(2, &["ok"], 0)));
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].exit_code, Some(101));
+        assert_eq!(runs[0].stdout.len(), CARGO_FAILURE.len());
+        assert!(runs[1].passed(0));
+
+        assert_eq!(
+            CiOutcome::from_result(&IterationResult::Complete { iterations: 2 }).exit_code(),
+            0
+        );
+        assert_eq!(
+            CiOutcome::from_result(&IterationResult::Stuck {
+                action: crate::r#loop::StuckAction::Pause,
+                unchanged_iterations: 3
+            })
+            .exit_code(),
+            2
+        );
+        assert_eq!(
+            CiReportFormat::from_path(Path::new("out/results.sarif")),
+            CiReportFormat::Sarif
+        );
+        assert_eq!(CiReportFormat::from_path(Path::new("junit.xml")), CiReportFormat::Junit);
+    }
+
+    #[test]
+    fn test_render_junit() {
+        let runs = collect(events(1, &CARGO_FAILURE, 101));
+        let xml = summary(CiOutcome::Failed, runs).render_junit();
+        assert!(xml.starts_with("<?xml"));
+        // Validation run + loop + 2 individual tests; the run, the loop and one test failed
+        assert!(xml.contains("<testsuites name=\"taskdaemon\" tests=\"4\" failures=\"3\""));
+        assert!(xml.contains("name=\"iteration 1: cargo test\" time=\"1.500\""));
+        assert!(xml.contains("<failure message=\"exit code 101\">"));
+        assert!(xml.contains("<failure message=\"loop failed\">Max iterations (2) exceeded</failure>"));
+        assert!(xml.contains("assertion failed: 1 &lt; 0"));
+        assert!(xml.contains("name=\"tests::ok\" time=\"0.000\" />"));
+    }
+
+    #[test]
+    fn test_render_sarif() {
+        let runs = collect(
+            events(1, &CARGO_FAILURE, 101)
+                .into_iter()
+                .chain(events(2, &["fine"], 0)),
+        );
+        let passing = summary(CiOutcome::Complete, runs).render_sarif();
+        assert_eq!(passing["version"], "2.1.0");
+        assert_eq!(passing["runs"][0]["results"].as_array().unwrap().len(), 0);
+        assert_eq!(passing["runs"][0]["invocations"][0]["exitCode"], 0);
+
+        let runs = collect(events(1, &CARGO_FAILURE, 101));
+        let failing = summary(CiOutcome::Failed, runs).render_sarif();
+        let results = failing["runs"][0]["results"].as_array().unwrap();
+        let rules: Vec<&str> = results.iter().map(|r| r["ruleId"].as_str().unwrap()).collect();
+        assert_eq!(rules, vec!["test-failed", "loop-incomplete"]);
+        assert!(
+            results[0]["message"]["text"]
+                .as_str()
+                .unwrap()
+                .starts_with("tests::bad failed")
+        );
+    }
+}
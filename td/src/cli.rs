@@ -1,9 +1,22 @@
 //! CLI command definitions and subcommands
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use std::path::PathBuf;
 use tracing::debug;
 
+use crate::ask::DEFAULT_LIMIT as DEFAULT_ASK_LIMIT;
+use crate::bulk::ExecFilter;
+use crate::ci::CiReportFormat;
+use crate::completions::Shell;
+use crate::domain::{RunOverride, ValidationOutcome};
+use crate::init::ProjectLanguage;
+use crate::loadtest::{DEFAULT_LOOPS, LoadProfile};
+use crate::r#loop::{PromptIteration, parse_history_days};
+use crate::report::ReportFormat;
+use crate::run_many::DEFAULT_MAX_PARALLEL;
+use crate::timeline::TimelineFormat;
+use crate::tools::Thoroughness;
+
 /// TaskDaemon - Ralph Wiggum Loop Orchestrator
 #[derive(Parser)]
 #[command(
@@ -25,6 +38,10 @@ pub struct Cli {
     )]
     pub log_level: Option<String>,
 
+    /// Override a config key for this invocation (repeatable), e.g. --set concurrency.max-loops=4
+    #[arg(long = "set", global = true, value_name = "KEY=VALUE")]
+    pub set: Vec<String>,
+
     /// Subcommand to execute
     #[command(subcommand)]
     pub command: Option<Command>,
@@ -51,21 +68,142 @@ pub enum Command {
         /// Maximum iterations
         #[arg(short, long)]
         max_iterations: Option<u32>,
+
+        /// CI mode: JSON-lines progress on stdout and an exit code per outcome
+        /// (0 complete, 1 failed, 2 stuck, 3 incomplete, 130 interrupted)
+        #[arg(long)]
+        ci: bool,
+
+        /// Write a summary of the validation runs to this file (CI mode)
+        #[arg(long, value_name = "FILE", requires = "ci")]
+        report: Option<PathBuf>,
+
+        /// Report format: junit or sarif (default: from the file extension)
+        #[arg(long, value_name = "FORMAT", requires = "report")]
+        report_format: Option<CiReportFormat>,
+    },
+
+    /// Explore the codebase read-only to answer a question (no worktree, no daemon)
+    ///
+    /// Findings stream as the exploration runs; the answer is saved as a
+    /// Markdown artifact and, when a TaskStore exists, recorded as an execution
+    /// so it shows up in the TUI.
+    Explore {
+        /// Question to investigate
+        question: String,
+
+        /// How thorough to be: quick (low), medium, thorough (high)
+        #[arg(short, long, default_value = "medium")]
+        thoroughness: Thoroughness,
+
+        /// Where to save the answer (default: .taskdaemon/explore/<id>.md)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+
+        /// Print the answer without saving or recording it
+        #[arg(long, conflicts_with = "output")]
+        no_save: bool,
+    },
+
+    /// Answer a question about past executions, citing execution IDs
+    ///
+    /// Picks the executions most relevant to the question and gives the LLM
+    /// their records, plans, failure events and validation output.
+    Ask {
+        /// Question, e.g. "why did the payments loop fail last week?"
+        question: String,
+
+        /// Only consider executions updated since (RFC 3339, YYYY-MM-DD, or 30m/12h/7d);
+        /// by default taken from the question ("yesterday", "last week")
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Maximum executions given to the LLM
+        #[arg(short = 'n', long, default_value_t = DEFAULT_ASK_LIMIT)]
+        limit: usize,
+    },
+
+    /// Run every task of a batch manifest in parallel, in the foreground (no daemon)
+    RunMany {
+        /// Manifest listing the tasks (same format as `exec submit`)
+        #[arg(short = 'f', long, value_name = "FILE")]
+        manifest: PathBuf,
+
+        /// Maximum loops running at once
+        #[arg(short = 'j', long, default_value_t = DEFAULT_MAX_PARALLEL)]
+        max_parallel: usize,
+
+        /// Maximum iterations per loop (overrides the loop types)
+        #[arg(short, long)]
+        max_iterations: Option<u32>,
+    },
+
+    /// Load test the orchestration against a simulated provider (latency and 429 injection)
+    Loadtest {
+        /// Number of loops to run
+        #[arg(short = 'n', long, default_value_t = DEFAULT_LOOPS)]
+        loops: usize,
+
+        /// Simulated provider: steady, flaky, or overloaded
+        #[arg(short, long, default_value = "steady")]
+        profile: LoadProfile,
+
+        /// Maximum concurrent API calls (scheduler limit)
+        #[arg(short = 'j', long)]
+        max_concurrent: Option<usize>,
+
+        /// Maximum API calls per minute (scheduler limit)
+        #[arg(long)]
+        requests_per_minute: Option<u32>,
+
+        /// Seed for the simulated latency and rate limits
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+
+        /// Output format
+        #[arg(short, long, default_value = "text")]
+        format: OutputFormat,
     },
 
     /// Internal: Run as daemon process (used by `daemon start`)
     #[command(hide = true)]
     RunDaemon,
 
-    /// List available loop types
-    Loops,
+    /// Internal: Serve tool calls over stdin/stdout (used with `tools.worker`)
+    #[command(hide = true)]
+    ToolWorker {
+        /// Serve the read-only tool profile
+        #[arg(long)]
+        read_only: bool,
+
+        /// Address space cap in MB for the worker and the commands it runs
+        #[arg(long)]
+        memory_mb: Option<u64>,
+    },
+
+    /// List available loop types, or install loop type packages from git
+    Loops {
+        #[command(subcommand)]
+        command: Option<LoopsCommand>,
+    },
 
     /// Show metrics and statistics
     Metrics {
         /// Loop type to filter by
-        #[arg(short = 't', long)]
+        #[arg(short = 't', long, alias = "type")]
         loop_type: Option<String>,
 
+        /// Show daily trends (success rate, mean iterations, cost) over a window, e.g. 30d or 4w
+        #[arg(long, value_parser = parse_history_days)]
+        history: Option<u32>,
+
+        /// Output format
+        #[arg(short, long, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// Show an overview of all executions: per-type outcomes, recent failures, queue, rate limits
+    Summary {
         /// Output format
         #[arg(short, long, default_value = "text")]
         format: OutputFormat,
@@ -78,7 +216,7 @@ pub enum Command {
         follow: bool,
 
         /// Number of lines to show
-        #[arg(short, long, default_value = "50")]
+        #[arg(short = 'n', long, default_value = "50")]
         lines: usize,
     },
 
@@ -87,6 +225,223 @@ pub enum Command {
         #[command(subcommand)]
         command: ExecCommand,
     },
+
+    /// Inspect the LLM request/response audit log (llm.audit in config)
+    Audit {
+        #[command(subcommand)]
+        command: AuditCommand,
+    },
+
+    /// Inspect and clean up loop worktrees (git.worktree-dir)
+    Worktree {
+        #[command(subcommand)]
+        command: WorktreeCommand,
+    },
+
+    /// Inspect the configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+
+    /// Manage API keys in the secrets store (llm.secrets.backend)
+    Secrets {
+        #[command(subcommand)]
+        command: SecretsCommand,
+    },
+
+    /// Set up taskdaemon in the current project
+    ///
+    /// Creates .taskdaemon.yml, example loop types in .taskdaemon/loops/, prompt
+    /// templates in .taskdaemon/prompts/ and .gitignore entries. The validation
+    /// command is pre-filled from the detected language (cargo test, npm test,
+    /// pytest). Asks for each setting when run in a terminal.
+    Init {
+        /// Project language: rust, node, python, other (default: detected)
+        #[arg(long)]
+        language: Option<ProjectLanguage>,
+
+        /// Validation command (default: from the language)
+        #[arg(long)]
+        validation_command: Option<String>,
+
+        /// Default model as provider/model (default: from the user config)
+        #[arg(long)]
+        model: Option<String>,
+
+        /// Don't ask, use the flags and detected defaults
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Overwrite files that already exist
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Check the environment: git, API keys, daemon, TaskStore, loop types, worktrees, disk
+    ///
+    /// Prints a fix for every problem found. Exits with status 1 if any check
+    /// failed, so it can gate CI jobs.
+    Doctor {
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Check execution records against the event log
+    ///
+    /// Replays each execution's persisted events and reports where the
+    /// TaskStore disagrees: an end or iteration that never got recorded, a run
+    /// interrupted by a crash, the log of a deleted execution. Exits with
+    /// status 1 if anything disagrees and was not repaired.
+    Fsck {
+        /// Update the records to match the event log (stop the daemon first)
+        #[arg(long)]
+        repair: bool,
+
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Print a shell completion script (bash, zsh, fish)
+    ///
+    /// For example `td completions bash > ~/.local/share/bash-completion/completions/td`
+    /// or `td completions fish > ~/.config/fish/completions/td.fish`. Execution
+    /// IDs are completed from the TaskStore.
+    Completions {
+        /// Shell to generate the script for (bash, zsh, fish)
+        shell: Shell,
+    },
+}
+
+/// Loop type package subcommands
+#[derive(Debug, Subcommand)]
+pub enum LoopsCommand {
+    /// Install a loop type package (YAML, prompts, scripts) from a git repository
+    ///
+    /// SOURCE is `<repo>//<dir>`, e.g. github.com/org/td-loops//security-review.
+    /// Every file is checked against the package's package.yml manifest.
+    Install {
+        /// Repository and package directory
+        source: String,
+
+        /// Branch or tag to install from (default: the repository's default branch)
+        #[arg(long = "ref", value_name = "REF")]
+        git_ref: Option<String>,
+
+        /// Expected sha256 of the package's package.yml
+        #[arg(long)]
+        checksum: Option<String>,
+
+        /// Install into ~/.config/taskdaemon/loops instead of .taskdaemon/loops
+        #[arg(short, long)]
+        global: bool,
+    },
+
+    /// Re-fetch installed packages from their source (all of them without a name)
+    Update {
+        /// Package name
+        name: Option<String>,
+
+        /// Update packages in ~/.config/taskdaemon/loops
+        #[arg(short, long)]
+        global: bool,
+    },
+
+    /// Remove an installed package
+    Remove {
+        /// Package name
+        name: String,
+
+        /// Remove from ~/.config/taskdaemon/loops
+        #[arg(short, long)]
+        global: bool,
+    },
+
+    /// List installed packages with their source and version
+    Packages {
+        /// List packages in ~/.config/taskdaemon/loops
+        #[arg(short, long)]
+        global: bool,
+    },
+}
+
+impl LoopsCommand {
+    /// Whether the command targets the user-wide loops directory
+    pub fn global(&self) -> bool {
+        match self {
+            Self::Install { global, .. }
+            | Self::Update { global, .. }
+            | Self::Remove { global, .. }
+            | Self::Packages { global } => *global,
+        }
+    }
+}
+
+/// Worktree subcommands
+#[derive(Debug, Subcommand)]
+pub enum WorktreeCommand {
+    /// List worktrees with their disk usage and execution status
+    List,
+
+    /// Remove worktrees of finished executions per git.prune-after-days and git.disk-quota-gb
+    Prune {
+        /// Remove finished worktrees older than this many days (overrides git.prune-after-days)
+        #[arg(long)]
+        older_than: Option<u32>,
+
+        /// Show what would be removed without removing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+/// Config subcommands
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Show the config layers in effect and the merged config
+    Show {
+        /// Print every key with its effective value and the layer that set it
+        #[arg(long)]
+        resolved: bool,
+    },
+}
+
+/// Secrets subcommands
+#[derive(Debug, Subcommand)]
+pub enum SecretsCommand {
+    /// Store a secret, read from stdin or a prompt that doesn't echo
+    ///
+    /// Reference it from a provider with `api-key-secret: <name>`.
+    Set {
+        /// Secret name, e.g. anthropic-prod
+        name: String,
+    },
+
+    /// List secret names (values are never printed)
+    List,
+
+    /// Delete a secret
+    Rm {
+        /// Secret name
+        name: String,
+    },
+}
+
+/// Audit log subcommands
+#[derive(Debug, Subcommand)]
+pub enum AuditCommand {
+    /// Write audit records as JSON lines, oldest first
+    Export {
+        /// Earliest record to include: RFC 3339, YYYY-MM-DD, or an age like 30m, 12h, 7d
+        #[arg(long)]
+        since: String,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
 }
 
 /// Execution management subcommands
@@ -94,9 +449,21 @@ pub enum Command {
 pub enum ExecCommand {
     /// List all executions
     List {
-        /// Filter by status (draft, pending, running, paused, complete, failed)
+        /// Filter by status (draft, pending, running, paused, parked, waiting_provider, complete, failed)
         #[arg(short, long)]
         status: Option<String>,
+
+        /// Only executions carrying this tag (repeat to require several)
+        #[arg(short, long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+
+        /// Only executions submitted by this user (login or display name)
+        #[arg(short, long, conflicts_with = "mine")]
+        user: Option<String>,
+
+        /// Only executions you submitted
+        #[arg(long)]
+        mine: bool,
     },
 
     /// Start a draft execution (draft -> pending)
@@ -108,13 +475,182 @@ pub enum ExecCommand {
     /// Pause a running execution (running -> paused)
     Pause {
         /// Execution ID (or partial match)
-        id: String,
+        #[arg(required_unless_present = "filter")]
+        id: Option<String>,
+
+        #[command(flatten)]
+        bulk: BulkArgs,
     },
 
     /// Resume a paused execution (paused -> running)
     Resume {
+        /// Execution ID (or partial match)
+        #[arg(required_unless_present = "filter")]
+        id: Option<String>,
+
+        #[command(flatten)]
+        bulk: BulkArgs,
+    },
+
+    /// Cancel an execution that hasn't finished (-> stopped)
+    Cancel {
+        /// Execution ID (or partial match)
+        #[arg(required_unless_present = "filter")]
+        id: Option<String>,
+
+        #[command(flatten)]
+        bulk: BulkArgs,
+    },
+
+    /// Delete an execution and its iteration logs
+    Delete {
+        /// Execution ID (or partial match)
+        #[arg(required_unless_present = "filter")]
+        id: Option<String>,
+
+        #[command(flatten)]
+        bulk: BulkArgs,
+    },
+
+    /// Park an execution until a wake condition holds (-> parked, then pending)
+    ///
+    /// The daemon checks the conditions periodically and wakes the execution
+    /// as soon as any one of them holds.
+    Park {
         /// Execution ID (or partial match)
         id: String,
+
+        /// Wake when a file matching this glob exists (relative to the worktree)
+        #[arg(long = "wake-on-file", value_name = "GLOB")]
+        files: Vec<String>,
+
+        /// Wake when this git ref moves from its current commit (e.g. origin/main)
+        #[arg(long = "wake-on-ref", value_name = "REF")]
+        refs: Vec<String>,
+
+        /// Wake when a GET of this URL returns 200
+        #[arg(long = "wake-on-http", value_name = "URL")]
+        urls: Vec<String>,
+    },
+
+    /// Wake a parked execution now (parked -> pending)
+    Wake {
+        /// Execution ID (or partial match)
+        id: String,
+    },
+
+    /// Approve merging an execution held by its risk score (blocked -> running)
+    Approve {
+        /// Execution ID (or partial match)
+        id: String,
+    },
+
+    /// Reset an execution's worktree to the snapshot after an iteration and continue from there
+    ///
+    /// The execution must not be running (pause it first). Files ignored by
+    /// git, such as build output, are kept.
+    Rollback {
+        /// Execution ID (or partial match)
+        id: String,
+
+        /// Iteration whose snapshot to restore
+        #[arg(long)]
+        to_iteration: u32,
+    },
+
+    /// Run an execution again as a new execution with a fresh worktree
+    ///
+    /// The clone keeps the loop type, task context, tags and acceptance
+    /// criteria, and is linked to the original so `td metrics` can compare them.
+    Rerun {
+        /// Execution ID (or partial match)
+        id: String,
+
+        /// Edit a copy of the execution's plan.md in $EDITOR before queueing the re-run
+        #[arg(long)]
+        edit_plan: bool,
+
+        /// Model to use instead of llm.default, as provider/model
+        #[arg(long)]
+        model: Option<String>,
+
+        /// Iteration cap to use instead of the loop type's max-iterations
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+        max_iterations: Option<u32>,
+    },
+
+    /// Compare two executions side by side: iterations, cost, validation history and changes
+    ///
+    /// Meant for two runs of the same task, e.g. an execution and its re-run.
+    /// The changes are compared as a diff of the two executions' diffs.
+    Diff {
+        /// First execution ID (or partial match)
+        left: String,
+
+        /// Second execution ID (or partial match)
+        right: String,
+    },
+
+    /// Change a pending or running execution's model or iteration cap
+    ///
+    /// A running loop applies the change at its next iteration boundary and
+    /// logs it as an event. A value of `default` clears the override.
+    Set {
+        /// Execution ID (or partial match)
+        id: String,
+
+        /// Settings as key=value: model=provider/model, max_iterations=N
+        #[arg(required = true, value_name = "KEY=VALUE")]
+        settings: Vec<RunOverride>,
+    },
+
+    /// Cherry-pick commits from an execution's branch onto a new branch
+    ///
+    /// Useful for keeping the good parts of a failed loop. Run from the
+    /// repository root; the pick is recorded in the execution.
+    CherryPick {
+        /// Execution ID (or partial match)
+        id: String,
+
+        /// Commits and ranges to pick, comma-separated (e.g. "abc123" or "abc123..def456"; default: all)
+        #[arg(long)]
+        commits: Option<String>,
+
+        /// Branch to create (default: picked/<execution-id>)
+        #[arg(long)]
+        branch: Option<String>,
+
+        /// Branch to start the new branch from
+        #[arg(long, default_value = "main")]
+        base: String,
+    },
+
+    /// Add tags to an execution (or remove them with --remove)
+    Tag {
+        /// Execution ID (or partial match)
+        id: String,
+
+        /// Tags to add (e.g. backend urgent)
+        #[arg(required = true)]
+        tags: Vec<String>,
+
+        /// Remove the tags instead of adding them
+        #[arg(short, long)]
+        remove: bool,
+    },
+
+    /// Submit a batch of executions from a YAML manifest
+    ///
+    /// Each entry lists a loop-type, task, priority and depends-on (entry names
+    /// or existing execution IDs). The manifest is validated before anything
+    /// is created.
+    Submit {
+        /// Path to the manifest file
+        manifest: PathBuf,
+
+        /// Stream status changes until every submitted execution finishes
+        #[arg(short, long)]
+        watch: bool,
     },
 
     /// Set execution status directly (for testing)
@@ -125,6 +661,140 @@ pub enum ExecCommand {
         /// New status (draft, pending, running, paused, complete, failed)
         status: String,
     },
+
+    /// Export a shareable report (plan, timeline, tool calls, validations, diff, cost)
+    Report {
+        /// Execution ID (or partial match)
+        id: String,
+
+        /// Report format (md, html)
+        #[arg(short, long, default_value = "md")]
+        format: ReportFormat,
+
+        /// Write the report to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Show an execution's tool output, LLM text and validation lines
+    ///
+    /// Prints what the event log holds; with --follow, then streams the live
+    /// events from the daemon until the execution terminates (or Ctrl+C).
+    Logs {
+        /// Execution ID (or partial match)
+        id: String,
+
+        /// Keep streaming new output from the daemon
+        #[arg(short, long)]
+        follow: bool,
+    },
+
+    /// Watch one execution live in a compact single-screen view
+    ///
+    /// Shows the status, iteration, latest validation run and the tail of the
+    /// output, and exits when the execution terminates (or on q, Esc or Ctrl+C).
+    Watch {
+        /// Execution ID (or partial match)
+        id: String,
+    },
+
+    /// Export a timeline of iterations, LLM calls, tool calls and validation runs
+    Timeline {
+        /// Execution ID (or partial match)
+        id: String,
+
+        /// Timeline format (html, mermaid)
+        #[arg(short, long, default_value = "html")]
+        format: TimelineFormat,
+
+        /// Write the timeline to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Render the prompt an iteration starts with, without calling the LLM
+    ///
+    /// Shows the system prompt, the first user message (template, progress,
+    /// previous errors, acceptance failures, context excerpts) and the tools
+    /// offered. Past iterations are rebuilt from their iteration logs.
+    Prompt {
+        /// Execution ID (or partial match)
+        id: String,
+
+        /// Iteration to render: next, or an iteration number
+        #[arg(long, default_value = "next", value_name = "next|N")]
+        iteration: PromptIteration,
+
+        /// Print the request as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show an execution's iterations: duration, tokens, tools used and validation result
+    Iterations {
+        /// Execution ID (or partial match)
+        id: String,
+
+        /// Only iterations since: RFC 3339, YYYY-MM-DD, or an age like 30m, 12h, 7d
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only iterations before: RFC 3339, YYYY-MM-DD, or an age like 30m, 12h, 7d
+        #[arg(long)]
+        until: Option<String>,
+
+        /// Only iterations whose validation passed, failed, or could not run (error)
+        #[arg(long)]
+        outcome: Option<ValidationOutcome>,
+
+        /// Print the iterations as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Internal: Print all execution IDs, one per line (used by shell completions)
+    #[command(hide = true)]
+    Ids,
+}
+
+impl ExecCommand {
+    /// The execution ID argument, for subcommands that take one
+    pub fn id_mut(&mut self) -> Option<&mut String> {
+        match self {
+            Self::Pause { id, .. } | Self::Resume { id, .. } | Self::Cancel { id, .. } | Self::Delete { id, .. } => {
+                id.as_mut()
+            }
+            Self::Start { id }
+            | Self::Park { id, .. }
+            | Self::Wake { id }
+            | Self::Approve { id }
+            | Self::Rollback { id, .. }
+            | Self::Rerun { id, .. }
+            | Self::Set { id, .. }
+            | Self::CherryPick { id, .. }
+            | Self::Tag { id, .. }
+            | Self::Status { id, .. }
+            | Self::Report { id, .. }
+            | Self::Timeline { id, .. }
+            | Self::Logs { id, .. }
+            | Self::Watch { id }
+            | Self::Prompt { id, .. }
+            | Self::Iterations { id, .. } => Some(id),
+            Self::List { .. } | Self::Submit { .. } | Self::Diff { .. } | Self::Ids => None,
+        }
+    }
+}
+
+/// Selection for actions that can apply to many executions at once
+#[derive(Debug, Args)]
+pub struct BulkArgs {
+    /// Act on every execution matching the filter (e.g. status=failed,type=plan,tag=backend,user=me)
+    #[arg(long, value_name = "FILTER", conflicts_with = "id")]
+    pub filter: Option<ExecFilter>,
+
+    /// Apply a filtered action (without it, only the affected IDs are listed)
+    #[arg(short, long, requires = "filter")]
+    pub yes: bool,
 }
 
 /// Daemon management subcommands
@@ -153,6 +823,15 @@ pub enum DaemonCommand {
 
     /// Ping the daemon to check if it's alive and responsive
     Ping,
+
+    /// Freeze everything for repo surgery: pause running executions, stop pickups
+    Maintenance {
+        /// on: pause at the next iteration boundary; off: resume what was paused
+        state: Switch,
+    },
+
+    /// Re-read the LLM API key and resume executions paused because it was rejected
+    ReloadSecrets,
 }
 
 /// Result of checking a required tool
@@ -305,6 +984,35 @@ pub fn generate_after_help() -> String {
     help
 }
 
+/// On/off argument (e.g. `td daemon maintenance on`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Switch {
+    On,
+    Off,
+}
+
+impl std::str::FromStr for Switch {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        debug!(%s, "Switch::from_str: called");
+        match s.to_lowercase().as_str() {
+            "on" => Ok(Self::On),
+            "off" => Ok(Self::Off),
+            _ => Err(format!("Unknown state: {}. Use: on or off", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for Switch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::On => write!(f, "on"),
+            Self::Off => write!(f, "off"),
+        }
+    }
+}
+
 /// Output format for status/metrics commands
 #[derive(Clone, Debug, Default)]
 pub enum OutputFormat {
@@ -363,6 +1071,12 @@ impl std::fmt::Display for OutputFormat {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
 
     #[test]
     fn test_cli_parse_no_command() {
@@ -392,6 +1106,29 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_cli_parse_daemon_reload_secrets() {
+        let cli = Cli::parse_from(["taskdaemon", "daemon", "reload-secrets"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Daemon {
+                command: DaemonCommand::ReloadSecrets
+            })
+        ));
+    }
+
+    #[test]
+    fn test_cli_parse_daemon_maintenance() {
+        let cli = Cli::parse_from(["taskdaemon", "daemon", "maintenance", "on"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Daemon {
+                command: DaemonCommand::Maintenance { state: Switch::On }
+            })
+        ));
+        assert!(Cli::try_parse_from(["taskdaemon", "daemon", "maintenance", "maybe"]).is_err());
+    }
+
     #[test]
     fn test_cli_parse_daemon_stop() {
         let cli = Cli::parse_from(["taskdaemon", "daemon", "stop"]);
@@ -414,6 +1151,39 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_cli_parse_tool_worker() {
+        let cli = Cli::parse_from(["taskdaemon", "tool-worker", "--read-only", "--memory-mb", "2048"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::ToolWorker {
+                read_only: true,
+                memory_mb: Some(2048)
+            })
+        ));
+    }
+
+    #[test]
+    fn test_cli_parse_fsck() {
+        let cli = Cli::parse_from(["taskdaemon", "fsck"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Fsck {
+                repair: false,
+                json: false
+            })
+        ));
+
+        let cli = Cli::parse_from(["taskdaemon", "fsck", "--repair", "--json"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Fsck {
+                repair: true,
+                json: true
+            })
+        ));
+    }
+
     #[test]
     fn test_cli_parse_run() {
         let cli = Cli::parse_from(["taskdaemon", "run", "ralph", "Fix the bug"]);
@@ -421,14 +1191,314 @@ mod tests {
             loop_type,
             task,
             max_iterations,
+            ci,
+            ..
         }) = cli.command
         {
             assert_eq!(loop_type, "ralph");
             assert_eq!(task, "Fix the bug");
             assert!(max_iterations.is_none());
+            assert!(!ci);
         } else {
             panic!("Expected Run command");
         }
+
+        let cli = Cli::parse_from([
+            "taskdaemon",
+            "run",
+            "ralph",
+            "Fix clippy warnings",
+            "--ci",
+            "--report",
+            "results.sarif",
+        ]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Run {
+                ci: true,
+                report: Some(_),
+                report_format: None,
+                ..
+            })
+        ));
+        assert!(Cli::try_parse_from(["taskdaemon", "run", "ralph", "x", "--report", "junit.xml"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parse_explore() {
+        let cli = Cli::parse_from([
+            "taskdaemon",
+            "explore",
+            "how does auth middleware work",
+            "--thoroughness",
+            "high",
+        ]);
+        if let Some(Command::Explore {
+            question,
+            thoroughness,
+            output,
+            no_save,
+        }) = cli.command
+        {
+            assert_eq!(question, "how does auth middleware work");
+            assert_eq!(thoroughness, Thoroughness::Thorough);
+            assert!(output.is_none());
+            assert!(!no_save);
+        } else {
+            panic!("Expected Explore command");
+        }
+        assert!(Cli::try_parse_from(["taskdaemon", "explore", "q", "-t", "extreme"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parse_ask() {
+        let cli = Cli::parse_from(["taskdaemon", "ask", "why did the payments loop fail?", "--since", "7d"]);
+        if let Some(Command::Ask { question, since, limit }) = cli.command {
+            assert_eq!(question, "why did the payments loop fail?");
+            assert_eq!(since.as_deref(), Some("7d"));
+            assert_eq!(limit, 5);
+        } else {
+            panic!("Expected Ask command");
+        }
+    }
+
+    #[test]
+    fn test_cli_parse_run_many() {
+        let cli = Cli::parse_from([
+            "taskdaemon",
+            "run-many",
+            "--manifest",
+            "tasks.yaml",
+            "--max-parallel",
+            "2",
+        ]);
+        if let Some(Command::RunMany {
+            manifest,
+            max_parallel,
+            max_iterations,
+        }) = cli.command
+        {
+            assert_eq!(manifest, PathBuf::from("tasks.yaml"));
+            assert_eq!(max_parallel, 2);
+            assert!(max_iterations.is_none());
+        } else {
+            panic!("Expected RunMany command");
+        }
+
+        let cli = Cli::parse_from(["taskdaemon", "run-many", "-f", "tasks.yaml"]);
+        assert!(matches!(cli.command, Some(Command::RunMany { max_parallel: 4, .. })));
+    }
+
+    #[test]
+    fn test_cli_parse_loadtest() {
+        let cli = Cli::parse_from(["taskdaemon", "loadtest", "--loops", "50", "--profile", "flaky"]);
+        if let Some(Command::Loadtest {
+            loops,
+            profile,
+            max_concurrent,
+            seed,
+            ..
+        }) = cli.command
+        {
+            assert_eq!(loops, 50);
+            assert_eq!(profile, LoadProfile::Flaky);
+            assert!(max_concurrent.is_none());
+            assert_eq!(seed, 0);
+        } else {
+            panic!("Expected Loadtest command");
+        }
+
+        assert!(Cli::try_parse_from(["taskdaemon", "loadtest", "--profile", "chaos"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parse_metrics_history() {
+        let cli = Cli::parse_from(["taskdaemon", "metrics", "--history", "30d", "--type", "implement"]);
+        if let Some(Command::Metrics { loop_type, history, .. }) = cli.command {
+            assert_eq!(loop_type.as_deref(), Some("implement"));
+            assert_eq!(history, Some(30));
+        } else {
+            panic!("Expected Metrics command");
+        }
+
+        assert!(Cli::try_parse_from(["taskdaemon", "metrics", "--history", "soon"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parse_loops() {
+        let cli = Cli::parse_from(["taskdaemon", "loops"]);
+        assert!(matches!(cli.command, Some(Command::Loops { command: None })));
+
+        let cli = Cli::parse_from([
+            "taskdaemon",
+            "loops",
+            "install",
+            "github.com/org/td-loops//security-review",
+            "--ref",
+            "v1.2.0",
+        ]);
+        if let Some(Command::Loops {
+            command:
+                Some(LoopsCommand::Install {
+                    source,
+                    git_ref,
+                    checksum,
+                    global,
+                }),
+        }) = cli.command
+        {
+            assert_eq!(source, "github.com/org/td-loops//security-review");
+            assert_eq!(git_ref.as_deref(), Some("v1.2.0"));
+            assert_eq!(checksum, None);
+            assert!(!global);
+        } else {
+            panic!("Expected Loops Install command");
+        }
+
+        let cli = Cli::parse_from(["taskdaemon", "loops", "update", "--global"]);
+        if let Some(Command::Loops { command: Some(command) }) = cli.command {
+            assert!(command.global());
+            assert!(matches!(command, LoopsCommand::Update { name: None, .. }));
+        } else {
+            panic!("Expected Loops Update command");
+        }
+    }
+
+    #[test]
+    fn test_cli_parse_exec_rerun() {
+        let cli = Cli::parse_from([
+            "taskdaemon",
+            "exec",
+            "rerun",
+            "abc123",
+            "--edit-plan",
+            "--model",
+            "openai/gpt-4o",
+            "--max-iterations",
+            "20",
+        ]);
+        if let Some(Command::Exec {
+            command:
+                ExecCommand::Rerun {
+                    id,
+                    edit_plan,
+                    model,
+                    max_iterations,
+                },
+        }) = cli.command
+        {
+            assert_eq!(id, "abc123");
+            assert!(edit_plan);
+            assert_eq!(model.as_deref(), Some("openai/gpt-4o"));
+            assert_eq!(max_iterations, Some(20));
+        } else {
+            panic!("Expected Exec Rerun command");
+        }
+    }
+
+    #[test]
+    fn test_cli_parse_exec_diff() {
+        let cli = Cli::parse_from(["taskdaemon", "exec", "diff", "abc123", "def456"]);
+        if let Some(Command::Exec {
+            command: ExecCommand::Diff { left, right },
+        }) = cli.command
+        {
+            assert_eq!(left, "abc123");
+            assert_eq!(right, "def456");
+        } else {
+            panic!("Expected Exec Diff command");
+        }
+    }
+
+    #[test]
+    fn test_cli_parse_exec_set() {
+        let cli = Cli::parse_from([
+            "taskdaemon",
+            "exec",
+            "set",
+            "abc123",
+            "model=anthropic/claude-opus-4",
+            "max_iterations=20",
+        ]);
+        if let Some(Command::Exec {
+            command: ExecCommand::Set { id, settings },
+        }) = cli.command
+        {
+            assert_eq!(id, "abc123");
+            assert_eq!(
+                settings,
+                vec![
+                    RunOverride::Model(Some("anthropic/claude-opus-4".to_string())),
+                    RunOverride::MaxIterations(Some(20)),
+                ]
+            );
+        } else {
+            panic!("Expected Exec Set command");
+        }
+
+        assert!(Cli::try_parse_from(["taskdaemon", "exec", "set", "abc123", "temperature=1"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parse_exec_prompt() {
+        let cli = Cli::parse_from(["taskdaemon", "exec", "prompt", "abc123"]);
+        if let Some(Command::Exec {
+            command: ExecCommand::Prompt { id, iteration, json },
+        }) = cli.command
+        {
+            assert_eq!(id, "abc123");
+            assert_eq!(iteration, PromptIteration::Next);
+            assert!(!json);
+        } else {
+            panic!("Expected Exec Prompt command");
+        }
+
+        let cli = Cli::parse_from(["taskdaemon", "exec", "prompt", "abc123", "--iteration", "3", "--json"]);
+        if let Some(Command::Exec {
+            command: ExecCommand::Prompt { iteration, json, .. },
+        }) = cli.command
+        {
+            assert_eq!(iteration, PromptIteration::Number(3));
+            assert!(json);
+        } else {
+            panic!("Expected Exec Prompt command");
+        }
+    }
+
+    #[test]
+    fn test_cli_parse_exec_iterations() {
+        let cli = Cli::parse_from([
+            "taskdaemon",
+            "exec",
+            "iterations",
+            "abc123",
+            "--since",
+            "7d",
+            "--outcome",
+            "failed",
+            "--json",
+        ]);
+        if let Some(Command::Exec {
+            command:
+                ExecCommand::Iterations {
+                    id,
+                    since,
+                    until,
+                    outcome,
+                    json,
+                },
+        }) = cli.command
+        {
+            assert_eq!(id, "abc123");
+            assert_eq!(since.as_deref(), Some("7d"));
+            assert_eq!(until, None);
+            assert_eq!(outcome, Some(ValidationOutcome::Failed));
+            assert!(json);
+        } else {
+            panic!("Expected Exec Iterations command");
+        }
+
+        assert!(Cli::try_parse_from(["taskdaemon", "exec", "iterations", "abc", "--outcome", "flaky"]).is_err());
     }
 
     #[test]
@@ -0,0 +1,190 @@
+//! Injectable time and ID sources
+//!
+//! The loop engine, task manager, scheduler and cascade read the time and
+//! generate IDs through [`Clock`] and [`IdGen`] rather than calling
+//! `now_ms()`, `Instant::now()`, `tokio::time::sleep` or `generate_id`
+//! directly. Production uses [`SystemClock`] and [`RandomIdGen`]; tests and
+//! the replay harness inject [`ManualClock`] and [`SequentialIdGen`] so that
+//! an execution driven by a scripted LLM produces the same events, iteration
+//! logs and IDs on every run.
+//!
+//! Covered: iteration pacing and rate-limit sleeps, tool and validation
+//! durations, iteration log timestamps, scheduler windows and waits,
+//! worktree pruning age, and the IDs and creation times of cascade records.
+//! Not covered: `updated_at` stamps written by domain setters and the
+//! wall-clock timestamps of event log entries.
+
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use tracing::debug;
+
+use crate::domain::{generate_id, slugify};
+
+/// Source of the current time and of delays
+#[async_trait]
+pub trait Clock: Send + Sync + Debug {
+    /// Current time in Unix milliseconds
+    fn now_ms(&self) -> i64;
+
+    /// Current monotonic instant
+    fn instant(&self) -> Instant;
+
+    /// Wait for `duration`
+    async fn sleep(&self, duration: Duration);
+}
+
+/// Shared clock reference
+pub type ClockRef = Arc<dyn Clock>;
+
+/// The real clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl SystemClock {
+    /// Shared reference to the real clock
+    pub fn shared() -> ClockRef {
+        Arc::new(SystemClock)
+    }
+}
+
+#[async_trait]
+impl Clock for SystemClock {
+    fn now_ms(&self) -> i64 {
+        taskstore::now_ms()
+    }
+
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+}
+
+/// A clock that only moves when told to
+///
+/// `sleep` advances the clock by the requested duration and returns after
+/// yielding once, so code that waits runs instantly and always observes the
+/// same elapsed times.
+#[derive(Debug)]
+pub struct ManualClock {
+    start_ms: i64,
+    base: Instant,
+    offset_ms: AtomicU64,
+}
+
+impl ManualClock {
+    /// Create a clock reading `start_ms`
+    pub fn new(start_ms: i64) -> Self {
+        debug!(start_ms, "ManualClock::new: called");
+        Self {
+            start_ms,
+            base: Instant::now(),
+            offset_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Move the clock forward
+    pub fn advance(&self, duration: Duration) {
+        debug!(?duration, "ManualClock::advance: called");
+        self.offset_ms.fetch_add(duration.as_millis() as u64, Ordering::SeqCst);
+    }
+
+    /// Time elapsed since the clock was created
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_millis(self.offset_ms.load(Ordering::SeqCst))
+    }
+}
+
+#[async_trait]
+impl Clock for ManualClock {
+    fn now_ms(&self) -> i64 {
+        self.start_ms + self.elapsed().as_millis() as i64
+    }
+
+    fn instant(&self) -> Instant {
+        self.base + self.elapsed()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+        tokio::task::yield_now().await;
+    }
+}
+
+/// Source of domain IDs
+pub trait IdGen: Send + Sync + Debug {
+    /// Next ID in the `{6-char-hex}-{type}-{slug}` format
+    fn next_id(&self, domain_type: &str, title: &str) -> String;
+}
+
+/// Shared ID generator reference
+pub type IdGenRef = Arc<dyn IdGen>;
+
+/// UUIDv7-prefixed IDs (the production generator)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomIdGen;
+
+impl RandomIdGen {
+    /// Shared reference to the production generator
+    pub fn shared() -> IdGenRef {
+        Arc::new(RandomIdGen)
+    }
+}
+
+impl IdGen for RandomIdGen {
+    fn next_id(&self, domain_type: &str, title: &str) -> String {
+        generate_id(domain_type, title)
+    }
+}
+
+/// IDs prefixed with a counter: `000001-plan-add-oauth`, `000002-...`
+#[derive(Debug, Default)]
+pub struct SequentialIdGen {
+    next: AtomicU64,
+}
+
+impl SequentialIdGen {
+    /// Create a generator whose first ID is numbered 1
+    pub fn new() -> Self {
+        debug!("SequentialIdGen::new: called");
+        Self::default()
+    }
+}
+
+impl IdGen for SequentialIdGen {
+    fn next_id(&self, domain_type: &str, title: &str) -> String {
+        let n = self.next.fetch_add(1, Ordering::SeqCst) + 1;
+        let id = format!("{:06x}-{}-{}", n, domain_type, slugify(title));
+        debug!(%id, "SequentialIdGen::next_id: generated");
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_manual_clock_sleep_advances() {
+        let clock = ManualClock::new(1_000);
+        let start = clock.instant();
+        clock.sleep(Duration::from_secs(5)).await;
+        clock.advance(Duration::from_millis(250));
+        assert_eq!(clock.now_ms(), 6_250);
+        assert_eq!(clock.instant() - start, Duration::from_millis(5_250));
+    }
+
+    #[test]
+    fn test_sequential_id_gen() {
+        let ids = SequentialIdGen::new();
+        assert_eq!(ids.next_id("plan", "Add OAuth"), "000001-plan-add-oauth");
+        assert_eq!(ids.next_id("loop", "phase-phase"), "000002-loop-phase-phase");
+        assert!(RandomIdGen.next_id("plan", "Add OAuth").ends_with("-plan-add-oauth"));
+    }
+}
@@ -0,0 +1,422 @@
+//! Side-by-side execution comparison
+//!
+//! Contrasts two executions of the same task, typically an original and its
+//! `td exec rerun`: iterations used, cost, validation history, and a
+//! diff-of-diffs of their final changes. Backs `td exec diff` and the TUI
+//! comparison view.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::path::Path;
+
+use tracing::debug;
+
+use crate::domain::LoopExecution;
+use crate::events::read_execution_events;
+use crate::report::ExecutionReport;
+use crate::summary::format_duration_ms;
+
+/// Above this many cells the line diff of one file is skipped (shown as fully different)
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// How one file's changes differ between the two executions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchDelta {
+    /// Both made the same changes
+    Same,
+    /// Only the left execution changed the file
+    OnlyLeft,
+    /// Only the right execution changed the file
+    OnlyRight,
+    /// Both changed the file, differently: the changed lines unique to each side
+    Differs(Vec<DeltaLine>),
+}
+
+/// A changed line (`+...` or `-...`) that only one side's patch has
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeltaLine {
+    Left(String),
+    Right(String),
+}
+
+/// One file touched by either execution
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileComparison {
+    pub path: String,
+    pub delta: PatchDelta,
+}
+
+/// Two executions next to each other
+#[derive(Debug, Clone)]
+pub struct ExecutionComparison {
+    pub left: ExecutionReport,
+    pub right: ExecutionReport,
+    /// Diff-of-diffs of the final changes, by file
+    pub files: Vec<FileComparison>,
+}
+
+impl ExecutionComparison {
+    /// Compare two reports given the patches (`git diff` output) of their changes
+    pub fn new(left: ExecutionReport, right: ExecutionReport, left_patch: &str, right_patch: &str) -> Self {
+        debug!(left = %left.execution.id, right = %right.execution.id, "ExecutionComparison::new: called");
+        Self {
+            files: compare_patches(left_patch, right_patch),
+            left,
+            right,
+        }
+    }
+
+    /// Load both executions' event logs and patches and compare them
+    ///
+    /// Cost is estimated for each execution's model override, or `default_model`.
+    pub async fn load(left: LoopExecution, right: LoopExecution, runs_dir: &Path, default_model: &str) -> Self {
+        debug!(left = %left.id, right = %right.id, "ExecutionComparison::load: called");
+        let left_patch = execution_diff(&left.id, left.worktree.as_deref().map(Path::new), &[]).await;
+        let right_patch = execution_diff(&right.id, right.worktree.as_deref().map(Path::new), &[]).await;
+        Self::new(
+            report_for(left, runs_dir, default_model),
+            report_for(right, runs_dir, default_model),
+            left_patch.as_deref().unwrap_or_default(),
+            right_patch.as_deref().unwrap_or_default(),
+        )
+    }
+
+    /// Whether the two look like runs of the same task (same type, and a re-run link or the same context)
+    pub fn same_task(&self) -> bool {
+        let (left, right) = (&self.left.execution, &self.right.execution);
+        left.loop_type == right.loop_type
+            && (left.rerun_of.as_deref() == Some(right.id.as_str())
+                || right.rerun_of.as_deref() == Some(left.id.as_str())
+                || (left.rerun_of.is_some() && left.rerun_of == right.rerun_of)
+                || left.context == right.context)
+    }
+
+    /// Metric rows: (label, left value, right value)
+    pub fn rows(&self) -> Vec<(&'static str, String, String)> {
+        let row = |label, f: &dyn Fn(&ExecutionReport) -> String| (label, f(&self.left), f(&self.right));
+        vec![
+            row("Type", &|r| r.execution.loop_type.clone()),
+            row("Status", &|r| r.execution.status.to_string()),
+            row("Model", &|r| r.model.clone()),
+            row("Iterations", &|r| r.execution.iteration.to_string()),
+            row("Tokens", &|r| format!("{} in / {} out", r.input_tokens, r.output_tokens)),
+            row("Cost", &|r| format!("${:.4}", r.cost_usd())),
+            row("Duration", &|r| format_duration_ms(r.execution.total_duration_ms)),
+            row("Validations", &|r| {
+                let passed = r.validations.iter().filter(|v| v.exit_code == 0).count();
+                format!("{}/{} passed", passed, r.validations.len())
+            }),
+            row("History", &validation_history),
+        ]
+    }
+
+    /// Render as plain text for the terminal
+    pub fn to_text(&self) -> String {
+        debug!("ExecutionComparison::to_text: called");
+        let mut out = String::new();
+        let _ = writeln!(out, "A: {}", self.left.execution.id);
+        let _ = writeln!(out, "B: {}", self.right.execution.id);
+        if !self.same_task() {
+            let _ = writeln!(out, "Note: these executions do not look like runs of the same task");
+        }
+        let _ = writeln!(out);
+
+        let rows = self.rows();
+        let width = rows
+            .iter()
+            .map(|(_, left, _)| left.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let _ = writeln!(out, "{:<12} {:<width$}  B", "", "A");
+        for (label, left, right) in &rows {
+            let _ = writeln!(out, "{:<12} {:<width$}  {}", label, left, right);
+        }
+
+        let _ = writeln!(out);
+        let _ = writeln!(out, "Changes (= same, A/B only one side, ~ differ)");
+        if self.files.is_empty() {
+            let _ = writeln!(out, "  (neither execution changed any files)");
+        }
+        for file in &self.files {
+            match &file.delta {
+                PatchDelta::Same => {
+                    let _ = writeln!(out, "  = {}", file.path);
+                }
+                PatchDelta::OnlyLeft => {
+                    let _ = writeln!(out, "  A {}", file.path);
+                }
+                PatchDelta::OnlyRight => {
+                    let _ = writeln!(out, "  B {}", file.path);
+                }
+                PatchDelta::Differs(lines) => {
+                    let _ = writeln!(out, "  ~ {}", file.path);
+                    for line in lines {
+                        let _ = match line {
+                            DeltaLine::Left(text) => writeln!(out, "      A {}", text),
+                            DeltaLine::Right(text) => writeln!(out, "      B {}", text),
+                        };
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Pass/fail marks for each validation run, oldest first (e.g. "✗✗✓")
+pub fn validation_history(report: &ExecutionReport) -> String {
+    if report.validations.is_empty() {
+        return "-".to_string();
+    }
+    report
+        .validations
+        .iter()
+        .map(|v| if v.exit_code == 0 { '✓' } else { '✗' })
+        .collect()
+}
+
+/// Build a report for one execution from its event log
+fn report_for(exec: LoopExecution, runs_dir: &Path, default_model: &str) -> ExecutionReport {
+    let entries = read_execution_events(runs_dir, &exec.id).unwrap_or_default();
+    let model = exec.model.clone().unwrap_or_else(|| default_model.to_string());
+    ExecutionReport::from_events(exec, &entries, model)
+}
+
+/// Compare two patches file by file, ignoring context lines and line numbers
+pub fn compare_patches(left: &str, right: &str) -> Vec<FileComparison> {
+    debug!(left_len = left.len(), right_len = right.len(), "compare_patches: called");
+    let left = patch_changes(left);
+    let right = patch_changes(right);
+    let paths: BTreeSet<&String> = left.keys().chain(right.keys()).collect();
+    paths
+        .into_iter()
+        .map(|path| {
+            let delta = match (left.get(path), right.get(path)) {
+                (Some(l), Some(r)) if l == r => PatchDelta::Same,
+                (Some(l), Some(r)) => PatchDelta::Differs(line_delta(l, r)),
+                (Some(_), None) => PatchDelta::OnlyLeft,
+                _ => PatchDelta::OnlyRight,
+            };
+            FileComparison {
+                path: path.clone(),
+                delta,
+            }
+        })
+        .collect()
+}
+
+/// The added and removed lines of each file in a unified diff
+fn patch_changes(patch: &str) -> BTreeMap<String, Vec<&str>> {
+    let mut files: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    let mut current: Option<String> = None;
+    for line in patch.lines() {
+        if let Some(header) = line.strip_prefix("diff --git ") {
+            let path = header.rsplit_once(" b/").map_or(header, |(_, path)| path);
+            files.entry(path.to_string()).or_default();
+            current = Some(path.to_string());
+        } else if line.starts_with("+++") || line.starts_with("---") {
+            continue;
+        } else if (line.starts_with('+') || line.starts_with('-'))
+            && let Some(path) = &current
+        {
+            files.entry(path.clone()).or_default().push(line);
+        }
+    }
+    files
+}
+
+/// Lines only one side has, in order (longest common subsequence)
+fn line_delta(left: &[&str], right: &[&str]) -> Vec<DeltaLine> {
+    let (n, m) = (left.len(), right.len());
+    if n * m > MAX_DIFF_CELLS {
+        debug!(n, m, "line_delta: too large, listing both sides");
+        return left
+            .iter()
+            .map(|l| DeltaLine::Left(l.to_string()))
+            .chain(right.iter().map(|r| DeltaLine::Right(r.to_string())))
+            .collect();
+    }
+
+    // lcs[i][j] = length of the LCS of left[i..] and right[j..]
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if left[i] == right[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut delta = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && left[i] == right[j] {
+            i += 1;
+            j += 1;
+        } else if j == m || (i < n && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            delta.push(DeltaLine::Left(left[i].to_string()));
+            i += 1;
+        } else {
+            delta.push(DeltaLine::Right(right[j].to_string()));
+            j += 1;
+        }
+    }
+    delta
+}
+
+/// `git diff` of an execution's changes: its live worktree if present, else its branch
+///
+/// `args` are passed before the revision, e.g. `["--stat"]`. None if git fails.
+pub async fn execution_diff(exec_id: &str, worktree: Option<&Path>, args: &[&str]) -> Option<String> {
+    debug!(%exec_id, ?worktree, ?args, "execution_diff: called");
+    if let Some(worktree) = worktree.filter(|w| w.exists()) {
+        debug!("execution_diff: using worktree");
+        let base = git_output(&["merge-base", "main", "HEAD"], Some(worktree))
+            .await
+            .map(|s| s.trim().to_string())
+            .unwrap_or_else(|| "main".to_string());
+        let mut cmd = vec!["diff"];
+        cmd.extend_from_slice(args);
+        cmd.push(&base);
+        return git_output(&cmd, Some(worktree)).await;
+    }
+
+    debug!("execution_diff: using branch");
+    let range = format!("main...taskdaemon/{}", exec_id);
+    let mut cmd = vec!["diff"];
+    cmd.extend_from_slice(args);
+    cmd.push(&range);
+    git_output(&cmd, None).await
+}
+
+/// Run git and return stdout on success
+async fn git_output(args: &[&str], dir: Option<&Path>) -> Option<String> {
+    debug!(?args, ?dir, "git_output: called");
+    let mut cmd = tokio::process::Command::new("git");
+    cmd.args(args);
+    if let Some(dir) = dir {
+        cmd.current_dir(dir);
+    }
+    cmd.output()
+        .await
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::LoopExecutionStatus;
+    use crate::report::ValidationRun;
+
+    const LEFT: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 111..222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,3 @@
+ fn main() {
+-    old();
++    left();
+ }
+diff --git a/README.md b/README.md
+--- a/README.md
++++ b/README.md
+@@ -1 +1,2 @@
+ # Title
++More docs
+diff --git a/only_left.rs b/only_left.rs
+new file mode 100644
+--- /dev/null
++++ b/only_left.rs
+@@ -0,0 +1 @@
++fn left() {}
+";
+
+    const RIGHT: &str = "\
+diff --git a/README.md b/README.md
+--- a/README.md
++++ b/README.md
+@@ -4 +4,2 @@
+ # Title
++More docs
+diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,3 @@
+ fn main() {
+-    old();
++    right();
+ }
+diff --git a/only_right.rs b/only_right.rs
+--- /dev/null
++++ b/only_right.rs
+@@ -0,0 +1 @@
++fn right() {}
+";
+
+    #[test]
+    fn test_compare_patches() {
+        let files = compare_patches(LEFT, RIGHT);
+        let by_path: BTreeMap<&str, &PatchDelta> = files.iter().map(|f| (f.path.as_str(), &f.delta)).collect();
+        assert_eq!(by_path.len(), 4);
+        // Same change at a different line number counts as the same
+        assert_eq!(by_path["README.md"], &PatchDelta::Same);
+        assert_eq!(by_path["only_left.rs"], &PatchDelta::OnlyLeft);
+        assert_eq!(by_path["only_right.rs"], &PatchDelta::OnlyRight);
+        assert_eq!(
+            by_path["src/lib.rs"],
+            &PatchDelta::Differs(vec![
+                DeltaLine::Left("+    left();".to_string()),
+                DeltaLine::Right("+    right();".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_comparison_rows_and_text() {
+        let mut original = LoopExecution::with_id("orig", "ralph");
+        original.set_status(LoopExecutionStatus::Failed);
+        original.iteration = 3;
+        let mut rerun = original.rerun(1);
+        rerun.set_status(LoopExecutionStatus::Complete);
+        rerun.iteration = 1;
+
+        let mut left = ExecutionReport::from_events(original, &[], "anthropic/claude-sonnet-4");
+        left.validations = (1..=3)
+            .map(|iteration| ValidationRun {
+                iteration,
+                command: None,
+                exit_code: 1,
+                duration_ms: 10,
+            })
+            .collect();
+        let mut right = ExecutionReport::from_events(rerun, &[], "openai/gpt-4o");
+        right.validations.push(ValidationRun {
+            iteration: 1,
+            command: None,
+            exit_code: 0,
+            duration_ms: 10,
+        });
+
+        let comparison = ExecutionComparison::new(left, right, LEFT, RIGHT);
+        assert!(comparison.same_task());
+        let rows = comparison.rows();
+        let row = |label: &str| rows.iter().find(|(l, _, _)| *l == label).cloned().unwrap();
+        assert_eq!(row("Status").1, "failed");
+        assert_eq!(row("Status").2, "complete");
+        assert_eq!(row("Validations").1, "0/3 passed");
+        assert_eq!(row("History").1, "✗✗✗");
+        assert_eq!(row("History").2, "✓");
+
+        let text = comparison.to_text();
+        assert!(text.contains("A: orig"));
+        assert!(!text.contains("Note:"));
+        assert!(text.contains("  ~ src/lib.rs"));
+        assert!(text.contains("      B +    right();"));
+    }
+}
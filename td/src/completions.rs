@@ -0,0 +1,322 @@
+//! Shell completion scripts (`td completions <shell>`)
+//!
+//! Scripts are generated from the clap command tree, so new subcommands and
+//! flags are picked up automatically. Positional `id` arguments complete
+//! execution IDs by calling the hidden `td exec ids` at completion time.
+
+use clap::Command;
+use tracing::debug;
+
+/// Shell to generate completions for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl std::str::FromStr for Shell {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        debug!(%s, "Shell::from_str: called");
+        match s.to_lowercase().as_str() {
+            "bash" => Ok(Self::Bash),
+            "zsh" => Ok(Self::Zsh),
+            "fish" => Ok(Self::Fish),
+            _ => Err(format!("Unknown shell: {}. Use: bash, zsh or fish", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for Shell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bash => write!(f, "bash"),
+            Self::Zsh => write!(f, "zsh"),
+            Self::Fish => write!(f, "fish"),
+        }
+    }
+}
+
+/// One (sub)command in the tree, e.g. path "exec rollback"
+#[derive(Debug, Default)]
+struct CommandSpec {
+    /// Space-separated subcommand names from the root ("" for the root)
+    path: String,
+    /// Visible subcommands with their descriptions
+    subcommands: Vec<(String, String)>,
+    /// Flags: long name, short name, description, whether it takes a value
+    flags: Vec<(String, Option<char>, String, bool)>,
+    /// Whether a positional argument completes execution IDs
+    takes_exec_id: bool,
+}
+
+/// Flatten the command tree (depth first, root first)
+fn collect(cmd: &Command, path: &str, out: &mut Vec<CommandSpec>) {
+    let mut spec = CommandSpec {
+        path: path.to_string(),
+        ..Default::default()
+    };
+    for arg in cmd.get_arguments().filter(|a| !a.is_hide_set()) {
+        let help = arg.get_help().map(|h| h.to_string()).unwrap_or_default();
+        if arg.is_positional() {
+            spec.takes_exec_id |= arg.get_id() == "id";
+        } else if let Some(long) = arg.get_long() {
+            let takes_value = arg.get_action().takes_values();
+            spec.flags.push((long.to_string(), arg.get_short(), help, takes_value));
+        }
+    }
+    let subcommands: Vec<&Command> = cmd
+        .get_subcommands()
+        .filter(|s| !s.is_hide_set() && s.get_name() != "help")
+        .collect();
+    for sub in &subcommands {
+        let about = sub.get_about().map(|a| a.to_string()).unwrap_or_default();
+        spec.subcommands.push((sub.get_name().to_string(), about));
+    }
+    out.push(spec);
+    for sub in subcommands {
+        let sub_path = if path.is_empty() {
+            sub.get_name().to_string()
+        } else {
+            format!("{} {}", path, sub.get_name())
+        };
+        collect(sub, &sub_path, out);
+    }
+}
+
+/// Generate the completion script for `bin` (the installed binary name)
+pub fn generate(shell: Shell, cmd: &Command, bin: &str) -> String {
+    debug!(%shell, %bin, "completions::generate: called");
+    let mut cmd = cmd.clone();
+    cmd.build();
+    let mut specs = Vec::new();
+    collect(&cmd, "", &mut specs);
+    let func = format!("_{}", bin.replace('-', "_"));
+    match shell {
+        Shell::Bash => bash(&specs, bin, &func),
+        Shell::Zsh => zsh(&specs, bin, &func),
+        Shell::Fish => fish(&specs, bin, &func),
+    }
+}
+
+/// Shell functions shared by bash and zsh: subcommands, flags and ID positions per path
+fn posix_tables(specs: &[CommandSpec], func: &str) -> String {
+    let mut out = format!("{}_subcommands() {{\n    case \"$1\" in\n", func);
+    for spec in specs.iter().filter(|s| !s.subcommands.is_empty()) {
+        let names: Vec<&str> = spec.subcommands.iter().map(|(n, _)| n.as_str()).collect();
+        out.push_str(&format!("        \"{}\") echo \"{}\" ;;\n", spec.path, names.join(" ")));
+    }
+    out.push_str("    esac\n}\n\n");
+
+    out.push_str(&format!("{}_flags() {{\n    case \"$1\" in\n", func));
+    for spec in specs {
+        let mut flags: Vec<String> = Vec::new();
+        for (long, short, _, _) in &spec.flags {
+            flags.push(format!("--{}", long));
+            if let Some(short) = short {
+                flags.push(format!("-{}", short));
+            }
+        }
+        out.push_str(&format!("        \"{}\") echo \"{}\" ;;\n", spec.path, flags.join(" ")));
+    }
+    out.push_str("    esac\n}\n\n");
+
+    let id_paths: Vec<String> = specs
+        .iter()
+        .filter(|s| s.takes_exec_id)
+        .map(|s| format!("\"{}\"", s.path))
+        .collect();
+    out.push_str(&format!("{}_takes_id() {{\n    case \"$1\" in\n", func));
+    if !id_paths.is_empty() {
+        out.push_str(&format!("        {}) return 0 ;;\n", id_paths.join("|")));
+    }
+    out.push_str("    esac\n    return 1\n}\n\n");
+    out
+}
+
+fn bash(specs: &[CommandSpec], bin: &str, func: &str) -> String {
+    let mut out = format!("# bash completion for {}\n\n", bin);
+    out.push_str(&posix_tables(specs, func));
+    out.push_str(&format!(
+        r#"{func}() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}" cmdpath="" word i
+    for ((i = 1; i < COMP_CWORD; i++)); do
+        word="${{COMP_WORDS[i]}}"
+        [[ "$word" == -* ]] && continue
+        if [[ " $({func}_subcommands "$cmdpath") " == *" $word "* ]]; then
+            cmdpath="${{cmdpath:+$cmdpath }}$word"
+        fi
+    done
+
+    if [[ "$cur" == -* ]]; then
+        COMPREPLY=($(compgen -W "$({func}_flags "$cmdpath")" -- "$cur"))
+        return
+    fi
+    local candidates
+    candidates="$({func}_subcommands "$cmdpath")"
+    if {func}_takes_id "$cmdpath"; then
+        candidates="$candidates $({bin} exec ids 2>/dev/null)"
+    fi
+    COMPREPLY=($(compgen -W "$candidates" -- "$cur"))
+}}
+
+complete -F {func} {bin}
+"#
+    ));
+    out
+}
+
+fn zsh(specs: &[CommandSpec], bin: &str, func: &str) -> String {
+    let mut out = format!("#compdef {}\n\n", bin);
+    out.push_str(&posix_tables(specs, func));
+    out.push_str(&format!(
+        r#"{func}() {{
+    local cur="${{words[CURRENT]}}" cmdpath="" word i
+    local -a subs candidates
+    for ((i = 2; i < CURRENT; i++)); do
+        word="${{words[i]}}"
+        [[ "$word" == -* ]] && continue
+        subs=(${{=$({func}_subcommands "$cmdpath")}})
+        if (( ${{subs[(Ie)$word]}} )); then
+            cmdpath="${{cmdpath:+$cmdpath }}$word"
+        fi
+    done
+
+    if [[ "$cur" == -* ]]; then
+        candidates=(${{=$({func}_flags "$cmdpath")}})
+    else
+        candidates=(${{=$({func}_subcommands "$cmdpath")}})
+        if {func}_takes_id "$cmdpath"; then
+            candidates+=(${{(f)"$({bin} exec ids 2>/dev/null)"}})
+        fi
+    fi
+    compadd -- $candidates
+}}
+
+if [[ "$funcstack[1]" = "{func}" ]]; then
+    {func} "$@"
+else
+    compdef {func} {bin}
+fi
+"#
+    ));
+    out
+}
+
+/// Quote a string for fish
+fn fish_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn fish(specs: &[CommandSpec], bin: &str, func: &str) -> String {
+    let mut out = format!("# fish completion for {}\n\n", bin);
+    out.push_str(&format!("function {}_subcommands\n    switch \"$argv[1]\"\n", func));
+    for spec in specs.iter().filter(|s| !s.subcommands.is_empty()) {
+        let names: Vec<&str> = spec.subcommands.iter().map(|(n, _)| n.as_str()).collect();
+        out.push_str(&format!(
+            "        case {}\n            printf '%s\\n' {}\n",
+            fish_quote(&spec.path),
+            names.join(" ")
+        ));
+    }
+    out.push_str("    end\nend\n\n");
+    out.push_str(&format!(
+        r#"function {func}_path
+    set -l cmdpath
+    set -l tokens (commandline -opc)
+    set -e tokens[1]
+    for word in $tokens
+        string match -q -- '-*' $word; and continue
+        if contains -- $word ({func}_subcommands (string join ' ' $cmdpath))
+            set -a cmdpath $word
+        end
+    end
+    string join ' ' $cmdpath
+end
+
+function {func}_at
+    set -l cmdpath ({func}_path)
+    test "$cmdpath" = "$argv[1]"
+end
+
+complete -c {bin} -f
+"#
+    ));
+
+    for spec in specs {
+        let cond = format!("{}_at {}", func, fish_quote(&spec.path));
+        for (name, about) in &spec.subcommands {
+            out.push_str(&format!(
+                "complete -c {} -n {} -a {} -d {}\n",
+                bin,
+                fish_quote(&cond),
+                name,
+                fish_quote(about)
+            ));
+        }
+        for (long, short, help, takes_value) in &spec.flags {
+            let mut line = format!("complete -c {} -n {} -l {}", bin, fish_quote(&cond), long);
+            if let Some(short) = short {
+                line.push_str(&format!(" -s {}", short));
+            }
+            if *takes_value {
+                line.push_str(" -r");
+            }
+            line.push_str(&format!(" -d {}\n", fish_quote(help)));
+            out.push_str(&line);
+        }
+        if spec.takes_exec_id {
+            out.push_str(&format!(
+                "complete -c {} -n {} -a '({} exec ids 2>/dev/null)' -d 'execution'\n",
+                bin,
+                fish_quote(&cond),
+                bin
+            ));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Cli;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_shell_from_str() {
+        assert_eq!("bash".parse::<Shell>().unwrap(), Shell::Bash);
+        assert_eq!("ZSH".parse::<Shell>().unwrap(), Shell::Zsh);
+        assert_eq!("fish".parse::<Shell>().unwrap(), Shell::Fish);
+        assert!("powershell".parse::<Shell>().is_err());
+    }
+
+    #[test]
+    fn test_bash_completions() {
+        let script = generate(Shell::Bash, &Cli::command(), "td");
+        assert!(script.contains("complete -F _td td"));
+        // Subcommands per path, without hidden ones
+        assert!(script.contains("\"exec\") echo \"list start"));
+        assert!(!script.contains("run-daemon"));
+        // Flags, including globals propagated to subcommands
+        assert!(script.contains("--to-iteration"));
+        assert!(script.contains("\"exec rollback\") echo \"--to-iteration --config -c"));
+        // Execution IDs come from the hidden `td exec ids`
+        assert!(script.contains("\"exec start\"|"));
+        assert!(script.contains("td exec ids"));
+    }
+
+    #[test]
+    fn test_zsh_and_fish_completions() {
+        let zsh = generate(Shell::Zsh, &Cli::command(), "td");
+        assert!(zsh.starts_with("#compdef td"));
+        assert!(zsh.contains("compdef _td td"));
+
+        let fish = generate(Shell::Fish, &Cli::command(), "td");
+        assert!(fish.contains("complete -c td -n '_td_at \\'exec\\'' -a rollback"));
+        assert!(fish.contains("-l to-iteration -r"));
+        assert!(fish.contains("-a '(td exec ids 2>/dev/null)'"));
+    }
+}
@@ -0,0 +1,474 @@
+//! Layered config resolution
+//!
+//! The effective config is built from layers, each overriding the previous
+//! one key by key:
+//!
+//! 1. Built-in defaults
+//! 2. User config: `~/.config/taskdaemon/taskdaemon.yml`, then `config.toml`
+//! 3. Project config: `.taskdaemon.yml`, then `.taskdaemon/config.toml`
+//! 4. Environment: `TASKDAEMON_<SECTION>__<KEY>=value`
+//! 5. CLI: `--set <section>.<key>=value`
+//!
+//! Mappings are merged recursively; any other value, lists included, replaces
+//! the lower layer's. String values in config files may reference environment
+//! variables as `${VAR}` or `${VAR:-default}` (`$${` for a literal `${`). An
+//! explicit `--config` file takes the place of layers 2 and 3.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use eyre::{Context, Result, eyre};
+use serde_yaml::{Mapping, Value};
+use tracing::{debug, warn};
+
+use super::Config;
+
+/// Prefix of environment variables that override config keys
+pub const ENV_PREFIX: &str = "TASKDAEMON_";
+
+/// Where a config value came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigLayer {
+    Default,
+    /// User config file
+    User(PathBuf),
+    /// Project config file
+    Project(PathBuf),
+    /// File given with --config
+    File(PathBuf),
+    /// Environment variable
+    Env(String),
+    /// `--set` on the command line
+    Flag,
+}
+
+impl std::fmt::Display for ConfigLayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::User(path) => write!(f, "user {}", path.display()),
+            Self::Project(path) => write!(f, "project {}", path.display()),
+            Self::File(path) => write!(f, "file {}", path.display()),
+            Self::Env(name) => write!(f, "env {}", name),
+            Self::Flag => write!(f, "flag --set"),
+        }
+    }
+}
+
+/// Config with the layers it was resolved from
+#[derive(Debug, Clone)]
+pub struct LayeredConfig {
+    /// The effective config
+    pub config: Config,
+    /// The effective config as a YAML tree
+    pub value: Value,
+    /// Layer that set each leaf, by dotted key (e.g. "llm.default")
+    pub provenance: BTreeMap<String, ConfigLayer>,
+    /// Layers applied, lowest first
+    pub layers: Vec<ConfigLayer>,
+}
+
+impl LayeredConfig {
+    /// Resolve the config for the current directory, process environment and `--set` overrides
+    pub fn load(config_path: Option<&Path>, overrides: &[String]) -> Result<Self> {
+        debug!(?config_path, ?overrides, "LayeredConfig::load: called");
+        let user_dir = dirs::config_dir().map(|d| d.join("taskdaemon"));
+        Self::resolve(
+            user_dir.as_deref(),
+            Path::new(""),
+            config_path,
+            std::env::vars(),
+            overrides,
+        )
+    }
+
+    /// Resolve from explicit locations (user config dir, project root) and environment
+    pub fn resolve(
+        user_dir: Option<&Path>,
+        project_dir: &Path,
+        config_path: Option<&Path>,
+        env: impl IntoIterator<Item = (String, String)>,
+        overrides: &[String],
+    ) -> Result<Self> {
+        debug!(?user_dir, ?project_dir, ?config_path, "LayeredConfig::resolve: called");
+        let mut resolved = Self {
+            config: Config::default(),
+            value: serde_yaml::to_value(Config::default()).context("Failed to serialize default config")?,
+            provenance: BTreeMap::new(),
+            layers: vec![ConfigLayer::Default],
+        };
+        let mut leaves = Vec::new();
+        collect_leaves(&resolved.value, "", &mut leaves);
+        for (key, _) in leaves {
+            resolved.provenance.insert(key, ConfigLayer::Default);
+        }
+
+        let mut files = Vec::new();
+        match config_path {
+            Some(path) => files.push(ConfigLayer::File(path.to_path_buf())),
+            None => {
+                if let Some(dir) = user_dir {
+                    files.push(ConfigLayer::User(dir.join("taskdaemon.yml")));
+                    files.push(ConfigLayer::User(dir.join("config.toml")));
+                }
+                files.push(ConfigLayer::Project(project_dir.join(".taskdaemon.yml")));
+                files.push(ConfigLayer::Project(project_dir.join(".taskdaemon/config.toml")));
+            }
+        }
+        let mut source = None;
+        for layer in files {
+            let path = match &layer {
+                ConfigLayer::User(p) | ConfigLayer::Project(p) | ConfigLayer::File(p) => p.clone(),
+                _ => unreachable!("only file layers are listed"),
+            };
+            let explicit = matches!(layer, ConfigLayer::File(_));
+            if !explicit && !path.exists() {
+                debug!(?path, "LayeredConfig::resolve: no config file");
+                continue;
+            }
+            let mut value = read_file(&path).context(format!("Failed to load config from {}", path.display()))?;
+            interpolate(&mut value, "").context(format!("Failed to load config from {}", path.display()))?;
+            resolved.apply(value, &layer);
+            resolved.layers.push(layer);
+            source = Some(path);
+        }
+
+        let mut env: Vec<(String, String)> = env.into_iter().filter(|(k, _)| k.starts_with(ENV_PREFIX)).collect();
+        env.sort();
+        for (name, raw) in env {
+            let segments: Vec<&str> = name[ENV_PREFIX.len()..].split("__").collect();
+            let Some(path) = find_path(&resolved.value, &segments, normalize_env) else {
+                warn!(%name, "Ignoring {}: no such config key", name);
+                continue;
+            };
+            let layer = ConfigLayer::Env(name);
+            resolved.set(&path, &raw, &layer);
+            resolved.layers.push(layer);
+        }
+
+        for item in overrides {
+            let (key, raw) = item
+                .split_once('=')
+                .ok_or_else(|| eyre!("Invalid --set {}: expected KEY=VALUE", item))?;
+            let segments: Vec<&str> = key.trim().split('.').collect();
+            let path = find_path(&resolved.value, &segments, |s| s.to_string())
+                .ok_or_else(|| eyre!("Invalid --set {}: no such config key {}", item, key))?;
+            resolved.set(&path, raw, &ConfigLayer::Flag);
+            if resolved.layers.last() != Some(&ConfigLayer::Flag) {
+                resolved.layers.push(ConfigLayer::Flag);
+            }
+        }
+
+        resolved.config = serde_yaml::from_value(resolved.value.clone()).context("Invalid configuration")?;
+        resolved.config.source = source;
+        debug!(layers = resolved.layers.len(), "LayeredConfig::resolve: complete");
+        Ok(resolved)
+    }
+
+    /// Merge a whole layer into the tree
+    fn apply(&mut self, layer_value: Value, layer: &ConfigLayer) {
+        debug!(%layer, "LayeredConfig::apply: called");
+        let Value::Mapping(map) = layer_value else {
+            debug!("LayeredConfig::apply: empty or non-mapping file, nothing to merge");
+            return;
+        };
+        merge(&mut self.value, Value::Mapping(map), "", layer, &mut self.provenance);
+    }
+
+    /// Set one key from a raw string (env var or --set)
+    ///
+    /// The string keeps its type when the key holds a string; otherwise it is
+    /// parsed as YAML, so numbers, booleans and `[a, b]` lists work.
+    fn set(&mut self, path: &[String], raw: &str, layer: &ConfigLayer) {
+        debug!(?path, %layer, "LayeredConfig::set: called");
+        let mut target = &mut self.value;
+        for segment in path {
+            target = target
+                .as_mapping_mut()
+                .and_then(|m| m.get_mut(segment.as_str()))
+                .expect("path was resolved against this tree");
+        }
+        let value = if target.is_string() {
+            Value::String(raw.to_string())
+        } else {
+            serde_yaml::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+        };
+        let key = path.join(".");
+        set_provenance(&value, &key, layer, &mut self.provenance);
+        *target = value;
+    }
+
+    /// One `key = value  # layer` line per leaf
+    pub fn render_resolved(&self) -> String {
+        debug!("LayeredConfig::render_resolved: called");
+        let mut leaves = Vec::new();
+        collect_leaves(&self.value, "", &mut leaves);
+        let width = leaves.iter().map(|(k, _)| k.len()).max().unwrap_or(0);
+        let mut out = String::new();
+        for (key, value) in leaves {
+            let value = serde_json::to_string(value).unwrap_or_default();
+            let layer = self.provenance.get(&key).cloned().unwrap_or(ConfigLayer::Default);
+            out.push_str(&format!("{:<width$} = {}  # {}\n", key, value, layer, width = width));
+        }
+        out
+    }
+}
+
+/// Parse a config file into a YAML tree (TOML for `.toml` files)
+fn read_file(path: &Path) -> Result<Value> {
+    debug!(?path, "read_file: called");
+    let content = std::fs::read_to_string(path).context("Failed to read config file")?;
+    if path.extension().is_some_and(|e| e == "toml") {
+        let value: toml::Value = toml::from_str(&content).context("Failed to parse config file")?;
+        serde_yaml::to_value(value).context("Failed to convert TOML config")
+    } else {
+        serde_yaml::from_str(&content).context("Failed to parse config file")
+    }
+}
+
+/// Merge `layer` into `base`, recording the layer for every leaf it sets
+fn merge(
+    base: &mut Value,
+    layer: Value,
+    key: &str,
+    source: &ConfigLayer,
+    provenance: &mut BTreeMap<String, ConfigLayer>,
+) {
+    match (base, layer) {
+        (Value::Mapping(base_map), Value::Mapping(layer_map)) => {
+            for (k, v) in layer_map {
+                let child_key = join_key(key, &key_string(&k));
+                match base_map.get_mut(&k) {
+                    Some(existing) => merge(existing, v, &child_key, source, provenance),
+                    None => {
+                        set_provenance(&v, &child_key, source, provenance);
+                        base_map.insert(k, v);
+                    }
+                }
+            }
+        }
+        (base, layer) => {
+            set_provenance(&layer, key, source, provenance);
+            *base = layer;
+        }
+    }
+}
+
+/// Attribute `value` (and everything below it) at `key` to `layer`
+fn set_provenance(value: &Value, key: &str, layer: &ConfigLayer, provenance: &mut BTreeMap<String, ConfigLayer>) {
+    let prefix = format!("{}.", key);
+    provenance.retain(|k, _| k != key && !k.starts_with(&prefix));
+    let mut leaves = Vec::new();
+    collect_leaves(value, key, &mut leaves);
+    for (leaf, _) in leaves {
+        provenance.insert(leaf, layer.clone());
+    }
+}
+
+/// All leaves with their dotted keys (non-empty mappings are descended into)
+fn collect_leaves<'a>(value: &'a Value, key: &str, out: &mut Vec<(String, &'a Value)>) {
+    match value {
+        Value::Mapping(map) if !map.is_empty() => {
+            let mut keys: Vec<(String, &Value)> = map.iter().map(|(k, v)| (key_string(k), v)).collect();
+            keys.sort_by(|a, b| a.0.cmp(&b.0));
+            for (k, v) in keys {
+                collect_leaves(v, &join_key(key, &k), out);
+            }
+        }
+        _ => out.push((key.to_string(), value)),
+    }
+}
+
+/// Resolve key segments against the tree, matching each with `normalize`
+fn find_path(value: &Value, segments: &[&str], normalize: impl Fn(&str) -> String) -> Option<Vec<String>> {
+    let mut current = value;
+    let mut path = Vec::new();
+    for segment in segments {
+        let wanted = normalize(segment);
+        let (key, next) = current
+            .as_mapping()?
+            .iter()
+            .map(|(k, v)| (key_string(k), v))
+            .find(|(k, _)| normalize(k) == wanted)?;
+        path.push(key);
+        current = next;
+    }
+    (!path.is_empty()).then_some(path)
+}
+
+/// Compare env var segments and config keys: `MAX_LOOPS` matches `max-loops`
+fn normalize_env(s: &str) -> String {
+    s.to_lowercase().replace('-', "_")
+}
+
+fn key_string(key: &Value) -> String {
+    match key {
+        Value::String(s) => s.clone(),
+        other => serde_yaml::to_string(other).unwrap_or_default().trim().to_string(),
+    }
+}
+
+fn join_key(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() { key.to_string() } else { format!("{}.{}", prefix, key) }
+}
+
+/// Expand `${VAR}` and `${VAR:-default}` in every string value
+fn interpolate(value: &mut Value, key: &str) -> Result<()> {
+    match value {
+        Value::String(s) => {
+            *s = expand_env(s, |name| std::env::var(name).ok()).context(format!("In {}", key))?;
+        }
+        Value::Mapping(map) => {
+            let mut expanded = Mapping::new();
+            for (k, mut v) in std::mem::take(map) {
+                interpolate(&mut v, &join_key(key, &key_string(&k)))?;
+                expanded.insert(k, v);
+            }
+            *map = expanded;
+        }
+        Value::Sequence(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                interpolate(item, &format!("{}[{}]", key, i))?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Expand environment references in one string
+fn expand_env(s: &str, lookup: impl Fn(&str) -> Option<String>) -> Result<String> {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+        if let Some(literal) = after.strip_prefix("${") {
+            out.push_str("${");
+            rest = literal;
+        } else if let Some(reference) = after.strip_prefix('{') {
+            let end = reference
+                .find('}')
+                .ok_or_else(|| eyre!("Unterminated ${{ in {:?}", s))?;
+            let (name, default) = match reference[..end].split_once(":-") {
+                Some((name, default)) => (name, Some(default)),
+                None => (&reference[..end], None),
+            };
+            match (lookup(name), default) {
+                (Some(value), _) => out.push_str(&value),
+                (None, Some(default)) => out.push_str(default),
+                (None, None) => return Err(eyre!("Environment variable {} is not set", name)),
+            }
+            rest = &reference[end + 1..];
+        } else {
+            out.push('$');
+            rest = after;
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_expand_env() {
+        let lookup = |name: &str| (name == "HOME").then(|| "/home/me".to_string());
+        assert_eq!(expand_env("${HOME}/wt", lookup).unwrap(), "/home/me/wt");
+        assert_eq!(expand_env("${NOPE:-/tmp}/wt", lookup).unwrap(), "/tmp/wt");
+        assert_eq!(expand_env("$${HOME} costs $5", lookup).unwrap(), "${HOME} costs $5");
+        assert!(expand_env("${NOPE}", lookup).is_err());
+        assert!(expand_env("${HOME", lookup).is_err());
+    }
+
+    #[test]
+    fn test_layer_precedence() {
+        let user = TempDir::new().unwrap();
+        let project = TempDir::new().unwrap();
+        std::fs::write(
+            user.path().join("taskdaemon.yml"),
+            "llm:\n  default: anthropic/claude-sonnet-4-20250514\nconcurrency:\n  max-loops: 5\n  max-worktrees: 7\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(project.path().join(".taskdaemon")).unwrap();
+        std::fs::write(
+            project.path().join(".taskdaemon/config.toml"),
+            "[concurrency]\nmax-loops = 8\n\n[validation]\ncommand = \"cargo test\"\n",
+        )
+        .unwrap();
+
+        let resolved = LayeredConfig::resolve(
+            Some(user.path()),
+            project.path(),
+            None,
+            env(&[
+                ("TASKDAEMON_CONCURRENCY__MAX_API_CALLS", "3"),
+                ("TASKDAEMON_BOGUS", "1"),
+            ]),
+            &["validation.command=make check".to_string()],
+        )
+        .unwrap();
+        let config = &resolved.config;
+
+        assert_eq!(config.llm.default, "anthropic/claude-sonnet-4-20250514");
+        assert_eq!(config.concurrency.max_worktrees, 7);
+        assert_eq!(config.concurrency.max_loops, 8);
+        assert_eq!(config.concurrency.max_api_calls, 3);
+        assert_eq!(config.validation.command, "make check");
+        assert_eq!(config.progress.max_entries, 5);
+        assert_eq!(
+            config.source.as_deref(),
+            Some(project.path().join(".taskdaemon/config.toml").as_path())
+        );
+
+        let provenance = &resolved.provenance;
+        assert!(matches!(provenance["llm.default"], ConfigLayer::User(_)));
+        assert!(matches!(provenance["concurrency.max-loops"], ConfigLayer::Project(_)));
+        assert_eq!(
+            provenance["concurrency.max-api-calls"],
+            ConfigLayer::Env("TASKDAEMON_CONCURRENCY__MAX_API_CALLS".to_string())
+        );
+        assert_eq!(provenance["validation.command"], ConfigLayer::Flag);
+        assert_eq!(provenance["progress.max-entries"], ConfigLayer::Default);
+        // Maps merge by key, so the user's settings don't hide the default providers
+        assert!(resolved.config.llm.providers.contains_key("openai"));
+
+        let rendered = resolved.render_resolved();
+        assert!(rendered.contains("\"make check\"  # flag --set"));
+        assert!(
+            rendered
+                .lines()
+                .any(|l| l.starts_with("concurrency.max-loops") && l.contains("= 8  # project"))
+        );
+    }
+
+    #[test]
+    fn test_explicit_file_and_errors() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("custom.yml");
+        std::fs::write(&path, "git:\n  worktree-dir: ${TD_TEST_UNSET_VAR:-/tmp/wt}\n").unwrap();
+        // The project file is ignored when --config is given
+        std::fs::write(dir.path().join(".taskdaemon.yml"), "log-level: debug\n").unwrap();
+
+        let resolved = LayeredConfig::resolve(None, dir.path(), Some(&path), env(&[]), &[]).unwrap();
+        assert_eq!(resolved.config.git.worktree_dir, PathBuf::from("/tmp/wt"));
+        assert_eq!(resolved.config.log_level, None);
+        assert_eq!(
+            resolved.layers,
+            vec![ConfigLayer::Default, ConfigLayer::File(path.clone())]
+        );
+
+        assert!(LayeredConfig::resolve(None, dir.path(), Some(&path), env(&[]), &["nope.key=1".to_string()]).is_err());
+        assert!(LayeredConfig::resolve(None, dir.path(), Some(&path), env(&[]), &["no-equals".to_string()]).is_err());
+        std::fs::write(&path, "git:\n  worktree-dir: ${TD_TEST_UNSET_VAR}\n").unwrap();
+        let err = LayeredConfig::resolve(None, dir.path(), Some(&path), env(&[]), &[]).unwrap_err();
+        assert!(format!("{:#}", err).contains("TD_TEST_UNSET_VAR is not set"));
+    }
+}
@@ -0,0 +1,2234 @@
+//! TaskDaemon configuration types and loading
+//!
+//! See [`layers`] for how defaults, user and project files, environment
+//! variables and `--set` flags are combined.
+
+pub mod layers;
+
+pub use layers::{ConfigLayer, ENV_PREFIX, LayeredConfig};
+
+use crate::chat::ChatPlatform;
+use crate::coordinator::CoordinatorConfig;
+use crate::domain::Submitter;
+use crate::events::TokenBatching;
+use crate::hooks::Hooks;
+use crate::llm::ContextStrategy;
+use crate::policy::PolicyErrorMode;
+use crate::scheduler::{FairnessPolicy, SchedulerConfig};
+use crate::secrets::SecretBackend;
+use eyre::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// Main TaskDaemon configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Log level (TRACE, DEBUG, INFO, WARN, ERROR)
+    #[serde(rename = "log-level")]
+    pub log_level: Option<String>,
+
+    /// LLM provider configuration
+    pub llm: LlmConfig,
+
+    /// Concurrency limits
+    pub concurrency: ConcurrencyConfig,
+
+    /// Validation defaults
+    pub validation: ValidationConfig,
+
+    /// Progress strategy configuration
+    pub progress: ProgressConfig,
+
+    /// Git configuration
+    pub git: GitConfig,
+
+    /// Storage configuration
+    pub storage: StorageConfig,
+
+    /// Loop type paths configuration
+    pub loops: LoopsConfig,
+
+    /// Tool plugins loaded at startup
+    pub tools: ToolsConfig,
+
+    /// Self-evaluation (LLM-as-judge) pass before merging
+    pub evaluation: EvaluationConfig,
+
+    /// Risk scoring of diffs before merging; risky ones wait for approval
+    pub risk: RiskConfig,
+
+    /// Publishing of `review` loops and holding merges on blocking comments
+    pub review: ReviewConfig,
+
+    /// Hooks run for every execution, before the loop type's own
+    pub hooks: Hooks,
+
+    /// WebAssembly policy modules gating starts, tool calls and merges
+    pub policies: PoliciesConfig,
+
+    /// Inter-loop coordination (alert retention, rate limits, buffers)
+    pub coordinator: CoordinatorConfig,
+
+    /// TUI preferences
+    pub tui: TuiConfig,
+
+    /// Desktop notifications and terminal bell
+    pub notifications: NotificationsConfig,
+
+    /// Event log rotation and compaction
+    #[serde(rename = "event-log")]
+    pub event_log: EventLogConfig,
+
+    /// Coalescing of streamed LLM output on the event bus
+    pub streaming: StreamingConfig,
+
+    /// Daemon self-monitoring and the limits that pause pickups
+    pub resources: ResourceMonitorConfig,
+
+    /// HTTP endpoint where external systems create executions
+    pub webhooks: WebhookConfig,
+
+    /// Detection of tasks submitted twice
+    pub dedup: DedupConfig,
+
+    /// Slack/Discord notifications and chat commands
+    pub chat: ChatConfig,
+
+    /// Who executions submitted from this machine are attributed to
+    pub user: UserConfig,
+
+    /// gRPC control API (needs the `grpc` feature)
+    pub grpc: GrpcConfig,
+
+    /// Debug configuration
+    pub debug: DebugConfig,
+
+    /// Most specific config file that was loaded (None = defaults only)
+    #[serde(skip)]
+    pub source: Option<PathBuf>,
+}
+
+impl Config {
+    /// Load just the log level (for early logging setup)
+    ///
+    /// Resolves the config layers and returns `log-level` if one is set. This
+    /// is called before full config loading to enable proper logging during
+    /// startup; errors are left for the full load to report.
+    pub fn load_log_level(config_path: Option<&PathBuf>, overrides: &[String]) -> Option<String> {
+        LayeredConfig::load(config_path.map(PathBuf::as_path), overrides)
+            .ok()
+            .and_then(|layered| layered.config.log_level)
+    }
+
+    /// Validate configuration before use
+    ///
+    /// Checks that required environment variables and paths are set correctly.
+    /// Call this early in startup to fail fast with clear error messages.
+    pub fn validate(&self) -> Result<()> {
+        debug!("Config::validate: called");
+        // Check LLM API key is available (from env or file)
+        self.llm.get_api_key().context("LLM API key validation failed")?;
+        debug!("Config::validate: validation passed");
+        Ok(())
+    }
+
+    /// Load the layered configuration (defaults, user, project, environment)
+    ///
+    /// An explicit `config_path` replaces the user and project files.
+    pub fn load(config_path: Option<&PathBuf>) -> Result<Self> {
+        debug!(?config_path, "Config::load: called");
+        Self::load_with_overrides(config_path, &[])
+    }
+
+    /// Load the layered configuration with `--set KEY=VALUE` overrides on top
+    pub fn load_with_overrides(config_path: Option<&PathBuf>, overrides: &[String]) -> Result<Self> {
+        debug!(?config_path, ?overrides, "Config::load_with_overrides: called");
+        let layered = LayeredConfig::load(config_path.map(PathBuf::as_path), overrides)?;
+        match &layered.config.source {
+            Some(source) => tracing::info!("Loaded config from: {}", source.display()),
+            None => tracing::info!("No config file found, using defaults"),
+        }
+        Ok(layered.config)
+    }
+}
+
+/// LLM configuration with multiple providers and models
+///
+/// Uses a hierarchical structure:
+/// ```yaml
+/// llm:
+///   default: openai/gpt-4o
+///   timeout-ms: 300000
+///   providers:
+///     openai:
+///       api-key-env: OPENAI_API_KEY
+///       base-url: https://api.openai.com
+///       models:
+///         gpt-4o:
+///           max-tokens: 16384
+///     openrouter:                 # any OpenAI-compatible endpoint
+///       api: openai
+///       api-key-env: OPENROUTER_API_KEY
+///       base-url: https://openrouter.ai/api/v1
+///       models:
+///         deepseek/deepseek-r1:
+///           max-tokens: 8192
+///           temperature: 0.2
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmConfig {
+    /// Default provider/model in "provider/model" format (e.g., "openai/gpt-4o")
+    /// REQUIRED - no fallback, must be explicitly set in config
+    pub default: String,
+
+    /// Request timeout in milliseconds (shared across all providers)
+    #[serde(rename = "timeout-ms", default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+
+    /// Provider configurations keyed by provider name
+    #[serde(default = "default_providers")]
+    pub providers: std::collections::HashMap<String, ProviderConfig>,
+
+    /// Audit log of requests sent to the providers
+    #[serde(default)]
+    pub audit: AuditConfig,
+
+    /// Store that `api-key-secret` names are looked up in
+    #[serde(default)]
+    pub secrets: SecretsConfig,
+
+    /// Provider used for embeddings (default: the provider of `default`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embeddings: Option<String>,
+
+    /// What to do with a request larger than the model's context window
+    #[serde(rename = "context-strategy", default)]
+    pub context_strategy: ContextStrategy,
+
+    /// "provider/model" specs tried in order when `default` is down or rejects a request
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallback: Vec<String>,
+
+    /// Consecutive outage errors (5xx, network, timeout) before a model in the chain is skipped
+    #[serde(rename = "failover-after", default = "default_failover_after")]
+    pub failover_after: u32,
+
+    /// Seconds a skipped model is left alone before it is tried again
+    #[serde(rename = "failover-retry-secs", default = "default_failover_retry_secs")]
+    pub failover_retry_secs: u64,
+}
+
+/// Configuration for a single LLM provider (e.g., OpenAI, Anthropic)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderConfig {
+    /// Environment variable containing the API key (checked first; unused by `api: local`)
+    #[serde(rename = "api-key-env", default)]
+    pub api_key_env: String,
+
+    /// File path containing the API key (used if env var not set)
+    /// Supports ~ for home directory
+    #[serde(rename = "api-key-file")]
+    pub api_key_file: Option<String>,
+
+    /// Name of a secret holding the API key (`td secrets set`), tried after the env var
+    #[serde(rename = "api-key-secret", default)]
+    pub api_key_secret: Option<String>,
+
+    /// API base URL (for OpenAI-compatible servers, with or without a trailing `/v1`)
+    #[serde(rename = "base-url")]
+    pub base_url: String,
+
+    /// Wire protocol: "anthropic", "openai", "azure" or "local" (default: the provider name)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api: Option<String>,
+
+    /// OpenAI organization, sent as the `OpenAI-Organization` header
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub organization: Option<String>,
+
+    /// Azure OpenAI `api-version` query parameter
+    #[serde(rename = "api-version", skip_serializing_if = "Option::is_none")]
+    pub api_version: Option<String>,
+
+    /// Model (Azure: deployment) used by `embed`; embeddings are unavailable without it
+    #[serde(rename = "embedding-model", skip_serializing_if = "Option::is_none")]
+    pub embedding_model: Option<String>,
+
+    /// Model configurations keyed by model name
+    pub models: std::collections::HashMap<String, ModelConfig>,
+}
+
+/// Configuration for a single model
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelConfig {
+    /// Maximum tokens per response
+    #[serde(rename = "max-tokens")]
+    pub max_tokens: u32,
+
+    /// Sampling temperature (provider default if unset)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+
+    /// Reasoning effort for reasoning models ("low", "medium", "high")
+    #[serde(rename = "reasoning-effort", skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<String>,
+
+    /// Azure deployment name (default: the model name)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deployment: Option<String>,
+
+    /// Context window in tokens (default: looked up from the model name)
+    #[serde(rename = "context-window", skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<u32>,
+}
+
+/// Resolved LLM configuration ready for client creation
+///
+/// This flattened struct is produced by `LlmConfig::resolve()` and contains
+/// all the information needed to create an LLM client.
+#[derive(Debug, Clone)]
+pub struct ResolvedLlmConfig {
+    /// Provider name as configured (e.g. "openai", "openrouter")
+    pub provider: String,
+    /// Wire protocol ("anthropic", "openai", "azure" or "local")
+    pub api: String,
+    /// Model identifier
+    pub model: String,
+    /// Environment variable for API key
+    pub api_key_env: String,
+    /// File path for API key (optional)
+    pub api_key_file: Option<String>,
+    /// Secret holding the API key (optional)
+    pub api_key_secret: Option<String>,
+    /// Secrets store settings
+    pub secrets: SecretsConfig,
+    /// API base URL
+    pub base_url: String,
+    /// Maximum tokens per response
+    pub max_tokens: u32,
+    /// OpenAI organization header
+    pub organization: Option<String>,
+    /// Azure `api-version`
+    pub api_version: Option<String>,
+    /// Azure deployment name
+    pub deployment: Option<String>,
+    /// Sampling temperature override
+    pub temperature: Option<f32>,
+    /// Reasoning effort override
+    pub reasoning_effort: Option<String>,
+    /// Embedding model of the provider
+    pub embedding_model: Option<String>,
+    /// Context window override
+    pub context_window: Option<u32>,
+    /// What to do with a request over the context window
+    pub context_strategy: ContextStrategy,
+    /// Request timeout in milliseconds
+    pub timeout_ms: u64,
+    /// Audit log settings
+    pub audit: AuditConfig,
+}
+
+impl ResolvedLlmConfig {
+    /// Get the API key from environment variable, secrets store or file
+    pub fn get_api_key(&self) -> Result<String> {
+        // First try environment variable
+        if let Ok(key) = std::env::var(&self.api_key_env) {
+            debug!(env_var = %self.api_key_env, "get_api_key: found in environment");
+            return Ok(key);
+        }
+
+        // Then the secrets store
+        if let Some(name) = &self.api_key_secret {
+            let store = crate::secrets::open(&self.secrets)?;
+            if let Some(key) = store
+                .get(name)
+                .context(format!("Failed to read secret {} ({})", name, self.secrets.backend))?
+            {
+                debug!(%name, backend = %self.secrets.backend, "get_api_key: found in secrets store");
+                return Ok(key);
+            }
+            debug!(%name, "get_api_key: secret not found");
+        }
+
+        // Then try file
+        if let Some(file_path) = &self.api_key_file {
+            let expanded = if file_path.starts_with("~/") {
+                dirs::home_dir()
+                    .map(|h| h.join(&file_path[2..]))
+                    .unwrap_or_else(|| PathBuf::from(file_path))
+            } else {
+                PathBuf::from(file_path)
+            };
+
+            if expanded.exists() {
+                let key = fs::read_to_string(&expanded)
+                    .context(format!("Failed to read API key from {}", expanded.display()))?
+                    .trim()
+                    .to_string();
+                debug!(file = %expanded.display(), "get_api_key: found in file");
+                return Ok(key);
+            }
+        }
+
+        if let Some(name) = &self.api_key_secret {
+            return Err(eyre::eyre!(
+                "API key not found. Set the {} environment variable or store it with `td secrets set {}`",
+                self.api_key_env,
+                name
+            ));
+        }
+        Err(eyre::eyre!(
+            "API key not found. Set the {} environment variable or configure api-key-file or api-key-secret in your config",
+            self.api_key_env
+        ))
+    }
+}
+
+impl LlmConfig {
+    /// Resolve the default provider/model into a flat config ready for client creation
+    pub fn resolve(&self) -> Result<ResolvedLlmConfig> {
+        debug!(default = %self.default, "LlmConfig::resolve: called");
+        self.resolve_model(&self.default)
+    }
+
+    /// Resolve a specific "provider/model" into a flat config ready for client creation
+    pub fn resolve_model(&self, spec: &str) -> Result<ResolvedLlmConfig> {
+        debug!(%spec, "LlmConfig::resolve_model: called");
+
+        let parts: Vec<&str> = spec.split('/').collect();
+        if parts.len() != 2 {
+            return Err(eyre::eyre!(
+                "Invalid LLM format '{}'. Expected 'provider/model' (e.g., 'openai/gpt-4o')",
+                spec
+            ));
+        }
+
+        let provider_name = parts[0];
+        let model_name = parts[1];
+
+        let provider = self.provider(provider_name)?;
+
+        let model = provider.models.get(model_name).ok_or_else(|| {
+            eyre::eyre!(
+                "Model '{}' not found for provider '{}'. Available: {:?}",
+                model_name,
+                provider_name,
+                provider.models.keys().collect::<Vec<_>>()
+            )
+        })?;
+
+        debug!(
+            provider = %provider_name,
+            model = %model_name,
+            max_tokens = model.max_tokens,
+            "LlmConfig::resolve_model: resolved"
+        );
+
+        Ok(self.build_resolved(provider_name, provider, model_name, model))
+    }
+
+    /// Resolve `default` followed by each `fallback`, in the order they are tried
+    pub fn resolve_chain(&self) -> Result<Vec<ResolvedLlmConfig>> {
+        debug!(default = %self.default, fallback = ?self.fallback, "LlmConfig::resolve_chain: called");
+        std::iter::once(&self.default)
+            .chain(&self.fallback)
+            .map(|spec| self.resolve_model(spec))
+            .collect()
+    }
+
+    /// Resolve the provider used for embeddings: `embeddings`, else the default model's provider
+    ///
+    /// The resolved `model` is the provider's `embedding-model` (empty if unset).
+    pub fn resolve_embeddings(&self) -> Result<ResolvedLlmConfig> {
+        let provider_name = match &self.embeddings {
+            Some(name) => name.as_str(),
+            None => self.default.split('/').next().unwrap_or_default(),
+        };
+        debug!(%provider_name, "LlmConfig::resolve_embeddings: called");
+        let provider = self.provider(provider_name)?;
+        let model_name = provider.embedding_model.clone().unwrap_or_default();
+        Ok(self.build_resolved(provider_name, provider, &model_name, &ModelConfig::default()))
+    }
+
+    fn provider(&self, name: &str) -> Result<&ProviderConfig> {
+        self.providers.get(name).ok_or_else(|| {
+            eyre::eyre!(
+                "Provider '{}' not found in config. Available: {:?}",
+                name,
+                self.providers.keys().collect::<Vec<_>>()
+            )
+        })
+    }
+
+    fn build_resolved(
+        &self,
+        provider_name: &str,
+        provider: &ProviderConfig,
+        model_name: &str,
+        model: &ModelConfig,
+    ) -> ResolvedLlmConfig {
+        ResolvedLlmConfig {
+            provider: provider_name.to_string(),
+            api: provider.api.clone().unwrap_or_else(|| provider_name.to_string()),
+            model: model_name.to_string(),
+            api_key_env: provider.api_key_env.clone(),
+            api_key_file: provider.api_key_file.clone(),
+            api_key_secret: provider.api_key_secret.clone(),
+            secrets: self.secrets.clone(),
+            base_url: provider.base_url.clone(),
+            max_tokens: model.max_tokens,
+            organization: provider.organization.clone(),
+            api_version: provider.api_version.clone(),
+            deployment: model.deployment.clone(),
+            temperature: model.temperature,
+            reasoning_effort: model.reasoning_effort.clone(),
+            embedding_model: provider.embedding_model.clone(),
+            context_window: model.context_window,
+            context_strategy: self.context_strategy,
+            timeout_ms: self.timeout_ms,
+            audit: self.audit.clone(),
+        }
+    }
+
+    /// Resolve "provider/model" or a bare model name that exists under exactly one provider
+    pub fn resolve_model_name(&self, name: &str) -> Result<ResolvedLlmConfig> {
+        debug!(%name, "LlmConfig::resolve_model_name: called");
+        if name.contains('/') {
+            return self.resolve_model(name);
+        }
+
+        let matches: Vec<String> = self
+            .available_models()
+            .into_iter()
+            .filter(|spec| spec.split_once('/').is_some_and(|(_, model)| model == name))
+            .collect();
+        match matches.as_slice() {
+            [spec] => self.resolve_model(spec),
+            [] => Err(eyre::eyre!(
+                "Model '{}' not found in config. Available: {:?}",
+                name,
+                self.available_models()
+            )),
+            _ => Err(eyre::eyre!("Model '{}' is ambiguous; use one of {:?}", name, matches)),
+        }
+    }
+
+    /// Get the API key for the default provider (convenience method)
+    pub fn get_api_key(&self) -> Result<String> {
+        self.resolve()?.get_api_key()
+    }
+
+    /// List all available provider/model combinations
+    pub fn available_models(&self) -> Vec<String> {
+        let mut models = Vec::new();
+        for (provider_name, provider) in &self.providers {
+            for model_name in provider.models.keys() {
+                models.push(format!("{}/{}", provider_name, model_name));
+            }
+        }
+        models.sort();
+        models
+    }
+}
+
+/// Default timeout for LLM requests (5 minutes)
+fn default_timeout_ms() -> u64 {
+    300_000
+}
+
+fn default_failover_after() -> u32 {
+    3
+}
+
+fn default_failover_retry_secs() -> u64 {
+    60
+}
+
+/// Default provider configurations (OpenAI and Anthropic)
+fn default_providers() -> std::collections::HashMap<String, ProviderConfig> {
+    use std::collections::HashMap;
+
+    let mut providers = HashMap::new();
+
+    // Anthropic provider
+    let mut anthropic_models = HashMap::new();
+    anthropic_models.insert(
+        "claude-sonnet-4-20250514".to_string(),
+        ModelConfig {
+            max_tokens: 8192,
+            ..Default::default()
+        },
+    );
+    anthropic_models.insert(
+        "claude-opus-4-20250514".to_string(),
+        ModelConfig {
+            max_tokens: 4096,
+            ..Default::default()
+        },
+    );
+    providers.insert(
+        "anthropic".to_string(),
+        ProviderConfig {
+            api_key_env: "ANTHROPIC_API_KEY".to_string(),
+            api_key_file: None,
+            api_key_secret: None,
+            base_url: "https://api.anthropic.com".to_string(),
+            api: None,
+            organization: None,
+            api_version: None,
+            embedding_model: None,
+            models: anthropic_models,
+        },
+    );
+
+    // OpenAI provider
+    let mut openai_models = HashMap::new();
+    openai_models.insert(
+        "gpt-4o".to_string(),
+        ModelConfig {
+            max_tokens: 16384,
+            ..Default::default()
+        },
+    );
+    openai_models.insert(
+        "gpt-4o-mini".to_string(),
+        ModelConfig {
+            max_tokens: 16384,
+            ..Default::default()
+        },
+    );
+    providers.insert(
+        "openai".to_string(),
+        ProviderConfig {
+            api_key_env: "OPENAI_API_KEY".to_string(),
+            api_key_file: None,
+            api_key_secret: None,
+            base_url: "https://api.openai.com".to_string(),
+            api: None,
+            organization: None,
+            api_version: None,
+            embedding_model: Some("text-embedding-3-small".to_string()),
+            models: openai_models,
+        },
+    );
+
+    providers
+}
+
+impl Default for LlmConfig {
+    /// Default is only for tests - in production, `default` field MUST be in config YAML
+    fn default() -> Self {
+        Self {
+            default: "openai/gpt-4o".to_string(), // Only used in tests
+            timeout_ms: default_timeout_ms(),
+            providers: default_providers(),
+            audit: AuditConfig::default(),
+            secrets: SecretsConfig::default(),
+            embeddings: None,
+            context_strategy: ContextStrategy::default(),
+            fallback: Vec::new(),
+            failover_after: default_failover_after(),
+            failover_retry_secs: default_failover_retry_secs(),
+        }
+    }
+}
+
+/// Audit log of LLM requests and responses (`~/.taskdaemon/audit/`)
+///
+/// Every call records provider, model, token counts and SHA-256 hashes of the
+/// request and response. Full bodies are kept only with `include-bodies`, and
+/// any text matching a `redact` pattern is replaced before it is written.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditConfig {
+    /// Record every request/response
+    pub enabled: bool,
+
+    /// Also store the (redacted) request and response bodies
+    #[serde(rename = "include-bodies")]
+    pub include_bodies: bool,
+
+    /// Regex patterns whose matches are replaced with `[REDACTED]`
+    pub redact: Vec<String>,
+
+    /// Directory for the audit files (default: ~/.taskdaemon/audit)
+    pub dir: Option<String>,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            include_bodies: false,
+            redact: default_redact_patterns(),
+            dir: None,
+        }
+    }
+}
+
+/// API keys, bearer tokens and email addresses
+fn default_redact_patterns() -> Vec<String> {
+    [
+        r"sk-[A-Za-z0-9_\-]{16,}",
+        r"gh[pousr]_[A-Za-z0-9]{36,}",
+        r"AKIA[0-9A-Z]{16}",
+        r"(?i)bearer\s+[A-Za-z0-9._\-]{16,}",
+        r"[A-Za-z0-9._%+\-]+@[A-Za-z0-9.\-]+\.[A-Za-z]{2,}",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+impl AuditConfig {
+    /// Directory holding the audit files, with `~` expanded
+    pub fn dir(&self) -> Result<PathBuf> {
+        match &self.dir {
+            Some(dir) => match dir.strip_prefix("~/") {
+                Some(rest) => dirs::home_dir()
+                    .map(|h| h.join(rest))
+                    .ok_or_else(|| eyre::eyre!("Could not determine home directory")),
+                None => Ok(PathBuf::from(dir)),
+            },
+            None => dirs::home_dir()
+                .map(|h| h.join(".taskdaemon").join("audit"))
+                .ok_or_else(|| eyre::eyre!("Could not determine home directory")),
+        }
+    }
+}
+
+/// Secrets store for `api-key-secret` (`td secrets`)
+///
+/// The keychain backend uses the OS keychain. The file backend keeps all
+/// secrets in an age-encrypted file, decrypted with an identity created on
+/// first use.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecretsConfig {
+    /// Backend: keychain or file
+    pub backend: SecretBackend,
+
+    /// Encrypted secrets file (default: ~/.config/taskdaemon/secrets.age)
+    pub file: Option<String>,
+
+    /// age identity for the file (default: ~/.config/taskdaemon/secrets.key)
+    #[serde(rename = "identity-file")]
+    pub identity_file: Option<String>,
+}
+
+impl SecretsConfig {
+    /// Encrypted secrets file, with `~` expanded
+    pub fn file(&self) -> Result<PathBuf> {
+        Self::path_or_default(self.file.as_deref(), "secrets.age")
+    }
+
+    /// Identity file, with `~` expanded
+    pub fn identity_file(&self) -> Result<PathBuf> {
+        Self::path_or_default(self.identity_file.as_deref(), "secrets.key")
+    }
+
+    fn path_or_default(path: Option<&str>, default_name: &str) -> Result<PathBuf> {
+        match path {
+            Some(path) => match path.strip_prefix("~/") {
+                Some(rest) => dirs::home_dir()
+                    .map(|h| h.join(rest))
+                    .ok_or_else(|| eyre::eyre!("Could not determine home directory")),
+                None => Ok(PathBuf::from(path)),
+            },
+            None => dirs::config_dir()
+                .map(|d| d.join("taskdaemon").join(default_name))
+                .ok_or_else(|| eyre::eyre!("Could not determine config directory")),
+        }
+    }
+}
+
+/// Concurrency limits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConcurrencyConfig {
+    /// Maximum concurrent loops
+    #[serde(rename = "max-loops")]
+    pub max_loops: u32,
+
+    /// Maximum concurrent API calls
+    #[serde(rename = "max-api-calls")]
+    pub max_api_calls: u32,
+
+    /// Maximum worktrees
+    #[serde(rename = "max-worktrees")]
+    pub max_worktrees: u32,
+
+    /// Seconds of waiting that raise a queued execution's priority one level (0 disables aging)
+    #[serde(rename = "aging-secs")]
+    pub aging_secs: u64,
+
+    /// Queue ordering: strict priority or weighted fair share per loop type
+    pub fairness: FairnessPolicy,
+
+    /// Share of slots per loop type under the weighted policy (unlisted types weigh 1)
+    pub weights: BTreeMap<String, u32>,
+
+    /// Consecutive provider errors that open the circuit breaker (0 disables it)
+    #[serde(rename = "breaker-threshold")]
+    pub breaker_threshold: u32,
+
+    /// Seconds between probes of the provider while the circuit is open
+    #[serde(rename = "breaker-probe-secs")]
+    pub breaker_probe_secs: u64,
+}
+
+impl Default for ConcurrencyConfig {
+    fn default() -> Self {
+        Self {
+            max_loops: 50,
+            max_api_calls: 10,
+            max_worktrees: 50,
+            aging_secs: 600,
+            fairness: FairnessPolicy::Strict,
+            weights: BTreeMap::new(),
+            breaker_threshold: 5,
+            breaker_probe_secs: 30,
+        }
+    }
+}
+
+impl ConcurrencyConfig {
+    /// Scheduler configuration for these limits and queue policy
+    pub fn scheduler_config(&self) -> SchedulerConfig {
+        debug!(?self.fairness, self.aging_secs, "ConcurrencyConfig::scheduler_config: called");
+        SchedulerConfig {
+            max_concurrent: self.max_api_calls as usize,
+            aging_secs: self.aging_secs,
+            fairness: self.fairness,
+            weights: self.weights.clone(),
+            breaker_threshold: self.breaker_threshold,
+            breaker_probe_secs: self.breaker_probe_secs,
+            ..Default::default()
+        }
+    }
+}
+
+/// Validation defaults
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ValidationConfig {
+    /// Default validation command
+    pub command: String,
+
+    /// Iteration timeout in milliseconds
+    #[serde(rename = "iteration-timeout-ms")]
+    pub iteration_timeout_ms: u64,
+
+    /// Maximum iterations before giving up
+    #[serde(rename = "max-iterations")]
+    pub max_iterations: u32,
+}
+
+impl Default for ValidationConfig {
+    fn default() -> Self {
+        Self {
+            command: "otto ci".to_string(),
+            iteration_timeout_ms: 300_000,
+            max_iterations: 100,
+        }
+    }
+}
+
+/// Git configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GitConfig {
+    /// Directory for git worktrees
+    #[serde(rename = "worktree-dir")]
+    pub worktree_dir: PathBuf,
+
+    /// Disk quota for worktrees in GB; above it the oldest finished worktrees are pruned (0 = no quota)
+    #[serde(rename = "disk-quota-gb")]
+    pub disk_quota_gb: u32,
+
+    /// Days to keep the worktree of a finished execution (0 = remove when the loop ends)
+    #[serde(rename = "prune-after-days")]
+    pub prune_after_days: u32,
+
+    /// Shared CARGO_TARGET_DIR for commands run in worktrees (None = each worktree builds into its own target/)
+    #[serde(rename = "cargo-target-dir")]
+    pub cargo_target_dir: Option<PathBuf>,
+}
+
+impl Default for GitConfig {
+    fn default() -> Self {
+        Self {
+            worktree_dir: PathBuf::from("/tmp/taskdaemon/worktrees"),
+            disk_quota_gb: 100,
+            prune_after_days: 0,
+            cargo_target_dir: None,
+        }
+    }
+}
+
+/// Storage configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// Directory for TaskStore data
+    #[serde(rename = "taskstore-dir")]
+    pub taskstore_dir: String,
+
+    /// Warning threshold for JSONL file size in MB
+    #[serde(rename = "jsonl-warn-mb")]
+    pub jsonl_warn_mb: u32,
+
+    /// Error threshold for JSONL file size in MB
+    #[serde(rename = "jsonl-error-mb")]
+    pub jsonl_error_mb: u32,
+
+    /// Check execution records against the event log when the daemon starts
+    #[serde(rename = "fsck-on-start")]
+    pub fsck_on_start: FsckMode,
+}
+
+/// What the daemon does with `td fsck` at startup
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FsckMode {
+    /// Skip the check
+    #[default]
+    Off,
+    /// Log discrepancies
+    Report,
+    /// Log discrepancies and repair the records
+    Repair,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        // Use XDG data directory (~/.local/share/taskdaemon on Linux)
+        let taskstore_dir = dirs::data_dir()
+            .map(|d| d.join("taskdaemon"))
+            .unwrap_or_else(|| PathBuf::from(".taskstore"))
+            .to_string_lossy()
+            .into_owned();
+
+        Self {
+            taskstore_dir,
+            jsonl_warn_mb: 100,
+            jsonl_error_mb: 500,
+            fsck_on_start: FsckMode::Off,
+        }
+    }
+}
+
+/// Progress strategy configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProgressConfig {
+    /// Progress strategy (currently only "system-captured" supported)
+    pub strategy: String,
+
+    /// Maximum number of iterations to keep in progress
+    #[serde(rename = "max-entries")]
+    pub max_entries: usize,
+
+    /// Maximum output characters per iteration
+    #[serde(rename = "max-output-chars")]
+    pub max_output_chars: usize,
+}
+
+impl Default for ProgressConfig {
+    fn default() -> Self {
+        Self {
+            strategy: "system-captured".to_string(),
+            max_entries: 5,
+            max_output_chars: 500,
+        }
+    }
+}
+
+/// Loop type paths configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoopsConfig {
+    /// Paths to search for loop type definitions (searched in order)
+    pub paths: Vec<String>,
+
+    /// Environment selecting loop types' `when:` overrides (default: `ci` if $CI is set, else `local`)
+    pub environment: Option<String>,
+}
+
+impl Default for LoopsConfig {
+    fn default() -> Self {
+        Self {
+            paths: vec![
+                "builtin".to_string(),
+                "~/.config/taskdaemon/loops".to_string(),
+                ".taskdaemon/loops".to_string(),
+            ],
+            environment: None,
+        }
+    }
+}
+
+impl LoopsConfig {
+    /// Expand paths (resolve ~/ and relative paths)
+    pub fn expanded_paths(&self) -> Vec<PathBuf> {
+        debug!(?self.paths, "LoopsConfig::expanded_paths: called");
+        let paths: Vec<PathBuf> = self
+            .paths
+            .iter()
+            .filter_map(|p| {
+                if p == "builtin" {
+                    debug!(%p, "LoopsConfig::expanded_paths: skipping builtin");
+                    None // builtin is handled specially
+                } else if p.starts_with("~/") {
+                    debug!(%p, "LoopsConfig::expanded_paths: expanding home path");
+                    dirs::home_dir().map(|home| home.join(&p[2..]))
+                } else {
+                    debug!(%p, "LoopsConfig::expanded_paths: using as-is");
+                    Some(PathBuf::from(p))
+                }
+            })
+            .collect();
+        debug!(?paths, "LoopsConfig::expanded_paths: returning paths");
+        paths
+    }
+
+    /// Environment selecting loop types' `when:` overrides
+    pub fn environment(&self) -> String {
+        if let Some(environment) = &self.environment {
+            return environment.clone();
+        }
+        let ci = std::env::var("CI").is_ok_and(|v| !v.is_empty() && v != "0" && !v.eq_ignore_ascii_case("false"));
+        let environment = if ci { "ci" } else { "local" };
+        debug!(environment, "LoopsConfig::environment: from $CI");
+        environment.to_string()
+    }
+
+    /// Check if builtin types should be loaded
+    pub fn use_builtin(&self) -> bool {
+        debug!("LoopsConfig::use_builtin: called");
+        let result = self.paths.iter().any(|p| p == "builtin");
+        debug!(result, "LoopsConfig::use_builtin: returning");
+        result
+    }
+}
+
+/// Tool plugins
+///
+/// ```yaml
+/// tools:
+///   wasm:
+///     paths: [~/.config/taskdaemon/tools, .taskdaemon/tools]
+///     fuel: 1000000000
+///     max-memory-mb: 64
+///   worker:
+///     enabled: true
+///     memory-mb: 4096
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolsConfig {
+    /// Sandboxed WebAssembly tools (needs the `wasm` feature)
+    pub wasm: WasmToolsConfig,
+
+    /// Run built-in tools in a worker process per execution
+    pub worker: ToolWorkerConfig,
+}
+
+/// Worker processes that run an execution's file and command tools
+///
+/// A tool that panics or exhausts memory then kills only its execution's
+/// worker, which is restarted for the next call, instead of the daemon.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolWorkerConfig {
+    /// Run tools in workers (default: in the daemon process)
+    pub enabled: bool,
+
+    /// `td` binary to start workers from (default: the running executable)
+    ///
+    /// Programs embedding the daemon must set this.
+    pub program: Option<PathBuf>,
+
+    /// Address space cap for each worker and the commands it runs, in MB
+    #[serde(rename = "memory-mb")]
+    pub memory_mb: Option<u64>,
+}
+
+/// Where WebAssembly tools are discovered and the limits each call runs under
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WasmToolsConfig {
+    /// `.wasm` files, or directories whose `.wasm` files are all loaded
+    pub paths: Vec<String>,
+
+    /// Instructions a single call may execute before it is stopped
+    pub fuel: u64,
+
+    /// Linear memory a module may grow to, in MiB
+    #[serde(rename = "max-memory-mb")]
+    pub max_memory_mb: u32,
+}
+
+impl Default for WasmToolsConfig {
+    fn default() -> Self {
+        Self {
+            paths: vec!["~/.config/taskdaemon/tools".to_string(), ".taskdaemon/tools".to_string()],
+            fuel: 1_000_000_000,
+            max_memory_mb: 64,
+        }
+    }
+}
+
+impl WasmToolsConfig {
+    /// Module files found under `paths`, sorted within each directory
+    ///
+    /// Missing paths are skipped, so the defaults cost nothing when unused.
+    pub fn module_files(&self) -> Vec<PathBuf> {
+        debug!(?self.paths, "WasmToolsConfig::module_files: called");
+        wasm_module_files(&self.paths)
+    }
+}
+
+/// `.wasm` files named by `paths` or found in them (`~/` expanded), sorted within each directory
+fn wasm_module_files(paths: &[String]) -> Vec<PathBuf> {
+    debug!(?paths, "wasm_module_files: called");
+    let mut files = Vec::new();
+    for p in paths {
+        let path = match p.strip_prefix("~/") {
+            Some(rest) => match dirs::home_dir() {
+                Some(home) => home.join(rest),
+                None => continue,
+            },
+            None => PathBuf::from(p),
+        };
+        if path.is_file() {
+            files.push(path);
+        } else if let Ok(entries) = fs::read_dir(&path) {
+            let mut found: Vec<PathBuf> = entries
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|f| f.is_file() && f.extension().is_some_and(|ext| ext == "wasm"))
+                .collect();
+            found.sort();
+            files.extend(found);
+        } else {
+            debug!(?path, "wasm_module_files: path not found, skipping");
+        }
+    }
+    debug!(count = files.len(), "wasm_module_files: returning");
+    files
+}
+
+/// WebAssembly policy modules consulted before executions start, tool calls run and branches merge
+///
+/// ```yaml
+/// policies:
+///   paths: [~/.config/taskdaemon/policies, .taskdaemon/policies]
+///   fuel: 100000000
+///   max-memory-mb: 16
+///   on-error: deny
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PoliciesConfig {
+    /// `.wasm` files, or directories whose `.wasm` files are all loaded (in order)
+    pub paths: Vec<String>,
+
+    /// Instructions a single evaluation may execute before it is stopped
+    pub fuel: u64,
+
+    /// Linear memory a module may grow to, in MiB
+    #[serde(rename = "max-memory-mb")]
+    pub max_memory_mb: u32,
+
+    /// Verdict when a module traps, runs out of fuel or returns garbage
+    #[serde(rename = "on-error")]
+    pub on_error: PolicyErrorMode,
+}
+
+impl Default for PoliciesConfig {
+    fn default() -> Self {
+        Self {
+            paths: vec![
+                "~/.config/taskdaemon/policies".to_string(),
+                ".taskdaemon/policies".to_string(),
+            ],
+            fuel: 100_000_000,
+            max_memory_mb: 16,
+            on_error: PolicyErrorMode::default(),
+        }
+    }
+}
+
+impl PoliciesConfig {
+    /// Module files found under `paths`, sorted within each directory
+    pub fn module_files(&self) -> Vec<PathBuf> {
+        debug!(?self.paths, "PoliciesConfig::module_files: called");
+        wasm_module_files(&self.paths)
+    }
+}
+
+/// Self-evaluation configuration
+///
+/// When enabled, code-producing loops that pass validation are scored by a
+/// separate (usually cheaper) model against the acceptance criteria in their
+/// parent document. Executions scoring below the threshold are blocked for
+/// human review instead of being merged.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EvaluationConfig {
+    /// Run the evaluation pass before merging
+    pub enabled: bool,
+
+    /// Judge model in "provider/model" format (defaults to llm.default)
+    pub model: Option<String>,
+
+    /// Minimum mean rubric score (0-10) required to auto-merge
+    pub threshold: f64,
+
+    /// Maximum diff characters sent to the judge
+    #[serde(rename = "max-diff-chars")]
+    pub max_diff_chars: usize,
+}
+
+impl Default for EvaluationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            model: None,
+            threshold: 7.0,
+            max_diff_chars: 30_000,
+        }
+    }
+}
+
+/// Risk scoring before auto-merge (see [`crate::r#loop::assess_risk`])
+///
+/// Completed code loops get a score from their diff; at or above
+/// `approve-at` the merge waits for `td exec approve`, below it the work
+/// merges as usual.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskConfig {
+    /// Score diffs before merging
+    pub enabled: bool,
+
+    /// Score at or above which a human must approve the merge
+    #[serde(rename = "approve-at")]
+    pub approve_at: u32,
+
+    /// Points added when any changed file matches the glob
+    pub sensitive: BTreeMap<String, u32>,
+
+    /// Points per 100 changed lines (insertions plus deletions)
+    #[serde(rename = "points-per-100-lines")]
+    pub points_per_100_lines: u32,
+
+    /// Globs of test files, for the test delta
+    #[serde(rename = "test-globs")]
+    pub test_globs: Vec<String>,
+
+    /// Points when non-test files changed but no test file did
+    #[serde(rename = "untested-points")]
+    pub untested_points: u32,
+
+    /// Points when the diff removes more test lines than it adds
+    #[serde(rename = "tests-removed-points")]
+    pub tests_removed_points: u32,
+}
+
+impl Default for RiskConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            approve_at: 50,
+            sensitive: BTreeMap::from([
+                ("**/migrations/**".to_string(), 40),
+                (".github/workflows/**".to_string(), 30),
+                ("**/Cargo.lock".to_string(), 10),
+            ]),
+            points_per_100_lines: 5,
+            test_globs: vec![
+                "**/tests/**".to_string(),
+                "**/*_test.*".to_string(),
+                "**/test_*".to_string(),
+                "**/*.test.*".to_string(),
+                "**/*_spec.*".to_string(),
+            ],
+            untested_points: 15,
+            tests_removed_points: 25,
+        }
+    }
+}
+
+/// Diff reviews by the `review` loop type (see [`crate::review`])
+///
+/// Completed reviews are always stored as artifacts. With `gate-merges`, a
+/// completed code loop whose latest review has blocking comments is held
+/// instead of merged; a later review without them releases it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReviewConfig {
+    /// Hold merges of executions whose latest review has blocking comments
+    #[serde(rename = "gate-merges")]
+    pub gate_merges: bool,
+
+    /// Post completed reviews to the branch's pull request
+    pub github: GithubConfig,
+}
+
+/// GitHub repository that reviews are posted to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GithubConfig {
+    /// `owner/name` of the repository (unset: don't post)
+    pub repo: Option<String>,
+
+    /// Environment variable holding a token allowed to review pull requests
+    #[serde(rename = "token-env")]
+    pub token_env: String,
+
+    /// REST API root (GitHub Enterprise: `https://HOST/api/v3`)
+    #[serde(rename = "api-url")]
+    pub api_url: String,
+}
+
+impl Default for GithubConfig {
+    fn default() -> Self {
+        Self {
+            repo: None,
+            token_env: "GITHUB_TOKEN".to_string(),
+            api_url: "https://api.github.com".to_string(),
+        }
+    }
+}
+
+/// Notification configuration
+///
+/// The daemon sends desktop notifications (so they arrive with the TUI
+/// closed); a running TUI rings the terminal bell.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    /// Send desktop notifications (notify-send on Linux, osascript on macOS)
+    pub desktop: bool,
+
+    /// Ring the terminal bell in the TUI
+    pub bell: bool,
+
+    /// Notify when an execution completes
+    #[serde(rename = "on-complete")]
+    pub on_complete: bool,
+
+    /// Notify when an execution fails
+    #[serde(rename = "on-failed")]
+    pub on_failed: bool,
+
+    /// Notify when an execution is waiting for approval (draft or blocked)
+    #[serde(rename = "on-waiting-approval")]
+    pub on_waiting_approval: bool,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            desktop: false,
+            bell: false,
+            on_complete: true,
+            on_failed: true,
+            on_waiting_approval: true,
+        }
+    }
+}
+
+/// Event log rotation and compaction (`~/.taskdaemon/runs/{id}/`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventLogConfig {
+    /// Rotate the active segment once it reaches this many bytes
+    #[serde(rename = "max-segment-bytes")]
+    pub max_segment_bytes: u64,
+
+    /// Rotate the active segment once its first event is this old
+    #[serde(rename = "max-segment-age-hours")]
+    pub max_segment_age_hours: u64,
+
+    /// Compact an execution's log once it has been idle this long
+    #[serde(rename = "compact-after-hours")]
+    pub compact_after_hours: u64,
+
+    /// How often the daemon looks for logs to compact
+    #[serde(rename = "compaction-interval-secs")]
+    pub compaction_interval_secs: u64,
+}
+
+impl Default for EventLogConfig {
+    fn default() -> Self {
+        Self {
+            max_segment_bytes: 16 * 1024 * 1024,
+            max_segment_age_hours: 24,
+            compact_after_hours: 24,
+            compaction_interval_secs: 3600,
+        }
+    }
+}
+
+/// Coalescing of streamed token deltas into fewer `TokenReceived` events
+///
+/// Keeps the event bus and its subscribers (TUI, logger) from lagging when many
+/// loops stream at once. The logged text is unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StreamingConfig {
+    /// Flush buffered tokens after this many milliseconds
+    #[serde(rename = "token-flush-ms")]
+    pub token_flush_ms: u64,
+
+    /// Flush buffered tokens once this many bytes are buffered (0 = one event per delta)
+    #[serde(rename = "token-flush-bytes")]
+    pub token_flush_bytes: usize,
+}
+
+impl Default for StreamingConfig {
+    fn default() -> Self {
+        Self {
+            token_flush_ms: 50,
+            token_flush_bytes: 512,
+        }
+    }
+}
+
+impl StreamingConfig {
+    /// Batching settings for the event bus (None if disabled)
+    pub fn token_batching(&self) -> Option<TokenBatching> {
+        debug!(?self, "StreamingConfig::token_batching: called");
+        (self.token_flush_bytes > 0).then(|| TokenBatching {
+            flush_interval: std::time::Duration::from_millis(self.token_flush_ms),
+            max_bytes: self.token_flush_bytes,
+        })
+    }
+}
+
+/// Daemon self-monitoring (see [`crate::resources`])
+///
+/// While any set limit is exceeded the daemon stops picking up executions;
+/// running ones continue. Unset limits are not checked.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourceMonitorConfig {
+    /// Seconds between samples (0 disables monitoring)
+    #[serde(rename = "interval-secs")]
+    pub interval_secs: u64,
+
+    /// Resident memory of the daemon process, in MB
+    #[serde(rename = "max-rss-mb")]
+    pub max_rss_mb: Option<u64>,
+
+    /// CPU use of the daemon process, in percent of one core
+    #[serde(rename = "max-cpu-percent")]
+    pub max_cpu_percent: Option<f64>,
+
+    /// Open file descriptors of the daemon process
+    #[serde(rename = "max-open-files")]
+    pub max_open_files: Option<usize>,
+
+    /// Alive tokio tasks in the daemon
+    #[serde(rename = "max-tasks")]
+    pub max_tasks: Option<usize>,
+}
+
+impl Default for ResourceMonitorConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 15,
+            max_rss_mb: None,
+            max_cpu_percent: None,
+            max_open_files: None,
+            max_tasks: None,
+        }
+    }
+}
+
+/// Duplicate task detection on submission (see [`crate::dedup`])
+///
+/// A new execution is compared with unfinished executions of the same loop
+/// type created within the window; one at least `threshold` similar is a
+/// duplicate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DedupConfig {
+    /// What to do with a duplicate
+    pub mode: DedupMode,
+
+    /// Word-overlap similarity (0.0-1.0) at which two tasks count as duplicates
+    pub threshold: f64,
+
+    /// Only executions created this many hours ago or later are compared
+    #[serde(rename = "window-hours")]
+    pub window_hours: u64,
+}
+
+impl Default for DedupConfig {
+    fn default() -> Self {
+        Self {
+            mode: DedupMode::Warn,
+            threshold: 0.85,
+            window_hours: 24,
+        }
+    }
+}
+
+/// What happens when a submitted task duplicates an existing one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DedupMode {
+    /// No check
+    Off,
+    /// Create it anyway and point at the existing execution
+    #[default]
+    Warn,
+    /// Refuse to create it
+    Block,
+}
+
+/// HTTP trigger endpoint (see [`crate::webhook`])
+///
+/// Each route accepts signed POSTs that create pending executions. No
+/// server runs unless `listen` is set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    /// Address to listen on, e.g. `127.0.0.1:8787` (unset: no webhook server)
+    pub listen: Option<String>,
+
+    /// Largest accepted request body, in bytes
+    #[serde(rename = "max-body-bytes")]
+    pub max_body_bytes: usize,
+
+    /// Routes and their shared secrets
+    pub routes: Vec<WebhookRoute>,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            listen: None,
+            max_body_bytes: 256 * 1024,
+            routes: Vec::new(),
+        }
+    }
+}
+
+/// One webhook route
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookRoute {
+    /// URL path, e.g. `/hooks/issues`
+    pub path: String,
+
+    /// Environment variable holding the shared secret
+    #[serde(rename = "secret-env", default)]
+    pub secret_env: Option<String>,
+
+    /// File holding the shared secret (used if `secret-env` is unset or empty)
+    #[serde(rename = "secret-file", default)]
+    pub secret_file: Option<String>,
+
+    /// Loop types this route may start (empty: any loaded type)
+    #[serde(rename = "loop-types", default)]
+    pub loop_types: Vec<String>,
+
+    /// Requests accepted per minute (0: unlimited)
+    #[serde(rename = "rate-limit", default = "default_webhook_rate_limit")]
+    pub rate_limit: u32,
+
+    /// Tags added to every execution the route creates
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl WebhookRoute {
+    /// Read the shared secret from `secret-env` or `secret-file`
+    pub fn secret(&self) -> Result<String> {
+        if let Some(var) = &self.secret_env
+            && let Ok(secret) = std::env::var(var)
+            && !secret.is_empty()
+        {
+            debug!(path = %self.path, env_var = %var, "WebhookRoute::secret: found in environment");
+            return Ok(secret);
+        }
+        if let Some(file_path) = &self.secret_file {
+            let expanded = if let Some(rest) = file_path.strip_prefix("~/") {
+                dirs::home_dir()
+                    .map(|h| h.join(rest))
+                    .unwrap_or_else(|| PathBuf::from(file_path))
+            } else {
+                PathBuf::from(file_path)
+            };
+            let secret = fs::read_to_string(&expanded)
+                .context(format!("Failed to read webhook secret from {}", expanded.display()))?
+                .trim()
+                .to_string();
+            if !secret.is_empty() {
+                debug!(path = %self.path, file = %expanded.display(), "WebhookRoute::secret: found in file");
+                return Ok(secret);
+            }
+        }
+        Err(eyre::eyre!(
+            "Webhook route {} has no secret. Set secret-env or secret-file.",
+            self.path
+        ))
+    }
+}
+
+fn default_webhook_rate_limit() -> u32 {
+    30
+}
+
+/// gRPC control API (see `crate::grpc`, built with the `grpc` feature)
+///
+/// No server runs unless `listen` is set. When a token is configured every
+/// call must carry `authorization: Bearer <token>`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GrpcConfig {
+    /// Address to listen on, e.g. `127.0.0.1:50051` (unset: no gRPC server)
+    pub listen: Option<String>,
+
+    /// Environment variable holding the bearer token
+    #[serde(rename = "token-env")]
+    pub token_env: Option<String>,
+
+    /// File holding the bearer token (used if `token-env` is unset or empty)
+    #[serde(rename = "token-file")]
+    pub token_file: Option<String>,
+}
+
+impl GrpcConfig {
+    /// Read the bearer token from `token-env` or `token-file` (None: no auth)
+    pub fn token(&self) -> Result<Option<String>> {
+        if let Some(var) = &self.token_env
+            && let Ok(token) = std::env::var(var)
+            && !token.is_empty()
+        {
+            debug!(env_var = %var, "GrpcConfig::token: found in environment");
+            return Ok(Some(token));
+        }
+        if let Some(file_path) = &self.token_file {
+            let expanded = if let Some(rest) = file_path.strip_prefix("~/") {
+                dirs::home_dir()
+                    .map(|h| h.join(rest))
+                    .unwrap_or_else(|| PathBuf::from(file_path))
+            } else {
+                PathBuf::from(file_path)
+            };
+            let token = fs::read_to_string(&expanded)
+                .context(format!("Failed to read gRPC token from {}", expanded.display()))?
+                .trim()
+                .to_string();
+            if !token.is_empty() {
+                debug!(file = %expanded.display(), "GrpcConfig::token: found in file");
+                return Ok(Some(token));
+            }
+        }
+        if self.token_env.is_some() || self.token_file.is_some() {
+            return Err(eyre::eyre!("grpc token-env/token-file is set but no token was found"));
+        }
+        Ok(None)
+    }
+}
+
+/// Chat bridge (see [`crate::chat`])
+///
+/// Posts the same transitions as desktop notifications to a channel and,
+/// when `command-path` is set, takes commands through the webhook server.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatConfig {
+    /// `slack` or `discord` (unset: no chat bridge)
+    pub platform: Option<ChatPlatform>,
+
+    /// Environment variable holding the channel's incoming-webhook URL
+    #[serde(rename = "webhook-url-env")]
+    pub webhook_url_env: Option<String>,
+
+    /// Path on the webhook server that receives commands, e.g. `/chat/slack`
+    #[serde(rename = "command-path")]
+    pub command_path: Option<String>,
+
+    /// Environment variable holding the secret incoming commands are signed with
+    #[serde(rename = "signing-secret-env")]
+    pub signing_secret_env: Option<String>,
+
+    /// Users allowed to run each command: command -> user IDs (`*`: anyone)
+    pub allow: BTreeMap<String, Vec<String>>,
+}
+
+/// Attribution of submitted executions
+///
+/// Executions record the OS user that submitted them; `name` adds a display
+/// name shown next to it in listings and notifications.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserConfig {
+    /// Display name, e.g. "Alice Liddell"
+    pub name: Option<String>,
+}
+
+impl UserConfig {
+    /// The submitter recorded on executions created by this process
+    pub fn submitter(&self) -> Option<Submitter> {
+        debug!(?self.name, "UserConfig::submitter: called");
+        Submitter::current(self.name.as_deref())
+    }
+}
+
+/// TUI configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TuiConfig {
+    /// Split-pane layout (REPL/list on one side, pinned execution on the other)
+    pub layout: LayoutConfig,
+
+    /// Color theme: a built-in name (default, light, high-contrast) or a
+    /// custom theme file `<themes-dir>/<name>.toml`
+    pub theme: String,
+
+    /// Directory for custom theme files (default: ~/.config/taskdaemon/themes)
+    #[serde(rename = "themes-dir")]
+    pub themes_dir: Option<PathBuf>,
+
+    /// Key remapping: action name -> key (e.g. `quit: Q`, `cycle-split: ctrl-s`)
+    pub keys: BTreeMap<String, String>,
+
+    /// Restore the most recent REPL session when the TUI starts
+    #[serde(rename = "restore-session")]
+    pub restore_session: bool,
+
+    /// Saved execution filters: name -> filter query (e.g. `failing-backend: status:failed tag:backend`)
+    pub filters: BTreeMap<String, String>,
+}
+
+impl Default for TuiConfig {
+    fn default() -> Self {
+        Self {
+            layout: LayoutConfig::default(),
+            theme: "default".to_string(),
+            themes_dir: None,
+            keys: BTreeMap::new(),
+            restore_session: false,
+            filters: BTreeMap::new(),
+        }
+    }
+}
+
+/// How the main area is split when an execution is pinned
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SplitMode {
+    /// Single pane
+    #[default]
+    Off,
+    /// Side by side (pinned pane on the right)
+    Horizontal,
+    /// Stacked (pinned pane below)
+    Vertical,
+}
+
+impl SplitMode {
+    /// Next mode in the Off -> Horizontal -> Vertical cycle
+    pub fn next(self) -> Self {
+        debug!(?self, "SplitMode::next: called");
+        match self {
+            Self::Off => Self::Horizontal,
+            Self::Horizontal => Self::Vertical,
+            Self::Vertical => Self::Off,
+        }
+    }
+}
+
+/// Split-pane layout preferences, persisted when changed from the TUI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutConfig {
+    /// Split mode
+    pub split: SplitMode,
+
+    /// Share of the main area given to the primary pane, in percent
+    pub ratio: u16,
+}
+
+impl LayoutConfig {
+    /// Smallest/largest primary pane share, so neither pane disappears
+    pub const MIN_RATIO: u16 = 20;
+    pub const MAX_RATIO: u16 = 80;
+
+    /// Grow (positive) or shrink (negative) the primary pane
+    pub fn resize(&mut self, delta: i16) {
+        debug!(ratio = self.ratio, delta, "LayoutConfig::resize: called");
+        self.ratio = (self.ratio as i16 + delta).clamp(Self::MIN_RATIO as i16, Self::MAX_RATIO as i16) as u16;
+    }
+
+    /// Ratio clamped to the allowed range (config files may hold anything)
+    pub fn primary_percent(&self) -> u16 {
+        self.ratio.clamp(Self::MIN_RATIO, Self::MAX_RATIO)
+    }
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            split: SplitMode::Off,
+            ratio: 60,
+        }
+    }
+}
+
+/// Persist TUI layout preferences to a config file
+///
+/// Writes `tui.layout` into `path`, or into the config file `Config::load`
+/// would pick when `path` is None (creating the user config if no file
+/// exists). Other keys are preserved, but YAML comments are not.
+pub fn save_tui_layout(path: Option<&Path>, layout: &LayoutConfig) -> Result<PathBuf> {
+    debug!(?path, ?layout, "save_tui_layout: called");
+    let path = match path {
+        Some(p) => p.to_path_buf(),
+        None => {
+            let local = PathBuf::from(".taskdaemon.yml");
+            if local.exists() {
+                local
+            } else {
+                dirs::config_dir()
+                    .ok_or_else(|| eyre::eyre!("Could not determine config directory"))?
+                    .join("taskdaemon")
+                    .join("taskdaemon.yml")
+            }
+        }
+    };
+
+    let mut doc: serde_yaml::Value = if path.exists() {
+        debug!(?path, "save_tui_layout: updating existing file");
+        let content = fs::read_to_string(&path).context("Failed to read config file")?;
+        serde_yaml::from_str(&content).context("Failed to parse config file")?
+    } else {
+        debug!(?path, "save_tui_layout: creating new file");
+        serde_yaml::Value::Null
+    };
+    if !doc.is_mapping() {
+        doc = serde_yaml::Value::Mapping(Default::default());
+    }
+
+    let root = doc.as_mapping_mut().expect("mapping");
+    let tui = root
+        .entry("tui".into())
+        .or_insert_with(|| serde_yaml::Value::Mapping(Default::default()));
+    if !tui.is_mapping() {
+        *tui = serde_yaml::Value::Mapping(Default::default());
+    }
+    tui.as_mapping_mut()
+        .expect("mapping")
+        .insert("layout".into(), serde_yaml::to_value(layout)?);
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).context("Failed to create config directory")?;
+    }
+    fs::write(&path, serde_yaml::to_string(&doc)?).context("Failed to write config file")?;
+    debug!(?path, "save_tui_layout: saved");
+    Ok(path)
+}
+
+/// Debug configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DebugConfig {
+    /// Log REPL conversations to files for debugging
+    /// Files are written to ~/.taskdaemon/conversations/
+    #[serde(rename = "log-conversations")]
+    pub log_conversations: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config() {
+        let config = Config::default();
+
+        assert!(config.llm.default.starts_with("openai/"));
+        assert_eq!(config.concurrency.max_loops, 50);
+        assert_eq!(config.validation.max_iterations, 100);
+        assert_eq!(config.dedup.mode, DedupMode::Warn);
+    }
+
+    #[test]
+    fn test_llm_config_defaults() {
+        let config = LlmConfig::default();
+
+        // Check default is set to openai/gpt-4o
+        assert!(config.default.contains("openai"));
+        assert!(config.default.contains("gpt-4o"));
+
+        // Check providers are set up
+        assert!(config.providers.contains_key("anthropic"));
+        assert!(config.providers.contains_key("openai"));
+
+        // Check openai provider config
+        let openai = config.providers.get("openai").unwrap();
+        assert_eq!(openai.api_key_env, "OPENAI_API_KEY");
+        assert_eq!(openai.base_url, "https://api.openai.com");
+    }
+
+    #[test]
+    fn test_llm_config_resolve() {
+        let config = LlmConfig::default();
+
+        // Should resolve the default successfully
+        let resolved = config.resolve().unwrap();
+        assert_eq!(resolved.provider, "openai");
+        assert!(resolved.model.contains("gpt-4o"));
+        assert_eq!(resolved.api_key_env, "OPENAI_API_KEY");
+    }
+
+    #[test]
+    fn test_llm_config_resolve_openai() {
+        let config = LlmConfig {
+            default: "openai/gpt-4o".to_string(),
+            ..Default::default()
+        };
+
+        let resolved = config.resolve().unwrap();
+        assert_eq!(resolved.provider, "openai");
+        assert_eq!(resolved.model, "gpt-4o");
+        assert_eq!(resolved.api_key_env, "OPENAI_API_KEY");
+        assert_eq!(resolved.max_tokens, 16384);
+    }
+
+    #[test]
+    fn test_llm_config_resolve_model() {
+        let config = LlmConfig::default();
+
+        let resolved = config.resolve_model("openai/gpt-4o-mini").unwrap();
+        assert_eq!(resolved.model, "gpt-4o-mini");
+        assert!(config.resolve_model("openai/unknown").is_err());
+    }
+
+    #[test]
+    fn test_evaluation_config_defaults() {
+        let config: Config = serde_yaml::from_str("evaluation:\n  enabled: true\n").unwrap();
+
+        assert!(config.evaluation.enabled);
+        assert!(config.evaluation.model.is_none());
+        assert_eq!(config.evaluation.threshold, 7.0);
+    }
+
+    #[test]
+    fn test_save_tui_layout_preserves_other_keys() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("taskdaemon.yml");
+        fs::write(
+            &path,
+            "llm:\n  default: anthropic/claude-sonnet-4\ntui:\n  layout:\n    ratio: 50\n",
+        )
+        .unwrap();
+
+        let layout = LayoutConfig {
+            split: SplitMode::Vertical,
+            ratio: 70,
+        };
+        save_tui_layout(Some(&path), &layout).unwrap();
+
+        let config = Config::load(Some(&path)).unwrap();
+        assert_eq!(config.llm.default, "anthropic/claude-sonnet-4");
+        assert_eq!(config.tui.layout, layout);
+        assert_eq!(config.source.as_deref(), Some(path.as_path()));
+    }
+
+    #[test]
+    fn test_tui_theme_and_keys() {
+        let config: Config =
+            serde_yaml::from_str("tui:\n  theme: light\n  themes-dir: /tmp/themes\n  keys:\n    quit: Q\n").unwrap();
+        assert_eq!(config.tui.theme, "light");
+        assert_eq!(config.tui.themes_dir, Some(PathBuf::from("/tmp/themes")));
+        assert_eq!(config.tui.keys.get("quit").map(String::as_str), Some("Q"));
+        assert_eq!(Config::default().tui.theme, "default");
+    }
+
+    #[test]
+    fn test_tui_saved_filters() {
+        let config: Config = serde_yaml::from_str(
+            "tui:\n  filters:\n    mine: \"tag:mine\"\n    failing-backend: \"status:failed tag:backend\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.tui.filters.get("failing-backend").map(String::as_str),
+            Some("status:failed tag:backend")
+        );
+        assert!(Config::default().tui.filters.is_empty());
+    }
+
+    #[test]
+    fn test_layout_resize_clamps() {
+        let mut layout = LayoutConfig::default();
+        layout.resize(50);
+        assert_eq!(layout.ratio, LayoutConfig::MAX_RATIO);
+        layout.resize(-100);
+        assert_eq!(layout.ratio, LayoutConfig::MIN_RATIO);
+        assert_eq!(SplitMode::Vertical.next(), SplitMode::Off);
+    }
+
+    #[test]
+    fn test_llm_config_context_window_settings() {
+        let yaml = "default: local/qwen\ncontext-strategy: drop-oldest-turns\nproviders:\n  local:\n    api: openai\n    base-url: http://localhost:8000\n    models:\n      qwen:\n        max-tokens: 4096\n        context-window: 32768\n";
+        let config: LlmConfig = serde_yaml::from_str(yaml).unwrap();
+        let resolved = config.resolve().unwrap();
+        assert_eq!(resolved.context_window, Some(32768));
+        assert_eq!(resolved.context_strategy, ContextStrategy::DropOldestTurns);
+        assert_eq!(
+            LlmConfig::default().resolve().unwrap().context_strategy,
+            ContextStrategy::Error
+        );
+    }
+
+    #[test]
+    fn test_llm_config_resolve_chain() {
+        let config: LlmConfig = serde_yaml::from_str(
+            "default: anthropic/claude-sonnet-4-20250514\nfallback: [openai/gpt-4o]\nfailover-after: 2\n",
+        )
+        .unwrap();
+        assert_eq!(config.failover_after, 2);
+        assert_eq!(config.failover_retry_secs, 60);
+
+        let chain = config.resolve_chain().unwrap();
+        let models: Vec<_> = chain.iter().map(|r| format!("{}/{}", r.provider, r.model)).collect();
+        assert_eq!(models, vec!["anthropic/claude-sonnet-4-20250514", "openai/gpt-4o"]);
+
+        let bad = LlmConfig {
+            fallback: vec!["openai/no-such-model".to_string()],
+            ..config
+        };
+        assert!(bad.resolve_chain().is_err());
+    }
+
+    #[test]
+    fn test_llm_config_resolve_invalid_format() {
+        let config = LlmConfig {
+            default: "invalid-no-slash".to_string(),
+            ..Default::default()
+        };
+
+        let result = config.resolve();
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("provider/model"));
+    }
+
+    #[test]
+    fn test_llm_config_resolve_unknown_provider() {
+        let config = LlmConfig {
+            default: "unknown/model".to_string(),
+            ..Default::default()
+        };
+
+        let result = config.resolve();
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("not found"));
+    }
+
+    #[test]
+    fn test_llm_config_resolve_model_name() {
+        let config = LlmConfig::default();
+
+        let resolved = config.resolve_model_name("gpt-4o-mini").unwrap();
+        assert_eq!(resolved.provider, "openai");
+        assert_eq!(resolved.model, "gpt-4o-mini");
+
+        let resolved = config.resolve_model_name("anthropic/claude-opus-4-20250514").unwrap();
+        assert_eq!(resolved.max_tokens, 4096);
+
+        assert!(config.resolve_model_name("gpt-5").is_err());
+    }
+
+    #[test]
+    fn test_llm_config_resolve_openai_compatible_endpoints() {
+        let yaml = r#"
+default: azure/gpt-4o
+providers:
+  azure:
+    api-key-env: AZURE_OPENAI_API_KEY
+    base-url: https://acme.openai.azure.com
+    api-version: 2025-01-01-preview
+    models:
+      gpt-4o:
+        max-tokens: 4096
+        deployment: prod-gpt4o
+  openrouter:
+    api: openai
+    api-key-env: OPENROUTER_API_KEY
+    base-url: https://openrouter.ai/api/v1
+    organization: org-123
+    models:
+      o3-mini:
+        max-tokens: 8192
+        temperature: 0.2
+        reasoning-effort: high
+"#;
+        let config: LlmConfig = serde_yaml::from_str(yaml).unwrap();
+
+        let azure = config.resolve().unwrap();
+        assert_eq!(azure.api, "azure");
+        assert_eq!(azure.api_version.as_deref(), Some("2025-01-01-preview"));
+        assert_eq!(azure.deployment.as_deref(), Some("prod-gpt4o"));
+
+        let router = config.resolve_model("openrouter/o3-mini").unwrap();
+        assert_eq!(router.provider, "openrouter");
+        assert_eq!(router.api, "openai");
+        assert_eq!(router.organization.as_deref(), Some("org-123"));
+        assert_eq!(router.temperature, Some(0.2));
+        assert_eq!(router.reasoning_effort.as_deref(), Some("high"));
+    }
+
+    #[test]
+    fn test_get_api_key_from_secret() {
+        use crate::secrets::{AgeFileStore, SecretStore};
+
+        let temp = tempfile::tempdir().unwrap();
+        let mut config = LlmConfig {
+            secrets: SecretsConfig {
+                backend: SecretBackend::File,
+                file: Some(temp.path().join("secrets.age").to_string_lossy().into_owned()),
+                identity_file: Some(temp.path().join("secrets.key").to_string_lossy().into_owned()),
+            },
+            ..Default::default()
+        };
+        let provider = config.providers.get_mut("openai").unwrap();
+        provider.api_key_env = "TD_TEST_UNSET_OPENAI_KEY".to_string();
+        provider.api_key_secret = Some("openai-prod".to_string());
+
+        let resolved = config.resolve_model("openai/gpt-4o").unwrap();
+        let err = resolved.get_api_key().unwrap_err();
+        assert!(err.to_string().contains("td secrets set openai-prod"));
+
+        AgeFileStore::new(config.secrets.file().unwrap(), config.secrets.identity_file().unwrap())
+            .set("openai-prod", "sk-test")
+            .unwrap();
+        assert_eq!(resolved.get_api_key().unwrap(), "sk-test");
+    }
+
+    #[test]
+    fn test_llm_config_resolve_embeddings() {
+        let mut config = LlmConfig::default();
+        let resolved = config.resolve_embeddings().unwrap();
+        assert_eq!(resolved.provider, "openai");
+        assert_eq!(resolved.model, "text-embedding-3-small");
+
+        config.embeddings = Some("anthropic".to_string());
+        let resolved = config.resolve_embeddings().unwrap();
+        assert_eq!(resolved.embedding_model, None);
+
+        config.embeddings = Some("missing".to_string());
+        assert!(config.resolve_embeddings().is_err());
+    }
+
+    #[test]
+    fn test_llm_config_available_models() {
+        let config = LlmConfig::default();
+        let models = config.available_models();
+
+        assert!(models.contains(&"anthropic/claude-sonnet-4-20250514".to_string()));
+        assert!(models.contains(&"openai/gpt-4o".to_string()));
+    }
+
+    #[test]
+    fn test_deserialize_new_config_schema() {
+        let yaml = r#"
+llm:
+  default: openai/gpt-4o
+  timeout-ms: 60000
+  providers:
+    openai:
+      api-key-env: MY_OPENAI_KEY
+      base-url: https://api.openai.com
+      models:
+        gpt-4o:
+          max-tokens: 8192
+
+concurrency:
+  max-loops: 25
+  max-api-calls: 5
+  max-worktrees: 25
+  fairness: weighted
+  weights:
+    docs: 3
+
+validation:
+  command: "make test"
+  iteration-timeout-ms: 120000
+  max-iterations: 50
+"#;
+
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+
+        assert_eq!(config.llm.default, "openai/gpt-4o");
+        assert_eq!(config.llm.timeout_ms, 60000);
+
+        let resolved = config.llm.resolve().unwrap();
+        assert_eq!(resolved.provider, "openai");
+        assert_eq!(resolved.model, "gpt-4o");
+        assert_eq!(resolved.api_key_env, "MY_OPENAI_KEY");
+        assert_eq!(resolved.max_tokens, 8192);
+
+        assert_eq!(config.concurrency.max_loops, 25);
+        let scheduler = config.concurrency.scheduler_config();
+        assert_eq!(scheduler.max_concurrent, 5);
+        assert_eq!(scheduler.fairness, FairnessPolicy::Weighted);
+        assert_eq!(scheduler.weights.get("docs"), Some(&3));
+        assert_eq!(scheduler.aging_secs, 600);
+        assert_eq!(config.validation.command, "make test");
+        assert_eq!(config.validation.max_iterations, 50);
+    }
+
+    #[test]
+    fn test_partial_config_uses_defaults() {
+        let yaml = r#"
+llm:
+  default: openai/gpt-4o
+"#;
+
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+
+        // Specified value
+        assert_eq!(config.llm.default, "openai/gpt-4o");
+
+        // Defaults for unspecified - should have default providers
+        assert!(config.llm.providers.contains_key("anthropic"));
+        assert!(config.llm.providers.contains_key("openai"));
+        assert_eq!(config.concurrency.max_loops, 50);
+    }
+
+    #[test]
+    fn test_wasm_tools_module_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let tools = dir.path().join("tools");
+        fs::create_dir(&tools).unwrap();
+        fs::write(tools.join("b.wasm"), b"").unwrap();
+        fs::write(tools.join("a.wasm"), b"").unwrap();
+        fs::write(tools.join("README.md"), b"").unwrap();
+        let single = dir.path().join("single.wasm");
+        fs::write(&single, b"").unwrap();
+
+        let config = WasmToolsConfig {
+            paths: vec![
+                tools.display().to_string(),
+                dir.path().join("missing").display().to_string(),
+                single.display().to_string(),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            config.module_files(),
+            vec![tools.join("a.wasm"), tools.join("b.wasm"), single]
+        );
+    }
+}
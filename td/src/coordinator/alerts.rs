@@ -0,0 +1,190 @@
+//! Retained alerts for late subscribers
+//!
+//! Alerts go to whoever is subscribed when they are sent, so an execution
+//! that is queued, paused or between runs would never see them. Each topic
+//! numbers its alerts and keeps the recent ones (bounded by count and age);
+//! when an execution subscribes it is sent every retained alert on the topic
+//! past the last one it was delivered. Sequence numbers and delivery cursors
+//! live in [`AlertCursors`] so they can be persisted with the event store.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use tracing::debug;
+
+use super::persistence::AlertCursors;
+
+/// An alert kept for executions that subscribe later
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RetainedAlert {
+    pub(crate) seq: u64,
+    pub(crate) from_exec_id: String,
+    pub(crate) data: serde_json::Value,
+    /// Unix seconds
+    pub(crate) created_at: i64,
+}
+
+/// Recent alerts by topic, plus what each execution has already been sent
+#[derive(Debug)]
+pub(crate) struct AlertLog {
+    topics: HashMap<String, VecDeque<RetainedAlert>>,
+    cursors: AlertCursors,
+    max_count: usize,
+    max_age_secs: i64,
+}
+
+impl AlertLog {
+    pub(crate) fn new(max_count: usize, max_age: Duration) -> Self {
+        debug!(%max_count, ?max_age, "AlertLog::new: called");
+        Self {
+            topics: HashMap::new(),
+            cursors: AlertCursors::default(),
+            max_count,
+            max_age_secs: max_age.as_secs() as i64,
+        }
+    }
+
+    /// Pick up sequence numbers and delivery cursors saved by an earlier run
+    pub(crate) fn with_cursors(mut self, cursors: AlertCursors) -> Self {
+        debug!(topics = cursors.last_seq.len(), "AlertLog::with_cursors: called");
+        self.cursors = cursors;
+        self
+    }
+
+    pub(crate) fn cursors(&self) -> &AlertCursors {
+        &self.cursors
+    }
+
+    /// Oldest time (Unix seconds) an alert may have been sent and still be kept
+    pub(crate) fn cutoff(&self, now: i64) -> i64 {
+        now - self.max_age_secs
+    }
+
+    /// Re-retain an alert loaded from the event store
+    pub(crate) fn restore(&mut self, topic: &str, alert: RetainedAlert) {
+        debug!(%topic, seq = alert.seq, "AlertLog::restore: called");
+        let last = self.cursors.last_seq.entry(topic.to_string()).or_default();
+        *last = (*last).max(alert.seq);
+        let retained = self.topics.entry(topic.to_string()).or_default();
+        retained.push_back(alert);
+        while retained.len() > self.max_count {
+            retained.pop_front();
+        }
+    }
+
+    /// Retain a new alert, returning its sequence number
+    pub(crate) fn record(&mut self, topic: &str, from_exec_id: &str, data: serde_json::Value, now: i64) -> u64 {
+        debug!(%topic, %from_exec_id, "AlertLog::record: called");
+        let last = self.cursors.last_seq.entry(topic.to_string()).or_default();
+        *last += 1;
+        let seq = *last;
+        self.restore(
+            topic,
+            RetainedAlert {
+                seq,
+                from_exec_id: from_exec_id.to_string(),
+                data,
+                created_at: now,
+            },
+        );
+        self.prune(topic, now);
+        seq
+    }
+
+    /// Retained alerts on `topic` that `exec_id` has not been sent, oldest first
+    ///
+    /// An execution is never sent its own alerts.
+    pub(crate) fn missed(&mut self, exec_id: &str, topic: &str, now: i64) -> Vec<RetainedAlert> {
+        debug!(%exec_id, %topic, "AlertLog::missed: called");
+        self.prune(topic, now);
+        let after = self
+            .cursors
+            .delivered
+            .get(exec_id)
+            .and_then(|topics| topics.get(topic))
+            .copied()
+            .unwrap_or(0);
+        self.topics
+            .get(topic)
+            .into_iter()
+            .flatten()
+            .filter(|alert| alert.seq > after && alert.from_exec_id != exec_id)
+            .cloned()
+            .collect()
+    }
+
+    /// Note that `exec_id` was sent alert `seq` on `topic`
+    pub(crate) fn delivered(&mut self, exec_id: &str, topic: &str, seq: u64) {
+        debug!(%exec_id, %topic, %seq, "AlertLog::delivered: called");
+        let cursor = self
+            .cursors
+            .delivered
+            .entry(exec_id.to_string())
+            .or_default()
+            .entry(topic.to_string())
+            .or_default();
+        *cursor = (*cursor).max(seq);
+    }
+
+    /// Drop expired alerts on `topic`, and cursors that no longer hold anything back
+    ///
+    /// A cursor older than every retained alert replays the same alerts as no
+    /// cursor at all, so it can go; sequence numbers never restart, so a later
+    /// alert is still past it.
+    fn prune(&mut self, topic: &str, now: i64) {
+        debug!(%topic, %now, "AlertLog::prune: called");
+        let cutoff = self.cutoff(now);
+        let oldest = match self.topics.get_mut(topic) {
+            Some(retained) => {
+                while retained.front().is_some_and(|alert| alert.created_at < cutoff) {
+                    retained.pop_front();
+                }
+                retained.front().map(|alert| alert.seq)
+            }
+            None => None,
+        };
+        let last = self.cursors.last_seq.get(topic).copied().unwrap_or(0);
+        let oldest = oldest.unwrap_or(last + 1);
+        for topics in self.cursors.delivered.values_mut() {
+            if topics.get(topic).is_some_and(|&seq| seq < oldest) {
+                topics.remove(topic);
+            }
+        }
+        self.cursors.delivered.retain(|_, topics| !topics.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_late_subscriber_gets_missed_alerts() {
+        let mut log = AlertLog::new(2, Duration::from_secs(60));
+        assert_eq!(log.record("main_updated", "exec-a", json!({"n": 1}), 1000), 1);
+        assert_eq!(log.record("main_updated", "exec-a", json!({"n": 2}), 1001), 2);
+        assert_eq!(log.record("main_updated", "exec-b", json!({"n": 3}), 1002), 3);
+
+        // Only the two most recent are retained, and nobody gets their own alert
+        let seqs = |alerts: Vec<RetainedAlert>| alerts.iter().map(|a| a.seq).collect::<Vec<_>>();
+        assert_eq!(seqs(log.missed("exec-c", "main_updated", 1003)), vec![2, 3]);
+        assert_eq!(seqs(log.missed("exec-b", "main_updated", 1003)), vec![2]);
+        assert!(log.missed("exec-c", "other", 1003).is_empty());
+
+        log.delivered("exec-c", "main_updated", 2);
+        assert_eq!(seqs(log.missed("exec-c", "main_updated", 1003)), vec![3]);
+        log.delivered("exec-c", "main_updated", 3);
+        assert!(log.missed("exec-c", "main_updated", 1003).is_empty());
+
+        // Expired alerts are dropped, and sequence numbers carry on
+        assert!(log.missed("exec-d", "main_updated", 1063).is_empty());
+        assert!(log.cursors().delivered.is_empty());
+        assert_eq!(log.record("main_updated", "exec-a", json!({"n": 4}), 1064), 4);
+        assert_eq!(seqs(log.missed("exec-c", "main_updated", 1064)), vec![4]);
+
+        // A restarted coordinator resumes where the saved cursors left off
+        let mut restarted = AlertLog::new(2, Duration::from_secs(60)).with_cursors(log.cursors().clone());
+        assert_eq!(restarted.record("main_updated", "exec-a", json!({"n": 5}), 1065), 5);
+    }
+}
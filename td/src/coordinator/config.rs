@@ -8,24 +8,32 @@ use tracing::debug;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CoordinatorConfig {
     /// Default query timeout in seconds
-    #[serde(default = "default_query_timeout_secs")]
+    #[serde(default = "default_query_timeout_secs", rename = "query-timeout-secs")]
     pub query_timeout_secs: u64,
 
     /// Max messages per second per loop (rate limiting)
-    #[serde(default = "default_rate_limit")]
+    #[serde(default = "default_rate_limit", rename = "rate-limit-per-sec")]
     pub rate_limit_per_sec: usize,
 
     /// Max payload size in bytes (1MB default)
-    #[serde(default = "default_max_payload_size")]
+    #[serde(default = "default_max_payload_size", rename = "max-payload-size")]
     pub max_payload_size: usize,
 
     /// Channel buffer size for coordinator requests
-    #[serde(default = "default_channel_buffer")]
+    #[serde(default = "default_channel_buffer", rename = "channel-buffer")]
     pub channel_buffer: usize,
 
     /// Channel buffer size for loop messages
-    #[serde(default = "default_loop_channel_buffer")]
+    #[serde(default = "default_loop_channel_buffer", rename = "loop-channel-buffer")]
     pub loop_channel_buffer: usize,
+
+    /// How long broadcast alerts are kept for executions that subscribe later
+    #[serde(default = "default_alert_retention_secs", rename = "alert-retention-secs")]
+    pub alert_retention_secs: u64,
+
+    /// Max alerts kept per event type for executions that subscribe later
+    #[serde(default = "default_alert_retention_count", rename = "alert-retention-count")]
+    pub alert_retention_count: usize,
 }
 
 fn default_query_timeout_secs() -> u64 {
@@ -53,6 +61,16 @@ fn default_loop_channel_buffer() -> usize {
     100
 }
 
+fn default_alert_retention_secs() -> u64 {
+    debug!("default_alert_retention_secs: called");
+    3600
+}
+
+fn default_alert_retention_count() -> usize {
+    debug!("default_alert_retention_count: called");
+    100
+}
+
 impl Default for CoordinatorConfig {
     fn default() -> Self {
         debug!("CoordinatorConfig::default: called");
@@ -62,6 +80,8 @@ impl Default for CoordinatorConfig {
             max_payload_size: 1024 * 1024,
             channel_buffer: 1000,
             loop_channel_buffer: 100,
+            alert_retention_secs: 3600,
+            alert_retention_count: 100,
         }
     }
 }
@@ -72,6 +92,12 @@ impl CoordinatorConfig {
         debug!(query_timeout_secs = %self.query_timeout_secs, "CoordinatorConfig::query_timeout: called");
         Duration::from_secs(self.query_timeout_secs)
     }
+
+    /// Get the alert retention window as a Duration
+    pub fn alert_retention(&self) -> Duration {
+        debug!(alert_retention_secs = %self.alert_retention_secs, "CoordinatorConfig::alert_retention: called");
+        Duration::from_secs(self.alert_retention_secs)
+    }
 }
 
 #[cfg(test)]
@@ -86,6 +112,8 @@ mod tests {
         assert_eq!(config.max_payload_size, 1024 * 1024);
         assert_eq!(config.channel_buffer, 1000);
         assert_eq!(config.loop_channel_buffer, 100);
+        assert_eq!(config.alert_retention_secs, 3600);
+        assert_eq!(config.alert_retention_count, 100);
     }
 
     #[test]
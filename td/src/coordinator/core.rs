@@ -1,26 +1,113 @@
 //! Main Coordinator task implementation
 
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use eyre::Result;
 use tokio::sync::{mpsc, oneshot};
 use tracing::{debug, info, warn};
 
+use super::alerts::{AlertLog, RetainedAlert};
 use super::config::CoordinatorConfig;
+use super::error::CoordError;
 use super::handle::CoordinatorHandle;
+use super::locks::PathLocks;
 use super::messages::{CoordMessage, CoordRequest, CoordinatorMetrics};
-use super::persistence::{EventStore, PersistedEvent};
+use super::persistence::{EventStore, PersistedEvent, now_timestamp};
+use super::topics::{FileLockReleased, Topic, TopicRegistry};
+use crate::events::{Event, EventBus};
 
 /// Pending query tracking
 struct PendingQuery {
     reply_tx: oneshot::Sender<Result<String>>,
-    #[allow(dead_code)]
     from_exec_id: String,
-    #[allow(dead_code)]
     target_exec_id: String,
 }
 
+/// Find the wait-for cycle a new query from `from` to `target` would close
+///
+/// Every pending query is an edge querier -> target (the querier is blocked
+/// until the target answers). Returns the cycle starting and ending with `from`,
+/// or None if `target` does not (transitively) wait on `from`.
+fn find_wait_cycle<'a>(
+    edges: impl IntoIterator<Item = (&'a str, &'a str)>,
+    from: &'a str,
+    target: &'a str,
+) -> Option<Vec<String>> {
+    debug!(%from, %target, "find_wait_cycle: called");
+    let mut graph: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (querier, queried) in edges {
+        graph.entry(querier).or_default().push(queried);
+    }
+
+    // Breadth-first from the target, remembering how each node was reached
+    let mut parents: HashMap<&str, &str> = HashMap::new();
+    let mut seen: HashSet<&str> = HashSet::from([target]);
+    let mut queue: VecDeque<&str> = VecDeque::from([target]);
+    while let Some(node) = queue.pop_front() {
+        if node == from {
+            let mut path = vec![node.to_string()];
+            let mut current = node;
+            while current != target {
+                current = parents[current];
+                path.push(current.to_string());
+            }
+            path.reverse();
+            let mut cycle = vec![from.to_string()];
+            cycle.extend(path);
+            debug!(?cycle, "find_wait_cycle: cycle found");
+            return Some(cycle);
+        }
+        for &next in graph.get(node).into_iter().flatten() {
+            if seen.insert(next) {
+                parents.insert(next, node);
+                queue.push_back(next);
+            }
+        }
+    }
+    debug!("find_wait_cycle: no cycle");
+    None
+}
+
+/// Seed `log` with the alerts and cursors an earlier run left in `store`
+///
+/// Expired alerts are pruned from the store first. A store that cannot be
+/// read only costs replay, so errors are logged rather than returned.
+async fn load_alert_log(store: &EventStore, log: AlertLog) -> AlertLog {
+    debug!("load_alert_log: called");
+    if let Err(e) = store.prune_alerts(log.cutoff(now_timestamp())).await {
+        warn!("Failed to prune retained alerts: {}", e);
+    }
+    let cursors = store.load_alert_cursors().await.unwrap_or_else(|e| {
+        warn!("Failed to load alert cursors: {}", e);
+        Default::default()
+    });
+    let mut log = log.with_cursors(cursors);
+
+    let events = store.get_all().await.unwrap_or_else(|e| {
+        warn!("Failed to load retained alerts: {}", e);
+        Vec::new()
+    });
+    for event in events {
+        if let Some(payload) = event.alert_payload()
+            && let Some(seq) = payload.seq
+        {
+            let data = serde_json::from_str(&payload.data).unwrap_or(serde_json::Value::String(payload.data));
+            log.restore(
+                &payload.event_type,
+                RetainedAlert {
+                    seq,
+                    from_exec_id: event.from_exec_id,
+                    data,
+                    created_at: event.created_at,
+                },
+            );
+        }
+    }
+    log
+}
+
 /// Rate limiter for per-loop message limiting
 struct RateLimiter {
     counters: HashMap<String, VecDeque<Instant>>,
@@ -78,6 +165,10 @@ pub struct Coordinator {
     rx: mpsc::Receiver<CoordRequest>,
     /// Optional event store for persistence
     event_store: Option<EventStore>,
+    /// Optional event bus for surfacing coordination problems (e.g. deadlocks)
+    event_bus: Option<Arc<EventBus>>,
+    /// Topics whose alert payloads are validated
+    topics: Arc<TopicRegistry>,
 }
 
 impl Coordinator {
@@ -90,6 +181,8 @@ impl Coordinator {
             tx,
             rx,
             event_store: None,
+            event_bus: None,
+            topics: Arc::new(TopicRegistry::default()),
         }
     }
 
@@ -102,9 +195,25 @@ impl Coordinator {
             tx,
             rx,
             event_store: Some(EventStore::new(store_path)),
+            event_bus: None,
+            topics: Arc::new(TopicRegistry::default()),
         }
     }
 
+    /// Emit coordination events (e.g. `DeadlockDetected`) on the given bus
+    pub fn with_event_bus(mut self, event_bus: Arc<EventBus>) -> Self {
+        debug!("Coordinator::with_event_bus: called");
+        self.event_bus = Some(event_bus);
+        self
+    }
+
+    /// Validate alerts on topic `T` in addition to the built-in topics
+    pub fn with_topic<T: Topic>(mut self) -> Self {
+        debug!(topic = T::NAME, "Coordinator::with_topic: called");
+        self.topics = Arc::new((*self.topics).clone().with_topic::<T>());
+        self
+    }
+
     /// Get a sender for creating handles
     pub fn sender(&self) -> mpsc::Sender<CoordRequest> {
         debug!("Coordinator::sender: called");
@@ -128,7 +237,7 @@ impl Coordinator {
             .map_err(|_| eyre::eyre!("Coordinator channel closed"))?;
 
         debug!(%exec_id, "Coordinator::register: registration sent");
-        Ok(CoordinatorHandle::new(self.tx.clone(), msg_rx, exec_id.to_string()))
+        Ok(CoordinatorHandle::new(self.tx.clone(), msg_rx, exec_id.to_string()).with_topics(self.topics.clone()))
     }
 
     /// Unregister an execution
@@ -164,6 +273,7 @@ impl Coordinator {
         debug!("Coordinator::run: called");
         let coord_tx = self.tx.clone();
         let event_store = self.event_store.take();
+        let event_bus = self.event_bus.take();
 
         // Internal state
         let mut registry: HashMap<String, mpsc::Sender<CoordMessage>> = HashMap::new();
@@ -171,6 +281,11 @@ impl Coordinator {
         let mut pending_queries: HashMap<String, PendingQuery> = HashMap::new();
         let mut pending_event_ids: HashMap<String, String> = HashMap::new(); // query_id -> event_id
         let mut rate_limiter = RateLimiter::new(self.config.rate_limit_per_sec, Duration::from_secs(1));
+        let mut path_locks = PathLocks::default();
+        let mut alert_log = AlertLog::new(self.config.alert_retention_count, self.config.alert_retention());
+        if let Some(ref store) = event_store {
+            alert_log = load_alert_log(store, alert_log).await;
+        }
 
         // Metrics
         let mut metrics = CoordinatorMetrics::default();
@@ -191,6 +306,24 @@ impl Coordinator {
                     debug!(%exec_id, "Coordinator::run: Unregister branch");
                     registry.remove(&exec_id);
                     rate_limiter.clear(&exec_id);
+                    let released = path_locks.release(&exec_id);
+                    metrics.path_locks = path_locks.len();
+
+                    // Let waiting loops know the paths are free (queued behind this request)
+                    for path in released {
+                        let payload = FileLockReleased {
+                            path,
+                            exec_id: exec_id.clone(),
+                        };
+                        let alert = CoordRequest::Alert {
+                            from_exec_id: exec_id.clone(),
+                            event_type: FileLockReleased::NAME.to_string(),
+                            data: serde_json::to_value(&payload).expect("FileLockReleased serialization failed"),
+                        };
+                        if coord_tx.try_send(alert).is_err() {
+                            warn!(%exec_id, path = %payload.path, "Failed to queue file_lock_released alert");
+                        }
+                    }
 
                     // Remove from all subscriptions
                     for subscribers in subscriptions.values_mut() {
@@ -206,6 +339,13 @@ impl Coordinator {
                     data,
                 } => {
                     debug!(%from_exec_id, %event_type, "Coordinator::run: Alert branch");
+                    if let Err(e) = self.topics.validate(&event_type, &data) {
+                        debug!("Coordinator::run: Alert payload invalid");
+                        warn!(%from_exec_id, "Dropping alert: {}", e);
+                        metrics.invalid_alerts += 1;
+                        continue;
+                    }
+
                     // Rate limit check
                     if !rate_limiter.check_and_record(&from_exec_id) {
                         debug!("Coordinator::run: Alert rate limit exceeded");
@@ -215,11 +355,12 @@ impl Coordinator {
                     }
 
                     debug!("Coordinator::run: Alert rate limit passed");
+                    let seq = alert_log.record(&event_type, &from_exec_id, data.clone(), now_timestamp());
 
-                    // Persist the alert event for crash recovery
+                    // Persist the alert event for crash recovery and replay
                     if let Some(ref store) = event_store {
                         debug!("Coordinator::run: Alert persisting event");
-                        let event = PersistedEvent::alert(&from_exec_id, &event_type, data.to_string());
+                        let event = PersistedEvent::alert(&from_exec_id, &event_type, seq, data.to_string());
                         if let Err(e) = store.persist(&event).await {
                             debug!("Coordinator::run: Alert persist failed");
                             warn!("Failed to persist alert event: {}", e);
@@ -237,6 +378,7 @@ impl Coordinator {
                             from_exec_id: from_exec_id.clone(),
                             event_type: event_type.clone(),
                             data: data.clone(),
+                            seq,
                         };
 
                         for exec_id in subscribers {
@@ -244,12 +386,19 @@ impl Coordinator {
                                 && tx.send(msg.clone()).await.is_ok()
                             {
                                 debug!(%exec_id, "Coordinator::run: Alert sent to subscriber");
+                                alert_log.delivered(exec_id, &event_type, seq);
                                 metrics.messages_sent += 1;
                             }
                         }
                     } else {
                         debug!("Coordinator::run: Alert no subscribers");
                     }
+
+                    if let Some(ref store) = event_store
+                        && let Err(e) = store.save_alert_cursors(alert_log.cursors()).await
+                    {
+                        warn!("Failed to save alert cursors: {}", e);
+                    }
                 }
 
                 CoordRequest::Query {
@@ -272,6 +421,24 @@ impl Coordinator {
 
                     debug!("Coordinator::run: Query rate limit passed");
 
+                    // Deadlock check: fail fast if the target is (transitively) waiting on us
+                    let edges = pending_queries
+                        .values()
+                        .map(|p| (p.from_exec_id.as_str(), p.target_exec_id.as_str()));
+                    if let Some(cycle) = find_wait_cycle(edges, &from_exec_id, &target_exec_id) {
+                        debug!("Coordinator::run: Query would deadlock");
+                        warn!(query_id = %query_id, cycle = %cycle.join(" -> "), "Deadlock detected for query");
+                        metrics.deadlocks_detected += 1;
+                        if let Some(ref bus) = event_bus {
+                            bus.emit(Event::DeadlockDetected {
+                                execution_id: from_exec_id.clone(),
+                                cycle: cycle.clone(),
+                            });
+                        }
+                        let _ = reply_tx.send(Err(CoordError::DeadlockDetected { cycle }.into()));
+                        continue;
+                    }
+
                     // Persist the query event for crash recovery
                     if let Some(ref store) = event_store {
                         debug!("Coordinator::run: Query persisting event");
@@ -457,6 +624,35 @@ impl Coordinator {
                 CoordRequest::Subscribe { exec_id, event_type } => {
                     debug!(%exec_id, %event_type, "Coordinator::run: Subscribe branch");
 
+                    // Catch the subscriber up on alerts it was not around for
+                    let missed = alert_log.missed(&exec_id, &event_type, now_timestamp());
+                    if !missed.is_empty()
+                        && let Some(tx) = registry.get(&exec_id)
+                    {
+                        debug!(count = missed.len(), "Coordinator::run: Subscribe replaying missed alerts");
+                        for alert in missed {
+                            let msg = CoordMessage::Notification {
+                                from_exec_id: alert.from_exec_id,
+                                event_type: event_type.clone(),
+                                data: alert.data,
+                                seq: alert.seq,
+                            };
+                            if tx.send(msg).await.is_err() {
+                                debug!("Coordinator::run: Subscribe replay receiver gone");
+                                break;
+                            }
+                            alert_log.delivered(&exec_id, &event_type, alert.seq);
+                            metrics.alerts_replayed += 1;
+                            metrics.messages_sent += 1;
+                        }
+
+                        if let Some(ref store) = event_store
+                            && let Err(e) = store.save_alert_cursors(alert_log.cursors()).await
+                        {
+                            warn!("Failed to save alert cursors: {}", e);
+                        }
+                    }
+
                     subscriptions.entry(event_type).or_default().insert(exec_id);
 
                     metrics.total_subscriptions = subscriptions.values().map(|s| s.len()).sum();
@@ -496,6 +692,35 @@ impl Coordinator {
                     }
                 }
 
+                CoordRequest::LockPaths {
+                    exec_id,
+                    paths,
+                    reply_tx,
+                } => {
+                    debug!(%exec_id, ?paths, "Coordinator::run: LockPaths branch");
+                    let conflicts = path_locks.claim(&exec_id, &paths);
+                    for conflict in &conflicts {
+                        warn!(
+                            %exec_id,
+                            path = %conflict.path,
+                            held_by = %conflict.held_by,
+                            "Path already locked by another execution"
+                        );
+                    }
+                    metrics.path_locks = path_locks.len();
+                    metrics.path_conflicts += conflicts.len() as u64;
+                    let _ = reply_tx.send(conflicts);
+                }
+
+                CoordRequest::CheckPaths {
+                    exec_id,
+                    paths,
+                    reply_tx,
+                } => {
+                    debug!(%exec_id, ?paths, "Coordinator::run: CheckPaths branch");
+                    let _ = reply_tx.send(path_locks.conflicts(&exec_id, &paths));
+                }
+
                 CoordRequest::GetMetrics { reply_tx } => {
                     debug!("Coordinator::run: GetMetrics branch");
                     let _ = reply_tx.send(metrics.clone());
@@ -624,6 +849,146 @@ mod tests {
         coord_task.await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_coordinator_replays_missed_alerts() {
+        let temp = tempfile::tempdir().unwrap();
+        let start = || {
+            let coord = Coordinator::with_persistence(CoordinatorConfig::default(), temp.path());
+            let sender = coord.sender();
+            (sender, tokio::spawn(coord.run()))
+        };
+        let subscribe = |exec_id: &str| CoordRequest::Subscribe {
+            exec_id: exec_id.to_string(),
+            event_type: "main_updated".to_string(),
+        };
+        let seqs = |rx: &mut mpsc::Receiver<CoordMessage>| {
+            let mut seqs = Vec::new();
+            while let Ok(msg) = rx.try_recv() {
+                match msg {
+                    CoordMessage::Notification { seq, .. } => seqs.push(seq),
+                    other => panic!("Expected Notification, got {:?}", other),
+                }
+            }
+            seqs
+        };
+
+        let (coord_sender, coord_task) = start();
+        let (msg_tx, mut msg_rx) = mpsc::channel(10);
+        coord_sender
+            .send(CoordRequest::Register {
+                exec_id: "exec-002".to_string(),
+                tx: msg_tx,
+            })
+            .await
+            .unwrap();
+
+        // The alert goes out before exec-002 subscribes
+        coord_sender
+            .send(CoordRequest::Alert {
+                from_exec_id: "exec-001".to_string(),
+                event_type: "main_updated".to_string(),
+                data: json!({"new_sha": "abc"}),
+            })
+            .await
+            .unwrap();
+        coord_sender.send(subscribe("exec-002")).await.unwrap();
+        coord_sender.send(subscribe("exec-002")).await.unwrap();
+
+        let (reply_tx, reply_rx) = oneshot::channel();
+        coord_sender.send(CoordRequest::GetMetrics { reply_tx }).await.unwrap();
+        assert_eq!(reply_rx.await.unwrap().alerts_replayed, 1);
+        assert_eq!(seqs(&mut msg_rx), vec![1]);
+
+        coord_sender.send(CoordRequest::Shutdown).await.unwrap();
+        coord_task.await.unwrap();
+
+        // After a restart the alert is still there for newcomers, but not redelivered
+        let (coord_sender, coord_task) = start();
+        let (msg_tx2, mut msg_rx2) = mpsc::channel(10);
+        let (msg_tx3, mut msg_rx3) = mpsc::channel(10);
+        for (exec_id, tx) in [("exec-002", msg_tx2), ("exec-003", msg_tx3)] {
+            coord_sender
+                .send(CoordRequest::Register {
+                    exec_id: exec_id.to_string(),
+                    tx,
+                })
+                .await
+                .unwrap();
+            coord_sender.send(subscribe(exec_id)).await.unwrap();
+        }
+        coord_sender
+            .send(CoordRequest::Alert {
+                from_exec_id: "exec-001".to_string(),
+                event_type: "main_updated".to_string(),
+                data: json!({"new_sha": "def"}),
+            })
+            .await
+            .unwrap();
+
+        let (reply_tx, reply_rx) = oneshot::channel();
+        coord_sender.send(CoordRequest::GetMetrics { reply_tx }).await.unwrap();
+        reply_rx.await.unwrap();
+        assert_eq!(seqs(&mut msg_rx2), vec![2]);
+        assert_eq!(seqs(&mut msg_rx3), vec![1, 2]);
+
+        coord_sender.send(CoordRequest::Shutdown).await.unwrap();
+        coord_task.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_coordinator_typed_topics() {
+        let coord = Coordinator::new(CoordinatorConfig::default());
+        let coord_sender = coord.sender();
+        let watcher = coord.register("exec-watch").await.unwrap();
+        let holder = coord.register("exec-hold").await.unwrap();
+        let coord_task = tokio::spawn(coord.run());
+
+        // Only src/ releases get through the filter
+        watcher
+            .subscribe_filtered::<FileLockReleased>(|released| released.path.starts_with("src/"))
+            .await
+            .unwrap();
+        holder
+            .lock_paths(&["docs/guide.md".to_string(), "src/lib.rs".to_string()])
+            .await
+            .unwrap();
+        coord_sender
+            .send(CoordRequest::Unregister {
+                exec_id: "exec-hold".to_string(),
+            })
+            .await
+            .unwrap();
+
+        let msg = tokio::time::timeout(Duration::from_secs(1), watcher.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            msg.payload::<FileLockReleased>(),
+            Some(FileLockReleased {
+                path: "src/lib.rs".to_string(),
+                exec_id: "exec-hold".to_string(),
+            })
+        );
+        assert!(watcher.try_recv().is_none());
+
+        // Malformed payloads are refused by the handle and dropped by the coordinator
+        let err = watcher.alert("budget_warning", json!({"remaining": 2})).await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<CoordError>(), Some(CoordError::InvalidPayload { .. })));
+        coord_sender
+            .send(CoordRequest::Alert {
+                from_exec_id: "exec-raw".to_string(),
+                event_type: "main_updated".to_string(),
+                data: json!({"sha": "abc"}),
+            })
+            .await
+            .unwrap();
+        assert_eq!(watcher.metrics().await.unwrap().invalid_alerts, 1);
+
+        coord_sender.send(CoordRequest::Shutdown).await.unwrap();
+        coord_task.await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_coordinator_query_reply() {
         let coord = Coordinator::new(CoordinatorConfig::default());
@@ -749,6 +1114,108 @@ mod tests {
         coord_task.await.unwrap();
     }
 
+    #[test]
+    fn test_find_wait_cycle() {
+        // a -> b -> c pending; c asking a closes the loop
+        let edges = [("a", "b"), ("b", "c")];
+        assert_eq!(
+            find_wait_cycle(edges, "c", "a"),
+            Some(vec!["c".to_string(), "a".to_string(), "b".to_string(), "c".to_string()])
+        );
+        // a asking c just waits longer
+        assert_eq!(find_wait_cycle(edges, "a", "c"), None);
+        // Querying yourself is the smallest cycle
+        assert_eq!(
+            find_wait_cycle([], "a", "a"),
+            Some(vec!["a".to_string(), "a".to_string()])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_coordinator_query_deadlock() {
+        let bus = Arc::new(EventBus::with_default_capacity());
+        let mut events = bus.subscribe();
+        let coord = Coordinator::new(CoordinatorConfig::default()).with_event_bus(bus);
+        let coord_sender = coord.sender();
+
+        let coord_task = tokio::spawn(coord.run());
+
+        let (msg_tx1, _msg_rx1) = mpsc::channel(10);
+        let (msg_tx2, _msg_rx2) = mpsc::channel(10);
+        for (exec_id, tx) in [("exec-001", msg_tx1), ("exec-002", msg_tx2)] {
+            coord_sender
+                .send(CoordRequest::Register {
+                    exec_id: exec_id.to_string(),
+                    tx,
+                })
+                .await
+                .unwrap();
+        }
+
+        // exec-001 waits on exec-002
+        let (reply_tx1, _reply_rx1) = oneshot::channel();
+        coord_sender
+            .send(CoordRequest::Query {
+                query_id: "query-001".to_string(),
+                from_exec_id: "exec-001".to_string(),
+                target_exec_id: "exec-002".to_string(),
+                question: "Is the schema final?".to_string(),
+                reply_tx: reply_tx1,
+                timeout: Duration::from_secs(30),
+            })
+            .await
+            .unwrap();
+
+        // exec-002 now asks exec-001: fails immediately instead of timing out
+        let (reply_tx2, reply_rx2) = oneshot::channel();
+        coord_sender
+            .send(CoordRequest::Query {
+                query_id: "query-002".to_string(),
+                from_exec_id: "exec-002".to_string(),
+                target_exec_id: "exec-001".to_string(),
+                question: "Which schema should I use?".to_string(),
+                reply_tx: reply_tx2,
+                timeout: Duration::from_secs(30),
+            })
+            .await
+            .unwrap();
+
+        let err = tokio::time::timeout(Duration::from_secs(1), reply_rx2)
+            .await
+            .expect("deadlock should fail fast")
+            .unwrap()
+            .unwrap_err();
+        let cycle = vec!["exec-002".to_string(), "exec-001".to_string(), "exec-002".to_string()];
+        assert_eq!(
+            err.downcast_ref::<CoordError>(),
+            Some(&CoordError::DeadlockDetected { cycle: cycle.clone() })
+        );
+
+        match events.recv().await.unwrap() {
+            Event::DeadlockDetected {
+                execution_id,
+                cycle: event_cycle,
+            } => {
+                assert_eq!(execution_id, "exec-002");
+                assert_eq!(event_cycle, cycle);
+            }
+            other => panic!("Unexpected event: {:?}", other),
+        }
+
+        let (metrics_tx, metrics_rx) = oneshot::channel();
+        coord_sender
+            .send(CoordRequest::GetMetrics { reply_tx: metrics_tx })
+            .await
+            .unwrap();
+        let metrics = metrics_rx.await.unwrap();
+        assert_eq!(metrics.deadlocks_detected, 1);
+        assert_eq!(metrics.pending_queries, 1);
+
+        // Shutdown
+        coord_sender.send(CoordRequest::Shutdown).await.unwrap();
+        coord_task.await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_coordinator_share() {
         let coord = Coordinator::new(CoordinatorConfig::default());
@@ -806,6 +1273,42 @@ mod tests {
         coord_task.await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_coordinator_path_locks() {
+        let coord = Coordinator::new(CoordinatorConfig::default());
+        let first = coord.register("exec-001").await.unwrap();
+        let second = coord.register("exec-002").await.unwrap();
+        let coord_sender = coord.sender();
+        let coord_task = tokio::spawn(coord.run());
+
+        let claimed = first.lock_paths(&["src/parser".to_string()]).await.unwrap();
+        assert!(claimed.is_empty());
+
+        let conflicts = second.lock_paths(&["src/parser/lexer.rs".to_string()]).await.unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].held_by, "exec-001");
+        assert_eq!(conflicts[0].held_path, "src/parser");
+
+        // Unregistering releases the holder's locks
+        coord_sender
+            .send(CoordRequest::Unregister {
+                exec_id: "exec-001".to_string(),
+            })
+            .await
+            .unwrap();
+        let conflicts = second.lock_paths(&["src/parser/lexer.rs".to_string()]).await.unwrap();
+        assert!(conflicts.is_empty());
+
+        let (reply_tx, reply_rx) = oneshot::channel();
+        coord_sender.send(CoordRequest::GetMetrics { reply_tx }).await.unwrap();
+        let metrics = reply_rx.await.unwrap();
+        assert_eq!(metrics.path_locks, 1);
+        assert_eq!(metrics.path_conflicts, 1);
+
+        coord_sender.send(CoordRequest::Shutdown).await.unwrap();
+        coord_task.await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_coordinator_stop() {
         let coord = Coordinator::new(CoordinatorConfig::default());
@@ -0,0 +1,42 @@
+//! Coordinator error types
+
+use thiserror::Error;
+
+/// Errors the Coordinator reports back to requesting loops
+///
+/// Replies travel as `eyre::Result`; callers that need to react to a specific
+/// failure can `downcast_ref::<CoordError>()` the report.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum CoordError {
+    /// Answering the query would require the querying loop to answer first
+    #[error("Deadlock detected: {}", cycle.join(" -> "))]
+    DeadlockDetected {
+        /// Executions in the wait-for cycle, starting and ending with the querier
+        cycle: Vec<String>,
+    },
+
+    /// An alert's payload does not match its topic's schema
+    #[error("Invalid payload for topic {topic}: {reason}")]
+    InvalidPayload {
+        /// Event type the alert was published under
+        topic: String,
+        /// What the schema rejected
+        reason: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deadlock_detected_message() {
+        let err = CoordError::DeadlockDetected {
+            cycle: vec!["exec-a".to_string(), "exec-b".to_string(), "exec-a".to_string()],
+        };
+        assert_eq!(err.to_string(), "Deadlock detected: exec-a -> exec-b -> exec-a");
+
+        let report: eyre::Report = err.clone().into();
+        assert_eq!(report.downcast_ref::<CoordError>(), Some(&err));
+    }
+}
@@ -1,5 +1,7 @@
 //! CoordinatorHandle - Client interface for loop communication
 
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use eyre::{Result, eyre};
@@ -7,7 +9,12 @@ use tokio::sync::{mpsc, oneshot};
 use tracing::debug;
 use uuid::Uuid;
 
+use super::locks::PathConflict;
 use super::messages::{CoordMessage, CoordRequest, CoordinatorMetrics};
+use super::topics::{Topic, TopicRegistry};
+
+/// Decides whether a notification payload is wanted
+type NotificationFilter = Arc<dyn Fn(&serde_json::Value) -> bool + Send + Sync>;
 
 /// Handle for loops to interact with the Coordinator
 ///
@@ -24,6 +31,12 @@ pub struct CoordinatorHandle {
 
     /// This handle's execution ID
     exec_id: String,
+
+    /// Schemas alerts are checked against before sending
+    topics: Arc<TopicRegistry>,
+
+    /// Payload filters by event type (shared between clones)
+    filters: Arc<Mutex<HashMap<String, NotificationFilter>>>,
 }
 
 impl CoordinatorHandle {
@@ -34,13 +47,28 @@ impl CoordinatorHandle {
             tx,
             rx: Some(std::sync::Arc::new(tokio::sync::Mutex::new(rx))),
             exec_id,
+            topics: Arc::new(TopicRegistry::default()),
+            filters: Arc::default(),
         }
     }
 
     /// Create a handle without a receiver (for sending only)
     pub(crate) fn sender_only(tx: mpsc::Sender<CoordRequest>, exec_id: String) -> Self {
         debug!(%exec_id, "CoordinatorHandle::sender_only: called");
-        Self { tx, rx: None, exec_id }
+        Self {
+            tx,
+            rx: None,
+            exec_id,
+            topics: Arc::new(TopicRegistry::default()),
+            filters: Arc::default(),
+        }
+    }
+
+    /// Check alerts against `topics` instead of the built-in topics
+    pub(crate) fn with_topics(mut self, topics: Arc<TopicRegistry>) -> Self {
+        debug!(exec_id = %self.exec_id, "CoordinatorHandle::with_topics: called");
+        self.topics = topics;
+        self
     }
 
     /// Get this handle's execution ID
@@ -50,8 +78,12 @@ impl CoordinatorHandle {
     }
 
     /// Broadcast an event to all subscribers
+    ///
+    /// Fails with [`CoordError::InvalidPayload`](super::CoordError::InvalidPayload)
+    /// if `event_type` is a registered topic and `data` does not match its schema.
     pub async fn alert(&self, event_type: &str, data: serde_json::Value) -> Result<()> {
         debug!(exec_id = %self.exec_id, %event_type, "CoordinatorHandle::alert: called");
+        self.topics.validate(event_type, &data)?;
         self.tx
             .send(CoordRequest::Alert {
                 from_exec_id: self.exec_id.clone(),
@@ -65,6 +97,12 @@ impl CoordinatorHandle {
         Ok(())
     }
 
+    /// Broadcast a typed alert on its topic
+    pub async fn publish<T: Topic>(&self, payload: &T) -> Result<()> {
+        debug!(exec_id = %self.exec_id, topic = T::NAME, "CoordinatorHandle::publish: called");
+        self.alert(T::NAME, serde_json::to_value(payload)?).await
+    }
+
     /// Send a query to a specific execution and wait for a reply
     pub async fn query(&self, target_exec_id: &str, question: &str, timeout: Duration) -> Result<String> {
         debug!(exec_id = %self.exec_id, %target_exec_id, %question, ?timeout, "CoordinatorHandle::query: called");
@@ -137,8 +175,50 @@ impl CoordinatorHandle {
     }
 
     /// Subscribe to an event type
+    ///
+    /// Every notification of the type is received, replacing any filter set
+    /// by [`Self::subscribe_filtered`].
     pub async fn subscribe(&self, event_type: &str) -> Result<()> {
         debug!(exec_id = %self.exec_id, %event_type, "CoordinatorHandle::subscribe: called");
+        self.lock_filters().remove(event_type);
+        self.send_subscribe(event_type).await
+    }
+
+    /// Subscribe to a typed topic
+    pub async fn subscribe_topic<T: Topic>(&self) -> Result<()> {
+        debug!(exec_id = %self.exec_id, topic = T::NAME, "CoordinatorHandle::subscribe_topic: called");
+        self.subscribe(T::NAME).await
+    }
+
+    /// Subscribe to a typed topic, receiving only notifications `filter` accepts
+    ///
+    /// Notifications whose payload does not parse as `T` are dropped too.
+    pub async fn subscribe_filtered<T: Topic>(
+        &self,
+        filter: impl Fn(&T) -> bool + Send + Sync + 'static,
+    ) -> Result<()> {
+        debug!(exec_id = %self.exec_id, topic = T::NAME, "CoordinatorHandle::subscribe_filtered: called");
+        let filter: NotificationFilter =
+            Arc::new(move |data| T::deserialize(data).is_ok_and(|payload| filter(&payload)));
+        self.lock_filters().insert(T::NAME.to_string(), filter);
+        self.send_subscribe(T::NAME).await
+    }
+
+    fn lock_filters(&self) -> std::sync::MutexGuard<'_, HashMap<String, NotificationFilter>> {
+        self.filters.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Whether `msg` passes the filter for its event type (everything else does)
+    fn wanted(&self, msg: &CoordMessage) -> bool {
+        match msg {
+            CoordMessage::Notification { event_type, data, .. } => {
+                self.lock_filters().get(event_type).is_none_or(|filter| filter(data))
+            }
+            _ => true,
+        }
+    }
+
+    async fn send_subscribe(&self, event_type: &str) -> Result<()> {
         self.tx
             .send(CoordRequest::Subscribe {
                 exec_id: self.exec_id.clone(),
@@ -147,13 +227,14 @@ impl CoordinatorHandle {
             .await
             .map_err(|_| eyre!("Coordinator channel closed"))?;
 
-        debug!("CoordinatorHandle::subscribe: sent");
+        debug!("CoordinatorHandle::send_subscribe: sent");
         Ok(())
     }
 
     /// Unsubscribe from an event type
     pub async fn unsubscribe(&self, event_type: &str) -> Result<()> {
         debug!(exec_id = %self.exec_id, %event_type, "CoordinatorHandle::unsubscribe: called");
+        self.lock_filters().remove(event_type);
         self.tx
             .send(CoordRequest::Unsubscribe {
                 exec_id: self.exec_id.clone(),
@@ -182,26 +263,49 @@ impl CoordinatorHandle {
         Ok(())
     }
 
+    /// Claim advisory locks on paths this execution is about to edit
+    ///
+    /// Returns the paths already locked by other executions; those are not
+    /// claimed. Locks are released when the execution unregisters.
+    pub async fn lock_paths(&self, paths: &[String]) -> Result<Vec<PathConflict>> {
+        debug!(exec_id = %self.exec_id, ?paths, "CoordinatorHandle::lock_paths: called");
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.tx
+            .send(CoordRequest::LockPaths {
+                exec_id: self.exec_id.clone(),
+                paths: paths.to_vec(),
+                reply_tx,
+            })
+            .await
+            .map_err(|_| eyre!("Coordinator channel closed"))?;
+
+        reply_rx.await.map_err(|_| eyre!("Coordinator shutdown"))
+    }
+
     /// Receive messages from the Coordinator
     ///
-    /// Returns None if the channel is closed or if this is a sender-only handle.
+    /// Notifications rejected by a subscription filter are skipped. Returns
+    /// None if the channel is closed or if this is a sender-only handle.
     pub async fn recv(&self) -> Option<CoordMessage> {
         debug!(exec_id = %self.exec_id, "CoordinatorHandle::recv: called");
         let rx = self.rx.as_ref()?;
         debug!("CoordinatorHandle::recv: has receiver");
         let mut rx_guard = rx.lock().await;
-        let result = rx_guard.recv().await;
-        if result.is_some() {
-            debug!("CoordinatorHandle::recv: received message");
-        } else {
-            debug!("CoordinatorHandle::recv: channel closed");
+        while let Some(msg) = rx_guard.recv().await {
+            if self.wanted(&msg) {
+                debug!("CoordinatorHandle::recv: received message");
+                return Some(msg);
+            }
+            debug!("CoordinatorHandle::recv: filtered out notification");
         }
-        result
+        debug!("CoordinatorHandle::recv: channel closed");
+        None
     }
 
     /// Try to receive a message without blocking
     ///
-    /// Returns None if no message is available or if this is a sender-only handle.
+    /// Notifications rejected by a subscription filter are skipped. Returns
+    /// None if no message is available or if this is a sender-only handle.
     pub fn try_recv(&self) -> Option<CoordMessage> {
         debug!(exec_id = %self.exec_id, "CoordinatorHandle::try_recv: called");
         let rx = self.rx.as_ref()?;
@@ -209,13 +313,15 @@ impl CoordinatorHandle {
         // Use try_lock to avoid blocking
         let mut rx_guard = rx.try_lock().ok()?;
         debug!("CoordinatorHandle::try_recv: acquired lock");
-        let result = rx_guard.try_recv().ok();
-        if result.is_some() {
-            debug!("CoordinatorHandle::try_recv: received message");
-        } else {
-            debug!("CoordinatorHandle::try_recv: no message available");
+        while let Ok(msg) = rx_guard.try_recv() {
+            if self.wanted(&msg) {
+                debug!("CoordinatorHandle::try_recv: received message");
+                return Some(msg);
+            }
+            debug!("CoordinatorHandle::try_recv: filtered out notification");
         }
-        result
+        debug!("CoordinatorHandle::try_recv: no message available");
+        None
     }
 
     /// Get current coordinator metrics
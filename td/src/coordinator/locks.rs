@@ -0,0 +1,134 @@
+//! Advisory path locks
+//!
+//! Loops working in separate worktrees can still edit the same module, which
+//! guarantees merge pain later. Executions claim the paths they intend to
+//! touch (declared up front, or on their first write to a file); a claim that
+//! overlaps another execution's path is reported as a conflict instead of
+//! being granted. Locks are advisory: the caller decides whether to wait or
+//! carry on with a warning. A directory claim covers everything below it.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// A claimed path that overlaps a path held by another execution
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PathConflict {
+    /// The path that was claimed
+    pub path: String,
+    /// Execution holding the overlapping lock
+    pub held_by: String,
+    /// The path it holds (equal to, above or below `path`)
+    pub held_path: String,
+}
+
+/// Path locks by holder
+#[derive(Debug, Default)]
+pub(crate) struct PathLocks {
+    held: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Normalize a worktree-relative path for comparison
+///
+/// Strips leading `./` and trailing `/`, and collapses repeated separators.
+pub(crate) fn normalize_lock_path(path: &str) -> String {
+    path.split('/')
+        .filter(|part| !part.is_empty() && *part != ".")
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Whether two normalized paths are equal or one contains the other
+fn overlaps(a: &str, b: &str) -> bool {
+    let within = |inner: &str, outer: &str| {
+        inner
+            .strip_prefix(outer)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    };
+    within(a, b) || within(b, a)
+}
+
+impl PathLocks {
+    /// Conflicts `paths` would have with locks held by other executions
+    pub(crate) fn conflicts(&self, exec_id: &str, paths: &[String]) -> Vec<PathConflict> {
+        debug!(%exec_id, ?paths, "PathLocks::conflicts: called");
+        let mut conflicts = Vec::new();
+        for path in paths.iter().map(|p| normalize_lock_path(p)) {
+            for (holder, held) in self.held.iter().filter(|(holder, _)| *holder != exec_id) {
+                if let Some(held_path) = held.iter().find(|h| overlaps(h, &path)) {
+                    conflicts.push(PathConflict {
+                        path: path.clone(),
+                        held_by: holder.clone(),
+                        held_path: held_path.clone(),
+                    });
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// Claim `paths` for `exec_id`
+    ///
+    /// Paths without conflicts are locked; conflicting paths are left to their
+    /// current holders and returned.
+    pub(crate) fn claim(&mut self, exec_id: &str, paths: &[String]) -> Vec<PathConflict> {
+        debug!(%exec_id, ?paths, "PathLocks::claim: called");
+        let conflicts = self.conflicts(exec_id, paths);
+        let granted: Vec<String> = paths
+            .iter()
+            .map(|p| normalize_lock_path(p))
+            .filter(|p| !p.is_empty() && !conflicts.iter().any(|c| &c.path == p))
+            .collect();
+        if !granted.is_empty() {
+            self.held.entry(exec_id.to_string()).or_default().extend(granted);
+        }
+        conflicts
+    }
+
+    /// Release every lock held by `exec_id`, returning the paths that were held
+    pub(crate) fn release(&mut self, exec_id: &str) -> BTreeSet<String> {
+        debug!(%exec_id, "PathLocks::release: called");
+        self.held.remove(exec_id).unwrap_or_default()
+    }
+
+    /// Total number of locked paths
+    pub(crate) fn len(&self) -> usize {
+        self.held.values().map(|paths| paths.len()).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(list: &[&str]) -> Vec<String> {
+        list.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_claim_conflicts_on_overlap() {
+        let mut locks = PathLocks::default();
+        assert!(locks.claim("a", &paths(&["src/parser/", "README.md"])).is_empty());
+
+        // Same file, a file inside a held directory, and a directory above one
+        let conflicts = locks.claim(
+            "b",
+            &paths(&["./src/parser/lexer.rs", "src", "README.md", "src/cli.rs"]),
+        );
+        let conflicting: Vec<&str> = conflicts.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(conflicting, ["src/parser/lexer.rs", "src", "README.md"]);
+        assert!(conflicts.iter().all(|c| c.held_by == "a"));
+
+        // Only the non-conflicting path was granted; a holder never conflicts with itself
+        assert_eq!(locks.len(), 3);
+        assert!(locks.conflicts("a", &paths(&["src/parser/mod.rs"])).is_empty());
+        assert!(!locks.conflicts("c", &paths(&["src/cli.rs"])).is_empty());
+        assert!(locks.conflicts("c", &paths(&["src/parser2.rs"])).is_empty());
+
+        locks.release("a");
+        assert!(locks.claim("b", &paths(&["src/parser/lexer.rs"])).is_empty());
+        assert_eq!(locks.len(), 2);
+    }
+}
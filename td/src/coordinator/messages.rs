@@ -5,6 +5,14 @@ use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use tokio::sync::{mpsc, oneshot};
 
+use super::locks::PathConflict;
+use super::topics::Topic;
+
+/// Share type carrying an operator message for a loop's next prompt
+///
+/// The data is `{"message": "...", "from": "..."}`.
+pub const NUDGE_SHARE_TYPE: &str = "nudge";
+
 /// Messages sent to loops from the Coordinator
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum CoordMessage {
@@ -15,6 +23,9 @@ pub enum CoordMessage {
         #[serde(rename = "event-type")]
         event_type: String,
         data: serde_json::Value,
+        /// Position of the alert in its topic (0 for alerts that were never retained)
+        #[serde(default)]
+        seq: u64,
     },
 
     /// Query from another loop
@@ -43,6 +54,16 @@ pub enum CoordMessage {
     },
 }
 
+impl CoordMessage {
+    /// The typed payload of a notification on topic `T` (None for anything else)
+    pub fn payload<T: Topic>(&self) -> Option<T> {
+        match self {
+            Self::Notification { event_type, data, .. } if event_type == T::NAME => T::deserialize(data).ok(),
+            _ => None,
+        }
+    }
+}
+
 /// Internal requests to the Coordinator task
 #[derive(Debug)]
 pub enum CoordRequest {
@@ -86,7 +107,7 @@ pub enum CoordRequest {
         data: serde_json::Value,
     },
 
-    /// Subscribe to an event type
+    /// Subscribe to an event type, replaying retained alerts the execution missed
     Subscribe { exec_id: String, event_type: String },
 
     /// Unsubscribe from an event type
@@ -99,6 +120,20 @@ pub enum CoordRequest {
         reason: String,
     },
 
+    /// Claim advisory locks on paths; replies with the paths other executions hold
+    LockPaths {
+        exec_id: String,
+        paths: Vec<String>,
+        reply_tx: oneshot::Sender<Vec<PathConflict>>,
+    },
+
+    /// Check paths against held locks without claiming them
+    CheckPaths {
+        exec_id: String,
+        paths: Vec<String>,
+        reply_tx: oneshot::Sender<Vec<PathConflict>>,
+    },
+
     /// Query timeout notification (internal)
     QueryTimeout { query_id: String },
 
@@ -147,6 +182,11 @@ pub struct CoordinatorMetrics {
     pub messages_received: u64,
     pub query_timeouts: u64,
     pub rate_limit_violations: u64,
+    pub deadlocks_detected: u64,
+    pub path_locks: usize,
+    pub path_conflicts: u64,
+    pub alerts_replayed: u64,
+    pub invalid_alerts: u64,
 }
 
 #[cfg(test)]
@@ -159,6 +199,7 @@ mod tests {
             from_exec_id: "exec-001".to_string(),
             event_type: "phase_complete".to_string(),
             data: serde_json::json!({"phase": "Phase 1"}),
+            seq: 1,
         };
 
         let json = serde_json::to_string(&msg).unwrap();
@@ -2,17 +2,36 @@
 //!
 //! The Coordinator mediates all inter-loop communication via three primitives:
 //! - **Alert:** Broadcast event to all subscribers
-//! - **Query:** Request/reply with timeout
+//! - **Query:** Request/reply with timeout (queries that would close a wait-for
+//!   cycle fail fast with `CoordError::DeadlockDetected`)
 //! - **Share:** Point-to-point data transfer
+//!
+//! Alerts on registered [`Topic`]s (e.g. [`MainUpdated`]) have typed payloads
+//! that are validated on publish; other event types are free-form.
+//!
+//! Recent alerts are retained per topic with sequence numbers, so an execution
+//! that subscribes late (queued, paused, or between runs) still receives the
+//! ones it missed.
+//!
+//! It also keeps advisory path locks so loops in different worktrees are
+//! warned (or serialized) before they edit the same files.
 
+mod alerts;
 mod config;
 mod core;
+mod error;
 mod handle;
+mod locks;
 mod messages;
 mod persistence;
+mod topics;
 
 pub use config::CoordinatorConfig;
 pub use core::Coordinator;
+pub use error::CoordError;
 pub use handle::CoordinatorHandle;
-pub use messages::{CoordMessage, CoordRequest, CoordinatorMetrics, QueryPayload};
-pub use persistence::{EventStore, PersistedEvent, PersistedEventType};
+pub use locks::PathConflict;
+pub(crate) use locks::normalize_lock_path;
+pub use messages::{CoordMessage, CoordRequest, CoordinatorMetrics, NUDGE_SHARE_TYPE, QueryPayload};
+pub use persistence::{AlertCursors, AlertPayload, EventStore, PersistedEvent, PersistedEventType};
+pub use topics::{BudgetWarning, FileLockReleased, MainUpdated, Topic, TopicRegistry};
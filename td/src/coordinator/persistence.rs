@@ -1,8 +1,11 @@
 //! Coordinator event persistence for crash recovery
 //!
 //! Persists coordination events (alerts, queries, shares) to disk for recovery
-//! after crashes or restarts.
+//! after crashes or restarts. Alerts carry a per-topic sequence number, and the
+//! last one delivered to each execution is kept alongside, so alerts survive a
+//! restart for executions that have not seen them yet.
 
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -63,8 +66,31 @@ pub struct PersistedEvent {
     pub resolved_at: Option<i64>,
 }
 
+/// Payload of a persisted alert
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertPayload {
+    /// Topic the alert was broadcast on
+    pub event_type: String,
+    /// Position in the topic (None for alerts persisted before sequencing)
+    #[serde(default)]
+    pub seq: Option<u64>,
+    /// Alert data as JSON text
+    pub data: String,
+}
+
+/// Alert sequence state that must outlive the Coordinator
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertCursors {
+    /// Last sequence number handed out, by topic
+    #[serde(rename = "last-seq")]
+    pub last_seq: BTreeMap<String, u64>,
+    /// Last sequence number delivered, by execution then topic
+    pub delivered: BTreeMap<String, BTreeMap<String, u64>>,
+}
+
 /// Get current Unix timestamp in seconds
-fn now_timestamp() -> i64 {
+pub(crate) fn now_timestamp() -> i64 {
     debug!("now_timestamp: called");
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -92,21 +118,37 @@ impl PersistedEvent {
         }
     }
 
-    /// Create an alert event
-    pub fn alert(from_exec_id: impl Into<String>, event_type_name: &str, payload: impl Into<String>) -> Self {
-        debug!(%event_type_name, "PersistedEvent::alert: called");
+    /// Create an alert event with its position `seq` in the topic
+    pub fn alert(
+        from_exec_id: impl Into<String>,
+        event_type_name: &str,
+        seq: u64,
+        payload: impl Into<String>,
+    ) -> Self {
+        debug!(%event_type_name, %seq, "PersistedEvent::alert: called");
         Self::new(
             PersistedEventType::Alert,
             from_exec_id,
             None,
             serde_json::json!({
                 "event_type": event_type_name,
+                "seq": seq,
                 "data": payload.into()
             })
             .to_string(),
         )
     }
 
+    /// Parse the payload of an alert event (None for other event types)
+    pub fn alert_payload(&self) -> Option<AlertPayload> {
+        debug!(id = %self.id, "PersistedEvent::alert_payload: called");
+        if self.event_type != PersistedEventType::Alert {
+            debug!("PersistedEvent::alert_payload: not an alert");
+            return None;
+        }
+        serde_json::from_str(&self.payload).ok()
+    }
+
     /// Create a query event
     pub fn query(from_exec_id: impl Into<String>, to_exec_id: impl Into<String>, question: &str) -> Self {
         debug!(%question, "PersistedEvent::query: called");
@@ -176,6 +218,12 @@ impl EventStore {
         self.store_path.join("coordinator_events.jsonl")
     }
 
+    /// Get the alert cursors file path
+    fn cursors_file(&self) -> PathBuf {
+        debug!("EventStore::cursors_file: called");
+        self.store_path.join("coordinator_alert_cursors.json")
+    }
+
     /// Ensure the store directory exists
     async fn ensure_dir(&self) -> Result<()> {
         debug!(path = ?self.store_path, "EventStore::ensure_dir: called");
@@ -348,6 +396,54 @@ impl EventStore {
         Ok(removed_count)
     }
 
+    /// Remove alerts created before `cutoff` (Unix seconds), resolved or not
+    ///
+    /// Alerts are never resolved, so [`Self::cleanup_old`] keeps them forever;
+    /// they only matter for as long as they may still be replayed.
+    pub async fn prune_alerts(&self, cutoff: i64) -> Result<usize> {
+        debug!(%cutoff, "EventStore::prune_alerts: called");
+        let events = self.get_all().await?;
+        let original_count = events.len();
+        let kept: Vec<_> = events
+            .into_iter()
+            .filter(|e| e.event_type != PersistedEventType::Alert || e.created_at >= cutoff)
+            .collect();
+
+        let removed_count = original_count - kept.len();
+        if removed_count > 0 {
+            debug!(%removed_count, "EventStore::prune_alerts: removing old alerts");
+            let new_content: String = kept
+                .iter()
+                .map(|e| serde_json::to_string(e).expect("PersistedEvent serialization failed") + "\n")
+                .collect();
+            fs::write(self.events_file(), new_content).await?;
+        } else {
+            debug!("EventStore::prune_alerts: no alerts to remove");
+        }
+
+        Ok(removed_count)
+    }
+
+    /// Load alert cursors (empty if none were saved)
+    pub async fn load_alert_cursors(&self) -> Result<AlertCursors> {
+        debug!("EventStore::load_alert_cursors: called");
+        let cursors_file = self.cursors_file();
+        if !cursors_file.exists() {
+            debug!("EventStore::load_alert_cursors: cursors file does not exist");
+            return Ok(AlertCursors::default());
+        }
+        let content = fs::read_to_string(&cursors_file).await?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Save alert cursors, replacing what was there
+    pub async fn save_alert_cursors(&self, cursors: &AlertCursors) -> Result<()> {
+        debug!("EventStore::save_alert_cursors: called");
+        self.ensure_dir().await?;
+        fs::write(self.cursors_file(), serde_json::to_string(cursors)?).await?;
+        Ok(())
+    }
+
     /// Clear all events (for testing)
     pub async fn clear(&self) -> Result<()> {
         debug!("EventStore::clear: called");
@@ -374,7 +470,7 @@ mod tests {
         let temp = tempdir().unwrap();
         let store = EventStore::new(temp.path());
 
-        let event1 = PersistedEvent::alert("exec-001", "test_event", "data1");
+        let event1 = PersistedEvent::alert("exec-001", "test_event", 1, "data1");
         let event2 = PersistedEvent::query("exec-001", "exec-002", "What is the status?");
 
         store.persist(&event1).await.unwrap();
@@ -389,7 +485,7 @@ mod tests {
         let temp = tempdir().unwrap();
         let store = EventStore::new(temp.path());
 
-        let event = PersistedEvent::alert("exec-001", "test_event", "data");
+        let event = PersistedEvent::alert("exec-001", "test_event", 1, "data");
         let event_id = event.id.clone();
 
         store.persist(&event).await.unwrap();
@@ -427,7 +523,7 @@ mod tests {
         let store = EventStore::new(temp.path());
 
         // Create events involving different executions
-        let event1 = PersistedEvent::alert("exec-001", "event1", "data1");
+        let event1 = PersistedEvent::alert("exec-001", "event1", 1, "data1");
         let event2 = PersistedEvent::query("exec-001", "exec-002", "question");
         let event3 = PersistedEvent::share("exec-003", "exec-002", "type", "data");
 
@@ -454,14 +550,14 @@ mod tests {
         let store = EventStore::new(temp.path());
 
         // Create and resolve an event
-        let mut event = PersistedEvent::alert("exec-001", "old_event", "data");
+        let mut event = PersistedEvent::alert("exec-001", "old_event", 1, "data");
         // Make it look old (resolved 48 hours ago)
         event.resolved_at = Some(now_timestamp() - 48 * 3600);
 
         store.persist(&event).await.unwrap();
 
         // Create a fresh unresolved event
-        let event2 = PersistedEvent::alert("exec-002", "new_event", "data");
+        let event2 = PersistedEvent::alert("exec-002", "new_event", 1, "data");
         store.persist(&event2).await.unwrap();
 
         // Cleanup events older than 24 hours
@@ -480,7 +576,7 @@ mod tests {
         let store = EventStore::new(temp.path());
 
         store
-            .persist(&PersistedEvent::alert("exec-001", "event", "data"))
+            .persist(&PersistedEvent::alert("exec-001", "event", 1, "data"))
             .await
             .unwrap();
 
@@ -493,6 +589,46 @@ mod tests {
         assert_eq!(all.len(), 0);
     }
 
+    #[tokio::test]
+    async fn test_alert_retention_state() {
+        let temp = tempdir().unwrap();
+        let store = EventStore::new(temp.path());
+
+        let mut old = PersistedEvent::alert("exec-001", "main_updated", 1, r#"{"new_sha":"abc"}"#);
+        old.created_at -= 7200;
+        let recent = PersistedEvent::alert("exec-001", "main_updated", 2, r#"{"new_sha":"def"}"#);
+        let query = PersistedEvent::query("exec-001", "exec-002", "question");
+        for event in [&old, &recent, &query] {
+            store.persist(event).await.unwrap();
+        }
+
+        assert_eq!(
+            recent.alert_payload(),
+            Some(AlertPayload {
+                event_type: "main_updated".to_string(),
+                seq: Some(2),
+                data: r#"{"new_sha":"def"}"#.to_string(),
+            })
+        );
+        assert!(query.alert_payload().is_none());
+
+        // Only the old alert goes; queries are left to cleanup_old
+        assert_eq!(store.prune_alerts(now_timestamp() - 3600).await.unwrap(), 1);
+        let ids: Vec<_> = store.get_all().await.unwrap().into_iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![recent.id, query.id]);
+
+        assert_eq!(store.load_alert_cursors().await.unwrap(), AlertCursors::default());
+        let mut cursors = AlertCursors::default();
+        cursors.last_seq.insert("main_updated".to_string(), 2);
+        cursors
+            .delivered
+            .entry("exec-002".to_string())
+            .or_default()
+            .insert("main_updated".to_string(), 2);
+        store.save_alert_cursors(&cursors).await.unwrap();
+        assert_eq!(store.load_alert_cursors().await.unwrap(), cursors);
+    }
+
     #[test]
     fn test_event_type_display() {
         assert_eq!(PersistedEventType::Alert.to_string(), "Alert");
@@ -502,7 +638,7 @@ mod tests {
 
     #[test]
     fn test_persisted_event_constructors() {
-        let alert = PersistedEvent::alert("exec-1", "test", "payload");
+        let alert = PersistedEvent::alert("exec-1", "test", 1, "payload");
         assert_eq!(alert.event_type, PersistedEventType::Alert);
         assert!(alert.to_exec_id.is_none());
 
@@ -0,0 +1,158 @@
+//! Typed alert topics
+//!
+//! An alert is an event type plus a JSON payload. A [`Topic`] pins both down:
+//! its name is the event type and its struct is the payload schema. Topics in
+//! the Coordinator's [`TopicRegistry`] are checked when published, so a
+//! malformed alert is rejected at the sender instead of confusing every
+//! subscriber. Event types outside the registry stay free-form.
+
+use std::collections::BTreeMap;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use super::error::CoordError;
+
+/// An alert event type with a payload schema
+pub trait Topic: Serialize + DeserializeOwned {
+    /// Event type the topic is published under
+    const NAME: &'static str;
+}
+
+/// The main branch moved (published by the main watcher)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MainUpdated {
+    /// New head of the branch
+    #[serde(rename = "new_sha")]
+    pub sha: String,
+    /// Previous head, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_sha: Option<String>,
+    /// Branch that moved
+    #[serde(default = "default_branch")]
+    pub branch: String,
+}
+
+fn default_branch() -> String {
+    debug!("default_branch: called");
+    "main".to_string()
+}
+
+impl Topic for MainUpdated {
+    const NAME: &'static str = "main_updated";
+}
+
+/// An advisory path lock was released (published by the Coordinator)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileLockReleased {
+    /// Worktree-relative path that is free again
+    pub path: String,
+    /// Execution that held it
+    pub exec_id: String,
+}
+
+impl Topic for FileLockReleased {
+    const NAME: &'static str = "file_lock_released";
+}
+
+/// An execution is running out of iterations
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BudgetWarning {
+    /// Execution whose budget is low
+    pub exec_id: String,
+    /// Iterations it has left
+    pub remaining: u32,
+}
+
+impl Topic for BudgetWarning {
+    const NAME: &'static str = "budget_warning";
+}
+
+/// Checks a payload against a topic's schema
+type Validator = fn(&serde_json::Value) -> Result<(), serde_json::Error>;
+
+fn validate_as<T: Topic>(data: &serde_json::Value) -> Result<(), serde_json::Error> {
+    T::deserialize(data).map(|_| ())
+}
+
+/// Topics whose payloads are checked on publish
+///
+/// The default registry holds the built-in topics.
+#[derive(Debug, Clone)]
+pub struct TopicRegistry {
+    topics: BTreeMap<&'static str, Validator>,
+}
+
+impl Default for TopicRegistry {
+    fn default() -> Self {
+        debug!("TopicRegistry::default: called");
+        Self { topics: BTreeMap::new() }
+            .with_topic::<MainUpdated>()
+            .with_topic::<FileLockReleased>()
+            .with_topic::<BudgetWarning>()
+    }
+}
+
+impl TopicRegistry {
+    /// Register a topic (replacing any schema registered under its name)
+    pub fn with_topic<T: Topic>(mut self) -> Self {
+        debug!(topic = T::NAME, "TopicRegistry::with_topic: called");
+        self.topics.insert(T::NAME, validate_as::<T>);
+        self
+    }
+
+    /// Names of the registered topics, sorted
+    pub fn names(&self) -> Vec<&'static str> {
+        self.topics.keys().copied().collect()
+    }
+
+    /// Whether `event_type` has a registered schema
+    pub fn contains(&self, event_type: &str) -> bool {
+        self.topics.contains_key(event_type)
+    }
+
+    /// Check `data` against the schema registered for `event_type`
+    ///
+    /// Unregistered event types always pass.
+    pub fn validate(&self, event_type: &str, data: &serde_json::Value) -> Result<(), CoordError> {
+        debug!(%event_type, "TopicRegistry::validate: called");
+        match self.topics.get(event_type) {
+            Some(validate) => validate(data).map_err(|e| CoordError::InvalidPayload {
+                topic: event_type.to_string(),
+                reason: e.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_registry_validates_known_topics() {
+        let registry = TopicRegistry::default();
+        assert_eq!(registry.names(), vec!["budget_warning", "file_lock_released", "main_updated"]);
+
+        let update = MainUpdated {
+            sha: "abc".to_string(),
+            old_sha: None,
+            branch: "main".to_string(),
+        };
+        let data = serde_json::to_value(&update).unwrap();
+        assert_eq!(data, json!({"new_sha": "abc", "branch": "main"}));
+        assert!(registry.validate(MainUpdated::NAME, &data).is_ok());
+        assert!(registry.validate(MainUpdated::NAME, &json!({"sha": "abc"})).is_err());
+
+        let err = registry
+            .validate(BudgetWarning::NAME, &json!({"exec_id": "exec-1", "remaining": "two"}))
+            .unwrap_err();
+        assert!(matches!(err, CoordError::InvalidPayload { ref topic, .. } if topic == "budget_warning"));
+
+        // Free-form event types are not checked
+        assert!(registry.validate("phase_complete", &json!("anything")).is_ok());
+    }
+}
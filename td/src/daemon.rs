@@ -4,10 +4,11 @@
 
 use std::fs;
 use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
 use eyre::{Context, Result};
+use serde::{Deserialize, Serialize};
 use tracing::{debug, info, warn};
 
 /// Current version from git describe (set at compile time)
@@ -312,6 +313,77 @@ impl DaemonManager {
         debug!(?self.pid_file, "DaemonManager::pid_file: called");
         &self.pid_file
     }
+
+    /// Maintenance mode marker file (alongside PID file)
+    pub fn maintenance_file(&self) -> PathBuf {
+        debug!(?self.pid_file, "DaemonManager::maintenance_file: called");
+        self.pid_file.with_extension("maintenance")
+    }
+
+    /// Current maintenance mode, if it is on
+    pub fn maintenance(&self) -> Option<MaintenanceState> {
+        debug!("DaemonManager::maintenance: called");
+        MaintenanceState::load(&self.maintenance_file())
+    }
+}
+
+/// Maintenance mode: no executions are picked up until it is turned off
+///
+/// Persisted so the daemon stays frozen across restarts and the TUI can show
+/// a banner. `paused` lists the executions maintenance paused, which are the
+/// ones resumed when it is turned off.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct MaintenanceState {
+    /// When maintenance mode was turned on (Unix milliseconds)
+    pub since: i64,
+
+    /// Executions paused on entering maintenance mode
+    #[serde(default)]
+    pub paused: Vec<String>,
+}
+
+impl MaintenanceState {
+    /// Maintenance mode starting now with nothing paused yet
+    pub fn new() -> Self {
+        debug!("MaintenanceState::new: called");
+        Self {
+            since: taskstore::now_ms(),
+            paused: Vec::new(),
+        }
+    }
+
+    /// Read the marker file; None when maintenance mode is off
+    pub fn load(path: &Path) -> Option<Self> {
+        debug!(?path, "MaintenanceState::load: called");
+        let contents = fs::read_to_string(path).ok()?;
+        match serde_json::from_str(&contents) {
+            Ok(state) => Some(state),
+            Err(e) => {
+                warn!(?path, error = %e, "Unreadable maintenance file, treating maintenance as on");
+                Some(Self::default())
+            }
+        }
+    }
+
+    /// Write the marker file, turning maintenance mode on
+    pub fn save(&self, path: &Path) -> Result<()> {
+        debug!(?path, paused = self.paused.len(), "MaintenanceState::save: called");
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create maintenance file directory")?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?).context("Failed to write maintenance file")?;
+        Ok(())
+    }
+
+    /// Remove the marker file, turning maintenance mode off
+    pub fn clear(path: &Path) -> Result<()> {
+        debug!(?path, "MaintenanceState::clear: called");
+        if path.exists() {
+            fs::remove_file(path).context("Failed to remove maintenance file")?;
+        }
+        Ok(())
+    }
 }
 
 /// Check if a process with the given PID is running
@@ -404,6 +476,24 @@ mod tests {
         assert!(!manager.is_running());
     }
 
+    #[test]
+    fn test_maintenance_file_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = DaemonManager::with_pid_file(temp_dir.path().join("test.pid"));
+        assert_eq!(manager.maintenance_file(), temp_dir.path().join("test.maintenance"));
+        assert_eq!(manager.maintenance(), None);
+
+        let state = MaintenanceState {
+            paused: vec!["exec-1".to_string()],
+            ..MaintenanceState::new()
+        };
+        state.save(&manager.maintenance_file()).unwrap();
+        assert_eq!(manager.maintenance(), Some(state));
+
+        MaintenanceState::clear(&manager.maintenance_file()).unwrap();
+        assert_eq!(manager.maintenance(), None);
+    }
+
     #[test]
     fn test_write_and_read_pid() {
         let temp_dir = TempDir::new().unwrap();
@@ -0,0 +1,167 @@
+//! Duplicate task detection
+//!
+//! Submitting the same task twice wastes a worktree and tokens. Before an
+//! execution is created it is compared with the unfinished executions of the
+//! same loop type from the last `dedup.window-hours`: the task text of each is
+//! reduced to its set of words and two tasks whose word sets overlap by at
+//! least `dedup.threshold` (Jaccard similarity) count as duplicates. With
+//! `dedup.mode = "warn"` the execution is created anyway; with `"block"` it is
+//! refused. Either way the caller is pointed at the existing execution.
+
+use std::collections::BTreeSet;
+
+use tracing::debug;
+
+use crate::config::{DedupConfig, DedupMode};
+use crate::domain::{LoopExecution, LoopExecutionStatus};
+
+/// Words too common to tell two tasks apart
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "be", "by", "for", "from", "in", "into", "is", "it", "of", "on", "or", "please",
+    "that", "the", "this", "to", "with",
+];
+
+/// Context keys holding the task text, most specific first
+const TASK_KEYS: &[&str] = &["task", "user-request", "question"];
+
+/// An existing execution a new one looks like a copy of
+#[derive(Debug, Clone, PartialEq)]
+pub struct Duplicate {
+    /// ID of the existing execution
+    pub existing_id: String,
+    /// Its status when the check ran
+    pub existing_status: LoopExecutionStatus,
+    /// Word-overlap similarity of the two tasks (0.0-1.0)
+    pub similarity: f64,
+    /// Whether `dedup.mode` refuses the new execution
+    pub blocked: bool,
+}
+
+impl std::fmt::Display for Duplicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Looks like a duplicate of execution {} ({}, {:.0}% similar)",
+            self.existing_id,
+            self.existing_status,
+            self.similarity * 100.0
+        )
+    }
+}
+
+/// The text an execution's task is compared by: its task context, else its title
+pub fn task_text(exec: &LoopExecution) -> Option<&str> {
+    TASK_KEYS
+        .iter()
+        .find_map(|key| exec.context.get(*key).and_then(|v| v.as_str()))
+        .or(exec.title.as_deref())
+        .filter(|text| !text.trim().is_empty())
+}
+
+/// Lowercased words of `text`, without stop words
+pub fn fingerprint(text: &str) -> BTreeSet<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty() && !STOP_WORDS.contains(word))
+        .map(String::from)
+        .collect()
+}
+
+/// Jaccard similarity of two tasks' fingerprints (0.0 if either has no words)
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (fingerprint(a), fingerprint(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let shared = a.intersection(&b).count();
+    shared as f64 / (a.len() + b.len() - shared) as f64
+}
+
+/// The most similar recent unfinished execution of the same type, if it is similar enough
+///
+/// Returns None when `config.mode` is off or `exec` has no task text.
+pub fn find_duplicate(
+    exec: &LoopExecution,
+    candidates: &[LoopExecution],
+    config: &DedupConfig,
+    now_ms: i64,
+) -> Option<Duplicate> {
+    debug!(exec_id = %exec.id, candidates = candidates.len(), ?config, "find_duplicate: called");
+    if config.mode == DedupMode::Off {
+        return None;
+    }
+    let text = task_text(exec)?;
+    let since = now_ms - (config.window_hours as i64) * 3_600_000;
+
+    let (existing, score) = candidates
+        .iter()
+        .filter(|c| c.id != exec.id && c.loop_type == exec.loop_type)
+        .filter(|c| !c.is_terminal() && c.created_at >= since)
+        .filter_map(|c| task_text(c).map(|other| (c, similarity(text, other))))
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+    if score < config.threshold {
+        debug!(closest = %existing.id, score, "find_duplicate: no duplicate");
+        return None;
+    }
+
+    debug!(existing = %existing.id, score, "find_duplicate: found duplicate");
+    Some(Duplicate {
+        existing_id: existing.id.clone(),
+        existing_status: existing.status,
+        similarity: score,
+        blocked: config.mode == DedupMode::Block,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: &str, text: &str) -> LoopExecution {
+        LoopExecution::with_id(id, "ralph").with_context_value("task", text)
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity("Fix the login bug", "fix login bug!"), 1.0);
+        assert!(similarity("Fix the login bug in auth", "Fix login bug") > 0.7);
+        assert!(similarity("Fix the login bug", "Add dark mode to settings") < 0.1);
+        assert_eq!(similarity("the", "Fix login"), 0.0);
+    }
+
+    #[test]
+    fn test_find_duplicate() {
+        let now = taskstore::now_ms();
+        let config = DedupConfig::default();
+        let new = task("new", "Add rate limiting to the API gateway");
+
+        let mut finished = task("done", "Add rate limiting to the API gateway");
+        finished.set_status(LoopExecutionStatus::Complete);
+        let mut old = task("old", "Add rate limiting to the API gateway");
+        old.created_at = now - 48 * 3_600_000;
+        let mut other_type = task("plan", "Add rate limiting to the API gateway");
+        other_type.loop_type = "plan".to_string();
+        let unrelated = task("unrelated", "Upgrade tokio");
+        let candidates = vec![finished, old, other_type, unrelated];
+        assert_eq!(find_duplicate(&new, &candidates, &config, now), None);
+
+        let mut running = task("running", "add rate-limiting to API gateway");
+        running.set_status(LoopExecutionStatus::Running);
+        let candidates = [candidates, vec![running]].concat();
+        let duplicate = find_duplicate(&new, &candidates, &config, now).unwrap();
+        assert_eq!(duplicate.existing_id, "running");
+        assert!(!duplicate.blocked);
+        assert!(duplicate.to_string().contains("running, 100% similar"));
+
+        let block = DedupConfig {
+            mode: DedupMode::Block,
+            ..config.clone()
+        };
+        assert!(find_duplicate(&new, &candidates, &block, now).unwrap().blocked);
+        let off = DedupConfig {
+            mode: DedupMode::Off,
+            ..config
+        };
+        assert_eq!(find_duplicate(&new, &candidates, &off, now), None);
+    }
+}
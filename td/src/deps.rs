@@ -0,0 +1,401 @@
+//! Dependency upgrades: outdated packages, per-dependency loops and the summary
+//!
+//! The `deps-upgrade` loop type lists outdated dependencies with the
+//! `outdated_deps` tool, which parses `cargo outdated` and `npm outdated` and
+//! saves the list to [`OUTDATED_FILE`] in the worktree. When the loop
+//! completes, the cascade creates one `dep-bump` execution per dependency that
+//! bumps it, updates the lockfile and fixes whatever breaks. `dep-bump` loops
+//! share an exclusive group so only one edits the lockfile at a time. Once
+//! every child has finished, the join renders a summary table of upgraded and
+//! failed packages with [`render_summary`].
+
+use std::path::{Path, PathBuf};
+
+use eyre::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::debug;
+
+use crate::domain::{LoopExecution, LoopExecutionStatus};
+
+/// Loop type that lists outdated dependencies
+pub const DEPS_UPGRADE_TYPE: &str = "deps-upgrade";
+
+/// Loop type that upgrades a single dependency
+pub const DEP_BUMP_TYPE: &str = "dep-bump";
+
+/// Directory (relative to the worktree) holding the outdated list
+pub const DEPS_DIR: &str = ".taskdaemon/deps";
+
+/// Outdated dependencies to upgrade, as JSON
+pub const OUTDATED_FILE: &str = "outdated.json";
+
+/// Characters of a failed upgrade's error shown in the summary
+const SUMMARY_ERROR_CHARS: usize = 80;
+
+/// A package manager whose dependencies can be upgraded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Ecosystem {
+    Cargo,
+    Npm,
+}
+
+impl Ecosystem {
+    /// Every supported ecosystem
+    pub const ALL: [Ecosystem; 2] = [Ecosystem::Cargo, Ecosystem::Npm];
+
+    /// Manifest whose presence enables the ecosystem
+    pub fn manifest(&self) -> &'static str {
+        match self {
+            Self::Cargo => "Cargo.toml",
+            Self::Npm => "package.json",
+        }
+    }
+
+    /// Lockfile that must be updated together with the manifest
+    pub fn lockfile(&self) -> &'static str {
+        match self {
+            Self::Cargo => "Cargo.lock",
+            Self::Npm => "package-lock.json",
+        }
+    }
+
+    /// Command listing outdated direct dependencies as JSON
+    pub fn outdated_command(&self) -> &'static str {
+        match self {
+            Self::Cargo => "cargo outdated --root-deps-only --format json",
+            Self::Npm => "npm outdated --json --long",
+        }
+    }
+
+    /// Ecosystems with a manifest in `root`
+    pub fn detect(root: &Path) -> Vec<Ecosystem> {
+        let found: Vec<Ecosystem> = Self::ALL
+            .into_iter()
+            .filter(|e| root.join(e.manifest()).exists())
+            .collect();
+        debug!(?root, ?found, "Ecosystem::detect: called");
+        found
+    }
+}
+
+impl std::fmt::Display for Ecosystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cargo => write!(f, "cargo"),
+            Self::Npm => write!(f, "npm"),
+        }
+    }
+}
+
+impl std::str::FromStr for Ecosystem {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cargo" => Ok(Self::Cargo),
+            "npm" => Ok(Self::Npm),
+            other => Err(format!("Unknown ecosystem '{}' (expected cargo or npm)", other)),
+        }
+    }
+}
+
+/// How a dependency is used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DepKind {
+    #[default]
+    Normal,
+    Dev,
+    Build,
+}
+
+impl std::fmt::Display for DepKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Normal => write!(f, "normal"),
+            Self::Dev => write!(f, "dev"),
+            Self::Build => write!(f, "build"),
+        }
+    }
+}
+
+/// A direct dependency with a newer release
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct OutdatedDep {
+    pub name: String,
+    pub ecosystem: Ecosystem,
+    #[serde(default)]
+    pub kind: DepKind,
+    /// Version in the lockfile
+    pub current: String,
+    /// Newest published version
+    pub latest: String,
+}
+
+impl OutdatedDep {
+    /// Command that bumps the manifest requirement and the lockfile
+    pub fn bump_command(&self) -> String {
+        match (self.ecosystem, self.kind) {
+            (Ecosystem::Cargo, DepKind::Normal) => format!("cargo add {}@{}", self.name, self.latest),
+            (Ecosystem::Cargo, kind) => format!("cargo add --{} {}@{}", kind, self.name, self.latest),
+            (Ecosystem::Npm, DepKind::Dev) => format!("npm install --save-dev {}@{}", self.name, self.latest),
+            (Ecosystem::Npm, _) => format!("npm install {}@{}", self.name, self.latest),
+        }
+    }
+}
+
+/// Parse the output of an ecosystem's [`Ecosystem::outdated_command`]
+pub fn parse_outdated(ecosystem: Ecosystem, stdout: &str) -> Result<Vec<OutdatedDep>> {
+    debug!(%ecosystem, len = stdout.len(), "parse_outdated: called");
+    let mut deps = match ecosystem {
+        Ecosystem::Cargo => parse_cargo_outdated(stdout)?,
+        Ecosystem::Npm => parse_npm_outdated(stdout)?,
+    };
+    deps.sort_by(|a, b| a.name.cmp(&b.name));
+    deps.dedup_by(|a, b| a.name == b.name);
+    Ok(deps)
+}
+
+/// `cargo outdated --format json` prints one object per workspace member
+fn parse_cargo_outdated(stdout: &str) -> Result<Vec<OutdatedDep>> {
+    let mut deps = Vec::new();
+    for line in stdout.lines().map(str::trim).filter(|l| l.starts_with('{')) {
+        let member: Value = serde_json::from_str(line).context("Invalid cargo outdated JSON")?;
+        for dep in member["dependencies"].as_array().into_iter().flatten() {
+            let (Some(name), Some(current), Some(latest)) =
+                (dep["name"].as_str(), dep["project"].as_str(), dep["latest"].as_str())
+            else {
+                continue;
+            };
+            // "---" means no newer release, "Removed" a dependency that went away
+            if latest == current || latest == "---" || latest == "Removed" {
+                continue;
+            }
+            let kind = match dep["kind"].as_str() {
+                Some("Development") => DepKind::Dev,
+                Some("Build") => DepKind::Build,
+                _ => DepKind::Normal,
+            };
+            deps.push(OutdatedDep {
+                name: name.to_string(),
+                ecosystem: Ecosystem::Cargo,
+                kind,
+                current: current.to_string(),
+                latest: latest.to_string(),
+            });
+        }
+    }
+    Ok(deps)
+}
+
+/// `npm outdated --json --long` maps package names to one entry, or one per workspace
+fn parse_npm_outdated(stdout: &str) -> Result<Vec<OutdatedDep>> {
+    if stdout.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let report: Value = serde_json::from_str(stdout).context("Invalid npm outdated JSON")?;
+    let Some(packages) = report.as_object() else {
+        eyre::bail!("npm outdated JSON is not an object");
+    };
+    let mut deps = Vec::new();
+    for (name, entries) in packages {
+        let entries = match entries {
+            Value::Array(entries) => entries.iter().collect(),
+            entry => vec![entry],
+        };
+        for entry in entries {
+            let Some(latest) = entry["latest"].as_str() else {
+                continue;
+            };
+            // Not installed yet: the lockfile is the next best source
+            let current = entry["current"].as_str().or(entry["wanted"].as_str()).unwrap_or("none");
+            if current == latest {
+                continue;
+            }
+            let kind = match entry["type"].as_str() {
+                Some("devDependencies") => DepKind::Dev,
+                _ => DepKind::Normal,
+            };
+            deps.push(OutdatedDep {
+                name: name.clone(),
+                ecosystem: Ecosystem::Npm,
+                kind,
+                current: current.to_string(),
+                latest: latest.to_string(),
+            });
+        }
+    }
+    Ok(deps)
+}
+
+/// Path of the outdated list inside `worktree`
+pub fn outdated_path(worktree: &Path) -> PathBuf {
+    worktree.join(DEPS_DIR).join(OUTDATED_FILE)
+}
+
+/// Write the dependencies to upgrade into `worktree`
+pub fn save_outdated(worktree: &Path, deps: &[OutdatedDep]) -> Result<()> {
+    let path = outdated_path(worktree);
+    debug!(?path, count = deps.len(), "save_outdated: called");
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(deps)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Read the dependencies to upgrade from `worktree` (None if `outdated_deps` never ran)
+pub fn load_outdated(worktree: &Path) -> Result<Option<Vec<OutdatedDep>>> {
+    let path = outdated_path(worktree);
+    debug!(?path, "load_outdated: called");
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let deps = serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))?;
+    Ok(Some(deps))
+}
+
+/// Markdown table of a `deps-upgrade` run's `dep-bump` children
+pub fn render_summary(children: &[LoopExecution]) -> String {
+    debug!(count = children.len(), "render_summary: called");
+    if children.is_empty() {
+        return "# Dependency upgrades\n\nEvery dependency is up to date.\n".to_string();
+    }
+
+    let context = |exec: &LoopExecution, key: &str| {
+        exec.context
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or("-")
+            .to_string()
+    };
+    let mut upgraded = 0;
+    let mut rows = String::new();
+    for exec in children {
+        let result = match exec.status {
+            LoopExecutionStatus::Complete => {
+                upgraded += 1;
+                "upgraded".to_string()
+            }
+            _ => {
+                let reason: String = exec
+                    .last_error
+                    .as_deref()
+                    .unwrap_or("")
+                    .chars()
+                    .take(SUMMARY_ERROR_CHARS)
+                    .collect();
+                let reason = reason.replace('|', "\\|").replace('\n', " ");
+                if reason.is_empty() {
+                    exec.status.to_string()
+                } else {
+                    format!("{}: {}", exec.status, reason)
+                }
+            }
+        };
+        rows.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} |\n",
+            context(exec, "dep-name"),
+            context(exec, "dep-ecosystem"),
+            context(exec, "dep-current"),
+            context(exec, "dep-latest"),
+            result,
+            exec.id
+        ));
+    }
+
+    format!(
+        "# Dependency upgrades\n\n{} upgraded, {} failed\n\n\
+         | Package | Ecosystem | From | To | Result | Execution |\n\
+         |---------|-----------|------|----|--------|-----------|\n{}",
+        upgraded,
+        children.len() - upgraded,
+        rows
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_cargo_outdated() {
+        let stdout = concat!(
+            r#"{"crate_name":"app","dependencies":[{"name":"serde","project":"1.0.100","compat":"1.0.200","latest":"1.0.200","kind":"Normal","platform":null},{"name":"gone","project":"0.1.0","compat":"---","latest":"Removed","kind":"Normal","platform":null}]}"#,
+            "\n",
+            r#"{"crate_name":"cli","dependencies":[{"name":"serde","project":"1.0.100","compat":"1.0.200","latest":"1.0.200","kind":"Normal","platform":null},{"name":"tempfile","project":"2.0.0","compat":"---","latest":"3.10.0","kind":"Development","platform":null}]}"#,
+        );
+
+        let deps = parse_outdated(Ecosystem::Cargo, stdout).unwrap();
+
+        assert_eq!(deps.len(), 2);
+        assert_eq!(deps[0].name, "serde");
+        assert_eq!(deps[0].bump_command(), "cargo add serde@1.0.200");
+        assert_eq!(deps[1].kind, DepKind::Dev);
+        assert_eq!(deps[1].bump_command(), "cargo add --dev tempfile@3.10.0");
+    }
+
+    #[test]
+    fn test_parse_npm_outdated() {
+        let stdout = r#"{
+            "lodash": {"current": "4.17.0", "wanted": "4.17.21", "latest": "4.17.21", "type": "dependencies"},
+            "jest": [{"current": "28.0.0", "wanted": "28.1.3", "latest": "29.7.0", "type": "devDependencies"}],
+            "left-pad": {"current": "1.3.0", "wanted": "1.3.0", "latest": "1.3.0", "type": "dependencies"}
+        }"#;
+
+        let deps = parse_outdated(Ecosystem::Npm, stdout).unwrap();
+
+        assert_eq!(deps.len(), 2);
+        assert_eq!(deps[0].name, "jest");
+        assert_eq!(deps[0].bump_command(), "npm install --save-dev jest@29.7.0");
+        assert_eq!(deps[1].current, "4.17.0");
+        assert!(parse_outdated(Ecosystem::Npm, "").unwrap().is_empty());
+        assert!(parse_outdated(Ecosystem::Npm, "npm ERR!").is_err());
+    }
+
+    #[test]
+    fn test_save_and_load_outdated() {
+        let temp = tempdir().unwrap();
+        assert!(load_outdated(temp.path()).unwrap().is_none());
+        std::fs::write(temp.path().join("Cargo.toml"), "[package]").unwrap();
+        assert_eq!(Ecosystem::detect(temp.path()), vec![Ecosystem::Cargo]);
+
+        let deps = vec![OutdatedDep {
+            name: "serde".to_string(),
+            ecosystem: Ecosystem::Cargo,
+            kind: DepKind::Normal,
+            current: "1.0.100".to_string(),
+            latest: "1.0.200".to_string(),
+        }];
+        save_outdated(temp.path(), &deps).unwrap();
+
+        assert_eq!(load_outdated(temp.path()).unwrap(), Some(deps));
+    }
+
+    #[test]
+    fn test_render_summary() {
+        let child = |name: &str, status: LoopExecutionStatus| {
+            let mut exec = LoopExecution::new(DEP_BUMP_TYPE, name)
+                .with_context_value("dep-name", name)
+                .with_context_value("dep-ecosystem", "cargo")
+                .with_context_value("dep-current", "1.0.0")
+                .with_context_value("dep-latest", "2.0.0");
+            exec.set_status(status);
+            exec
+        };
+        let mut failed = child("tokio", LoopExecutionStatus::Failed);
+        failed.set_error("Max iterations reached | build broken");
+
+        let summary = render_summary(&[child("serde", LoopExecutionStatus::Complete), failed]);
+
+        assert!(summary.contains("1 upgraded, 1 failed"));
+        assert!(summary.contains("| serde | cargo | 1.0.0 | 2.0.0 | upgraded |"));
+        assert!(summary.contains("failed: Max iterations reached \\| build broken"));
+        assert!(render_summary(&[]).contains("up to date"));
+    }
+}
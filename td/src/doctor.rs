@@ -0,0 +1,512 @@
+//! Environment diagnostics (`td doctor`)
+//!
+//! Each check reports ok, warn or fail with a one-line detail and, when
+//! something is wrong, the fix to apply. `td doctor --json` prints the same
+//! report for CI health checks and exits non-zero if any check failed.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::process::Command;
+use tracing::debug;
+
+use crate::cli::ToolCheck;
+use crate::config::{Config, LoopsConfig};
+use crate::daemon::{DaemonManager, VERSION};
+use crate::ipc::DaemonClient;
+use crate::r#loop::LoopLoader;
+use crate::state::StateManager;
+use crate::worktree::format_size;
+
+/// Free space below which the disk check fails
+const DISK_FAIL_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Free space below which the disk check warns
+const DISK_WARN_BYTES: u64 = 5 * 1024 * 1024 * 1024;
+
+/// How long to wait for the daemon to answer a ping
+const PING_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Outcome of a single check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Warn,
+    Fail,
+}
+
+/// One diagnostic check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Check {
+    /// Short name, e.g. "git" or "taskstore"
+    pub name: String,
+    pub status: CheckStatus,
+    /// What was found
+    pub detail: String,
+    /// How to fix it (warn and fail only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn ok(name: &str, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: CheckStatus::Ok,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(name: &str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: CheckStatus::Warn,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn fail(name: &str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Result of all checks
+#[derive(Debug, Clone, Serialize)]
+pub struct DoctorReport {
+    /// False if any check failed
+    pub healthy: bool,
+    pub checks: Vec<Check>,
+}
+
+impl DoctorReport {
+    fn new(checks: Vec<Check>) -> Self {
+        let healthy = checks.iter().all(|c| c.status != CheckStatus::Fail);
+        Self { healthy, checks }
+    }
+
+    /// Human-readable report with a summary line
+    pub fn render_text(&self) -> String {
+        let mut out = String::new();
+        for check in &self.checks {
+            let icon = match check.status {
+                CheckStatus::Ok => "\u{2705}",
+                CheckStatus::Warn => "\u{26A0}\u{FE0F} ",
+                CheckStatus::Fail => "\u{274C}",
+            };
+            out.push_str(&format!("{} {:<12} {}\n", icon, check.name, check.detail));
+            if let Some(fix) = &check.fix {
+                out.push_str(&format!("   {:<12} fix: {}\n", "", fix));
+            }
+        }
+        let count = |status| self.checks.iter().filter(|c| c.status == status).count();
+        out.push_str(&format!(
+            "\n{} checks: {} ok, {} warnings, {} failed\n",
+            self.checks.len(),
+            count(CheckStatus::Ok),
+            count(CheckStatus::Warn),
+            count(CheckStatus::Fail)
+        ));
+        out
+    }
+}
+
+/// Run all checks from `cwd` (the project directory)
+///
+/// `config` is the result of loading the configuration; when it failed to
+/// load, that is reported and the remaining checks use the defaults.
+pub async fn diagnose(config: eyre::Result<Config>, cwd: &Path) -> DoctorReport {
+    debug!(?cwd, "diagnose: called");
+    let mut checks = Vec::new();
+    let config = match config {
+        Ok(config) => {
+            checks.push(match &config.source {
+                Some(path) => Check::ok("config", format!("Loaded {}", path.display())),
+                None => Check::warn(
+                    "config",
+                    "No config file found, using defaults",
+                    "Create .taskdaemon.yml in the project or ~/.config/taskdaemon/taskdaemon.yml",
+                ),
+            });
+            config
+        }
+        Err(e) => {
+            checks.push(Check::fail(
+                "config",
+                format!("{:#}", e),
+                "Fix the config file (see docs/config-schema.md) or pass another with --config",
+            ));
+            Config::default()
+        }
+    };
+
+    checks.extend(check_git(cwd).await);
+    checks.extend(check_api_keys(&config));
+    checks.push(check_daemon().await);
+    checks.push(check_taskstore(Path::new(&config.storage.taskstore_dir)).await);
+    checks.push(check_loop_types(&config.loops));
+    checks.push(check_worktree_dir(&config.git.worktree_dir));
+    checks.push(check_disk_space(&config.git.worktree_dir).await);
+    checks.push(check_tail());
+
+    DoctorReport::new(checks)
+}
+
+/// git is installed, `cwd` is inside a repository, and it has a `main` branch
+async fn check_git(cwd: &Path) -> Vec<Check> {
+    debug!(?cwd, "check_git: called");
+    let tool = ToolCheck::check("git", &["--version"]);
+    if !tool.available {
+        return vec![Check::fail(
+            "git",
+            "git not found on PATH",
+            "Install git (loops run in git worktrees)",
+        )];
+    }
+    let mut checks = vec![Check::ok("git", format!("git {}", tool.version.unwrap_or_default()))];
+
+    let toplevel = Command::new("git")
+        .args(["rev-parse", "--show-toplevel"])
+        .current_dir(cwd)
+        .output()
+        .await;
+    let root = match toplevel {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        _ => {
+            checks.push(Check::fail(
+                "repository",
+                format!("{} is not inside a git repository", cwd.display()),
+                "Run td from your project's repository (or `git init` it)",
+            ));
+            return checks;
+        }
+    };
+
+    let has_main = Command::new("git")
+        .args(["rev-parse", "--verify", "--quiet", "refs/heads/main"])
+        .current_dir(cwd)
+        .status()
+        .await
+        .is_ok_and(|s| s.success());
+    checks.push(if has_main {
+        Check::ok("repository", root)
+    } else {
+        Check::warn(
+            "repository",
+            format!("{} has no `main` branch", root),
+            "Completed loops are merged into `main`; create it (git branch main) or rename your default branch",
+        )
+    });
+    checks
+}
+
+/// API keys of the default model and the evaluation judge can be read
+fn check_api_keys(config: &Config) -> Vec<Check> {
+    debug!(default = %config.llm.default, "check_api_keys: called");
+    let mut specs = vec![config.llm.default.clone()];
+    if config.evaluation.enabled
+        && let Some(judge) = &config.evaluation.model
+        && !specs.contains(judge)
+    {
+        specs.push(judge.clone());
+    }
+
+    specs
+        .iter()
+        .map(|spec| {
+            let resolved = match config.llm.resolve_model(spec) {
+                Ok(resolved) => resolved,
+                Err(e) => {
+                    return Check::fail(
+                        "api-key",
+                        format!("Cannot resolve model '{}': {}", spec, e),
+                        "Set llm.default to a configured provider/model, e.g. openai/gpt-4o",
+                    );
+                }
+            };
+            if resolved.api == "local" {
+                return Check::ok("api-key", format!("{}: local server, no key needed", spec));
+            }
+            match resolved.get_api_key() {
+                Ok(key) if !key.is_empty() => {
+                    let from_secret = resolved.api_key_secret.as_ref().filter(|name| {
+                        crate::secrets::open(&resolved.secrets)
+                            .and_then(|store| store.get(name))
+                            .is_ok_and(|value| value.is_some())
+                    });
+                    let source = if std::env::var(&resolved.api_key_env).is_ok() {
+                        format!("${}", resolved.api_key_env)
+                    } else if let Some(name) = from_secret {
+                        format!("secret {} ({})", name, resolved.secrets.backend)
+                    } else {
+                        resolved.api_key_file.clone().unwrap_or_default()
+                    };
+                    Check::ok("api-key", format!("{}: found in {}", spec, source))
+                }
+                _ => Check::fail(
+                    "api-key",
+                    format!("{}: no API key", spec),
+                    match &resolved.api_key_secret {
+                        Some(name) => format!(
+                            "export {}=<key>, or run `td secrets set {}`",
+                            resolved.api_key_env, name
+                        ),
+                        None => format!(
+                            "export {}=<key>, or set llm.providers.{}.api-key-file or api-key-secret",
+                            resolved.api_key_env, resolved.provider
+                        ),
+                    },
+                ),
+            }
+        })
+        .collect()
+}
+
+/// PID file and IPC socket agree, and a running daemon answers pings
+async fn check_daemon() -> Check {
+    debug!("check_daemon: called");
+    let daemon = DaemonManager::new();
+    let Some(pid) = daemon.running_pid() else {
+        if daemon.pid_file().exists() {
+            return Check::warn(
+                "daemon",
+                format!("Stale PID file {} (process is gone)", daemon.pid_file().display()),
+                "Run `td daemon start` (it replaces the file) or delete it",
+            );
+        }
+        return Check::ok("daemon", "Not running (start with `td daemon start`)");
+    };
+
+    let client = DaemonClient::new().with_timeout(PING_TIMEOUT);
+    if !client.socket_exists() {
+        return Check::fail(
+            "daemon",
+            format!("Running (pid {}) but its IPC socket is missing", pid),
+            "Restart it: td daemon stop && td daemon start",
+        );
+    }
+    match client.ping().await {
+        Ok(version) if version == VERSION => Check::ok("daemon", format!("Running (pid {}), responsive", pid)),
+        Ok(version) => Check::warn(
+            "daemon",
+            format!("Running (pid {}) version {}, this td is {}", pid, version, VERSION),
+            "Restart it to pick up the new version: td daemon stop && td daemon start",
+        ),
+        Err(e) => Check::fail(
+            "daemon",
+            format!("Running (pid {}) but not answering: {}", pid, e),
+            "Restart it: td daemon stop && td daemon start",
+        ),
+    }
+}
+
+/// The TaskStore opens and its executions can be listed
+async fn check_taskstore(path: &Path) -> Check {
+    debug!(?path, "check_taskstore: called");
+    if !path.exists() {
+        return Check::warn(
+            "taskstore",
+            format!("{} does not exist yet", path.display()),
+            "It is created the first time td or the daemon runs",
+        );
+    }
+    let fix = format!(
+        "Check permissions on {}; if a JSONL file is corrupt, move it aside",
+        path.display()
+    );
+    let state = match StateManager::spawn(path) {
+        Ok(state) => state,
+        Err(e) => return Check::fail("taskstore", format!("Cannot open: {}", e), fix),
+    };
+    let check = match state.list_executions(None, None).await {
+        Ok(executions) => Check::ok(
+            "taskstore",
+            format!("{} ({} executions)", path.display(), executions.len()),
+        ),
+        Err(e) => Check::fail("taskstore", format!("Cannot read: {}", e), fix),
+    };
+    let _ = state.shutdown().await;
+    check
+}
+
+/// Every loop type file parses and inheritance resolves
+fn check_loop_types(loops: &LoopsConfig) -> Check {
+    debug!(?loops.paths, "check_loop_types: called");
+    let loader = match LoopLoader::new(loops) {
+        Ok(loader) => loader,
+        Err(e) => {
+            return Check::fail(
+                "loop-types",
+                format!("{:#}", e),
+                "Fix the loop type definitions (check `extends` names)",
+            );
+        }
+    };
+    if let Some((path, error)) = loader.load_errors().first() {
+        let others = loader.load_errors().len() - 1;
+        let more = if others > 0 { format!(" (and {} more)", others) } else { String::new() };
+        return Check::fail(
+            "loop-types",
+            format!("{}: {}{}", path.display(), error, more),
+            "Fix the YAML; invalid files are skipped, so their loop types are unavailable",
+        );
+    }
+    Check::ok("loop-types", format!("{} loop types valid", loader.names().count()))
+}
+
+/// The worktree directory exists (or can be created) and is writable
+fn check_worktree_dir(dir: &Path) -> Check {
+    debug!(?dir, "check_worktree_dir: called");
+    let fix = "Set git.worktree-dir to a writable directory";
+    if let Err(e) = std::fs::create_dir_all(dir) {
+        return Check::fail("worktrees", format!("Cannot create {}: {}", dir.display(), e), fix);
+    }
+    let probe = dir.join(format!(".td-doctor-{}", std::process::id()));
+    match std::fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+            Check::ok("worktrees", format!("{} is writable", dir.display()))
+        }
+        Err(e) => Check::fail("worktrees", format!("Cannot write to {}: {}", dir.display(), e), fix),
+    }
+}
+
+/// Available bytes from `df -Pk` output
+fn parse_df_available(output: &str) -> Option<u64> {
+    let fields: Vec<&str> = output.lines().nth(1)?.split_whitespace().collect();
+    fields.get(3)?.parse::<u64>().ok().map(|kb| kb * 1024)
+}
+
+/// Free space on the filesystem holding the worktrees (and their build output)
+async fn check_disk_space(dir: &Path) -> Check {
+    debug!(?dir, "check_disk_space: called");
+    let existing: PathBuf = dir
+        .ancestors()
+        .find(|p| p.exists())
+        .map_or_else(|| PathBuf::from("/"), Path::to_path_buf);
+    let output = Command::new("df").arg("-Pk").arg(&existing).output().await;
+    let available = match output {
+        Ok(output) if output.status.success() => parse_df_available(&String::from_utf8_lossy(&output.stdout)),
+        _ => None,
+    };
+    let Some(available) = available else {
+        return Check::warn(
+            "disk",
+            format!("Could not determine free space for {}", existing.display()),
+            "Make sure `df` is installed",
+        );
+    };
+
+    let detail = format!("{} free on {}", format_size(available), existing.display());
+    if available < DISK_FAIL_BYTES {
+        Check::fail(
+            "disk",
+            detail,
+            "Free up space; `td worktree prune` removes finished worktrees",
+        )
+    } else if available < DISK_WARN_BYTES {
+        Check::warn(
+            "disk",
+            detail,
+            "Worktrees with build output can be large; `td worktree prune` or set git.disk-quota-gb",
+        )
+    } else {
+        Check::ok("disk", detail)
+    }
+}
+
+/// `tail` is available for `td logs --follow`
+fn check_tail() -> Check {
+    debug!("check_tail: called");
+    let tool = ToolCheck::check("tail", &["--version"]);
+    if tool.available {
+        Check::ok("tail", "available")
+    } else {
+        Check::warn(
+            "tail",
+            "tail not found on PATH",
+            "Install coreutils; `td logs --follow` needs tail",
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_report_health_and_text() {
+        let report = DoctorReport::new(vec![
+            Check::ok("git", "git 2.43"),
+            Check::warn("tail", "tail not found on PATH", "Install coreutils"),
+        ]);
+        assert!(report.healthy);
+        let text = report.render_text();
+        assert!(text.contains("fix: Install coreutils"));
+        assert!(text.contains("2 checks: 1 ok, 1 warnings, 0 failed"));
+
+        let report = DoctorReport::new(vec![Check::fail("disk", "100 MB free", "Free up space")]);
+        assert!(!report.healthy);
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["healthy"], false);
+        assert_eq!(json["checks"][0]["status"], "fail");
+        assert_eq!(json["checks"][0]["fix"], "Free up space");
+    }
+
+    #[test]
+    fn test_parse_df_available() {
+        let output = "Filesystem     1024-blocks      Used Available Capacity Mounted on\n\
+                      /dev/sda1        102400000  51200000  51200000      50% /\n";
+        assert_eq!(parse_df_available(output), Some(51_200_000 * 1024));
+        assert_eq!(parse_df_available("garbage"), None);
+    }
+
+    #[test]
+    fn test_check_loop_types_reports_invalid_files() {
+        let temp = tempdir().unwrap();
+        let loops = LoopsConfig {
+            paths: vec![temp.path().to_string_lossy().to_string()],
+            ..Default::default()
+        };
+        std::fs::write(temp.path().join("good.yml"), "prompt-template: \"Do it\"\n").unwrap();
+        assert_eq!(check_loop_types(&loops).status, CheckStatus::Ok);
+
+        std::fs::write(temp.path().join("broken.yml"), "prompt-template: [unclosed\n").unwrap();
+        let check = check_loop_types(&loops);
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert!(check.detail.contains("broken.yml"));
+    }
+
+    #[test]
+    fn test_check_worktree_dir() {
+        let temp = tempdir().unwrap();
+        assert_eq!(
+            check_worktree_dir(&temp.path().join("worktrees")).status,
+            CheckStatus::Ok
+        );
+        assert!(temp.path().join("worktrees").is_dir());
+
+        let file = temp.path().join("file");
+        std::fs::write(&file, "").unwrap();
+        assert_eq!(check_worktree_dir(&file.join("worktrees")).status, CheckStatus::Fail);
+    }
+
+    #[tokio::test]
+    async fn test_check_git_outside_repository() {
+        let temp = tempdir().unwrap();
+        let checks = check_git(temp.path()).await;
+        if checks[0].status == CheckStatus::Ok {
+            assert_eq!(checks[1].name, "repository");
+            assert_eq!(checks[1].status, CheckStatus::Fail);
+        }
+    }
+}
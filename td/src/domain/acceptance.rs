@@ -0,0 +1,185 @@
+//! Acceptance criteria for executions
+//!
+//! A plan or spec may end with a fenced YAML block holding an `acceptance:`
+//! list of machine-checkable assertions. The cascade copies them onto each
+//! execution created from that document; the LoopEngine re-checks them every
+//! iteration and the execution only completes once validation passes and
+//! every criterion holds.
+//!
+//! ````markdown
+//! ## Acceptance
+//!
+//! ```yaml
+//! acceptance:
+//!   - type: command
+//!     command: cargo test -p taskdaemon
+//!   - type: file-exists
+//!     path: td/src/ipc/writer.rs
+//!   - type: grep
+//!     path: td/src/ipc/mod.rs
+//!     pattern: "pub mod writer"
+//! ```
+//! ````
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// A single machine-checkable assertion
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum AcceptanceCriterion {
+    /// A shell command exits 0 (run in the worktree)
+    Command { command: String },
+
+    /// A file matching the glob exists (relative paths resolve against the worktree)
+    FileExists { path: String },
+
+    /// A line of the file matches the regex
+    Grep { path: String, pattern: String },
+}
+
+impl std::fmt::Display for AcceptanceCriterion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Command { command } => write!(f, "`{}` succeeds", command),
+            Self::FileExists { path } => write!(f, "{} exists", path),
+            Self::Grep { path, pattern } => write!(f, "{} matches /{}/", path, pattern),
+        }
+    }
+}
+
+/// Result of the latest check of a criterion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CriterionStatus {
+    /// Not checked yet
+    #[default]
+    Pending,
+    Passed,
+    Failed,
+}
+
+impl std::fmt::Display for CriterionStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pending => write!(f, "pending"),
+            Self::Passed => write!(f, "passed"),
+            Self::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+/// A criterion with its latest status, stored on the LoopExecution
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AcceptanceCheck {
+    pub criterion: AcceptanceCriterion,
+    #[serde(default)]
+    pub status: CriterionStatus,
+    /// Why it failed (command output tail, missing file, ...)
+    #[serde(default)]
+    pub detail: Option<String>,
+    /// Iteration of the latest check (0 = never checked)
+    #[serde(default)]
+    pub iteration: u32,
+}
+
+impl AcceptanceCheck {
+    /// An unchecked criterion
+    pub fn pending(criterion: AcceptanceCriterion) -> Self {
+        Self {
+            criterion,
+            status: CriterionStatus::Pending,
+            detail: None,
+            iteration: 0,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct AcceptanceSection {
+    acceptance: Vec<AcceptanceCriterion>,
+}
+
+/// Criteria from the fenced YAML `acceptance:` blocks of a plan or spec
+///
+/// YAML blocks without an `acceptance` key are ignored; a malformed one is an
+/// error rather than silently dropping its criteria.
+pub fn parse_acceptance(markdown: &str) -> Result<Vec<AcceptanceCriterion>, String> {
+    debug!(len = markdown.len(), "parse_acceptance: called");
+    let mut criteria = Vec::new();
+    // Inside a code block: whether it is YAML, and its body so far
+    let mut block: Option<(bool, String)> = None;
+    for line in markdown.lines() {
+        let is_fence = line.trim_start().starts_with("```");
+        match &mut block {
+            None if is_fence => {
+                let lang = line.trim_start().trim_start_matches('`').trim();
+                block = Some((matches!(lang, "yaml" | "yml"), String::new()));
+            }
+            None => {}
+            Some((is_yaml, body)) if is_fence => {
+                if *is_yaml && body.lines().any(|l| l.starts_with("acceptance:")) {
+                    let section: AcceptanceSection =
+                        serde_yaml::from_str(body).map_err(|e| format!("Invalid acceptance block: {}", e))?;
+                    criteria.extend(section.acceptance);
+                }
+                block = None;
+            }
+            Some((_, body)) => {
+                body.push_str(line);
+                body.push('\n');
+            }
+        }
+    }
+    debug!(count = criteria.len(), "parse_acceptance: parsed criteria");
+    Ok(criteria)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_acceptance_from_markdown() {
+        let markdown = r#"# Plan
+
+```rust
+// acceptance: not yaml
+```
+
+## Acceptance
+
+```yaml
+acceptance:
+  - type: command
+    command: cargo test
+  - type: file-exists
+    path: src/writer.rs
+  - type: grep
+    path: src/mod.rs
+    pattern: "pub mod writer"
+```
+"#;
+        let criteria = parse_acceptance(markdown).unwrap();
+        assert_eq!(
+            criteria,
+            vec![
+                AcceptanceCriterion::Command {
+                    command: "cargo test".to_string()
+                },
+                AcceptanceCriterion::FileExists {
+                    path: "src/writer.rs".to_string()
+                },
+                AcceptanceCriterion::Grep {
+                    path: "src/mod.rs".to_string(),
+                    pattern: "pub mod writer".to_string()
+                },
+            ]
+        );
+        assert_eq!(criteria[2].to_string(), "src/mod.rs matches /pub mod writer/");
+
+        assert!(parse_acceptance("# No criteria\n").unwrap().is_empty());
+        let err = parse_acceptance("```yaml\nacceptance:\n  - type: bogus\n```\n").unwrap_err();
+        assert!(err.starts_with("Invalid acceptance block"));
+    }
+}
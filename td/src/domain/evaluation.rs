@@ -0,0 +1,99 @@
+//! Evaluation domain type
+//!
+//! Rubric scores from the self-evaluation (LLM-as-judge) pass that runs
+//! after a loop's validation passes and before its work is merged.
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Score for a single acceptance criterion
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RubricScore {
+    /// The criterion being scored
+    pub criterion: String,
+    /// Score from 0 (not met) to 10 (fully met)
+    pub score: u8,
+    /// Judge's short justification
+    #[serde(default)]
+    pub rationale: String,
+}
+
+/// Result of a self-evaluation pass, attached to the LoopExecution
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Evaluation {
+    /// Model that produced the scores ("provider/model")
+    pub model: String,
+    /// Per-criterion scores
+    pub scores: Vec<RubricScore>,
+    /// Mean score across all criteria (0-10)
+    pub overall: f64,
+    /// Minimum overall score required to auto-merge
+    pub threshold: f64,
+    /// Judge's overall summary
+    #[serde(default)]
+    pub summary: String,
+    /// When the evaluation ran (Unix milliseconds)
+    pub evaluated_at: i64,
+}
+
+impl Evaluation {
+    /// Build an evaluation from rubric scores, computing the overall mean
+    pub fn new(model: impl Into<String>, scores: Vec<RubricScore>, threshold: f64, summary: impl Into<String>) -> Self {
+        let model = model.into();
+        let overall = if scores.is_empty() {
+            0.0
+        } else {
+            scores.iter().map(|s| s.score as f64).sum::<f64>() / scores.len() as f64
+        };
+        debug!(%model, criteria = scores.len(), overall, threshold, "Evaluation::new: called");
+        Self {
+            model,
+            scores,
+            overall,
+            threshold,
+            summary: summary.into(),
+            evaluated_at: taskstore::now_ms(),
+        }
+    }
+
+    /// Whether the work scored high enough to merge without human review
+    pub fn passed(&self) -> bool {
+        let passed = !self.scores.is_empty() && self.overall >= self.threshold;
+        debug!(
+            overall = self.overall,
+            threshold = self.threshold,
+            passed,
+            "Evaluation::passed: called"
+        );
+        passed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(criterion: &str, score: u8) -> RubricScore {
+        RubricScore {
+            criterion: criterion.to_string(),
+            score,
+            rationale: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_evaluation_overall_and_threshold() {
+        let eval = Evaluation::new("openai/gpt-4o-mini", vec![score("a", 8), score("b", 6)], 7.0, "ok");
+        assert_eq!(eval.overall, 7.0);
+        assert!(eval.passed());
+
+        let eval = Evaluation::new("openai/gpt-4o-mini", vec![score("a", 8), score("b", 5)], 7.0, "meh");
+        assert!(!eval.passed());
+    }
+
+    #[test]
+    fn test_evaluation_without_scores_fails() {
+        let eval = Evaluation::new("openai/gpt-4o-mini", vec![], 0.0, "");
+        assert!(!eval.passed());
+    }
+}
@@ -21,7 +21,7 @@ pub fn generate_id(domain_type: &str, title: &str) -> String {
 const MAX_SLUG_LENGTH: usize = 40;
 
 /// Slugify a title for use in IDs
-fn slugify(title: &str) -> String {
+pub(crate) fn slugify(title: &str) -> String {
     debug!(%title, "slugify: called");
     let slug: String = title
         .to_lowercase()
@@ -183,62 +183,87 @@ impl<'a> IdResolver<'a> {
 
     /// Resolve a partial reference to a full ID
     ///
+    /// Matches are ranked: exact ID, ID prefix (e.g. the hex prefix), slug
+    /// substring, then fuzzy (the reference's characters appear in order in the
+    /// ID). Only the best-ranked matches count, so a reference that is a prefix
+    /// of one ID and a substring of another resolves to the first.
+    ///
     /// Returns:
-    /// - Ok(Some(id)) if exactly one match
+    /// - Ok(Some(id)) if exactly one best match
     /// - Ok(None) if no matches
-    /// - Err with candidates if ambiguous
+    /// - Err with candidates (sorted) if ambiguous
     pub fn resolve(&self, reference: &str) -> Result<Option<String>, Vec<String>> {
         debug!(%reference, "IdResolver::resolve: called");
-        let matches: Vec<String> = self
+        let ranked: Vec<(u8, &String)> = self
             .ids
             .keys()
-            .filter(|id| Self::matches(id, reference))
-            .cloned()
+            .filter_map(|id| Self::match_rank(id, reference).map(|rank| (rank, id)))
+            .collect();
+        let Some(best) = ranked.iter().map(|(rank, _)| *rank).min() else {
+            debug!("IdResolver::resolve: no matches found");
+            return Ok(None);
+        };
+        let mut matches: Vec<String> = ranked
+            .into_iter()
+            .filter(|(rank, _)| *rank == best)
+            .map(|(_, id)| id.clone())
             .collect();
 
-        match matches.len() {
-            0 => {
-                debug!("IdResolver::resolve: no matches found");
-                Ok(None)
-            }
-            1 => {
-                debug!(?matches, "IdResolver::resolve: exactly one match");
-                // SAFETY: We just checked matches.len() == 1, so next() will always succeed
-                Ok(matches.into_iter().next())
-            }
-            _ => {
-                debug!(?matches, "IdResolver::resolve: ambiguous, multiple matches");
-                Err(matches)
-            }
+        if matches.len() == 1 {
+            debug!(?matches, best, "IdResolver::resolve: exactly one match");
+            return Ok(matches.pop());
         }
+        debug!(?matches, best, "IdResolver::resolve: ambiguous, multiple matches");
+        matches.sort();
+        Err(matches)
     }
 
-    /// Check if an ID matches a reference
-    fn matches(id: &str, reference: &str) -> bool {
-        debug!(%id, %reference, "IdResolver::matches: called");
+    /// Display name registered for an ID
+    pub fn name(&self, id: &str) -> Option<&str> {
+        self.ids.get(id).map(String::as_str)
+    }
+
+    /// How well an ID matches a reference (lower is better; None: no match)
+    fn match_rank(id: &str, reference: &str) -> Option<u8> {
+        debug!(%id, %reference, "IdResolver::match_rank: called");
+        if reference.is_empty() {
+            return None;
+        }
+
         // Exact match
         if id == reference {
-            debug!("IdResolver::matches: exact match");
-            return true;
+            debug!("IdResolver::match_rank: exact match");
+            return Some(0);
         }
 
-        // Hex prefix match (first 6 chars)
+        // Prefix match (hex prefix, or more of the ID)
         if id.starts_with(reference) {
-            debug!("IdResolver::matches: hex prefix match");
-            return true;
+            debug!("IdResolver::match_rank: prefix match");
+            return Some(1);
         }
 
         // Slug contains match
         if let Some(slug_start) = id.find('-') {
             let slug_part = &id[slug_start + 1..];
             if slug_part.contains(reference) {
-                debug!("IdResolver::matches: slug contains match");
-                return true;
+                debug!("IdResolver::match_rank: slug contains match");
+                return Some(2);
             }
         }
 
-        debug!("IdResolver::matches: no match");
-        false
+        // Fuzzy match: the reference's characters in order, case-insensitively
+        let mut chars = id.chars().map(|c| c.to_ascii_lowercase());
+        if reference
+            .chars()
+            .map(|c| c.to_ascii_lowercase())
+            .all(|wanted| chars.any(|c| c == wanted))
+        {
+            debug!("IdResolver::match_rank: fuzzy match");
+            return Some(3);
+        }
+
+        debug!("IdResolver::match_rank: no match");
+        None
     }
 }
 
@@ -345,5 +370,46 @@ mod tests {
 
         let resolver = IdResolver::new(&ids);
         assert_eq!(resolver.resolve("nonexistent").unwrap(), None);
+        assert_eq!(resolver.resolve("").unwrap(), None);
+    }
+
+    #[test]
+    fn test_id_resolver_prefers_better_matches() {
+        let mut ids = HashMap::new();
+        ids.insert("019430-plan-add-oauth".to_string(), "Add OAuth".to_string());
+        ids.insert("0abcde-spec-fix-019430-regression".to_string(), "Fix".to_string());
+        ids.insert("019431-spec-oauth-db".to_string(), "OAuth DB Schema".to_string());
+
+        // Prefix of one ID beats a substring of another
+        let resolver = IdResolver::new(&ids);
+        assert_eq!(
+            resolver.resolve("019430").unwrap(),
+            Some("019430-plan-add-oauth".to_string())
+        );
+        assert_eq!(resolver.name("019431-spec-oauth-db"), Some("OAuth DB Schema"));
+
+        // Ambiguous candidates come back sorted
+        assert_eq!(
+            resolver.resolve("01943").unwrap_err(),
+            vec!["019430-plan-add-oauth".to_string(), "019431-spec-oauth-db".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_id_resolver_fuzzy() {
+        let mut ids = HashMap::new();
+        ids.insert("019430-plan-add-oauth".to_string(), "Add OAuth".to_string());
+        ids.insert("019431-spec-oauth-db".to_string(), "OAuth DB Schema".to_string());
+
+        let resolver = IdResolver::new(&ids);
+        assert_eq!(
+            resolver.resolve("addOauth").unwrap(),
+            Some("019430-plan-add-oauth".to_string())
+        );
+        assert_eq!(
+            resolver.resolve("spec-odb").unwrap(),
+            Some("019431-spec-oauth-db".to_string())
+        );
+        assert_eq!(resolver.resolve("oauth-zz").unwrap(), None);
     }
 }
@@ -5,6 +5,8 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
 use taskstore::{IndexValue, Record, now_ms};
 use tracing::debug;
 
@@ -77,6 +79,11 @@ pub struct IterationLog {
     /// Summary of tool calls made during agentic loop
     pub tool_calls: Vec<ToolCallSummary>,
 
+    /// "provider/model" that served this iteration's LLM calls (the last one if a failover
+    /// chain switched mid-iteration; None = the execution's configured model)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub served_by: Option<String>,
+
     /// Creation timestamp (milliseconds since Unix epoch)
     pub created_at: i64,
 
@@ -104,11 +111,20 @@ impl IterationLog {
             llm_input_tokens: None,
             llm_output_tokens: None,
             tool_calls: Vec::new(),
+            served_by: None,
             created_at: now,
             updated_at: now,
         }
     }
 
+    /// Builder: set the creation time (when the log is stamped by an injected clock)
+    pub fn with_created_at(mut self, created_at: i64) -> Self {
+        debug!(%self.id, created_at, "IterationLog::with_created_at");
+        self.created_at = created_at;
+        self.updated_at = created_at;
+        self
+    }
+
     /// Builder: set validation command
     pub fn with_validation_command(mut self, command: impl Into<String>) -> Self {
         self.validation_command = command.into();
@@ -166,6 +182,13 @@ impl IterationLog {
         self
     }
 
+    /// Builder: set the model that served the iteration
+    pub fn with_served_by(mut self, served_by: Option<String>) -> Self {
+        debug!(%self.id, ?served_by, "IterationLog::with_served_by");
+        self.served_by = served_by;
+        self
+    }
+
     /// Check if this iteration succeeded (exit_code == 0)
     pub fn is_success(&self) -> bool {
         debug!(%self.id, self.exit_code, "IterationLog::is_success: called");
@@ -189,6 +212,100 @@ impl IterationLog {
     }
 }
 
+/// Validation result of an iteration, as recorded by its exit code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ValidationOutcome {
+    /// Validation exited 0
+    Passed,
+    /// Validation ran and exited non-zero
+    Failed,
+    /// Validation could not run (exit code -1)
+    Error,
+}
+
+impl ValidationOutcome {
+    /// Classify an iteration log
+    pub fn of(log: &IterationLog) -> Self {
+        if log.is_success() {
+            Self::Passed
+        } else if log.is_error() {
+            Self::Error
+        } else {
+            Self::Failed
+        }
+    }
+}
+
+impl fmt::Display for ValidationOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Passed => "passed",
+            Self::Failed => "failed",
+            Self::Error => "error",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for ValidationOutcome {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> eyre::Result<Self> {
+        debug!(%s, "ValidationOutcome::from_str: called");
+        match s.to_lowercase().as_str() {
+            "passed" | "pass" => Ok(Self::Passed),
+            "failed" | "fail" => Ok(Self::Failed),
+            "error" => Ok(Self::Error),
+            _ => Err(eyre::eyre!("Unknown outcome '{}' (expected passed, failed or error)", s)),
+        }
+    }
+}
+
+/// Selection for [`IterationLog`] queries; every given field must match
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IterationLogFilter {
+    pub execution_id: Option<String>,
+    /// Earliest creation time (milliseconds since Unix epoch, inclusive)
+    pub since: Option<i64>,
+    /// Latest creation time (milliseconds since Unix epoch, exclusive)
+    pub until: Option<i64>,
+    pub outcome: Option<ValidationOutcome>,
+}
+
+impl IterationLogFilter {
+    /// Filter for one execution's iterations
+    pub fn for_execution(execution_id: impl Into<String>) -> Self {
+        Self {
+            execution_id: Some(execution_id.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Builder: only iterations created in `[since, until)`
+    pub fn with_time_range(mut self, since: Option<i64>, until: Option<i64>) -> Self {
+        debug!(?since, ?until, "IterationLogFilter::with_time_range");
+        self.since = since;
+        self.until = until;
+        self
+    }
+
+    /// Builder: only iterations with this validation result
+    pub fn with_outcome(mut self, outcome: Option<ValidationOutcome>) -> Self {
+        debug!(?outcome, "IterationLogFilter::with_outcome");
+        self.outcome = outcome;
+        self
+    }
+
+    /// Whether `log` matches every field of the filter
+    pub fn matches(&self, log: &IterationLog) -> bool {
+        self.execution_id.as_ref().is_none_or(|id| &log.execution_id == id)
+            && self.since.is_none_or(|since| log.created_at >= since)
+            && self.until.is_none_or(|until| log.created_at < until)
+            && self.outcome.is_none_or(|outcome| ValidationOutcome::of(log) == outcome)
+    }
+}
+
 impl Record for IterationLog {
     fn id(&self) -> &str {
         debug!(%self.id, "IterationLog::id: called");
@@ -300,6 +417,27 @@ mod tests {
         assert_eq!(fields.get("exit_code"), Some(&IndexValue::Int(1)));
     }
 
+    #[test]
+    fn test_iteration_log_filter() {
+        let passed = IterationLog::new("exec-1", 1).with_created_at(1_000);
+        let failed = IterationLog::new("exec-1", 2).with_created_at(2_000).with_exit_code(1);
+        let error = IterationLog::new("exec-2", 1).with_created_at(3_000).with_exit_code(-1);
+
+        assert_eq!(ValidationOutcome::of(&passed), ValidationOutcome::Passed);
+        assert_eq!(ValidationOutcome::of(&failed), ValidationOutcome::Failed);
+        assert_eq!(ValidationOutcome::of(&error), ValidationOutcome::Error);
+
+        let filter = IterationLogFilter::for_execution("exec-1");
+        assert!(filter.matches(&passed) && filter.matches(&failed) && !filter.matches(&error));
+
+        let filter = IterationLogFilter::default().with_time_range(Some(2_000), Some(3_000));
+        assert!(!filter.matches(&passed) && filter.matches(&failed) && !filter.matches(&error));
+
+        let filter = IterationLogFilter::default().with_outcome(Some("failed".parse().unwrap()));
+        assert!(!filter.matches(&passed) && filter.matches(&failed) && !filter.matches(&error));
+        assert!("flaky".parse::<ValidationOutcome>().is_err());
+    }
+
     #[test]
     fn test_iteration_log_serde() {
         let log = IterationLog::new("exec-123", 1)
@@ -0,0 +1,303 @@
+//! Metrics snapshot domain types
+//!
+//! `LoopMetrics` only lives as long as the daemon. When an execution finishes,
+//! a MetricsSnapshot of its iterations, tokens, cost and duration is persisted,
+//! and the DailyRollup for its (day, loop type) is recomputed from the day's
+//! snapshots. `td metrics --history` reads the rollups to show trends.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use taskstore::{IndexValue, Record, now_ms};
+use tracing::debug;
+
+use super::run::LoopExecution;
+use crate::llm::TokenUsage;
+
+/// Metrics of one finished execution
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    /// Same as the execution ID, so a resumed execution replaces its earlier snapshot
+    pub id: String,
+
+    /// Loop type (plan, spec, phase, ralph, ...)
+    pub loop_type: String,
+
+    /// Final status ("complete", "failed", "stopped")
+    pub status: String,
+
+    /// Model the cost was estimated for
+    #[serde(default)]
+    pub model: String,
+
+    /// Iterations run
+    pub iterations: u32,
+
+    /// Input tokens consumed
+    pub input_tokens: u64,
+
+    /// Output tokens produced
+    pub output_tokens: u64,
+
+    /// Estimated cost in USD
+    pub cost_usd: f64,
+
+    /// Wall-clock duration in milliseconds
+    pub duration_ms: u64,
+
+    /// UTC day the execution finished on (YYYY-MM-DD)
+    pub day: String,
+
+    /// When the execution finished (Unix milliseconds)
+    pub finished_at: i64,
+
+    /// Last update timestamp
+    pub updated_at: i64,
+}
+
+impl MetricsSnapshot {
+    /// Snapshot an execution that just finished with `status`
+    pub fn from_execution(exec: &LoopExecution, status: impl Into<String>, model: impl Into<String>) -> Self {
+        let status = status.into();
+        let model = model.into();
+        debug!(exec_id = %exec.id, %status, %model, "MetricsSnapshot::from_execution: called");
+        let cost_usd = TokenUsage {
+            input_tokens: exec.total_input_tokens,
+            output_tokens: exec.total_output_tokens,
+            ..Default::default()
+        }
+        .cost_usd(&model);
+        let now = now_ms();
+        Self {
+            id: exec.id.clone(),
+            loop_type: exec.loop_type.clone(),
+            status,
+            model,
+            iterations: exec.iteration,
+            input_tokens: exec.total_input_tokens,
+            output_tokens: exec.total_output_tokens,
+            cost_usd,
+            duration_ms: exec.total_duration_ms,
+            day: day_of(now),
+            finished_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Builder: set the duration (when the execution didn't record one)
+    pub fn with_duration_ms(mut self, duration_ms: u64) -> Self {
+        debug!(%self.id, duration_ms, "MetricsSnapshot::with_duration_ms: called");
+        self.duration_ms = duration_ms;
+        self
+    }
+}
+
+impl Record for MetricsSnapshot {
+    fn id(&self) -> &str {
+        debug!(%self.id, "MetricsSnapshot::id: called");
+        &self.id
+    }
+
+    fn updated_at(&self) -> i64 {
+        debug!(%self.id, self.updated_at, "MetricsSnapshot::updated_at: called");
+        self.updated_at
+    }
+
+    fn collection_name() -> &'static str {
+        debug!("MetricsSnapshot::collection_name: called");
+        "metrics_snapshots"
+    }
+
+    fn indexed_fields(&self) -> HashMap<String, IndexValue> {
+        debug!(%self.id, "MetricsSnapshot::indexed_fields: called");
+        let mut fields = HashMap::new();
+        fields.insert("loop_type".to_string(), IndexValue::String(self.loop_type.clone()));
+        fields.insert("status".to_string(), IndexValue::String(self.status.clone()));
+        fields.insert("day".to_string(), IndexValue::String(self.day.clone()));
+        fields
+    }
+}
+
+/// Per-day totals for one loop type
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DailyRollup {
+    /// ID: {day}-{loop_type}
+    pub id: String,
+
+    /// UTC day (YYYY-MM-DD)
+    pub day: String,
+
+    /// Loop type
+    pub loop_type: String,
+
+    /// Executions that finished this day
+    pub runs: u64,
+
+    /// Of which completed
+    pub complete: u64,
+
+    /// Of which failed
+    pub failed: u64,
+
+    /// Iterations across all runs
+    pub total_iterations: u64,
+
+    /// Tokens (input + output) across all runs
+    pub total_tokens: u64,
+
+    /// Estimated cost across all runs in USD
+    pub total_cost_usd: f64,
+
+    /// Wall-clock time across all runs in milliseconds
+    pub total_duration_ms: u64,
+
+    /// Last update timestamp
+    pub updated_at: i64,
+}
+
+impl DailyRollup {
+    /// Rollup ID for a day and loop type
+    pub fn id_for(day: &str, loop_type: &str) -> String {
+        format!("{}-{}", day, loop_type)
+    }
+
+    /// Total up the snapshots of one day and loop type
+    pub fn from_snapshots(day: &str, loop_type: &str, snapshots: &[MetricsSnapshot]) -> Self {
+        debug!(%day, %loop_type, count = snapshots.len(), "DailyRollup::from_snapshots: called");
+        let mut rollup = Self {
+            id: Self::id_for(day, loop_type),
+            day: day.to_string(),
+            loop_type: loop_type.to_string(),
+            updated_at: now_ms(),
+            ..Default::default()
+        };
+        for snapshot in snapshots {
+            rollup.add(snapshot);
+        }
+        rollup
+    }
+
+    fn add(&mut self, snapshot: &MetricsSnapshot) {
+        self.runs += 1;
+        match snapshot.status.as_str() {
+            "complete" => self.complete += 1,
+            "failed" => self.failed += 1,
+            _ => {}
+        }
+        self.total_iterations += snapshot.iterations as u64;
+        self.total_tokens += snapshot.input_tokens + snapshot.output_tokens;
+        self.total_cost_usd += snapshot.cost_usd;
+        self.total_duration_ms += snapshot.duration_ms;
+    }
+
+    /// Fold another rollup into this one (e.g. to combine loop types)
+    pub fn merge(&mut self, other: &DailyRollup) {
+        self.runs += other.runs;
+        self.complete += other.complete;
+        self.failed += other.failed;
+        self.total_iterations += other.total_iterations;
+        self.total_tokens += other.total_tokens;
+        self.total_cost_usd += other.total_cost_usd;
+        self.total_duration_ms += other.total_duration_ms;
+    }
+
+    /// Completed / (completed + failed); stopped runs don't count
+    pub fn success_rate(&self) -> Option<f64> {
+        let decided = self.complete + self.failed;
+        (decided > 0).then(|| self.complete as f64 / decided as f64)
+    }
+
+    /// Mean iterations per run
+    pub fn mean_iterations(&self) -> Option<f64> {
+        (self.runs > 0).then(|| self.total_iterations as f64 / self.runs as f64)
+    }
+
+    /// Mean cost per run in USD
+    pub fn mean_cost_usd(&self) -> Option<f64> {
+        (self.runs > 0).then(|| self.total_cost_usd / self.runs as f64)
+    }
+}
+
+impl Record for DailyRollup {
+    fn id(&self) -> &str {
+        debug!(%self.id, "DailyRollup::id: called");
+        &self.id
+    }
+
+    fn updated_at(&self) -> i64 {
+        debug!(%self.id, self.updated_at, "DailyRollup::updated_at: called");
+        self.updated_at
+    }
+
+    fn collection_name() -> &'static str {
+        debug!("DailyRollup::collection_name: called");
+        "metrics_rollups"
+    }
+
+    fn indexed_fields(&self) -> HashMap<String, IndexValue> {
+        debug!(%self.id, "DailyRollup::indexed_fields: called");
+        let mut fields = HashMap::new();
+        fields.insert("day".to_string(), IndexValue::String(self.day.clone()));
+        fields.insert("loop_type".to_string(), IndexValue::String(self.loop_type.clone()));
+        fields
+    }
+}
+
+/// UTC day (YYYY-MM-DD) of a Unix-millisecond timestamp
+pub fn day_of(ms: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(ms)
+        .unwrap_or_default()
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(id: &str, status: &str, iterations: u32, cost_usd: f64) -> MetricsSnapshot {
+        let mut exec = LoopExecution::new("implement", id);
+        exec.iteration = iterations;
+        MetricsSnapshot {
+            cost_usd,
+            ..MetricsSnapshot::from_execution(&exec, status, "claude-sonnet-4")
+        }
+    }
+
+    #[test]
+    fn test_snapshot_from_execution() {
+        let mut exec = LoopExecution::new("implement", "add cache");
+        exec.iteration = 4;
+        exec.total_input_tokens = 1_000_000;
+        let snapshot = MetricsSnapshot::from_execution(&exec, "complete", "claude-sonnet-4");
+        assert_eq!(snapshot.id, exec.id);
+        assert_eq!(snapshot.iterations, 4);
+        assert!(snapshot.cost_usd > 0.0);
+        assert_eq!(snapshot.day, day_of(snapshot.finished_at));
+    }
+
+    #[test]
+    fn test_daily_rollup_from_snapshots() {
+        let snapshots = vec![
+            snapshot("a", "complete", 2, 0.5),
+            snapshot("b", "failed", 6, 1.5),
+            snapshot("c", "complete", 4, 1.0),
+            snapshot("d", "stopped", 1, 0.0),
+        ];
+        let rollup = DailyRollup::from_snapshots("2026-10-01", "implement", &snapshots);
+        assert_eq!(rollup.id, "2026-10-01-implement");
+        assert_eq!(rollup.runs, 4);
+        assert_eq!(rollup.success_rate(), Some(2.0 / 3.0));
+        assert_eq!(rollup.mean_iterations(), Some(3.25));
+        assert_eq!(rollup.mean_cost_usd(), Some(0.75));
+
+        let empty = DailyRollup::from_snapshots("2026-10-02", "implement", &[]);
+        assert_eq!(empty.success_rate(), None);
+        assert_eq!(empty.mean_iterations(), None);
+    }
+
+    #[test]
+    fn test_day_of() {
+        assert_eq!(day_of(0), "1970-01-01");
+        assert_eq!(day_of(1_767_225_600_000), "2026-01-01");
+    }
+}
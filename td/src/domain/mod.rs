@@ -1,25 +1,44 @@
 //! Domain types for TaskDaemon
 //!
-//! Core domain types: Loop, LoopExecution, IterationLog
+//! Core domain types: Loop, LoopExecution, IterationLog, ReplSession,
+//! MetricsSnapshot, DailyRollup, AcceptanceCheck, RiskAssessment, Plan, Spec
 //! All implement the Record trait for TaskStore persistence.
 //!
 //! The generic Loop type works with any loop type defined in YAML configuration.
-//! The `type` field determines behavior at runtime.
+//! The `type` field determines behavior at runtime. Plan and Spec are typed
+//! companions of the `plan` and `spec` Loop records, sharing their IDs.
 
 #[allow(unused_imports)]
 use tracing::debug;
 
+mod acceptance;
+mod evaluation;
 mod id;
 mod iteration_log;
+mod metrics_snapshot;
+mod plan;
 mod priority;
 mod record;
+mod repl_session;
+mod risk;
 mod run;
+mod spec;
+mod wake;
 
+pub use acceptance::{AcceptanceCheck, AcceptanceCriterion, CriterionStatus, parse_acceptance};
+pub use evaluation::{Evaluation, RubricScore};
 pub use id::{DomainId, IdResolver};
-pub use iteration_log::{IterationLog, ToolCallSummary};
+pub(crate) use id::{generate_id, slugify};
+pub use iteration_log::{IterationLog, IterationLogFilter, ValidationOutcome, ToolCallSummary};
+pub use metrics_snapshot::{DailyRollup, MetricsSnapshot, day_of};
+pub use plan::{PLAN_TYPE, Plan};
 pub use priority::Priority;
 pub use record::{Loop, LoopStatus, Phase, PhaseStatus};
-pub use run::{LoopExecution, LoopExecutionStatus, LoopRun, LoopRunStatus};
+pub use repl_session::{ReplSession, SessionMessage, SessionRole};
+pub use risk::{RiskAssessment, RiskFactor};
+pub use run::{CherryPick, LoopExecution, LoopExecutionStatus, LoopRun, LoopRunStatus, RunOverride, Submitter};
+pub use spec::{SPEC_TYPE, Spec};
+pub use wake::WakeCondition;
 
 // Re-export taskstore types for convenience
-pub use taskstore::{Filter, FilterOp, IndexValue, Record, Store};
+pub use taskstore::{Batch, Filter, FilterOp, IndexValue, Record, Store};
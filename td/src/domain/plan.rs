@@ -0,0 +1,162 @@
+//! Plan domain type
+//!
+//! Typed record for the document a `plan` loop produces. The cascade still
+//! runs on generic [`Loop`] records; a Plan sits next to the Loop with the same
+//! ID and adds what the generic record has no field for: the plan's
+//! acceptance criteria, the execution that wrote it, and the specs it was
+//! decomposed into.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use taskstore::{IndexValue, Record, now_ms};
+use tracing::debug;
+
+use super::acceptance::AcceptanceCriterion;
+use super::priority::Priority;
+use super::record::{Loop, LoopStatus};
+
+/// Loop type whose records are Plans
+pub const PLAN_TYPE: &str = "plan";
+
+/// A plan document and its decomposition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Plan {
+    /// Unique identifier (shared with the plan's Loop record)
+    pub id: String,
+
+    /// Human-readable title
+    pub title: String,
+
+    /// Current status in the workflow
+    pub status: LoopStatus,
+
+    /// Absolute path to plan.md (if any)
+    #[serde(default)]
+    pub file: Option<String>,
+
+    /// Execution that wrote the plan
+    #[serde(default)]
+    pub exec_id: Option<String>,
+
+    /// Acceptance criteria from the plan's YAML `acceptance:` block
+    #[serde(default)]
+    pub acceptance: Vec<AcceptanceCriterion>,
+
+    /// Specs the plan was decomposed into, in creation order
+    #[serde(default)]
+    pub spec_ids: Vec<String>,
+
+    /// Priority for scheduler ordering
+    #[serde(default)]
+    pub priority: Priority,
+
+    /// Creation timestamp (Unix milliseconds)
+    pub created_at: i64,
+
+    /// Last update timestamp (Unix milliseconds)
+    pub updated_at: i64,
+}
+
+impl Plan {
+    /// Typed view of a generic `plan` Loop record
+    pub fn from_loop(record: &Loop) -> Self {
+        debug!(%record.id, "Plan::from_loop: called");
+        Self {
+            id: record.id.clone(),
+            title: record.title.clone(),
+            status: record.status.clone(),
+            file: record.file.clone(),
+            exec_id: record
+                .context
+                .get("exec_id")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            acceptance: Vec::new(),
+            spec_ids: Vec::new(),
+            priority: record.priority,
+            created_at: record.created_at,
+            updated_at: record.updated_at,
+        }
+    }
+
+    /// Take the fields both records carry from the plan's Loop record
+    pub fn sync_from_loop(&mut self, record: &Loop) {
+        debug!(%self.id, status = ?record.status, "Plan::sync_from_loop: called");
+        self.title = record.title.clone();
+        self.status = record.status.clone();
+        self.file = record.file.clone().or(self.file.take());
+        self.priority = record.priority;
+        self.updated_at = record.updated_at.max(self.updated_at);
+    }
+
+    /// Link a spec decomposed from this plan (once)
+    pub fn add_spec(&mut self, spec_id: impl Into<String>) {
+        let spec_id = spec_id.into();
+        debug!(%self.id, %spec_id, "Plan::add_spec: called");
+        if !self.spec_ids.contains(&spec_id) {
+            self.spec_ids.push(spec_id);
+            self.updated_at = now_ms();
+        }
+    }
+
+    /// Replace the acceptance criteria
+    pub fn set_acceptance(&mut self, acceptance: Vec<AcceptanceCriterion>) {
+        debug!(%self.id, count = acceptance.len(), "Plan::set_acceptance: called");
+        self.acceptance = acceptance;
+        self.updated_at = now_ms();
+    }
+}
+
+impl Record for Plan {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn updated_at(&self) -> i64 {
+        self.updated_at
+    }
+
+    fn collection_name() -> &'static str {
+        "plans"
+    }
+
+    fn indexed_fields(&self) -> HashMap<String, IndexValue> {
+        debug!(%self.id, "Plan::indexed_fields: called");
+        let mut fields = HashMap::new();
+        fields.insert("status".to_string(), IndexValue::String(self.status.to_string()));
+        if let Some(exec_id) = &self.exec_id {
+            fields.insert("exec_id".to_string(), IndexValue::String(exec_id.clone()));
+        }
+        fields
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_from_loop_and_sync() {
+        let mut record = Loop::with_id("p1", PLAN_TYPE, "Add auth").with_file("/repo/plan.md");
+        record.set_context(serde_json::json!({ "exec_id": "exec-1" }));
+        let mut plan = Plan::from_loop(&record);
+        assert_eq!(plan.exec_id.as_deref(), Some("exec-1"));
+        assert_eq!(plan.file.as_deref(), Some("/repo/plan.md"));
+        assert_eq!(plan.status, LoopStatus::Pending);
+
+        plan.add_spec("s1");
+        plan.add_spec("s1");
+        plan.set_acceptance(vec![AcceptanceCriterion::Command {
+            command: "cargo test".to_string(),
+        }]);
+        record.set_status(LoopStatus::Complete);
+        plan.sync_from_loop(&record);
+        assert_eq!(plan.status, LoopStatus::Complete);
+        assert_eq!(plan.spec_ids, ["s1"]);
+        assert_eq!(plan.acceptance.len(), 1);
+        assert_eq!(
+            plan.indexed_fields().get("exec_id"),
+            Some(&IndexValue::String("exec-1".to_string()))
+        );
+    }
+}
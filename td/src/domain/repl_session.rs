@@ -0,0 +1,235 @@
+//! ReplSession domain type
+//!
+//! Persistent record of a TUI REPL conversation: the display history, the
+//! LLM conversation it was built from, the mode, and token totals. Saved
+//! after every completed response so `/resume` can pick a session back up.
+//! Plan-mode sessions also link the plan executions they produced, so the
+//! clarification conversation behind a plan can be reopened later.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use taskstore::{IndexValue, Record, now_ms};
+use tracing::debug;
+
+use super::id::generate_id;
+use crate::llm::Message;
+
+/// Maximum title length (first user message, truncated)
+const MAX_TITLE_LEN: usize = 60;
+
+/// Role of a displayed REPL message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionRole {
+    User,
+    Assistant,
+    ToolResult { tool_name: String },
+    Error,
+}
+
+/// A displayed REPL message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionMessage {
+    pub role: SessionRole,
+    pub content: String,
+    #[serde(default)]
+    pub tool_args: Option<String>,
+    pub timestamp: i64,
+}
+
+/// Persistent REPL session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplSession {
+    /// Unique ID: {hex}-session-{mode}-{random}
+    pub id: String,
+
+    /// REPL mode ("chat" or "plan")
+    pub mode: String,
+
+    /// Short title (first user message)
+    #[serde(default)]
+    pub title: String,
+
+    /// Display history
+    pub messages: Vec<SessionMessage>,
+
+    /// LLM conversation (user/assistant/tool blocks) for continuing the session
+    #[serde(default)]
+    pub conversation: Vec<Message>,
+
+    /// Total input tokens sent in this session
+    #[serde(default)]
+    pub input_tokens: u64,
+
+    /// Total output tokens received in this session
+    #[serde(default)]
+    pub output_tokens: u64,
+
+    /// Estimated session cost in USD
+    #[serde(default)]
+    pub cost_usd: f64,
+
+    /// Plan executions created from this session with `/create`
+    #[serde(default)]
+    pub plan_ids: Vec<String>,
+
+    /// Creation timestamp (milliseconds since Unix epoch)
+    pub created_at: i64,
+
+    /// Last update timestamp
+    pub updated_at: i64,
+}
+
+impl ReplSession {
+    /// Create a new, empty session
+    pub fn new(mode: impl Into<String>) -> Self {
+        let mode = mode.into();
+        debug!(%mode, "ReplSession::new: called");
+        let now = now_ms();
+        // generate_id's hex prefix only changes every few hours, so add the
+        // random tail of a v7 UUID to keep sessions in the same mode distinct
+        let uuid = uuid::Uuid::now_v7().simple().to_string();
+        let random = &uuid[uuid.len() - 6..];
+        Self {
+            id: generate_id("session", &format!("{}-{}", mode, random)),
+            mode,
+            title: String::new(),
+            messages: Vec::new(),
+            conversation: Vec::new(),
+            input_tokens: 0,
+            output_tokens: 0,
+            cost_usd: 0.0,
+            plan_ids: Vec::new(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Replace the session contents and bump `updated_at`
+    ///
+    /// The title is taken from the first user message the first time one exists.
+    pub fn update(&mut self, mode: impl Into<String>, messages: Vec<SessionMessage>, conversation: Vec<Message>) {
+        self.mode = mode.into();
+        debug!(%self.id, num_messages = messages.len(), "ReplSession::update: called");
+        if self.title.is_empty()
+            && let Some(first) = messages.iter().find(|m| m.role == SessionRole::User)
+        {
+            let line = first.content.lines().next().unwrap_or("").trim();
+            self.title = line.chars().take(MAX_TITLE_LEN).collect();
+        }
+        self.messages = messages;
+        self.conversation = conversation;
+        self.updated_at = now_ms();
+    }
+
+    /// Record a plan execution created from this session
+    pub fn add_plan(&mut self, plan_id: impl Into<String>) {
+        let plan_id = plan_id.into();
+        debug!(%self.id, %plan_id, "ReplSession::add_plan: called");
+        if !self.plan_ids.contains(&plan_id) {
+            self.plan_ids.push(plan_id);
+        }
+        self.updated_at = now_ms();
+    }
+
+    /// Builder: set token totals
+    pub fn with_usage(mut self, input_tokens: u64, output_tokens: u64, cost_usd: f64) -> Self {
+        debug!(%self.id, input_tokens, output_tokens, "ReplSession::with_usage");
+        self.input_tokens = input_tokens;
+        self.output_tokens = output_tokens;
+        self.cost_usd = cost_usd;
+        self
+    }
+}
+
+impl Record for ReplSession {
+    fn id(&self) -> &str {
+        debug!(%self.id, "ReplSession::id: called");
+        &self.id
+    }
+
+    fn updated_at(&self) -> i64 {
+        debug!(%self.id, self.updated_at, "ReplSession::updated_at: called");
+        self.updated_at
+    }
+
+    fn collection_name() -> &'static str {
+        debug!("ReplSession::collection_name: called");
+        "repl_sessions"
+    }
+
+    fn indexed_fields(&self) -> HashMap<String, IndexValue> {
+        debug!(%self.id, "ReplSession::indexed_fields: called");
+        let mut fields = HashMap::new();
+        fields.insert("mode".to_string(), IndexValue::String(self.mode.clone()));
+        fields
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: SessionRole, content: &str) -> SessionMessage {
+        SessionMessage {
+            role,
+            content: content.to_string(),
+            tool_args: None,
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_repl_session_new() {
+        let session = ReplSession::new("chat");
+        assert!(session.id.contains("-session-chat-"));
+        assert_ne!(session.id, ReplSession::new("chat").id);
+        assert_eq!(session.mode, "chat");
+        assert!(session.title.is_empty());
+        assert!(session.messages.is_empty());
+    }
+
+    #[test]
+    fn test_repl_session_update_sets_title_once() {
+        let mut session = ReplSession::new("chat");
+        session.update(
+            "chat",
+            vec![
+                message(SessionRole::Assistant, "hello"),
+                message(SessionRole::User, "fix the login bug\nmore detail"),
+            ],
+            vec![Message::user("fix the login bug")],
+        );
+        assert_eq!(session.title, "fix the login bug");
+        assert_eq!(session.conversation.len(), 1);
+
+        session.update("plan", vec![message(SessionRole::User, "something else")], vec![]);
+        assert_eq!(session.title, "fix the login bug");
+        assert_eq!(session.mode, "plan");
+    }
+
+    #[test]
+    fn test_repl_session_add_plan() {
+        let mut session = ReplSession::new("plan");
+        session.add_plan("abc123-plan-auth");
+        session.add_plan("abc123-plan-auth");
+        assert_eq!(session.plan_ids, vec!["abc123-plan-auth"]);
+
+        // Sessions saved before plan links existed still load
+        let mut json = serde_json::to_value(&session).unwrap();
+        json.as_object_mut().unwrap().remove("plan_ids");
+        let loaded: ReplSession = serde_json::from_value(json).unwrap();
+        assert!(loaded.plan_ids.is_empty());
+    }
+
+    #[test]
+    fn test_session_role_serde() {
+        let json = serde_json::to_string(&SessionRole::ToolResult {
+            tool_name: "read".to_string(),
+        })
+        .unwrap();
+        assert_eq!(json, r#"{"tool_result":{"tool_name":"read"}}"#);
+        let role: SessionRole = serde_json::from_str(r#""user""#).unwrap();
+        assert_eq!(role, SessionRole::User);
+    }
+}
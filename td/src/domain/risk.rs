@@ -0,0 +1,138 @@
+//! Risk assessment domain type
+//!
+//! Score of how risky a completed loop's diff is to merge unreviewed, computed
+//! from the `risk` policy before auto-merge and attached to the execution. A
+//! score at or above the policy's threshold holds the merge until a human
+//! approves it with `td exec approve`.
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use super::run::Submitter;
+
+/// One contribution to a risk score
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RiskFactor {
+    /// What raised the score, e.g. "2 files in migrations/**"
+    pub reason: String,
+    pub points: u32,
+}
+
+/// Risk score of an execution's diff, attached to the LoopExecution
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RiskAssessment {
+    /// Sum of the factors' points
+    pub score: u32,
+    /// Score at or above which the merge needs approval
+    pub threshold: u32,
+    /// What the score is made of
+    pub factors: Vec<RiskFactor>,
+    /// Changed files
+    pub files: usize,
+    /// Changed lines (insertions plus deletions)
+    pub lines: u64,
+    /// Who approved the merge
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approved_by: Option<Submitter>,
+    /// When the merge was approved (Unix milliseconds)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approved_at: Option<i64>,
+    /// When the diff was scored (Unix milliseconds)
+    pub assessed_at: i64,
+}
+
+impl RiskAssessment {
+    /// Build an assessment from its factors, summing the score
+    pub fn new(factors: Vec<RiskFactor>, threshold: u32, files: usize, lines: u64) -> Self {
+        let score = factors.iter().map(|f| f.points).sum();
+        debug!(score, threshold, files, lines, "RiskAssessment::new: called");
+        Self {
+            score,
+            threshold,
+            factors,
+            files,
+            lines,
+            approved_by: None,
+            approved_at: None,
+            assessed_at: taskstore::now_ms(),
+        }
+    }
+
+    /// Whether the score calls for a human approval gate
+    pub fn requires_approval(&self) -> bool {
+        self.score >= self.threshold
+    }
+
+    /// Whether the merge is held until someone approves it
+    pub fn holds_merge(&self) -> bool {
+        self.requires_approval() && self.approved_at.is_none()
+    }
+
+    /// Record an approval of the merge
+    pub fn approve(&mut self, approver: Option<Submitter>) {
+        debug!(score = self.score, ?approver, "RiskAssessment::approve: called");
+        self.approved_by = approver;
+        self.approved_at = Some(taskstore::now_ms());
+    }
+
+    /// Carry an approval of `previous` over to this (re-)assessment
+    ///
+    /// An approval stands as long as the diff has not become riskier.
+    pub fn keep_approval(&mut self, previous: &RiskAssessment) {
+        if previous.approved_at.is_some() && self.score <= previous.score {
+            debug!(score = self.score, approved = previous.score, "RiskAssessment::keep_approval: kept");
+            self.approved_by = previous.approved_by.clone();
+            self.approved_at = previous.approved_at;
+        }
+    }
+
+    /// One line for listings: "72/50 (approved by alice)"
+    pub fn summary(&self) -> String {
+        let verdict = match (&self.approved_by, self.approved_at) {
+            (Some(by), Some(_)) => format!(" (approved by {})", by),
+            (None, Some(_)) => " (approved)".to_string(),
+            _ if self.requires_approval() => " (needs approval)".to_string(),
+            _ => String::new(),
+        };
+        format!("{}/{}{}", self.score, self.threshold, verdict)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn factor(points: u32) -> RiskFactor {
+        RiskFactor {
+            reason: "test".to_string(),
+            points,
+        }
+    }
+
+    #[test]
+    fn test_risk_assessment_approval() {
+        let mut risk = RiskAssessment::new(vec![factor(40), factor(15)], 50, 3, 120);
+        assert_eq!(risk.score, 55);
+        assert!(risk.holds_merge());
+        assert_eq!(risk.summary(), "55/50 (needs approval)");
+
+        risk.approve(Some(Submitter {
+            user: "alice".to_string(),
+            name: None,
+        }));
+        assert!(!risk.holds_merge());
+        assert_eq!(risk.summary(), "55/50 (approved by alice)");
+
+        // A re-assessment keeps the approval unless the score went up
+        let mut same = RiskAssessment::new(vec![factor(55)], 50, 3, 120);
+        same.keep_approval(&risk);
+        assert!(!same.holds_merge());
+        let mut riskier = RiskAssessment::new(vec![factor(70)], 50, 5, 400);
+        riskier.keep_approval(&risk);
+        assert!(riskier.holds_merge());
+
+        let low = RiskAssessment::new(vec![factor(10)], 50, 1, 5);
+        assert!(!low.holds_merge());
+        assert_eq!(low.summary(), "10/50");
+    }
+}
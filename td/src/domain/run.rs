@@ -8,7 +8,12 @@ use std::collections::HashMap;
 use taskstore::{IndexValue, Record, now_ms};
 use tracing::debug;
 
+use super::acceptance::{AcceptanceCheck, AcceptanceCriterion, CriterionStatus};
+use super::evaluation::Evaluation;
 use super::id::generate_id;
+use super::priority::Priority;
+use super::risk::RiskAssessment;
+use super::wake::WakeCondition;
 
 /// Loop run status
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -27,6 +32,10 @@ pub enum LoopRunStatus {
     Rebasing,
     /// Rebase conflict or other blocker
     Blocked,
+    /// Waiting for a wake condition (returns to Pending when one holds)
+    Parked,
+    /// Waiting for the LLM provider to recover (returns to Pending when the circuit breaker closes)
+    WaitingProvider,
     /// Validation passed
     Complete,
     /// Max iterations or unrecoverable error
@@ -63,6 +72,14 @@ impl std::fmt::Display for LoopRunStatus {
                 debug!("LoopRunStatus::fmt: Blocked branch");
                 write!(f, "blocked")
             }
+            Self::Parked => {
+                debug!("LoopRunStatus::fmt: Parked branch");
+                write!(f, "parked")
+            }
+            Self::WaitingProvider => {
+                debug!("LoopRunStatus::fmt: WaitingProvider branch");
+                write!(f, "waiting_provider")
+            }
             Self::Complete => {
                 debug!("LoopRunStatus::fmt: Complete branch");
                 write!(f, "complete")
@@ -79,6 +96,107 @@ impl std::fmt::Display for LoopRunStatus {
     }
 }
 
+/// Commits cherry-picked from a run's branch onto a new branch (`td exec cherry-pick`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CherryPick {
+    /// Branch the commits were applied to
+    pub branch: String,
+    /// Branch the new branch was created from
+    pub base: String,
+    /// Picked commits from the run's branch, in the order applied
+    pub commits: Vec<String>,
+    /// When the pick was made (Unix milliseconds)
+    pub created_at: i64,
+}
+
+/// Who submitted a run: the OS user, plus the `user.name` display name if configured
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Submitter {
+    /// OS login name
+    pub user: String,
+    /// Display name from the submitter's config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl Submitter {
+    /// The OS user running this process, with an optional display name
+    ///
+    /// The login name comes from `$USER`/`$LOGNAME`, falling back to the
+    /// password database; None if neither knows it.
+    pub fn current(name: Option<&str>) -> Option<Self> {
+        debug!(?name, "Submitter::current: called");
+        let user = ["USER", "LOGNAME"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|user| !user.trim().is_empty())
+            .or_else(|| {
+                nix::unistd::User::from_uid(nix::unistd::getuid())
+                    .ok()
+                    .flatten()
+                    .map(|user| user.name)
+            })?;
+        Some(Self {
+            user,
+            name: name.map(str::trim).filter(|name| !name.is_empty()).map(str::to_string),
+        })
+    }
+
+    /// Whether `who` names this submitter (login or display name, ignoring case)
+    pub fn is(&self, who: &str) -> bool {
+        self.user.eq_ignore_ascii_case(who) || self.name.as_deref().is_some_and(|name| name.eq_ignore_ascii_case(who))
+    }
+}
+
+impl std::fmt::Display for Submitter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{} ({})", name, self.user),
+            None => write!(f, "{}", self.user),
+        }
+    }
+}
+
+/// A change to a run's overrides, given as `key=value` to `td exec set`
+///
+/// The value `default` clears the override so the loop-type or config default applies again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunOverride {
+    /// `model=provider/model`
+    Model(Option<String>),
+    /// `max_iterations=N` (or `max-iterations=N`)
+    MaxIterations(Option<u32>),
+}
+
+impl std::str::FromStr for RunOverride {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        debug!(%s, "RunOverride::from_str: called");
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("Expected key=value, got '{}'", s))?;
+        let value = value.trim();
+        let clear = value.is_empty() || value.eq_ignore_ascii_case("default");
+        match key.trim() {
+            "model" => Ok(Self::Model((!clear).then(|| value.to_string()))),
+            "max_iterations" | "max-iterations" => {
+                if clear {
+                    return Ok(Self::MaxIterations(None));
+                }
+                match value.parse::<u32>() {
+                    Ok(n) if n > 0 => Ok(Self::MaxIterations(Some(n))),
+                    _ => Err(format!("max_iterations must be a positive number, got '{}'", value)),
+                }
+            }
+            other => {
+                debug!(%other, "RunOverride::from_str: unknown key");
+                Err(format!("Unknown setting '{}' (expected model or max_iterations)", other))
+            }
+        }
+    }
+}
+
 /// Tracks the runtime state of a loop run
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoopRun {
@@ -98,6 +216,10 @@ pub struct LoopRun {
     /// Run dependencies (LoopRun IDs that must complete first)
     pub deps: Vec<String>,
 
+    /// Scheduling priority (higher runs first when slots are contended)
+    #[serde(default)]
+    pub priority: Priority,
+
     /// Current status
     pub status: LoopRunStatus,
 
@@ -116,6 +238,10 @@ pub struct LoopRun {
     /// Last error message (if any)
     pub last_error: Option<String>,
 
+    /// Code of the last error, e.g. `llm.auth` (see [`crate::error`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+
     /// Path to primary artifact (e.g., ".taskdaemon/plans/{id}/plan.md")
     #[serde(default)]
     pub artifact_path: Option<String>,
@@ -136,6 +262,55 @@ pub struct LoopRun {
     #[serde(default)]
     pub total_duration_ms: u64,
 
+    /// Self-evaluation rubric scores (set when the judge pass runs)
+    #[serde(default)]
+    pub evaluation: Option<Evaluation>,
+
+    /// Conditions that wake a parked run (any one suffices)
+    #[serde(default)]
+    pub wake_conditions: Vec<WakeCondition>,
+
+    /// Commits picked from this run's branch onto other branches
+    #[serde(default)]
+    pub cherry_picks: Vec<CherryPick>,
+
+    /// Paths this run holds advisory locks on while it runs
+    #[serde(default)]
+    pub locked_paths: Vec<String>,
+
+    /// Acceptance criteria from the plan/spec and their latest status
+    #[serde(default)]
+    pub acceptance: Vec<AcceptanceCheck>,
+
+    /// Free-form labels for grouping and filtering (`td exec tag`)
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Run this one was cloned from by `td exec rerun`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rerun_of: Option<String>,
+
+    /// Model ("provider/model") used instead of `llm.default` for this run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+
+    /// Iteration cap used instead of the loop type's `max-iterations` for this run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_iterations: Option<u32>,
+
+    /// Risk score of the diff before auto-merge (see [`RiskAssessment`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub risk: Option<RiskAssessment>,
+
+    /// Who submitted the run; cascade children inherit their parent's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub submitted_by: Option<Submitter>,
+
+    /// Bumped by the StateManager on every stored update; an update carrying
+    /// an older revision than the stored one is rejected as a conflict
+    #[serde(default)]
+    pub revision: u64,
+
     /// Creation timestamp (Unix milliseconds)
     pub created_at: i64,
 
@@ -157,17 +332,31 @@ impl LoopRun {
             title: None,
             parent: None,
             deps: Vec::new(),
+            priority: Priority::default(),
             status: LoopRunStatus::Pending,
             worktree: None,
             iteration: 0,
             progress: String::new(),
             context: Value::Null,
             last_error: None,
+            error_code: None,
             artifact_path: None,
             artifact_status: None,
             total_input_tokens: 0,
             total_output_tokens: 0,
             total_duration_ms: 0,
+            evaluation: None,
+            wake_conditions: Vec::new(),
+            cherry_picks: Vec::new(),
+            locked_paths: Vec::new(),
+            acceptance: Vec::new(),
+            tags: Vec::new(),
+            rerun_of: None,
+            model: None,
+            max_iterations: None,
+            risk: None,
+            submitted_by: None,
+            revision: 0,
             created_at: now,
             updated_at: now,
         }
@@ -185,17 +374,31 @@ impl LoopRun {
             title: None,
             parent: None,
             deps: Vec::new(),
+            priority: Priority::default(),
             status: LoopRunStatus::Pending,
             worktree: None,
             iteration: 0,
             progress: String::new(),
             context: Value::Null,
             last_error: None,
+            error_code: None,
             artifact_path: None,
             artifact_status: None,
             total_input_tokens: 0,
             total_output_tokens: 0,
             total_duration_ms: 0,
+            evaluation: None,
+            wake_conditions: Vec::new(),
+            cherry_picks: Vec::new(),
+            locked_paths: Vec::new(),
+            acceptance: Vec::new(),
+            tags: Vec::new(),
+            rerun_of: None,
+            model: None,
+            max_iterations: None,
+            risk: None,
+            submitted_by: None,
+            revision: 0,
             created_at: now,
             updated_at: now,
         }
@@ -243,6 +446,30 @@ impl LoopRun {
         self.updated_at = now_ms();
     }
 
+    /// Attach self-evaluation results
+    pub fn set_evaluation(&mut self, evaluation: Evaluation) {
+        debug!(%self.id, overall = evaluation.overall, "LoopRun::set_evaluation: called");
+        self.evaluation = Some(evaluation);
+        self.updated_at = now_ms();
+    }
+
+    /// Attach a risk assessment, keeping an approval of the previous one if it still applies
+    pub fn set_risk(&mut self, mut risk: RiskAssessment) {
+        debug!(%self.id, score = risk.score, "LoopRun::set_risk: called");
+        if let Some(previous) = &self.risk {
+            risk.keep_approval(previous);
+        }
+        self.risk = Some(risk);
+        self.updated_at = now_ms();
+    }
+
+    /// Record commits picked from this run's branch
+    pub fn record_cherry_pick(&mut self, pick: CherryPick) {
+        debug!(%self.id, branch = %pick.branch, commits = pick.commits.len(), "LoopRun::record_cherry_pick: called");
+        self.cherry_picks.push(pick);
+        self.updated_at = now_ms();
+    }
+
     /// Add tokens and duration from a completed iteration
     pub fn add_iteration_metrics(&mut self, input_tokens: u64, output_tokens: u64, duration_ms: u64) {
         debug!(
@@ -279,6 +506,58 @@ impl LoopRun {
         self.updated_at = now_ms();
     }
 
+    /// Paths declared up front in the `paths` context value (a list, or one string)
+    pub fn declared_paths(&self) -> Vec<String> {
+        match self.context.get("paths") {
+            Some(Value::Array(items)) => items.iter().filter_map(|v| v.as_str()).map(String::from).collect(),
+            Some(Value::String(path)) => vec![path.clone()],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Record paths whose locks this run now holds
+    pub fn add_locked_paths(&mut self, paths: &[String]) {
+        debug!(%self.id, ?paths, "LoopRun::add_locked_paths: called");
+        for path in paths {
+            if !self.locked_paths.contains(path) {
+                self.locked_paths.push(path.clone());
+            }
+        }
+        self.updated_at = now_ms();
+    }
+
+    /// Add tags (lowercased), skipping ones already present
+    pub fn add_tags(&mut self, tags: &[String]) {
+        debug!(%self.id, ?tags, "LoopRun::add_tags: called");
+        for tag in tags {
+            let tag = tag.trim().to_lowercase();
+            if !tag.is_empty() && !self.tags.contains(&tag) {
+                self.tags.push(tag);
+            }
+        }
+        self.updated_at = now_ms();
+    }
+
+    /// Remove tags (case-insensitive)
+    pub fn remove_tags(&mut self, tags: &[String]) {
+        debug!(%self.id, ?tags, "LoopRun::remove_tags: called");
+        self.tags
+            .retain(|tag| !tags.iter().any(|t| t.trim().eq_ignore_ascii_case(tag)));
+        self.updated_at = now_ms();
+    }
+
+    /// Whether the run carries `tag` (case-insensitive)
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    /// Whether every acceptance criterion passed its latest check
+    pub fn acceptance_met(&self) -> bool {
+        self.acceptance
+            .iter()
+            .all(|check| check.status == CriterionStatus::Passed)
+    }
+
     /// Set the context
     pub fn set_context(&mut self, context: Value) {
         debug!(%self.id, ?context, "LoopRun::set_context: called");
@@ -293,11 +572,64 @@ impl LoopRun {
         self.updated_at = now_ms();
     }
 
+    /// Park the run until one of `conditions` holds
+    pub fn park(&mut self, conditions: Vec<WakeCondition>) {
+        debug!(%self.id, count = conditions.len(), "LoopRun::park: called");
+        self.wake_conditions = conditions;
+        self.status = LoopRunStatus::Parked;
+        self.updated_at = now_ms();
+    }
+
+    /// Transition from Parked back to Pending, dropping the wake conditions
+    /// Returns true if the transition was made, false if not parked
+    pub fn wake(&mut self) -> bool {
+        debug!(%self.id, ?self.status, "LoopRun::wake: called");
+        if self.status != LoopRunStatus::Parked {
+            debug!("LoopRun::wake: not parked");
+            return false;
+        }
+        self.wake_conditions.clear();
+        self.status = LoopRunStatus::Pending;
+        self.updated_at = now_ms();
+        true
+    }
+
+    /// Continue from `iteration` after its worktree snapshot was restored (-> Pending)
+    /// Returns false if a task may still be using the worktree (or the run completed)
+    pub fn roll_back_to(&mut self, iteration: u32) -> bool {
+        debug!(%self.id, ?self.status, iteration, "LoopRun::roll_back_to: called");
+        if !matches!(
+            self.status,
+            LoopRunStatus::Paused
+                | LoopRunStatus::Blocked
+                | LoopRunStatus::Parked
+                | LoopRunStatus::WaitingProvider
+                | LoopRunStatus::Failed
+                | LoopRunStatus::Stopped
+        ) {
+            debug!("LoopRun::roll_back_to: execution is active or complete");
+            return false;
+        }
+        self.iteration = iteration;
+        self.wake_conditions.clear();
+        self.last_error = None;
+        self.error_code = None;
+        self.status = LoopRunStatus::Pending;
+        self.updated_at = now_ms();
+        true
+    }
+
     /// Set an error
     pub fn set_error(&mut self, error: impl Into<String>) {
+        self.set_error_with_code(error, None);
+    }
+
+    /// Set an error and its code (None if it has none)
+    pub fn set_error_with_code(&mut self, error: impl Into<String>, code: Option<String>) {
         let error = error.into();
-        debug!(%self.id, %error, "LoopRun::set_error: called");
+        debug!(%self.id, %error, ?code, "LoopRun::set_error_with_code: called");
         self.last_error = Some(error);
+        self.error_code = code;
         self.updated_at = now_ms();
     }
 
@@ -305,6 +637,7 @@ impl LoopRun {
     pub fn clear_error(&mut self) {
         debug!(%self.id, "LoopRun::clear_error: called");
         self.last_error = None;
+        self.error_code = None;
         self.updated_at = now_ms();
     }
 
@@ -358,7 +691,10 @@ impl LoopRun {
     /// Check if the loop can be resumed
     pub fn is_resumable(&self) -> bool {
         debug!(%self.id, ?self.status, "LoopRun::is_resumable: called");
-        let result = matches!(self.status, LoopRunStatus::Paused | LoopRunStatus::Blocked);
+        let result = matches!(
+            self.status,
+            LoopRunStatus::Paused | LoopRunStatus::Blocked | LoopRunStatus::Parked | LoopRunStatus::WaitingProvider
+        );
         if result {
             debug!("LoopRun::is_resumable: is resumable");
         } else {
@@ -406,6 +742,77 @@ impl LoopRun {
         self
     }
 
+    /// Set the dependency edges and return self (builder pattern)
+    pub fn with_deps(mut self, deps: Vec<String>) -> Self {
+        debug!(%self.id, ?deps, "LoopRun::with_deps: called");
+        self.deps = deps;
+        self.updated_at = now_ms();
+        self
+    }
+
+    /// Set the scheduling priority and return self (builder pattern)
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        debug!(%self.id, %priority, "LoopRun::with_priority: called");
+        self.priority = priority;
+        self.updated_at = now_ms();
+        self
+    }
+
+    /// Set who submitted the run and return self (builder pattern)
+    pub fn with_submitter(mut self, submitter: Option<Submitter>) -> Self {
+        debug!(%self.id, ?submitter, "LoopRun::with_submitter: called");
+        self.submitted_by = submitter;
+        self.updated_at = now_ms();
+        self
+    }
+
+    /// Whether `who` submitted the run (see [`Submitter::is`])
+    pub fn submitted_by_user(&self, who: &str) -> bool {
+        self.submitted_by.as_ref().is_some_and(|submitter| submitter.is(who))
+    }
+
+    /// Set the acceptance criteria, all unchecked, and return self (builder pattern)
+    pub fn with_acceptance(mut self, criteria: Vec<AcceptanceCriterion>) -> Self {
+        debug!(%self.id, count = criteria.len(), "LoopRun::with_acceptance: called");
+        self.acceptance = criteria.into_iter().map(AcceptanceCheck::pending).collect();
+        self.updated_at = now_ms();
+        self
+    }
+
+    /// A fresh pending copy of this run for `td exec rerun`
+    ///
+    /// Keeps the type, title, priority, context, tags, acceptance criteria and
+    /// overrides; progress, metrics, worktree and parent are not carried over.
+    /// `attempt` numbers the re-runs of one original so their IDs differ.
+    pub fn rerun(&self, attempt: u32) -> Self {
+        debug!(%self.id, attempt, "LoopRun::rerun: called");
+        let slug = self.id.split_once("-loop-").map_or(self.id.as_str(), |(_, slug)| slug);
+        let mut run = Self::with_id(generate_id("loop", &format!("rerun-{}-{}", attempt, slug)), &self.loop_type);
+        run.title = self.title.clone();
+        run.priority = self.priority;
+        run.context = self.context.clone();
+        run.tags = self.tags.clone();
+        run.acceptance = self
+            .acceptance
+            .iter()
+            .map(|check| AcceptanceCheck::pending(check.criterion.clone()))
+            .collect();
+        run.rerun_of = Some(self.id.clone());
+        run.model = self.model.clone();
+        run.max_iterations = self.max_iterations;
+        run
+    }
+
+    /// Apply a `td exec set` override; a running loop picks it up at its next iteration
+    pub fn apply_override(&mut self, change: &RunOverride) {
+        debug!(%self.id, ?change, "LoopRun::apply_override: called");
+        match change {
+            RunOverride::Model(model) => self.model = model.clone(),
+            RunOverride::MaxIterations(max_iterations) => self.max_iterations = *max_iterations,
+        }
+        self.updated_at = now_ms();
+    }
+
     /// Add a context value (builder pattern)
     pub fn with_context_value(mut self, key: &str, value: &str) -> Self {
         debug!(%self.id, %key, %value, "LoopRun::with_context_value: called");
@@ -592,6 +999,96 @@ mod tests {
         assert_eq!(run.context, deserialized.context);
     }
 
+    #[test]
+    fn test_loop_run_submitter() {
+        let run = LoopRun::new("ralph", "test-task");
+        assert!(!serde_json::to_string(&run).unwrap().contains("submitted_by"));
+        assert!(!run.submitted_by_user("alice"));
+
+        let run = run.with_submitter(Some(Submitter {
+            user: "alice".to_string(),
+            name: Some("Alice Liddell".to_string()),
+        }));
+        assert!(run.submitted_by_user("ALICE"));
+        assert!(run.submitted_by_user("alice liddell"));
+        assert!(!run.submitted_by_user("bob"));
+        assert_eq!(run.submitted_by.as_ref().unwrap().to_string(), "Alice Liddell (alice)");
+
+        let json = serde_json::to_string(&run).unwrap();
+        let deserialized: LoopRun = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.submitted_by, run.submitted_by);
+
+        let current = Submitter::current(Some("  ")).unwrap();
+        assert!(!current.user.is_empty());
+        assert_eq!(current.name, None);
+    }
+
+    #[test]
+    fn test_loop_run_declared_and_locked_paths() {
+        let mut run = LoopRun::new("ralph", "split-parser").with_context_value("paths", "src/parser");
+        assert_eq!(run.declared_paths(), ["src/parser"]);
+
+        run.add_locked_paths(&["src/parser".to_string()]);
+        run.add_locked_paths(&["src/parser".to_string(), "README.md".to_string()]);
+        assert_eq!(run.locked_paths, ["src/parser", "README.md"]);
+    }
+
+    #[test]
+    fn test_loop_run_tags() {
+        let mut run = LoopRun::new("ralph", "fix-api");
+        run.add_tags(&["Backend".to_string(), "urgent".to_string(), "backend".to_string()]);
+        assert_eq!(run.tags, ["backend", "urgent"]);
+        assert!(run.has_tag("BACKEND"));
+
+        run.remove_tags(&["URGENT".to_string()]);
+        assert_eq!(run.tags, ["backend"]);
+        assert!(!run.has_tag("urgent"));
+    }
+
+    #[test]
+    fn test_loop_run_rerun() {
+        let mut run = LoopRun::new("phase", "oauth-endpoints").with_context_value("task", "add oauth");
+        run.add_tags(&["backend".to_string()]);
+        run.max_iterations = Some(5);
+        run.set_parent("plan-1");
+        run.set_status(LoopRunStatus::Failed);
+        run.set_worktree("/tmp/wt");
+        run.increment_iteration();
+        run.add_iteration_metrics(100, 50, 1000);
+
+        let rerun = run.rerun(1);
+        assert_ne!(rerun.id, run.id);
+        assert!(rerun.id.contains("-loop-rerun-1-phase-oauth-endpoints"));
+        assert_ne!(rerun.id, run.rerun(2).id);
+        assert_eq!(rerun.rerun_of.as_deref(), Some(run.id.as_str()));
+        assert_eq!(rerun.status, LoopRunStatus::Pending);
+        assert_eq!(rerun.context, run.context);
+        assert_eq!(rerun.tags, run.tags);
+        assert_eq!(rerun.max_iterations, Some(5));
+        assert!(rerun.parent.is_none());
+        assert!(rerun.worktree.is_none());
+        assert_eq!(rerun.iteration, 0);
+        assert_eq!(rerun.total_tokens(), 0);
+    }
+
+    #[test]
+    fn test_run_override_parse_and_apply() {
+        let mut run = LoopRun::new("ralph", "task");
+        run.apply_override(&"model=openai/gpt-4.1".parse().unwrap());
+        run.apply_override(&"max-iterations=20".parse().unwrap());
+        assert_eq!(run.model.as_deref(), Some("openai/gpt-4.1"));
+        assert_eq!(run.max_iterations, Some(20));
+
+        run.apply_override(&"model=default".parse().unwrap());
+        run.apply_override(&"max_iterations=".parse().unwrap());
+        assert_eq!(run.model, None);
+        assert_eq!(run.max_iterations, None);
+
+        assert!("max_iterations=0".parse::<RunOverride>().is_err());
+        assert!("temperature=0.2".parse::<RunOverride>().is_err());
+        assert!("model".parse::<RunOverride>().is_err());
+    }
+
     #[test]
     fn test_loop_run_draft_status() {
         let mut run = LoopRun::new("plan", "test-plan");
@@ -633,6 +1130,45 @@ mod tests {
         assert_eq!(deserialized.status, LoopRunStatus::Draft);
     }
 
+    #[test]
+    fn test_loop_run_park_and_wake() {
+        let mut run = LoopRun::new("phase", "wait-for-pr");
+        run.set_status(LoopRunStatus::Running);
+        assert!(!run.wake());
+
+        run.park(vec![WakeCondition::file_exists("done.flag")]);
+        assert_eq!(run.status, LoopRunStatus::Parked);
+        assert_eq!(run.status.to_string(), "parked");
+        assert!(run.is_resumable());
+
+        let json = serde_json::to_string(&run).unwrap();
+        assert!(json.contains("\"status\":\"parked\""));
+        let deserialized: LoopRun = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.wake_conditions, run.wake_conditions);
+
+        assert!(run.wake());
+        assert_eq!(run.status, LoopRunStatus::Pending);
+        assert!(run.wake_conditions.is_empty());
+    }
+
+    #[test]
+    fn test_loop_run_roll_back_to() {
+        let mut run = LoopRun::new("phase", "fix-parser");
+        run.set_status(LoopRunStatus::Running);
+        assert!(!run.roll_back_to(2));
+
+        run.set_status(LoopRunStatus::Failed);
+        run.iteration = 7;
+        run.set_error("Max iterations reached");
+        assert!(run.roll_back_to(3));
+        assert_eq!(run.status, LoopRunStatus::Pending);
+        assert_eq!(run.iteration, 3);
+        assert!(run.last_error.is_none());
+
+        run.set_status(LoopRunStatus::Complete);
+        assert!(!run.roll_back_to(1));
+    }
+
     #[test]
     fn test_draft_status_display() {
         assert_eq!(LoopRunStatus::Draft.to_string(), "draft");
@@ -0,0 +1,171 @@
+//! Spec domain type
+//!
+//! Typed record for a spec decomposed from a plan. Like [`super::Plan`] it
+//! shares its ID with the generic [`Loop`] record the cascade runs on, and
+//! adds the link back to its plan and the spec's own acceptance criteria.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use taskstore::{IndexValue, Record, now_ms};
+use tracing::debug;
+
+use super::acceptance::AcceptanceCriterion;
+use super::priority::Priority;
+use super::record::{Loop, LoopStatus, Phase};
+
+/// Loop type whose records are Specs
+pub const SPEC_TYPE: &str = "spec";
+
+/// A spec document, its phases, and the plan it came from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Spec {
+    /// Unique identifier (shared with the spec's Loop record)
+    pub id: String,
+
+    /// Human-readable title
+    pub title: String,
+
+    /// Current status in the workflow
+    pub status: LoopStatus,
+
+    /// Plan this spec was decomposed from
+    #[serde(default)]
+    pub plan_id: Option<String>,
+
+    /// Absolute path to the spec markdown (if any)
+    #[serde(default)]
+    pub file: Option<String>,
+
+    /// Execution that wrote the spec
+    #[serde(default)]
+    pub exec_id: Option<String>,
+
+    /// Specs that must complete before this one starts
+    #[serde(default)]
+    pub deps: Vec<String>,
+
+    /// Implementation phases
+    #[serde(default)]
+    pub phases: Vec<Phase>,
+
+    /// Acceptance criteria from the spec's YAML `acceptance:` block
+    #[serde(default)]
+    pub acceptance: Vec<AcceptanceCriterion>,
+
+    /// Priority for scheduler ordering
+    #[serde(default)]
+    pub priority: Priority,
+
+    /// Creation timestamp (Unix milliseconds)
+    pub created_at: i64,
+
+    /// Last update timestamp (Unix milliseconds)
+    pub updated_at: i64,
+}
+
+impl Spec {
+    /// Typed view of a generic `spec` Loop record
+    ///
+    /// The Loop's parent becomes the plan link when it points at one.
+    pub fn from_loop(record: &Loop) -> Self {
+        debug!(%record.id, "Spec::from_loop: called");
+        Self {
+            id: record.id.clone(),
+            title: record.title.clone(),
+            status: record.status.clone(),
+            plan_id: record.parent.clone(),
+            file: record.file.clone(),
+            exec_id: record
+                .context
+                .get("exec_id")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            deps: record.deps.clone(),
+            phases: record.phases.clone(),
+            acceptance: Vec::new(),
+            priority: record.priority,
+            created_at: record.created_at,
+            updated_at: record.updated_at,
+        }
+    }
+
+    /// Take the fields both records carry from the spec's Loop record
+    pub fn sync_from_loop(&mut self, record: &Loop) {
+        debug!(%self.id, status = ?record.status, "Spec::sync_from_loop: called");
+        self.title = record.title.clone();
+        self.status = record.status.clone();
+        self.file = record.file.clone().or(self.file.take());
+        self.deps = record.deps.clone();
+        self.phases = record.phases.clone();
+        self.priority = record.priority;
+        self.updated_at = record.updated_at.max(self.updated_at);
+    }
+
+    /// Replace the acceptance criteria
+    pub fn set_acceptance(&mut self, acceptance: Vec<AcceptanceCriterion>) {
+        debug!(%self.id, count = acceptance.len(), "Spec::set_acceptance: called");
+        self.acceptance = acceptance;
+        self.updated_at = now_ms();
+    }
+
+    /// Completed phases out of all phases, e.g. "2/4" ("-" without phases)
+    pub fn phases_progress(&self) -> String {
+        if self.phases.is_empty() {
+            return "-".to_string();
+        }
+        let complete = self.phases.iter().filter(|p| p.is_complete()).count();
+        format!("{}/{}", complete, self.phases.len())
+    }
+}
+
+impl Record for Spec {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn updated_at(&self) -> i64 {
+        self.updated_at
+    }
+
+    fn collection_name() -> &'static str {
+        "specs"
+    }
+
+    fn indexed_fields(&self) -> HashMap<String, IndexValue> {
+        debug!(%self.id, "Spec::indexed_fields: called");
+        let mut fields = HashMap::new();
+        fields.insert("status".to_string(), IndexValue::String(self.status.to_string()));
+        if let Some(plan_id) = &self.plan_id {
+            fields.insert("plan_id".to_string(), IndexValue::String(plan_id.clone()));
+        }
+        if let Some(exec_id) = &self.exec_id {
+            fields.insert("exec_id".to_string(), IndexValue::String(exec_id.clone()));
+        }
+        fields
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec_from_loop_keeps_phases_and_plan_link() {
+        let mut record = Loop::with_id("s1", SPEC_TYPE, "Schema").with_parent("p1");
+        record.add_phase(Phase::new("Phase 1", "Tables"));
+        record.add_phase(Phase::new("Phase 2", "Migrations"));
+        record.add_dependency("s0");
+        let mut spec = Spec::from_loop(&record);
+        assert_eq!(spec.plan_id.as_deref(), Some("p1"));
+        assert_eq!(spec.deps, ["s0"]);
+        assert_eq!(spec.phases_progress(), "0/2");
+
+        record.complete_phase(0);
+        spec.sync_from_loop(&record);
+        assert_eq!(spec.phases_progress(), "1/2");
+        assert_eq!(
+            spec.indexed_fields().get("plan_id"),
+            Some(&IndexValue::String("p1".to_string()))
+        );
+    }
+}
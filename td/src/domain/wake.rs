@@ -0,0 +1,87 @@
+//! Wake conditions for parked executions
+//!
+//! A parked execution waits for something outside TaskDaemon (a PR merged, a
+//! file appearing, a webhook) without consuming iterations. The daemon checks
+//! its wake conditions periodically and moves it back to Pending as soon as
+//! any one of them holds.
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// A declarative condition that wakes a parked execution
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum WakeCondition {
+    /// A file matching the glob exists (relative paths resolve against the worktree)
+    FileExists { pattern: String },
+
+    /// A git ref points somewhere other than where it did when parked
+    RefAdvanced {
+        #[serde(rename = "ref")]
+        git_ref: String,
+        /// Commit the ref resolved to at park time (None if it didn't exist yet)
+        #[serde(default)]
+        from: Option<String>,
+    },
+
+    /// An HTTP GET of the URL returns 200
+    HttpOk { url: String },
+}
+
+impl WakeCondition {
+    /// Wake when a file matching `pattern` exists
+    pub fn file_exists(pattern: impl Into<String>) -> Self {
+        let pattern = pattern.into();
+        debug!(%pattern, "WakeCondition::file_exists: called");
+        Self::FileExists { pattern }
+    }
+
+    /// Wake when `git_ref` no longer resolves to `from`
+    pub fn ref_advanced(git_ref: impl Into<String>, from: Option<String>) -> Self {
+        let git_ref = git_ref.into();
+        debug!(%git_ref, ?from, "WakeCondition::ref_advanced: called");
+        Self::RefAdvanced { git_ref, from }
+    }
+
+    /// Wake when `url` returns HTTP 200
+    pub fn http_ok(url: impl Into<String>) -> Self {
+        let url = url.into();
+        debug!(%url, "WakeCondition::http_ok: called");
+        Self::HttpOk { url }
+    }
+}
+
+impl std::fmt::Display for WakeCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::FileExists { pattern } => write!(f, "file {}", pattern),
+            Self::RefAdvanced { git_ref, from } => match from {
+                Some(from) => write!(f, "ref {} moves from {}", git_ref, &from[..from.len().min(8)]),
+                None => write!(f, "ref {} exists", git_ref),
+            },
+            Self::HttpOk { url } => write!(f, "GET {} returns 200", url),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wake_condition_serde() {
+        let conditions = vec![
+            WakeCondition::file_exists("target/*.done"),
+            WakeCondition::ref_advanced("origin/main", Some("0123456789abcdef".to_string())),
+            WakeCondition::http_ok("http://localhost:8080/ready"),
+        ];
+
+        let json = serde_json::to_string(&conditions).unwrap();
+        assert!(json.contains(r#""type":"ref-advanced","ref":"origin/main""#));
+        let parsed: Vec<WakeCondition> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, conditions);
+
+        assert_eq!(conditions[1].to_string(), "ref origin/main moves from 01234567");
+        assert_eq!(WakeCondition::ref_advanced("v2", None).to_string(), "ref v2 exists");
+    }
+}
@@ -0,0 +1,630 @@
+//! Embedding the daemon in another program
+//!
+//! [`DaemonBuilder`] wires the same components `td daemon start` runs
+//! (StateManager, Coordinator, Scheduler, TaskManager, MainWatcher, IPC, and
+//! the webhook/chat/gRPC servers when configured) from a [`Config`], with
+//! hooks for what an embedding program usually wants to supply itself:
+//!
+//! ```ignore
+//! use taskdaemon::{Config, DaemonBuilder};
+//!
+//! let mut daemon = DaemonBuilder::new(Config::load(None)?)
+//!     .with_repo_root("/srv/checkout")
+//!     .with_llm_client(my_client)            // instead of the configured provider
+//!     .with_tool(TicketTool::new(tracker))  // offered to loop types that list "ticket"
+//!     .with_event_subscriber(|event| metrics.record(&event))
+//!     .with_ipc(false)                       // don't take over td's socket
+//!     .build()
+//!     .await?;
+//! daemon.start().await?;
+//! daemon.state().create_execution(exec).await?;
+//! // ...
+//! daemon.shutdown().await?;
+//! ```
+//!
+//! `build` validates the setup and opens the TaskStore, so the handle's
+//! StateManager can be used before `start`. Signal handling and the PID file
+//! stay with the caller.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+use eyre::{Context, Result, bail};
+use tokio::sync::mpsc;
+use tokio::task::{AbortHandle, JoinHandle};
+use tracing::{debug, info, warn};
+
+use crate::chat::ChatBridge;
+use crate::config::{Config, FsckMode};
+use crate::coordinator::Coordinator;
+use crate::daemon::DaemonManager;
+use crate::events::{DEFAULT_CHANNEL_CAPACITY, Event, EventBus, OverflowPolicy, default_runs_dir};
+use crate::ipc;
+use crate::llm::{LlmClient, create_client, create_client_from_resolved};
+use crate::notify::Notifier;
+use crate::policy::{Policy, PolicySet};
+use crate::r#loop::{Evaluator, LoopLoader, TaskManager, TaskManagerConfig};
+use crate::scheduler::Scheduler;
+use crate::state::{self, StateManager};
+use crate::tools::Tool;
+use crate::watcher::{MainWatcher, WatcherConfig};
+use crate::webhook::WebhookServer;
+use crate::worktree::PrunePolicy;
+
+/// Callback run for every event on the daemon's event bus
+pub type EventCallback = Box<dyn Fn(Event) + Send + Sync>;
+
+/// Builds a [`DaemonHandle`] from a config, with overrides for embedding
+pub struct DaemonBuilder {
+    config: Config,
+    repo_root: Option<PathBuf>,
+    llm: Option<Arc<dyn LlmClient>>,
+    tools: Vec<Arc<dyn Tool>>,
+    policies: Vec<Arc<dyn Policy>>,
+    subscribers: Vec<EventCallback>,
+    ipc: bool,
+    watcher: bool,
+}
+
+impl DaemonBuilder {
+    /// Start from `config`; everything else defaults to what `td daemon start` does
+    pub fn new(config: Config) -> Self {
+        debug!("DaemonBuilder::new: called");
+        Self {
+            config,
+            repo_root: None,
+            llm: None,
+            tools: Vec::new(),
+            policies: Vec::new(),
+            subscribers: Vec::new(),
+            ipc: true,
+            watcher: true,
+        }
+    }
+
+    /// Repository loops work on (default: the current directory)
+    pub fn with_repo_root(mut self, path: impl Into<PathBuf>) -> Self {
+        self.repo_root = Some(path.into());
+        debug!(repo_root = ?self.repo_root, "DaemonBuilder::with_repo_root: called");
+        self
+    }
+
+    /// Use `client` for loops instead of the provider in `llm` (no API key needed)
+    pub fn with_llm_client(mut self, client: Arc<dyn LlmClient>) -> Self {
+        debug!("DaemonBuilder::with_llm_client: called");
+        self.llm = Some(client);
+        self
+    }
+
+    /// Offer `tool` to loops in addition to the standard set
+    ///
+    /// A loop type sees it only if it lists the tool's name under `tools`. A
+    /// tool whose name is already taken by a built-in is skipped.
+    pub fn with_tool(mut self, tool: impl Tool + 'static) -> Self {
+        debug!(tool = tool.name(), "DaemonBuilder::with_tool: called");
+        self.tools.push(Arc::new(tool));
+        self
+    }
+
+    /// Consult `policy` before executions start, tool calls run and branches merge
+    ///
+    /// Added policies are evaluated before the WebAssembly ones under
+    /// `policies.paths`, in the order they were added.
+    pub fn with_policy(mut self, policy: impl Policy + 'static) -> Self {
+        debug!(policy = policy.name(), "DaemonBuilder::with_policy: called");
+        self.policies.push(Arc::new(policy));
+        self
+    }
+
+    /// Call `callback` for every event once the daemon is started
+    ///
+    /// Callbacks run on their own task each and should not block.
+    pub fn with_event_subscriber(mut self, callback: impl Fn(Event) + Send + Sync + 'static) -> Self {
+        debug!("DaemonBuilder::with_event_subscriber: called");
+        self.subscribers.push(Box::new(callback));
+        self
+    }
+
+    /// Listen on the IPC socket `td` clients use (default: on)
+    pub fn with_ipc(mut self, enabled: bool) -> Self {
+        debug!(enabled, "DaemonBuilder::with_ipc: called");
+        self.ipc = enabled;
+        self
+    }
+
+    /// Watch the main branch and rebase running loops when it moves (default: on)
+    pub fn with_watcher(mut self, enabled: bool) -> Self {
+        debug!(enabled, "DaemonBuilder::with_watcher: called");
+        self.watcher = enabled;
+        self
+    }
+
+    /// Validate the setup and open the TaskStore
+    ///
+    /// Fails fast on a missing API key, a repo root that is not a git
+    /// repository, or a worktree directory that cannot be created. Runs the
+    /// startup fsck if `storage.fsck-on-start` asks for one.
+    pub async fn build(self) -> Result<DaemonHandle> {
+        debug!("DaemonBuilder::build: called");
+        let config = self.config;
+
+        // Validate the LLM setup by resolving the config (unless the caller brings a client)
+        let llm = match self.llm {
+            Some(client) => client,
+            None => {
+                config
+                    .llm
+                    .resolve()
+                    .and_then(|r| r.get_api_key())
+                    .context("LLM API key not found. Check api-key-env or api-key-file in your config.")?;
+                debug!("DaemonBuilder::build: API key found");
+                create_client(&config.llm).context("Failed to create LLM client")?
+            }
+        };
+
+        let repo_root = match self.repo_root {
+            Some(path) => path,
+            None => std::env::current_dir().context("Failed to get current directory")?,
+        };
+        if !repo_root.join(".git").exists() {
+            debug!(?repo_root, "DaemonBuilder::build: not a git repository");
+            bail!(
+                "Not a git repository: {}. TaskDaemon requires a git repo.",
+                repo_root.display()
+            );
+        }
+
+        let worktree_dir = &config.git.worktree_dir;
+        if let Err(e) = fs::create_dir_all(worktree_dir) {
+            debug!(?worktree_dir, error = %e, "DaemonBuilder::build: cannot create worktree directory");
+            bail!("Cannot create worktree directory {}: {}", worktree_dir.display(), e);
+        }
+        info!("Startup validation passed");
+
+        let store_path = PathBuf::from(&config.storage.taskstore_dir);
+        fs::create_dir_all(&store_path)
+            .with_context(|| format!("Failed to create store directory {}", store_path.display()))?;
+        let state = StateManager::spawn(&store_path)?;
+        info!("StateManager initialized");
+
+        // Reconcile records with the event log before anything is picked up
+        if config.storage.fsck_on_start != FsckMode::Off {
+            let repair = config.storage.fsck_on_start == FsckMode::Repair;
+            match state::fsck(&state, &default_runs_dir()?, false, repair).await {
+                Ok(report) => {
+                    for d in &report.discrepancies {
+                        warn!(exec_id = %d.exec_id, repaired = d.repaired, "fsck: {}", d.describe());
+                    }
+                }
+                Err(e) => warn!(error = %e, "Startup fsck failed"),
+            }
+        }
+
+        // Plugins are compiled once here and shared by every loop
+        #[cfg(feature = "wasm")]
+        let tools: Vec<Arc<dyn Tool>> = {
+            let plugins = crate::tools::load_wasm_tools(&config.tools.wasm)?;
+            let plugins = plugins.into_iter().map(|tool| Arc::new(tool) as Arc<dyn Tool>);
+            self.tools.into_iter().chain(plugins).collect()
+        };
+        #[cfg(not(feature = "wasm"))]
+        let tools = {
+            if !config.tools.wasm.module_files().is_empty() {
+                warn!("WASM tools found under tools.wasm.paths but td was built without the wasm feature; skipping them");
+            }
+            self.tools
+        };
+
+        // Policy modules likewise
+        #[cfg(feature = "wasm")]
+        let policies = {
+            let modules = crate::policy::load_wasm_policies(&config.policies)?;
+            let modules = modules.into_iter().map(|policy| Arc::new(policy) as Arc<dyn Policy>);
+            self.policies.into_iter().chain(modules)
+        };
+        #[cfg(not(feature = "wasm"))]
+        let policies = {
+            if !config.policies.module_files().is_empty() {
+                warn!("WASM policies found under policies.paths but td was built without the wasm feature; skipping them");
+            }
+            self.policies.into_iter()
+        };
+        let policies = policies.fold(PolicySet::new(config.policies.on_error), |set, policy| {
+            set.with_policy(policy)
+        });
+        if !policies.is_empty() {
+            info!(policies = ?policies.names(), "Loaded policies");
+        }
+
+        let loader = LoopLoader::new(&config.loops)?;
+        info!(
+            "Loaded {} loop types: {:?}",
+            loader.len(),
+            loader.names().collect::<Vec<_>>()
+        );
+
+        // Event bus shared by the coordinator and the TaskManager, bridged to the TUI
+        let mut event_bus = EventBus::with_default_capacity();
+        if let Some(batching) = config.streaming.token_batching() {
+            event_bus = event_bus.with_token_batching(batching);
+        }
+
+        Ok(DaemonHandle {
+            config,
+            repo_root,
+            store_path,
+            llm,
+            tools,
+            policies,
+            subscribers: self.subscribers,
+            ipc: self.ipc,
+            watcher: self.watcher,
+            state,
+            event_bus: Arc::new(event_bus),
+            type_loader: Arc::new(RwLock::new(loader)),
+            running: None,
+        })
+    }
+}
+
+/// Tasks of a started daemon
+struct Running {
+    shutdown_tx: mpsc::Sender<()>,
+    manager: JoinHandle<()>,
+    coordinator: AbortHandle,
+    /// Watcher, servers and subscribers, aborted on shutdown
+    background: Vec<AbortHandle>,
+    socket_path: Option<PathBuf>,
+}
+
+/// A built daemon; [`start`](Self::start) it, then [`shutdown`](Self::shutdown)
+pub struct DaemonHandle {
+    config: Config,
+    repo_root: PathBuf,
+    store_path: PathBuf,
+    llm: Arc<dyn LlmClient>,
+    tools: Vec<Arc<dyn Tool>>,
+    policies: PolicySet,
+    subscribers: Vec<EventCallback>,
+    ipc: bool,
+    watcher: bool,
+    state: StateManager,
+    event_bus: Arc<EventBus>,
+    type_loader: Arc<RwLock<LoopLoader>>,
+    running: Option<Running>,
+}
+
+impl DaemonHandle {
+    /// The daemon's StateManager (usable before `start`)
+    pub fn state(&self) -> &StateManager {
+        &self.state
+    }
+
+    /// The event bus loops and the coordinator emit on
+    pub fn event_bus(&self) -> Arc<EventBus> {
+        self.event_bus.clone()
+    }
+
+    /// Loaded loop types
+    pub fn loop_types(&self) -> Arc<RwLock<LoopLoader>> {
+        self.type_loader.clone()
+    }
+
+    /// The config the daemon was built from
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// True between `start` and `shutdown`
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    /// Spawn the daemon's tasks and start picking up executions
+    ///
+    /// Every listener is bound before anything is spawned, so a port or
+    /// socket that is taken leaves nothing running.
+    pub async fn start(&mut self) -> Result<()> {
+        debug!("DaemonHandle::start: called");
+        if self.running.is_some() {
+            bail!("Daemon already started");
+        }
+        let config = &self.config;
+
+        // Coordinator for inter-loop communication (with event persistence)
+        let coordinator = Coordinator::with_persistence(config.coordinator.clone(), &self.store_path)
+            .with_event_bus(self.event_bus.clone());
+        let coordinator_tx = coordinator.sender();
+
+        // Chat bridge: lifecycle notifications to a channel, commands back (only if configured)
+        let chat_bridge = ChatBridge::new(&config.chat, self.state.clone())?
+            .map(|bridge| Arc::new(bridge.with_coordinator(coordinator_tx.clone())));
+        if chat_bridge.as_ref().is_some_and(|b| b.command_path().is_some()) && config.webhooks.listen.is_none() {
+            warn!("chat.command-path is set but webhooks.listen is not; chat commands are disabled");
+        }
+
+        // HTTP trigger endpoint for external systems (only if configured)
+        let webhook = match &config.webhooks.listen {
+            Some(addr) => {
+                let mut server = WebhookServer::new(&config.webhooks, self.state.clone(), self.type_loader.clone())?
+                    .with_dedup(config.dedup.clone());
+                if let Some(bridge) = &chat_bridge {
+                    server = server.with_chat(bridge.clone());
+                }
+                let listener = tokio::net::TcpListener::bind(addr)
+                    .await
+                    .context(format!("Failed to bind webhook server to {}", addr))?;
+                info!("Webhook server listening on {}", addr);
+                Some((Arc::new(server), listener))
+            }
+            None => None,
+        };
+
+        // gRPC control API for embedding platforms (only if built in and configured)
+        #[cfg(feature = "grpc")]
+        let grpc = match &config.grpc.listen {
+            Some(addr) => {
+                let token = config.grpc.token()?;
+                let service = crate::grpc::GrpcService::new(
+                    self.state.clone(),
+                    self.type_loader.clone(),
+                    self.event_bus.clone(),
+                )
+                .with_dedup(config.dedup.clone());
+                let listener = tokio::net::TcpListener::bind(addr)
+                    .await
+                    .context(format!("Failed to bind gRPC server to {}", addr))?;
+                info!("gRPC server listening on {}", addr);
+                Some((service, listener, token))
+            }
+            None => None,
+        };
+        #[cfg(not(feature = "grpc"))]
+        if config.grpc.listen.is_some() {
+            warn!("grpc.listen is set but td was built without the grpc feature; the gRPC API is disabled");
+        }
+
+        // IPC listener for cross-process wake-up and observers
+        let ipc_listener = if self.ipc {
+            let (listener, socket_path) = ipc::create_listener()?;
+            info!(?socket_path, "IPC socket listening");
+            Some((listener, socket_path))
+        } else {
+            None
+        };
+
+        // Optional self-evaluation pass with a separate (cheaper) judge model
+        let evaluator = if config.evaluation.enabled {
+            let judge_model = config
+                .evaluation
+                .model
+                .clone()
+                .unwrap_or_else(|| config.llm.default.clone());
+            let resolved = config
+                .llm
+                .resolve_model(&judge_model)
+                .context("Failed to resolve evaluation model")?;
+            let judge_client =
+                create_client_from_resolved(&resolved).context("Failed to create evaluation LLM client")?;
+            info!(
+                "Self-evaluation enabled ({}, threshold {})",
+                judge_model, config.evaluation.threshold
+            );
+            Some(Evaluator::new(
+                judge_client,
+                judge_model,
+                config.evaluation.threshold,
+                config.evaluation.max_diff_chars,
+            ))
+        } else {
+            None
+        };
+
+        // Everything is bound and resolved; spawn
+        let mut background = Vec::new();
+        let coordinator = tokio::spawn(coordinator.run()).abort_handle();
+        info!("Coordinator started");
+
+        if let Some(bridge) = &chat_bridge {
+            background.push(tokio::spawn(bridge.clone().watch(config.notifications.clone())).abort_handle());
+            info!("Chat bridge enabled");
+        }
+        if let Some((server, listener)) = webhook {
+            background.push(tokio::spawn(server.serve(listener)).abort_handle());
+        }
+        #[cfg(feature = "grpc")]
+        if let Some((service, listener, token)) = grpc {
+            let handle = tokio::spawn(async move {
+                if let Err(e) = service.serve(listener, token).await {
+                    tracing::error!(error = %e, "gRPC server error");
+                }
+            });
+            background.push(handle.abort_handle());
+        }
+
+        // MainWatcher for git main branch monitoring
+        if self.watcher {
+            let main_watcher = MainWatcher::new(WatcherConfig::default(), self.repo_root.clone(), coordinator_tx.clone());
+            let handle = tokio::spawn(async move {
+                if let Err(e) = main_watcher.run().await {
+                    tracing::error!(error = %e, "MainWatcher error");
+                }
+            });
+            background.push(handle.abort_handle());
+            info!("MainWatcher started");
+        }
+
+        // Desktop notifications on completion/failure/approval
+        if config.notifications.desktop {
+            let notifier = Notifier::new(config.notifications.clone());
+            background.push(tokio::spawn(notifier.watch(self.state.clone())).abort_handle());
+            info!("Desktop notifications enabled");
+        }
+
+        // Caller-supplied event subscribers, each on its own task
+        for callback in std::mem::take(&mut self.subscribers) {
+            let mut subscriber = self
+                .event_bus
+                .subscribe_buffered(DEFAULT_CHANNEL_CAPACITY, OverflowPolicy::DropTokenEventsFirst);
+            let handle = tokio::spawn(async move {
+                while let Some(event) = subscriber.recv().await {
+                    callback(event);
+                }
+            });
+            background.push(handle.abort_handle());
+        }
+
+        // TaskManager for task orchestration
+        // poll_interval_secs is 60s (fallback) since event-driven pickup handles immediate work
+        let manager_config = TaskManagerConfig {
+            max_concurrent_tasks: config.concurrency.max_loops as usize,
+            poll_interval_secs: 60,
+            wake_check_interval_secs: 30,
+            shutdown_timeout_secs: 60,
+            repo_root: self.repo_root.clone(),
+            worktree_dir: config.git.worktree_dir.clone(),
+            event_log: config.event_log.clone(),
+            worktree_prune: PrunePolicy::from_config(&config.git),
+            cargo_target_dir: config.git.cargo_target_dir.clone(),
+            runs_dir: None,
+            tool_worker: config.tools.worker.clone(),
+            hooks: config.hooks.clone(),
+        };
+        let loop_configs = self.type_loader.read().expect("loop loader poisoned").to_configs();
+        let mut task_manager = TaskManager::new(
+            manager_config,
+            coordinator_tx, // TaskManager gets the sender, not the Coordinator
+            Scheduler::new(config.concurrency.scheduler_config()),
+            self.llm.clone(),
+            self.state.clone(),
+            loop_configs,
+            self.type_loader.clone(),
+        )
+        .with_event_bus(self.event_bus.clone())
+        .with_model(&config.llm.default)
+        .with_llm_config(config.llm.clone())
+        .with_maintenance_file(DaemonManager::new().maintenance_file())
+        .with_resource_monitor(config.resources.clone())
+        .with_tools(self.tools.clone())
+        .with_policies(self.policies.clone());
+        if let Some(evaluator) = evaluator {
+            task_manager = task_manager.with_evaluator(evaluator);
+        }
+        if config.risk.enabled {
+            task_manager = task_manager.with_risk_policy(config.risk.clone());
+        }
+        if config.review.gate_merges || config.review.github.repo.is_some() {
+            task_manager = task_manager.with_review_policy(config.review.clone());
+        }
+
+        let (shutdown_tx, shutdown_rx) = mpsc::channel::<()>(1);
+        let (listener, socket_path) = ipc_listener.unzip();
+        let manager = tokio::spawn(async move {
+            if let Err(e) = task_manager.run(shutdown_rx, listener).await {
+                tracing::error!(error = %e, "TaskManager error");
+            }
+        });
+        info!("TaskManager started");
+
+        self.running = Some(Running {
+            shutdown_tx,
+            manager,
+            coordinator,
+            background,
+            socket_path,
+        });
+        Ok(())
+    }
+
+    /// Stop picking up work, let running loops wind down, and stop every task
+    ///
+    /// Does nothing if the daemon is not running.
+    pub async fn shutdown(&mut self) -> Result<()> {
+        debug!("DaemonHandle::shutdown: called");
+        let Some(running) = self.running.take() else {
+            return Ok(());
+        };
+        info!("Daemon shutting down...");
+        let _ = running.shutdown_tx.send(()).await;
+
+        // Wait for TaskManager to finish (it handles coordinator shutdown)
+        if let Err(e) = running.manager.await {
+            warn!(error = %e, "TaskManager task failed");
+        }
+        debug!("DaemonHandle::shutdown: TaskManager finished");
+
+        if let Some(socket_path) = &running.socket_path {
+            ipc::cleanup_socket(socket_path);
+        }
+        for handle in running.background {
+            handle.abort();
+        }
+        // Coordinator was shut down by the TaskManager, but abort for safety
+        running.coordinator.abort();
+        debug!("DaemonHandle::shutdown: complete");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::client::mock::MockLlmClient;
+    use tempfile::TempDir;
+
+    fn config(temp: &TempDir) -> Config {
+        let mut config = Config::default();
+        config.storage.taskstore_dir = temp.path().join("store").display().to_string();
+        config.git.worktree_dir = temp.path().join("worktrees");
+        config
+    }
+
+    #[tokio::test]
+    async fn test_build_rejects_non_git_repo_root() {
+        let temp = TempDir::new().unwrap();
+        let result = DaemonBuilder::new(config(&temp))
+            .with_llm_client(Arc::new(MockLlmClient::new(vec![])))
+            .with_repo_root(temp.path())
+            .build()
+            .await;
+        let error = result.err().expect("not a git repository").to_string();
+        assert!(error.contains("Not a git repository"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_start_delivers_events_and_shuts_down() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir(temp.path().join(".git")).unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut daemon = DaemonBuilder::new(config(&temp))
+            .with_llm_client(Arc::new(MockLlmClient::new(vec![])))
+            .with_repo_root(temp.path())
+            .with_ipc(false)
+            .with_watcher(false)
+            .with_event_subscriber(move |event| {
+                let _ = tx.send(event.execution_id().to_string());
+            })
+            .build()
+            .await
+            .unwrap();
+        assert!(!daemon.is_running());
+
+        daemon.start().await.unwrap();
+        assert!(daemon.is_running());
+        assert!(daemon.start().await.is_err());
+
+        daemon.event_bus().emit(Event::IterationStarted {
+            execution_id: "exec-1".to_string(),
+            iteration: 1,
+        });
+        let seen = tokio::time::timeout(std::time::Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap();
+        assert_eq!(seen.as_deref(), Some("exec-1"));
+
+        daemon.shutdown().await.unwrap();
+        assert!(!daemon.is_running());
+        daemon.shutdown().await.unwrap();
+    }
+}
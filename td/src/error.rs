@@ -0,0 +1,365 @@
+//! Crate-wide error taxonomy
+//!
+//! Subsystems keep their own error types ([`LlmError`], [`ToolError`],
+//! [`StateError`], [`WorktreeError`], [`CoordError`]); [`TdError`] wraps any of
+//! them, and every one of them has a stable code (`llm.rate-limited`,
+//! `tool.sandbox-violation`, ...) and an [`ErrorCategory`] that decides
+//! whether retrying can help.
+//!
+//! Most code passes errors around as `eyre::Report`; [`classify`] finds the
+//! first known error in a report's chain so the code survives into events,
+//! execution records and JSON output.
+
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::coordinator::CoordError;
+use crate::llm::LlmError;
+use crate::state::StateError;
+use crate::tools::ToolError;
+use crate::worktree::WorktreeError;
+
+/// Broad kind of failure, for deciding how to react to it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorCategory {
+    /// The provider asked us to slow down
+    RateLimit,
+    /// Credentials missing or rejected
+    Auth,
+    /// Connection failed or dropped
+    Network,
+    /// An operation ran out of time
+    Timeout,
+    /// The provider failed on its side (5xx)
+    Provider,
+    /// The provider rejected the request (4xx other than auth and rate limits)
+    InvalidRequest,
+    /// The provider answered with something we can't use
+    InvalidResponse,
+    /// The conversation no longer fits the model's context window
+    ContextLimit,
+    /// A tool tried to leave its worktree
+    Sandbox,
+    /// A command hit a CPU, memory or wall-clock limit
+    ResourceLimit,
+    /// A tool was called with arguments it can't act on
+    InvalidInput,
+    /// A record, file or worktree doesn't exist
+    NotFound,
+    /// Concurrent changes collided (rebase conflicts, stale record revisions)
+    Conflict,
+    /// Reading or writing persistent state failed
+    Storage,
+    /// Loops are waiting on each other
+    Deadlock,
+    /// A bug or broken invariant inside the daemon
+    Internal,
+}
+
+impl ErrorCategory {
+    /// Whether the same operation may succeed if retried unchanged
+    pub fn is_retryable(self) -> bool {
+        matches!(self, Self::RateLimit | Self::Network | Self::Timeout | Self::Provider)
+    }
+
+    /// Whether the failure suggests the provider is down (counts towards the circuit breaker)
+    pub fn is_outage(self) -> bool {
+        matches!(self, Self::Network | Self::Timeout | Self::Provider)
+    }
+}
+
+impl fmt::Display for ErrorCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::RateLimit => "rate-limit",
+            Self::Auth => "auth",
+            Self::Network => "network",
+            Self::Timeout => "timeout",
+            Self::Provider => "provider",
+            Self::InvalidRequest => "invalid-request",
+            Self::InvalidResponse => "invalid-response",
+            Self::ContextLimit => "context-limit",
+            Self::Sandbox => "sandbox",
+            Self::ResourceLimit => "resource-limit",
+            Self::InvalidInput => "invalid-input",
+            Self::NotFound => "not-found",
+            Self::Conflict => "conflict",
+            Self::Storage => "storage",
+            Self::Deadlock => "deadlock",
+            Self::Internal => "internal",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// An error with a stable code and a category
+pub trait ErrorCode {
+    /// Stable `<subsystem>.<kind>` code, e.g. `llm.rate-limited`
+    fn code(&self) -> &'static str;
+
+    fn category(&self) -> ErrorCategory;
+
+    fn is_retryable(&self) -> bool {
+        self.category().is_retryable()
+    }
+}
+
+impl ErrorCode for LlmError {
+    fn code(&self) -> &'static str {
+        match self {
+            LlmError::RateLimited { .. } => "llm.rate-limited",
+            LlmError::ApiError { status: 401 | 403, .. } => "llm.auth",
+            LlmError::ApiError { status: 429, .. } => "llm.rate-limited",
+            LlmError::ApiError { status, .. } if *status >= 500 => "llm.provider",
+            LlmError::ApiError { .. } => "llm.bad-request",
+            LlmError::Network(_) => "llm.network",
+            LlmError::InvalidResponse(_) => "llm.invalid-response",
+            LlmError::Timeout(_) => "llm.timeout",
+            LlmError::Json(_) => "llm.json",
+            LlmError::Unsupported(_) => "llm.unsupported",
+            LlmError::ContextTooLarge { .. } => "llm.context-too-large",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            LlmError::RateLimited { .. } | LlmError::ApiError { status: 429, .. } => ErrorCategory::RateLimit,
+            LlmError::ApiError { status: 401 | 403, .. } => ErrorCategory::Auth,
+            LlmError::ApiError { status, .. } if *status >= 500 => ErrorCategory::Provider,
+            LlmError::ApiError { .. } | LlmError::Unsupported(_) => ErrorCategory::InvalidRequest,
+            LlmError::Network(_) => ErrorCategory::Network,
+            LlmError::Timeout(_) => ErrorCategory::Timeout,
+            LlmError::InvalidResponse(_) | LlmError::Json(_) => ErrorCategory::InvalidResponse,
+            LlmError::ContextTooLarge { .. } => ErrorCategory::ContextLimit,
+        }
+    }
+}
+
+impl ErrorCode for ToolError {
+    fn code(&self) -> &'static str {
+        match self {
+            ToolError::SandboxViolation { .. } => "tool.sandbox-violation",
+            ToolError::FileNotFound { .. } => "tool.file-not-found",
+            ToolError::EditWithoutRead { .. } => "tool.edit-without-read",
+            ToolError::CommandTimeout { .. } => "tool.command-timeout",
+            ToolError::ResourceLimitExceeded { .. } => "tool.resource-limit",
+            ToolError::UnknownTool { .. } => "tool.unknown-tool",
+            ToolError::DuplicateTool { .. } => "tool.duplicate-tool",
+            ToolError::Io(_) => "tool.io",
+            ToolError::InvalidArgument(_) => "tool.invalid-argument",
+            ToolError::PatternNotFound { .. } => "tool.pattern-not-found",
+            ToolError::PatternNotUnique { .. } => "tool.pattern-not-unique",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            ToolError::SandboxViolation { .. } => ErrorCategory::Sandbox,
+            ToolError::FileNotFound { .. } => ErrorCategory::NotFound,
+            ToolError::CommandTimeout { .. } => ErrorCategory::Timeout,
+            ToolError::ResourceLimitExceeded { .. } => ErrorCategory::ResourceLimit,
+            ToolError::Io(_) => ErrorCategory::Storage,
+            ToolError::EditWithoutRead { .. }
+            | ToolError::UnknownTool { .. }
+            | ToolError::DuplicateTool { .. }
+            | ToolError::InvalidArgument(_)
+            | ToolError::PatternNotFound { .. }
+            | ToolError::PatternNotUnique { .. } => ErrorCategory::InvalidInput,
+        }
+    }
+}
+
+impl ErrorCode for StateError {
+    fn code(&self) -> &'static str {
+        match self {
+            StateError::NotFound(_) => "state.not-found",
+            StateError::StoreError(_) => "state.store",
+            StateError::DeserializationError(_) => "state.deserialization",
+            StateError::ChannelError => "state.channel",
+            StateError::Conflict { .. } => "state.conflict",
+            StateError::Duplicate(_) => "state.duplicate",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            StateError::NotFound(_) => ErrorCategory::NotFound,
+            StateError::StoreError(_) | StateError::DeserializationError(_) => ErrorCategory::Storage,
+            StateError::ChannelError => ErrorCategory::Internal,
+            StateError::Conflict { .. } | StateError::Duplicate(_) => ErrorCategory::Conflict,
+        }
+    }
+}
+
+impl ErrorCode for WorktreeError {
+    fn code(&self) -> &'static str {
+        match self {
+            WorktreeError::CreateFailed(_) => "worktree.create-failed",
+            WorktreeError::RemoveFailed(_) => "worktree.remove-failed",
+            WorktreeError::RebaseConflict(_) => "worktree.rebase-conflict",
+            WorktreeError::NotFound(_) => "worktree.not-found",
+            WorktreeError::Corrupted(_) => "worktree.corrupted",
+            WorktreeError::DiskSpace(_) => "worktree.disk-space",
+            WorktreeError::GitError(_) => "worktree.git",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            WorktreeError::RebaseConflict(_) => ErrorCategory::Conflict,
+            WorktreeError::NotFound(_) => ErrorCategory::NotFound,
+            WorktreeError::CreateFailed(_)
+            | WorktreeError::RemoveFailed(_)
+            | WorktreeError::Corrupted(_)
+            | WorktreeError::DiskSpace(_)
+            | WorktreeError::GitError(_) => ErrorCategory::Storage,
+        }
+    }
+}
+
+impl ErrorCode for CoordError {
+    fn code(&self) -> &'static str {
+        match self {
+            CoordError::DeadlockDetected { .. } => "coord.deadlock",
+            CoordError::InvalidPayload { .. } => "coord.invalid-payload",
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            CoordError::DeadlockDetected { .. } => ErrorCategory::Deadlock,
+            CoordError::InvalidPayload { .. } => ErrorCategory::InvalidInput,
+        }
+    }
+}
+
+/// Any subsystem error
+#[derive(Debug, Error)]
+pub enum TdError {
+    #[error(transparent)]
+    Llm(#[from] LlmError),
+
+    #[error(transparent)]
+    Tool(#[from] ToolError),
+
+    #[error(transparent)]
+    State(#[from] StateError),
+
+    #[error(transparent)]
+    Worktree(#[from] WorktreeError),
+
+    #[error(transparent)]
+    Coord(#[from] CoordError),
+}
+
+impl TdError {
+    fn inner(&self) -> &dyn ErrorCode {
+        match self {
+            TdError::Llm(e) => e,
+            TdError::Tool(e) => e,
+            TdError::State(e) => e,
+            TdError::Worktree(e) => e,
+            TdError::Coord(e) => e,
+        }
+    }
+
+    /// How long to wait before retrying, if the error says
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            TdError::Llm(e) => e.retry_after(),
+            _ => None,
+        }
+    }
+}
+
+impl ErrorCode for TdError {
+    fn code(&self) -> &'static str {
+        self.inner().code()
+    }
+
+    fn category(&self) -> ErrorCategory {
+        self.inner().category()
+    }
+}
+
+/// Code and category of the first known error in `report`'s chain
+pub fn classify(report: &eyre::Report) -> Option<(&'static str, ErrorCategory)> {
+    report.chain().find_map(|err| {
+        let coded: &dyn ErrorCode = if let Some(e) = err.downcast_ref::<TdError>() {
+            e
+        } else if let Some(e) = err.downcast_ref::<LlmError>() {
+            e
+        } else if let Some(e) = err.downcast_ref::<ToolError>() {
+            e
+        } else if let Some(e) = err.downcast_ref::<StateError>() {
+            e
+        } else if let Some(e) = err.downcast_ref::<WorktreeError>() {
+            e
+        } else {
+            err.downcast_ref::<CoordError>()?
+        };
+        Some((coded.code(), coded.category()))
+    })
+}
+
+/// Just the code of [`classify`], for records and events
+pub fn code_of(report: &eyre::Report) -> Option<String> {
+    classify(report).map(|(code, _)| code.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eyre::WrapErr;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_codes_and_retryability() {
+        let auth = TdError::from(LlmError::ApiError {
+            status: 401,
+            message: "bad key".to_string(),
+        });
+        assert_eq!(auth.code(), "llm.auth");
+        assert_eq!(auth.category(), ErrorCategory::Auth);
+        assert!(!auth.is_retryable());
+
+        let limited = TdError::from(LlmError::RateLimited {
+            retry_after: Duration::from_secs(5),
+        });
+        assert!(limited.is_retryable());
+        assert_eq!(limited.retry_after(), Some(Duration::from_secs(5)));
+
+        let sandbox = TdError::from(ToolError::SandboxViolation {
+            path: PathBuf::from("/etc/passwd"),
+            worktree: PathBuf::from("/tmp/wt"),
+        });
+        assert_eq!(sandbox.code(), "tool.sandbox-violation");
+        assert_eq!(sandbox.category().to_string(), "sandbox");
+        assert!(!sandbox.is_retryable());
+
+        assert_eq!(
+            serde_json::to_string(&ErrorCategory::RateLimit).unwrap(),
+            r#""rate-limit""#
+        );
+    }
+
+    #[test]
+    fn test_classify_walks_the_chain() {
+        let report = Err::<(), _>(WorktreeError::RebaseConflict("src/lib.rs".to_string()))
+            .wrap_err("Failed to rebase exec-1")
+            .unwrap_err();
+        assert_eq!(
+            classify(&report),
+            Some(("worktree.rebase-conflict", ErrorCategory::Conflict))
+        );
+
+        let report: eyre::Report = StateError::ChannelError.into();
+        assert_eq!(code_of(&report).as_deref(), Some("state.channel"));
+
+        assert_eq!(classify(&eyre::eyre!("plain failure")), None);
+    }
+}
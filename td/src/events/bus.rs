@@ -2,39 +2,77 @@
 //!
 //! The EventBus uses tokio broadcast channels to deliver events to all subscribers
 //! with minimal latency. Components emit events, consumers (TUI, loggers) subscribe.
+//!
+//! Streaming token deltas can optionally be coalesced: with [`TokenBatching`]
+//! set, an emitter buffers deltas and sends them as one `TokenReceived` once
+//! the buffer is old or large enough, or before any other event from the same
+//! emitter. The concatenated text is exact, so the file log still holds the
+//! full response while the channel carries a fraction of the events.
+//!
+//! Consumers that must not lose events to a shared ring buffer subscribe with
+//! [`EventBus::subscribe_buffered`] instead (see [`super::dispatch`]).
 
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use tokio::sync::broadcast;
 use tracing::debug;
 
+use super::dispatch::{BufferedSubscriber, Dispatcher, OverflowPolicy};
 use super::types::Event;
+use crate::tools::{NetworkAccess, ResourceViolation};
 
 /// Default channel capacity (events)
 /// At ~100 tokens/second, this provides ~100 seconds of buffer
 pub const DEFAULT_CHANNEL_CAPACITY: usize = 10_000;
 
+/// When buffered token deltas are flushed as one `TokenReceived` event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenBatching {
+    /// Flush once the oldest buffered delta is this old
+    pub flush_interval: Duration,
+    /// Flush once this many bytes are buffered
+    pub max_bytes: usize,
+}
+
+/// Token deltas not yet emitted
+#[derive(Debug, Default)]
+struct TokenBuffer {
+    iteration: u32,
+    text: String,
+    since: Option<Instant>,
+}
+
 /// Central event bus for TaskDaemon activity streaming
 ///
 /// Every significant action in TD emits an event to this bus.
 /// All consumers (TUI, file logger, database) subscribe to receive events.
 pub struct EventBus {
-    tx: broadcast::Sender<Event>,
+    dispatch: Arc<Dispatcher>,
     #[allow(dead_code)]
     channel_capacity: usize,
+    /// Coalescing for emitters created by this bus (None = one event per delta)
+    token_batching: Option<TokenBatching>,
 }
 
 impl EventBus {
     /// Create a new event bus with the given capacity
     pub fn new(capacity: usize) -> Self {
         debug!(capacity, "EventBus::new: creating event bus");
-        let (tx, _) = broadcast::channel(capacity);
         Self {
-            tx,
+            dispatch: Arc::new(Dispatcher::new(capacity)),
             channel_capacity: capacity,
+            token_batching: None,
         }
     }
 
+    /// Coalesce streaming token deltas in emitters created by this bus
+    pub fn with_token_batching(mut self, batching: TokenBatching) -> Self {
+        debug!(?batching, "EventBus::with_token_batching: called");
+        self.token_batching = Some(batching);
+        self
+    }
+
     /// Create a new event bus with default capacity
     pub fn with_default_capacity() -> Self {
         Self::new(DEFAULT_CHANNEL_CAPACITY)
@@ -50,8 +88,7 @@ impl EventBus {
             execution_id = event.execution_id(),
             "EventBus::emit"
         );
-        // Ignore send errors (no subscribers is OK)
-        let _ = self.tx.send(event);
+        self.dispatch.send(event);
     }
 
     /// Subscribe to receive events
@@ -60,7 +97,21 @@ impl EventBus {
     /// Note: Events emitted before subscription are not received.
     pub fn subscribe(&self) -> broadcast::Receiver<Event> {
         debug!("EventBus::subscribe: new subscriber");
-        self.tx.subscribe()
+        self.dispatch.subscribe()
+    }
+
+    /// Subscribe with a private queue of `capacity` events and an overflow policy
+    ///
+    /// Unlike [`subscribe`](Self::subscribe), a slow consumer only affects its
+    /// own queue, and what it loses on overflow is chosen by `policy`.
+    pub fn subscribe_buffered(&self, capacity: usize, policy: OverflowPolicy) -> BufferedSubscriber {
+        debug!(capacity, ?policy, "EventBus::subscribe_buffered: new subscriber");
+        self.dispatch.subscribe_buffered(capacity, policy)
+    }
+
+    /// Total events dropped from buffered subscribers' queues on overflow
+    pub fn dropped_events(&self) -> u64 {
+        self.dispatch.dropped_events()
     }
 
     /// Create an emitter handle for a specific execution
@@ -71,14 +122,16 @@ impl EventBus {
         let execution_id = execution_id.into();
         debug!(%execution_id, "EventBus::emitter_for: creating emitter");
         EventEmitter {
-            tx: self.tx.clone(),
+            dispatch: self.dispatch.clone(),
             execution_id,
+            token_batching: self.token_batching,
+            token_buffer: Arc::new(Mutex::new(TokenBuffer::default())),
         }
     }
 
     /// Get the number of active subscribers
     pub fn subscriber_count(&self) -> usize {
-        self.tx.receiver_count()
+        self.dispatch.subscriber_count()
     }
 }
 
@@ -91,11 +144,14 @@ impl Default for EventBus {
 /// Handle for components to emit events without owning the bus
 ///
 /// EventEmitter is cheap to clone and provides convenience methods
-/// for emitting events with a pre-set execution ID.
+/// for emitting events with a pre-set execution ID. Clones share the
+/// token buffer, so deltas from a streaming task stay in order.
 #[derive(Clone)]
 pub struct EventEmitter {
-    tx: broadcast::Sender<Event>,
+    dispatch: Arc<Dispatcher>,
     execution_id: String,
+    token_batching: Option<TokenBatching>,
+    token_buffer: Arc<Mutex<TokenBuffer>>,
 }
 
 impl EventEmitter {
@@ -104,10 +160,33 @@ impl EventEmitter {
         &self.execution_id
     }
 
-    /// Emit a raw event
+    /// Emit a raw event (after any buffered tokens)
     pub fn emit(&self, event: Event) {
         debug!(event_type = event.event_type(), "EventEmitter::emit");
-        let _ = self.tx.send(event);
+        self.flush_tokens();
+        self.dispatch.send(event);
+    }
+
+    /// Emit buffered token deltas now
+    pub fn flush_tokens(&self) {
+        let Ok(mut buffer) = self.token_buffer.lock() else {
+            return;
+        };
+        self.send_buffered(&mut buffer);
+    }
+
+    fn send_buffered(&self, buffer: &mut TokenBuffer) {
+        buffer.since = None;
+        if buffer.text.is_empty() {
+            return;
+        }
+        let token = std::mem::take(&mut buffer.text);
+        debug!(bytes = token.len(), "EventEmitter::send_buffered: flushing tokens");
+        self.dispatch.send(Event::TokenReceived {
+            execution_id: self.execution_id.clone(),
+            iteration: buffer.iteration,
+            token,
+        });
     }
 
     // === Convenience methods ===
@@ -131,6 +210,25 @@ impl EventEmitter {
         });
     }
 
+    /// Emit a phase completed event
+    pub fn phase_completed(
+        &self,
+        phase_index: usize,
+        phase_name: &str,
+        iterations_used: u32,
+        budget: u32,
+        carried_over: u32,
+    ) {
+        self.emit(Event::PhaseCompleted {
+            execution_id: self.execution_id.clone(),
+            phase_index,
+            phase_name: phase_name.to_string(),
+            iterations_used,
+            budget,
+            carried_over,
+        });
+    }
+
     /// Emit an iteration started event
     pub fn iteration_started(&self, iteration: u32) {
         self.emit(Event::IterationStarted {
@@ -157,6 +255,27 @@ impl EventEmitter {
         });
     }
 
+    /// Emit a loop stuck event
+    pub fn loop_stuck(&self, iteration: u32, unchanged_iterations: u32, action: &str) {
+        self.emit(Event::LoopStuck {
+            execution_id: self.execution_id.clone(),
+            iteration,
+            unchanged_iterations,
+            action: action.to_string(),
+        });
+    }
+
+    /// Emit an override changed event
+    pub fn override_changed(&self, iteration: u32, setting: &str, from: &str, to: &str) {
+        self.emit(Event::OverrideChanged {
+            execution_id: self.execution_id.clone(),
+            iteration,
+            setting: setting.to_string(),
+            from: from.to_string(),
+            to: to.to_string(),
+        });
+    }
+
     /// Emit a prompt sent event
     pub fn prompt_sent(&self, iteration: u32, summary: &str, token_count: u64) {
         self.emit(Event::PromptSent {
@@ -167,13 +286,26 @@ impl EventEmitter {
         });
     }
 
-    /// Emit a token received event (streaming)
+    /// Emit a token received event (streaming), coalesced if the bus batches tokens
     pub fn token_received(&self, iteration: u32, token: &str) {
-        self.emit(Event::TokenReceived {
-            execution_id: self.execution_id.clone(),
-            iteration,
-            token: token.to_string(),
-        });
+        let (Some(batching), Ok(mut buffer)) = (self.token_batching, self.token_buffer.lock()) else {
+            self.dispatch.send(Event::TokenReceived {
+                execution_id: self.execution_id.clone(),
+                iteration,
+                token: token.to_string(),
+            });
+            return;
+        };
+
+        if buffer.iteration != iteration {
+            self.send_buffered(&mut buffer);
+            buffer.iteration = iteration;
+        }
+        buffer.text.push_str(token);
+        let since = *buffer.since.get_or_insert_with(Instant::now);
+        if buffer.text.len() >= batching.max_bytes || since.elapsed() >= batching.flush_interval {
+            self.send_buffered(&mut buffer);
+        }
     }
 
     /// Emit a response completed event
@@ -184,6 +316,7 @@ impl EventEmitter {
         input_tokens: u64,
         output_tokens: u64,
         has_tool_calls: bool,
+        served_by: Option<&str>,
     ) {
         self.emit(Event::ResponseCompleted {
             execution_id: self.execution_id.clone(),
@@ -192,6 +325,7 @@ impl EventEmitter {
             input_tokens,
             output_tokens,
             has_tool_calls,
+            served_by: served_by.map(String::from),
         });
     }
 
@@ -224,6 +358,47 @@ impl EventEmitter {
         });
     }
 
+    /// Emit a resource limit exceeded event
+    pub fn resource_limit_exceeded(&self, iteration: u32, tool_name: &str, violation: &ResourceViolation) {
+        self.emit(Event::ResourceLimitExceeded {
+            execution_id: self.execution_id.clone(),
+            iteration,
+            tool_name: tool_name.to_string(),
+            resource: violation.kind.to_string(),
+            limit: violation.limit,
+        });
+    }
+
+    /// Emit an outbound request a tool made or was refused
+    pub fn network_request(&self, iteration: u32, access: &NetworkAccess) {
+        self.emit(Event::NetworkRequest {
+            execution_id: self.execution_id.clone(),
+            iteration,
+            tool_name: access.tool.clone(),
+            host: access.host.clone(),
+            target: access.target.clone(),
+            allowed: access.allowed,
+        });
+    }
+
+    /// Emit a path lock conflict event
+    pub fn path_conflict(&self, path: &str, held_by: &str) {
+        self.emit(Event::PathConflict {
+            execution_id: self.execution_id.clone(),
+            path: path.to_string(),
+            held_by: held_by.to_string(),
+        });
+    }
+
+    /// Emit a rate limited event
+    pub fn rate_limited(&self, iteration: u32, retry_after_ms: u64) {
+        self.emit(Event::RateLimited {
+            execution_id: self.execution_id.clone(),
+            iteration,
+            retry_after_ms,
+        });
+    }
+
     /// Emit a validation started event
     pub fn validation_started(&self, iteration: u32, command: &str) {
         self.emit(Event::ValidationStarted {
@@ -253,12 +428,13 @@ impl EventEmitter {
         });
     }
 
-    /// Emit an error event
-    pub fn error(&self, context: &str, message: &str) {
+    /// Emit an error event, with the error's code if it has one
+    pub fn error(&self, context: &str, message: &str, code: Option<&str>) {
         self.emit(Event::Error {
             execution_id: self.execution_id.clone(),
             context: context.to_string(),
             message: message.to_string(),
+            code: code.map(str::to_string),
         });
     }
 
@@ -374,6 +550,48 @@ mod tests {
         assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
     }
 
+    #[tokio::test]
+    async fn test_token_batching_coalesces_and_preserves_text() {
+        let bus = EventBus::new(100).with_token_batching(TokenBatching {
+            flush_interval: Duration::from_secs(60),
+            max_bytes: 10,
+        });
+        let mut rx = bus.subscribe();
+        let emitter = bus.emitter_for("batched");
+        let streaming = emitter.clone();
+
+        let tokens: Vec<String> = (0..30).map(|i| format!("t{} ", i)).collect();
+        for token in &tokens {
+            streaming.token_received(1, token);
+        }
+        streaming.token_received(2, "next");
+        emitter.response_completed(2, "done", 10, 5, false, None);
+
+        let mut events = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            events.push(event);
+        }
+        assert!(
+            events.len() < tokens.len() / 2,
+            "expected coalescing, got {} events",
+            events.len()
+        );
+
+        let mut text = String::new();
+        for event in &events[..events.len() - 2] {
+            let Event::TokenReceived { iteration, token, .. } = event else {
+                panic!("expected only token events before the flush");
+            };
+            assert_eq!(*iteration, 1);
+            text.push_str(token);
+        }
+        assert_eq!(text, tokens.concat());
+        assert!(
+            matches!(&events[events.len() - 2], Event::TokenReceived { iteration: 2, token, .. } if token == "next")
+        );
+        assert_eq!(events[events.len() - 1].event_type(), "ResponseCompleted");
+    }
+
     #[tokio::test]
     async fn test_multiple_subscribers() {
         let bus = EventBus::new(100);
@@ -409,7 +627,7 @@ mod tests {
         emitter.token_received(1, "I'll");
         emitter.token_received(1, " start");
         emitter.token_received(1, " by");
-        emitter.response_completed(1, "I'll start by...", 500, 100, true);
+        emitter.response_completed(1, "I'll start by...", 500, 100, true, None);
         emitter.tool_call_started(1, "write_file", "path: src/main.rs");
         emitter.tool_call_completed(1, "write_file", true, "File written", 50);
         emitter.validation_started(1, "cargo test");
@@ -506,7 +724,7 @@ mod tests {
         let emitter = bus.emitter_for("error-test");
 
         emitter.warning("validation", "Timeout approaching");
-        emitter.error("llm", "Rate limit exceeded");
+        emitter.error("llm", "Rate limit exceeded", Some("llm.rate-limited"));
 
         let warning = rx.recv().await.unwrap();
         assert_eq!(warning.event_type(), "Warning");
@@ -550,6 +768,7 @@ mod tests {
             5,
             IterationOutcome::LlmError {
                 error: "Context too long".to_string(),
+                code: Some("llm.context-too-large".to_string()),
             },
         );
 
@@ -0,0 +1,337 @@
+//! Per-subscriber buffered dispatch
+//!
+//! A plain [`EventBus::subscribe`](super::EventBus::subscribe) receiver shares
+//! one ring buffer with every other subscriber: a slow consumer falls behind
+//! and loses whatever was overwritten (`Lagged`). A buffered subscriber gets
+//! its own queue instead, filled directly by the emitters, and chooses what to
+//! give up when that queue is full. Emitting never waits on a subscriber.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Notify, broadcast};
+use tracing::{debug, warn};
+
+use super::types::Event;
+
+/// What a buffered subscriber gives up when its queue is full
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverflowPolicy {
+    /// Drop the oldest queued event
+    #[default]
+    DropOldest,
+    /// Drop the oldest queued token-level event, or the oldest event if there are none
+    DropTokenEventsFirst,
+    /// Drop nothing: the queue grows past its capacity rather than hold up the producer
+    BlockProducerNever,
+}
+
+impl std::str::FromStr for OverflowPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        debug!(%s, "OverflowPolicy::from_str: called");
+        match s {
+            "drop-oldest" => Ok(Self::DropOldest),
+            "drop-token-events-first" => Ok(Self::DropTokenEventsFirst),
+            "block-producer-never" => Ok(Self::BlockProducerNever),
+            _ => Err(format!(
+                "Unknown overflow policy: {}. Use: drop-oldest, drop-token-events-first, or block-producer-never",
+                s
+            )),
+        }
+    }
+}
+
+/// Counters for one buffered subscriber
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SubscriberStats {
+    /// Events handed to the consumer
+    pub delivered: u64,
+    /// Events dropped on overflow
+    pub dropped: u64,
+    /// Of the dropped events, how many were token-level
+    pub dropped_tokens: u64,
+    /// Largest queue length seen
+    pub high_water: usize,
+}
+
+/// Error from [`BufferedSubscriber::try_recv`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// No event queued right now
+    Empty,
+    /// The bus and all emitters are gone and the queue is drained
+    Closed,
+}
+
+/// Queue shared between the dispatcher and one buffered subscriber
+struct SubscriberQueue {
+    events: Mutex<(VecDeque<Event>, SubscriberStats)>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    notify: Notify,
+    closed: AtomicBool,
+}
+
+impl SubscriberQueue {
+    /// Queue `event`, applying the overflow policy; returns whether an event was dropped
+    fn push(&self, event: Event) -> bool {
+        let Ok(mut guard) = self.events.lock() else {
+            return false;
+        };
+        let (queue, stats) = &mut *guard;
+        let mut dropped = None;
+        if queue.len() >= self.capacity {
+            dropped = match self.policy {
+                OverflowPolicy::DropOldest => queue.pop_front(),
+                OverflowPolicy::DropTokenEventsFirst => match queue.iter().position(Event::is_token_level) {
+                    Some(index) => queue.remove(index),
+                    None => queue.pop_front(),
+                },
+                OverflowPolicy::BlockProducerNever => None,
+            };
+        }
+        if let Some(ref event) = dropped {
+            stats.dropped += 1;
+            if event.is_token_level() {
+                stats.dropped_tokens += 1;
+            }
+        }
+        queue.push_back(event);
+        stats.high_water = stats.high_water.max(queue.len());
+        drop(guard);
+        self.notify.notify_one();
+        dropped.is_some()
+    }
+
+    fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        self.notify.notify_one();
+    }
+}
+
+/// Fan-out shared by the bus and every emitter
+pub(crate) struct Dispatcher {
+    tx: broadcast::Sender<Event>,
+    buffered: Mutex<Vec<Weak<SubscriberQueue>>>,
+    dropped: AtomicU64,
+}
+
+impl Dispatcher {
+    pub fn new(capacity: usize) -> Self {
+        debug!(capacity, "Dispatcher::new: called");
+        let (tx, _) = broadcast::channel(capacity);
+        Self {
+            tx,
+            buffered: Mutex::new(Vec::new()),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Deliver to broadcast receivers and every live buffered subscriber
+    pub fn send(&self, event: Event) {
+        if let Ok(mut buffered) = self.buffered.lock() {
+            buffered.retain(|queue| queue.strong_count() > 0);
+            for queue in buffered.iter().filter_map(Weak::upgrade) {
+                if queue.push(event.clone()) {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+        // Ignore send errors (no subscribers is OK)
+        let _ = self.tx.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.tx.subscribe()
+    }
+
+    pub fn subscribe_buffered(&self, capacity: usize, policy: OverflowPolicy) -> BufferedSubscriber {
+        debug!(capacity, ?policy, "Dispatcher::subscribe_buffered: called");
+        let queue = Arc::new(SubscriberQueue {
+            events: Mutex::new((VecDeque::with_capacity(capacity.min(1024)), SubscriberStats::default())),
+            capacity: capacity.max(1),
+            policy,
+            notify: Notify::new(),
+            closed: AtomicBool::new(false),
+        });
+        match self.buffered.lock() {
+            Ok(mut buffered) => buffered.push(Arc::downgrade(&queue)),
+            Err(_) => warn!("Dispatcher::subscribe_buffered: subscriber list poisoned"),
+        }
+        BufferedSubscriber { queue }
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        let buffered = self
+            .buffered
+            .lock()
+            .map(|b| b.iter().filter(|q| q.strong_count() > 0).count())
+            .unwrap_or(0);
+        self.tx.receiver_count() + buffered
+    }
+
+    pub fn dropped_events(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for Dispatcher {
+    fn drop(&mut self) {
+        if let Ok(buffered) = self.buffered.lock() {
+            for queue in buffered.iter().filter_map(Weak::upgrade) {
+                queue.close();
+            }
+        }
+    }
+}
+
+/// Receiver with its own bounded queue and overflow policy
+pub struct BufferedSubscriber {
+    queue: Arc<SubscriberQueue>,
+}
+
+impl BufferedSubscriber {
+    /// Take the next queued event without waiting
+    pub fn try_recv(&mut self) -> Result<Event, TryRecvError> {
+        let mut guard = self.queue.events.lock().map_err(|_| TryRecvError::Closed)?;
+        let (queue, stats) = &mut *guard;
+        match queue.pop_front() {
+            Some(event) => {
+                stats.delivered += 1;
+                Ok(event)
+            }
+            None if self.queue.closed.load(Ordering::Acquire) => Err(TryRecvError::Closed),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Wait for the next event (None once the bus is gone and the queue is drained)
+    pub async fn recv(&mut self) -> Option<Event> {
+        loop {
+            match self.try_recv() {
+                Ok(event) => return Some(event),
+                Err(TryRecvError::Closed) => return None,
+                Err(TryRecvError::Empty) => self.queue.notify.notified().await,
+            }
+        }
+    }
+
+    /// Counters for this subscriber
+    pub fn stats(&self) -> SubscriberStats {
+        self.queue.events.lock().map(|g| g.1).unwrap_or_default()
+    }
+
+    /// Overflow policy chosen at subscription
+    pub fn policy(&self) -> OverflowPolicy {
+        self.queue.policy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn started(iteration: u32) -> Event {
+        Event::IterationStarted {
+            execution_id: "exec-1".to_string(),
+            iteration,
+        }
+    }
+
+    fn token(text: &str) -> Event {
+        Event::TokenReceived {
+            execution_id: "exec-1".to_string(),
+            iteration: 1,
+            token: text.to_string(),
+        }
+    }
+
+    fn drain(sub: &mut BufferedSubscriber) -> Vec<Event> {
+        std::iter::from_fn(|| sub.try_recv().ok()).collect()
+    }
+
+    #[test]
+    fn test_drop_oldest_keeps_newest() {
+        let dispatch = Dispatcher::new(16);
+        let mut sub = dispatch.subscribe_buffered(2, OverflowPolicy::DropOldest);
+        for i in 1..=4 {
+            dispatch.send(started(i));
+        }
+
+        let iterations: Vec<u32> = drain(&mut sub)
+            .iter()
+            .map(|e| match e {
+                Event::IterationStarted { iteration, .. } => *iteration,
+                _ => 0,
+            })
+            .collect();
+        assert_eq!(iterations, vec![3, 4]);
+        let stats = sub.stats();
+        assert_eq!(stats.dropped, 2);
+        assert_eq!(stats.delivered, 2);
+        assert_eq!(stats.high_water, 2);
+        assert_eq!(dispatch.dropped_events(), 2);
+    }
+
+    #[test]
+    fn test_drop_token_events_first_spares_lifecycle_events() {
+        let dispatch = Dispatcher::new(16);
+        let mut sub = dispatch.subscribe_buffered(3, OverflowPolicy::DropTokenEventsFirst);
+        dispatch.send(started(1));
+        dispatch.send(token("a"));
+        dispatch.send(token("b"));
+        dispatch.send(started(2));
+        dispatch.send(started(3));
+
+        let events = drain(&mut sub);
+        assert_eq!(events.len(), 3);
+        assert!(events.iter().all(|e| !e.is_token_level()));
+        let stats = sub.stats();
+        assert_eq!(stats.dropped, 2);
+        assert_eq!(stats.dropped_tokens, 2);
+    }
+
+    #[test]
+    fn test_block_producer_never_keeps_everything() {
+        let dispatch = Dispatcher::new(4);
+        let mut sub = dispatch.subscribe_buffered(2, OverflowPolicy::BlockProducerNever);
+        for i in 0..10 {
+            dispatch.send(token(&i.to_string()));
+        }
+
+        assert_eq!(drain(&mut sub).len(), 10);
+        assert_eq!(sub.stats().dropped, 0);
+        assert_eq!(sub.stats().high_water, 10);
+        assert_eq!(dispatch.dropped_events(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_recv_drains_then_closes_when_dispatcher_dropped() {
+        let dispatch = Dispatcher::new(4);
+        let mut sub = dispatch.subscribe_buffered(8, OverflowPolicy::default());
+        assert_eq!(dispatch.subscriber_count(), 1);
+        dispatch.send(started(1));
+        drop(dispatch);
+
+        assert!(matches!(
+            sub.recv().await,
+            Some(Event::IterationStarted { iteration: 1, .. })
+        ));
+        assert!(sub.recv().await.is_none());
+        assert_eq!(sub.try_recv().unwrap_err(), TryRecvError::Closed);
+    }
+
+    #[test]
+    fn test_overflow_policy_parse() {
+        assert_eq!(
+            "drop-token-events-first".parse::<OverflowPolicy>().unwrap(),
+            OverflowPolicy::DropTokenEventsFirst
+        );
+        assert!("drop-newest".parse::<OverflowPolicy>().is_err());
+    }
+}
@@ -1,19 +1,33 @@
 //! Event Logger - persists events to JSONL files
 //!
 //! The EventLogger subscribes to the EventBus and writes all events to
-//! per-execution JSONL files for history, debugging, and replay.
+//! per-execution JSONL files for history, debugging, and replay. Files are
+//! rotated by size and age, and logs of idle executions are periodically
+//! compacted (see [`super::segments`]).
 
 use std::collections::HashMap;
 use std::fs::{self, File, OpenOptions};
 use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
-use tokio::sync::broadcast;
-use tracing::{debug, error, warn};
+use chrono::Utc;
+use tracing::{debug, error, info, warn};
 
-use super::bus::EventBus;
+use super::bus::{DEFAULT_CHANNEL_CAPACITY, EventBus};
+use super::dispatch::OverflowPolicy;
+use super::segments::{
+    ACTIVE_SEGMENT, SegmentIndex, SegmentStats, compact_idle_executions, read_execution_events_since, segment_stats,
+};
 use super::types::{Event, EventLogEntry};
+use crate::config::EventLogConfig;
+
+/// Open active segment of one execution
+struct SegmentWriter {
+    writer: BufWriter<File>,
+    stats: SegmentStats,
+}
 
 /// Event logger that writes events to JSONL files
 ///
@@ -22,7 +36,9 @@ pub struct EventLogger {
     /// Base directory for run data (~/.taskdaemon/runs)
     runs_dir: PathBuf,
     /// Open file writers per execution
-    writers: HashMap<String, BufWriter<File>>,
+    writers: HashMap<String, SegmentWriter>,
+    /// Rotation, compaction, and retention settings
+    config: EventLogConfig,
 }
 
 impl EventLogger {
@@ -33,44 +49,92 @@ impl EventLogger {
         Self {
             runs_dir,
             writers: HashMap::new(),
+            config: EventLogConfig::default(),
         }
     }
 
     /// Create a logger with the default runs directory (~/.taskdaemon/runs)
     pub fn with_default_path() -> eyre::Result<Self> {
-        let home = dirs::home_dir().ok_or_else(|| eyre::eyre!("Could not determine home directory"))?;
-        let runs_dir = home.join(".taskdaemon").join("runs");
+        let runs_dir = default_runs_dir()?;
         fs::create_dir_all(&runs_dir)?;
         Ok(Self::new(runs_dir))
     }
 
+    /// Use the given rotation and compaction settings
+    pub fn with_config(mut self, config: EventLogConfig) -> Self {
+        debug!(?config, "EventLogger::with_config: called");
+        self.config = config;
+        self
+    }
+
+    /// Open (or reopen after a restart) the active segment of an execution
+    fn open_segment(&self, execution_id: &str) -> eyre::Result<SegmentWriter> {
+        let exec_dir = self.runs_dir.join(execution_id);
+        fs::create_dir_all(&exec_dir)?;
+
+        let log_path = exec_dir.join(ACTIVE_SEGMENT);
+        debug!(?log_path, "EventLogger: opening log file");
+        let stats = segment_stats(&log_path).unwrap_or_default();
+        let file = OpenOptions::new().create(true).append(true).open(&log_path)?;
+        Ok(SegmentWriter {
+            writer: BufWriter::new(file),
+            stats,
+        })
+    }
+
+    /// Whether the active segment should be rotated before the next write
+    fn needs_rotation(&self, stats: &SegmentStats) -> bool {
+        let too_large = stats.bytes >= self.config.max_segment_bytes;
+        let too_old = stats.first.is_some_and(|first| {
+            (Utc::now() - first).num_seconds() >= (self.config.max_segment_age_hours * 3600) as i64
+        });
+        stats.events > 0 && (too_large || too_old)
+    }
+
+    /// Move the active segment to `events.{n}.jsonl` and record it in the index
+    fn rotate(&mut self, execution_id: &str) -> eyre::Result<()> {
+        debug!(%execution_id, "EventLogger::rotate: called");
+        let exec_dir = self.runs_dir.join(execution_id);
+        let Some(mut segment) = self.writers.remove(execution_id) else {
+            return Ok(());
+        };
+        segment.writer.flush()?;
+        drop(segment.writer);
+
+        let mut index = SegmentIndex::load(&exec_dir);
+        let file = index.next_segment_file();
+        fs::rename(exec_dir.join(ACTIVE_SEGMENT), exec_dir.join(&file))?;
+        index.segments.push(segment.stats.into_info(file));
+        index.save(&exec_dir)?;
+        Ok(())
+    }
+
     /// Write an event to its execution's log file
     pub fn write_event(&mut self, event: &Event) -> eyre::Result<()> {
         let execution_id = event.execution_id();
         debug!(%execution_id, event_type = event.event_type(), "EventLogger::write_event");
 
+        if self
+            .writers
+            .get(execution_id)
+            .is_some_and(|segment| self.needs_rotation(&segment.stats))
+        {
+            self.rotate(execution_id)?;
+        }
+
         // Get or create writer for this execution
-        let writer = if let Some(w) = self.writers.get_mut(execution_id) {
-            w
-        } else {
-            // Create directory and file for new execution
-            let exec_dir = self.runs_dir.join(execution_id);
-            fs::create_dir_all(&exec_dir)?;
-
-            let log_path = exec_dir.join("events.jsonl");
-            debug!(?log_path, "EventLogger: creating new log file");
-
-            let file = OpenOptions::new().create(true).append(true).open(&log_path)?;
-            let writer = BufWriter::new(file);
-            self.writers.insert(execution_id.to_string(), writer);
-            self.writers.get_mut(execution_id).unwrap()
-        };
+        if !self.writers.contains_key(execution_id) {
+            let segment = self.open_segment(execution_id)?;
+            self.writers.insert(execution_id.to_string(), segment);
+        }
+        let segment = self.writers.get_mut(execution_id).unwrap();
 
         // Write event as JSON line
         let entry = EventLogEntry::new(event.clone());
         let json = serde_json::to_string(&entry)?;
-        writeln!(writer, "{}", json)?;
-        writer.flush()?;
+        writeln!(segment.writer, "{}", json)?;
+        segment.writer.flush()?;
+        segment.stats.record(entry.timestamp, json.len() as u64 + 1);
 
         Ok(())
     }
@@ -78,8 +142,42 @@ impl EventLogger {
     /// Close writer for an execution (e.g., when loop completes)
     pub fn close_execution(&mut self, execution_id: &str) {
         debug!(%execution_id, "EventLogger::close_execution");
-        if let Some(mut writer) = self.writers.remove(execution_id) {
-            let _ = writer.flush();
+        if let Some(mut segment) = self.writers.remove(execution_id) {
+            let _ = segment.writer.flush();
+        }
+    }
+
+    /// Compact the logs of executions idle for longer than `compact-after-hours`
+    ///
+    /// Executions with an open writer are still running and are skipped.
+    pub fn compact(&self) {
+        let idle_for = Duration::from_secs(self.config.compact_after_hours * 3600);
+        let open: Vec<&str> = self.writers.keys().map(String::as_str).collect();
+        match compact_idle_executions(&self.runs_dir, idle_for, &open) {
+            Ok(stats) if stats.executions > 0 => info!(
+                executions = stats.executions,
+                events_dropped = stats.events_dropped,
+                bytes_before = stats.bytes_before,
+                bytes_after = stats.bytes_after,
+                "EventLogger: compacted event logs"
+            ),
+            Ok(_) => debug!("EventLogger::compact: nothing to compact"),
+            Err(e) => warn!(error = %e, "EventLogger: compaction failed"),
+        }
+    }
+
+    /// Handle one event from the bus
+    fn handle_event(&mut self, event: Event) {
+        // Close writer if loop completed
+        let execution_id = event.execution_id().to_string();
+        let is_loop_completed = matches!(event, Event::LoopCompleted { .. });
+
+        if let Err(e) = self.write_event(&event) {
+            error!(%execution_id, error = %e, "EventLogger: failed to write event");
+        }
+
+        if is_loop_completed {
+            self.close_execution(&execution_id);
         }
     }
 
@@ -88,73 +186,49 @@ impl EventLogger {
     /// This is meant to be spawned as a background task.
     pub async fn run(mut self, event_bus: Arc<EventBus>) {
         debug!("EventLogger::run: starting event logger");
-        let mut rx = event_bus.subscribe();
+        // Every event must reach the log file, so this queue never drops
+        let mut rx = event_bus.subscribe_buffered(DEFAULT_CHANNEL_CAPACITY, OverflowPolicy::BlockProducerNever);
+        let mut compaction = tokio::time::interval(Duration::from_secs(self.config.compaction_interval_secs.max(1)));
 
         loop {
-            match rx.recv().await {
-                Ok(event) => {
-                    // Close writer if loop completed
-                    let execution_id = event.execution_id().to_string();
-                    let is_loop_completed = matches!(event, Event::LoopCompleted { .. });
-
-                    if let Err(e) = self.write_event(&event) {
-                        error!(%execution_id, error = %e, "EventLogger: failed to write event");
+            tokio::select! {
+                event = rx.recv() => match event {
+                    Some(event) => self.handle_event(event),
+                    None => {
+                        debug!("EventLogger: channel closed, shutting down");
+                        break;
                     }
-
-                    if is_loop_completed {
-                        self.close_execution(&execution_id);
-                    }
-                }
-                Err(broadcast::error::RecvError::Lagged(n)) => {
-                    warn!(missed = n, "EventLogger: lagged behind, missed events");
-                    // Continue processing - we'll catch up
-                }
-                Err(broadcast::error::RecvError::Closed) => {
-                    debug!("EventLogger: channel closed, shutting down");
-                    break;
-                }
+                },
+                _ = compaction.tick() => self.compact(),
             }
         }
 
         // Flush all remaining writers
-        for (exec_id, mut writer) in self.writers.drain() {
+        for (exec_id, mut segment) in self.writers.drain() {
             debug!(%exec_id, "EventLogger: flushing writer on shutdown");
-            let _ = writer.flush();
+            let _ = segment.writer.flush();
         }
     }
 }
 
-/// Read events from an execution's log file
-pub fn read_execution_events(runs_dir: impl AsRef<Path>, execution_id: &str) -> eyre::Result<Vec<EventLogEntry>> {
-    let log_path = runs_dir.as_ref().join(execution_id).join("events.jsonl");
-    debug!(?log_path, "read_execution_events: reading log file");
-
-    if !log_path.exists() {
-        return Ok(Vec::new());
-    }
-
-    let content = fs::read_to_string(&log_path)?;
-    let mut entries = Vec::new();
-
-    for line in content.lines() {
-        if line.trim().is_empty() {
-            continue;
-        }
-        match serde_json::from_str::<EventLogEntry>(line) {
-            Ok(entry) => entries.push(entry),
-            Err(e) => {
-                warn!(line, error = %e, "read_execution_events: failed to parse line");
-            }
-        }
-    }
+/// Default runs directory (~/.taskdaemon/runs)
+pub fn default_runs_dir() -> eyre::Result<PathBuf> {
+    let home = dirs::home_dir().ok_or_else(|| eyre::eyre!("Could not determine home directory"))?;
+    Ok(home.join(".taskdaemon").join("runs"))
+}
 
-    debug!(count = entries.len(), "read_execution_events: loaded entries");
-    Ok(entries)
+/// Read events from an execution's log files (rotated segments, then the active one)
+pub fn read_execution_events(runs_dir: impl AsRef<Path>, execution_id: &str) -> eyre::Result<Vec<EventLogEntry>> {
+    debug!(%execution_id, "read_execution_events: called");
+    read_execution_events_since(runs_dir, execution_id, None)
 }
 
 /// Spawn the event logger as a background task
-pub fn spawn_event_logger(event_bus: Arc<EventBus>) -> eyre::Result<tokio::task::JoinHandle<()>> {
-    let logger = EventLogger::with_default_path()?;
+pub fn spawn_event_logger(
+    event_bus: Arc<EventBus>,
+    config: EventLogConfig,
+) -> eyre::Result<tokio::task::JoinHandle<()>> {
+    let logger = EventLogger::with_default_path()?.with_config(config);
     Ok(tokio::spawn(async move {
         logger.run(event_bus).await;
     }))
@@ -165,9 +239,7 @@ pub fn spawn_event_logger(event_bus: Arc<EventBus>) -> eyre::Result<tokio::task:
 /// Returns all events for the given execution ID, sorted by timestamp.
 /// Returns an empty Vec if the execution has no logged events.
 pub fn replay_execution_events(execution_id: &str) -> eyre::Result<Vec<Event>> {
-    let home = dirs::home_dir().ok_or_else(|| eyre::eyre!("Could not determine home directory"))?;
-    let runs_dir = home.join(".taskdaemon").join("runs");
-    let entries = read_execution_events(&runs_dir, execution_id)?;
+    let entries = read_execution_events(default_runs_dir()?, execution_id)?;
     Ok(entries.into_iter().map(|e| e.event).collect())
 }
 
@@ -287,6 +359,40 @@ mod tests {
         assert_eq!(entries[1].event.event_type(), "IterationStarted");
     }
 
+    #[test]
+    fn test_rotation_by_size_keeps_events_readable() {
+        let temp = tempdir().unwrap();
+        let mut logger = EventLogger::new(temp.path()).with_config(EventLogConfig {
+            max_segment_bytes: 1,
+            ..Default::default()
+        });
+
+        for iteration in 1..=3 {
+            logger
+                .write_event(&Event::IterationStarted {
+                    execution_id: "rotating".to_string(),
+                    iteration,
+                })
+                .unwrap();
+        }
+
+        let exec_dir = temp.path().join("rotating");
+        let index = SegmentIndex::load(&exec_dir);
+        let files: Vec<_> = index.segments.iter().map(|s| s.file.as_str()).collect();
+        assert_eq!(files, vec!["events.1.jsonl", "events.2.jsonl"]);
+        assert!(exec_dir.join("index.json").exists());
+
+        let entries = read_execution_events(temp.path(), "rotating").unwrap();
+        let iterations: Vec<_> = entries
+            .iter()
+            .map(|e| match e.event {
+                Event::IterationStarted { iteration, .. } => iteration,
+                _ => 0,
+            })
+            .collect();
+        assert_eq!(iterations, vec![1, 2, 3]);
+    }
+
     #[test]
     fn test_read_nonexistent_execution() {
         let temp = tempdir().unwrap();
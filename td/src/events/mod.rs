@@ -55,16 +55,24 @@
 //! # Event Types
 //!
 //! See [`TdEvent`] for the complete list of events:
-//! - Loop lifecycle: `LoopStarted`, `PhaseStarted`, `IterationStarted`, etc.
-//! - LLM interactions: `PromptSent`, `TokenReceived`, `ResponseCompleted`
-//! - Tool execution: `ToolCallStarted`, `ToolCallCompleted`
+//! - Loop lifecycle: `LoopStarted`, `PhaseStarted`, `PhaseCompleted`, `IterationStarted`, `OverrideChanged`, etc.
+//! - LLM interactions: `PromptSent`, `TokenReceived`, `ResponseCompleted`, `RateLimited`
+//! - Tool execution: `ToolCallStarted`, `ToolCallCompleted`, `ResourceLimitExceeded`, `NetworkRequest`
+//! - Coordination: `DeadlockDetected`, `PathConflict`
+//! - Daemon: `ResourcePressure`, `ProviderCircuit`
 //! - Validation: `ValidationStarted`, `ValidationOutput`, `ValidationCompleted`
 //! - Errors: `Error`, `Warning`
 
 mod bus;
+mod dispatch;
 mod logger;
+mod segments;
 mod types;
 
-pub use bus::{DEFAULT_CHANNEL_CAPACITY, EventBus, EventEmitter, create_event_bus};
-pub use logger::{EventLogger, read_execution_events, replay_execution_events, spawn_event_logger};
+pub use bus::{DEFAULT_CHANNEL_CAPACITY, EventBus, EventEmitter, TokenBatching, create_event_bus};
+pub use dispatch::{BufferedSubscriber, OverflowPolicy, SubscriberStats, TryRecvError};
+pub use logger::{EventLogger, default_runs_dir, read_execution_events, replay_execution_events, spawn_event_logger};
+pub use segments::{
+    CompactionStats, SegmentIndex, SegmentInfo, compact_execution, compact_idle_executions, read_execution_events_since,
+};
 pub use types::{Event, EventLogEntry, IterationOutcome};
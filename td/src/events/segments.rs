@@ -0,0 +1,415 @@
+//! Event log segments, index, and compaction
+//!
+//! Each execution's log lives in `runs/{execution-id}/`. The logger appends to
+//! `events.jsonl` and rotates it to `events.{n}.jsonl` when it gets too large
+//! or too old. `index.json` lists the rotated segments in order with their time
+//! range, so readers find an execution's events without scanning the directory
+//! and can skip segments older than what they need.
+//!
+//! Compaction merges every segment of an idle execution into a single
+//! `events.jsonl`, dropping token-level events (`TokenReceived`) and keeping
+//! everything else, which is what reports, summaries, and log views read.
+
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use super::types::EventLogEntry;
+
+/// File the logger appends to
+pub const ACTIVE_SEGMENT: &str = "events.jsonl";
+
+/// Per-execution segment index
+pub const INDEX_FILE: &str = "index.json";
+
+/// One rotated segment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SegmentInfo {
+    /// File name within the execution directory
+    pub file: String,
+    pub first: DateTime<Utc>,
+    pub last: DateTime<Utc>,
+    pub events: usize,
+    pub bytes: u64,
+}
+
+/// Rotated segments of one execution, oldest first
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SegmentIndex {
+    #[serde(default)]
+    pub segments: Vec<SegmentInfo>,
+    /// Token-level events have been dropped
+    #[serde(default)]
+    pub compacted: bool,
+}
+
+impl SegmentIndex {
+    /// Load the index of `exec_dir`, rebuilding it from the segment files if it is missing or unreadable
+    pub fn load(exec_dir: &Path) -> Self {
+        debug!(?exec_dir, "SegmentIndex::load: called");
+        let path = exec_dir.join(INDEX_FILE);
+        if let Ok(content) = fs::read_to_string(&path) {
+            match serde_json::from_str(&content) {
+                Ok(index) => return index,
+                Err(e) => warn!(?path, error = %e, "SegmentIndex::load: invalid index, rebuilding"),
+            }
+        }
+        Self::rebuild(exec_dir)
+    }
+
+    /// Rebuild the index by reading the rotated segment files
+    fn rebuild(exec_dir: &Path) -> Self {
+        debug!(?exec_dir, "SegmentIndex::rebuild: called");
+        let mut numbered: Vec<(u64, String)> = fs::read_dir(exec_dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let number = segment_number(&name)?;
+                Some((number, name))
+            })
+            .collect();
+        numbered.sort();
+
+        let segments = numbered
+            .into_iter()
+            .filter_map(|(_, file)| segment_stats(&exec_dir.join(&file)).map(|stats| stats.into_info(file)))
+            .collect();
+        Self {
+            segments,
+            compacted: false,
+        }
+    }
+
+    /// Write the index atomically
+    pub fn save(&self, exec_dir: &Path) -> eyre::Result<()> {
+        debug!(?exec_dir, segments = self.segments.len(), "SegmentIndex::save: called");
+        let tmp = exec_dir.join(format!("{}.tmp", INDEX_FILE));
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&tmp, exec_dir.join(INDEX_FILE))?;
+        Ok(())
+    }
+
+    /// File name for the next rotated segment
+    pub fn next_segment_file(&self) -> String {
+        let next = self
+            .segments
+            .iter()
+            .filter_map(|s| segment_number(&s.file))
+            .max()
+            .map_or(1, |n| n + 1);
+        format!("events.{}.jsonl", next)
+    }
+}
+
+/// `events.{n}.jsonl` -> n
+fn segment_number(name: &str) -> Option<u64> {
+    name.strip_prefix("events.")?.strip_suffix(".jsonl")?.parse().ok()
+}
+
+/// Size and time range of a segment being written or read
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SegmentStats {
+    pub first: Option<DateTime<Utc>>,
+    pub last: Option<DateTime<Utc>>,
+    pub events: usize,
+    pub bytes: u64,
+}
+
+impl SegmentStats {
+    /// Account for one written line
+    pub fn record(&mut self, timestamp: DateTime<Utc>, bytes: u64) {
+        self.first.get_or_insert(timestamp);
+        self.last = Some(timestamp);
+        self.events += 1;
+        self.bytes += bytes;
+    }
+
+    pub fn into_info(self, file: String) -> SegmentInfo {
+        let now = Utc::now();
+        SegmentInfo {
+            file,
+            first: self.first.unwrap_or(now),
+            last: self.last.unwrap_or(now),
+            events: self.events,
+            bytes: self.bytes,
+        }
+    }
+}
+
+/// Stats of an existing segment file (None if it can't be read)
+pub(crate) fn segment_stats(path: &Path) -> Option<SegmentStats> {
+    debug!(?path, "segment_stats: called");
+    let file = File::open(path).ok()?;
+    let mut stats = SegmentStats::default();
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        let bytes = line.len() as u64 + 1;
+        match serde_json::from_str::<EventLogEntry>(&line) {
+            Ok(entry) => stats.record(entry.timestamp, bytes),
+            Err(_) => stats.bytes += bytes,
+        }
+    }
+    Some(stats)
+}
+
+/// Append the entries of one segment file to `entries`
+fn read_segment(path: &Path, entries: &mut Vec<EventLogEntry>) -> eyre::Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    let content = fs::read_to_string(path)?;
+    for line in content.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<EventLogEntry>(line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => {
+                warn!(line, error = %e, "read_segment: failed to parse line");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Read an execution's events logged at or after `since` (all events if None)
+///
+/// Rotated segments that end before `since` are skipped without being opened.
+pub fn read_execution_events_since(
+    runs_dir: impl AsRef<Path>,
+    execution_id: &str,
+    since: Option<DateTime<Utc>>,
+) -> eyre::Result<Vec<EventLogEntry>> {
+    let exec_dir = runs_dir.as_ref().join(execution_id);
+    debug!(?exec_dir, ?since, "read_execution_events_since: called");
+    if !exec_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let index = SegmentIndex::load(&exec_dir);
+    let mut entries = Vec::new();
+    for segment in &index.segments {
+        if since.is_some_and(|since| segment.last < since) {
+            debug!(file = %segment.file, "read_execution_events_since: skipping old segment");
+            continue;
+        }
+        read_segment(&exec_dir.join(&segment.file), &mut entries)?;
+    }
+    read_segment(&exec_dir.join(ACTIVE_SEGMENT), &mut entries)?;
+
+    if let Some(since) = since {
+        entries.retain(|e| e.timestamp >= since);
+    }
+    debug!(count = entries.len(), "read_execution_events_since: loaded entries");
+    Ok(entries)
+}
+
+/// Result of compacting one or more execution logs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompactionStats {
+    pub executions: usize,
+    pub events_dropped: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+/// Merge an execution's segments into one `events.jsonl` without token-level events
+pub fn compact_execution(runs_dir: impl AsRef<Path>, execution_id: &str) -> eyre::Result<CompactionStats> {
+    let exec_dir = runs_dir.as_ref().join(execution_id);
+    debug!(?exec_dir, "compact_execution: called");
+    let index = SegmentIndex::load(&exec_dir);
+    let mut files: Vec<PathBuf> = index.segments.iter().map(|s| exec_dir.join(&s.file)).collect();
+    files.push(exec_dir.join(ACTIVE_SEGMENT));
+    let bytes_before: u64 = files.iter().filter_map(|f| fs::metadata(f).ok()).map(|m| m.len()).sum();
+
+    let entries = read_execution_events_since(runs_dir.as_ref(), execution_id, None)?;
+    let total = entries.len();
+    let tmp = exec_dir.join(format!("{}.tmp", ACTIVE_SEGMENT));
+    {
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+        for entry in entries.iter().filter(|e| !e.event.is_token_level()) {
+            writeln!(writer, "{}", serde_json::to_string(entry)?)?;
+        }
+        writer.flush()?;
+    }
+    fs::rename(&tmp, exec_dir.join(ACTIVE_SEGMENT))?;
+    for segment in &index.segments {
+        let _ = fs::remove_file(exec_dir.join(&segment.file));
+    }
+    SegmentIndex {
+        segments: Vec::new(),
+        compacted: true,
+    }
+    .save(&exec_dir)?;
+
+    let kept = entries.iter().filter(|e| !e.event.is_token_level()).count();
+    let stats = CompactionStats {
+        executions: 1,
+        events_dropped: total - kept,
+        bytes_before,
+        bytes_after: fs::metadata(exec_dir.join(ACTIVE_SEGMENT)).map_or(0, |m| m.len()),
+    };
+    debug!(?stats, "compact_execution: done");
+    Ok(stats)
+}
+
+/// Most recent modification time of any file in `dir`
+fn last_modified(dir: &Path) -> Option<SystemTime> {
+    fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| entry.metadata().ok()?.modified().ok())
+        .max()
+}
+
+/// Compact every execution log under `runs_dir` untouched for `idle_for`
+///
+/// Already-compacted logs and IDs in `skip` (executions still being written) are left alone.
+pub fn compact_idle_executions(
+    runs_dir: impl AsRef<Path>,
+    idle_for: Duration,
+    skip: &[&str],
+) -> eyre::Result<CompactionStats> {
+    let runs_dir = runs_dir.as_ref();
+    debug!(?runs_dir, ?idle_for, "compact_idle_executions: called");
+    let mut total = CompactionStats::default();
+    if !runs_dir.exists() {
+        return Ok(total);
+    }
+
+    let cutoff = SystemTime::now()
+        .checked_sub(idle_for)
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+    for entry in fs::read_dir(runs_dir)?.flatten() {
+        let exec_dir = entry.path();
+        let execution_id = entry.file_name().to_string_lossy().to_string();
+        if !exec_dir.is_dir() || skip.contains(&execution_id.as_str()) {
+            continue;
+        }
+        if SegmentIndex::load(&exec_dir).compacted {
+            continue;
+        }
+        if last_modified(&exec_dir).is_none_or(|modified| modified > cutoff) {
+            continue;
+        }
+        match compact_execution(runs_dir, &execution_id) {
+            Ok(stats) => {
+                total.executions += stats.executions;
+                total.events_dropped += stats.events_dropped;
+                total.bytes_before += stats.bytes_before;
+                total.bytes_after += stats.bytes_after;
+            }
+            Err(e) => warn!(%execution_id, error = %e, "compact_idle_executions: failed to compact"),
+        }
+    }
+    debug!(?total, "compact_idle_executions: done");
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::Event;
+    use tempfile::tempdir;
+
+    fn entry(event: Event, offset_secs: i64) -> EventLogEntry {
+        EventLogEntry {
+            timestamp: DateTime::<Utc>::from_timestamp(1_700_000_000 + offset_secs, 0).unwrap(),
+            event,
+        }
+    }
+
+    fn token(offset_secs: i64) -> EventLogEntry {
+        entry(
+            Event::TokenReceived {
+                execution_id: "exec".to_string(),
+                iteration: 1,
+                token: "tok".to_string(),
+            },
+            offset_secs,
+        )
+    }
+
+    fn started(offset_secs: i64) -> EventLogEntry {
+        entry(
+            Event::IterationStarted {
+                execution_id: "exec".to_string(),
+                iteration: 1,
+            },
+            offset_secs,
+        )
+    }
+
+    fn write_segment(path: &Path, entries: &[EventLogEntry]) {
+        let lines: Vec<String> = entries.iter().map(|e| serde_json::to_string(e).unwrap()).collect();
+        fs::write(path, lines.join("\n") + "\n").unwrap();
+    }
+
+    #[test]
+    fn test_index_rebuilds_from_segment_files() {
+        let temp = tempdir().unwrap();
+        write_segment(&temp.path().join("events.2.jsonl"), &[started(20)]);
+        write_segment(&temp.path().join("events.1.jsonl"), &[started(0), token(10)]);
+        write_segment(&temp.path().join(ACTIVE_SEGMENT), &[started(30)]);
+
+        let index = SegmentIndex::load(temp.path());
+        let files: Vec<_> = index.segments.iter().map(|s| s.file.as_str()).collect();
+        assert_eq!(files, vec!["events.1.jsonl", "events.2.jsonl"]);
+        assert_eq!(index.segments[0].events, 2);
+        assert_eq!(index.segments[0].last, token(10).timestamp);
+        assert_eq!(index.next_segment_file(), "events.3.jsonl");
+    }
+
+    #[test]
+    fn test_read_since_skips_old_segments() {
+        let temp = tempdir().unwrap();
+        let exec_dir = temp.path().join("exec");
+        fs::create_dir(&exec_dir).unwrap();
+        write_segment(&exec_dir.join("events.1.jsonl"), &[started(0), token(10)]);
+        write_segment(&exec_dir.join(ACTIVE_SEGMENT), &[started(100), token(110)]);
+
+        assert_eq!(read_execution_events_since(temp.path(), "exec", None).unwrap().len(), 4);
+        let recent = read_execution_events_since(temp.path(), "exec", Some(started(105).timestamp)).unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].event.event_type(), "TokenReceived");
+    }
+
+    #[test]
+    fn test_compaction_drops_token_events_and_merges_segments() {
+        let temp = tempdir().unwrap();
+        let exec_dir = temp.path().join("exec");
+        fs::create_dir(&exec_dir).unwrap();
+        write_segment(&exec_dir.join("events.1.jsonl"), &[started(0), token(1), token(2)]);
+        write_segment(&exec_dir.join(ACTIVE_SEGMENT), &[token(3), started(4)]);
+
+        let stats = compact_idle_executions(temp.path(), Duration::ZERO, &["other"]).unwrap();
+        assert_eq!(stats.executions, 1);
+        assert_eq!(stats.events_dropped, 3);
+        assert!(stats.bytes_after < stats.bytes_before);
+
+        assert!(!exec_dir.join("events.1.jsonl").exists());
+        let index = SegmentIndex::load(&exec_dir);
+        assert!(index.compacted && index.segments.is_empty());
+        let entries = read_execution_events_since(temp.path(), "exec", None).unwrap();
+        let kept: Vec<_> = entries.iter().map(|e| (e.event.event_type(), e.timestamp)).collect();
+        assert_eq!(
+            kept,
+            vec![
+                ("IterationStarted", started(0).timestamp),
+                ("IterationStarted", started(4).timestamp)
+            ]
+        );
+
+        // Compacted logs and skipped executions are left alone
+        let stats = compact_idle_executions(temp.path(), Duration::ZERO, &[]).unwrap();
+        assert_eq!(stats.executions, 0);
+    }
+}
@@ -4,6 +4,7 @@
 //! - Loop lifecycle (start, iteration, complete)
 //! - LLM interactions (prompts, streaming tokens, responses)
 //! - Tool execution (start, complete)
+//! - Coordination (deadlocked queries, path lock conflicts)
 //! - Validation (start, output lines, complete)
 
 use chrono::{DateTime, Utc};
@@ -27,6 +28,18 @@ pub enum Event {
         phase_name: String,
         total_phases: usize,
     },
+    /// A phase of a loop's iteration budget has ended
+    PhaseCompleted {
+        execution_id: String,
+        phase_index: usize,
+        phase_name: String,
+        /// Iterations the phase ran
+        iterations_used: u32,
+        /// Iterations it was allotted, including any borrowed from earlier phases
+        budget: u32,
+        /// Unused iterations rolled into the next phase
+        carried_over: u32,
+    },
     /// An iteration within a loop has started
     IterationStarted { execution_id: String, iteration: u32 },
     /// An iteration has completed
@@ -41,6 +54,24 @@ pub enum Event {
         success: bool,
         total_iterations: u32,
     },
+    /// A loop made no material progress for several iterations
+    LoopStuck {
+        execution_id: String,
+        iteration: u32,
+        unchanged_iterations: u32,
+        /// Intervention applied (pause, steer, escalate)
+        action: String,
+    },
+    /// A `td exec set` override was applied at an iteration boundary
+    OverrideChanged {
+        execution_id: String,
+        /// Iteration the new value applies from
+        iteration: u32,
+        /// `model` or `max_iterations`
+        setting: String,
+        from: String,
+        to: String,
+    },
 
     // === LLM Interactions ===
     /// A prompt has been sent to the LLM
@@ -65,6 +96,9 @@ pub enum Event {
         input_tokens: u64,
         output_tokens: u64,
         has_tool_calls: bool,
+        /// "provider/model" that produced the response, when a failover chain is configured
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        served_by: Option<String>,
     },
 
     // === Tool Execution ===
@@ -84,6 +118,70 @@ pub enum Event {
         result_summary: String,
         duration_ms: u64,
     },
+    /// A command spawned by a tool was stopped by a resource limit
+    ResourceLimitExceeded {
+        execution_id: String,
+        iteration: u32,
+        tool_name: String,
+        /// Limit that was hit (cpu, memory, wall-clock)
+        resource: String,
+        /// Configured limit, in the unit of the resource (seconds, MB, milliseconds)
+        limit: u64,
+    },
+
+    /// A tool made (or was refused) an outbound request, checked against the network policy
+    NetworkRequest {
+        execution_id: String,
+        iteration: u32,
+        /// Tool that made it (`command` for spawned commands, through the proxy)
+        tool_name: String,
+        host: String,
+        /// URL, or host:port for tunnelled connections
+        target: String,
+        allowed: bool,
+    },
+
+    /// The LLM API rate-limited the iteration; it is retried after the delay
+    RateLimited {
+        execution_id: String,
+        iteration: u32,
+        retry_after_ms: u64,
+    },
+
+    // === Coordination ===
+    /// A query was rejected because it would close a wait-for cycle between loops
+    DeadlockDetected {
+        /// The querying execution
+        execution_id: String,
+        /// Executions in the cycle, starting and ending with the querier
+        cycle: Vec<String>,
+    },
+    /// A path this execution edits or declared is locked by another execution
+    PathConflict {
+        execution_id: String,
+        /// The path this execution claimed
+        path: String,
+        /// Execution holding the overlapping lock
+        held_by: String,
+    },
+
+    // === Daemon ===
+    /// The daemon exceeded a resource limit and stopped picking up executions
+    ResourcePressure {
+        /// Always [`crate::resources::DAEMON_EVENT_ID`]
+        execution_id: String,
+        /// Limits exceeded, e.g. "rss 2100MB > 2048MB"
+        exceeded: Vec<String>,
+    },
+    /// The provider circuit breaker opened (provider failing) or closed (provider recovered)
+    ProviderCircuit {
+        /// Always [`crate::resources::DAEMON_EVENT_ID`]
+        execution_id: String,
+        /// True when the circuit opened
+        open: bool,
+        /// Executions resumed when the circuit closed (0 when it opened)
+        resumed: usize,
+    },
 
     // === Validation ===
     /// Validation has started
@@ -113,6 +211,9 @@ pub enum Event {
         execution_id: String,
         context: String,
         message: String,
+        /// Error code, e.g. `llm.network` (see [`crate::error`])
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        code: Option<String>,
     },
     /// A warning occurred
     Warning {
@@ -128,14 +229,24 @@ impl Event {
         match self {
             Event::LoopStarted { execution_id, .. }
             | Event::PhaseStarted { execution_id, .. }
+            | Event::PhaseCompleted { execution_id, .. }
             | Event::IterationStarted { execution_id, .. }
             | Event::IterationCompleted { execution_id, .. }
             | Event::LoopCompleted { execution_id, .. }
+            | Event::LoopStuck { execution_id, .. }
+            | Event::OverrideChanged { execution_id, .. }
             | Event::PromptSent { execution_id, .. }
             | Event::TokenReceived { execution_id, .. }
             | Event::ResponseCompleted { execution_id, .. }
             | Event::ToolCallStarted { execution_id, .. }
             | Event::ToolCallCompleted { execution_id, .. }
+            | Event::ResourceLimitExceeded { execution_id, .. }
+            | Event::NetworkRequest { execution_id, .. }
+            | Event::RateLimited { execution_id, .. }
+            | Event::DeadlockDetected { execution_id, .. }
+            | Event::PathConflict { execution_id, .. }
+            | Event::ResourcePressure { execution_id, .. }
+            | Event::ProviderCircuit { execution_id, .. }
             | Event::ValidationStarted { execution_id, .. }
             | Event::ValidationOutput { execution_id, .. }
             | Event::ValidationCompleted { execution_id, .. }
@@ -144,19 +255,34 @@ impl Event {
         }
     }
 
+    /// Whether this is a token-level event (dropped when old logs are compacted)
+    pub fn is_token_level(&self) -> bool {
+        matches!(self, Event::TokenReceived { .. })
+    }
+
     /// Get the event type name
     pub fn event_type(&self) -> &'static str {
         match self {
             Event::LoopStarted { .. } => "LoopStarted",
             Event::PhaseStarted { .. } => "PhaseStarted",
+            Event::PhaseCompleted { .. } => "PhaseCompleted",
             Event::IterationStarted { .. } => "IterationStarted",
             Event::IterationCompleted { .. } => "IterationCompleted",
             Event::LoopCompleted { .. } => "LoopCompleted",
+            Event::LoopStuck { .. } => "LoopStuck",
+            Event::OverrideChanged { .. } => "OverrideChanged",
             Event::PromptSent { .. } => "PromptSent",
             Event::TokenReceived { .. } => "TokenReceived",
             Event::ResponseCompleted { .. } => "ResponseCompleted",
             Event::ToolCallStarted { .. } => "ToolCallStarted",
             Event::ToolCallCompleted { .. } => "ToolCallCompleted",
+            Event::ResourceLimitExceeded { .. } => "ResourceLimitExceeded",
+            Event::NetworkRequest { .. } => "NetworkRequest",
+            Event::RateLimited { .. } => "RateLimited",
+            Event::DeadlockDetected { .. } => "DeadlockDetected",
+            Event::PathConflict { .. } => "PathConflict",
+            Event::ResourcePressure { .. } => "ResourcePressure",
+            Event::ProviderCircuit { .. } => "ProviderCircuit",
             Event::ValidationStarted { .. } => "ValidationStarted",
             Event::ValidationOutput { .. } => "ValidationOutput",
             Event::ValidationCompleted { .. } => "ValidationCompleted",
@@ -179,7 +305,14 @@ pub enum IterationOutcome {
     /// Tool error occurred
     ToolError { tool: String, error: String },
     /// LLM error occurred
-    LlmError { error: String },
+    LlmError {
+        error: String,
+        /// Error code, e.g. `llm.auth` (see [`crate::error`])
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        code: Option<String>,
+    },
+    /// The watchdog cancelled the iteration
+    TimedOut { cause: String },
 }
 
 /// A timestamped event log entry for file persistence
@@ -274,6 +407,14 @@ mod tests {
                 phase_name: "phase".to_string(),
                 total_phases: 1,
             },
+            Event::PhaseCompleted {
+                execution_id: exec_id.to_string(),
+                phase_index: 0,
+                phase_name: "phase".to_string(),
+                iterations_used: 1,
+                budget: 2,
+                carried_over: 1,
+            },
             Event::IterationStarted {
                 execution_id: exec_id.to_string(),
                 iteration: 1,
@@ -306,6 +447,7 @@ mod tests {
                 input_tokens: 100,
                 output_tokens: 50,
                 has_tool_calls: false,
+                served_by: None,
             },
             Event::ToolCallStarted {
                 execution_id: exec_id.to_string(),
@@ -342,6 +484,7 @@ mod tests {
                 execution_id: exec_id.to_string(),
                 context: "ctx".to_string(),
                 message: "err".to_string(),
+                code: None,
             },
             Event::Warning {
                 execution_id: exec_id.to_string(),
@@ -374,6 +517,14 @@ mod tests {
                 phase_name: "phase".to_string(),
                 total_phases: 3,
             },
+            Event::PhaseCompleted {
+                execution_id: "e1".to_string(),
+                phase_index: 0,
+                phase_name: "explore".to_string(),
+                iterations_used: 2,
+                budget: 5,
+                carried_over: 3,
+            },
             Event::IterationStarted {
                 execution_id: "e1".to_string(),
                 iteration: 1,
@@ -406,6 +557,7 @@ mod tests {
                 input_tokens: 500,
                 output_tokens: 200,
                 has_tool_calls: true,
+                served_by: None,
             },
             Event::ToolCallStarted {
                 execution_id: "e1".to_string(),
@@ -442,6 +594,7 @@ mod tests {
                 execution_id: "e1".to_string(),
                 context: "llm".to_string(),
                 message: "Rate limited".to_string(),
+                code: Some("llm.rate-limited".to_string()),
             },
             Event::Warning {
                 execution_id: "e1".to_string(),
@@ -472,6 +625,10 @@ mod tests {
             },
             IterationOutcome::LlmError {
                 error: "Context length exceeded".to_string(),
+                code: None,
+            },
+            IterationOutcome::TimedOut {
+                cause: "tool call 'bash' timed out after 600s".to_string(),
             },
         ];
 
@@ -496,6 +653,7 @@ mod tests {
                     IterationOutcome::MaxTurnsReached => "MaxTurnsReached",
                     IterationOutcome::ToolError { .. } => "ToolError",
                     IterationOutcome::LlmError { .. } => "LlmError",
+                    IterationOutcome::TimedOut { .. } => "TimedOut",
                 };
                 let actual_type = match parsed_outcome {
                     IterationOutcome::ValidationPassed => "ValidationPassed",
@@ -503,6 +661,7 @@ mod tests {
                     IterationOutcome::MaxTurnsReached => "MaxTurnsReached",
                     IterationOutcome::ToolError { .. } => "ToolError",
                     IterationOutcome::LlmError { .. } => "LlmError",
+                    IterationOutcome::TimedOut { .. } => "TimedOut",
                 };
                 assert_eq!(expected_type, actual_type);
             } else {
@@ -0,0 +1,451 @@
+//! gRPC control API (`grpc` feature)
+//!
+//! Platforms embedding TaskDaemon drive it through the `taskdaemon.v1.TaskDaemon`
+//! service described in `proto/taskdaemon.proto`: execution CRUD, the live
+//! event stream and the loaded loop types. The server runs next to the IPC
+//! socket when `grpc.listen` is set, reading and writing through the same
+//! StateManager, so executions it creates are picked up like any other.
+//!
+//! With `grpc.token-env` or `grpc.token-file` set, every call must carry
+//! `authorization: Bearer <token>`. There is no TLS; put the server behind a
+//! proxy or bind it to localhost.
+
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+
+use eyre::{Context, Result};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_stream::Stream;
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tonic::{Request, Response, Status};
+use tracing::{debug, info, warn};
+
+use crate::batch::task_execution;
+use crate::config::DedupConfig;
+use crate::domain::{LoopExecution, LoopExecutionStatus};
+use crate::events::{DEFAULT_CHANNEL_CAPACITY, Event as LoopEvent, EventBus, OverflowPolicy};
+use crate::r#loop::LoopLoader;
+use crate::state::{StateError, StateManager};
+use crate::webhook::constant_time_eq;
+
+/// Types generated from `proto/taskdaemon.proto`
+pub mod proto {
+    tonic::include_proto!("taskdaemon.v1");
+}
+
+use proto::task_daemon_server::{TaskDaemon, TaskDaemonServer};
+
+/// Tag added to every execution created over gRPC
+pub const GRPC_TAG: &str = "grpc";
+
+/// Stream of events answering `StreamEvents`
+type EventStream = Pin<Box<dyn Stream<Item = Result<proto::Event, Status>> + Send>>;
+
+/// The `TaskDaemon` service
+pub struct GrpcService {
+    state: StateManager,
+    loader: Arc<RwLock<LoopLoader>>,
+    events: Arc<EventBus>,
+    dedup: DedupConfig,
+}
+
+impl GrpcService {
+    pub fn new(state: StateManager, loader: Arc<RwLock<LoopLoader>>, events: Arc<EventBus>) -> Self {
+        debug!("GrpcService::new: called");
+        Self {
+            state,
+            loader,
+            events,
+            dedup: DedupConfig::default(),
+        }
+    }
+
+    /// Check created executions for duplicates of unfinished ones
+    pub fn with_dedup(mut self, config: DedupConfig) -> Self {
+        self.dedup = config;
+        self
+    }
+
+    /// Serve on `listener` until the task is aborted, requiring `token` if given
+    pub async fn serve(self, listener: TcpListener, token: Option<String>) -> Result<()> {
+        if let Ok(addr) = listener.local_addr() {
+            info!(%addr, auth = token.is_some(), "gRPC server listening");
+        }
+        let service = TaskDaemonServer::with_interceptor(self, move |request: Request<()>| {
+            check_token(token.as_deref(), &request)?;
+            Ok(request)
+        });
+        tonic::transport::Server::builder()
+            .add_service(service)
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .context("gRPC server failed")
+    }
+
+    /// Pending execution for a create request, checked against its loop type's variables
+    fn build_execution(&self, request: &proto::CreateExecutionRequest) -> Result<LoopExecution, Status> {
+        let context = parse_context(&request.context_json)?;
+        let mut tags = vec![GRPC_TAG.to_string()];
+        tags.extend(request.tags.iter().cloned());
+        let loader = self.loader.read().expect("loop loader poisoned");
+        task_execution(&loader, &request.loop_type, &request.task, context, &tags)
+            .map_err(|problems| Status::invalid_argument(problems.join("; ")))
+    }
+}
+
+#[tonic::async_trait]
+impl TaskDaemon for GrpcService {
+    type StreamEventsStream = EventStream;
+
+    async fn list_executions(
+        &self,
+        request: Request<proto::ListExecutionsRequest>,
+    ) -> Result<Response<proto::ListExecutionsResponse>, Status> {
+        let request = request.into_inner();
+        debug!(?request, "GrpcService::list_executions: called");
+        let executions = self
+            .state
+            .list_executions(request.status, request.loop_type)
+            .await
+            .map_err(to_status)?;
+        Ok(Response::new(proto::ListExecutionsResponse {
+            executions: executions.iter().map(to_proto).collect(),
+        }))
+    }
+
+    async fn get_execution(
+        &self,
+        request: Request<proto::GetExecutionRequest>,
+    ) -> Result<Response<proto::Execution>, Status> {
+        let id = request.into_inner().id;
+        debug!(%id, "GrpcService::get_execution: called");
+        match self.state.get_execution(&id).await.map_err(to_status)? {
+            Some(exec) => Ok(Response::new(to_proto(&exec))),
+            None => Err(Status::not_found(format!("No execution {}", id))),
+        }
+    }
+
+    async fn create_execution(
+        &self,
+        request: Request<proto::CreateExecutionRequest>,
+    ) -> Result<Response<proto::Execution>, Status> {
+        let request = request.into_inner();
+        debug!(loop_type = %request.loop_type, "GrpcService::create_execution: called");
+        let exec = self.build_execution(&request)?;
+        let proto = to_proto(&exec);
+        let (id, duplicate) = self
+            .state
+            .submit_execution_checked(exec, &self.dedup)
+            .await
+            .map_err(to_status)?;
+        let duplicate_of = duplicate.map(|d| d.existing_id);
+        info!(%id, loop_type = %request.loop_type, ?duplicate_of, "gRPC created execution");
+        Ok(Response::new(proto))
+    }
+
+    async fn update_execution(
+        &self,
+        request: Request<proto::UpdateExecutionRequest>,
+    ) -> Result<Response<proto::Execution>, Status> {
+        let request = request.into_inner();
+        debug!(id = %request.id, expected = request.expected_revision, "GrpcService::update_execution: called");
+        let context = parse_context(&request.context_json)?;
+        let apply = |exec: &mut LoopExecution| {
+            if let Some(title) = &request.title {
+                exec.set_title(title.clone());
+            }
+            exec.add_tags(&request.add_tags);
+            if !context.is_empty() {
+                if !exec.context.is_object() {
+                    exec.context = serde_json::Value::Object(Default::default());
+                }
+                if let Some(object) = exec.context.as_object_mut() {
+                    object.extend(context.clone());
+                }
+            }
+        };
+
+        let exec = if request.expected_revision == 0 {
+            self.state.modify_execution(&request.id, apply).await.map_err(to_status)?
+        } else {
+            let mut exec = self
+                .state
+                .get_execution(&request.id)
+                .await
+                .map_err(to_status)?
+                .ok_or_else(|| Status::not_found(format!("No execution {}", request.id)))?;
+            if exec.revision != request.expected_revision {
+                return Err(to_status(StateError::Conflict {
+                    id: request.id.clone(),
+                    expected: request.expected_revision,
+                    actual: exec.revision,
+                }));
+            }
+            apply(&mut exec);
+            exec.revision = self.state.update_execution(exec.clone()).await.map_err(to_status)?;
+            exec
+        };
+        Ok(Response::new(to_proto(&exec)))
+    }
+
+    async fn delete_execution(
+        &self,
+        request: Request<proto::DeleteExecutionRequest>,
+    ) -> Result<Response<proto::DeleteExecutionResponse>, Status> {
+        let id = request.into_inner().id;
+        debug!(%id, "GrpcService::delete_execution: called");
+        let exec = self
+            .state
+            .get_execution(&id)
+            .await
+            .map_err(to_status)?
+            .ok_or_else(|| Status::not_found(format!("No execution {}", id)))?;
+        if exec.status == LoopExecutionStatus::Running {
+            return Err(Status::failed_precondition(format!(
+                "Execution {} is running; cancel it first",
+                id
+            )));
+        }
+        self.state.delete_execution(&id).await.map_err(to_status)?;
+        info!(%id, "gRPC deleted execution");
+        Ok(Response::new(proto::DeleteExecutionResponse {}))
+    }
+
+    async fn stream_events(
+        &self,
+        request: Request<proto::StreamEventsRequest>,
+    ) -> Result<Response<Self::StreamEventsStream>, Status> {
+        let execution_id = request.into_inner().execution_id;
+        debug!(?execution_id, "GrpcService::stream_events: called");
+        let mut subscriber = self
+            .events
+            .subscribe_buffered(DEFAULT_CHANNEL_CAPACITY, OverflowPolicy::DropTokenEventsFirst);
+        let (tx, rx) = mpsc::channel(64);
+        tokio::spawn(async move {
+            while let Some(event) = subscriber.recv().await {
+                if execution_id.as_deref().is_some_and(|id| id != event.execution_id()) {
+                    continue;
+                }
+                if tx.send(event_to_proto(&event)).await.is_err() {
+                    debug!("GrpcService::stream_events: client went away");
+                    return;
+                }
+            }
+            debug!("GrpcService::stream_events: event bus closed");
+        });
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
+
+    async fn list_loop_types(
+        &self,
+        _request: Request<proto::ListLoopTypesRequest>,
+    ) -> Result<Response<proto::ListLoopTypesResponse>, Status> {
+        debug!("GrpcService::list_loop_types: called");
+        let loader = self.loader.read().expect("loop loader poisoned");
+        let mut loop_types: Vec<proto::LoopType> = loader
+            .iter()
+            .map(|(name, loop_type)| proto::LoopType {
+                name: name.to_string(),
+                description: loop_type.description.clone(),
+                parent: loop_type.parent.clone(),
+                inputs: loop_type.inputs.clone(),
+                outputs: loop_type.outputs.clone(),
+                max_iterations: loop_type.max_iterations,
+            })
+            .collect();
+        loop_types.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(Response::new(proto::ListLoopTypesResponse { loop_types }))
+    }
+}
+
+/// Reject `request` unless it carries `authorization: Bearer <token>` (no token: allow all)
+fn check_token<T>(token: Option<&str>, request: &Request<T>) -> Result<(), Status> {
+    let Some(token) = token else {
+        return Ok(());
+    };
+    let presented = request
+        .metadata()
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or("");
+    if constant_time_eq(presented, token) {
+        Ok(())
+    } else {
+        warn!("gRPC call rejected: missing or invalid token");
+        Err(Status::unauthenticated("Missing or invalid bearer token"))
+    }
+}
+
+/// Parse a JSON object field; an empty string is an empty object
+fn parse_context(json: &str) -> Result<serde_json::Map<String, serde_json::Value>, Status> {
+    if json.trim().is_empty() {
+        return Ok(Default::default());
+    }
+    serde_json::from_str(json).map_err(|e| Status::invalid_argument(format!("context_json is not a JSON object: {}", e)))
+}
+
+fn to_status(error: StateError) -> Status {
+    match error {
+        StateError::NotFound(id) => Status::not_found(format!("No record {}", id)),
+        e @ StateError::Conflict { .. } => Status::aborted(e.to_string()),
+        e @ StateError::Duplicate(_) => Status::already_exists(e.to_string()),
+        e => Status::internal(e.to_string()),
+    }
+}
+
+/// Wire form of an execution
+pub fn to_proto(exec: &LoopExecution) -> proto::Execution {
+    proto::Execution {
+        id: exec.id.clone(),
+        loop_type: exec.loop_type.clone(),
+        title: exec.title.clone(),
+        status: exec.status.to_string(),
+        parent: exec.parent.clone(),
+        deps: exec.deps.clone(),
+        iteration: exec.iteration,
+        progress: exec.progress.clone(),
+        context_json: exec.context.to_string(),
+        last_error: exec.last_error.clone(),
+        tags: exec.tags.clone(),
+        revision: exec.revision,
+        created_at: exec.created_at,
+        updated_at: exec.updated_at,
+    }
+}
+
+fn event_to_proto(event: &LoopEvent) -> Result<proto::Event, Status> {
+    let json = serde_json::to_string(event).map_err(|e| Status::internal(format!("Failed to encode event: {}", e)))?;
+    Ok(proto::Event {
+        execution_id: event.execution_id().to_string(),
+        kind: event.event_type().to_string(),
+        json,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LoopsConfig;
+    use tempfile::tempdir;
+
+    fn service(temp: &std::path::Path) -> GrpcService {
+        let state = StateManager::spawn(temp).unwrap();
+        let loader = LoopLoader::new(&LoopsConfig {
+            paths: vec!["builtin".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+        GrpcService::new(
+            state,
+            Arc::new(RwLock::new(loader)),
+            Arc::new(EventBus::with_default_capacity()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_execution_crud() {
+        let temp = tempdir().unwrap();
+        let service = service(temp.path());
+
+        let created = service
+            .create_execution(Request::new(proto::CreateExecutionRequest {
+                loop_type: "ralph".to_string(),
+                task: "Fix the login redirect".to_string(),
+                context_json: r#"{"issue": "412"}"#.to_string(),
+                tags: vec!["Platform".to_string()],
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(created.status, "pending");
+        assert!(created.tags.contains(&"grpc".to_string()));
+        assert!(created.tags.contains(&"platform".to_string()));
+
+        let stale = created.revision + 5;
+        let updated = service
+            .update_execution(Request::new(proto::UpdateExecutionRequest {
+                id: created.id.clone(),
+                expected_revision: 0,
+                title: Some("Login redirect".to_string()),
+                add_tags: vec![],
+                context_json: r#"{"priority": "high"}"#.to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(updated.title.as_deref(), Some("Login redirect"));
+        let context: serde_json::Value = serde_json::from_str(&updated.context_json).unwrap();
+        assert_eq!(context["issue"], "412");
+        assert_eq!(context["priority"], "high");
+
+        let conflict = service
+            .update_execution(Request::new(proto::UpdateExecutionRequest {
+                id: created.id.clone(),
+                expected_revision: stale,
+                title: Some("Lost".to_string()),
+                add_tags: vec![],
+                context_json: String::new(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(conflict.code(), tonic::Code::Aborted);
+
+        let listed = service
+            .list_executions(Request::new(proto::ListExecutionsRequest {
+                status: Some("pending".to_string()),
+                loop_type: None,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(listed.executions.len(), 1);
+
+        service
+            .delete_execution(Request::new(proto::DeleteExecutionRequest { id: created.id.clone() }))
+            .await
+            .unwrap();
+        let missing = service
+            .get_execution(Request::new(proto::GetExecutionRequest { id: created.id }))
+            .await
+            .unwrap_err();
+        assert_eq!(missing.code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_create_rejects_unknown_loop_type() {
+        let temp = tempdir().unwrap();
+        let service = service(temp.path());
+        let status = service
+            .create_execution(Request::new(proto::CreateExecutionRequest {
+                loop_type: "nope".to_string(),
+                task: "Anything".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+        let types = service
+            .list_loop_types(Request::new(proto::ListLoopTypesRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(types.loop_types.iter().any(|t| t.name == "ralph"));
+    }
+
+    #[test]
+    fn test_check_token() {
+        let mut request = Request::new(());
+        assert!(check_token(None, &request).is_ok());
+        assert_eq!(
+            check_token(Some("s3cret"), &request).unwrap_err().code(),
+            tonic::Code::Unauthenticated
+        );
+        request
+            .metadata_mut()
+            .insert("authorization", "Bearer s3cret".parse().unwrap());
+        assert!(check_token(Some("s3cret"), &request).is_ok());
+    }
+}
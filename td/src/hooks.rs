@@ -0,0 +1,363 @@
+//! Lifecycle hooks
+//!
+//! Shell commands run at points in an execution's life, configured globally
+//! (`hooks:` in the config) and per loop type (`hooks:` in its YAML):
+//!
+//! ```yaml
+//! hooks:
+//!   pre-merge:
+//!     - command: ./scripts/check-license-headers.sh
+//!       timeout-secs: 60
+//!       on-failure: block
+//!   on-fail:
+//!     - command: curl -fsS -d "$TD_EXEC_ID failed: $TD_ERROR" https://hooks.example.com/td
+//! ```
+//!
+//! A hook runs with `sh -c` in the execution's worktree. The execution's
+//! metadata is in `TD_*` environment variables and, as JSON, on stdin. A hook
+//! fails when it exits non-zero, times out or cannot be started; with
+//! `on-failure: warn` (the default) that is logged, with `block` the execution
+//! is blocked for human review (`td exec resume` runs the hook again). Hooks
+//! of `on-complete` and `on-fail` run after the outcome, so they only warn.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tracing::{debug, info};
+
+/// Point in an execution's life where hooks run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HookEvent {
+    /// Before each iteration's LLM call
+    PreIteration,
+    /// After each iteration's validation (`TD_EXIT_CODE` holds its exit code)
+    PostIteration,
+    /// After a code loop passed its merge gates, before merging to main
+    PreMerge,
+    /// The execution completed
+    OnComplete,
+    /// The execution failed (`TD_ERROR` holds the reason)
+    OnFail,
+}
+
+impl HookEvent {
+    /// Whether a failing `block` hook can still hold the execution
+    pub fn can_block(self) -> bool {
+        matches!(self, Self::PreIteration | Self::PostIteration | Self::PreMerge)
+    }
+}
+
+impl std::fmt::Display for HookEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PreIteration => write!(f, "pre-iteration"),
+            Self::PostIteration => write!(f, "post-iteration"),
+            Self::PreMerge => write!(f, "pre-merge"),
+            Self::OnComplete => write!(f, "on-complete"),
+            Self::OnFail => write!(f, "on-fail"),
+        }
+    }
+}
+
+/// What a failing hook does to the execution
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookPolicy {
+    /// Log a warning and carry on
+    #[default]
+    Warn,
+    /// Block the execution for human review
+    Block,
+}
+
+fn default_hook_timeout_secs() -> u64 {
+    30
+}
+
+/// One hook command
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HookSpec {
+    /// Shell command (run with `sh -c` in the worktree)
+    pub command: String,
+
+    /// Seconds before the hook is killed and counted as failed
+    #[serde(rename = "timeout-secs", default = "default_hook_timeout_secs")]
+    pub timeout_secs: u64,
+
+    /// What a failure does: `warn` or `block`
+    #[serde(rename = "on-failure", default)]
+    pub on_failure: HookPolicy,
+}
+
+/// Hooks by event, each run in order
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Hooks(BTreeMap<HookEvent, Vec<HookSpec>>);
+
+impl Hooks {
+    /// Whether no hooks are configured
+    pub fn is_empty(&self) -> bool {
+        self.0.values().all(Vec::is_empty)
+    }
+
+    /// Hooks for `event`
+    pub fn get(&self, event: HookEvent) -> &[HookSpec] {
+        self.0.get(&event).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Add a hook for `event`
+    pub fn with(mut self, event: HookEvent, spec: HookSpec) -> Self {
+        self.0.entry(event).or_default().push(spec);
+        self
+    }
+
+    /// Take `parent`'s hooks for the events this set doesn't configure (`extends`)
+    pub fn inherit(&mut self, parent: &Hooks) {
+        for (event, specs) in &parent.0 {
+            self.0.entry(*event).or_insert_with(|| specs.clone());
+        }
+    }
+
+    /// These hooks, then `other`'s, for every event (global hooks before a loop type's)
+    pub fn followed_by(&self, other: &Hooks) -> Hooks {
+        let mut combined = self.clone();
+        for (event, specs) in &other.0 {
+            combined.0.entry(*event).or_default().extend(specs.iter().cloned());
+        }
+        combined
+    }
+}
+
+/// What a hook is told about the execution
+#[derive(Debug, Clone, Serialize)]
+pub struct HookContext {
+    pub event: HookEvent,
+    pub execution_id: String,
+    pub loop_type: String,
+    /// Current iteration (0 before the first)
+    pub iteration: u32,
+    pub worktree: PathBuf,
+    /// Validation exit code (post-iteration)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// Failure reason (on-fail)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The execution's template context (task, title, ...)
+    pub context: serde_json::Value,
+}
+
+impl HookContext {
+    /// `TD_*` environment variables for the hook
+    pub fn env(&self) -> Vec<(String, String)> {
+        let mut env = vec![
+            ("TD_HOOK_EVENT".to_string(), self.event.to_string()),
+            ("TD_EXEC_ID".to_string(), self.execution_id.clone()),
+            ("TD_LOOP_TYPE".to_string(), self.loop_type.clone()),
+            ("TD_ITERATION".to_string(), self.iteration.to_string()),
+            ("TD_WORKTREE".to_string(), self.worktree.display().to_string()),
+        ];
+        if let Some(task) = self.context.get("task").and_then(|v| v.as_str()) {
+            env.push(("TD_TASK".to_string(), task.to_string()));
+        }
+        if let Some(code) = self.exit_code {
+            env.push(("TD_EXIT_CODE".to_string(), code.to_string()));
+        }
+        if let Some(error) = &self.error {
+            env.push(("TD_ERROR".to_string(), error.clone()));
+        }
+        env
+    }
+}
+
+/// A hook that failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookFailure {
+    pub event: HookEvent,
+    pub command: String,
+    /// Why: exit code and last line of stderr, timeout, or spawn error
+    pub message: String,
+    pub policy: HookPolicy,
+}
+
+impl HookFailure {
+    /// Whether this failure holds the execution
+    pub fn blocks(&self) -> bool {
+        self.policy == HookPolicy::Block && self.event.can_block()
+    }
+}
+
+impl std::fmt::Display for HookFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} hook `{}` failed: {}", self.event, self.command, self.message)
+    }
+}
+
+/// Run one hook; Err(reason) if it failed
+async fn run_hook(spec: &HookSpec, ctx: &HookContext, env: &[(String, String)]) -> Result<(), String> {
+    debug!(event = %ctx.event, command = %spec.command, "run_hook: called");
+    let mut child = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(&spec.command)
+        .current_dir(&ctx.worktree)
+        .envs(env.iter().cloned())
+        .envs(ctx.env())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("cannot start: {}", e))?;
+
+    // A hook may not read its stdin; a closed pipe is not a failure
+    if let Some(mut stdin) = child.stdin.take() {
+        let json = serde_json::to_vec(ctx).unwrap_or_default();
+        let _ = stdin.write_all(&json).await;
+    }
+
+    let timeout = Duration::from_secs(spec.timeout_secs);
+    let output = match tokio::time::timeout(timeout, child.wait_with_output()).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => return Err(e.to_string()),
+        Err(_) => return Err(format!("timed out after {}s", spec.timeout_secs)),
+    };
+    if output.status.success() {
+        debug!(event = %ctx.event, command = %spec.command, "run_hook: succeeded");
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let exit = match output.status.code() {
+        Some(code) => format!("exit {}", code),
+        None => "killed by a signal".to_string(),
+    };
+    match stderr.lines().rev().find(|l| !l.trim().is_empty()) {
+        Some(line) => Err(format!("{}: {}", exit, line.trim())),
+        None => Err(exit),
+    }
+}
+
+/// Run `hooks` in order, stopping at the first failure that blocks
+///
+/// Returns the failures; the last one blocks if any does.
+pub async fn run_hooks(hooks: &[HookSpec], ctx: &HookContext, env: &[(String, String)]) -> Vec<HookFailure> {
+    debug!(event = %ctx.event, count = hooks.len(), exec_id = %ctx.execution_id, "run_hooks: called");
+    let mut failures = Vec::new();
+    for spec in hooks {
+        if let Err(message) = run_hook(spec, ctx, env).await {
+            let failure = HookFailure {
+                event: ctx.event,
+                command: spec.command.clone(),
+                message,
+                policy: spec.on_failure,
+            };
+            info!(exec_id = %ctx.execution_id, %failure, "Hook failed");
+            let blocks = failure.blocks();
+            failures.push(failure);
+            if blocks {
+                break;
+            }
+        }
+    }
+    failures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn context(event: HookEvent, worktree: PathBuf) -> HookContext {
+        HookContext {
+            event,
+            execution_id: "exec-1".to_string(),
+            loop_type: "phase".to_string(),
+            iteration: 3,
+            worktree,
+            exit_code: Some(1),
+            error: None,
+            context: serde_json::json!({"task": "Fix the parser"}),
+        }
+    }
+
+    fn hook(command: &str, on_failure: HookPolicy) -> HookSpec {
+        HookSpec {
+            command: command.to_string(),
+            timeout_secs: 5,
+            on_failure,
+        }
+    }
+
+    #[test]
+    fn test_hooks_config() {
+        let global: Hooks =
+            serde_yaml::from_str("on-fail:\n  - command: notify-team\npre-merge:\n  - command: lint\n    on-failure: block\n")
+                .unwrap();
+        assert_eq!(global.get(HookEvent::PreMerge)[0].on_failure, HookPolicy::Block);
+        assert_eq!(global.get(HookEvent::OnFail)[0].timeout_secs, 30);
+        assert!(global.get(HookEvent::PreIteration).is_empty());
+
+        let mut child = Hooks::default().with(HookEvent::PreMerge, hook("audit", HookPolicy::Warn));
+        let parent = Hooks::default()
+            .with(HookEvent::PreMerge, hook("lint", HookPolicy::Block))
+            .with(HookEvent::PostIteration, hook("fmt", HookPolicy::Warn));
+        child.inherit(&parent);
+        let commands = |hooks: &Hooks, event| -> Vec<String> {
+            hooks.get(event).iter().map(|h| h.command.clone()).collect()
+        };
+        assert_eq!(commands(&child, HookEvent::PreMerge), ["audit"]);
+        assert_eq!(commands(&child, HookEvent::PostIteration), ["fmt"]);
+
+        let combined = global.followed_by(&child);
+        assert_eq!(commands(&combined, HookEvent::PreMerge), ["lint", "audit"]);
+    }
+
+    #[tokio::test]
+    async fn test_run_hooks() {
+        let temp = tempdir().unwrap();
+        let ctx = context(HookEvent::PostIteration, temp.path().to_path_buf());
+
+        // Metadata arrives in the environment and as JSON on stdin
+        let record = hook(
+            "echo \"$TD_HOOK_EVENT $TD_EXEC_ID $TD_ITERATION $TD_EXIT_CODE $TD_TASK\" > env.txt && cat > stdin.json",
+            HookPolicy::Warn,
+        );
+        assert!(run_hooks(&[record], &ctx, &[]).await.is_empty());
+        let env = std::fs::read_to_string(temp.path().join("env.txt")).unwrap();
+        assert_eq!(env.trim(), "post-iteration exec-1 3 1 Fix the parser");
+        let stdin: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(temp.path().join("stdin.json")).unwrap()).unwrap();
+        assert_eq!(stdin["event"], "post-iteration");
+        assert_eq!(stdin["context"]["task"], "Fix the parser");
+
+        // A warning doesn't stop later hooks; a block does
+        let hooks = [
+            hook("echo 'lint failed' >&2; exit 2", HookPolicy::Warn),
+            hook("exit 1", HookPolicy::Block),
+            hook("touch never", HookPolicy::Warn),
+        ];
+        let failures = run_hooks(&hooks, &ctx, &[]).await;
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0].message, "exit 2: lint failed");
+        assert!(!failures[0].blocks());
+        assert!(failures[1].blocks());
+        assert!(!temp.path().join("never").exists());
+
+        let slow = HookSpec {
+            timeout_secs: 1,
+            ..hook("sleep 5", HookPolicy::Warn)
+        };
+        let failures = run_hooks(&[slow], &ctx, &[]).await;
+        assert_eq!(failures[0].message, "timed out after 1s");
+
+        // After the outcome, block only warns
+        let done = context(HookEvent::OnComplete, temp.path().to_path_buf());
+        let failures = run_hooks(&[hook("exit 1", HookPolicy::Block)], &done, &[]).await;
+        assert!(!failures[0].blocks());
+    }
+}
@@ -0,0 +1,478 @@
+//! Project setup (`td init`)
+//!
+//! Creates the project config `.taskdaemon.yml`, example loop types in
+//! `.taskdaemon/loops/`, editable copies of the prompt templates in
+//! `.taskdaemon/prompts/` and `.gitignore` entries for the files taskdaemon
+//! writes into the project. The validation command is pre-filled from the
+//! detected project language.
+
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use eyre::{Context, Result};
+use tracing::debug;
+
+use crate::config::ValidationConfig;
+use crate::prompts::embedded;
+
+/// Model written to the starter config when none is configured yet
+pub const DEFAULT_MODEL: &str = "anthropic/claude-sonnet-4-20250514";
+
+/// Lines added to `.gitignore` (local drafts and the fallback taskstore)
+const GITIGNORE_ENTRIES: &[&str] = &[".taskdaemon/plans/", ".taskstore/"];
+
+/// Language of the project, used to pick the validation command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectLanguage {
+    Rust,
+    Node,
+    Python,
+    Other,
+}
+
+impl ProjectLanguage {
+    /// Detect the language from the marker files in `dir`
+    pub fn detect(dir: &Path) -> Self {
+        debug!(?dir, "ProjectLanguage::detect: called");
+        let has = |name: &str| dir.join(name).exists();
+        if has("Cargo.toml") {
+            Self::Rust
+        } else if has("package.json") {
+            Self::Node
+        } else if ["pyproject.toml", "setup.py", "setup.cfg", "requirements.txt"]
+            .iter()
+            .any(|name| has(name))
+        {
+            Self::Python
+        } else {
+            Self::Other
+        }
+    }
+
+    /// Validation command that runs the project's tests
+    pub fn validation_command(&self) -> String {
+        match self {
+            Self::Rust => "cargo test".to_string(),
+            Self::Node => "npm test".to_string(),
+            Self::Python => "pytest".to_string(),
+            Self::Other => ValidationConfig::default().command,
+        }
+    }
+}
+
+impl std::str::FromStr for ProjectLanguage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        debug!(%s, "ProjectLanguage::from_str: called");
+        match s.to_lowercase().as_str() {
+            "rust" => Ok(Self::Rust),
+            "node" | "javascript" | "typescript" => Ok(Self::Node),
+            "python" => Ok(Self::Python),
+            "other" => Ok(Self::Other),
+            _ => Err(format!("Unknown language: {}. Use: rust, node, python or other", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for ProjectLanguage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rust => write!(f, "rust"),
+            Self::Node => write!(f, "node"),
+            Self::Python => write!(f, "python"),
+            Self::Other => write!(f, "other"),
+        }
+    }
+}
+
+/// Settings for the generated files
+#[derive(Debug, Clone)]
+pub struct InitOptions {
+    /// Project language
+    pub language: ProjectLanguage,
+    /// Validation command (None = the language's default)
+    pub validation_command: Option<String>,
+    /// Default model in "provider/model" format
+    pub model: String,
+    /// Overwrite files that already exist
+    pub force: bool,
+}
+
+impl InitOptions {
+    /// Options with the language detected in `dir`
+    pub fn new(dir: &Path) -> Self {
+        debug!(?dir, "InitOptions::new: called");
+        Self {
+            language: ProjectLanguage::detect(dir),
+            validation_command: None,
+            model: DEFAULT_MODEL.to_string(),
+            force: false,
+        }
+    }
+
+    /// Set the project language
+    pub fn with_language(mut self, language: ProjectLanguage) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// Set the validation command
+    pub fn with_validation_command(mut self, command: impl Into<String>) -> Self {
+        self.validation_command = Some(command.into());
+        self
+    }
+
+    /// Set the default model
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// Overwrite existing files
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// The validation command to write
+    pub fn validation_command(&self) -> String {
+        self.validation_command
+            .clone()
+            .unwrap_or_else(|| self.language.validation_command())
+    }
+}
+
+/// Ask for each setting, with the current value as the default
+///
+/// An empty answer keeps the default. Changing the language also changes the
+/// suggested validation command unless one was given explicitly.
+pub fn prompt_options(
+    mut options: InitOptions,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+) -> Result<InitOptions> {
+    debug!(?options, "prompt_options: called");
+    loop {
+        let answer = ask(
+            input,
+            output,
+            "Project language (rust, node, python, other)",
+            &options.language.to_string(),
+        )?;
+        match answer.parse() {
+            Ok(language) => {
+                options.language = language;
+                break;
+            }
+            Err(e) => writeln!(output, "{}", e)?,
+        }
+    }
+    let command = ask(input, output, "Validation command", &options.validation_command())?;
+    if command != options.language.validation_command() {
+        options.validation_command = Some(command);
+    }
+    options.model = ask(input, output, "Default model (provider/model)", &options.model)?;
+    Ok(options)
+}
+
+/// Print a question with its default and read one line (empty = default)
+fn ask(input: &mut impl BufRead, output: &mut impl Write, question: &str, default: &str) -> Result<String> {
+    write!(output, "{} [{}]: ", question, default)?;
+    output.flush()?;
+    let mut line = String::new();
+    input.read_line(&mut line).context("Failed to read answer")?;
+    let answer = line.trim();
+    Ok(if answer.is_empty() { default } else { answer }.to_string())
+}
+
+/// What happened to a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileAction {
+    Created,
+    Updated,
+    /// Already existed and was left alone
+    Skipped,
+}
+
+impl std::fmt::Display for FileAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Created => write!(f, "created"),
+            Self::Updated => write!(f, "updated"),
+            Self::Skipped => write!(f, "exists, skipped"),
+        }
+    }
+}
+
+/// Create the taskdaemon files in `dir`
+///
+/// Existing files are skipped unless `options.force` is set; `.gitignore` is
+/// only ever appended to. Returns each file (relative to `dir`) with what was
+/// done to it.
+pub fn init_project(dir: &Path, options: &InitOptions) -> Result<Vec<(PathBuf, FileAction)>> {
+    debug!(?dir, ?options, "init_project: called");
+    let validation_command = options.validation_command();
+    let files = [
+        (".taskdaemon.yml", starter_config(&options.model, &validation_command)),
+        (".taskdaemon/loops/fix.yml", fix_loop_type(&validation_command)),
+        (".taskdaemon/loops/docs.yml", docs_loop_type(&validation_command)),
+        (".taskdaemon/prompts/plan.pmt", embedded::PLAN.to_string()),
+        (".taskdaemon/prompts/title.pmt", embedded::TITLE_GENERATOR.to_string()),
+    ];
+
+    let mut actions = Vec::new();
+    for (name, content) in files {
+        let path = dir.join(name);
+        let action = if !path.exists() {
+            FileAction::Created
+        } else if options.force {
+            FileAction::Updated
+        } else {
+            debug!(?path, "init_project: exists, skipping");
+            actions.push((PathBuf::from(name), FileAction::Skipped));
+            continue;
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).context(format!("Failed to create {}", parent.display()))?;
+        }
+        std::fs::write(&path, content).context(format!("Failed to write {}", path.display()))?;
+        actions.push((PathBuf::from(name), action));
+    }
+    actions.push((PathBuf::from(".gitignore"), update_gitignore(&dir.join(".gitignore"))?));
+    Ok(actions)
+}
+
+/// Append the taskdaemon entries missing from a `.gitignore`
+fn update_gitignore(path: &Path) -> Result<FileAction> {
+    debug!(?path, "update_gitignore: called");
+    let existed = path.exists();
+    let existing = if existed {
+        std::fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?
+    } else {
+        String::new()
+    };
+    let missing: Vec<&str> = GITIGNORE_ENTRIES
+        .iter()
+        .copied()
+        .filter(|entry| !existing.lines().any(|line| line.trim() == *entry))
+        .collect();
+    if missing.is_empty() {
+        return Ok(FileAction::Skipped);
+    }
+
+    let mut content = existing.clone();
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    if !content.is_empty() {
+        content.push('\n');
+    }
+    content.push_str("# taskdaemon\n");
+    for entry in missing {
+        content.push_str(entry);
+        content.push('\n');
+    }
+    std::fs::write(path, content).context(format!("Failed to write {}", path.display()))?;
+    Ok(if existed { FileAction::Updated } else { FileAction::Created })
+}
+
+/// Quote a value for YAML (double-quoted scalar)
+fn yaml_quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn starter_config(model: &str, validation_command: &str) -> String {
+    format!(
+        r#"# taskdaemon project config (created by `td init`)
+#
+# Keys set here override ~/.config/taskdaemon/taskdaemon.yml when td runs in
+# this directory. See docs/config-schema.md for all keys.
+
+llm:
+  default: {model}
+
+validation:
+  command: {command}
+
+loops:
+  paths:
+    - builtin
+    - ~/.config/taskdaemon/loops
+    - .taskdaemon/loops
+"#,
+        model = yaml_quote(model),
+        command = yaml_quote(validation_command),
+    )
+}
+
+fn fix_loop_type(validation_command: &str) -> String {
+    format!(
+        r#"# Example loop type (created by `td init`)
+# Extends the builtin ralph type. Edit it, copy it, or delete it.
+extends: ralph
+description: "Fix the failing validation command"
+
+prompt-template: |
+  The validation command for this project is failing. Find the cause and fix it.
+
+  {{{{#if task-description}}}}
+  ## Task Description
+  {{{{task-description}}}}
+  {{{{/if}}}}
+
+  ## Current State
+  Working directory: {{{{working-directory}}}}
+
+  {{{{#if previous-errors}}}}
+  ## Validation Output (failed)
+  {{{{previous-errors}}}}
+  {{{{/if}}}}
+
+  ## Instructions
+  Make the smallest change that fixes the failure. Don't weaken or delete tests.
+  Commit your changes with a meaningful message.
+
+validation-command: {command}
+max-iterations: 20
+"#,
+        command = yaml_quote(validation_command),
+    )
+}
+
+fn docs_loop_type(validation_command: &str) -> String {
+    format!(
+        r#"# Example loop type (created by `td init`)
+# Extends the builtin ralph type. Edit it, copy it, or delete it.
+extends: ralph
+description: "Bring documentation up to date with the code"
+
+prompt-template: |
+  Update the project's documentation (README, doc comments, docs/) so it
+  matches what the code does today.
+
+  {{{{#if task-description}}}}
+  ## Task Description
+  {{{{task-description}}}}
+  {{{{/if}}}}
+
+  ## Current State
+  Working directory: {{{{working-directory}}}}
+
+  {{{{#if git-diff}}}}
+  Git diff (recent changes):
+  {{{{git-diff}}}}
+  {{{{/if}}}}
+
+  {{{{#if previous-errors}}}}
+  ## Validation Output (failed)
+  {{{{previous-errors}}}}
+  {{{{/if}}}}
+
+  ## Instructions
+  Only change documentation and examples. Commit your changes with a meaningful message.
+
+validation-command: {command}
+max-iterations: 10
+"#,
+        command = yaml_quote(validation_command),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::r#loop::LoopType;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect_language() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(ProjectLanguage::detect(temp.path()), ProjectLanguage::Other);
+        std::fs::write(temp.path().join("pyproject.toml"), "").unwrap();
+        assert_eq!(ProjectLanguage::detect(temp.path()), ProjectLanguage::Python);
+        std::fs::write(temp.path().join("package.json"), "{}").unwrap();
+        assert_eq!(ProjectLanguage::detect(temp.path()), ProjectLanguage::Node);
+        std::fs::write(temp.path().join("Cargo.toml"), "").unwrap();
+        assert_eq!(ProjectLanguage::detect(temp.path()), ProjectLanguage::Rust);
+        assert_eq!(ProjectLanguage::Rust.validation_command(), "cargo test");
+        assert_eq!("TypeScript".parse::<ProjectLanguage>().unwrap(), ProjectLanguage::Node);
+        assert!("cobol".parse::<ProjectLanguage>().is_err());
+    }
+
+    #[test]
+    fn test_init_project() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(temp.path().join("Cargo.toml"), "").unwrap();
+        std::fs::write(temp.path().join(".gitignore"), "target/").unwrap();
+        let options = InitOptions::new(temp.path()).with_model("openai/gpt-4o");
+
+        let actions = init_project(temp.path(), &options).unwrap();
+        assert!(
+            actions
+                .iter()
+                .all(|(path, action)| *action == FileAction::Created || path == Path::new(".gitignore"))
+        );
+
+        // The generated files parse
+        let config: Config =
+            serde_yaml::from_str(&std::fs::read_to_string(temp.path().join(".taskdaemon.yml")).unwrap()).unwrap();
+        assert_eq!(config.llm.default, "openai/gpt-4o");
+        assert_eq!(config.validation.command, "cargo test");
+        for name in ["fix.yml", "docs.yml"] {
+            let content = std::fs::read_to_string(temp.path().join(".taskdaemon/loops").join(name)).unwrap();
+            let loop_type: LoopType = serde_yaml::from_str(&content).unwrap();
+            assert_eq!(loop_type.extends.as_deref(), Some("ralph"));
+            assert_eq!(loop_type.validation_command, "cargo test");
+            assert!(loop_type.prompt_template.contains("{{working-directory}}"));
+        }
+        assert!(temp.path().join(".taskdaemon/prompts/plan.pmt").exists());
+
+        let gitignore = std::fs::read_to_string(temp.path().join(".gitignore")).unwrap();
+        assert_eq!(gitignore, "target/\n\n# taskdaemon\n.taskdaemon/plans/\n.taskstore/\n");
+
+        // A second run leaves everything alone
+        let actions = init_project(temp.path(), &options).unwrap();
+        assert!(actions.iter().all(|(_, action)| *action == FileAction::Skipped));
+
+        // --force rewrites the generated files but never duplicates gitignore entries
+        let actions = init_project(temp.path(), &options.clone().with_force(true)).unwrap();
+        assert!(actions.contains(&(PathBuf::from(".taskdaemon.yml"), FileAction::Updated)));
+        assert_eq!(
+            std::fs::read_to_string(temp.path().join(".gitignore")).unwrap(),
+            gitignore
+        );
+    }
+
+    #[test]
+    fn test_prompt_options() {
+        let temp = TempDir::new().unwrap();
+        let options = InitOptions::new(temp.path());
+
+        // Defaults accepted
+        let mut output = Vec::new();
+        let answered = prompt_options(options.clone(), &mut "\n\n\n".as_bytes(), &mut output).unwrap();
+        assert_eq!(answered.language, ProjectLanguage::Other);
+        assert_eq!(answered.validation_command, None);
+        assert_eq!(answered.model, DEFAULT_MODEL);
+
+        // An invalid language is asked again; the command follows the language
+        let mut output = Vec::new();
+        let answered = prompt_options(
+            options.clone(),
+            &mut "cobol\npython\n\nopenai/gpt-4o\n".as_bytes(),
+            &mut output,
+        )
+        .unwrap();
+        assert_eq!(answered.language, ProjectLanguage::Python);
+        assert_eq!(answered.validation_command(), "pytest");
+        assert_eq!(answered.model, "openai/gpt-4o");
+        assert!(String::from_utf8(output).unwrap().contains("Unknown language: cobol"));
+
+        // A custom command is kept
+        let mut output = Vec::new();
+        let answered = prompt_options(options, &mut "rust\nmake check\n\n".as_bytes(), &mut output).unwrap();
+        assert_eq!(answered.validation_command(), "make check");
+    }
+}
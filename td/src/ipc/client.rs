@@ -1,7 +1,8 @@
 //! IPC client for communicating with the daemon
 //!
 //! Provides a simple interface for the TUI/CLI to send messages to the daemon
-//! via Unix Domain Socket.
+//! via Unix Domain Socket, and to observe it (list, describe, subscribe)
+//! without touching its state files.
 
 use std::path::PathBuf;
 use std::time::Duration;
@@ -13,6 +14,9 @@ use tracing::debug;
 
 use super::get_socket_path;
 use super::messages::{DaemonMessage, DaemonResponse};
+use crate::domain::{IterationLog, LoopExecution};
+use crate::events::Event;
+use crate::resources::ResourceSample;
 
 /// Default timeout for IPC operations
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
@@ -20,11 +24,35 @@ const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
 /// Maximum message size (1KB as per design doc)
 const MAX_MESSAGE_SIZE: usize = 1024;
 
+/// Maximum response size; execution lists and describe data outgrow 1KB
+const MAX_RESPONSE_SIZE: usize = 64 * 1024 * 1024;
+
 /// Client for communicating with the daemon via IPC
 #[derive(Debug, Clone)]
 pub struct DaemonClient {
     socket_path: PathBuf,
     timeout: Duration,
+    writer_token: Option<String>,
+}
+
+/// Events streamed from a [`DaemonClient::subscribe`] connection
+#[derive(Debug)]
+pub struct EventStream {
+    reader: BufReader<UnixStream>,
+}
+
+impl EventStream {
+    /// Wait for the next event; `None` once the daemon closes the stream
+    pub async fn next(&mut self) -> Result<Option<Event>> {
+        let Some(response) = read_response(&mut self.reader).await? else {
+            return Ok(None);
+        };
+        match response {
+            DaemonResponse::Event { event } => Ok(Some(*event)),
+            DaemonResponse::Error { message } => Err(eyre::eyre!("Daemon error: {}", message)),
+            _ => Err(eyre::eyre!("Unexpected response")),
+        }
+    }
 }
 
 impl Default for DaemonClient {
@@ -39,6 +67,7 @@ impl DaemonClient {
         Self {
             socket_path: get_socket_path(),
             timeout: DEFAULT_TIMEOUT,
+            writer_token: None,
         }
     }
 
@@ -47,6 +76,7 @@ impl DaemonClient {
         Self {
             socket_path,
             timeout: DEFAULT_TIMEOUT,
+            writer_token: None,
         }
     }
 
@@ -56,6 +86,12 @@ impl DaemonClient {
         self
     }
 
+    /// Send `token` with write requests (see [`Self::claim_writer`])
+    pub fn with_writer_token(mut self, token: impl Into<String>) -> Self {
+        self.writer_token = Some(token.into());
+        self
+    }
+
     /// Check if the daemon socket exists
     pub fn socket_exists(&self) -> bool {
         self.socket_path.exists()
@@ -106,8 +142,127 @@ impl DaemonClient {
     /// Request daemon to shutdown gracefully
     pub async fn shutdown(&self) -> Result<()> {
         debug!("DaemonClient: requesting daemon shutdown");
-        let response = self.send_message(DaemonMessage::Shutdown).await?;
+        let msg = DaemonMessage::Shutdown {
+            token: self.writer_token.clone(),
+        };
+        let response = self.send_message(msg).await?;
+        match response {
+            DaemonResponse::Ok => Ok(()),
+            DaemonResponse::Error { message } => Err(eyre::eyre!("Daemon error: {}", message)),
+            _ => Err(eyre::eyre!("Unexpected response")),
+        }
+    }
+
+    /// Turn maintenance mode on or off; returns the executions paused or resumed
+    pub async fn set_maintenance(&self, enabled: bool) -> Result<Vec<String>> {
+        debug!(enabled, "DaemonClient: setting maintenance mode");
+        let msg = DaemonMessage::SetMaintenance {
+            enabled,
+            token: self.writer_token.clone(),
+        };
+        let response = self.send_message(msg).await?;
+        match response {
+            DaemonResponse::Maintenance { executions, .. } => Ok(executions),
+            DaemonResponse::Error { message } => Err(eyre::eyre!("Daemon error: {}", message)),
+            _ => Err(eyre::eyre!("Unexpected response")),
+        }
+    }
+
+    /// Have the daemon re-read its API key; returns whether it changed and the executions resumed
+    pub async fn reload_secrets(&self) -> Result<(bool, Vec<String>)> {
+        debug!("DaemonClient: reloading secrets");
+        let msg = DaemonMessage::ReloadSecrets {
+            token: self.writer_token.clone(),
+        };
+        match self.send_message(msg).await? {
+            DaemonResponse::SecretsReloaded { changed, resumed } => Ok((changed, resumed)),
+            DaemonResponse::Error { message } => Err(eyre::eyre!("Daemon error: {}", message)),
+            _ => Err(eyre::eyre!("Unexpected response")),
+        }
+    }
+
+    /// List the daemon's executions, optionally filtered by status
+    pub async fn list_executions(&self, status: Option<&str>) -> Result<Vec<LoopExecution>> {
+        debug!(?status, "DaemonClient: listing executions");
+        let msg = DaemonMessage::ListExecutions {
+            status: status.map(str::to_string),
+        };
+        match self.send_message(msg).await? {
+            DaemonResponse::Executions { executions } => Ok(executions),
+            DaemonResponse::Error { message } => Err(eyre::eyre!("Daemon error: {}", message)),
+            _ => Err(eyre::eyre!("Unexpected response")),
+        }
+    }
+
+    /// Fetch an execution and its iteration logs
+    pub async fn describe_execution(&self, id: &str) -> Result<(LoopExecution, Vec<IterationLog>)> {
+        debug!(%id, "DaemonClient: describing execution");
+        let msg = DaemonMessage::DescribeExecution { id: id.to_string() };
+        match self.send_message(msg).await? {
+            DaemonResponse::Description { execution, iterations } => Ok((*execution, iterations)),
+            DaemonResponse::Error { message } => Err(eyre::eyre!("Daemon error: {}", message)),
+            _ => Err(eyre::eyre!("Unexpected response")),
+        }
+    }
+
+    /// Subscribe to the daemon's events, optionally for one execution only
+    pub async fn subscribe(&self, execution_id: Option<&str>) -> Result<EventStream> {
+        debug!(?execution_id, "DaemonClient: subscribing to events");
+        let msg = DaemonMessage::Subscribe {
+            execution_id: execution_id.map(str::to_string),
+        };
+        let mut stream = self.connect().await?;
+        self.write_message(&mut stream, msg).await?;
+        let mut reader = BufReader::new(stream);
+        let response = tokio::time::timeout(self.timeout, read_response(&mut reader))
+            .await
+            .context("Read timeout")??;
         match response {
+            Some(DaemonResponse::Subscribed) => Ok(EventStream { reader }),
+            Some(DaemonResponse::Error { message }) => Err(eyre::eyre!("Daemon error: {}", message)),
+            Some(_) => Err(eyre::eyre!("Unexpected response")),
+            None => Err(eyre::eyre!("Daemon closed the connection")),
+        }
+    }
+
+    /// Latest resource sample and the limits exceeded; `None` if monitoring is off
+    pub async fn resources(&self) -> Result<Option<(Option<ResourceSample>, Vec<String>)>> {
+        debug!("DaemonClient: fetching resources");
+        match self.send_message(DaemonMessage::Resources).await? {
+            DaemonResponse::Resources {
+                monitoring,
+                sample,
+                pressure,
+            } => Ok(monitoring.then_some((sample, pressure))),
+            DaemonResponse::Error { message } => Err(eyre::eyre!("Daemon error: {}", message)),
+            _ => Err(eyre::eyre!("Unexpected response")),
+        }
+    }
+
+    /// Claim (or renew) the single-writer token under `holder`
+    ///
+    /// The outer error means the daemon could not be asked; the inner one is
+    /// its refusal because another holder has the token. Until that claim is
+    /// released or lapses the daemon refuses this client's write requests.
+    pub async fn claim_writer(&self, holder: &str) -> Result<std::result::Result<String, String>> {
+        debug!(%holder, "DaemonClient: claiming writer token");
+        let msg = DaemonMessage::ClaimWriter {
+            holder: holder.to_string(),
+        };
+        match self.send_message(msg).await? {
+            DaemonResponse::Writer { token } => Ok(Ok(token)),
+            DaemonResponse::Error { message } => Ok(Err(message)),
+            _ => Err(eyre::eyre!("Unexpected response")),
+        }
+    }
+
+    /// Give the writer token up
+    pub async fn release_writer(&self, token: &str) -> Result<()> {
+        debug!("DaemonClient: releasing writer token");
+        let msg = DaemonMessage::ReleaseWriter {
+            token: token.to_string(),
+        };
+        match self.send_message(msg).await? {
             DaemonResponse::Ok => Ok(()),
             DaemonResponse::Error { message } => Err(eyre::eyre!("Daemon error: {}", message)),
             _ => Err(eyre::eyre!("Unexpected response")),
@@ -117,18 +272,29 @@ impl DaemonClient {
     /// Send a message to the daemon and wait for response
     async fn send_message(&self, msg: DaemonMessage) -> Result<DaemonResponse> {
         debug!(?self.socket_path, ?msg, "DaemonClient: sending message");
+        let mut stream = self.connect().await?;
+        self.write_message(&mut stream, msg).await?;
 
-        // Connect with timeout
-        let stream = tokio::time::timeout(self.timeout, UnixStream::connect(&self.socket_path))
+        let mut reader = BufReader::new(stream);
+        let response = tokio::time::timeout(self.timeout, read_response(&mut reader))
             .await
-            .context("Connection timeout")?
-            .context("Failed to connect to daemon socket")?;
+            .context("Read timeout")??
+            .ok_or_else(|| eyre::eyre!("Daemon closed the connection"))?;
 
-        self.send_on_stream(stream, msg).await
+        debug!(?response, "DaemonClient: received response");
+        Ok(response)
     }
 
-    /// Send message on an existing stream (extracted for testing)
-    async fn send_on_stream(&self, mut stream: UnixStream, msg: DaemonMessage) -> Result<DaemonResponse> {
+    /// Connect to the daemon socket with timeout
+    async fn connect(&self) -> Result<UnixStream> {
+        tokio::time::timeout(self.timeout, UnixStream::connect(&self.socket_path))
+            .await
+            .context("Connection timeout")?
+            .context("Failed to connect to daemon socket")
+    }
+
+    /// Write one message line
+    async fn write_message(&self, stream: &mut UnixStream, msg: DaemonMessage) -> Result<()> {
         // Serialize message
         let msg_json = serde_json::to_string(&msg).context("Failed to serialize message")?;
 
@@ -149,33 +315,27 @@ impl DaemonClient {
         })
         .await
         .context("Write timeout")??;
+        Ok(())
+    }
+}
 
-        // Read response with size limit
-        let mut reader = BufReader::new(&mut stream);
-        let mut response_line = String::new();
-
-        tokio::time::timeout(self.timeout, async {
-            let bytes_read = reader
-                .read_line(&mut response_line)
-                .await
-                .context("Failed to read response")?;
-
-            if bytes_read > MAX_MESSAGE_SIZE {
-                return Err(eyre::eyre!("Response too large: {} bytes", bytes_read));
-            }
-
-            Ok::<_, eyre::Error>(())
-        })
+/// Read one response line; `None` at end of stream
+async fn read_response(reader: &mut BufReader<UnixStream>) -> Result<Option<DaemonResponse>> {
+    let mut response_line = String::new();
+    let bytes_read = reader
+        .read_line(&mut response_line)
         .await
-        .context("Read timeout")??;
+        .context("Failed to read response")?;
 
-        // Parse response
-        let response: DaemonResponse =
-            serde_json::from_str(response_line.trim()).context("Failed to parse daemon response")?;
-
-        debug!(?response, "DaemonClient: received response");
-        Ok(response)
+    if bytes_read == 0 {
+        return Ok(None);
     }
+    if bytes_read > MAX_RESPONSE_SIZE {
+        return Err(eyre::eyre!("Response too large: {} bytes", bytes_read));
+    }
+
+    let response = serde_json::from_str(response_line.trim()).context("Failed to parse daemon response")?;
+    Ok(Some(response))
 }
 
 #[cfg(test)]
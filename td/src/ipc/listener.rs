@@ -11,6 +11,7 @@ use tracing::{debug, warn};
 
 use super::get_socket_path;
 use super::messages::{DaemonMessage, DaemonResponse};
+use crate::events::BufferedSubscriber;
 
 /// Maximum message size (1KB as per design doc)
 const MAX_MESSAGE_SIZE: usize = 1024;
@@ -96,6 +97,25 @@ pub async fn send_response(stream: &mut UnixStream, response: DaemonResponse) ->
     Ok(())
 }
 
+/// Forward events from `subscriber` to a subscribed connection
+///
+/// Only events for `execution_id` are sent when one is given. Returns once the
+/// client hangs up (a write fails) or the event bus closes.
+pub async fn stream_events(mut stream: UnixStream, mut subscriber: BufferedSubscriber, execution_id: Option<String>) {
+    debug!(?execution_id, "stream_events: called");
+    while let Some(event) = subscriber.recv().await {
+        if execution_id.as_deref().is_some_and(|id| id != event.execution_id()) {
+            continue;
+        }
+        let response = DaemonResponse::Event { event: Box::new(event) };
+        if let Err(e) = send_response(&mut stream, response).await {
+            debug!(error = %e, "stream_events: subscriber went away");
+            return;
+        }
+    }
+    debug!("stream_events: event bus closed");
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -241,4 +261,48 @@ mod tests {
         // Cleanup
         mock_daemon.await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_end_to_end_subscribe_streams_filtered_events() {
+        use super::super::client::DaemonClient;
+        use crate::events::{Event, EventBus, OverflowPolicy};
+        use std::sync::Arc;
+
+        let temp = TempDir::new().unwrap();
+        let socket_path = temp.path().join("test.sock");
+        let (listener, _) = create_listener_at(&socket_path).unwrap();
+        let bus = Arc::new(EventBus::with_default_capacity());
+
+        // Mock daemon: accept the subscription and hand it to stream_events
+        let daemon_bus = bus.clone();
+        let mock_daemon = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let DaemonMessage::Subscribe { execution_id } = read_message(&mut stream).await.unwrap() else {
+                panic!("Expected Subscribe");
+            };
+            let subscriber = daemon_bus.subscribe_buffered(64, OverflowPolicy::DropTokenEventsFirst);
+            send_response(&mut stream, DaemonResponse::Subscribed).await.unwrap();
+            stream_events(stream, subscriber, execution_id).await;
+        });
+
+        let client = DaemonClient::with_socket_path(socket_path);
+        let mut events = client.subscribe(Some("exec-1")).await.unwrap();
+
+        for id in ["exec-2", "exec-1"] {
+            bus.emit(Event::IterationStarted {
+                execution_id: id.to_string(),
+                iteration: 1,
+            });
+        }
+        let event = events.next().await.unwrap().unwrap();
+        assert_eq!(event.execution_id(), "exec-1");
+
+        // Hanging up ends the forwarding task on its next write
+        drop(events);
+        bus.emit(Event::IterationStarted {
+            execution_id: "exec-1".to_string(),
+            iteration: 2,
+        });
+        mock_daemon.await.unwrap();
+    }
 }
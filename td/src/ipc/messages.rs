@@ -1,9 +1,16 @@
 //! IPC message types for daemon communication
 //!
 //! Simple JSON-over-newline protocol. Each message is a single line of JSON followed by `\n`.
+//!
+//! Requests that change the daemon carry an optional writer `token` (see
+//! [`super::writer`]); read requests never need one.
 
 use serde::{Deserialize, Serialize};
 
+use crate::domain::{IterationLog, LoopExecution};
+use crate::events::Event;
+use crate::resources::ResourceSample;
+
 /// Messages from TUI/CLI to Daemon
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(tag = "type")]
@@ -18,11 +25,51 @@ pub enum DaemonMessage {
     Ping,
 
     /// Request daemon to stop gracefully
-    Shutdown,
+    Shutdown {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        token: Option<String>,
+    },
+
+    /// Turn maintenance mode on (pause everything, stop pickups) or off (resume)
+    SetMaintenance {
+        enabled: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        token: Option<String>,
+    },
+
+    /// Re-read the LLM API key and resume executions paused because it was rejected
+    ReloadSecrets {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        token: Option<String>,
+    },
+
+    /// List executions, optionally only those with the given status
+    ListExecutions {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        status: Option<String>,
+    },
+
+    /// Fetch an execution with its iteration logs (what `td exec describe` shows)
+    DescribeExecution { id: String },
+
+    /// Stream events on this connection, optionally for one execution only
+    Subscribe {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        execution_id: Option<String>,
+    },
+
+    /// Fetch the latest resource sample (what `td daemon status --detailed` shows)
+    Resources,
+
+    /// Claim (or renew) the single-writer token
+    ClaimWriter { holder: String },
+
+    /// Give the writer token up
+    ReleaseWriter { token: String },
 }
 
 /// Responses from Daemon to TUI/CLI
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum DaemonResponse {
     /// Acknowledgment
@@ -33,6 +80,37 @@ pub enum DaemonResponse {
 
     /// Error response
     Error { message: String },
+
+    /// Maintenance mode changed; `executions` were paused (on) or resumed (off)
+    Maintenance { enabled: bool, executions: Vec<String> },
+
+    /// API key re-read; `resumed` were paused because the old one was rejected
+    SecretsReloaded { changed: bool, resumed: Vec<String> },
+
+    /// Answer to `ListExecutions`
+    Executions { executions: Vec<LoopExecution> },
+
+    /// Answer to `DescribeExecution`
+    Description {
+        execution: Box<LoopExecution>,
+        iterations: Vec<IterationLog>,
+    },
+
+    /// Subscription accepted; `Event` lines follow until the connection closes
+    Subscribed,
+
+    /// One event on a subscribed connection
+    Event { event: Box<Event> },
+
+    /// Writer token granted
+    Writer { token: String },
+
+    /// Answer to `Resources`; `pressure` lists the limits currently exceeded
+    Resources {
+        monitoring: bool,
+        sample: Option<ResourceSample>,
+        pressure: Vec<String>,
+    },
 }
 
 #[cfg(test)]
@@ -78,9 +156,19 @@ mod tests {
 
     #[test]
     fn test_shutdown_serialize() {
-        let msg = DaemonMessage::Shutdown;
+        let msg = DaemonMessage::Shutdown { token: None };
         let json = serde_json::to_string(&msg).unwrap();
         assert_eq!(json, r#"{"type":"Shutdown"}"#);
+
+        // Clients that predate the writer token still parse
+        let msg: DaemonMessage = serde_json::from_str(r#"{"type":"SetMaintenance","enabled":true}"#).unwrap();
+        assert_eq!(
+            msg,
+            DaemonMessage::SetMaintenance {
+                enabled: true,
+                token: None
+            }
+        );
     }
 
     #[test]
@@ -114,7 +202,25 @@ mod tests {
             DaemonMessage::ExecutionPending { id: "test".to_string() },
             DaemonMessage::ExecutionResumed { id: "test".to_string() },
             DaemonMessage::Ping,
-            DaemonMessage::Shutdown,
+            DaemonMessage::Shutdown {
+                token: Some("abc".to_string()),
+            },
+            DaemonMessage::SetMaintenance {
+                enabled: true,
+                token: None,
+            },
+            DaemonMessage::ListExecutions {
+                status: Some("running".to_string()),
+            },
+            DaemonMessage::DescribeExecution { id: "test".to_string() },
+            DaemonMessage::Subscribe { execution_id: None },
+            DaemonMessage::Resources,
+            DaemonMessage::ClaimWriter {
+                holder: "tui-1".to_string(),
+            },
+            DaemonMessage::ReleaseWriter {
+                token: "abc".to_string(),
+            },
         ];
 
         for msg in messages {
@@ -134,12 +240,38 @@ mod tests {
             DaemonResponse::Error {
                 message: "test error".to_string(),
             },
+            DaemonResponse::Maintenance {
+                enabled: false,
+                executions: vec!["exec-1".to_string()],
+            },
+            DaemonResponse::Executions {
+                executions: vec![LoopExecution::new("ralph", "Test")],
+            },
+            DaemonResponse::Subscribed,
+            DaemonResponse::Event {
+                event: Box::new(Event::IterationStarted {
+                    execution_id: "exec-1".to_string(),
+                    iteration: 2,
+                }),
+            },
+            DaemonResponse::Writer {
+                token: "abc".to_string(),
+            },
+            DaemonResponse::Resources {
+                monitoring: true,
+                sample: Some(ResourceSample {
+                    cpu_percent: Some(12.5),
+                    tokio_tasks: 40,
+                    ..Default::default()
+                }),
+                pressure: vec!["tokio tasks 40 > 30".to_string()],
+            },
         ];
 
         for resp in responses {
             let json = serde_json::to_string(&resp).unwrap();
             let parsed: DaemonResponse = serde_json::from_str(&json).unwrap();
-            assert_eq!(resp, parsed);
+            assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
         }
     }
 }
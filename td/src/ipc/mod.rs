@@ -3,16 +3,22 @@
 //! This module provides Unix Domain Socket-based IPC between the TUI/CLI and the daemon.
 //! When the TUI changes execution state, it connects to the daemon's socket and sends
 //! a message to wake it immediately (instead of waiting for the 60-second poll interval).
+//!
+//! Observers use the same socket: any number of clients may list executions,
+//! fetch describe data and subscribe to the event stream. Requests that change
+//! the daemon are gated by a single-writer token ([`writer`]).
 
 use std::path::PathBuf;
 
 pub mod client;
 pub mod listener;
 pub mod messages;
+pub mod writer;
 
-pub use client::DaemonClient;
-pub use listener::{cleanup_socket, create_listener, read_message, send_response};
+pub use client::{DaemonClient, EventStream};
+pub use listener::{cleanup_socket, create_listener, read_message, send_response, stream_events};
 pub use messages::{DaemonMessage, DaemonResponse};
+pub use writer::{WRITER_LEASE, WriterGate};
 
 /// Get the socket path for daemon IPC
 ///
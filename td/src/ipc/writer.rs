@@ -0,0 +1,115 @@
+//! Single-writer token for daemon IPC
+//!
+//! Any number of clients may observe the daemon (list, describe, subscribe),
+//! but only one at a time may change it. A client claims the writer token
+//! under a holder name and passes the token with its write requests; while
+//! the token is held, writes without it are refused. The claim is a lease:
+//! the holder renews it by claiming again, and a holder that disappears
+//! loses it after [`WRITER_LEASE`]. Writes are open while nobody holds it.
+
+use std::time::{Duration, Instant};
+
+use tracing::debug;
+
+/// How long a claim lasts without renewal
+pub const WRITER_LEASE: Duration = Duration::from_secs(60);
+
+#[derive(Debug)]
+struct Lease {
+    holder: String,
+    token: String,
+    renewed_at: Instant,
+}
+
+/// The daemon's record of who may write
+#[derive(Debug, Default)]
+pub struct WriterGate {
+    lease: Option<Lease>,
+}
+
+impl WriterGate {
+    /// Drop a lease that was not renewed in time
+    fn expire(&mut self, now: Instant) {
+        if self
+            .lease
+            .as_ref()
+            .is_some_and(|lease| now.duration_since(lease.renewed_at) >= WRITER_LEASE)
+        {
+            debug!("WriterGate::expire: lease expired");
+            self.lease = None;
+        }
+    }
+
+    /// Claim (or renew) the token for `holder`
+    ///
+    /// Fails with the current holder's name if someone else holds it.
+    pub fn claim(&mut self, holder: &str, now: Instant) -> Result<String, String> {
+        debug!(%holder, "WriterGate::claim: called");
+        self.expire(now);
+        match &mut self.lease {
+            Some(lease) if lease.holder == holder => {
+                lease.renewed_at = now;
+                Ok(lease.token.clone())
+            }
+            Some(lease) => Err(format!("Writer token is held by {}", lease.holder)),
+            None => {
+                let token = uuid::Uuid::now_v7().simple().to_string();
+                self.lease = Some(Lease {
+                    holder: holder.to_string(),
+                    token: token.clone(),
+                    renewed_at: now,
+                });
+                Ok(token)
+            }
+        }
+    }
+
+    /// Give the token up; false if `token` is not the current one
+    pub fn release(&mut self, token: &str) -> bool {
+        debug!("WriterGate::release: called");
+        if self.lease.as_ref().is_some_and(|lease| lease.token == token) {
+            self.lease = None;
+            return true;
+        }
+        false
+    }
+
+    /// Whether a write request carrying `token` may proceed
+    pub fn check(&mut self, token: Option<&str>, now: Instant) -> Result<(), String> {
+        self.expire(now);
+        match &self.lease {
+            Some(lease) if Some(lease.token.as_str()) != token => {
+                Err(format!("Read-only: writer token is held by {}", lease.holder))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_writer_gate_single_writer() {
+        let mut gate = WriterGate::default();
+        let start = Instant::now();
+
+        // Open until claimed
+        assert!(gate.check(None, start).is_ok());
+        let token = gate.claim("tui-1", start).unwrap();
+        assert_eq!(gate.claim("tui-1", start).unwrap(), token);
+        assert_eq!(gate.claim("tui-2", start).unwrap_err(), "Writer token is held by tui-1");
+        assert!(gate.check(None, start).is_err());
+        assert!(gate.check(Some("wrong"), start).is_err());
+        assert!(gate.check(Some(&token), start).is_ok());
+
+        // An abandoned claim lapses; a released one frees the gate at once
+        let later = start + WRITER_LEASE;
+        let second = gate.claim("tui-2", later).unwrap();
+        assert_ne!(second, token);
+        assert!(!gate.release(&token));
+        assert!(gate.release(&second));
+        assert!(gate.check(None, later).is_ok());
+    }
+}
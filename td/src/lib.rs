@@ -19,27 +19,84 @@
 //! - [`tools`] - Tool system for file/command operations
 //! - [`r#loop`] - Loop execution engine
 //! - [`config`] - Configuration types and loading
+//! - [`error`] - Error codes and categories shared by all subsystems
+//! - [`clock`] - Injectable clock and ID generator (deterministic variants for tests)
+//! - [`report`] - Shareable execution reports (Markdown/HTML)
+//! - [`compare`] - Side-by-side comparison of two executions (`td exec diff`, TUI compare view)
+//! - [`timeline`] - Execution timelines as Mermaid Gantt charts or HTML (`td exec timeline`)
+//! - [`logs`] - One execution's tool output, LLM text and validation lines (`td exec logs`)
+//! - [`batch`] - Batch submission manifests (`td exec submit`)
+//! - [`dedup`] - Duplicate task detection when executions are submitted
+//! - [`bulk`] - Bulk pause/resume/cancel/delete of filtered executions
+//! - [`policy`] - Policies (WebAssembly or embedded) gating starts, tool calls and merges
+//! - [`hooks`] - Shell hooks run on lifecycle events (iteration, merge, completion, failure)
+//! - [`chat`] - Slack/Discord bridge: lifecycle notifications and chat commands
+//! - [`ask`] - Question answering over past executions (`td ask`)
+//! - [`run_many`] - Foreground parallel runs of a manifest (`td run-many`)
+//! - [`loadtest`] - Orchestration load test against a simulated provider (`td loadtest`)
+//! - [`ci`] - CI mode for `td run --ci`: JSON progress, JUnit/SARIF reports, exit codes
+//! - [`deps`] - Outdated dependencies and the upgrade summary for `deps-upgrade` loops
+//! - [`security`] - Scanner findings, triage and SARIF report for `security-review` loops
+//! - [`resources`] - Daemon self-monitoring and pickup throttling under resource pressure
+//! - [`webhook`] - HTTP trigger endpoint that creates executions from signed POSTs
+//! - [`embed`] - `DaemonBuilder` for running the daemon inside another program
+//! - [`summary`] - Overview of all executions (`td summary`, TUI summary screen)
+//! - [`notify`] - Desktop notifications and terminal bell on completion
+//! - [`secrets`] - Keychain and age-encrypted secrets store for API keys (`td secrets`)
 //! - [`cli`] - Command-line interface
+//! - [`completions`] - Shell completion scripts (`td completions`)
+//! - [`doctor`] - Environment diagnostics (`td doctor`)
+//! - [`init`] - Project setup (`td init`)
 
 // Phase 1 infrastructure - these types are used in later phases when CLI is wired up
 #![allow(dead_code)]
 
+pub mod ask;
+pub mod batch;
+pub mod bulk;
+pub mod chat;
+pub mod ci;
 pub mod cli;
+pub mod clock;
+pub mod compare;
+pub mod completions;
 pub mod config;
 pub mod coordinator;
 pub mod daemon;
+pub mod dedup;
+pub mod deps;
+pub mod doctor;
 pub mod domain;
+pub mod embed;
+pub mod error;
 pub mod events;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod hooks;
+pub mod init;
 pub mod ipc;
 pub mod llm;
+pub mod loadtest;
+pub mod logs;
+pub mod notify;
+pub mod policy;
 pub mod progress;
 pub mod prompts;
+pub mod report;
+pub mod resources;
+pub mod review;
+pub mod run_many;
 pub mod scheduler;
+pub mod secrets;
+pub mod security;
 pub mod state;
+pub mod summary;
+pub mod timeline;
 pub mod tools;
 pub mod tui;
 pub mod validation;
 pub mod watcher;
+pub mod webhook;
 pub mod worktree;
 
 // Note: 'loop' is a reserved keyword, so we use r#loop
@@ -50,12 +107,13 @@ pub mod r#loop;
 pub use config::{Config, LlmConfig};
 pub use coordinator::{
     CoordMessage, CoordRequest, Coordinator, CoordinatorConfig, CoordinatorHandle, CoordinatorMetrics, EventStore,
-    PersistedEvent, PersistedEventType,
+    PersistedEvent, PersistedEventType, Topic, TopicRegistry,
 };
 pub use domain::{
     DomainId, Filter, FilterOp, IndexValue, Loop, LoopExecution, LoopExecutionStatus, LoopRun, LoopRunStatus,
     LoopStatus, Phase, PhaseStatus, Priority, Record, Store,
 };
+pub use embed::{DaemonBuilder, DaemonHandle, EventCallback};
 pub use llm::{
     AnthropicClient, CompletionRequest, CompletionResponse, LlmClient, LlmError, OpenAIClient, create_client,
 };
@@ -69,10 +127,12 @@ pub use prompts::{FocusArea, PromptContext, PromptLoader};
 pub use scheduler::{QueueEntry, QueueEntryStatus, QueueState, ScheduleResult, Scheduler, SchedulerConfig};
 pub use state::{RecoveryStats, StateCommand, StateError, StateManager, StateResponse, recover, scan_for_recovery};
 pub use tools::{
-    ExploreConfig, ExploreSpawner, ExploreSpawnerRef, Thoroughness, Tool, ToolContext, ToolError, ToolExecutor,
-    ToolProfile, ToolResult,
+    ExploreConfig, ExploreSpawner, ExploreSpawnerRef, LspServerSpec, LspSession, LspSessionRef, Thoroughness, Tool,
+    ToolContext, ToolError, ToolExecutor, ToolProfile, ToolResult, ToolWorker,
+};
+pub use validation::{
+    Convergence, PassResult, PlanRefinementContext, ReviewPass, ReviewPassConfig, ReviewPipeline, ReviewProgress, ReviewStep,
 };
-pub use validation::{PassResult, PlanRefinementContext, ReviewPass};
 pub use watcher::{MainWatcher, WatcherConfig};
 pub use worktree::{MergeResult, WorktreeConfig, WorktreeError, WorktreeInfo, WorktreeManager, merge_to_main};
 
@@ -4,14 +4,18 @@
 //! support for both blocking and streaming responses.
 
 use async_trait::async_trait;
+use eventsource_stream::Eventsource;
 use futures::StreamExt;
 use reqwest::Client;
-use reqwest_eventsource::{Event, EventSource};
 use serde::Deserialize;
+use std::sync::Mutex;
 use std::time::Duration;
 use tokio::sync::mpsc;
 use tracing::{debug, warn};
 
+use super::context::ContextGuard;
+use super::credential::Credential;
+use super::ratelimit::RateLimitStatus;
 use super::{
     CompletionRequest, CompletionResponse, ContentBlock, LlmClient, LlmError, Message, MessageContent, StopReason,
     StreamChunk, TokenUsage, ToolCall,
@@ -32,10 +36,12 @@ fn is_retryable_status(status: u16) -> bool {
 /// Anthropic Claude API client
 pub struct AnthropicClient {
     model: String,
-    api_key: String,
+    api_key: Credential,
     base_url: String,
     http: Client,
     max_tokens: u32,
+    context: ContextGuard,
+    rate_limits: Mutex<Option<RateLimitStatus>>,
     #[allow(dead_code)]
     timeout: Duration,
 }
@@ -46,9 +52,7 @@ impl AnthropicClient {
     /// Takes a ResolvedLlmConfig which contains all necessary fields.
     pub fn from_config(config: &ResolvedLlmConfig) -> Result<Self, LlmError> {
         debug!(?config, "from_config: called");
-        let api_key = config
-            .get_api_key()
-            .map_err(|e| LlmError::InvalidResponse(e.to_string()))?;
+        let api_key = Credential::resolve(config)?;
 
         let timeout = Duration::from_millis(config.timeout_ms);
 
@@ -60,6 +64,8 @@ impl AnthropicClient {
             base_url: config.base_url.clone(),
             http,
             max_tokens: config.max_tokens,
+            context: ContextGuard::from_config(config),
+            rate_limits: Mutex::new(None),
             timeout,
         })
     }
@@ -148,6 +154,17 @@ impl AnthropicClient {
                     "is_error": is_error,
                 })
             }
+            ContentBlock::Image { media_type, data } => {
+                debug!(%media_type, len = data.len(), "convert_content_block: Image block");
+                serde_json::json!({
+                    "type": "image",
+                    "source": {
+                        "type": "base64",
+                        "media_type": media_type,
+                        "data": data,
+                    },
+                })
+            }
         }
     }
 
@@ -180,6 +197,17 @@ impl AnthropicClient {
                 cache_read_tokens: api_response.usage.cache_read_input_tokens.unwrap_or(0),
                 cache_creation_tokens: api_response.usage.cache_creation_input_tokens.unwrap_or(0),
             },
+            served_by: None,
+        }
+    }
+
+    /// Remember the rate limit headers of a response
+    fn record_rate_limits(&self, headers: &reqwest::header::HeaderMap) {
+        if let Some(status) = RateLimitStatus::from_anthropic_headers(headers)
+            && let Ok(mut latest) = self.rate_limits.lock()
+        {
+            debug!(?status.requests_remaining, ?status.tokens_remaining, "record_rate_limits: updated");
+            *latest = Some(status);
         }
     }
 }
@@ -188,10 +216,12 @@ impl AnthropicClient {
 impl LlmClient for AnthropicClient {
     async fn complete(&self, request: CompletionRequest) -> Result<CompletionResponse, LlmError> {
         debug!(%self.model, %request.max_tokens, "complete: called");
+        let request = self.context.check(request, self.max_tokens)?;
         let url = format!("{}/v1/messages", self.base_url);
         let body = self.build_request_body(&request);
 
         let mut last_error = None;
+        let mut reloaded = false;
         for attempt in 0..=MAX_RETRIES {
             if attempt > 0 {
                 let backoff = INITIAL_BACKOFF_MS * 2u64.pow(attempt - 1);
@@ -206,7 +236,7 @@ impl LlmClient for AnthropicClient {
             let response = match self
                 .http
                 .post(url.clone())
-                .header("x-api-key", self.api_key.clone())
+                .header("x-api-key", self.api_key.key())
                 .header("anthropic-version", "2023-06-01")
                 .header("content-type", "application/json")
                 .json(&body)
@@ -222,6 +252,7 @@ impl LlmClient for AnthropicClient {
             };
 
             let status = response.status().as_u16();
+            self.record_rate_limits(response.headers());
 
             if status == 429 {
                 debug!("complete: rate limited (429)");
@@ -237,6 +268,15 @@ impl LlmClient for AnthropicClient {
                 });
             }
 
+            // A rotated key: re-read it once and try again
+            if status == 401 && !reloaded && self.api_key.reload_rejected() {
+                debug!(attempt, "complete: API key rejected, retrying with the reloaded key");
+                reloaded = true;
+                let text = response.text().await.unwrap_or_default();
+                last_error = Some(LlmError::ApiError { status, message: text });
+                continue;
+            }
+
             if is_retryable_status(status) && attempt < MAX_RETRIES {
                 let text = response.text().await.unwrap_or_default();
                 debug!(attempt, status, "complete: retryable error");
@@ -264,12 +304,14 @@ impl LlmClient for AnthropicClient {
         chunk_tx: mpsc::Sender<StreamChunk>,
     ) -> Result<CompletionResponse, LlmError> {
         debug!(%self.model, %request.max_tokens, "stream: called");
+        let request = self.context.check(request, self.max_tokens)?;
         let url = format!("{}/v1/messages", self.base_url);
         let mut body = self.build_request_body(&request);
         body["stream"] = serde_json::json!(true);
 
         let mut last_error = None;
-        let mut es = None;
+        let mut reloaded = false;
+        let mut connected = None;
 
         // Retry loop for establishing the connection
         for attempt in 0..=MAX_RETRIES {
@@ -279,30 +321,71 @@ impl LlmClient for AnthropicClient {
                 tokio::time::sleep(Duration::from_millis(backoff)).await;
             }
 
-            let http_request = self
+            let response = match self
                 .http
                 .post(url.clone())
-                .header("x-api-key", self.api_key.clone())
+                .header("x-api-key", self.api_key.key())
                 .header("anthropic-version", "2023-06-01")
                 .header("content-type", "application/json")
-                .json(&body);
-
-            match EventSource::new(http_request) {
-                Ok(event_source) => {
-                    es = Some(event_source);
-                    break;
-                }
+                .json(&body)
+                .send()
+                .await
+            {
+                Ok(r) => r,
                 Err(e) => {
-                    debug!(attempt, error = %e, "stream: EventSource creation failed");
-                    last_error = Some(LlmError::InvalidResponse(e.to_string()));
+                    debug!(attempt, error = %e, "stream: network error");
+                    last_error = Some(LlmError::Network(e));
                     continue;
                 }
+            };
+
+            let status = response.status().as_u16();
+            self.record_rate_limits(response.headers());
+
+            if status == 429 {
+                debug!("stream: rate limited (429)");
+                let retry_after = response
+                    .headers()
+                    .get("retry-after")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|s| s.parse::<u64>().ok())
+                    .unwrap_or(60);
+
+                return Err(LlmError::RateLimited {
+                    retry_after: Duration::from_secs(retry_after),
+                });
             }
+
+            // A rotated key: re-read it once and try again
+            if status == 401 && !reloaded && self.api_key.reload_rejected() {
+                debug!(attempt, "stream: API key rejected, retrying with the reloaded key");
+                reloaded = true;
+                let text = response.text().await.unwrap_or_default();
+                last_error = Some(LlmError::ApiError { status, message: text });
+                continue;
+            }
+
+            if is_retryable_status(status) && attempt < MAX_RETRIES {
+                let text = response.text().await.unwrap_or_default();
+                debug!(attempt, status, "stream: retryable error");
+                last_error = Some(LlmError::ApiError { status, message: text });
+                continue;
+            }
+
+            if !response.status().is_success() {
+                debug!(%status, "stream: API error");
+                let text = response.text().await.unwrap_or_default();
+                return Err(LlmError::ApiError { status, message: text });
+            }
+
+            connected = Some(response);
+            break;
         }
 
-        let mut es = es.ok_or_else(|| {
-            last_error.unwrap_or_else(|| LlmError::InvalidResponse("Failed to create EventSource".to_string()))
-        })?;
+        let mut es = connected
+            .ok_or_else(|| last_error.unwrap_or_else(|| LlmError::InvalidResponse("Max retries exceeded".to_string())))?
+            .bytes_stream()
+            .eventsource();
 
         let mut full_content = String::new();
         let mut tool_calls: Vec<ToolCall> = Vec::new();
@@ -312,8 +395,8 @@ impl LlmClient for AnthropicClient {
 
         while let Some(event) = es.next().await {
             match event {
-                Ok(Event::Message(msg)) => {
-                    debug!("stream: received Event::Message");
+                Ok(msg) => {
+                    debug!("stream: received event");
                     let data: serde_json::Value = serde_json::from_str(&msg.data).map_err(LlmError::Json)?;
 
                     match data["type"].as_str() {
@@ -400,9 +483,6 @@ impl LlmClient for AnthropicClient {
                         }
                     }
                 }
-                Ok(Event::Open) => {
-                    debug!("stream: Event::Open");
-                }
                 Err(e) => {
                     debug!(%e, "stream: Event error");
                     let _ = chunk_tx.send(StreamChunk::Error(e.to_string())).await;
@@ -424,8 +504,17 @@ impl LlmClient for AnthropicClient {
             tool_calls,
             stop_reason,
             usage,
+            served_by: None,
         })
     }
+
+    fn rate_limits(&self) -> Option<RateLimitStatus> {
+        self.rate_limits.lock().ok().and_then(|latest| latest.clone())
+    }
+
+    fn reload_credentials(&self) -> Result<bool, LlmError> {
+        self.api_key.reload()
+    }
 }
 
 // Anthropic API response types
@@ -468,10 +557,12 @@ mod tests {
         // the internal methods with a manually constructed client
         let client = AnthropicClient {
             model: "claude-sonnet-4".to_string(),
-            api_key: "test-key".to_string(),
+            api_key: Credential::fixed("test-key"),
             base_url: "https://api.anthropic.com".to_string(),
             http: Client::new(),
             max_tokens: 8192,
+            context: ContextGuard::new(None, Default::default()),
+            rate_limits: Mutex::new(None),
             timeout: Duration::from_secs(300),
         };
 
@@ -491,16 +582,42 @@ mod tests {
         assert!(body.get("tools").is_none());
     }
 
+    #[test]
+    fn test_convert_image_block() {
+        let client = AnthropicClient {
+            model: "claude-sonnet-4".to_string(),
+            api_key: Credential::fixed("test-key"),
+            base_url: "https://api.anthropic.com".to_string(),
+            http: Client::new(),
+            max_tokens: 8192,
+            context: ContextGuard::new(None, Default::default()),
+            rate_limits: Mutex::new(None),
+            timeout: Duration::from_secs(300),
+        };
+
+        let block = client.convert_content_block(&ContentBlock::Image {
+            media_type: "image/jpeg".to_string(),
+            data: "AAAA".to_string(),
+        });
+
+        assert_eq!(block["type"], "image");
+        assert_eq!(block["source"]["type"], "base64");
+        assert_eq!(block["source"]["media_type"], "image/jpeg");
+        assert_eq!(block["source"]["data"], "AAAA");
+    }
+
     #[test]
     fn test_build_request_body_with_tools() {
         use crate::llm::ToolDefinition;
 
         let client = AnthropicClient {
             model: "claude-sonnet-4".to_string(),
-            api_key: "test-key".to_string(),
+            api_key: Credential::fixed("test-key"),
             base_url: "https://api.anthropic.com".to_string(),
             http: Client::new(),
             max_tokens: 8192,
+            context: ContextGuard::new(None, Default::default()),
+            rate_limits: Mutex::new(None),
             timeout: Duration::from_secs(300),
         };
 
@@ -530,10 +647,12 @@ mod tests {
     fn test_max_tokens_capped() {
         let client = AnthropicClient {
             model: "claude-sonnet-4".to_string(),
-            api_key: "test-key".to_string(),
+            api_key: Credential::fixed("test-key"),
             base_url: "https://api.anthropic.com".to_string(),
             http: Client::new(),
             max_tokens: 1000, // Client configured with 1000 max
+            context: ContextGuard::new(None, Default::default()),
+            rate_limits: Mutex::new(None),
             timeout: Duration::from_secs(300),
         };
 
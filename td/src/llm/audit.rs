@@ -0,0 +1,503 @@
+//! Audit log of LLM requests and responses
+//!
+//! When `llm.audit.enabled` is set, every client returned by
+//! [`create_client_from_resolved`](super::create_client_from_resolved) is wrapped
+//! in an [`AuditedClient`] that appends one [`AuditRecord`] per call to
+//! `~/.taskdaemon/audit/audit-YYYY-MM-DD.jsonl`. Records hold the provider,
+//! model, token counts, timing and SHA-256 hashes of the request and response
+//! as sent and received; the bodies themselves are stored only when
+//! `include-bodies` is set, and only after every string in them has been run
+//! through the configured redaction patterns. Embedding calls are recorded
+//! the same way, with the input texts as the request and the vector count and
+//! size as the response. `td audit export --since` streams the records back out.
+
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use eyre::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+use super::client::Capabilities;
+use super::{CompletionRequest, CompletionResponse, LlmClient, LlmError, RateLimitStatus, StreamChunk, TokenUsage};
+use crate::config::AuditConfig;
+
+/// Replacement for redacted text
+pub const REDACTED: &str = "[REDACTED]";
+
+/// One audited LLM call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    pub provider: String,
+    pub model: String,
+    pub streamed: bool,
+    #[serde(rename = "duration-ms")]
+    pub duration_ms: u64,
+    #[serde(rename = "input-tokens")]
+    pub input_tokens: u64,
+    #[serde(rename = "output-tokens")]
+    pub output_tokens: u64,
+    #[serde(rename = "cache-read-tokens")]
+    pub cache_read_tokens: u64,
+    #[serde(rename = "cache-creation-tokens")]
+    pub cache_creation_tokens: u64,
+    /// SHA-256 of the unredacted request body
+    #[serde(rename = "request-sha256")]
+    pub request_sha256: String,
+    /// SHA-256 of the unredacted response body (None if the call failed)
+    #[serde(rename = "response-sha256", skip_serializing_if = "Option::is_none")]
+    pub response_sha256: Option<String>,
+    #[serde(rename = "stop-reason", skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<String>,
+    /// Redacted error message if the call failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Redacted request body (only with `include-bodies`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request: Option<Value>,
+    /// Redacted response body (only with `include-bodies`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<Value>,
+}
+
+/// Compiled redaction patterns
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    patterns: Vec<Regex>,
+}
+
+impl Redactor {
+    /// Compile the patterns, failing on the first invalid one
+    pub fn new(patterns: &[String]) -> Result<Self> {
+        debug!(count = patterns.len(), "Redactor::new: called");
+        let patterns = patterns
+            .iter()
+            .map(|p| Regex::new(p).with_context(|| format!("Invalid redaction pattern '{}'", p)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { patterns })
+    }
+
+    /// Replace every match in `text`
+    pub fn redact(&self, text: &str) -> String {
+        self.patterns.iter().fold(text.to_string(), |acc, re| {
+            if re.is_match(&acc) {
+                re.replace_all(&acc, REDACTED).into_owned()
+            } else {
+                acc
+            }
+        })
+    }
+
+    /// Redact every string (keys untouched) in a JSON value
+    pub fn redact_value(&self, value: &mut Value) {
+        match value {
+            Value::String(s) => *s = self.redact(s),
+            Value::Array(items) => items.iter_mut().for_each(|v| self.redact_value(v)),
+            Value::Object(map) => map.values_mut().for_each(|v| self.redact_value(v)),
+            _ => {}
+        }
+    }
+}
+
+/// Append-only store of audit records, one file per UTC day
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    dir: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        debug!(?dir, "AuditLog::new: called");
+        Self { dir }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn file_for(&self, day: NaiveDate) -> PathBuf {
+        self.dir.join(format!("audit-{}.jsonl", day.format("%Y-%m-%d")))
+    }
+
+    /// Append a record to its day's file
+    pub fn append(&self, record: &AuditRecord) -> Result<()> {
+        debug!(id = %record.id, "AuditLog::append: called");
+        fs::create_dir_all(&self.dir).with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        let path = self.file_for(record.timestamp.date_naive());
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        // One write per record so concurrent appenders never interleave lines
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut f| f.write_all(line.as_bytes()))
+            .with_context(|| format!("Failed to append to {}", path.display()))
+    }
+
+    /// All records at or after `since`, oldest first
+    pub fn read_since(&self, since: DateTime<Utc>) -> Result<Vec<AuditRecord>> {
+        debug!(%since, "AuditLog::read_since: called");
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let first_day = since.date_naive();
+        let mut files: Vec<(NaiveDate, PathBuf)> = fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter_map(|path| {
+                let name = path.file_name()?.to_str()?;
+                let day = name.strip_prefix("audit-")?.strip_suffix(".jsonl")?;
+                let day = NaiveDate::parse_from_str(day, "%Y-%m-%d").ok()?;
+                (day >= first_day).then_some((day, path))
+            })
+            .collect();
+        files.sort();
+
+        let mut records = Vec::new();
+        for (_, path) in files {
+            let file = fs::File::open(&path).with_context(|| format!("Failed to open {}", path.display()))?;
+            for line in BufReader::new(file).lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str::<AuditRecord>(&line) {
+                    Ok(record) if record.timestamp >= since => records.push(record),
+                    Ok(_) => {}
+                    Err(e) => warn!(path = %path.display(), error = %e, "AuditLog::read_since: skipping bad line"),
+                }
+            }
+        }
+        records.sort_by_key(|r| r.timestamp);
+        Ok(records)
+    }
+}
+
+/// Parse `--since`: RFC 3339, a date (`2026-01-31`), or a relative age (`30m`, `12h`, `7d`)
+pub fn parse_since(s: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    debug!(%s, "parse_since: called");
+    if let Ok(ts) = DateTime::parse_from_rfc3339(s) {
+        return Ok(ts.with_timezone(&Utc));
+    }
+    if let Ok(day) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Ok(day.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+    let invalid = || eyre::eyre!("Invalid --since '{}'. Use RFC 3339, YYYY-MM-DD, or 30m/12h/7d", s);
+    let unit = s.chars().last().ok_or_else(invalid)?;
+    let amount: i64 = s[..s.len() - unit.len_utf8()].parse().map_err(|_| invalid())?;
+    let age = match unit {
+        'm' => chrono::Duration::minutes(amount),
+        'h' => chrono::Duration::hours(amount),
+        'd' => chrono::Duration::days(amount),
+        _ => return Err(eyre::eyre!("Invalid --since unit in '{}'. Use m, h, or d", s)),
+    };
+    Ok(now - age)
+}
+
+fn sha256_hex(value: &Value) -> String {
+    format!("{:x}", Sha256::digest(value.to_string().as_bytes()))
+}
+
+fn request_body(request: &CompletionRequest) -> Value {
+    json!({
+        "system": request.system_prompt,
+        "messages": request.messages,
+        "tools": request.tools,
+        "max-tokens": request.max_tokens,
+    })
+}
+
+/// Response body, usage and stop reason of a completion
+fn completion_outcome(response: &CompletionResponse) -> (Value, &TokenUsage, Option<String>) {
+    let body = json!({
+        "content": response.content,
+        "tool-calls": response
+            .tool_calls
+            .iter()
+            .map(|c| json!({ "id": c.id, "name": c.name, "input": c.input }))
+            .collect::<Vec<_>>(),
+        "stop-reason": format!("{:?}", response.stop_reason),
+    });
+    (body, &response.usage, Some(format!("{:?}", response.stop_reason)))
+}
+
+/// LLM client decorator that records every call in the audit log
+pub struct AuditedClient {
+    inner: Arc<dyn LlmClient>,
+    provider: String,
+    model: String,
+    include_bodies: bool,
+    redactor: Redactor,
+    log: AuditLog,
+}
+
+impl AuditedClient {
+    pub fn new(
+        inner: Arc<dyn LlmClient>,
+        provider: impl Into<String>,
+        model: impl Into<String>,
+        config: &AuditConfig,
+    ) -> Result<Self> {
+        let provider = provider.into();
+        let model = model.into();
+        debug!(%provider, %model, include_bodies = config.include_bodies, "AuditedClient::new: called");
+        Ok(Self {
+            inner,
+            provider,
+            model,
+            include_bodies: config.include_bodies,
+            redactor: Redactor::new(&config.redact)?,
+            log: AuditLog::new(config.dir()?),
+        })
+    }
+
+    /// Write one record; `response` is the response body, token usage and stop reason
+    fn record(
+        &self,
+        request: Value,
+        response: Result<(Value, &TokenUsage, Option<String>), &LlmError>,
+        streamed: bool,
+        started: (DateTime<Utc>, Instant),
+    ) {
+        debug!(streamed, "AuditedClient::record: called");
+        let mut record = AuditRecord {
+            id: uuid::Uuid::now_v7().to_string(),
+            timestamp: started.0,
+            provider: self.provider.clone(),
+            model: self.model.clone(),
+            streamed,
+            duration_ms: started.1.elapsed().as_millis() as u64,
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
+            request_sha256: sha256_hex(&request),
+            response_sha256: None,
+            stop_reason: None,
+            error: None,
+            request: None,
+            response: None,
+        };
+        match response {
+            Ok((body, usage, stop_reason)) => {
+                record.input_tokens = usage.input_tokens;
+                record.output_tokens = usage.output_tokens;
+                record.cache_read_tokens = usage.cache_read_tokens;
+                record.cache_creation_tokens = usage.cache_creation_tokens;
+                record.response_sha256 = Some(sha256_hex(&body));
+                record.stop_reason = stop_reason;
+                if self.include_bodies {
+                    let mut body = body;
+                    self.redactor.redact_value(&mut body);
+                    record.response = Some(body);
+                }
+            }
+            Err(e) => record.error = Some(self.redactor.redact(&e.to_string())),
+        }
+        if self.include_bodies {
+            let mut request = request;
+            self.redactor.redact_value(&mut request);
+            record.request = Some(request);
+        }
+        if let Err(e) = self.log.append(&record) {
+            warn!(error = %e, "AuditedClient::record: failed to write audit record");
+        }
+    }
+}
+
+#[async_trait]
+impl LlmClient for AuditedClient {
+    async fn complete(&self, request: CompletionRequest) -> Result<CompletionResponse, LlmError> {
+        debug!("AuditedClient::complete: called");
+        let body = request_body(&request);
+        let started = (Utc::now(), Instant::now());
+        let result = self.inner.complete(request).await;
+        self.record(body, result.as_ref().map(completion_outcome), false, started);
+        result
+    }
+
+    async fn stream(
+        &self,
+        request: CompletionRequest,
+        chunk_tx: mpsc::Sender<StreamChunk>,
+    ) -> Result<CompletionResponse, LlmError> {
+        debug!("AuditedClient::stream: called");
+        let body = request_body(&request);
+        let started = (Utc::now(), Instant::now());
+        let result = self.inner.stream(request, chunk_tx).await;
+        self.record(body, result.as_ref().map(completion_outcome), true, started);
+        result
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    fn rate_limits(&self) -> Option<RateLimitStatus> {
+        self.inner.rate_limits()
+    }
+
+    fn reload_credentials(&self) -> Result<bool, LlmError> {
+        self.inner.reload_credentials()
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        debug!(count = texts.len(), "AuditedClient::embed: called");
+        let body = json!({ "input": texts });
+        let started = (Utc::now(), Instant::now());
+        let result = self.inner.embed(texts).await;
+        let usage = TokenUsage::default();
+        let outcome = result.as_ref().map(|vectors| {
+            let summary = json!({
+                "vectors": vectors.len(),
+                "dimensions": vectors.first().map_or(0, Vec::len),
+            });
+            (summary, &usage, None)
+        });
+        self.record(body, outcome, false, started);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::client::mock::MockLlmClient;
+    use crate::llm::{Message, StopReason};
+    use tempfile::TempDir;
+
+    fn config(dir: &Path, include_bodies: bool) -> AuditConfig {
+        AuditConfig {
+            enabled: true,
+            include_bodies,
+            dir: Some(dir.to_string_lossy().into_owned()),
+            ..Default::default()
+        }
+    }
+
+    fn response(text: &str) -> CompletionResponse {
+        CompletionResponse {
+            content: Some(text.to_string()),
+            tool_calls: vec![],
+            stop_reason: StopReason::EndTurn,
+            usage: TokenUsage {
+                input_tokens: 120,
+                output_tokens: 30,
+                ..Default::default()
+            },
+            served_by: None,
+        }
+    }
+
+    fn request(text: &str) -> CompletionRequest {
+        CompletionRequest {
+            system_prompt: "You are helpful".to_string(),
+            messages: vec![Message::user(text)],
+            tools: vec![],
+            max_tokens: 100,
+        }
+    }
+
+    #[test]
+    fn test_default_patterns_redact_keys_and_emails() {
+        let redactor = Redactor::new(&AuditConfig::default().redact).unwrap();
+        let text = "key sk-ant-REDACTED, mail jane.doe@example.com, Bearer abcdefghijklmnopqrst";
+        let redacted = redactor.redact(text);
+        assert!(!redacted.contains("sk-ant"));
+        assert!(!redacted.contains("jane.doe"));
+        assert!(!redacted.contains("abcdefghijklmnopqrst"));
+        assert_eq!(redacted.matches(REDACTED).count(), 3);
+        assert!(Redactor::new(&["(unclosed".to_string()]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_audited_client_records_redacted_bodies() {
+        let temp = TempDir::new().unwrap();
+        let inner = Arc::new(MockLlmClient::new(vec![response("reply to ops@example.com")]));
+        let client = AuditedClient::new(inner, "anthropic", "claude-test", &config(temp.path(), true)).unwrap();
+
+        let result = client.complete(request("my key is sk-abcdefghijklmnopqrstuvwx")).await;
+        assert!(result.is_ok());
+
+        let records = AuditLog::new(temp.path()).read_since(DateTime::UNIX_EPOCH).unwrap();
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.provider, "anthropic");
+        assert_eq!(record.input_tokens, 120);
+        assert_eq!(record.output_tokens, 30);
+        assert_eq!(record.request_sha256.len(), 64);
+        assert!(record.response_sha256.is_some());
+
+        let raw = fs::read_to_string(AuditLog::new(temp.path()).file_for(record.timestamp.date_naive())).unwrap();
+        assert!(!raw.contains("sk-abcdefghijklmnopqrstuvwx"));
+        assert!(!raw.contains("ops@example.com"));
+        assert!(raw.contains(REDACTED));
+    }
+
+    #[tokio::test]
+    async fn test_audited_client_omits_bodies_and_records_errors() {
+        let temp = TempDir::new().unwrap();
+        let inner = Arc::new(MockLlmClient::new(vec![response("ok")]));
+        let client = AuditedClient::new(inner, "openai", "gpt-test", &config(temp.path(), false)).unwrap();
+
+        client.complete(request("first")).await.unwrap();
+        assert!(client.complete(request("second")).await.is_err());
+
+        let records = AuditLog::new(temp.path()).read_since(DateTime::UNIX_EPOCH).unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(records.iter().all(|r| r.request.is_none() && r.response.is_none()));
+        assert_ne!(records[0].request_sha256, records[1].request_sha256);
+        assert!(records[1].error.is_some());
+        assert!(records[1].response_sha256.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_audited_client_records_embeddings() {
+        let temp = TempDir::new().unwrap();
+        let inner = Arc::new(crate::llm::LocalClient::new());
+        let client = AuditedClient::new(inner, "local", "", &config(temp.path(), true)).unwrap();
+        assert!(client.capabilities().embeddings);
+
+        let vectors = client.embed(&["contact bob@example.com".to_string()]).await.unwrap();
+        assert_eq!(vectors.len(), 1);
+
+        let records = AuditLog::new(temp.path()).read_since(DateTime::UNIX_EPOCH).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(
+            records[0].request,
+            Some(json!({ "input": [format!("contact {}", REDACTED)] }))
+        );
+        assert_eq!(records[0].response.as_ref().unwrap()["vectors"], 1);
+    }
+
+    #[test]
+    fn test_parse_since() {
+        let now = DateTime::parse_from_rfc3339("2026-03-10T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(parse_since("2h", now).unwrap(), now - chrono::Duration::hours(2));
+        assert_eq!(parse_since("7d", now).unwrap(), now - chrono::Duration::days(7));
+        assert_eq!(
+            parse_since("2026-03-01", now).unwrap().to_rfc3339(),
+            "2026-03-01T00:00:00+00:00"
+        );
+        assert_eq!(
+            parse_since("2026-03-09T08:30:00Z", now).unwrap().to_rfc3339(),
+            "2026-03-09T08:30:00+00:00"
+        );
+        assert!(parse_since("yesterday", now).is_err());
+        assert!(parse_since("5w", now).is_err());
+    }
+}
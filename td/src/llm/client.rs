@@ -2,10 +2,27 @@
 
 use async_trait::async_trait;
 use tokio::sync::mpsc;
-#[allow(unused_imports)]
 use tracing::debug;
 
-use super::{CompletionRequest, CompletionResponse, LlmError, StreamChunk};
+use super::{CompletionRequest, CompletionResponse, LlmError, RateLimitStatus, StreamChunk};
+
+/// What a client can do, so callers can check before relying on a feature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// `complete` and `stream` produce responses
+    pub completions: bool,
+    /// `embed` produces vectors
+    pub embeddings: bool,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            completions: true,
+            embeddings: false,
+        }
+    }
+}
 
 /// Stateless LLM client - each call is independent (fresh context)
 ///
@@ -30,26 +47,117 @@ pub trait LlmClient: Send + Sync {
         request: CompletionRequest,
         chunk_tx: mpsc::Sender<StreamChunk>,
     ) -> Result<CompletionResponse, LlmError>;
+
+    /// Features this client supports
+    ///
+    /// Defaults to completions only.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
+
+    /// Rate limit status from the most recent provider response
+    ///
+    /// None when the provider sends no rate limit headers (the default).
+    fn rate_limits(&self) -> Option<RateLimitStatus> {
+        None
+    }
+
+    /// Re-read the API key from its configured source; true if it changed
+    ///
+    /// Clients without a key (the default) have nothing to reload.
+    fn reload_credentials(&self) -> Result<bool, LlmError> {
+        Ok(false)
+    }
+
+    /// Embed each text, returning one vector per input in the same order
+    ///
+    /// Only available when `capabilities().embeddings` is true.
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        debug!(count = texts.len(), "LlmClient::embed: not supported by this client");
+        Err(LlmError::Unsupported(
+            "embeddings are not supported by this provider".to_string(),
+        ))
+    }
 }
 
-#[cfg(test)]
+/// Mock LLM client
+///
+/// Replays scripted responses in unit tests. A `simulated` client instead
+/// answers every request with a short end-turn response, after an injected
+/// latency and with a fraction of calls failing as 429s, so `td loadtest` can
+/// exercise the orchestration without touching a real provider.
 pub mod mock {
     use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::sync::Mutex;
     use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
     use tracing::debug;
 
-    /// Mock LLM client for unit tests
+    use crate::llm::{StopReason, TokenUsage};
+
+    /// Mock LLM client for unit tests and load tests
     pub struct MockLlmClient {
         responses: Vec<CompletionResponse>,
+        simulated: bool,
+        latency: Duration,
+        jitter: Duration,
+        rate_limit_ratio: f64,
+        retry_after: Duration,
+        rng: Mutex<StdRng>,
         call_count: AtomicUsize,
+        rate_limited_count: AtomicUsize,
     }
 
     impl MockLlmClient {
+        /// Replay `responses` in order, then fail
         pub fn new(responses: Vec<CompletionResponse>) -> Self {
             debug!(response_count = %responses.len(), "MockLlmClient::new: called");
             Self {
                 responses,
+                simulated: false,
+                latency: Duration::ZERO,
+                jitter: Duration::ZERO,
+                rate_limit_ratio: 0.0,
+                retry_after: Duration::from_secs(1),
+                rng: Mutex::new(StdRng::seed_from_u64(0)),
                 call_count: AtomicUsize::new(0),
+                rate_limited_count: AtomicUsize::new(0),
+            }
+        }
+
+        /// Answer every request with a short end-turn response
+        pub fn simulated() -> Self {
+            debug!("MockLlmClient::simulated: called");
+            Self {
+                simulated: true,
+                ..Self::new(vec![])
+            }
+        }
+
+        /// Builder: delay each call by `latency` plus up to `jitter`
+        pub fn with_latency(mut self, latency: Duration, jitter: Duration) -> Self {
+            debug!(?latency, ?jitter, "MockLlmClient::with_latency: called");
+            self.latency = latency;
+            self.jitter = jitter;
+            self
+        }
+
+        /// Builder: fail `ratio` (0.0-1.0) of calls with a 429 asking to retry after `retry_after`
+        pub fn with_rate_limits(mut self, ratio: f64, retry_after: Duration) -> Self {
+            debug!(ratio, ?retry_after, "MockLlmClient::with_rate_limits: called");
+            self.rate_limit_ratio = ratio.clamp(0.0, 1.0);
+            self.retry_after = retry_after;
+            self
+        }
+
+        /// Builder: seed the jitter and 429 injection so runs are repeatable
+        pub fn with_seed(self, seed: u64) -> Self {
+            debug!(seed, "MockLlmClient::with_seed: called");
+            Self {
+                rng: Mutex::new(StdRng::seed_from_u64(seed)),
+                ..self
             }
         }
 
@@ -57,6 +165,38 @@ pub mod mock {
             debug!("MockLlmClient::call_count: called");
             self.call_count.load(Ordering::SeqCst)
         }
+
+        /// Calls that were failed with an injected 429
+        pub fn rate_limited_count(&self) -> usize {
+            debug!("MockLlmClient::rate_limited_count: called");
+            self.rate_limited_count.load(Ordering::SeqCst)
+        }
+
+        /// Latency for the next call, and whether to fail it with a 429
+        fn draw(&self) -> (Duration, bool) {
+            let mut rng = self.rng.lock().unwrap_or_else(|e| e.into_inner());
+            let jitter = if self.jitter.is_zero() {
+                Duration::ZERO
+            } else {
+                self.jitter.mul_f64(rng.random::<f64>())
+            };
+            let rate_limited = self.rate_limit_ratio > 0.0 && rng.random::<f64>() < self.rate_limit_ratio;
+            (self.latency + jitter, rate_limited)
+        }
+
+        fn simulated_response() -> CompletionResponse {
+            CompletionResponse {
+                content: Some("Made the requested change; validation should pass now.".to_string()),
+                tool_calls: vec![],
+                stop_reason: StopReason::EndTurn,
+                usage: TokenUsage {
+                    input_tokens: 1200,
+                    output_tokens: 150,
+                    ..Default::default()
+                },
+                served_by: None,
+            }
+        }
     }
 
     #[async_trait]
@@ -64,6 +204,21 @@ pub mod mock {
         async fn complete(&self, _request: CompletionRequest) -> Result<CompletionResponse, LlmError> {
             debug!("MockLlmClient::complete: called");
             let idx = self.call_count.fetch_add(1, Ordering::SeqCst);
+            let (delay, rate_limited) = self.draw();
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
+            }
+            if rate_limited {
+                debug!(%idx, "MockLlmClient::complete: injecting 429");
+                self.rate_limited_count.fetch_add(1, Ordering::SeqCst);
+                return Err(LlmError::RateLimited {
+                    retry_after: self.retry_after,
+                });
+            }
+            if self.simulated {
+                debug!(%idx, "MockLlmClient::complete: simulated response");
+                return Ok(Self::simulated_response());
+            }
             debug!(%idx, "MockLlmClient::complete: fetching response");
             self.responses.get(idx).cloned().ok_or_else(|| {
                 debug!("MockLlmClient::complete: no more mock responses");
@@ -74,18 +229,25 @@ pub mod mock {
         async fn stream(
             &self,
             request: CompletionRequest,
-            _chunk_tx: mpsc::Sender<StreamChunk>,
+            chunk_tx: mpsc::Sender<StreamChunk>,
         ) -> Result<CompletionResponse, LlmError> {
             debug!("MockLlmClient::stream: called");
-            // For mock, just return complete response without streaming
-            self.complete(request).await
+            let response = self.complete(request).await?;
+            // Simulated responses stream word by word so token events flow like a real provider's
+            if self.simulated
+                && let Some(content) = &response.content
+            {
+                for word in content.split_inclusive(' ') {
+                    let _ = chunk_tx.send(StreamChunk::TextDelta(word.to_string())).await;
+                }
+            }
+            Ok(response)
         }
     }
 
     #[cfg(test)]
     mod tests {
         use super::*;
-        use crate::llm::{StopReason, TokenUsage};
 
         #[tokio::test]
         async fn test_mock_client_returns_responses() {
@@ -95,12 +257,14 @@ pub mod mock {
                     tool_calls: vec![],
                     stop_reason: StopReason::EndTurn,
                     usage: TokenUsage::default(),
+                    served_by: None,
                 },
                 CompletionResponse {
                     content: Some("Response 2".to_string()),
                     tool_calls: vec![],
                     stop_reason: StopReason::EndTurn,
                     usage: TokenUsage::default(),
+                    served_by: None,
                 },
             ];
 
@@ -122,6 +286,47 @@ pub mod mock {
             assert_eq!(client.call_count(), 2);
         }
 
+        #[tokio::test]
+        async fn test_default_capabilities_reject_embed() {
+            let client = MockLlmClient::new(vec![]);
+            assert!(!client.capabilities().embeddings);
+
+            let result = client.embed(&["hello".to_string()]).await;
+            assert!(matches!(result, Err(LlmError::Unsupported(_))));
+        }
+
+        #[tokio::test]
+        async fn test_simulated_client_injects_rate_limits() {
+            let client = MockLlmClient::simulated()
+                .with_rate_limits(0.5, Duration::from_millis(10))
+                .with_seed(7);
+            let req = CompletionRequest {
+                system_prompt: "Test".to_string(),
+                messages: vec![],
+                tools: vec![],
+                max_tokens: 1000,
+            };
+
+            let mut ok = 0;
+            for _ in 0..40 {
+                match client.complete(req.clone()).await {
+                    Ok(resp) => {
+                        assert_eq!(resp.stop_reason, StopReason::EndTurn);
+                        ok += 1;
+                    }
+                    Err(e) => assert!(e.is_rate_limit()),
+                }
+            }
+            assert_eq!(client.call_count(), 40);
+            assert_eq!(ok + client.rate_limited_count(), 40);
+            assert!(ok > 0 && client.rate_limited_count() > 0);
+
+            let (tx, mut rx) = mpsc::channel(64);
+            let client = MockLlmClient::simulated();
+            client.stream(req, tx).await.unwrap();
+            assert!(matches!(rx.recv().await, Some(StreamChunk::TextDelta(_))));
+        }
+
         #[tokio::test]
         async fn test_mock_client_errors_when_exhausted() {
             let client = MockLlmClient::new(vec![]);
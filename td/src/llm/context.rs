@@ -0,0 +1,328 @@
+//! Context window guardrails
+//!
+//! Clients estimate the size of every request before sending it and compare
+//! it, plus the room reserved for the response, against the model's context
+//! window. An oversized request is either cut down per the configured
+//! [`ContextStrategy`] or refused with [`LlmError::ContextTooLarge`], rather
+//! than being sent only to come back as an opaque 400.
+//!
+//! The estimate is deliberately rough (about four characters per token, a
+//! flat cost per image) and errs towards overestimating.
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use super::{CompletionRequest, ContentBlock, LlmError, MessageContent, Role};
+use crate::config::ResolvedLlmConfig;
+
+/// Tokens charged for each image (the providers' cost for a large image)
+const IMAGE_TOKENS: u32 = 1_600;
+
+/// Tokens charged for each message on top of its content (role, separators)
+const MESSAGE_OVERHEAD_TOKENS: u32 = 4;
+
+/// Characters kept from the start of a tool result when truncating it
+const TRUNCATED_TOOL_RESULT_CHARS: usize = 2_000;
+
+/// Known context windows, matched by model name prefix (most specific first)
+const CONTEXT_WINDOWS: &[(&str, u32)] = &[
+    ("claude-", 200_000),
+    ("gpt-4.1", 1_047_576),
+    ("gpt-4o", 128_000),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-4", 8_192),
+    ("gpt-3.5-turbo", 16_385),
+    ("gpt-5", 400_000),
+    ("o1-mini", 128_000),
+    ("o1-preview", 128_000),
+    ("o1", 200_000),
+    ("o3", 200_000),
+    ("o4-mini", 200_000),
+];
+
+/// Context window of a known model, in tokens
+///
+/// Accepts routed names such as `anthropic/claude-sonnet-4` by ignoring
+/// everything up to the last `/`. Returns None for unknown models, which are
+/// not checked unless `context-window` is configured.
+pub fn context_window(model: &str) -> Option<u32> {
+    debug!(%model, "context_window: called");
+    let name = model.rsplit('/').next().unwrap_or(model);
+    CONTEXT_WINDOWS
+        .iter()
+        .find(|(prefix, _)| name.starts_with(prefix))
+        .map(|(_, limit)| *limit)
+}
+
+fn text_tokens(text: &str) -> u32 {
+    text.chars().count().div_ceil(4) as u32
+}
+
+fn block_tokens(block: &ContentBlock) -> u32 {
+    match block {
+        ContentBlock::Text { text } => text_tokens(text),
+        ContentBlock::ToolUse { name, input, .. } => text_tokens(name) + text_tokens(&input.to_string()),
+        ContentBlock::ToolResult { content, .. } => text_tokens(content),
+        ContentBlock::Image { .. } => IMAGE_TOKENS,
+    }
+}
+
+/// Estimated input tokens of a request
+pub fn estimate_tokens(request: &CompletionRequest) -> u32 {
+    let system = text_tokens(&request.system_prompt);
+    let tools: u32 = request
+        .tools
+        .iter()
+        .map(|t| text_tokens(&t.name) + text_tokens(&t.description) + text_tokens(&t.input_schema.to_string()))
+        .sum();
+    let messages: u32 = request
+        .messages
+        .iter()
+        .map(|m| {
+            MESSAGE_OVERHEAD_TOKENS
+                + match &m.content {
+                    MessageContent::Text(text) => text_tokens(text),
+                    MessageContent::Blocks(blocks) => blocks.iter().map(block_tokens).sum(),
+                }
+        })
+        .sum();
+    system + tools + messages
+}
+
+/// What to do with a request that does not fit the context window
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ContextStrategy {
+    /// Refuse the request with `LlmError::ContextTooLarge`
+    #[default]
+    Error,
+    /// Drop the oldest assistant/user exchanges after the first message
+    DropOldestTurns,
+    /// Cut tool results down, oldest first
+    TruncateToolResults,
+}
+
+impl std::str::FromStr for ContextStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        debug!(%s, "ContextStrategy::from_str: called");
+        match s {
+            "error" => Ok(Self::Error),
+            "drop-oldest-turns" => Ok(Self::DropOldestTurns),
+            "truncate-tool-results" => Ok(Self::TruncateToolResults),
+            _ => Err(format!(
+                "Unknown context strategy: {}. Use: error, drop-oldest-turns, or truncate-tool-results",
+                s
+            )),
+        }
+    }
+}
+
+/// Pre-flight size check held by each client
+#[derive(Debug, Clone, Copy)]
+pub struct ContextGuard {
+    limit: Option<u32>,
+    strategy: ContextStrategy,
+}
+
+impl ContextGuard {
+    pub fn new(limit: Option<u32>, strategy: ContextStrategy) -> Self {
+        debug!(?limit, ?strategy, "ContextGuard::new: called");
+        Self { limit, strategy }
+    }
+
+    /// Guard for a resolved model: the configured window, else the known one
+    pub fn from_config(config: &ResolvedLlmConfig) -> Self {
+        debug!(model = %config.model, "ContextGuard::from_config: called");
+        let limit = config.context_window.or_else(|| context_window(&config.model));
+        Self::new(limit, config.context_strategy)
+    }
+
+    /// Context window in tokens, if known
+    pub fn limit(&self) -> Option<u32> {
+        self.limit
+    }
+
+    /// Return a request that fits, leaving room for its response
+    ///
+    /// The response may use `request.max_tokens`, capped at the client's `max_tokens`.
+    pub fn check(&self, mut request: CompletionRequest, max_tokens: u32) -> Result<CompletionRequest, LlmError> {
+        let Some(limit) = self.limit else {
+            debug!("ContextGuard::check: no known limit, skipping");
+            return Ok(request);
+        };
+        let max_output = request.max_tokens.min(max_tokens);
+        let fits = |request: &CompletionRequest| estimate_tokens(request).saturating_add(max_output) <= limit;
+        if fits(&request) {
+            return Ok(request);
+        }
+
+        debug!(?self.strategy, limit, "ContextGuard::check: request over limit");
+        match self.strategy {
+            ContextStrategy::Error => {}
+            ContextStrategy::DropOldestTurns => {
+                // Drop whole assistant/user exchanges so tool calls stay paired with their results
+                while !fits(&request)
+                    && request.messages.len() > 3
+                    && request.messages[1].role == Role::Assistant
+                    && request.messages[2].role == Role::User
+                {
+                    request.messages.drain(1..3);
+                }
+            }
+            ContextStrategy::TruncateToolResults => {
+                let mut index = 0;
+                while !fits(&request) && index < request.messages.len() {
+                    if let MessageContent::Blocks(blocks) = &mut request.messages[index].content {
+                        for block in blocks.iter_mut() {
+                            if let ContentBlock::ToolResult { content, .. } = block {
+                                truncate_tool_result(content);
+                            }
+                        }
+                    }
+                    index += 1;
+                }
+            }
+        }
+
+        let estimated = estimate_tokens(&request).saturating_add(max_output);
+        if estimated > limit {
+            return Err(LlmError::ContextTooLarge { estimated, limit });
+        }
+        warn!(?self.strategy, estimated, limit, "ContextGuard::check: request cut down to fit the context window");
+        Ok(request)
+    }
+}
+
+fn truncate_tool_result(content: &mut String) {
+    let Some((cut, _)) = content.char_indices().nth(TRUNCATED_TOOL_RESULT_CHARS) else {
+        return;
+    };
+    let removed = content[cut..].chars().count();
+    content.truncate(cut);
+    content.push_str(&format!(
+        "\n[... {} characters truncated to fit the context window ...]",
+        removed
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::Message;
+
+    fn exchange(id: &str, output: &str) -> [Message; 2] {
+        [
+            Message::assistant_blocks(vec![ContentBlock::ToolUse {
+                id: id.to_string(),
+                name: "read_file".to_string(),
+                input: serde_json::json!({ "path": "src/main.rs" }),
+            }]),
+            Message::user_blocks(vec![ContentBlock::tool_result(id, output, false)]),
+        ]
+    }
+
+    fn request(messages: Vec<Message>) -> CompletionRequest {
+        CompletionRequest {
+            system_prompt: "You are helpful".to_string(),
+            messages,
+            tools: vec![],
+            max_tokens: 1_000,
+        }
+    }
+
+    fn long_conversation() -> CompletionRequest {
+        let mut messages = vec![Message::user("Fix the bug")];
+        for i in 0..4 {
+            messages.extend(exchange(&format!("call-{}", i), &"x".repeat(20_000)));
+        }
+        request(messages)
+    }
+
+    #[test]
+    fn test_context_window_registry() {
+        assert_eq!(context_window("claude-sonnet-4-20250514"), Some(200_000));
+        assert_eq!(context_window("gpt-4o-mini"), Some(128_000));
+        assert_eq!(context_window("gpt-4"), Some(8_192));
+        assert_eq!(context_window("o1-mini"), Some(128_000));
+        assert_eq!(context_window("openai/gpt-4.1"), Some(1_047_576));
+        assert_eq!(context_window("llama3"), None);
+    }
+
+    #[test]
+    fn test_estimate_tokens_counts_all_parts() {
+        let small = request(vec![Message::user("abcd")]);
+        let mut large = small.clone();
+        large.messages.push(Message::user_blocks(vec![ContentBlock::Image {
+            media_type: "image/png".to_string(),
+            data: String::new(),
+        }]));
+        assert_eq!(
+            estimate_tokens(&large) - estimate_tokens(&small),
+            IMAGE_TOKENS + MESSAGE_OVERHEAD_TOKENS
+        );
+    }
+
+    #[test]
+    fn test_check_errors_by_default() {
+        let guard = ContextGuard::new(Some(10_000), ContextStrategy::Error);
+        let err = guard.check(long_conversation(), 1_000).unwrap_err();
+        assert!(matches!(err, LlmError::ContextTooLarge { estimated, limit: 10_000 } if estimated > 10_000));
+        assert!(!err.is_retryable());
+
+        let unknown = ContextGuard::new(None, ContextStrategy::Error);
+        assert!(unknown.check(long_conversation(), 1_000).is_ok());
+    }
+
+    #[test]
+    fn test_drop_oldest_turns_keeps_first_message_and_pairs() {
+        let guard = ContextGuard::new(Some(8_000), ContextStrategy::DropOldestTurns);
+        let trimmed = guard.check(long_conversation(), 1_000).unwrap();
+
+        assert_eq!(trimmed.messages.len(), 3);
+        assert_eq!(trimmed.messages[0].content.as_text(), Some("Fix the bug"));
+        assert_eq!(trimmed.messages[1].role, Role::Assistant);
+        assert!(matches!(
+            &trimmed.messages[2].content,
+            MessageContent::Blocks(blocks) if matches!(&blocks[0], ContentBlock::ToolResult { tool_use_id, .. } if tool_use_id == "call-3")
+        ));
+
+        let too_small = ContextGuard::new(Some(2_000), ContextStrategy::DropOldestTurns);
+        assert!(matches!(
+            too_small.check(long_conversation(), 1_000),
+            Err(LlmError::ContextTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_truncate_tool_results_oldest_first() {
+        let guard = ContextGuard::new(Some(15_000), ContextStrategy::TruncateToolResults);
+        let trimmed = guard.check(long_conversation(), 1_000).unwrap();
+
+        let lengths: Vec<usize> = trimmed
+            .messages
+            .iter()
+            .filter_map(|m| match &m.content {
+                MessageContent::Blocks(blocks) => match &blocks[0] {
+                    ContentBlock::ToolResult { content, .. } => Some(content.len()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        assert!(lengths[0] < 3_000);
+        assert!(lengths[1] < 3_000);
+        assert_eq!(lengths[3], 20_000);
+        assert!(estimate_tokens(&trimmed) + 1_000 <= 15_000);
+    }
+
+    #[test]
+    fn test_context_strategy_parse() {
+        assert_eq!(
+            "truncate-tool-results".parse::<ContextStrategy>().unwrap(),
+            ContextStrategy::TruncateToolResults
+        );
+        assert!("summarize".parse::<ContextStrategy>().is_err());
+    }
+}
@@ -0,0 +1,115 @@
+//! API keys that can be re-read while the daemon runs
+//!
+//! A key read once at startup goes stale when it is rotated, and every request
+//! then fails with 401. A [`Credential`] remembers where its key came from (the
+//! provider's `api-key-env`, `api-key-secret` or `api-key-file`), so a client
+//! can re-read it when the provider rejects it and `td daemon reload-secrets`
+//! can re-read it on demand.
+
+use std::sync::RwLock;
+
+use tracing::{debug, info, warn};
+
+use super::LlmError;
+use crate::config::ResolvedLlmConfig;
+
+/// An API key and the provider config it is resolved from
+pub struct Credential {
+    /// Where the key is re-read from (None = a fixed key)
+    source: Option<ResolvedLlmConfig>,
+    key: RwLock<String>,
+}
+
+impl Credential {
+    /// Resolve the provider's API key
+    pub fn resolve(config: &ResolvedLlmConfig) -> Result<Self, LlmError> {
+        debug!(provider = %config.provider, "Credential::resolve: called");
+        let key = config
+            .get_api_key()
+            .map_err(|e| LlmError::InvalidResponse(e.to_string()))?;
+        Ok(Self {
+            source: Some(config.clone()),
+            key: RwLock::new(key),
+        })
+    }
+
+    /// A key that is never re-read
+    pub fn fixed(key: impl Into<String>) -> Self {
+        Self {
+            source: None,
+            key: RwLock::new(key.into()),
+        }
+    }
+
+    /// The current key
+    pub fn key(&self) -> String {
+        self.key.read().map(|key| key.clone()).unwrap_or_default()
+    }
+
+    /// Re-read the key from its source; true if it changed
+    pub fn reload(&self) -> Result<bool, LlmError> {
+        let Some(source) = &self.source else {
+            debug!("Credential::reload: fixed key");
+            return Ok(false);
+        };
+        debug!(provider = %source.provider, "Credential::reload: called");
+        let key = source
+            .get_api_key()
+            .map_err(|e| LlmError::InvalidResponse(e.to_string()))?;
+        let Ok(mut current) = self.key.write() else {
+            return Ok(false);
+        };
+        if *current == key {
+            debug!("Credential::reload: key unchanged");
+            return Ok(false);
+        }
+        info!(provider = %source.provider, "Reloaded rotated API key");
+        *current = key;
+        Ok(true)
+    }
+
+    /// Re-read the key after the provider rejected it; true if there is a new one to retry with
+    pub fn reload_rejected(&self) -> bool {
+        match self.reload() {
+            Ok(changed) => changed,
+            Err(e) => {
+                warn!(error = %e, "API key rejected and could not be re-read");
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LlmConfig;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_credential_reload_reads_rotated_key() {
+        let temp = tempdir().unwrap();
+        let key_file = temp.path().join("key");
+        std::fs::write(&key_file, "sk-old\n").unwrap();
+        let mut config = LlmConfig::default().resolve().unwrap();
+        config.api_key_env = "TD_TEST_CREDENTIAL_UNSET".to_string();
+        config.api_key_file = Some(key_file.display().to_string());
+
+        let credential = Credential::resolve(&config).unwrap();
+        assert_eq!(credential.key(), "sk-old");
+        assert!(!credential.reload().unwrap());
+
+        std::fs::write(&key_file, "sk-new\n").unwrap();
+        assert!(credential.reload_rejected());
+        assert_eq!(credential.key(), "sk-new");
+
+        std::fs::remove_file(&key_file).unwrap();
+        assert!(credential.reload().is_err());
+        assert!(!credential.reload_rejected());
+        assert_eq!(credential.key(), "sk-new");
+
+        let fixed = Credential::fixed("sk-test");
+        assert!(!fixed.reload().unwrap());
+        assert_eq!(fixed.key(), "sk-test");
+    }
+}
@@ -4,6 +4,8 @@ use std::time::Duration;
 use thiserror::Error;
 use tracing::debug;
 
+use crate::error::ErrorCode;
+
 /// Errors that can occur during LLM operations
 #[derive(Debug, Error)]
 pub enum LlmError {
@@ -24,6 +26,12 @@ pub enum LlmError {
 
     #[error("JSON serialization error: {0}")]
     Json(#[from] serde_json::Error),
+
+    #[error("Unsupported: {0}")]
+    Unsupported(String),
+
+    #[error("Request too large for the context window: ~{estimated} tokens estimated, limit {limit}")]
+    ContextTooLarge { estimated: u32, limit: u32 },
 }
 
 impl LlmError {
@@ -39,40 +47,11 @@ impl LlmError {
         result
     }
 
-    /// Check if this error is retryable
+    /// Check if this error is retryable (decided by its [`crate::error::ErrorCategory`])
     pub fn is_retryable(&self) -> bool {
-        debug!(?self, "is_retryable: called");
-        match self {
-            LlmError::RateLimited { .. } => {
-                debug!("is_retryable: RateLimited - true");
-                true
-            }
-            LlmError::ApiError { status, .. } => {
-                let retryable = *status >= 500;
-                if retryable {
-                    debug!(%status, "is_retryable: ApiError 5xx - true");
-                } else {
-                    debug!(%status, "is_retryable: ApiError non-5xx - false");
-                }
-                retryable
-            }
-            LlmError::Network(_) => {
-                debug!("is_retryable: Network - true");
-                true
-            }
-            LlmError::Timeout(_) => {
-                debug!("is_retryable: Timeout - true");
-                true
-            }
-            LlmError::InvalidResponse(_) => {
-                debug!("is_retryable: InvalidResponse - false");
-                false
-            }
-            LlmError::Json(_) => {
-                debug!("is_retryable: Json - false");
-                false
-            }
-        }
+        let category = self.category();
+        debug!(?self, %category, "is_retryable: called");
+        category.is_retryable()
     }
 
     /// Get the retry duration if this is a rate limit error
@@ -150,6 +129,9 @@ mod tests {
 
         // Invalid response should not be retryable
         assert!(!LlmError::InvalidResponse("Bad JSON".to_string()).is_retryable());
+
+        // Missing capabilities should not be retryable
+        assert!(!LlmError::Unsupported("embeddings".to_string()).is_retryable());
     }
 
     #[test]
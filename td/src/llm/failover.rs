@@ -0,0 +1,293 @@
+//! Failover across a chain of providers/models
+//!
+//! With `llm.fallback` set, [`create_client`](super::create_client) returns a
+//! [`FailoverClient`] over `default` followed by each fallback. Requests go to
+//! the first model in the chain whose circuit is closed. A model's circuit
+//! opens after `llm.failover-after` consecutive outage errors (5xx, network
+//! errors, timeouts) and it is skipped for `llm.failover-retry-secs`, then
+//! tried again. A request a model rejects outright (a non-retryable error)
+//! moves straight on to the next one; other retryable errors such as rate
+//! limits are returned so the caller backs off as usual. Every response
+//! names the model that served it in [`CompletionResponse::served_by`].
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+use super::client::Capabilities;
+use super::{CompletionRequest, CompletionResponse, LlmClient, LlmError, RateLimitStatus, StreamChunk};
+use crate::error::ErrorCode;
+
+/// Circuit state of one model in the chain
+#[derive(Debug, Default)]
+struct LinkState {
+    /// Outage errors since the model last answered
+    consecutive_outages: u32,
+    /// Skip the model until then
+    open_until: Option<Instant>,
+}
+
+/// One model in the chain
+struct Link {
+    /// "provider/model"
+    name: String,
+    client: Arc<dyn LlmClient>,
+    state: Mutex<LinkState>,
+}
+
+impl Link {
+    /// Whether the model is being skipped
+    fn is_open(&self, now: Instant) -> bool {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.open_until.is_some_and(|until| until > now)
+    }
+
+    fn record_success(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.open_until.take().is_some() {
+            info!(model = %self.name, "Model answered again, failing back to it");
+        }
+        state.consecutive_outages = 0;
+    }
+
+    /// Count an outage error; true if the model is now skipped
+    fn record_outage(&self, after: u32, retry: Duration) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.consecutive_outages += 1;
+        if state.consecutive_outages < after {
+            debug!(model = %self.name, outages = state.consecutive_outages, "Link::record_outage: below threshold");
+            return false;
+        }
+        if state.open_until.is_none() {
+            warn!(model = %self.name, ?retry, "Model failing repeatedly, failing over");
+        }
+        state.open_until = Some(Instant::now() + retry);
+        true
+    }
+}
+
+/// An [`LlmClient`] that fails over along a chain of models
+pub struct FailoverClient {
+    links: Vec<Link>,
+    /// Consecutive outage errors that open a model's circuit
+    after: u32,
+    /// How long an open model is skipped
+    retry: Duration,
+}
+
+impl FailoverClient {
+    /// Fail over along `chain` ("provider/model" and its client, primary first)
+    pub fn new(chain: Vec<(String, Arc<dyn LlmClient>)>) -> Self {
+        debug!(models = ?chain.iter().map(|(name, _)| name).collect::<Vec<_>>(), "FailoverClient::new: called");
+        Self {
+            links: chain
+                .into_iter()
+                .map(|(name, client)| Link {
+                    name,
+                    client,
+                    state: Mutex::new(LinkState::default()),
+                })
+                .collect(),
+            after: 3,
+            retry: Duration::from_secs(60),
+        }
+    }
+
+    /// Builder: open a model's circuit after `after` outage errors and skip it for `retry`
+    pub fn with_breaker(mut self, after: u32, retry: Duration) -> Self {
+        debug!(after, ?retry, "FailoverClient::with_breaker: called");
+        self.after = after.max(1);
+        self.retry = retry;
+        self
+    }
+
+    /// Whether to move on to the next model after `link` failed with `error`
+    fn should_fail_over(&self, link: &Link, error: &LlmError) -> bool {
+        if error.category().is_outage() {
+            return link.record_outage(self.after, self.retry);
+        }
+        // Rate limits and the like: back off on this model rather than switch
+        !error.is_retryable()
+    }
+
+    /// Send `request` along the chain with `call`, returning the first answer or the last error
+    async fn call_chain<'a, F, Fut>(&'a self, request: CompletionRequest, call: F) -> Result<CompletionResponse, LlmError>
+    where
+        F: Fn(&'a Link, CompletionRequest) -> Fut,
+        Fut: std::future::Future<Output = Result<CompletionResponse, LlmError>>,
+    {
+        let now = Instant::now();
+        let last = self.links.len().saturating_sub(1);
+        let mut last_error = None;
+        for (i, link) in self.links.iter().enumerate() {
+            // The last model is always tried, so a fully open chain still reports a real error
+            if i < last && link.is_open(now) {
+                debug!(model = %link.name, "FailoverClient: circuit open, skipping");
+                continue;
+            }
+            match call(link, request.clone()).await {
+                Ok(mut response) => {
+                    link.record_success();
+                    if i > 0 {
+                        debug!(model = %link.name, "FailoverClient: served by fallback");
+                    }
+                    response.served_by = Some(link.name.clone());
+                    return Ok(response);
+                }
+                Err(e) if i < last && self.should_fail_over(link, &e) => {
+                    warn!(model = %link.name, error = %e, "Request failed, trying the next model");
+                    last_error = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| LlmError::InvalidResponse("No models in the failover chain".to_string())))
+    }
+
+    /// The primary model's client
+    fn primary(&self) -> Option<&Arc<dyn LlmClient>> {
+        self.links.first().map(|link| &link.client)
+    }
+}
+
+#[async_trait]
+impl LlmClient for FailoverClient {
+    async fn complete(&self, request: CompletionRequest) -> Result<CompletionResponse, LlmError> {
+        debug!("FailoverClient::complete: called");
+        self.call_chain(request, |link, request| link.client.complete(request))
+            .await
+    }
+
+    async fn stream(
+        &self,
+        request: CompletionRequest,
+        chunk_tx: mpsc::Sender<StreamChunk>,
+    ) -> Result<CompletionResponse, LlmError> {
+        debug!("FailoverClient::stream: called");
+        self.call_chain(request, |link, request| link.client.stream(request, chunk_tx.clone()))
+            .await
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.primary().map(|c| c.capabilities()).unwrap_or_default()
+    }
+
+    fn rate_limits(&self) -> Option<RateLimitStatus> {
+        self.primary().and_then(|c| c.rate_limits())
+    }
+
+    fn reload_credentials(&self) -> Result<bool, LlmError> {
+        debug!("FailoverClient::reload_credentials: called");
+        let mut changed = false;
+        for link in &self.links {
+            changed |= link.client.reload_credentials()?;
+        }
+        Ok(changed)
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        match self.primary() {
+            Some(client) => client.embed(texts).await,
+            None => Err(LlmError::Unsupported("no models in the failover chain".to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::client::mock::MockLlmClient;
+    use crate::llm::{Message, StopReason, TokenUsage};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Fails every call with a 503
+    struct DownClient {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl LlmClient for DownClient {
+        async fn complete(&self, _request: CompletionRequest) -> Result<CompletionResponse, LlmError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Err(LlmError::ApiError {
+                status: 503,
+                message: "overloaded".to_string(),
+            })
+        }
+
+        async fn stream(
+            &self,
+            request: CompletionRequest,
+            _chunk_tx: mpsc::Sender<StreamChunk>,
+        ) -> Result<CompletionResponse, LlmError> {
+            self.complete(request).await
+        }
+    }
+
+    fn request() -> CompletionRequest {
+        CompletionRequest {
+            system_prompt: String::new(),
+            messages: vec![Message::user("hi")],
+            tools: Vec::new(),
+            max_tokens: 16,
+        }
+    }
+
+    fn answer() -> CompletionResponse {
+        CompletionResponse {
+            content: Some("ok".to_string()),
+            tool_calls: vec![],
+            stop_reason: StopReason::EndTurn,
+            usage: TokenUsage::default(),
+            served_by: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failover_after_repeated_outages() {
+        let primary = Arc::new(DownClient {
+            calls: AtomicUsize::new(0),
+        });
+        let fallback = Arc::new(MockLlmClient::new(vec![answer(), answer()]));
+        let client = FailoverClient::new(vec![
+            ("anthropic/claude-sonnet-4".to_string(), primary.clone() as Arc<dyn LlmClient>),
+            ("openai/gpt-4.1".to_string(), fallback.clone() as Arc<dyn LlmClient>),
+        ])
+        .with_breaker(2, Duration::from_secs(60));
+
+        // The first outage is returned for the caller to retry
+        let err = client.complete(request()).await.unwrap_err();
+        assert_eq!(err.code(), "llm.provider");
+        assert_eq!(fallback.call_count(), 0);
+
+        // The second opens the primary's circuit and the fallback answers
+        let response = client.complete(request()).await.unwrap();
+        assert_eq!(response.served_by.as_deref(), Some("openai/gpt-4.1"));
+
+        // While the circuit is open the primary is skipped
+        let response = client.complete(request()).await.unwrap();
+        assert_eq!(response.served_by.as_deref(), Some("openai/gpt-4.1"));
+        assert_eq!(primary.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failover_on_non_retryable_error() {
+        // An exhausted mock fails with a non-retryable invalid response
+        let primary = Arc::new(MockLlmClient::new(vec![]));
+        let fallback = Arc::new(MockLlmClient::new(vec![answer()]));
+        let client = FailoverClient::new(vec![
+            ("anthropic/claude-sonnet-4".to_string(), primary as Arc<dyn LlmClient>),
+            ("local/qwen".to_string(), fallback as Arc<dyn LlmClient>),
+        ]);
+
+        let response = client.complete(request()).await.unwrap();
+        assert_eq!(response.served_by.as_deref(), Some("local/qwen"));
+
+        // The last model's error is returned when nothing answers
+        let err = client.complete(request()).await.unwrap_err();
+        assert!(err.to_string().contains("No more mock responses"));
+    }
+}
@@ -0,0 +1,151 @@
+//! Local embeddings provider
+//!
+//! Produces embeddings in-process with no network access or model download,
+//! for offline use and tests. Each text is lowercased, split into words, and
+//! its words and word pairs are hashed into a fixed number of buckets
+//! (feature hashing); the vector is L2-normalised so cosine similarity is a
+//! plain dot product. It captures lexical overlap, not meaning, and cannot
+//! answer completions. Select it with `api: local` on a provider.
+
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+use tracing::debug;
+
+use super::client::Capabilities;
+use super::{CompletionRequest, CompletionResponse, LlmClient, LlmError, StreamChunk};
+use crate::config::ResolvedLlmConfig;
+
+/// Length of every local embedding
+pub const LOCAL_EMBEDDING_DIMENSIONS: usize = 256;
+
+/// 64-bit FNV-1a, stable across builds and platforms
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Embedding client that runs in-process
+pub struct LocalClient {
+    dimensions: usize,
+}
+
+impl LocalClient {
+    pub fn new() -> Self {
+        debug!("LocalClient::new: called");
+        Self {
+            dimensions: LOCAL_EMBEDDING_DIMENSIONS,
+        }
+    }
+
+    /// Create a client from resolved configuration (nothing in it is needed yet)
+    pub fn from_config(config: &ResolvedLlmConfig) -> Result<Self, LlmError> {
+        debug!(provider = %config.provider, "LocalClient::from_config: called");
+        Ok(Self::new())
+    }
+
+    /// Feature-hashed, normalised vector for one text
+    fn embed_one(&self, text: &str) -> Vec<f32> {
+        let words: Vec<String> = text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+
+        let mut vector = vec![0.0f32; self.dimensions];
+        let pairs = words.windows(2).map(|w| format!("{} {}", w[0], w[1]));
+        for feature in words.iter().cloned().chain(pairs) {
+            let hash = fnv1a(feature.as_bytes());
+            let bucket = (hash % self.dimensions as u64) as usize;
+            // The top bit picks the sign so collisions tend to cancel out
+            vector[bucket] += if hash >> 63 == 0 { 1.0 } else { -1.0 };
+        }
+
+        let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|v| *v /= norm);
+        }
+        vector
+    }
+}
+
+impl Default for LocalClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl LlmClient for LocalClient {
+    async fn complete(&self, _request: CompletionRequest) -> Result<CompletionResponse, LlmError> {
+        debug!("LocalClient::complete: called");
+        Err(LlmError::Unsupported(
+            "the local provider only supports embeddings".to_string(),
+        ))
+    }
+
+    async fn stream(
+        &self,
+        request: CompletionRequest,
+        _chunk_tx: mpsc::Sender<StreamChunk>,
+    ) -> Result<CompletionResponse, LlmError> {
+        debug!("LocalClient::stream: called");
+        self.complete(request).await
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            completions: false,
+            embeddings: true,
+        }
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        debug!(count = texts.len(), "LocalClient::embed: called");
+        Ok(texts.iter().map(|t| self.embed_one(t)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cosine(a: &[f32], b: &[f32]) -> f32 {
+        a.iter().zip(b).map(|(x, y)| x * y).sum()
+    }
+
+    #[tokio::test]
+    async fn test_local_embeddings_are_deterministic_and_normalised() {
+        let client = LocalClient::new();
+        let texts = vec![
+            "Fix the login timeout bug".to_string(),
+            "fix the LOGIN timeout bug!".to_string(),
+            "Add dark mode to the settings page".to_string(),
+            String::new(),
+        ];
+
+        let vectors = client.embed(&texts).await.unwrap();
+        assert_eq!(vectors.len(), 4);
+        assert!(vectors.iter().all(|v| v.len() == LOCAL_EMBEDDING_DIMENSIONS));
+        assert!((cosine(&vectors[0], &vectors[0]) - 1.0).abs() < 1e-5);
+        assert!((cosine(&vectors[0], &vectors[1]) - 1.0).abs() < 1e-5);
+        assert!(cosine(&vectors[0], &vectors[2]) < 0.5);
+        assert!(vectors[3].iter().all(|v| *v == 0.0));
+
+        assert_eq!(client.embed(&texts[..1]).await.unwrap()[0], vectors[0]);
+    }
+
+    #[tokio::test]
+    async fn test_local_client_rejects_completions() {
+        let client = LocalClient::new();
+        assert!(!client.capabilities().completions);
+
+        let request = CompletionRequest {
+            system_prompt: String::new(),
+            messages: vec![],
+            tools: vec![],
+            max_tokens: 10,
+        };
+        assert!(matches!(client.complete(request).await, Err(LlmError::Unsupported(_))));
+    }
+}
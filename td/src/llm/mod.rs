@@ -1,26 +1,39 @@
 //! LLM Client module for TaskDaemon
 //!
-//! Provides LLM completion requests and utilities.
+//! Provides LLM completion requests and utilities, plus embeddings for
+//! clients that advertise them in [`Capabilities`].
 
 use std::sync::Arc;
 
-use tracing::debug;
+use tracing::{debug, warn};
 
 mod anthropic;
+pub mod audit;
 pub mod client;
+pub mod context;
+mod credential;
 mod error;
+mod failover;
+mod local;
 mod openai;
+pub mod ratelimit;
 mod types;
 
 pub use anthropic::AnthropicClient;
-pub use client::LlmClient;
+pub use audit::AuditedClient;
+pub use client::{Capabilities, LlmClient};
+pub use context::{ContextGuard, ContextStrategy};
+pub use credential::Credential;
 pub use error::LlmError;
+pub use failover::FailoverClient;
+pub use local::{LOCAL_EMBEDDING_DIMENSIONS, LocalClient};
 pub use openai::OpenAIClient;
+pub use ratelimit::RateLimitStatus;
 #[allow(unused_imports)]
 pub use types::Role;
 pub use types::{
-    CompletionRequest, CompletionResponse, ContentBlock, Message, MessageContent, StopReason, StreamChunk, TokenUsage,
-    ToolCall, ToolDefinition,
+    CompletionRequest, CompletionResponse, ContentBlock, MAX_IMAGE_BYTES, Message, MessageContent, StopReason,
+    StreamChunk, TokenUsage, ToolCall, ToolDefinition,
 };
 
 use crate::config::{LlmConfig, ResolvedLlmConfig};
@@ -28,36 +41,94 @@ use crate::config::{LlmConfig, ResolvedLlmConfig};
 /// Create an LLM client based on the provider specified in config
 ///
 /// Resolves the default provider/model from the config and creates the appropriate client.
-/// Supports "anthropic" and "openai" providers.
+/// Supports the "anthropic", "openai", "azure" and "local" APIs. With `fallback` set the
+/// client is a [`FailoverClient`] over the default and its fallbacks; a fallback whose
+/// client can't be created (e.g. no API key) is left out of the chain with a warning.
 pub fn create_client(config: &LlmConfig) -> Result<Arc<dyn LlmClient>, LlmError> {
-    let resolved = config.resolve().map_err(|e| LlmError::InvalidResponse(e.to_string()))?;
+    if config.fallback.is_empty() {
+        let resolved = config.resolve().map_err(|e| LlmError::InvalidResponse(e.to_string()))?;
+        return create_client_from_resolved(&resolved);
+    }
 
-    create_client_from_resolved(&resolved)
+    debug!(default = %config.default, fallback = ?config.fallback, "create_client: building failover chain");
+    let mut chain = config
+        .resolve_chain()
+        .map_err(|e| LlmError::InvalidResponse(e.to_string()))?
+        .into_iter();
+    let primary = chain.next().expect("chain starts with the default");
+    let mut links = vec![(
+        format!("{}/{}", primary.provider, primary.model),
+        create_client_from_resolved(&primary)?,
+    )];
+    for resolved in chain {
+        let name = format!("{}/{}", resolved.provider, resolved.model);
+        match create_client_from_resolved(&resolved) {
+            Ok(client) => links.push((name, client)),
+            Err(e) => warn!(model = %name, error = %e, "Leaving fallback model out of the failover chain"),
+        }
+    }
+    let client = FailoverClient::new(links).with_breaker(
+        config.failover_after,
+        std::time::Duration::from_secs(config.failover_retry_secs),
+    );
+    Ok(Arc::new(client))
 }
 
 /// Create an LLM client from a resolved configuration
 ///
 /// This is useful when you've already resolved the config or want to use
-/// a specific provider/model combination.
+/// a specific provider/model combination. With auditing enabled the client is
+/// wrapped in an [`AuditedClient`].
 pub fn create_client_from_resolved(config: &ResolvedLlmConfig) -> Result<Arc<dyn LlmClient>, LlmError> {
     debug!(provider = %config.provider, model = %config.model, "create_client_from_resolved: called");
-    match config.provider.as_str() {
+    let client: Arc<dyn LlmClient> = match config.api.as_str() {
         "anthropic" => {
             debug!("create_client_from_resolved: creating Anthropic client");
-            Ok(Arc::new(AnthropicClient::from_config(config)?))
+            Arc::new(AnthropicClient::from_config(config)?)
         }
-        "openai" => {
-            debug!("create_client_from_resolved: creating OpenAI client");
-            Ok(Arc::new(OpenAIClient::from_config(config)?))
+        "openai" | "azure" => {
+            debug!(api = %config.api, "create_client_from_resolved: creating OpenAI client");
+            Arc::new(OpenAIClient::from_config(config)?)
+        }
+        "local" => {
+            debug!("create_client_from_resolved: creating local client");
+            Arc::new(LocalClient::from_config(config)?)
         }
         other => {
-            debug!(provider = %other, "create_client_from_resolved: unknown provider");
-            Err(LlmError::InvalidResponse(format!(
-                "Unknown LLM provider: '{}'. Supported: anthropic, openai",
-                other
-            )))
+            debug!(api = %other, "create_client_from_resolved: unknown api");
+            return Err(LlmError::InvalidResponse(format!(
+                "Unknown LLM api '{}' for provider '{}'. Supported: anthropic, openai, azure, local (set `api` on the provider)",
+                other, config.provider
+            )));
         }
+    };
+
+    if !config.audit.enabled {
+        return Ok(client);
+    }
+    debug!("create_client_from_resolved: wrapping client for audit");
+    let audited = AuditedClient::new(client, &config.provider, &config.model, &config.audit)
+        .map_err(|e| LlmError::InvalidResponse(e.to_string()))?;
+    Ok(Arc::new(audited))
+}
+
+/// Create a client for embeddings from `llm.embeddings` (or the default provider)
+///
+/// Fails if that provider cannot produce embeddings, e.g. it has no `embedding-model`.
+pub fn create_embedding_client(config: &LlmConfig) -> Result<Arc<dyn LlmClient>, LlmError> {
+    debug!(embeddings = ?config.embeddings, "create_embedding_client: called");
+    let resolved = config
+        .resolve_embeddings()
+        .map_err(|e| LlmError::InvalidResponse(e.to_string()))?;
+    let client = create_client_from_resolved(&resolved)?;
+    if !client.capabilities().embeddings {
+        debug!(provider = %resolved.provider, "create_embedding_client: provider lacks embeddings");
+        return Err(LlmError::Unsupported(format!(
+            "provider '{}' does not support embeddings (set embedding-model, or use api: local)",
+            resolved.provider
+        )));
     }
+    Ok(client)
 }
 
 /// Generate a short title from markdown/text content
@@ -1,16 +1,25 @@
 //! OpenAI API client implementation
 //!
 //! Implements the LlmClient trait for OpenAI's Chat Completions API with
-//! support for both blocking and streaming responses.
+//! support for both blocking and streaming responses. The same client serves
+//! any OpenAI-compatible server (vLLM, OpenRouter, LM Studio) through
+//! `base-url`, and Azure OpenAI through `api: azure`, which switches to
+//! deployment-style URLs with an `api-version` and `api-key` authentication.
 
 use async_trait::async_trait;
 use futures::StreamExt;
 use reqwest::Client;
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue};
 use serde::Deserialize;
+use std::sync::Mutex;
 use std::time::Duration;
 use tokio::sync::mpsc;
 use tracing::{debug, warn};
 
+use super::client::Capabilities;
+use super::context::ContextGuard;
+use super::credential::Credential;
+use super::ratelimit::RateLimitStatus;
 use super::{
     CompletionRequest, CompletionResponse, ContentBlock, LlmClient, LlmError, Message, MessageContent, StopReason,
     StreamChunk, TokenUsage, ToolCall,
@@ -23,18 +32,60 @@ const MAX_RETRIES: u32 = 3;
 /// Initial backoff delay for retries
 const INITIAL_BACKOFF_MS: u64 = 1000;
 
+/// Azure `api-version` used when the provider does not set one
+pub const DEFAULT_AZURE_API_VERSION: &str = "2024-10-21";
+
 /// Check if an HTTP status code is retryable
 fn is_retryable_status(status: u16) -> bool {
     matches!(status, 408 | 429 | 500 | 502 | 503 | 504)
 }
 
+/// Maximum inputs per embeddings request
+const EMBED_BATCH_SIZE: usize = 256;
+
+/// Build an endpoint URL (`path` is e.g. "chat/completions") for the configured server
+///
+/// Azure uses `{base}/openai/deployments/{deployment}/{path}?api-version=...`;
+/// everything else appends `/{path}`, adding `/v1` unless the base URL
+/// already ends with it.
+fn endpoint_url(config: &ResolvedLlmConfig, deployment: &str, path: &str) -> String {
+    debug!(api = %config.api, base_url = %config.base_url, %path, "endpoint_url: called");
+    let base = config.base_url.trim_end_matches('/');
+    if config.api == "azure" {
+        let api_version = config.api_version.as_deref().unwrap_or(DEFAULT_AZURE_API_VERSION);
+        format!(
+            "{}/openai/deployments/{}/{}?api-version={}",
+            base, deployment, path, api_version
+        )
+    } else if base.ends_with("/v1") {
+        format!("{}/{}", base, path)
+    } else {
+        format!("{}/v1/{}", base, path)
+    }
+}
+
+fn chat_completions_url(config: &ResolvedLlmConfig) -> String {
+    let deployment = config.deployment.as_deref().unwrap_or(&config.model);
+    endpoint_url(config, deployment, "chat/completions")
+}
+
 /// OpenAI API client
 pub struct OpenAIClient {
     model: String,
-    api_key: String,
-    base_url: String,
+    url: String,
+    /// Request headers other than the API key
+    headers: HeaderMap,
+    api_key: Credential,
+    /// Azure sends the key in `api-key` rather than `Authorization`
+    azure: bool,
     http: Client,
     max_tokens: u32,
+    context: ContextGuard,
+    rate_limits: Mutex<Option<RateLimitStatus>>,
+    temperature: Option<f32>,
+    reasoning_effort: Option<String>,
+    /// Embedding model and its endpoint, if configured
+    embeddings: Option<(String, String)>,
     #[allow(dead_code)]
     timeout: Duration,
 }
@@ -45,9 +96,21 @@ impl OpenAIClient {
     /// Takes a ResolvedLlmConfig which contains all necessary fields.
     pub fn from_config(config: &ResolvedLlmConfig) -> Result<Self, LlmError> {
         debug!(?config, "from_config: called");
-        let api_key = config
-            .get_api_key()
-            .map_err(|e| LlmError::InvalidResponse(e.to_string()))?;
+        Self::new(config, Credential::resolve(config)?)
+    }
+
+    /// Create a client with an explicit API key
+    fn new(config: &ResolvedLlmConfig, api_key: Credential) -> Result<Self, LlmError> {
+        debug!(api = %config.api, model = %config.model, "OpenAIClient::new: called");
+        let header = |value: &str| {
+            HeaderValue::from_str(value).map_err(|e| LlmError::InvalidResponse(format!("Invalid header value: {}", e)))
+        };
+
+        let mut headers = HeaderMap::new();
+        if let Some(org) = &config.organization {
+            headers.insert("OpenAI-Organization", header(org)?);
+        }
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
 
         let timeout = Duration::from_millis(config.timeout_ms);
 
@@ -55,14 +118,69 @@ impl OpenAIClient {
 
         Ok(Self {
             model: config.model.clone(),
+            url: chat_completions_url(config),
+            headers,
             api_key,
-            base_url: config.base_url.clone(),
+            azure: config.api == "azure",
             http,
             max_tokens: config.max_tokens,
+            context: ContextGuard::from_config(config),
+            rate_limits: Mutex::new(None),
+            temperature: config.temperature,
+            reasoning_effort: config.reasoning_effort.clone(),
+            embeddings: config
+                .embedding_model
+                .as_ref()
+                .map(|model| (model.clone(), endpoint_url(config, model, "embeddings"))),
             timeout,
         })
     }
 
+    /// Request headers with the current API key
+    fn headers(&self) -> Result<HeaderMap, LlmError> {
+        let key = self.api_key.key();
+        let (name, value) = if self.azure {
+            ("api-key", key)
+        } else {
+            (AUTHORIZATION.as_str(), format!("Bearer {}", key))
+        };
+        let value =
+            HeaderValue::from_str(&value).map_err(|e| LlmError::InvalidResponse(format!("Invalid header value: {}", e)))?;
+        let mut headers = self.headers.clone();
+        headers.insert(name, value);
+        Ok(headers)
+    }
+
+    /// POST `body` to `url` with the current API key, re-reading a rejected key once
+    async fn post(&self, url: &str, body: &serde_json::Value) -> Result<reqwest::Response, LlmError> {
+        let send = || async {
+            let headers = self.headers()?;
+            self.http
+                .post(url)
+                .headers(headers)
+                .json(body)
+                .send()
+                .await
+                .map_err(LlmError::Network)
+        };
+        let response = send().await?;
+        if response.status().as_u16() == 401 && self.api_key.reload_rejected() {
+            debug!(%url, "post: API key rejected, retrying with the reloaded key");
+            return send().await;
+        }
+        Ok(response)
+    }
+
+    /// Remember the rate limit headers of a response
+    fn record_rate_limits(&self, headers: &HeaderMap) {
+        if let Some(status) = RateLimitStatus::from_openai_headers(headers)
+            && let Ok(mut latest) = self.rate_limits.lock()
+        {
+            debug!(?status.requests_remaining, ?status.tokens_remaining, "record_rate_limits: updated");
+            *latest = Some(status);
+        }
+    }
+
     /// Build the request body for the OpenAI API
     fn build_request_body(&self, request: &CompletionRequest) -> serde_json::Value {
         debug!(%self.model, %request.max_tokens, "build_request_body: called");
@@ -91,6 +209,13 @@ impl OpenAIClient {
             body["max_tokens"] = serde_json::json!(max_tokens);
         }
 
+        if let Some(temperature) = self.temperature {
+            body["temperature"] = serde_json::json!(temperature);
+        }
+        if let Some(effort) = &self.reasoning_effort {
+            body["reasoning_effort"] = serde_json::json!(effort);
+        }
+
         if !request.tools.is_empty() {
             debug!("build_request_body: tools not empty, adding tools");
             body["tools"] = serde_json::json!(request.tools.iter().map(|t| t.to_openai_schema()).collect::<Vec<_>>());
@@ -105,7 +230,9 @@ impl OpenAIClient {
     /// Convert internal Message types to OpenAI API format
     ///
     /// OpenAI requires one message per tool result, so a single internal message
-    /// with multiple tool results becomes multiple OpenAI messages.
+    /// with multiple tool results becomes multiple OpenAI messages. Tool messages
+    /// cannot carry images, so images sent alongside tool results follow them in
+    /// a separate user message.
     fn convert_messages(&self, messages: &[Message]) -> Vec<serde_json::Value> {
         debug!(message_count = %messages.len(), "convert_messages: called");
         let mut result = Vec::new();
@@ -129,6 +256,7 @@ impl OpenAIClient {
                     // For blocks, we need to handle tool calls and tool results specially
                     let mut tool_calls = Vec::new();
                     let mut tool_results = Vec::new();
+                    let mut images = Vec::new();
                     let mut text_content = String::new();
 
                     for block in blocks {
@@ -151,6 +279,12 @@ impl OpenAIClient {
                             } => {
                                 tool_results.push((tool_use_id.clone(), content.clone()));
                             }
+                            ContentBlock::Image { media_type, data } => {
+                                images.push(serde_json::json!({
+                                    "type": "image_url",
+                                    "image_url": { "url": format!("data:{};base64,{}", media_type, data) },
+                                }));
+                            }
                         }
                     }
 
@@ -163,6 +297,12 @@ impl OpenAIClient {
                                 "content": content,
                             }));
                         }
+                        if !images.is_empty() {
+                            result.push(serde_json::json!({
+                                "role": "user",
+                                "content": images,
+                            }));
+                        }
                         continue;
                     }
 
@@ -179,6 +319,20 @@ impl OpenAIClient {
                         continue;
                     }
 
+                    if !images.is_empty() {
+                        // Text and images as content parts
+                        let mut parts = Vec::new();
+                        if !text_content.is_empty() {
+                            parts.push(serde_json::json!({ "type": "text", "text": text_content }));
+                        }
+                        parts.extend(images);
+                        result.push(serde_json::json!({
+                            "role": role,
+                            "content": parts,
+                        }));
+                        continue;
+                    }
+
                     // Plain text message
                     result.push(serde_json::json!({
                         "role": role,
@@ -231,6 +385,7 @@ impl OpenAIClient {
                 cache_read_tokens: 0,
                 cache_creation_tokens: 0,
             },
+            served_by: None,
         }
     }
 }
@@ -239,7 +394,7 @@ impl OpenAIClient {
 impl LlmClient for OpenAIClient {
     async fn complete(&self, request: CompletionRequest) -> Result<CompletionResponse, LlmError> {
         debug!(%self.model, %request.max_tokens, "complete: called");
-        let url = format!("{}/v1/chat/completions", self.base_url);
+        let request = self.context.check(request, self.max_tokens)?;
         let body = self.build_request_body(&request);
 
         let mut last_error = None;
@@ -254,24 +409,18 @@ impl LlmClient for OpenAIClient {
                 tokio::time::sleep(Duration::from_millis(backoff)).await;
             }
 
-            let response = match self
-                .http
-                .post(url.clone())
-                .header("Authorization", format!("Bearer {}", self.api_key))
-                .header("content-type", "application/json")
-                .json(&body)
-                .send()
-                .await
-            {
+            let response = match self.post(&self.url, &body).await {
                 Ok(r) => r,
-                Err(e) => {
+                Err(e @ LlmError::Network(_)) => {
                     debug!(attempt, error = %e, "complete: network error");
-                    last_error = Some(LlmError::Network(e));
+                    last_error = Some(e);
                     continue;
                 }
+                Err(e) => return Err(e),
             };
 
             let status = response.status().as_u16();
+            self.record_rate_limits(response.headers());
 
             if status == 429 {
                 debug!("complete: rate limited (429)");
@@ -314,19 +463,25 @@ impl LlmClient for OpenAIClient {
         chunk_tx: mpsc::Sender<StreamChunk>,
     ) -> Result<CompletionResponse, LlmError> {
         debug!(%self.model, %request.max_tokens, "stream: called");
-        let url = format!("{}/v1/chat/completions", self.base_url);
+        let request = self.context.check(request, self.max_tokens)?;
         let mut body = self.build_request_body(&request);
         body["stream"] = serde_json::json!(true);
 
-        let response = self
-            .http
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("content-type", "application/json")
-            .json(&body)
-            .send()
-            .await
-            .map_err(LlmError::Network)?;
+        let response = self.post(&self.url, &body).await?;
+
+        self.record_rate_limits(response.headers());
+        if response.status().as_u16() == 429 {
+            debug!("stream: rate limited (429)");
+            let retry_after = response
+                .headers()
+                .get("retry-after")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or(60);
+            return Err(LlmError::RateLimited {
+                retry_after: Duration::from_secs(retry_after),
+            });
+        }
 
         if !response.status().is_success() {
             let status = response.status().as_u16();
@@ -442,8 +597,71 @@ impl LlmClient for OpenAIClient {
             tool_calls,
             stop_reason,
             usage,
+            served_by: None,
         })
     }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            completions: true,
+            embeddings: self.embeddings.is_some(),
+        }
+    }
+
+    fn reload_credentials(&self) -> Result<bool, LlmError> {
+        self.api_key.reload()
+    }
+
+    fn rate_limits(&self) -> Option<RateLimitStatus> {
+        self.rate_limits.lock().ok().and_then(|latest| latest.clone())
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        debug!(count = texts.len(), "embed: called");
+        let Some((model, url)) = &self.embeddings else {
+            return Err(LlmError::Unsupported(
+                "no embedding-model configured for this provider".to_string(),
+            ));
+        };
+
+        let mut vectors = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(EMBED_BATCH_SIZE) {
+            let body = serde_json::json!({ "model": model, "input": batch });
+            let response = self.post(url, &body).await?;
+
+            let status = response.status().as_u16();
+            self.record_rate_limits(response.headers());
+            if status == 429 {
+                debug!("embed: rate limited (429)");
+                let retry_after = response
+                    .headers()
+                    .get("retry-after")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|s| s.parse::<u64>().ok())
+                    .unwrap_or(60);
+                return Err(LlmError::RateLimited {
+                    retry_after: Duration::from_secs(retry_after),
+                });
+            }
+            if !response.status().is_success() {
+                debug!(%status, "embed: API error");
+                let text = response.text().await.unwrap_or_default();
+                return Err(LlmError::ApiError { status, message: text });
+            }
+
+            let mut api_response: OpenAIEmbeddingResponse = response.json().await?;
+            if api_response.data.len() != batch.len() {
+                return Err(LlmError::InvalidResponse(format!(
+                    "expected {} embeddings, got {}",
+                    batch.len(),
+                    api_response.data.len()
+                )));
+            }
+            api_response.data.sort_by_key(|d| d.index);
+            vectors.extend(api_response.data.into_iter().map(|d| d.embedding));
+        }
+        Ok(vectors)
+    }
 }
 
 // OpenAI API response types
@@ -484,6 +702,17 @@ struct OpenAIUsage {
     completion_tokens: u64,
 }
 
+#[derive(Debug, Deserialize)]
+struct OpenAIEmbeddingResponse {
+    data: Vec<OpenAIEmbedding>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIEmbedding {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
 // Streaming types
 
 #[derive(Debug, Deserialize)]
@@ -520,17 +749,22 @@ struct OpenAIStreamFunction {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::LlmConfig;
+
+    fn resolved(model: &str, max_tokens: u32) -> ResolvedLlmConfig {
+        let mut config = LlmConfig::default().resolve_model("openai/gpt-4o").unwrap();
+        config.model = model.to_string();
+        config.max_tokens = max_tokens;
+        config
+    }
+
+    fn test_client(config: &ResolvedLlmConfig) -> OpenAIClient {
+        OpenAIClient::new(config, Credential::fixed("test-key")).unwrap()
+    }
 
     #[test]
     fn test_build_request_body_basic() {
-        let client = OpenAIClient {
-            model: "gpt-4o".to_string(),
-            api_key: "test-key".to_string(),
-            base_url: "https://api.openai.com".to_string(),
-            http: Client::new(),
-            max_tokens: 8192,
-            timeout: Duration::from_secs(300),
-        };
+        let client = test_client(&resolved("gpt-4o", 8192));
 
         let request = CompletionRequest {
             system_prompt: "You are helpful".to_string(),
@@ -548,18 +782,13 @@ mod tests {
         assert_eq!(body["messages"][0]["content"], "You are helpful");
         assert_eq!(body["messages"][1]["role"], "user");
         assert!(body.get("tools").is_none());
+        assert!(body.get("temperature").is_none());
+        assert!(body.get("reasoning_effort").is_none());
     }
 
     #[test]
     fn test_max_tokens_capped() {
-        let client = OpenAIClient {
-            model: "gpt-4o".to_string(),
-            api_key: "test-key".to_string(),
-            base_url: "https://api.openai.com".to_string(),
-            http: Client::new(),
-            max_tokens: 1000,
-            timeout: Duration::from_secs(300),
-        };
+        let client = test_client(&resolved("gpt-4o", 1000));
 
         let request = CompletionRequest {
             system_prompt: "Test".to_string(),
@@ -571,4 +800,119 @@ mod tests {
         let body = client.build_request_body(&request);
         assert_eq!(body["max_tokens"], 1000);
     }
+
+    #[test]
+    fn test_model_overrides_in_body() {
+        let mut config = resolved("o3-mini", 4096);
+        config.temperature = Some(0.5);
+        config.reasoning_effort = Some("high".to_string());
+        let client = test_client(&config);
+
+        let request = CompletionRequest {
+            system_prompt: "Test".to_string(),
+            messages: vec![],
+            tools: vec![],
+            max_tokens: 100,
+        };
+
+        let body = client.build_request_body(&request);
+        assert_eq!(body["temperature"], 0.5);
+        assert_eq!(body["reasoning_effort"], "high");
+        assert_eq!(body["max_completion_tokens"], 100);
+    }
+
+    #[test]
+    fn test_convert_messages_with_images() {
+        let client = test_client(&resolved("gpt-4o", 1000));
+        let image = ContentBlock::Image {
+            media_type: "image/png".to_string(),
+            data: "AAAA".to_string(),
+        };
+
+        let messages = client.convert_messages(&[
+            Message::user_blocks(vec![ContentBlock::text("What is this?"), image.clone()]),
+            Message::user_blocks(vec![ContentBlock::tool_result("call_1", "Loaded image", false), image]),
+        ]);
+
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0]["content"][0]["text"], "What is this?");
+        assert_eq!(
+            messages[0]["content"][1]["image_url"]["url"],
+            "data:image/png;base64,AAAA"
+        );
+        assert_eq!(messages[1]["role"], "tool");
+        assert_eq!(messages[2]["role"], "user");
+        assert_eq!(messages[2]["content"][0]["type"], "image_url");
+    }
+
+    #[test]
+    fn test_chat_completions_url() {
+        let mut config = resolved("gpt-4o", 1000);
+        assert_eq!(
+            chat_completions_url(&config),
+            "https://api.openai.com/v1/chat/completions"
+        );
+
+        config.base_url = "https://openrouter.ai/api/v1/".to_string();
+        assert_eq!(
+            chat_completions_url(&config),
+            "https://openrouter.ai/api/v1/chat/completions"
+        );
+
+        config.api = "azure".to_string();
+        config.base_url = "https://acme.openai.azure.com".to_string();
+        assert_eq!(
+            chat_completions_url(&config),
+            "https://acme.openai.azure.com/openai/deployments/gpt-4o/chat/completions?api-version=2024-10-21"
+        );
+
+        config.deployment = Some("prod-gpt4o".to_string());
+        config.api_version = Some("2025-01-01-preview".to_string());
+        assert_eq!(
+            chat_completions_url(&config),
+            "https://acme.openai.azure.com/openai/deployments/prod-gpt4o/chat/completions?api-version=2025-01-01-preview"
+        );
+    }
+
+    #[test]
+    fn test_embeddings_endpoint_and_capability() {
+        let mut config = resolved("gpt-4o", 1000);
+        let client = test_client(&config);
+        assert!(client.capabilities().embeddings);
+        assert_eq!(
+            client.embeddings,
+            Some((
+                "text-embedding-3-small".to_string(),
+                "https://api.openai.com/v1/embeddings".to_string()
+            ))
+        );
+
+        config.api = "azure".to_string();
+        config.base_url = "https://acme.openai.azure.com".to_string();
+        config.embedding_model = Some("embed-prod".to_string());
+        let client = test_client(&config);
+        assert_eq!(
+            client.embeddings.map(|(_, url)| url).as_deref(),
+            Some("https://acme.openai.azure.com/openai/deployments/embed-prod/embeddings?api-version=2024-10-21")
+        );
+
+        config.embedding_model = None;
+        assert!(!test_client(&config).capabilities().embeddings);
+    }
+
+    #[test]
+    fn test_auth_and_organization_headers() {
+        let mut config = resolved("gpt-4o", 1000);
+        config.organization = Some("org-123".to_string());
+        let headers = test_client(&config).headers().unwrap();
+        assert_eq!(headers[AUTHORIZATION], "Bearer test-key");
+        assert_eq!(headers["OpenAI-Organization"], "org-123");
+
+        config.api = "azure".to_string();
+        config.organization = None;
+        let headers = test_client(&config).headers().unwrap();
+        assert_eq!(headers["api-key"], "test-key");
+        assert!(headers.get(AUTHORIZATION).is_none());
+        assert!(headers.get("OpenAI-Organization").is_none());
+    }
 }
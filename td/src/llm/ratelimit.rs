@@ -0,0 +1,213 @@
+//! Rate limit headers
+//!
+//! Providers report how much of the current rate window is left on every
+//! response: Anthropic as `anthropic-ratelimit-{requests,tokens}-{limit,remaining,reset}`
+//! (reset is an RFC 3339 timestamp), OpenAI and compatible servers as
+//! `x-ratelimit-{limit,remaining,reset}-{requests,tokens}` (reset is a
+//! duration such as `6m0s`). Clients keep the latest [`RateLimitStatus`] so
+//! the scheduler can slow down before the provider starts answering 429.
+
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use reqwest::header::HeaderMap;
+use tracing::debug;
+
+/// Remaining capacity reported by a provider
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimitStatus {
+    pub requests_limit: Option<u64>,
+    pub requests_remaining: Option<u64>,
+    /// Time until the request allowance is fully replenished
+    pub requests_reset: Option<Duration>,
+    pub tokens_limit: Option<u64>,
+    pub tokens_remaining: Option<u64>,
+    /// Time until the token allowance is fully replenished
+    pub tokens_reset: Option<Duration>,
+    /// When the headers were received (resets are relative to this)
+    pub observed_at: Instant,
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|v| v.to_str().ok()).map(str::trim)
+}
+
+fn number(headers: &HeaderMap, name: &str) -> Option<u64> {
+    header(headers, name).and_then(|v| v.parse().ok())
+}
+
+/// Time from now until an RFC 3339 timestamp (zero if it has passed)
+fn until_timestamp(value: &str) -> Option<Duration> {
+    let at = DateTime::parse_from_rfc3339(value).ok()?;
+    Some((at.with_timezone(&Utc) - Utc::now()).to_std().unwrap_or(Duration::ZERO))
+}
+
+/// Parse a duration like `1s`, `6m0s`, `59.972s`, `20ms` or `1h2m` (a bare number is seconds)
+pub fn parse_reset_duration(value: &str) -> Option<Duration> {
+    let mut rest = value.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    let mut seconds = 0.0f64;
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let amount: f64 = rest[..number_end].parse().ok()?;
+        rest = &rest[number_end..];
+        let unit_end = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        let scale = match &rest[..unit_end] {
+            "" | "s" => 1.0,
+            "ms" => 1e-3,
+            "us" | "µs" => 1e-6,
+            "ns" => 1e-9,
+            "m" => 60.0,
+            "h" => 3600.0,
+            _ => return None,
+        };
+        seconds += amount * scale;
+        rest = &rest[unit_end..];
+    }
+    Some(Duration::from_secs_f64(seconds))
+}
+
+impl RateLimitStatus {
+    fn empty() -> Self {
+        Self {
+            requests_limit: None,
+            requests_remaining: None,
+            requests_reset: None,
+            tokens_limit: None,
+            tokens_remaining: None,
+            tokens_reset: None,
+            observed_at: Instant::now(),
+        }
+    }
+
+    fn nonempty(self) -> Option<Self> {
+        let any = self.requests_remaining.is_some() || self.tokens_remaining.is_some();
+        any.then_some(self)
+    }
+
+    /// Read `anthropic-ratelimit-*` headers
+    pub fn from_anthropic_headers(headers: &HeaderMap) -> Option<Self> {
+        debug!("RateLimitStatus::from_anthropic_headers: called");
+        Self {
+            requests_limit: number(headers, "anthropic-ratelimit-requests-limit"),
+            requests_remaining: number(headers, "anthropic-ratelimit-requests-remaining"),
+            requests_reset: header(headers, "anthropic-ratelimit-requests-reset").and_then(until_timestamp),
+            tokens_limit: number(headers, "anthropic-ratelimit-tokens-limit"),
+            tokens_remaining: number(headers, "anthropic-ratelimit-tokens-remaining"),
+            tokens_reset: header(headers, "anthropic-ratelimit-tokens-reset").and_then(until_timestamp),
+            ..Self::empty()
+        }
+        .nonempty()
+    }
+
+    /// Read `x-ratelimit-*` headers (OpenAI, Azure and most compatible servers)
+    pub fn from_openai_headers(headers: &HeaderMap) -> Option<Self> {
+        debug!("RateLimitStatus::from_openai_headers: called");
+        Self {
+            requests_limit: number(headers, "x-ratelimit-limit-requests"),
+            requests_remaining: number(headers, "x-ratelimit-remaining-requests"),
+            requests_reset: header(headers, "x-ratelimit-reset-requests").and_then(parse_reset_duration),
+            tokens_limit: number(headers, "x-ratelimit-limit-tokens"),
+            tokens_remaining: number(headers, "x-ratelimit-remaining-tokens"),
+            tokens_reset: header(headers, "x-ratelimit-reset-tokens").and_then(parse_reset_duration),
+            ..Self::empty()
+        }
+        .nonempty()
+    }
+
+    /// Smallest remaining fraction across requests and tokens (0.0 to 1.0)
+    pub fn remaining_fraction(&self) -> Option<f64> {
+        let fraction = |remaining: Option<u64>, limit: Option<u64>| match (remaining, limit) {
+            (Some(remaining), Some(limit)) if limit > 0 => Some((remaining as f64 / limit as f64).min(1.0)),
+            _ => None,
+        };
+        let requests = fraction(self.requests_remaining, self.requests_limit);
+        let tokens = fraction(self.tokens_remaining, self.tokens_limit);
+        match (requests, tokens) {
+            (Some(r), Some(t)) => Some(r.min(t)),
+            (r, t) => r.or(t),
+        }
+    }
+
+    /// Time until the scarcer allowance is replenished
+    pub fn reset_after(&self) -> Option<Duration> {
+        let requests = self.requests_remaining.zip(self.requests_limit);
+        let tokens = self.tokens_remaining.zip(self.tokens_limit);
+        let scarce_is_tokens = match (requests, tokens) {
+            (Some((rr, rl)), Some((tr, tl))) => (tr as f64 / tl.max(1) as f64) < (rr as f64 / rl.max(1) as f64),
+            (None, Some(_)) => true,
+            _ => false,
+        };
+        if scarce_is_tokens {
+            self.tokens_reset.or(self.requests_reset)
+        } else {
+            self.requests_reset.or(self.tokens_reset)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, value.parse().unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn test_parse_reset_duration() {
+        assert_eq!(parse_reset_duration("1s"), Some(Duration::from_secs(1)));
+        assert_eq!(parse_reset_duration("6m0s"), Some(Duration::from_secs(360)));
+        assert_eq!(parse_reset_duration("20ms"), Some(Duration::from_millis(20)));
+        assert_eq!(parse_reset_duration("1h2m3s"), Some(Duration::from_secs(3723)));
+        assert_eq!(parse_reset_duration("7"), Some(Duration::from_secs(7)));
+        assert!(parse_reset_duration("59.5s").unwrap().as_millis() == 59_500);
+        assert_eq!(parse_reset_duration("soon"), None);
+        assert_eq!(parse_reset_duration(""), None);
+    }
+
+    #[test]
+    fn test_from_openai_headers() {
+        let status = RateLimitStatus::from_openai_headers(&headers(&[
+            ("x-ratelimit-limit-requests", "500"),
+            ("x-ratelimit-remaining-requests", "450"),
+            ("x-ratelimit-reset-requests", "12s"),
+            ("x-ratelimit-limit-tokens", "30000"),
+            ("x-ratelimit-remaining-tokens", "3000"),
+            ("x-ratelimit-reset-tokens", "1m30s"),
+        ]))
+        .unwrap();
+
+        assert_eq!(status.requests_remaining, Some(450));
+        assert!((status.remaining_fraction().unwrap() - 0.1).abs() < 1e-9);
+        assert_eq!(status.reset_after(), Some(Duration::from_secs(90)));
+        assert!(RateLimitStatus::from_openai_headers(&HeaderMap::new()).is_none());
+    }
+
+    #[test]
+    fn test_from_anthropic_headers() {
+        let reset = (Utc::now() + chrono::Duration::seconds(30)).to_rfc3339();
+        let status = RateLimitStatus::from_anthropic_headers(&headers(&[
+            ("anthropic-ratelimit-requests-limit", "50"),
+            ("anthropic-ratelimit-requests-remaining", "0"),
+            ("anthropic-ratelimit-requests-reset", &reset),
+            ("anthropic-ratelimit-tokens-limit", "40000"),
+            ("anthropic-ratelimit-tokens-remaining", "39000"),
+        ]))
+        .unwrap();
+
+        assert_eq!(status.remaining_fraction(), Some(0.0));
+        let after = status.reset_after().unwrap();
+        assert!(after > Duration::from_secs(25) && after <= Duration::from_secs(30));
+    }
+}
@@ -3,9 +3,16 @@
 //! These types model the Anthropic Messages API but are provider-agnostic enough
 //! to support other providers in the future.
 
+use std::path::Path;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
 use serde::{Deserialize, Serialize};
 use tracing::debug;
 
+/// Largest image accepted in a request, before base64 encoding (Anthropic's per-image limit)
+pub const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+
 /// A completion request - everything needed for one LLM call
 #[derive(Debug, Clone)]
 pub struct CompletionRequest {
@@ -121,6 +128,10 @@ pub enum ContentBlock {
         #[serde(default)]
         is_error: bool,
     },
+
+    /// Base64-encoded image (PNG, JPEG, GIF or WebP)
+    #[serde(rename = "image")]
+    Image { media_type: String, data: String },
 }
 
 impl ContentBlock {
@@ -139,6 +150,47 @@ impl ContentBlock {
             is_error,
         }
     }
+
+    /// Create an image block from raw bytes, detecting the format from its signature
+    pub fn image_from_bytes(bytes: &[u8], max_bytes: usize) -> Result<Self, String> {
+        debug!(len = bytes.len(), max_bytes, "ContentBlock::image_from_bytes: called");
+        if bytes.len() > max_bytes {
+            return Err(format!(
+                "Image is {} bytes, over the {} byte limit",
+                bytes.len(),
+                max_bytes
+            ));
+        }
+        let media_type = image_media_type(bytes).ok_or("Unsupported image format (expected PNG, JPEG, GIF or WebP)")?;
+        Ok(ContentBlock::Image {
+            media_type: media_type.to_string(),
+            data: BASE64.encode(bytes),
+        })
+    }
+
+    /// Create an image block from a file, checking its size before reading it
+    pub fn image_from_path(path: &Path, max_bytes: usize) -> Result<Self, String> {
+        debug!(?path, max_bytes, "ContentBlock::image_from_path: called");
+        let len = std::fs::metadata(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+            .len();
+        if len > max_bytes as u64 {
+            return Err(format!("Image is {} bytes, over the {} byte limit", len, max_bytes));
+        }
+        let bytes = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::image_from_bytes(&bytes, max_bytes)
+    }
+}
+
+/// Media type of an image from its leading bytes
+fn image_media_type(bytes: &[u8]) -> Option<&'static str> {
+    match bytes {
+        [0x89, b'P', b'N', b'G', ..] => Some("image/png"),
+        [0xFF, 0xD8, 0xFF, ..] => Some("image/jpeg"),
+        [b'G', b'I', b'F', b'8', ..] => Some("image/gif"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some("image/webp"),
+        _ => None,
+    }
 }
 
 /// Response from a completion request
@@ -155,10 +207,13 @@ pub struct CompletionResponse {
 
     /// Token usage for cost tracking
     pub usage: TokenUsage,
+
+    /// "provider/model" that produced the response (set by a failover chain)
+    pub served_by: Option<String>,
 }
 
 /// A tool call requested by the model
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolCall {
     pub id: String,
     pub name: String,
@@ -417,4 +472,19 @@ mod tests {
             _ => panic!("Expected ToolResult block"),
         }
     }
+
+    #[test]
+    fn test_content_block_image_from_bytes() {
+        let png = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+        match ContentBlock::image_from_bytes(&png, 1024).unwrap() {
+            ContentBlock::Image { media_type, data } => {
+                assert_eq!(media_type, "image/png");
+                assert_eq!(data, "iVBORw0KGgo=");
+            }
+            _ => panic!("Expected Image block"),
+        }
+
+        assert!(ContentBlock::image_from_bytes(&png, 4).unwrap_err().contains("limit"));
+        assert!(ContentBlock::image_from_bytes(b"plain text", 1024).is_err());
+    }
 }
@@ -0,0 +1,459 @@
+//! Orchestration load test (`td loadtest`)
+//!
+//! Runs many loops through the real TaskManager, Scheduler, Coordinator and
+//! EventBus, with a simulated [`MockLlmClient`] standing in for the provider.
+//! The profile sets the mock's latency and how often it answers with a 429.
+//! Everything happens in a scratch directory: a throwaway git repository,
+//! worktrees, TaskStore and event logs, which are removed afterwards. Each
+//! loop passes validation on its first successful iteration, so the numbers
+//! reflect orchestration overhead rather than model behavior.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use eyre::{Context, Result};
+use serde::Serialize;
+use tokio::process::Command;
+use tracing::debug;
+
+use crate::config::LoopsConfig;
+use crate::coordinator::Coordinator;
+use crate::domain::{LoopExecution, LoopExecutionStatus};
+use crate::events::{DEFAULT_CHANNEL_CAPACITY, EventBus, OverflowPolicy};
+use crate::llm::client::mock::MockLlmClient;
+use crate::r#loop::{LoopConfig, LoopLoader, TaskManager, TaskManagerConfig};
+use crate::scheduler::{Scheduler, SchedulerConfig};
+use crate::state::StateManager;
+
+/// Loop type the load test's executions run as
+pub const LOADTEST_LOOP_TYPE: &str = "loadtest";
+
+/// Default number of loops
+pub const DEFAULT_LOOPS: usize = 20;
+
+/// Give up waiting for loops after this long
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// How often to check whether all loops have finished
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Simulated provider behavior
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LoadProfile {
+    /// Fast, consistent responses and no rate limiting
+    #[default]
+    Steady,
+    /// Variable latency, 10% of calls rate limited
+    Flaky,
+    /// Slow responses, 30% of calls rate limited
+    Overloaded,
+}
+
+impl LoadProfile {
+    /// Base latency, jitter, share of calls answered with a 429, and its retry-after
+    fn parameters(&self) -> (Duration, Duration, f64, Duration) {
+        match self {
+            Self::Steady => (
+                Duration::from_millis(50),
+                Duration::from_millis(20),
+                0.0,
+                Duration::ZERO,
+            ),
+            Self::Flaky => (
+                Duration::from_millis(200),
+                Duration::from_millis(300),
+                0.1,
+                Duration::from_secs(1),
+            ),
+            Self::Overloaded => (
+                Duration::from_millis(800),
+                Duration::from_millis(800),
+                0.3,
+                Duration::from_secs(2),
+            ),
+        }
+    }
+
+    /// A simulated client behaving like this profile
+    pub fn client(&self, seed: u64) -> MockLlmClient {
+        debug!(profile = %self, seed, "LoadProfile::client: called");
+        let (latency, jitter, rate_limit_ratio, retry_after) = self.parameters();
+        MockLlmClient::simulated()
+            .with_latency(latency, jitter)
+            .with_rate_limits(rate_limit_ratio, retry_after)
+            .with_seed(seed)
+    }
+}
+
+impl std::str::FromStr for LoadProfile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        debug!(%s, "LoadProfile::from_str: called");
+        match s.to_lowercase().as_str() {
+            "steady" => Ok(Self::Steady),
+            "flaky" => Ok(Self::Flaky),
+            "overloaded" => Ok(Self::Overloaded),
+            _ => Err(format!(
+                "Unknown load profile: {}. Use: steady, flaky, or overloaded",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for LoadProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Steady => write!(f, "steady"),
+            Self::Flaky => write!(f, "flaky"),
+            Self::Overloaded => write!(f, "overloaded"),
+        }
+    }
+}
+
+/// Load test settings
+#[derive(Debug, Clone)]
+pub struct LoadTestOptions {
+    /// Loops to run
+    pub loops: usize,
+    /// Simulated provider behavior
+    pub profile: LoadProfile,
+    /// Seed for the simulated latency and 429s
+    pub seed: u64,
+    /// Scheduler limits (concurrency, requests per window)
+    pub scheduler: SchedulerConfig,
+    /// Give up after this long
+    pub timeout: Duration,
+}
+
+impl Default for LoadTestOptions {
+    fn default() -> Self {
+        Self {
+            loops: DEFAULT_LOOPS,
+            profile: LoadProfile::default(),
+            seed: 0,
+            scheduler: SchedulerConfig::default(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+impl LoadTestOptions {
+    /// Builder: number of loops
+    pub fn with_loops(mut self, loops: usize) -> Self {
+        debug!(loops, "LoadTestOptions::with_loops: called");
+        self.loops = loops;
+        self
+    }
+
+    /// Builder: simulated provider behavior
+    pub fn with_profile(mut self, profile: LoadProfile) -> Self {
+        debug!(%profile, "LoadTestOptions::with_profile: called");
+        self.profile = profile;
+        self
+    }
+
+    /// Builder: seed for the simulated latency and 429s
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        debug!(seed, "LoadTestOptions::with_seed: called");
+        self.seed = seed;
+        self
+    }
+
+    /// Builder: scheduler limits
+    pub fn with_scheduler(mut self, scheduler: SchedulerConfig) -> Self {
+        debug!(?scheduler, "LoadTestOptions::with_scheduler: called");
+        self.scheduler = scheduler;
+        self
+    }
+
+    /// Builder: give up after this long
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        debug!(?timeout, "LoadTestOptions::with_timeout: called");
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// What a load test measured
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct LoadTestReport {
+    pub profile: LoadProfile,
+    pub loops: usize,
+    pub completed: usize,
+    pub failed: usize,
+    /// Loops still unfinished when the timeout hit
+    pub unfinished: usize,
+    pub elapsed_ms: u64,
+    /// Calls made to the simulated provider
+    pub llm_calls: u64,
+    /// Of which answered with an injected 429
+    pub llm_rate_limited: u64,
+    /// Times the scheduler turned a request away for its own rate window
+    pub scheduler_rate_limited: u64,
+    pub mean_queue_wait_ms: Option<f64>,
+    pub max_queue_wait_ms: u64,
+    pub peak_queue_depth: usize,
+    pub peak_concurrent: usize,
+    /// Events a TUI-style subscriber received
+    pub events_received: u64,
+    /// Events dropped from buffered subscribers' queues on overflow
+    pub events_dropped: u64,
+}
+
+impl LoadTestReport {
+    /// Finished loops per minute
+    pub fn loops_per_minute(&self) -> f64 {
+        let minutes = self.elapsed_ms as f64 / 60_000.0;
+        if minutes > 0.0 {
+            (self.completed + self.failed) as f64 / minutes
+        } else {
+            0.0
+        }
+    }
+
+    /// Provider calls per second
+    pub fn calls_per_second(&self) -> f64 {
+        let seconds = self.elapsed_ms as f64 / 1000.0;
+        if seconds > 0.0 { self.llm_calls as f64 / seconds } else { 0.0 }
+    }
+
+    /// Render as text
+    pub fn render(&self) -> String {
+        debug!(loops = self.loops, "LoadTestReport::render: called");
+        use std::fmt::Write;
+        let mut out = String::new();
+        let _ = writeln!(out, "Load test: {} loops, profile {}", self.loops, self.profile);
+        let _ = writeln!(out);
+        let _ = writeln!(
+            out,
+            "Loops:       {} complete, {} failed, {} unfinished",
+            self.completed, self.failed, self.unfinished
+        );
+        let _ = writeln!(
+            out,
+            "Elapsed:     {:.1}s ({:.1} loops/min)",
+            self.elapsed_ms as f64 / 1000.0,
+            self.loops_per_minute()
+        );
+        let _ = writeln!(
+            out,
+            "LLM calls:   {} ({:.1}/s), {} rate limited by the provider",
+            self.llm_calls,
+            self.calls_per_second(),
+            self.llm_rate_limited
+        );
+        let _ = writeln!(
+            out,
+            "Queue wait:  mean {}, max {}ms, peak depth {}, peak concurrent {}",
+            self.mean_queue_wait_ms
+                .map_or("-".to_string(), |w| format!("{:.0}ms", w)),
+            self.max_queue_wait_ms,
+            self.peak_queue_depth,
+            self.peak_concurrent
+        );
+        let _ = writeln!(
+            out,
+            "Scheduler:   {} requests held back by the rate window",
+            self.scheduler_rate_limited
+        );
+        let _ = writeln!(
+            out,
+            "Events:      {} received, {} dropped",
+            self.events_received, self.events_dropped
+        );
+        out
+    }
+}
+
+/// Run `options.loops` loops through the orchestration and measure it
+pub async fn run_loadtest(options: LoadTestOptions) -> Result<LoadTestReport> {
+    debug!(loops = options.loops, profile = %options.profile, "run_loadtest: called");
+    let scratch = std::env::temp_dir().join(format!("td-loadtest-{}", uuid::Uuid::now_v7().simple()));
+    let result = run_in(&scratch, &options).await;
+    if let Err(e) = std::fs::remove_dir_all(&scratch) {
+        debug!(?scratch, error = %e, "run_loadtest: could not remove scratch directory");
+    }
+    result
+}
+
+async fn run_in(scratch: &Path, options: &LoadTestOptions) -> Result<LoadTestReport> {
+    debug!(?scratch, "run_in: called");
+    let repo_root = scratch.join("repo");
+    let store_dir = scratch.join("taskstore");
+    std::fs::create_dir_all(&repo_root)?;
+    std::fs::create_dir_all(&store_dir)?;
+    init_repo(&repo_root).await?;
+
+    let state = StateManager::spawn(&store_dir)?;
+    let event_bus = Arc::new(EventBus::with_default_capacity());
+
+    // A subscriber with the TUI's buffering, so dropped events show up in the report
+    let events_received = Arc::new(AtomicU64::new(0));
+    let mut events = event_bus.subscribe_buffered(DEFAULT_CHANNEL_CAPACITY, OverflowPolicy::DropTokenEventsFirst);
+    let counter = events_received.clone();
+    let counter_handle = tokio::spawn(async move {
+        while events.recv().await.is_some() {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+
+    let coordinator = Coordinator::with_persistence(Default::default(), &store_dir).with_event_bus(event_bus.clone());
+    let coordinator_tx = coordinator.sender();
+    let coord_handle = tokio::spawn(coordinator.run());
+
+    let llm = Arc::new(options.profile.client(options.seed));
+    let loop_config = LoopConfig {
+        loop_type: LOADTEST_LOOP_TYPE.to_string(),
+        prompt_template: "Load test task: {{task}}".to_string(),
+        validation_command: "true".to_string(),
+        max_iterations: 5,
+        ..Default::default()
+    };
+    let loop_configs = HashMap::from([(LOADTEST_LOOP_TYPE.to_string(), loop_config)]);
+    let type_loader = Arc::new(RwLock::new(LoopLoader::new(&LoopsConfig {
+        paths: vec!["builtin".to_string()],
+        ..Default::default()
+    })?));
+
+    let manager_config = TaskManagerConfig {
+        max_concurrent_tasks: options.loops.max(1),
+        poll_interval_secs: 1,
+        repo_root: repo_root.clone(),
+        worktree_dir: scratch.join("worktrees"),
+        runs_dir: Some(scratch.join("runs")),
+        ..Default::default()
+    };
+    let mut manager = TaskManager::new(
+        manager_config,
+        coordinator_tx,
+        Scheduler::new(options.scheduler.clone()),
+        llm.clone(),
+        state.clone(),
+        loop_configs,
+        type_loader,
+    )
+    .with_event_bus(event_bus.clone());
+    let scheduler = manager.scheduler();
+
+    let started = Instant::now();
+    let (shutdown_tx, shutdown_rx) = tokio::sync::mpsc::channel::<()>(1);
+    let manager_handle = tokio::spawn(async move { manager.run(shutdown_rx, None).await });
+
+    for i in 1..=options.loops {
+        let mut exec = LoopExecution::new(LOADTEST_LOOP_TYPE, format!("load test {}", i));
+        exec.set_context(serde_json::json!({ "task": format!("simulated task {}", i) }));
+        state.create_execution(exec).await?;
+    }
+
+    let deadline = started + options.timeout;
+    let executions = loop {
+        let executions = state
+            .list_executions(None, Some(LOADTEST_LOOP_TYPE.to_string()))
+            .await?;
+        let finished = executions.iter().filter(|e| e.is_terminal()).count();
+        debug!(finished, total = executions.len(), "run_in: polling for completion");
+        if finished == options.loops || Instant::now() >= deadline {
+            break executions;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    };
+    let elapsed = started.elapsed();
+
+    let _ = shutdown_tx.send(()).await;
+    match manager_handle.await {
+        Ok(result) => result.context("TaskManager failed during the load test")?,
+        Err(e) => debug!(error = %e, "run_in: manager task panicked"),
+    }
+    coord_handle.abort();
+    counter_handle.abort();
+
+    let count = |status: LoopExecutionStatus| executions.iter().filter(|e| e.status == status).count();
+    let completed = count(LoopExecutionStatus::Complete);
+    let failed = executions.iter().filter(|e| e.is_terminal()).count() - completed;
+    let stats = scheduler.stats().await;
+    let report = LoadTestReport {
+        profile: options.profile,
+        loops: options.loops,
+        completed,
+        failed,
+        unfinished: options.loops - completed - failed,
+        elapsed_ms: elapsed.as_millis() as u64,
+        llm_calls: llm.call_count() as u64,
+        llm_rate_limited: llm.rate_limited_count() as u64,
+        scheduler_rate_limited: stats.total_rate_limited,
+        mean_queue_wait_ms: stats.mean_queue_wait_ms(),
+        max_queue_wait_ms: stats.max_queue_wait_ms,
+        peak_queue_depth: stats.peak_queue_depth,
+        peak_concurrent: stats.peak_concurrent,
+        events_received: events_received.load(Ordering::Relaxed),
+        events_dropped: event_bus.dropped_events(),
+    };
+    state.shutdown().await?;
+    debug!(completed, failed, "run_in: complete");
+    Ok(report)
+}
+
+/// Create a git repository with one commit for the worktrees to branch from
+async fn init_repo(repo_root: &PathBuf) -> Result<()> {
+    debug!(?repo_root, "init_repo: called");
+    let git = |args: &'static [&'static str]| {
+        let mut cmd = Command::new("git");
+        cmd.args([
+            "-c",
+            "user.name=taskdaemon",
+            "-c",
+            "user.email=loadtest@taskdaemon.local",
+        ])
+        .args(args)
+        .current_dir(repo_root);
+        cmd
+    };
+    for args in [
+        &["init", "-q", "-b", "main"][..],
+        &["commit", "-q", "--allow-empty", "-m", "Load test base"][..],
+    ] {
+        let output = git(args).output().await.context("Failed to run git")?;
+        if !output.status.success() {
+            return Err(eyre::eyre!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_profile_from_str() {
+        assert_eq!("flaky".parse::<LoadProfile>().unwrap(), LoadProfile::Flaky);
+        assert_eq!("Steady".parse::<LoadProfile>().unwrap(), LoadProfile::Steady);
+        assert!("chaos".parse::<LoadProfile>().is_err());
+        assert_eq!(LoadProfile::Overloaded.to_string(), "overloaded");
+    }
+
+    #[tokio::test]
+    async fn test_run_loadtest_steady() {
+        let options = LoadTestOptions::default()
+            .with_loops(3)
+            .with_timeout(Duration::from_secs(60));
+        let report = run_loadtest(options).await.unwrap();
+        assert_eq!(report.completed, 3, "{}", report.render());
+        assert_eq!(report.unfinished, 0);
+        assert!(report.llm_calls >= 3);
+        assert_eq!(report.llm_rate_limited, 0);
+        assert!(report.events_received > 0);
+        assert!(report.render().contains("3 complete"));
+    }
+}
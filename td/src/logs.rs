@@ -0,0 +1,371 @@
+//! Execution logs
+//!
+//! Renders one execution's activity as readable output for `td exec logs`:
+//! iteration markers, the LLM's text, tool calls with their results, and
+//! validation commands with their output. Past activity comes from the event
+//! log (`~/.taskdaemon/runs/{id}/events.jsonl`); with `--follow` the daemon's
+//! live events for the execution are streamed over IPC until it terminates
+//! or the user presses Ctrl+C.
+
+use std::io::Write;
+use std::time::Duration;
+
+use eyre::Result;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::debug;
+
+use crate::domain::LoopExecutionStatus;
+use crate::events::{Event, IterationOutcome};
+use crate::ipc::EventStream;
+use crate::state::StateManager;
+
+/// How often a followed execution's status is checked (it may end without a LoopCompleted event)
+pub const STATUS_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Longest tool result shown, in characters
+const MAX_RESULT_CHARS: usize = 200;
+
+/// First line of `text`, cut to `max` characters
+fn first_line(text: &str, max: usize) -> String {
+    let line = text.trim().lines().next().unwrap_or("");
+    if line.chars().count() > max {
+        format!("{}...", line.chars().take(max).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+/// What an iteration's outcome was, e.g. "validation failed (exit 1)"
+fn outcome_text(outcome: &IterationOutcome) -> String {
+    match outcome {
+        IterationOutcome::ValidationPassed => "validation passed".to_string(),
+        IterationOutcome::ValidationFailed { exit_code } => format!("validation failed (exit {})", exit_code),
+        IterationOutcome::MaxTurnsReached => "max turns reached".to_string(),
+        IterationOutcome::ToolError { tool, error } => format!("{} error: {}", tool, error),
+        IterationOutcome::LlmError { error, code: Some(code) } => format!("LLM error [{}]: {}", code, error),
+        IterationOutcome::LlmError { error, code: None } => format!("LLM error: {}", error),
+        IterationOutcome::TimedOut { cause } => cause.clone(),
+    }
+}
+
+/// Turns events into log output
+///
+/// Streamed LLM tokens are printed as they arrive, on one running line; the
+/// response's summary is only printed when no tokens were streamed for it.
+#[derive(Debug, Default)]
+pub struct LogPrinter {
+    /// Whether the last output was a streamed token (no newline yet)
+    streaming: bool,
+}
+
+impl LogPrinter {
+    /// Create a printer
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Output for an event (None for events the log leaves out)
+    pub fn render(&mut self, event: &Event) -> Option<String> {
+        if let Event::TokenReceived { token, .. } = event {
+            self.streaming = true;
+            return Some(token.clone());
+        }
+
+        let line = match event {
+            Event::LoopStarted {
+                loop_type,
+                task_description,
+                ..
+            } => format!("== {} loop started: {}", loop_type, first_line(task_description, 100)),
+            Event::PhaseStarted {
+                phase_name,
+                phase_index,
+                total_phases,
+                ..
+            } => format!("== phase {}/{}: {}", phase_index + 1, total_phases, phase_name),
+            Event::IterationStarted { iteration, .. } => format!("\n-- iteration {} --", iteration),
+            Event::IterationCompleted { iteration, outcome, .. } => {
+                format!("-- iteration {}: {}", iteration, outcome_text(outcome))
+            }
+            Event::LoopCompleted {
+                success,
+                total_iterations,
+                ..
+            } => format!(
+                "== loop {} after {} iterations",
+                if *success { "completed" } else { "failed" },
+                total_iterations
+            ),
+            Event::LoopStuck {
+                unchanged_iterations,
+                action,
+                ..
+            } => format!("!! stuck: no progress for {} iterations ({})", unchanged_iterations, action),
+            Event::OverrideChanged { setting, from, to, .. } => format!("** {}: {} -> {}", setting, from, to),
+            Event::ResponseCompleted { response_summary, .. } => {
+                // The text was already printed token by token
+                if std::mem::take(&mut self.streaming) {
+                    return Some("\n".to_string());
+                }
+                let text = response_summary.trim();
+                if text.is_empty() {
+                    return None;
+                }
+                text.to_string()
+            }
+            Event::ToolCallStarted {
+                tool_name,
+                tool_args_summary,
+                ..
+            } => format!("→ {} {}", tool_name, tool_args_summary),
+            Event::ToolCallCompleted {
+                tool_name,
+                success,
+                result_summary,
+                duration_ms,
+                ..
+            } => format!(
+                "  {} {} ({}ms) {}",
+                if *success { "✓" } else { "✗" },
+                tool_name,
+                duration_ms,
+                first_line(result_summary, MAX_RESULT_CHARS)
+            )
+            .trim_end()
+            .to_string(),
+            Event::ResourceLimitExceeded {
+                tool_name,
+                resource,
+                limit,
+                ..
+            } => format!("  ✗ {} hit the {} limit ({})", tool_name, resource, limit),
+            Event::NetworkRequest {
+                tool_name,
+                target,
+                allowed: false,
+                ..
+            } => format!("  ✗ {} denied network access to {}", tool_name, target),
+            Event::RateLimited { retry_after_ms, .. } => format!("!! rate limited, retrying in {}ms", retry_after_ms),
+            Event::ValidationStarted { command, .. } => format!("$ {}", command),
+            Event::ValidationOutput { line, .. } => format!("  {}", line),
+            Event::ValidationCompleted {
+                exit_code, duration_ms, ..
+            } => format!("$ exit {} ({}ms)", exit_code, duration_ms),
+            Event::Error { context, message, .. } => format!("!! error: {}: {}", context, message),
+            Event::Warning { context, message, .. } => format!("!! warning: {}: {}", context, message),
+            _ => return None,
+        };
+
+        // End a line of streamed tokens before anything else
+        let prefix = if std::mem::take(&mut self.streaming) { "\n" } else { "" };
+        Some(format!("{}{}\n", prefix, line))
+    }
+
+    /// Print an event's output to stdout
+    pub fn print(&mut self, event: &Event) {
+        if let Some(output) = self.render(event) {
+            let mut stdout = std::io::stdout().lock();
+            let _ = stdout.write_all(output.as_bytes());
+            let _ = stdout.flush();
+        }
+    }
+}
+
+/// Why following an execution stopped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FollowEnd {
+    /// The execution reached a terminal status
+    Finished(LoopExecutionStatus),
+    /// The daemon closed the event stream
+    Disconnected,
+    /// Ctrl+C
+    Interrupted,
+}
+
+/// Read `stream` on its own task, so the events can be received in a `select!`
+///
+/// Reading a line is not cancellation safe. The channel carries everything
+/// the stream returns up to and including its end (`Ok(None)`) or an error.
+pub fn spawn_reader(stream: EventStream) -> (JoinHandle<()>, mpsc::Receiver<Result<Option<Event>>>) {
+    debug!("spawn_reader: called");
+    let (tx, rx) = mpsc::channel(256);
+    let reader = tokio::spawn(async move {
+        let mut stream = stream;
+        loop {
+            let next = stream.next().await;
+            let done = !matches!(next, Ok(Some(_)));
+            if tx.send(next).await.is_err() || done {
+                break;
+            }
+        }
+    });
+    (reader, rx)
+}
+
+/// Print `exec_id`'s live events from `stream` until it terminates, the daemon goes away, or Ctrl+C
+pub async fn follow(
+    state: &StateManager,
+    exec_id: &str,
+    stream: EventStream,
+    printer: &mut LogPrinter,
+) -> Result<FollowEnd> {
+    debug!(%exec_id, "follow: called");
+    let (reader, mut rx) = spawn_reader(stream);
+
+    let mut poll = tokio::time::interval(STATUS_POLL_INTERVAL);
+    let end = loop {
+        tokio::select! {
+            next = rx.recv() => match next {
+                Some(Ok(Some(event))) => {
+                    printer.print(&event);
+                    if let Event::LoopCompleted { success, .. } = event {
+                        debug!(%exec_id, success, "follow: loop completed");
+                        let status = if success { LoopExecutionStatus::Complete } else { LoopExecutionStatus::Failed };
+                        break FollowEnd::Finished(status);
+                    }
+                }
+                Some(Err(e)) => {
+                    reader.abort();
+                    return Err(e);
+                }
+                Some(Ok(None)) | None => {
+                    debug!(%exec_id, "follow: daemon closed the stream");
+                    break FollowEnd::Disconnected;
+                }
+            },
+            _ = poll.tick() => {
+                if let Some(exec) = state.get_execution(exec_id).await?.filter(|e| e.is_terminal()) {
+                    debug!(%exec_id, status = ?exec.status, "follow: execution finished");
+                    break FollowEnd::Finished(exec.status);
+                }
+            }
+            _ = tokio::signal::ctrl_c() => {
+                debug!(%exec_id, "follow: interrupted");
+                break FollowEnd::Interrupted;
+            }
+        }
+    };
+    reader.abort();
+    Ok(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "exec-1";
+
+    fn render_all(events: &[Event]) -> String {
+        let mut printer = LogPrinter::new();
+        events.iter().filter_map(|e| printer.render(e)).collect()
+    }
+
+    #[test]
+    fn test_render_iteration() {
+        let events = vec![
+            Event::IterationStarted {
+                execution_id: ID.to_string(),
+                iteration: 2,
+            },
+            Event::TokenReceived {
+                execution_id: ID.to_string(),
+                iteration: 2,
+                token: "Fixing ".to_string(),
+            },
+            Event::TokenReceived {
+                execution_id: ID.to_string(),
+                iteration: 2,
+                token: "the parser".to_string(),
+            },
+            Event::ResponseCompleted {
+                execution_id: ID.to_string(),
+                iteration: 2,
+                response_summary: "Fixing the parser".to_string(),
+                input_tokens: 100,
+                output_tokens: 10,
+                has_tool_calls: true,
+                served_by: None,
+            },
+            Event::ToolCallStarted {
+                execution_id: ID.to_string(),
+                iteration: 2,
+                tool_name: "edit".to_string(),
+                tool_args_summary: "src/parser.rs".to_string(),
+            },
+            Event::ToolCallCompleted {
+                execution_id: ID.to_string(),
+                iteration: 2,
+                tool_name: "edit".to_string(),
+                success: true,
+                result_summary: "Edited src/parser.rs\n3 lines changed".to_string(),
+                duration_ms: 4,
+            },
+            Event::ResponseCompleted {
+                execution_id: ID.to_string(),
+                iteration: 2,
+                response_summary: "Done.".to_string(),
+                input_tokens: 100,
+                output_tokens: 2,
+                has_tool_calls: false,
+                served_by: None,
+            },
+            Event::ValidationStarted {
+                execution_id: ID.to_string(),
+                iteration: 2,
+                command: "cargo test".to_string(),
+            },
+            Event::ValidationOutput {
+                execution_id: ID.to_string(),
+                iteration: 2,
+                line: "test result: ok".to_string(),
+                is_stderr: false,
+            },
+            Event::ValidationCompleted {
+                execution_id: ID.to_string(),
+                iteration: 2,
+                exit_code: 0,
+                duration_ms: 1200,
+            },
+            Event::IterationCompleted {
+                execution_id: ID.to_string(),
+                iteration: 2,
+                outcome: IterationOutcome::ValidationPassed,
+            },
+        ];
+
+        assert_eq!(
+            render_all(&events),
+            "\n-- iteration 2 --\n\
+             Fixing the parser\n\
+             → edit src/parser.rs\n\
+             \x20 ✓ edit (4ms) Edited src/parser.rs\n\
+             Done.\n\
+             $ cargo test\n\
+             \x20 test result: ok\n\
+             $ exit 0 (1200ms)\n\
+             -- iteration 2: validation passed\n"
+        );
+    }
+
+    #[test]
+    fn test_render_skips_quiet_events() {
+        let mut printer = LogPrinter::new();
+        let allowed = Event::NetworkRequest {
+            execution_id: ID.to_string(),
+            iteration: 1,
+            tool_name: "fetch".to_string(),
+            host: "docs.rs".to_string(),
+            target: "https://docs.rs".to_string(),
+            allowed: true,
+        };
+        assert!(printer.render(&allowed).is_none());
+        let prompt = Event::PromptSent {
+            execution_id: ID.to_string(),
+            iteration: 1,
+            prompt_summary: "Implement".to_string(),
+            token_count: 10,
+        };
+        assert!(printer.render(&prompt).is_none());
+    }
+}
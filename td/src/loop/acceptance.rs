@@ -0,0 +1,152 @@
+//! Acceptance criteria evaluation
+//!
+//! The LoopEngine calls `check_all` after each iteration's validation. Checks
+//! run in the execution's worktree: commands through `sh -c` with the same
+//! environment and timeout as validation, file globs and regex greps against
+//! the files on disk. A check that cannot run (bad regex, unreadable file,
+//! command timeout) fails with the reason as its detail.
+
+use std::path::Path;
+use std::time::Duration;
+
+use tracing::debug;
+
+use super::validation::run_validation;
+use super::wake::glob_matches;
+use crate::clock::Clock;
+use crate::domain::{AcceptanceCheck, AcceptanceCriterion, CriterionStatus};
+
+/// Longest failure detail kept on the execution
+const MAX_DETAIL_CHARS: usize = 500;
+
+/// Last `max` characters of `s`
+fn tail(s: &str, max: usize) -> String {
+    let skip = s.chars().count().saturating_sub(max);
+    s.chars().skip(skip).collect()
+}
+
+/// Check one criterion; `Err` carries why it does not hold
+pub async fn check_criterion(
+    criterion: &AcceptanceCriterion,
+    worktree: &Path,
+    env: &[(String, String)],
+    timeout: Duration,
+    clock: &dyn Clock,
+) -> Result<(), String> {
+    debug!(%criterion, "check_criterion: called");
+    match criterion {
+        AcceptanceCriterion::Command { command } => {
+            let result = run_validation(command, worktree, env, timeout, clock)
+                .await
+                .map_err(|e| e.to_string())?;
+            if result.passed(0) {
+                return Ok(());
+            }
+            let output = if result.stderr.trim().is_empty() { result.stdout } else { result.stderr };
+            Err(format!(
+                "exit code {}: {}",
+                result.exit_code,
+                tail(output.trim(), MAX_DETAIL_CHARS)
+            ))
+        }
+        AcceptanceCriterion::FileExists { path } => {
+            if glob_matches(worktree, path) {
+                Ok(())
+            } else {
+                Err(format!("no file matches {}", path))
+            }
+        }
+        AcceptanceCriterion::Grep { path, pattern } => {
+            let regex = regex::Regex::new(pattern).map_err(|e| format!("invalid pattern: {}", e))?;
+            let content = tokio::fs::read_to_string(worktree.join(path))
+                .await
+                .map_err(|e| format!("cannot read {}: {}", path, e))?;
+            if content.lines().any(|line| regex.is_match(line)) {
+                Ok(())
+            } else {
+                Err(format!("no line of {} matches", path))
+            }
+        }
+    }
+}
+
+/// Re-check every criterion, recording the result against `iteration`
+pub async fn check_all(
+    checks: &mut [AcceptanceCheck],
+    iteration: u32,
+    worktree: &Path,
+    env: &[(String, String)],
+    timeout: Duration,
+    clock: &dyn Clock,
+) {
+    debug!(count = checks.len(), iteration, "check_all: called");
+    for check in checks.iter_mut() {
+        let result = check_criterion(&check.criterion, worktree, env, timeout, clock).await;
+        check.iteration = iteration;
+        match result {
+            Ok(()) => {
+                check.status = CriterionStatus::Passed;
+                check.detail = None;
+            }
+            Err(detail) => {
+                debug!(criterion = %check.criterion, %detail, "check_all: criterion failed");
+                check.status = CriterionStatus::Failed;
+                check.detail = Some(detail);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_check_all_records_status_and_detail() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "pub mod writer;\n").unwrap();
+        let mut checks: Vec<AcceptanceCheck> = [
+            AcceptanceCriterion::Command {
+                command: "echo broken >&2; exit 3".to_string(),
+            },
+            AcceptanceCriterion::FileExists {
+                path: "*.rs".to_string(),
+            },
+            AcceptanceCriterion::Grep {
+                path: "lib.rs".to_string(),
+                pattern: r"^pub mod \w+;$".to_string(),
+            },
+            AcceptanceCriterion::Grep {
+                path: "missing.rs".to_string(),
+                pattern: "x".to_string(),
+            },
+        ]
+        .into_iter()
+        .map(AcceptanceCheck::pending)
+        .collect();
+
+        check_all(&mut checks, 4, dir.path(), &[], Duration::from_secs(10), &SystemClock).await;
+
+        let statuses: Vec<_> = checks.iter().map(|c| c.status).collect();
+        assert_eq!(
+            statuses,
+            [
+                CriterionStatus::Failed,
+                CriterionStatus::Passed,
+                CriterionStatus::Passed,
+                CriterionStatus::Failed
+            ]
+        );
+        assert_eq!(checks[0].detail.as_deref(), Some("exit code 3: broken"));
+        assert!(
+            checks[3]
+                .detail
+                .as_deref()
+                .unwrap()
+                .starts_with("cannot read missing.rs")
+        );
+        assert!(checks.iter().all(|c| c.iteration == 4));
+    }
+}
@@ -0,0 +1,264 @@
+//! Per-phase iteration budgets
+//!
+//! `max-iterations` caps a whole loop, but the work inside a loop usually has
+//! phases that need different depth: a few iterations reading the code, many
+//! more implementing. A loop type's `budget` section splits the iterations
+//! into named phases, each with its own allowance and instructions:
+//!
+//! ```yaml
+//! budget:
+//!   borrow: true
+//!   phases:
+//!     - name: explore
+//!       iterations: 3
+//!       until: test -f NOTES.md
+//!       prompt: Read the code and write your findings to NOTES.md. Do not edit sources.
+//!     - name: implement
+//!       iterations: 12
+//!       prompt: Implement the change described in NOTES.md.
+//! ```
+//!
+//! A phase ends when its `until` command exits 0 (without one, when the
+//! loop's validation passes) or when its allowance is used up. With `borrow`,
+//! iterations a phase didn't use roll into the next one. The loop can only
+//! complete from the final phase, and fails when the final phase's allowance
+//! runs out.
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// One phase of an iteration budget
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhaseBudget {
+    /// Phase name, unique within the budget (e.g. `explore`, `implement`)
+    pub name: String,
+
+    /// Iterations allotted to the phase
+    pub iterations: u32,
+
+    /// Instructions for this phase (Handlebars, exposed as `{{budget-phase-instructions}}`)
+    #[serde(default)]
+    pub prompt: String,
+
+    /// Command that ends the phase early when it exits 0; unset: the loop's validation
+    #[serde(default)]
+    pub until: Option<String>,
+}
+
+/// Iterations split across ordered phases (`budget` in loop YAML)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct IterationBudget {
+    /// Phases in the order they run (empty: no budget)
+    #[serde(default)]
+    pub phases: Vec<PhaseBudget>,
+
+    /// Roll iterations a phase didn't use into the next phase
+    #[serde(default)]
+    pub borrow: bool,
+}
+
+impl IterationBudget {
+    /// Check the budget is runnable: named phases, no duplicate names, non-zero allowances
+    pub fn validate(&self) -> Result<(), String> {
+        debug!(phases = self.phases.len(), "IterationBudget::validate: called");
+        let mut seen = std::collections::HashSet::new();
+        for phase in &self.phases {
+            if phase.name.trim().is_empty() {
+                return Err("budget phase without a name".to_string());
+            }
+            if !seen.insert(phase.name.as_str()) {
+                return Err(format!("duplicate budget phase '{}'", phase.name));
+            }
+            if phase.iterations == 0 {
+                return Err(format!("budget phase '{}' has no iterations", phase.name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Iterations across all phases, before borrowing
+    pub fn total(&self) -> u32 {
+        self.phases.iter().map(|phase| phase.iterations).sum()
+    }
+}
+
+/// What recording an iteration did to the budget
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PhaseStep {
+    /// The current phase continues
+    Stay,
+    /// The phase ended and the next one started
+    Advanced {
+        /// Iterations the finished phase used
+        used: u32,
+        /// Its allowance, including iterations borrowed from earlier phases
+        allowance: u32,
+        /// Iterations carried into the next phase
+        carried: u32,
+    },
+    /// The final phase used its whole allowance
+    Exhausted,
+}
+
+/// Position of a running loop within its iteration budget
+#[derive(Debug, Clone)]
+pub struct BudgetProgress {
+    budget: IterationBudget,
+    current: usize,
+    used: u32,
+    allowance: u32,
+}
+
+impl BudgetProgress {
+    /// Start at the first phase; None when the budget has no phases
+    pub fn new(budget: IterationBudget) -> Option<Self> {
+        debug!(phases = budget.phases.len(), "BudgetProgress::new: called");
+        let allowance = budget.phases.first()?.iterations;
+        Some(Self {
+            budget,
+            current: 0,
+            used: 0,
+            allowance,
+        })
+    }
+
+    /// The phase currently running
+    pub fn current(&self) -> &PhaseBudget {
+        &self.budget.phases[self.current]
+    }
+
+    /// 0-based index of the current phase
+    pub fn index(&self) -> usize {
+        self.current
+    }
+
+    /// Number of phases in the budget
+    pub fn total(&self) -> usize {
+        self.budget.phases.len()
+    }
+
+    /// Whether the current phase is the last one
+    pub fn is_final(&self) -> bool {
+        self.current + 1 == self.budget.phases.len()
+    }
+
+    /// Iterations left in the current phase
+    pub fn remaining(&self) -> u32 {
+        self.allowance.saturating_sub(self.used)
+    }
+
+    /// Whether the final phase has used its whole allowance
+    pub fn is_exhausted(&self) -> bool {
+        self.is_final() && self.remaining() == 0
+    }
+
+    /// Record one iteration of the current phase
+    ///
+    /// `done` is whether the phase's end condition held after the iteration.
+    /// The final phase never advances; the loop completes from it instead.
+    pub fn record(&mut self, done: bool) -> PhaseStep {
+        debug!(phase = %self.current().name, done, used = self.used, allowance = self.allowance, "BudgetProgress::record: called");
+        self.used += 1;
+        if self.is_final() {
+            return if self.remaining() == 0 {
+                PhaseStep::Exhausted
+            } else {
+                PhaseStep::Stay
+            };
+        }
+        if !done && self.remaining() > 0 {
+            return PhaseStep::Stay;
+        }
+
+        let (used, allowance) = (self.used, self.allowance);
+        let carried = if self.budget.borrow { self.remaining() } else { 0 };
+        self.current += 1;
+        self.used = 0;
+        self.allowance = self.current().iterations + carried;
+        PhaseStep::Advanced {
+            used,
+            allowance,
+            carried,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn phase(name: &str, iterations: u32) -> PhaseBudget {
+        PhaseBudget {
+            name: name.to_string(),
+            iterations,
+            prompt: String::new(),
+            until: None,
+        }
+    }
+
+    #[test]
+    fn test_budget_borrowing() {
+        let budget = IterationBudget {
+            phases: vec![phase("explore", 3), phase("design", 1), phase("implement", 2)],
+            borrow: true,
+        };
+        assert_eq!(budget.total(), 6);
+        let mut progress = BudgetProgress::new(budget).unwrap();
+
+        // explore ends after one iteration and lends the other two to design
+        assert_eq!(
+            progress.record(true),
+            PhaseStep::Advanced {
+                used: 1,
+                allowance: 3,
+                carried: 2
+            }
+        );
+        assert_eq!(progress.current().name, "design");
+        assert_eq!(progress.remaining(), 3);
+
+        // design runs out, nothing left to carry
+        assert_eq!(progress.record(false), PhaseStep::Stay);
+        assert_eq!(progress.record(false), PhaseStep::Stay);
+        assert_eq!(
+            progress.record(false),
+            PhaseStep::Advanced {
+                used: 3,
+                allowance: 3,
+                carried: 0
+            }
+        );
+        assert!(progress.is_final());
+
+        // The final phase ignores `done` and fails once its allowance is gone
+        assert_eq!(progress.record(true), PhaseStep::Stay);
+        assert!(!progress.is_exhausted());
+        assert_eq!(progress.record(false), PhaseStep::Exhausted);
+        assert!(progress.is_exhausted());
+    }
+
+    #[test]
+    fn test_budget_without_borrowing_and_validation() {
+        let budget = IterationBudget {
+            phases: vec![phase("explore", 4), phase("implement", 2)],
+            borrow: false,
+        };
+        let mut progress = BudgetProgress::new(budget.clone()).unwrap();
+        assert!(matches!(progress.record(true), PhaseStep::Advanced { carried: 0, .. }));
+        assert_eq!(progress.remaining(), 2);
+
+        assert!(BudgetProgress::new(IterationBudget::default()).is_none());
+        assert!(budget.validate().is_ok());
+        let duplicate = IterationBudget {
+            phases: vec![phase("explore", 1), phase("explore", 1)],
+            borrow: false,
+        };
+        assert!(duplicate.validate().unwrap_err().contains("duplicate"));
+        let empty = IterationBudget {
+            phases: vec![phase("explore", 0)],
+            borrow: false,
+        };
+        assert!(empty.validate().unwrap_err().contains("no iterations"));
+    }
+}
@@ -3,13 +3,21 @@
 //! When a loop completes, the cascade triggers child loops based on
 //! the parent-child relationships defined in loop type configs.
 //! Child types declare their parent via the `parent` field in YAML.
+//! Executions created for a record carry the acceptance criteria found in
+//! the record's markdown file, which is also kept on the record's typed
+//! Plan or Spec along with the plan -> spec decomposition links.
 
 use std::sync::{Arc, RwLock};
 
 use eyre::Result;
 use tracing::{debug, info, warn};
 
-use crate::domain::{Loop, LoopExecution, LoopStatus};
+use crate::clock::{ClockRef, IdGenRef, RandomIdGen, SystemClock};
+use crate::deps::{DEP_BUMP_TYPE, OutdatedDep, render_summary};
+use crate::domain::{
+    AcceptanceCriterion, Loop, LoopExecution, LoopExecutionStatus, LoopStatus, PLAN_TYPE, Plan, SPEC_TYPE, Spec,
+    Submitter, parse_acceptance,
+};
 use crate::state::StateManager;
 
 use super::type_loader::LoopLoader;
@@ -18,13 +26,111 @@ use super::type_loader::LoopLoader;
 pub struct CascadeHandler {
     state: Arc<StateManager>,
     type_loader: Arc<RwLock<LoopLoader>>,
+    id_gen: IdGenRef,
+    clock: ClockRef,
 }
 
 impl CascadeHandler {
     /// Create a new cascade handler
     pub fn new(state: Arc<StateManager>, type_loader: Arc<RwLock<LoopLoader>>) -> Self {
         debug!("CascadeHandler::new: called");
-        Self { state, type_loader }
+        Self {
+            state,
+            type_loader,
+            id_gen: RandomIdGen::shared(),
+            clock: SystemClock::shared(),
+        }
+    }
+
+    /// Generate child execution IDs with `id_gen`
+    pub fn with_id_gen(mut self, id_gen: IdGenRef) -> Self {
+        debug!("CascadeHandler::with_id_gen: called");
+        self.id_gen = id_gen;
+        self
+    }
+
+    /// Stamp child executions with `clock`
+    pub fn with_clock(mut self, clock: ClockRef) -> Self {
+        debug!("CascadeHandler::with_clock: called");
+        self.clock = clock;
+        self
+    }
+
+    /// Create a Loop record from the injected ID generator and clock
+    pub fn new_record(&self, r#type: &str, title: &str) -> Loop {
+        debug!(%r#type, %title, "CascadeHandler::new_record: called");
+        let mut record = Loop::with_id(self.id_gen.next_id(r#type, title), r#type, title);
+        record.created_at = self.clock.now_ms();
+        record.updated_at = record.created_at;
+        record
+    }
+
+    /// Create a pending child execution from the injected ID generator and clock
+    fn new_execution(&self, child_type: &str) -> LoopExecution {
+        debug!(%child_type, "CascadeHandler::new_execution: called");
+        let id = self.id_gen.next_id("loop", &format!("{}-{}", child_type, child_type));
+        let mut exec = LoopExecution::with_id(id, child_type);
+        exec.created_at = self.clock.now_ms();
+        exec.updated_at = exec.created_at;
+        exec
+    }
+
+    /// Who submitted execution `exec_id`, for its children to inherit
+    async fn submitter_of(&self, exec_id: &str) -> Option<Submitter> {
+        match self.state.get_execution(exec_id).await {
+            Ok(Some(exec)) => exec.submitted_by,
+            Ok(None) => None,
+            Err(e) => {
+                debug!(%exec_id, error = %e, "submitter_of: lookup failed");
+                None
+            }
+        }
+    }
+
+    /// Fill in the typed Plan or Spec of a record `exec` produced
+    ///
+    /// Records the producing execution and the document's acceptance criteria;
+    /// a spec is also linked to the plan whose execution spawned `exec`.
+    pub async fn record_typed(&self, record: &Loop, exec: &LoopExecution) -> Result<()> {
+        debug!(record_id = %record.id, r#type = %record.r#type, exec_id = %exec.id, "record_typed: called");
+        let acceptance = match &record.file {
+            Some(file) => acceptance_from_file(file).await,
+            None => Vec::new(),
+        };
+        match record.r#type.as_str() {
+            PLAN_TYPE => {
+                let mut plan = self
+                    .state
+                    .get_plan(&record.id)
+                    .await?
+                    .unwrap_or_else(|| Plan::from_loop(record));
+                plan.exec_id = Some(exec.id.clone());
+                plan.set_acceptance(acceptance);
+                self.state.save_plan(plan).await?;
+            }
+            SPEC_TYPE => {
+                let mut spec = self
+                    .state
+                    .get_spec(&record.id)
+                    .await?
+                    .unwrap_or_else(|| Spec::from_loop(record));
+                spec.exec_id = Some(exec.id.clone());
+                if spec.plan_id.is_none()
+                    && let Some(parent_exec) = &exec.parent
+                {
+                    spec.plan_id = self
+                        .state
+                        .get_plan_for_execution(parent_exec)
+                        .await?
+                        .map(|plan| plan.id);
+                    debug!(record_id = %record.id, plan_id = ?spec.plan_id, "record_typed: linked spec to plan");
+                }
+                spec.set_acceptance(acceptance);
+                self.state.save_spec(spec).await?;
+            }
+            _ => debug!(record_id = %record.id, "record_typed: not a plan or spec"),
+        }
+        Ok(())
     }
 
     /// Get child loop types for a given parent type
@@ -59,12 +165,15 @@ impl CascadeHandler {
 
         info!(id = %record.id, child_types = ?child_types, "Loop ready, creating child loops");
 
+        let submitter = self.submitter_of(parent_exec_id).await;
         let mut executions = Vec::new();
         for child_type in child_types {
             debug!(id = %record.id, %child_type, "on_loop_ready: creating child execution");
             // Create child execution - parent is the EXECUTION ID for tree hierarchy
-            let exec = LoopExecution::new(&child_type, &child_type)
+            let exec = self
+                .new_execution(&child_type)
                 .with_parent(parent_exec_id)
+                .with_submitter(submitter.clone())
                 .with_context_value("parent-id", parent_exec_id)
                 .with_context_value("parent-type", &record.r#type)
                 .with_context_value("parent-title", &record.title);
@@ -76,14 +185,88 @@ impl CascadeHandler {
                 debug!(id = %record.id, %child_type, "on_loop_ready: child has no parent file");
                 exec
             };
-            self.state.create_loop_execution(exec.clone()).await?;
-            info!(exec_id = %exec.id, %parent_exec_id, child_type = %child_type, "Created child loop");
             executions.push(exec);
         }
 
+        // All children or none, so a failed write can't leave a partial cascade
+        self.state
+            .transaction(|tx| {
+                for exec in &executions {
+                    tx.create_execution(exec.clone());
+                }
+            })
+            .await?;
+        for exec in &executions {
+            info!(exec_id = %exec.id, %parent_exec_id, child_type = %exec.loop_type, "Created child loop");
+        }
+
+        Ok(executions)
+    }
+
+    /// Create one `dep-bump` execution per dependency a `deps-upgrade` loop saved
+    ///
+    /// The children run one at a time (their loop type holds an exclusive
+    /// group), each starting from main with the previous upgrades merged.
+    pub async fn on_outdated_listed(
+        &self,
+        parent: &LoopExecution,
+        outdated: &[OutdatedDep],
+    ) -> Result<Vec<LoopExecution>> {
+        debug!(parent_id = %parent.id, count = outdated.len(), "on_outdated_listed: called");
+        let mut executions = Vec::new();
+        for dep in outdated {
+            let exec = self
+                .new_execution(DEP_BUMP_TYPE)
+                .with_title(format!("Upgrade {} to {}", dep.name, dep.latest))
+                .with_parent(&parent.id)
+                .with_priority(parent.priority)
+                .with_submitter(parent.submitted_by.clone())
+                .with_context_value(
+                    "task",
+                    &format!("Upgrade {} from {} to {}", dep.name, dep.current, dep.latest),
+                )
+                .with_context_value("dep-name", &dep.name)
+                .with_context_value("dep-ecosystem", &dep.ecosystem.to_string())
+                .with_context_value("dep-kind", &dep.kind.to_string())
+                .with_context_value("dep-current", &dep.current)
+                .with_context_value("dep-latest", &dep.latest)
+                .with_context_value("dep-bump-command", &dep.bump_command())
+                .with_context_value("dep-lockfile", dep.ecosystem.lockfile());
+            executions.push(exec);
+        }
+        self.state
+            .transaction(|tx| {
+                for exec in &executions {
+                    tx.create_execution(exec.clone());
+                }
+            })
+            .await?;
+        for (exec, dep) in executions.iter().zip(outdated) {
+            info!(exec_id = %exec.id, parent_id = %parent.id, dep = %dep.name, "Created dependency upgrade loop");
+        }
         Ok(executions)
     }
 
+    /// Summary table of a `deps-upgrade` run once all its `dep-bump` children finished
+    ///
+    /// Returns None while any child is still pending, running or waiting for review.
+    pub async fn join_dep_upgrades(&self, parent_id: &str) -> Result<Option<String>> {
+        debug!(%parent_id, "join_dep_upgrades: called");
+        let mut children: Vec<LoopExecution> = self
+            .state
+            .list_executions(None, Some(DEP_BUMP_TYPE.to_string()))
+            .await?
+            .into_iter()
+            .filter(|exec| exec.parent.as_deref() == Some(parent_id))
+            .collect();
+        if children.iter().any(|exec| !exec.is_terminal()) {
+            debug!(%parent_id, "join_dep_upgrades: children still running");
+            return Ok(None);
+        }
+        children.sort_by_key(|exec| exec.created_at);
+        Ok(Some(render_summary(&children)))
+    }
+
     /// Handle completion of decomposition (creates children)
     ///
     /// When decomposition completes, update the parent Loop status to InProgress
@@ -138,44 +321,73 @@ impl CascadeHandler {
     /// Create child loop executions for a Loop record based on type hierarchy
     async fn create_child_loops_for_record(&self, record: &Loop) -> Result<Vec<LoopExecution>> {
         debug!(record_id = %record.id, r#type = %record.r#type, "create_child_loops_for_record: called");
+        let executions = self.child_executions_for_record(record).await?;
+        self.state
+            .transaction(|tx| {
+                for exec in &executions {
+                    tx.create_execution(exec.clone());
+                }
+            })
+            .await?;
+        log_child_loops(record, &executions);
+        Ok(executions)
+    }
+
+    /// Child loop executions a Loop record should get next (not yet stored)
+    async fn child_executions_for_record(&self, record: &Loop) -> Result<Vec<LoopExecution>> {
+        debug!(record_id = %record.id, r#type = %record.r#type, "child_executions_for_record: called");
         // Check if there's already a running execution for this record
         let existing = self.state.get_loop_execution_for_spec(&record.id).await?;
         if let Some(existing) = existing
             && !existing.is_terminal()
         {
-            debug!(record_id = %record.id, exec_id = %existing.id, "create_child_loops_for_record: execution already running");
+            debug!(record_id = %record.id, exec_id = %existing.id, "child_executions_for_record: execution already running");
             return Ok(vec![]);
         }
 
         // Find child loop types for this record's type
         let child_types = self.get_child_types(&record.r#type);
         if child_types.is_empty() {
-            debug!(record_id = %record.id, loop_type = %record.r#type, "create_child_loops_for_record: no child loop types defined");
+            debug!(record_id = %record.id, loop_type = %record.r#type, "child_executions_for_record: no child loop types defined");
             return Ok(vec![]);
         }
-        debug!(record_id = %record.id, ?child_types, "create_child_loops_for_record: found child types");
+        debug!(record_id = %record.id, ?child_types, "child_executions_for_record: found child types");
+
+        // Dependency edges: the executions that produced this record's deps
+        let deps = self.dependency_executions(record).await?;
 
         // Get current phase if record has phases
         let current_phase_idx = record.current_phase_index().unwrap_or(0);
         let current_phase = record.phases.get(current_phase_idx);
 
+        // Submitter of the execution that produced the record
+        let submitter = match record.context.get("exec_id").and_then(|v| v.as_str()) {
+            Some(exec_id) => self.submitter_of(exec_id).await,
+            None => None,
+        };
+
         let mut executions = Vec::new();
         for child_type in child_types {
             // Create child execution - title will be generated by LLM when loop starts
-            let mut exec = LoopExecution::new(&child_type, &child_type)
+            let mut exec = self
+                .new_execution(&child_type)
                 .with_parent(&record.id)
+                .with_deps(deps.clone())
+                .with_submitter(submitter.clone())
                 .with_context_value("record-id", &record.id)
                 .with_context_value("record-type", &record.r#type)
                 .with_context_value("record-title", &record.title);
 
             if let Some(file) = &record.file {
-                debug!(record_id = %record.id, %file, "create_child_loops_for_record: adding record file to context");
-                exec = exec.with_context_value("record-file", file);
+                debug!(record_id = %record.id, %file, "child_executions_for_record: adding record file to context");
+                exec = exec
+                    .with_context_value("record-file", file)
+                    .with_acceptance(acceptance_from_file(file).await);
             }
 
             // Add phase context if phases exist
             if !record.phases.is_empty() {
-                debug!(record_id = %record.id, phase_count = record.phases.len(), "create_child_loops_for_record: adding phase context");
+                debug!(record_id = %record.id, phase_count = record.phases.len(), "child_executions_for_record: adding phase context");
                 exec = exec
                     .with_context_value("phase-number", &(current_phase_idx + 1).to_string())
                     .with_context_value("total-phases", &record.phases.len().to_string());
@@ -187,22 +399,31 @@ impl CascadeHandler {
                 }
             }
 
-            self.state.create_loop_execution(exec.clone()).await?;
-
-            info!(
-                exec_id = %exec.id,
-                record_id = %record.id,
-                child_type = %child_type,
-                phase = current_phase_idx + 1,
-                total = record.phases.len(),
-                "Created child loop for record"
-            );
             executions.push(exec);
         }
 
         Ok(executions)
     }
 
+    /// Completed executions for a record's dependency records
+    ///
+    /// Children are only created once their deps are complete, so these edges
+    /// record where the inputs came from without holding up scheduling.
+    async fn dependency_executions(&self, record: &Loop) -> Result<Vec<String>> {
+        debug!(record_id = %record.id, deps = ?record.deps, "dependency_executions: called");
+        let mut exec_ids = Vec::new();
+        for dep_id in &record.deps {
+            match self.state.get_loop_execution_for_spec(dep_id).await? {
+                Some(exec) if exec.status == LoopExecutionStatus::Complete => {
+                    debug!(%dep_id, exec_id = %exec.id, "dependency_executions: found edge");
+                    exec_ids.push(exec.id);
+                }
+                _ => debug!(%dep_id, "dependency_executions: no completed execution"),
+            }
+        }
+        Ok(exec_ids)
+    }
+
     /// Handle completion of a child loop execution
     ///
     /// When a child loop completes, mark the phase as complete (if applicable)
@@ -262,10 +483,20 @@ impl CascadeHandler {
             return self.wake_dependent_records(&record.id).await;
         }
 
-        // More phases to run - create next child loops
+        // More phases to run - record the finished phase and create the next
+        // phase's child loops together
         debug!(%record_id, "on_child_loop_complete: more phases to run, creating next child loops");
-        self.state.update_loop(record.clone()).await?;
-        self.create_child_loops_for_record(&record).await
+        let executions = self.child_executions_for_record(&record).await?;
+        self.state
+            .transaction(|tx| {
+                tx.update_loop(record.clone());
+                for exec in &executions {
+                    tx.create_execution(exec.clone());
+                }
+            })
+            .await?;
+        log_child_loops(&record, &executions);
+        Ok(executions)
     }
 
     /// Wake records that depend on the completed record
@@ -363,10 +594,41 @@ impl CascadeHandler {
     }
 }
 
+/// Log the child loops just created for a record
+fn log_child_loops(record: &Loop, executions: &[LoopExecution]) {
+    let phase = record.current_phase_index().unwrap_or(0) + 1;
+    for exec in executions {
+        info!(
+            exec_id = %exec.id,
+            record_id = %record.id,
+            child_type = %exec.loop_type,
+            phase,
+            total = record.phases.len(),
+            "Created child loop for record"
+        );
+    }
+}
+
+/// Acceptance criteria declared in a plan/spec file (none if unreadable or malformed)
+async fn acceptance_from_file(file: &str) -> Vec<AcceptanceCriterion> {
+    debug!(%file, "acceptance_from_file: called");
+    let content = match tokio::fs::read_to_string(file).await {
+        Ok(content) => content,
+        Err(e) => {
+            debug!(%file, error = %e, "acceptance_from_file: cannot read file");
+            return Vec::new();
+        }
+    };
+    parse_acceptance(&content).unwrap_or_else(|e| {
+        warn!(%file, error = %e, "Ignoring acceptance criteria");
+        Vec::new()
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::Phase;
+    use crate::domain::{AcceptanceCheck, Phase};
 
     // Note: Integration tests would require a mock StateManager
     // These are placeholder tests for the logic patterns
@@ -398,6 +660,113 @@ mod tests {
         assert!(!record.is_ready(&completed));
     }
 
+    #[tokio::test]
+    async fn test_child_execution_gets_dependency_edges() {
+        let temp = tempfile::tempdir().unwrap();
+        let state = Arc::new(StateManager::spawn(temp.path()).unwrap());
+        let loader = LoopLoader::new(&crate::config::LoopsConfig::default()).unwrap();
+        let cascade = CascadeHandler::new(state.clone(), Arc::new(RwLock::new(loader)));
+
+        // Two specs under one plan; "api" depends on "schema" and declares acceptance criteria
+        let mut schema = Loop::new("spec", "Schema").with_parent("plan-1");
+        schema.set_status(LoopStatus::Complete);
+        let spec_file = temp.path().join("api.md");
+        std::fs::write(
+            &spec_file,
+            "# API\n\n```yaml\nacceptance:\n  - type: file-exists\n    path: src/api.rs\n```\n",
+        )
+        .unwrap();
+        let mut api = Loop::new("spec", "API")
+            .with_parent("plan-1")
+            .with_file(spec_file.display().to_string());
+        api.add_dependency(&schema.id);
+        state.create_loop(schema.clone()).await.unwrap();
+        state.create_loop(api.clone()).await.unwrap();
+
+        let mut schema_exec = LoopExecution::new("phase", "schema").with_parent(&schema.id);
+        schema_exec.set_status(LoopExecutionStatus::Complete);
+        state.create_loop_execution(schema_exec.clone()).await.unwrap();
+
+        let children = cascade.get_ready_children("plan-1").await.unwrap();
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].parent.as_deref(), Some(api.id.as_str()));
+        assert_eq!(children[0].deps, vec![schema_exec.id]);
+        assert_eq!(
+            children[0].acceptance,
+            vec![AcceptanceCheck::pending(AcceptanceCriterion::FileExists {
+                path: "src/api.rs".to_string()
+            })]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_children_inherit_submitter() {
+        let temp = tempfile::tempdir().unwrap();
+        let state = Arc::new(StateManager::spawn(temp.path()).unwrap());
+        let loader = LoopLoader::new(&crate::config::LoopsConfig::default()).unwrap();
+        let cascade = CascadeHandler::new(state.clone(), Arc::new(RwLock::new(loader)));
+
+        let alice = Submitter {
+            user: "alice".to_string(),
+            name: Some("Alice".to_string()),
+        };
+        let plan_exec = LoopExecution::new(PLAN_TYPE, "auth").with_submitter(Some(alice.clone()));
+        state.create_execution(plan_exec.clone()).await.unwrap();
+        let mut record = Loop::new(PLAN_TYPE, "Auth");
+        record.set_status(LoopStatus::Ready);
+
+        let children = cascade.on_loop_ready(&record, &plan_exec.id).await.unwrap();
+        assert!(!children.is_empty());
+        assert!(children.iter().all(|child| child.submitted_by.as_ref() == Some(&alice)));
+    }
+
+    #[tokio::test]
+    async fn test_record_typed_links_spec_to_plan() {
+        let temp = tempfile::tempdir().unwrap();
+        let state = Arc::new(StateManager::spawn(temp.path()).unwrap());
+        let loader = LoopLoader::new(&crate::config::LoopsConfig::default()).unwrap();
+        let cascade = CascadeHandler::new(state.clone(), Arc::new(RwLock::new(loader)));
+
+        let plan_file = temp.path().join("plan.md");
+        std::fs::write(
+            &plan_file,
+            "# Plan\n\n```yaml\nacceptance:\n  - type: command\n    command: cargo test\n```\n",
+        )
+        .unwrap();
+        let plan_exec = LoopExecution::new(PLAN_TYPE, "auth");
+        let mut plan_record = Loop::new(PLAN_TYPE, "Auth").with_file(plan_file.display().to_string());
+        plan_record.set_context(serde_json::json!({ "exec_id": plan_exec.id }));
+        state.create_loop(plan_record.clone()).await.unwrap();
+        cascade.record_typed(&plan_record, &plan_exec).await.unwrap();
+
+        // The spec execution was spawned by the plan's execution
+        let spec_exec = LoopExecution::new(SPEC_TYPE, "schema").with_parent(&plan_exec.id);
+        let spec_record = Loop::new(SPEC_TYPE, "Schema");
+        state.create_loop(spec_record.clone()).await.unwrap();
+        cascade.record_typed(&spec_record, &spec_exec).await.unwrap();
+
+        let plan = state.get_plan(&plan_record.id).await.unwrap().unwrap();
+        assert_eq!(plan.exec_id.as_deref(), Some(plan_exec.id.as_str()));
+        assert_eq!(
+            plan.acceptance,
+            vec![AcceptanceCriterion::Command {
+                command: "cargo test".to_string()
+            }]
+        );
+        assert_eq!(plan.spec_ids, vec![spec_record.id.clone()]);
+        let specs = state.list_specs_for_plan(&plan_record.id).await.unwrap();
+        assert_eq!(specs.len(), 1);
+        assert_eq!(specs[0].exec_id.as_deref(), Some(spec_exec.id.as_str()));
+
+        // Cascade status changes on the Loop record reach the typed record
+        let mut plan_record = state.get_loop_required(&plan_record.id).await.unwrap();
+        plan_record.set_status(LoopStatus::Complete);
+        state.update_loop(plan_record.clone()).await.unwrap();
+        let plan = state.get_plan(&plan_record.id).await.unwrap().unwrap();
+        assert_eq!(plan.status, LoopStatus::Complete);
+        assert_eq!(plan.spec_ids.len(), 1);
+    }
+
     #[test]
     fn test_phase_completion_index() {
         let mut record = Loop::new("mytype", "Test Record");
@@ -0,0 +1,350 @@
+//! Loop type file composition: includes, profiles and environment overrides
+//!
+//! Before a loop type definition is parsed, its YAML is assembled in layers,
+//! each later layer winning over the earlier ones:
+//!
+//! 1. `include:` files (a path or list of paths, relative to the including file)
+//! 2. `profile:` named profiles, `profiles/<name>.yml` in the loops directories
+//! 3. the definition's own keys
+//! 4. the `when:` entry matching the loader's environment (e.g. `ci`, `local`)
+//!
+//! ```yaml
+//! include: shared/prompts.yml
+//! profile: rust-workspace
+//! max-iterations: 30
+//! when:
+//!   ci:
+//!     validation-command: cargo test --workspace --locked
+//! ```
+//!
+//! Mappings merge key by key; any other value replaces the one beneath it.
+//! Included files and profiles are fragments of a definition and may include
+//! or use profiles themselves. Within a file, YAML anchors and `<<:` merge keys
+//! work as usual; top-level keys starting with `x-` are not loop types and can
+//! hold the anchored blocks.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use eyre::{Context, Result};
+use serde_yaml::{Mapping, Value};
+use tracing::debug;
+
+/// Subdirectory of a loops directory holding named profiles
+pub const PROFILES_DIR: &str = "profiles";
+
+/// Top-level key prefix for blocks that only hold anchors
+const EXTENSION_PREFIX: &str = "x-";
+
+/// `.yml`/`.yaml` files directly in `dir` (none if it doesn't exist)
+pub fn profile_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && p.extension().is_some_and(|e| e == "yml" || e == "yaml"))
+        .collect();
+    files.sort();
+    files
+}
+
+/// Parse YAML and apply `<<:` merge keys
+fn parse(content: &str) -> Result<Value> {
+    let mut value: Value = serde_yaml::from_str(content)?;
+    value.apply_merge()?;
+    if let Value::Mapping(mapping) = &mut value {
+        mapping.retain(|key, _| !key.as_str().is_some_and(|k| k.starts_with(EXTENSION_PREFIX)));
+    }
+    Ok(value)
+}
+
+/// Merge `overlay` into `base`: mappings key by key, anything else replaced
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// A string or list of strings under `key`
+fn string_list(value: Option<Value>, key: &str) -> Result<Vec<String>> {
+    match value {
+        None | Some(Value::Null) => Ok(Vec::new()),
+        Some(Value::String(s)) => Ok(vec![s]),
+        Some(Value::Sequence(items)) => items
+            .into_iter()
+            .map(|item| match item {
+                Value::String(s) => Ok(s),
+                _ => Err(eyre::eyre!("`{}` entries must be strings", key)),
+            })
+            .collect(),
+        Some(_) => Err(eyre::eyre!("`{}` must be a string or a list of strings", key)),
+    }
+}
+
+/// Composes loop type definitions from their includes, profiles and `when` overrides
+pub struct Composer {
+    /// Environment selecting the `when:` entry
+    environment: String,
+
+    /// Profile files by name (later loops directories override earlier)
+    profiles: HashMap<String, PathBuf>,
+
+    /// Included and profile files read so far (for hot-reload)
+    read: Vec<PathBuf>,
+}
+
+impl Composer {
+    /// Create a composer for `environment`, finding profiles in the loops directories
+    pub fn new(environment: impl Into<String>, loops_dirs: &[PathBuf]) -> Self {
+        let environment = environment.into();
+        debug!(%environment, ?loops_dirs, "Composer::new: called");
+        let mut profiles = HashMap::new();
+        for dir in loops_dirs {
+            for path in profile_files(&dir.join(PROFILES_DIR)) {
+                if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
+                    debug!(%name, ?path, "Composer::new: found profile");
+                    profiles.insert(name.to_string(), path.clone());
+                }
+            }
+        }
+        Self {
+            environment,
+            profiles,
+            read: Vec::new(),
+        }
+    }
+
+    /// Environment selecting the `when:` entry
+    pub fn environment(&self) -> &str {
+        &self.environment
+    }
+
+    /// Included and profile files read since the last call
+    pub fn take_read(&mut self) -> Vec<PathBuf> {
+        std::mem::take(&mut self.read)
+    }
+
+    /// Compose the loop types in a file's content
+    ///
+    /// A file holds either a map of name -> definition, or a single definition
+    /// (returned under `None`, to be named after the file).
+    pub fn compose_file(&mut self, content: &str, dir: &Path) -> Result<Vec<(Option<String>, Value)>> {
+        debug!(?dir, "Composer::compose_file: called");
+        let value = parse(content)?;
+
+        // Every definition has a scalar somewhere at its top level (the prompt
+        // template, or what it is composed from); a map of them has none
+        let is_map = match &value {
+            Value::Mapping(mapping) => !mapping.is_empty() && mapping.values().all(Value::is_mapping),
+            _ => false,
+        };
+        if !is_map {
+            debug!("Composer::compose_file: single definition");
+            return Ok(vec![(None, self.compose(value, dir)?)]);
+        }
+
+        let Value::Mapping(mapping) = value else {
+            unreachable!("checked above");
+        };
+        let mut definitions = Vec::new();
+        for (key, definition) in mapping {
+            let name = key
+                .as_str()
+                .ok_or_else(|| eyre::eyre!("Loop type names must be strings"))?
+                .to_string();
+            debug!(%name, "Composer::compose_file: composing definition");
+            let definition = self
+                .compose(definition, dir)
+                .with_context(|| format!("Loop type '{}'", name))?;
+            definitions.push((Some(name), definition));
+        }
+        Ok(definitions)
+    }
+
+    /// Compose one definition, then apply its `when:` entry for the environment
+    pub fn compose(&mut self, definition: Value, dir: &Path) -> Result<Value> {
+        let mut composed = self.layer(definition, dir, &mut Vec::new())?;
+        let when = match &mut composed {
+            Value::Mapping(mapping) => mapping.remove("when"),
+            _ => return Ok(composed),
+        };
+        match when {
+            None | Some(Value::Null) => {}
+            Some(Value::Mapping(when)) => {
+                for (environment, overrides) in when {
+                    if environment.as_str() == Some(self.environment.as_str()) {
+                        debug!(environment = %self.environment, "Composer::compose: applying when overrides");
+                        merge(&mut composed, overrides);
+                        break;
+                    }
+                }
+            }
+            Some(_) => return Err(eyre::eyre!("`when` must map environment names to overrides")),
+        }
+        Ok(composed)
+    }
+
+    /// Resolve a definition's includes and profiles beneath its own keys
+    fn layer(&mut self, definition: Value, dir: &Path, stack: &mut Vec<PathBuf>) -> Result<Value> {
+        let Value::Mapping(mut own) = definition else {
+            // Not a mapping: left for the LoopType parser to reject
+            return Ok(definition);
+        };
+        let includes = string_list(own.remove("include"), "include")?;
+        let profiles = string_list(own.remove("profile"), "profile")?;
+
+        let mut composed = Value::Mapping(Mapping::new());
+        for include in includes {
+            let path = match include.strip_prefix("~/") {
+                Some(rest) => dirs::home_dir().map(|home| home.join(rest)).unwrap_or_else(|| dir.join(&include)),
+                None => dir.join(&include),
+            };
+            debug!(?path, "Composer::layer: including file");
+            let fragment = self.fragment(&path, stack)?;
+            merge(&mut composed, fragment);
+        }
+        for name in profiles {
+            let path = self.profiles.get(&name).cloned().ok_or_else(|| {
+                let mut known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+                known.sort();
+                eyre::eyre!(
+                    "Unknown profile '{}' (known: {})",
+                    name,
+                    if known.is_empty() { "none".to_string() } else { known.join(", ") }
+                )
+            })?;
+            debug!(%name, ?path, "Composer::layer: applying profile");
+            let fragment = self.fragment(&path, stack).with_context(|| format!("Profile '{}'", name))?;
+            merge(&mut composed, fragment);
+        }
+        merge(&mut composed, Value::Mapping(own));
+        Ok(composed)
+    }
+
+    /// Read and compose an included file or profile
+    fn fragment(&mut self, path: &Path, stack: &mut Vec<PathBuf>) -> Result<Value> {
+        let key = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if stack.contains(&key) {
+            let chain: Vec<String> = stack.iter().map(|p| p.display().to_string()).collect();
+            return Err(eyre::eyre!(
+                "Include cycle detected: {} -> {}",
+                chain.join(" -> "),
+                key.display()
+            ));
+        }
+
+        let content = fs::read_to_string(path).with_context(|| format!("Failed to read include: {}", path.display()))?;
+        if !self.read.contains(&path.to_path_buf()) {
+            self.read.push(path.to_path_buf());
+        }
+        let value = parse(&content).with_context(|| format!("Failed to parse include: {}", path.display()))?;
+        if !value.is_mapping() {
+            return Err(eyre::eyre!("Include {} is not a mapping", path.display()));
+        }
+
+        stack.push(key);
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let composed = self.layer(value, dir, stack);
+        stack.pop();
+        composed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn compose_single(composer: &mut Composer, content: &str, dir: &Path) -> Value {
+        let mut definitions = composer.compose_file(content, dir).unwrap();
+        assert_eq!(definitions.len(), 1);
+        definitions.remove(0).1
+    }
+
+    #[test]
+    fn test_includes_profiles_and_when() {
+        let temp = tempdir().unwrap();
+        let loops = temp.path().join("loops");
+        fs::create_dir_all(loops.join("shared")).unwrap();
+        fs::create_dir_all(loops.join(PROFILES_DIR)).unwrap();
+        fs::write(
+            loops.join("shared/prompts.yml"),
+            "prompt-template: Shared prompt\nmax-iterations: 10\nstuck-detection:\n  threshold: 3\n",
+        )
+        .unwrap();
+        fs::write(
+            loops.join(PROFILES_DIR).join("rust-workspace.yml"),
+            "validation-command: cargo test\nstuck-detection:\n  action: fail\nwhen:\n  ci:\n    validation-command: cargo test --locked\n",
+        )
+        .unwrap();
+
+        let content = "include: shared/prompts.yml\nprofile: rust-workspace\nmax-iterations: 30\n";
+
+        let mut local = Composer::new("local", std::slice::from_ref(&loops));
+        let value = compose_single(&mut local, content, &loops);
+        assert_eq!(value["prompt-template"], "Shared prompt");
+        assert_eq!(value["max-iterations"], 30);
+        assert_eq!(value["validation-command"], "cargo test");
+        // Mappings merge key by key
+        assert_eq!(value["stuck-detection"]["threshold"], 3);
+        assert_eq!(value["stuck-detection"]["action"], "fail");
+        assert!(value.get("when").is_none());
+        assert_eq!(local.take_read().len(), 2);
+
+        let mut ci = Composer::new("ci", std::slice::from_ref(&loops));
+        let value = compose_single(&mut ci, content, &loops);
+        assert_eq!(value["validation-command"], "cargo test --locked");
+
+        let err = Composer::new("local", std::slice::from_ref(&loops))
+            .compose_file("profile: go-module\nprompt-template: x\n", &loops)
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("Unknown profile 'go-module' (known: rust-workspace)"));
+    }
+
+    #[test]
+    fn test_map_file_with_anchors() {
+        let content = r#"
+x-common: &common
+  validation-command: make check
+  max-iterations: 20
+fast:
+  <<: *common
+  prompt-template: Go fast
+careful:
+  <<: *common
+  prompt-template: Go slow
+  max-iterations: 80
+"#;
+        let mut composer = Composer::new("local", &[]);
+        let definitions = composer.compose_file(content, Path::new(".")).unwrap();
+        let names: Vec<_> = definitions.iter().map(|(name, _)| name.as_deref().unwrap()).collect();
+        assert_eq!(names, ["fast", "careful"]);
+        assert_eq!(definitions[0].1["validation-command"], "make check");
+        assert_eq!(definitions[0].1["max-iterations"], 20);
+        assert_eq!(definitions[1].1["max-iterations"], 80);
+    }
+
+    #[test]
+    fn test_include_cycle() {
+        let temp = tempdir().unwrap();
+        fs::write(temp.path().join("a.yml"), "include: b.yml\n").unwrap();
+        fs::write(temp.path().join("b.yml"), "include: a.yml\n").unwrap();
+        let err = Composer::new("local", &[])
+            .compose_file("include: a.yml\nprompt-template: x\n", temp.path())
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("Include cycle detected"));
+    }
+}
@@ -1,8 +1,43 @@
 //! Loop configuration types
 
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
 use serde::{Deserialize, Serialize};
 use tracing::debug;
 
+use super::budget::IterationBudget;
+use super::rollback::SnapshotPolicy;
+use super::stuck::StuckDetection;
+use super::template::VariableSchema;
+use super::watchdog::WatchdogPolicy;
+use crate::hooks::Hooks;
+use crate::progress::{ContextSelection, ExplorationCacheConfig};
+use crate::security::ScannerSpec;
+use crate::tools::{NetworkPolicy, ResourceLimits};
+use crate::validation::{DebateConfig, ReviewPipeline};
+use crate::worktree::MergeConfig;
+
+/// What to do when an execution's declared paths are locked by another execution
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PathLockMode {
+    /// Start anyway and log a warning
+    #[default]
+    Warn,
+    /// Leave the execution pending until the locks are released
+    Serialize,
+}
+
+impl std::fmt::Display for PathLockMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Warn => write!(f, "warn"),
+            Self::Serialize => write!(f, "serialize"),
+        }
+    }
+}
+
 /// Configuration for a loop type (from YAML)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoopConfig {
@@ -27,7 +62,8 @@ pub struct LoopConfig {
     #[serde(default = "default_max_turns")]
     pub max_turns_per_iteration: u32,
 
-    /// Timeout for each iteration in milliseconds
+    /// Timeout for each iteration's validation command in milliseconds
+    /// (see `watchdog` for the iteration as a whole)
     #[serde(default = "default_iteration_timeout")]
     pub iteration_timeout_ms: u64,
 
@@ -45,6 +81,78 @@ pub struct LoopConfig {
 
     #[serde(default = "default_progress_max_chars")]
     pub progress_max_chars: usize,
+
+    /// Stuck-loop detection settings
+    #[serde(default)]
+    pub stuck_detection: StuckDetection,
+
+    /// Excerpts from the worktree's ContextStore added to each prompt
+    #[serde(default)]
+    pub context_selection: ContextSelection,
+
+    /// Digests of files read in earlier iterations, listed in each prompt
+    #[serde(default)]
+    pub exploration_cache: ExplorationCacheConfig,
+
+    /// Limits for commands spawned by tools
+    #[serde(default)]
+    pub resource_limits: ResourceLimits,
+
+    /// Per-iteration worktree snapshots and automatic rollback
+    #[serde(default)]
+    pub snapshots: SnapshotPolicy,
+
+    /// Declared prompt variables (defaults and types applied before rendering)
+    #[serde(default)]
+    pub variables: VariableSchema,
+
+    /// Most executions of this type running at once (None: only the global limit)
+    #[serde(default)]
+    pub max_concurrent: Option<usize>,
+
+    /// Executions of types sharing this group never run at the same time
+    #[serde(default)]
+    pub exclusive_group: Option<String>,
+
+    /// Whether declared paths locked by other executions delay the start
+    #[serde(default)]
+    pub path_locks: PathLockMode,
+
+    /// Iteration and tool-call timeouts, and what to do when one trips
+    #[serde(default)]
+    pub watchdog: WatchdogPolicy,
+
+    /// Review passes to work through before completing (None: validation alone decides)
+    #[serde(default)]
+    pub review: Option<ReviewPipeline>,
+
+    /// Iterations split across named phases (None: `max_iterations` alone)
+    #[serde(default)]
+    pub budget: Option<IterationBudget>,
+
+    /// Persona debate and judge pass over the plan once it validates (None: no debate)
+    #[serde(default)]
+    pub debate: Option<DebateConfig>,
+
+    /// Static-analysis commands available to the `security_scan` tool
+    #[serde(default)]
+    pub scanners: Vec<ScannerSpec>,
+
+    /// Conventional-commit message, squash and changelog for the merge (None: plain merge)
+    #[serde(default)]
+    pub merge: Option<MergeConfig>,
+
+    /// Extra directories the read-only tools may read, by mount name
+    #[serde(default)]
+    pub read_only_mounts: BTreeMap<String, PathBuf>,
+
+    /// Hosts tools may and may not reach
+    #[serde(default)]
+    pub network: NetworkPolicy,
+
+    /// Shell hooks by lifecycle event (global hooks first once spawned)
+    #[serde(default)]
+    pub hooks: Hooks,
 }
 
 fn default_max_iterations() -> u32 {
@@ -101,6 +209,24 @@ impl Default for LoopConfig {
             ],
             progress_max_entries: default_progress_max_entries(),
             progress_max_chars: default_progress_max_chars(),
+            stuck_detection: StuckDetection::default(),
+            context_selection: ContextSelection::default(),
+            exploration_cache: ExplorationCacheConfig::default(),
+            resource_limits: ResourceLimits::default(),
+            snapshots: SnapshotPolicy::default(),
+            variables: VariableSchema::default(),
+            watchdog: WatchdogPolicy::default(),
+            max_concurrent: None,
+            exclusive_group: None,
+            path_locks: PathLockMode::default(),
+            review: None,
+            budget: None,
+            debate: None,
+            scanners: Vec::new(),
+            merge: None,
+            read_only_mounts: BTreeMap::new(),
+            network: NetworkPolicy::default(),
+            hooks: Hooks::default(),
         }
     }
 }
@@ -1,27 +1,54 @@
 //! LoopEngine - executes Ralph Wiggum loop iterations
 
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
 use handlebars::Handlebars;
 use tracing::{debug, info, warn};
 
-use crate::coordinator::{CoordMessage, CoordinatorHandle};
-use crate::domain::{IterationLog, Priority, ToolCallSummary};
+use crate::clock::{ClockRef, SystemClock};
+use crate::config::{LlmConfig, ToolWorkerConfig};
+use crate::coordinator::{
+    BudgetWarning, CoordMessage, CoordinatorHandle, MainUpdated, NUDGE_SHARE_TYPE, Topic, normalize_lock_path,
+};
+use crate::domain::{AcceptanceCheck, CriterionStatus, IterationLog, Priority, ToolCallSummary};
+use crate::error::ErrorCode;
+use crate::hooks::{HookContext, HookEvent};
 use crate::events::{EventEmitter, IterationOutcome as EventIterationOutcome};
 use crate::llm::{
-    CompletionRequest, CompletionResponse, ContentBlock, LlmClient, Message, StopReason, StreamChunk, TokenUsage,
-    ToolDefinition,
+    CompletionRequest, CompletionResponse, ContentBlock, LlmClient, LlmError, Message, StopReason, StreamChunk,
+    TokenUsage, ToolDefinition, create_client,
+};
+use crate::policy::{ExecutionInfo, PolicyInput, PolicyOutcome, PolicySet};
+use crate::progress::{
+    ContextSelector, ExplorationCache, ExplorationCacheRef, IterationContext, ProgressStrategy, SystemCapturedProgress,
+    render_snippets, section_terms,
 };
-use crate::progress::{IterationContext, ProgressStrategy, SystemCapturedProgress};
 use crate::scheduler::Scheduler;
+use crate::review::{REVIEW_TYPE, Review, branch_diff, execution_id, resolve_target};
+use crate::security::FindingStore;
 use crate::state::StateManager;
-use crate::tools::{ToolContext, ToolExecutor, ToolResult};
+use crate::tools::{
+    LspSession, LspSessionRef, NetworkGuard, NetworkProxy, Tool, ToolContext, ToolExecutor, ToolResult,
+};
+use crate::validation::{Debate, ReviewProgress, ReviewStep};
+use crate::worktree::{DiffSummary, MergeMessage, commit_pending, create_snapshot, restore_snapshot};
 
 use super::LoopConfig;
-use super::validation::{run_validation, run_validation_streaming};
+use super::acceptance::check_all;
+use super::budget::{BudgetProgress, PhaseStep};
+use super::metrics::LoopMetrics;
+use super::reporter::TestReport;
+use super::rollback::{RegressionTracker, ValidationScore};
+use super::stuck::{ProgressMonitor, StuckAction};
+use super::template;
+use super::validation::{ValidationResult, run_validation, run_validation_streaming};
+use super::watchdog::WatchdogTimeout;
+
+/// Maximum characters of raw validation output carried into the next prompt
+const MAX_PREVIOUS_ERRORS_CHARS: usize = 4000;
 
 /// Truncate a string to a maximum length, adding "..." if truncated
 fn truncate_str(s: &str, max_len: usize) -> String {
@@ -32,6 +59,19 @@ fn truncate_str(s: &str, max_len: usize) -> String {
     }
 }
 
+/// Keep the last `max_len` bytes of a string (on a char boundary), prefixing "..." if cut
+fn tail_str(s: &str, max_len: usize) -> String {
+    if s.len() <= max_len {
+        s.to_string()
+    } else {
+        let mut start = s.len() - max_len;
+        while !s.is_char_boundary(start) {
+            start += 1;
+        }
+        format!("...{}", &s[start..])
+    }
+}
+
 /// Status of a loop execution
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum LoopStatus {
@@ -44,6 +84,21 @@ pub enum LoopStatus {
     Stopped,
 }
 
+/// The request an iteration starts with, rendered without calling the LLM (`td exec prompt`)
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PromptPreview {
+    /// Iteration the prompt is for
+    pub iteration: u32,
+    /// System prompt sent with every turn
+    pub system_prompt: String,
+    /// First user message
+    pub prompt: String,
+    /// Names of the tools offered
+    pub tools: Vec<String>,
+    /// Output token limit
+    pub max_tokens: u32,
+}
+
 /// Result of a single iteration
 #[derive(Debug)]
 pub enum IterationResult {
@@ -56,7 +111,21 @@ pub enum IterationResult {
     /// Loop was interrupted (stop signal, etc)
     Interrupted { reason: String },
     /// Error occurred
-    Error { message: String, recoverable: bool },
+    Error {
+        message: String,
+        recoverable: bool,
+        /// Error code when the failure came from a coded error (see [`crate::error`])
+        code: Option<String>,
+    },
+    /// No material progress for several iterations; execution should pause or escalate
+    Stuck {
+        action: StuckAction,
+        unchanged_iterations: u32,
+    },
+    /// The provider circuit breaker is open; execution should wait for the provider to recover
+    ProviderUnavailable { message: String },
+    /// A hook with `on-failure: block` failed; execution should wait for human review
+    Blocked { reason: String },
 }
 
 /// Loop execution engine
@@ -70,6 +139,18 @@ pub struct LoopEngine {
     /// LLM client
     llm: Arc<dyn LlmClient>,
 
+    /// LLM config for rebuilding the client when `td exec set` changes the model
+    llm_config: Option<LlmConfig>,
+
+    /// Model override in effect (None = `llm.default`)
+    model_override: Option<String>,
+
+    /// Iteration cap override in effect (None = the loop type's)
+    max_iterations_override: Option<u32>,
+
+    /// The loop type's own `max-iterations`
+    type_max_iterations: u32,
+
     /// Tool executor
     tool_executor: ToolExecutor,
 
@@ -86,7 +167,6 @@ pub struct LoopEngine {
     status: LoopStatus,
 
     /// Template engine
-    #[allow(dead_code)]
     handlebars: Handlebars<'static>,
 
     /// Coordinator handle for inter-loop communication
@@ -110,8 +190,68 @@ pub struct LoopEngine {
     /// Token usage accumulated in the current iteration
     iteration_token_usage: TokenUsage,
 
+    /// Model that served the current iteration's latest LLM response (set by a failover chain)
+    iteration_served_by: Option<String>,
+
     /// Event emitter for observability (optional)
     event_emitter: Option<EventEmitter>,
+
+    /// Language server session shared by code intelligence tools (started lazily)
+    lsp: LspSessionRef,
+
+    /// Digests of files read in earlier iterations (None = exploration cache disabled)
+    exploration: Option<ExplorationCacheRef>,
+
+    /// Shared metrics tracker (optional)
+    metrics: Option<Arc<LoopMetrics>>,
+
+    /// Extra environment for tool commands and validation (e.g. a shared CARGO_TARGET_DIR)
+    command_env: Vec<(String, String)>,
+
+    /// Policies consulted before tool calls and the merge
+    policies: PolicySet,
+
+    /// Failures from the last validation run, injected into the next prompt
+    previous_errors: Option<String>,
+
+    /// Detects iterations that make no material progress
+    progress_monitor: ProgressMonitor,
+
+    /// Best snapshotted iteration so far, for rollback on regression
+    regressions: RegressionTracker,
+
+    /// Steering prompt to append to the next iteration's prompt
+    steering: Option<String>,
+
+    /// Operator messages (nudges) to append to the next iteration's prompt
+    nudges: Vec<String>,
+
+    /// Data shared by other loops, by share type (`{{facts.<type>}}` in prompts)
+    shared_facts: serde_json::Map<String, serde_json::Value>,
+
+    /// Time source for pacing sleeps, durations and iteration log timestamps
+    clock: ClockRef,
+
+    /// Paths already claimed with the Coordinator (granted or not)
+    claimed_paths: HashSet<String>,
+
+    /// Iterations in a row the watchdog has cancelled
+    consecutive_timeouts: u32,
+
+    /// Acceptance criteria and their latest status (empty = validation alone decides)
+    acceptance: Vec<AcceptanceCheck>,
+
+    /// Position in the loop type's review pipeline (None = no review passes)
+    review: Option<ReviewProgress>,
+
+    /// Position in the loop type's iteration budget (None = no phases)
+    budget: Option<BudgetProgress>,
+
+    /// The loop type's network policy and the requests checked against it
+    network: NetworkGuard,
+
+    /// Proxy that applies the network policy to tool commands (started with the first iteration)
+    network_proxy: Option<NetworkProxy>,
 }
 
 impl LoopEngine {
@@ -122,25 +262,58 @@ impl LoopEngine {
             config.progress_max_entries,
             config.progress_max_chars,
         ));
+        let progress_monitor = ProgressMonitor::new(config.stuck_detection.threshold);
+        let review = config.review.clone().and_then(ReviewProgress::new);
+        let budget = config.budget.clone().and_then(BudgetProgress::new);
+        let type_max_iterations = config.max_iterations;
+        let network = NetworkGuard::new(config.network.clone());
+        let exploration = config
+            .exploration_cache
+            .enabled
+            .then(|| Arc::new(tokio::sync::Mutex::new(ExplorationCache::new(&config.exploration_cache))));
 
         Self {
             exec_id,
             config,
             llm,
+            llm_config: None,
+            model_override: None,
+            max_iterations_override: None,
+            type_max_iterations,
+            network,
+            network_proxy: None,
             tool_executor: ToolExecutor::standard(),
             progress,
             worktree: worktree.clone(),
             iteration: 0,
             status: LoopStatus::Running,
-            handlebars: Handlebars::new(),
+            handlebars: template::renderer(),
             coord_handle: None,
             scheduler: None,
             execution_context: serde_json::json!({}),
-            repo_root: worktree,
+            repo_root: worktree.clone(),
             state: None,
             tool_call_buffer: Vec::new(),
             iteration_token_usage: TokenUsage::default(),
+            iteration_served_by: None,
             event_emitter: None,
+            lsp: Arc::new(LspSession::new(worktree)),
+            exploration,
+            metrics: None,
+            command_env: Vec::new(),
+            policies: PolicySet::default(),
+            previous_errors: None,
+            progress_monitor,
+            regressions: RegressionTracker::default(),
+            steering: None,
+            nudges: Vec::new(),
+            shared_facts: serde_json::Map::new(),
+            clock: SystemClock::shared(),
+            claimed_paths: HashSet::new(),
+            consecutive_timeouts: 0,
+            acceptance: Vec::new(),
+            review,
+            budget,
         }
     }
 
@@ -157,25 +330,58 @@ impl LoopEngine {
             config.progress_max_entries,
             config.progress_max_chars,
         ));
+        let progress_monitor = ProgressMonitor::new(config.stuck_detection.threshold);
+        let review = config.review.clone().and_then(ReviewProgress::new);
+        let budget = config.budget.clone().and_then(BudgetProgress::new);
+        let type_max_iterations = config.max_iterations;
+        let network = NetworkGuard::new(config.network.clone());
+        let exploration = config
+            .exploration_cache
+            .enabled
+            .then(|| Arc::new(tokio::sync::Mutex::new(ExplorationCache::new(&config.exploration_cache))));
 
         Self {
             exec_id,
             config,
             llm,
+            llm_config: None,
+            model_override: None,
+            max_iterations_override: None,
+            type_max_iterations,
+            network,
+            network_proxy: None,
             tool_executor: ToolExecutor::standard(),
             progress,
             worktree: worktree.clone(),
             iteration: 0,
             status: LoopStatus::Running,
-            handlebars: Handlebars::new(),
+            handlebars: template::renderer(),
             coord_handle: Some(coord_handle),
             scheduler: None,
             execution_context: serde_json::json!({}),
-            repo_root: worktree,
+            repo_root: worktree.clone(),
             state: None,
             tool_call_buffer: Vec::new(),
             iteration_token_usage: TokenUsage::default(),
+            iteration_served_by: None,
             event_emitter: None,
+            lsp: Arc::new(LspSession::new(worktree)),
+            exploration,
+            metrics: None,
+            command_env: Vec::new(),
+            policies: PolicySet::default(),
+            previous_errors: None,
+            progress_monitor,
+            regressions: RegressionTracker::default(),
+            steering: None,
+            nudges: Vec::new(),
+            shared_facts: serde_json::Map::new(),
+            clock: SystemClock::shared(),
+            claimed_paths: HashSet::new(),
+            consecutive_timeouts: 0,
+            acceptance: Vec::new(),
+            review,
+            budget,
         }
     }
 
@@ -200,6 +406,24 @@ impl LoopEngine {
         self
     }
 
+    /// Set the LLM config used to rebuild the client when the model override changes
+    pub fn with_llm_config(mut self, config: LlmConfig) -> Self {
+        debug!(exec_id = %self.exec_id, default = %config.default, "with_llm_config: called");
+        self.llm_config = Some(config);
+        self
+    }
+
+    /// Set the execution's model and iteration cap overrides the engine starts with
+    ///
+    /// The client passed to the constructor must already serve `model`.
+    pub fn with_overrides(mut self, model: Option<String>, max_iterations: Option<u32>) -> Self {
+        debug!(exec_id = %self.exec_id, ?model, ?max_iterations, "with_overrides: called");
+        self.model_override = model;
+        self.max_iterations_override = max_iterations;
+        self.config.max_iterations = max_iterations.unwrap_or(self.type_max_iterations);
+        self
+    }
+
     /// Set the state manager for persisting iteration logs
     pub fn with_state(mut self, state: StateManager) -> Self {
         debug!(exec_id = %self.exec_id, "with_state: called");
@@ -214,6 +438,63 @@ impl LoopEngine {
         self
     }
 
+    /// Set the metrics tracker for per-test outcome tracking
+    pub fn with_metrics(mut self, metrics: Arc<LoopMetrics>) -> Self {
+        debug!(exec_id = %self.exec_id, "with_metrics: called");
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Set extra environment variables for commands run in the worktree
+    pub fn with_command_env(mut self, env: Vec<(String, String)>) -> Self {
+        debug!(exec_id = %self.exec_id, ?env, "with_command_env: called");
+        self.command_env = env;
+        self
+    }
+
+    /// Consult `policies` before each tool call and before merging
+    pub fn with_policies(mut self, policies: PolicySet) -> Self {
+        debug!(exec_id = %self.exec_id, policies = ?policies.names(), "with_policies: called");
+        self.policies = policies;
+        self
+    }
+
+    /// Add tools beyond the standard set (offered when the loop type lists them)
+    pub fn with_tools(mut self, tools: &[Arc<dyn Tool>]) -> Self {
+        debug!(exec_id = %self.exec_id, count = tools.len(), "with_tools: called");
+        for tool in tools {
+            if let Err(e) = self.tool_executor.register_tool(Box::new(tool.clone())) {
+                warn!(exec_id = %self.exec_id, error = %e, "Skipping custom tool");
+            }
+        }
+        self
+    }
+
+    /// Run the file and command tools in a worker process (`tools.worker`)
+    pub fn with_tool_worker(mut self, config: &ToolWorkerConfig) -> Self {
+        debug!(exec_id = %self.exec_id, enabled = config.enabled, "with_tool_worker: called");
+        if config.enabled
+            && let Err(e) = self.tool_executor.use_worker(config)
+        {
+            warn!(exec_id = %self.exec_id, error = %e, "Tool worker unavailable, running tools in-process");
+        }
+        self
+    }
+
+    /// Set the acceptance criteria checked after each validation (from LoopExecution.acceptance)
+    pub fn with_acceptance(mut self, acceptance: Vec<AcceptanceCheck>) -> Self {
+        debug!(exec_id = %self.exec_id, count = acceptance.len(), "with_acceptance: called");
+        self.acceptance = acceptance;
+        self
+    }
+
+    /// Set the clock (a `ManualClock` makes runs reproducible in tests)
+    pub fn with_clock(mut self, clock: ClockRef) -> Self {
+        debug!(exec_id = %self.exec_id, "with_clock: called");
+        self.clock = clock;
+        self
+    }
+
     /// Get the accumulated progress text
     ///
     /// This returns the progress text that should be persisted to LoopExecution
@@ -229,8 +510,183 @@ impl LoopEngine {
         self.iteration
     }
 
+    /// What policies are told about this execution
+    fn policy_execution(&self) -> ExecutionInfo {
+        ExecutionInfo {
+            id: self.exec_id.clone(),
+            loop_type: self.config.loop_type.clone(),
+            iteration: self.iteration,
+            title: self.execution_context.get("title").and_then(|v| v.as_str()).map(String::from),
+            submitted_by: None,
+            context: self.execution_context.clone(),
+        }
+    }
+
+    /// Why policies refuse to merge this loop's branch (None: go ahead)
+    pub async fn merge_denial(&self) -> Option<String> {
+        if self.policies.is_empty() {
+            return None;
+        }
+        debug!(exec_id = %self.exec_id, "merge_denial: called");
+        let diff = match DiffSummary::collect(&self.worktree, "main").await {
+            Ok(diff) => diff,
+            Err(e) => {
+                warn!(exec_id = %self.exec_id, error = %e, "Failed to collect the diff for merge policies");
+                DiffSummary::default()
+            }
+        };
+        let outcome = self
+            .policies
+            .evaluate(PolicyInput::merge(self.policy_execution(), &diff))
+            .await;
+        outcome.denial()
+    }
+
+    /// Run the hooks configured for `event`
+    ///
+    /// Failures are logged and emitted as warnings. Returns the reason when a
+    /// failing hook blocks the execution.
+    pub async fn run_hooks(&self, event: HookEvent, exit_code: Option<i32>, error: Option<&str>) -> Option<String> {
+        let hooks = self.config.hooks.get(event);
+        if hooks.is_empty() {
+            return None;
+        }
+        debug!(exec_id = %self.exec_id, %event, count = hooks.len(), "run_hooks: called");
+        let ctx = HookContext {
+            event,
+            execution_id: self.exec_id.clone(),
+            loop_type: self.config.loop_type.clone(),
+            iteration: self.iteration,
+            worktree: self.worktree.clone(),
+            exit_code,
+            error: error.map(String::from),
+            context: self.execution_context.clone(),
+        };
+        let mut blocked = None;
+        for failure in crate::hooks::run_hooks(hooks, &ctx, &self.command_env).await {
+            if failure.blocks() {
+                blocked = Some(format!("Blocked by {}", failure));
+            } else {
+                warn!(exec_id = %self.exec_id, %failure, "Hook failed");
+                if let Some(ref emitter) = self.event_emitter {
+                    emitter.warning("hook", &failure.to_string());
+                }
+            }
+        }
+        blocked
+    }
+
+    /// Hold the loop after a blocking hook failed
+    fn block_on_hook(&mut self, reason: String) -> IterationResult {
+        debug!(exec_id = %self.exec_id, %reason, "block_on_hook: called");
+        warn!(exec_id = %self.exec_id, %reason, "Hook blocked execution");
+        if let Some(ref emitter) = self.event_emitter {
+            emitter.error("hook", &reason, None);
+        }
+        self.status = LoopStatus::Blocked { reason: reason.clone() };
+        IterationResult::Blocked { reason }
+    }
+
+    /// Generate the merge commit message and changelog entry for this loop's branch
+    ///
+    /// None if the loop type has no `merge` section (or generation failed), in
+    /// which case the branch gets a plain merge commit. Uncommitted work is
+    /// committed first so the diff summary covers it.
+    pub async fn merge_message(&self, title: &str) -> Option<MergeMessage> {
+        let config = self.config.merge.as_ref()?;
+        debug!(exec_id = %self.exec_id, %title, "merge_message: called");
+        if let Err(e) = commit_pending(&self.worktree, title).await {
+            warn!(exec_id = %self.exec_id, error = %e, "Failed to commit pending changes before merge");
+        }
+        let mut context = self.execution_context.clone();
+        if let Some(obj) = context.as_object_mut() {
+            obj.insert("exec-id".to_string(), self.exec_id.clone().into());
+            obj.insert("loop-type".to_string(), self.config.loop_type.clone().into());
+        } else {
+            context = serde_json::json!({ "exec-id": self.exec_id, "loop-type": self.config.loop_type });
+        }
+        match MergeMessage::generate(config, Some(&self.llm), &self.worktree, "main", title, &context).await {
+            Ok(message) => {
+                debug!(exec_id = %self.exec_id, commit = %message.commit, "merge_message: generated");
+                Some(message)
+            }
+            Err(e) => {
+                warn!(exec_id = %self.exec_id, error = %e, "Failed to generate merge message, using a plain merge");
+                None
+            }
+        }
+    }
+
     /// Run the loop until completion or max iterations
     pub async fn run(&mut self) -> eyre::Result<IterationResult> {
+        let result = self.run_iterations().await;
+
+        // The language server lives for the whole execution, not per iteration
+        self.lsp.shutdown().await;
+
+        result
+    }
+
+    /// Pick up `td exec set` changes to the execution's model or iteration cap
+    async fn apply_overrides(&mut self) {
+        let Some(state) = &self.state else {
+            return;
+        };
+        let exec = match state.get_execution(&self.exec_id).await {
+            Ok(Some(exec)) => exec,
+            Ok(None) => return,
+            Err(e) => {
+                warn!(exec_id = %self.exec_id, error = %e, "Failed to read overrides");
+                return;
+            }
+        };
+        let next = self.iteration + 1;
+
+        if exec.max_iterations != self.max_iterations_override {
+            let from = self.config.max_iterations;
+            let to = exec.max_iterations.unwrap_or(self.type_max_iterations);
+            info!(exec_id = %self.exec_id, from, to, "Iteration cap changed");
+            self.max_iterations_override = exec.max_iterations;
+            self.config.max_iterations = to;
+            if let Some(ref emitter) = self.event_emitter {
+                emitter.override_changed(next, "max_iterations", &from.to_string(), &to.to_string());
+            }
+        }
+
+        if exec.model != self.model_override {
+            let Some(config) = self.llm_config.as_ref() else {
+                warn!(exec_id = %self.exec_id, model = ?exec.model, "No LLM config to switch models with; keeping the current model");
+                self.model_override = exec.model;
+                return;
+            };
+            let from = self.model_override.clone().unwrap_or_else(|| config.default.clone());
+            let to = exec.model.clone().unwrap_or_else(|| config.default.clone());
+            let switched = LlmConfig {
+                default: to.clone(),
+                ..config.clone()
+            };
+            match create_client(&switched) {
+                Ok(client) => {
+                    info!(exec_id = %self.exec_id, %from, %to, "Model changed");
+                    self.llm = client;
+                    if let Some(ref emitter) = self.event_emitter {
+                        emitter.override_changed(next, "model", &from, &to);
+                    }
+                }
+                Err(e) => {
+                    warn!(exec_id = %self.exec_id, model = %to, error = %e, "Cannot switch model; keeping the current one");
+                    if let Some(ref emitter) = self.event_emitter {
+                        emitter.warning("override", &format!("Cannot switch to model '{}': {}", to, e));
+                    }
+                }
+            }
+            // Don't retry a failed switch every iteration; a new `td exec set` tries again
+            self.model_override = exec.model;
+        }
+    }
+
+    /// Iterate until the loop completes, fails, or is interrupted
+    async fn run_iterations(&mut self) -> eyre::Result<IterationResult> {
         debug!(exec_id = %self.exec_id, loop_type = %self.config.loop_type, max_iterations = self.config.max_iterations, "run: called");
         info!(
             "Starting loop {} (type: {}, max_iterations: {})",
@@ -240,7 +696,7 @@ impl LoopEngine {
         // Subscribe to main_updated alerts if coordinator is available
         if let Some(ref coord_handle) = self.coord_handle {
             debug!(exec_id = %self.exec_id, "run: subscribing to main_updated");
-            if let Err(e) = coord_handle.subscribe("main_updated").await {
+            if let Err(e) = coord_handle.subscribe_topic::<MainUpdated>().await {
                 warn!("Failed to subscribe to main_updated: {}", e);
             }
         } else {
@@ -256,15 +712,30 @@ impl LoopEngine {
                 .and_then(|v| v.as_str())
                 .unwrap_or(&self.config.loop_type);
             emitter.loop_started(&self.config.loop_type, task_desc);
+            if let Some(budget) = &self.budget {
+                emitter.phase_started(budget.index(), &budget.current().name, budget.total());
+            }
         }
 
-        while self.iteration < self.config.max_iterations {
+        loop {
+            // `td exec set` changes apply between iterations, so check them before the cap
+            self.apply_overrides().await;
+            if self.iteration >= self.config.max_iterations {
+                break;
+            }
             debug!(exec_id = %self.exec_id, iteration = self.iteration, max = self.config.max_iterations, "run: loop iteration start");
             // Check for coordinator messages before each iteration
             if let Some(result) = self.poll_coordinator_messages().await {
                 debug!(exec_id = %self.exec_id, "run: coordinator message caused early return");
                 return Ok(result);
             }
+            if let Some(result) = self.check_budget_exhausted() {
+                return Ok(result);
+            }
+            if let Some(result) = self.check_provider_circuit(None).await {
+                self.status = LoopStatus::Paused;
+                return Ok(result);
+            }
 
             self.iteration += 1;
             info!(
@@ -276,8 +747,35 @@ impl LoopEngine {
             if let Some(ref emitter) = self.event_emitter {
                 emitter.iteration_started(self.iteration);
             }
+            self.warn_if_budget_low().await;
+
+            if let Some(reason) = self.run_hooks(HookEvent::PreIteration, None, None).await {
+                self.iteration -= 1; // The iteration never ran; resuming retries it
+                return Ok(self.block_on_hook(reason));
+            }
+
+            let result = match self.run_watched_iteration().await? {
+                Ok(result) => {
+                    self.consecutive_timeouts = 0;
+                    result
+                }
+                Err(cause) => match self.handle_timeout(cause).await {
+                    Some(result) => return Ok(result),
+                    None => continue,
+                },
+            };
 
-            let result = self.run_iteration().await?;
+            // Post-iteration hooks see the validation exit code
+            let exit_code = match &result {
+                IterationResult::Complete { .. } => Some(0),
+                IterationResult::Continue { exit_code, .. } => Some(*exit_code),
+                _ => None,
+            };
+            if let Some(code) = exit_code
+                && let Some(reason) = self.run_hooks(HookEvent::PostIteration, Some(code), None).await
+            {
+                return Ok(self.block_on_hook(reason));
+            }
 
             match result {
                 IterationResult::Complete { .. } => {
@@ -298,14 +796,32 @@ impl LoopEngine {
                             .iteration_completed(self.iteration, EventIterationOutcome::ValidationFailed { exit_code });
                     }
                     // Continue to next iteration
-                    tokio::time::sleep(Duration::from_millis(500)).await;
+                    self.clock.sleep(Duration::from_millis(500)).await;
                 }
                 IterationResult::RateLimited { retry_after } => {
                     debug!(exec_id = %self.exec_id, ?retry_after, "run: rate limited");
                     warn!("Rate limited, sleeping for {:?}", retry_after);
-                    tokio::time::sleep(retry_after).await;
+                    if let Some(ref emitter) = self.event_emitter {
+                        emitter.rate_limited(self.iteration, retry_after.as_millis() as u64);
+                    }
+                    self.clock.sleep(retry_after).await;
                     self.iteration -= 1; // Don't count this iteration
                 }
+                IterationResult::Stuck {
+                    action,
+                    unchanged_iterations,
+                } => {
+                    debug!(exec_id = %self.exec_id, %action, unchanged_iterations, "run: stuck");
+                    let reason = format!("No progress for {} iterations", unchanged_iterations);
+                    self.status = match action {
+                        StuckAction::Escalate => LoopStatus::Blocked { reason },
+                        _ => LoopStatus::Paused,
+                    };
+                    return Ok(IterationResult::Stuck {
+                        action,
+                        unchanged_iterations,
+                    });
+                }
                 IterationResult::Interrupted { reason } => {
                     debug!(exec_id = %self.exec_id, %reason, "run: interrupted");
                     // Emit loop completed (not successful)
@@ -315,25 +831,51 @@ impl LoopEngine {
                     self.status = LoopStatus::Stopped;
                     return Ok(IterationResult::Interrupted { reason });
                 }
-                IterationResult::Error { message, recoverable } => {
+                IterationResult::Error {
+                    message,
+                    recoverable,
+                    code,
+                } => {
                     if !recoverable {
-                        debug!(exec_id = %self.exec_id, %message, "run: non-recoverable error");
+                        debug!(exec_id = %self.exec_id, %message, ?code, "run: non-recoverable error");
                         // Emit iteration completed with LLM error
                         if let Some(ref emitter) = self.event_emitter {
                             emitter.iteration_completed(
                                 self.iteration,
-                                EventIterationOutcome::LlmError { error: message.clone() },
+                                EventIterationOutcome::LlmError {
+                                    error: message.clone(),
+                                    code: code.clone(),
+                                },
                             );
                             emitter.loop_completed(false, self.iteration);
                         }
                         self.status = LoopStatus::Failed {
                             reason: message.clone(),
                         };
-                        return Ok(IterationResult::Error { message, recoverable });
+                        return Ok(IterationResult::Error {
+                            message,
+                            recoverable,
+                            code,
+                        });
+                    }
+                    if let Some(result) = self.check_provider_circuit(Some(message.clone())).await {
+                        self.iteration -= 1; // The provider failed, not this iteration
+                        self.status = LoopStatus::Paused;
+                        return Ok(result);
                     }
                     debug!(exec_id = %self.exec_id, %message, "run: recoverable error, continuing");
                     warn!("Recoverable error: {}", message);
                 }
+                IterationResult::ProviderUnavailable { message } => {
+                    debug!(exec_id = %self.exec_id, %message, "run: provider unavailable");
+                    self.iteration -= 1;
+                    self.status = LoopStatus::Paused;
+                    return Ok(IterationResult::ProviderUnavailable { message });
+                }
+                IterationResult::Blocked { reason } => {
+                    debug!(exec_id = %self.exec_id, %reason, "run: blocked");
+                    return Ok(self.block_on_hook(reason));
+                }
             }
         }
 
@@ -349,6 +891,83 @@ impl LoopEngine {
         Ok(IterationResult::Error {
             message: format!("Max iterations ({}) exceeded", self.config.max_iterations),
             recoverable: false,
+            code: None,
+        })
+    }
+
+    /// Run an iteration under the watchdog's iteration limit
+    ///
+    /// Returns the cause instead of a result if the watchdog cancelled it.
+    async fn run_watched_iteration(&mut self) -> eyre::Result<Result<IterationResult, WatchdogTimeout>> {
+        let Some(limit) = self.config.watchdog.iteration_timeout() else {
+            return self.run_iteration().await;
+        };
+        debug!(exec_id = %self.exec_id, iteration = self.iteration, ?limit, "run_watched_iteration: called");
+        match tokio::time::timeout(limit, self.run_iteration()).await {
+            Ok(result) => result,
+            Err(_) => {
+                // The cancelled iteration may have held or awaited a scheduler slot
+                if let Some(scheduler) = &self.scheduler {
+                    let prefix = format!("{}-turn-", self.exec_id);
+                    for entry in scheduler.queue_details().await {
+                        if entry.exec_id.starts_with(&prefix) {
+                            scheduler.cancel(&entry.exec_id).await;
+                            scheduler.complete(&entry.exec_id).await;
+                        }
+                    }
+                }
+                Ok(Err(WatchdogTimeout::Iteration { limit }))
+            }
+        }
+    }
+
+    /// Record a cancelled iteration and apply the watchdog policy
+    ///
+    /// Returns None to carry on with the next iteration, or the result that
+    /// ends the loop.
+    async fn handle_timeout(&mut self, cause: WatchdogTimeout) -> Option<IterationResult> {
+        self.consecutive_timeouts += 1;
+        let message = cause.to_string();
+        debug!(exec_id = %self.exec_id, iteration = self.iteration, %message, consecutive = self.consecutive_timeouts, "handle_timeout: called");
+        warn!(exec_id = %self.exec_id, iteration = self.iteration, %message, "Watchdog cancelled iteration");
+
+        if let Some(ref emitter) = self.event_emitter {
+            emitter.warning(
+                "watchdog",
+                &format!("Iteration {} cancelled: {}", self.iteration, message),
+            );
+            emitter.iteration_completed(
+                self.iteration,
+                EventIterationOutcome::TimedOut { cause: message.clone() },
+            );
+        }
+        if let Some(ref state) = self.state {
+            let error = format!("Iteration {}: {}", self.iteration, message);
+            if let Err(e) = state
+                .modify_execution(&self.exec_id, |exec| exec.set_error(error.clone()))
+                .await
+            {
+                warn!(exec_id = %self.exec_id, error = %e, "Failed to record timeout");
+            }
+        }
+
+        if self.config.watchdog.should_retry(self.consecutive_timeouts) {
+            debug!(exec_id = %self.exec_id, "handle_timeout: retrying with a fresh iteration");
+            return None;
+        }
+
+        let reason = format!(
+            "Watchdog: {} ({} timed-out iteration(s) in a row, on-timeout: {})",
+            message, self.consecutive_timeouts, self.config.watchdog.on_timeout
+        );
+        if let Some(ref emitter) = self.event_emitter {
+            emitter.loop_completed(false, self.iteration);
+        }
+        self.status = LoopStatus::Failed { reason: reason.clone() };
+        Some(IterationResult::Error {
+            message: reason,
+            recoverable: false,
+            code: None,
         })
     }
 
@@ -408,33 +1027,41 @@ impl LoopEngine {
                     data,
                 } => {
                     debug!(exec_id = %self.exec_id, %from_exec_id, %share_type, "poll_coordinator_messages: received share");
-                    // Log shared data - could be used for inter-loop coordination
                     info!(
                         "Loop {} received share '{}' from {}: {}",
                         self.exec_id, share_type, from_exec_id, data
                     );
+                    if share_type == NUDGE_SHARE_TYPE {
+                        // Operator messages go into the next prompt, not the facts
+                        let message = data.get("message").and_then(|v| v.as_str()).unwrap_or_default();
+                        match data.get("from").and_then(|v| v.as_str()) {
+                            Some(from) => self.nudges.push(format!("{}: {}", from, message)),
+                            None => self.nudges.push(message.to_string()),
+                        }
+                        continue;
+                    }
+                    // Latest value per share type is available to prompts as a fact
+                    self.shared_facts.insert(share_type, data);
                 }
                 CoordMessage::Notification {
-                    from_exec_id,
-                    event_type,
-                    data,
+                    ref from_exec_id,
+                    ref event_type,
+                    ref data,
+                    seq,
                 } => {
-                    debug!(exec_id = %self.exec_id, %from_exec_id, %event_type, "poll_coordinator_messages: received notification");
+                    debug!(exec_id = %self.exec_id, %from_exec_id, %event_type, %seq, "poll_coordinator_messages: received notification");
                     // Handle notifications - main_updated is particularly important
-                    if event_type == "main_updated" {
+                    if event_type == MainUpdated::NAME {
                         debug!(exec_id = %self.exec_id, "poll_coordinator_messages: main_updated notification");
                         info!(
                             "Loop {} received main_updated notification from {}: {}",
                             self.exec_id, from_exec_id, data
                         );
 
-                        // Extract new SHA from notification data
-                        let new_sha = data.get("new_sha").and_then(|v| v.as_str()).map(|s| s.to_string());
-                        let branch = data
-                            .get("branch")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("main")
-                            .to_string();
+                        // The coordinator validated the payload, so this only misses on a schema change
+                        let update = msg.payload::<MainUpdated>();
+                        let new_sha = update.as_ref().map(|u| u.sha.clone());
+                        let branch = update.map(|u| u.branch).unwrap_or_else(|| "main".to_string());
 
                         // Perform rebase
                         debug!(exec_id = %self.exec_id, %branch, ?new_sha, "poll_coordinator_messages: performing rebase");
@@ -471,6 +1098,25 @@ impl LoopEngine {
         None
     }
 
+    /// Publish a `budget_warning` alert when a fifth of the iterations remain
+    async fn warn_if_budget_low(&self) {
+        let remaining = self.config.max_iterations.saturating_sub(self.iteration);
+        if remaining == 0 || remaining != self.config.max_iterations / 5 {
+            return;
+        }
+        let Some(ref coord_handle) = self.coord_handle else {
+            return;
+        };
+        debug!(exec_id = %self.exec_id, %remaining, "warn_if_budget_low: publishing");
+        let warning = BudgetWarning {
+            exec_id: self.exec_id.clone(),
+            remaining,
+        };
+        if let Err(e) = coord_handle.publish(&warning).await {
+            warn!(exec_id = %self.exec_id, "Failed to publish budget warning: {}", e);
+        }
+    }
+
     /// Handle rebase when main branch is updated
     ///
     /// This pauses the loop, performs a git rebase onto the updated main branch,
@@ -527,22 +1173,25 @@ impl LoopEngine {
     }
 
     /// Run a single iteration
-    async fn run_iteration(&mut self) -> eyre::Result<IterationResult> {
+    ///
+    /// Returns the cause instead of a result if a tool call timed out.
+    async fn run_iteration(&mut self) -> eyre::Result<Result<IterationResult, WatchdogTimeout>> {
         debug!(exec_id = %self.exec_id, iteration = self.iteration, "run_iteration: called");
 
         // Clear iteration-level tracking
         self.tool_call_buffer.clear();
         self.iteration_token_usage = TokenUsage::default();
+        self.iteration_served_by = None;
 
-        // Build context for template
-        let context = self.build_template_context().await?;
-        debug!(exec_id = %self.exec_id, "run_iteration: built template context");
-
-        // Render prompt
-        let prompt = self.render_prompt(&context)?;
+        // Steering and operator messages go into this prompt only
+        self.warm_start().await;
+        let prompt = self.assemble_prompt().await?;
+        self.steering = None;
+        self.nudges.clear();
         debug!(exec_id = %self.exec_id, prompt_len = prompt.len(), "run_iteration: rendered prompt");
 
         // Create tool context for this iteration - with coordinator if available
+        let tool_env = self.tool_env().await?;
         let tool_ctx = if let Some(ref coord_handle) = self.coord_handle {
             debug!(exec_id = %self.exec_id, "run_iteration: creating tool context with coordinator");
             ToolContext::with_coordinator(self.worktree.clone(), self.exec_id.clone(), coord_handle.clone())
@@ -550,6 +1199,17 @@ impl LoopEngine {
             debug!(exec_id = %self.exec_id, "run_iteration: creating tool context without coordinator");
             ToolContext::new(self.worktree.clone(), self.exec_id.clone())
         };
+        let tool_ctx = tool_ctx
+            .with_lsp(self.lsp.clone())
+            .with_resource_limits(self.config.resource_limits.clone())
+            .with_env(tool_env)
+            .with_scanners(self.config.scanners.clone())
+            .with_read_only_mounts(self.config.read_only_mounts.clone())
+            .with_network(self.network.clone());
+        let tool_ctx = match self.exploration {
+            Some(ref exploration) => tool_ctx.with_exploration(exploration.clone()),
+            None => tool_ctx,
+        };
         tool_ctx.clear_reads().await;
 
         // Get tool definitions for this loop type
@@ -566,11 +1226,23 @@ impl LoopEngine {
             }
             AgenticLoopResult::RateLimited { retry_after } => {
                 debug!(exec_id = %self.exec_id, ?retry_after, "run_iteration: agentic loop rate limited");
-                return Ok(IterationResult::RateLimited { retry_after });
+                return Ok(Ok(IterationResult::RateLimited { retry_after }));
+            }
+            AgenticLoopResult::Error {
+                message,
+                recoverable,
+                code,
+            } => {
+                debug!(exec_id = %self.exec_id, %message, recoverable, ?code, "run_iteration: agentic loop error");
+                return Ok(Ok(IterationResult::Error {
+                    message,
+                    recoverable,
+                    code,
+                }));
             }
-            AgenticLoopResult::Error { message, recoverable } => {
-                debug!(exec_id = %self.exec_id, %message, recoverable, "run_iteration: agentic loop error");
-                return Ok(IterationResult::Error { message, recoverable });
+            AgenticLoopResult::TimedOut(cause) => {
+                debug!(exec_id = %self.exec_id, %cause, "run_iteration: tool call timed out");
+                return Ok(Err(cause));
             }
         }
 
@@ -580,21 +1252,31 @@ impl LoopEngine {
             run_validation_streaming(
                 &self.config.validation_command,
                 &self.worktree,
+                &self.command_env,
                 Duration::from_millis(self.config.iteration_timeout_ms),
                 emitter,
                 self.iteration,
+                self.clock.as_ref(),
             )
             .await?
         } else {
             run_validation(
                 &self.config.validation_command,
                 &self.worktree,
+                &self.command_env,
                 Duration::from_millis(self.config.iteration_timeout_ms),
+                self.clock.as_ref(),
             )
             .await?
         };
         debug!(exec_id = %self.exec_id, exit_code = validation.exit_code, duration_ms = validation.duration_ms, "run_iteration: validation complete");
 
+        // Extract structured test failures for the next prompt and metrics
+        let score = self.record_validation_report(&validation);
+        let acceptance_failures = self.check_acceptance().await;
+        let review_pending = self.advance_review(&validation).await;
+        let phase_pending = self.advance_budget(&validation).await;
+
         // Record progress
         let files_changed = self.get_changed_files().await;
         debug!(exec_id = %self.exec_id, files_changed_count = files_changed.len(), "run_iteration: got changed files");
@@ -607,11 +1289,12 @@ impl LoopEngine {
             validation.duration_ms,
             files_changed.clone(),
         );
-        self.progress.record(&iter_ctx);
+        let progress_entry = self.progress.record(&iter_ctx);
 
         // Persist iteration log with FULL validation output (before truncation)
         if let Some(ref state) = self.state {
             let log = IterationLog::new(&self.exec_id, self.iteration)
+                .with_created_at(self.clock.now_ms())
                 .with_validation_command(&self.config.validation_command)
                 .with_exit_code(validation.exit_code)
                 .with_stdout(&validation.stdout)
@@ -622,7 +1305,8 @@ impl LoopEngine {
                     Some(self.iteration_token_usage.input_tokens),
                     Some(self.iteration_token_usage.output_tokens),
                 )
-                .with_tool_calls(std::mem::take(&mut self.tool_call_buffer));
+                .with_tool_calls(std::mem::take(&mut self.tool_call_buffer))
+                .with_served_by(self.iteration_served_by.clone());
 
             if let Err(e) = state.create_iteration_log(log).await {
                 warn!(exec_id = %self.exec_id, iteration = self.iteration, error = %e, "Failed to persist iteration log");
@@ -631,28 +1315,75 @@ impl LoopEngine {
             }
 
             // Update aggregate metrics on the LoopExecution
-            if let Ok(Some(mut exec)) = state.get_execution(&self.exec_id).await {
-                exec.add_iteration_metrics(
-                    self.iteration_token_usage.input_tokens,
-                    self.iteration_token_usage.output_tokens,
-                    validation.duration_ms,
-                );
-                if let Err(e) = state.update_execution(exec).await {
-                    warn!(exec_id = %self.exec_id, error = %e, "Failed to update execution metrics");
-                }
+            if let Err(e) = state
+                .modify_execution(&self.exec_id, |exec| {
+                    exec.add_iteration_metrics(
+                        self.iteration_token_usage.input_tokens,
+                        self.iteration_token_usage.output_tokens,
+                        validation.duration_ms,
+                    );
+                    if !self.acceptance.is_empty() {
+                        exec.acceptance = self.acceptance.clone();
+                    }
+                })
+                .await
+            {
+                warn!(exec_id = %self.exec_id, error = %e, "Failed to update execution metrics");
             }
         }
 
-        // Check if validation passed
-        if validation.passed(self.config.success_exit_code) {
+        // Snapshot the worktree, rolling back if this iteration made things worse
+        self.snapshot_iteration(score).await;
+
+        // Check if validation passed (and, if any, the acceptance criteria hold,
+        // the review pipeline has finished and the iteration ran in the final phase)
+        if validation.passed(self.config.success_exit_code)
+            && acceptance_failures.is_none()
+            && review_pending.is_none()
+            && phase_pending.is_none()
+        {
             debug!(exec_id = %self.exec_id, "run_iteration: validation passed");
+            self.hold_debate().await;
             info!(
                 "Loop {} completed successfully after {} iterations",
                 self.exec_id, self.iteration
             );
-            return Ok(IterationResult::Complete {
+            return Ok(Ok(IterationResult::Complete {
                 iterations: self.iteration,
-            });
+            }));
+        }
+
+        if let Some(failures) = acceptance_failures.filter(|_| validation.passed(self.config.success_exit_code)) {
+            debug!(exec_id = %self.exec_id, "run_iteration: validation passed but acceptance criteria failed");
+            info!(
+                "Loop {} iteration {} validation passed, acceptance criteria not met",
+                self.exec_id, self.iteration
+            );
+            if let Some(result) = self.check_stuck(&progress_entry).await {
+                debug!(exec_id = %self.exec_id, "run_iteration: loop is stuck");
+                return Ok(Ok(result));
+            }
+            return Ok(Ok(IterationResult::Continue {
+                validation_output: failures,
+                exit_code: validation.exit_code,
+            }));
+        }
+
+        if let Some(next) = review_pending.filter(|_| validation.passed(self.config.success_exit_code)) {
+            // A clean review pass changes nothing by design, so stuck detection does not apply
+            debug!(exec_id = %self.exec_id, "run_iteration: validation passed, review passes remain");
+            return Ok(Ok(IterationResult::Continue {
+                validation_output: next,
+                exit_code: validation.exit_code,
+            }));
+        }
+
+        if let Some(next) = phase_pending.filter(|_| validation.passed(self.config.success_exit_code)) {
+            debug!(exec_id = %self.exec_id, "run_iteration: validation passed before the final phase");
+            return Ok(Ok(IterationResult::Continue {
+                validation_output: next,
+                exit_code: validation.exit_code,
+            }));
         }
 
         debug!(exec_id = %self.exec_id, exit_code = validation.exit_code, "run_iteration: validation failed");
@@ -661,35 +1392,216 @@ impl LoopEngine {
             self.exec_id, self.iteration, validation.exit_code
         );
 
-        Ok(IterationResult::Continue {
+        if let Some(result) = self.check_stuck(&progress_entry).await {
+            debug!(exec_id = %self.exec_id, "run_iteration: loop is stuck");
+            return Ok(Ok(result));
+        }
+
+        Ok(Ok(IterationResult::Continue {
             validation_output: if !validation.stdout.is_empty() {
                 validation.stdout
             } else {
                 validation.stderr
             },
             exit_code: validation.exit_code,
-        })
+        }))
     }
 
-    /// Run the agentic tool loop within an iteration
-    async fn run_agentic_loop(
-        &mut self,
-        initial_prompt: &str,
-        tool_ctx: &ToolContext,
-        tool_defs: &[ToolDefinition],
-    ) -> eyre::Result<AgenticLoopResult> {
-        debug!(exec_id = %self.exec_id, prompt_len = initial_prompt.len(), tool_count = tool_defs.len(), "run_agentic_loop: called");
-        let system_prompt = format!(
+    /// Hand the progress strategy the digests of files earlier iterations read and that are unchanged since
+    async fn warm_start(&mut self) {
+        debug!(exec_id = %self.exec_id, iteration = self.iteration, "warm_start: called");
+        let Some(ref exploration) = self.exploration else {
+            return;
+        };
+        let mut cache = exploration.lock().await;
+        cache.refresh().await;
+        debug!(exec_id = %self.exec_id, files = cache.len(), "warm_start: refreshed exploration cache");
+        self.progress.set_explored(cache.render(self.config.exploration_cache.max_files));
+        cache.begin_iteration(self.iteration);
+    }
+
+    /// The iteration's first user message: the rendered template plus steering,
+    /// operator messages and context store excerpts
+    async fn assemble_prompt(&self) -> eyre::Result<String> {
+        let context = self.build_template_context().await?;
+        debug!(exec_id = %self.exec_id, "assemble_prompt: built template context");
+
+        let mut prompt = self.render_prompt(&context)?;
+        if let Some(ref steering) = self.steering {
+            debug!(exec_id = %self.exec_id, "assemble_prompt: appending steering prompt");
+            prompt.push_str("\n\n## Change of Approach Required\n");
+            prompt.push_str(steering);
+        }
+        if !self.nudges.is_empty() {
+            debug!(exec_id = %self.exec_id, count = self.nudges.len(), "assemble_prompt: appending operator messages");
+            prompt.push_str("\n\n## Messages from the Operator\n");
+            for nudge in &self.nudges {
+                prompt.push_str(&format!("- {}\n", nudge));
+            }
+        }
+        if let Some(excerpts) = self.select_context().await {
+            debug!(exec_id = %self.exec_id, "assemble_prompt: appending context store excerpts");
+            prompt.push_str("\n\n## Relevant Context\n");
+            prompt.push_str(&excerpts);
+        }
+        Ok(prompt)
+    }
+
+    /// The system prompt sent with every turn
+    /// Environment for commands spawned by tools, routed through the network proxy when the policy restricts
+    ///
+    /// Without the proxy the policy could not be applied to commands, so failing to start it fails the iteration.
+    async fn tool_env(&mut self) -> eyre::Result<Vec<(String, String)>> {
+        let mut env = self.command_env.clone();
+        if self.config.network.is_restricted() && self.network_proxy.is_none() {
+            let proxy = NetworkProxy::start(self.network.clone())
+                .await
+                .map_err(|e| eyre::eyre!("Failed to start the network proxy: {}", e))?;
+            debug!(exec_id = %self.exec_id, addr = %proxy.addr(), "tool_env: started network proxy");
+            self.network_proxy = Some(proxy);
+        }
+        if let Some(ref proxy) = self.network_proxy {
+            env.extend(proxy.env());
+        }
+        Ok(env)
+    }
+
+    /// Emit the outbound requests tools made since the last call
+    fn emit_network_log(&self, network: &NetworkGuard) {
+        for access in network.take_log() {
+            if !access.allowed {
+                warn!(exec_id = %self.exec_id, tool = %access.tool, host = %access.host, "Outbound request blocked by network policy");
+            }
+            if let Some(ref emitter) = self.event_emitter {
+                emitter.network_request(self.iteration, &access);
+            }
+        }
+    }
+
+    fn system_prompt(&self) -> String {
+        let mut prompt = format!(
             "You are an AI assistant working on a task. Complete the task using the available tools.\n\
              Working directory: {}\n\
              Loop type: {}",
             self.worktree.display(),
             self.config.loop_type
         );
+        if !self.config.read_only_mounts.is_empty() {
+            prompt.push_str("\nRead-only mounts (readable as @name/path, never writable):");
+            for (name, path) in &self.config.read_only_mounts {
+                prompt.push_str(&format!("\n- @{}: {}", name, path.display()));
+            }
+        }
+        prompt
+    }
+
+    /// Render the request the next iteration would start with, without calling the LLM
+    pub async fn preview_prompt(&mut self) -> eyre::Result<PromptPreview> {
+        debug!(exec_id = %self.exec_id, iteration = self.iteration, "preview_prompt: called");
+        // The engine renders with the iteration already counted
+        self.iteration += 1;
+        let prompt = self.assemble_prompt().await;
+        self.iteration -= 1;
+        Ok(PromptPreview {
+            iteration: self.iteration + 1,
+            system_prompt: self.system_prompt(),
+            prompt: prompt?,
+            tools: self
+                .tool_executor
+                .definitions_for(&self.config.tools)
+                .into_iter()
+                .map(|def| def.name)
+                .collect(),
+            max_tokens: self.config.max_tokens,
+        })
+    }
+
+    /// Feed a recorded iteration back in, as if the engine had just run it
+    ///
+    /// Rebuilds the progress and previous errors the following prompt is
+    /// assembled from; acceptance results are used when they were recorded by
+    /// that iteration.
+    pub fn replay_iteration(&mut self, log: &IterationLog) {
+        debug!(exec_id = %self.exec_id, iteration = log.iteration, "replay_iteration: called");
+        self.iteration = log.iteration;
+        let validation = ValidationResult {
+            exit_code: log.exit_code,
+            stdout: log.stdout.clone(),
+            stderr: log.stderr.clone(),
+            duration_ms: log.duration_ms,
+        };
+        self.record_validation_report(&validation);
+        if self.acceptance.iter().any(|check| check.iteration == log.iteration) {
+            self.append_acceptance_failures();
+        }
+        self.progress.record(&IterationContext::new(
+            log.iteration,
+            &log.validation_command,
+            log.exit_code,
+            &log.stdout,
+            &log.stderr,
+            log.duration_ms,
+            log.files_changed.clone(),
+        ));
+    }
+
+    /// Let the scheduler adapt to the provider's latest rate limit headers
+    async fn report_rate_limits(&self) {
+        if let Some(scheduler) = &self.scheduler
+            && let Some(status) = self.llm.rate_limits()
+        {
+            debug!(exec_id = %self.exec_id, "report_rate_limits: forwarding to scheduler");
+            scheduler.observe_rate_limits(&status).await;
+        }
+    }
+
+    /// Count a provider call towards the scheduler's circuit breaker
+    ///
+    /// Only outages (5xx, network errors, timeouts) count as failures; any
+    /// response from the provider counts as a success.
+    async fn report_provider_outcome(&self, error: Option<&LlmError>) {
+        let Some(scheduler) = &self.scheduler else {
+            return;
+        };
+        match error {
+            None => {
+                scheduler.record_provider_success().await;
+            }
+            Some(e) if e.category().is_outage() => {
+                debug!(exec_id = %self.exec_id, error = %e, "report_provider_outcome: provider outage error");
+                scheduler.record_provider_error().await;
+            }
+            Some(_) => {}
+        }
+    }
+
+    /// Stop iterating while the provider circuit breaker is open
+    ///
+    /// `error` is the failure that ended the iteration, if any.
+    async fn check_provider_circuit(&self, error: Option<String>) -> Option<IterationResult> {
+        let scheduler = self.scheduler.as_ref()?;
+        if !scheduler.provider_circuit_open().await {
+            return None;
+        }
+        info!(exec_id = %self.exec_id, "Provider circuit open, waiting for the provider to recover");
+        Some(IterationResult::ProviderUnavailable {
+            message: error.unwrap_or_else(|| "Provider circuit breaker open".to_string()),
+        })
+    }
+
+    /// Run the agentic tool loop within an iteration
+    async fn run_agentic_loop(
+        &mut self,
+        initial_prompt: &str,
+        tool_ctx: &ToolContext,
+        tool_defs: &[ToolDefinition],
+    ) -> eyre::Result<AgenticLoopResult> {
+        debug!(exec_id = %self.exec_id, prompt_len = initial_prompt.len(), tool_count = tool_defs.len(), "run_agentic_loop: called");
+        let system_prompt = self.system_prompt();
+
+        let mut messages = vec![Message::user(initial_prompt)];
+        let mut turn = 0;
 
-        let mut messages = vec![Message::user(initial_prompt)];
-        let mut turn = 0;
-
         loop {
             turn += 1;
             debug!(exec_id = %self.exec_id, turn, max_turns = self.config.max_turns_per_iteration, "run_agentic_loop: turn start");
@@ -715,11 +1627,15 @@ impl LoopEngine {
                 // Use a turn-specific ID for per-turn rate limiting
                 let turn_id = format!("{}-turn-{}", self.exec_id, turn);
                 debug!(exec_id = %self.exec_id, %turn_id, "run_agentic_loop: waiting for scheduler slot");
-                if let Err(e) = scheduler.wait_for_slot(&turn_id, Priority::Normal).await {
+                if let Err(e) = scheduler
+                    .wait_for_slot_as(&turn_id, Priority::Normal, &self.config.loop_type)
+                    .await
+                {
                     debug!(exec_id = %self.exec_id, error = %e, "run_agentic_loop: scheduler error");
                     return Ok(AgenticLoopResult::Error {
                         message: format!("Scheduler error: {}", e),
                         recoverable: true,
+                        code: None,
                     });
                 }
                 debug!(exec_id = %self.exec_id, "run_agentic_loop: got scheduler slot");
@@ -776,6 +1692,9 @@ impl LoopEngine {
                             }
                         }
                     }
+                    if let Some(ref e) = emitter {
+                        e.flush_tokens();
+                    }
                     response_text
                 });
 
@@ -786,6 +1705,8 @@ impl LoopEngine {
                         let _ = stream_handle.await;
 
                         debug!(exec_id = %self.exec_id, turn, stop_reason = ?r.stop_reason, "run_agentic_loop: LLM response received");
+                        self.report_rate_limits().await;
+                        self.report_provider_outcome(None).await;
                         // Mark scheduler slot as complete after successful call
                         if let Some(scheduler) = &self.scheduler {
                             let turn_id = format!("{}-turn-{}", self.exec_id, turn);
@@ -801,16 +1722,30 @@ impl LoopEngine {
                                 r.usage.input_tokens,
                                 r.usage.output_tokens,
                                 !r.tool_calls.is_empty(),
+                                r.served_by.as_deref(),
                             );
                         }
 
                         // Accumulate token usage for this iteration
                         self.iteration_token_usage.input_tokens += r.usage.input_tokens;
                         self.iteration_token_usage.output_tokens += r.usage.output_tokens;
+                        if r.served_by.is_some() {
+                            self.iteration_served_by = r.served_by.clone();
+                        }
                         r
                     }
+                    Err(LlmError::ContextTooLarge { estimated, limit }) if turn > 1 => {
+                        // The conversation outgrew the window: end the iteration so the next starts fresh
+                        warn!(exec_id = %self.exec_id, turn, estimated, limit, "run_agentic_loop: context window full, ending iteration");
+                        if let Some(scheduler) = &self.scheduler {
+                            let turn_id = format!("{}-turn-{}", self.exec_id, turn);
+                            scheduler.complete(&turn_id).await;
+                        }
+                        break;
+                    }
                     Err(e) if e.is_rate_limit() => {
                         debug!(exec_id = %self.exec_id, turn, "run_agentic_loop: LLM rate limited");
+                        self.report_rate_limits().await;
                         if let Some(scheduler) = &self.scheduler {
                             let turn_id = format!("{}-turn-{}", self.exec_id, turn);
                             scheduler.complete(&turn_id).await;
@@ -821,6 +1756,7 @@ impl LoopEngine {
                     }
                     Err(e) if e.is_retryable() => {
                         debug!(exec_id = %self.exec_id, turn, error = %e, "run_agentic_loop: LLM retryable error");
+                        self.report_provider_outcome(Some(&e)).await;
                         if let Some(scheduler) = &self.scheduler {
                             let turn_id = format!("{}-turn-{}", self.exec_id, turn);
                             scheduler.complete(&turn_id).await;
@@ -828,6 +1764,7 @@ impl LoopEngine {
                         return Ok(AgenticLoopResult::Error {
                             message: e.to_string(),
                             recoverable: true,
+                            code: Some(e.code().to_string()),
                         });
                     }
                     Err(e) => {
@@ -839,6 +1776,7 @@ impl LoopEngine {
                         return Ok(AgenticLoopResult::Error {
                             message: e.to_string(),
                             recoverable: false,
+                            code: Some(e.code().to_string()),
                         });
                     }
                 }
@@ -847,6 +1785,8 @@ impl LoopEngine {
                 match self.llm.complete(request).await {
                     Ok(r) => {
                         debug!(exec_id = %self.exec_id, turn, stop_reason = ?r.stop_reason, "run_agentic_loop: LLM response received");
+                        self.report_rate_limits().await;
+                        self.report_provider_outcome(None).await;
                         // Mark scheduler slot as complete after successful call
                         if let Some(scheduler) = &self.scheduler {
                             let turn_id = format!("{}-turn-{}", self.exec_id, turn);
@@ -855,10 +1795,23 @@ impl LoopEngine {
                         // Accumulate token usage for this iteration
                         self.iteration_token_usage.input_tokens += r.usage.input_tokens;
                         self.iteration_token_usage.output_tokens += r.usage.output_tokens;
+                        if r.served_by.is_some() {
+                            self.iteration_served_by = r.served_by.clone();
+                        }
                         r
                     }
+                    Err(LlmError::ContextTooLarge { estimated, limit }) if turn > 1 => {
+                        // The conversation outgrew the window: end the iteration so the next starts fresh
+                        warn!(exec_id = %self.exec_id, turn, estimated, limit, "run_agentic_loop: context window full, ending iteration");
+                        if let Some(scheduler) = &self.scheduler {
+                            let turn_id = format!("{}-turn-{}", self.exec_id, turn);
+                            scheduler.complete(&turn_id).await;
+                        }
+                        break;
+                    }
                     Err(e) if e.is_rate_limit() => {
                         debug!(exec_id = %self.exec_id, turn, "run_agentic_loop: LLM rate limited");
+                        self.report_rate_limits().await;
                         // Mark slot complete even on rate limit
                         if let Some(scheduler) = &self.scheduler {
                             let turn_id = format!("{}-turn-{}", self.exec_id, turn);
@@ -870,6 +1823,7 @@ impl LoopEngine {
                     }
                     Err(e) if e.is_retryable() => {
                         debug!(exec_id = %self.exec_id, turn, error = %e, "run_agentic_loop: LLM retryable error");
+                        self.report_provider_outcome(Some(&e)).await;
                         // Mark slot complete on retryable error
                         if let Some(scheduler) = &self.scheduler {
                             let turn_id = format!("{}-turn-{}", self.exec_id, turn);
@@ -878,6 +1832,7 @@ impl LoopEngine {
                         return Ok(AgenticLoopResult::Error {
                             message: e.to_string(),
                             recoverable: true,
+                            code: Some(e.code().to_string()),
                         });
                     }
                     Err(e) => {
@@ -890,6 +1845,7 @@ impl LoopEngine {
                         return Ok(AgenticLoopResult::Error {
                             message: e.to_string(),
                             recoverable: false,
+                            code: Some(e.code().to_string()),
                         });
                     }
                 }
@@ -917,9 +1873,13 @@ impl LoopEngine {
                     }
 
                     // Execute tools and continue
-                    let start_time = std::time::Instant::now();
-                    let tool_results = self.execute_tools(&response.tool_calls, tool_ctx).await;
-                    let duration_ms = start_time.elapsed().as_millis() as u64;
+                    let start_time = self.clock.instant();
+                    let mut tool_results = match self.execute_tools(&response.tool_calls, tool_ctx).await {
+                        Ok(results) => results,
+                        Err(cause) => return Ok(AgenticLoopResult::TimedOut(cause)),
+                    };
+                    let duration_ms = self.clock.instant().duration_since(start_time).as_millis() as u64;
+                    self.claim_written_paths(&response.tool_calls, &mut tool_results).await;
                     debug!(exec_id = %self.exec_id, turn, results_count = tool_results.len(), "run_agentic_loop: tools executed");
 
                     // Record tool call summaries for iteration log and emit events
@@ -939,7 +1899,15 @@ impl LoopEngine {
                                 duration_ms,
                             );
                         }
+
+                        if let Some(ref violation) = result.violation {
+                            warn!(exec_id = %self.exec_id, tool = %call.name, %violation, "Tool command exceeded resource limit");
+                            if let Some(ref emitter) = self.event_emitter {
+                                emitter.resource_limit_exceeded(self.iteration, &call.name, violation);
+                            }
+                        }
                     }
+                    self.emit_network_log(&tool_ctx.network);
 
                     // Build user message with tool results
                     let tool_result_message = self.build_tool_result_message(&tool_results);
@@ -964,9 +1932,108 @@ impl LoopEngine {
     }
 
     /// Execute tool calls and return results
-    async fn execute_tools(&self, tool_calls: &[crate::llm::ToolCall], ctx: &ToolContext) -> Vec<(String, ToolResult)> {
+    ///
+    /// Each call is bounded by the watchdog's tool limit; a call that exceeds
+    /// it abandons the rest of the batch.
+    async fn execute_tools(
+        &self,
+        tool_calls: &[crate::llm::ToolCall],
+        ctx: &ToolContext,
+    ) -> Result<Vec<(String, ToolResult)>, WatchdogTimeout> {
         debug!(exec_id = %self.exec_id, tool_count = tool_calls.len(), "execute_tools: called");
-        self.tool_executor.execute_all(tool_calls, ctx).await
+        let limit = self.config.watchdog.tool_timeout();
+        let mut results = Vec::with_capacity(tool_calls.len());
+        for call in tool_calls {
+            let started = self.clock.instant();
+            let input = PolicyInput::tool_call(self.policy_execution(), &call.name, &call.input);
+            let modified;
+            let call = match self.policies.evaluate(input).await {
+                PolicyOutcome::Allow => call,
+                PolicyOutcome::Modify(input) => {
+                    debug!(exec_id = %self.exec_id, tool = %call.name, "execute_tools: input modified by policy");
+                    modified = crate::llm::ToolCall {
+                        input: input.tool.map(|t| t.input).unwrap_or_else(|| call.input.clone()),
+                        ..call.clone()
+                    };
+                    &modified
+                }
+                denied @ PolicyOutcome::Deny { .. } => {
+                    let reason = denied.denial().unwrap_or_default();
+                    debug!(exec_id = %self.exec_id, tool = %call.name, %reason, "execute_tools: call denied by policy");
+                    results.push((call.id.clone(), ToolResult::error(reason)));
+                    continue;
+                }
+            };
+            match self.tool_executor.execute_within(call, ctx, limit).await {
+                Some(result) => results.push((call.id.clone(), result)),
+                None => {
+                    let cause = WatchdogTimeout::ToolCall {
+                        tool: call.name.clone(),
+                        limit: limit.unwrap_or_default(),
+                    };
+                    if let Some(ref emitter) = self.event_emitter {
+                        let duration_ms = self.clock.instant().duration_since(started).as_millis() as u64;
+                        emitter.tool_call_completed(self.iteration, &call.name, false, &cause.to_string(), duration_ms);
+                    }
+                    return Err(cause);
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    /// Claim advisory locks on files written or edited for the first time
+    ///
+    /// Locks are advisory, so a write to a path another execution holds has
+    /// already happened; its tool result gains a note so the LLM can keep the
+    /// change small. Granted locks are recorded on the execution for the TUI.
+    async fn claim_written_paths(&mut self, tool_calls: &[crate::llm::ToolCall], results: &mut [(String, ToolResult)]) {
+        let Some(coord_handle) = self.coord_handle.clone() else {
+            return;
+        };
+        debug!(exec_id = %self.exec_id, "claim_written_paths: called");
+
+        let mut granted = Vec::new();
+        for (call, (_, result)) in tool_calls.iter().zip(results.iter_mut()) {
+            if result.is_error || !matches!(call.name.as_str(), "write" | "edit") {
+                continue;
+            }
+            let Some(raw) = call.input["path"].as_str() else {
+                continue;
+            };
+            let relative = Path::new(raw).strip_prefix(&self.worktree).unwrap_or(Path::new(raw));
+            let path = normalize_lock_path(&relative.to_string_lossy());
+            if path.is_empty() || !self.claimed_paths.insert(path.clone()) {
+                continue;
+            }
+
+            match coord_handle.lock_paths(std::slice::from_ref(&path)).await {
+                Ok(conflicts) if conflicts.is_empty() => granted.push(path),
+                Ok(conflicts) => {
+                    for conflict in conflicts {
+                        warn!(exec_id = %self.exec_id, %path, held_by = %conflict.held_by, "Edited a path locked by another execution");
+                        if let Some(ref emitter) = self.event_emitter {
+                            emitter.path_conflict(&path, &conflict.held_by);
+                        }
+                        result.content.push_str(&format!(
+                            "\n\nNote: {} is locked by execution {}, which is changing it in another worktree. \
+                             Keep your changes to it minimal to limit merge conflicts.",
+                            conflict.held_path, conflict.held_by
+                        ));
+                    }
+                }
+                Err(e) => warn!(exec_id = %self.exec_id, %path, error = %e, "Failed to claim path lock"),
+            }
+        }
+
+        if !granted.is_empty()
+            && let Some(ref state) = self.state
+            && let Err(e) = state
+                .modify_execution(&self.exec_id, |exec| exec.add_locked_paths(&granted))
+                .await
+        {
+            warn!(exec_id = %self.exec_id, error = %e, "Failed to record path locks");
+        }
     }
 
     /// Build assistant message from response
@@ -994,29 +2061,42 @@ impl LoopEngine {
     /// Build user message with tool results
     fn build_tool_result_message(&self, results: &[(String, ToolResult)]) -> Message {
         debug!(exec_id = %self.exec_id, result_count = results.len(), "build_tool_result_message: called");
-        let blocks: Vec<ContentBlock> = results
+        let mut blocks: Vec<ContentBlock> = results
             .iter()
             .map(|(id, result)| {
                 debug!(exec_id = %self.exec_id, %id, is_error = result.is_error, "build_tool_result_message: adding result");
                 ContentBlock::tool_result(id, &result.content, result.is_error)
             })
             .collect();
+        // Images must follow every tool result in the message
+        blocks.extend(results.iter().flat_map(|(_, result)| result.images.iter().cloned()));
 
         Message::user_blocks(blocks)
     }
 
     /// Build template context for prompt rendering
-    async fn build_template_context(&self) -> eyre::Result<HashMap<String, String>> {
+    ///
+    /// Execution context values are copied as-is; the engine adds iteration info,
+    /// git state, progress, parent content, a `phase` object and shared `facts`.
+    async fn build_template_context(&self) -> eyre::Result<serde_json::Value> {
         debug!(exec_id = %self.exec_id, "build_template_context: called");
-        let mut context = HashMap::new();
+        let mut context = serde_json::Map::new();
 
         // Basic loop info
-        context.insert("working-directory".to_string(), self.worktree.display().to_string());
-        context.insert("iteration".to_string(), self.iteration.to_string());
+        context.insert(
+            "working-directory".to_string(),
+            self.worktree.display().to_string().into(),
+        );
+        context.insert("iteration".to_string(), self.iteration.into());
+        context.insert("max-iterations".to_string(), self.config.max_iterations.into());
+        context.insert("exec-id".to_string(), self.exec_id.clone().into());
+        context.insert("loop-type".to_string(), self.config.loop_type.clone().into());
         debug!(exec_id = %self.exec_id, "build_template_context: added basic info");
 
-        // Add execution context values (from cascade)
+        // Add execution context values (from cascade or submission)
         self.populate_execution_context(&mut context);
+        self.populate_phase(&mut context);
+        context.insert("facts".to_string(), self.shared_facts.clone().into());
 
         // Read parent content from file if this is a child loop
         self.populate_parent_content(&mut context).await;
@@ -1031,7 +2111,7 @@ impl LoopEngine {
         {
             let status = String::from_utf8_lossy(&output.stdout).to_string();
             debug!(exec_id = %self.exec_id, status_len = status.len(), "build_template_context: got git status");
-            context.insert("git-status".to_string(), status);
+            context.insert("git-status".to_string(), status.into());
         } else {
             debug!(exec_id = %self.exec_id, "build_template_context: failed to get git status");
         }
@@ -1053,36 +2133,652 @@ impl LoopEngine {
                 debug!(exec_id = %self.exec_id, diff_len = diff.len(), "build_template_context: diff fits");
                 diff
             };
-            context.insert("git-diff".to_string(), truncated);
+            context.insert("git-diff".to_string(), truncated.into());
         } else {
             debug!(exec_id = %self.exec_id, "build_template_context: failed to get git diff");
         }
 
         // Progress from previous iterations
         debug!(exec_id = %self.exec_id, "build_template_context: adding progress");
-        context.insert("progress".to_string(), self.progress.get_progress());
+        context.insert("progress".to_string(), self.progress.get_progress().into());
+
+        // Failures from the last validation run
+        if let Some(ref errors) = self.previous_errors {
+            debug!(exec_id = %self.exec_id, errors_len = errors.len(), "build_template_context: adding previous errors");
+            context.insert("previous-errors".to_string(), errors.clone().into());
+        }
+
+        self.populate_review(&mut context);
+        self.populate_budget(&mut context);
+        self.populate_security_findings(&mut context);
+        self.populate_diff_review(&mut context).await;
 
         debug!(exec_id = %self.exec_id, context_keys = context.len(), "build_template_context: complete");
-        Ok(context)
+        Ok(context.into())
+    }
+
+    /// Parse validation output into a test report, record it, and remember the failures
+    ///
+    /// When the output matches a known test runner only the failing tests are kept
+    /// for the next prompt; otherwise the tail of the raw output is used.
+    fn record_validation_report(&mut self, validation: &ValidationResult) -> ValidationScore {
+        debug!(exec_id = %self.exec_id, exit_code = validation.exit_code, "record_validation_report: called");
+        let report = TestReport::parse(&validation.stdout, &validation.stderr);
+        let score = ValidationScore::of(validation, report.as_ref(), self.config.success_exit_code);
+
+        if let (Some(report), Some(metrics)) = (&report, &self.metrics) {
+            debug!(exec_id = %self.exec_id, "record_validation_report: recording test report in metrics");
+            metrics.record_test_report(&self.exec_id, self.iteration, report);
+        }
+
+        if validation.passed(self.config.success_exit_code) {
+            debug!(exec_id = %self.exec_id, "record_validation_report: validation passed, clearing errors");
+            self.previous_errors = None;
+            return score;
+        }
+
+        self.previous_errors = match report {
+            Some(report) if report.has_failures() => {
+                debug!(exec_id = %self.exec_id, failed = report.failed.len(), "record_validation_report: using structured failures");
+                Some(report.format_failures())
+            }
+            _ => {
+                debug!(exec_id = %self.exec_id, "record_validation_report: using raw output tail");
+                let raw = if validation.stderr.trim().is_empty() {
+                    &validation.stdout
+                } else {
+                    &validation.stderr
+                };
+                Some(tail_str(raw.trim(), MAX_PREVIOUS_ERRORS_CHARS))
+            }
+        };
+        score
+    }
+
+    /// Excerpts from the worktree's ContextStore for the failing tests and plan section
+    ///
+    /// None when there is no corpus, nothing to search for, or nothing matched.
+    async fn select_context(&self) -> Option<String> {
+        let config = self.config.context_selection.clone();
+        if !config.enabled {
+            return None;
+        }
+        let mut terms = self.progress.context_terms();
+        if let Some(section) = self.execution_context.get("phase-name").and_then(|v| v.as_str()) {
+            for term in section_terms(section) {
+                if !terms.contains(&term) {
+                    terms.push(term);
+                }
+            }
+        }
+        if terms.is_empty() {
+            return None;
+        }
+        debug!(exec_id = %self.exec_id, ?terms, "select_context: called");
+
+        let worktree = self.worktree.clone();
+        let snippets = tokio::task::spawn_blocking(move || {
+            ContextSelector::open(&worktree, &config)
+                .map(|selector| selector.select(&terms))
+                .unwrap_or_default()
+        })
+        .await
+        .ok()?;
+        if snippets.is_empty() {
+            return None;
+        }
+        debug!(exec_id = %self.exec_id, selected = snippets.len(), "select_context: selected excerpts");
+        Some(render_snippets(&snippets))
+    }
+
+    /// Re-check the acceptance criteria; the failures, if any, as prompt text
+    ///
+    /// Failures are appended to `previous_errors` so the next iteration sees
+    /// which criteria still do not hold.
+    async fn check_acceptance(&mut self) -> Option<String> {
+        if self.acceptance.is_empty() {
+            return None;
+        }
+        debug!(exec_id = %self.exec_id, count = self.acceptance.len(), "check_acceptance: called");
+        check_all(
+            &mut self.acceptance,
+            self.iteration,
+            &self.worktree,
+            &self.command_env,
+            Duration::from_millis(self.config.iteration_timeout_ms),
+            self.clock.as_ref(),
+        )
+        .await;
+        self.append_acceptance_failures()
+    }
+
+    /// The criteria that do not hold, as prompt text, also appended to `previous_errors`
+    fn append_acceptance_failures(&mut self) -> Option<String> {
+        let failures: Vec<String> = self
+            .acceptance
+            .iter()
+            .filter(|check| check.status != CriterionStatus::Passed)
+            .map(|check| match &check.detail {
+                Some(detail) => format!("- {}: {}", check.criterion, detail),
+                None => format!("- {}", check.criterion),
+            })
+            .collect();
+        if failures.is_empty() {
+            debug!(exec_id = %self.exec_id, "append_acceptance_failures: all criteria met");
+            return None;
+        }
+
+        let failures = format!("Acceptance criteria not met:\n{}", failures.join("\n"));
+        self.previous_errors = Some(match self.previous_errors.take() {
+            Some(errors) => format!("{}\n\n{}", errors, failures),
+            None => failures.clone(),
+        });
+        Some(failures)
+    }
+
+    /// Add the current review pass (`review-pass`, `review-pass-name`, `review-instructions`, ...)
+    ///
+    /// The pass prompt is rendered against the rest of the context so it can
+    /// refer to variables such as `{{output-file}}`.
+    fn populate_review(&self, context: &mut serde_json::Map<String, serde_json::Value>) {
+        let Some(review) = &self.review else {
+            return;
+        };
+        let Some(pass) = review.current() else {
+            return;
+        };
+        debug!(exec_id = %self.exec_id, pass = %pass.name, "populate_review: called");
+
+        let instructions = self
+            .handlebars
+            .render_template(&pass.prompt, &serde_json::Value::Object(context.clone()))
+            .unwrap_or_else(|e| {
+                warn!(exec_id = %self.exec_id, pass = %pass.name, error = %e, "Failed to render review pass prompt");
+                pass.prompt.clone()
+            });
+        context.insert("review-pass".to_string(), review.number().into());
+        context.insert("review-pass-count".to_string(), review.total().into());
+        context.insert("review-pass-name".to_string(), pass.name.clone().into());
+        context.insert("review-pass-description".to_string(), pass.description.clone().into());
+        context.insert("review-instructions".to_string(), instructions.into());
+    }
+
+    /// Add the current budget phase (`budget-phase-name`, `budget-phase-instructions`, ...)
+    ///
+    /// Like review pass prompts, the phase prompt is rendered against the rest
+    /// of the context.
+    fn populate_budget(&self, context: &mut serde_json::Map<String, serde_json::Value>) {
+        let Some(budget) = &self.budget else {
+            return;
+        };
+        let phase = budget.current();
+        debug!(exec_id = %self.exec_id, phase = %phase.name, "populate_budget: called");
+
+        let instructions = self
+            .handlebars
+            .render_template(&phase.prompt, &serde_json::Value::Object(context.clone()))
+            .unwrap_or_else(|e| {
+                warn!(exec_id = %self.exec_id, phase = %phase.name, error = %e, "Failed to render budget phase prompt");
+                phase.prompt.clone()
+            });
+        context.insert("budget-phase".to_string(), (budget.index() + 1).into());
+        context.insert("budget-phase-count".to_string(), budget.total().into());
+        context.insert("budget-phase-name".to_string(), phase.name.clone().into());
+        context.insert("budget-phase-remaining".to_string(), budget.remaining().into());
+        context.insert("budget-phase-instructions".to_string(), instructions.into());
+    }
+
+    /// Add the scanner findings still waiting for triage (`security-findings`, `security-summary`)
+    ///
+    /// Only for loop types with scanners; absent until the first `security_scan`.
+    fn populate_security_findings(&self, context: &mut serde_json::Map<String, serde_json::Value>) {
+        if self.config.scanners.is_empty() {
+            return;
+        }
+        debug!(exec_id = %self.exec_id, "populate_security_findings: called");
+        let store = match FindingStore::load(&self.worktree) {
+            Ok(store) if !store.scanned.is_empty() => store,
+            Ok(_) => return,
+            Err(e) => {
+                warn!(exec_id = %self.exec_id, error = %e, "Failed to load security findings");
+                return;
+            }
+        };
+        context.insert("security-summary".to_string(), store.counts().to_string().into());
+        context.insert("security-findings".to_string(), store.format_pending().into());
+    }
+
+    /// Add the diff under review, its plan and the comments so far (`review-*`)
+    ///
+    /// Only for the `review` loop type. The first iteration resolves the task
+    /// (a branch or an execution ID) to a branch and starts the review file,
+    /// pinning the commit; later iterations review that same commit.
+    async fn populate_diff_review(&self, context: &mut serde_json::Map<String, serde_json::Value>) {
+        if self.config.loop_type != REVIEW_TYPE {
+            return;
+        }
+        debug!(exec_id = %self.exec_id, "populate_diff_review: called");
+        let target = self
+            .execution_context
+            .get("task")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+
+        let prepared = match Review::load(&self.worktree) {
+            Ok(Some(review)) if review.target == target => {
+                debug!(exec_id = %self.exec_id, head = %review.head, "populate_diff_review: continuing review");
+                branch_diff(&self.worktree, &review.head).await.map(|(_, diff)| (review, diff))
+            }
+            Ok(_) => self.start_diff_review(&target).await,
+            Err(e) => Err(e),
+        };
+        let (review, diff) = match prepared {
+            Ok(prepared) => prepared,
+            Err(e) => {
+                warn!(exec_id = %self.exec_id, %target, error = %e, "Failed to prepare review");
+                context.insert("review-error".to_string(), format!("{:#}", e).into());
+                return;
+            }
+        };
+
+        context.insert("review-diff".to_string(), diff.into());
+        context.insert("review-target".to_string(), review.target.clone().into());
+        context.insert("review-branch".to_string(), review.branch.clone().into());
+        context.insert("review-summary".to_string(), review.counts().into());
+        if !review.comments.is_empty() {
+            context.insert("review-comments".to_string(), review.format_comments().into());
+        }
+
+        // The plan is the reviewed execution's parent document or task
+        let reviewed = execution_id(&review.branch).unwrap_or(&review.target);
+        if let Some(ref state) = self.state
+            && let Ok(Some(exec)) = state.get_execution(reviewed).await
+        {
+            debug!(exec_id = %self.exec_id, %reviewed, "populate_diff_review: adding plan of reviewed execution");
+            let plan = super::manager::evaluation_criteria(&exec, &self.repo_root).await;
+            context.insert("review-plan".to_string(), plan.into());
+        }
+    }
+
+    /// Resolve `target` to a branch and start a review of its current head
+    async fn start_diff_review(&self, target: &str) -> eyre::Result<(Review, String)> {
+        debug!(exec_id = %self.exec_id, %target, "start_diff_review: called");
+        if target.is_empty() {
+            return Err(eyre::eyre!("no branch or execution ID given"));
+        }
+        let branch = resolve_target(&self.worktree, target).await?;
+        let (head, diff) = branch_diff(&self.worktree, &branch).await?;
+        let review = Review::new(target, &branch, &head);
+        review.save(&self.worktree)?;
+        info!(exec_id = %self.exec_id, %branch, %head, "Started review");
+        Ok((review, diff))
+    }
+
+    /// Record this iteration against the current review pass
+    ///
+    /// A pass converges when its command (or, without one, the loop's
+    /// validation) succeeds and, if it asks for `unchanged`, the iteration
+    /// wrote no files. Returns a note for the next prompt while passes remain.
+    async fn advance_review(&mut self, validation: &ValidationResult) -> Option<String> {
+        let pass = self.review.as_ref()?.current()?.clone();
+        debug!(exec_id = %self.exec_id, pass = %pass.name, "advance_review: called");
+
+        let mut converged = match &pass.converge.command {
+            Some(command) => {
+                let command = self
+                    .handlebars
+                    .render_template(command, &self.execution_context)
+                    .unwrap_or_else(|_| command.clone());
+                match run_validation(
+                    &command,
+                    &self.worktree,
+                    &self.command_env,
+                    Duration::from_millis(self.config.iteration_timeout_ms),
+                    self.clock.as_ref(),
+                )
+                .await
+                {
+                    Ok(result) => result.exit_code == 0,
+                    Err(e) => {
+                        warn!(exec_id = %self.exec_id, pass = %pass.name, error = %e, "Failed to run review pass command");
+                        false
+                    }
+                }
+            }
+            None => validation.passed(self.config.success_exit_code),
+        };
+        if pass.converge.unchanged {
+            let wrote = self
+                .tool_call_buffer
+                .iter()
+                .any(|call| !call.is_error && matches!(call.tool_name.as_str(), "write" | "edit"));
+            converged &= !wrote;
+        }
+
+        let review = self.review.as_mut()?;
+        let step = review.record(converged);
+        debug!(exec_id = %self.exec_id, pass = %pass.name, converged, ?step, "advance_review: recorded");
+        match step {
+            ReviewStep::Complete => {
+                info!("Loop {} finished its review passes", self.exec_id);
+                None
+            }
+            ReviewStep::Stay => Some(format!(
+                "Review pass '{}' has not converged yet; keep reviewing.",
+                pass.name
+            )),
+            ReviewStep::Advanced { forced } => {
+                let next = review.current().map(|p| p.name.clone()).unwrap_or_default();
+                if forced {
+                    warn!(exec_id = %self.exec_id, pass = %pass.name, "Review pass hit its iteration cap without converging");
+                }
+                info!(
+                    "Loop {} review pass '{}' done, next: '{}'",
+                    self.exec_id, pass.name, next
+                );
+                Some(format!(
+                    "Review pass '{}' {}; continue with pass '{}'.",
+                    pass.name,
+                    if forced {
+                        "reached its iteration cap"
+                    } else {
+                        "converged"
+                    },
+                    next
+                ))
+            }
+        }
+    }
+
+    /// Record this iteration against the current budget phase
+    ///
+    /// A phase ends once its `until` command (or, without one, the loop's
+    /// validation) succeeds, or when its allowance is used up. Returns a note
+    /// for the next prompt when the iteration ran before the final phase, so
+    /// the loop cannot complete yet.
+    async fn advance_budget(&mut self, validation: &ValidationResult) -> Option<String> {
+        let budget = self.budget.as_ref()?;
+        let phase = budget.current().clone();
+        let index = budget.index();
+        if budget.is_final() {
+            self.budget.as_mut()?.record(false);
+            return None;
+        }
+        debug!(exec_id = %self.exec_id, phase = %phase.name, "advance_budget: called");
+
+        let done = match &phase.until {
+            Some(command) => {
+                let command = self
+                    .handlebars
+                    .render_template(command, &self.execution_context)
+                    .unwrap_or_else(|_| command.clone());
+                match run_validation(
+                    &command,
+                    &self.worktree,
+                    &self.command_env,
+                    Duration::from_millis(self.config.iteration_timeout_ms),
+                    self.clock.as_ref(),
+                )
+                .await
+                {
+                    Ok(result) => result.exit_code == 0,
+                    Err(e) => {
+                        warn!(exec_id = %self.exec_id, phase = %phase.name, error = %e, "Failed to run budget phase command");
+                        false
+                    }
+                }
+            }
+            None => validation.passed(self.config.success_exit_code),
+        };
+
+        let budget = self.budget.as_mut()?;
+        let step = budget.record(done);
+        debug!(exec_id = %self.exec_id, phase = %phase.name, done, ?step, "advance_budget: recorded");
+        let PhaseStep::Advanced {
+            used,
+            allowance,
+            carried,
+        } = step
+        else {
+            return Some(format!(
+                "Phase '{}' is not finished yet ({} iteration(s) left); keep to its instructions.",
+                phase.name,
+                budget.remaining()
+            ));
+        };
+
+        let next = budget.current().name.clone();
+        info!(
+            "Loop {} phase '{}' done after {}/{} iterations, next: '{}' ({} carried over)",
+            self.exec_id, phase.name, used, allowance, next, carried
+        );
+        if let Some(ref emitter) = self.event_emitter {
+            emitter.phase_completed(index, &phase.name, used, allowance, carried);
+            emitter.phase_started(budget.index(), &next, budget.total());
+        }
+        Some(format!(
+            "Phase '{}' {}; continue with phase '{}'.",
+            phase.name,
+            if done {
+                "is done"
+            } else {
+                "used its iteration budget"
+            },
+            next
+        ))
+    }
+
+    /// Fail the loop once the final budget phase has used its allowance
+    fn check_budget_exhausted(&mut self) -> Option<IterationResult> {
+        let budget = self.budget.as_ref().filter(|budget| budget.is_exhausted())?;
+        let phase = budget.current().name.clone();
+        debug!(exec_id = %self.exec_id, %phase, "check_budget_exhausted: final phase out of iterations");
+        let reason = format!("Phase '{}' iteration budget exhausted", phase);
+        warn!(exec_id = %self.exec_id, "{}", reason);
+        if let Some(ref emitter) = self.event_emitter {
+            emitter.loop_completed(false, self.iteration);
+        }
+        self.status = LoopStatus::Failed { reason: reason.clone() };
+        Some(IterationResult::Error {
+            message: reason,
+            recoverable: false,
+            code: None,
+        })
+    }
+
+    /// Snapshot the worktree after this iteration and apply the rollback policy
+    ///
+    /// On a regression the best iteration's snapshot is restored and the next
+    /// prompt is steered away from the discarded approach.
+    async fn snapshot_iteration(&mut self, score: ValidationScore) {
+        let policy = &self.config.snapshots;
+        if !policy.enabled {
+            debug!(exec_id = %self.exec_id, "snapshot_iteration: snapshots disabled");
+            return;
+        }
+        debug!(exec_id = %self.exec_id, iteration = self.iteration, ?score, "snapshot_iteration: called");
+        if let Err(e) = create_snapshot(&self.worktree, &self.exec_id, self.iteration).await {
+            warn!(exec_id = %self.exec_id, iteration = self.iteration, error = %e, "Failed to snapshot worktree");
+            return;
+        }
+
+        let Some(regression) = self.regressions.observe(self.iteration, score, policy.rollback) else {
+            return;
+        };
+        warn!(
+            exec_id = %self.exec_id,
+            iteration = self.iteration,
+            restore_to = regression.restore_to,
+            "Iteration made validation worse ({} vs {}), rolling back",
+            regression.current,
+            regression.best
+        );
+        if let Err(e) = restore_snapshot(&self.worktree, &self.exec_id, regression.restore_to).await {
+            warn!(exec_id = %self.exec_id, error = %e, "Failed to roll back worktree");
+            return;
+        }
+        self.steering = Some(format!(
+            "Iteration {} made validation worse ({}, versus {} after iteration {}), so its changes were \
+             discarded and the worktree was reset to the state after iteration {}. The validation errors reported \
+             above come from the discarded changes. Do not repeat that approach.",
+            self.iteration, regression.current, regression.best, regression.restore_to, regression.restore_to
+        ));
+    }
+
+    /// Have the loop type's debate personas argue over the finished plan
+    ///
+    /// The judge's final plan replaces the draft in `output-file` and the
+    /// transcript is written next to it. If the debate fails the draft stands.
+    async fn hold_debate(&self) {
+        let Some(config) = &self.config.debate else {
+            return;
+        };
+        let Some(output_file) = self.execution_context.get("output-file").and_then(|v| v.as_str()) else {
+            warn!(exec_id = %self.exec_id, "Debate configured but the loop has no output file, skipping");
+            return;
+        };
+        debug!(exec_id = %self.exec_id, %output_file, "hold_debate: called");
+        let plan_path = self.worktree.join(output_file);
+        let draft = match tokio::fs::read_to_string(&plan_path).await {
+            Ok(draft) => draft,
+            Err(e) => {
+                warn!(exec_id = %self.exec_id, file = ?plan_path, error = %e, "Failed to read plan for debate");
+                return;
+            }
+        };
+        let task = ["user-request", "task"]
+            .iter()
+            .find_map(|key| self.execution_context.get(*key).and_then(|v| v.as_str()))
+            .unwrap_or_default();
+
+        info!(exec_id = %self.exec_id, personas = config.personas.len(), rounds = config.rounds, "Debating plan");
+        let transcript = match Debate::new(self.llm.clone(), config.clone()).run(task, &draft).await {
+            Ok(transcript) => transcript,
+            Err(e) => {
+                warn!(exec_id = %self.exec_id, error = %format!("{:#}", e), "Plan debate failed, keeping the draft");
+                return;
+            }
+        };
+
+        let transcript_path = plan_path.with_file_name(&config.transcript);
+        if let Err(e) = tokio::fs::write(&transcript_path, transcript.render_markdown()).await {
+            warn!(exec_id = %self.exec_id, file = ?transcript_path, error = %e, "Failed to write debate transcript");
+        }
+        if let Err(e) = tokio::fs::write(&plan_path, format!("{}\n", transcript.final_plan)).await {
+            warn!(exec_id = %self.exec_id, file = ?plan_path, error = %e, "Failed to write debated plan");
+            return;
+        }
+        info!(
+            exec_id = %self.exec_id,
+            turns = transcript.turns.len(),
+            input_tokens = transcript.input_tokens,
+            output_tokens = transcript.output_tokens,
+            "Plan debate finished, final plan written"
+        );
+    }
+
+    /// Check whether the loop has stopped making progress and apply the configured action
+    ///
+    /// Returns a result only when the action ends the run (pause or escalate);
+    /// the steer action queues a steering prompt for the next iteration instead.
+    async fn check_stuck(&mut self, progress_entry: &str) -> Option<IterationResult> {
+        debug!(exec_id = %self.exec_id, "check_stuck: called");
+        let worktree_state = self.worktree_state().await;
+        let unchanged = self.progress_monitor.observe(&worktree_state, progress_entry)?;
+
+        let action = self.config.stuck_detection.action;
+        warn!(
+            exec_id = %self.exec_id,
+            iteration = self.iteration,
+            unchanged,
+            %action,
+            "Loop is stuck: no material progress"
+        );
+        if let Some(ref emitter) = self.event_emitter {
+            emitter.loop_stuck(self.iteration, unchanged, &action.to_string());
+        }
+
+        match action {
+            StuckAction::Steer => {
+                debug!(exec_id = %self.exec_id, "check_stuck: queueing steering prompt");
+                self.steering = Some(self.config.stuck_detection.steering_prompt().to_string());
+                self.progress_monitor.reset();
+                None
+            }
+            StuckAction::Pause | StuckAction::Escalate => {
+                debug!(exec_id = %self.exec_id, %action, "check_stuck: ending run");
+                Some(IterationResult::Stuck {
+                    action,
+                    unchanged_iterations: unchanged,
+                })
+            }
+        }
+    }
+
+    /// Snapshot of everything an iteration could change in the worktree (HEAD, index, diff)
+    async fn worktree_state(&self) -> String {
+        debug!(exec_id = %self.exec_id, "worktree_state: called");
+        let mut state = String::new();
+        for args in [
+            &["rev-parse", "HEAD"][..],
+            &["status", "--porcelain"][..],
+            &["diff", "HEAD"][..],
+        ] {
+            match tokio::process::Command::new("git")
+                .args(args)
+                .current_dir(&self.worktree)
+                .output()
+                .await
+            {
+                Ok(output) => state.push_str(&String::from_utf8_lossy(&output.stdout)),
+                Err(e) => debug!(exec_id = %self.exec_id, error = %e, ?args, "worktree_state: git failed"),
+            }
+            state.push('\0');
+        }
+        state
     }
 
     /// Populate template context from execution context (cascade values)
-    fn populate_execution_context(&self, context: &mut HashMap<String, String>) {
+    fn populate_execution_context(&self, context: &mut serde_json::Map<String, serde_json::Value>) {
         debug!(exec_id = %self.exec_id, "populate_execution_context: called");
 
-        // Copy string values from execution_context to template context
         if let Some(obj) = self.execution_context.as_object() {
             for (key, value) in obj {
-                if let Some(s) = value.as_str() {
-                    debug!(exec_id = %self.exec_id, %key, "populate_execution_context: adding value");
-                    context.insert(key.clone(), s.to_string());
-                }
+                debug!(exec_id = %self.exec_id, %key, "populate_execution_context: adding value");
+                context.insert(key.clone(), value.clone());
+            }
+        }
+    }
+
+    /// Group the cascade's phase values into a `phase` object (`{{phase.name}}`, ...)
+    fn populate_phase(&self, context: &mut serde_json::Map<String, serde_json::Value>) {
+        debug!(exec_id = %self.exec_id, "populate_phase: called");
+        let mut phase = serde_json::Map::new();
+        for (key, field) in [
+            ("phase-name", "name"),
+            ("phase-description", "description"),
+            ("phase-number", "number"),
+            ("total-phases", "total"),
+        ] {
+            if let Some(value) = self.execution_context.get(key) {
+                // Numbers arrive as strings from the cascade
+                let value = match value.as_str().and_then(|s| s.parse::<u64>().ok()) {
+                    Some(n) if field == "number" || field == "total" => n.into(),
+                    _ => value.clone(),
+                };
+                phase.insert(field.to_string(), value);
             }
         }
+        if !phase.is_empty() {
+            debug!(exec_id = %self.exec_id, "populate_phase: adding phase object");
+            context.insert("phase".to_string(), phase.into());
+        }
     }
 
     /// Populate parent content from file (for cascade child loops)
-    async fn populate_parent_content(&self, context: &mut HashMap<String, String>) {
+    async fn populate_parent_content(&self, context: &mut serde_json::Map<String, serde_json::Value>) {
         debug!(exec_id = %self.exec_id, "populate_parent_content: called");
 
         // Get parent type and file path from execution context
@@ -1115,7 +2811,7 @@ impl LoopEngine {
                     };
 
                     debug!(exec_id = %self.exec_id, %var_name, "populate_parent_content: setting variable");
-                    context.insert(var_name.to_string(), content);
+                    context.insert(var_name.to_string(), content.into());
                 }
                 Err(e) => {
                     warn!(exec_id = %self.exec_id, file = ?full_path, error = %e, "Failed to read parent file");
@@ -1128,21 +2824,29 @@ impl LoopEngine {
             let output_path = self.worktree.join(output_file);
             if let Ok(content) = tokio::fs::read_to_string(&output_path).await {
                 debug!(exec_id = %self.exec_id, file = ?output_path, "populate_parent_content: read output file");
-                context.insert("current-plan".to_string(), content);
+                context.insert("current-plan".to_string(), content.into());
             }
         }
     }
 
     /// Render the prompt template with context
-    fn render_prompt(&self, context: &HashMap<String, String>) -> eyre::Result<String> {
-        debug!(exec_id = %self.exec_id, context_keys = context.len(), "render_prompt: called");
-        // For now, use simple string replacement since Handlebars setup is complex
-        let mut result = self.config.prompt_template.clone();
+    ///
+    /// The loop type's variable schema is applied first (defaults, types,
+    /// required variables); a missing required variable fails the iteration.
+    fn render_prompt(&self, context: &serde_json::Value) -> eyre::Result<String> {
+        debug!(exec_id = %self.exec_id, "render_prompt: called");
+        let context = template::resolve(&self.config.variables, context).map_err(|errors| {
+            eyre::eyre!(
+                "Invalid template context for {} loop: {}",
+                self.config.loop_type,
+                errors.join("; ")
+            )
+        })?;
 
-        for (key, value) in context {
-            let placeholder = format!("{{{{{}}}}}", key);
-            result = result.replace(&placeholder, value);
-        }
+        let result = self
+            .handlebars
+            .render_template(&self.config.prompt_template, &context)
+            .map_err(|e| eyre::eyre!("Failed to render {} prompt: {}", self.config.loop_type, e))?;
 
         debug!(exec_id = %self.exec_id, result_len = result.len(), "render_prompt: complete");
         Ok(result)
@@ -1193,7 +2897,12 @@ impl LoopEngine {
 enum AgenticLoopResult {
     Complete,
     RateLimited { retry_after: Duration },
-    Error { message: String, recoverable: bool },
+    Error {
+        message: String,
+        recoverable: bool,
+        code: Option<String>,
+    },
+    TimedOut(WatchdogTimeout),
 }
 
 #[cfg(test)]
@@ -1203,13 +2912,13 @@ mod tests {
     use crate::llm::client::mock::MockLlmClient;
     use tempfile::tempdir;
 
-    #[allow(dead_code)]
     fn make_mock_response(content: &str) -> CompletionResponse {
         CompletionResponse {
             content: Some(content.to_string()),
             tool_calls: vec![],
             stop_reason: StopReason::EndTurn,
             usage: TokenUsage::default(),
+            served_by: None,
         }
     }
 
@@ -1225,6 +2934,35 @@ mod tests {
         assert_eq!(*engine.status(), LoopStatus::Running);
     }
 
+    #[tokio::test]
+    async fn test_blocking_pre_iteration_hook_holds_loop() {
+        use crate::hooks::{HookPolicy, HookSpec, Hooks};
+
+        let temp = tempdir().unwrap();
+        let hook = |command: &str, on_failure| HookSpec {
+            command: command.to_string(),
+            timeout_secs: 5,
+            on_failure,
+        };
+        let config = LoopConfig {
+            loop_type: "phase".to_string(),
+            hooks: Hooks::default()
+                .with(HookEvent::PreIteration, hook("echo $TD_ITERATION > warned; exit 1", HookPolicy::Warn))
+                .with(HookEvent::PreIteration, hook("echo 'tree is dirty' >&2; exit 3", HookPolicy::Block)),
+            ..Default::default()
+        };
+        let llm = Arc::new(MockLlmClient::new(vec![]));
+        let mut engine = LoopEngine::new("test-exec".to_string(), config, llm, temp.path().to_path_buf());
+
+        let result = engine.run().await.unwrap();
+        let reason = "Blocked by pre-iteration hook `echo 'tree is dirty' >&2; exit 3` failed: exit 3: tree is dirty";
+        assert!(matches!(&result, IterationResult::Blocked { reason: r } if r == reason));
+        assert_eq!(engine.iteration(), 0);
+        assert!(matches!(engine.status(), LoopStatus::Blocked { .. }));
+        // The warning hook ran first, for the iteration about to start
+        assert_eq!(std::fs::read_to_string(temp.path().join("warned")).unwrap().trim(), "1");
+    }
+
     #[tokio::test]
     async fn test_build_template_context() {
         let temp = tempdir().unwrap();
@@ -1242,9 +2980,11 @@ mod tests {
 
         let context = engine.build_template_context().await.unwrap();
 
-        assert!(context.contains_key("working-directory"));
-        assert!(context.contains_key("git-status"));
-        assert!(context.contains_key("progress"));
+        assert!(context.get("working-directory").is_some());
+        assert!(context.get("git-status").is_some());
+        assert!(context.get("progress").is_some());
+        assert_eq!(context["iteration"], 0);
+        assert_eq!(context["facts"], serde_json::json!({}));
     }
 
     #[tokio::test]
@@ -1258,13 +2998,498 @@ mod tests {
         let llm = Arc::new(MockLlmClient::new(vec![]));
         let engine = LoopEngine::new("test-exec".to_string(), config, llm, temp.path().to_path_buf());
 
-        let mut context = HashMap::new();
-        context.insert("working-directory".to_string(), "/tmp/test".to_string());
-        context.insert("iteration".to_string(), "5".to_string());
+        let context = serde_json::json!({ "working-directory": "/tmp/test", "iteration": 5 });
 
         let result = engine.render_prompt(&context).unwrap();
 
-        assert!(result.contains("/tmp/test"));
-        assert!(result.contains("5"));
+        assert_eq!(result, "Working in /tmp/test, iteration 5");
+    }
+
+    #[tokio::test]
+    async fn test_render_prompt_applies_variable_schema() {
+        let temp = tempdir().unwrap();
+        let config = LoopConfig {
+            loop_type: "refactor".to_string(),
+            prompt_template: "{{task}} (max {{max-files}} files){{#if dry-run}} - dry run{{/if}}".to_string(),
+            variables: serde_yaml::from_str(
+                "task: {required: true}\nmax-files: {type: number, default: 5}\ndry-run: {type: boolean}",
+            )
+            .unwrap(),
+            ..Default::default()
+        };
+        let llm = Arc::new(MockLlmClient::new(vec![]));
+        let engine = LoopEngine::new("test-exec".to_string(), config, llm, temp.path().to_path_buf());
+
+        let result = engine
+            .render_prompt(&serde_json::json!({ "task": "Split the parser", "dry-run": "false" }))
+            .unwrap();
+        assert_eq!(result, "Split the parser (max 5 files)");
+
+        let err = engine.render_prompt(&serde_json::json!({})).unwrap_err();
+        assert!(err.to_string().contains("Missing required variable 'task'"));
+    }
+
+    #[tokio::test]
+    async fn test_validation_report_feeds_previous_errors() {
+        let temp = tempdir().unwrap();
+        let llm = Arc::new(MockLlmClient::new(vec![]));
+        let metrics = Arc::new(LoopMetrics::new());
+        metrics.start_loop("test-exec", "phase");
+        let mut engine = LoopEngine::new(
+            "test-exec".to_string(),
+            LoopConfig::default(),
+            llm,
+            temp.path().to_path_buf(),
+        )
+        .with_metrics(metrics.clone());
+        engine.iteration = 1;
+
+        let failing = ValidationResult {
+            exit_code: 101,
+            stdout: "test a ... ok\ntest b ... FAILED\n\n---- b stdout ----\nboom\n\nfailures:\n".to_string(),
+            stderr: "   Compiling crate v0.1.0\n".to_string(),
+            duration_ms: 10,
+        };
+        engine.record_validation_report(&failing);
+
+        let context = engine.build_template_context().await.unwrap();
+        let errors = context["previous-errors"].as_str().unwrap();
+        assert!(errors.contains("### b"));
+        assert!(errors.contains("boom"));
+        assert!(!errors.contains("Compiling"));
+
+        engine.iteration = 2;
+        let passing = ValidationResult {
+            exit_code: 0,
+            stdout: "test a ... ok\ntest b ... ok\n".to_string(),
+            stderr: String::new(),
+            duration_ms: 10,
+        };
+        engine.record_validation_report(&passing);
+
+        assert!(engine.previous_errors.is_none());
+        let stats = metrics.get_loop_stats("test-exec").unwrap();
+        assert_eq!(stats.fixed_tests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_select_context_from_context_store() {
+        let temp = tempdir().unwrap();
+        let llm = Arc::new(MockLlmClient::new(vec![]));
+        let mut engine = LoopEngine::new(
+            "test-exec".to_string(),
+            LoopConfig::default(),
+            llm,
+            temp.path().to_path_buf(),
+        );
+        let failing = IterationContext::new(
+            1,
+            "cargo test",
+            101,
+            "test auth::test_refresh ... FAILED\n",
+            "",
+            10,
+            vec![],
+        );
+        engine.progress.record(&failing);
+
+        // No corpus yet
+        assert!(engine.select_context().await.is_none());
+
+        let doc = temp.path().join("auth.md");
+        std::fs::write(&doc, "Tokens refresh five minutes before expiry.").unwrap();
+        contextstore::ContextStore::open(temp.path().join(".contextstore"))
+            .unwrap()
+            .ingest(&[doc.display().to_string()], Default::default())
+            .unwrap();
+
+        let excerpts = engine.select_context().await.unwrap();
+        assert!(excerpts.contains("auth.md"));
+        assert!(excerpts.contains("Tokens refresh five minutes"));
+    }
+
+    #[test]
+    fn test_tail_str() {
+        assert_eq!(tail_str("short", 10), "short");
+        assert_eq!(tail_str("0123456789", 4), "...6789");
+    }
+
+    #[tokio::test]
+    async fn test_check_stuck_steers_then_pauses() {
+        let temp = tempdir().unwrap();
+        let llm = Arc::new(MockLlmClient::new(vec![]));
+        let mut config = LoopConfig::default();
+        config.stuck_detection.threshold = 1;
+        let mut engine = LoopEngine::new("test-exec".to_string(), config, llm, temp.path().to_path_buf());
+
+        assert!(engine.check_stuck("## Iteration 1\nfailed").await.is_none());
+        assert!(engine.check_stuck("## Iteration 2\nfailed").await.is_none());
+        assert!(engine.steering.is_some());
+
+        // After steering, another `threshold` unchanged iterations are needed
+        engine.config.stuck_detection.action = StuckAction::Pause;
+        let result = engine.check_stuck("## Iteration 3\nfailed").await;
+        assert!(matches!(
+            result,
+            Some(IterationResult::Stuck {
+                action: StuckAction::Pause,
+                unchanged_iterations: 1
+            })
+        ));
+    }
+
+    /// Run a two-iteration loop on a manual clock and return its events with the worktree path elided
+    async fn run_on_manual_clock() -> (Vec<String>, Duration) {
+        let temp = tempdir().unwrap();
+        tokio::process::Command::new("git")
+            .args(["init"])
+            .current_dir(temp.path())
+            .output()
+            .await
+            .unwrap();
+        let config = LoopConfig {
+            validation_command: "echo checking; exit 1".to_string(),
+            max_iterations: 2,
+            ..Default::default()
+        };
+        let llm = Arc::new(MockLlmClient::new(vec![
+            make_mock_response("first attempt"),
+            make_mock_response("second attempt"),
+        ]));
+        let bus = crate::events::EventBus::with_default_capacity();
+        let mut rx = bus.subscribe();
+        let clock = Arc::new(crate::clock::ManualClock::new(1_767_225_600_000));
+        let mut engine = LoopEngine::new("test-exec".to_string(), config, llm, temp.path().to_path_buf())
+            .with_event_emitter(bus.emitter_for("test-exec"))
+            .with_clock(clock.clone());
+        let _ = engine.run().await;
+
+        let worktree = temp.path().display().to_string();
+        let mut events = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            events.push(format!("{:?}", event).replace(&worktree, "<worktree>"));
+        }
+        (events, clock.elapsed())
+    }
+
+    #[tokio::test]
+    async fn test_manual_clock_runs_are_reproducible() {
+        let (first, first_elapsed) = run_on_manual_clock().await;
+        let (second, second_elapsed) = run_on_manual_clock().await;
+        assert!(first.iter().any(|e| e.contains("ValidationCompleted")));
+        assert_eq!(first, second);
+        // Only the 500ms pacing sleep after each failed iteration moved the clock
+        assert_eq!(first_elapsed, Duration::from_secs(1));
+        assert_eq!(second_elapsed, first_elapsed);
+    }
+
+    #[tokio::test]
+    async fn test_watchdog_retries_then_fails_hung_tool_calls() {
+        let temp = tempdir().unwrap();
+        tokio::process::Command::new("git")
+            .args(["init"])
+            .current_dir(temp.path())
+            .output()
+            .await
+            .unwrap();
+        let config = LoopConfig {
+            validation_command: "exit 1".to_string(),
+            watchdog: crate::r#loop::WatchdogPolicy {
+                tool_timeout_ms: 200,
+                max_retries: 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let hung_call = || CompletionResponse {
+            content: None,
+            tool_calls: vec![crate::llm::ToolCall {
+                id: "call_1".to_string(),
+                name: "bash".to_string(),
+                input: serde_json::json!({"command": "sleep 30"}),
+            }],
+            stop_reason: StopReason::ToolUse,
+            usage: TokenUsage::default(),
+            served_by: None,
+        };
+        let llm = Arc::new(MockLlmClient::new(vec![hung_call(), hung_call()]));
+        let bus = crate::events::EventBus::with_default_capacity();
+        let mut rx = bus.subscribe();
+        let mut engine = LoopEngine::new("test-exec".to_string(), config, llm, temp.path().to_path_buf())
+            .with_event_emitter(bus.emitter_for("test-exec"));
+
+        // One timeout is retried with a fresh iteration; the second fails the loop
+        match engine.run().await.unwrap() {
+            IterationResult::Error { message, recoverable, .. } => {
+                assert!(!recoverable);
+                assert!(message.contains("tool call 'bash' timed out"), "{}", message);
+            }
+            other => panic!("expected Error, got {:?}", other),
+        }
+        assert_eq!(engine.current_iteration(), 2);
+
+        let mut warnings = 0;
+        while let Ok(event) = rx.try_recv() {
+            if matches!(event, crate::events::Event::Warning { ref context, .. } if context == "watchdog") {
+                warnings += 1;
+            }
+        }
+        assert_eq!(warnings, 2);
+    }
+
+    #[tokio::test]
+    async fn test_overrides_apply_at_iteration_boundary() {
+        let temp = tempdir().unwrap();
+        tokio::process::Command::new("git")
+            .args(["init"])
+            .current_dir(temp.path())
+            .output()
+            .await
+            .unwrap();
+        let state_dir = tempdir().unwrap();
+        let state = StateManager::spawn(state_dir.path()).unwrap();
+        state
+            .create_execution(crate::domain::LoopExecution::with_id("test-exec", "ralph"))
+            .await
+            .unwrap();
+        // `td exec set test-exec max_iterations=2` before the first iteration
+        state
+            .set_overrides("test-exec", &[crate::domain::RunOverride::MaxIterations(Some(2))])
+            .await
+            .unwrap();
+
+        let config = LoopConfig {
+            validation_command: "exit 1".to_string(),
+            max_iterations: 5,
+            ..Default::default()
+        };
+        let llm = Arc::new(MockLlmClient::new(vec![
+            make_mock_response("first"),
+            make_mock_response("second"),
+            make_mock_response("third"),
+        ]));
+        let bus = crate::events::EventBus::with_default_capacity();
+        let mut rx = bus.subscribe();
+        let mut engine = LoopEngine::new("test-exec".to_string(), config, llm, temp.path().to_path_buf())
+            .with_state(state.clone())
+            .with_event_emitter(bus.emitter_for("test-exec"));
+
+        match engine.run().await.unwrap() {
+            IterationResult::Error { message, .. } => assert_eq!(message, "Max iterations (2) exceeded"),
+            other => panic!("expected Error, got {:?}", other),
+        }
+        assert_eq!(engine.current_iteration(), 2);
+
+        let changes: Vec<_> = std::iter::from_fn(|| rx.try_recv().ok())
+            .filter_map(|event| match event {
+                crate::events::Event::OverrideChanged {
+                    iteration,
+                    setting,
+                    from,
+                    to,
+                    ..
+                } => Some((iteration, setting, from, to)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            vec![(1, "max_iterations".to_string(), "5".to_string(), "2".to_string())]
+        );
+
+        state.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_acceptance_criteria_gate_completion() {
+        let temp = tempdir().unwrap();
+        tokio::process::Command::new("git")
+            .args(["init"])
+            .current_dir(temp.path())
+            .output()
+            .await
+            .unwrap();
+        // Validation always passes; the criterion only holds from the second run on
+        let config = LoopConfig {
+            validation_command: "test -f marker && touch done.txt; touch marker".to_string(),
+            max_iterations: 3,
+            ..Default::default()
+        };
+        let llm = Arc::new(MockLlmClient::new(vec![
+            make_mock_response("first attempt"),
+            make_mock_response("second attempt"),
+        ]));
+        let criterion = crate::domain::AcceptanceCriterion::FileExists {
+            path: "done.txt".to_string(),
+        };
+        let mut engine = LoopEngine::new("test-exec".to_string(), config, llm, temp.path().to_path_buf())
+            .with_acceptance(vec![AcceptanceCheck::pending(criterion)]);
+
+        match engine.run().await.unwrap() {
+            IterationResult::Complete { iterations } => assert_eq!(iterations, 2),
+            other => panic!("expected Complete, got {:?}", other),
+        }
+        assert_eq!(engine.acceptance[0].status, CriterionStatus::Passed);
+        assert_eq!(engine.acceptance[0].iteration, 2);
+    }
+
+    #[tokio::test]
+    async fn test_review_pipeline_gates_completion() {
+        let temp = tempdir().unwrap();
+        let pass = |name: &str, command: &str| crate::validation::ReviewPassConfig {
+            name: name.to_string(),
+            description: String::new(),
+            prompt: format!("Review for {} in {{{{working-directory}}}}", name),
+            converge: crate::validation::Convergence {
+                command: Some(command.to_string()),
+                ..Default::default()
+            },
+        };
+        // "security" only converges on its second iteration
+        let config = LoopConfig {
+            prompt_template: "{{review-pass}}/{{review-pass-count}} {{review-pass-name}}: {{review-instructions}}"
+                .to_string(),
+            validation_command: "true".to_string(),
+            max_iterations: 5,
+            review: Some(crate::validation::ReviewPipeline {
+                passes: vec![
+                    pass("draft", "true"),
+                    pass("security", "test -f reviewed || (touch reviewed; false)"),
+                ],
+                stop_after_clean: 0,
+            }),
+            ..Default::default()
+        };
+        let llm = Arc::new(MockLlmClient::new(vec![
+            make_mock_response("draft"),
+            make_mock_response("security, first look"),
+            make_mock_response("security, second look"),
+        ]));
+        let mut engine = LoopEngine::new("test-exec".to_string(), config, llm, temp.path().to_path_buf());
+
+        let context = engine.build_template_context().await.unwrap();
+        let prompt = engine.render_prompt(&context).unwrap();
+        assert!(prompt.starts_with("1/2 draft: Review for draft in "));
+        assert!(prompt.ends_with(&temp.path().display().to_string()));
+
+        match engine.run().await.unwrap() {
+            IterationResult::Complete { iterations } => assert_eq!(iterations, 3),
+            other => panic!("expected Complete, got {:?}", other),
+        }
+        assert!(engine.review.unwrap().is_complete());
+    }
+
+    #[tokio::test]
+    async fn test_budget_phases_gate_completion() {
+        let temp = tempdir().unwrap();
+        let phase = |name: &str, iterations: u32, until: Option<&str>| crate::r#loop::PhaseBudget {
+            name: name.to_string(),
+            iterations,
+            prompt: format!("Only {}", name),
+            until: until.map(str::to_string),
+        };
+        // "explore" ends on its second iteration and lends its third to "implement"
+        let budget = |implement: u32| crate::r#loop::IterationBudget {
+            phases: vec![
+                phase("explore", 3, Some("test -f explored || (touch explored; false)")),
+                phase("implement", implement, None),
+            ],
+            borrow: true,
+        };
+        let config = LoopConfig {
+            prompt_template: "{{budget-phase}}/{{budget-phase-count}} {{budget-phase-name}} \
+                              ({{budget-phase-remaining}} left): {{budget-phase-instructions}}"
+                .to_string(),
+            validation_command: "true".to_string(),
+            max_iterations: 10,
+            budget: Some(budget(1)),
+            ..Default::default()
+        };
+        let llm = Arc::new(MockLlmClient::new(vec![
+            make_mock_response("explore"),
+            make_mock_response("explore more"),
+            make_mock_response("implement"),
+        ]));
+        let mut engine = LoopEngine::new("test-exec".to_string(), config.clone(), llm, temp.path().to_path_buf());
+
+        let context = engine.build_template_context().await.unwrap();
+        let prompt = engine.render_prompt(&context).unwrap();
+        assert_eq!(prompt, "1/2 explore (3 left): Only explore");
+
+        // Validation passes from the start, but only the final phase may complete
+        match engine.run().await.unwrap() {
+            IterationResult::Complete { iterations } => assert_eq!(iterations, 3),
+            other => panic!("expected Complete, got {:?}", other),
+        }
+        let progress = engine.budget.as_ref().unwrap();
+        assert_eq!(progress.current().name, "implement");
+        assert_eq!(progress.remaining(), 1);
+
+        // A final phase that never passes validation fails once its allowance is gone
+        let temp = tempdir().unwrap();
+        let config = LoopConfig {
+            validation_command: "false".to_string(),
+            budget: Some(budget(1)),
+            ..config
+        };
+        let llm = Arc::new(MockLlmClient::new(
+            (0..4).map(|i| make_mock_response(&format!("attempt {}", i))).collect(),
+        ));
+        let mut engine = LoopEngine::new("test-exec".to_string(), config, llm, temp.path().to_path_buf());
+        match engine.run().await.unwrap() {
+            IterationResult::Error { message, .. } => {
+                assert_eq!(message, "Phase 'implement' iteration budget exhausted")
+            }
+            other => panic!("expected Error, got {:?}", other),
+        }
+        assert_eq!(engine.iteration, 4);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_iteration_rolls_back_regression() {
+        let temp = tempdir().unwrap();
+        for args in [
+            &["init"][..],
+            &[
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@test.com",
+                "commit",
+                "--allow-empty",
+                "-m",
+                "initial",
+            ][..],
+        ] {
+            tokio::process::Command::new("git")
+                .args(args)
+                .current_dir(temp.path())
+                .output()
+                .await
+                .unwrap();
+        }
+        let llm = Arc::new(MockLlmClient::new(vec![]));
+        let mut config = LoopConfig::default();
+        config.snapshots.rollback = crate::r#loop::RollbackPolicy::OnRegression;
+        let mut engine = LoopEngine::new("test-exec".to_string(), config, llm, temp.path().to_path_buf());
+        let file = temp.path().join("lib.rs");
+
+        engine.iteration = 1;
+        std::fs::write(&file, "fn close() {}").unwrap();
+        engine.snapshot_iteration(ValidationScore::Failing(1)).await;
+        assert!(engine.steering.is_none());
+
+        engine.iteration = 2;
+        std::fs::write(&file, "fn broken( {}").unwrap();
+        engine.snapshot_iteration(ValidationScore::Broken).await;
+
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "fn close() {}");
+        assert!(
+            engine
+                .steering
+                .as_deref()
+                .unwrap()
+                .contains("reset to the state after iteration 1")
+        );
     }
 }
@@ -0,0 +1,203 @@
+//! Self-evaluation pass (LLM-as-judge)
+//!
+//! After a code-producing loop passes validation, the Evaluator asks a
+//! separate model to score the diff against the acceptance criteria from the
+//! loop's parent document. The resulting rubric is attached to the execution
+//! record; executions below the configured threshold are held for human review
+//! instead of being merged.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use eyre::{Context, Result};
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::domain::{Evaluation, RubricScore};
+use crate::llm::{CompletionRequest, LlmClient, Message};
+
+const SYSTEM_PROMPT: &str = "You are a strict code reviewer acting as a judge. \
+     Score how well a code change satisfies each acceptance criterion. \
+     Use a 0-10 scale per criterion: 0 = not addressed, 5 = partially met, 10 = fully met with tests. \
+     Respond with ONLY a JSON object of the form \
+     {\"scores\": [{\"criterion\": \"...\", \"score\": 0, \"rationale\": \"...\"}], \"summary\": \"...\"}. \
+     If no explicit criteria are given, derive 3-5 criteria from the task description.";
+
+/// Maximum tokens for the judge's response
+const MAX_RESPONSE_TOKENS: u32 = 2048;
+
+/// Scores completed work against acceptance criteria using an LLM
+pub struct Evaluator {
+    llm: Arc<dyn LlmClient>,
+    model: String,
+    threshold: f64,
+    max_diff_chars: usize,
+}
+
+/// Judge response as requested in the system prompt
+#[derive(Debug, Deserialize)]
+struct JudgeResponse {
+    scores: Vec<RubricScore>,
+    #[serde(default)]
+    summary: String,
+}
+
+impl Evaluator {
+    /// Create an evaluator backed by the given judge client
+    pub fn new(llm: Arc<dyn LlmClient>, model: impl Into<String>, threshold: f64, max_diff_chars: usize) -> Self {
+        let model = model.into();
+        debug!(%model, threshold, max_diff_chars, "Evaluator::new: called");
+        Self {
+            llm,
+            model,
+            threshold,
+            max_diff_chars,
+        }
+    }
+
+    /// Score a diff against acceptance criteria
+    pub async fn evaluate(&self, criteria: &str, diff: &str) -> Result<Evaluation> {
+        debug!(
+            criteria_len = criteria.len(),
+            diff_len = diff.len(),
+            "Evaluator::evaluate: called"
+        );
+        let diff = if diff.len() > self.max_diff_chars {
+            debug!("Evaluator::evaluate: truncating diff");
+            let mut end = self.max_diff_chars;
+            while !diff.is_char_boundary(end) {
+                end -= 1;
+            }
+            format!("{}\n[diff truncated]", &diff[..end])
+        } else {
+            diff.to_string()
+        };
+
+        let prompt = format!(
+            "## Acceptance Criteria / Task\n{}\n\n## Diff\n```diff\n{}\n```",
+            criteria.trim(),
+            diff
+        );
+        let request = CompletionRequest {
+            system_prompt: SYSTEM_PROMPT.to_string(),
+            messages: vec![Message::user(prompt)],
+            max_tokens: MAX_RESPONSE_TOKENS,
+            tools: vec![],
+        };
+
+        let response = self.llm.complete(request).await.context("Evaluation request failed")?;
+        let content = response
+            .content
+            .ok_or_else(|| eyre::eyre!("Evaluation response had no content"))?;
+        let judged = parse_judge_response(&content)?;
+
+        let evaluation = Evaluation::new(&self.model, judged.scores, self.threshold, judged.summary);
+        debug!(
+            overall = evaluation.overall,
+            passed = evaluation.passed(),
+            "Evaluator::evaluate: complete"
+        );
+        Ok(evaluation)
+    }
+
+    /// Score the changes on the worktree's branch since it forked from `base`
+    ///
+    /// Includes uncommitted changes, which are auto-committed at merge time.
+    pub async fn evaluate_worktree(&self, criteria: &str, worktree: &Path, base: &str) -> Result<Evaluation> {
+        debug!(?worktree, %base, "Evaluator::evaluate_worktree: called");
+        let merge_base = tokio::process::Command::new("git")
+            .args(["merge-base", base, "HEAD"])
+            .current_dir(worktree)
+            .output()
+            .await
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+            .unwrap_or_else(|| base.to_string());
+        debug!(%merge_base, "Evaluator::evaluate_worktree: resolved merge base");
+
+        let output = tokio::process::Command::new("git")
+            .args(["diff", &merge_base])
+            .current_dir(worktree)
+            .output()
+            .await
+            .context("Failed to run git diff")?;
+        if !output.status.success() {
+            debug!("Evaluator::evaluate_worktree: git diff failed");
+            return Err(eyre::eyre!(
+                "git diff failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+
+        let diff = String::from_utf8_lossy(&output.stdout);
+        self.evaluate(criteria, &diff).await
+    }
+}
+
+/// Parse the judge's JSON, tolerating surrounding prose or code fences
+fn parse_judge_response(content: &str) -> Result<JudgeResponse> {
+    debug!(content_len = content.len(), "parse_judge_response: called");
+    let start = content.find('{');
+    let end = content.rfind('}');
+    let json = match (start, end) {
+        (Some(start), Some(end)) if end > start => &content[start..=end],
+        _ => {
+            debug!("parse_judge_response: no JSON object found");
+            return Err(eyre::eyre!("Evaluation response did not contain a JSON object"));
+        }
+    };
+
+    let mut parsed: JudgeResponse = serde_json::from_str(json).context("Failed to parse evaluation JSON")?;
+    for score in &mut parsed.scores {
+        score.score = score.score.min(10);
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::client::mock::MockLlmClient;
+    use crate::llm::{CompletionResponse, StopReason, TokenUsage};
+
+    fn response(content: &str) -> CompletionResponse {
+        CompletionResponse {
+            content: Some(content.to_string()),
+            tool_calls: vec![],
+            stop_reason: StopReason::EndTurn,
+            usage: TokenUsage::default(),
+            served_by: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_judge_response_with_fences() {
+        let content = "Here you go:\n```json\n{\"scores\": [{\"criterion\": \"adds tests\", \"score\": 12, \
+                       \"rationale\": \"yes\"}], \"summary\": \"good\"}\n```";
+        let parsed = parse_judge_response(content).unwrap();
+
+        assert_eq!(parsed.scores.len(), 1);
+        assert_eq!(parsed.scores[0].score, 10);
+        assert_eq!(parsed.summary, "good");
+    }
+
+    #[test]
+    fn test_parse_judge_response_invalid() {
+        assert!(parse_judge_response("I think it looks fine").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_below_threshold() {
+        let llm = Arc::new(MockLlmClient::new(vec![response(
+            r#"{"scores": [{"criterion": "a", "score": 9}, {"criterion": "b", "score": 3}], "summary": "b missing"}"#,
+        )]));
+        let evaluator = Evaluator::new(llm, "openai/gpt-4o-mini", 7.0, 1000);
+
+        let evaluation = evaluator.evaluate("- a\n- b", "+fn a() {}").await.unwrap();
+
+        assert_eq!(evaluation.overall, 6.0);
+        assert!(!evaluation.passed());
+        assert_eq!(evaluation.model, "openai/gpt-4o-mini");
+    }
+}
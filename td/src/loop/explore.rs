@@ -8,19 +8,32 @@
 //! - Merge to git branches
 //!
 //! It simply runs a multi-turn conversation until it has an answer.
+//!
+//! `td explore` runs one directly against the current directory, streams its
+//! findings, and saves the answer as a Markdown artifact under
+//! [`EXPLORE_DIR`].
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use eyre::Result;
 use tracing::{debug, info, warn};
 
+use crate::error::ErrorCode;
+use crate::events::EventEmitter;
 use crate::llm::{
-    CompletionRequest, CompletionResponse, ContentBlock, LlmClient, Message, StopReason, ToolCall, ToolDefinition,
+    CompletionRequest, CompletionResponse, ContentBlock, LlmClient, Message, StopReason, TokenUsage, ToolCall,
+    ToolDefinition,
 };
 use crate::tools::{ExploreConfig, Thoroughness, ToolContext, ToolExecutor, ToolProfile, ToolResult};
 
+/// Directory (relative to the repo root) where `td explore` saves its answers
+pub const EXPLORE_DIR: &str = ".taskdaemon/explore";
+
+/// Characters of tool arguments/results carried in events
+const EVENT_SUMMARY_CHARS: usize = 200;
+
 /// Lightweight exploration agent - NOT a Ralph loop
 pub struct ExploreTask {
     /// Unique identifier for this exploration
@@ -37,6 +50,12 @@ pub struct ExploreTask {
 
     /// Working directory for file operations
     worktree: PathBuf,
+
+    /// Event emitter for streaming progress (None = silent)
+    event_emitter: Option<EventEmitter>,
+
+    /// Tokens used across all turns
+    usage: TokenUsage,
 }
 
 impl ExploreTask {
@@ -55,9 +74,27 @@ impl ExploreTask {
             llm,
             tools,
             worktree,
+            event_emitter: None,
+            usage: TokenUsage::default(),
         }
     }
 
+    /// Stream turns, interim findings and tool calls as events
+    ///
+    /// Each LLM turn is reported as an iteration; `ResponseCompleted` carries
+    /// the full response text rather than a truncated summary, since for an
+    /// exploration the interim text is the finding.
+    pub fn with_event_emitter(mut self, emitter: EventEmitter) -> Self {
+        debug!(%self.id, "ExploreTask::with_event_emitter: called");
+        self.event_emitter = Some(emitter);
+        self
+    }
+
+    /// Tokens used so far
+    pub fn usage(&self) -> &TokenUsage {
+        &self.usage
+    }
+
     /// Run exploration and return summary string
     pub async fn run(&mut self) -> Result<String> {
         debug!(%self.id, "ExploreTask::run: starting exploration");
@@ -95,13 +132,30 @@ impl ExploreTask {
                 tools: tool_defs.clone(),
             };
 
+            if let Some(ref emitter) = self.event_emitter {
+                emitter.iteration_started(iterations);
+            }
             let response = match self.llm.complete(request).await {
                 Ok(r) => r,
                 Err(e) => {
                     warn!(%self.id, error = %e, "ExploreTask: LLM call failed");
+                    if let Some(ref emitter) = self.event_emitter {
+                        emitter.error("explore", &e.to_string(), Some(e.code()));
+                    }
                     return Err(e.into());
                 }
             };
+            self.record_usage(&response.usage);
+            if let Some(ref emitter) = self.event_emitter {
+                emitter.response_completed(
+                    iterations,
+                    response.content.as_deref().unwrap_or_default(),
+                    response.usage.input_tokens,
+                    response.usage.output_tokens,
+                    !response.tool_calls.is_empty(),
+                    response.served_by.as_deref(),
+                );
+            }
 
             debug!(
                 %self.id,
@@ -121,7 +175,7 @@ impl ExploreTask {
 
             // Execute any tool calls
             if !response.tool_calls.is_empty() {
-                let results = self.execute_tools(&response.tool_calls, &ctx).await;
+                let results = self.execute_tools(iterations, &response.tool_calls, &ctx).await;
                 messages.push(self.format_tool_results(&results));
             }
         }
@@ -186,13 +240,34 @@ impl ExploreTask {
         Message::assistant_blocks(blocks)
     }
 
+    /// Add a response's token usage to the running total
+    fn record_usage(&mut self, usage: &TokenUsage) {
+        self.usage.input_tokens += usage.input_tokens;
+        self.usage.output_tokens += usage.output_tokens;
+        self.usage.cache_read_tokens += usage.cache_read_tokens;
+        self.usage.cache_creation_tokens += usage.cache_creation_tokens;
+    }
+
     /// Execute tool calls and return results
-    async fn execute_tools(&self, tool_calls: &[ToolCall], ctx: &ToolContext) -> Vec<(String, ToolResult)> {
+    async fn execute_tools(&self, turn: u32, tool_calls: &[ToolCall], ctx: &ToolContext) -> Vec<(String, ToolResult)> {
         let mut results = Vec::new();
 
         for call in tool_calls {
             debug!(%self.id, tool = %call.name, "ExploreTask: executing tool");
+            if let Some(ref emitter) = self.event_emitter {
+                emitter.tool_call_started(turn, &call.name, &clip(&call.input.to_string(), EVENT_SUMMARY_CHARS));
+            }
+            let start = Instant::now();
             let result = self.tools.execute(call, ctx).await;
+            if let Some(ref emitter) = self.event_emitter {
+                emitter.tool_call_completed(
+                    turn,
+                    &call.name,
+                    !result.is_error,
+                    &clip(&result.content, EVENT_SUMMARY_CHARS),
+                    start.elapsed().as_millis() as u64,
+                );
+            }
             results.push((call.id.clone(), result));
         }
 
@@ -201,10 +276,12 @@ impl ExploreTask {
 
     /// Format tool results as a user message
     fn format_tool_results(&self, results: &[(String, ToolResult)]) -> Message {
-        let blocks: Vec<ContentBlock> = results
+        let mut blocks: Vec<ContentBlock> = results
             .iter()
             .map(|(id, result)| ContentBlock::tool_result(id, &result.content, result.is_error))
             .collect();
+        // Images must follow every tool result in the message
+        blocks.extend(results.iter().flat_map(|(_, result)| result.images.iter().cloned()));
 
         Message::user_blocks(blocks)
     }
@@ -227,7 +304,7 @@ impl ExploreTask {
     }
 
     /// Force a summary when iteration/timeout limit reached
-    async fn force_summary(&self, messages: &[Message]) -> Result<String> {
+    async fn force_summary(&mut self, messages: &[Message]) -> Result<String> {
         debug!(%self.id, "ExploreTask::force_summary: requesting forced summary");
 
         // Build a message asking for summary of what we've found so far
@@ -247,7 +324,10 @@ impl ExploreTask {
         };
 
         match self.llm.complete(request).await {
-            Ok(response) => Ok(self.extract_summary(&response)),
+            Ok(response) => {
+                self.record_usage(&response.usage);
+                Ok(self.extract_summary(&response))
+            }
             Err(e) => {
                 // If summary fails, extract what we can from the last messages
                 warn!(%self.id, error = %e, "ExploreTask: force_summary LLM call failed");
@@ -257,6 +337,44 @@ impl ExploreTask {
     }
 }
 
+/// Shorten text to at most `max_chars` characters, adding "..." if cut
+fn clip(s: &str, max_chars: usize) -> String {
+    if s.chars().count() <= max_chars {
+        s.to_string()
+    } else {
+        let cut: String = s.chars().take(max_chars.saturating_sub(3)).collect();
+        format!("{}...", cut)
+    }
+}
+
+/// Where `td explore` saves the answer for an exploration
+pub fn explore_artifact_path(repo_root: &Path, id: &str) -> PathBuf {
+    repo_root.join(EXPLORE_DIR).join(format!("{}.md", id))
+}
+
+/// Render an exploration's answer as a standalone Markdown document
+pub fn render_explore_markdown(
+    id: &str,
+    question: &str,
+    thoroughness: Thoroughness,
+    answer: &str,
+    duration: Duration,
+    usage: &TokenUsage,
+) -> String {
+    debug!(%id, "render_explore_markdown: called");
+    format!(
+        "# {}\n\n         | | |\n         |---|---|\n         | Exploration | `{}` |\n         | Thoroughness | {} |\n         | Date | {} |\n         | Duration | {}s |\n         | Tokens | {} in / {} out |\n\n         ## Findings\n\n{}\n",
+        question.lines().next().unwrap_or_default().trim(),
+        id,
+        thoroughness,
+        chrono::Utc::now().format("%Y-%m-%d %H:%M UTC"),
+        duration.as_secs(),
+        usage.input_tokens,
+        usage.output_tokens,
+        answer.trim()
+    )
+}
+
 /// Generate a unique ID for an explore task
 pub fn generate_explore_id(parent_id: Option<&str>) -> String {
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -289,6 +407,8 @@ mod tests {
         assert_eq!("medium".parse::<Thoroughness>(), Ok(Thoroughness::Medium));
         assert_eq!("thorough".parse::<Thoroughness>(), Ok(Thoroughness::Thorough));
         assert_eq!("MEDIUM".parse::<Thoroughness>(), Ok(Thoroughness::Medium));
+        assert_eq!("low".parse::<Thoroughness>(), Ok(Thoroughness::Quick));
+        assert_eq!("high".parse::<Thoroughness>(), Ok(Thoroughness::Thorough));
         assert!("invalid".parse::<Thoroughness>().is_err());
     }
 
@@ -302,6 +422,31 @@ mod tests {
         assert!(id2.contains("loop-abc-123"));
     }
 
+    #[test]
+    fn test_render_explore_markdown() {
+        let usage = TokenUsage {
+            input_tokens: 1200,
+            output_tokens: 300,
+            ..Default::default()
+        };
+        let md = render_explore_markdown(
+            "explore-42",
+            "How does auth middleware work?",
+            Thoroughness::Thorough,
+            "- Middleware lives in src/auth.rs\n",
+            Duration::from_secs(42),
+            &usage,
+        );
+        assert!(md.starts_with("# How does auth middleware work?\n"));
+        assert!(md.contains("| Thoroughness | thorough |"));
+        assert!(md.contains("| Tokens | 1200 in / 300 out |"));
+        assert!(md.ends_with("## Findings\n\n- Middleware lives in src/auth.rs\n"));
+        assert_eq!(
+            explore_artifact_path(Path::new("/repo"), "explore-42"),
+            PathBuf::from("/repo/.taskdaemon/explore/explore-42.md")
+        );
+    }
+
     #[test]
     fn test_explore_config_default() {
         let config = ExploreConfig::default();
@@ -326,6 +471,7 @@ mod tests {
             tool_calls: vec![],
             stop_reason: StopReason::EndTurn,
             usage: TokenUsage::default(),
+            served_by: None,
         };
 
         // Test that summary extraction would work
@@ -14,20 +14,40 @@ use std::time::Duration;
 
 use eyre::{Context, Result};
 use tokio::net::UnixListener;
-use tokio::sync::{Semaphore, mpsc};
+use tokio::sync::{Semaphore, mpsc, oneshot};
 use tokio::task::JoinHandle;
 use tracing::{debug, error, info, warn};
 
-use crate::coordinator::{CoordRequest, CoordinatorHandle};
-use crate::daemon::VERSION;
-use crate::domain::{Loop, LoopExecution, LoopExecutionStatus, LoopStatus};
-use crate::events::{Event as LoopEvent, EventBus, spawn_event_logger};
-use crate::ipc::{DaemonMessage, DaemonResponse, read_message, send_response};
-use crate::llm::LlmClient;
-use crate::r#loop::{CascadeHandler, LoopConfig, LoopEngine, LoopLoader};
+use crate::clock::{ClockRef, IdGenRef, RandomIdGen, SystemClock};
+use crate::config::{EventLogConfig, LlmConfig, ResourceMonitorConfig, ReviewConfig, RiskConfig, ToolWorkerConfig};
+use crate::coordinator::{CoordRequest, CoordinatorHandle, normalize_lock_path};
+use crate::daemon::{MaintenanceState, VERSION};
+use crate::deps::{DEP_BUMP_TYPE, DEPS_UPGRADE_TYPE, load_outdated};
+use crate::domain::{Loop, LoopExecution, LoopExecutionStatus, LoopStatus, MetricsSnapshot};
+use crate::error::{ErrorCode, code_of};
+use crate::hooks::{HookEvent, Hooks};
+use crate::events::{
+    DEFAULT_CHANNEL_CAPACITY, Event as LoopEvent, EventBus, EventLogger, OverflowPolicy, spawn_event_logger,
+};
+use crate::ipc::{DaemonMessage, DaemonResponse, WriterGate, read_message, send_response, stream_events};
+use crate::llm::{CompletionRequest, LlmClient, Message, create_client};
+use crate::r#loop::{
+    CascadeHandler, Evaluator, LoopConfig, LoopEngine, LoopLoader, LoopMetrics, PathLockMode, StuckAction,
+    assess_worktree_risk, first_met,
+};
+use crate::resources::{DAEMON_EVENT_ID, PressureChange, ResourceMonitor};
+use crate::policy::{PolicyInput, PolicyOutcome, PolicySet};
+use crate::review::{REVIEW_TYPE, Review, latest_review, post_to_github, save_artifact};
 use crate::scheduler::Scheduler;
-use crate::state::{StateEvent, StateManager};
-use crate::worktree::{MergeResult, WorktreeConfig, WorktreeManager, merge_to_main};
+use crate::state::{StateEvent, StateManager, StateWatcher};
+use crate::tools::Tool;
+use crate::worktree::{
+    MergeResult, PrunePolicy, WorktreeConfig, WorktreeManager, WorktreeState, classify, format_size, merge_to_main,
+    plan_prune,
+};
+
+/// How often worktree retention and the disk quota are enforced
+const WORKTREE_PRUNE_INTERVAL: Duration = Duration::from_secs(600);
 
 /// Configuration for the TaskManager
 #[derive(Debug, Clone)]
@@ -38,6 +58,9 @@ pub struct TaskManagerConfig {
     /// Polling interval for ready tasks (in seconds)
     pub poll_interval_secs: u64,
 
+    /// Interval for evaluating parked executions' wake conditions (in seconds)
+    pub wake_check_interval_secs: u64,
+
     /// Shutdown timeout (in seconds)
     pub shutdown_timeout_secs: u64,
 
@@ -46,6 +69,24 @@ pub struct TaskManagerConfig {
 
     /// Worktree base directory
     pub worktree_dir: PathBuf,
+
+    /// Event log rotation and compaction
+    pub event_log: EventLogConfig,
+
+    /// Retention and disk quota for worktrees of finished executions
+    pub worktree_prune: PrunePolicy,
+
+    /// Shared CARGO_TARGET_DIR for commands run in worktrees
+    pub cargo_target_dir: Option<PathBuf>,
+
+    /// Directory for execution event logs (None for ~/.taskdaemon/runs)
+    pub runs_dir: Option<PathBuf>,
+
+    /// Worker processes for tool calls (`tools.worker`)
+    pub tool_worker: ToolWorkerConfig,
+
+    /// Global hooks, run before each loop type's own
+    pub hooks: Hooks,
 }
 
 impl Default for TaskManagerConfig {
@@ -55,9 +96,16 @@ impl Default for TaskManagerConfig {
             // Increased from 10s to 60s since event-driven pickup handles immediate work.
             // Polling is now a fallback for edge cases, orphan recovery, and missed events.
             poll_interval_secs: 60,
+            wake_check_interval_secs: 30,
             shutdown_timeout_secs: 60,
             repo_root: PathBuf::from("."),
             worktree_dir: PathBuf::from("/tmp/taskdaemon/worktrees"),
+            event_log: EventLogConfig::default(),
+            worktree_prune: PrunePolicy::default(),
+            cargo_target_dir: None,
+            runs_dir: None,
+            tool_worker: ToolWorkerConfig::default(),
+            hooks: Hooks::default(),
         }
     }
 }
@@ -74,6 +122,8 @@ pub enum TaskResult {
     Failed { exec_id: String, reason: String },
     /// Task was stopped
     Stopped { exec_id: String },
+    /// Task was paused or blocked for human review (stuck-loop intervention)
+    Paused { exec_id: String, reason: String },
 }
 
 // Type alias for backward compatibility
@@ -87,6 +137,9 @@ pub struct TaskManager {
     /// Running tasks by exec_id
     tasks: HashMap<String, JoinHandle<TaskResult>>,
 
+    /// Loop type of each running task, for per-type concurrency limits
+    task_types: HashMap<String, String>,
+
     /// Concurrency limiter
     semaphore: Arc<Semaphore>,
 
@@ -119,11 +172,75 @@ pub struct TaskManager {
 
     /// Event bridge task handle (forwards events to StateManager)
     event_bridge_handle: Option<JoinHandle<()>>,
+
+    /// Per-loop metrics (iterations, test outcome transitions)
+    metrics: Arc<LoopMetrics>,
+
+    /// Self-evaluation judge run before merging (optional)
+    evaluator: Option<Arc<Evaluator>>,
+
+    /// Change risk policy scored before merging (optional)
+    risk: Option<Arc<RiskConfig>>,
+
+    /// Publishing of completed reviews and the blocking-comment merge gate (optional)
+    review: Option<Arc<ReviewConfig>>,
+
+    /// When finished worktrees were last pruned
+    last_worktree_prune: Option<tokio::time::Instant>,
+
+    /// Model used to estimate the cost in persisted metrics snapshots
+    model: String,
+
+    /// LLM config for building clients for executions that override the model
+    llm_config: Option<LlmConfig>,
+
+    /// Time source handed to engines (and used for worktree pruning)
+    clock: ClockRef,
+
+    /// ID source for cascade records and child executions
+    id_gen: IdGenRef,
+
+    /// Maintenance mode (no pickups) and the executions it paused
+    maintenance: Option<MaintenanceState>,
+
+    /// Where maintenance mode is persisted (None keeps it in memory only)
+    maintenance_file: Option<PathBuf>,
+
+    /// Executions paused because the provider rejected the API key (Some = no pickups until
+    /// the key is reloaded)
+    credential_breaker: Option<Vec<String>>,
+
+    /// Whether the provider circuit breaker was announced open and executions wait on it
+    provider_outage: bool,
+
+    /// Single-writer token for IPC write requests
+    writer: WriterGate,
+
+    /// Self-monitoring; pickups stop while it reports pressure (None = disabled)
+    resource_monitor: Option<ResourceMonitor>,
+
+    /// Tools added to every engine on top of the standard set
+    extra_tools: Vec<Arc<dyn Tool>>,
+
+    /// Policies consulted before starts, tool calls and merges
+    policies: PolicySet,
 }
 
 // Type alias for backward compatibility
 pub type LoopManager = TaskManager;
 
+/// Stop reason sent to executions paused by maintenance mode
+const MAINTENANCE_STOP_REASON: &str = "Maintenance mode";
+
+/// Error code of a request the provider refused for the API key
+const AUTH_ERROR_CODE: &str = "llm.auth";
+
+/// Pause reason of executions paused by the credential breaker
+const CREDENTIALS_PAUSE_REASON: &str = "API key rejected";
+
+/// Pause reason of executions waiting on the provider circuit breaker
+const PROVIDER_WAIT_REASON: &str = "Waiting for the provider to recover";
+
 impl TaskManager {
     /// Create a new TaskManager
     ///
@@ -161,6 +278,7 @@ impl TaskManager {
             semaphore: Arc::new(Semaphore::new(config.max_concurrent_tasks)),
             config,
             tasks: HashMap::new(),
+            task_types: HashMap::new(),
             coordinator_tx,
             scheduler: Arc::new(scheduler),
             llm,
@@ -171,9 +289,112 @@ impl TaskManager {
             shutdown_requested: false,
             event_bus,
             event_bridge_handle: None,
+            metrics: Arc::new(LoopMetrics::new()),
+            evaluator: None,
+            risk: None,
+            review: None,
+            last_worktree_prune: None,
+            model: String::new(),
+            llm_config: None,
+            clock: SystemClock::shared(),
+            id_gen: RandomIdGen::shared(),
+            maintenance: None,
+            maintenance_file: None,
+            credential_breaker: None,
+            provider_outage: false,
+            writer: WriterGate::default(),
+            resource_monitor: None,
+            extra_tools: Vec::new(),
+            policies: PolicySet::default(),
         }
     }
 
+    /// Enable the self-evaluation pass before merging completed loops
+    pub fn with_evaluator(mut self, evaluator: Evaluator) -> Self {
+        debug!("TaskManager::with_evaluator: called");
+        self.evaluator = Some(Arc::new(evaluator));
+        self
+    }
+
+    /// Score completed loops' diffs before merging, holding risky ones for approval
+    pub fn with_risk_policy(mut self, risk: RiskConfig) -> Self {
+        debug!(approve_at = risk.approve_at, "TaskManager::with_risk_policy: called");
+        self.risk = Some(Arc::new(risk));
+        self
+    }
+
+    /// Post completed reviews to GitHub and hold merges on blocking review comments, as configured
+    pub fn with_review_policy(mut self, review: ReviewConfig) -> Self {
+        debug!(gate_merges = review.gate_merges, "TaskManager::with_review_policy: called");
+        self.review = Some(Arc::new(review));
+        self
+    }
+
+    /// Set the model that persisted metrics snapshots estimate cost for
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        debug!(model = %self.model, "TaskManager::with_model: called");
+        self
+    }
+
+    /// Set the LLM config used to build a client for executions that override the model
+    pub fn with_llm_config(mut self, config: LlmConfig) -> Self {
+        debug!(default = %config.default, "TaskManager::with_llm_config: called");
+        self.llm_config = Some(config);
+        self
+    }
+
+    /// Set the clock handed to engines (the scheduler takes its own via `Scheduler::with_clock`)
+    pub fn with_clock(mut self, clock: ClockRef) -> Self {
+        debug!("TaskManager::with_clock: called");
+        self.clock = clock;
+        self
+    }
+
+    /// Set the ID generator for cascade records and child executions
+    pub fn with_id_gen(mut self, id_gen: IdGenRef) -> Self {
+        debug!("TaskManager::with_id_gen: called");
+        self.id_gen = id_gen;
+        self
+    }
+
+    /// Persist maintenance mode in `path` (read on startup, written on every change)
+    pub fn with_maintenance_file(mut self, path: PathBuf) -> Self {
+        debug!(?path, "TaskManager::with_maintenance_file: called");
+        self.maintenance_file = Some(path);
+        self
+    }
+
+    /// Sample the daemon's resources and stop pickups while a limit is exceeded
+    pub fn with_resource_monitor(mut self, config: ResourceMonitorConfig) -> Self {
+        debug!(?config, "TaskManager::with_resource_monitor: called");
+        self.resource_monitor = (config.interval_secs > 0).then(|| ResourceMonitor::new(config));
+        self
+    }
+
+    /// Use a shared event bus (e.g. one the Coordinator also emits on) instead of a private one
+    pub fn with_event_bus(mut self, event_bus: Arc<EventBus>) -> Self {
+        debug!("TaskManager::with_event_bus: called");
+        self.event_bus = event_bus;
+        self
+    }
+
+    /// Offer `tools` to every loop in addition to the standard set
+    ///
+    /// A loop type sees a tool only if it lists its name under `tools`.
+    pub fn with_tools(mut self, tools: Vec<Arc<dyn Tool>>) -> Self {
+        debug!(count = tools.len(), "TaskManager::with_tools: called");
+        self.extra_tools = tools;
+        self
+    }
+
+    /// Consult `policies` before executions start, tool calls run and branches merge
+    pub fn with_policies(mut self, policies: PolicySet) -> Self {
+        debug!(policies = ?policies.names(), "TaskManager::with_policies: called");
+        self.policies = policies;
+        self
+    }
+
     /// Create a CoordinatorHandle for a new execution by registering with the Coordinator
     ///
     /// This sends a Register message to the Coordinator and creates a handle with
@@ -198,33 +419,51 @@ impl TaskManager {
         ))
     }
 
+    /// Unregister a finished task from the Coordinator, releasing its path locks
+    async fn release_coord_registration(&self, exec_id: &str) {
+        debug!(%exec_id, "release_coord_registration: called");
+        let _ = self
+            .coordinator_tx
+            .send(CoordRequest::Unregister {
+                exec_id: exec_id.to_string(),
+            })
+            .await;
+
+        if let Ok(Some(mut exec)) = self.state.get_execution(exec_id).await
+            && !exec.locked_paths.is_empty()
+        {
+            exec.locked_paths.clear();
+            if let Err(e) = self.state.update_execution(exec).await {
+                warn!(%exec_id, error = %e, "Failed to clear path locks");
+            }
+        }
+    }
+
     /// Start the event bridge that forwards EventBus events to StateManager's broadcast
     ///
     /// This allows the TUI to receive live streaming events from daemon-spawned loops
     /// via the existing StateManager subscription mechanism.
     fn start_event_bridge(&self) -> JoinHandle<()> {
-        let mut event_rx = self.event_bus.subscribe();
+        // Under load, give up streamed tokens before lifecycle events
+        let mut event_rx = self
+            .event_bus
+            .subscribe_buffered(DEFAULT_CHANNEL_CAPACITY, OverflowPolicy::DropTokenEventsFirst);
         let state_event_tx = self.state.event_sender();
 
         tokio::spawn(async move {
             debug!("event_bridge: started");
-            loop {
-                match event_rx.recv().await {
-                    Ok(event) => {
-                        // Convert EventBus event to StateEvent and forward
-                        if let Some(state_event) = convert_to_state_event(&event) {
-                            let _ = state_event_tx.send(state_event);
-                        }
-                    }
-                    Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
-                        debug!(n, "event_bridge: lagged, missed events");
-                    }
-                    Err(tokio::sync::broadcast::error::RecvError::Closed) => {
-                        debug!("event_bridge: channel closed, exiting");
-                        break;
-                    }
+            while let Some(event) = event_rx.recv().await {
+                // Convert EventBus event to StateEvent and forward
+                if let Some(state_event) = convert_to_state_event(&event) {
+                    let _ = state_event_tx.send(state_event);
                 }
             }
+            let stats = event_rx.stats();
+            debug!(
+                delivered = stats.delivered,
+                dropped = stats.dropped,
+                "event_bridge: channel closed, exiting"
+            );
         })
     }
 
@@ -232,7 +471,8 @@ impl TaskManager {
     ///
     /// This polls for ready loops and spawns them, handling shutdown gracefully.
     /// Also subscribes to state events for immediate work pickup when executions
-    /// become pending (instead of waiting for the next poll interval).
+    /// become pending (instead of waiting for the next poll interval), and
+    /// watches the store for executions other processes make pending.
     ///
     /// If an IPC listener is provided, it will also handle cross-process messages
     /// from the TUI/CLI for immediate work pickup.
@@ -243,14 +483,34 @@ impl TaskManager {
         // Subscribe to state events for immediate work pickup
         let mut state_events = self.state.subscribe_events();
 
+        // Watch the store for executions other processes make pending
+        let state_watcher = StateWatcher::spawn(self.state.store_path());
+
         // Start event bridge task - forwards EventBus events to StateManager's broadcast
         // so TUI can receive live streaming events
         self.event_bridge_handle = Some(self.start_event_bridge());
 
         // Start event logger - writes events to ~/.taskdaemon/runs/{exec-id}/events.jsonl
         // This allows TUI to read live output from disk (cross-process)
-        let _event_logger_handle =
-            spawn_event_logger(self.event_bus.clone()).context("Failed to spawn event logger")?;
+        // Also rotates large logs and compacts idle ones
+        let _event_logger_handle = match &self.config.runs_dir {
+            Some(runs_dir) => {
+                debug!(?runs_dir, "run: logging events to configured runs directory");
+                let logger = EventLogger::new(runs_dir).with_config(self.config.event_log.clone());
+                let event_bus = self.event_bus.clone();
+                tokio::spawn(async move { logger.run(event_bus).await })
+            }
+            None => spawn_event_logger(self.event_bus.clone(), self.config.event_log.clone())
+                .context("Failed to spawn event logger")?,
+        };
+
+        // Stay frozen if maintenance mode was on when the daemon stopped
+        if let Some(path) = &self.maintenance_file {
+            self.maintenance = MaintenanceState::load(path);
+            if self.maintenance.is_some() {
+                info!("LoopManager: starting in maintenance mode, not picking up executions");
+            }
+        }
 
         // Run recovery first
         debug!("run: starting recovery");
@@ -262,6 +522,11 @@ impl TaskManager {
 
         let poll_interval = Duration::from_secs(self.config.poll_interval_secs);
         let mut interval = tokio::time::interval(poll_interval);
+        let mut wake_interval = tokio::time::interval(Duration::from_secs(self.config.wake_check_interval_secs));
+        let monitoring = self.resource_monitor.is_some();
+        let monitor_secs = self.resource_monitor.as_ref().map_or(1, |m| m.config().interval_secs);
+        let mut monitor_interval = tokio::time::interval(Duration::from_secs(monitor_secs));
+        let mut breaker_interval = tokio::time::interval(self.scheduler.config().breaker_probe_interval());
 
         // Check if we have an IPC listener
         let has_ipc = ipc_listener.is_some();
@@ -286,9 +551,9 @@ impl TaskManager {
                     // Handle IPC connections for cross-process wake-up
                     accept_result = listener.accept() => {
                         match accept_result {
-                            Ok((mut stream, _addr)) => {
+                            Ok((stream, _addr)) => {
                                 debug!("run: IPC connection accepted");
-                                if let Err(e) = self.handle_ipc_connection(&mut stream).await {
+                                if let Err(e) = self.handle_ipc_connection(stream).await {
                                     warn!(error = %e, "run: IPC connection error");
                                 }
                             }
@@ -303,11 +568,31 @@ impl TaskManager {
                         self.handle_state_event(event).await?;
                     }
 
+                    // Changes other processes wrote to the store
+                    _ = state_watcher.changed() => {
+                        self.handle_state_file_change().await?;
+                    }
+
                     // Fallback polling for edge cases and orphan recovery
                     _ = interval.tick() => {
                         self.handle_poll_tick().await?;
                     }
 
+                    // Wake parked executions whose conditions now hold
+                    _ = wake_interval.tick() => {
+                        self.check_parked_executions().await?;
+                    }
+
+                    // Sample our own resources; pause pickups under pressure
+                    _ = monitor_interval.tick(), if monitoring => {
+                        self.sample_resources().await?;
+                    }
+
+                    // Probe the provider while the circuit breaker is open
+                    _ = breaker_interval.tick() => {
+                        self.check_provider_circuit().await?;
+                    }
+
                     _ = shutdown_rx.recv() => {
                         debug!("run: shutdown signal received");
                         info!("Shutdown signal received");
@@ -323,11 +608,31 @@ impl TaskManager {
                         self.handle_state_event(event).await?;
                     }
 
+                    // Changes other processes wrote to the store
+                    _ = state_watcher.changed() => {
+                        self.handle_state_file_change().await?;
+                    }
+
                     // Fallback polling for edge cases and orphan recovery
                     _ = interval.tick() => {
                         self.handle_poll_tick().await?;
                     }
 
+                    // Wake parked executions whose conditions now hold
+                    _ = wake_interval.tick() => {
+                        self.check_parked_executions().await?;
+                    }
+
+                    // Sample our own resources; pause pickups under pressure
+                    _ = monitor_interval.tick(), if monitoring => {
+                        self.sample_resources().await?;
+                    }
+
+                    // Probe the provider while the circuit breaker is open
+                    _ = breaker_interval.tick() => {
+                        self.check_provider_circuit().await?;
+                    }
+
                     _ = shutdown_rx.recv() => {
                         debug!("run: shutdown signal received");
                         info!("Shutdown signal received");
@@ -372,22 +677,132 @@ impl TaskManager {
         Ok(())
     }
 
+    /// Pick up executions another process made pending
+    async fn handle_state_file_change(&mut self) -> Result<()> {
+        debug!("handle_state_file_change: state changed on disk");
+        if !self.shutdown_requested {
+            self.poll_and_spawn().await?;
+        }
+        Ok(())
+    }
+
     /// Handle the poll interval tick
     async fn handle_poll_tick(&mut self) -> Result<()> {
         debug!("handle_poll_tick: tick");
+        if self.credential_breaker.is_some() {
+            // Pick a rotated key up without waiting for `td daemon reload-secrets`
+            if let Err(e) = self.reload_secrets(false).await {
+                debug!(error = %e, "handle_poll_tick: API key still unavailable");
+            }
+        }
         if !self.shutdown_requested {
             debug!("handle_poll_tick: polling and spawning");
             self.poll_and_spawn().await?;
         } else {
             debug!("handle_poll_tick: shutdown requested, skipping poll");
         }
+        self.reap_completed_tasks().await;
+        self.prune_worktrees().await;
+        Ok(())
+    }
+
+    /// Prune expired worktrees and enforce the disk quota (at most every WORKTREE_PRUNE_INTERVAL)
+    async fn prune_worktrees(&mut self) {
+        if self
+            .last_worktree_prune
+            .is_some_and(|last| last.elapsed() < WORKTREE_PRUNE_INTERVAL)
+        {
+            debug!("prune_worktrees: pruned recently, skipping");
+            return;
+        }
+        debug!(policy = ?self.config.worktree_prune, "prune_worktrees: called");
+        self.last_worktree_prune = Some(tokio::time::Instant::now());
+
+        let usage = match self.worktree_manager.usage().await {
+            Ok(usage) => usage,
+            Err(e) => {
+                warn!(error = %e, "Failed to measure worktree usage");
+                return;
+            }
+        };
+        let executions = match self.state.list_executions(None, None).await {
+            Ok(executions) => executions,
+            Err(e) => {
+                warn!(error = %e, "Failed to list executions for worktree pruning");
+                return;
+            }
+        };
+
+        // A worktree with a live task is in use whatever its recorded status
+        let entries: Vec<_> = classify(usage, &executions)
+            .into_iter()
+            .map(|(usage, state)| {
+                let state = if self.tasks.contains_key(&usage.exec_id) {
+                    WorktreeState::Active
+                } else {
+                    state
+                };
+                (usage, state)
+            })
+            .collect();
+
+        let candidates = plan_prune(&entries, &self.config.worktree_prune, self.clock.now_ms());
+        if candidates.is_empty() {
+            debug!("prune_worktrees: nothing to prune");
+            return;
+        }
+        let freed = self.worktree_manager.prune(&candidates).await;
+        info!(count = candidates.len(), freed = %format_size(freed), "Pruned worktrees");
+    }
+
+    /// Evaluate wake conditions of parked executions and wake the satisfied ones
+    ///
+    /// A parked execution that still has a running task (parked from the CLI
+    /// while iterating) is stopped first; it is evaluated once the task is gone.
+    async fn check_parked_executions(&mut self) -> Result<()> {
+        debug!("check_parked_executions: called");
+        if self.shutdown_requested {
+            debug!("check_parked_executions: shutdown requested, skipping");
+            return Ok(());
+        }
+
+        let parked = self
+            .state
+            .list_executions(Some("parked".to_string()), None)
+            .await
+            .context("Failed to list parked executions")?;
+        debug!(
+            parked_count = parked.len(),
+            "check_parked_executions: found parked executions"
+        );
+
+        for exec in parked {
+            if self.tasks.contains_key(&exec.id) {
+                debug!(exec_id = %exec.id, "check_parked_executions: stopping running task");
+                if let Err(e) = self.stop_loop(&exec.id).await {
+                    warn!(exec_id = %exec.id, error = %e, "check_parked_executions: failed to stop task");
+                }
+                continue;
+            }
+
+            if let Some(condition) = first_met(&exec, &self.config.repo_root).await {
+                info!(exec_id = %exec.id, %condition, "Waking parked execution");
+                if let Err(e) = self.state.wake_execution(&exec.id).await {
+                    warn!(exec_id = %exec.id, error = %e, "check_parked_executions: failed to wake");
+                }
+            }
+        }
+
         self.reap_completed_tasks().await;
         Ok(())
     }
 
     /// Handle an IPC connection from TUI/CLI
-    async fn handle_ipc_connection(&mut self, stream: &mut tokio::net::UnixStream) -> Result<()> {
-        let msg = read_message(stream).await?;
+    ///
+    /// A `Subscribe` connection is handed to a forwarding task and stays open;
+    /// every other request gets one response.
+    async fn handle_ipc_connection(&mut self, mut stream: tokio::net::UnixStream) -> Result<()> {
+        let msg = read_message(&mut stream).await?;
         debug!(?msg, "handle_ipc_connection: received message");
 
         let response = match msg {
@@ -407,21 +822,391 @@ impl TaskManager {
                     version: VERSION.to_string(),
                 }
             }
-            DaemonMessage::Shutdown => {
+            DaemonMessage::Shutdown { token } => {
                 debug!("handle_ipc_connection: Shutdown");
-                self.shutdown_requested = true;
-                DaemonResponse::Ok
+                match self.writer.check(token.as_deref(), self.clock.instant()) {
+                    Ok(()) => {
+                        self.shutdown_requested = true;
+                        DaemonResponse::Ok
+                    }
+                    Err(message) => DaemonResponse::Error { message },
+                }
+            }
+            DaemonMessage::SetMaintenance { enabled, token } => {
+                debug!(enabled, "handle_ipc_connection: SetMaintenance");
+                if let Err(message) = self.writer.check(token.as_deref(), self.clock.instant()) {
+                    DaemonResponse::Error { message }
+                } else {
+                    let result = if enabled {
+                        self.enter_maintenance().await
+                    } else {
+                        self.exit_maintenance().await
+                    };
+                    match result {
+                        Ok(executions) => DaemonResponse::Maintenance { enabled, executions },
+                        Err(e) => DaemonResponse::Error { message: e.to_string() },
+                    }
+                }
+            }
+            DaemonMessage::ReloadSecrets { token } => {
+                debug!("handle_ipc_connection: ReloadSecrets");
+                if let Err(message) = self.writer.check(token.as_deref(), self.clock.instant()) {
+                    DaemonResponse::Error { message }
+                } else {
+                    match self.reload_secrets(true).await {
+                        Ok((changed, resumed)) => DaemonResponse::SecretsReloaded { changed, resumed },
+                        Err(e) => DaemonResponse::Error {
+                            message: format!("{:#}", e),
+                        },
+                    }
+                }
+            }
+            DaemonMessage::ListExecutions { status } => {
+                debug!(?status, "handle_ipc_connection: ListExecutions");
+                match self.state.list_executions(status, None).await {
+                    Ok(executions) => DaemonResponse::Executions { executions },
+                    Err(e) => DaemonResponse::Error { message: e.to_string() },
+                }
+            }
+            DaemonMessage::DescribeExecution { id } => {
+                debug!(%id, "handle_ipc_connection: DescribeExecution");
+                self.describe_execution(&id).await
+            }
+            DaemonMessage::Subscribe { execution_id } => {
+                debug!(?execution_id, "handle_ipc_connection: Subscribe");
+                // Subscribe before acknowledging so no event falls in between
+                let subscriber = self
+                    .event_bus
+                    .subscribe_buffered(DEFAULT_CHANNEL_CAPACITY, OverflowPolicy::DropTokenEventsFirst);
+                send_response(&mut stream, DaemonResponse::Subscribed).await?;
+                tokio::spawn(stream_events(stream, subscriber, execution_id));
+                return Ok(());
+            }
+            DaemonMessage::Resources => {
+                debug!("handle_ipc_connection: Resources");
+                DaemonResponse::Resources {
+                    monitoring: self.resource_monitor.is_some(),
+                    sample: self.resource_monitor.as_ref().and_then(|m| m.latest().cloned()),
+                    pressure: self
+                        .resource_monitor
+                        .as_ref()
+                        .map(|m| m.pressure().to_vec())
+                        .unwrap_or_default(),
+                }
+            }
+            DaemonMessage::ClaimWriter { holder } => {
+                debug!(%holder, "handle_ipc_connection: ClaimWriter");
+                match self.writer.claim(&holder, self.clock.instant()) {
+                    Ok(token) => DaemonResponse::Writer { token },
+                    Err(message) => DaemonResponse::Error { message },
+                }
+            }
+            DaemonMessage::ReleaseWriter { token } => {
+                debug!("handle_ipc_connection: ReleaseWriter");
+                if self.writer.release(&token) {
+                    DaemonResponse::Ok
+                } else {
+                    DaemonResponse::Error {
+                        message: "Writer token is not held".to_string(),
+                    }
+                }
+            }
+        };
+
+        send_response(&mut stream, response).await?;
+        Ok(())
+    }
+
+    /// Execution plus iteration logs for a `DescribeExecution` request
+    async fn describe_execution(&self, id: &str) -> DaemonResponse {
+        let execution = match self.state.get_execution(id).await {
+            Ok(Some(execution)) => execution,
+            Ok(None) => {
+                return DaemonResponse::Error {
+                    message: format!("Execution not found: {}", id),
+                };
+            }
+            Err(e) => return DaemonResponse::Error { message: e.to_string() },
+        };
+        match self.state.list_iteration_logs(id).await {
+            Ok(iterations) => DaemonResponse::Description {
+                execution: Box::new(execution),
+                iterations,
+            },
+            Err(e) => DaemonResponse::Error { message: e.to_string() },
+        }
+    }
+
+    /// Whether maintenance mode is on
+    pub fn in_maintenance(&self) -> bool {
+        self.maintenance.is_some()
+    }
+
+    /// Turn maintenance mode on: stop pickups and pause every running execution
+    ///
+    /// Running executions are marked Paused now and asked to stop, which they
+    /// do at their next iteration boundary. Returns the executions paused.
+    pub async fn enter_maintenance(&mut self) -> Result<Vec<String>> {
+        debug!(running = self.tasks.len(), "enter_maintenance: called");
+        if let Some(maintenance) = &self.maintenance {
+            debug!("enter_maintenance: already in maintenance mode");
+            return Ok(maintenance.paused.clone());
+        }
+
+        let mut maintenance = MaintenanceState {
+            since: self.clock.now_ms(),
+            paused: Vec::new(),
+        };
+        let mut running: Vec<String> = self.tasks.keys().cloned().collect();
+        running.sort();
+        for exec_id in running {
+            match self.state.pause_execution(&exec_id).await {
+                Ok(()) => {
+                    debug!(%exec_id, "enter_maintenance: paused");
+                    maintenance.paused.push(exec_id.clone());
+                    let _ = self
+                        .coordinator_tx
+                        .send(CoordRequest::Stop {
+                            from_exec_id: "loop_manager".to_string(),
+                            target_exec_id: exec_id,
+                            reason: MAINTENANCE_STOP_REASON.to_string(),
+                        })
+                        .await;
+                }
+                Err(e) => debug!(%exec_id, error = %e, "enter_maintenance: not pausable, leaving as is"),
+            }
+        }
+
+        if let Some(path) = &self.maintenance_file {
+            maintenance.save(path)?;
+        }
+        info!(paused = maintenance.paused.len(), "Entered maintenance mode");
+        let paused = maintenance.paused.clone();
+        self.maintenance = Some(maintenance);
+        Ok(paused)
+    }
+
+    /// Turn maintenance mode off: resume what it paused and pick up work again
+    ///
+    /// Executions paused by hand during maintenance stay paused. Returns the
+    /// executions resumed.
+    pub async fn exit_maintenance(&mut self) -> Result<Vec<String>> {
+        debug!("exit_maintenance: called");
+        let Some(maintenance) = self.maintenance.take() else {
+            debug!("exit_maintenance: not in maintenance mode");
+            return Ok(Vec::new());
+        };
+        if let Some(path) = &self.maintenance_file {
+            MaintenanceState::clear(path)?;
+        }
+
+        let resumed = self.resume_paused(maintenance.paused).await?;
+        info!(resumed = resumed.len(), "Left maintenance mode");
+
+        // Resumed executions are "running" without a task, so the poll respawns them
+        self.poll_and_spawn().await?;
+        Ok(resumed)
+    }
+
+    /// Mark those of `exec_ids` that are still paused running again; returns them
+    async fn resume_paused(&self, exec_ids: Vec<String>) -> Result<Vec<String>> {
+        debug!(count = exec_ids.len(), "resume_paused: called");
+        let mut resumed = Vec::new();
+        for exec_id in exec_ids {
+            let Ok(Some(mut exec)) = self.state.get_execution(&exec_id).await else {
+                debug!(%exec_id, "resume_paused: execution gone");
+                continue;
+            };
+            if exec.status != LoopExecutionStatus::Paused {
+                debug!(%exec_id, status = ?exec.status, "resume_paused: no longer paused, skipping");
+                continue;
             }
+            exec.set_status(LoopExecutionStatus::Running);
+            self.state.update_execution(exec).await?;
+            resumed.push(exec_id);
+        }
+        Ok(resumed)
+    }
+
+    /// Whether the provider rejected the API key (pickups are paused until it is reloaded)
+    pub fn credentials_rejected(&self) -> bool {
+        self.credential_breaker.is_some()
+    }
+
+    /// Open the credential breaker (if it isn't already) with `exec_id` among the paused
+    fn trip_credential_breaker(&mut self, exec_id: String) {
+        let paused = self.credential_breaker.get_or_insert_with(|| {
+            warn!("API key rejected, pausing executions until it is reloaded (td daemon reload-secrets)");
+            Vec::new()
+        });
+        paused.push(exec_id);
+    }
+
+    /// Re-read the API key and resume what the credential breaker paused
+    ///
+    /// Without `force` the breaker stays open unless the key changed; with it
+    /// (`td daemon reload-secrets`) the breaker closes whenever the key can be
+    /// read, since it may have been fixed at the provider. Returns whether the
+    /// key changed and the executions resumed.
+    pub async fn reload_secrets(&mut self, force: bool) -> Result<(bool, Vec<String>)> {
+        debug!(force, open = self.credential_breaker.is_some(), "reload_secrets: called");
+        let changed = self.llm.reload_credentials().context("Failed to reload the API key")?;
+        if !changed && !force {
+            debug!("reload_secrets: key unchanged, breaker stays open");
+            return Ok((false, Vec::new()));
+        }
+        let Some(paused) = self.credential_breaker.take() else {
+            debug!(changed, "reload_secrets: breaker closed, nothing to resume");
+            return Ok((changed, Vec::new()));
         };
 
-        send_response(stream, response).await?;
+        let resumed = self.resume_paused(paused).await?;
+        info!(changed, resumed = resumed.len(), "API key reloaded, resuming executions");
+        // The reload itself succeeded; a failed pickup is retried on the next poll
+        if let Err(e) = self.poll_and_spawn().await {
+            warn!(error = %e, "Failed to pick up executions after reloading the API key");
+        }
+        Ok((changed, resumed))
+    }
+
+    /// Follow the provider circuit breaker
+    ///
+    /// Announces it opening, probes the provider while it is open, and once it
+    /// closes (a probe or a running loop got through) returns the executions
+    /// waiting on it to Pending.
+    async fn check_provider_circuit(&mut self) -> Result<()> {
+        debug!(outage = self.provider_outage, "check_provider_circuit: called");
+        if self.scheduler.provider_circuit_open().await {
+            if !self.provider_outage {
+                self.provider_outage = true;
+                warn!("Provider circuit open, executions wait for the provider to recover");
+                self.event_bus.emit(LoopEvent::ProviderCircuit {
+                    execution_id: DAEMON_EVENT_ID.to_string(),
+                    open: true,
+                    resumed: 0,
+                });
+                return Ok(());
+            }
+            if !self.probe_provider().await {
+                debug!("check_provider_circuit: provider still down");
+                return Ok(());
+            }
+            self.scheduler.record_provider_success().await;
+        }
+
+        // Also catches executions left waiting by a daemon restart
+        let waiting = self
+            .state
+            .list_executions(Some(LoopExecutionStatus::WaitingProvider.to_string()), None)
+            .await
+            .context("Failed to list executions waiting for the provider")?;
+        if waiting.is_empty() && !self.provider_outage {
+            return Ok(());
+        }
+        let mut resumed = 0;
+        for mut exec in waiting {
+            exec.set_status(LoopExecutionStatus::Pending);
+            exec.clear_error();
+            self.state.update_execution(exec).await?;
+            resumed += 1;
+        }
+        self.provider_outage = false;
+        info!(resumed, "Provider recovered, resuming executions");
+        self.event_bus.emit(LoopEvent::ProviderCircuit {
+            execution_id: DAEMON_EVENT_ID.to_string(),
+            open: false,
+            resumed,
+        });
+        // The executions are back in the queue; a failed pickup is retried on the next poll
+        if !self.shutdown_requested
+            && let Err(e) = self.poll_and_spawn().await
+        {
+            warn!(error = %e, "Failed to pick up executions after the provider recovered");
+        }
+        Ok(())
+    }
+
+    /// Send the provider a one-token request; true if it answered
+    ///
+    /// Any answer counts, even an error, unless the error looks like an outage.
+    async fn probe_provider(&self) -> bool {
+        debug!("probe_provider: called");
+        let request = CompletionRequest {
+            system_prompt: String::new(),
+            messages: vec![Message::user("ping")],
+            tools: Vec::new(),
+            max_tokens: 1,
+        };
+        match self.llm.complete(request).await {
+            Ok(_) => true,
+            Err(e) => {
+                debug!(error = %e, "probe_provider: probe failed");
+                !e.category().is_outage()
+            }
+        }
+    }
+
+    /// Whether a resource limit is exceeded (pickups are paused until it clears)
+    pub fn under_resource_pressure(&self) -> bool {
+        self.resource_monitor
+            .as_ref()
+            .is_some_and(ResourceMonitor::under_pressure)
+    }
+
+    /// Sample the daemon's resources, emitting `ResourcePressure` when a limit is first exceeded
+    async fn sample_resources(&mut self) -> Result<()> {
+        let worktrees: Vec<_> = self
+            .tasks
+            .keys()
+            .map(|id| (id.clone(), self.worktree_manager.worktree_path(id)))
+            .collect();
+        let (loop_tasks, now_ms) = (self.tasks.len(), self.clock.now_ms());
+        let Some(monitor) = self.resource_monitor.as_mut() else {
+            return Ok(());
+        };
+        match monitor.sample(&worktrees, loop_tasks, now_ms) {
+            PressureChange::Started(exceeded) => {
+                warn!(?exceeded, "Resource pressure, not picking up executions");
+                self.event_bus.emit(LoopEvent::ResourcePressure {
+                    execution_id: DAEMON_EVENT_ID.to_string(),
+                    exceeded,
+                });
+            }
+            PressureChange::Cleared => {
+                info!("Resource pressure cleared, picking up executions again");
+                if !self.shutdown_requested {
+                    self.poll_and_spawn().await?;
+                }
+            }
+            PressureChange::Unchanged => debug!("sample_resources: pressure unchanged"),
+        }
         Ok(())
     }
 
     /// Try to spawn an execution if it exists and deps are satisfied
     async fn try_spawn_execution(&mut self, id: &str) {
+        if self.maintenance.is_some() {
+            debug!(%id, "try_spawn_execution: maintenance mode, not spawning");
+            return;
+        }
+        if self.credential_breaker.is_some() {
+            debug!(%id, "try_spawn_execution: API key rejected, will pick up once it is reloaded");
+            return;
+        }
+        if self.scheduler.provider_circuit_open().await {
+            debug!(%id, "try_spawn_execution: provider circuit open, will pick up once it closes");
+            return;
+        }
+        if self.under_resource_pressure() {
+            debug!(%id, "try_spawn_execution: resource pressure, will pick up once it clears");
+            return;
+        }
         if let Ok(Some(exec)) = self.state.get_execution(id).await {
-            if self.loop_deps_satisfied(&exec).await.unwrap_or(false) {
+            if let Some(reason) = self.concurrency_blocker(&exec) {
+                debug!(%id, %reason, "try_spawn_execution: concurrency limit reached, will pick up on next poll");
+            } else if !self.path_locks_allow(&exec).await {
+                debug!(%id, "try_spawn_execution: declared paths locked, will pick up on next poll");
+            } else if self.loop_deps_satisfied(&exec).await.unwrap_or(false) {
                 debug!(%id, "try_spawn_execution: deps satisfied, spawning");
                 if let Err(e) = self.spawn_loop(&exec).await {
                     warn!(%id, error = %e, "try_spawn_execution: failed to spawn");
@@ -441,6 +1226,22 @@ impl TaskManager {
     /// Also recovers "running" executions that aren't actually being processed (e.g., after restart).
     async fn poll_and_spawn(&mut self) -> Result<()> {
         debug!("poll_and_spawn: called");
+        if self.maintenance.is_some() {
+            debug!("poll_and_spawn: maintenance mode, not picking up executions");
+            return Ok(());
+        }
+        if self.credential_breaker.is_some() {
+            debug!("poll_and_spawn: API key rejected, not picking up executions");
+            return Ok(());
+        }
+        if self.scheduler.provider_circuit_open().await {
+            debug!("poll_and_spawn: provider circuit open, not picking up executions");
+            return Ok(());
+        }
+        if self.under_resource_pressure() {
+            debug!("poll_and_spawn: resource pressure, not picking up executions");
+            return Ok(());
+        }
 
         // Find pending LoopExecutions with satisfied dependencies
         let pending_executions = self
@@ -453,9 +1254,25 @@ impl TaskManager {
             "poll_and_spawn: found pending executions"
         );
 
+        // Pick up in the scheduler's order: aged priority under its fairness policy
+        let mut running: HashMap<String, usize> = HashMap::new();
+        for loop_type in self.task_types.values() {
+            *running.entry(loop_type.clone()).or_default() += 1;
+        }
+        let order = self
+            .scheduler
+            .config()
+            .pending_order(&pending_executions, &running, self.clock.now_ms());
+        let mut slots: Vec<Option<LoopExecution>> = pending_executions.into_iter().map(Some).collect();
+        let pending_executions: Vec<LoopExecution> = order.into_iter().filter_map(|i| slots[i].take()).collect();
+
         for exec in pending_executions {
             debug!(exec_id = %exec.id, "poll_and_spawn: checking deps for execution");
-            if self.loop_deps_satisfied(&exec).await? {
+            if let Some(reason) = self.concurrency_blocker(&exec) {
+                debug!(exec_id = %exec.id, %reason, "poll_and_spawn: concurrency limit reached");
+            } else if !self.path_locks_allow(&exec).await {
+                debug!(exec_id = %exec.id, "poll_and_spawn: declared paths locked");
+            } else if self.loop_deps_satisfied(&exec).await? {
                 debug!(exec_id = %exec.id, "poll_and_spawn: deps satisfied, spawning");
                 self.spawn_loop(&exec).await?;
             } else {
@@ -473,6 +1290,10 @@ impl TaskManager {
 
         for exec in running_executions {
             if !self.tasks.contains_key(&exec.id) {
+                if let Some(reason) = self.concurrency_blocker(&exec) {
+                    debug!(exec_id = %exec.id, %reason, "poll_and_spawn: orphaned execution waits for concurrency limit");
+                    continue;
+                }
                 info!(exec_id = %exec.id, loop_type = %exec.loop_type, "poll_and_spawn: recovering orphaned running execution");
                 self.spawn_loop(&exec).await?;
             }
@@ -482,6 +1303,53 @@ impl TaskManager {
         Ok(())
     }
 
+    /// Why the loop type's concurrency limits keep an execution waiting, if they do
+    fn concurrency_blocker(&self, exec: &LoopExecution) -> Option<String> {
+        let running: Vec<&str> = self
+            .task_types
+            .iter()
+            .filter(|(id, _)| **id != exec.id)
+            .map(|(_, loop_type)| loop_type.as_str())
+            .collect();
+        concurrency_blocker(&self.loop_configs, &exec.loop_type, &running)
+    }
+
+    /// Whether the execution may start given the locks on its declared paths
+    ///
+    /// Conflicts hold a `path-locks: serialize` type back until the holder
+    /// finishes; other types start with a warning.
+    async fn path_locks_allow(&self, exec: &LoopExecution) -> bool {
+        let paths = exec.declared_paths();
+        if paths.is_empty() {
+            return true;
+        }
+        debug!(exec_id = %exec.id, ?paths, "path_locks_allow: called");
+
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let request = CoordRequest::CheckPaths {
+            exec_id: exec.id.clone(),
+            paths,
+            reply_tx,
+        };
+        if self.coordinator_tx.send(request).await.is_err() {
+            return true;
+        }
+        let conflicts = reply_rx.await.unwrap_or_default();
+        if conflicts.is_empty() {
+            return true;
+        }
+
+        let mode = self
+            .loop_configs
+            .get(&exec.loop_type)
+            .map(|c| c.path_locks)
+            .unwrap_or_default();
+        for conflict in &conflicts {
+            warn!(exec_id = %exec.id, path = %conflict.path, held_by = %conflict.held_by, %mode, "Declared path is locked by another execution");
+        }
+        mode != PathLockMode::Serialize
+    }
+
     /// Check if a LoopExecution's dependencies are satisfied
     async fn loop_deps_satisfied(&self, exec: &LoopExecution) -> Result<bool> {
         debug!(exec_id = %exec.id, dep_count = exec.deps.len(), "loop_deps_satisfied: called");
@@ -545,12 +1413,41 @@ impl TaskManager {
                 let dir = format!(".taskdaemon/artifacts/phases/{}", exec.id);
                 (None, Some(dir))
             }
+            REVIEW_TYPE => {
+                // The review is stored here when the loop completes
+                let dir = format!("{}/{}", crate::review::ARTIFACT_DIR, exec.id);
+                let file = format!("{}/review.md", dir);
+                (Some(file), Some(dir))
+            }
+            "security-review" => {
+                // The SARIF report is written into the worktree and merged with the fixes
+                let dir = crate::security::SECURITY_DIR.to_string();
+                let file = format!("{}/{}", dir, crate::security::REPORT_FILE);
+                (Some(file), Some(dir))
+            }
             _ => return (None, None), // ralph and other types don't produce markdown artifacts
         };
         debug!(exec_id = %exec.id, loop_type = %exec.loop_type, ?file, ?dir, "get_output_paths");
         (file, dir)
     }
 
+    /// The LLM client for an execution: the shared one unless it overrides the model
+    fn client_for(&self, exec: &LoopExecution) -> Result<Arc<dyn LlmClient>> {
+        let Some(model) = exec.model.as_deref() else {
+            return Ok(self.llm.clone());
+        };
+        debug!(exec_id = %exec.id, %model, "client_for: model override");
+        let Some(config) = self.llm_config.as_ref() else {
+            warn!(exec_id = %exec.id, %model, "No LLM config to build the model override from; using the default model");
+            return Ok(self.llm.clone());
+        };
+        let config = LlmConfig {
+            default: model.to_string(),
+            ..config.clone()
+        };
+        create_client(&config).with_context(|| format!("Failed to create LLM client for model '{}'", model))
+    }
+
     /// Spawn a loop execution as a tokio task
     pub async fn spawn_loop(&mut self, exec: &LoopExecution) -> Result<()> {
         debug!(exec_id = %exec.id, loop_type = %exec.loop_type, "spawn_loop: called");
@@ -587,28 +1484,65 @@ impl TaskManager {
             }
             debug!(exec_id = %exec.id, %dir, "spawn_loop: set output-dir");
         }
-        self.state.update_execution(exec.clone()).await?;
+        // The title call can take a while: apply the new fields to a fresh read
+        let (title, context) = (exec.title.clone(), exec.context.clone());
+        let (artifact_path, artifact_status) = (exec.artifact_path.clone(), exec.artifact_status.clone());
+        let exec = self
+            .state
+            .modify_execution(&exec.id, |stored| {
+                stored.title = title.clone();
+                stored.context = context.clone();
+                stored.artifact_path = artifact_path.clone();
+                stored.artifact_status = artifact_status.clone();
+            })
+            .await?;
+
+        // Policies may refuse the start (held for review) or adjust the context
+        let exec = match self.policies.evaluate(PolicyInput::start(&exec)).await {
+            PolicyOutcome::Allow => exec,
+            PolicyOutcome::Modify(input) => {
+                debug!(exec_id = %exec.id, "spawn_loop: context modified by policy");
+                let context = input.execution.context;
+                self.state
+                    .modify_execution(&exec.id, |stored| stored.context = context.clone())
+                    .await?
+            }
+            denied @ PolicyOutcome::Deny { .. } => {
+                let reason = denied.denial().unwrap_or_default();
+                debug!(exec_id = %exec.id, %reason, "spawn_loop: start denied by policy");
+                warn!(exec_id = %exec.id, %reason, "Execution start denied");
+                self.state
+                    .modify_execution(&exec.id, |stored| {
+                        stored.set_status(LoopExecutionStatus::Blocked);
+                        stored.set_error(&reason);
+                    })
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        let llm = self.client_for(&exec)?;
 
         // Wait for scheduler slot (handles rate limiting and priority queuing)
-        // TODO: Extract priority from parent Spec/Plan once we wire that up
-        debug!(exec_id = %exec.id, "spawn_loop: waiting for scheduler slot");
+        debug!(exec_id = %exec.id, priority = %exec.priority, "spawn_loop: waiting for scheduler slot");
         self.scheduler
-            .wait_for_slot(&exec.id, crate::domain::Priority::Normal)
+            .wait_for_slot_as(&exec.id, exec.priority, &exec.loop_type)
             .await
             .context("Failed to acquire scheduler slot")?;
         debug!(exec_id = %exec.id, "spawn_loop: got scheduler slot");
 
-        // Create/verify worktree
+        // Create/verify worktree (a resumed or rolled back execution keeps its own)
         debug!(exec_id = %exec.id, "spawn_loop: creating worktree");
         let worktree_info = self
             .worktree_manager
-            .create(&exec.id)
+            .open_or_create(&exec.id)
             .await
             .context("Failed to create worktree")?;
         debug!(exec_id = %exec.id, worktree = ?worktree_info.path, "spawn_loop: worktree created");
 
         // Get loop config for this type
-        let loop_config = self.loop_configs.get(&exec.loop_type).cloned().unwrap_or_default();
+        let mut loop_config = self.loop_configs.get(&exec.loop_type).cloned().unwrap_or_default();
+        loop_config.hooks = self.config.hooks.followed_by(&loop_config.hooks);
         debug!(exec_id = %exec.id, has_config = self.loop_configs.contains_key(&exec.loop_type), "spawn_loop: got loop config");
 
         // Register with coordinator and get a handle
@@ -620,10 +1554,30 @@ impl TaskManager {
 
         // Update execution status to running
         debug!(exec_id = %exec.id, "spawn_loop: updating status to running");
-        let mut exec_running = exec.clone();
-        exec_running.set_status(LoopExecutionStatus::Running);
-        exec_running.set_worktree(worktree_info.path.display().to_string());
-        self.state.update_execution(exec_running).await?;
+        let worktree = worktree_info.path.display().to_string();
+        let mut granted = Vec::new();
+
+        // Lock the paths the execution declared up front (conflicts were checked at pickup)
+        let declared_paths = exec.declared_paths();
+        if !declared_paths.is_empty() {
+            let conflicts = coord_handle.lock_paths(&declared_paths).await.unwrap_or_default();
+            let emitter = self.event_bus.emitter_for(&exec.id);
+            for conflict in &conflicts {
+                emitter.path_conflict(&conflict.path, &conflict.held_by);
+            }
+            granted = declared_paths
+                .iter()
+                .map(|p| normalize_lock_path(p))
+                .filter(|p| !conflicts.iter().any(|c| &c.path == p))
+                .collect();
+        }
+        self.state
+            .modify_execution(&exec.id, |stored| {
+                stored.set_status(LoopExecutionStatus::Running);
+                stored.set_worktree(worktree.clone());
+                stored.add_locked_paths(&granted);
+            })
+            .await?;
 
         // Log loop start time clearly for cascade timing analysis
         info!(
@@ -638,28 +1592,60 @@ impl TaskManager {
         let exec_id = exec.id.clone();
         let loop_type = exec.loop_type.clone();
         let exec_context = exec.context.clone();
-        let llm = self.llm.clone();
+        let acceptance = exec.acceptance.clone();
+        let (model, max_iterations) = (exec.model.clone(), exec.max_iterations);
+        let llm_config = self.llm_config.clone();
         let state = self.state.clone();
         let worktree_path = worktree_info.path.clone();
         let repo_root = self.config.repo_root.clone();
         let scheduler = self.scheduler.clone();
-        let type_loader = self.type_loader.clone();
+        let cascade = CascadeHandler::new(Arc::new(self.state.clone()), self.type_loader.clone())
+            .with_id_gen(self.id_gen.clone())
+            .with_clock(self.clock.clone());
+        let clock = self.clock.clone();
+        let metrics = self.metrics.clone();
+        metrics.start_loop(&exec.id, &exec.loop_type);
+        let gates = MergeGates {
+            evaluator: self.evaluator.clone(),
+            risk: self.risk.clone(),
+            review: self.review.clone(),
+        };
+        let command_env: Vec<(String, String)> = self
+            .config
+            .cargo_target_dir
+            .iter()
+            .map(|dir| ("CARGO_TARGET_DIR".to_string(), dir.display().to_string()))
+            .collect();
 
         // Create event emitter for live streaming to TUI
         let event_emitter = self.event_bus.emitter_for(&exec.id);
+        let extra_tools = self.extra_tools.clone();
+        let policies = self.policies.clone();
+        let tool_worker = self.config.tool_worker.clone();
 
         let handle = tokio::spawn(async move {
             debug!(exec_id = %exec_id, "spawn_loop task: starting");
             // Build engine with coordinator, scheduler, execution context, repo root, state, and event emitter
-            let engine =
+            let mut engine =
                 LoopEngine::with_coordinator(exec_id.clone(), loop_config, llm, worktree_path.clone(), coord_handle)
+                    .with_overrides(model, max_iterations)
                     .with_scheduler(scheduler.clone())
                     .with_execution_context(exec_context)
+                    .with_acceptance(acceptance)
                     .with_repo_root(repo_root.clone())
                     .with_state(state.clone())
-                    .with_event_emitter(event_emitter);
+                    .with_event_emitter(event_emitter)
+                    .with_metrics(metrics)
+                    .with_command_env(command_env)
+                    .with_tools(&extra_tools)
+                    .with_policies(policies)
+                    .with_tool_worker(&tool_worker)
+                    .with_clock(clock);
+            if let Some(config) = llm_config {
+                engine = engine.with_llm_config(config);
+            }
 
-            let result = run_loop_task(engine, state, worktree_path, repo_root, type_loader, loop_type).await;
+            let result = run_loop_task(engine, state, worktree_path, repo_root, cascade, loop_type, gates).await;
 
             // Mark scheduler slot as complete (releases slot for next queued request)
             debug!(exec_id = %exec_id, "spawn_loop task: completing scheduler slot");
@@ -670,6 +1656,7 @@ impl TaskManager {
         });
 
         self.tasks.insert(exec.id.clone(), handle);
+        self.task_types.insert(exec.id.clone(), exec.loop_type.clone());
         info!(exec_id = %exec.id, "Spawned loop");
         debug!(exec_id = %exec.id, running_count = self.tasks.len(), "spawn_loop: complete");
 
@@ -693,37 +1680,103 @@ impl TaskManager {
         );
 
         for exec_id in completed_ids {
+            let loop_type = self.task_types.remove(&exec_id);
             if let Some(handle) = self.tasks.remove(&exec_id) {
+                self.release_coord_registration(&exec_id).await;
                 debug!(exec_id = %exec_id, "reap_completed_tasks: awaiting task result");
-                match handle.await {
+                let final_status = match handle.await {
                     Ok(LoopTaskResult::Complete { exec_id, iterations }) => {
                         debug!(exec_id = %exec_id, iterations, "reap_completed_tasks: loop completed successfully");
                         info!(exec_id = %exec_id, iterations, "Loop completed successfully");
+                        "complete"
                     }
                     Ok(LoopTaskResult::Failed { exec_id, reason }) => {
                         debug!(exec_id = %exec_id, %reason, "reap_completed_tasks: loop failed");
                         error!(exec_id = %exec_id, reason = %reason, "Loop failed");
+                        "failed"
                     }
                     Ok(LoopTaskResult::Stopped { exec_id }) => {
                         debug!(exec_id = %exec_id, "reap_completed_tasks: loop stopped");
                         info!(exec_id = %exec_id, "Loop stopped");
+                        "stopped"
+                    }
+                    Ok(LoopTaskResult::Paused { exec_id, reason }) => {
+                        debug!(exec_id = %exec_id, %reason, "reap_completed_tasks: loop paused");
+                        warn!(exec_id = %exec_id, reason = %reason, "Loop paused for review");
+                        // Keep the worktree so the stalled work can be inspected and resumed
+                        self.metrics.complete_loop(&exec_id, "paused");
+                        if reason == CREDENTIALS_PAUSE_REASON {
+                            self.trip_credential_breaker(exec_id);
+                        }
+                        continue;
                     }
                     Err(e) => {
                         debug!(exec_id = %exec_id, error = %e, "reap_completed_tasks: loop task panicked");
                         error!(exec_id = %exec_id, error = %e, "Loop task panicked");
+                        "failed"
                     }
+                };
+                self.metrics.complete_loop(&exec_id, final_status);
+                self.persist_metrics_snapshot(&exec_id, final_status).await;
+                if loop_type.as_deref() == Some(DEP_BUMP_TYPE) {
+                    self.join_dep_upgrade(&exec_id).await;
                 }
 
-                // Cleanup worktree
-                debug!(exec_id = %exec_id, "reap_completed_tasks: removing worktree");
-                if let Err(e) = self.worktree_manager.remove(&exec_id).await {
-                    warn!(exec_id = %exec_id, error = %e, "Failed to remove worktree");
+                // Cleanup worktree, unless finished worktrees are retained for pruning later
+                if self.config.worktree_prune.removes_on_finish() {
+                    debug!(exec_id = %exec_id, "reap_completed_tasks: removing worktree");
+                    if let Err(e) = self.worktree_manager.remove(&exec_id).await {
+                        warn!(exec_id = %exec_id, error = %e, "Failed to remove worktree");
+                    }
+                } else {
+                    debug!(exec_id = %exec_id, "reap_completed_tasks: keeping worktree until it expires");
                 }
             }
         }
         debug!("reap_completed_tasks: complete");
     }
 
+    /// Write the upgrade summary once a finished `dep-bump` was its parent's last
+    async fn join_dep_upgrade(&self, exec_id: &str) {
+        debug!(%exec_id, "join_dep_upgrade: called");
+        let Ok(Some(exec)) = self.state.get_execution(exec_id).await else {
+            return;
+        };
+        let Some(parent_id) = exec.parent else {
+            debug!(%exec_id, "join_dep_upgrade: no parent");
+            return;
+        };
+        let cascade = CascadeHandler::new(Arc::new(self.state.clone()), self.type_loader.clone());
+        join_dep_upgrades(&self.state, &cascade, &self.config.repo_root, &parent_id).await;
+    }
+
+    /// Persist a finished execution's metrics so trends survive daemon restarts
+    async fn persist_metrics_snapshot(&self, exec_id: &str, status: &str) {
+        debug!(%exec_id, %status, "persist_metrics_snapshot: called");
+        let exec = match self.state.get_execution(exec_id).await {
+            Ok(Some(exec)) => exec,
+            Ok(None) => {
+                debug!(%exec_id, "persist_metrics_snapshot: execution not found");
+                return;
+            }
+            Err(e) => {
+                warn!(exec_id = %exec_id, error = %e, "Failed to load execution for metrics snapshot");
+                return;
+            }
+        };
+
+        let mut snapshot = MetricsSnapshot::from_execution(&exec, status, &self.model);
+        if snapshot.duration_ms == 0
+            && let Some(stats) = self.metrics.get_loop_stats(exec_id)
+        {
+            debug!(%exec_id, "persist_metrics_snapshot: using in-memory duration");
+            snapshot = snapshot.with_duration_ms(stats.total_duration_ms().max(0) as u64);
+        }
+        if let Err(e) = self.state.save_metrics_snapshot(snapshot).await {
+            warn!(exec_id = %exec_id, error = %e, "Failed to persist metrics snapshot");
+        }
+    }
+
     /// Recover interrupted loops on startup
     async fn recover_interrupted_loops(&mut self) -> Result<()> {
         debug!("recover_interrupted_loops: called");
@@ -793,6 +1846,7 @@ impl TaskManager {
         if !self.tasks.is_empty() {
             debug!(remaining = self.tasks.len(), "shutdown: force aborting remaining tasks");
             warn!("Aborting {} remaining loops after timeout", self.tasks.len());
+            self.task_types.clear();
             for (exec_id, handle) in self.tasks.drain() {
                 debug!(exec_id = %exec_id, "shutdown: aborting task");
                 handle.abort();
@@ -828,6 +1882,18 @@ impl TaskManager {
         self.tasks.keys().cloned().collect()
     }
 
+    /// Get the shared API scheduler (queue depth, wait times, rate limiting)
+    pub fn scheduler(&self) -> Arc<Scheduler> {
+        debug!("scheduler: called");
+        self.scheduler.clone()
+    }
+
+    /// Get the shared loop metrics tracker
+    pub fn metrics(&self) -> Arc<LoopMetrics> {
+        debug!("metrics: called");
+        self.metrics.clone()
+    }
+
     /// Stop a specific loop
     pub async fn stop_loop(&self, exec_id: &str) -> Result<()> {
         debug!(%exec_id, "stop_loop: called");
@@ -845,19 +1911,253 @@ impl TaskManager {
     }
 }
 
-/// Run a loop task and handle completion
+/// Run the self-evaluation pass and record the scores on the execution
 ///
-/// On successful completion, merges the worktree branch to main and triggers cascade.
+/// Returns Some(result) when the execution scored below the threshold and was
+/// blocked for human review; None when the merge should proceed. Evaluation
+/// failures are logged and do not block the merge.
+async fn evaluate_before_merge(
+    evaluator: &Evaluator,
+    state: &StateManager,
+    engine: &LoopEngine,
+    exec_id: &str,
+    repo_root: &std::path::Path,
+    worktree_path: &std::path::Path,
+) -> Option<LoopTaskResult> {
+    debug!(%exec_id, "evaluate_before_merge: called");
+    let mut exec = state.get_execution(exec_id).await.ok().flatten()?;
+    let criteria = evaluation_criteria(&exec, repo_root).await;
+
+    let evaluation = match evaluator.evaluate_worktree(&criteria, worktree_path, "main").await {
+        Ok(evaluation) => evaluation,
+        Err(e) => {
+            debug!(%exec_id, error = %e, "evaluate_before_merge: evaluation failed");
+            warn!(exec_id = %exec_id, error = %e, "Self-evaluation failed, continuing with merge");
+            return None;
+        }
+    };
+
+    let passed = evaluation.passed();
+    let overall = evaluation.overall;
+    let threshold = evaluation.threshold;
+    info!(exec_id = %exec_id, overall, threshold, passed, "Self-evaluation complete");
+    exec.set_evaluation(evaluation);
+
+    if passed {
+        debug!(%exec_id, "evaluate_before_merge: passed");
+        let _ = state.update_execution(exec).await;
+        return None;
+    }
+
+    let reason = format!(
+        "Self-evaluation score {:.1} below threshold {:.1}, awaiting human review",
+        overall, threshold
+    );
+    debug!(%exec_id, %reason, "evaluate_before_merge: flagged for review");
+    exec.set_status(LoopExecutionStatus::Blocked);
+    exec.set_error(&reason);
+    exec.iteration = engine.current_iteration();
+    exec.progress = engine.get_progress();
+    let _ = state.update_execution(exec).await;
+
+    Some(LoopTaskResult::Paused {
+        exec_id: exec_id.to_string(),
+        reason,
+    })
+}
+
+/// Score the diff and record it on the execution
+///
+/// Returns Some(result) when the score calls for approval and nobody has
+/// approved it yet, leaving the execution blocked until `td exec approve`;
+/// None when the merge should proceed. Scoring failures are logged and do not
+/// block the merge.
+async fn gate_on_risk(
+    risk: &RiskConfig,
+    state: &StateManager,
+    engine: &LoopEngine,
+    exec_id: &str,
+    worktree_path: &std::path::Path,
+) -> Option<LoopTaskResult> {
+    debug!(%exec_id, "gate_on_risk: called");
+    let mut exec = state.get_execution(exec_id).await.ok().flatten()?;
+
+    let assessment = match assess_worktree_risk(risk, worktree_path, "main").await {
+        Ok(assessment) => assessment,
+        Err(e) => {
+            debug!(%exec_id, error = %e, "gate_on_risk: scoring failed");
+            warn!(exec_id = %exec_id, error = %e, "Risk scoring failed, continuing with merge");
+            return None;
+        }
+    };
+    exec.set_risk(assessment);
+    let assessment = exec.risk.clone()?;
+    info!(exec_id = %exec_id, risk = %assessment.summary(), "Risk assessment complete");
+
+    if !assessment.holds_merge() {
+        debug!(%exec_id, "gate_on_risk: merge allowed");
+        let _ = state.update_execution(exec).await;
+        return None;
+    }
+
+    let reason = format!(
+        "Risk score {}/{}, awaiting approval (td exec approve {})",
+        assessment.score, assessment.threshold, exec_id
+    );
+    debug!(%exec_id, %reason, "gate_on_risk: held for approval");
+    exec.set_status(LoopExecutionStatus::Blocked);
+    exec.set_error(&reason);
+    exec.iteration = engine.current_iteration();
+    exec.progress = engine.get_progress();
+    let _ = state.update_execution(exec).await;
+
+    Some(LoopTaskResult::Paused {
+        exec_id: exec_id.to_string(),
+        reason,
+    })
+}
+
+/// Checks a completed code loop must pass before it merges
+struct MergeGates {
+    /// Self-evaluation judge
+    evaluator: Option<Arc<Evaluator>>,
+    /// Change risk policy
+    risk: Option<Arc<RiskConfig>>,
+    /// Review publishing and the blocking-comment gate
+    review: Option<Arc<ReviewConfig>>,
+}
+
+/// Acceptance criteria for evaluation: the parent document if any, else the task description
+pub(super) async fn evaluation_criteria(exec: &LoopExecution, repo_root: &std::path::Path) -> String {
+    debug!(exec_id = %exec.id, "evaluation_criteria: called");
+    if let Some(parent_file) = exec.context.get("parent-file").and_then(|v| v.as_str()) {
+        let path = if parent_file.starts_with('/') {
+            PathBuf::from(parent_file)
+        } else {
+            repo_root.join(parent_file)
+        };
+        if let Ok(content) = tokio::fs::read_to_string(&path).await {
+            debug!(exec_id = %exec.id, ?path, "evaluation_criteria: using parent file");
+            return content;
+        }
+    }
+
+    debug!(exec_id = %exec.id, "evaluation_criteria: using task description");
+    exec.context
+        .get("task")
+        .or_else(|| exec.context.get("description"))
+        .and_then(|v| v.as_str())
+        .map(String::from)
+        .or_else(|| exec.title.clone())
+        .unwrap_or_else(|| exec.loop_type.clone())
+}
+
+/// Store a completed review as the execution's artifact and post it to GitHub if configured
+///
+/// Failures are logged; the review stays in the worktree either way.
+async fn publish_review(
+    config: Option<&ReviewConfig>,
+    exec: &mut LoopExecution,
+    worktree_path: &std::path::Path,
+    repo_root: &std::path::Path,
+) {
+    debug!(exec_id = %exec.id, "publish_review: called");
+    let review = match Review::load(worktree_path) {
+        Ok(Some(review)) => review,
+        Ok(None) => {
+            warn!(exec_id = %exec.id, "Review loop completed without a review");
+            return;
+        }
+        Err(e) => {
+            warn!(exec_id = %exec.id, error = %e, "Failed to load review");
+            return;
+        }
+    };
+    match save_artifact(repo_root, &exec.id, &review) {
+        Ok(path) => {
+            info!(exec_id = %exec.id, target = %review.target, comments = %review.counts(), "Review stored");
+            exec.set_artifact(&path);
+        }
+        Err(e) => warn!(exec_id = %exec.id, error = %e, "Failed to store review"),
+    }
+
+    let Some(github) = config.map(|c| &c.github).filter(|g| g.repo.is_some()) else {
+        return;
+    };
+    match post_to_github(&reqwest::Client::new(), github, &review).await {
+        Ok(url) => info!(exec_id = %exec.id, %url, "Review posted to GitHub"),
+        Err(e) => warn!(exec_id = %exec.id, error = %e, "Failed to post review to GitHub"),
+    }
+}
+
+/// Why the latest review of `exec_id` holds its merge, if it has blocking comments
+///
+/// Unreviewed executions merge as usual; unreadable reviews are logged and do not block.
+fn review_hold(exec_id: &str, repo_root: &std::path::Path) -> Option<String> {
+    debug!(%exec_id, "review_hold: called");
+    match latest_review(repo_root, exec_id) {
+        Ok(review) => review?.merge_hold(),
+        Err(e) => {
+            warn!(exec_id = %exec_id, error = %e, "Failed to read reviews, continuing with merge");
+            None
+        }
+    }
+}
+
+/// Block the execution for human review after a blocking hook, a policy or a review held the merge
+async fn block_for_review(state: &StateManager, engine: &LoopEngine, exec_id: &str, reason: String) -> LoopTaskResult {
+    debug!(%exec_id, %reason, "block_for_review: called");
+    if let Ok(Some(mut exec)) = state.get_execution(exec_id).await {
+        exec.set_status(LoopExecutionStatus::Blocked);
+        exec.set_error(&reason);
+        exec.iteration = engine.current_iteration();
+        exec.progress = engine.get_progress();
+        let _ = state.update_execution(exec).await;
+    }
+    LoopTaskResult::Paused {
+        exec_id: exec_id.to_string(),
+        reason,
+    }
+}
+
+/// Run a loop task, then its `on-complete` or `on-fail` hooks
 async fn run_loop_task(
     mut engine: LoopEngine,
     state: StateManager,
     worktree_path: PathBuf,
     repo_root: PathBuf,
-    type_loader: Arc<RwLock<LoopLoader>>,
+    cascade: CascadeHandler,
     loop_type: String,
+    gates: MergeGates,
+) -> LoopTaskResult {
+    let result = drive_loop_task(&mut engine, state, worktree_path, repo_root, cascade, loop_type, gates).await;
+    // The outcome is settled: these hooks can only warn
+    match &result {
+        LoopTaskResult::Complete { .. } => {
+            engine.run_hooks(HookEvent::OnComplete, None, None).await;
+        }
+        LoopTaskResult::Failed { reason, .. } => {
+            engine.run_hooks(HookEvent::OnFail, None, Some(reason)).await;
+        }
+        LoopTaskResult::Stopped { .. } | LoopTaskResult::Paused { .. } => {}
+    }
+    result
+}
+
+/// Run the loop and handle completion
+///
+/// On successful completion, merges the worktree branch to main and triggers cascade.
+async fn drive_loop_task(
+    engine: &mut LoopEngine,
+    state: StateManager,
+    worktree_path: PathBuf,
+    repo_root: PathBuf,
+    cascade: CascadeHandler,
+    loop_type: String,
+    gates: MergeGates,
 ) -> LoopTaskResult {
     let exec_id = engine.exec_id.clone();
-    debug!(exec_id = %exec_id, %loop_type, "run_loop_task: called");
+    debug!(exec_id = %exec_id, %loop_type, "drive_loop_task: called");
 
     match engine.run().await {
         Ok(crate::r#loop::IterationResult::Complete { iterations }) => {
@@ -869,9 +2169,9 @@ async fn run_loop_task(
                 .and_then(|e| e.context.get("title").and_then(|v| v.as_str()).map(String::from))
                 .unwrap_or_else(|| "Completed work".to_string());
 
-            // Only merge for code-producing loops (phase, ralph)
+            // Only merge for code-producing loops (phase, ralph, security-review)
             // Plan and Spec loops produce markdown docs, not code to merge
-            let should_merge = matches!(loop_type.as_str(), "phase" | "ralph");
+            let should_merge = matches!(loop_type.as_str(), "phase" | "ralph" | "security-review");
 
             if !should_merge {
                 debug!(exec_id = %exec_id, loop_type = %loop_type, "run_loop_task: skipping merge for doc loop");
@@ -881,18 +2181,74 @@ async fn run_loop_task(
                     exec.set_artifact_status("complete");
                     exec.iteration = engine.current_iteration();
                     exec.progress = engine.get_progress();
+                    if loop_type == REVIEW_TYPE {
+                        publish_review(gates.review.as_deref(), &mut exec, &worktree_path, &repo_root).await;
+                    }
                     let _ = state.update_execution(exec.clone()).await;
 
-                    // Trigger cascade: create Loop record and spawn child executions
-                    debug!(exec_id = %exec_id, "run_loop_task: triggering cascade (no merge)");
-                    trigger_cascade(&state, &type_loader, &exec, &loop_type).await;
+                    if loop_type == DEPS_UPGRADE_TYPE {
+                        // One upgrade loop per dependency instead of the type hierarchy
+                        debug!(exec_id = %exec_id, "run_loop_task: creating dependency upgrade loops");
+                        fan_out_dep_upgrades(&state, &cascade, &exec, &worktree_path, &repo_root).await;
+                    } else {
+                        // Trigger cascade: create Loop record and spawn child executions
+                        debug!(exec_id = %exec_id, "run_loop_task: triggering cascade (no merge)");
+                        trigger_cascade(&state, &cascade, &exec, &loop_type).await;
+                    }
                 }
                 return LoopTaskResult::Complete { exec_id, iterations };
             }
 
+            // Score the work before merging; low scores wait for human review
+            if let Some(ref evaluator) = gates.evaluator
+                && let Some(result) =
+                    evaluate_before_merge(evaluator, &state, engine, &exec_id, &repo_root, &worktree_path).await
+            {
+                debug!(exec_id = %exec_id, "run_loop_task: held for review by evaluation");
+                return result;
+            }
+
+            // Score the diff; risky changes wait for a human approval
+            if let Some(ref risk) = gates.risk
+                && let Some(result) = gate_on_risk(risk, &state, engine, &exec_id, &worktree_path).await
+            {
+                debug!(exec_id = %exec_id, "run_loop_task: held for approval by risk score");
+                return result;
+            }
+
+            // Blocking comments in the latest review hold the merge
+            if gates.review.as_ref().is_some_and(|review| review.gate_merges)
+                && let Some(reason) = review_hold(&exec_id, &repo_root)
+            {
+                debug!(exec_id = %exec_id, "run_loop_task: held by review comments");
+                return block_for_review(&state, engine, &exec_id, reason).await;
+            }
+
+            // Pre-merge hooks (license checks, artifact builds, ...) may hold the merge
+            if let Some(reason) = engine.run_hooks(HookEvent::PreMerge, None, None).await {
+                debug!(exec_id = %exec_id, "run_loop_task: held by pre-merge hook");
+                return block_for_review(&state, engine, &exec_id, reason).await;
+            }
+
+            // So may a policy
+            if let Some(reason) = engine.merge_denial().await {
+                debug!(exec_id = %exec_id, "run_loop_task: merge denied by policy");
+                return block_for_review(&state, engine, &exec_id, reason).await;
+            }
+
             // Merge to main before marking complete (for code loops)
             debug!(exec_id = %exec_id, "run_loop_task: merging to main");
-            match merge_to_main(&repo_root, &worktree_path, &exec_id, &spec_title).await {
+            // Conventional-commit message and changelog entry, if the loop type asks for them
+            let merge_message = engine.merge_message(&spec_title).await;
+            match merge_to_main(
+                &repo_root,
+                &worktree_path,
+                &exec_id,
+                &spec_title,
+                merge_message.as_ref(),
+            )
+            .await
+            {
                 Ok(MergeResult::Success) => {
                     debug!(exec_id = %exec_id, "run_loop_task: merge successful");
                     info!(exec_id = %exec_id, "Successfully merged to main");
@@ -906,7 +2262,7 @@ async fn run_loop_task(
 
                         // Trigger cascade: create Loop record and spawn child executions
                         debug!(exec_id = %exec_id, "run_loop_task: triggering cascade");
-                        trigger_cascade(&state, &type_loader, &exec, &loop_type).await;
+                        trigger_cascade(&state, &cascade, &exec, &loop_type).await;
                     }
                     LoopTaskResult::Complete { exec_id, iterations }
                 }
@@ -962,24 +2318,107 @@ async fn run_loop_task(
                 }
             }
         }
-        Ok(crate::r#loop::IterationResult::Interrupted { reason: _ }) => {
+        Ok(crate::r#loop::IterationResult::Stuck {
+            action,
+            unchanged_iterations,
+        }) => {
+            debug!(exec_id = %exec_id, %action, unchanged_iterations, "run_loop_task: loop stuck");
+            let (status, reason) = match action {
+                StuckAction::Escalate => (
+                    LoopExecutionStatus::Blocked,
+                    format!(
+                        "Stuck: no progress for {} iterations, awaiting human review",
+                        unchanged_iterations
+                    ),
+                ),
+                _ => (
+                    LoopExecutionStatus::Paused,
+                    format!("Stuck: no progress for {} iterations, paused", unchanged_iterations),
+                ),
+            };
+            if let Ok(Some(mut exec)) = state.get_execution(&exec_id).await {
+                exec.set_status(status);
+                exec.set_error(&reason);
+                exec.iteration = engine.current_iteration();
+                exec.progress = engine.get_progress();
+                let _ = state.update_execution(exec).await;
+            }
+            LoopTaskResult::Paused { exec_id, reason }
+        }
+        Ok(crate::r#loop::IterationResult::Blocked { reason }) => {
+            debug!(exec_id = %exec_id, %reason, "run_loop_task: loop blocked by a hook");
+            block_for_review(&state, engine, &exec_id, reason).await
+        }
+        Ok(crate::r#loop::IterationResult::Interrupted { reason }) => {
             debug!(exec_id = %exec_id, "run_loop_task: loop interrupted");
-            // Update state to stopped with progress (artifact status stays draft)
+            // Update state to stopped with progress (artifact status stays draft).
+            // A parked execution was stopped to wait for its wake conditions: keep it parked.
+            // One stopped for maintenance keeps the status maintenance gave it: paused, or
+            // running again if maintenance was turned off first (the next poll respawns it).
+            let maintenance = reason.ends_with(MAINTENANCE_STOP_REASON);
+            let mut parked = false;
             if let Ok(Some(mut exec)) = state.get_execution(&exec_id).await {
-                exec.set_status(LoopExecutionStatus::Stopped);
+                parked = exec.status == LoopExecutionStatus::Parked;
+                if !parked && !maintenance {
+                    exec.set_status(LoopExecutionStatus::Stopped);
+                }
                 exec.iteration = engine.current_iteration();
                 exec.progress = engine.get_progress();
                 let _ = state.update_execution(exec).await;
             }
-            LoopTaskResult::Stopped { exec_id }
+            if parked {
+                debug!(exec_id = %exec_id, "run_loop_task: loop parked");
+                LoopTaskResult::Paused {
+                    exec_id,
+                    reason: "Parked until a wake condition holds".to_string(),
+                }
+            } else if maintenance {
+                debug!(exec_id = %exec_id, "run_loop_task: loop paused for maintenance");
+                LoopTaskResult::Paused {
+                    exec_id,
+                    reason: MAINTENANCE_STOP_REASON.to_string(),
+                }
+            } else {
+                LoopTaskResult::Stopped { exec_id }
+            }
         }
-        Ok(crate::r#loop::IterationResult::Error { message, .. }) => {
-            debug!(exec_id = %exec_id, %message, "run_loop_task: loop error");
+        Ok(crate::r#loop::IterationResult::Error { message, code, .. }) if code.as_deref() == Some(AUTH_ERROR_CODE) => {
+            debug!(exec_id = %exec_id, %message, "run_loop_task: API key rejected");
+            // Every execution fails alike until the key is fixed: pause rather than fail
+            if let Ok(Some(mut exec)) = state.get_execution(&exec_id).await {
+                exec.set_status(LoopExecutionStatus::Paused);
+                exec.set_error_with_code(&message, code);
+                exec.iteration = engine.current_iteration();
+                exec.progress = engine.get_progress();
+                let _ = state.update_execution(exec).await;
+            }
+            LoopTaskResult::Paused {
+                exec_id,
+                reason: CREDENTIALS_PAUSE_REASON.to_string(),
+            }
+        }
+        Ok(crate::r#loop::IterationResult::ProviderUnavailable { message }) => {
+            debug!(exec_id = %exec_id, %message, "run_loop_task: provider unavailable");
+            // Don't burn iterations during an outage: wait for the circuit breaker to close
+            if let Ok(Some(mut exec)) = state.get_execution(&exec_id).await {
+                exec.set_status(LoopExecutionStatus::WaitingProvider);
+                exec.set_error(&message);
+                exec.iteration = engine.current_iteration();
+                exec.progress = engine.get_progress();
+                let _ = state.update_execution(exec).await;
+            }
+            LoopTaskResult::Paused {
+                exec_id,
+                reason: PROVIDER_WAIT_REASON.to_string(),
+            }
+        }
+        Ok(crate::r#loop::IterationResult::Error { message, code, .. }) => {
+            debug!(exec_id = %exec_id, %message, ?code, "run_loop_task: loop error");
             // Update state to failed with progress
             if let Ok(Some(mut exec)) = state.get_execution(&exec_id).await {
                 exec.set_status(LoopExecutionStatus::Failed);
                 exec.set_artifact_status("failed");
-                exec.set_error(&message);
+                exec.set_error_with_code(&message, code);
                 exec.iteration = engine.current_iteration();
                 exec.progress = engine.get_progress();
                 let _ = state.update_execution(exec).await;
@@ -1011,7 +2450,7 @@ async fn run_loop_task(
             if let Ok(Some(mut exec)) = state.get_execution(&exec_id).await {
                 exec.set_status(LoopExecutionStatus::Failed);
                 exec.set_artifact_status("failed");
-                exec.set_error(e.to_string());
+                exec.set_error_with_code(e.to_string(), code_of(&e));
                 exec.iteration = engine.current_iteration();
                 exec.progress = engine.get_progress();
                 let _ = state.update_execution(exec).await;
@@ -1028,12 +2467,7 @@ async fn run_loop_task(
 ///
 /// Creates a Loop record with status Ready and uses CascadeHandler to spawn child executions.
 /// The child executions will be created in Pending state and picked up by poll_and_spawn.
-async fn trigger_cascade(
-    state: &StateManager,
-    type_loader: &Arc<RwLock<LoopLoader>>,
-    exec: &LoopExecution,
-    loop_type: &str,
-) {
+async fn trigger_cascade(state: &StateManager, cascade: &CascadeHandler, exec: &LoopExecution, loop_type: &str) {
     debug!(exec_id = %exec.id, %loop_type, "trigger_cascade: called");
     // Get the execution title for the Loop record
     let title = exec.context.get("title").and_then(|v| v.as_str()).unwrap_or(&exec.id);
@@ -1043,8 +2477,8 @@ async fn trigger_cascade(
     let output_file = exec.context.get("output-file").and_then(|v| v.as_str());
 
     // Create a Loop record representing the completed work
-    let mut loop_record = Loop::new(loop_type, title);
-    loop_record.set_status(LoopStatus::Ready);
+    let mut loop_record = cascade.new_record(loop_type, title);
+    loop_record.status = LoopStatus::Ready;
 
     // Link to output file if present
     if let Some(file) = output_file {
@@ -1053,9 +2487,7 @@ async fn trigger_cascade(
     }
 
     // Store the execution ID for reference in context
-    if let Some(obj) = loop_record.context.as_object_mut() {
-        obj.insert("exec_id".to_string(), serde_json::json!(exec.id));
-    }
+    loop_record.set_context(serde_json::json!({ "exec_id": exec.id }));
 
     // Create the Loop record in state
     debug!(exec_id = %exec.id, loop_id = %loop_record.id, "trigger_cascade: creating loop record");
@@ -1072,9 +2504,13 @@ async fn trigger_cascade(
         "Created Loop record for cascade"
     );
 
+    // Plans and specs also get their acceptance criteria and decomposition links
+    if let Err(e) = cascade.record_typed(&loop_record, exec).await {
+        warn!(loop_id = %loop_record.id, error = %e, "Failed to update typed plan/spec record");
+    }
+
     // Create cascade handler and trigger child execution creation
     debug!(exec_id = %exec.id, "trigger_cascade: calling on_loop_ready");
-    let cascade = CascadeHandler::new(Arc::new(state.clone()), type_loader.clone());
     match cascade.on_loop_ready(&loop_record, &exec.id).await {
         Ok(children) => {
             if children.is_empty() {
@@ -1098,6 +2534,72 @@ async fn trigger_cascade(
     debug!(exec_id = %exec.id, "trigger_cascade: complete");
 }
 
+/// Create a `dep-bump` execution for every dependency a `deps-upgrade` loop saved
+///
+/// The list is read from the loop's worktree before it is removed. With
+/// nothing to upgrade the summary is written right away.
+async fn fan_out_dep_upgrades(
+    state: &StateManager,
+    cascade: &CascadeHandler,
+    exec: &LoopExecution,
+    worktree_path: &std::path::Path,
+    repo_root: &std::path::Path,
+) {
+    debug!(exec_id = %exec.id, "fan_out_dep_upgrades: called");
+    let outdated = match load_outdated(worktree_path) {
+        Ok(outdated) => outdated.unwrap_or_default(),
+        Err(e) => {
+            error!(exec_id = %exec.id, error = %e, "Failed to read outdated dependencies");
+            return;
+        }
+    };
+    match cascade.on_outdated_listed(exec, &outdated).await {
+        Ok(children) => info!(exec_id = %exec.id, child_count = children.len(), "Created dependency upgrade loops"),
+        Err(e) => error!(exec_id = %exec.id, error = %e, "Failed to create dependency upgrade loops"),
+    }
+    if outdated.is_empty() {
+        join_dep_upgrades(state, cascade, repo_root, &exec.id).await;
+    }
+}
+
+/// Write a `deps-upgrade` run's summary table once all its upgrades have finished
+///
+/// The table goes to `.taskdaemon/artifacts/deps/{id}/summary.md` in the repo
+/// root and becomes the parent execution's artifact.
+async fn join_dep_upgrades(
+    state: &StateManager,
+    cascade: &CascadeHandler,
+    repo_root: &std::path::Path,
+    parent_id: &str,
+) {
+    debug!(%parent_id, "join_dep_upgrades: called");
+    let summary = match cascade.join_dep_upgrades(parent_id).await {
+        Ok(Some(summary)) => summary,
+        Ok(None) => return,
+        Err(e) => {
+            warn!(%parent_id, error = %e, "Failed to join dependency upgrades");
+            return;
+        }
+    };
+
+    let relative = format!(".taskdaemon/artifacts/deps/{}/summary.md", parent_id);
+    let path = repo_root.join(&relative);
+    let written = match path.parent() {
+        Some(dir) => tokio::fs::create_dir_all(dir).await.is_ok() && tokio::fs::write(&path, &summary).await.is_ok(),
+        None => false,
+    };
+    if !written {
+        warn!(%parent_id, path = %path.display(), "Failed to write dependency upgrade summary");
+        return;
+    }
+
+    if let Ok(Some(mut parent)) = state.get_execution(parent_id).await {
+        parent.set_artifact(&relative);
+        let _ = state.update_execution(parent).await;
+    }
+    info!(%parent_id, path = %relative, "Dependency upgrades finished, summary written");
+}
+
 /// Validate a dependency graph for cycles
 ///
 /// Uses DFS to detect cycles. Returns Ok(()) if no cycles, Err with cycle info if found.
@@ -1123,6 +2625,33 @@ pub fn validate_dependency_graph<'a>(loops: impl IntoIterator<Item = &'a Loop>)
     Ok(())
 }
 
+/// Check the per-type limits from loop YAML against the running executions
+///
+/// `running` holds the loop type of every execution that has a task. Returns
+/// the reason an execution of `loop_type` must wait, or None if it may start.
+/// The global `max_concurrent_tasks` limit is enforced separately.
+fn concurrency_blocker(configs: &HashMap<String, LoopConfig>, loop_type: &str, running: &[&str]) -> Option<String> {
+    let config = configs.get(loop_type)?;
+
+    if let Some(max) = config.max_concurrent {
+        let count = running.iter().filter(|t| **t == loop_type).count();
+        if count >= max {
+            return Some(format!("{} of {} '{}' loops running", count, max, loop_type));
+        }
+    }
+
+    if let Some(group) = &config.exclusive_group {
+        let holder = running
+            .iter()
+            .find(|t| configs.get(**t).and_then(|c| c.exclusive_group.as_ref()) == Some(group));
+        if let Some(holder) = holder {
+            return Some(format!("'{}' loop holds exclusive group '{}'", holder, group));
+        }
+    }
+
+    None
+}
+
 /// DFS helper for cycle detection
 fn has_cycle_dfs<'a>(
     node: &'a str,
@@ -1298,6 +2827,26 @@ fn convert_to_state_event(event: &LoopEvent) -> Option<StateEvent> {
             success: *success,
             total_iterations: *total_iterations,
         }),
+        LoopEvent::DeadlockDetected { execution_id, cycle } => Some(StateEvent::DeadlockDetected {
+            execution_id: execution_id.clone(),
+            cycle: cycle.clone(),
+        }),
+        LoopEvent::PathConflict {
+            execution_id,
+            path,
+            held_by,
+        } => Some(StateEvent::PathConflict {
+            execution_id: execution_id.clone(),
+            path: path.clone(),
+            held_by: held_by.clone(),
+        }),
+        LoopEvent::ResourcePressure { exceeded, .. } => Some(StateEvent::ResourcePressure {
+            exceeded: exceeded.clone(),
+        }),
+        LoopEvent::ProviderCircuit { open, resumed, .. } => Some(StateEvent::ProviderCircuit {
+            open: *open,
+            resumed: *resumed,
+        }),
         // Other events don't need to be forwarded to TUI
         _ => None,
     }
@@ -1447,6 +2996,205 @@ mod tests {
         assert_eq!(sorted.len(), 3);
     }
 
+    #[test]
+    fn test_concurrency_blocker_limits_and_groups() {
+        let config = |max: Option<usize>, group: Option<&str>| LoopConfig {
+            max_concurrent: max,
+            exclusive_group: group.map(String::from),
+            ..Default::default()
+        };
+        let configs = HashMap::from([
+            ("migration".to_string(), config(Some(1), Some("schema"))),
+            ("seed".to_string(), config(None, Some("schema"))),
+            ("docs".to_string(), config(Some(2), None)),
+        ]);
+
+        assert!(concurrency_blocker(&configs, "migration", &[]).is_none());
+        assert!(concurrency_blocker(&configs, "migration", &["migration"]).is_some());
+        assert!(concurrency_blocker(&configs, "docs", &["docs", "migration"]).is_none());
+        assert!(concurrency_blocker(&configs, "docs", &["docs", "docs"]).is_some());
+
+        // Different types in one group exclude each other
+        let reason = concurrency_blocker(&configs, "migration", &["seed"]).unwrap();
+        assert!(reason.contains("schema"), "{reason}");
+        assert!(concurrency_blocker(&configs, "seed", &["docs", "migration"]).is_some());
+
+        // Types without config are only bound by the global limit
+        assert!(concurrency_blocker(&configs, "ralph", &["ralph", "ralph"]).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_pauses_and_resumes_running_executions() {
+        let temp = tempfile::tempdir().unwrap();
+        let state = StateManager::spawn(temp.path().join("store")).unwrap();
+        let (coordinator_tx, mut coordinator_rx) = mpsc::channel(16);
+        let type_loader = Arc::new(RwLock::new(
+            LoopLoader::new(&crate::config::LoopsConfig {
+                paths: vec!["builtin".to_string()],
+                ..Default::default()
+            })
+            .unwrap(),
+        ));
+        let maintenance_file = temp.path().join("taskdaemon.maintenance");
+        let mut manager = TaskManager::new(
+            TaskManagerConfig {
+                repo_root: temp.path().to_path_buf(),
+                ..Default::default()
+            },
+            coordinator_tx,
+            Scheduler::new(Default::default()),
+            Arc::new(crate::llm::client::mock::MockLlmClient::new(vec![])),
+            state.clone(),
+            HashMap::new(),
+            type_loader,
+        )
+        .with_maintenance_file(maintenance_file.clone());
+
+        let mut exec = LoopExecution::new("ralph", "refactor");
+        exec.set_status(LoopExecutionStatus::Running);
+        state.create_loop_execution(exec.clone()).await.unwrap();
+        // Stands in for the engine task, which would stop at its next iteration boundary
+        let id = exec.id.clone();
+        manager.tasks.insert(
+            exec.id.clone(),
+            tokio::spawn(async move { TaskResult::Stopped { exec_id: id } }),
+        );
+
+        let paused = manager.enter_maintenance().await.unwrap();
+        assert_eq!(paused, vec![exec.id.clone()]);
+        assert!(manager.in_maintenance());
+        assert_eq!(MaintenanceState::load(&maintenance_file).unwrap().paused, paused);
+        let status = state.get_execution(&exec.id).await.unwrap().unwrap().status;
+        assert_eq!(status, LoopExecutionStatus::Paused);
+        assert!(matches!(
+            coordinator_rx.try_recv(),
+            Ok(CoordRequest::Stop { reason, .. }) if reason == MAINTENANCE_STOP_REASON
+        ));
+
+        let resumed = manager.exit_maintenance().await.unwrap();
+        assert_eq!(resumed, paused);
+        assert!(!manager.in_maintenance());
+        assert!(MaintenanceState::load(&maintenance_file).is_none());
+        let status = state.get_execution(&exec.id).await.unwrap().unwrap().status;
+        assert_eq!(status, LoopExecutionStatus::Running);
+    }
+
+    #[tokio::test]
+    async fn test_credential_breaker_pauses_until_secrets_reload() {
+        let temp = tempfile::tempdir().unwrap();
+        let state = StateManager::spawn(temp.path().join("store")).unwrap();
+        let (coordinator_tx, _coordinator_rx) = mpsc::channel(16);
+        let type_loader = Arc::new(RwLock::new(
+            LoopLoader::new(&crate::config::LoopsConfig {
+                paths: vec!["builtin".to_string()],
+                ..Default::default()
+            })
+            .unwrap(),
+        ));
+        let mut manager = TaskManager::new(
+            TaskManagerConfig {
+                repo_root: temp.path().to_path_buf(),
+                ..Default::default()
+            },
+            coordinator_tx,
+            Scheduler::new(Default::default()),
+            Arc::new(crate::llm::client::mock::MockLlmClient::new(vec![])),
+            state.clone(),
+            HashMap::new(),
+            type_loader,
+        );
+
+        // Stands in for an engine task that hit a 401
+        let mut exec = LoopExecution::new("ralph", "refactor");
+        exec.set_status(LoopExecutionStatus::Paused);
+        state.create_loop_execution(exec.clone()).await.unwrap();
+        let id = exec.id.clone();
+        manager.tasks.insert(
+            exec.id.clone(),
+            tokio::spawn(async move {
+                TaskResult::Paused {
+                    exec_id: id,
+                    reason: CREDENTIALS_PAUSE_REASON.to_string(),
+                }
+            }),
+        );
+        while !manager.tasks.values().all(|h| h.is_finished()) {
+            tokio::task::yield_now().await;
+        }
+        manager.reap_completed_tasks().await;
+        assert!(manager.credentials_rejected());
+
+        // The mock's key never changes: only a forced reload closes the breaker
+        assert_eq!(manager.reload_secrets(false).await.unwrap(), (false, Vec::new()));
+        assert!(manager.credentials_rejected());
+        let (changed, resumed) = manager.reload_secrets(true).await.unwrap();
+        assert!(!changed);
+        assert_eq!(resumed, vec![exec.id.clone()]);
+        assert!(!manager.credentials_rejected());
+        let status = state.get_execution(&exec.id).await.unwrap().unwrap().status;
+        assert_eq!(status, LoopExecutionStatus::Running);
+    }
+
+    #[tokio::test]
+    async fn test_provider_circuit_parks_until_provider_answers() {
+        let temp = tempfile::tempdir().unwrap();
+        let state = StateManager::spawn(temp.path().join("store")).unwrap();
+        let (coordinator_tx, _coordinator_rx) = mpsc::channel(16);
+        let type_loader = Arc::new(RwLock::new(
+            LoopLoader::new(&crate::config::LoopsConfig {
+                paths: vec!["builtin".to_string()],
+                ..Default::default()
+            })
+            .unwrap(),
+        ));
+        let mut manager = TaskManager::new(
+            TaskManagerConfig {
+                repo_root: temp.path().to_path_buf(),
+                ..Default::default()
+            },
+            coordinator_tx,
+            Scheduler::new(crate::scheduler::SchedulerConfig {
+                breaker_threshold: 2,
+                ..Default::default()
+            }),
+            Arc::new(crate::llm::client::mock::MockLlmClient::new(vec![])),
+            state.clone(),
+            HashMap::new(),
+            type_loader,
+        );
+        let mut events = manager.event_bus.subscribe();
+
+        // Stands in for an engine that stopped on the open circuit
+        let mut exec = LoopExecution::new("ralph", "refactor");
+        exec.set_status(LoopExecutionStatus::WaitingProvider);
+        state.create_loop_execution(exec.clone()).await.unwrap();
+        let scheduler = manager.scheduler();
+        scheduler.record_provider_error().await;
+        scheduler.record_provider_error().await;
+        assert!(scheduler.provider_circuit_open().await);
+
+        // The first check announces the outage; the next probes the provider, which answers
+        manager.check_provider_circuit().await.unwrap();
+        assert!(manager.provider_outage);
+        assert!(matches!(events.try_recv(), Ok(LoopEvent::ProviderCircuit { open: true, .. })));
+        let status = state.get_execution(&exec.id).await.unwrap().unwrap().status;
+        assert_eq!(status, LoopExecutionStatus::WaitingProvider);
+
+        manager.check_provider_circuit().await.unwrap();
+        assert!(!manager.provider_outage);
+        assert!(!scheduler.provider_circuit_open().await);
+        assert!(matches!(
+            events.try_recv(),
+            Ok(LoopEvent::ProviderCircuit {
+                open: false,
+                resumed: 1,
+                ..
+            })
+        ));
+        let status = state.get_execution(&exec.id).await.unwrap().unwrap().status;
+        assert_ne!(status, LoopExecutionStatus::WaitingProvider);
+    }
+
     #[test]
     fn test_task_manager_config_default() {
         let config = TaskManagerConfig::default();
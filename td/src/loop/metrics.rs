@@ -5,6 +5,7 @@
 //! - Success/failure rates
 //! - Tool usage statistics
 //! - LLM token consumption
+//! - Per-test fail -> pass transitions from validation reports
 
 use std::collections::HashMap;
 use std::sync::RwLock;
@@ -14,6 +15,9 @@ use std::time::{Duration, Instant};
 use serde::{Deserialize, Serialize};
 use tracing::debug;
 
+use super::reporter::TestReport;
+use crate::domain::DailyRollup;
+
 /// Aggregate metrics for all loops
 #[derive(Debug, Default)]
 pub struct LoopMetrics {
@@ -83,6 +87,33 @@ pub struct LoopStats {
     pub ended_at: i64,
     /// Final status
     pub final_status: String,
+    /// Latest known outcome for each test seen in validation reports
+    #[serde(default)]
+    pub test_outcomes: HashMap<String, TestOutcome>,
+    /// Outcome changes between iterations, in order
+    #[serde(default)]
+    pub test_transitions: Vec<TestTransition>,
+}
+
+/// Outcome of a single test in a validation run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestOutcome {
+    Passed,
+    Failed,
+}
+
+/// A test whose outcome changed between iterations
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestTransition {
+    /// Test name
+    pub test: String,
+    /// Iteration in which the new outcome was observed
+    pub iteration: u32,
+    /// Previous outcome
+    pub from: TestOutcome,
+    /// New outcome
+    pub to: TestOutcome,
 }
 
 impl LoopStats {
@@ -120,6 +151,71 @@ impl LoopStats {
         *self.tool_calls.entry(tool_name.to_string()).or_default() += 1;
     }
 
+    /// Record the outcome of a validation test report
+    ///
+    /// Tests that failed previously but are absent from this report are counted
+    /// as passing: runners only list failures in their summaries, and a test that
+    /// was fixed by deleting or renaming it is no longer failing either.
+    pub fn record_test_report(&mut self, iteration: u32, report: &TestReport) {
+        debug!(exec_id = %self.exec_id, iteration, passed = report.passed.len(), failed = report.failed.len(), "LoopStats::record_test_report: called");
+        let mut current: HashMap<&str, TestOutcome> = report
+            .passed
+            .iter()
+            .map(|name| (name.as_str(), TestOutcome::Passed))
+            .collect();
+        for test in &report.failed {
+            current.insert(test.name.as_str(), TestOutcome::Failed);
+        }
+
+        for (name, previous) in &self.test_outcomes {
+            if *previous == TestOutcome::Failed && !current.contains_key(name.as_str()) {
+                current.insert(name.as_str(), TestOutcome::Passed);
+            }
+        }
+
+        let mut transitions = Vec::new();
+        for (name, outcome) in &current {
+            if let Some(previous) = self.test_outcomes.get(*name)
+                && previous != outcome
+            {
+                transitions.push(TestTransition {
+                    test: name.to_string(),
+                    iteration,
+                    from: *previous,
+                    to: *outcome,
+                });
+            }
+        }
+        transitions.sort_by(|a, b| a.test.cmp(&b.test));
+        debug!(exec_id = %self.exec_id, transitions = transitions.len(), "record_test_report: computed transitions");
+
+        let updates: Vec<(String, TestOutcome)> = current.into_iter().map(|(n, o)| (n.to_string(), o)).collect();
+        self.test_outcomes.extend(updates);
+        self.test_transitions.extend(transitions);
+    }
+
+    /// Tests that went from failing to passing during this loop
+    pub fn fixed_tests(&self) -> Vec<&TestTransition> {
+        debug!(exec_id = %self.exec_id, "LoopStats::fixed_tests: called");
+        self.test_transitions
+            .iter()
+            .filter(|t| t.from == TestOutcome::Failed && t.to == TestOutcome::Passed)
+            .collect()
+    }
+
+    /// Tests currently failing
+    pub fn failing_tests(&self) -> Vec<&str> {
+        debug!(exec_id = %self.exec_id, "LoopStats::failing_tests: called");
+        let mut failing: Vec<&str> = self
+            .test_outcomes
+            .iter()
+            .filter(|(_, outcome)| **outcome == TestOutcome::Failed)
+            .map(|(name, _)| name.as_str())
+            .collect();
+        failing.sort_unstable();
+        failing
+    }
+
     /// Mark completion
     pub fn mark_complete(&mut self, status: &str) {
         debug!(exec_id = %self.exec_id, %status, "LoopStats::mark_complete: called");
@@ -236,6 +332,19 @@ impl LoopMetrics {
         self.global.total_tool_calls.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// Record a parsed validation test report for a loop
+    pub fn record_test_report(&self, exec_id: &str, iteration: u32, report: &TestReport) {
+        debug!(%exec_id, iteration, "LoopMetrics::record_test_report: called");
+        if let Ok(mut loops) = self.loops.write()
+            && let Some(stats) = loops.get_mut(exec_id)
+        {
+            debug!(%exec_id, "record_test_report: recording to stats");
+            stats.record_test_report(iteration, report);
+        } else {
+            debug!(%exec_id, "record_test_report: loop not found or lock failed");
+        }
+    }
+
     /// Mark a loop as complete
     pub fn complete_loop(&self, exec_id: &str, status: &str) {
         debug!(%exec_id, %status, "LoopMetrics::complete_loop: called");
@@ -392,6 +501,170 @@ impl IterationTimer {
     }
 }
 
+/// Persisted per-day metrics over a window, for `td metrics --history`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsHistory {
+    /// Loop type the history is limited to (None for all types)
+    pub loop_type: Option<String>,
+    /// First day of the window (YYYY-MM-DD)
+    pub since: String,
+    /// Rollups with runs, one per day (loop types merged), oldest first
+    pub days: Vec<DailyRollup>,
+    /// Totals over the whole window
+    pub total: DailyRollup,
+    /// First half of the window's days compared to the second half
+    pub trend: Option<Trend>,
+}
+
+/// Earlier vs later totals within a history window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trend {
+    pub earlier: DailyRollup,
+    pub later: DailyRollup,
+}
+
+impl MetricsHistory {
+    /// Build the history from daily rollups (any loop types, any order)
+    pub fn from_rollups(loop_type: Option<&str>, since: &str, rollups: &[DailyRollup]) -> Self {
+        debug!(?loop_type, %since, count = rollups.len(), "MetricsHistory::from_rollups: called");
+        let label = loop_type.unwrap_or("all");
+        let mut by_day: std::collections::BTreeMap<&str, DailyRollup> = std::collections::BTreeMap::new();
+        for rollup in rollups.iter().filter(|r| r.runs > 0 && r.day.as_str() >= since) {
+            by_day
+                .entry(rollup.day.as_str())
+                .or_insert_with(|| DailyRollup::from_snapshots(&rollup.day, label, &[]))
+                .merge(rollup);
+        }
+        let days: Vec<DailyRollup> = by_day.into_values().collect();
+
+        let combine = |part: &[DailyRollup]| {
+            let mut total = DailyRollup::from_snapshots(since, label, &[]);
+            for day in part {
+                total.merge(day);
+            }
+            total
+        };
+        let total = combine(&days);
+        let trend = (days.len() >= 2).then(|| {
+            let (earlier, later) = days.split_at(days.len() / 2);
+            Trend {
+                earlier: DailyRollup {
+                    day: earlier[0].day.clone(),
+                    ..combine(earlier)
+                },
+                later: DailyRollup {
+                    day: later[0].day.clone(),
+                    ..combine(later)
+                },
+            }
+        });
+        debug!(
+            days = days.len(),
+            has_trend = trend.is_some(),
+            "MetricsHistory::from_rollups: built"
+        );
+        Self {
+            loop_type: loop_type.map(str::to_string),
+            since: since.to_string(),
+            days,
+            total,
+            trend,
+        }
+    }
+
+    /// Render as a text table with a trend summary
+    pub fn render(&self) -> String {
+        debug!(days = self.days.len(), "MetricsHistory::render: called");
+        use std::fmt::Write;
+        let rate = |r: &DailyRollup| {
+            r.success_rate()
+                .map_or("-".to_string(), |r| format!("{:.0}%", r * 100.0))
+        };
+        let iters = |r: &DailyRollup| r.mean_iterations().map_or("-".to_string(), |i| format!("{:.1}", i));
+        let cost = |r: &DailyRollup| r.mean_cost_usd().map_or("-".to_string(), |c| format!("${:.2}", c));
+
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "Metrics history for {} since {}",
+            self.loop_type.as_deref().unwrap_or("all loop types"),
+            self.since
+        );
+        let _ = writeln!(out);
+        if self.days.is_empty() {
+            let _ = writeln!(out, "No finished executions in this window.");
+            return out;
+        }
+
+        let _ = writeln!(
+            out,
+            "{:<12} {:>6} {:>8} {:>10} {:>10}",
+            "DAY", "RUNS", "SUCCESS", "MEAN ITER", "MEAN COST"
+        );
+        let rows = self.days.iter().map(|d| (d.day.as_str(), d));
+        for (label, day) in rows.chain(std::iter::once(("total", &self.total))) {
+            let _ = writeln!(
+                out,
+                "{:<12} {:>6} {:>8} {:>10} {:>10}",
+                label,
+                day.runs,
+                rate(day),
+                iters(day),
+                cost(day)
+            );
+        }
+
+        if let Some(trend) = &self.trend {
+            let (a, b) = (&trend.earlier, &trend.later);
+            let _ = writeln!(out);
+            let _ = writeln!(out, "Trend (from {} vs from {}):", a.day, b.day);
+            let delta = |x: Option<f64>, y: Option<f64>, fmt: &dyn Fn(f64) -> String| match (x, y) {
+                (Some(x), Some(y)) => format!("  ({}{})", if y >= x { "+" } else { "-" }, fmt((y - x).abs())),
+                _ => String::new(),
+            };
+            let _ = writeln!(
+                out,
+                "  Success rate     {} -> {}{}",
+                rate(a),
+                rate(b),
+                delta(a.success_rate(), b.success_rate(), &|d| format!("{:.0} pts", d * 100.0))
+            );
+            let _ = writeln!(
+                out,
+                "  Mean iterations  {} -> {}{}",
+                iters(a),
+                iters(b),
+                delta(a.mean_iterations(), b.mean_iterations(), &|d| format!("{:.1}", d))
+            );
+            let _ = writeln!(
+                out,
+                "  Mean cost        {} -> {}{}",
+                cost(a),
+                cost(b),
+                delta(a.mean_cost_usd(), b.mean_cost_usd(), &|d| format!("${:.2}", d))
+            );
+        }
+        out
+    }
+}
+
+/// Parse a `--history` window ("30d", "4w", or a bare number of days) into days
+pub fn parse_history_days(s: &str) -> Result<u32, String> {
+    debug!(%s, "parse_history_days: called");
+    let s = s.trim();
+    let invalid = || format!("Invalid history window '{}'. Use e.g. 30d, 4w or 30", s);
+    let (amount, multiplier) = match s.chars().last() {
+        Some('d') => (&s[..s.len() - 1], 1),
+        Some('w') => (&s[..s.len() - 1], 7),
+        _ => (s, 1),
+    };
+    let days = amount.parse::<u32>().map_err(|_| invalid())? * multiplier;
+    if days == 0 {
+        return Err(invalid());
+    }
+    Ok(days)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -438,6 +711,58 @@ mod tests {
         assert_eq!(stats.tool_calls.get("write"), Some(&1));
     }
 
+    fn report(passed: &[&str], failed: &[&str]) -> TestReport {
+        use crate::r#loop::reporter::{FailedTest, TestFramework};
+        TestReport {
+            framework: TestFramework::Cargo,
+            passed: passed.iter().map(|s| s.to_string()).collect(),
+            failed: failed
+                .iter()
+                .map(|s| FailedTest {
+                    name: s.to_string(),
+                    message: String::new(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_loop_stats_test_transitions() {
+        let mut stats = LoopStats::new("exec-1", "phase");
+        stats.record_test_report(1, &report(&["a"], &["b", "c"]));
+        assert!(stats.test_transitions.is_empty());
+        assert_eq!(stats.failing_tests(), vec!["b", "c"]);
+
+        // b fixed, a regressed
+        stats.record_test_report(2, &report(&["b"], &["a", "c"]));
+        assert_eq!(stats.test_transitions.len(), 2);
+        assert_eq!(stats.fixed_tests().len(), 1);
+        assert_eq!(stats.fixed_tests()[0].test, "b");
+        assert_eq!(stats.fixed_tests()[0].iteration, 2);
+        assert_eq!(stats.failing_tests(), vec!["a", "c"]);
+    }
+
+    #[test]
+    fn test_loop_stats_missing_failure_counts_as_fixed() {
+        let mut stats = LoopStats::new("exec-1", "phase");
+        stats.record_test_report(1, &report(&[], &["flaky"]));
+        stats.record_test_report(2, &report(&[], &[]));
+
+        assert_eq!(stats.fixed_tests().len(), 1);
+        assert!(stats.failing_tests().is_empty());
+    }
+
+    #[test]
+    fn test_metrics_record_test_report() {
+        let metrics = LoopMetrics::new();
+        metrics.start_loop("exec-1", "phase");
+        metrics.record_test_report("exec-1", 1, &report(&[], &["x"]));
+        metrics.record_test_report("exec-1", 2, &report(&["x"], &[]));
+
+        let stats = metrics.get_loop_stats("exec-1").unwrap();
+        assert_eq!(stats.fixed_tests().len(), 1);
+    }
+
     #[test]
     fn test_metrics_start_and_complete_loop() {
         let metrics = LoopMetrics::new();
@@ -487,4 +812,57 @@ mod tests {
         assert_eq!(stats.iterations, 1);
         assert!(stats.iteration_times_ms[0] >= 10);
     }
+
+    fn rollup(day: &str, loop_type: &str, complete: u64, failed: u64, iterations: u64, cost: f64) -> DailyRollup {
+        DailyRollup {
+            id: DailyRollup::id_for(day, loop_type),
+            day: day.to_string(),
+            loop_type: loop_type.to_string(),
+            runs: complete + failed,
+            complete,
+            failed,
+            total_iterations: iterations,
+            total_cost_usd: cost,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_metrics_history_trend() {
+        let rollups = vec![
+            rollup("2026-09-01", "implement", 1, 1, 10, 2.0),
+            rollup("2026-10-01", "implement", 1, 1, 10, 2.0),
+            rollup("2026-10-01", "plan", 1, 0, 2, 0.2),
+            rollup("2026-10-02", "implement", 2, 0, 6, 1.0),
+            rollup("2026-10-03", "implement", 3, 0, 6, 1.5),
+        ];
+        let history = MetricsHistory::from_rollups(Some("implement"), "2026-09-15", &rollups[..2]);
+        assert_eq!(history.days.len(), 1);
+        assert!(history.trend.is_none());
+
+        let history = MetricsHistory::from_rollups(None, "2026-09-15", &rollups);
+        assert_eq!(history.days.len(), 3);
+        assert_eq!(history.days[0].runs, 3);
+        assert_eq!(history.total.runs, 8);
+
+        let trend = history.trend.as_ref().unwrap();
+        assert_eq!(trend.earlier.day, "2026-10-01");
+        assert_eq!(trend.later.day, "2026-10-02");
+        assert_eq!(trend.earlier.success_rate(), Some(2.0 / 3.0));
+        assert_eq!(trend.later.success_rate(), Some(1.0));
+        assert_eq!(trend.later.mean_iterations(), Some(2.4));
+
+        let text = history.render();
+        assert!(text.contains("total"));
+        assert!(text.contains("+33 pts"));
+    }
+
+    #[test]
+    fn test_parse_history_days() {
+        assert_eq!(parse_history_days("30d"), Ok(30));
+        assert_eq!(parse_history_days("2w"), Ok(14));
+        assert_eq!(parse_history_days("7"), Ok(7));
+        assert!(parse_history_days("0d").is_err());
+        assert!(parse_history_days("month").is_err());
+    }
 }
@@ -7,25 +7,56 @@
 //! The ExploreTask provides a lighter-weight read-only exploration capability
 //! for investigating codebases without the full Ralph loop pattern.
 
+mod acceptance;
+mod budget;
 mod cascade;
+mod compose;
 mod config;
 mod engine;
+mod evaluator;
 mod explore;
 mod manager;
 mod metrics;
+mod package;
+mod preview;
+mod reporter;
+mod risk;
+mod rollback;
+mod stuck;
+mod template;
 mod type_loader;
 mod validation;
+mod wake;
+mod watchdog;
 
+pub use budget::{BudgetProgress, IterationBudget, PhaseBudget, PhaseStep};
 pub use cascade::CascadeHandler;
-pub use config::LoopConfig;
+pub use compose::{Composer, PROFILES_DIR};
+pub use config::{LoopConfig, PathLockMode};
 #[allow(unused_imports)]
-pub use engine::{IterationResult, LoopEngine, LoopStatus};
-pub use explore::{ExploreTask, generate_explore_id};
+pub use engine::{IterationResult, LoopEngine, LoopStatus, PromptPreview};
+pub use evaluator::Evaluator;
+pub use explore::{EXPLORE_DIR, ExploreTask, explore_artifact_path, generate_explore_id, render_explore_markdown};
 pub use manager::{
     LoopManager, LoopManagerConfig, LoopTaskResult, TaskManager, TaskManagerConfig, TaskResult, topological_sort,
     validate_dependency_graph,
 };
-pub use metrics::{GlobalSummary, IterationTimer, LoopMetrics, LoopStats, TypeMetrics};
-pub use type_loader::{LoopLoader, LoopType};
+pub use metrics::{
+    GlobalSummary, IterationTimer, LoopMetrics, LoopStats, MetricsHistory, TestOutcome, TestTransition, Trend,
+    TypeMetrics, parse_history_days,
+};
+pub use package::{
+    InstalledPackage, PACKAGE_LOCK, PACKAGE_MANIFEST, PackageManifest, PackageRegistry, PackageSource, PackageUpdate,
+    verify_package,
+};
+pub use preview::{PromptIteration, preview_prompt};
+pub use reporter::{FailedTest, TestFramework, TestReport};
+pub use risk::{assess_risk, assess_worktree_risk};
+pub use rollback::{Regression, RegressionTracker, RollbackPolicy, SnapshotPolicy, ValidationScore};
+pub use stuck::{DEFAULT_STEERING_PROMPT, ProgressMonitor, StuckAction, StuckDetection};
+pub use template::{ENGINE_VARIABLES, VariableSchema, VariableSpec, VariableType, validate_submission};
+pub use type_loader::{LoopLoader, LoopType, ReplCommandDef};
 #[allow(unused_imports)]
 pub use validation::ValidationResult;
+pub use wake::{condition_met, first_met, resolve_ref};
+pub use watchdog::{TimeoutAction, WatchdogPolicy, WatchdogTimeout};
@@ -0,0 +1,591 @@
+//! Loop type packages installed from git
+//!
+//! A package is a directory in a git repository holding one or more loop type
+//! YAML files, any prompt files and validation scripts they use, and a
+//! `package.yml` manifest:
+//!
+//! ```yaml
+//! name: security-review
+//! version: 1.2.0
+//! description: OWASP-focused review loop
+//! files:
+//!   security-review.yml: 3f1c...   # sha256 of each file
+//!   prompts/review.md: 9a0b...
+//!   scripts/check.sh: 77de...
+//! ```
+//!
+//! `td loops install github.com/org/td-loops//security-review` clones the
+//! repository, verifies every file against the manifest (and rejects files it
+//! doesn't list), and copies the package to `<loops dir>/<name>/`. The source,
+//! ref, commit, version and manifest checksum are recorded in
+//! `<loops dir>/packages.lock` so `td loops update` can re-fetch it.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use eyre::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::process::Command;
+use tracing::{debug, info};
+
+/// Manifest file at the root of every package
+pub const PACKAGE_MANIFEST: &str = "package.yml";
+
+/// Registry of installed packages, kept next to them in the loops directory
+pub const PACKAGE_LOCK: &str = "packages.lock";
+
+/// Where a package lives: a git repository and a directory inside it
+///
+/// Written `<repo>//<subdir>`. A repo starting with a host name
+/// (`github.com/org/td-loops`) is fetched over https; URLs (`https://`,
+/// `ssh://`, `file://`), scp-style `git@host:org/repo` and local paths are
+/// passed to git as given.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageSource {
+    pub repo: String,
+    pub subdir: Option<String>,
+}
+
+impl PackageSource {
+    /// URL handed to `git clone` (local paths made absolute)
+    pub fn clone_url(&self) -> String {
+        if self.repo.contains("://") || self.repo.starts_with("git@") || self.repo.starts_with('/') {
+            self.repo.clone()
+        } else if let Some(rest) = self.repo.strip_prefix("~/") {
+            dirs::home_dir()
+                .map(|home| home.join(rest).to_string_lossy().to_string())
+                .unwrap_or_else(|| self.repo.clone())
+        } else if self.repo.starts_with('.') {
+            std::env::current_dir()
+                .map(|cwd| cwd.join(&self.repo).to_string_lossy().to_string())
+                .unwrap_or_else(|_| self.repo.clone())
+        } else {
+            format!("https://{}", self.repo)
+        }
+    }
+}
+
+impl FromStr for PackageSource {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        debug!(%s, "PackageSource::from_str: called");
+        let s = s.trim();
+        // Look for the `//` separator after any URL scheme
+        let scheme_end = s.find("://").map(|i| i + 3).unwrap_or(0);
+        let (repo, subdir) = match s[scheme_end..].find("//") {
+            Some(i) => {
+                let (repo, subdir) = s.split_at(scheme_end + i);
+                (repo, Some(subdir[2..].trim_matches('/')))
+            }
+            None => (s, None),
+        };
+        let repo = repo.trim_end_matches('/');
+        if repo.is_empty() {
+            bail!("Invalid package source '{}': missing repository", s);
+        }
+        let subdir = subdir.filter(|d| !d.is_empty());
+        if let Some(subdir) = subdir
+            && !is_relative_inside(Path::new(subdir))
+        {
+            bail!("Invalid package source '{}': '{}' must stay inside the repository", s, subdir);
+        }
+        Ok(Self {
+            repo: repo.to_string(),
+            subdir: subdir.map(String::from),
+        })
+    }
+}
+
+impl std::fmt::Display for PackageSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.subdir {
+            Some(subdir) => write!(f, "{}//{}", self.repo, subdir),
+            None => write!(f, "{}", self.repo),
+        }
+    }
+}
+
+/// Contents of `package.yml`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackageManifest {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    /// Package-relative path -> sha256 (hex) of every file in the package
+    pub files: BTreeMap<String, String>,
+}
+
+/// An installed package as recorded in `packages.lock`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct InstalledPackage {
+    pub name: String,
+    pub version: String,
+    /// Source as given to `td loops install`
+    pub source: String,
+    /// Branch or tag requested with `--ref` (default branch when absent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_ref: Option<String>,
+    /// Commit the package was installed from
+    pub commit: String,
+    /// sha256 of the package's `package.yml`
+    pub checksum: String,
+    pub installed_at: DateTime<Utc>,
+}
+
+/// Result of `PackageRegistry::update` for one package
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackageUpdate {
+    /// Source commit unchanged
+    UpToDate(InstalledPackage),
+    /// Reinstalled from a newer commit
+    Updated { from: InstalledPackage, to: InstalledPackage },
+}
+
+/// Installed packages in one loops directory
+#[derive(Debug)]
+pub struct PackageRegistry {
+    dir: PathBuf,
+    packages: BTreeMap<String, InstalledPackage>,
+}
+
+impl PackageRegistry {
+    /// Open the registry of `loops_dir` (empty if nothing is installed yet)
+    pub fn open(loops_dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = loops_dir.into();
+        debug!(?dir, "PackageRegistry::open: called");
+        let lock = dir.join(PACKAGE_LOCK);
+        let packages = if lock.exists() {
+            let content = fs::read_to_string(&lock).with_context(|| format!("Failed to read {}", lock.display()))?;
+            serde_yaml::from_str(&content).with_context(|| format!("Failed to parse {}", lock.display()))?
+        } else {
+            BTreeMap::new()
+        };
+        Ok(Self { dir, packages })
+    }
+
+    /// Installed packages, by name
+    pub fn packages(&self) -> impl Iterator<Item = &InstalledPackage> {
+        self.packages.values()
+    }
+
+    /// Look up an installed package
+    pub fn get(&self, name: &str) -> Option<&InstalledPackage> {
+        self.packages.get(name)
+    }
+
+    /// Fetch, verify and install a package
+    ///
+    /// `checksum` pins the sha256 of the package's `package.yml`. Fails if a
+    /// package with the same name is already installed.
+    pub async fn install(
+        &mut self,
+        source: &str,
+        git_ref: Option<&str>,
+        checksum: Option<&str>,
+    ) -> Result<InstalledPackage> {
+        debug!(%source, ?git_ref, ?checksum, "PackageRegistry::install: called");
+        let package = self.fetch(source, git_ref, checksum, None).await?;
+        info!(name = %package.name, version = %package.version, "Installed loop package");
+        Ok(package)
+    }
+
+    /// Re-fetch a package from its recorded source and ref, reinstalling it if the commit moved
+    pub async fn update(&mut self, name: &str) -> Result<PackageUpdate> {
+        debug!(%name, "PackageRegistry::update: called");
+        let Some(current) = self.packages.get(name).cloned() else {
+            bail!("Loop package '{}' is not installed", name);
+        };
+        let source: PackageSource = current.source.parse()?;
+        let remote = ls_remote(&source.clone_url(), current.git_ref.as_deref()).await?;
+        if remote.as_deref() == Some(current.commit.as_str()) {
+            debug!(%name, "PackageRegistry::update: up to date");
+            return Ok(PackageUpdate::UpToDate(current));
+        }
+        let updated = self
+            .fetch(&current.source, current.git_ref.as_deref(), None, Some(name))
+            .await?;
+        if updated.commit == current.commit {
+            return Ok(PackageUpdate::UpToDate(updated));
+        }
+        info!(%name, from = %current.version, to = %updated.version, "Updated loop package");
+        Ok(PackageUpdate::Updated {
+            from: current,
+            to: updated,
+        })
+    }
+
+    /// Delete an installed package and its record
+    pub fn remove(&mut self, name: &str) -> Result<InstalledPackage> {
+        debug!(%name, "PackageRegistry::remove: called");
+        let Some(package) = self.packages.remove(name) else {
+            bail!("Loop package '{}' is not installed", name);
+        };
+        let dir = self.dir.join(name);
+        if dir.exists() {
+            fs::remove_dir_all(&dir).with_context(|| format!("Failed to remove {}", dir.display()))?;
+        }
+        self.save()?;
+        info!(%name, "Removed loop package");
+        Ok(package)
+    }
+
+    /// Clone the source, verify the package and copy it into place
+    ///
+    /// `replacing` names the installed package being updated; otherwise the name must be free.
+    async fn fetch(
+        &mut self,
+        source: &str,
+        git_ref: Option<&str>,
+        checksum: Option<&str>,
+        replacing: Option<&str>,
+    ) -> Result<InstalledPackage> {
+        let scratch = self.dir.join(format!(".fetch-{}", std::process::id()));
+        let result = self.fetch_into(&scratch, source, git_ref, checksum, replacing).await;
+        if scratch.exists() {
+            let _ = fs::remove_dir_all(&scratch);
+        }
+        result
+    }
+
+    async fn fetch_into(
+        &mut self,
+        scratch: &Path,
+        source: &str,
+        git_ref: Option<&str>,
+        checksum: Option<&str>,
+        replacing: Option<&str>,
+    ) -> Result<InstalledPackage> {
+        let parsed: PackageSource = source.parse()?;
+        fs::create_dir_all(scratch).with_context(|| format!("Failed to create {}", scratch.display()))?;
+        let checkout = scratch.join("repo");
+        let url = parsed.clone_url();
+        let checkout_arg = checkout.to_string_lossy().to_string();
+        let mut args = vec!["clone", "--quiet", "--depth", "1"];
+        if let Some(git_ref) = git_ref {
+            args.extend(["--branch", git_ref]);
+        }
+        args.extend([url.as_str(), checkout_arg.as_str()]);
+        git(scratch, &args).await?;
+        let commit = git(&checkout, &["rev-parse", "HEAD"]).await?;
+
+        let package_dir = match &parsed.subdir {
+            Some(subdir) => checkout.join(subdir),
+            None => checkout.clone(),
+        };
+        if !package_dir.join(PACKAGE_MANIFEST).exists() {
+            bail!("No {} in {}", PACKAGE_MANIFEST, parsed);
+        }
+        let manifest = verify_package(&package_dir)?;
+        let manifest_checksum = sha256_file(&package_dir.join(PACKAGE_MANIFEST))?;
+        if let Some(expected) = checksum
+            && !expected.eq_ignore_ascii_case(&manifest_checksum)
+        {
+            bail!(
+                "Checksum mismatch for {}: expected {}, got {}",
+                PACKAGE_MANIFEST,
+                expected,
+                manifest_checksum
+            );
+        }
+        match replacing {
+            Some(name) if name != manifest.name => bail!(
+                "Source of '{}' now holds package '{}'; remove it and install the new one",
+                name,
+                manifest.name
+            ),
+            None if self.packages.contains_key(&manifest.name) => bail!(
+                "Loop package '{}' is already installed; use `td loops update {}`",
+                manifest.name,
+                manifest.name
+            ),
+            _ => {}
+        }
+
+        // Copy into a staging directory first so a failure leaves any installed version intact
+        let target = self.dir.join(&manifest.name);
+        let staging = self.dir.join(format!(".{}.installing", manifest.name));
+        if staging.exists() {
+            fs::remove_dir_all(&staging)?;
+        }
+        for file in manifest.files.keys().map(String::as_str).chain([PACKAGE_MANIFEST]) {
+            let to = staging.join(file);
+            if let Some(parent) = to.parent() {
+                fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+            }
+            fs::copy(package_dir.join(file), &to).with_context(|| format!("Failed to copy {}", file))?;
+        }
+        if target.exists() {
+            fs::remove_dir_all(&target).with_context(|| format!("Failed to replace {}", target.display()))?;
+        }
+        fs::rename(&staging, &target).with_context(|| format!("Failed to install {}", target.display()))?;
+
+        let package = InstalledPackage {
+            name: manifest.name.clone(),
+            version: manifest.version,
+            source: source.to_string(),
+            git_ref: git_ref.map(String::from),
+            commit,
+            checksum: manifest_checksum,
+            installed_at: Utc::now(),
+        };
+        self.packages.insert(manifest.name, package.clone());
+        self.save()?;
+        Ok(package)
+    }
+
+    /// Write `packages.lock`
+    fn save(&self) -> Result<()> {
+        debug!(dir = ?self.dir, count = self.packages.len(), "PackageRegistry::save: called");
+        fs::create_dir_all(&self.dir).with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        let lock = self.dir.join(PACKAGE_LOCK);
+        fs::write(&lock, serde_yaml::to_string(&self.packages)?)
+            .with_context(|| format!("Failed to write {}", lock.display()))
+    }
+}
+
+/// Check a package directory against its manifest
+///
+/// Every listed file must exist with the listed sha256, no other files may be
+/// present, and at least one top-level `.yml`/`.yaml` loop type must be listed.
+pub fn verify_package(dir: &Path) -> Result<PackageManifest> {
+    debug!(?dir, "verify_package: called");
+    let manifest_path = dir.join(PACKAGE_MANIFEST);
+    let content =
+        fs::read_to_string(&manifest_path).with_context(|| format!("Failed to read {}", manifest_path.display()))?;
+    let manifest: PackageManifest =
+        serde_yaml::from_str(&content).with_context(|| format!("Failed to parse {}", manifest_path.display()))?;
+
+    if manifest.name.is_empty() || !is_relative_inside(Path::new(&manifest.name)) || manifest.name.contains('/') {
+        bail!("Invalid package name '{}'", manifest.name);
+    }
+    if manifest.version.is_empty() {
+        bail!("Package '{}' has no version", manifest.name);
+    }
+    if !manifest.files.keys().any(|f| is_loop_type_file(Path::new(f))) {
+        bail!("Package '{}' lists no top-level loop type YAML", manifest.name);
+    }
+
+    for (file, expected) in &manifest.files {
+        if !is_relative_inside(Path::new(file)) || file == PACKAGE_MANIFEST {
+            bail!("Invalid file '{}' in {}", file, PACKAGE_MANIFEST);
+        }
+        let path = dir.join(file);
+        if !path.is_file() {
+            bail!("Package '{}' is missing {}", manifest.name, file);
+        }
+        let actual = sha256_file(&path)?;
+        if !actual.eq_ignore_ascii_case(expected) {
+            bail!(
+                "Checksum mismatch for {}: manifest says {}, file is {}",
+                file,
+                expected,
+                actual
+            );
+        }
+    }
+
+    for path in walk_files(dir)? {
+        let relative = path.strip_prefix(dir).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+        if relative != PACKAGE_MANIFEST && !manifest.files.contains_key(&relative) {
+            bail!("Package '{}' contains {}, which {} does not list", manifest.name, relative, PACKAGE_MANIFEST);
+        }
+    }
+
+    debug!(name = %manifest.name, files = manifest.files.len(), "verify_package: ok");
+    Ok(manifest)
+}
+
+/// Whether an installed package directory holds loop type files (it has a manifest)
+pub fn is_package_dir(dir: &Path) -> bool {
+    dir.join(PACKAGE_MANIFEST).is_file()
+}
+
+/// Top-level `.yml`/`.yaml` file other than the manifest
+pub fn is_loop_type_file(path: &Path) -> bool {
+    path.components().count() == 1
+        && path.extension().is_some_and(|e| e == "yml" || e == "yaml")
+        && path.file_name().is_some_and(|n| n != PACKAGE_MANIFEST)
+}
+
+/// sha256 of a file, hex-encoded
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let bytes = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(format!("{:x}", Sha256::digest(&bytes)))
+}
+
+fn is_relative_inside(path: &Path) -> bool {
+    path.components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+/// Files under `dir`, skipping `.git`
+fn walk_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in fs::read_dir(&current).with_context(|| format!("Failed to read {}", current.display()))? {
+            let path = entry?.path();
+            if path.file_name().is_some_and(|n| n == ".git") {
+                continue;
+            }
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Commit `git_ref` (or HEAD) points to in a remote, without cloning
+async fn ls_remote(url: &str, git_ref: Option<&str>) -> Result<Option<String>> {
+    let cwd = std::env::temp_dir();
+    let out = git(&cwd, &["ls-remote", url, git_ref.unwrap_or("HEAD")]).await?;
+    Ok(out.split_whitespace().next().map(String::from))
+}
+
+/// Run git in `dir`, returning trimmed stdout
+async fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    debug!(?dir, ?args, "package git: called");
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .await
+        .with_context(|| format!("Failed to run git {}", args.join(" ")))?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const LOOP_YAML: &str = "description: Test review\nprompt-template: Review {{task}}\n";
+
+    fn git_sync(dir: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    /// Write a package into `repo/pkg` with a correct manifest and commit it
+    fn write_package(repo: &Path, version: &str, prompt: &str) {
+        let pkg = repo.join("pkg");
+        fs::create_dir_all(pkg.join("prompts")).unwrap();
+        fs::write(pkg.join("review.yml"), LOOP_YAML).unwrap();
+        fs::write(pkg.join("prompts/review.md"), prompt).unwrap();
+        let mut files = BTreeMap::new();
+        for file in ["review.yml", "prompts/review.md"] {
+            files.insert(file.to_string(), sha256_file(&pkg.join(file)).unwrap());
+        }
+        let manifest = PackageManifest {
+            name: "review".to_string(),
+            version: version.to_string(),
+            description: String::new(),
+            files,
+        };
+        fs::write(pkg.join(PACKAGE_MANIFEST), serde_yaml::to_string(&manifest).unwrap()).unwrap();
+        git_sync(repo, &["add", "-A"]);
+        git_sync(repo, &["commit", "-q", "-m", version]);
+    }
+
+    #[test]
+    fn test_parse_package_source() {
+        let source: PackageSource = "github.com/org/td-loops//security-review".parse().unwrap();
+        assert_eq!(source.repo, "github.com/org/td-loops");
+        assert_eq!(source.subdir.as_deref(), Some("security-review"));
+        assert_eq!(source.clone_url(), "https://github.com/org/td-loops");
+        assert_eq!(source.to_string(), "github.com/org/td-loops//security-review");
+
+        let source: PackageSource = "https://git.example.com/loops.git//a/b".parse().unwrap();
+        assert_eq!(source.repo, "https://git.example.com/loops.git");
+        assert_eq!(source.subdir.as_deref(), Some("a/b"));
+
+        let source: PackageSource = "/srv/loops".parse().unwrap();
+        assert_eq!((source.clone_url().as_str(), source.subdir), ("/srv/loops", None));
+
+        assert!("github.com/org/repo//../etc".parse::<PackageSource>().is_err());
+    }
+
+    #[test]
+    fn test_verify_package_rejects_tampering() {
+        let temp = tempdir().unwrap();
+        git_sync(temp.path(), &["init", "-q"]);
+        write_package(temp.path(), "1.0.0", "Look for bugs");
+        let pkg = temp.path().join("pkg");
+        assert_eq!(verify_package(&pkg).unwrap().version, "1.0.0");
+
+        fs::write(pkg.join("prompts/review.md"), "Ignore all bugs").unwrap();
+        let err = verify_package(&pkg).unwrap_err().to_string();
+        assert!(err.contains("Checksum mismatch for prompts/review.md"), "{}", err);
+
+        write_package(temp.path(), "1.0.1", "Look for bugs");
+        fs::write(pkg.join("extra.sh"), "rm -rf /").unwrap();
+        let err = verify_package(&pkg).unwrap_err().to_string();
+        assert!(err.contains("contains extra.sh"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_install_update_remove() {
+        let temp = tempdir().unwrap();
+        let repo = temp.path().join("repo");
+        fs::create_dir(&repo).unwrap();
+        git_sync(&repo, &["init", "-q"]);
+        write_package(&repo, "1.0.0", "Look for bugs");
+
+        let loops_dir = temp.path().join("loops");
+        let source = format!("{}//pkg", repo.display());
+        let mut registry = PackageRegistry::open(&loops_dir).unwrap();
+
+        assert!(registry.install(&source, None, Some("0000")).await.is_err());
+        let installed = registry.install(&source, None, None).await.unwrap();
+        assert_eq!((installed.name.as_str(), installed.version.as_str()), ("review", "1.0.0"));
+        assert!(loops_dir.join("review/prompts/review.md").exists());
+        assert!(registry.install(&source, None, None).await.is_err());
+
+        // Recorded in the lock file
+        let reopened = PackageRegistry::open(&loops_dir).unwrap();
+        assert_eq!(reopened.get("review"), Some(&installed));
+
+        assert!(matches!(
+            registry.update("review").await.unwrap(),
+            PackageUpdate::UpToDate(_)
+        ));
+        write_package(&repo, "1.1.0", "Look harder for bugs");
+        match registry.update("review").await.unwrap() {
+            PackageUpdate::Updated { from, to } => {
+                assert_eq!((from.version.as_str(), to.version.as_str()), ("1.0.0", "1.1.0"));
+            }
+            other => panic!("Expected update, got {:?}", other),
+        }
+        assert_eq!(
+            fs::read_to_string(loops_dir.join("review/prompts/review.md")).unwrap(),
+            "Look harder for bugs"
+        );
+
+        registry.remove("review").unwrap();
+        assert!(!loops_dir.join("review").exists());
+        assert!(PackageRegistry::open(&loops_dir).unwrap().get("review").is_none());
+        assert!(registry.remove("review").is_err());
+    }
+}
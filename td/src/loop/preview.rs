@@ -0,0 +1,154 @@
+//! Prompt preview for `td exec prompt`
+//!
+//! Renders the request a loop iteration starts with (system prompt, first
+//! user message and tools) without calling the LLM. The engine is rebuilt from
+//! the stored execution and its iteration logs are replayed into it, so the
+//! progress, previous errors and acceptance failures are the ones the real
+//! iteration saw. Git status and diff come from the worktree as it is now, and
+//! state a running loop only holds in memory (steering after stuck detection,
+//! pending operator messages, facts shared by other loops) is not included.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use eyre::Result;
+use tracing::debug;
+
+use super::{LoopConfig, LoopEngine, PromptPreview};
+use crate::domain::{IterationLog, LoopExecution};
+use crate::llm::LlmClient;
+use crate::llm::client::mock::MockLlmClient;
+
+/// Which iteration's prompt to preview
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptIteration {
+    /// The iteration the execution would run next
+    Next,
+    /// A given iteration (1-based), as it was assembled
+    Number(u32),
+}
+
+impl std::str::FromStr for PromptIteration {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        debug!(%s, "PromptIteration::from_str: called");
+        match s {
+            "next" => Ok(Self::Next),
+            _ => match s.parse::<u32>() {
+                Ok(n) if n > 0 => Ok(Self::Number(n)),
+                _ => Err(format!("Expected 'next' or an iteration number, got '{}'", s)),
+            },
+        }
+    }
+}
+
+/// Render the prompt `exec` sends at `iteration`
+///
+/// `config` is the loop type's config; the execution's iteration cap override
+/// is applied here. `logs` are the execution's iteration logs in any order.
+/// The worktree is the execution's when it still exists, else `repo_root`.
+pub async fn preview_prompt(
+    exec: &LoopExecution,
+    config: LoopConfig,
+    logs: &[IterationLog],
+    iteration: PromptIteration,
+    repo_root: &Path,
+) -> Result<PromptPreview> {
+    debug!(exec_id = %exec.id, ?iteration, logs = logs.len(), "preview_prompt: called");
+    let mut logs: Vec<&IterationLog> = logs.iter().collect();
+    logs.sort_by_key(|log| log.iteration);
+    let last = logs.last().map_or(0, |log| log.iteration);
+    let before = match iteration {
+        PromptIteration::Next => last + 1,
+        PromptIteration::Number(n) if n <= last + 1 => n,
+        PromptIteration::Number(n) => {
+            eyre::bail!(
+                "Execution {} has not reached iteration {} (last recorded: {})",
+                exec.id,
+                n,
+                last
+            )
+        }
+    };
+
+    let worktree = exec
+        .worktree
+        .as_deref()
+        .map(Path::new)
+        .filter(|path| path.is_dir())
+        .unwrap_or(repo_root)
+        .to_path_buf();
+    // Nothing is sent; the engine only needs a client to be constructed
+    let llm: Arc<dyn LlmClient> = Arc::new(MockLlmClient::new(Vec::new()));
+    let mut engine = LoopEngine::new(exec.id.clone(), config, llm, worktree)
+        .with_overrides(exec.model.clone(), exec.max_iterations)
+        .with_execution_context(exec.context.clone())
+        .with_acceptance(exec.acceptance.clone())
+        .with_repo_root(repo_root.to_path_buf());
+    for log in logs.iter().filter(|log| log.iteration < before) {
+        engine.replay_iteration(log);
+    }
+    engine.preview_prompt().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_prompt_iteration_from_str() {
+        assert_eq!("next".parse::<PromptIteration>(), Ok(PromptIteration::Next));
+        assert_eq!("3".parse::<PromptIteration>(), Ok(PromptIteration::Number(3)));
+        assert!("0".parse::<PromptIteration>().is_err());
+        assert!("last".parse::<PromptIteration>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_preview_prompt_replays_iteration_logs() {
+        let temp = tempdir().unwrap();
+        let config = LoopConfig {
+            loop_type: "ralph".to_string(),
+            prompt_template: "Iteration {{iteration}}: {{task}}\n{{progress}}\n{{previous-errors}}".to_string(),
+            ..Default::default()
+        };
+        let exec = LoopExecution::with_id("exec-1", "ralph").with_context_value("task", "fix the parser");
+        let logs = vec![
+            IterationLog::new("exec-1", 2)
+                .with_validation_command("cargo test")
+                .with_exit_code(1)
+                .with_stderr("error: second failure"),
+            IterationLog::new("exec-1", 1)
+                .with_validation_command("cargo test")
+                .with_exit_code(1)
+                .with_stderr("error: first failure"),
+        ];
+
+        let next = preview_prompt(&exec, config.clone(), &logs, PromptIteration::Next, temp.path())
+            .await
+            .unwrap();
+        assert_eq!(next.iteration, 3);
+        assert!(next.prompt.starts_with("Iteration 3: fix the parser"));
+        assert!(next.prompt.contains("error: second failure"));
+        assert!(next.system_prompt.contains("Loop type: ralph"));
+
+        let second = preview_prompt(&exec, config.clone(), &logs, PromptIteration::Number(2), temp.path())
+            .await
+            .unwrap();
+        assert!(second.prompt.starts_with("Iteration 2: fix the parser"));
+        assert!(second.prompt.contains("error: first failure"));
+        assert!(!second.prompt.contains("second failure"));
+
+        let first = preview_prompt(&exec, config.clone(), &logs, PromptIteration::Number(1), temp.path())
+            .await
+            .unwrap();
+        assert!(!first.prompt.contains("failure"));
+
+        assert!(
+            preview_prompt(&exec, config, &logs, PromptIteration::Number(5), temp.path())
+                .await
+                .is_err()
+        );
+    }
+}
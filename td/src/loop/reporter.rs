@@ -0,0 +1,398 @@
+//! Validation reporter - structured test results from raw validation output
+//!
+//! When validation fails the loop used to see only raw command output, which
+//! is mostly noise (compile progress, passing tests). The reporter recognizes
+//! common test runner formats (cargo test, pytest, jest) and extracts the
+//! failing tests with their messages so only those are injected into the next
+//! prompt. Passing tests are kept too, so fail -> pass transitions can be
+//! tracked across iterations in `LoopMetrics`.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Maximum lines of failure message kept per test
+const MAX_MESSAGE_LINES: usize = 20;
+
+/// Test runner that produced a report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestFramework {
+    Cargo,
+    Pytest,
+    Jest,
+}
+
+impl std::fmt::Display for TestFramework {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cargo => write!(f, "cargo test"),
+            Self::Pytest => write!(f, "pytest"),
+            Self::Jest => write!(f, "jest"),
+        }
+    }
+}
+
+/// A single failing test
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailedTest {
+    /// Fully qualified test name as printed by the runner
+    pub name: String,
+
+    /// Failure message (assertion, panic, traceback excerpt)
+    pub message: String,
+}
+
+/// Structured result of a test run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestReport {
+    /// Runner that produced the output
+    pub framework: TestFramework,
+
+    /// Names of tests that passed
+    pub passed: Vec<String>,
+
+    /// Tests that failed, in output order
+    pub failed: Vec<FailedTest>,
+}
+
+impl TestReport {
+    /// Parse validation output, trying each known format
+    ///
+    /// Returns None when the output doesn't look like any supported runner.
+    pub fn parse(stdout: &str, stderr: &str) -> Option<Self> {
+        debug!(
+            stdout_len = stdout.len(),
+            stderr_len = stderr.len(),
+            "TestReport::parse: called"
+        );
+        let combined = format!("{}\n{}", stdout, stderr);
+
+        let report = parse_cargo(&combined)
+            .or_else(|| parse_pytest(&combined))
+            .or_else(|| parse_jest(&combined));
+        debug!(framework = ?report.as_ref().map(|r| r.framework), "TestReport::parse: returning");
+        report
+    }
+
+    /// Whether any test failed
+    pub fn has_failures(&self) -> bool {
+        !self.failed.is_empty()
+    }
+
+    /// Render just the failures for prompt injection
+    pub fn format_failures(&self) -> String {
+        debug!(failed = self.failed.len(), "TestReport::format_failures: called");
+        let mut out = format!(
+            "{} failing test(s) ({}, {} passing):\n",
+            self.failed.len(),
+            self.framework,
+            self.passed.len()
+        );
+        for test in &self.failed {
+            out.push_str(&format!("\n### {}\n", test.name));
+            if !test.message.is_empty() {
+                out.push_str(&format!("```\n{}\n```\n", test.message));
+            }
+        }
+        out
+    }
+}
+
+/// Keep at most `MAX_MESSAGE_LINES` non-empty-trimmed lines
+fn clip_message(lines: &[&str]) -> String {
+    let trimmed: Vec<&str> = {
+        let start = lines.iter().position(|l| !l.trim().is_empty()).unwrap_or(lines.len());
+        let end = lines
+            .iter()
+            .rposition(|l| !l.trim().is_empty())
+            .map_or(start, |e| e + 1);
+        lines[start..end].to_vec()
+    };
+    let mut message = trimmed
+        .iter()
+        .take(MAX_MESSAGE_LINES)
+        .copied()
+        .collect::<Vec<_>>()
+        .join("\n");
+    if trimmed.len() > MAX_MESSAGE_LINES {
+        message.push_str(&format!("\n... ({} more lines)", trimmed.len() - MAX_MESSAGE_LINES));
+    }
+    message
+}
+
+/// cargo test / libtest: `test a::b ... ok|FAILED` plus `---- a::b stdout ----` sections
+fn parse_cargo(output: &str) -> Option<TestReport> {
+    let mut passed = Vec::new();
+    let mut failed_names = Vec::new();
+    let mut saw_result_line = false;
+
+    for line in output.lines() {
+        let Some(rest) = line.trim().strip_prefix("test ") else {
+            continue;
+        };
+        if let Some(name) = rest.strip_suffix(" ... ok") {
+            passed.push(name.to_string());
+            saw_result_line = true;
+        } else if let Some(name) = rest.strip_suffix(" ... FAILED") {
+            failed_names.push(name.to_string());
+            saw_result_line = true;
+        }
+    }
+
+    if !saw_result_line {
+        return None;
+    }
+
+    // Collect `---- name stdout ----` sections for messages
+    let mut messages: HashMap<String, String> = HashMap::new();
+    let lines: Vec<&str> = output.lines().collect();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim();
+        if let Some(name) = line.strip_prefix("---- ").and_then(|l| l.strip_suffix(" stdout ----")) {
+            let start = i + 1;
+            let mut end = start;
+            while end < lines.len() {
+                let l = lines[end].trim();
+                if l.starts_with("---- ") || l == "failures:" || l.starts_with("test result:") {
+                    break;
+                }
+                end += 1;
+            }
+            messages.insert(name.to_string(), clip_message(&lines[start..end]));
+            i = end;
+        } else {
+            i += 1;
+        }
+    }
+
+    let failed = failed_names
+        .into_iter()
+        .map(|name| FailedTest {
+            message: messages.remove(&name).unwrap_or_default(),
+            name,
+        })
+        .collect();
+
+    Some(TestReport {
+        framework: TestFramework::Cargo,
+        passed,
+        failed,
+    })
+}
+
+/// pytest: `FAILED path::test - message` summary lines, `path::test PASSED` in verbose mode
+fn parse_pytest(output: &str) -> Option<TestReport> {
+    let is_pytest = output.contains("short test summary info") || output.contains("test session starts");
+    if !is_pytest {
+        return None;
+    }
+
+    let mut passed = Vec::new();
+    let mut failed = Vec::new();
+
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("FAILED ").or_else(|| line.strip_prefix("ERROR ")) {
+            let (name, message) = match rest.split_once(" - ") {
+                Some((name, message)) => (name.trim(), message.trim()),
+                None => (rest.trim(), ""),
+            };
+            if !failed.iter().any(|f: &FailedTest| f.name == name) {
+                failed.push(FailedTest {
+                    name: name.to_string(),
+                    message: message.to_string(),
+                });
+            }
+        } else if let Some((name, _)) = line.split_once(" PASSED")
+            && name.contains("::")
+        {
+            passed.push(name.trim().to_string());
+        }
+    }
+
+    Some(TestReport {
+        framework: TestFramework::Pytest,
+        passed,
+        failed,
+    })
+}
+
+/// jest: `✓ name` / `✕ name` lines plus `● Suite › name` failure detail blocks
+fn parse_jest(output: &str) -> Option<TestReport> {
+    let is_jest = output.lines().any(|l| {
+        let l = l.trim_start();
+        l.starts_with("Tests:") && (l.contains("passed") || l.contains("failed"))
+    });
+    if !is_jest {
+        return None;
+    }
+
+    let mut passed = Vec::new();
+    let mut failed_names = Vec::new();
+    let lines: Vec<&str> = output.lines().collect();
+
+    // Strip the trailing "(5 ms)" timing jest appends to result lines
+    let strip_timing = |s: &str| -> String {
+        let s = s.trim();
+        match s.rfind(" (") {
+            Some(idx) if s.ends_with("ms)") || s.ends_with(" s)") => s[..idx].to_string(),
+            _ => s.to_string(),
+        }
+    };
+
+    for line in &lines {
+        let line = line.trim();
+        if let Some(name) = line.strip_prefix("✓ ").or_else(|| line.strip_prefix("√ ")) {
+            passed.push(strip_timing(name));
+        } else if let Some(name) = line.strip_prefix("✕ ").or_else(|| line.strip_prefix("× ")) {
+            failed_names.push(strip_timing(name));
+        }
+    }
+
+    // Detail blocks: "● Describe › test name" followed by the message
+    let mut failed: Vec<FailedTest> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim();
+        if let Some(title) = line.strip_prefix("● ") {
+            let start = i + 1;
+            let mut end = start;
+            while end < lines.len() && !lines[end].trim().starts_with("● ") && !lines[end].starts_with("Test Suites:")
+            {
+                end += 1;
+            }
+            let name = title.rsplit(" › ").next().unwrap_or(title).trim().to_string();
+            failed.push(FailedTest {
+                name: title.trim().to_string(),
+                message: clip_message(&lines[start..end]),
+            });
+            failed_names.retain(|n| *n != name);
+            i = end;
+        } else {
+            i += 1;
+        }
+    }
+
+    // Failures listed with ✕ but without a detail block
+    failed.extend(failed_names.into_iter().map(|name| FailedTest {
+        name,
+        message: String::new(),
+    }));
+
+    Some(TestReport {
+        framework: TestFramework::Jest,
+        passed,
+        failed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CARGO_OUTPUT: &str = r#"
+running 3 tests
+test auth::test_login ... ok
+test auth::test_validate ... FAILED
+test db::test_connect ... ok
+
+failures:
+
+---- auth::test_validate stdout ----
+thread 'auth::test_validate' panicked at src/auth.rs:42:5:
+assertion `left == right` failed
+  left: 1
+ right: 2
+
+failures:
+    auth::test_validate
+
+test result: FAILED. 2 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out
+"#;
+
+    #[test]
+    fn test_parse_cargo() {
+        let report = TestReport::parse(CARGO_OUTPUT, "").unwrap();
+
+        assert_eq!(report.framework, TestFramework::Cargo);
+        assert_eq!(report.passed, vec!["auth::test_login", "db::test_connect"]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].name, "auth::test_validate");
+        assert!(report.failed[0].message.contains("src/auth.rs:42:5"));
+        assert!(report.failed[0].message.contains("right: 2"));
+    }
+
+    #[test]
+    fn test_parse_pytest() {
+        let output = r#"
+============================= test session starts ==============================
+tests/test_api.py::test_get PASSED                                       [ 50%]
+tests/test_api.py::test_post FAILED                                      [100%]
+=========================== short test summary info ============================
+FAILED tests/test_api.py::test_post - AssertionError: expected 201, got 500
+========================= 1 failed, 1 passed in 0.12s ==========================
+"#;
+        let report = TestReport::parse(output, "").unwrap();
+
+        assert_eq!(report.framework, TestFramework::Pytest);
+        assert_eq!(report.passed, vec!["tests/test_api.py::test_get"]);
+        assert_eq!(report.failed[0].name, "tests/test_api.py::test_post");
+        assert_eq!(report.failed[0].message, "AssertionError: expected 201, got 500");
+    }
+
+    #[test]
+    fn test_parse_jest() {
+        let output = r#"
+ FAIL  src/sum.test.js
+  math
+    ✓ adds numbers (3 ms)
+    ✕ subtracts numbers (2 ms)
+
+  ● math › subtracts numbers
+
+    expect(received).toBe(expected)
+
+    Expected: 1
+    Received: 3
+
+Test Suites: 1 failed, 1 total
+Tests:       1 failed, 1 passed, 2 total
+"#;
+        let report = TestReport::parse("", output).unwrap();
+
+        assert_eq!(report.framework, TestFramework::Jest);
+        assert_eq!(report.passed, vec!["adds numbers"]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].name, "math › subtracts numbers");
+        assert!(report.failed[0].message.contains("Received: 3"));
+    }
+
+    #[test]
+    fn test_parse_unrecognized_output() {
+        assert!(TestReport::parse("error[E0308]: mismatched types", "").is_none());
+    }
+
+    #[test]
+    fn test_format_failures_only_lists_failures() {
+        let report = TestReport::parse(CARGO_OUTPUT, "").unwrap();
+        let formatted = report.format_failures();
+
+        assert!(formatted.contains("1 failing test(s) (cargo test, 2 passing)"));
+        assert!(formatted.contains("### auth::test_validate"));
+        assert!(!formatted.contains("test_login"));
+    }
+
+    #[test]
+    fn test_clip_message_limits_lines() {
+        let lines: Vec<String> = (0..30).map(|i| format!("line {}", i)).collect();
+        let refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
+        let clipped = clip_message(&refs);
+
+        assert!(clipped.contains("line 19"));
+        assert!(!clipped.contains("line 20\n"));
+        assert!(clipped.contains("10 more lines"));
+    }
+}
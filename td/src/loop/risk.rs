@@ -0,0 +1,152 @@
+//! Change risk scoring before auto-merge
+//!
+//! With `risk.enabled`, a completed code loop's diff against main is scored
+//! before it is merged: points for each sensitive glob a changed file falls
+//! under (e.g. `**/migrations/**`), for the number of changed lines, and for
+//! the test delta (code changed without any test, or tests shrinking). At or
+//! above `risk.approve-at` the execution is blocked until a human runs
+//! `td exec approve`; below it the work merges automatically. Either way the
+//! [`RiskAssessment`] is recorded on the execution.
+
+use std::path::Path;
+
+use eyre::Result;
+use glob::{MatchOptions, Pattern};
+use tracing::{debug, warn};
+
+use crate::config::RiskConfig;
+use crate::domain::{RiskAssessment, RiskFactor};
+use crate::worktree::{DiffSummary, FileChange};
+
+/// `*` stays within one path component; `**/` spans directories
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Compile globs, dropping (and warning about) invalid ones
+fn patterns<'a>(globs: impl IntoIterator<Item = &'a String>) -> Vec<(&'a String, Pattern)> {
+    globs
+        .into_iter()
+        .filter_map(|glob| match Pattern::new(glob) {
+            Ok(pattern) => Some((glob, pattern)),
+            Err(e) => {
+                warn!(%glob, error = %e, "Ignoring invalid risk glob");
+                None
+            }
+        })
+        .collect()
+}
+
+fn plural(n: usize, word: &str) -> String {
+    format!("{} {}{}", n, word, if n == 1 { "" } else { "s" })
+}
+
+/// Score `diff` against `config`
+pub fn assess_risk(config: &RiskConfig, diff: &DiffSummary) -> RiskAssessment {
+    debug!(files = diff.changes.len(), "assess_risk: called");
+    let mut factors = Vec::new();
+
+    for (glob, pattern) in patterns(config.sensitive.keys()) {
+        let matched = diff
+            .changes
+            .iter()
+            .filter(|change| pattern.matches_with(&change.path, MATCH_OPTIONS))
+            .count();
+        let points = config.sensitive[glob];
+        if matched > 0 && points > 0 {
+            factors.push(RiskFactor {
+                reason: format!("{} in {}", plural(matched, "file"), glob),
+                points,
+            });
+        }
+    }
+
+    let lines = diff.changes.iter().map(|c| c.insertions + c.deletions).sum::<u64>();
+    let size_points = (lines * config.points_per_100_lines as u64 / 100) as u32;
+    if size_points > 0 {
+        factors.push(RiskFactor {
+            reason: format!("{} changed lines", lines),
+            points: size_points,
+        });
+    }
+
+    let tests = patterns(&config.test_globs);
+    let is_test = |change: &&FileChange| {
+        tests
+            .iter()
+            .any(|(_, pattern)| pattern.matches_with(&change.path, MATCH_OPTIONS))
+    };
+    let (test_changes, code_changes): (Vec<&FileChange>, Vec<&FileChange>) = diff.changes.iter().partition(is_test);
+    let test_added = test_changes.iter().map(|c| c.insertions).sum::<u64>();
+    let test_removed = test_changes.iter().map(|c| c.deletions).sum::<u64>();
+    if test_removed > test_added && config.tests_removed_points > 0 {
+        factors.push(RiskFactor {
+            reason: format!("tests shrank by {} lines", test_removed - test_added),
+            points: config.tests_removed_points,
+        });
+    } else if test_changes.is_empty() && !code_changes.is_empty() && config.untested_points > 0 {
+        factors.push(RiskFactor {
+            reason: "no test changes".to_string(),
+            points: config.untested_points,
+        });
+    }
+
+    RiskAssessment::new(factors, config.approve_at, diff.changes.len(), lines)
+}
+
+/// Score `worktree`'s branch against `base`
+pub async fn assess_worktree_risk(config: &RiskConfig, worktree: &Path, base: &str) -> Result<RiskAssessment> {
+    debug!(?worktree, %base, "assess_worktree_risk: called");
+    let diff = DiffSummary::collect(worktree, base).await?;
+    Ok(assess_risk(config, &diff))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff(changes: &[(&str, u64, u64)]) -> DiffSummary {
+        DiffSummary {
+            changes: changes
+                .iter()
+                .map(|(path, insertions, deletions)| FileChange {
+                    path: path.to_string(),
+                    insertions: *insertions,
+                    deletions: *deletions,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_assess_risk() {
+        let config = RiskConfig::default();
+
+        // A small change with a test is low risk
+        let low = assess_risk(&config, &diff(&[("src/auth.rs", 30, 5), ("tests/auth.rs", 20, 0)]));
+        assert_eq!(low.score, 2);
+        assert!(!low.requires_approval());
+
+        // A migration without tests needs approval
+        let high = assess_risk(
+            &config,
+            &diff(&[("db/migrations/002_users.sql", 40, 0), ("src/users.rs", 160, 0)]),
+        );
+        let reasons: Vec<&str> = high.factors.iter().map(|f| f.reason.as_str()).collect();
+        assert_eq!(reasons, ["1 file in **/migrations/**", "200 changed lines", "no test changes"]);
+        assert_eq!(high.score, 40 + 10 + 15);
+        assert!(high.holds_merge());
+        assert_eq!((high.files, high.lines), (2, 200));
+
+        // Deleting tests counts against the change
+        let shrinking = assess_risk(&config, &diff(&[("src/lib.rs", 2, 2), ("src/parser_test.rs", 0, 40)]));
+        assert!(shrinking.factors.iter().any(|f| f.reason == "tests shrank by 40 lines"));
+
+        // `*` does not cross directories
+        let nested = assess_risk(&config, &diff(&[("src/test_utils/mod.rs", 1, 0)]));
+        assert!(nested.factors.iter().any(|f| f.reason == "no test changes"));
+    }
+}
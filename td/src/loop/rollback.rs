@@ -0,0 +1,187 @@
+//! Snapshot and rollback policy
+//!
+//! With snapshots enabled the engine records the worktree after every
+//! iteration (see `worktree::create_snapshot`). The RegressionTracker
+//! remembers the best validation result seen so far; with `rollback:
+//! on-regression` an iteration that makes validation worse than that is
+//! undone by restoring the best iteration's snapshot, and the next iteration
+//! is told why.
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use super::reporter::TestReport;
+use super::validation::ValidationResult;
+
+/// When to roll the worktree back to an earlier snapshot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RollbackPolicy {
+    /// Only on request (`td exec rollback`)
+    #[default]
+    Never,
+    /// When an iteration's validation is worse than the best one so far
+    OnRegression,
+}
+
+impl std::fmt::Display for RollbackPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Never => write!(f, "never"),
+            Self::OnRegression => write!(f, "on-regression"),
+        }
+    }
+}
+
+/// Snapshot settings for a loop type (`snapshots` in YAML)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct SnapshotPolicy {
+    /// Snapshot the worktree after every iteration
+    pub enabled: bool,
+
+    /// When to restore an earlier snapshot automatically
+    pub rollback: RollbackPolicy,
+}
+
+impl Default for SnapshotPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rollback: RollbackPolicy::default(),
+        }
+    }
+}
+
+/// How good an iteration's validation was; lower is better
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ValidationScore {
+    /// Validation passed
+    Passed,
+    /// Tests ran and this many failed
+    Failing(usize),
+    /// Validation failed without a parseable test report (e.g. a build error)
+    Broken,
+}
+
+impl ValidationScore {
+    /// Score a validation run, using its parsed test report if there is one
+    pub fn of(validation: &ValidationResult, report: Option<&TestReport>, success_exit_code: i32) -> Self {
+        if validation.passed(success_exit_code) {
+            return Self::Passed;
+        }
+        match report {
+            Some(report) => Self::Failing(report.failed.len()),
+            None => Self::Broken,
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationScore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Passed => write!(f, "passing"),
+            Self::Failing(1) => write!(f, "1 failing test"),
+            Self::Failing(n) => write!(f, "{} failing tests", n),
+            Self::Broken => write!(f, "failing without test results"),
+        }
+    }
+}
+
+/// A rollback the engine should perform
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Regression {
+    /// Iteration whose snapshot to restore
+    pub restore_to: u32,
+    /// Score of that iteration
+    pub best: ValidationScore,
+    /// Score of the iteration that regressed
+    pub current: ValidationScore,
+}
+
+/// Tracks the best-scoring snapshotted iteration
+#[derive(Debug, Clone, Default)]
+pub struct RegressionTracker {
+    best: Option<(u32, ValidationScore)>,
+}
+
+impl RegressionTracker {
+    /// Record a snapshotted iteration; returns the rollback to perform if it regressed
+    ///
+    /// Ties go to the newer iteration, so a rollback never discards work that
+    /// kept validation at the same level.
+    pub fn observe(&mut self, iteration: u32, score: ValidationScore, policy: RollbackPolicy) -> Option<Regression> {
+        debug!(iteration, ?score, ?self.best, %policy, "RegressionTracker::observe: called");
+        match self.best {
+            Some((best_iteration, best)) if score > best => {
+                if policy == RollbackPolicy::OnRegression {
+                    debug!(best_iteration, "RegressionTracker::observe: regression");
+                    return Some(Regression {
+                        restore_to: best_iteration,
+                        best,
+                        current: score,
+                    });
+                }
+                debug!("RegressionTracker::observe: worse, but rollback disabled");
+                None
+            }
+            _ => {
+                self.best = Some((iteration, score));
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_ordering() {
+        assert!(ValidationScore::Passed < ValidationScore::Failing(0));
+        assert!(ValidationScore::Failing(2) < ValidationScore::Failing(5));
+        assert!(ValidationScore::Failing(500) < ValidationScore::Broken);
+    }
+
+    #[test]
+    fn test_tracker_rolls_back_to_best_iteration() {
+        let mut tracker = RegressionTracker::default();
+        let policy = RollbackPolicy::OnRegression;
+
+        assert_eq!(tracker.observe(1, ValidationScore::Broken, policy), None);
+        assert_eq!(tracker.observe(2, ValidationScore::Failing(4), policy), None);
+        assert_eq!(tracker.observe(3, ValidationScore::Failing(4), policy), None);
+
+        let regression = tracker.observe(4, ValidationScore::Broken, policy).unwrap();
+        assert_eq!(regression.restore_to, 3);
+        assert_eq!(regression.current, ValidationScore::Broken);
+
+        // Still measured against iteration 3 after the rollback
+        assert_eq!(
+            tracker
+                .observe(5, ValidationScore::Failing(6), policy)
+                .map(|r| r.restore_to),
+            Some(3)
+        );
+        assert_eq!(tracker.observe(6, ValidationScore::Failing(1), policy), None);
+    }
+
+    #[test]
+    fn test_tracker_without_rollback_policy() {
+        let mut tracker = RegressionTracker::default();
+        tracker.observe(1, ValidationScore::Failing(1), RollbackPolicy::Never);
+        assert_eq!(tracker.observe(2, ValidationScore::Broken, RollbackPolicy::Never), None);
+    }
+
+    #[test]
+    fn test_snapshot_policy_yaml() {
+        let policy: SnapshotPolicy = serde_yaml::from_str("rollback: on-regression").unwrap();
+        assert!(policy.enabled);
+        assert_eq!(policy.rollback, RollbackPolicy::OnRegression);
+
+        let policy: SnapshotPolicy = serde_yaml::from_str("enabled: false").unwrap();
+        assert!(!policy.enabled);
+        assert_eq!(policy.rollback, RollbackPolicy::Never);
+    }
+}
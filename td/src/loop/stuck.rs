@@ -0,0 +1,202 @@
+//! Stuck-loop detection
+//!
+//! A loop that keeps iterating without changing the worktree or the shape of
+//! its validation failures is burning tokens for nothing. The ProgressMonitor
+//! fingerprints each iteration's worktree state and progress entry and counts
+//! consecutive iterations with an identical fingerprint. Once the count reaches
+//! the configured threshold the engine applies the configured StuckAction.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Default steering prompt injected by the `steer` action
+pub const DEFAULT_STEERING_PROMPT: &str = "The last several iterations made no measurable progress: the worktree and \
+     the validation failures are unchanged. Stop repeating the previous approach. Re-read the failing output, question \
+     your assumptions about the cause, and try a substantially different approach.";
+
+/// What to do when a loop is detected as stuck
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StuckAction {
+    /// Pause the execution (resume with `td exec resume`)
+    Pause,
+    /// Inject a steering prompt into the next iteration and keep going
+    #[default]
+    Steer,
+    /// Block the execution until a human reviews and resumes it
+    Escalate,
+}
+
+impl std::fmt::Display for StuckAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pause => write!(f, "pause"),
+            Self::Steer => write!(f, "steer"),
+            Self::Escalate => write!(f, "escalate"),
+        }
+    }
+}
+
+/// Stuck detection settings for a loop type (`stuck-detection` in YAML)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct StuckDetection {
+    /// Consecutive unchanged iterations before intervening (0 disables detection)
+    pub threshold: u32,
+
+    /// Action to apply once the threshold is reached
+    pub action: StuckAction,
+
+    /// Prompt injected by the `steer` action (defaults to DEFAULT_STEERING_PROMPT)
+    pub steering_prompt: Option<String>,
+}
+
+impl Default for StuckDetection {
+    fn default() -> Self {
+        Self {
+            threshold: 3,
+            action: StuckAction::default(),
+            steering_prompt: None,
+        }
+    }
+}
+
+impl StuckDetection {
+    /// Steering prompt to inject for the `steer` action
+    pub fn steering_prompt(&self) -> &str {
+        self.steering_prompt.as_deref().unwrap_or(DEFAULT_STEERING_PROMPT)
+    }
+}
+
+/// Tracks whether consecutive iterations materially changed anything
+#[derive(Debug, Clone, Default)]
+pub struct ProgressMonitor {
+    threshold: u32,
+    last_fingerprint: Option<u64>,
+    unchanged: u32,
+}
+
+impl ProgressMonitor {
+    /// Create a monitor that trips after `threshold` unchanged iterations
+    pub fn new(threshold: u32) -> Self {
+        debug!(threshold, "ProgressMonitor::new: called");
+        Self {
+            threshold,
+            ..Default::default()
+        }
+    }
+
+    /// Record one iteration's state; returns the unchanged count if the loop is stuck
+    ///
+    /// `worktree_state` should capture everything the iteration could have changed
+    /// (HEAD, diff, untracked files). `progress_entry` is the progress summary for
+    /// the iteration; numbers are ignored so iteration counters and timings don't
+    /// count as change.
+    pub fn observe(&mut self, worktree_state: &str, progress_entry: &str) -> Option<u32> {
+        debug!(
+            threshold = self.threshold,
+            unchanged = self.unchanged,
+            "ProgressMonitor::observe: called"
+        );
+        if self.threshold == 0 {
+            debug!("ProgressMonitor::observe: detection disabled");
+            return None;
+        }
+
+        let fingerprint = fingerprint(worktree_state, progress_entry);
+        if self.last_fingerprint == Some(fingerprint) {
+            self.unchanged += 1;
+            debug!(
+                unchanged = self.unchanged,
+                "ProgressMonitor::observe: no material change"
+            );
+        } else {
+            debug!("ProgressMonitor::observe: state changed");
+            self.last_fingerprint = Some(fingerprint);
+            self.unchanged = 0;
+        }
+
+        if self.unchanged >= self.threshold {
+            debug!(unchanged = self.unchanged, "ProgressMonitor::observe: loop is stuck");
+            Some(self.unchanged)
+        } else {
+            None
+        }
+    }
+
+    /// Restart the unchanged count (after an intervention)
+    pub fn reset(&mut self) {
+        debug!("ProgressMonitor::reset: called");
+        self.unchanged = 0;
+    }
+}
+
+/// Hash the worktree state together with the progress entry, digits normalized away
+fn fingerprint(worktree_state: &str, progress_entry: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    worktree_state.hash(&mut hasher);
+    let mut in_number = false;
+    for c in progress_entry.chars() {
+        if c.is_ascii_digit() {
+            if !in_number {
+                '#'.hash(&mut hasher);
+            }
+            in_number = true;
+        } else {
+            in_number = false;
+            c.hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monitor_trips_after_threshold() {
+        let mut monitor = ProgressMonitor::new(2);
+
+        assert_eq!(monitor.observe("abc", "## Iteration 1\nerror at line 4"), None);
+        assert_eq!(monitor.observe("abc", "## Iteration 2\nerror at line 4"), None);
+        assert_eq!(monitor.observe("abc", "## Iteration 3\nerror at line 4"), Some(2));
+    }
+
+    #[test]
+    fn test_monitor_resets_on_change() {
+        let mut monitor = ProgressMonitor::new(2);
+
+        monitor.observe("abc", "fail");
+        monitor.observe("abc", "fail");
+        assert_eq!(monitor.observe("abd", "fail"), None);
+        assert_eq!(monitor.observe("abd", "other failure"), None);
+        assert_eq!(monitor.observe("abd", "other failure"), None);
+        assert_eq!(monitor.observe("abd", "other failure"), Some(2));
+
+        monitor.reset();
+        assert_eq!(monitor.observe("abd", "other failure"), None);
+    }
+
+    #[test]
+    fn test_monitor_disabled() {
+        let mut monitor = ProgressMonitor::new(0);
+        for _ in 0..5 {
+            assert_eq!(monitor.observe("same", "same"), None);
+        }
+    }
+
+    #[test]
+    fn test_stuck_detection_yaml() {
+        let config: StuckDetection = serde_yaml::from_str("threshold: 5\naction: escalate").unwrap();
+        assert_eq!(config.threshold, 5);
+        assert_eq!(config.action, StuckAction::Escalate);
+        assert_eq!(config.steering_prompt(), DEFAULT_STEERING_PROMPT);
+
+        let defaults: StuckDetection = serde_yaml::from_str("{}").unwrap();
+        assert_eq!(defaults, StuckDetection::default());
+    }
+}
@@ -0,0 +1,247 @@
+//! Prompt template variables
+//!
+//! A loop type declares the variables its prompt template uses under
+//! `variables:` (type, required, default). The schema is checked twice: when
+//! an execution is submitted, for the variables the submitter has to supply,
+//! and before every render, after the engine has added its own variables
+//! (iteration, git state, progress, parent content, phase, shared facts).
+//! Rendering is full Handlebars with HTML escaping disabled.
+
+use std::collections::BTreeMap;
+
+use handlebars::Handlebars;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::debug;
+
+/// Variables the engine sets on every render (never required from submitters)
+pub const ENGINE_VARIABLES: &[&str] = &[
+    "working-directory",
+    "iteration",
+    "max-iterations",
+    "exec-id",
+    "loop-type",
+    "git-status",
+    "git-diff",
+    "progress",
+    "previous-errors",
+    "plan-content",
+    "spec-content",
+    "phase-content",
+    "parent-content",
+    "current-plan",
+    "phase",
+    "facts",
+    "review-pass",
+    "review-pass-count",
+    "review-pass-name",
+    "review-pass-description",
+    "review-instructions",
+    "budget-phase",
+    "budget-phase-count",
+    "budget-phase-name",
+    "budget-phase-remaining",
+    "budget-phase-instructions",
+    "security-summary",
+    "security-findings",
+];
+
+/// Declared variables of a loop type, by name
+pub type VariableSchema = BTreeMap<String, VariableSpec>;
+
+/// Value type of a template variable
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VariableType {
+    #[default]
+    String,
+    Number,
+    Boolean,
+    List,
+    Object,
+}
+
+impl std::fmt::Display for VariableType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::String => write!(f, "string"),
+            Self::Number => write!(f, "number"),
+            Self::Boolean => write!(f, "boolean"),
+            Self::List => write!(f, "list"),
+            Self::Object => write!(f, "object"),
+        }
+    }
+}
+
+/// Declaration of one template variable
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VariableSpec {
+    /// Value type (string if omitted)
+    #[serde(rename = "type", default)]
+    pub kind: VariableType,
+
+    /// Whether a value must be present (after defaults)
+    #[serde(default)]
+    pub required: bool,
+
+    /// Value used when none is supplied
+    #[serde(default)]
+    pub default: Option<Value>,
+
+    /// Human-readable description
+    #[serde(default)]
+    pub description: String,
+}
+
+/// Convert `value` to `kind`, accepting the string forms execution contexts store
+///
+/// Returns None if the value can't be read as that type.
+fn coerce(kind: VariableType, value: &Value) -> Option<Value> {
+    debug!(%kind, "coerce: called");
+    match (kind, value) {
+        (VariableType::String, Value::String(_)) => Some(value.clone()),
+        (VariableType::String, Value::Number(n)) => Some(Value::String(n.to_string())),
+        (VariableType::String, Value::Bool(b)) => Some(Value::String(b.to_string())),
+        (VariableType::Number, Value::Number(_)) => Some(value.clone()),
+        (VariableType::Number, Value::String(s)) => serde_json::from_str::<serde_json::Number>(s.trim())
+            .ok()
+            .map(Value::Number),
+        (VariableType::Boolean, Value::Bool(_)) => Some(value.clone()),
+        (VariableType::Boolean, Value::String(s)) => match s.trim() {
+            "true" => Some(Value::Bool(true)),
+            "false" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        (VariableType::List, Value::Array(_)) => Some(value.clone()),
+        (VariableType::Object, Value::Object(_)) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+/// Check and normalize `context` against `schema`
+///
+/// Fills defaults, converts values to their declared types, and requires every
+/// required variable except those in `skip`. Undeclared keys pass through.
+fn check(schema: &VariableSchema, context: &Value, skip: &[&str]) -> Result<Value, Vec<String>> {
+    debug!(variables = schema.len(), "check: called");
+    let mut resolved = match context {
+        Value::Object(map) => map.clone(),
+        Value::Null => serde_json::Map::new(),
+        _ => return Err(vec!["Template context must be an object".to_string()]),
+    };
+
+    let mut errors = Vec::new();
+    for (name, spec) in schema {
+        let supplied = resolved.get(name).filter(|v| !v.is_null()).cloned();
+        match supplied.or_else(|| spec.default.clone()) {
+            Some(value) => match coerce(spec.kind, &value) {
+                Some(value) => {
+                    resolved.insert(name.clone(), value);
+                }
+                None => errors.push(format!("Variable '{}' should be a {}, got {}", name, spec.kind, value)),
+            },
+            None if spec.required && !skip.contains(&name.as_str()) => {
+                errors.push(format!("Missing required variable '{}'", name));
+            }
+            None => {}
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(Value::Object(resolved))
+    } else {
+        debug!(error_count = errors.len(), "check: invalid context");
+        Err(errors)
+    }
+}
+
+/// Validate an execution's context when it is submitted
+///
+/// Variables the engine provides (see [`ENGINE_VARIABLES`]) are not required here.
+pub fn validate_submission(schema: &VariableSchema, context: &Value) -> Result<(), Vec<String>> {
+    debug!(variables = schema.len(), "validate_submission: called");
+    check(schema, context, ENGINE_VARIABLES).map(|_| ())
+}
+
+/// Resolve the full render context: defaults applied, types converted, all required present
+pub fn resolve(schema: &VariableSchema, context: &Value) -> Result<Value, Vec<String>> {
+    debug!(variables = schema.len(), "resolve: called");
+    check(schema, context, &[])
+}
+
+/// Handlebars registry for prompts (no HTML escaping)
+pub fn renderer() -> Handlebars<'static> {
+    debug!("renderer: called");
+    let mut hbs = Handlebars::new();
+    hbs.register_escape_fn(handlebars::no_escape);
+    hbs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> VariableSchema {
+        serde_yaml::from_str(
+            r#"
+task:
+  required: true
+max-files:
+  type: number
+  default: 10
+dry-run:
+  type: boolean
+phase-content:
+  required: true
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_submission_skips_engine_variables() {
+        let schema = schema();
+        assert!(validate_submission(&schema, &json!({ "task": "Refactor" })).is_ok());
+
+        let errors = validate_submission(&schema, &json!({ "max-files": "many" })).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                "Variable 'max-files' should be a number, got \"many\"".to_string(),
+                "Missing required variable 'task'".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_applies_defaults_and_types() {
+        let schema = schema();
+        let context = json!({ "task": "Refactor", "dry-run": "false", "phase-content": "# Phase", "extra": 1 });
+        let resolved = resolve(&schema, &context).unwrap();
+        assert_eq!(resolved["max-files"], json!(10));
+        assert_eq!(resolved["dry-run"], json!(false));
+        assert_eq!(resolved["extra"], json!(1));
+
+        let errors = resolve(&schema, &json!({ "task": "Refactor" })).unwrap_err();
+        assert_eq!(errors, vec!["Missing required variable 'phase-content'".to_string()]);
+    }
+
+    #[test]
+    fn test_renderer_handles_blocks_and_hyphenated_names() {
+        let template = "{{task}} in {{working-directory}}{{#if dry-run}} (dry run){{/if}}\n\
+                        {{#each facts}}- {{@key}}: {{this}}\n{{/each}}Phase {{phase.number}}/{{phase.total}} <ok>";
+        let context = json!({
+            "task": "Fix \"quotes\" & <tags>",
+            "working-directory": "/tmp/wt",
+            "dry-run": false,
+            "facts": { "api-url": "http://localhost" },
+            "phase": { "number": 2, "total": 3 },
+        });
+        let rendered = renderer().render_template(template, &context).unwrap();
+        assert_eq!(
+            rendered,
+            "Fix \"quotes\" & <tags> in /tmp/wt\n- api-url: http://localhost\nPhase 2/3 <ok>"
+        );
+    }
+}
@@ -20,12 +20,18 @@
 //! Inherited fields are merged with the child type's fields, with child values
 //! taking precedence.
 //!
+//! ## Composition
+//!
+//! Before parsing, a definition's YAML is assembled from its `include:` files,
+//! `profile:` profiles and the `when:` entry for the environment
+//! (`loops.environment`); see [`super::compose`].
+//!
 //! ## Hot-Reload
 //!
 //! The loader supports hot-reloading via `reload()` method, allowing config
 //! changes without daemon restart.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
@@ -34,8 +40,21 @@ use eyre::{Context, Result};
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info, warn};
 
-use super::config::LoopConfig;
+use super::budget::IterationBudget;
+use super::compose::{Composer, PROFILES_DIR, profile_files};
+use super::config::{LoopConfig, PathLockMode};
+use super::package::{is_loop_type_file, is_package_dir};
+use super::rollback::SnapshotPolicy;
+use super::stuck::StuckDetection;
+use super::template::VariableSchema;
+use super::watchdog::WatchdogPolicy;
 use crate::config::LoopsConfig;
+use crate::hooks::Hooks;
+use crate::progress::{ContextSelection, ExplorationCacheConfig};
+use crate::security::ScannerSpec;
+use crate::tools::{NetworkPolicy, ResourceLimits};
+use crate::validation::{DebateConfig, ReviewPipeline};
+use crate::worktree::MergeConfig;
 
 /// A loop type definition as loaded from YAML
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -78,6 +97,10 @@ pub struct LoopType {
     #[serde(default)]
     pub inputs: Vec<String>,
 
+    /// Declared prompt variables (type, required, default), checked at submission and render
+    #[serde(default)]
+    pub variables: VariableSchema,
+
     /// Output artifacts
     #[serde(default)]
     pub outputs: Vec<String>,
@@ -85,6 +108,99 @@ pub struct LoopType {
     /// Tools available to this loop type
     #[serde(default = "default_tools")]
     pub tools: Vec<String>,
+
+    /// Stuck-loop detection (threshold and action)
+    #[serde(rename = "stuck-detection", default)]
+    pub stuck_detection: Option<StuckDetection>,
+
+    /// Excerpts from the worktree's ContextStore added to each prompt (top-k, token budget)
+    #[serde(rename = "context-selection", default)]
+    pub context_selection: Option<ContextSelection>,
+
+    /// Digests of files read in earlier iterations, listed in each prompt
+    #[serde(rename = "exploration-cache", default)]
+    pub exploration_cache: Option<ExplorationCacheConfig>,
+
+    /// Limits for commands spawned by tools (CPU, memory, wall clock)
+    #[serde(rename = "resource-limits", default)]
+    pub resource_limits: Option<ResourceLimits>,
+
+    /// Per-iteration worktree snapshots and rollback policy
+    #[serde(default)]
+    pub snapshots: Option<SnapshotPolicy>,
+
+    /// Iteration and tool-call timeouts and the action when one trips
+    #[serde(default)]
+    pub watchdog: Option<WatchdogPolicy>,
+
+    /// Most executions of this type running at once (unset: only `concurrency.max-loops`)
+    #[serde(rename = "max-concurrent", default)]
+    pub max_concurrent: Option<usize>,
+
+    /// Mutual-exclusion group: at most one execution across all types in the group runs
+    #[serde(rename = "exclusive-group", default)]
+    pub exclusive_group: Option<String>,
+
+    /// Whether executions wait for declared paths locked elsewhere (`warn` or `serialize`)
+    #[serde(rename = "path-locks", default)]
+    pub path_locks: Option<PathLockMode>,
+
+    /// Ordered review passes the loop works through before it may complete
+    #[serde(default)]
+    pub review: Option<ReviewPipeline>,
+
+    /// Iteration allowances per phase, with optional borrowing between phases
+    #[serde(default)]
+    pub budget: Option<IterationBudget>,
+
+    /// Personas that debate the finished plan before a judge writes the final version
+    #[serde(default)]
+    pub debate: Option<DebateConfig>,
+
+    /// Static-analysis commands the `security_scan` tool runs (cargo-audit, semgrep, ...)
+    #[serde(default)]
+    pub scanners: Option<Vec<ScannerSpec>>,
+
+    /// How branches are merged: conventional-commit message, squash, changelog
+    #[serde(default)]
+    pub merge: Option<MergeConfig>,
+
+    /// Extra directories the read-only tools may read, by mount name (`~/` expanded)
+    #[serde(rename = "read-only-mounts", default)]
+    pub read_only_mounts: Option<BTreeMap<String, String>>,
+
+    /// Hosts tools may reach (`allow`) and may not (`deny`); every request is logged
+    #[serde(default)]
+    pub network: Option<NetworkPolicy>,
+
+    /// Shell hooks by lifecycle event (an event the child leaves out is inherited)
+    #[serde(default)]
+    pub hooks: Hooks,
+
+    /// Slash commands this type adds to the TUI REPL
+    #[serde(rename = "repl-commands", default)]
+    pub repl_commands: Vec<ReplCommandDef>,
+}
+
+/// A REPL slash command contributed by a loop type
+///
+/// Invoking the command renders `prompt` (Handlebars, with `{{args}}` bound to
+/// the command arguments) and sends it to the LLM as a user message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplCommandDef {
+    /// Command name without the leading slash
+    pub name: String,
+
+    /// One-line help text
+    #[serde(default)]
+    pub description: String,
+
+    /// Usage line shown in help (defaults to `/<name> [args...]`)
+    #[serde(default)]
+    pub usage: Option<String>,
+
+    /// Handlebars prompt template
+    pub prompt: String,
 }
 
 impl LoopType {
@@ -127,6 +243,118 @@ impl LoopType {
             self.iteration_timeout_ms = parent.iteration_timeout_ms;
         }
 
+        // Use parent stuck_detection if child doesn't set one
+        if self.stuck_detection.is_none() {
+            debug!("merge_parent: using parent stuck_detection");
+            self.stuck_detection = parent.stuck_detection.clone();
+        }
+
+        // Use parent context selection if child doesn't set one
+        if self.context_selection.is_none() {
+            debug!("merge_parent: using parent context_selection");
+            self.context_selection = parent.context_selection.clone();
+        }
+
+        // Use parent exploration cache if child doesn't set one
+        if self.exploration_cache.is_none() {
+            debug!("merge_parent: using parent exploration_cache");
+            self.exploration_cache = parent.exploration_cache.clone();
+        }
+
+        // Use parent snapshots if child doesn't set them
+        if self.snapshots.is_none() {
+            debug!("merge_parent: using parent snapshots");
+            self.snapshots = parent.snapshots.clone();
+        }
+
+        // Use parent watchdog if child doesn't set one
+        if self.watchdog.is_none() {
+            debug!("merge_parent: using parent watchdog");
+            self.watchdog = parent.watchdog.clone();
+        }
+
+        // Use parent review pipeline if child doesn't set one (`passes: []` drops it)
+        if self.review.is_none() {
+            debug!("merge_parent: using parent review");
+            self.review = parent.review.clone();
+        }
+
+        // Use parent iteration budget if child doesn't set one (`phases: []` drops it)
+        if self.budget.is_none() {
+            debug!("merge_parent: using parent budget");
+            self.budget = parent.budget.clone();
+        }
+
+        // Use parent debate if child doesn't set one
+        if self.debate.is_none() {
+            debug!("merge_parent: using parent debate");
+            self.debate = parent.debate.clone();
+        }
+
+        // Use parent scanners if child doesn't set them
+        if self.scanners.is_none() {
+            debug!("merge_parent: using parent scanners");
+            self.scanners = parent.scanners.clone();
+        }
+
+        // Use parent merge settings if child doesn't set them
+        if self.merge.is_none() {
+            debug!("merge_parent: using parent merge");
+            self.merge = parent.merge.clone();
+        }
+
+        // Use parent read-only mounts if child doesn't set them
+        if self.read_only_mounts.is_none() {
+            debug!("merge_parent: using parent read_only_mounts");
+            self.read_only_mounts = parent.read_only_mounts.clone();
+        }
+
+        // Use parent network policy if child doesn't set one
+        if self.network.is_none() {
+            debug!("merge_parent: using parent network");
+            self.network = parent.network.clone();
+        }
+
+        // Use parent hooks for the events the child doesn't hook
+        self.hooks.inherit(&parent.hooks);
+
+        // Use parent concurrency limits if child doesn't set them
+        if self.max_concurrent.is_none() {
+            debug!("merge_parent: using parent max_concurrent");
+            self.max_concurrent = parent.max_concurrent;
+        }
+        if self.exclusive_group.is_none() {
+            debug!("merge_parent: using parent exclusive_group");
+            self.exclusive_group = parent.exclusive_group.clone();
+        }
+        if self.path_locks.is_none() {
+            debug!("merge_parent: using parent path_locks");
+            self.path_locks = parent.path_locks;
+        }
+
+        // Inherit each resource limit the child leaves unset
+        if let Some(parent_limits) = &parent.resource_limits {
+            debug!("merge_parent: merging parent resource_limits");
+            let limits = self.resource_limits.take().unwrap_or_default();
+            self.resource_limits = Some(limits.merged_with(parent_limits));
+        }
+
+        // Merge REPL commands: add parent commands the child doesn't override
+        for command in &parent.repl_commands {
+            if !self.repl_commands.iter().any(|c| c.name == command.name) {
+                debug!(name = %command.name, "merge_parent: adding parent REPL command");
+                self.repl_commands.push(command.clone());
+            }
+        }
+
+        // Merge variables: add parent declarations the child doesn't override
+        for (name, spec) in &parent.variables {
+            if !self.variables.contains_key(name) {
+                debug!(%name, "merge_parent: adding parent variable");
+                self.variables.insert(name.clone(), spec.clone());
+            }
+        }
+
         // Merge inputs: add parent inputs that child doesn't have
         for input in &parent.inputs {
             if !self.inputs.contains(input) {
@@ -164,6 +392,59 @@ impl LoopType {
     }
 }
 
+/// Reject a review pipeline the engine could not run
+fn check_review(name: &str, loop_type: &LoopType) -> Result<()> {
+    match &loop_type.review {
+        Some(review) => review
+            .validate()
+            .map_err(|e| eyre::eyre!("Loop type '{}' has an invalid review pipeline: {}", name, e)),
+        None => Ok(()),
+    }
+}
+
+/// Reject an iteration budget the engine could not run
+fn check_budget(name: &str, loop_type: &LoopType) -> Result<()> {
+    match &loop_type.budget {
+        Some(budget) => budget
+            .validate()
+            .map_err(|e| eyre::eyre!("Loop type '{}' has an invalid iteration budget: {}", name, e)),
+        None => Ok(()),
+    }
+}
+
+/// Reject a debate the engine could not hold
+fn check_debate(name: &str, loop_type: &LoopType) -> Result<()> {
+    match &loop_type.debate {
+        Some(debate) => debate
+            .validate()
+            .map_err(|e| eyre::eyre!("Loop type '{}' has an invalid debate: {}", name, e)),
+        None => Ok(()),
+    }
+}
+
+/// Loop type files in a loops directory: its `.yml`/`.yaml` files plus those
+/// of each installed package subdirectory (see [`super::package`])
+fn loop_type_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = fs::read_dir(dir).with_context(|| format!("Failed to read directory: {}", dir.display()))?;
+    let mut files = Vec::new();
+    for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+        if path.is_dir() && is_package_dir(&path) {
+            if let Ok(package_entries) = fs::read_dir(&path) {
+                files.extend(
+                    package_entries
+                        .filter_map(|e| e.ok())
+                        .map(|e| e.path())
+                        .filter(|p| p.file_name().is_some_and(|n| is_loop_type_file(Path::new(n)))),
+                );
+            }
+        } else if path.extension().is_some_and(|e| e == "yml" || e == "yaml") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
 fn default_validation_command() -> String {
     debug!("default_validation_command: called");
     "otto ci".to_string()
@@ -198,6 +479,10 @@ const BUILTIN_PLAN: &str = include_str!("builtin_types/plan.yml");
 const BUILTIN_SPEC: &str = include_str!("builtin_types/spec.yml");
 const BUILTIN_PHASE: &str = include_str!("builtin_types/phase.yml");
 const BUILTIN_RALPH: &str = include_str!("builtin_types/ralph.yml");
+const BUILTIN_SECURITY_REVIEW: &str = include_str!("builtin_types/security-review.yml");
+const BUILTIN_DEPS_UPGRADE: &str = include_str!("builtin_types/deps-upgrade.yml");
+const BUILTIN_DEP_BUMP: &str = include_str!("builtin_types/dep-bump.yml");
+const BUILTIN_REVIEW: &str = include_str!("builtin_types/review.yml");
 
 /// Tracked file for hot-reload detection
 #[derive(Debug, Clone)]
@@ -217,6 +502,9 @@ pub struct LoopLoader {
     /// Tracked files for hot-reload
     tracked_files: Vec<TrackedFile>,
 
+    /// Files that failed to load (skipped), with the error
+    load_errors: Vec<(PathBuf, String)>,
+
     /// Configuration used for loading
     config: LoopsConfig,
 }
@@ -229,6 +517,7 @@ impl LoopLoader {
             raw_types: HashMap::new(),
             types: HashMap::new(),
             tracked_files: Vec::new(),
+            load_errors: Vec::new(),
             config: config.clone(),
         };
 
@@ -242,6 +531,7 @@ impl LoopLoader {
         debug!("load_all: called");
         self.raw_types.clear();
         self.tracked_files.clear();
+        self.load_errors.clear();
 
         // Load builtin types first (if enabled)
         if self.config.use_builtin() {
@@ -252,10 +542,17 @@ impl LoopLoader {
         }
 
         // Load from configured paths (later overrides earlier)
-        for path in self.config.expanded_paths() {
+        let paths = self.config.expanded_paths();
+        let mut composer = Composer::new(self.config.environment(), &paths);
+        for path in &paths {
+            for profile in profile_files(&path.join(PROFILES_DIR)) {
+                self.track(&profile);
+            }
+        }
+        for path in paths {
             if path.exists() {
                 debug!(?path, "load_all: loading from directory");
-                self.load_from_directory(&path)?;
+                self.load_from_directory(&path, &mut composer)?;
             } else {
                 debug!(?path, "load_all: directory does not exist, skipping");
             }
@@ -283,20 +580,12 @@ impl LoopLoader {
             }
         }
 
-        // Also check for new files in tracked directories
+        // Also check for new files in tracked directories (and installed packages)
         for path in self.config.expanded_paths() {
-            if path.exists()
-                && path.is_dir()
-                && let Ok(entries) = fs::read_dir(&path)
-            {
-                for entry in entries.filter_map(|e| e.ok()) {
-                    let file_path = entry.path();
-                    if file_path
-                        .extension()
-                        .map(|e| e == "yml" || e == "yaml")
-                        .unwrap_or(false)
-                        && !self.tracked_files.iter().any(|t| t.path == file_path)
-                    {
+            if path.is_dir() {
+                let profiles = profile_files(&path.join(PROFILES_DIR));
+                for file_path in loop_type_files(&path).unwrap_or_default().into_iter().chain(profiles) {
+                    if !self.tracked_files.iter().any(|t| t.path == file_path) {
                         debug!(path = ?file_path, "has_changes: new file detected");
                         return true;
                     }
@@ -330,7 +619,11 @@ impl LoopLoader {
         self.load_builtin_type("spec", BUILTIN_SPEC)?;
         self.load_builtin_type("phase", BUILTIN_PHASE)?;
         self.load_builtin_type("ralph", BUILTIN_RALPH)?;
-        debug!("load_builtins: loaded 4 builtin loop types");
+        self.load_builtin_type("security-review", BUILTIN_SECURITY_REVIEW)?;
+        self.load_builtin_type("deps-upgrade", BUILTIN_DEPS_UPGRADE)?;
+        self.load_builtin_type("dep-bump", BUILTIN_DEP_BUMP)?;
+        self.load_builtin_type("review", BUILTIN_REVIEW)?;
+        debug!("load_builtins: loaded 8 builtin loop types");
         Ok(())
     }
 
@@ -344,22 +637,21 @@ impl LoopLoader {
         Ok(())
     }
 
-    /// Load all .yml files from a directory
-    fn load_from_directory(&mut self, dir: &Path) -> Result<()> {
+    /// Load all .yml files from a directory and the packages installed in it
+    fn load_from_directory(&mut self, dir: &Path, composer: &mut Composer) -> Result<()> {
         debug!(?dir, "load_from_directory: called");
 
-        let entries = fs::read_dir(dir).with_context(|| format!("Failed to read directory: {}", dir.display()))?;
-
-        for entry in entries.filter_map(|e| e.ok()) {
-            let path = entry.path();
-            if path.extension().map(|e| e == "yml" || e == "yaml").unwrap_or(false) {
-                debug!(?path, "load_from_directory: loading file");
-                if let Err(e) = self.load_from_file(&path) {
-                    debug!(?path, error = %e, "load_from_directory: failed to load file");
-                    warn!(?path, error = %e, "Failed to load loop type file");
-                }
-            } else {
-                debug!(?path, "load_from_directory: skipping non-yaml file");
+        for path in loop_type_files(dir)? {
+            debug!(?path, "load_from_directory: loading file");
+            let result = self.load_from_file(&path, composer);
+            // Track includes and profiles even when the file failed, so fixing them reloads it
+            for included in composer.take_read() {
+                self.track(&included);
+            }
+            if let Err(e) = result {
+                debug!(?path, error = %e, "load_from_directory: failed to load file");
+                warn!(?path, error = %e, "Failed to load loop type file");
+                self.load_errors.push((path, format!("{:#}", e)));
             }
         }
 
@@ -367,49 +659,60 @@ impl LoopLoader {
         Ok(())
     }
 
-    /// Load a loop type from a YAML file
-    fn load_from_file(&mut self, path: &Path) -> Result<()> {
-        debug!(?path, "load_from_file: called");
-        let content = fs::read_to_string(path).with_context(|| format!("Failed to read: {}", path.display()))?;
-        debug!(?path, content_len = content.len(), "load_from_file: read content");
-
-        // Track file for hot-reload
+    /// Track a file for hot-reload (once)
+    fn track(&mut self, path: &Path) {
+        if self.tracked_files.iter().any(|t| t.path == path) {
+            return;
+        }
         if let Ok(metadata) = fs::metadata(path)
             && let Ok(modified) = metadata.modified()
         {
-            debug!(?path, "load_from_file: tracking file for hot-reload");
+            debug!(?path, "track: tracking file for hot-reload");
             self.tracked_files.push(TrackedFile {
                 path: path.to_path_buf(),
                 modified,
             });
         } else {
-            debug!(?path, "load_from_file: could not track file metadata");
+            debug!(?path, "track: could not track file metadata");
         }
+    }
 
-        // The file can contain a map of name -> definition, or just a definition
-        // Try parsing as a map first (like taskdaemon.yml format)
-        if let Ok(map) = serde_yaml::from_str::<HashMap<String, LoopType>>(&content) {
-            debug!(?path, count = map.len(), "load_from_file: parsed as map");
-            for (name, loop_type) in map {
-                debug!(?path, %name, "load_from_file: inserting type from map");
-                self.raw_types.insert(name, loop_type);
-            }
-            return Ok(());
+    /// Load the loop types in a YAML file, composing includes, profiles and `when` overrides
+    fn load_from_file(&mut self, path: &Path, composer: &mut Composer) -> Result<()> {
+        debug!(?path, "load_from_file: called");
+        let content = fs::read_to_string(path).with_context(|| format!("Failed to read: {}", path.display()))?;
+        debug!(?path, content_len = content.len(), "load_from_file: read content");
+        self.track(path);
+
+        // The file can contain a map of name -> definition (like taskdaemon.yml),
+        // or just a definition named after the file
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let definitions = composer
+            .compose_file(&content, dir)
+            .with_context(|| format!("Failed to compose: {}", path.display()))?;
+
+        let mut loaded = Vec::new();
+        for (name, definition) in definitions {
+            let name = match name {
+                Some(name) => name,
+                None => path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .ok_or_else(|| eyre::eyre!("Invalid filename: {}", path.display()))?
+                    .to_string(),
+            };
+            let loop_type: LoopType = serde_yaml::from_value(definition)
+                .with_context(|| format!("Failed to parse loop type '{}' in {}", name, path.display()))?;
+            check_review(&name, &loop_type)?;
+            check_budget(&name, &loop_type)?;
+            check_debate(&name, &loop_type)?;
+            loaded.push((name, loop_type));
         }
 
-        debug!(?path, "load_from_file: parsing as single definition");
-        // Fall back to single definition with filename as name
-        let loop_type: LoopType =
-            serde_yaml::from_str(&content).with_context(|| format!("Failed to parse: {}", path.display()))?;
-
-        let name = path
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .ok_or_else(|| eyre::eyre!("Invalid filename: {}", path.display()))?;
-
-        debug!(?path, %name, "load_from_file: inserting single type");
-        self.raw_types.insert(name.to_string(), loop_type);
-
+        for (name, loop_type) in loaded {
+            debug!(?path, %name, "load_from_file: inserting type");
+            self.raw_types.insert(name, loop_type);
+        }
         Ok(())
     }
 
@@ -487,6 +790,11 @@ impl LoopLoader {
         result
     }
 
+    /// Loop type files that failed to load and were skipped
+    pub fn load_errors(&self) -> &[(PathBuf, String)] {
+        &self.load_errors
+    }
+
     /// Get all loaded loop type names
     pub fn names(&self) -> impl Iterator<Item = &str> {
         debug!(count = self.types.len(), "LoopLoader::names: called");
@@ -547,6 +855,24 @@ impl LoopLoader {
                         tools: loop_type.tools.clone(),
                         progress_max_entries: 5, // Default
                         progress_max_chars: 500, // Default
+                        stuck_detection: loop_type.stuck_detection.clone().unwrap_or_default(),
+                        context_selection: loop_type.context_selection.clone().unwrap_or_default(),
+                        exploration_cache: loop_type.exploration_cache.clone().unwrap_or_default(),
+                        resource_limits: loop_type.resource_limits.clone().unwrap_or_default(),
+                        snapshots: loop_type.snapshots.clone().unwrap_or_default(),
+                        variables: loop_type.variables.clone(),
+                        max_concurrent: loop_type.max_concurrent,
+                        exclusive_group: loop_type.exclusive_group.clone(),
+                        path_locks: loop_type.path_locks.unwrap_or_default(),
+                        watchdog: loop_type.watchdog.clone().unwrap_or_default(),
+                        review: loop_type.review.clone(),
+                        budget: loop_type.budget.clone(),
+                        debate: loop_type.debate.clone(),
+                        scanners: loop_type.scanners.clone().unwrap_or_default(),
+                        merge: loop_type.merge.clone(),
+                        read_only_mounts: expand_mounts(loop_type.read_only_mounts.as_ref()),
+                        network: loop_type.network.clone().unwrap_or_default(),
+                        hooks: loop_type.hooks.clone(),
                     },
                 )
             })
@@ -556,6 +882,22 @@ impl LoopLoader {
     }
 }
 
+/// Mount paths with a leading `~/` expanded to the home directory
+fn expand_mounts(mounts: Option<&BTreeMap<String, String>>) -> BTreeMap<String, PathBuf> {
+    debug!(?mounts, "expand_mounts: called");
+    mounts
+        .into_iter()
+        .flatten()
+        .map(|(name, path)| {
+            let expanded = match (path.strip_prefix("~/"), dirs::home_dir()) {
+                (Some(rest), Some(home)) => home.join(rest),
+                _ => PathBuf::from(path),
+            };
+            (name.clone(), expanded)
+        })
+        .collect()
+}
+
 impl From<LoopType> for LoopConfig {
     fn from(lt: LoopType) -> Self {
         LoopConfig {
@@ -570,6 +912,24 @@ impl From<LoopType> for LoopConfig {
             tools: lt.tools,
             progress_max_entries: 5,
             progress_max_chars: 500,
+            stuck_detection: lt.stuck_detection.unwrap_or_default(),
+            context_selection: lt.context_selection.unwrap_or_default(),
+            exploration_cache: lt.exploration_cache.unwrap_or_default(),
+            resource_limits: lt.resource_limits.unwrap_or_default(),
+            snapshots: lt.snapshots.unwrap_or_default(),
+            variables: lt.variables,
+            max_concurrent: lt.max_concurrent,
+            exclusive_group: lt.exclusive_group,
+            path_locks: lt.path_locks.unwrap_or_default(),
+            watchdog: lt.watchdog.unwrap_or_default(),
+            review: lt.review,
+            budget: lt.budget,
+            debate: lt.debate,
+            scanners: lt.scanners.unwrap_or_default(),
+            merge: lt.merge,
+            read_only_mounts: expand_mounts(lt.read_only_mounts.as_ref()),
+            network: lt.network.unwrap_or_default(),
+            hooks: lt.hooks,
         }
     }
 }
@@ -616,6 +976,38 @@ mod tests {
         assert!(!loop_type.prompt_template.is_empty());
     }
 
+    #[test]
+    fn test_builtin_security_review_parses() {
+        let loop_type: LoopType = serde_yaml::from_str(BUILTIN_SECURITY_REVIEW).unwrap();
+        let scanners = loop_type.scanners.unwrap();
+        assert_eq!(scanners.len(), 2);
+        assert_eq!(scanners[0].format, crate::security::ScannerFormat::CargoAudit);
+        assert!(loop_type.tools.contains(&"security_scan".to_string()));
+        assert!(loop_type.tools.contains(&"triage_finding".to_string()));
+        assert!(loop_type.parent.is_none());
+    }
+
+    #[test]
+    fn test_builtin_dependency_upgrade_types_parse() {
+        let upgrade: LoopType = serde_yaml::from_str(BUILTIN_DEPS_UPGRADE).unwrap();
+        assert!(upgrade.tools.contains(&"outdated_deps".to_string()));
+        assert!(!upgrade.tools.contains(&"write".to_string()));
+
+        let bump: LoopType = serde_yaml::from_str(BUILTIN_DEP_BUMP).unwrap();
+        assert_eq!(bump.exclusive_group.as_deref(), Some("lockfile"));
+        assert!(bump.parent.is_none());
+        assert!(bump.prompt_template.contains("{{dep-bump-command}}"));
+    }
+
+    #[test]
+    fn test_builtin_review_parses() {
+        let loop_type: LoopType = serde_yaml::from_str(BUILTIN_REVIEW).unwrap();
+        assert!(loop_type.tools.contains(&"review_comment".to_string()));
+        assert!(loop_type.tools.contains(&"finish_review".to_string()));
+        assert!(!loop_type.tools.contains(&"write".to_string()));
+        assert!(loop_type.prompt_template.contains("{{review-diff}}"));
+    }
+
     #[test]
     fn test_load_builtins() {
         let config = LoopsConfig::default();
@@ -625,7 +1017,11 @@ mod tests {
         assert!(loader.get("spec").is_some());
         assert!(loader.get("phase").is_some());
         assert!(loader.get("ralph").is_some());
-        assert_eq!(loader.len(), 4);
+        assert!(loader.get("security-review").is_some());
+        assert!(loader.get("deps-upgrade").is_some());
+        assert!(loader.get("dep-bump").is_some());
+        assert!(loader.get("review").is_some());
+        assert_eq!(loader.len(), 8);
     }
 
     #[test]
@@ -695,6 +1091,9 @@ inputs:
 tools:
   - read_file
   - custom_tool
+stuck-detection:
+  threshold: 4
+  action: pause
 "#;
 
         let child_yaml = r#"
@@ -722,12 +1121,178 @@ inputs:
         // Tools from parent (child used default)
         assert!(child.tools.contains(&"read_file".to_string()));
         assert!(child.tools.contains(&"custom_tool".to_string()));
+        // Stuck detection from parent (child didn't set one)
+        let stuck = child.stuck_detection.unwrap();
+        assert_eq!(stuck.threshold, 4);
+        assert_eq!(stuck.action, crate::r#loop::StuckAction::Pause);
+    }
+
+    #[test]
+    fn test_merge_parent_resource_limits() {
+        let parent_yaml = r#"
+prompt-template: "Parent prompt"
+resource-limits:
+  cpu-secs: 600
+  timeout-ms: 900000
+"#;
+
+        let child_yaml = r#"
+extends: parent
+prompt-template: "Child prompt"
+resource-limits:
+  memory-mb: 4096
+  timeout-ms: 60000
+"#;
+
+        let parent: LoopType = serde_yaml::from_str(parent_yaml).unwrap();
+        let mut child: LoopType = serde_yaml::from_str(child_yaml).unwrap();
+
+        child.merge_parent(&parent);
+
+        let config = LoopConfig::from(child);
+        assert_eq!(config.resource_limits.cpu_secs, Some(600));
+        assert_eq!(config.resource_limits.memory_mb, Some(4096));
+        assert_eq!(config.resource_limits.timeout_ms, Some(60000));
+    }
+
+    #[test]
+    fn test_read_only_mounts_inherit_and_expand() {
+        let parent_yaml = r#"
+prompt-template: "Parent prompt"
+read-only-mounts:
+  upstream: ~/src/upstream
+  specs: /srv/specs
+"#;
+        let parent: LoopType = serde_yaml::from_str(parent_yaml).unwrap();
+        let mut child: LoopType = serde_yaml::from_str("extends: parent\nprompt-template: \"Child\"").unwrap();
+        child.merge_parent(&parent);
+
+        let config = LoopConfig::from(child);
+        assert_eq!(config.read_only_mounts["specs"], PathBuf::from("/srv/specs"));
+        if let Some(home) = dirs::home_dir() {
+            assert_eq!(config.read_only_mounts["upstream"], home.join("src/upstream"));
+        }
+    }
+
+    #[test]
+    fn test_merge_parent_concurrency_limits() {
+        let parent: LoopType = serde_yaml::from_str(
+            "prompt-template: p\nmax-concurrent: 1\nexclusive-group: schema\npath-locks: serialize",
+        )
+        .unwrap();
+        let mut child: LoopType =
+            serde_yaml::from_str("extends: parent\nprompt-template: c\nmax-concurrent: 3").unwrap();
+
+        child.merge_parent(&parent);
+
+        let config = LoopConfig::from(child);
+        assert_eq!(config.max_concurrent, Some(3));
+        assert_eq!(config.exclusive_group.as_deref(), Some("schema"));
+        assert_eq!(config.path_locks, PathLockMode::Serialize);
+    }
+
+    #[test]
+    fn test_builtin_plan_review_pipeline() {
+        let loop_type: LoopType = serde_yaml::from_str(BUILTIN_PLAN).unwrap();
+        let review = loop_type.review.unwrap();
+        let names: Vec<&str> = review.passes.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["completeness", "correctness", "edge-cases", "architecture", "clarity"]
+        );
+        assert!(review.passes.iter().all(|p| p.converge.unchanged));
+        assert!(review.validate().is_ok());
+    }
+
+    #[test]
+    fn test_merge_parent_merge_config() {
+        let parent: LoopType =
+            serde_yaml::from_str("prompt-template: p\nmerge:\n  squash: true\n  type: fix\n  changelog: CHANGELOG.md")
+                .unwrap();
+        let mut child: LoopType = serde_yaml::from_str("extends: parent\nprompt-template: c").unwrap();
+        child.merge_parent(&parent);
+
+        let merge = LoopConfig::from(child).merge.unwrap();
+        assert!(merge.squash && merge.llm);
+        assert_eq!(merge.commit_type.as_deref(), Some("fix"));
+        assert_eq!(merge.changelog.as_deref(), Some("CHANGELOG.md"));
+    }
+
+    #[test]
+    fn test_debate_parses_and_inherits() {
+        let parent: LoopType = serde_yaml::from_str(
+            "prompt-template: p\ndebate:\n  rounds: 3\n  personas:\n    - name: architect\n      prompt: build\n\
+             \x20   - name: security\n      prompt: attack",
+        )
+        .unwrap();
+        assert!(check_debate("parent", &parent).is_ok());
+        let mut child: LoopType = serde_yaml::from_str("extends: parent\nprompt-template: c").unwrap();
+        child.merge_parent(&parent);
+
+        let debate = LoopConfig::from(child).debate.unwrap();
+        assert_eq!(debate.rounds, 3);
+        assert_eq!(debate.personas[1].name, "security");
+        assert_eq!(debate.transcript, "debate.md");
+
+        let lone: LoopType =
+            serde_yaml::from_str("prompt-template: p\ndebate:\n  personas:\n    - name: a\n      prompt: x").unwrap();
+        assert!(check_debate("lone", &lone).is_err());
+    }
+
+    #[test]
+    fn test_merge_parent_review() {
+        let parent: LoopType = serde_yaml::from_str(
+            "prompt-template: p\nreview:\n  passes:\n    - name: security\n      prompt: look for secrets",
+        )
+        .unwrap();
+        let mut inherits: LoopType = serde_yaml::from_str("extends: parent\nprompt-template: c").unwrap();
+        let mut drops: LoopType =
+            serde_yaml::from_str("extends: parent\nprompt-template: c\nreview:\n  passes: []").unwrap();
+
+        inherits.merge_parent(&parent);
+        drops.merge_parent(&parent);
+
+        assert_eq!(LoopConfig::from(inherits).review.unwrap().passes[0].name, "security");
+        assert!(LoopConfig::from(drops).review.unwrap().passes.is_empty());
+    }
+
+    #[test]
+    fn test_merge_parent_repl_commands() {
+        let parent_yaml = r#"
+prompt-template: "Parent prompt"
+repl-commands:
+  - name: review
+    description: "Review a file"
+    prompt: "Review {{args}}"
+  - name: explain
+    prompt: "Explain {{args}}"
+"#;
+
+        let child_yaml = r#"
+extends: parent
+prompt-template: "Child prompt"
+repl-commands:
+  - name: review
+    usage: "/review <file>"
+    prompt: "Review {{args}} strictly"
+"#;
+
+        let parent: LoopType = serde_yaml::from_str(parent_yaml).unwrap();
+        let mut child: LoopType = serde_yaml::from_str(child_yaml).unwrap();
+        child.merge_parent(&parent);
+
+        let names: Vec<_> = child.repl_commands.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["review", "explain"]);
+        // Child override wins
+        assert_eq!(child.repl_commands[0].prompt, "Review {{args}} strictly");
+        assert_eq!(child.repl_commands[0].usage.as_deref(), Some("/review <file>"));
     }
 
     #[test]
     fn test_has_changes_no_files() {
         let config = LoopsConfig {
             paths: vec!["builtin".to_string()],
+            ..Default::default()
         };
         let loader = LoopLoader::new(&config).unwrap();
 
@@ -735,6 +1300,74 @@ inputs:
         assert!(!loader.has_changes());
     }
 
+    #[test]
+    fn test_loads_installed_packages() {
+        let temp = tempfile::tempdir().unwrap();
+        let package = temp.path().join("review");
+        std::fs::create_dir(&package).unwrap();
+        std::fs::write(package.join("package.yml"), "name: review\nversion: 1.0.0\nfiles: {}\n").unwrap();
+        std::fs::write(package.join("review.yml"), "prompt-template: Review {{task}}\n").unwrap();
+        // Directories without a manifest are not packages
+        std::fs::create_dir(temp.path().join("drafts")).unwrap();
+        std::fs::write(temp.path().join("drafts/draft.yml"), "prompt-template: Draft\n").unwrap();
+
+        let config = LoopsConfig {
+            paths: vec![temp.path().to_string_lossy().to_string()],
+            ..Default::default()
+        };
+        let loader = LoopLoader::new(&config).unwrap();
+        assert!(loader.get("review").is_some());
+        assert!(loader.get("package").is_none());
+        assert!(loader.get("draft").is_none());
+        assert!(loader.load_errors().is_empty());
+    }
+
+    #[test]
+    fn test_profiles_includes_and_environment() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(temp.path().join("profiles")).unwrap();
+        std::fs::create_dir_all(temp.path().join("shared")).unwrap();
+        std::fs::write(
+            temp.path().join("profiles/rust-workspace.yml"),
+            "validation-command: cargo test --workspace\nwhen:\n  ci:\n    validation-command: cargo test --workspace --locked\n",
+        )
+        .unwrap();
+        std::fs::write(temp.path().join("shared/prompt.yml"), "prompt-template: Implement {{task}}\n").unwrap();
+        std::fs::write(
+            temp.path().join("loops.yml"),
+            "fix:\n  include: shared/prompt.yml\n  profile: rust-workspace\n  max-iterations: 20\n\
+             refactor:\n  include: shared/prompt.yml\n  extends: fix\n",
+        )
+        .unwrap();
+
+        let mut config = LoopsConfig {
+            paths: vec![temp.path().to_string_lossy().to_string()],
+            environment: Some("local".to_string()),
+        };
+        let loader = LoopLoader::new(&config).unwrap();
+        assert!(loader.load_errors().is_empty(), "{:?}", loader.load_errors());
+        let fix = loader.get("fix").unwrap();
+        assert_eq!(fix.prompt_template, "Implement {{task}}");
+        assert_eq!(fix.validation_command, "cargo test --workspace");
+        assert_eq!(fix.max_iterations, 20);
+        // Profiles are not loop types
+        assert!(loader.get("rust-workspace").is_none());
+        assert_eq!(loader.get("refactor").unwrap().max_iterations, 20);
+
+        config.environment = Some("ci".to_string());
+        let loader = LoopLoader::new(&config).unwrap();
+        assert_eq!(
+            loader.get("fix").unwrap().validation_command,
+            "cargo test --workspace --locked"
+        );
+
+        // A missing profile skips the file with an error
+        std::fs::write(temp.path().join("broken.yml"), "profile: go-module\nprompt-template: x\n").unwrap();
+        let loader = LoopLoader::new(&config).unwrap();
+        assert!(loader.get("broken").is_none());
+        assert!(loader.load_errors()[0].1.contains("Unknown profile 'go-module'"));
+    }
+
     #[test]
     fn test_four_level_hierarchy() {
         let config = LoopsConfig::default();
@@ -6,6 +6,7 @@ use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tracing::debug;
 
+use crate::clock::Clock;
 use crate::events::EventEmitter;
 
 /// Result of running validation command
@@ -40,10 +41,12 @@ impl ValidationResult {
 pub async fn run_validation(
     command: &str,
     worktree: &std::path::Path,
+    env: &[(String, String)],
     timeout: Duration,
+    clock: &dyn Clock,
 ) -> eyre::Result<ValidationResult> {
     debug!(%command, ?worktree, timeout_ms = timeout.as_millis() as u64, "run_validation: called");
-    let start = std::time::Instant::now();
+    let start = clock.instant();
 
     debug!(%command, "run_validation: executing command");
     let output = tokio::time::timeout(
@@ -52,13 +55,15 @@ pub async fn run_validation(
             .arg("-c")
             .arg(command)
             .current_dir(worktree)
+            .envs(env.iter().cloned())
+            .kill_on_drop(true)
             .output(),
     )
     .await;
 
     match output {
         Ok(Ok(output)) => {
-            let duration_ms = start.elapsed().as_millis() as u64;
+            let duration_ms = clock.instant().duration_since(start).as_millis() as u64;
             let exit_code = output.status.code().unwrap_or(-1);
             debug!(exit_code, duration_ms, "run_validation: command completed");
             Ok(ValidationResult {
@@ -86,12 +91,14 @@ pub async fn run_validation(
 pub async fn run_validation_streaming(
     command: &str,
     worktree: &std::path::Path,
+    env: &[(String, String)],
     timeout: Duration,
     emitter: &EventEmitter,
     iteration: u32,
+    clock: &dyn Clock,
 ) -> eyre::Result<ValidationResult> {
     debug!(%command, ?worktree, timeout_ms = timeout.as_millis() as u64, "run_validation_streaming: called");
-    let start = std::time::Instant::now();
+    let start = clock.instant();
 
     // Emit validation started event
     emitter.validation_started(iteration, command);
@@ -101,8 +108,10 @@ pub async fn run_validation_streaming(
         .arg("-c")
         .arg(command)
         .current_dir(worktree)
+        .envs(env.iter().cloned())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
+        .kill_on_drop(true)
         .spawn()?;
 
     let stdout = child
@@ -169,7 +178,7 @@ pub async fn run_validation_streaming(
     let stdout_output = stdout_task.await.unwrap_or_default();
     let stderr_output = stderr_task.await.unwrap_or_default();
 
-    let duration_ms = start.elapsed().as_millis() as u64;
+    let duration_ms = clock.instant().duration_since(start).as_millis() as u64;
     let exit_code = status.code().unwrap_or(-1);
 
     // Emit validation completed event
@@ -187,13 +196,14 @@ pub async fn run_validation_streaming(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::SystemClock;
     use crate::events::EventBus;
     use tempfile::tempdir;
 
     #[tokio::test]
     async fn test_validation_success() {
         let temp = tempdir().unwrap();
-        let result = run_validation("echo ok", temp.path(), Duration::from_secs(30))
+        let result = run_validation("echo ok", temp.path(), &[], Duration::from_secs(30), &SystemClock)
             .await
             .unwrap();
 
@@ -205,7 +215,7 @@ mod tests {
     #[tokio::test]
     async fn test_validation_failure() {
         let temp = tempdir().unwrap();
-        let result = run_validation("exit 1", temp.path(), Duration::from_secs(30))
+        let result = run_validation("exit 1", temp.path(), &[], Duration::from_secs(30), &SystemClock)
             .await
             .unwrap();
 
@@ -216,7 +226,7 @@ mod tests {
     #[tokio::test]
     async fn test_validation_timeout() {
         let temp = tempdir().unwrap();
-        let result = run_validation("sleep 10", temp.path(), Duration::from_millis(100)).await;
+        let result = run_validation("sleep 10", temp.path(), &[], Duration::from_millis(100), &SystemClock).await;
 
         // Should timeout
         assert!(result.is_err());
@@ -232,9 +242,11 @@ mod tests {
         let result = run_validation_streaming(
             "echo hello; echo world",
             temp.path(),
+            &[],
             Duration::from_secs(30),
             &emitter,
             1,
+            &SystemClock,
         )
         .await
         .unwrap();
@@ -261,9 +273,17 @@ mod tests {
         let emitter = bus.emitter_for("test-exec");
         let mut rx = bus.subscribe();
 
-        let result = run_validation_streaming("echo error >&2", temp.path(), Duration::from_secs(30), &emitter, 1)
-            .await
-            .unwrap();
+        let result = run_validation_streaming(
+            "echo error >&2",
+            temp.path(),
+            &[],
+            Duration::from_secs(30),
+            &emitter,
+            1,
+            &SystemClock,
+        )
+        .await
+        .unwrap();
 
         assert_eq!(result.exit_code, 0);
         assert!(result.stderr.contains("error"));
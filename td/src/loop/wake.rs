@@ -0,0 +1,207 @@
+//! Wake condition evaluation for parked executions
+//!
+//! The TaskManager calls `first_met` for every parked execution on its wake
+//! check interval. Conditions are cheap, read-only probes: a glob lookup, a
+//! `git rev-parse` (after fetching the remote for `<remote>/<branch>` refs),
+//! or a single HTTP GET. Probe failures count as "not yet", never as errors.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tracing::debug;
+
+use crate::domain::{LoopExecution, WakeCondition};
+
+/// Timeout for the HTTP probe
+const HTTP_PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Resolve a git ref to a commit SHA in `repo_root` (None if it doesn't exist)
+pub async fn resolve_ref(repo_root: &Path, git_ref: &str) -> Option<String> {
+    debug!(?repo_root, %git_ref, "resolve_ref: called");
+    let output = tokio::process::Command::new("git")
+        .args(["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", git_ref)])
+        .current_dir(repo_root)
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        debug!(%git_ref, "resolve_ref: ref not found");
+        return None;
+    }
+    let sha = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!sha.is_empty()).then_some(sha)
+}
+
+/// Fetch `<remote>/<branch>` refs so a merged PR shows up without anyone pulling
+async fn fetch_remote_ref(repo_root: &Path, git_ref: &str) {
+    debug!(?repo_root, %git_ref, "fetch_remote_ref: called");
+    let Some((remote, branch)) = git_ref.split_once('/') else {
+        debug!("fetch_remote_ref: not a remote ref");
+        return;
+    };
+
+    let remotes = match tokio::process::Command::new("git")
+        .arg("remote")
+        .current_dir(repo_root)
+        .output()
+        .await
+    {
+        Ok(output) => String::from_utf8_lossy(&output.stdout).to_string(),
+        Err(e) => {
+            debug!(%e, "fetch_remote_ref: git remote failed");
+            return;
+        }
+    };
+    if !remotes.lines().any(|r| r.trim() == remote) {
+        debug!(%remote, "fetch_remote_ref: unknown remote");
+        return;
+    }
+
+    let result = tokio::process::Command::new("git")
+        .args(["fetch", "--quiet", remote, branch])
+        .current_dir(repo_root)
+        .output()
+        .await;
+    debug!(ok = result.is_ok(), "fetch_remote_ref: fetched");
+}
+
+/// Whether any path matches `pattern` (relative patterns resolve against `base`)
+pub(super) fn glob_matches(base: &Path, pattern: &str) -> bool {
+    debug!(?base, %pattern, "glob_matches: called");
+    let full = if Path::new(pattern).is_absolute() {
+        PathBuf::from(pattern)
+    } else {
+        base.join(pattern)
+    };
+    match glob::glob(&full.to_string_lossy()) {
+        Ok(mut paths) => paths.any(|p| p.is_ok()),
+        Err(e) => {
+            debug!(%e, "glob_matches: invalid pattern");
+            false
+        }
+    }
+}
+
+/// Whether a GET of `url` returns 200
+async fn http_ok(url: &str) -> bool {
+    debug!(%url, "http_ok: called");
+    let client = reqwest::Client::builder()
+        .timeout(HTTP_PROBE_TIMEOUT)
+        .user_agent("TaskDaemon/0.1 (wake check)")
+        .build()
+        .unwrap_or_default();
+    match client.get(url).send().await {
+        Ok(response) => response.status() == reqwest::StatusCode::OK,
+        Err(e) => {
+            debug!(%e, "http_ok: request failed");
+            false
+        }
+    }
+}
+
+/// Evaluate a single wake condition
+///
+/// `worktree` anchors relative file globs; `repo_root` is where git refs are resolved.
+pub async fn condition_met(condition: &WakeCondition, worktree: &Path, repo_root: &Path) -> bool {
+    debug!(%condition, "condition_met: called");
+    match condition {
+        WakeCondition::FileExists { pattern } => glob_matches(worktree, pattern),
+        WakeCondition::RefAdvanced { git_ref, from } => {
+            fetch_remote_ref(repo_root, git_ref).await;
+            match resolve_ref(repo_root, git_ref).await {
+                Some(current) => from.as_deref() != Some(current.as_str()),
+                None => false,
+            }
+        }
+        WakeCondition::HttpOk { url } => http_ok(url).await,
+    }
+}
+
+/// First of the execution's wake conditions that currently holds
+pub async fn first_met<'a>(exec: &'a LoopExecution, repo_root: &Path) -> Option<&'a WakeCondition> {
+    debug!(exec_id = %exec.id, count = exec.wake_conditions.len(), "first_met: called");
+    let worktree = exec
+        .worktree
+        .as_deref()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| repo_root.to_path_buf());
+    for condition in &exec.wake_conditions {
+        if condition_met(condition, &worktree, repo_root).await {
+            debug!(exec_id = %exec.id, %condition, "first_met: condition met");
+            return Some(condition);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[tokio::test]
+    async fn test_file_exists_condition() {
+        let temp = tempdir().unwrap();
+        let condition = WakeCondition::file_exists("out/*.done");
+
+        assert!(!condition_met(&condition, temp.path(), temp.path()).await);
+
+        std::fs::create_dir(temp.path().join("out")).unwrap();
+        std::fs::write(temp.path().join("out/build.done"), "").unwrap();
+        assert!(condition_met(&condition, temp.path(), temp.path()).await);
+    }
+
+    #[tokio::test]
+    async fn test_ref_advanced_condition() {
+        let temp = tempdir().unwrap();
+        let repo = temp.path();
+        git(repo, &["init", "--quiet", "-b", "main"]);
+        git(repo, &["config", "user.email", "test@example.com"]);
+        git(repo, &["config", "user.name", "Test"]);
+        git(repo, &["commit", "--quiet", "--allow-empty", "-m", "first"]);
+
+        let from = resolve_ref(repo, "main").await;
+        assert!(from.is_some());
+        let condition = WakeCondition::ref_advanced("main", from);
+        assert!(!condition_met(&condition, repo, repo).await);
+
+        git(repo, &["commit", "--quiet", "--allow-empty", "-m", "second"]);
+        assert!(condition_met(&condition, repo, repo).await);
+
+        // A ref that didn't exist at park time wakes once it appears
+        let condition = WakeCondition::ref_advanced("release", None);
+        assert!(!condition_met(&condition, repo, repo).await);
+        git(repo, &["branch", "release"]);
+        assert!(condition_met(&condition, repo, repo).await);
+    }
+
+    #[tokio::test]
+    async fn test_first_met_uses_worktree_and_any_condition() {
+        let temp = tempdir().unwrap();
+        let mut exec = LoopExecution::with_id("parked", "phase");
+        exec.set_worktree(temp.path().to_string_lossy());
+        exec.park(vec![
+            WakeCondition::http_ok("http://127.0.0.1:1/ready"),
+            WakeCondition::file_exists("ready.flag"),
+        ]);
+
+        let other = tempdir().unwrap();
+        assert!(first_met(&exec, other.path()).await.is_none());
+
+        std::fs::write(temp.path().join("ready.flag"), "").unwrap();
+        assert_eq!(
+            first_met(&exec, other.path()).await,
+            Some(&WakeCondition::file_exists("ready.flag"))
+        );
+    }
+}
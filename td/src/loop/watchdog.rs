@@ -0,0 +1,123 @@
+//! Iteration and tool-call watchdog
+//!
+//! A tool call that never returns (a command waiting on stdin, a fetch to a
+//! host that never answers) would otherwise stall its iteration forever. The
+//! engine bounds every tool call and every iteration with the limits below;
+//! when one trips it cancels the iteration, records the cause on the
+//! execution, emits a Warning event and then retries with a fresh iteration or
+//! fails the execution according to the policy.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// What to do after an iteration is cancelled by the watchdog
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeoutAction {
+    /// Start the next iteration (up to `max-retries` timeouts in a row)
+    #[default]
+    Retry,
+    /// Fail the execution
+    Fail,
+}
+
+impl std::fmt::Display for TimeoutAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Retry => write!(f, "retry"),
+            Self::Fail => write!(f, "fail"),
+        }
+    }
+}
+
+/// Watchdog settings for a loop type (`watchdog` in YAML)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct WatchdogPolicy {
+    /// Wall clock for a whole iteration (LLM turns, tools, validation); 0 disables
+    pub iteration_timeout_ms: u64,
+
+    /// Wall clock for a single tool call; 0 disables
+    pub tool_timeout_ms: u64,
+
+    /// Action once an iteration is cancelled
+    pub on_timeout: TimeoutAction,
+
+    /// Consecutive timed-out iterations `retry` tolerates before failing
+    pub max_retries: u32,
+}
+
+impl Default for WatchdogPolicy {
+    fn default() -> Self {
+        Self {
+            iteration_timeout_ms: 3_600_000,
+            tool_timeout_ms: 600_000,
+            on_timeout: TimeoutAction::default(),
+            max_retries: 2,
+        }
+    }
+}
+
+impl WatchdogPolicy {
+    /// Limit for a whole iteration, if any
+    pub fn iteration_timeout(&self) -> Option<Duration> {
+        (self.iteration_timeout_ms > 0).then(|| Duration::from_millis(self.iteration_timeout_ms))
+    }
+
+    /// Limit for a single tool call, if any
+    pub fn tool_timeout(&self) -> Option<Duration> {
+        (self.tool_timeout_ms > 0).then(|| Duration::from_millis(self.tool_timeout_ms))
+    }
+
+    /// Whether to retry after `consecutive` timed-out iterations in a row
+    pub fn should_retry(&self, consecutive: u32) -> bool {
+        debug!(consecutive, on_timeout = %self.on_timeout, max_retries = self.max_retries, "WatchdogPolicy::should_retry: called");
+        self.on_timeout == TimeoutAction::Retry && consecutive <= self.max_retries
+    }
+}
+
+/// Why the watchdog cancelled an iteration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchdogTimeout {
+    /// The iteration as a whole ran past its limit
+    Iteration { limit: Duration },
+    /// A tool call ran past its limit
+    ToolCall { tool: String, limit: Duration },
+}
+
+impl std::fmt::Display for WatchdogTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Iteration { limit } => write!(f, "iteration timed out after {}s", limit.as_secs()),
+            Self::ToolCall { tool, limit } => write!(f, "tool call '{}' timed out after {}s", tool, limit.as_secs()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watchdog_policy() {
+        let policy: WatchdogPolicy = serde_yaml::from_str("tool-timeout-ms: 0\nmax-retries: 1").unwrap();
+        assert_eq!(policy.tool_timeout(), None);
+        assert_eq!(policy.iteration_timeout(), Some(Duration::from_secs(3600)));
+        assert!(policy.should_retry(1));
+        assert!(!policy.should_retry(2));
+
+        let fail = WatchdogPolicy {
+            on_timeout: TimeoutAction::Fail,
+            ..Default::default()
+        };
+        assert!(!fail.should_retry(1));
+
+        let cause = WatchdogTimeout::ToolCall {
+            tool: "bash".to_string(),
+            limit: Duration::from_secs(600),
+        };
+        assert_eq!(cause.to_string(), "tool call 'bash' timed out after 600s");
+    }
+}
@@ -2,8 +2,9 @@
 //!
 //! CLI entry point for launching and managing concurrent loops.
 
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, IsTerminal, Read, Write};
 use std::path::PathBuf;
 
 use clap::{CommandFactory, FromArgMatches};
@@ -12,17 +13,54 @@ use tracing::{debug, info, warn};
 
 use std::sync::Arc;
 
-use taskdaemon::cli::{Cli, Command, DaemonCommand, ExecCommand, OutputFormat, generate_after_help};
-use taskdaemon::config::Config;
-use taskdaemon::coordinator::Coordinator;
-use taskdaemon::daemon::DaemonManager;
+use taskdaemon::ask::{self, Evidence};
+use taskdaemon::batch::BatchManifest;
+use taskdaemon::bulk::{BulkAction, apply_all};
+use taskdaemon::ci::{self, CiCollector, CiOutcome, CiReportFormat, CiSummary};
+use taskdaemon::cli::{
+    AuditCommand, BulkArgs, Cli, Command, ConfigCommand, DaemonCommand, ExecCommand, LoopsCommand, OutputFormat,
+    SecretsCommand, Switch, WorktreeCommand, generate_after_help,
+};
+use taskdaemon::compare::{ExecutionComparison, execution_diff};
+use taskdaemon::completions;
+use taskdaemon::config::{Config, LayeredConfig, LlmConfig};
+use taskdaemon::daemon::{DaemonManager, MaintenanceState};
+use taskdaemon::dedup::find_duplicate;
+use taskdaemon::DaemonBuilder;
+use taskdaemon::doctor;
+use taskdaemon::domain::{
+    IdResolver, IterationLog, IterationLogFilter, LoopExecution, LoopExecutionStatus, RunOverride, ValidationOutcome,
+    day_of,
+};
+use taskdaemon::error::code_of;
+use taskdaemon::events::{
+    DEFAULT_CHANNEL_CAPACITY, Event, EventBus, OverflowPolicy, default_runs_dir, read_execution_events,
+};
+use taskdaemon::hooks::HookEvent;
+use taskdaemon::init::{self, InitOptions, ProjectLanguage};
 use taskdaemon::ipc;
+use taskdaemon::llm::audit::{AuditLog, parse_since};
 use taskdaemon::llm::{LlmClient, create_client};
-use taskdaemon::r#loop::{IterationResult, LoopEngine, LoopLoader, TaskManager, TaskManagerConfig};
-use taskdaemon::scheduler::{Scheduler, SchedulerConfig};
-use taskdaemon::state::StateManager;
+use taskdaemon::loadtest::{LoadProfile, LoadTestOptions, run_loadtest};
+use taskdaemon::logs::{self, FollowEnd, LogPrinter};
+use taskdaemon::r#loop::{
+    ExploreTask, IterationResult, LoopConfig, LoopEngine, LoopLoader, MetricsHistory, PackageRegistry, PackageUpdate,
+    explore_artifact_path, preview_prompt, render_explore_markdown, resolve_ref, validate_submission,
+};
+use taskdaemon::report::ExecutionReport;
+use taskdaemon::resources::{ResourceSample, format_mb};
+use taskdaemon::run_many::{self, RunManyOptions};
+use taskdaemon::scheduler::SchedulerConfig;
+use taskdaemon::secrets;
+use taskdaemon::state::{self, StateManager};
+use taskdaemon::summary::Summary;
+use taskdaemon::timeline::Timeline;
+use taskdaemon::tools::{self, ExploreConfig, Thoroughness, ToolProfile};
 use taskdaemon::tui;
-use taskdaemon::watcher::{MainWatcher, WatcherConfig};
+use taskdaemon::worktree::{
+    CherryPickResult, PrunePolicy, WorktreeConfig, WorktreeManager, cherry_pick_to_branch, classify, format_size,
+    list_snapshots, now_ms, plan_prune, restore_snapshot,
+};
 
 fn setup_logging(cli_log_level: Option<&str>, config_log_level: Option<&str>) -> Result<()> {
     // Note: Can't log params here since logging isn't initialized yet
@@ -92,14 +130,43 @@ async fn main() -> Result<()> {
     // Parse CLI arguments using the modified command
     let cli = Cli::from_arg_matches(&cmd.get_matches())?;
 
+    // Tool workers speak the worker protocol on stdout and must not reopen the daemon log
+    if let Some(Command::ToolWorker { read_only, memory_mb }) = cli.command {
+        return cmd_tool_worker(read_only, memory_mb).await;
+    }
+
     // Load log level from config file early (before full config load)
-    let config_log_level = Config::load_log_level(cli.config.as_ref());
+    let config_log_level = Config::load_log_level(cli.config.as_ref(), &cli.set);
 
     // Setup logging with priority: CLI > config > INFO default
     setup_logging(cli.log_level.as_deref(), config_log_level.as_deref()).context("Failed to setup logging")?;
 
-    // Load configuration
-    let config = Config::load(cli.config.as_ref()).context("Failed to load configuration")?;
+    // Load configuration (doctor reports a broken config instead of failing on
+    // it, and init may be what creates the config)
+    let config = Config::load_with_overrides(cli.config.as_ref(), &cli.set).context("Failed to load configuration");
+    if let Some(Command::Doctor { json }) = cli.command {
+        debug!(json, "main: matched Doctor command");
+        return cmd_doctor(config, json).await;
+    }
+    if let Some(Command::Init {
+        language,
+        validation_command,
+        model,
+        yes,
+        force,
+    }) = &cli.command
+    {
+        debug!(?language, ?validation_command, ?model, %yes, %force, "main: matched Init command");
+        return cmd_init(
+            config.ok(),
+            *language,
+            validation_command.clone(),
+            model.clone(),
+            *yes,
+            *force,
+        );
+    }
+    let config = config?;
 
     info!("TaskDaemon loaded config: default={}", config.llm.default);
 
@@ -125,27 +192,103 @@ async fn main() -> Result<()> {
                     debug!("main: matched DaemonCommand::Ping");
                     cmd_ping().await
                 }
+                DaemonCommand::Maintenance { state } => {
+                    debug!(%state, "main: matched DaemonCommand::Maintenance");
+                    cmd_maintenance(&config, state).await
+                }
+                DaemonCommand::ReloadSecrets => {
+                    debug!("main: matched DaemonCommand::ReloadSecrets");
+                    cmd_reload_secrets().await
+                }
             }
         }
         Some(Command::Run {
             loop_type,
             task,
             max_iterations,
+            ci,
+            report,
+            report_format,
+        }) => {
+            debug!(%loop_type, %task, ?max_iterations, ci, ?report, "main: matched Run command");
+            if ci {
+                cmd_run_ci(
+                    &config,
+                    &loop_type,
+                    &task,
+                    max_iterations,
+                    report.as_deref(),
+                    report_format,
+                )
+                .await
+            } else {
+                cmd_run(&config, &loop_type, &task, max_iterations).await
+            }
+        }
+        Some(Command::Explore {
+            question,
+            thoroughness,
+            output,
+            no_save,
+        }) => {
+            debug!(%question, %thoroughness, ?output, no_save, "main: matched Explore command");
+            cmd_explore(&config, &question, thoroughness, output, no_save).await
+        }
+        Some(Command::Ask { question, since, limit }) => {
+            debug!(%question, ?since, limit, "main: matched Ask command");
+            cmd_ask(&config, &question, since.as_deref(), limit).await
+        }
+        Some(Command::Loadtest {
+            loops,
+            profile,
+            max_concurrent,
+            requests_per_minute,
+            seed,
+            format,
+        }) => {
+            debug!(loops, %profile, ?max_concurrent, ?requests_per_minute, seed, "main: matched Loadtest command");
+            cmd_loadtest(loops, profile, max_concurrent, requests_per_minute, seed, format).await
+        }
+        Some(Command::RunMany {
+            manifest,
+            max_parallel,
+            max_iterations,
         }) => {
-            debug!(%loop_type, %task, ?max_iterations, "main: matched Run command");
-            cmd_run(&config, &loop_type, &task, max_iterations).await
+            debug!(
+                ?manifest,
+                max_parallel,
+                ?max_iterations,
+                "main: matched RunMany command"
+            );
+            cmd_run_many(&config, &manifest, max_parallel, max_iterations).await
         }
         Some(Command::RunDaemon) => {
             debug!("main: matched RunDaemon command");
             cmd_run_daemon(&config).await
         }
-        Some(Command::Loops) => {
+        Some(Command::ToolWorker { .. }) => unreachable!("tool-worker runs before logging is set up"),
+        Some(Command::Loops { command: None }) => {
             debug!("main: matched Loops command");
             cmd_list_loops(&config).await
         }
-        Some(Command::Metrics { loop_type, format }) => {
-            debug!(?loop_type, ?format, "main: matched Metrics command");
-            cmd_metrics(loop_type.as_deref(), format).await
+        Some(Command::Loops { command: Some(command) }) => {
+            debug!(?command, "main: matched Loops package command");
+            cmd_loop_packages(&config, command).await
+        }
+        Some(Command::Metrics {
+            loop_type,
+            history,
+            format,
+        }) => {
+            debug!(?loop_type, ?history, ?format, "main: matched Metrics command");
+            match history {
+                Some(days) => cmd_metrics_history(loop_type.as_deref(), days, format).await,
+                None => cmd_metrics(loop_type.as_deref(), format).await,
+            }
+        }
+        Some(Command::Summary { format }) => {
+            debug!(?format, "main: matched Summary command");
+            cmd_summary(&config, format).await
         }
         Some(Command::Logs { follow, lines }) => {
             debug!(follow, lines, "main: matched Logs command");
@@ -155,6 +298,33 @@ async fn main() -> Result<()> {
             debug!(?command, "main: matched Exec command");
             cmd_exec(&config, command).await
         }
+        Some(Command::Audit { command }) => {
+            debug!(?command, "main: matched Audit command");
+            cmd_audit(&config, command)
+        }
+        Some(Command::Worktree { command }) => {
+            debug!(?command, "main: matched Worktree command");
+            cmd_worktree(&config, command).await
+        }
+        Some(Command::Config { command }) => {
+            debug!(?command, "main: matched Config command");
+            cmd_config(cli.config.as_ref(), &cli.set, command)
+        }
+        Some(Command::Secrets { command }) => {
+            debug!(?command, "main: matched Secrets command");
+            cmd_secrets(&config, command)
+        }
+        Some(Command::Fsck { repair, json }) => {
+            debug!(repair, json, "main: matched Fsck command");
+            cmd_fsck(&config, repair, json).await
+        }
+        Some(Command::Doctor { .. }) => unreachable!("doctor runs before the config is required"),
+        Some(Command::Init { .. }) => unreachable!("init runs before the config is required"),
+        Some(Command::Completions { shell }) => {
+            debug!(%shell, "main: matched Completions command");
+            print!("{}", completions::generate(shell, &Cli::command(), "td"));
+            Ok(())
+        }
         None => {
             debug!("main: no command specified, launching TUI");
             // Default: launch TUI with REPL view
@@ -209,7 +379,13 @@ async fn cmd_stop() -> Result<()> {
     let client = ipc::DaemonClient::new();
     if client.socket_exists() {
         debug!("cmd_stop: trying IPC shutdown");
-        match client.shutdown().await {
+        let result = match client.claim_writer(&cli_writer_holder()).await {
+            Ok(Ok(token)) => client.clone().with_writer_token(token).shutdown().await,
+            // The daemon answers but refuses: another client holds the writer token
+            Ok(Err(message)) => return Err(eyre::eyre!(message)),
+            Err(e) => Err(e),
+        };
+        match result {
             Ok(()) => {
                 debug!("cmd_stop: IPC shutdown acknowledged");
                 // Wait for process to exit
@@ -245,6 +421,11 @@ async fn cmd_stop() -> Result<()> {
     Ok(())
 }
 
+/// Holder name for the writer token claimed by one-shot CLI writes
+fn cli_writer_holder() -> String {
+    format!("td-cli-{}", std::process::id())
+}
+
 /// Ping the daemon via IPC to check if it's alive and responsive
 async fn cmd_ping() -> Result<()> {
     debug!("cmd_ping: called");
@@ -283,20 +464,146 @@ async fn cmd_ping() -> Result<()> {
     Ok(())
 }
 
+/// Have the running daemon re-read its API key after a rotation
+async fn cmd_reload_secrets() -> Result<()> {
+    debug!("cmd_reload_secrets: called");
+    let daemon = DaemonManager::new();
+    let client = ipc::DaemonClient::new();
+    if !daemon.is_running() || !client.socket_exists() {
+        debug!("cmd_reload_secrets: daemon not running");
+        eyre::bail!("Daemon is not running; the API key is read when it starts");
+    }
+
+    let token = client
+        .claim_writer(&cli_writer_holder())
+        .await?
+        .map_err(|m| eyre::eyre!(m))?;
+    let writer = client.with_writer_token(token.clone());
+    let result = writer.reload_secrets().await;
+    if let Err(e) = writer.release_writer(&token).await {
+        debug!(error = %e, "cmd_reload_secrets: failed to release writer token");
+    }
+    let (changed, resumed) = result?;
+
+    if changed {
+        println!("Reloaded the API key");
+    } else {
+        println!("API key unchanged");
+    }
+    if !resumed.is_empty() {
+        println!("Resumed {} execution(s) paused by the rejected key:", resumed.len());
+        for id in &resumed {
+            println!("  {}", id);
+        }
+    }
+    Ok(())
+}
+
+/// Turn maintenance mode on or off
+///
+/// A running daemon pauses or resumes its executions itself. Without one,
+/// only the marker file changes (and `off` resumes what maintenance paused),
+/// so the next daemon start honours it.
+async fn cmd_maintenance(config: &Config, state: Switch) -> Result<()> {
+    debug!(%state, "cmd_maintenance: called");
+    let enabled = state == Switch::On;
+    let daemon = DaemonManager::new();
+    let client = ipc::DaemonClient::new();
+
+    let executions = if daemon.is_running() && client.socket_exists() {
+        debug!("cmd_maintenance: asking the daemon");
+        let token = client
+            .claim_writer(&cli_writer_holder())
+            .await?
+            .map_err(|m| eyre::eyre!(m))?;
+        let writer = client.with_writer_token(token.clone());
+        let result = writer.set_maintenance(enabled).await;
+        if let Err(e) = writer.release_writer(&token).await {
+            debug!(error = %e, "cmd_maintenance: failed to release writer token");
+        }
+        result?
+    } else if enabled {
+        debug!("cmd_maintenance: daemon not running, writing marker file");
+        if daemon.maintenance().is_none() {
+            MaintenanceState::new().save(&daemon.maintenance_file())?;
+        }
+        Vec::new()
+    } else {
+        debug!("cmd_maintenance: daemon not running, resuming from marker file");
+        let paused = daemon.maintenance().map(|m| m.paused).unwrap_or_default();
+        let mut resumed = Vec::new();
+        if !paused.is_empty() {
+            let store = StateManager::spawn(PathBuf::from(&config.storage.taskstore_dir))?;
+            for id in paused {
+                match store.get_execution(&id).await? {
+                    Some(exec) if exec.status == LoopExecutionStatus::Paused => {
+                        store.resume_execution(&id).await?;
+                        resumed.push(id);
+                    }
+                    _ => debug!(%id, "cmd_maintenance: no longer paused, skipping"),
+                }
+            }
+        }
+        MaintenanceState::clear(&daemon.maintenance_file())?;
+        resumed
+    };
+
+    if enabled {
+        println!("Maintenance mode on: no executions will be picked up");
+        if executions.is_empty() {
+            println!("Nothing was running");
+        } else {
+            println!(
+                "Pausing {} execution(s) at their next iteration boundary:",
+                executions.len()
+            );
+        }
+    } else {
+        println!("Maintenance mode off");
+        if !executions.is_empty() {
+            println!("Resumed {} execution(s):", executions.len());
+        }
+    }
+    for id in &executions {
+        println!("  {}", id);
+    }
+    Ok(())
+}
+
 /// Show daemon status
 async fn cmd_status(detailed: bool, format: OutputFormat) -> Result<()> {
     debug!(detailed, ?format, "cmd_status: called");
     let daemon = DaemonManager::new();
     let status = daemon.status();
 
+    // None = not asked for (or daemon down); Some(None) = monitoring disabled
+    let resources = if detailed && status.running {
+        debug!("cmd_status: fetching resources from daemon");
+        Some(
+            ipc::DaemonClient::new()
+                .resources()
+                .await
+                .context("Failed to fetch resources from daemon")?,
+        )
+    } else {
+        None
+    };
+
     match format {
         OutputFormat::Json => {
             debug!("cmd_status: format is Json");
-            let json = serde_json::json!({
+            let mut json = serde_json::json!({
                 "running": status.running,
                 "pid": status.pid,
+                "maintenance": daemon.maintenance().is_some(),
                 "pid_file": status.pid_file.to_string_lossy()
             });
+            if let Some(resources) = &resources {
+                json["resources"] = match resources {
+                    Some((sample, pressure)) => serde_json::json!({ "sample": sample, "pressure": pressure }),
+                    None => serde_json::Value::Null,
+                };
+            }
             println!("{}", serde_json::to_string_pretty(&json)?);
         }
         OutputFormat::Text | OutputFormat::Table => {
@@ -313,13 +620,19 @@ async fn cmd_status(detailed: bool, format: OutputFormat) -> Result<()> {
                 debug!("cmd_status: daemon is stopped");
                 println!("Status: stopped");
             }
+            if let Some(maintenance) = daemon.maintenance() {
+                println!("Maintenance: on ({} paused)", maintenance.paused.len());
+            }
             println!("PID file: {}", status.pid_file.display());
 
-            if detailed && status.running {
+            if let Some(resources) = resources {
                 debug!("cmd_status: detailed view requested");
                 println!();
-                println!("Detailed metrics not available (daemon IPC not implemented)");
-                // TODO: Connect to daemon via IPC for detailed metrics
+                match resources {
+                    Some((Some(sample), pressure)) => print_resources(&sample, &pressure),
+                    Some((None, _)) => println!("Resources: not sampled yet"),
+                    None => println!("Resources: monitoring disabled (resources.interval-secs = 0)"),
+                }
             }
         }
     }
@@ -327,6 +640,40 @@ async fn cmd_status(detailed: bool, format: OutputFormat) -> Result<()> {
     Ok(())
 }
 
+/// Print a daemon resource sample for `td daemon status --detailed`
+fn print_resources(sample: &ResourceSample, pressure: &[String]) {
+    let unknown = || "n/a".to_string();
+    println!("Resources");
+    println!("---------");
+    println!(
+        "CPU: {}",
+        sample.cpu_percent.map_or_else(unknown, |cpu| format!("{:.1}%", cpu))
+    );
+    println!("RSS: {}", sample.rss_bytes.map_or_else(unknown, format_mb));
+    println!(
+        "Open files: {}",
+        sample.open_files.map_or_else(unknown, |n| n.to_string())
+    );
+    println!("Tokio tasks: {} ({} loops)", sample.tokio_tasks, sample.loop_tasks);
+    if pressure.is_empty() {
+        println!("Pressure: none");
+    } else {
+        println!("Pressure: {} (not picking up executions)", pressure.join(", "));
+    }
+    if !sample.loops.is_empty() {
+        println!();
+        println!("{:<50} {:>10} {:>6}", "EXECUTION", "RSS", "PROCS");
+        for exec in &sample.loops {
+            println!(
+                "{:<50} {:>10} {:>6}",
+                exec.exec_id,
+                format_mb(exec.rss_bytes),
+                exec.processes
+            );
+        }
+    }
+}
+
 /// Launch the TUI with REPL as default view
 async fn cmd_tui(config: &Config) -> Result<()> {
     debug!("cmd_tui: called");
@@ -419,7 +766,7 @@ async fn cmd_tui(config: &Config) -> Result<()> {
 
     // Run TUI with LLM client
     debug!("cmd_tui: launching TUI");
-    tui::run_with_state_and_llm(state_manager, llm_client, max_tokens, config.debug.clone()).await
+    tui::run_with_state_and_llm(state_manager, llm_client, max_tokens, config).await
 }
 
 /// Show logs
@@ -467,36 +814,7 @@ async fn cmd_logs(follow: bool, lines: usize) -> Result<()> {
 /// Run a loop to completion (batch mode)
 async fn cmd_run(config: &Config, loop_type: &str, task: &str, max_iterations: Option<u32>) -> Result<()> {
     debug!(%loop_type, %task, ?max_iterations, "cmd_run: called");
-    // Validate API key early by resolving the config
-    config
-        .llm
-        .resolve()
-        .and_then(|r| r.get_api_key())
-        .context("LLM API key not found. Check api-key-env or api-key-file in your config.")?;
-    debug!("cmd_run: API key found");
-
-    // Load loop types
-    let loader = LoopLoader::new(&config.loops)?;
-    let _loop_def = loader
-        .get(loop_type)
-        .ok_or_else(|| eyre::eyre!("Unknown loop type: {}", loop_type))?;
-    debug!(%loop_type, "cmd_run: loop type found");
-
-    // Get loop config from the loader
-    let all_configs = loader.to_configs();
-    let mut loop_config = all_configs
-        .get(loop_type)
-        .cloned()
-        .ok_or_else(|| eyre::eyre!("Failed to build config for loop type: {}", loop_type))?;
-
-    // Override max_iterations if specified
-    if let Some(max) = max_iterations {
-        debug!(max, "cmd_run: overriding max_iterations");
-        loop_config.max_iterations = max;
-    }
-
-    // Inject task into prompt template context
-    loop_config.prompt_template = loop_config.prompt_template.replace("{{task}}", task);
+    let (loop_config, execution_context) = prepare_run(config, loop_type, task, max_iterations)?;
 
     println!("Running {} loop", loop_type);
     println!("  Task: {}", task);
@@ -513,7 +831,8 @@ async fn cmd_run(config: &Config, loop_type: &str, task: &str, max_iterations: O
 
     // Create and run engine (no coordinator for REPL mode)
     let exec_id = format!("repl-{}", std::process::id());
-    let mut engine = LoopEngine::new(exec_id.clone(), loop_config, llm, worktree);
+    let mut engine =
+        LoopEngine::new(exec_id.clone(), loop_config, llm, worktree).with_execution_context(execution_context);
     debug!(%exec_id, "cmd_run: engine created");
 
     // Run with progress output
@@ -524,13 +843,20 @@ async fn cmd_run(config: &Config, loop_type: &str, task: &str, max_iterations: O
     match result {
         IterationResult::Complete { iterations } => {
             debug!(iterations, "cmd_run: loop completed");
+            engine.run_hooks(HookEvent::OnComplete, None, None).await;
             println!("\n✓ Loop completed successfully after {} iterations", iterations);
         }
         IterationResult::Error { message, .. } => {
             debug!(%message, "cmd_run: loop failed");
+            engine.run_hooks(HookEvent::OnFail, None, Some(&message)).await;
             println!("\n✗ Loop failed: {}", message);
             std::process::exit(1);
         }
+        IterationResult::Blocked { reason } => {
+            debug!(%reason, "cmd_run: loop blocked by a hook");
+            println!("\n✗ {}", reason);
+            std::process::exit(1);
+        }
         IterationResult::Interrupted { reason } => {
             debug!(%reason, "cmd_run: loop interrupted");
             println!("\n⚠ Loop interrupted: {}", reason);
@@ -544,126 +870,1289 @@ async fn cmd_run(config: &Config, loop_type: &str, task: &str, max_iterations: O
             debug!(?retry_after, "cmd_run: rate limited");
             println!("\n⚠ Rate limited, retry after {:?}", retry_after);
         }
+        IterationResult::ProviderUnavailable { message } => {
+            debug!(%message, "cmd_run: provider unavailable");
+            println!("\n⚠ Provider unavailable: {}", message);
+        }
+        IterationResult::Stuck {
+            action,
+            unchanged_iterations,
+        } => {
+            debug!(%action, unchanged_iterations, "cmd_run: loop stuck");
+            println!(
+                "\n⚠ Loop stuck: no progress for {} iterations ({})",
+                unchanged_iterations, action
+            );
+            std::process::exit(1);
+        }
     }
 
     Ok(())
 }
 
-/// Run as the daemon process (internal command)
-async fn cmd_run_daemon(config: &Config) -> Result<()> {
-    debug!("cmd_run_daemon: called");
-    let daemon = DaemonManager::new();
-    daemon.register_self()?;
-    debug!("cmd_run_daemon: daemon registered, starting run_daemon");
+/// Run a loop in CI mode: JSON-lines progress, an optional JUnit/SARIF report, outcome exit code
+async fn cmd_run_ci(
+    config: &Config,
+    loop_type: &str,
+    task: &str,
+    max_iterations: Option<u32>,
+    report: Option<&std::path::Path>,
+    report_format: Option<CiReportFormat>,
+) -> Result<()> {
+    debug!(%loop_type, %task, ?max_iterations, ?report, ?report_format, "cmd_run_ci: called");
+    let (loop_config, execution_context) = prepare_run(config, loop_type, task, max_iterations)?;
+    let success_exit_code = loop_config.success_exit_code;
+    let worktree = std::env::current_dir()?;
+    let llm: Arc<dyn LlmClient> = create_client(&config.llm).context("Failed to create LLM client")?;
 
-    run_daemon(config).await
+    // Every event is seen by the collector; the queue grows rather than drop validation output
+    let exec_id = format!("ci-{}", std::process::id());
+    let bus = EventBus::with_default_capacity();
+    let mut events = bus.subscribe_buffered(DEFAULT_CHANNEL_CAPACITY, OverflowPolicy::BlockProducerNever);
+    let printer = tokio::spawn(async move {
+        let mut collector = CiCollector::new();
+        while let Some(event) = events.recv().await {
+            collector.record(&event);
+            if ci::is_progress_event(&event)
+                && let Ok(line) = serde_json::to_string(&event)
+            {
+                println!("{}", line);
+            }
+        }
+        collector
+    });
+
+    let mut engine = LoopEngine::new(exec_id.clone(), loop_config, llm, worktree)
+        .with_execution_context(execution_context)
+        .with_event_emitter(bus.emitter_for(&exec_id));
+    let result = tokio::select! {
+        result = engine.run() => result,
+        _ = tokio::signal::ctrl_c() => {
+            debug!("cmd_run_ci: interrupted");
+            Ok(IterationResult::Interrupted { reason: "interrupted by signal".to_string() })
+        }
+    };
+    debug!(?result, "cmd_run_ci: engine finished");
+    let current_iteration = engine.current_iteration();
+
+    // Dropping the engine and the bus closes the stream so the printer drains and exits
+    drop(engine);
+    drop(bus);
+    let runs = printer.await.context("Progress printer failed")?.into_runs();
+
+    let (outcome, message, iterations) = match &result {
+        Ok(IterationResult::Complete { iterations }) => (CiOutcome::Complete, None, *iterations),
+        Ok(IterationResult::Error { message, .. }) => (CiOutcome::Failed, Some(message.clone()), current_iteration),
+        Ok(IterationResult::Blocked { reason }) => (CiOutcome::Failed, Some(reason.clone()), current_iteration),
+        Ok(IterationResult::Interrupted { reason }) => {
+            (CiOutcome::Interrupted, Some(reason.clone()), current_iteration)
+        }
+        Ok(IterationResult::Stuck {
+            action,
+            unchanged_iterations,
+        }) => (
+            CiOutcome::Stuck,
+            Some(format!(
+                "no progress for {} iterations ({})",
+                unchanged_iterations, action
+            )),
+            current_iteration,
+        ),
+        Ok(other) => (
+            CiOutcome::from_result(other),
+            Some(format!("{:?}", other)),
+            current_iteration,
+        ),
+        Err(e) => (CiOutcome::Failed, Some(e.to_string()), current_iteration),
+    };
+    let error_code = match &result {
+        Ok(IterationResult::Error { code, .. }) => code.clone(),
+        Err(e) => code_of(e),
+        _ => None,
+    };
+    let summary = CiSummary {
+        exec_id,
+        loop_type: loop_type.to_string(),
+        outcome,
+        message,
+        error_code,
+        iterations,
+        success_exit_code,
+        runs,
+    };
+
+    if let Some(path) = report {
+        let format = report_format.unwrap_or_else(|| CiReportFormat::from_path(path));
+        debug!(?path, %format, "cmd_run_ci: writing report");
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).context(format!("Failed to create {}", parent.display()))?;
+        }
+        fs::write(path, summary.render(format)).context(format!("Failed to write {}", path.display()))?;
+    }
+    println!("{}", summary.finished_line());
+
+    let code = outcome.exit_code();
+    debug!(code, "cmd_run_ci: complete");
+    if code != 0 {
+        std::process::exit(code);
+    }
+    Ok(())
 }
 
-/// List available loop types
-async fn cmd_list_loops(config: &Config) -> Result<()> {
-    debug!("cmd_list_loops: called");
+/// Check the API key, loop type and task, and build the loop config and context for a run
+fn prepare_run(
+    config: &Config,
+    loop_type: &str,
+    task: &str,
+    max_iterations: Option<u32>,
+) -> Result<(LoopConfig, serde_json::Value)> {
+    debug!(%loop_type, %task, ?max_iterations, "prepare_run: called");
+    // Validate API key early by resolving the config
+    config
+        .llm
+        .resolve()
+        .and_then(|r| r.get_api_key())
+        .context("LLM API key not found. Check api-key-env or api-key-file in your config.")?;
+    debug!("prepare_run: API key found");
+
+    // Load loop types
     let loader = LoopLoader::new(&config.loops)?;
+    let _loop_def = loader
+        .get(loop_type)
+        .ok_or_else(|| eyre::eyre!("Unknown loop type: {}", loop_type))?;
+    debug!(%loop_type, "prepare_run: loop type found");
 
-    let loop_types: Vec<String> = loader.names().map(|s| s.to_string()).collect();
-    debug!(count = loop_types.len(), "cmd_list_loops: found loop types");
+    // Get loop config from the loader
+    let all_configs = loader.to_configs();
+    let mut loop_config = all_configs
+        .get(loop_type)
+        .cloned()
+        .ok_or_else(|| eyre::eyre!("Failed to build config for loop type: {}", loop_type))?;
 
-    if loop_types.is_empty() {
-        debug!("cmd_list_loops: no loop types found");
-        println!("No loop types found.");
-        println!("Loop type paths searched:");
-        for path in &config.loops.paths {
-            println!("  - {}", path);
+    // Override max_iterations if specified
+    if let Some(max) = max_iterations {
+        debug!(max, "prepare_run: overriding max_iterations");
+        loop_config.max_iterations = max;
+    }
+
+    // Global hooks run before the loop type's own
+    loop_config.hooks = config.hooks.followed_by(&loop_config.hooks);
+
+    // The task is an ordinary template variable, checked against the type's schema
+    let execution_context = serde_json::json!({ "task": task });
+    if let Err(errors) = validate_submission(&loop_config.variables, &execution_context) {
+        eyre::bail!("Invalid context for {} loop:\n  - {}", loop_type, errors.join("\n  - "));
+    }
+
+    Ok((loop_config, execution_context))
+}
+
+/// Explore the current directory read-only, streaming findings and saving the answer
+async fn cmd_explore(
+    config: &Config,
+    question: &str,
+    thoroughness: Thoroughness,
+    output: Option<PathBuf>,
+    no_save: bool,
+) -> Result<()> {
+    debug!(%question, %thoroughness, ?output, no_save, "cmd_explore: called");
+    config
+        .llm
+        .resolve()
+        .and_then(|r| r.get_api_key())
+        .context("LLM API key not found. Check api-key-env or api-key-file in your config.")?;
+    let worktree = std::env::current_dir().context("Failed to get current directory")?;
+    let llm: Arc<dyn LlmClient> = create_client(&config.llm).context("Failed to create LLM client")?;
+
+    // The execution record's ID names the artifact too, so the TUI entry and the file match
+    let mut exec = LoopExecution::new("explore", question).with_submitter(config.user.submitter());
+    let explore_config = ExploreConfig {
+        question: question.to_string(),
+        thoroughness,
+        parent_id: None,
+        worktree: worktree.clone(),
+        max_iterations: thoroughness.max_iterations(),
+        model: None,
+        // Interactive runs get more time than the in-loop tool: a minute per turn
+        timeout_secs: 60 * thoroughness.max_iterations(),
+    };
+
+    let bus = EventBus::with_default_capacity();
+    let mut events = bus.subscribe_buffered(DEFAULT_CHANNEL_CAPACITY, OverflowPolicy::BlockProducerNever);
+    let printer = tokio::spawn(async move {
+        while let Some(event) = events.recv().await {
+            match event {
+                Event::ToolCallStarted {
+                    tool_name,
+                    tool_args_summary,
+                    ..
+                } => println!("  → {} {}", tool_name, tool_args_summary),
+                // Interim findings; the final answer is printed once the exploration ends
+                Event::ResponseCompleted {
+                    response_summary,
+                    has_tool_calls: true,
+                    ..
+                } if !response_summary.trim().is_empty() => println!("\n{}\n", response_summary.trim()),
+                Event::Error { message, .. } => eprintln!("  ✗ {}", message),
+                _ => {}
+            }
         }
+    });
+
+    println!("Exploring ({}): {}", thoroughness, question);
+    println!();
+    let started = std::time::Instant::now();
+    let mut task = ExploreTask::new(exec.id.clone(), explore_config, llm).with_event_emitter(bus.emitter_for(&exec.id));
+    let answer = task.run().await;
+    let usage = task.usage().clone();
+    let duration = started.elapsed();
+    drop(task);
+    drop(bus);
+    let _ = printer.await;
+    let answer = answer.context("Exploration failed")?;
+    debug!(answer_len = answer.len(), "cmd_explore: exploration finished");
+
+    println!();
+    println!("## Findings");
+    println!();
+    println!("{}", answer.trim());
+    if no_save {
         return Ok(());
     }
 
-    debug!(?loop_types, "cmd_list_loops: listing loop types");
-    println!("Available loop types:");
+    let path = output.unwrap_or_else(|| explore_artifact_path(&worktree, &exec.id));
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).context(format!("Failed to create {}", parent.display()))?;
+    }
+    let markdown = render_explore_markdown(&exec.id, question, thoroughness, &answer, duration, &usage);
+    fs::write(&path, markdown).context(format!("Failed to write {}", path.display()))?;
     println!();
+    println!("Saved to {}", path.display());
 
-    for name in &loop_types {
-        if let Some(def) = loader.get(name) {
-            debug!(%name, "cmd_list_loops: printing loop type");
-            println!("  {}", name);
-            println!("    {}", def.description);
-            if let Some(extends) = &def.extends {
-                debug!(%extends, "cmd_list_loops: loop extends another");
-                println!("    Extends: {}", extends);
-            }
-            println!("    Max iterations: {}", def.max_iterations);
-            println!();
+    // Record it so the TUI lists the exploration with its artifact (only if a TaskStore exists)
+    let store_path = PathBuf::from(&config.storage.taskstore_dir);
+    if !store_path.exists() {
+        debug!(?store_path, "cmd_explore: no TaskStore, not recording");
+        return Ok(());
+    }
+    exec = exec
+        .with_context_value("question", question)
+        .with_context_value("thoroughness", &thoroughness.to_string());
+    exec.set_title(question.lines().next().unwrap_or(question).trim());
+    let artifact = path.strip_prefix(&worktree).unwrap_or(&path);
+    exec.set_artifact(artifact.display().to_string());
+    exec.set_artifact_status("complete");
+    exec.total_input_tokens = usage.input_tokens;
+    exec.total_output_tokens = usage.output_tokens;
+    exec.total_duration_ms = duration.as_millis() as u64;
+    exec.set_status(LoopExecutionStatus::Complete);
+    let state = StateManager::spawn(&store_path)?;
+    match state.create_execution(exec).await {
+        Ok(id) => println!("Recorded as execution {}", id),
+        Err(e) => {
+            debug!(error = %e, "cmd_explore: failed to record execution");
+            eprintln!("Saved, but failed to record the exploration: {}", e);
         }
     }
-
     Ok(())
 }
 
-/// Show metrics from the daemon's TaskStore
-async fn cmd_metrics(loop_type: Option<&str>, format: OutputFormat) -> Result<()> {
-    debug!(?loop_type, ?format, "cmd_metrics: called");
-    let config = Config::load(None)?;
+/// Answer a question about past executions from their records and event logs
+async fn cmd_ask(config: &Config, question: &str, since: Option<&str>, limit: usize) -> Result<()> {
+    debug!(%question, ?since, limit, "cmd_ask: called");
     let store_path = PathBuf::from(&config.storage.taskstore_dir);
-
     if !store_path.exists() {
-        debug!(?store_path, "cmd_metrics: TaskStore does not exist");
-        match format {
-            OutputFormat::Json => {
-                debug!("cmd_metrics: outputting JSON error");
-                println!(
-                    "{}",
-                    serde_json::json!({"error": "No TaskStore found. Has the daemon run?"})
-                );
-            }
-            OutputFormat::Text | OutputFormat::Table => {
-                debug!("cmd_metrics: outputting text error");
-                println!("No TaskStore found. Has the daemon run?");
-            }
-        }
+        debug!(?store_path, "cmd_ask: TaskStore does not exist");
+        println!("No TaskStore found. Has the daemon run?");
         return Ok(());
     }
+    let llm: Arc<dyn LlmClient> = create_client(&config.llm).context("Failed to create LLM client")?;
 
-    debug!(?store_path, "cmd_metrics: TaskStore exists");
+    let now = chrono::Utc::now();
+    let since = match since {
+        Some(s) => Some(parse_since(s, now)?),
+        None => ask::time_window(question, now),
+    };
     let state = StateManager::spawn(&store_path)?;
-    let metrics = state.get_metrics().await?;
-    debug!(?metrics, "cmd_metrics: got metrics");
-
-    // Note: loop_type filter not yet implemented - would need to filter executions by type
-    if loop_type.is_some() {
-        debug!(
-            ?loop_type,
-            "cmd_metrics: loop_type filter requested but not implemented"
+    let executions = state.list_executions(None, None).await?;
+    let relevant = ask::rank(&executions, question, since, limit);
+    if relevant.is_empty() {
+        println!(
+            "No executions found{}.",
+            if since.is_some() { " in that time window" } else { "" }
         );
-        warn!("Loop type filter not yet implemented for metrics");
+        return Ok(());
     }
 
-    match format {
-        OutputFormat::Json => {
-            debug!("cmd_metrics: outputting JSON");
-            println!("{}", serde_json::to_string_pretty(&metrics)?);
-        }
-        OutputFormat::Text | OutputFormat::Table => {
-            debug!("cmd_metrics: outputting text/table");
-            println!("TaskDaemon Metrics");
-            println!("-----------------");
+    let runs_dir = default_runs_dir()?;
+    let repo_root = std::env::current_dir().context("Failed to get current directory")?;
+    let mut evidence = Vec::with_capacity(relevant.len());
+    for exec in &relevant {
+        let events = read_execution_events(&runs_dir, &exec.id).unwrap_or_else(|e| {
+            debug!(exec_id = %exec.id, error = %e, "cmd_ask: failed to read events");
+            Vec::new()
+        });
+        evidence.push(Evidence::gather(exec, &events, &repo_root));
+    }
+    debug!(count = evidence.len(), "cmd_ask: evidence gathered");
+
+    let answer = ask::answer(llm.as_ref(), question, &evidence).await?;
+    println!("{}", answer);
+    println!();
+
+    // Sources: what the answer cites, or everything it was given if it cites nothing
+    let cited = ask::cited(&answer, &evidence);
+    let sources: Vec<&str> = if cited.is_empty() {
+        evidence.iter().map(|e| e.id.as_str()).collect()
+    } else {
+        cited
+    };
+    println!("Sources:");
+    for id in sources {
+        if let Some(exec) = relevant.iter().find(|e| e.id == id) {
+            println!(
+                "  {:<50} {:<10} {:<9} {}",
+                exec.id,
+                exec.loop_type,
+                exec.status.to_string(),
+                exec.title.as_deref().unwrap_or_default()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Run every task of a manifest in parallel in this process, exiting with the consolidated status
+/// Run loops through the orchestration against a simulated provider and report
+async fn cmd_loadtest(
+    loops: usize,
+    profile: LoadProfile,
+    max_concurrent: Option<usize>,
+    requests_per_minute: Option<u32>,
+    seed: u64,
+    format: OutputFormat,
+) -> Result<()> {
+    debug!(loops, %profile, ?max_concurrent, ?requests_per_minute, seed, "cmd_loadtest: called");
+    let mut scheduler = SchedulerConfig::default();
+    if let Some(max_concurrent) = max_concurrent {
+        scheduler.max_concurrent = max_concurrent;
+    }
+    if let Some(rpm) = requests_per_minute {
+        scheduler.max_requests_per_window = rpm;
+        scheduler.rate_window_secs = 60;
+    }
+    let options = LoadTestOptions::default()
+        .with_loops(loops)
+        .with_profile(profile)
+        .with_seed(seed)
+        .with_scheduler(scheduler);
+
+    if matches!(format, OutputFormat::Text | OutputFormat::Table) {
+        println!("Running {} loops against the {} profile...", loops, profile);
+    }
+    let report = run_loadtest(options).await?;
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        OutputFormat::Text | OutputFormat::Table => print!("{}", report.render()),
+    }
+    Ok(())
+}
+
+async fn cmd_run_many(
+    config: &Config,
+    manifest: &std::path::Path,
+    max_parallel: usize,
+    max_iterations: Option<u32>,
+) -> Result<()> {
+    debug!(?manifest, max_parallel, ?max_iterations, "cmd_run_many: called");
+    config
+        .llm
+        .resolve()
+        .and_then(|r| r.get_api_key())
+        .context("LLM API key not found. Check api-key-env or api-key-file in your config.")?;
+
+    let batch = BatchManifest::load(manifest)?;
+    let loader = LoopLoader::new(&config.loops).context("Failed to load loop types")?;
+    let executions = match batch.to_executions(&loader, &HashSet::new()) {
+        Ok(executions) => executions,
+        Err(errors) => {
+            debug!(error_count = errors.len(), "cmd_run_many: manifest invalid");
+            eyre::bail!(
+                "Invalid manifest {}:\n  - {}",
+                manifest.display(),
+                errors.join("\n  - ")
+            );
+        }
+    };
+
+    let repo_root = std::env::current_dir().context("Failed to get current directory")?;
+    let worktrees = WorktreeManager::new(WorktreeConfig {
+        base_dir: config.git.worktree_dir.clone(),
+        ..WorktreeConfig::with_repo(repo_root.clone())
+    });
+    let llm: Arc<dyn LlmClient> = create_client(&config.llm).context("Failed to create LLM client")?;
+    let command_env: Vec<(String, String)> = config
+        .git
+        .cargo_target_dir
+        .iter()
+        .map(|dir| ("CARGO_TARGET_DIR".to_string(), dir.display().to_string()))
+        .collect();
+    let options = RunManyOptions::default()
+        .with_max_parallel(max_parallel)
+        .with_max_iterations(max_iterations)
+        .with_command_env(command_env);
+
+    println!(
+        "Running {} tasks from {} ({} at a time)",
+        executions.len(),
+        manifest.display(),
+        options.max_parallel
+    );
+    println!();
+    // Global hooks run before each loop type's own
+    let mut loop_configs = loader.to_configs();
+    for loop_config in loop_configs.values_mut() {
+        loop_config.hooks = config.hooks.followed_by(&loop_config.hooks);
+    }
+    let reports = run_many::run_many(executions, loop_configs, llm, worktrees, repo_root, options).await?;
+
+    println!();
+    println!(
+        "{:<30} {:<10} {:<12} {:>9}  BRANCH",
+        "TASK", "TYPE", "STATUS", "DURATION"
+    );
+    println!("{}", "-".repeat(90));
+    for report in &reports {
+        println!(
+            "{:<30} {:<10} {:<12} {:>8}s  {}",
+            report.name,
+            report.loop_type,
+            report.outcome.status(),
+            report.duration.as_secs(),
+            report.branch.as_deref().unwrap_or("-")
+        );
+    }
+    let problems: Vec<_> = reports.iter().filter(|r| !r.outcome.is_success()).collect();
+    if !problems.is_empty() {
+        println!();
+        for report in &problems {
+            println!("  {}: {}", report.name, report.outcome);
+        }
+    }
+    println!();
+    println!(
+        "{} of {} tasks completed",
+        reports.len() - problems.len(),
+        reports.len()
+    );
+
+    let code = run_many::exit_code(&reports);
+    debug!(code, "cmd_run_many: complete");
+    if code != run_many::EXIT_SUCCESS {
+        std::process::exit(code);
+    }
+    Ok(())
+}
+
+/// Run as the daemon process (internal command)
+async fn cmd_run_daemon(config: &Config) -> Result<()> {
+    debug!("cmd_run_daemon: called");
+    let daemon = DaemonManager::new();
+    daemon.register_self()?;
+    debug!("cmd_run_daemon: daemon registered, starting run_daemon");
+
+    run_daemon(config).await
+}
+
+/// Serve tool calls for the daemon over stdin/stdout (internal command)
+async fn cmd_tool_worker(read_only: bool, memory_mb: Option<u64>) -> Result<()> {
+    if let Some(mb) = memory_mb {
+        tools::limit_memory(mb)?;
+    }
+    let profile = if read_only { ToolProfile::ReadOnly } else { ToolProfile::Full };
+    tools::serve_worker(profile, tokio::io::stdin(), tokio::io::stdout()).await
+}
+
+/// List available loop types
+async fn cmd_list_loops(config: &Config) -> Result<()> {
+    debug!("cmd_list_loops: called");
+    let loader = LoopLoader::new(&config.loops)?;
+
+    let loop_types: Vec<String> = loader.names().map(|s| s.to_string()).collect();
+    debug!(count = loop_types.len(), "cmd_list_loops: found loop types");
+
+    if loop_types.is_empty() {
+        debug!("cmd_list_loops: no loop types found");
+        println!("No loop types found.");
+        println!("Loop type paths searched:");
+        for path in &config.loops.paths {
+            println!("  - {}", path);
+        }
+        return Ok(());
+    }
+
+    debug!(?loop_types, "cmd_list_loops: listing loop types");
+    println!("Available loop types:");
+    println!();
+
+    for name in &loop_types {
+        if let Some(def) = loader.get(name) {
+            debug!(%name, "cmd_list_loops: printing loop type");
+            println!("  {}", name);
+            println!("    {}", def.description);
+            if let Some(extends) = &def.extends {
+                debug!(%extends, "cmd_list_loops: loop extends another");
+                println!("    Extends: {}", extends);
+            }
+            println!("    Max iterations: {}", def.max_iterations);
+            println!();
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle `td loops install|update|remove|packages`
+async fn cmd_loop_packages(config: &Config, command: LoopsCommand) -> Result<()> {
+    debug!(?command, "cmd_loop_packages: called");
+    let loops_dir = if command.global() {
+        dirs::home_dir()
+            .ok_or_else(|| eyre::eyre!("Could not determine home directory"))?
+            .join(".config/taskdaemon/loops")
+    } else {
+        std::env::current_dir()?.join(".taskdaemon/loops")
+    };
+    let mut registry = PackageRegistry::open(&loops_dir)?;
+
+    match command {
+        LoopsCommand::Install {
+            source,
+            git_ref,
+            checksum,
+            ..
+        } => {
+            let package = registry
+                .install(&source, git_ref.as_deref(), checksum.as_deref())
+                .await?;
+            println!(
+                "Installed {} {} into {} (commit {}, package.yml sha256 {})",
+                package.name,
+                package.version,
+                loops_dir.join(&package.name).display(),
+                &package.commit[..package.commit.len().min(12)],
+                package.checksum
+            );
+            let searched = config.loops.expanded_paths();
+            if !searched.iter().any(|p| p.canonicalize().ok() == loops_dir.canonicalize().ok()) {
+                warn!(dir = %loops_dir.display(), "Installed outside the configured loop type paths");
+                println!("Note: {} is not in loops.paths, so its loop types won't load", loops_dir.display());
+            }
+        }
+        LoopsCommand::Update { name, .. } => {
+            let names: Vec<String> = match name {
+                Some(name) => vec![name],
+                None => registry.packages().map(|p| p.name.clone()).collect(),
+            };
+            if names.is_empty() {
+                println!("No loop packages installed in {}", loops_dir.display());
+            }
+            for name in names {
+                match registry.update(&name).await? {
+                    PackageUpdate::UpToDate(package) => {
+                        println!("{} {} is up to date", package.name, package.version);
+                    }
+                    PackageUpdate::Updated { from, to } => {
+                        println!("Updated {} {} -> {}", to.name, from.version, to.version);
+                    }
+                }
+            }
+        }
+        LoopsCommand::Remove { name, .. } => {
+            let package = registry.remove(&name)?;
+            println!("Removed {} {}", package.name, package.version);
+        }
+        LoopsCommand::Packages { .. } => {
+            let packages: Vec<_> = registry.packages().collect();
+            if packages.is_empty() {
+                println!("No loop packages installed in {}", loops_dir.display());
+                return Ok(());
+            }
+            println!("{:<24} {:<10} {:<12} SOURCE", "NAME", "VERSION", "COMMIT");
+            for package in packages {
+                let source = match &package.git_ref {
+                    Some(git_ref) => format!("{} ({})", package.source, git_ref),
+                    None => package.source.clone(),
+                };
+                println!(
+                    "{:<24} {:<10} {:<12} {}",
+                    package.name,
+                    package.version,
+                    &package.commit[..package.commit.len().min(12)],
+                    source
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Show persisted daily metrics and their trend over the last `days` days
+async fn cmd_metrics_history(loop_type: Option<&str>, days: u32, format: OutputFormat) -> Result<()> {
+    debug!(?loop_type, days, ?format, "cmd_metrics_history: called");
+    let config = Config::load(None)?;
+    let store_path = PathBuf::from(&config.storage.taskstore_dir);
+    if !store_path.exists() {
+        debug!(?store_path, "cmd_metrics_history: TaskStore does not exist");
+        println!("No TaskStore found. Has the daemon run?");
+        return Ok(());
+    }
+
+    let since = day_of(taskstore::now_ms() - (days as i64 - 1) * 86_400_000);
+    let state = StateManager::spawn(&store_path)?;
+    let rollups = state
+        .list_daily_rollups(loop_type.map(str::to_string), Some(since.clone()))
+        .await?;
+    let history = MetricsHistory::from_rollups(loop_type, &since, &rollups);
+    debug!(days = history.days.len(), "cmd_metrics_history: built history");
+
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&history)?),
+        OutputFormat::Text | OutputFormat::Table => print!("{}", history.render()),
+    }
+    Ok(())
+}
+
+/// Show metrics from the daemon's TaskStore
+async fn cmd_metrics(loop_type: Option<&str>, format: OutputFormat) -> Result<()> {
+    debug!(?loop_type, ?format, "cmd_metrics: called");
+    let config = Config::load(None)?;
+    let store_path = PathBuf::from(&config.storage.taskstore_dir);
+
+    if !store_path.exists() {
+        debug!(?store_path, "cmd_metrics: TaskStore does not exist");
+        match format {
+            OutputFormat::Json => {
+                debug!("cmd_metrics: outputting JSON error");
+                println!(
+                    "{}",
+                    serde_json::json!({"error": "No TaskStore found. Has the daemon run?"})
+                );
+            }
+            OutputFormat::Text | OutputFormat::Table => {
+                debug!("cmd_metrics: outputting text error");
+                println!("No TaskStore found. Has the daemon run?");
+            }
+        }
+        return Ok(());
+    }
+
+    debug!(?store_path, "cmd_metrics: TaskStore exists");
+    let state = StateManager::spawn(&store_path)?;
+    let metrics = state.get_metrics().await?;
+    debug!(?metrics, "cmd_metrics: got metrics");
+
+    // Note: loop_type filter not yet implemented - would need to filter executions by type
+    if loop_type.is_some() {
+        debug!(
+            ?loop_type,
+            "cmd_metrics: loop_type filter requested but not implemented"
+        );
+        warn!("Loop type filter not yet implemented for metrics");
+    }
+
+    match format {
+        OutputFormat::Json => {
+            debug!("cmd_metrics: outputting JSON");
+            println!("{}", serde_json::to_string_pretty(&metrics)?);
+        }
+        OutputFormat::Text | OutputFormat::Table => {
+            debug!("cmd_metrics: outputting text/table");
+            println!("TaskDaemon Metrics");
+            println!("-----------------");
             println!("Total executions: {}", metrics.total_executions);
             println!("  Running:   {}", metrics.running);
             println!("  Pending:   {}", metrics.pending);
             println!("  Completed: {}", metrics.completed);
             println!("  Failed:    {}", metrics.failed);
             println!("  Paused:    {}", metrics.paused);
+            println!("  Parked:    {}", metrics.parked);
             println!("  Stopped:   {}", metrics.stopped);
             println!();
             println!("Total iterations: {}", metrics.total_iterations);
+            if !metrics.reruns.is_empty() {
+                println!();
+                println!("Re-runs");
+                println!("-------");
+                for comparison in &metrics.reruns {
+                    let (original, rerun) = (&comparison.original, &comparison.rerun);
+                    println!("{} -> {}", original.id, rerun.id);
+                    println!(
+                        "  {:<11} {:>10} -> {}",
+                        "status:",
+                        original.status.to_string(),
+                        rerun.status
+                    );
+                    if rerun.model.is_some() || original.model.is_some() {
+                        println!(
+                            "  {:<11} {:>10} -> {}",
+                            "model:",
+                            original.model.as_deref().unwrap_or("default"),
+                            rerun.model.as_deref().unwrap_or("default")
+                        );
+                    }
+                    println!(
+                        "  {:<11} {:>10} -> {}",
+                        "iterations:", original.iterations, rerun.iterations
+                    );
+                    println!("  {:<11} {:>10} -> {}", "tokens:", original.tokens, rerun.tokens);
+                    println!(
+                        "  {:<11} {:>10} -> {}",
+                        "duration:",
+                        taskdaemon::summary::format_duration_ms(original.duration_ms),
+                        taskdaemon::summary::format_duration_ms(rerun.duration_ms)
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Show the overview of all executions
+async fn cmd_summary(config: &Config, format: OutputFormat) -> Result<()> {
+    debug!(?format, "cmd_summary: called");
+    let store_path = PathBuf::from(&config.storage.taskstore_dir);
+    if !store_path.exists() {
+        debug!(?store_path, "cmd_summary: TaskStore does not exist");
+        println!("No TaskStore found. Has the daemon run?");
+        return Ok(());
+    }
+
+    let state = StateManager::spawn(&store_path)?;
+    let executions = state.list_executions(None, None).await?;
+    let runs_dir = dirs::home_dir()
+        .ok_or_else(|| eyre::eyre!("Could not determine home directory"))?
+        .join(".taskdaemon")
+        .join("runs");
+    let mut events = HashMap::new();
+    for exec in &executions {
+        events.insert(exec.id.clone(), read_execution_events(&runs_dir, &exec.id)?);
+    }
+    let summary = Summary::compute(&executions, &events, &config.llm.default, chrono::Utc::now());
+
+    match format {
+        OutputFormat::Json => {
+            debug!("cmd_summary: outputting JSON");
+            println!("{}", serde_json::to_string_pretty(&summary)?);
+        }
+        OutputFormat::Text | OutputFormat::Table => {
+            debug!("cmd_summary: outputting text");
+            print!("{}", summary.to_text());
+        }
+    }
+    Ok(())
+}
+
+/// Handle `td audit` subcommands
+fn cmd_audit(config: &Config, command: AuditCommand) -> Result<()> {
+    debug!(?command, "cmd_audit: called");
+    match command {
+        AuditCommand::Export { since, output } => {
+            let since = parse_since(&since, chrono::Utc::now())?;
+            let log = AuditLog::new(config.llm.audit.dir()?);
+            let records = log.read_since(since)?;
+            debug!(count = records.len(), "cmd_audit: exporting records");
+
+            let mut out: Box<dyn std::io::Write> = match &output {
+                Some(path) => Box::new(std::fs::File::create(path).context("Failed to create output file")?),
+                None => Box::new(std::io::stdout().lock()),
+            };
+            for record in &records {
+                writeln!(out, "{}", serde_json::to_string(record)?)?;
+            }
+            out.flush()?;
+
+            if let Some(path) = output {
+                eprintln!("Exported {} audit records to {}", records.len(), path.display());
+            }
+            if !config.llm.audit.enabled && records.is_empty() {
+                eprintln!("Auditing is disabled (set llm.audit.enabled in config)");
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Worktree commands: list disk usage, prune finished worktrees
+async fn cmd_worktree(config: &Config, command: WorktreeCommand) -> Result<()> {
+    debug!(?command, "cmd_worktree: called");
+    let store_path = PathBuf::from(&config.storage.taskstore_dir);
+    if !store_path.exists() {
+        debug!(?store_path, "cmd_worktree: TaskStore does not exist");
+        eprintln!("No TaskStore found at {:?}.", store_path);
+        return Ok(());
+    }
+    let state = StateManager::spawn(&store_path)?;
+    let executions = state.list_executions(None, None).await?;
+
+    let repo_root = std::env::current_dir().context("Failed to get current directory")?;
+    let manager = WorktreeManager::new(WorktreeConfig {
+        base_dir: config.git.worktree_dir.clone(),
+        ..WorktreeConfig::with_repo(repo_root)
+    });
+    let entries = classify(manager.usage().await?, &executions);
+    let mut policy = PrunePolicy::from_config(&config.git);
+
+    match command {
+        WorktreeCommand::List => {
+            debug!(count = entries.len(), "cmd_worktree: matched List command");
+            if entries.is_empty() {
+                println!("No worktrees in {}", config.git.worktree_dir.display());
+                return Ok(());
+            }
+            let status_of = |id: &str| {
+                executions
+                    .iter()
+                    .find(|e| e.id == id)
+                    .map_or_else(|| "orphaned".to_string(), |e| e.status.to_string())
+            };
+            println!("{:<50} {:<10} {:>10}", "ID", "STATUS", "SIZE");
+            println!("{}", "-".repeat(72));
+            for (usage, _) in &entries {
+                println!(
+                    "{:<50} {:<10} {:>10}",
+                    usage.exec_id,
+                    status_of(&usage.exec_id),
+                    format_size(usage.size_bytes)
+                );
+            }
+            let total: u64 = entries.iter().map(|(usage, _)| usage.size_bytes).sum();
+            let quota = policy
+                .quota_bytes
+                .map(|quota| format!(" of {} quota", format_size(quota)))
+                .unwrap_or_default();
+            println!("\n{} worktrees, {}{}", entries.len(), format_size(total), quota);
+        }
+        WorktreeCommand::Prune { older_than, dry_run } => {
+            debug!(?older_than, dry_run, "cmd_worktree: matched Prune command");
+            if let Some(days) = older_than {
+                policy.keep_finished = Some(std::time::Duration::from_secs(u64::from(days) * 24 * 60 * 60));
+            }
+            let candidates = plan_prune(&entries, &policy, now_ms());
+            if candidates.is_empty() {
+                println!("Nothing to prune");
+                return Ok(());
+            }
+            for candidate in &candidates {
+                println!(
+                    "{}{} ({}, {})",
+                    if dry_run { "Would remove " } else { "Removing " },
+                    candidate.exec_id,
+                    candidate.reason,
+                    format_size(candidate.size_bytes)
+                );
+            }
+            let freed = if dry_run {
+                candidates.iter().map(|c| c.size_bytes).sum()
+            } else {
+                manager.prune(&candidates).await
+            };
+            println!(
+                "{} {}",
+                if dry_run { "Would free" } else { "Freed" },
+                format_size(freed)
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Print status and iteration changes for `ids` until all of them are terminal
+async fn watch_executions(state: &StateManager, ids: &[String]) -> Result<()> {
+    debug!(count = ids.len(), "watch_executions: called");
+    let mut last_seen: HashMap<&str, (String, u32)> = HashMap::new();
+    loop {
+        let mut remaining = 0;
+        for id in ids {
+            let Some(exec) = state.get_execution(id).await? else {
+                debug!(%id, "watch_executions: execution disappeared");
+                continue;
+            };
+            let current = (exec.status.to_string(), exec.iteration);
+            if last_seen.get(id.as_str()) != Some(&current) {
+                let detail = match (&exec.last_error, exec.is_terminal()) {
+                    (Some(error), true) => format!(" ({})", error),
+                    _ => String::new(),
+                };
+                println!(
+                    "{:<50} {:<10} iteration {}{}",
+                    exec.id, exec.status, exec.iteration, detail
+                );
+                last_seen.insert(id.as_str(), current);
+            }
+            if !exec.is_terminal() {
+                remaining += 1;
+            }
+        }
+        if remaining == 0 {
+            debug!("watch_executions: all executions finished");
+            println!("All {} executions finished", ids.len());
+            return Ok(());
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    }
+}
+
+/// Run the environment checks; exits with status 1 if any failed
+async fn cmd_doctor(config: Result<Config>, json: bool) -> Result<()> {
+    debug!(json, "cmd_doctor: called");
+    let cwd = std::env::current_dir().context("Failed to get current directory")?;
+    let report = doctor::diagnose(config, &cwd).await;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{}", report.render_text());
+    }
+    if !report.healthy {
+        debug!("cmd_doctor: checks failed");
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Compare execution records with the event log, repairing them if asked
+async fn cmd_fsck(config: &Config, repair: bool, json: bool) -> Result<()> {
+    debug!(repair, json, "cmd_fsck: called");
+    let daemon_running = DaemonManager::new().is_running();
+    if repair && daemon_running {
+        debug!("cmd_fsck: daemon running, refusing to repair");
+        return Err(eyre::eyre!(
+            "The daemon is running; stop it with `td daemon stop` before `td fsck --repair`"
+        ));
+    }
+
+    let state = StateManager::spawn(PathBuf::from(&config.storage.taskstore_dir))?;
+    let report = state::fsck(&state, &default_runs_dir()?, daemon_running, repair).await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{}", report.render_text());
+    }
+    if report.repaired() < report.discrepancies.len() {
+        debug!("cmd_fsck: unrepaired discrepancies");
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Show the config layers and the effective config
+fn cmd_config(config_path: Option<&PathBuf>, overrides: &[String], command: ConfigCommand) -> Result<()> {
+    debug!(?config_path, ?overrides, ?command, "cmd_config: called");
+    match command {
+        ConfigCommand::Show { resolved } => {
+            let layered = LayeredConfig::load(config_path.map(PathBuf::as_path), overrides)?;
+            if resolved {
+                debug!("cmd_config: printing resolved keys");
+                print!("{}", layered.render_resolved());
+            } else {
+                println!("Layers (lowest first):");
+                for layer in &layered.layers {
+                    println!("  {}", layer);
+                }
+                println!();
+                print!("{}", serde_yaml::to_string(&layered.value)?);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Manage secrets in the configured store
+fn cmd_secrets(config: &Config, command: SecretsCommand) -> Result<()> {
+    debug!(?command, "cmd_secrets: called");
+    let backend = config.llm.secrets.backend;
+    let store = secrets::open(&config.llm.secrets)?;
+    match command {
+        SecretsCommand::Set { name } => {
+            secrets::validate_name(&name)?;
+            let value = read_secret_value(&name)?;
+            if value.is_empty() {
+                return Err(eyre::eyre!("Empty value, secret {} not stored", name));
+            }
+            store.set(&name, &value)?;
+            println!("Stored secret {} ({})", name, backend);
+        }
+        SecretsCommand::List => {
+            let names = store.list()?;
+            if names.is_empty() {
+                println!("No secrets in the {} store", backend);
+            }
+            for name in names {
+                let mut users: Vec<&str> = config
+                    .llm
+                    .providers
+                    .iter()
+                    .filter(|(_, p)| p.api_key_secret.as_deref() == Some(name.as_str()))
+                    .map(|(provider, _)| provider.as_str())
+                    .collect();
+                users.sort();
+                if users.is_empty() {
+                    println!("{}", name);
+                } else {
+                    println!("{}  (used by {})", name, users.join(", "));
+                }
+            }
+        }
+        SecretsCommand::Rm { name } => {
+            if !store.remove(&name)? {
+                return Err(eyre::eyre!("No secret {} in the {} store", name, backend));
+            }
+            println!("Removed secret {}", name);
+        }
+    }
+    Ok(())
+}
+
+/// Read a secret value from stdin when piped, else from a prompt that doesn't echo
+fn read_secret_value(name: &str) -> Result<String> {
+    debug!(%name, "read_secret_value: called");
+    if !std::io::stdin().is_terminal() {
+        let mut value = String::new();
+        std::io::stdin()
+            .read_to_string(&mut value)
+            .context("Failed to read secret from stdin")?;
+        return Ok(value.trim_end_matches(['\r', '\n']).to_string());
+    }
+
+    eprint!("Value for {}: ", name);
+    std::io::stderr().flush()?;
+    crossterm::terminal::enable_raw_mode()?;
+    let value = read_hidden_line();
+    crossterm::terminal::disable_raw_mode()?;
+    eprintln!();
+    value
+}
+
+/// Read keys until Enter without echoing them (the terminal is in raw mode)
+fn read_hidden_line() -> Result<String> {
+    use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+    let mut value = String::new();
+    loop {
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Enter => return Ok(value),
+            KeyCode::Backspace => {
+                value.pop();
+            }
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Err(eyre::eyre!("Cancelled"));
+            }
+            KeyCode::Char(c) => value.push(c),
+            _ => {}
+        }
+    }
+}
+
+/// Create the taskdaemon files for the project in the current directory
+fn cmd_init(
+    config: Option<Config>,
+    language: Option<ProjectLanguage>,
+    validation_command: Option<String>,
+    model: Option<String>,
+    yes: bool,
+    force: bool,
+) -> Result<()> {
+    debug!(?language, ?validation_command, ?model, %yes, %force, "cmd_init: called");
+    let cwd = std::env::current_dir().context("Failed to get current directory")?;
+    let mut options = InitOptions::new(&cwd).with_force(force);
+    if let Some(language) = language {
+        options = options.with_language(language);
+    }
+    if let Some(command) = validation_command {
+        options = options.with_validation_command(command);
+    }
+    // Keep the model of an existing config file unless one is given
+    match (model, config.filter(|c| c.source.is_some())) {
+        (Some(model), _) => options = options.with_model(model),
+        (None, Some(config)) => options = options.with_model(config.llm.default),
+        (None, None) => {}
+    }
+
+    if !yes && std::io::stdin().is_terminal() {
+        debug!("cmd_init: asking interactively");
+        options = init::prompt_options(options, &mut std::io::stdin().lock(), &mut std::io::stdout())?;
+    }
+
+    let actions = init::init_project(&cwd, &options)?;
+    for (path, action) in &actions {
+        println!("  {:<32} {}", path.display(), action);
+    }
+    println!();
+    println!(
+        "Initialized taskdaemon for a {} project (validation: {})",
+        options.language,
+        options.validation_command()
+    );
+    println!("Run `td doctor` to check the setup.");
+    Ok(())
+}
+
+/// Most candidates listed when an execution ID is ambiguous
+const MAX_ID_CANDIDATES: usize = 10;
+
+/// Resolve an execution ID, prefix or fragment to a full ID
+///
+/// Prints an error (listing candidates when the reference is ambiguous) and
+/// returns None if it doesn't identify exactly one execution.
+async fn resolve_execution_id(state: &StateManager, reference: &str) -> Result<Option<String>> {
+    debug!(%reference, "resolve_execution_id: called");
+    let ids: HashMap<String, String> = state
+        .list_executions(None, None)
+        .await?
+        .into_iter()
+        .map(|exec| {
+            let name = exec.title.clone().unwrap_or_else(|| exec.loop_type.clone());
+            (exec.id, format!("{} [{}]", name, exec.status))
+        })
+        .collect();
+    let resolver = IdResolver::new(&ids);
+
+    match resolver.resolve(reference) {
+        Ok(Some(id)) => {
+            debug!(%reference, %id, "resolve_execution_id: resolved");
+            Ok(Some(id))
+        }
+        Ok(None) => {
+            debug!(%reference, "resolve_execution_id: no match");
+            eprintln!("Execution '{}' not found", reference);
+            Ok(None)
+        }
+        Err(candidates) => {
+            debug!(%reference, count = candidates.len(), "resolve_execution_id: ambiguous");
+            eprintln!("Execution ID '{}' is ambiguous; did you mean:", reference);
+            for id in candidates.iter().take(MAX_ID_CANDIDATES) {
+                eprintln!("  {:<50} {}", id, resolver.name(id).unwrap_or_default());
+            }
+            if candidates.len() > MAX_ID_CANDIDATES {
+                eprintln!("  ... and {} more", candidates.len() - MAX_ID_CANDIDATES);
+            }
+            Ok(None)
+        }
+    }
+}
+
+/// Preview (or with `--yes` apply) a bulk action on the executions matching `--filter`
+async fn cmd_exec_bulk(state: &StateManager, action: BulkAction, bulk: BulkArgs) -> Result<()> {
+    debug!(%action, ?bulk, "cmd_exec_bulk: called");
+    let filter = bulk
+        .filter
+        .ok_or_else(|| eyre::eyre!("Give an execution ID or --filter"))?;
+    let (targets, skipped): (Vec<LoopExecution>, Vec<LoopExecution>) = state
+        .list_executions(None, None)
+        .await?
+        .into_iter()
+        .filter(|exec| filter.matches(exec))
+        .partition(|exec| action.applies_to(exec));
+    if !skipped.is_empty() {
+        println!(
+            "Skipping {} matching execution(s) that can't be {} in their current status",
+            skipped.len(),
+            action.past_tense().to_lowercase()
+        );
+    }
+    if targets.is_empty() {
+        debug!("cmd_exec_bulk: nothing to do");
+        println!("No executions to {}", action);
+        return Ok(());
+    }
+
+    if !bulk.yes {
+        debug!(count = targets.len(), "cmd_exec_bulk: dry run");
+        println!("Would {} {} execution(s):", action, targets.len());
+        for exec in &targets {
+            println!("  {:<50} {:<10} {}", exec.id, exec.status, exec.loop_type);
         }
+        println!("Re-run with --yes to apply.");
+        return Ok(());
+    }
+
+    let ids: Vec<String> = targets.into_iter().map(|exec| exec.id).collect();
+    let outcome = apply_all(state, action, &ids).await;
+    println!("{} {} execution(s)", action.past_tense(), outcome.done.len());
+    for (id, error) in &outcome.failed {
+        eprintln!("Failed to {} {}: {}", action, id, error);
+    }
+    Ok(())
+}
+
+/// Queue position and estimated wait of every pending execution, by ID
+///
+/// Positions follow the daemon's pickup order (aged priority under the
+/// configured fairness policy). The estimate spreads the mean duration of
+/// completed executions over `max-loops` slots; it is None until one completes.
+fn queue_positions(config: &Config, executions: &[LoopExecution]) -> HashMap<String, (usize, Option<u64>)> {
+    debug!(count = executions.len(), "queue_positions: called");
+    let pending: Vec<LoopExecution> = executions
+        .iter()
+        .filter(|e| e.status == LoopExecutionStatus::Pending)
+        .cloned()
+        .collect();
+    let mut running: HashMap<String, usize> = HashMap::new();
+    for exec in executions.iter().filter(|e| e.status == LoopExecutionStatus::Running) {
+        *running.entry(exec.loop_type.clone()).or_default() += 1;
     }
+    let durations: Vec<u64> = executions
+        .iter()
+        .filter(|e| e.status == LoopExecutionStatus::Complete && e.total_duration_ms > 0)
+        .map(|e| e.total_duration_ms)
+        .collect();
+    let mean_ms = (!durations.is_empty()).then(|| durations.iter().sum::<u64>() / durations.len() as u64);
+    let slots = config.concurrency.max_loops.max(1) as u64;
+
+    let order = config
+        .concurrency
+        .scheduler_config()
+        .pending_order(&pending, &running, now_ms());
+    order
+        .into_iter()
+        .enumerate()
+        .map(|(position, i)| {
+            let eta = mean_ms.map(|mean| position as u64 * mean / slots);
+            (pending[i].id.clone(), (position + 1, eta))
+        })
+        .collect()
+}
 
+/// Open `path` in $VISUAL or $EDITOR (default: vi) and wait for it to exit
+fn open_in_editor(path: &std::path::Path) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    debug!(%editor, ?path, "open_in_editor: called");
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    let status = std::process::Command::new(program)
+        .args(words)
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to run editor '{}'", editor))?;
+    if !status.success() {
+        eyre::bail!("editor '{}' exited with {}", editor, status);
+    }
     Ok(())
 }
 
 /// Handle execution management commands
-async fn cmd_exec(config: &Config, command: ExecCommand) -> Result<()> {
+async fn cmd_exec(config: &Config, mut command: ExecCommand) -> Result<()> {
     debug!(?command, "cmd_exec: called");
-    use taskdaemon::domain::LoopExecutionStatus;
+    use taskdaemon::domain::{CherryPick, WakeCondition};
 
     let store_path = PathBuf::from(&config.storage.taskstore_dir);
     if !store_path.exists() {
@@ -678,22 +2167,105 @@ async fn cmd_exec(config: &Config, command: ExecCommand) -> Result<()> {
     debug!(?store_path, "cmd_exec: TaskStore exists");
     let state = StateManager::spawn(&store_path)?;
 
+    if let Some(id) = command.id_mut() {
+        let Some(full_id) = resolve_execution_id(&state, id).await? else {
+            return Ok(());
+        };
+        *id = full_id;
+    }
+
     match command {
-        ExecCommand::List { status } => {
-            debug!(?status, "cmd_exec: matched List command");
-            let executions = state.list_executions(status.clone(), None).await?;
+        ExecCommand::Ids => {
+            debug!("cmd_exec: matched Ids command");
+            for exec in state.list_executions(None, None).await? {
+                println!("{}", exec.id);
+            }
+        }
+        ExecCommand::List {
+            status,
+            tags,
+            user,
+            mine,
+        } => {
+            debug!(?status, ?tags, ?user, mine, "cmd_exec: matched List command");
+            let user = if mine {
+                match config.user.submitter() {
+                    Some(me) => Some(me.user),
+                    None => eyre::bail!("Can't tell who you are for --mine (set $USER)"),
+                }
+            } else {
+                user
+            };
+            let mut executions = state.list_executions(status.clone(), None).await?;
+            executions.retain(|exec| tags.iter().all(|tag| exec.has_tag(tag)));
+            if let Some(user) = &user {
+                executions.retain(|exec| exec.submitted_by_user(user));
+            }
             if executions.is_empty() {
                 debug!("cmd_exec: no executions found");
+                let mut criteria = Vec::new();
+                if let Some(status) = status {
+                    criteria.push(format!("status '{}'", status));
+                }
+                if !tags.is_empty() {
+                    criteria.push(format!("tags '{}'", tags.join(", ")));
+                }
+                if let Some(user) = user {
+                    criteria.push(format!("user '{}'", user));
+                }
                 println!(
                     "No executions found{}",
-                    status.map(|s| format!(" with status '{}'", s)).unwrap_or_default()
+                    if criteria.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" with {}", criteria.join(" and "))
+                    }
                 );
             } else {
                 debug!(count = executions.len(), "cmd_exec: found executions");
-                println!("{:<50} {:<10} {:<20}", "ID", "STATUS", "TYPE");
-                println!("{}", "-".repeat(80));
+                let queue = queue_positions(config, &state.list_executions(None, None).await?);
+                println!(
+                    "{:<50} {:<10} {:<20} {:<12} {:>5} {:>8}  TAGS",
+                    "ID", "STATUS", "TYPE", "USER", "QUEUE", "ETA"
+                );
+                println!("{}", "-".repeat(123));
                 for exec in executions {
-                    println!("{:<50} {:<10} {:<20}", exec.id, exec.status, exec.loop_type);
+                    let (position, eta) = match queue.get(&exec.id) {
+                        Some((position, eta)) => (
+                            position.to_string(),
+                            eta.map(taskdaemon::summary::format_duration_ms)
+                                .unwrap_or_else(|| "?".to_string()),
+                        ),
+                        None => ("-".to_string(), "-".to_string()),
+                    };
+                    println!(
+                        "{:<50} {:<10} {:<20} {:<12} {:>5} {:>8}  {}",
+                        exec.id,
+                        exec.status,
+                        exec.loop_type,
+                        exec.submitted_by.as_ref().map_or("-", |by| by.user.as_str()),
+                        position,
+                        eta,
+                        exec.tags.join(",")
+                    );
+                }
+            }
+        }
+        ExecCommand::Tag { id, tags, remove } => {
+            debug!(%id, ?tags, remove, "cmd_exec: matched Tag command");
+            let (add, drop) = if remove { (Vec::new(), tags) } else { (tags, Vec::new()) };
+            match state.tag_execution(&id, &add, &drop).await {
+                Ok(tags) => {
+                    debug!(%id, ?tags, "cmd_exec: tag succeeded");
+                    if tags.is_empty() {
+                        println!("Execution '{}' has no tags", id);
+                    } else {
+                        println!("Execution '{}' tags: {}", id, tags.join(", "));
+                    }
+                }
+                Err(e) => {
+                    debug!(%id, error = %e, "cmd_exec: tag failed");
+                    eprintln!("Failed to tag: {}", e);
                 }
             }
         }
@@ -710,7 +2282,7 @@ async fn cmd_exec(config: &Config, command: ExecCommand) -> Result<()> {
                 }
             }
         }
-        ExecCommand::Pause { id } => {
+        ExecCommand::Pause { id: Some(id), .. } => {
             debug!(%id, "cmd_exec: matched Pause command");
             match state.pause_execution(&id).await {
                 Ok(()) => {
@@ -723,7 +2295,7 @@ async fn cmd_exec(config: &Config, command: ExecCommand) -> Result<()> {
                 }
             }
         }
-        ExecCommand::Resume { id } => {
+        ExecCommand::Resume { id: Some(id), .. } => {
             debug!(%id, "cmd_exec: matched Resume command");
             match state.resume_execution(&id).await {
                 Ok(()) => {
@@ -736,6 +2308,362 @@ async fn cmd_exec(config: &Config, command: ExecCommand) -> Result<()> {
                 }
             }
         }
+        ExecCommand::Cancel { id: Some(id), .. } => {
+            debug!(%id, "cmd_exec: matched Cancel command");
+            match state.cancel_execution(&id).await {
+                Ok(()) => {
+                    debug!(%id, "cmd_exec: cancel succeeded");
+                    println!("Cancelled execution '{}' (-> stopped)", id);
+                }
+                Err(e) => {
+                    debug!(%id, error = %e, "cmd_exec: cancel failed");
+                    eprintln!("Failed to cancel: {}", e);
+                }
+            }
+        }
+        ExecCommand::Delete { id: Some(id), .. } => {
+            debug!(%id, "cmd_exec: matched Delete command");
+            match state.delete_execution(&id).await {
+                Ok(()) => {
+                    debug!(%id, "cmd_exec: delete succeeded");
+                    println!("Deleted execution '{}'", id);
+                }
+                Err(e) => {
+                    debug!(%id, error = %e, "cmd_exec: delete failed");
+                    eprintln!("Failed to delete: {}", e);
+                }
+            }
+        }
+        ExecCommand::Pause { bulk, .. } => cmd_exec_bulk(&state, BulkAction::Pause, bulk).await?,
+        ExecCommand::Resume { bulk, .. } => cmd_exec_bulk(&state, BulkAction::Resume, bulk).await?,
+        ExecCommand::Cancel { bulk, .. } => cmd_exec_bulk(&state, BulkAction::Cancel, bulk).await?,
+        ExecCommand::Delete { bulk, .. } => cmd_exec_bulk(&state, BulkAction::Delete, bulk).await?,
+        ExecCommand::Park { id, files, refs, urls } => {
+            debug!(%id, ?files, ?refs, ?urls, "cmd_exec: matched Park command");
+            let repo_root = std::env::current_dir()?;
+            let mut conditions: Vec<WakeCondition> = files.into_iter().map(WakeCondition::file_exists).collect();
+            for git_ref in refs {
+                // Record where the ref points now; the daemon wakes the execution once it moves
+                let from = resolve_ref(&repo_root, &git_ref).await;
+                conditions.push(WakeCondition::ref_advanced(git_ref, from));
+            }
+            conditions.extend(urls.into_iter().map(WakeCondition::http_ok));
+
+            let summary = conditions.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(", ");
+            match state.park_execution(&id, conditions).await {
+                Ok(()) => {
+                    debug!(%id, "cmd_exec: park succeeded");
+                    println!("Parked execution '{}' until: {}", id, summary);
+                }
+                Err(e) => {
+                    debug!(%id, error = %e, "cmd_exec: park failed");
+                    eprintln!("Failed to park: {}", e);
+                }
+            }
+        }
+        ExecCommand::Wake { id } => {
+            debug!(%id, "cmd_exec: matched Wake command");
+            match state.wake_execution(&id).await {
+                Ok(()) => {
+                    debug!(%id, "cmd_exec: wake succeeded");
+                    println!("Woke execution '{}' (parked -> pending)", id);
+                }
+                Err(e) => {
+                    debug!(%id, error = %e, "cmd_exec: wake failed");
+                    eprintln!("Failed to wake: {}", e);
+                }
+            }
+        }
+        ExecCommand::Approve { id } => {
+            debug!(%id, "cmd_exec: matched Approve command");
+            match state.approve_execution(&id, config.user.submitter()).await {
+                Ok(()) => {
+                    debug!(%id, "cmd_exec: approve succeeded");
+                    println!("Approved execution '{}' for merge (blocked -> running)", id);
+                }
+                Err(e) => {
+                    debug!(%id, error = %e, "cmd_exec: approve failed");
+                    eprintln!("Failed to approve: {}", e);
+                }
+            }
+        }
+        ExecCommand::Rollback { id, to_iteration } => {
+            debug!(%id, to_iteration, "cmd_exec: matched Rollback command");
+            let Some(exec) = state.get_execution(&id).await? else {
+                eprintln!("Execution '{}' not found", id);
+                return Ok(());
+            };
+            if matches!(
+                exec.status,
+                LoopExecutionStatus::Running | LoopExecutionStatus::Pending | LoopExecutionStatus::Rebasing
+            ) {
+                debug!(%id, status = %exec.status, "cmd_exec: execution is active");
+                eprintln!("Execution '{}' is {}; pause it before rolling back", id, exec.status);
+                return Ok(());
+            }
+
+            let worktree = exec
+                .worktree
+                .as_ref()
+                .map(PathBuf::from)
+                .unwrap_or_else(|| config.git.worktree_dir.join(&exec.id));
+            if !worktree.exists() {
+                debug!(?worktree, "cmd_exec: worktree missing");
+                eprintln!("Worktree {} no longer exists", worktree.display());
+                return Ok(());
+            }
+
+            if let Err(e) = restore_snapshot(&worktree, &exec.id, to_iteration).await {
+                debug!(%id, error = %e, "cmd_exec: restore failed");
+                let available = list_snapshots(&worktree, &exec.id).await.unwrap_or_default();
+                eprintln!("Failed to roll back: {}", e);
+                if !available.is_empty() {
+                    let list: Vec<String> = available.iter().map(u32::to_string).collect();
+                    eprintln!("Snapshots exist for iterations: {}", list.join(", "));
+                }
+                return Ok(());
+            }
+            match state.rollback_execution(&exec.id, to_iteration).await {
+                Ok(()) => {
+                    debug!(%id, "cmd_exec: rollback succeeded");
+                    println!(
+                        "Rolled back execution '{}' to iteration {} ({} -> pending)",
+                        exec.id, to_iteration, exec.status
+                    );
+                }
+                Err(e) => {
+                    debug!(%id, error = %e, "cmd_exec: rollback failed");
+                    eprintln!("Worktree restored, but failed to requeue: {}", e);
+                }
+            }
+        }
+        ExecCommand::Rerun {
+            id,
+            edit_plan,
+            model,
+            max_iterations,
+        } => {
+            debug!(%id, edit_plan, ?model, ?max_iterations, "cmd_exec: matched Rerun command");
+            let Some(exec) = state.get_execution(&id).await? else {
+                eprintln!("Execution '{}' not found", id);
+                return Ok(());
+            };
+            if let Some(ref model) = model {
+                let llm = LlmConfig {
+                    default: model.clone(),
+                    ..config.llm.clone()
+                };
+                if let Err(e) = llm.resolve() {
+                    debug!(%model, error = %e, "cmd_exec: model override does not resolve");
+                    eprintln!("Invalid --model '{}': {}", model, e);
+                    return Ok(());
+                }
+            }
+
+            let attempt = state
+                .list_executions(None, None)
+                .await?
+                .iter()
+                .filter(|e| e.rerun_of.as_deref() == Some(exec.id.as_str()))
+                .count() as u32
+                + 1;
+            let mut rerun = exec.rerun(attempt).with_submitter(config.user.submitter());
+            if model.is_some() {
+                rerun.model = model;
+            }
+            if max_iterations.is_some() {
+                rerun.max_iterations = max_iterations;
+            }
+
+            let plans_dir = PathBuf::from(".taskdaemon/plans");
+            let plan = plans_dir.join(&exec.id).join("plan.md");
+            let rerun_dir = plans_dir.join(&rerun.id);
+            if plan.exists() {
+                debug!(?plan, "cmd_exec: copying plan");
+                fs::create_dir_all(&rerun_dir)?;
+                fs::copy(&plan, rerun_dir.join("plan.md"))?;
+            } else if edit_plan {
+                debug!(?plan, "cmd_exec: no plan to edit");
+                eprintln!("Execution '{}' has no plan at {}", exec.id, plan.display());
+                return Ok(());
+            }
+            if edit_plan && let Err(e) = open_in_editor(&rerun_dir.join("plan.md")) {
+                debug!(error = %e, "cmd_exec: editing plan failed");
+                let _ = fs::remove_dir_all(&rerun_dir);
+                eprintln!("Not re-running: {}", e);
+                return Ok(());
+            }
+
+            let new_id = state.submit_execution(rerun).await?;
+            println!("Re-running '{}' as '{}' (pending)", exec.id, new_id);
+            println!("Compare the two with `td exec diff {} {}`", exec.id, new_id);
+        }
+        ExecCommand::Diff { mut left, mut right } => {
+            debug!(%left, %right, "cmd_exec: matched Diff command");
+            for id in [&mut left, &mut right] {
+                let Some(full_id) = resolve_execution_id(&state, id).await? else {
+                    return Ok(());
+                };
+                *id = full_id;
+            }
+            let (Some(left), Some(right)) = (state.get_execution(&left).await?, state.get_execution(&right).await?)
+            else {
+                eprintln!("Execution not found");
+                return Ok(());
+            };
+            let comparison = ExecutionComparison::load(left, right, &default_runs_dir()?, &config.llm.default).await;
+            print!("{}", comparison.to_text());
+        }
+        ExecCommand::Set { id, settings } => {
+            debug!(%id, ?settings, "cmd_exec: matched Set command");
+            for setting in &settings {
+                if let RunOverride::Model(Some(model)) = setting {
+                    let llm = LlmConfig {
+                        default: model.clone(),
+                        ..config.llm.clone()
+                    };
+                    if let Err(e) = llm.resolve() {
+                        debug!(%model, error = %e, "cmd_exec: model override does not resolve");
+                        eprintln!("Invalid model '{}': {}", model, e);
+                        return Ok(());
+                    }
+                }
+            }
+            match state.set_overrides(&id, &settings).await {
+                Ok(exec) => {
+                    debug!(%id, "cmd_exec: set succeeded");
+                    println!("Execution '{}' overrides:", exec.id);
+                    println!("  model:          {}", exec.model.as_deref().unwrap_or("default"));
+                    match exec.max_iterations {
+                        Some(n) => println!("  max_iterations: {}", n),
+                        None => println!("  max_iterations: default"),
+                    }
+                    if exec.status == LoopExecutionStatus::Running {
+                        println!("Applies from the next iteration");
+                    }
+                }
+                Err(e) => {
+                    debug!(%id, error = %e, "cmd_exec: set failed");
+                    eprintln!("Failed to set overrides: {}", e);
+                }
+            }
+        }
+        ExecCommand::CherryPick {
+            id,
+            commits,
+            branch,
+            base,
+        } => {
+            debug!(%id, ?commits, ?branch, %base, "cmd_exec: matched CherryPick command");
+            let Some(exec) = state.get_execution(&id).await? else {
+                eprintln!("Execution '{}' not found", id);
+                return Ok(());
+            };
+            let branch = branch.unwrap_or_else(|| format!("picked/{}", exec.id));
+            let repo_root = std::env::current_dir().context("Failed to get current directory")?;
+
+            match cherry_pick_to_branch(&repo_root, &exec.id, commits.as_deref(), &branch, &base).await {
+                Ok(CherryPickResult::Success { branch, commits }) => {
+                    debug!(%id, count = commits.len(), "cmd_exec: cherry-pick succeeded");
+                    println!(
+                        "Picked {} commit(s) from '{}' onto new branch {} (from {})",
+                        commits.len(),
+                        exec.id,
+                        branch,
+                        base
+                    );
+                    let pick = CherryPick {
+                        branch,
+                        base,
+                        commits,
+                        created_at: now_ms(),
+                    };
+                    if let Err(e) = state.record_cherry_pick(&exec.id, pick).await {
+                        debug!(%id, error = %e, "cmd_exec: failed to record cherry-pick");
+                        eprintln!("Branch created, but failed to record it in the execution: {}", e);
+                    }
+                }
+                Ok(CherryPickResult::Conflict { commit, message }) => {
+                    debug!(%id, %commit, "cmd_exec: cherry-pick conflict");
+                    eprintln!(
+                        "Commit {} does not apply cleanly onto {}; no branch created",
+                        commit, base
+                    );
+                    eprintln!("{}", message.trim());
+                }
+                Err(e) => {
+                    debug!(%id, error = %e, "cmd_exec: cherry-pick failed");
+                    eprintln!("Failed to cherry-pick: {}", e);
+                }
+            }
+        }
+        ExecCommand::Submit { manifest, watch } => {
+            debug!(?manifest, watch, "cmd_exec: matched Submit command");
+            let batch = BatchManifest::load(&manifest)?;
+            let loader = LoopLoader::new(&config.loops).context("Failed to load loop types")?;
+            let current = state.list_executions(None, None).await?;
+            let existing: HashSet<String> = current.iter().map(|e| e.id.clone()).collect();
+
+            let submitter = config.user.submitter();
+            let executions = match batch.to_executions(&loader, &existing) {
+                Ok(executions) => executions
+                    .into_iter()
+                    .map(|exec| exec.with_submitter(submitter.clone()))
+                    .collect::<Vec<_>>(),
+                Err(errors) => {
+                    debug!(error_count = errors.len(), "cmd_exec: manifest invalid");
+                    eyre::bail!(
+                        "Invalid manifest {}:\n  - {}",
+                        manifest.display(),
+                        errors.join("\n  - ")
+                    );
+                }
+            };
+
+            // Check every task before creating any, so a blocked duplicate leaves nothing half-submitted
+            let now = taskstore::now_ms();
+            let mut blocked = Vec::new();
+            for exec in &executions {
+                if let Some(duplicate) = find_duplicate(exec, &current, &config.dedup, now) {
+                    let title = exec.title.as_deref().unwrap_or(&exec.id);
+                    if duplicate.blocked {
+                        blocked.push(format!("{}: {}", title, duplicate));
+                    } else {
+                        eprintln!("Warning: {}: {}", title, duplicate);
+                    }
+                }
+            }
+            if !blocked.is_empty() {
+                debug!(count = blocked.len(), "cmd_exec: duplicates blocked");
+                eyre::bail!(
+                    "Refusing to submit duplicate tasks (dedup.mode = \"block\"):\n  - {}",
+                    blocked.join("\n  - ")
+                );
+            }
+
+            println!("{:<50} {:<10} {:<9} {:<5} TITLE", "ID", "TYPE", "PRIORITY", "DEPS");
+            println!("{}", "-".repeat(100));
+            let mut ids = Vec::with_capacity(executions.len());
+            for exec in executions {
+                let row = format!(
+                    "{:<50} {:<10} {:<9} {:<5} {}",
+                    exec.id,
+                    exec.loop_type,
+                    exec.priority,
+                    exec.deps.len(),
+                    exec.title.as_deref().unwrap_or_default()
+                );
+                ids.push(state.submit_execution(exec).await?);
+                println!("{}", row);
+            }
+            println!();
+            println!("Submitted {} executions", ids.len());
+
+            if watch {
+                debug!(count = ids.len(), "cmd_exec: watching submitted executions");
+                watch_executions(&state, &ids).await?;
+            }
+        }
         ExecCommand::Status { id, status } => {
             debug!(%id, %status, "cmd_exec: matched Status command");
             let new_status = match status.to_lowercase().as_str() {
@@ -755,6 +2683,14 @@ async fn cmd_exec(config: &Config, command: ExecCommand) -> Result<()> {
                     debug!("cmd_exec: matched paused status");
                     LoopExecutionStatus::Paused
                 }
+                "parked" => {
+                    debug!("cmd_exec: matched parked status");
+                    LoopExecutionStatus::Parked
+                }
+                "waiting_provider" => {
+                    debug!("cmd_exec: matched waiting_provider status");
+                    LoopExecutionStatus::WaitingProvider
+                }
                 "complete" => {
                     debug!("cmd_exec: matched complete status");
                     LoopExecutionStatus::Complete
@@ -770,7 +2706,7 @@ async fn cmd_exec(config: &Config, command: ExecCommand) -> Result<()> {
                 _ => {
                     debug!(%status, "cmd_exec: invalid status");
                     eprintln!(
-                        "Invalid status '{}'. Valid: draft, pending, running, paused, complete, failed, stopped",
+                        "Invalid status '{}'. Valid: draft, pending, running, paused, parked, waiting_provider, complete, failed, stopped",
                         status
                     );
                     return Ok(());
@@ -791,142 +2727,316 @@ async fn cmd_exec(config: &Config, command: ExecCommand) -> Result<()> {
                 }
             }
         }
-    }
-
-    Ok(())
-}
-
-/// Run the daemon main loop
-async fn run_daemon(config: &Config) -> Result<()> {
-    debug!("run_daemon: called");
-    info!("Daemon starting...");
-
-    // ============================================================
-    // EARLY VALIDATION - Fail fast with clear error messages
-    // ============================================================
-
-    // Validate LLM API key is set by resolving the config
-    config
-        .llm
-        .resolve()
-        .and_then(|r| r.get_api_key())
-        .context("LLM API key not found. Check api-key-env or api-key-file in your config.")?;
-    debug!("run_daemon: API key found");
-
-    // Validate we're in a git repository
-    let repo_root = std::env::current_dir().context("Failed to get current directory")?;
-    if !repo_root.join(".git").exists() {
-        debug!(?repo_root, "run_daemon: not a git repository");
-        return Err(eyre::eyre!(
-            "Not a git repository: {}. TaskDaemon requires a git repo.",
-            repo_root.display()
-        ));
-    }
-    debug!(?repo_root, "run_daemon: git repository found");
-
-    // Ensure worktree directory is creatable
-    let worktree_dir = &config.git.worktree_dir;
-    if let Err(e) = fs::create_dir_all(worktree_dir) {
-        debug!(?worktree_dir, error = %e, "run_daemon: cannot create worktree directory");
-        return Err(eyre::eyre!(
-            "Cannot create worktree directory {}: {}",
-            worktree_dir.display(),
-            e
-        ));
-    }
-    debug!(?worktree_dir, "run_daemon: worktree directory created/exists");
-
-    info!("Startup validation passed");
+        ExecCommand::Report { id, format, output } => {
+            debug!(%id, %format, ?output, "cmd_exec: matched Report command");
+            let Some(exec) = state.get_execution(&id).await? else {
+                debug!(%id, "cmd_exec: execution not found");
+                eprintln!("Execution '{}' not found", id);
+                return Ok(());
+            };
 
-    // ============================================================
-    // INITIALIZATION
-    // ============================================================
+            let report = build_execution_report(config, exec).await?;
+            let rendered = report.render(format);
+            match output {
+                Some(path) => {
+                    debug!(?path, "cmd_exec: writing report to file");
+                    fs::write(&path, rendered).with_context(|| format!("Failed to write report to {:?}", path))?;
+                    println!("Wrote {} report to {}", format, path.display());
+                }
+                None => {
+                    debug!("cmd_exec: writing report to stdout");
+                    print!("{}", rendered);
+                }
+            }
+        }
+        ExecCommand::Timeline { id, format, output } => {
+            debug!(%id, %format, ?output, "cmd_exec: matched Timeline command");
+            let Some(exec) = state.get_execution(&id).await? else {
+                debug!(%id, "cmd_exec: execution not found");
+                eprintln!("Execution '{}' not found", id);
+                return Ok(());
+            };
 
-    // Initialize components
-    let store_path = PathBuf::from(&config.storage.taskstore_dir);
-    if !store_path.exists() {
-        debug!(?store_path, "run_daemon: creating store directory");
-        fs::create_dir_all(&store_path)?;
-    } else {
-        debug!(?store_path, "run_daemon: store directory exists");
-    }
+            let entries = read_execution_events(default_runs_dir()?, &exec.id)?;
+            let rendered = Timeline::from_events(exec.id, &entries).render(format);
+            match output {
+                Some(path) => {
+                    debug!(?path, "cmd_exec: writing timeline to file");
+                    fs::write(&path, rendered).with_context(|| format!("Failed to write timeline to {:?}", path))?;
+                    println!("Wrote {} timeline to {}", format, path.display());
+                }
+                None => {
+                    debug!("cmd_exec: writing timeline to stdout");
+                    print!("{}", rendered);
+                }
+            }
+        }
+        ExecCommand::Logs { id, follow } => {
+            debug!(%id, follow, "cmd_exec: matched Logs command");
+            let Some(exec) = state.get_execution(&id).await? else {
+                debug!(%id, "cmd_exec: execution not found");
+                eprintln!("Execution '{}' not found", id);
+                return Ok(());
+            };
 
-    let state_manager = StateManager::spawn(&store_path)?;
-    info!("StateManager initialized");
+            // Subscribe before reading the log so nothing falls in between
+            let live = if follow && !exec.is_terminal() {
+                match ipc::DaemonClient::new().subscribe(Some(&exec.id)).await {
+                    Ok(stream) => Some(stream),
+                    Err(e) => {
+                        debug!(error = %e, "cmd_exec: subscribe failed");
+                        eprintln!("Cannot follow, daemon not reachable ({}); showing logged output", e);
+                        None
+                    }
+                }
+            } else {
+                None
+            };
 
-    // Load loop types and convert to configs
-    let loader = LoopLoader::new(&config.loops)?;
-    let loop_configs = loader.to_configs();
-    info!(
-        "Loaded {} loop types: {:?}",
-        loop_configs.len(),
-        loop_configs.keys().collect::<Vec<_>>()
-    );
-    let type_loader = std::sync::Arc::new(std::sync::RwLock::new(loader));
+            let mut printer = LogPrinter::new();
+            for entry in read_execution_events(default_runs_dir()?, &exec.id)? {
+                printer.print(&entry.event);
+            }
+            if let Some(stream) = live {
+                match logs::follow(&state, &exec.id, stream, &mut printer).await? {
+                    FollowEnd::Finished(status) => println!("\nExecution {} {}", exec.id, status),
+                    FollowEnd::Disconnected => eprintln!("\nDaemon closed the event stream"),
+                    FollowEnd::Interrupted => {}
+                }
+            } else if follow {
+                println!("\nExecution {} {}", exec.id, exec.status);
+            }
+        }
+        ExecCommand::Watch { id } => {
+            debug!(%id, "cmd_exec: matched Watch command");
+            let Some(exec) = state.get_execution(&id).await? else {
+                debug!(%id, "cmd_exec: execution not found");
+                eprintln!("Execution '{}' not found", id);
+                return Ok(());
+            };
+            if exec.is_terminal() {
+                println!("Execution {} {}", exec.id, exec.status);
+                return Ok(());
+            }
 
-    // Initialize coordinator for inter-loop communication (with event persistence)
-    let coordinator = Coordinator::with_persistence(Default::default(), &store_path);
-    let coordinator_tx = coordinator.sender();
+            // Subscribe before reading the log so nothing falls in between
+            let live = match ipc::DaemonClient::new().subscribe(Some(&exec.id)).await {
+                Ok(stream) => Some(stream),
+                Err(e) => {
+                    debug!(error = %e, "cmd_exec: subscribe failed");
+                    eprintln!("Daemon not reachable ({}); showing status only", e);
+                    None
+                }
+            };
+            let max_iterations = LoopLoader::new(&config.loops)
+                .ok()
+                .and_then(|loader| loader.get(&exec.loop_type).map(|loop_type| loop_type.max_iterations));
+            let theme = tui::Theme::load(&config.tui.theme, config.tui.themes_dir.as_deref()).unwrap_or_else(|e| {
+                warn!(error = %e, "cmd_exec: failed to load theme, using default");
+                tui::Theme::default()
+            });
 
-    // Spawn coordinator task
-    let coord_handle = tokio::spawn(coordinator.run());
-    info!("Coordinator started");
+            let exec_id = exec.id.clone();
+            let mut view = tui::WatchView::new(exec, max_iterations);
+            for entry in read_execution_events(default_runs_dir()?, &exec_id)? {
+                view.apply(&entry.event);
+            }
+            match tui::watch(&state, view, live, &theme).await? {
+                FollowEnd::Finished(status) => println!("Execution {} {}", exec_id, status),
+                FollowEnd::Disconnected => eprintln!("Daemon closed the event stream"),
+                FollowEnd::Interrupted => {}
+            }
+        }
+        ExecCommand::Prompt { id, iteration, json } => {
+            debug!(%id, ?iteration, json, "cmd_exec: matched Prompt command");
+            let Some(exec) = state.get_execution(&id).await? else {
+                eprintln!("Execution '{}' not found", id);
+                return Ok(());
+            };
+            let loader = LoopLoader::new(&config.loops).context("Failed to load loop types")?;
+            let loop_config = loader
+                .to_configs()
+                .remove(&exec.loop_type)
+                .ok_or_else(|| eyre::eyre!("Unknown loop type: {}", exec.loop_type))?;
+            let logs = state.list_iteration_logs(&exec.id).await?;
+            let repo_root = std::env::current_dir().context("Failed to get current directory")?;
+            let preview = preview_prompt(&exec, loop_config, &logs, iteration, &repo_root).await?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&preview)?);
+            } else {
+                println!("# Iteration {} of {}", preview.iteration, exec.id);
+                println!();
+                println!("## System prompt");
+                println!();
+                println!("{}", preview.system_prompt);
+                println!();
+                println!("## User message");
+                println!();
+                println!("{}", preview.prompt.trim_end());
+                println!();
+                println!("## Tools (max tokens {})", preview.max_tokens);
+                println!();
+                println!("{}", if preview.tools.is_empty() { "(none)".to_string() } else { preview.tools.join(", ") });
+            }
+            if exec.status == LoopExecutionStatus::Running {
+                eprintln!(
+                    "Note: a running loop may also append steering, operator messages and shared facts it holds in memory"
+                );
+            }
+        }
+        ExecCommand::Iterations {
+            id,
+            since,
+            until,
+            outcome,
+            json,
+        } => {
+            debug!(%id, ?since, ?until, ?outcome, json, "cmd_exec: matched Iterations command");
+            let now = chrono::Utc::now();
+            let since = since.map(|s| parse_since(&s, now)).transpose()?;
+            let until = until.map(|s| parse_since(&s, now)).transpose()?;
+            let filter = IterationLogFilter::for_execution(&id)
+                .with_time_range(
+                    since.map(|t| t.timestamp_millis()),
+                    until.map(|t| t.timestamp_millis()),
+                )
+                .with_outcome(outcome);
+            let logs = state.query_iteration_logs(filter).await?;
+
+            // Wall-clock iteration durations come from the event log; the IterationLog only times validation
+            let mut started = HashMap::new();
+            let mut durations = HashMap::new();
+            for entry in read_execution_events(default_runs_dir()?, &id).unwrap_or_default() {
+                match entry.event {
+                    Event::IterationStarted { iteration, .. } => {
+                        started.insert(iteration, entry.timestamp);
+                    }
+                    Event::IterationCompleted { iteration, .. } => {
+                        if let Some(start) = started.get(&iteration) {
+                            let ms = (entry.timestamp - *start).num_milliseconds().max(0) as u64;
+                            durations.insert(iteration, ms);
+                        }
+                    }
+                    _ => {}
+                }
+            }
 
-    // Initialize and spawn MainWatcher for git main branch monitoring
-    let watcher_config = WatcherConfig::default();
-    let main_watcher = MainWatcher::new(watcher_config, repo_root.clone(), coordinator_tx.clone());
+            fn tools_used(log: &IterationLog) -> BTreeMap<&str, usize> {
+                let mut counts = BTreeMap::new();
+                for call in &log.tool_calls {
+                    *counts.entry(call.tool_name.as_str()).or_default() += 1;
+                }
+                counts
+            }
 
-    let watcher_handle = tokio::spawn(async move {
-        if let Err(e) = main_watcher.run().await {
-            tracing::error!(error = %e, "MainWatcher error");
+            if json {
+                let rows: Vec<serde_json::Value> = logs
+                    .iter()
+                    .map(|log| {
+                        serde_json::json!({
+                            "execution_id": log.execution_id,
+                            "iteration": log.iteration,
+                            "created_at": log.created_at,
+                            "duration_ms": durations.get(&log.iteration),
+                            "validation_command": log.validation_command,
+                            "validation_duration_ms": log.duration_ms,
+                            "exit_code": log.exit_code,
+                            "outcome": ValidationOutcome::of(log),
+                            "input_tokens": log.llm_input_tokens,
+                            "output_tokens": log.llm_output_tokens,
+                            "tool_calls": log.tool_calls.len(),
+                            "tool_errors": log.tool_calls.iter().filter(|c| c.is_error).count(),
+                            "tools": tools_used(log),
+                            "files_changed": log.files_changed,
+                        })
+                    })
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&rows)?);
+            } else if logs.is_empty() {
+                debug!(%id, "cmd_exec: no iterations found");
+                println!("No iterations found for execution '{}'", id);
+            } else {
+                debug!(count = logs.len(), "cmd_exec: found iterations");
+                let format_ms = taskdaemon::summary::format_duration_ms;
+                let tokens = |t: Option<u64>| t.map(|t| t.to_string()).unwrap_or_else(|| "-".to_string());
+                println!(
+                    "{:>5}  {:<19} {:>8} {:>8} {:>10} {:>10}  {:<14} TOOLS",
+                    "ITER", "STARTED", "DURATION", "VALIDATE", "IN", "OUT", "RESULT"
+                );
+                println!("{}", "-".repeat(110));
+                for log in &logs {
+                    let started = chrono::DateTime::from_timestamp_millis(log.created_at)
+                        .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+                        .unwrap_or_default();
+                    let result = match ValidationOutcome::of(log) {
+                        ValidationOutcome::Failed => format!("failed (exit {})", log.exit_code),
+                        other => other.to_string(),
+                    };
+                    let tools: Vec<String> = tools_used(log)
+                        .into_iter()
+                        .map(|(name, count)| format!("{}x{}", name, count))
+                        .collect();
+                    println!(
+                        "{:>5}  {:<19} {:>8} {:>8} {:>10} {:>10}  {:<14} {}",
+                        log.iteration,
+                        started,
+                        durations.get(&log.iteration).copied().map(format_ms).unwrap_or_else(|| "-".to_string()),
+                        format_ms(log.duration_ms),
+                        tokens(log.llm_input_tokens),
+                        tokens(log.llm_output_tokens),
+                        result,
+                        tools.join(", ")
+                    );
+                }
+            }
         }
-    });
-    info!("MainWatcher started");
-
-    // Initialize scheduler for API rate limiting
-    let scheduler_config = SchedulerConfig::default();
-    let scheduler = Scheduler::new(scheduler_config);
-    info!("Scheduler initialized");
-
-    // Create LLM client (reads API key from env var or file specified in config)
-    let llm_client: Arc<dyn LlmClient> = create_client(&config.llm).context("Failed to create LLM client")?;
-    info!("LLM client initialized ({})", config.llm.default);
-
-    // Initialize TaskManager for task orchestration
-    // poll_interval_secs is 60s (fallback) since event-driven pickup handles immediate work
-    let manager_config = TaskManagerConfig {
-        max_concurrent_tasks: config.concurrency.max_loops as usize,
-        poll_interval_secs: 60,
-        shutdown_timeout_secs: 60,
-        repo_root: repo_root.clone(),
-        worktree_dir: config.git.worktree_dir.clone(),
-    };
+    }
 
-    let mut task_manager = TaskManager::new(
-        manager_config,
-        coordinator_tx, // TaskManager gets the sender, not the Coordinator
-        scheduler,
-        llm_client,
-        state_manager.clone(),
-        loop_configs,
-        type_loader,
-    );
-    info!("TaskManager initialized");
+    Ok(())
+}
 
-    // Create IPC listener for cross-process wake-up
-    let (ipc_listener, socket_path) = ipc::create_listener()?;
-    info!(?socket_path, "IPC socket listening");
+/// Assemble an execution report from the TaskStore record, event log, plan file and git
+async fn build_execution_report(config: &Config, exec: taskdaemon::domain::LoopExecution) -> Result<ExecutionReport> {
+    debug!(exec_id = %exec.id, "build_execution_report: called");
+    let runs_dir = dirs::home_dir()
+        .ok_or_else(|| eyre::eyre!("Could not determine home directory"))?
+        .join(".taskdaemon")
+        .join("runs");
+    let entries = read_execution_events(&runs_dir, &exec.id)?;
+
+    // Plan: the parent document this loop worked from, or the loop's own artifact
+    let plan_path = exec
+        .context
+        .get("parent-file")
+        .and_then(|v| v.as_str())
+        .map(String::from)
+        .or_else(|| exec.artifact_path.clone());
+    let worktree = exec.worktree.clone().map(PathBuf::from);
+    let exec_id = exec.id.clone();
+
+    let model = exec.model.clone().unwrap_or_else(|| config.llm.default.clone());
+    let mut report = ExecutionReport::from_events(exec, &entries, model);
+    if let Some(path) = plan_path
+        && let Ok(content) = fs::read_to_string(&path)
+    {
+        debug!(%path, "build_execution_report: attached plan");
+        report = report.with_plan(path, content);
+    }
+    if let Some(diff) = execution_diff(&exec_id, worktree.as_deref(), &["--stat"]).await {
+        report = report.with_diff_summary(diff);
+    }
+    Ok(report)
+}
 
-    // Create shutdown channel for LoopManager
-    let (shutdown_tx, shutdown_rx) = tokio::sync::mpsc::channel::<()>(1);
+/// Run the daemon main loop
+async fn run_daemon(config: &Config) -> Result<()> {
+    debug!("run_daemon: called");
+    info!("Daemon starting...");
 
-    // Spawn TaskManager with IPC listener
-    let manager_handle = tokio::spawn(async move {
-        if let Err(e) = task_manager.run(shutdown_rx, Some(ipc_listener)).await {
-            tracing::error!(error = %e, "TaskManager error");
-        }
-    });
-    info!("TaskManager started");
+    // Validates the setup (API key, git repo, worktree dir) and opens the store
+    let mut daemon = DaemonBuilder::new(config.clone()).build().await?;
+    daemon.start().await?;
 
     info!("Daemon running. Press Ctrl+C to stop, SIGHUP to reload config.");
 
@@ -967,13 +3077,11 @@ async fn run_daemon(config: &Config) -> Result<()> {
                 _ = sigint.recv() => {
                     debug!("run_daemon: SIGINT received, initiating shutdown");
                     warn!("SIGINT received");
-                    let _ = shutdown_tx.send(()).await;
                     break;
                 }
                 _ = sigterm.recv() => {
                     debug!("run_daemon: SIGTERM received, initiating shutdown");
                     warn!("SIGTERM received");
-                    let _ = shutdown_tx.send(()).await;
                     break;
                 }
             }
@@ -986,28 +3094,9 @@ async fn run_daemon(config: &Config) -> Result<()> {
         // On non-Unix, just wait for Ctrl+C
         tokio::signal::ctrl_c().await?;
         debug!("run_daemon: ctrl_c received, initiating shutdown");
-        let _ = shutdown_tx.send(()).await;
     }
 
-    info!("Daemon shutting down...");
-    debug!("run_daemon: waiting for TaskManager to finish");
-
-    // Wait for TaskManager to finish (it handles coordinator shutdown)
-    let _ = manager_handle.await;
-    debug!("run_daemon: TaskManager finished");
-
-    // Cleanup - remove IPC socket
-    debug!("run_daemon: cleaning up IPC socket");
-    ipc::cleanup_socket(&socket_path);
-
-    // Cleanup - abort watcher task
-    debug!("run_daemon: aborting watcher task");
-    watcher_handle.abort();
-
-    // Coordinator was shut down by LoopManager, but abort handle for safety
-    debug!("run_daemon: aborting coordinator handle");
-    coord_handle.abort();
-
+    daemon.shutdown().await?;
     debug!("run_daemon: shutdown complete");
     Ok(())
 }
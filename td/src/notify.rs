@@ -0,0 +1,290 @@
+//! Completion notifications
+//!
+//! The Notifier watches execution statuses and reports transitions the user
+//! cares about: an execution completing, failing, or stopping to wait for
+//! approval. The daemon turns these into desktop notifications; the TUI
+//! rings the terminal bell.
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, info, warn};
+
+use crate::config::NotificationsConfig;
+use crate::domain::{LoopExecution, LoopExecutionStatus, Submitter};
+use crate::state::{StateEvent, StateManager};
+
+/// Kind of status transition worth notifying about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifyEvent {
+    /// Execution finished successfully
+    Complete,
+    /// Execution failed
+    Failed,
+    /// Execution is a draft awaiting approval or blocked for review
+    WaitingApproval,
+}
+
+impl NotifyEvent {
+    /// Map an execution status to the event it represents (if any)
+    pub fn from_status(status: LoopExecutionStatus) -> Option<Self> {
+        match status {
+            LoopExecutionStatus::Complete => Some(Self::Complete),
+            LoopExecutionStatus::Failed => Some(Self::Failed),
+            LoopExecutionStatus::Draft | LoopExecutionStatus::Blocked => Some(Self::WaitingApproval),
+            _ => None,
+        }
+    }
+
+    /// Whether notifications for this event are enabled
+    pub fn enabled(self, config: &NotificationsConfig) -> bool {
+        match self {
+            Self::Complete => config.on_complete,
+            Self::Failed => config.on_failed,
+            Self::WaitingApproval => config.on_waiting_approval,
+        }
+    }
+
+    fn summary(self) -> &'static str {
+        match self {
+            Self::Complete => "Execution complete",
+            Self::Failed => "Execution failed",
+            Self::WaitingApproval => "Execution waiting for approval",
+        }
+    }
+}
+
+/// A notification for one execution
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    pub event: NotifyEvent,
+    pub execution_id: String,
+    /// Execution title (falls back to the ID)
+    pub label: String,
+    /// Who submitted the execution
+    pub submitted_by: Option<Submitter>,
+}
+
+impl Notification {
+    /// Notification headline
+    pub fn summary(&self) -> String {
+        format!("TaskDaemon: {}", self.event.summary())
+    }
+
+    /// Notification body
+    pub fn body(&self) -> String {
+        let body = if self.label == self.execution_id {
+            self.execution_id.clone()
+        } else {
+            format!("{} ({})", self.label, self.execution_id)
+        };
+        match &self.submitted_by {
+            Some(submitter) => format!("{}, submitted by {}", body, submitter),
+            None => body,
+        }
+    }
+}
+
+/// Detects notification-worthy status transitions
+pub struct Notifier {
+    config: NotificationsConfig,
+    /// Last seen status per execution (None until the first snapshot)
+    last_status: Option<HashMap<String, LoopExecutionStatus>>,
+}
+
+impl Notifier {
+    /// Create a notifier with the given configuration
+    pub fn new(config: NotificationsConfig) -> Self {
+        debug!(?config, "Notifier::new: called");
+        Self {
+            config,
+            last_status: None,
+        }
+    }
+
+    /// Record a snapshot of executions and return notifications for transitions
+    ///
+    /// The first snapshot only establishes a baseline, so executions that were
+    /// already complete when the notifier started are not reported.
+    pub fn observe(&mut self, executions: &[LoopExecution]) -> Vec<Notification> {
+        debug!(count = executions.len(), "Notifier::observe: called");
+        let current: HashMap<String, LoopExecutionStatus> =
+            executions.iter().map(|e| (e.id.clone(), e.status)).collect();
+
+        let Some(previous) = self.last_status.replace(current) else {
+            debug!("Notifier::observe: baseline snapshot");
+            return Vec::new();
+        };
+
+        executions
+            .iter()
+            .filter(|e| previous.get(&e.id) != Some(&e.status))
+            .filter_map(|e| {
+                let event = NotifyEvent::from_status(e.status)?;
+                if !event.enabled(&self.config) {
+                    debug!(id = %e.id, ?event, "Notifier::observe: event disabled");
+                    return None;
+                }
+                Some(Notification {
+                    event,
+                    execution_id: e.id.clone(),
+                    label: e.title.clone().unwrap_or_else(|| e.id.clone()),
+                    submitted_by: e.submitted_by.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Watch the state manager and send desktop notifications until the channel closes
+    pub async fn watch(mut self, state_manager: StateManager) {
+        debug!("Notifier::watch: called");
+        let mut events = state_manager.subscribe_events();
+
+        // Baseline so existing executions don't notify at startup
+        match state_manager.list_executions(None, None).await {
+            Ok(executions) => {
+                self.observe(&executions);
+            }
+            Err(e) => warn!(error = %e, "Notifier: failed to list executions"),
+        }
+
+        loop {
+            match events.recv().await {
+                Ok(StateEvent::ExecutionCreated { .. } | StateEvent::ExecutionUpdated { .. }) => {
+                    let executions = match state_manager.list_executions(None, None).await {
+                        Ok(executions) => executions,
+                        Err(e) => {
+                            warn!(error = %e, "Notifier: failed to list executions");
+                            continue;
+                        }
+                    };
+                    for notification in self.observe(&executions) {
+                        send_desktop(&notification).await;
+                    }
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!(skipped, "Notifier::watch: lagged, will catch up on next update");
+                }
+                Err(RecvError::Closed) => {
+                    debug!("Notifier::watch: state events closed");
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Send a desktop notification via the platform's notification command
+pub async fn send_desktop(notification: &Notification) {
+    debug!(?notification, "send_desktop: called");
+    let summary = notification.summary();
+    let body = notification.body();
+
+    let mut command = if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification {} with title {}",
+            applescript_string(&body),
+            applescript_string(&summary)
+        );
+        let mut command = tokio::process::Command::new("osascript");
+        command.args(["-e", &script]);
+        command
+    } else {
+        let mut command = tokio::process::Command::new("notify-send");
+        command.args(["--app-name=taskdaemon", &summary, &body]);
+        command
+    };
+
+    match command.output().await {
+        Ok(output) if output.status.success() => info!(%summary, %body, "Desktop notification sent"),
+        Ok(output) => warn!(
+            stderr = %String::from_utf8_lossy(&output.stderr),
+            "Desktop notification command failed"
+        ),
+        Err(e) => warn!(error = %e, "Failed to run desktop notification command"),
+    }
+}
+
+/// Ring the terminal bell
+pub fn ring_bell() {
+    debug!("ring_bell: called");
+    let mut stdout = std::io::stdout();
+    let _ = stdout.write_all(b"\x07");
+    let _ = stdout.flush();
+}
+
+/// Quote a string for AppleScript
+fn applescript_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exec(id: &str, status: LoopExecutionStatus) -> LoopExecution {
+        let mut exec = LoopExecution::new("phase", id);
+        exec.id = id.to_string();
+        exec.status = status;
+        exec
+    }
+
+    #[test]
+    fn test_observe_reports_transitions_after_baseline() {
+        let mut notifier = Notifier::new(NotificationsConfig::default());
+
+        let baseline = vec![
+            exec("a", LoopExecutionStatus::Running),
+            exec("b", LoopExecutionStatus::Complete),
+        ];
+        assert!(notifier.observe(&baseline).is_empty());
+
+        let next = vec![
+            exec("a", LoopExecutionStatus::Failed),
+            exec("b", LoopExecutionStatus::Complete),
+            exec("c", LoopExecutionStatus::Draft),
+        ];
+        let notes = notifier.observe(&next);
+        let events: Vec<_> = notes.iter().map(|n| (n.execution_id.as_str(), n.event)).collect();
+        assert_eq!(
+            events,
+            vec![("a", NotifyEvent::Failed), ("c", NotifyEvent::WaitingApproval)]
+        );
+
+        // No change, no repeat
+        assert!(notifier.observe(&next).is_empty());
+    }
+
+    #[test]
+    fn test_body_names_submitter() {
+        let mut notifier = Notifier::new(NotificationsConfig::default());
+        notifier.observe(&[exec("a", LoopExecutionStatus::Running)]);
+
+        let mut done = exec("a", LoopExecutionStatus::Complete).with_submitter(Some(Submitter {
+            user: "alice".to_string(),
+            name: None,
+        }));
+        done.set_title("Fix login");
+        let notes = notifier.observe(&[done]);
+        assert_eq!(notes[0].body(), "Fix login (a), submitted by alice");
+    }
+
+    #[test]
+    fn test_observe_respects_event_flags() {
+        let config = NotificationsConfig {
+            on_complete: false,
+            ..Default::default()
+        };
+        let mut notifier = Notifier::new(config);
+        notifier.observe(&[exec("a", LoopExecutionStatus::Running)]);
+
+        assert!(notifier.observe(&[exec("a", LoopExecutionStatus::Complete)]).is_empty());
+    }
+
+    #[test]
+    fn test_applescript_string_escapes_quotes() {
+        assert_eq!(applescript_string(r#"say "hi""#), r#""say \"hi\"""#);
+    }
+}
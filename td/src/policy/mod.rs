@@ -0,0 +1,501 @@
+//! Programmable policies
+//!
+//! A policy is consulted at three decision points: can this execution start,
+//! can this tool call run, can this branch merge. It sees the execution's
+//! metadata (plus the tool call, or the diff) as a [`PolicyInput`] and returns
+//! a [`Verdict`]: allow, deny with a reason, or modify the request (the tool
+//! call's input, or the execution's context at start).
+//!
+//! Policies come from WebAssembly modules found under `policies.paths` (with
+//! the `wasm` feature, see [`wasm`]) and from programs embedding the daemon
+//! ([`crate::DaemonBuilder::with_policy`]). They run in order: the first deny
+//! wins, and each policy sees the request as modified by the ones before it.
+
+#[cfg(feature = "wasm")]
+mod wasm;
+
+#[cfg(feature = "wasm")]
+pub use wasm::{WasmPolicy, load_wasm_policies};
+
+use std::sync::Arc;
+
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, info, warn};
+
+use crate::domain::LoopExecution;
+use crate::worktree::DiffSummary;
+
+/// Where a policy is consulted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Decision {
+    /// The manager is about to start (spawn) an execution
+    Start,
+    /// The LLM asked for a tool call
+    ToolCall,
+    /// A completed code loop is about to merge to main
+    Merge,
+}
+
+impl std::fmt::Display for Decision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Start => write!(f, "start"),
+            Self::ToolCall => write!(f, "tool-call"),
+            Self::Merge => write!(f, "merge"),
+        }
+    }
+}
+
+/// What to do when a policy fails to produce a verdict
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyErrorMode {
+    /// Treat it as a deny (fail closed)
+    #[default]
+    Deny,
+    /// Log a warning and ask the next policy
+    Allow,
+}
+
+/// The execution a decision is about
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ExecutionInfo {
+    pub id: String,
+    #[serde(rename = "loop-type")]
+    pub loop_type: String,
+    /// Iterations run so far
+    pub iteration: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// OS user that submitted it
+    #[serde(rename = "submitted-by", skip_serializing_if = "Option::is_none")]
+    pub submitted_by: Option<String>,
+    /// Template context (task, parent file, ...)
+    pub context: Value,
+}
+
+impl From<&LoopExecution> for ExecutionInfo {
+    fn from(exec: &LoopExecution) -> Self {
+        Self {
+            id: exec.id.clone(),
+            loop_type: exec.loop_type.clone(),
+            iteration: exec.iteration,
+            title: exec.title.clone(),
+            submitted_by: exec.submitted_by.as_ref().map(|s| s.user.clone()),
+            context: exec.context.clone(),
+        }
+    }
+}
+
+/// The tool call a decision is about
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ToolCallInfo {
+    pub name: String,
+    pub input: Value,
+}
+
+/// One changed file in a merge
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChangedFile {
+    pub path: String,
+    pub insertions: u64,
+    pub deletions: u64,
+}
+
+/// The branch a merge decision is about
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DiffInfo {
+    pub files: Vec<ChangedFile>,
+    pub insertions: u64,
+    pub deletions: u64,
+    /// Commit subjects, oldest first
+    pub commits: Vec<String>,
+}
+
+impl From<&DiffSummary> for DiffInfo {
+    fn from(diff: &DiffSummary) -> Self {
+        Self {
+            files: diff
+                .changes
+                .iter()
+                .map(|c| ChangedFile {
+                    path: c.path.clone(),
+                    insertions: c.insertions,
+                    deletions: c.deletions,
+                })
+                .collect(),
+            insertions: diff.insertions,
+            deletions: diff.deletions,
+            commits: diff.commits.clone(),
+        }
+    }
+}
+
+/// What a policy is asked about (serialized as JSON for WebAssembly modules)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PolicyInput {
+    pub decision: Decision,
+    pub execution: ExecutionInfo,
+    /// The requested call (tool-call)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool: Option<ToolCallInfo>,
+    /// The branch against main (merge)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<DiffInfo>,
+}
+
+impl PolicyInput {
+    /// May `exec` start?
+    pub fn start(exec: &LoopExecution) -> Self {
+        Self {
+            decision: Decision::Start,
+            execution: exec.into(),
+            tool: None,
+            diff: None,
+        }
+    }
+
+    /// May the execution call `name` with `input`?
+    pub fn tool_call(execution: ExecutionInfo, name: &str, input: &Value) -> Self {
+        Self {
+            decision: Decision::ToolCall,
+            execution,
+            tool: Some(ToolCallInfo {
+                name: name.to_string(),
+                input: input.clone(),
+            }),
+            diff: None,
+        }
+    }
+
+    /// May the execution's branch, with `diff`, merge?
+    pub fn merge(execution: ExecutionInfo, diff: &DiffSummary) -> Self {
+        Self {
+            decision: Decision::Merge,
+            execution,
+            tool: None,
+            diff: Some(diff.into()),
+        }
+    }
+
+    /// Apply a `modify` verdict's input
+    ///
+    /// A tool call's input is replaced; at start the object's keys are set in
+    /// the execution's context. Merges can't be modified. Returns whether
+    /// anything changed.
+    fn apply(&mut self, input: Value) -> bool {
+        match self.decision {
+            Decision::ToolCall => match &mut self.tool {
+                Some(tool) if tool.input != input => {
+                    tool.input = input;
+                    true
+                }
+                _ => false,
+            },
+            Decision::Start => match (input, self.execution.context.as_object_mut()) {
+                (Value::Object(patch), Some(context)) => {
+                    let before = context.clone();
+                    context.extend(patch);
+                    *context != before
+                }
+                (Value::Object(patch), None) => {
+                    self.execution.context = Value::Object(patch);
+                    true
+                }
+                _ => false,
+            },
+            Decision::Merge => false,
+        }
+    }
+}
+
+/// A policy's answer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "verdict", rename_all = "lowercase")]
+pub enum Verdict {
+    /// Go ahead
+    Allow,
+    /// Refuse, with a reason for the user (and, for tool calls, the LLM)
+    Deny {
+        #[serde(default)]
+        reason: String,
+    },
+    /// Go ahead with a changed request (see [`PolicyInput`])
+    Modify {
+        input: Value,
+        #[serde(default)]
+        reason: String,
+    },
+}
+
+/// A policy consulted at decision points
+///
+/// `evaluate` runs on a blocking thread; an error is handled per
+/// `policies.on-error`.
+pub trait Policy: Send + Sync {
+    /// Name used in logs and deny reasons
+    fn name(&self) -> &str;
+
+    /// Verdict for `input`
+    fn evaluate(&self, input: &PolicyInput) -> Result<Verdict>;
+}
+
+/// What the policies decided together
+#[derive(Debug, Clone, PartialEq)]
+pub enum PolicyOutcome {
+    /// Every policy allowed the request as it was
+    Allow,
+    /// A policy refused it
+    Deny { policy: String, reason: String },
+    /// Allowed once changed: the input as the last policy left it
+    Modify(Box<PolicyInput>),
+}
+
+impl PolicyOutcome {
+    /// Deny message, e.g. "Denied by policy no-friday-merges: it's Friday"
+    pub fn denial(&self) -> Option<String> {
+        match self {
+            Self::Deny { policy, reason } if reason.is_empty() => Some(format!("Denied by policy {}", policy)),
+            Self::Deny { policy, reason } => Some(format!("Denied by policy {}: {}", policy, reason)),
+            _ => None,
+        }
+    }
+}
+
+/// The policies in effect, evaluated in order
+#[derive(Clone, Default)]
+pub struct PolicySet {
+    policies: Vec<Arc<dyn Policy>>,
+    on_error: PolicyErrorMode,
+}
+
+impl std::fmt::Debug for PolicySet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PolicySet")
+            .field("policies", &self.policies.iter().map(|p| p.name()).collect::<Vec<_>>())
+            .field("on_error", &self.on_error)
+            .finish()
+    }
+}
+
+impl PolicySet {
+    /// An empty set that handles evaluation errors per `on_error`
+    pub fn new(on_error: PolicyErrorMode) -> Self {
+        Self {
+            policies: Vec::new(),
+            on_error,
+        }
+    }
+
+    /// Add a policy, evaluated after those already in the set
+    pub fn with_policy(mut self, policy: Arc<dyn Policy>) -> Self {
+        debug!(policy = policy.name(), "PolicySet::with_policy: called");
+        self.policies.push(policy);
+        self
+    }
+
+    /// Whether there is nothing to evaluate
+    pub fn is_empty(&self) -> bool {
+        self.policies.is_empty()
+    }
+
+    /// Names of the policies, in evaluation order
+    pub fn names(&self) -> Vec<&str> {
+        self.policies.iter().map(|p| p.name()).collect()
+    }
+
+    /// Ask every policy about `input`
+    pub async fn evaluate(&self, mut input: PolicyInput) -> PolicyOutcome {
+        if self.policies.is_empty() {
+            return PolicyOutcome::Allow;
+        }
+        debug!(decision = %input.decision, exec_id = %input.execution.id, "PolicySet::evaluate: called");
+        let mut modified = false;
+        for policy in &self.policies {
+            let name = policy.name().to_string();
+            let verdict = {
+                let policy = policy.clone();
+                let input = input.clone();
+                match tokio::task::spawn_blocking(move || policy.evaluate(&input)).await {
+                    Ok(result) => result,
+                    Err(e) => Err(eyre::eyre!("policy task failed: {}", e)),
+                }
+            };
+            match verdict {
+                Ok(Verdict::Allow) => {}
+                Ok(Verdict::Deny { reason }) => {
+                    info!(policy = %name, decision = %input.decision, exec_id = %input.execution.id, %reason, "Policy denied");
+                    return PolicyOutcome::Deny { policy: name, reason };
+                }
+                Ok(Verdict::Modify { input: changed, reason }) => {
+                    if input.apply(changed) {
+                        info!(policy = %name, decision = %input.decision, exec_id = %input.execution.id, %reason, "Policy modified request");
+                        modified = true;
+                    }
+                }
+                Err(e) if self.on_error == PolicyErrorMode::Allow => {
+                    warn!(policy = %name, decision = %input.decision, error = %e, "Policy failed, ignoring it");
+                }
+                Err(e) => {
+                    warn!(policy = %name, decision = %input.decision, error = %e, "Policy failed, denying");
+                    return PolicyOutcome::Deny {
+                        policy: name,
+                        reason: format!("policy error: {}", e),
+                    };
+                }
+            }
+        }
+        if modified {
+            PolicyOutcome::Modify(Box::new(input))
+        } else {
+            PolicyOutcome::Allow
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Answers with a fixed verdict, or fails
+    struct Fixed(&'static str, Option<Verdict>);
+
+    impl Policy for Fixed {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn evaluate(&self, _input: &PolicyInput) -> Result<Verdict> {
+            self.1.clone().ok_or_else(|| eyre::eyre!("trapped"))
+        }
+    }
+
+    /// Denies `bash` calls that mention `rm -rf`, and adds a timeout to the rest
+    struct NoRmRf;
+
+    impl Policy for NoRmRf {
+        fn name(&self) -> &str {
+            "no-rm-rf"
+        }
+
+        fn evaluate(&self, input: &PolicyInput) -> Result<Verdict> {
+            let Some(tool) = input.tool.as_ref().filter(|t| t.name == "bash") else {
+                return Ok(Verdict::Allow);
+            };
+            let command = tool.input["command"].as_str().unwrap_or_default();
+            if command.contains("rm -rf") {
+                return Ok(Verdict::Deny {
+                    reason: "recursive deletes are not allowed".to_string(),
+                });
+            }
+            Ok(Verdict::Modify {
+                input: json!({"command": format!("timeout 60 {}", command)}),
+                reason: String::new(),
+            })
+        }
+    }
+
+    fn execution() -> ExecutionInfo {
+        ExecutionInfo {
+            id: "exec-1".to_string(),
+            loop_type: "phase".to_string(),
+            context: json!({"task": "Fix the parser"}),
+            ..Default::default()
+        }
+    }
+
+    fn set(policies: Vec<Arc<dyn Policy>>, on_error: PolicyErrorMode) -> PolicySet {
+        policies
+            .into_iter()
+            .fold(PolicySet::new(on_error), |set, policy| set.with_policy(policy))
+    }
+
+    #[test]
+    fn test_verdict_json() {
+        let verdict: Verdict = serde_json::from_str(r#"{"verdict": "deny", "reason": "frozen"}"#).unwrap();
+        assert_eq!(
+            verdict,
+            Verdict::Deny {
+                reason: "frozen".to_string()
+            }
+        );
+        let verdict: Verdict = serde_json::from_str(r#"{"verdict": "allow"}"#).unwrap();
+        assert_eq!(verdict, Verdict::Allow);
+
+        let input = PolicyInput::tool_call(execution(), "read", &json!({"path": "src/lib.rs"}));
+        let json = serde_json::to_value(&input).unwrap();
+        assert_eq!(json["decision"], "tool-call");
+        assert_eq!(json["execution"]["loop-type"], "phase");
+        assert_eq!(json["tool"]["input"]["path"], "src/lib.rs");
+        assert!(json.get("diff").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_policy_set_evaluate() {
+        let policies = set(
+            vec![Arc::new(NoRmRf), Arc::new(Fixed("audit", Some(Verdict::Allow)))],
+            PolicyErrorMode::Deny,
+        );
+        assert_eq!(policies.names(), ["no-rm-rf", "audit"]);
+
+        let denied = policies
+            .evaluate(PolicyInput::tool_call(execution(), "bash", &json!({"command": "rm -rf /"})))
+            .await;
+        assert_eq!(
+            denied.denial().unwrap(),
+            "Denied by policy no-rm-rf: recursive deletes are not allowed"
+        );
+
+        let modified = policies
+            .evaluate(PolicyInput::tool_call(execution(), "bash", &json!({"command": "cargo test"})))
+            .await;
+        let PolicyOutcome::Modify(input) = modified else {
+            panic!("expected modify, got {:?}", modified);
+        };
+        assert_eq!(input.tool.unwrap().input["command"], "timeout 60 cargo test");
+
+        let allowed = policies
+            .evaluate(PolicyInput::tool_call(execution(), "read", &json!({"path": "a"})))
+            .await;
+        assert_eq!(allowed, PolicyOutcome::Allow);
+    }
+
+    #[tokio::test]
+    async fn test_policy_errors_and_start_context() {
+        let broken: Arc<dyn Policy> = Arc::new(Fixed("broken", None));
+        let label: Arc<dyn Policy> = Arc::new(Fixed(
+            "label",
+            Some(Verdict::Modify {
+                input: json!({"label": "reviewed"}),
+                reason: "tag".to_string(),
+            }),
+        ));
+        let start = PolicyInput {
+            decision: Decision::Start,
+            execution: execution(),
+            tool: None,
+            diff: None,
+        };
+
+        let closed = set(vec![broken.clone(), label.clone()], PolicyErrorMode::Deny);
+        let outcome = closed.evaluate(start.clone()).await;
+        assert_eq!(outcome.denial().unwrap(), "Denied by policy broken: policy error: trapped");
+
+        // Failing open skips the broken policy; a start modify patches the context
+        let open = set(vec![broken, label], PolicyErrorMode::Allow);
+        let PolicyOutcome::Modify(input) = open.evaluate(start).await else {
+            panic!("expected modify");
+        };
+        assert_eq!(
+            input.execution.context,
+            json!({"task": "Fix the parser", "label": "reviewed"})
+        );
+
+        let merge = PolicyInput::merge(execution(), &DiffSummary::default());
+        assert_eq!(PolicySet::default().evaluate(merge).await, PolicyOutcome::Allow);
+    }
+}
@@ -0,0 +1,136 @@
+//! WebAssembly policy modules
+//!
+//! Sandboxed like tool plugins ([`crate::tools::WasmTool`]): the module
+//! imports nothing and each evaluation runs in a fresh instance with a fuel
+//! budget and a memory cap. The policy is named after its file. It exports:
+//!
+//! - `memory`: its linear memory
+//! - `td_alloc(len: i32) -> i32`: reserve `len` bytes for the input
+//! - `td_evaluate(ptr: i32, len: i32) -> i64`: judge the [`PolicyInput`] JSON
+//!   at `ptr`, returning a [`Verdict`] as JSON at `ptr << 32 | len`
+
+use std::path::Path;
+
+use eyre::{Result, eyre};
+use tracing::{debug, info};
+
+use super::{Policy, PolicyInput, Verdict};
+use crate::config::PoliciesConfig;
+use crate::tools::wasm::{Plugin, out_of_fuel};
+
+/// A policy backed by a WebAssembly module
+pub struct WasmPolicy {
+    name: String,
+    plugin: Plugin,
+}
+
+impl WasmPolicy {
+    /// Compile the module at `path`
+    pub fn load(path: &Path, config: &PoliciesConfig) -> Result<Self> {
+        debug!(?path, "WasmPolicy::load: called");
+        let plugin = Plugin::compile(path, config.fuel, config.max_memory_mb)?;
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        Ok(Self { name, plugin })
+    }
+}
+
+impl Policy for WasmPolicy {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn evaluate(&self, input: &PolicyInput) -> Result<Verdict> {
+        debug!(policy = %self.name, decision = %input.decision, "WasmPolicy::evaluate: called");
+        let output = self
+            .plugin
+            .call("td_evaluate", &serde_json::to_vec(input)?)
+            .map_err(|e| {
+                if out_of_fuel(&e) {
+                    eyre!("ran out of fuel ({} instructions)", self.plugin.fuel)
+                } else {
+                    eyre!("td_evaluate failed: {}", e)
+                }
+            })?;
+        serde_json::from_slice(&output)
+            .map_err(|e| eyre!("not a verdict ({}): {}", e, String::from_utf8_lossy(&output)))
+    }
+}
+
+/// Load every module `config` finds, failing on the first one that is broken
+pub fn load_wasm_policies(config: &PoliciesConfig) -> Result<Vec<WasmPolicy>> {
+    debug!("load_wasm_policies: called");
+    let mut policies = Vec::new();
+    for path in config.module_files() {
+        let policy = WasmPolicy::load(&path, config)?;
+        info!(policy = %policy.name, path = %path.display(), "Loaded WASM policy");
+        policies.push(policy);
+    }
+    Ok(policies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::{Decision, ExecutionInfo};
+    use tempfile::TempDir;
+
+    /// A module whose `td_evaluate` returns `verdict`, or runs `body` instead
+    fn module(dir: &TempDir, file: &str, verdict: &str, body: Option<&str>) -> std::path::PathBuf {
+        let data: String = verdict.bytes().map(|b| format!("\\{:02x}", b)).collect();
+        let body = body.map(String::from).unwrap_or_else(|| format!("(i64.const {})", verdict.len()));
+        let wat = format!(
+            r#"(module
+  (memory (export "memory") 1)
+  (global $heap (mut i32) (i32.const 1024))
+  (data (i32.const 0) "{data}")
+  (func (export "td_alloc") (param i32) (result i32)
+    (global.get $heap)
+    (global.set $heap (i32.add (global.get $heap) (local.get 0))))
+  (func (export "td_evaluate") (param i32 i32) (result i64) {body}))"#
+        );
+        let path = dir.path().join(file);
+        std::fs::write(&path, wat::parse_str(&wat).unwrap()).unwrap();
+        path
+    }
+
+    fn input() -> PolicyInput {
+        PolicyInput {
+            decision: Decision::Merge,
+            execution: ExecutionInfo::default(),
+            tool: None,
+            diff: None,
+        }
+    }
+
+    #[test]
+    fn test_wasm_policy_verdicts() {
+        let dir = TempDir::new().unwrap();
+        module(&dir, "freeze.wasm", r#"{"verdict":"deny","reason":"release freeze"}"#, None);
+        let config = PoliciesConfig {
+            paths: vec![dir.path().display().to_string()],
+            fuel: 100_000,
+            ..Default::default()
+        };
+
+        let policies = load_wasm_policies(&config).unwrap();
+        assert_eq!(policies.len(), 1);
+        assert_eq!(policies[0].name(), "freeze");
+        assert_eq!(
+            policies[0].evaluate(&input()).unwrap(),
+            Verdict::Deny {
+                reason: "release freeze".to_string()
+            }
+        );
+
+        let garbage = WasmPolicy::load(&module(&dir, "garbage.wasm", "nope", None), &config).unwrap();
+        let err = garbage.evaluate(&input()).unwrap_err().to_string();
+        assert!(err.starts_with("not a verdict"), "{}", err);
+
+        let spin = WasmPolicy::load(&module(&dir, "spin.wasm", "", Some("(loop $l (br $l)) (unreachable)")), &config);
+        let err = spin.unwrap().evaluate(&input()).unwrap_err().to_string();
+        assert!(err.contains("ran out of fuel"), "{}", err);
+    }
+}
@@ -0,0 +1,310 @@
+//! Automatic prompt context from the execution's ContextStore
+//!
+//! When the worktree has a ContextStore corpus (`.contextstore/` by default,
+//! built with `cs ingest`), each iteration searches it for the failing test
+//! names and the current plan section, and the best-matching chunk excerpts
+//! are added to the prompt under "Relevant Context" — within a token budget,
+//! so the model doesn't have to query the store itself every time.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use contextstore::{ContextId, ContextStore, SearchOptions};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+/// Store directory looked up in the worktree when `store` is unset
+pub const DEFAULT_CONTEXT_STORE_DIR: &str = ".contextstore";
+
+/// Matches collected per term and context before ranking
+const MATCHES_PER_TERM: usize = 20;
+
+/// Rough characters per token for the budget
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Words too common to be useful search terms
+const STOP_WORDS: &[&str] = &[
+    "about", "after", "also", "before", "from", "have", "into", "should", "that", "their", "then", "there", "these",
+    "this", "when", "where", "which", "with", "would",
+];
+
+/// Settings for automatic context selection (`context-selection` in loop YAML)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct ContextSelection {
+    /// Search the corpus and inject excerpts when one exists
+    pub enabled: bool,
+
+    /// Store directory, relative to the worktree (default `.contextstore`)
+    pub store: Option<String>,
+
+    /// Most excerpts per iteration
+    pub top_k: usize,
+
+    /// Token budget for all excerpts together
+    pub max_tokens: usize,
+
+    /// Bytes of text kept on each side of the best match in a chunk
+    pub window: usize,
+}
+
+impl Default for ContextSelection {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            store: None,
+            top_k: 5,
+            max_tokens: 1500,
+            window: 600,
+        }
+    }
+}
+
+/// One excerpt selected for the prompt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextSnippet {
+    /// `context_id/chunk_num`
+    pub chunk_id: String,
+    /// File the chunk was ingested from
+    pub source: String,
+    /// Terms found in the chunk
+    pub terms: Vec<String>,
+    pub text: String,
+}
+
+/// Searches a worktree's ContextStore for prompt context
+pub struct ContextSelector {
+    store: ContextStore,
+    /// Context IDs with their chunk sources by chunk number
+    contexts: Vec<(ContextId, HashMap<String, String>)>,
+    config: ContextSelection,
+}
+
+impl ContextSelector {
+    /// Open the worktree's corpus; None when disabled or there is no corpus
+    pub fn open(worktree: &Path, config: &ContextSelection) -> Option<Self> {
+        if !config.enabled || config.top_k == 0 || config.max_tokens == 0 {
+            return None;
+        }
+        let path = worktree.join(config.store.as_deref().unwrap_or(DEFAULT_CONTEXT_STORE_DIR));
+        if !path.is_dir() {
+            return None;
+        }
+        debug!(?path, "ContextSelector::open: called");
+        let store = match ContextStore::open(&path) {
+            Ok(store) => store,
+            Err(e) => {
+                warn!(?path, error = %e, "Failed to open context store");
+                return None;
+            }
+        };
+        let contexts: Vec<_> = store
+            .list_contexts()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|id| {
+                let sources = store
+                    .chunks(&id)
+                    .ok()?
+                    .into_iter()
+                    .map(|meta| (meta.chunk_id, meta.source))
+                    .collect();
+                Some((id, sources))
+            })
+            .collect();
+        if contexts.is_empty() {
+            debug!(?path, "ContextSelector::open: no contexts");
+            return None;
+        }
+        Some(Self {
+            store,
+            contexts,
+            config: config.clone(),
+        })
+    }
+
+    /// Best chunks for `terms`, ranked by distinct terms then total matches
+    pub fn select(&self, terms: &[String]) -> Vec<ContextSnippet> {
+        debug!(?terms, "ContextSelector::select: called");
+        // chunk id -> (matched terms, match count, first match offset)
+        let mut hits: HashMap<String, (Vec<String>, usize, usize)> = HashMap::new();
+        let options = SearchOptions {
+            max_results: MATCHES_PER_TERM,
+            case_insensitive: true,
+        };
+        for term in terms {
+            let pattern = regex::escape(term);
+            for (context_id, _) in &self.contexts {
+                let Ok(matches) = self.store.search(context_id, &pattern, options.clone()) else {
+                    continue;
+                };
+                for m in matches {
+                    let entry = hits
+                        .entry(format!("{}/{}", context_id, m.chunk_id))
+                        .or_insert_with(|| (Vec::new(), 0, m.offset));
+                    if !entry.0.contains(term) {
+                        entry.0.push(term.clone());
+                    }
+                    entry.1 += 1;
+                }
+            }
+        }
+
+        let mut ranked: Vec<_> = hits.into_iter().collect();
+        ranked.sort_by(|(a_id, a), (b_id, b)| {
+            b.0.len()
+                .cmp(&a.0.len())
+                .then(b.1.cmp(&a.1))
+                .then_with(|| a_id.cmp(b_id))
+        });
+
+        let mut budget = self.config.max_tokens * CHARS_PER_TOKEN;
+        let mut snippets = Vec::new();
+        for (chunk_id, (terms, _, offset)) in ranked.into_iter().take(self.config.top_k) {
+            let Ok(window) = self.store.get_window(&chunk_id, offset, self.config.window) else {
+                continue;
+            };
+            let mut text = window.trim().to_string();
+            if text.len() > budget {
+                let mut end = budget;
+                while !text.is_char_boundary(end) {
+                    end -= 1;
+                }
+                text.truncate(end);
+            }
+            if text.is_empty() {
+                break;
+            }
+            budget -= text.len();
+            snippets.push(ContextSnippet {
+                source: self.source_of(&chunk_id).unwrap_or_default(),
+                chunk_id,
+                terms,
+                text,
+            });
+        }
+        debug!(selected = snippets.len(), "ContextSelector::select: done");
+        snippets
+    }
+
+    fn source_of(&self, chunk_id: &str) -> Option<String> {
+        let (context_id, chunk_num) = chunk_id.split_once('/')?;
+        self.contexts
+            .iter()
+            .find(|(id, _)| id == context_id)
+            .and_then(|(_, sources)| sources.get(chunk_num).cloned())
+    }
+}
+
+/// Search terms for a failing test: the full name and its last path segment
+pub fn test_terms(name: &str) -> Vec<String> {
+    let mut terms = vec![name.to_string()];
+    let last = name.rsplit(['.', ':', '/']).next().unwrap_or(name);
+    let last = last.strip_prefix("test_").unwrap_or(last);
+    if last.len() >= 4 && last != name {
+        terms.push(last.to_string());
+    }
+    terms
+}
+
+/// Search terms from a plan section title or description: its distinctive words
+pub fn section_terms(text: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for word in text.split(|c: char| !c.is_alphanumeric() && c != '_' && c != '-') {
+        let word = word.trim_matches('-').to_lowercase();
+        if word.len() >= 4 && !STOP_WORDS.contains(&word.as_str()) && !terms.contains(&word) {
+            terms.push(word);
+        }
+    }
+    terms
+}
+
+/// Format selected excerpts as a prompt section body
+pub fn render_snippets(snippets: &[ContextSnippet]) -> String {
+    let mut out = String::new();
+    for snippet in snippets {
+        let source = if snippet.source.is_empty() {
+            snippet.chunk_id.as_str()
+        } else {
+            snippet.source.as_str()
+        };
+        out.push_str(&format!(
+            "### {} (chunk {}; matched: {})\n```\n{}\n```\n",
+            source,
+            snippet.chunk_id,
+            snippet.terms.join(", "),
+            snippet.text
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use contextstore::IngestOptions;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_terms_from_tests_and_sections() {
+        assert_eq!(
+            test_terms("auth::tests::test_token_refresh"),
+            ["auth::tests::test_token_refresh", "token_refresh"]
+        );
+        assert_eq!(
+            test_terms("tests/test_api.py::test_login"),
+            ["tests/test_api.py::test_login", "login"]
+        );
+        assert_eq!(test_terms("works"), ["works"]);
+        assert_eq!(
+            section_terms("Phase 2: Add the token-refresh flow to the auth middleware"),
+            ["phase", "token-refresh", "flow", "auth", "middleware"]
+        );
+    }
+
+    #[test]
+    fn test_selects_ranked_snippets_within_budget() {
+        let temp = TempDir::new().unwrap();
+        let docs = temp.path().join("docs");
+        std::fs::create_dir_all(&docs).unwrap();
+        std::fs::write(
+            docs.join("auth.md"),
+            "The middleware calls token_refresh when the session expires.",
+        )
+        .unwrap();
+        std::fs::write(docs.join("other.md"), "Unrelated: middleware ordering.").unwrap();
+        let store = ContextStore::open(temp.path().join(DEFAULT_CONTEXT_STORE_DIR)).unwrap();
+        store
+            .ingest(&[format!("{}/*.md", docs.display())], IngestOptions::default())
+            .unwrap();
+
+        assert!(
+            ContextSelector::open(
+                temp.path(),
+                &ContextSelection {
+                    enabled: false,
+                    ..Default::default()
+                }
+            )
+            .is_none()
+        );
+        assert!(ContextSelector::open(&docs, &ContextSelection::default()).is_none());
+
+        let selector = ContextSelector::open(temp.path(), &ContextSelection::default()).unwrap();
+        let snippets = selector.select(&["token_refresh".to_string(), "middleware".to_string()]);
+        assert_eq!(snippets.len(), 2);
+        assert!(snippets[0].source.ends_with("auth.md"));
+        assert_eq!(snippets[0].terms, ["token_refresh", "middleware"]);
+        assert!(render_snippets(&snippets).contains("matched: token_refresh, middleware"));
+
+        let tight = ContextSelection {
+            max_tokens: 5,
+            ..Default::default()
+        };
+        let snippets = ContextSelector::open(temp.path(), &tight)
+            .unwrap()
+            .select(&["middleware".to_string()]);
+        assert_eq!(snippets.len(), 1);
+        assert_eq!(snippets[0].text.len(), 20);
+    }
+}
@@ -0,0 +1,292 @@
+//! Exploration cache for warm-starting iterations
+//!
+//! Each iteration starts with a fresh context window, so a loop tends to read
+//! the same files again every time. With `exploration-cache` enabled, every
+//! whole file the `read` tool returns is remembered as a short digest (line
+//! count, leading comment and top-level declarations) keyed by path and
+//! content hash. The next prompt lists the digests of files that have not
+//! changed since, and reading one of them again answers "unchanged since
+//! iteration N" with the digest unless the model asks for the full contents.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+use tracing::debug;
+
+/// Longest outline line kept in a digest
+const MAX_OUTLINE_LINE_CHARS: usize = 120;
+
+/// Line starts (after up to one level of indentation) that declare something
+const DECLARATION_PREFIXES: &[&str] = &[
+    "pub ",
+    "pub(crate) ",
+    "fn ",
+    "async fn ",
+    "struct ",
+    "enum ",
+    "trait ",
+    "impl ",
+    "impl<",
+    "mod ",
+    "type ",
+    "const ",
+    "static ",
+    "class ",
+    "def ",
+    "async def ",
+    "function ",
+    "async function ",
+    "export ",
+    "interface ",
+    "func ",
+];
+
+/// Line starts of a leading comment used as the file's summary
+const COMMENT_PREFIXES: &[&str] = &["//!", "///", "//", "#", "/*", "\"\"\"", "--"];
+
+/// Settings for the exploration cache (`exploration-cache` in loop YAML)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct ExplorationCacheConfig {
+    /// Remember files read in earlier iterations and skip re-reading them
+    pub enabled: bool,
+
+    /// Most file digests listed in a prompt (most recently read first)
+    pub max_files: usize,
+
+    /// Most declarations kept in each digest's outline
+    pub max_outline: usize,
+}
+
+impl Default for ExplorationCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_files: 30,
+            max_outline: 15,
+        }
+    }
+}
+
+/// What a loop learned about a file from reading it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct FileDigest {
+    /// Path as the tools show it (worktree-relative or `@mount/...`)
+    pub path: String,
+    /// Absolute path the file was read from
+    pub full_path: PathBuf,
+    /// SHA-256 of the contents
+    pub hash: String,
+    /// Number of lines
+    pub lines: usize,
+    /// Leading comment, if the file starts with one
+    pub summary: Option<String>,
+    /// Top-level declarations as `line: text`
+    pub outline: Vec<String>,
+    /// Iteration that read it
+    pub iteration: u32,
+}
+
+impl FileDigest {
+    /// Digest `content`, read from `full_path` in `iteration`
+    pub fn of(path: &str, full_path: &Path, content: &str, iteration: u32, max_outline: usize) -> Self {
+        debug!(%path, %iteration, "FileDigest::of: called");
+        let summary = content.lines().map(str::trim).find(|line| !line.is_empty()).and_then(|line| {
+            let prefix = COMMENT_PREFIXES.iter().find(|p| line.starts_with(*p))?;
+            let text = line[prefix.len()..].trim();
+            (!text.is_empty()).then(|| text.to_string())
+        });
+        let outline = content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| {
+                let indent = line.len() - line.trim_start().len();
+                indent <= 4 && DECLARATION_PREFIXES.iter().any(|p| line.trim_start().starts_with(p))
+            })
+            .take(max_outline)
+            .map(|(i, line)| {
+                let text: String = line.trim().chars().take(MAX_OUTLINE_LINE_CHARS).collect();
+                format!("{}: {}", i + 1, text)
+            })
+            .collect();
+        Self {
+            path: path.to_string(),
+            full_path: full_path.to_path_buf(),
+            hash: content_hash(content),
+            lines: content.lines().count(),
+            summary,
+            outline,
+            iteration,
+        }
+    }
+
+    /// The digest as prompt text
+    pub fn render(&self) -> String {
+        let mut text = format!(
+            "### {} ({} lines, read in iteration {})\n",
+            self.path, self.lines, self.iteration
+        );
+        if let Some(summary) = &self.summary {
+            text.push_str(summary);
+            text.push('\n');
+        }
+        for line in &self.outline {
+            text.push_str("  ");
+            text.push_str(line);
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// SHA-256 of `content` as hex
+pub fn content_hash(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+/// Digests of the files an execution has read, by absolute path
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ExplorationCache {
+    files: BTreeMap<PathBuf, FileDigest>,
+    /// Iteration now running
+    iteration: u32,
+    /// Most declarations kept per digest
+    max_outline: usize,
+}
+
+/// Shared handle to an execution's exploration cache
+pub type ExplorationCacheRef = Arc<Mutex<ExplorationCache>>;
+
+impl ExplorationCache {
+    pub fn new(config: &ExplorationCacheConfig) -> Self {
+        debug!(?config, "ExplorationCache::new: called");
+        Self {
+            files: BTreeMap::new(),
+            iteration: 0,
+            max_outline: config.max_outline,
+        }
+    }
+
+    /// Note that `iteration` has started; reads from here on are new
+    pub fn begin_iteration(&mut self, iteration: u32) {
+        debug!(%iteration, "ExplorationCache::begin_iteration: called");
+        self.iteration = iteration;
+    }
+
+    /// The digest of `full_path` if it was read in an earlier iteration with these contents
+    pub fn unchanged(&self, full_path: &Path, hash: &str) -> Option<&FileDigest> {
+        debug!(?full_path, "ExplorationCache::unchanged: called");
+        self.files
+            .get(full_path)
+            .filter(|digest| digest.hash == hash && digest.iteration < self.iteration)
+    }
+
+    /// Remember a whole-file read made in the current iteration
+    pub fn record(&mut self, path: &str, full_path: &Path, content: &str) {
+        debug!(%path, iteration = self.iteration, "ExplorationCache::record: called");
+        let digest = FileDigest::of(path, full_path, content, self.iteration, self.max_outline);
+        self.files.insert(full_path.to_path_buf(), digest);
+    }
+
+    /// Drop digests of files that changed or disappeared since they were read
+    pub async fn refresh(&mut self) {
+        debug!(files = self.files.len(), "ExplorationCache::refresh: called");
+        let mut stale = Vec::new();
+        for (full_path, digest) in &self.files {
+            match tokio::fs::read_to_string(full_path).await {
+                Ok(content) if content_hash(&content) == digest.hash => {}
+                _ => stale.push(full_path.clone()),
+            }
+        }
+        for full_path in stale {
+            debug!(?full_path, "ExplorationCache::refresh: dropping stale digest");
+            self.files.remove(&full_path);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Prompt section listing up to `max_files` digests, most recently read first
+    ///
+    /// Empty when nothing has been read yet.
+    pub fn render(&self, max_files: usize) -> String {
+        debug!(files = self.files.len(), %max_files, "ExplorationCache::render: called");
+        if self.files.is_empty() {
+            return String::new();
+        }
+        let mut digests: Vec<&FileDigest> = self.files.values().collect();
+        digests.sort_by(|a, b| b.iteration.cmp(&a.iteration).then_with(|| a.path.cmp(&b.path)));
+        let mut text = String::from(
+            "## Files Already Examined\n\
+             Read in earlier iterations and unchanged since. `read` answers \"unchanged\" for these \
+             unless called with \"full\": true.\n\n",
+        );
+        for digest in digests.iter().take(max_files) {
+            text.push_str(&digest.render());
+            text.push('\n');
+        }
+        if digests.len() > max_files {
+            text.push_str(&format!("...and {} more\n", digests.len() - max_files));
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const SOURCE: &str = "//! Widget parsing\n\nuse std::fmt;\n\npub struct Widget {\n    name: String,\n}\n\n\
+                          impl Widget {\n    pub fn parse(s: &str) -> Self {\n        todo!()\n    }\n}\n";
+
+    #[test]
+    fn test_file_digest() {
+        let digest = FileDigest::of("src/widget.rs", Path::new("/w/src/widget.rs"), SOURCE, 2, 15);
+        assert_eq!(digest.lines, 13);
+        assert_eq!(digest.summary.as_deref(), Some("Widget parsing"));
+        assert_eq!(
+            digest.outline,
+            vec!["5: pub struct Widget {", "9: impl Widget {", "10: pub fn parse(s: &str) -> Self {"]
+        );
+        assert!(digest.render().starts_with("### src/widget.rs (13 lines, read in iteration 2)\nWidget parsing\n"));
+    }
+
+    #[tokio::test]
+    async fn test_cache_remembers_unchanged_files() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("widget.rs");
+        std::fs::write(&path, SOURCE).unwrap();
+
+        let mut cache = ExplorationCache::new(&ExplorationCacheConfig::default());
+        cache.begin_iteration(1);
+        cache.record("widget.rs", &path, SOURCE);
+        let hash = content_hash(SOURCE);
+
+        // Not "unchanged" within the iteration that read it, or with other contents
+        assert!(cache.unchanged(&path, &hash).is_none());
+        cache.begin_iteration(2);
+        assert_eq!(cache.unchanged(&path, &hash).map(|d| d.iteration), Some(1));
+        assert!(cache.unchanged(&path, &content_hash("changed")).is_none());
+        assert!(cache.render(10).contains("### widget.rs (13 lines, read in iteration 1)"));
+
+        cache.refresh().await;
+        assert_eq!(cache.len(), 1);
+        std::fs::write(&path, "changed").unwrap();
+        cache.refresh().await;
+        assert!(cache.is_empty());
+        assert_eq!(cache.render(10), "");
+    }
+}
@@ -3,10 +3,21 @@
 //! Since each iteration starts with a fresh LLM context window (the core Ralph
 //! Wiggum pattern), we must explicitly tell the LLM what happened in previous
 //! iterations. The `ProgressStrategy` trait abstracts this, with
-//! `SystemCapturedProgress` as the default implementation.
+//! `SystemCapturedProgress` as the default implementation. When the worktree
+//! has a ContextStore corpus, `ContextSelector` picks excerpts relevant to the
+//! strategy's `context_terms` for the next prompt. With an `ExplorationCache`,
+//! digests of files read in earlier iterations are carried into the progress
+//! so the loop need not read them again.
 
+mod context_selection;
+mod exploration;
 mod strategy;
 mod system_captured;
 
+pub use context_selection::{
+    ContextSelection, ContextSelector, ContextSnippet, DEFAULT_CONTEXT_STORE_DIR, render_snippets, section_terms,
+    test_terms,
+};
+pub use exploration::{ExplorationCache, ExplorationCacheConfig, ExplorationCacheRef, FileDigest, content_hash};
 pub use strategy::{IterationContext, ProgressStrategy};
 pub use system_captured::SystemCapturedProgress;
@@ -88,6 +88,19 @@ pub trait ProgressStrategy: Send + Sync {
     /// Number of iterations currently recorded
     fn len(&self) -> usize;
 
+    /// Terms to search the execution's ContextStore with before the next iteration
+    ///
+    /// Typically the names of tests that failed in the last iteration.
+    fn context_terms(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Note what the loop has already examined (rendered file digests)
+    ///
+    /// Strategies that support warm starts include it in `get_progress`; the
+    /// default ignores it.
+    fn set_explored(&mut self, _explored: String) {}
+
     /// Whether any progress has been recorded
     fn is_empty(&self) -> bool {
         debug!("ProgressStrategy::is_empty: called");
@@ -7,7 +7,8 @@
 use std::collections::VecDeque;
 use tracing::debug;
 
-use super::{IterationContext, ProgressStrategy};
+use super::{IterationContext, ProgressStrategy, test_terms};
+use crate::r#loop::TestReport;
 
 /// Default progress strategy: capture validation output verbatim
 ///
@@ -40,6 +41,10 @@ pub struct SystemCapturedProgress {
     entries: VecDeque<String>,
     max_entries: usize,
     max_output_chars: usize,
+    /// Tests that failed in the last recorded iteration
+    failed_tests: Vec<String>,
+    /// Digests of files read in earlier iterations, appended to the progress
+    explored: String,
 }
 
 impl SystemCapturedProgress {
@@ -60,6 +65,8 @@ impl SystemCapturedProgress {
             entries: VecDeque::with_capacity(max_entries),
             max_entries,
             max_output_chars,
+            failed_tests: Vec::new(),
+            explored: String::new(),
         }
     }
 }
@@ -71,6 +78,8 @@ impl Default for SystemCapturedProgress {
             entries: VecDeque::with_capacity(5),
             max_entries: 5,
             max_output_chars: 500,
+            failed_tests: Vec::new(),
+            explored: String::new(),
         }
     }
 }
@@ -84,6 +93,11 @@ impl ProgressStrategy for SystemCapturedProgress {
             "SystemCapturedProgress::record: called"
         );
 
+        self.failed_tests = match TestReport::parse(&ctx.stdout, &ctx.stderr) {
+            Some(report) if !ctx.passed() => report.failed.into_iter().map(|test| test.name).collect(),
+            _ => Vec::new(),
+        };
+
         // Combine stdout and stderr, prefer stdout if available
         let output = if !ctx.stdout.is_empty() {
             debug!("SystemCapturedProgress::record: using stdout (not empty)");
@@ -153,7 +167,12 @@ impl ProgressStrategy for SystemCapturedProgress {
             entries_count = %self.entries.len(),
             "SystemCapturedProgress::get_progress: called"
         );
-        self.entries.iter().cloned().collect::<Vec<_>>().join("")
+        let mut progress = self.entries.iter().cloned().collect::<Vec<_>>().join("");
+        if !progress.is_empty() && !self.explored.is_empty() {
+            debug!("SystemCapturedProgress::get_progress: appending explored files");
+            progress.push_str(&self.explored);
+        }
+        progress
     }
 
     fn clear(&mut self) {
@@ -162,12 +181,23 @@ impl ProgressStrategy for SystemCapturedProgress {
             "SystemCapturedProgress::clear: called"
         );
         self.entries.clear();
+        self.failed_tests.clear();
+        self.explored.clear();
     }
 
     fn len(&self) -> usize {
         debug!("SystemCapturedProgress::len: called");
         self.entries.len()
     }
+
+    fn context_terms(&self) -> Vec<String> {
+        self.failed_tests.iter().flat_map(|name| test_terms(name)).collect()
+    }
+
+    fn set_explored(&mut self, explored: String) {
+        debug!(explored_len = explored.len(), "SystemCapturedProgress::set_explored: called");
+        self.explored = explored;
+    }
 }
 
 #[cfg(test)]
@@ -229,6 +259,18 @@ mod tests {
         assert!(text.matches('x').count() <= 100); // Should be significantly less
     }
 
+    #[test]
+    fn test_system_captured_context_terms() {
+        let mut progress = SystemCapturedProgress::default();
+        let output = "running 2 tests\ntest auth::test_refresh ... FAILED\ntest auth::test_login ... ok\n\n\
+                      test result: FAILED. 1 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out\n";
+        progress.record(&make_ctx(1, 101, output));
+        assert_eq!(progress.context_terms(), ["auth::test_refresh", "refresh"]);
+
+        progress.record(&make_ctx(2, 0, "ok"));
+        assert!(progress.context_terms().is_empty());
+    }
+
     #[test]
     fn test_system_captured_clear() {
         let mut progress = SystemCapturedProgress::default();
@@ -242,6 +284,20 @@ mod tests {
         assert!(progress.get_progress().is_empty());
     }
 
+    #[test]
+    fn test_explored_files_follow_progress() {
+        let mut progress = SystemCapturedProgress::new();
+        progress.set_explored("## Files Already Examined\n".to_string());
+        assert_eq!(progress.get_progress(), "");
+
+        progress.record(&make_ctx(1, 1, "FAILED"));
+        assert!(progress.get_progress().ends_with("## Files Already Examined\n"));
+
+        progress.clear();
+        progress.record(&make_ctx(2, 1, "FAILED"));
+        assert!(!progress.get_progress().contains("Files Already Examined"));
+    }
+
     #[test]
     fn test_empty_progress_returns_empty_string() {
         let progress = SystemCapturedProgress::default();
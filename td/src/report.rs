@@ -0,0 +1,759 @@
+//! Execution reports
+//!
+//! Builds a shareable summary of a single execution from its TaskStore record
+//! and its event log (`~/.taskdaemon/runs/{id}/events.jsonl`): the plan it
+//! worked from, an iteration timeline, tool call counts, validation history,
+//! the final diff summary, and token cost. Rendered as Markdown or standalone
+//! HTML so CI can attach it to a pull request.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use chrono::{DateTime, Utc};
+use tracing::debug;
+
+use crate::domain::{CriterionStatus, LoopExecution};
+use crate::events::{Event, EventLogEntry, IterationOutcome};
+use crate::llm::TokenUsage;
+
+/// Output format for `td exec report`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    #[default]
+    Markdown,
+    Html,
+}
+
+impl std::str::FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        debug!(%s, "ReportFormat::from_str: called");
+        match s.to_lowercase().as_str() {
+            "md" | "markdown" => Ok(Self::Markdown),
+            "html" => Ok(Self::Html),
+            _ => Err(format!("Unknown report format: {}. Use: md or html", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for ReportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Markdown => write!(f, "md"),
+            Self::Html => write!(f, "html"),
+        }
+    }
+}
+
+/// One row of the iteration timeline
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IterationSummary {
+    pub iteration: u32,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Outcome description (None if the iteration never completed)
+    pub outcome: Option<String>,
+    pub tool_calls: u32,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Model a failover chain served the iteration with (None = the execution's model)
+    pub served_by: Option<String>,
+}
+
+/// Aggregated calls for one tool
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ToolCallStats {
+    pub calls: u32,
+    pub failures: u32,
+    pub total_duration_ms: u64,
+}
+
+/// One validation run
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationRun {
+    pub iteration: u32,
+    pub command: Option<String>,
+    pub exit_code: i32,
+    pub duration_ms: u64,
+}
+
+/// Everything needed to render a report for one execution
+#[derive(Debug, Clone)]
+pub struct ExecutionReport {
+    pub execution: LoopExecution,
+    /// Model used for cost estimation
+    pub model: String,
+    /// Path and content of the plan/spec the execution worked from
+    pub plan: Option<(String, String)>,
+    pub iterations: Vec<IterationSummary>,
+    pub tool_calls: BTreeMap<String, ToolCallStats>,
+    pub validations: Vec<ValidationRun>,
+    /// `git diff --stat` of the execution's changes
+    pub diff_summary: Option<String>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+impl ExecutionReport {
+    /// Build a report from an execution record and its logged events
+    pub fn from_events(execution: LoopExecution, entries: &[EventLogEntry], model: impl Into<String>) -> Self {
+        debug!(exec_id = %execution.id, events = entries.len(), "ExecutionReport::from_events: called");
+        let mut iterations: BTreeMap<u32, IterationSummary> = BTreeMap::new();
+        let mut tool_calls: BTreeMap<String, ToolCallStats> = BTreeMap::new();
+        let mut validations = Vec::new();
+        let mut pending_commands: BTreeMap<u32, String> = BTreeMap::new();
+
+        for entry in entries {
+            match &entry.event {
+                Event::IterationStarted { iteration, .. } => {
+                    let row = iteration_row(&mut iterations, *iteration);
+                    row.started_at.get_or_insert(entry.timestamp);
+                }
+                Event::IterationCompleted { iteration, outcome, .. } => {
+                    let row = iteration_row(&mut iterations, *iteration);
+                    row.completed_at = Some(entry.timestamp);
+                    row.outcome = Some(describe_outcome(outcome));
+                }
+                Event::ResponseCompleted {
+                    iteration,
+                    input_tokens,
+                    output_tokens,
+                    served_by,
+                    ..
+                } => {
+                    let row = iteration_row(&mut iterations, *iteration);
+                    row.input_tokens += input_tokens;
+                    row.output_tokens += output_tokens;
+                    if served_by.is_some() {
+                        row.served_by = served_by.clone();
+                    }
+                }
+                Event::ToolCallCompleted {
+                    iteration,
+                    tool_name,
+                    success,
+                    duration_ms,
+                    ..
+                } => {
+                    iteration_row(&mut iterations, *iteration).tool_calls += 1;
+                    let stats = tool_calls.entry(tool_name.clone()).or_default();
+                    stats.calls += 1;
+                    stats.total_duration_ms += duration_ms;
+                    if !success {
+                        stats.failures += 1;
+                    }
+                }
+                Event::ValidationStarted { iteration, command, .. } => {
+                    pending_commands.insert(*iteration, command.clone());
+                }
+                Event::ValidationCompleted {
+                    iteration,
+                    exit_code,
+                    duration_ms,
+                    ..
+                } => {
+                    validations.push(ValidationRun {
+                        iteration: *iteration,
+                        command: pending_commands.remove(iteration),
+                        exit_code: *exit_code,
+                        duration_ms: *duration_ms,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        let iterations: Vec<_> = iterations.into_values().collect();
+        let logged_input: u64 = iterations.iter().map(|i| i.input_tokens).sum();
+        let logged_output: u64 = iterations.iter().map(|i| i.output_tokens).sum();
+        let input_tokens = logged_input.max(execution.total_input_tokens);
+        let output_tokens = logged_output.max(execution.total_output_tokens);
+        debug!(
+            iterations = iterations.len(),
+            tools = tool_calls.len(),
+            validations = validations.len(),
+            "ExecutionReport::from_events: aggregated"
+        );
+
+        Self {
+            execution,
+            model: model.into(),
+            plan: None,
+            iterations,
+            tool_calls,
+            validations,
+            diff_summary: None,
+            input_tokens,
+            output_tokens,
+        }
+    }
+
+    /// Attach the plan/spec document the execution worked from
+    pub fn with_plan(mut self, path: impl Into<String>, content: impl Into<String>) -> Self {
+        debug!("ExecutionReport::with_plan: called");
+        self.plan = Some((path.into(), content.into()));
+        self
+    }
+
+    /// Attach a `git diff --stat` summary of the execution's changes
+    pub fn with_diff_summary(mut self, diff_summary: impl Into<String>) -> Self {
+        debug!("ExecutionReport::with_diff_summary: called");
+        self.diff_summary = Some(diff_summary.into());
+        self
+    }
+
+    /// Estimated cost in USD for the tokens used
+    ///
+    /// Iterations a failover chain served with another model are priced at
+    /// that model; everything else at `model`.
+    pub fn cost_usd(&self) -> f64 {
+        let price = |input_tokens, output_tokens, model: &str| {
+            TokenUsage {
+                input_tokens,
+                output_tokens,
+                ..Default::default()
+            }
+            .cost_usd(model)
+        };
+        let (mut input, mut output, mut cost) = (self.input_tokens, self.output_tokens, 0.0);
+        for row in &self.iterations {
+            if let Some(model) = &row.served_by {
+                cost += price(row.input_tokens, row.output_tokens, model);
+                input = input.saturating_sub(row.input_tokens);
+                output = output.saturating_sub(row.output_tokens);
+            }
+        }
+        cost + price(input, output, &self.model)
+    }
+
+    /// Render in the requested format
+    pub fn render(&self, format: ReportFormat) -> String {
+        debug!(%format, "ExecutionReport::render: called");
+        match format {
+            ReportFormat::Markdown => self.to_markdown(),
+            ReportFormat::Html => self.to_html(),
+        }
+    }
+
+    /// Render as GitHub-flavored Markdown
+    pub fn to_markdown(&self) -> String {
+        debug!(exec_id = %self.execution.id, "ExecutionReport::to_markdown: called");
+        let exec = &self.execution;
+        let mut out = String::new();
+
+        let _ = writeln!(out, "# Execution Report: {}\n", self.title());
+        let _ = writeln!(out, "| | |\n|---|---|");
+        for (key, value) in self.overview() {
+            let _ = writeln!(out, "| {} | {} |", key, value.replace('|', "\\|"));
+        }
+
+        if let Some(evaluation) = &exec.evaluation {
+            let _ = writeln!(
+                out,
+                "\n## Self-Evaluation\n\nOverall **{:.1}** / 10 (threshold {:.1}, {})\n",
+                evaluation.overall, evaluation.threshold, evaluation.model
+            );
+            for score in &evaluation.scores {
+                let _ = writeln!(out, "- {} — {}/10 {}", score.criterion, score.score, score.rationale);
+            }
+        }
+
+        if !exec.acceptance.is_empty() {
+            let _ = writeln!(out, "\n## Acceptance Criteria\n");
+            for check in &exec.acceptance {
+                let mark = if check.status == CriterionStatus::Passed { "x" } else { " " };
+                let _ = write!(out, "- [{}] {} ({})", mark, check.criterion, check.status);
+                match &check.detail {
+                    Some(detail) => {
+                        let _ = writeln!(out, " — {}", detail.lines().last().unwrap_or(""));
+                    }
+                    None => {
+                        let _ = writeln!(out);
+                    }
+                }
+            }
+        }
+
+        if let Some((path, content)) = &self.plan {
+            let _ = writeln!(out, "\n## Plan\n\n<details>\n<summary>{}</summary>\n", path);
+            let _ = writeln!(out, "{}\n\n</details>", content.trim());
+        }
+
+        let _ = writeln!(out, "\n## Iteration Timeline\n");
+        if self.iterations.is_empty() {
+            let _ = writeln!(out, "_No events logged._");
+        } else {
+            let _ = writeln!(
+                out,
+                "| # | Started | Duration | Tool calls | Tokens (in/out) | Outcome |"
+            );
+            let _ = writeln!(out, "|---|---|---|---|---|---|");
+            for row in &self.iterations {
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} | {} | {}/{} | {} |",
+                    row.iteration,
+                    row.started_at
+                        .map(|t| t.format("%H:%M:%S").to_string())
+                        .unwrap_or_default(),
+                    iteration_duration(row),
+                    row.tool_calls,
+                    row.input_tokens,
+                    row.output_tokens,
+                    outcome_label(row).replace('|', "\\|")
+                );
+            }
+        }
+
+        let _ = writeln!(out, "\n## Tool Calls\n");
+        if self.tool_calls.is_empty() {
+            let _ = writeln!(out, "_No tool calls._");
+        } else {
+            let _ = writeln!(out, "| Tool | Calls | Failures | Total time |\n|---|---|---|---|");
+            for (name, stats) in &self.tool_calls {
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} | {} |",
+                    name,
+                    stats.calls,
+                    stats.failures,
+                    format_duration_ms(stats.total_duration_ms)
+                );
+            }
+        }
+
+        let _ = writeln!(out, "\n## Validation History\n");
+        if self.validations.is_empty() {
+            let _ = writeln!(out, "_No validation runs._");
+        } else {
+            let _ = writeln!(out, "| Iteration | Command | Exit code | Duration |\n|---|---|---|---|");
+            for run in &self.validations {
+                let _ = writeln!(
+                    out,
+                    "| {} | `{}` | {} {} | {} |",
+                    run.iteration,
+                    run.command.as_deref().unwrap_or("-"),
+                    run.exit_code,
+                    if run.exit_code == 0 { "✅" } else { "❌" },
+                    format_duration_ms(run.duration_ms)
+                );
+            }
+        }
+
+        let _ = writeln!(out, "\n## Diff Summary\n");
+        match &self.diff_summary {
+            Some(diff) if !diff.trim().is_empty() => {
+                let _ = writeln!(out, "```\n{}\n```", diff.trim_end());
+            }
+            _ => {
+                let _ = writeln!(out, "_No diff available._");
+            }
+        }
+
+        out
+    }
+
+    /// Render as a standalone HTML document
+    pub fn to_html(&self) -> String {
+        debug!(exec_id = %self.execution.id, "ExecutionReport::to_html: called");
+        let exec = &self.execution;
+        let mut out = String::new();
+        let title = escape_html(&self.title());
+
+        let _ = writeln!(
+            out,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Execution Report: {title}</title>\n\
+             <style>body{{font-family:sans-serif;max-width:960px;margin:2em auto}}\
+             table{{border-collapse:collapse}}td,th{{border:1px solid #ccc;padding:4px 8px;text-align:left}}\
+             pre{{background:#f6f8fa;padding:1em;overflow-x:auto}}.fail{{color:#b00}}.pass{{color:#070}}</style>\n\
+             </head>\n<body>\n<h1>Execution Report: {title}</h1>"
+        );
+
+        let _ = writeln!(out, "<table>");
+        for (key, value) in self.overview() {
+            let _ = writeln!(out, "<tr><th>{}</th><td>{}</td></tr>", key, escape_html(&value));
+        }
+        let _ = writeln!(out, "</table>");
+
+        if let Some(evaluation) = &exec.evaluation {
+            let _ = writeln!(
+                out,
+                "<h2>Self-Evaluation</h2>\n<p>Overall <strong>{:.1}</strong> / 10 (threshold {:.1}, {})</p>\n<ul>",
+                evaluation.overall,
+                evaluation.threshold,
+                escape_html(&evaluation.model)
+            );
+            for score in &evaluation.scores {
+                let _ = writeln!(
+                    out,
+                    "<li>{} — {}/10 {}</li>",
+                    escape_html(&score.criterion),
+                    score.score,
+                    escape_html(&score.rationale)
+                );
+            }
+            let _ = writeln!(out, "</ul>");
+        }
+
+        if !exec.acceptance.is_empty() {
+            let _ = writeln!(out, "<h2>Acceptance Criteria</h2>\n<ul>");
+            for check in &exec.acceptance {
+                let _ = writeln!(
+                    out,
+                    "<li>{} <strong>{}</strong> {}</li>",
+                    escape_html(&check.criterion.to_string()),
+                    check.status,
+                    escape_html(check.detail.as_deref().unwrap_or(""))
+                );
+            }
+            let _ = writeln!(out, "</ul>");
+        }
+
+        if let Some((path, content)) = &self.plan {
+            let _ = writeln!(
+                out,
+                "<h2>Plan</h2>\n<details><summary>{}</summary>\n<pre>{}</pre>\n</details>",
+                escape_html(path),
+                escape_html(content.trim())
+            );
+        }
+
+        let _ = writeln!(out, "<h2>Iteration Timeline</h2>");
+        if self.iterations.is_empty() {
+            let _ = writeln!(out, "<p><em>No events logged.</em></p>");
+        } else {
+            let _ = writeln!(
+                out,
+                "<table>\n<tr><th>#</th><th>Started</th><th>Duration</th><th>Tool calls</th>\
+                 <th>Tokens (in/out)</th><th>Outcome</th></tr>"
+            );
+            for row in &self.iterations {
+                let _ = writeln!(
+                    out,
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}/{}</td><td>{}</td></tr>",
+                    row.iteration,
+                    row.started_at
+                        .map(|t| t.format("%H:%M:%S").to_string())
+                        .unwrap_or_default(),
+                    iteration_duration(row),
+                    row.tool_calls,
+                    row.input_tokens,
+                    row.output_tokens,
+                    escape_html(&outcome_label(row))
+                );
+            }
+            let _ = writeln!(out, "</table>");
+        }
+
+        let _ = writeln!(out, "<h2>Tool Calls</h2>");
+        if self.tool_calls.is_empty() {
+            let _ = writeln!(out, "<p><em>No tool calls.</em></p>");
+        } else {
+            let _ = writeln!(
+                out,
+                "<table>\n<tr><th>Tool</th><th>Calls</th><th>Failures</th><th>Total time</th></tr>"
+            );
+            for (name, stats) in &self.tool_calls {
+                let _ = writeln!(
+                    out,
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    escape_html(name),
+                    stats.calls,
+                    stats.failures,
+                    format_duration_ms(stats.total_duration_ms)
+                );
+            }
+            let _ = writeln!(out, "</table>");
+        }
+
+        let _ = writeln!(out, "<h2>Validation History</h2>");
+        if self.validations.is_empty() {
+            let _ = writeln!(out, "<p><em>No validation runs.</em></p>");
+        } else {
+            let _ = writeln!(
+                out,
+                "<table>\n<tr><th>Iteration</th><th>Command</th><th>Exit code</th><th>Duration</th></tr>"
+            );
+            for run in &self.validations {
+                let _ = writeln!(
+                    out,
+                    "<tr><td>{}</td><td><code>{}</code></td><td class=\"{}\">{}</td><td>{}</td></tr>",
+                    run.iteration,
+                    escape_html(run.command.as_deref().unwrap_or("-")),
+                    if run.exit_code == 0 { "pass" } else { "fail" },
+                    run.exit_code,
+                    format_duration_ms(run.duration_ms)
+                );
+            }
+            let _ = writeln!(out, "</table>");
+        }
+
+        let _ = writeln!(out, "<h2>Diff Summary</h2>");
+        match &self.diff_summary {
+            Some(diff) if !diff.trim().is_empty() => {
+                let _ = writeln!(out, "<pre>{}</pre>", escape_html(diff.trim_end()));
+            }
+            _ => {
+                let _ = writeln!(out, "<p><em>No diff available.</em></p>");
+            }
+        }
+
+        let _ = writeln!(out, "</body>\n</html>");
+        out
+    }
+
+    fn title(&self) -> String {
+        self.execution
+            .title
+            .clone()
+            .filter(|t| !t.is_empty())
+            .unwrap_or_else(|| self.execution.id.clone())
+    }
+
+    /// Key/value rows for the overview table
+    fn overview(&self) -> Vec<(&'static str, String)> {
+        let exec = &self.execution;
+        let created = DateTime::<Utc>::from_timestamp_millis(exec.created_at)
+            .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+            .unwrap_or_default();
+        let mut rows = vec![
+            ("ID", exec.id.clone()),
+            ("Type", exec.loop_type.clone()),
+            ("Status", exec.status.to_string()),
+            ("Created", created),
+            ("Iterations", exec.iteration.to_string()),
+            (
+                "Tokens",
+                format!("{} in / {} out", self.input_tokens, self.output_tokens),
+            ),
+            ("Estimated cost", format!("${:.4} ({})", self.cost_usd(), self.model)),
+        ];
+        if let Some(parent) = &exec.parent {
+            rows.push(("Parent", parent.clone()));
+        }
+        if let Some(error) = &exec.last_error {
+            rows.push(("Last error", error.clone()));
+        }
+        rows
+    }
+}
+
+fn iteration_row(rows: &mut BTreeMap<u32, IterationSummary>, iteration: u32) -> &mut IterationSummary {
+    rows.entry(iteration).or_insert_with(|| IterationSummary {
+        iteration,
+        ..Default::default()
+    })
+}
+
+/// An iteration's outcome, noting the model that served it if it wasn't the execution's
+fn outcome_label(row: &IterationSummary) -> String {
+    let outcome = row.outcome.as_deref().unwrap_or("incomplete");
+    match &row.served_by {
+        Some(model) => format!("{} (served by {})", outcome, model),
+        None => outcome.to_string(),
+    }
+}
+
+fn describe_outcome(outcome: &IterationOutcome) -> String {
+    match outcome {
+        IterationOutcome::ValidationPassed => "validation passed".to_string(),
+        IterationOutcome::ValidationFailed { exit_code } => format!("validation failed (exit {})", exit_code),
+        IterationOutcome::MaxTurnsReached => "max turns reached".to_string(),
+        IterationOutcome::ToolError { tool, error } => format!("tool error in {}: {}", tool, error),
+        IterationOutcome::LlmError { error, code } => match code {
+            Some(code) => format!("LLM error [{}]: {}", code, error),
+            None => format!("LLM error: {}", error),
+        },
+        IterationOutcome::TimedOut { cause } => cause.clone(),
+    }
+}
+
+fn iteration_duration(row: &IterationSummary) -> String {
+    match (row.started_at, row.completed_at) {
+        (Some(start), Some(end)) => format_duration_ms((end - start).num_milliseconds().max(0) as u64),
+        _ => "-".to_string(),
+    }
+}
+
+pub(crate) fn format_duration_ms(ms: u64) -> String {
+    if ms >= 60_000 {
+        format!("{}m{:02}s", ms / 60_000, (ms % 60_000) / 1000)
+    } else {
+        format!("{:.1}s", ms as f64 / 1000.0)
+    }
+}
+
+pub(crate) fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::AcceptanceCriterion;
+    use chrono::Duration;
+
+    fn entry(event: Event, offset_secs: i64) -> EventLogEntry {
+        EventLogEntry {
+            timestamp: DateTime::<Utc>::from_timestamp(1_700_000_000 + offset_secs, 0).unwrap(),
+            event,
+        }
+    }
+
+    fn sample_events() -> Vec<EventLogEntry> {
+        let id = "exec-1".to_string();
+        vec![
+            entry(
+                Event::IterationStarted {
+                    execution_id: id.clone(),
+                    iteration: 1,
+                },
+                0,
+            ),
+            entry(
+                Event::ResponseCompleted {
+                    execution_id: id.clone(),
+                    iteration: 1,
+                    response_summary: String::new(),
+                    input_tokens: 1000,
+                    output_tokens: 200,
+                    has_tool_calls: true,
+                    served_by: None,
+                },
+                5,
+            ),
+            entry(
+                Event::ToolCallCompleted {
+                    execution_id: id.clone(),
+                    iteration: 1,
+                    tool_name: "edit".to_string(),
+                    success: false,
+                    result_summary: String::new(),
+                    duration_ms: 20,
+                },
+                6,
+            ),
+            entry(
+                Event::ValidationStarted {
+                    execution_id: id.clone(),
+                    iteration: 1,
+                    command: "cargo test".to_string(),
+                },
+                7,
+            ),
+            entry(
+                Event::ValidationCompleted {
+                    execution_id: id.clone(),
+                    iteration: 1,
+                    exit_code: 101,
+                    duration_ms: 3000,
+                },
+                10,
+            ),
+            entry(
+                Event::IterationCompleted {
+                    execution_id: id.clone(),
+                    iteration: 1,
+                    outcome: IterationOutcome::ValidationFailed { exit_code: 101 },
+                },
+                10,
+            ),
+            entry(
+                Event::IterationStarted {
+                    execution_id: id.clone(),
+                    iteration: 2,
+                },
+                11,
+            ),
+            entry(
+                Event::ToolCallCompleted {
+                    execution_id: id,
+                    iteration: 2,
+                    tool_name: "edit".to_string(),
+                    success: true,
+                    result_summary: String::new(),
+                    duration_ms: 30,
+                },
+                12,
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_report_aggregates_events() {
+        let exec = LoopExecution::with_id("exec-1", "phase");
+        let report = ExecutionReport::from_events(exec, &sample_events(), "anthropic/claude-sonnet-4");
+
+        assert_eq!(report.iterations.len(), 2);
+        assert_eq!(report.iterations[0].tool_calls, 1);
+        assert_eq!(
+            report.iterations[0].completed_at.unwrap() - report.iterations[0].started_at.unwrap(),
+            Duration::seconds(10)
+        );
+        assert_eq!(report.iterations[1].outcome, None);
+
+        let edit = &report.tool_calls["edit"];
+        assert_eq!((edit.calls, edit.failures, edit.total_duration_ms), (2, 1, 50));
+
+        assert_eq!(report.validations.len(), 1);
+        assert_eq!(report.validations[0].command.as_deref(), Some("cargo test"));
+        assert_eq!(report.input_tokens, 1000);
+        assert!(report.cost_usd() > 0.0);
+    }
+
+    #[test]
+    fn test_report_prices_failover_iterations_at_their_model() {
+        let exec = LoopExecution::with_id("exec-1", "phase");
+        let baseline = ExecutionReport::from_events(exec.clone(), &sample_events(), "anthropic/claude-sonnet-4");
+
+        let mut events = sample_events();
+        if let Event::ResponseCompleted { served_by, .. } = &mut events[1].event {
+            *served_by = Some("anthropic/claude-opus-4".to_string());
+        }
+        let report = ExecutionReport::from_events(exec, &events, "anthropic/claude-sonnet-4");
+        assert_eq!(report.iterations[0].served_by.as_deref(), Some("anthropic/claude-opus-4"));
+        assert!(report.cost_usd() > baseline.cost_usd());
+        assert!(report.to_markdown().contains("(served by anthropic/claude-opus-4)"));
+    }
+
+    #[test]
+    fn test_report_renders_markdown_and_html() {
+        let mut exec = LoopExecution::with_id("exec-1", "phase").with_acceptance(vec![
+            AcceptanceCriterion::FileExists {
+                path: "src/a.rs".to_string(),
+            },
+            AcceptanceCriterion::Command {
+                command: "cargo test".to_string(),
+            },
+        ]);
+        exec.acceptance[0].status = CriterionStatus::Passed;
+        let report = ExecutionReport::from_events(exec, &sample_events(), "anthropic/claude-sonnet-4")
+            .with_plan("specs/a.md", "# Spec <A>")
+            .with_diff_summary(" src/a.rs | 3 ++-\n 1 file changed");
+
+        let md = report.render(ReportFormat::Markdown);
+        assert!(md.contains("# Execution Report: exec-1"));
+        assert!(md.contains("| 1 | "));
+        assert!(md.contains("validation failed (exit 101)"));
+        assert!(md.contains("| edit | 2 | 1 |"));
+        assert!(md.contains("1 file changed"));
+        assert!(md.contains("- [x] src/a.rs exists (passed)"));
+        assert!(md.contains("- [ ] `cargo test` succeeds (pending)"));
+
+        let html = report.render(ReportFormat::Html);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("# Spec &lt;A&gt;"));
+        assert!(html.contains("<code>cargo test</code>"));
+    }
+
+    #[test]
+    fn test_report_format_from_str() {
+        assert_eq!("md".parse::<ReportFormat>().unwrap(), ReportFormat::Markdown);
+        assert_eq!("HTML".parse::<ReportFormat>().unwrap(), ReportFormat::Html);
+        assert!("pdf".parse::<ReportFormat>().is_err());
+    }
+}
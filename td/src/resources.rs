@@ -0,0 +1,327 @@
+//! Daemon self-monitoring
+//!
+//! The daemon samples its own CPU, resident memory, open file handles and
+//! tokio task count every `resources.interval-secs`, and attributes the memory
+//! of the commands each loop runs to that loop's execution. While a configured
+//! limit is exceeded it stops picking up new executions and emits
+//! `ResourcePressure`; `td daemon status --detailed` shows the latest sample.
+//!
+//! Sampling reads `/proc`, so on other platforms only the task count is known
+//! and the other limits never trigger.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::config::ResourceMonitorConfig;
+
+/// `execution_id` of daemon-level events such as `ResourcePressure`
+pub const DAEMON_EVENT_ID: &str = "daemon";
+
+/// Clock ticks per second for `/proc/<pid>/stat` CPU times (USER_HZ, 100 on Linux)
+const TICKS_PER_SEC: f64 = 100.0;
+
+/// Memory attributed to one running execution
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoopResources {
+    pub exec_id: String,
+    /// Combined RSS of the commands running in the execution's worktree
+    pub rss_bytes: u64,
+    /// Number of those commands
+    pub processes: usize,
+}
+
+/// One sample of the daemon's resource usage
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourceSample {
+    /// When the sample was taken (ms since epoch)
+    pub sampled_at: i64,
+    /// CPU used since the previous sample, in percent of one core
+    pub cpu_percent: Option<f64>,
+    /// Resident memory of the daemon process
+    pub rss_bytes: Option<u64>,
+    /// Open file descriptors of the daemon process
+    pub open_files: Option<usize>,
+    /// Alive tokio tasks
+    pub tokio_tasks: usize,
+    /// Loop tasks the daemon is running
+    pub loop_tasks: usize,
+    /// Per-execution memory of spawned commands, largest first
+    pub loops: Vec<LoopResources>,
+}
+
+impl ResourceSample {
+    /// Limits from `config` this sample exceeds, as human-readable reasons
+    pub fn exceeded(&self, config: &ResourceMonitorConfig) -> Vec<String> {
+        let mut reasons = Vec::new();
+        if let (Some(limit), Some(rss)) = (config.max_rss_mb, self.rss_bytes)
+            && rss / (1024 * 1024) > limit
+        {
+            reasons.push(format!("rss {}MB > {}MB", rss / (1024 * 1024), limit));
+        }
+        if let (Some(limit), Some(cpu)) = (config.max_cpu_percent, self.cpu_percent)
+            && cpu > limit
+        {
+            reasons.push(format!("cpu {:.0}% > {:.0}%", cpu, limit));
+        }
+        if let (Some(limit), Some(files)) = (config.max_open_files, self.open_files)
+            && files > limit
+        {
+            reasons.push(format!("open files {} > {}", files, limit));
+        }
+        if let Some(limit) = config.max_tasks
+            && self.tokio_tasks > limit
+        {
+            reasons.push(format!("tokio tasks {} > {}", self.tokio_tasks, limit));
+        }
+        reasons
+    }
+}
+
+/// How the pressure state changed with the latest sample
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PressureChange {
+    /// A limit is now exceeded (reasons given)
+    Started(Vec<String>),
+    /// No limit is exceeded any more
+    Cleared,
+    /// Same state as before
+    Unchanged,
+}
+
+/// Samples the daemon's resources and tracks whether it is under pressure
+#[derive(Debug)]
+pub struct ResourceMonitor {
+    config: ResourceMonitorConfig,
+    /// CPU ticks and time of the previous sample
+    last_cpu: Option<(u64, Instant)>,
+    latest: Option<ResourceSample>,
+    pressure: Vec<String>,
+}
+
+impl ResourceMonitor {
+    pub fn new(config: ResourceMonitorConfig) -> Self {
+        debug!(?config, "ResourceMonitor::new: called");
+        Self {
+            config,
+            last_cpu: None,
+            latest: None,
+            pressure: Vec::new(),
+        }
+    }
+
+    pub fn config(&self) -> &ResourceMonitorConfig {
+        &self.config
+    }
+
+    /// The most recent sample
+    pub fn latest(&self) -> Option<&ResourceSample> {
+        self.latest.as_ref()
+    }
+
+    /// Limits exceeded by the most recent sample (empty when not under pressure)
+    pub fn pressure(&self) -> &[String] {
+        &self.pressure
+    }
+
+    pub fn under_pressure(&self) -> bool {
+        !self.pressure.is_empty()
+    }
+
+    /// Take a sample; `worktrees` maps running executions to their worktrees
+    pub fn sample(&mut self, worktrees: &[(String, PathBuf)], loop_tasks: usize, now_ms: i64) -> PressureChange {
+        debug!(running = worktrees.len(), "ResourceMonitor::sample: called");
+        let cpu_percent = self.cpu_percent();
+        let sample = ResourceSample {
+            sampled_at: now_ms,
+            cpu_percent,
+            rss_bytes: fs::read_to_string("/proc/self/status")
+                .ok()
+                .and_then(|s| parse_vm_rss(&s)),
+            open_files: fs::read_dir("/proc/self/fd").ok().map(|dir| dir.count()),
+            tokio_tasks: tokio::runtime::Handle::try_current()
+                .map(|handle| handle.metrics().num_alive_tasks())
+                .unwrap_or(0),
+            loop_tasks,
+            loops: attribute_loops(worktrees),
+        };
+        self.observe(sample)
+    }
+
+    /// Record `sample` as the latest and update the pressure state
+    pub fn observe(&mut self, sample: ResourceSample) -> PressureChange {
+        let reasons = sample.exceeded(&self.config);
+        self.latest = Some(sample);
+        let was_under = self.under_pressure();
+        self.pressure = reasons;
+        match (was_under, self.under_pressure()) {
+            (false, true) => PressureChange::Started(self.pressure.clone()),
+            (true, false) => PressureChange::Cleared,
+            _ => PressureChange::Unchanged,
+        }
+    }
+
+    /// CPU percent since the previous call (None on the first call or without /proc)
+    fn cpu_percent(&mut self) -> Option<f64> {
+        let ticks = fs::read_to_string("/proc/self/stat")
+            .ok()
+            .and_then(|s| parse_stat(&s))
+            .map(|stat| stat.cpu_ticks)?;
+        let now = Instant::now();
+        let previous = self.last_cpu.replace((ticks, now));
+        let (last_ticks, last_at) = previous?;
+        let elapsed = now.duration_since(last_at).as_secs_f64();
+        (elapsed > 0.0).then(|| ticks.saturating_sub(last_ticks) as f64 / TICKS_PER_SEC / elapsed * 100.0)
+    }
+}
+
+/// Fields read from `/proc/<pid>/stat`
+#[derive(Debug, PartialEq, Eq)]
+struct ProcStat {
+    ppid: u32,
+    /// utime + stime
+    cpu_ticks: u64,
+}
+
+/// Parse `/proc/<pid>/stat`; the command name may contain spaces and parens
+fn parse_stat(stat: &str) -> Option<ProcStat> {
+    let rest = &stat[stat.rfind(')')? + 1..];
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    // fields[0] is the state (field 3), so field N is fields[N - 3]
+    Some(ProcStat {
+        ppid: fields.get(1)?.parse().ok()?,
+        cpu_ticks: fields.get(11)?.parse::<u64>().ok()? + fields.get(12)?.parse::<u64>().ok()?,
+    })
+}
+
+/// Parse `VmRSS` from `/proc/<pid>/status`, in bytes
+fn parse_vm_rss(status: &str) -> Option<u64> {
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+/// Sum the RSS of the daemon's descendant processes by the worktree they run in
+fn attribute_loops(worktrees: &[(String, PathBuf)]) -> Vec<LoopResources> {
+    if worktrees.is_empty() {
+        return Vec::new();
+    }
+    let Ok(proc_dir) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+
+    let mut parents = HashMap::new();
+    for entry in proc_dir.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) else {
+            continue;
+        };
+        if let Some(stat) = fs::read_to_string(entry.path().join("stat"))
+            .ok()
+            .and_then(|s| parse_stat(&s))
+        {
+            parents.insert(pid, stat.ppid);
+        }
+    }
+
+    let mut totals: HashMap<&str, LoopResources> = HashMap::new();
+    for &pid in descendants(&parents, std::process::id()).iter() {
+        let proc_path = Path::new("/proc").join(pid.to_string());
+        let Ok(cwd) = fs::read_link(proc_path.join("cwd")) else {
+            continue;
+        };
+        let Some((exec_id, _)) = worktrees.iter().find(|(_, worktree)| cwd.starts_with(worktree)) else {
+            continue;
+        };
+        let rss = fs::read_to_string(proc_path.join("status"))
+            .ok()
+            .and_then(|s| parse_vm_rss(&s))
+            .unwrap_or(0);
+        let entry = totals.entry(exec_id.as_str()).or_insert_with(|| LoopResources {
+            exec_id: exec_id.clone(),
+            rss_bytes: 0,
+            processes: 0,
+        });
+        entry.rss_bytes += rss;
+        entry.processes += 1;
+    }
+
+    let mut loops: Vec<_> = totals.into_values().collect();
+    loops.sort_by(|a, b| b.rss_bytes.cmp(&a.rss_bytes).then_with(|| a.exec_id.cmp(&b.exec_id)));
+    loops
+}
+
+/// All processes below `root` in the `pid -> ppid` map
+fn descendants(parents: &HashMap<u32, u32>, root: u32) -> Vec<u32> {
+    let mut found = Vec::new();
+    let mut frontier = vec![root];
+    while let Some(parent) = frontier.pop() {
+        for (&pid, &ppid) in parents {
+            if ppid == parent && pid != root && !found.contains(&pid) {
+                found.push(pid);
+                frontier.push(pid);
+            }
+        }
+    }
+    found
+}
+
+/// Format a byte count as MB for status output
+pub fn format_mb(bytes: u64) -> String {
+    format!("{:.1}MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_files() {
+        let stat = "4242 (td (daemon)) S 1 4242 4242 0 -1 4194560 500 0 0 0 150 25 0 0 20 0 8 0 100 0 0";
+        assert_eq!(
+            parse_stat(stat),
+            Some(ProcStat {
+                ppid: 1,
+                cpu_ticks: 175
+            })
+        );
+        assert_eq!(parse_stat("garbage"), None);
+
+        let status = "Name:\ttd\nVmPeak:\t  90000 kB\nVmRSS:\t   2048 kB\nThreads:\t8\n";
+        assert_eq!(parse_vm_rss(status), Some(2048 * 1024));
+
+        let parents = HashMap::from([(10, 1), (11, 10), (12, 11), (20, 1)]);
+        let mut below = descendants(&parents, 10);
+        below.sort();
+        assert_eq!(below, [11, 12]);
+    }
+
+    #[test]
+    fn test_monitor_pressure_transitions() {
+        let config = ResourceMonitorConfig {
+            max_rss_mb: Some(100),
+            max_tasks: Some(50),
+            ..Default::default()
+        };
+        let mut monitor = ResourceMonitor::new(config);
+        let sample = |rss_mb: u64, tasks: usize| ResourceSample {
+            rss_bytes: Some(rss_mb * 1024 * 1024),
+            tokio_tasks: tasks,
+            ..Default::default()
+        };
+
+        assert_eq!(monitor.observe(sample(50, 10)), PressureChange::Unchanged);
+        assert_eq!(
+            monitor.observe(sample(150, 60)),
+            PressureChange::Started(vec!["rss 150MB > 100MB".to_string(), "tokio tasks 60 > 50".to_string()])
+        );
+        assert!(monitor.under_pressure());
+        assert_eq!(monitor.observe(sample(150, 10)), PressureChange::Unchanged);
+        assert_eq!(monitor.pressure(), ["rss 150MB > 100MB"]);
+        assert_eq!(monitor.observe(sample(90, 10)), PressureChange::Cleared);
+        assert!(!monitor.under_pressure());
+    }
+}
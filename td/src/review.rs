@@ -0,0 +1,523 @@
+//! Diff review: structured comments, the review artifact and the merge gate
+//!
+//! The `review` loop type is given a branch or an execution ID and reads the
+//! branch's diff against `main` together with the plan it implements. Each
+//! problem it finds becomes a [`ReviewComment`] (file, line range, severity,
+//! suggestion) recorded with the `review_comment` tool; `finish_review` adds
+//! the summary and marks the [`Review`] complete, which is what the loop's
+//! validation checks. The review lives in the worktree under [`REVIEW_DIR`].
+//!
+//! When the loop completes, the review is stored as JSON and Markdown under
+//! [`ARTIFACT_DIR`] and, with `review.github.repo` set, posted to the
+//! branch's open pull request. With `review.gate-merges`, an execution whose
+//! latest review has blocking comments is held instead of merged.
+
+use std::path::{Path, PathBuf};
+
+use eyre::{Context, Result, eyre};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tracing::debug;
+
+use crate::config::GithubConfig;
+
+/// Loop type that reviews a diff
+pub const REVIEW_TYPE: &str = "review";
+
+/// Directory (relative to the worktree) holding the review in progress
+pub const REVIEW_DIR: &str = ".taskdaemon/review";
+
+/// The review in progress, as JSON
+pub const REVIEW_FILE: &str = "review.json";
+
+/// Directory (relative to the repo root) holding completed reviews, one per execution
+pub const ARTIFACT_DIR: &str = ".taskdaemon/artifacts/reviews";
+
+/// Branch the reviewed diff is taken against
+const BASE_BRANCH: &str = "main";
+
+/// Prefix of execution branches (see `WorktreeConfig::branch_prefix`)
+const EXECUTION_BRANCH_PREFIX: &str = "taskdaemon";
+
+/// Longest diff put into a prompt
+const MAX_DIFF_CHARS: usize = 30_000;
+
+/// How much a review comment matters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommentSeverity {
+    /// Style or naming; take it or leave it
+    Nit,
+    /// A better way to do something that works
+    Suggestion,
+    /// Likely a problem, worth a second look
+    Warning,
+    /// Must be fixed before merging
+    Blocking,
+}
+
+impl std::fmt::Display for CommentSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Nit => write!(f, "nit"),
+            Self::Suggestion => write!(f, "suggestion"),
+            Self::Warning => write!(f, "warning"),
+            Self::Blocking => write!(f, "blocking"),
+        }
+    }
+}
+
+impl std::str::FromStr for CommentSeverity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "nit" => Ok(Self::Nit),
+            "suggestion" => Ok(Self::Suggestion),
+            "warning" => Ok(Self::Warning),
+            "blocking" => Ok(Self::Blocking),
+            other => Err(format!(
+                "Unknown severity '{}' (expected nit, suggestion, warning or blocking)",
+                other
+            )),
+        }
+    }
+}
+
+/// One comment on the reviewed diff
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ReviewComment {
+    /// File the comment is about, relative to the repo root
+    pub file: String,
+    /// First line commented on (1-based, in the new version of the file)
+    pub start_line: u32,
+    /// Last line commented on
+    pub end_line: u32,
+    pub severity: CommentSeverity,
+    /// What is wrong
+    pub comment: String,
+    /// How to fix it, if the reviewer has an idea
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+impl ReviewComment {
+    /// `file:start-end` (or `file:line` for a single line)
+    pub fn location(&self) -> String {
+        if self.start_line == self.end_line {
+            format!("{}:{}", self.file, self.start_line)
+        } else {
+            format!("{}:{}-{}", self.file, self.start_line, self.end_line)
+        }
+    }
+
+    /// The comment with its suggestion, as Markdown
+    fn body(&self) -> String {
+        let mut body = format!("**{}**: {}", self.severity, self.comment);
+        if let Some(ref suggestion) = self.suggestion {
+            body.push_str("\n\nSuggestion: ");
+            body.push_str(suggestion);
+        }
+        body
+    }
+}
+
+/// A review of one branch, persisted in the worktree while it is written
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Review {
+    /// What was asked to be reviewed (branch name or execution ID)
+    pub target: String,
+    /// Branch the diff was taken from
+    pub branch: String,
+    /// Commit of the branch that was reviewed
+    pub head: String,
+    #[serde(default)]
+    pub comments: Vec<ReviewComment>,
+    /// Overall assessment, set by `finish_review`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    /// Whether `finish_review` has been called
+    #[serde(default)]
+    pub complete: bool,
+    /// When the review was finished (Unix milliseconds)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<i64>,
+}
+
+impl Review {
+    pub fn new(target: &str, branch: &str, head: &str) -> Self {
+        debug!(%target, %branch, %head, "Review::new: called");
+        Self {
+            target: target.to_string(),
+            branch: branch.to_string(),
+            head: head.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Path of the review file in `worktree`
+    pub fn path(worktree: &Path) -> PathBuf {
+        worktree.join(REVIEW_DIR).join(REVIEW_FILE)
+    }
+
+    /// Load the review in progress from `worktree` (None before the first iteration)
+    pub fn load(worktree: &Path) -> Result<Option<Self>> {
+        let path = Self::path(worktree);
+        debug!(?path, "Review::load: called");
+        Self::read(&path)
+    }
+
+    fn read(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let review = serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))?;
+        Ok(Some(review))
+    }
+
+    /// Write the review into `worktree`
+    pub fn save(&self, worktree: &Path) -> Result<()> {
+        let dir = worktree.join(REVIEW_DIR);
+        debug!(?dir, comments = self.comments.len(), "Review::save: called");
+        std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        std::fs::write(dir.join(REVIEW_FILE), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Add a comment, checking its line range
+    pub fn add_comment(&mut self, comment: ReviewComment) -> Result<(), String> {
+        debug!(location = %comment.location(), severity = %comment.severity, "Review::add_comment: called");
+        if self.complete {
+            return Err("The review is already finished".to_string());
+        }
+        if comment.file.trim().is_empty() {
+            return Err("file is required".to_string());
+        }
+        if comment.start_line == 0 || comment.end_line < comment.start_line {
+            return Err(format!(
+                "Invalid line range {}-{} (lines start at 1 and end-line must not precede start-line)",
+                comment.start_line, comment.end_line
+            ));
+        }
+        self.comments.push(comment);
+        Ok(())
+    }
+
+    /// Mark the review complete with an overall summary
+    pub fn finish(&mut self, summary: &str) {
+        debug!(comments = self.comments.len(), "Review::finish: called");
+        self.summary = Some(summary.to_string());
+        self.complete = true;
+        self.completed_at = Some(taskstore::now_ms());
+    }
+
+    /// Comments that must be addressed before merging
+    pub fn blocking(&self) -> Vec<&ReviewComment> {
+        self.comments
+            .iter()
+            .filter(|c| c.severity == CommentSeverity::Blocking)
+            .collect()
+    }
+
+    /// Comment counts by severity, e.g. "3 comments (1 blocking, 2 nit)"
+    pub fn counts(&self) -> String {
+        let mut parts = Vec::new();
+        for severity in [
+            CommentSeverity::Blocking,
+            CommentSeverity::Warning,
+            CommentSeverity::Suggestion,
+            CommentSeverity::Nit,
+        ] {
+            let n = self.comments.iter().filter(|c| c.severity == severity).count();
+            if n > 0 {
+                parts.push(format!("{} {}", n, severity));
+            }
+        }
+        match self.comments.len() {
+            0 => "no comments".to_string(),
+            1 => format!("1 comment ({})", parts.join(", ")),
+            n => format!("{} comments ({})", n, parts.join(", ")),
+        }
+    }
+
+    /// Comments made so far as prompt text, one per line
+    pub fn format_comments(&self) -> String {
+        self.comments
+            .iter()
+            .map(|c| format!("- [{}] {}: {}", c.severity, c.location(), c.comment))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// The review as a Markdown document, most severe comments first
+    pub fn render_markdown(&self) -> String {
+        debug!(target = %self.target, "Review::render_markdown: called");
+        let mut text = format!("# Review of {}\n\n", self.target);
+        text.push_str(&format!("Branch `{}` at `{}`: {}\n\n", self.branch, self.head, self.counts()));
+        if let Some(ref summary) = self.summary {
+            text.push_str(summary.trim());
+            text.push_str("\n\n");
+        }
+        let mut comments: Vec<&ReviewComment> = self.comments.iter().collect();
+        comments.sort_by_key(|c| std::cmp::Reverse(c.severity));
+        for comment in comments {
+            text.push_str(&format!("## {} ({})\n\n{}\n", comment.location(), comment.severity, comment.comment));
+            if let Some(ref suggestion) = comment.suggestion {
+                text.push_str(&format!("\nSuggestion: {}\n", suggestion));
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Why the reviewed branch must not merge, if it has blocking comments
+    pub fn merge_hold(&self) -> Option<String> {
+        let blocking = self.blocking();
+        if blocking.is_empty() {
+            return None;
+        }
+        let locations: Vec<String> = blocking.iter().map(|c| c.location()).collect();
+        Some(format!(
+            "Review of {} has {} blocking comment(s): {}",
+            self.target,
+            blocking.len(),
+            locations.join(", ")
+        ))
+    }
+
+    /// Body of a GitHub "create a review" request for the pull request of this branch
+    ///
+    /// Blocking comments request changes; otherwise the review only comments.
+    pub fn github_payload(&self) -> Value {
+        let event = if self.blocking().is_empty() {
+            "COMMENT"
+        } else {
+            "REQUEST_CHANGES"
+        };
+        let comments: Vec<Value> = self
+            .comments
+            .iter()
+            .map(|c| {
+                let mut comment = json!({
+                    "path": c.file,
+                    "line": c.end_line,
+                    "side": "RIGHT",
+                    "body": c.body(),
+                });
+                if c.start_line < c.end_line {
+                    comment["start_line"] = c.start_line.into();
+                    comment["start_side"] = "RIGHT".into();
+                }
+                comment
+            })
+            .collect();
+        json!({
+            "commit_id": self.head,
+            "body": self.summary.clone().unwrap_or_else(|| self.counts()),
+            "event": event,
+            "comments": comments,
+        })
+    }
+}
+
+/// Execution whose branch `branch` is, if it is one
+pub fn execution_id(branch: &str) -> Option<&str> {
+    branch.strip_prefix(EXECUTION_BRANCH_PREFIX)?.strip_prefix('/')
+}
+
+/// Branch to review for `target`: the branch itself, or the branch of the execution with that ID
+pub async fn resolve_target(repo: &Path, target: &str) -> Result<String> {
+    debug!(?repo, %target, "resolve_target: called");
+    let candidates = [target.to_string(), format!("{}/{}", EXECUTION_BRANCH_PREFIX, target)];
+    for branch in candidates {
+        let output = tokio::process::Command::new("git")
+            .args(["rev-parse", "--verify", "--quiet", &format!("refs/heads/{}", branch)])
+            .current_dir(repo)
+            .output()
+            .await
+            .context("Failed to run git rev-parse")?;
+        if output.status.success() {
+            debug!(%branch, "resolve_target: found branch");
+            return Ok(branch);
+        }
+    }
+    Err(eyre!("'{}' is neither a branch nor an execution with a branch", target))
+}
+
+/// Head commit of `rev` and its diff against `main` (truncated for the prompt)
+pub async fn branch_diff(repo: &Path, rev: &str) -> Result<(String, String)> {
+    debug!(?repo, %rev, "branch_diff: called");
+    let git = |args: Vec<String>| async move {
+        let output = tokio::process::Command::new("git")
+            .args(&args)
+            .current_dir(repo)
+            .output()
+            .await
+            .with_context(|| format!("Failed to run git {}", args.join(" ")))?;
+        if !output.status.success() {
+            return Err(eyre!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+    let head = git(vec!["rev-parse".to_string(), rev.to_string()]).await?;
+    let mut diff = git(vec!["diff".to_string(), format!("{}...{}", BASE_BRANCH, rev)]).await?;
+    if diff.len() > MAX_DIFF_CHARS {
+        debug!(len = diff.len(), "branch_diff: truncating large diff");
+        let mut end = MAX_DIFF_CHARS;
+        while !diff.is_char_boundary(end) {
+            end -= 1;
+        }
+        diff.truncate(end);
+        diff.push_str("\n[diff truncated]");
+    }
+    Ok((head.trim().to_string(), diff))
+}
+
+/// Directory of the stored review made by execution `exec_id`
+pub fn artifact_dir(repo_root: &Path, exec_id: &str) -> PathBuf {
+    repo_root.join(ARTIFACT_DIR).join(exec_id)
+}
+
+/// Store a completed review as JSON and Markdown, returning the Markdown path relative to `repo_root`
+pub fn save_artifact(repo_root: &Path, exec_id: &str, review: &Review) -> Result<String> {
+    let dir = artifact_dir(repo_root, exec_id);
+    debug!(?dir, "save_artifact: called");
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    std::fs::write(dir.join(REVIEW_FILE), serde_json::to_string_pretty(review)?)?;
+    std::fs::write(dir.join("review.md"), review.render_markdown())?;
+    Ok(format!("{}/{}/review.md", ARTIFACT_DIR, exec_id))
+}
+
+/// Most recently completed stored review of execution `exec_id` (or of its branch)
+pub fn latest_review(repo_root: &Path, exec_id: &str) -> Result<Option<Review>> {
+    debug!(?repo_root, %exec_id, "latest_review: called");
+    let dir = repo_root.join(ARTIFACT_DIR);
+    if !dir.exists() {
+        return Ok(None);
+    }
+    let mut latest: Option<Review> = None;
+    for entry in std::fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let Some(review) = Review::read(&entry?.path().join(REVIEW_FILE))? else {
+            continue;
+        };
+        if !review.complete || (review.target != exec_id && execution_id(&review.branch) != Some(exec_id)) {
+            continue;
+        }
+        if latest.as_ref().is_none_or(|l| review.completed_at > l.completed_at) {
+            latest = Some(review);
+        }
+    }
+    Ok(latest)
+}
+
+/// Post `review` to the open pull request of its branch, returning the review's URL
+pub async fn post_to_github(http: &reqwest::Client, config: &GithubConfig, review: &Review) -> Result<String> {
+    debug!(branch = %review.branch, "post_to_github: called");
+    let repo = config.repo.as_deref().ok_or_else(|| eyre!("review.github.repo is not set"))?;
+    let token = std::env::var(&config.token_env).with_context(|| format!("{} is not set", config.token_env))?;
+    let owner = repo.split('/').next().unwrap_or(repo);
+    let api = config.api_url.trim_end_matches('/');
+
+    let pulls: Vec<Value> = http
+        .get(format!("{}/repos/{}/pulls", api, repo))
+        .query(&[("head", format!("{}:{}", owner, review.branch)), ("state", "open".to_string())])
+        .bearer_auth(&token)
+        .header("Accept", "application/vnd.github+json")
+        .header("User-Agent", "taskdaemon")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let number = pulls
+        .first()
+        .and_then(|pr| pr["number"].as_u64())
+        .ok_or_else(|| eyre!("No open pull request for {} in {}", review.branch, repo))?;
+    debug!(%number, "post_to_github: found pull request");
+
+    let posted: Value = http
+        .post(format!("{}/repos/{}/pulls/{}/reviews", api, repo, number))
+        .bearer_auth(&token)
+        .header("Accept", "application/vnd.github+json")
+        .header("User-Agent", "taskdaemon")
+        .json(&review.github_payload())
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(posted["html_url"].as_str().unwrap_or_default().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn comment(file: &str, start_line: u32, end_line: u32, severity: CommentSeverity) -> ReviewComment {
+        ReviewComment {
+            file: file.to_string(),
+            start_line,
+            end_line,
+            severity,
+            comment: "Unchecked unwrap".to_string(),
+            suggestion: Some("Return the error".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_review_comments_and_merge_hold() {
+        let mut review = Review::new("exec-1", "taskdaemon/exec-1", "abc123");
+        assert!(review.add_comment(comment("src/lib.rs", 5, 4, CommentSeverity::Nit)).is_err());
+        review.add_comment(comment("src/lib.rs", 10, 12, CommentSeverity::Blocking)).unwrap();
+        review.add_comment(comment("src/main.rs", 3, 3, CommentSeverity::Nit)).unwrap();
+        assert_eq!(review.counts(), "2 comments (1 blocking, 1 nit)");
+        assert_eq!(
+            review.merge_hold().as_deref(),
+            Some("Review of exec-1 has 1 blocking comment(s): src/lib.rs:10-12")
+        );
+
+        let payload = review.github_payload();
+        assert_eq!(payload["event"], "REQUEST_CHANGES");
+        assert_eq!(payload["comments"][0]["start_line"], 10);
+        assert_eq!(payload["comments"][0]["line"], 12);
+        assert!(payload["comments"][1].get("start_line").is_none());
+
+        review.finish("Solid apart from the error handling.");
+        assert!(review.add_comment(comment("src/lib.rs", 1, 1, CommentSeverity::Nit)).is_err());
+        let markdown = review.render_markdown();
+        assert!(markdown.starts_with("# Review of exec-1\n"));
+        assert!(markdown.find("src/lib.rs:10-12").unwrap() < markdown.find("src/main.rs:3").unwrap());
+    }
+
+    #[test]
+    fn test_latest_review_of_execution() {
+        let repo = TempDir::new().unwrap();
+        assert!(latest_review(repo.path(), "exec-1").unwrap().is_none());
+
+        let mut first = Review::new("exec-1", "taskdaemon/exec-1", "abc");
+        first.add_comment(comment("src/lib.rs", 1, 1, CommentSeverity::Blocking)).unwrap();
+        first.finish("Needs work");
+        first.completed_at = Some(1);
+        save_artifact(repo.path(), "review-1", &first).unwrap();
+
+        // Reviewed again by branch name after the fix
+        let mut second = Review::new("taskdaemon/exec-1", "taskdaemon/exec-1", "def");
+        second.finish("Looks good");
+        second.completed_at = Some(2);
+        let path = save_artifact(repo.path(), "review-2", &second).unwrap();
+        assert_eq!(path, ".taskdaemon/artifacts/reviews/review-2/review.md");
+
+        let latest = latest_review(repo.path(), "exec-1").unwrap().unwrap();
+        assert_eq!(latest.head, "def");
+        assert!(latest.merge_hold().is_none());
+        assert!(latest_review(repo.path(), "exec-2").unwrap().is_none());
+    }
+}
@@ -0,0 +1,504 @@
+//! Foreground parallel runs (`td run-many`)
+//!
+//! Runs every task of a batch manifest (see [`crate::batch`]) to completion
+//! inside the current process, with no daemon or TaskStore involved. Each task
+//! gets its own worktree and branch (`taskdaemon/{id}`), at most
+//! `max-parallel` loops run at once, and a task starts only after everything
+//! it `depends-on` has completed; dependents of a failed task are skipped.
+//! Progress from all loops is interleaved on stdout, one line per event,
+//! prefixed with the task name. Branches are left in place for review rather
+//! than merged.
+//!
+//! The process exit code summarizes the whole run, so a CI job can gate on it:
+//! [`EXIT_SUCCESS`] when every task completed, [`EXIT_FAILURE`] when any task
+//! failed, got stuck or was skipped, and [`EXIT_INTERRUPTED`] on Ctrl-C.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use eyre::Result;
+use tokio::sync::broadcast;
+use tokio::task::JoinSet;
+use tracing::debug;
+
+use crate::domain::LoopExecution;
+use crate::events::{Event, EventBus, IterationOutcome};
+use crate::llm::LlmClient;
+use crate::r#loop::{IterationResult, LoopConfig, LoopEngine};
+use crate::worktree::WorktreeManager;
+
+/// Exit code when every task completed
+pub const EXIT_SUCCESS: i32 = 0;
+
+/// Exit code when any task failed, got stuck or was skipped
+pub const EXIT_FAILURE: i32 = 1;
+
+/// Exit code when the run was interrupted (128 + SIGINT)
+pub const EXIT_INTERRUPTED: i32 = 130;
+
+/// Default number of loops running at once
+pub const DEFAULT_MAX_PARALLEL: usize = 4;
+
+/// How a task ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskOutcome {
+    /// Loop completed (validation passed)
+    Complete { iterations: u32 },
+    /// Loop failed or could not be started
+    Failed { reason: String },
+    /// Loop made no progress and was given up on
+    Stuck { reason: String },
+    /// Not run because a dependency didn't complete
+    Skipped { dependency: String },
+    /// Stopped by Ctrl-C (or never started)
+    Interrupted,
+}
+
+impl TaskOutcome {
+    /// Whether the task completed
+    pub fn is_success(&self) -> bool {
+        matches!(self, Self::Complete { .. })
+    }
+
+    /// Short status word for the summary table
+    pub fn status(&self) -> &'static str {
+        match self {
+            Self::Complete { .. } => "complete",
+            Self::Failed { .. } => "failed",
+            Self::Stuck { .. } => "stuck",
+            Self::Skipped { .. } => "skipped",
+            Self::Interrupted => "interrupted",
+        }
+    }
+}
+
+impl std::fmt::Display for TaskOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Complete { iterations } => write!(f, "completed after {} iterations", iterations),
+            Self::Failed { reason } => write!(f, "failed: {}", reason),
+            Self::Stuck { reason } => write!(f, "stuck: {}", reason),
+            Self::Skipped { dependency } => write!(f, "skipped: dependency {} did not complete", dependency),
+            Self::Interrupted => write!(f, "interrupted"),
+        }
+    }
+}
+
+/// Result of one task of the run
+#[derive(Debug, Clone)]
+pub struct TaskReport {
+    pub exec_id: String,
+    /// Task name (or title derived from the task)
+    pub name: String,
+    pub loop_type: String,
+    /// Branch holding the work (None if no worktree was created)
+    pub branch: Option<String>,
+    pub outcome: TaskOutcome,
+    pub duration: Duration,
+}
+
+/// Options for [`run_many`]
+#[derive(Debug, Clone)]
+pub struct RunManyOptions {
+    /// Loops running at once (at least 1)
+    pub max_parallel: usize,
+    /// Override every loop type's max iterations
+    pub max_iterations: Option<u32>,
+    /// Extra environment for commands the loops run
+    pub command_env: Vec<(String, String)>,
+}
+
+impl Default for RunManyOptions {
+    fn default() -> Self {
+        Self {
+            max_parallel: DEFAULT_MAX_PARALLEL,
+            max_iterations: None,
+            command_env: Vec::new(),
+        }
+    }
+}
+
+impl RunManyOptions {
+    pub fn with_max_parallel(mut self, max_parallel: usize) -> Self {
+        self.max_parallel = max_parallel.max(1);
+        self
+    }
+
+    pub fn with_max_iterations(mut self, max_iterations: Option<u32>) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    pub fn with_command_env(mut self, command_env: Vec<(String, String)>) -> Self {
+        self.command_env = command_env;
+        self
+    }
+}
+
+/// Whether a pending task can start
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Readiness {
+    Ready,
+    Waiting,
+    /// A dependency finished without completing
+    Blocked(String),
+}
+
+/// Check a task's dependencies against the tasks finished so far (ID -> completed)
+fn readiness(deps: &[String], finished: &HashMap<String, bool>) -> Readiness {
+    let mut waiting = false;
+    for dep in deps {
+        match finished.get(dep) {
+            Some(true) => {}
+            Some(false) => return Readiness::Blocked(dep.clone()),
+            None => waiting = true,
+        }
+    }
+    if waiting { Readiness::Waiting } else { Readiness::Ready }
+}
+
+/// Exit code for a finished run
+pub fn exit_code(reports: &[TaskReport]) -> i32 {
+    debug!(count = reports.len(), "exit_code: called");
+    if reports.iter().any(|r| r.outcome == TaskOutcome::Interrupted) {
+        EXIT_INTERRUPTED
+    } else if reports.iter().all(|r| r.outcome.is_success()) {
+        EXIT_SUCCESS
+    } else {
+        EXIT_FAILURE
+    }
+}
+
+/// One progress line for an event (None for events too chatty to print)
+fn describe(event: &Event) -> Option<String> {
+    let line = match event {
+        Event::LoopStarted { loop_type, .. } => format!("started {} loop", loop_type),
+        Event::IterationStarted { iteration, .. } => format!("iteration {}", iteration),
+        Event::IterationCompleted { iteration, outcome, .. } => match outcome {
+            IterationOutcome::ValidationPassed => format!("iteration {}: validation passed", iteration),
+            IterationOutcome::ValidationFailed { exit_code } => {
+                format!("iteration {}: validation failed (exit {})", iteration, exit_code)
+            }
+            IterationOutcome::MaxTurnsReached => format!("iteration {}: max turns reached", iteration),
+            IterationOutcome::ToolError { tool, error } => {
+                format!("iteration {}: {} error: {}", iteration, tool, error)
+            }
+            IterationOutcome::LlmError { error, code } => match code {
+                Some(code) => format!("iteration {}: LLM error [{}]: {}", iteration, code, error),
+                None => format!("iteration {}: LLM error: {}", iteration, error),
+            },
+            IterationOutcome::TimedOut { cause } => format!("iteration {}: {}", iteration, cause),
+        },
+        Event::ToolCallStarted {
+            tool_name,
+            tool_args_summary,
+            ..
+        } => format!("  {} {}", tool_name, tool_args_summary),
+        Event::ValidationStarted { command, .. } => format!("  validating: {}", command),
+        Event::LoopStuck {
+            unchanged_iterations,
+            action,
+            ..
+        } => format!(
+            "stuck: no progress for {} iterations ({})",
+            unchanged_iterations, action
+        ),
+        Event::OverrideChanged { setting, from, to, .. } => format!("  {}: {} -> {}", setting, from, to),
+        Event::RateLimited { retry_after_ms, .. } => format!("rate limited, retrying in {}ms", retry_after_ms),
+        Event::Error { context, message, .. } => format!("error: {}: {}", context, message),
+        Event::Warning { context, message, .. } => format!("warning: {}: {}", context, message),
+        _ => return None,
+    };
+    Some(line)
+}
+
+/// Print events from all loops, each prefixed with its task's name
+fn spawn_printer(mut rx: broadcast::Receiver<Event>, names: HashMap<String, String>) -> tokio::task::JoinHandle<()> {
+    let width = names.values().map(|n| n.chars().count()).max().unwrap_or(0);
+    tokio::spawn(async move {
+        loop {
+            match rx.recv().await {
+                Ok(event) => {
+                    if let Some(line) = describe(&event) {
+                        let id = event.execution_id();
+                        let name = names.get(id).map(String::as_str).unwrap_or(id);
+                        println!("[{:<width$}] {}", name, line, width = width);
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    debug!(skipped, "run_many printer: lagged");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    })
+}
+
+/// Everything a task needs to run, shared across tasks
+struct Shared {
+    loop_configs: HashMap<String, LoopConfig>,
+    llm: Arc<dyn LlmClient>,
+    worktrees: WorktreeManager,
+    repo_root: PathBuf,
+    bus: EventBus,
+    options: RunManyOptions,
+}
+
+/// Create the task's worktree and run its loop to the end
+async fn run_task(shared: Arc<Shared>, exec: LoopExecution) -> (Option<String>, TaskOutcome) {
+    debug!(exec_id = %exec.id, "run_task: called");
+    let worktree = match shared.worktrees.open_or_create(&exec.id).await {
+        Ok(info) => info,
+        Err(e) => {
+            return (
+                None,
+                TaskOutcome::Failed {
+                    reason: format!("failed to create worktree: {}", e),
+                },
+            );
+        }
+    };
+    let branch = Some(worktree.branch.clone());
+
+    let Some(mut loop_config) = shared.loop_configs.get(&exec.loop_type).cloned() else {
+        return (
+            branch,
+            TaskOutcome::Failed {
+                reason: format!("no config for loop type {}", exec.loop_type),
+            },
+        );
+    };
+    if let Some(max) = shared.options.max_iterations {
+        loop_config.max_iterations = max;
+    }
+
+    let mut engine = LoopEngine::new(exec.id.clone(), loop_config, shared.llm.clone(), worktree.path)
+        .with_execution_context(exec.context.clone())
+        .with_acceptance(exec.acceptance.clone())
+        .with_repo_root(shared.repo_root.clone())
+        .with_event_emitter(shared.bus.emitter_for(&exec.id))
+        .with_command_env(shared.options.command_env.clone());
+
+    let outcome = match engine.run().await {
+        Ok(IterationResult::Complete { iterations }) => TaskOutcome::Complete { iterations },
+        Ok(IterationResult::Error { message, .. }) => TaskOutcome::Failed { reason: message },
+        Ok(IterationResult::Stuck {
+            action,
+            unchanged_iterations,
+        }) => TaskOutcome::Stuck {
+            reason: format!("no progress for {} iterations ({})", unchanged_iterations, action),
+        },
+        Ok(IterationResult::Interrupted { reason }) | Ok(IterationResult::Blocked { reason }) => {
+            TaskOutcome::Failed { reason }
+        }
+        Ok(other) => TaskOutcome::Failed {
+            reason: format!("loop ended unexpectedly: {:?}", other),
+        },
+        Err(e) => TaskOutcome::Failed { reason: e.to_string() },
+    };
+    debug!(exec_id = %exec.id, ?outcome, "run_task: finished");
+    (branch, outcome)
+}
+
+/// Run all executions to completion, at most `options.max_parallel` at a time
+///
+/// `executions` must be in dependency order (as returned by
+/// [`crate::batch::BatchManifest::to_executions`]). Reports come back in the
+/// same order.
+pub async fn run_many(
+    executions: Vec<LoopExecution>,
+    loop_configs: HashMap<String, LoopConfig>,
+    llm: Arc<dyn LlmClient>,
+    worktrees: WorktreeManager,
+    repo_root: PathBuf,
+    options: RunManyOptions,
+) -> Result<Vec<TaskReport>> {
+    debug!(
+        count = executions.len(),
+        max_parallel = options.max_parallel,
+        "run_many: called"
+    );
+    let max_parallel = options.max_parallel.max(1);
+    let bus = EventBus::with_default_capacity();
+    let names: HashMap<String, String> = executions
+        .iter()
+        .map(|e| (e.id.clone(), e.title.clone().unwrap_or_else(|| e.id.clone())))
+        .collect();
+    let printer = spawn_printer(bus.subscribe(), names.clone());
+    let shared = Arc::new(Shared {
+        loop_configs,
+        llm,
+        worktrees,
+        repo_root,
+        bus,
+        options,
+    });
+
+    let order: Vec<String> = executions.iter().map(|e| e.id.clone()).collect();
+    let mut pending = executions;
+    let mut running: JoinSet<(Option<String>, TaskOutcome)> = JoinSet::new();
+    // Tokio task -> (execution ID, start time), so a panicked task can still be reported
+    let mut tasks: HashMap<tokio::task::Id, (String, Instant)> = HashMap::new();
+    let mut finished: HashMap<String, bool> = HashMap::new();
+    let mut reports: HashMap<String, TaskReport> = HashMap::new();
+    let mut loop_types: HashMap<String, String> = pending.iter().map(|e| (e.id.clone(), e.loop_type.clone())).collect();
+    let mut report = |id: String, branch: Option<String>, outcome: TaskOutcome, duration: Duration| {
+        let name = names.get(&id).cloned().unwrap_or_else(|| id.clone());
+        let loop_type = loop_types.remove(&id).unwrap_or_default();
+        println!("[{}] {}", name, outcome);
+        reports.insert(
+            id.clone(),
+            TaskReport {
+                exec_id: id,
+                name,
+                loop_type,
+                branch,
+                outcome,
+                duration,
+            },
+        );
+    };
+
+    let mut interrupted = false;
+    loop {
+        // Skip what can never run, then start what can while there are free slots
+        let mut i = 0;
+        while i < pending.len() {
+            match readiness(&pending[i].deps, &finished) {
+                Readiness::Blocked(dep) => {
+                    let exec = pending.remove(i);
+                    let dependency = names.get(&dep).cloned().unwrap_or(dep);
+                    finished.insert(exec.id.clone(), false);
+                    report(exec.id, None, TaskOutcome::Skipped { dependency }, Duration::ZERO);
+                    // An earlier entry may depend on this one; rescan
+                    i = 0;
+                }
+                Readiness::Ready if running.len() < max_parallel => {
+                    let exec = pending.remove(i);
+                    debug!(exec_id = %exec.id, "run_many: starting task");
+                    let id = exec.id.clone();
+                    let handle = running.spawn(run_task(shared.clone(), exec));
+                    tasks.insert(handle.id(), (id, Instant::now()));
+                }
+                _ => i += 1,
+            }
+        }
+
+        if running.is_empty() {
+            break;
+        }
+        tokio::select! {
+            joined = running.join_next_with_id() => {
+                let (task_id, branch, outcome) = match joined {
+                    Some(Ok((task_id, (branch, outcome)))) => (task_id, branch, outcome),
+                    Some(Err(e)) => {
+                        debug!(error = %e, "run_many: task panicked");
+                        (e.id(), None, TaskOutcome::Failed { reason: format!("loop panicked: {}", e) })
+                    }
+                    None => break,
+                };
+                if let Some((id, started)) = tasks.remove(&task_id) {
+                    finished.insert(id.clone(), outcome.is_success());
+                    report(id, branch, outcome, started.elapsed());
+                }
+            }
+            _ = tokio::signal::ctrl_c() => {
+                debug!("run_many: interrupted");
+                eprintln!("Interrupted; stopping {} running loops", running.len());
+                running.abort_all();
+                interrupted = true;
+                break;
+            }
+        }
+    }
+    if interrupted {
+        while running.join_next().await.is_some() {}
+    }
+
+    // Close the bus so the printer drains and exits
+    drop(shared);
+    let _ = printer.await;
+
+    let reports: Vec<TaskReport> = order
+        .into_iter()
+        .map(|id| {
+            reports.remove(&id).unwrap_or_else(|| TaskReport {
+                name: names.get(&id).cloned().unwrap_or_else(|| id.clone()),
+                loop_type: loop_types.remove(&id).unwrap_or_default(),
+                exec_id: id,
+                branch: None,
+                outcome: TaskOutcome::Interrupted,
+                duration: Duration::ZERO,
+            })
+        })
+        .collect();
+    debug!(count = reports.len(), "run_many: complete");
+    Ok(reports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(outcome: TaskOutcome) -> TaskReport {
+        TaskReport {
+            exec_id: "id".to_string(),
+            name: "name".to_string(),
+            loop_type: "ralph".to_string(),
+            branch: None,
+            outcome,
+            duration: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_readiness() {
+        let finished = HashMap::from([("a".to_string(), true), ("b".to_string(), false)]);
+        assert_eq!(readiness(&[], &finished), Readiness::Ready);
+        assert_eq!(readiness(&["a".to_string()], &finished), Readiness::Ready);
+        assert_eq!(
+            readiness(&["a".to_string(), "c".to_string()], &finished),
+            Readiness::Waiting
+        );
+        assert_eq!(
+            readiness(&["c".to_string(), "b".to_string()], &finished),
+            Readiness::Blocked("b".to_string())
+        );
+    }
+
+    #[test]
+    fn test_exit_code() {
+        let complete = report(TaskOutcome::Complete { iterations: 3 });
+        let failed = report(TaskOutcome::Failed {
+            reason: "boom".to_string(),
+        });
+        let skipped = report(TaskOutcome::Skipped {
+            dependency: "a".to_string(),
+        });
+        assert_eq!(exit_code(&[complete.clone(), complete.clone()]), EXIT_SUCCESS);
+        assert_eq!(exit_code(&[complete.clone(), failed.clone()]), EXIT_FAILURE);
+        assert_eq!(exit_code(&[complete.clone(), skipped]), EXIT_FAILURE);
+        assert_eq!(exit_code(&[failed, report(TaskOutcome::Interrupted)]), EXIT_INTERRUPTED);
+    }
+
+    #[test]
+    fn test_describe() {
+        let event = Event::IterationCompleted {
+            execution_id: "x".to_string(),
+            iteration: 2,
+            outcome: IterationOutcome::ValidationFailed { exit_code: 101 },
+        };
+        assert_eq!(
+            describe(&event).as_deref(),
+            Some("iteration 2: validation failed (exit 101)")
+        );
+        let token = Event::TokenReceived {
+            execution_id: "x".to_string(),
+            iteration: 2,
+            token: "hi".to_string(),
+        };
+        assert_eq!(describe(&token), None);
+    }
+}
@@ -1,10 +1,12 @@
 //! Scheduler configuration
 
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::time::Duration;
 use tracing::debug;
 
-use crate::domain::Priority;
+use super::fairness::{self, FairnessPolicy, QueueCandidate};
+use crate::domain::{LoopExecution, Priority};
 
 /// Scheduler configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +26,30 @@ pub struct SchedulerConfig {
     /// Default priority for new requests
     #[serde(default)]
     pub default_priority: Priority,
+
+    /// Adjust concurrency to the rate limit headers the provider reports
+    #[serde(default = "default_adaptive")]
+    pub adaptive: bool,
+
+    /// Seconds of waiting that raise a queued request's priority one level (0 disables aging)
+    #[serde(default = "default_aging_secs")]
+    pub aging_secs: u64,
+
+    /// How queued requests are ordered
+    #[serde(default)]
+    pub fairness: FairnessPolicy,
+
+    /// Share of slots per loop type under the weighted policy (unlisted types weigh 1)
+    #[serde(default)]
+    pub weights: BTreeMap<String, u32>,
+
+    /// Consecutive provider errors that open the circuit breaker (0 disables it)
+    #[serde(default = "default_breaker_threshold")]
+    pub breaker_threshold: u32,
+
+    /// Seconds between probes of the provider while the circuit is open
+    #[serde(default = "default_breaker_probe_secs")]
+    pub breaker_probe_secs: u64,
 }
 
 fn default_max_concurrent() -> usize {
@@ -41,6 +67,26 @@ fn default_rate_window_secs() -> u64 {
     60
 }
 
+fn default_adaptive() -> bool {
+    debug!("default_adaptive: called");
+    true
+}
+
+fn default_aging_secs() -> u64 {
+    debug!("default_aging_secs: called");
+    600
+}
+
+fn default_breaker_threshold() -> u32 {
+    debug!("default_breaker_threshold: called");
+    5
+}
+
+fn default_breaker_probe_secs() -> u64 {
+    debug!("default_breaker_probe_secs: called");
+    30
+}
+
 impl Default for SchedulerConfig {
     fn default() -> Self {
         debug!("SchedulerConfig::default: called");
@@ -49,6 +95,12 @@ impl Default for SchedulerConfig {
             max_requests_per_window: 50,
             rate_window_secs: 60,
             default_priority: Priority::Normal,
+            adaptive: true,
+            aging_secs: 600,
+            fairness: FairnessPolicy::Strict,
+            weights: BTreeMap::new(),
+            breaker_threshold: 5,
+            breaker_probe_secs: 30,
         }
     }
 }
@@ -59,6 +111,40 @@ impl SchedulerConfig {
         debug!(%self.rate_window_secs, "SchedulerConfig::rate_window: called");
         Duration::from_secs(self.rate_window_secs)
     }
+
+    /// How often to probe the provider while the circuit breaker is open
+    pub fn breaker_probe_interval(&self) -> Duration {
+        debug!(%self.breaker_probe_secs, "SchedulerConfig::breaker_probe_interval: called");
+        Duration::from_secs(self.breaker_probe_secs.max(1))
+    }
+
+    /// Order `candidates` for the next slots under this config's fairness policy and aging
+    pub fn queue_order(&self, candidates: &[QueueCandidate<'_>], running: &HashMap<String, usize>) -> Vec<usize> {
+        debug!(count = candidates.len(), "SchedulerConfig::queue_order: called");
+        fairness::queue_order(candidates, running, self.fairness, self.aging_secs, &self.weights)
+    }
+
+    /// Order in which pending executions get picked up (indices into `pending`)
+    ///
+    /// Wait time counts from each execution's creation; `running` counts the
+    /// executions each loop type already runs.
+    pub fn pending_order(
+        &self,
+        pending: &[LoopExecution],
+        running: &HashMap<String, usize>,
+        now_ms: i64,
+    ) -> Vec<usize> {
+        debug!(count = pending.len(), now_ms, "SchedulerConfig::pending_order: called");
+        let candidates: Vec<QueueCandidate<'_>> = pending
+            .iter()
+            .map(|exec| QueueCandidate {
+                priority: exec.priority,
+                waited: Duration::from_millis(now_ms.saturating_sub(exec.created_at).max(0) as u64),
+                loop_type: &exec.loop_type,
+            })
+            .collect();
+        self.queue_order(&candidates, running)
+    }
 }
 
 #[cfg(test)]
@@ -72,6 +158,9 @@ mod tests {
         assert_eq!(config.max_requests_per_window, 50);
         assert_eq!(config.rate_window_secs, 60);
         assert_eq!(config.default_priority, Priority::Normal);
+        assert!(config.adaptive);
+        assert_eq!(config.aging_secs, 600);
+        assert_eq!(config.fairness, FairnessPolicy::Strict);
     }
 
     #[test]
@@ -1,21 +1,24 @@
 //! Scheduler implementation
 
-use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::collections::{HashMap, VecDeque};
 use std::time::{Duration, Instant};
 
 use eyre::eyre;
 use tokio::sync::{Mutex, Notify};
-use tracing::{debug, warn};
+use tracing::{debug, info, warn};
 
+use crate::clock::{ClockRef, SystemClock};
 use crate::domain::Priority;
+use crate::llm::RateLimitStatus;
 
 use super::config::SchedulerConfig;
+use super::fairness::{QueueCandidate, effective_priority};
 use super::queue::{QueueEntry, QueueEntryStatus, QueueState, ScheduleResult, ScheduledRequest, SchedulerStats};
 
 /// Internal state protected by mutex
 struct SchedulerInner {
-    /// Priority queue of waiting requests
-    queue: BinaryHeap<ScheduledRequest>,
+    /// Waiting requests in submission order (see `queue_order` for service order)
+    queue: Vec<ScheduledRequest>,
 
     /// Currently running requests
     running: HashMap<String, ScheduledRequest>,
@@ -25,6 +28,84 @@ struct SchedulerInner {
 
     /// Statistics
     stats: SchedulerStats,
+
+    /// Concurrency allowed right now (adapted from provider rate limit headers)
+    concurrency_limit: usize,
+
+    /// No new requests start before this (the provider reported no capacity left)
+    paused_until: Option<Instant>,
+
+    /// Provider errors since the last successful call
+    consecutive_provider_errors: u32,
+
+    /// Whether the provider circuit breaker is open (the provider looks down)
+    circuit_open: bool,
+}
+
+/// Below this fraction of remaining provider capacity, halve concurrency
+const LOW_CAPACITY: f64 = 0.1;
+
+/// Below this fraction, stop starting requests until the provider's window resets
+const EXHAUSTED_CAPACITY: f64 = 0.02;
+
+/// At or above this fraction, allow one more concurrent request (up to the configured max)
+const HIGH_CAPACITY: f64 = 0.5;
+
+/// How often a queued waiter rechecks for a slot if it misses a notification
+const QUEUE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+impl SchedulerInner {
+    /// Whether a pause from exhausted provider capacity is still in effect
+    fn paused(&mut self, now: Instant) -> Option<Duration> {
+        match self.paused_until {
+            Some(until) if until > now => Some(until - now),
+            Some(_) => {
+                self.paused_until = None;
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Queue indices in the order the fairness policy would serve them
+    fn queue_order(&self, config: &SchedulerConfig, now: Instant) -> Vec<usize> {
+        let mut running: HashMap<String, usize> = HashMap::new();
+        for request in self.running.values() {
+            *running.entry(request.loop_type.clone()).or_default() += 1;
+        }
+        let candidates: Vec<QueueCandidate<'_>> = self
+            .queue
+            .iter()
+            .map(|r| QueueCandidate {
+                priority: r.priority,
+                waited: now.saturating_duration_since(r.submitted_at),
+                loop_type: &r.loop_type,
+            })
+            .collect();
+        config.queue_order(&candidates, &running)
+    }
+
+    /// Start queued requests while there is room under the concurrency limit
+    fn promote_queued(&mut self, config: &SchedulerConfig, now: Instant) {
+        if self.paused(now).is_some() {
+            debug!("SchedulerInner::promote_queued: paused, not promoting");
+            return;
+        }
+        while self.running.len() < self.concurrency_limit {
+            // Re-rank each time: a promotion changes the weighted shares
+            let Some(&index) = self.queue_order(config, now).first() else {
+                debug!("SchedulerInner::promote_queued: queue empty, nothing to promote");
+                break;
+            };
+            let mut next = self.queue.remove(index);
+            debug!(exec_id = %next.exec_id, ?next.priority, "Promoting from queue");
+            next.started_at = Some(now);
+            self.running.insert(next.exec_id.clone(), next);
+            self.request_times.push_back(now);
+            self.stats.total_scheduled += 1;
+            self.stats.peak_concurrent = self.stats.peak_concurrent.max(self.running.len());
+        }
+    }
 }
 
 /// The Scheduler manages loop execution with priority queuing,
@@ -33,6 +114,7 @@ pub struct Scheduler {
     config: SchedulerConfig,
     inner: Mutex<SchedulerInner>,
     notify: Notify,
+    clock: ClockRef,
 }
 
 impl Scheduler {
@@ -40,20 +122,45 @@ impl Scheduler {
     pub fn new(config: SchedulerConfig) -> Self {
         debug!(?config, "Scheduler::new: called");
         Self {
-            config,
             inner: Mutex::new(SchedulerInner {
-                queue: BinaryHeap::new(),
+                queue: Vec::new(),
                 running: HashMap::new(),
                 request_times: VecDeque::new(),
                 stats: SchedulerStats::default(),
+                concurrency_limit: config.max_concurrent,
+                paused_until: None,
+                consecutive_provider_errors: 0,
+                circuit_open: false,
             }),
+            config,
             notify: Notify::new(),
+            clock: SystemClock::shared(),
         }
     }
 
+    /// Use `clock` for rate windows, waits and sleeps (e.g. a `ManualClock` in tests)
+    pub fn with_clock(mut self, clock: ClockRef) -> Self {
+        debug!("Scheduler::with_clock: called");
+        self.clock = clock;
+        self
+    }
+
+    /// The configuration this scheduler was created with
+    pub fn config(&self) -> &SchedulerConfig {
+        &self.config
+    }
+
     /// Attempt to schedule a request
     pub async fn schedule(&self, exec_id: &str, priority: Priority) -> ScheduleResult {
         debug!(%exec_id, ?priority, "Scheduler::schedule: called");
+        self.schedule_as(exec_id, priority, "").await
+    }
+
+    /// Attempt to schedule a request on behalf of a loop type
+    ///
+    /// The loop type only matters under the weighted fairness policy.
+    pub async fn schedule_as(&self, exec_id: &str, priority: Priority, loop_type: &str) -> ScheduleResult {
+        debug!(%exec_id, ?priority, %loop_type, "Scheduler::schedule_as: called");
         let mut inner = self.inner.lock().await;
 
         // Check if already running
@@ -72,7 +179,7 @@ impl Scheduler {
             };
         }
 
-        let now = Instant::now();
+        let now = self.clock.instant();
 
         // Prune old request times (outside rate window)
         let window_start = now - self.config.rate_window();
@@ -94,8 +201,14 @@ impl Scheduler {
             return ScheduleResult::RateLimited { retry_after };
         }
 
+        // Check for a pause after the provider reported its capacity exhausted
+        if let Some(retry_after) = inner.paused(now) {
+            debug!(%exec_id, ?retry_after, "Scheduler::schedule: paused for provider rate limit");
+            return ScheduleResult::RateLimited { retry_after };
+        }
+
         // Check concurrent limit
-        if inner.running.len() < self.config.max_concurrent {
+        if inner.running.len() < inner.concurrency_limit {
             debug!(%exec_id, "Scheduler::schedule: under concurrent limit, running immediately");
             // Can run immediately
             let request = ScheduledRequest {
@@ -103,6 +216,7 @@ impl Scheduler {
                 priority,
                 submitted_at: now,
                 started_at: Some(now),
+                loop_type: loop_type.to_string(),
             };
 
             inner.running.insert(exec_id.to_string(), request);
@@ -121,28 +235,29 @@ impl Scheduler {
             priority,
             submitted_at: now,
             started_at: None,
+            loop_type: loop_type.to_string(),
         };
 
         inner.queue.push(request);
         inner.stats.peak_queue_depth = inner.stats.peak_queue_depth.max(inner.queue.len());
 
-        // Calculate position (approximate since heap doesn't have index)
+        let index = inner.queue.len() - 1;
         let position = inner
-            .queue
+            .queue_order(&self.config, now)
             .iter()
-            .filter(|r| r.priority > priority || (r.priority == priority && r.submitted_at < now))
-            .count()
+            .position(|&i| i == index)
+            .unwrap_or(index)
             + 1;
 
         // Estimate wait time based on average completion time
-        let avg_completion_ms = if inner.stats.total_completed > 0 {
-            inner.stats.total_wait_time_ms / inner.stats.total_completed
-        } else {
-            30_000 // Default 30s estimate
-        };
+        let avg_completion_ms = inner
+            .stats
+            .total_wait_time_ms
+            .checked_div(inner.stats.total_completed)
+            .unwrap_or(30_000); // Default 30s estimate
 
         let estimated_wait =
-            Duration::from_millis((position as u64 * avg_completion_ms) / self.config.max_concurrent as u64);
+            Duration::from_millis((position as u64 * avg_completion_ms) / inner.concurrency_limit as u64);
 
         debug!(exec_id, position, ?estimated_wait, "Queued");
         ScheduleResult::Queued {
@@ -154,20 +269,47 @@ impl Scheduler {
     /// Wait until a slot is available for this request
     pub async fn wait_for_slot(&self, exec_id: &str, priority: Priority) -> eyre::Result<()> {
         debug!(%exec_id, ?priority, "Scheduler::wait_for_slot: called");
+        self.wait_for_slot_as(exec_id, priority, "").await
+    }
+
+    /// Wait until a slot is available for a request made on behalf of a loop type
+    pub async fn wait_for_slot_as(&self, exec_id: &str, priority: Priority, loop_type: &str) -> eyre::Result<()> {
+        debug!(%exec_id, ?priority, %loop_type, "Scheduler::wait_for_slot_as: called");
+        let waiting_since = self.clock.instant();
         loop {
-            match self.schedule(exec_id, priority).await {
+            match self.schedule_as(exec_id, priority, loop_type).await {
                 ScheduleResult::Ready => {
                     debug!(%exec_id, "Scheduler::wait_for_slot: ready branch");
+                    self.inner
+                        .lock()
+                        .await
+                        .stats
+                        .record_wait(self.clock.instant().duration_since(waiting_since));
                     return Ok(());
                 }
                 ScheduleResult::Queued { .. } => {
                     debug!(%exec_id, "Scheduler::wait_for_slot: queued branch, waiting for notification");
-                    // Wait for notification that a slot opened
-                    self.notify.notified().await;
+                    // Wait until this request is promoted into a running slot
+                    loop {
+                        let _ = tokio::time::timeout(QUEUE_POLL_INTERVAL, self.notify.notified()).await;
+                        let mut inner = self.inner.lock().await;
+                        inner.promote_queued(&self.config, self.clock.instant());
+                        if inner.running.contains_key(exec_id) {
+                            debug!(%exec_id, "Scheduler::wait_for_slot: promoted from queue");
+                            inner
+                                .stats
+                                .record_wait(self.clock.instant().duration_since(waiting_since));
+                            return Ok(());
+                        }
+                        if !inner.queue.iter().any(|r| r.exec_id == exec_id) {
+                            debug!(%exec_id, "Scheduler::wait_for_slot: no longer queued");
+                            return Err(eyre!("Schedule cancelled"));
+                        }
+                    }
                 }
                 ScheduleResult::RateLimited { retry_after } => {
                     debug!(%exec_id, ?retry_after, "Scheduler::wait_for_slot: rate limited branch, sleeping");
-                    tokio::time::sleep(retry_after).await;
+                    self.clock.sleep(retry_after).await;
                 }
                 ScheduleResult::Rejected { reason } => {
                     debug!(%exec_id, %reason, "Scheduler::wait_for_slot: rejected branch");
@@ -186,7 +328,7 @@ impl Scheduler {
             debug!(%exec_id, "Scheduler::complete: found in running, removing");
             if let Some(started) = request.started_at {
                 debug!(%exec_id, "Scheduler::complete: has started_at, calculating wait time");
-                let wait_time = started.elapsed().as_millis() as u64;
+                let wait_time = self.clock.instant().duration_since(started).as_millis() as u64;
                 inner.stats.total_wait_time_ms += wait_time;
             }
             inner.stats.total_completed += 1;
@@ -195,16 +337,8 @@ impl Scheduler {
             debug!(%exec_id, "Scheduler::complete: not found in running");
         }
 
-        // Try to start next queued request
-        if let Some(mut next) = inner.queue.pop() {
-            debug!(exec_id = %next.exec_id, ?next.priority, "Scheduler::complete: promoting from queue");
-            debug!(exec_id = %next.exec_id, ?next.priority, "Promoting from queue");
-            next.started_at = Some(Instant::now());
-            inner.running.insert(next.exec_id.clone(), next);
-            inner.request_times.push_back(Instant::now());
-        } else {
-            debug!("Scheduler::complete: queue empty, nothing to promote");
-        }
+        // Try to start queued requests
+        inner.promote_queued(&self.config, self.clock.instant());
 
         drop(inner);
 
@@ -220,30 +354,129 @@ impl Scheduler {
         let mut inner = self.inner.lock().await;
 
         // Fill the rate window to block new requests
-        let now = Instant::now();
+        let now = self.clock.instant();
         while inner.request_times.len() < self.config.max_requests_per_window as usize {
             debug!("Scheduler::handle_rate_limit: filling rate window");
             inner.request_times.push_back(now);
         }
 
         inner.stats.total_rate_limited += 1;
+        if self.config.adaptive {
+            inner.concurrency_limit = (inner.concurrency_limit / 2).max(1);
+            debug!(
+                limit = inner.concurrency_limit,
+                "Scheduler::handle_rate_limit: halved concurrency"
+            );
+        }
 
         drop(inner);
 
         // Sleep for retry period
         debug!(?retry_after, "Scheduler::handle_rate_limit: sleeping for retry period");
-        tokio::time::sleep(retry_after).await;
+        self.clock.sleep(retry_after).await;
+    }
+
+    /// Adapt to the capacity a provider reported in its rate limit headers
+    ///
+    /// Halves concurrency when little capacity is left and pauses new requests
+    /// until the provider's window resets when it is all but exhausted; adds
+    /// back one slot at a time while capacity is plentiful.
+    pub async fn observe_rate_limits(&self, status: &RateLimitStatus) {
+        debug!(?status, "Scheduler::observe_rate_limits: called");
+        if !self.config.adaptive {
+            debug!("Scheduler::observe_rate_limits: adaptive scheduling disabled");
+            return;
+        }
+        let Some(fraction) = status.remaining_fraction() else {
+            debug!("Scheduler::observe_rate_limits: no remaining capacity reported");
+            return;
+        };
+
+        let mut inner = self.inner.lock().await;
+        let before = inner.concurrency_limit;
+        if fraction < LOW_CAPACITY {
+            inner.concurrency_limit = (before / 2).max(1);
+            inner.stats.total_backoffs += 1;
+            if fraction < EXHAUSTED_CAPACITY
+                && let Some(reset) = status.reset_after()
+            {
+                let until = status.observed_at + reset.min(self.config.rate_window());
+                inner.paused_until = Some(inner.paused_until.map_or(until, |p| p.max(until)));
+                info!(?reset, "Provider rate limit nearly exhausted, pausing new requests");
+            }
+        } else if fraction >= HIGH_CAPACITY && before < self.config.max_concurrent {
+            inner.concurrency_limit = before + 1;
+            inner.promote_queued(&self.config, self.clock.instant());
+        }
+        if inner.concurrency_limit != before {
+            info!(
+                from = before,
+                to = inner.concurrency_limit,
+                remaining = fraction,
+                "Adjusted concurrency to provider rate limits"
+            );
+        }
+
+        drop(inner);
+        self.notify.notify_waiters();
+    }
+
+    /// Record a successful provider call; true if it closed an open circuit
+    pub async fn record_provider_success(&self) -> bool {
+        debug!("Scheduler::record_provider_success: called");
+        let mut inner = self.inner.lock().await;
+        inner.consecutive_provider_errors = 0;
+        let closed = std::mem::take(&mut inner.circuit_open);
+        if closed {
+            info!("Provider recovered, closing circuit breaker");
+        }
+        closed
+    }
+
+    /// Record a provider outage error (5xx, network, timeout); true if it opened the circuit
+    ///
+    /// After `breaker_threshold` consecutive errors the circuit opens and stays
+    /// open until a call succeeds again.
+    pub async fn record_provider_error(&self) -> bool {
+        debug!("Scheduler::record_provider_error: called");
+        if self.config.breaker_threshold == 0 {
+            debug!("Scheduler::record_provider_error: circuit breaker disabled");
+            return false;
+        }
+        let mut inner = self.inner.lock().await;
+        inner.consecutive_provider_errors += 1;
+        if inner.circuit_open || inner.consecutive_provider_errors < self.config.breaker_threshold {
+            debug!(
+                errors = inner.consecutive_provider_errors,
+                "Scheduler::record_provider_error: circuit unchanged"
+            );
+            return false;
+        }
+        warn!(
+            errors = inner.consecutive_provider_errors,
+            "Provider failing repeatedly, opening circuit breaker"
+        );
+        inner.circuit_open = true;
+        true
+    }
+
+    /// Whether the provider circuit breaker is open
+    pub async fn provider_circuit_open(&self) -> bool {
+        debug!("Scheduler::provider_circuit_open: called");
+        self.inner.lock().await.circuit_open
     }
 
     /// Get current queue state for TUI
     pub async fn queue_state(&self) -> QueueState {
         debug!("Scheduler::queue_state: called");
-        let inner = self.inner.lock().await;
+        let mut inner = self.inner.lock().await;
+        let paused = inner.paused(self.clock.instant()).is_some();
 
         QueueState {
             running: inner.running.len(),
             queued: inner.queue.len(),
-            rate_limited: inner.request_times.len() >= self.config.max_requests_per_window as usize,
+            rate_limited: paused || inner.request_times.len() >= self.config.max_requests_per_window as usize,
+            concurrency_limit: inner.concurrency_limit,
             stats: inner.stats.clone(),
         }
     }
@@ -252,8 +485,9 @@ impl Scheduler {
     pub async fn queue_details(&self) -> Vec<QueueEntry> {
         debug!("Scheduler::queue_details: called");
         let inner = self.inner.lock().await;
-        let now = Instant::now();
+        let now = self.clock.instant();
 
+        // Running entries by priority, then queued entries in service order
         let mut entries: Vec<_> = inner
             .running
             .values()
@@ -262,16 +496,25 @@ impl Scheduler {
                 priority: r.priority,
                 status: QueueEntryStatus::Running,
                 wait_time: r.started_at.map(|s| now - s),
+                effective_priority: r.priority,
+                position: None,
             })
-            .chain(inner.queue.iter().map(|r| QueueEntry {
+            .collect();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.priority));
+
+        let order = inner.queue_order(&self.config, now);
+        entries.extend(order.iter().enumerate().map(|(position, &i)| {
+            let r = &inner.queue[i];
+            let waited = now.saturating_duration_since(r.submitted_at);
+            QueueEntry {
                 exec_id: r.exec_id.clone(),
                 priority: r.priority,
                 status: QueueEntryStatus::Queued,
-                wait_time: Some(now - r.submitted_at),
-            }))
-            .collect();
-
-        entries.sort_by(|a, b| b.priority.cmp(&a.priority));
+                wait_time: Some(waited),
+                effective_priority: effective_priority(r.priority, waited, self.config.aging_secs),
+                position: Some(position + 1),
+            }
+        }));
         entries
     }
 
@@ -296,8 +539,7 @@ impl Scheduler {
         // Remove from queue
         debug!(%exec_id, "Scheduler::cancel: removing from queue");
         let original_len = inner.queue.len();
-        let queue_vec: Vec<_> = inner.queue.drain().filter(|r| r.exec_id != exec_id).collect();
-        inner.queue = queue_vec.into_iter().collect();
+        inner.queue.retain(|r| r.exec_id != exec_id);
 
         let removed = original_len != inner.queue.len();
         if removed {
@@ -372,6 +614,40 @@ mod tests {
         assert_eq!(running[0].exec_id, "high");
     }
 
+    #[tokio::test]
+    async fn test_aged_request_overtakes_newer_higher_priority() {
+        let clock = std::sync::Arc::new(crate::clock::ManualClock::new(0));
+        let scheduler = Scheduler::new(SchedulerConfig {
+            max_concurrent: 1,
+            aging_secs: 60,
+            ..Default::default()
+        })
+        .with_clock(clock.clone());
+
+        scheduler.schedule("running", Priority::Normal).await;
+        scheduler.schedule_as("old-low", Priority::Low, "docs").await;
+        clock.advance(Duration::from_secs(130));
+        assert!(matches!(
+            scheduler.schedule_as("new-high", Priority::High, "feature").await,
+            ScheduleResult::Queued { position: 2, .. }
+        ));
+
+        let details = scheduler.queue_details().await;
+        let old = details.iter().find(|e| e.exec_id == "old-low").unwrap();
+        assert_eq!(old.effective_priority, Priority::High);
+        assert_eq!(old.position, Some(1));
+
+        scheduler.complete("running").await;
+        let running: Vec<_> = scheduler
+            .queue_details()
+            .await
+            .into_iter()
+            .filter(|e| e.status == QueueEntryStatus::Running)
+            .map(|e| e.exec_id)
+            .collect();
+        assert_eq!(running, ["old-low"]);
+    }
+
     #[tokio::test]
     async fn test_rate_limiting() {
         let scheduler = Scheduler::new(SchedulerConfig {
@@ -453,4 +729,143 @@ mod tests {
         assert_eq!(stats.total_completed, 2);
         assert_eq!(stats.peak_concurrent, 2);
     }
+
+    fn status(remaining: u64, limit: u64, reset: Duration) -> RateLimitStatus {
+        RateLimitStatus {
+            requests_limit: Some(limit),
+            requests_remaining: Some(remaining),
+            requests_reset: Some(reset),
+            tokens_limit: None,
+            tokens_remaining: None,
+            tokens_reset: None,
+            observed_at: Instant::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_observe_rate_limits_adapts_concurrency() {
+        let scheduler = Scheduler::new(SchedulerConfig {
+            max_concurrent: 4,
+            ..Default::default()
+        });
+
+        scheduler
+            .observe_rate_limits(&status(5, 100, Duration::from_secs(10)))
+            .await;
+        assert_eq!(scheduler.queue_state().await.concurrency_limit, 2);
+        scheduler
+            .observe_rate_limits(&status(5, 100, Duration::from_secs(10)))
+            .await;
+        assert_eq!(scheduler.queue_state().await.concurrency_limit, 1);
+
+        scheduler.schedule("a", Priority::Normal).await;
+        assert!(matches!(
+            scheduler.schedule("b", Priority::Normal).await,
+            ScheduleResult::Queued { .. }
+        ));
+
+        // Plenty of capacity again: one more slot, and the queued request starts
+        scheduler
+            .observe_rate_limits(&status(90, 100, Duration::from_secs(10)))
+            .await;
+        let state = scheduler.queue_state().await;
+        assert_eq!(state.concurrency_limit, 2);
+        assert_eq!(state.running, 2);
+        assert_eq!(state.stats.total_backoffs, 2);
+
+        for _ in 0..5 {
+            scheduler
+                .observe_rate_limits(&status(90, 100, Duration::from_secs(10)))
+                .await;
+        }
+        assert_eq!(scheduler.queue_state().await.concurrency_limit, 4);
+    }
+
+    #[tokio::test]
+    async fn test_observe_rate_limits_pauses_when_exhausted() {
+        let scheduler = Scheduler::new(SchedulerConfig::default());
+
+        scheduler
+            .observe_rate_limits(&status(0, 50, Duration::from_secs(20)))
+            .await;
+        assert!(scheduler.queue_state().await.rate_limited);
+        match scheduler.schedule("a", Priority::Normal).await {
+            ScheduleResult::RateLimited { retry_after } => assert!(retry_after <= Duration::from_secs(20)),
+            other => panic!("expected RateLimited, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_observe_rate_limits_ignored_when_not_adaptive() {
+        let scheduler = Scheduler::new(SchedulerConfig {
+            adaptive: false,
+            ..Default::default()
+        });
+
+        scheduler
+            .observe_rate_limits(&status(0, 50, Duration::from_secs(20)))
+            .await;
+        let state = scheduler.queue_state().await;
+        assert_eq!(state.concurrency_limit, 10);
+        assert!(!state.rate_limited);
+    }
+
+    #[tokio::test]
+    async fn test_provider_circuit_breaker() {
+        let scheduler = Scheduler::new(SchedulerConfig {
+            breaker_threshold: 3,
+            ..Default::default()
+        });
+
+        assert!(!scheduler.record_provider_error().await);
+        assert!(!scheduler.record_provider_error().await);
+        assert!(!scheduler.record_provider_success().await);
+        assert!(!scheduler.record_provider_error().await);
+        assert!(!scheduler.record_provider_error().await);
+        assert!(!scheduler.provider_circuit_open().await);
+
+        assert!(scheduler.record_provider_error().await);
+        assert!(scheduler.provider_circuit_open().await);
+        assert!(!scheduler.record_provider_error().await);
+
+        assert!(scheduler.record_provider_success().await);
+        assert!(!scheduler.provider_circuit_open().await);
+
+        let disabled = Scheduler::new(SchedulerConfig {
+            breaker_threshold: 0,
+            ..Default::default()
+        });
+        for _ in 0..10 {
+            assert!(!disabled.record_provider_error().await);
+        }
+        assert!(!disabled.provider_circuit_open().await);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_slot_returns_once_promoted() {
+        let scheduler = std::sync::Arc::new(Scheduler::new(SchedulerConfig {
+            max_concurrent: 1,
+            ..Default::default()
+        }));
+        scheduler.schedule("first", Priority::Normal).await;
+
+        let waiter = {
+            let scheduler = scheduler.clone();
+            tokio::spawn(async move { scheduler.wait_for_slot("second", Priority::Normal).await })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        scheduler.complete("first").await;
+
+        let result = tokio::time::timeout(Duration::from_secs(5), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(result.is_ok());
+        assert_eq!(scheduler.queue_state().await.running, 1);
+
+        let stats = scheduler.stats().await;
+        assert_eq!(stats.total_waits, 1);
+        assert!(stats.max_queue_wait_ms >= 50);
+        assert_eq!(stats.mean_queue_wait_ms(), Some(stats.max_queue_wait_ms as f64));
+    }
 }
@@ -0,0 +1,196 @@
+//! Queue ordering: priority aging and fairness policies
+//!
+//! Under strict priority a steady stream of high-priority work starves
+//! everything below it. Two mechanisms counter that:
+//!
+//! - **Aging:** every `aging_secs` a request has waited raises its effective
+//!   priority one level (up to `critical`), so old work eventually wins.
+//! - **Fairness policy:** `strict` orders by effective priority, then wait;
+//!   `weighted` hands out slots so each loop type's share of running work
+//!   tracks its weight, ordering by effective priority within a type.
+//!
+//! The same ordering drives the Scheduler queue, the TaskManager's pickup of
+//! pending executions and the queue positions `td exec list` shows.
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::domain::Priority;
+
+/// How queued requests are ordered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FairnessPolicy {
+    /// Highest effective priority first, oldest first within a priority
+    #[default]
+    Strict,
+    /// Fair share of slots per loop type, in proportion to its weight
+    Weighted,
+}
+
+impl std::fmt::Display for FairnessPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Strict => write!(f, "strict"),
+            Self::Weighted => write!(f, "weighted"),
+        }
+    }
+}
+
+impl std::str::FromStr for FairnessPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "strict" => Ok(Self::Strict),
+            "weighted" => Ok(Self::Weighted),
+            _ => Err(format!("Unknown fairness policy: {} (expected strict or weighted)", s)),
+        }
+    }
+}
+
+/// A request waiting for a slot, as seen by the ordering policy
+#[derive(Debug, Clone, Copy)]
+pub struct QueueCandidate<'a> {
+    pub priority: Priority,
+    pub waited: Duration,
+    pub loop_type: &'a str,
+}
+
+/// Priority raised one level per `aging_secs` waited (0 disables aging)
+pub fn effective_priority(priority: Priority, waited: Duration, aging_secs: u64) -> Priority {
+    if aging_secs == 0 {
+        return priority;
+    }
+    let levels = waited.as_secs() / aging_secs;
+    (0..levels).fold(priority, |p, _| match p {
+        Priority::Low => Priority::Normal,
+        Priority::Normal => Priority::High,
+        Priority::High | Priority::Critical => Priority::Critical,
+    })
+}
+
+/// Order in which `candidates` get the next slots (indices into `candidates`)
+///
+/// `running` counts the requests each loop type already runs; only the
+/// weighted policy uses it and `weights` (unlisted types weigh 1).
+pub fn queue_order(
+    candidates: &[QueueCandidate<'_>],
+    running: &HashMap<String, usize>,
+    policy: FairnessPolicy,
+    aging_secs: u64,
+    weights: &BTreeMap<String, u32>,
+) -> Vec<usize> {
+    debug!(count = candidates.len(), %policy, aging_secs, "queue_order: called");
+    // Strict order: effective priority, then longest wait, then submission order
+    let mut strict: Vec<usize> = (0..candidates.len()).collect();
+    strict.sort_by(|&a, &b| {
+        let (ca, cb) = (&candidates[a], &candidates[b]);
+        effective_priority(cb.priority, cb.waited, aging_secs)
+            .cmp(&effective_priority(ca.priority, ca.waited, aging_secs))
+            .then_with(|| cb.waited.cmp(&ca.waited))
+    });
+    if policy == FairnessPolicy::Strict {
+        return strict;
+    }
+
+    // Weighted: repeatedly serve the type furthest below its share, taking its
+    // best request in strict order; ties go to the type whose best request
+    // comes first in strict order
+    let mut load: HashMap<&str, usize> = HashMap::new();
+    let mut order = Vec::with_capacity(candidates.len());
+    while order.len() < candidates.len() {
+        let mut best: Option<(usize, f64)> = None;
+        for &i in strict.iter().filter(|i| !order.contains(*i)) {
+            let loop_type = candidates[i].loop_type;
+            let weight = weights.get(loop_type).copied().unwrap_or(1).max(1) as f64;
+            let used = running.get(loop_type).copied().unwrap_or(0) + load.get(loop_type).copied().unwrap_or(0);
+            let share = used as f64 / weight;
+            if best.is_none_or(|(_, s)| share < s) {
+                best = Some((i, share));
+            }
+        }
+        let Some((i, _)) = best else {
+            break;
+        };
+        *load.entry(candidates[i].loop_type).or_default() += 1;
+        order.push(i);
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(priority: Priority, waited_secs: u64, loop_type: &str) -> QueueCandidate<'_> {
+        QueueCandidate {
+            priority,
+            waited: Duration::from_secs(waited_secs),
+            loop_type,
+        }
+    }
+
+    #[test]
+    fn test_effective_priority_ages() {
+        assert_eq!(
+            effective_priority(Priority::Low, Duration::from_secs(599), 600),
+            Priority::Low
+        );
+        assert_eq!(
+            effective_priority(Priority::Low, Duration::from_secs(1200), 600),
+            Priority::High
+        );
+        assert_eq!(
+            effective_priority(Priority::Low, Duration::from_secs(99_999), 600),
+            Priority::Critical
+        );
+        assert_eq!(
+            effective_priority(Priority::Low, Duration::from_secs(99_999), 0),
+            Priority::Low
+        );
+        assert_eq!("weighted".parse::<FairnessPolicy>(), Ok(FairnessPolicy::Weighted));
+    }
+
+    #[test]
+    fn test_strict_order_lets_aged_requests_through() {
+        let candidates = [
+            candidate(Priority::High, 10, "ralph"),
+            candidate(Priority::Low, 1300, "ralph"),
+            candidate(Priority::High, 20, "ralph"),
+        ];
+        let none = HashMap::new();
+        let weights = BTreeMap::new();
+
+        // Without aging the low request waits behind every high one
+        assert_eq!(
+            queue_order(&candidates, &none, FairnessPolicy::Strict, 0, &weights),
+            [2, 0, 1]
+        );
+        // Waiting 1300s ages it past high; ties break on the longest wait
+        assert_eq!(
+            queue_order(&candidates, &none, FairnessPolicy::Strict, 600, &weights),
+            [1, 2, 0]
+        );
+    }
+
+    #[test]
+    fn test_weighted_order_shares_slots_by_type() {
+        let candidates = [
+            candidate(Priority::Critical, 5, "docs"),
+            candidate(Priority::Critical, 4, "docs"),
+            candidate(Priority::Critical, 3, "docs"),
+            candidate(Priority::Low, 1, "migration"),
+            candidate(Priority::Low, 2, "migration"),
+        ];
+        let weights = BTreeMap::from([("docs".to_string(), 2)]);
+
+        // docs already runs one: 1/2 share vs migration's 0/1
+        let running = HashMap::from([("docs".to_string(), 1)]);
+        let order = queue_order(&candidates, &running, FairnessPolicy::Weighted, 0, &weights);
+        assert_eq!(order, [4, 0, 1, 3, 2]);
+    }
+}
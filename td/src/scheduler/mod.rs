@@ -1,12 +1,15 @@
 //! Scheduler for loop execution
 //!
 //! Manages loop execution with priority queuing, concurrency limits,
-//! and rate limiting in a single component.
+//! and rate limiting in a single component. Queue order follows a fairness
+//! policy with priority aging (see [`fairness`]).
 
 mod config;
 mod core;
+pub mod fairness;
 mod queue;
 
 pub use config::SchedulerConfig;
 pub use core::Scheduler;
+pub use fairness::{FairnessPolicy, QueueCandidate, effective_priority, queue_order};
 pub use queue::{QueueEntry, QueueEntryStatus, QueueState, ScheduleResult, ScheduledRequest};
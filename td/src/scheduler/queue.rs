@@ -28,6 +28,8 @@ pub struct ScheduledRequest {
     pub priority: Priority,
     pub submitted_at: Instant,
     pub started_at: Option<Instant>,
+    /// Loop type the request belongs to (empty if untyped); used by weighted fairness
+    pub loop_type: String,
 }
 
 impl ScheduledRequest {
@@ -40,8 +42,16 @@ impl ScheduledRequest {
             priority,
             submitted_at: Instant::now(),
             started_at: None,
+            loop_type: String::new(),
         }
     }
+
+    /// Attribute the request to a loop type
+    pub fn with_loop_type(mut self, loop_type: impl Into<String>) -> Self {
+        self.loop_type = loop_type.into();
+        debug!(%self.exec_id, %self.loop_type, "ScheduledRequest::with_loop_type: called");
+        self
+    }
 }
 
 impl Eq for ScheduledRequest {}
@@ -78,6 +88,30 @@ pub struct SchedulerStats {
     pub total_wait_time_ms: u64,
     pub peak_queue_depth: usize,
     pub peak_concurrent: usize,
+    /// Times concurrency was lowered because the provider reported little capacity left
+    pub total_backoffs: u64,
+    /// Slots granted through `wait_for_slot`
+    pub total_waits: u64,
+    /// Time spent in `wait_for_slot` (queued or rate limited) across all waits
+    pub total_queue_wait_ms: u64,
+    /// Longest single wait in `wait_for_slot`
+    pub max_queue_wait_ms: u64,
+}
+
+impl SchedulerStats {
+    /// Record how long a request waited for its slot
+    pub fn record_wait(&mut self, wait: Duration) {
+        let wait_ms = wait.as_millis() as u64;
+        debug!(wait_ms, "SchedulerStats::record_wait: called");
+        self.total_waits += 1;
+        self.total_queue_wait_ms += wait_ms;
+        self.max_queue_wait_ms = self.max_queue_wait_ms.max(wait_ms);
+    }
+
+    /// Mean time a request waited for its slot
+    pub fn mean_queue_wait_ms(&self) -> Option<f64> {
+        (self.total_waits > 0).then(|| self.total_queue_wait_ms as f64 / self.total_waits as f64)
+    }
 }
 
 /// Queue state for TUI display
@@ -86,6 +120,8 @@ pub struct QueueState {
     pub running: usize,
     pub queued: usize,
     pub rate_limited: bool,
+    /// Current concurrency limit (below the configured one after backing off)
+    pub concurrency_limit: usize,
     pub stats: SchedulerStats,
 }
 
@@ -96,6 +132,10 @@ pub struct QueueEntry {
     pub priority: Priority,
     pub status: QueueEntryStatus,
     pub wait_time: Option<Duration>,
+    /// Priority after aging (equal to `priority` while running)
+    pub effective_priority: Priority,
+    /// 1-based position in the queue (None while running)
+    pub position: Option<usize>,
 }
 
 /// Status of a queue entry
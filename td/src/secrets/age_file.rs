@@ -0,0 +1,182 @@
+//! age-encrypted file backend
+//!
+//! All secrets are stored as one JSON object encrypted to an X25519 identity
+//! (`age-keygen` format). The identity is created on the first write; keep it
+//! out of version control, while the encrypted file itself can be shared or
+//! synced.
+
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use age::secrecy::ExposeSecret;
+use age::x25519::Identity;
+use eyre::{Context, Result, eyre};
+use tracing::debug;
+
+use super::SecretStore;
+
+/// Secrets in an age-encrypted file
+pub struct AgeFileStore {
+    path: PathBuf,
+    identity_path: PathBuf,
+}
+
+impl AgeFileStore {
+    /// Store secrets in `path`, encrypted to the identity in `identity_path`
+    pub fn new(path: PathBuf, identity_path: PathBuf) -> Self {
+        debug!(?path, ?identity_path, "AgeFileStore::new: called");
+        Self { path, identity_path }
+    }
+
+    /// Read the identity, generating one if `create` is set and there is none
+    fn identity(&self, create: bool) -> Result<Option<Identity>> {
+        debug!(path = ?self.identity_path, %create, "AgeFileStore::identity: called");
+        if self.identity_path.exists() {
+            let content = fs::read_to_string(&self.identity_path)
+                .context(format!("Failed to read {}", self.identity_path.display()))?;
+            let line = content
+                .lines()
+                .map(str::trim)
+                .find(|l| !l.is_empty() && !l.starts_with('#'))
+                .ok_or_else(|| eyre!("No identity in {}", self.identity_path.display()))?;
+            let identity = line
+                .parse::<Identity>()
+                .map_err(|e| eyre!("Invalid identity in {}: {}", self.identity_path.display(), e))?;
+            return Ok(Some(identity));
+        }
+        if !create {
+            return Ok(None);
+        }
+
+        debug!("AgeFileStore::identity: generating a new identity");
+        let identity = Identity::generate();
+        let content = format!(
+            "# taskdaemon secrets identity\n# public key: {}\n{}\n",
+            identity.to_public(),
+            identity.to_string().expose_secret()
+        );
+        write_private(&self.identity_path, content.as_bytes())?;
+        Ok(Some(identity))
+    }
+
+    fn load(&self) -> Result<BTreeMap<String, String>> {
+        debug!(path = ?self.path, "AgeFileStore::load: called");
+        if !self.path.exists() {
+            return Ok(BTreeMap::new());
+        }
+        let identity = self.identity(false)?.ok_or_else(|| {
+            eyre!(
+                "{} exists but its identity {} is missing",
+                self.path.display(),
+                self.identity_path.display()
+            )
+        })?;
+        let ciphertext = fs::read(&self.path).context(format!("Failed to read {}", self.path.display()))?;
+        let plaintext = age::decrypt(&identity, &ciphertext)
+            .map_err(|e| eyre!("Failed to decrypt {}: {}", self.path.display(), e))?;
+        serde_json::from_slice(&plaintext).context(format!("Corrupt secrets file {}", self.path.display()))
+    }
+
+    fn save(&self, secrets: &BTreeMap<String, String>) -> Result<()> {
+        debug!(path = ?self.path, count = secrets.len(), "AgeFileStore::save: called");
+        let identity = self.identity(true)?.expect("identity is created on demand");
+        let plaintext = serde_json::to_vec(secrets)?;
+        let ciphertext =
+            age::encrypt(&identity.to_public(), &plaintext).map_err(|e| eyre!("Failed to encrypt secrets: {}", e))?;
+
+        // Write a sibling file and rename, so a crash never leaves a truncated store
+        let tmp = self.path.with_extension("tmp");
+        write_private(&tmp, &ciphertext)?;
+        fs::rename(&tmp, &self.path).context(format!("Failed to write {}", self.path.display()))
+    }
+}
+
+/// Create or replace a file readable only by the owner
+fn write_private(path: &Path, content: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).context(format!("Failed to create {}", parent.display()))?;
+    }
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .context(format!("Failed to create {}", path.display()))?;
+    file.write_all(content)
+        .context(format!("Failed to write {}", path.display()))
+}
+
+impl SecretStore for AgeFileStore {
+    fn get(&self, name: &str) -> Result<Option<String>> {
+        debug!(%name, "AgeFileStore::get: called");
+        Ok(self.load()?.remove(name))
+    }
+
+    fn set(&self, name: &str, value: &str) -> Result<()> {
+        debug!(%name, "AgeFileStore::set: called");
+        let mut secrets = self.load()?;
+        secrets.insert(name.to_string(), value.to_string());
+        self.save(&secrets)
+    }
+
+    fn remove(&self, name: &str) -> Result<bool> {
+        debug!(%name, "AgeFileStore::remove: called");
+        let mut secrets = self.load()?;
+        if secrets.remove(name).is_none() {
+            return Ok(false);
+        }
+        self.save(&secrets)?;
+        Ok(true)
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        debug!("AgeFileStore::list: called");
+        Ok(self.load()?.into_keys().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_age_file_store() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("secrets.age");
+        let identity = temp.path().join("secrets.key");
+        let store = AgeFileStore::new(path.clone(), identity.clone());
+
+        assert_eq!(store.list().unwrap(), Vec::<String>::new());
+        assert_eq!(store.get("anthropic-prod").unwrap(), None);
+
+        store.set("anthropic-prod", "sk-ant-123").unwrap();
+        store.set("openai", "sk-456").unwrap();
+        assert_eq!(store.get("anthropic-prod").unwrap().as_deref(), Some("sk-ant-123"));
+        assert_eq!(store.list().unwrap(), vec!["anthropic-prod", "openai"]);
+
+        // Encrypted at rest, with an owner-only identity
+        let raw = fs::read(&path).unwrap();
+        assert!(raw.starts_with(b"age-encryption.org/v1"));
+        assert!(!String::from_utf8_lossy(&raw).contains("sk-ant-123"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&identity).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+
+        assert!(store.remove("openai").unwrap());
+        assert!(!store.remove("openai").unwrap());
+        assert_eq!(store.list().unwrap(), vec!["anthropic-prod"]);
+
+        // Without the identity the file can't be read
+        fs::remove_file(&identity).unwrap();
+        assert!(store.get("anthropic-prod").is_err());
+    }
+}
@@ -0,0 +1,92 @@
+//! OS keychain backend
+//!
+//! Each secret is a keychain entry under the `taskdaemon` service. Keychains
+//! can't enumerate entries portably, so the names are also kept in an index
+//! entry (`_index`, which no secret name can collide with).
+
+use eyre::{Context, Result};
+use keyring::Entry;
+use tracing::debug;
+
+use super::SecretStore;
+
+/// Entry holding the JSON list of secret names
+const INDEX_ENTRY: &str = "_index";
+
+/// Secrets in the OS keychain
+pub struct KeychainStore {
+    service: String,
+}
+
+impl KeychainStore {
+    /// Store entries under `service`
+    pub fn new(service: impl Into<String>) -> Self {
+        let service = service.into();
+        debug!(%service, "KeychainStore::new: called");
+        Self { service }
+    }
+
+    fn entry(&self, name: &str) -> Result<Entry> {
+        Entry::new(&self.service, name).context(format!("Failed to open keychain entry {}", name))
+    }
+
+    fn read(&self, name: &str) -> Result<Option<String>> {
+        match self.entry(name)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e).context(format!("Failed to read {} from the keychain", name)),
+        }
+    }
+
+    fn write_index(&self, names: &[String]) -> Result<()> {
+        debug!(count = names.len(), "KeychainStore::write_index: called");
+        let json = serde_json::to_string(names)?;
+        self.entry(INDEX_ENTRY)?
+            .set_password(&json)
+            .context("Failed to update the keychain index")
+    }
+}
+
+impl SecretStore for KeychainStore {
+    fn get(&self, name: &str) -> Result<Option<String>> {
+        debug!(%name, "KeychainStore::get: called");
+        self.read(name)
+    }
+
+    fn set(&self, name: &str, value: &str) -> Result<()> {
+        debug!(%name, "KeychainStore::set: called");
+        self.entry(name)?
+            .set_password(value)
+            .context(format!("Failed to store {} in the keychain", name))?;
+        let mut names = self.list()?;
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+            names.sort();
+            self.write_index(&names)?;
+        }
+        Ok(())
+    }
+
+    fn remove(&self, name: &str) -> Result<bool> {
+        debug!(%name, "KeychainStore::remove: called");
+        let existed = match self.entry(name)?.delete_credential() {
+            Ok(()) => true,
+            Err(keyring::Error::NoEntry) => false,
+            Err(e) => return Err(e).context(format!("Failed to remove {} from the keychain", name)),
+        };
+        let mut names = self.list()?;
+        if names.iter().any(|n| n == name) {
+            names.retain(|n| n != name);
+            self.write_index(&names)?;
+        }
+        Ok(existed)
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        debug!("KeychainStore::list: called");
+        match self.read(INDEX_ENTRY)? {
+            Some(json) => serde_json::from_str(&json).context("Corrupt keychain index"),
+            None => Ok(Vec::new()),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! Secrets store for API keys (`td secrets`)
+//!
+//! A provider references a secret by name with `api-key-secret`, so the key
+//! itself never sits in an environment variable or a plain file. Secrets live
+//! in the OS keychain (macOS Keychain, Windows Credential Manager, Secret
+//! Service on Linux) or in an age-encrypted file, selected by
+//! `llm.secrets.backend`.
+
+mod age_file;
+mod keychain;
+
+pub use age_file::AgeFileStore;
+pub use keychain::KeychainStore;
+
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::config::SecretsConfig;
+
+/// Service name the keychain entries are stored under
+pub const KEYCHAIN_SERVICE: &str = "taskdaemon";
+
+/// Where secrets are kept
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SecretBackend {
+    /// OS keychain
+    #[default]
+    Keychain,
+    /// age-encrypted file
+    File,
+}
+
+impl std::str::FromStr for SecretBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        debug!(%s, "SecretBackend::from_str: called");
+        match s.to_lowercase().as_str() {
+            "keychain" => Ok(Self::Keychain),
+            "file" => Ok(Self::File),
+            _ => Err(format!("Unknown secrets backend: {}. Use: keychain or file", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for SecretBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Keychain => write!(f, "keychain"),
+            Self::File => write!(f, "file"),
+        }
+    }
+}
+
+/// A named-secret store
+pub trait SecretStore {
+    /// Value of a secret (None if it doesn't exist)
+    fn get(&self, name: &str) -> Result<Option<String>>;
+
+    /// Create or replace a secret
+    fn set(&self, name: &str, value: &str) -> Result<()>;
+
+    /// Delete a secret; false if it didn't exist
+    fn remove(&self, name: &str) -> Result<bool>;
+
+    /// Names of all secrets, sorted
+    fn list(&self) -> Result<Vec<String>>;
+}
+
+/// Open the store configured in `llm.secrets`
+pub fn open(config: &SecretsConfig) -> Result<Box<dyn SecretStore>> {
+    debug!(backend = %config.backend, "secrets::open: called");
+    match config.backend {
+        SecretBackend::Keychain => Ok(Box::new(KeychainStore::new(KEYCHAIN_SERVICE))),
+        SecretBackend::File => Ok(Box::new(AgeFileStore::new(config.file()?, config.identity_file()?))),
+    }
+}
+
+/// Check a secret name: letters, digits, `.`, `_` and `-`, starting with a letter or digit
+pub fn validate_name(name: &str) -> Result<()> {
+    debug!(%name, "validate_name: called");
+    let valid = name.chars().next().is_some_and(|c| c.is_ascii_alphanumeric())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+    if valid {
+        Ok(())
+    } else {
+        Err(eyre::eyre!(
+            "Invalid secret name '{}': use letters, digits, '.', '_' and '-', starting with a letter or digit",
+            name
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("anthropic-prod").is_ok());
+        assert!(validate_name("openai.team_2").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("_index").is_err());
+        assert!(validate_name("has space").is_err());
+    }
+
+    #[test]
+    fn test_secret_backend_from_str() {
+        assert_eq!("keychain".parse::<SecretBackend>().unwrap(), SecretBackend::Keychain);
+        assert_eq!("FILE".parse::<SecretBackend>().unwrap(), SecretBackend::File);
+        assert!("vault".parse::<SecretBackend>().is_err());
+    }
+}
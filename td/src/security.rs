@@ -0,0 +1,671 @@
+//! Security review findings: scanner output, triage and the SARIF report
+//!
+//! The `security-review` loop type runs static-analysis scanners (cargo-audit,
+//! semgrep, or anything that emits SARIF) through the `security_scan` tool.
+//! Their findings are normalized into a [`FindingStore`] kept in the worktree
+//! under [`SECURITY_DIR`], fed into every iteration's prompt, and triaged one
+//! by one with the `triage_finding` tool. After every change the store is
+//! rendered as a SARIF 2.1.0 report next to it; the report's
+//! `pendingFindings` property is what the loop's validation checks.
+
+use std::path::{Path, PathBuf};
+
+use eyre::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use tracing::debug;
+
+/// Directory (relative to the worktree) holding findings and the report
+pub const SECURITY_DIR: &str = ".taskdaemon/security";
+
+/// Findings and their triage, as JSON
+pub const FINDINGS_FILE: &str = "findings.json";
+
+/// SARIF report of the triaged findings
+pub const REPORT_FILE: &str = "report.sarif";
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Output format of a scanner command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScannerFormat {
+    /// `cargo audit --json`
+    CargoAudit,
+    /// `semgrep --json`
+    Semgrep,
+    /// Any SARIF 2.1.0 producer
+    Sarif,
+}
+
+impl std::fmt::Display for ScannerFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CargoAudit => write!(f, "cargo-audit"),
+            Self::Semgrep => write!(f, "semgrep"),
+            Self::Sarif => write!(f, "sarif"),
+        }
+    }
+}
+
+/// A static-analysis command run by `security_scan` (`scanners` in loop YAML)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ScannerSpec {
+    /// Scanner name, used in finding IDs and to pick scanners
+    pub name: String,
+
+    /// Shell command run in the worktree; its stdout is parsed
+    pub command: String,
+
+    /// How to parse the command's stdout
+    pub format: ScannerFormat,
+
+    /// Wall clock for the command
+    #[serde(default = "default_scanner_timeout")]
+    pub timeout_ms: u64,
+}
+
+fn default_scanner_timeout() -> u64 {
+    600_000
+}
+
+/// Severity of a finding, normalized across scanners
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    /// SARIF result level
+    pub fn sarif_level(&self) -> &'static str {
+        match self {
+            Self::Low => "note",
+            Self::Medium => "warning",
+            Self::High | Self::Critical => "error",
+        }
+    }
+
+    /// From a SARIF result level (`error`, `warning`, `note`)
+    fn from_sarif_level(level: &str) -> Self {
+        match level {
+            "error" => Self::High,
+            "warning" => Self::Medium,
+            _ => Self::Low,
+        }
+    }
+
+    /// From a CVSS base score
+    fn from_cvss(score: f64) -> Self {
+        match score {
+            s if s >= 9.0 => Self::Critical,
+            s if s >= 7.0 => Self::High,
+            s if s >= 4.0 => Self::Medium,
+            _ => Self::Low,
+        }
+    }
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Low => write!(f, "low"),
+            Self::Medium => write!(f, "medium"),
+            Self::High => write!(f, "high"),
+            Self::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+/// Outcome of triaging a finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    /// A real issue whose risk is accepted for now
+    Accepted,
+    /// A real issue, fixed in the worktree
+    Fixed,
+    /// Not an issue (false positive, unreachable code, test-only)
+    Dismissed,
+}
+
+impl std::fmt::Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Accepted => write!(f, "accepted"),
+            Self::Fixed => write!(f, "fixed"),
+            Self::Dismissed => write!(f, "dismissed"),
+        }
+    }
+}
+
+impl std::str::FromStr for Verdict {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "accepted" => Ok(Self::Accepted),
+            "fixed" => Ok(Self::Fixed),
+            "dismissed" => Ok(Self::Dismissed),
+            _ => Err(format!("Unknown verdict: {}. Use: accepted, fixed or dismissed", s)),
+        }
+    }
+}
+
+/// Triage decision for one finding
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Triage {
+    pub verdict: Verdict,
+    /// Why: the fix made, the accepted risk, or why it is not an issue
+    pub justification: String,
+}
+
+/// One finding reported by a scanner
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Finding {
+    /// Stable ID (`<scanner>-<hash>`) used to triage it
+    pub id: String,
+    /// Scanner that reported it
+    pub scanner: String,
+    /// Scanner rule or advisory ID
+    pub rule_id: String,
+    pub severity: Severity,
+    pub message: String,
+    /// File the finding points at, relative to the worktree
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub line: Option<u64>,
+    /// None until triaged
+    #[serde(default)]
+    pub triage: Option<Triage>,
+}
+
+impl Finding {
+    /// Create an untriaged finding; the ID is derived from what identifies it
+    pub fn new(
+        scanner: &str,
+        rule_id: impl Into<String>,
+        severity: Severity,
+        message: impl Into<String>,
+        path: Option<String>,
+        line: Option<u64>,
+    ) -> Self {
+        let rule_id = rule_id.into();
+        let message = message.into();
+        let key = format!(
+            "{}\0{}\0{}\0{}",
+            rule_id,
+            path.as_deref().unwrap_or(""),
+            line.unwrap_or(0),
+            message
+        );
+        let hash = format!("{:x}", Sha256::digest(key.as_bytes()));
+        Self {
+            id: format!("{}-{}", scanner, &hash[..8]),
+            scanner: scanner.to_string(),
+            rule_id,
+            severity,
+            message,
+            path,
+            line,
+            triage: None,
+        }
+    }
+
+    /// `path:line`, `path`, or nothing
+    pub fn location(&self) -> Option<String> {
+        match (&self.path, self.line) {
+            (Some(path), Some(line)) => Some(format!("{}:{}", path, line)),
+            (Some(path), None) => Some(path.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a scanner's stdout into findings
+pub fn parse_findings(scanner: &str, format: ScannerFormat, stdout: &str) -> Result<Vec<Finding>> {
+    debug!(%scanner, %format, stdout_len = stdout.len(), "parse_findings: called");
+    let value: Value =
+        serde_json::from_str(stdout.trim()).with_context(|| format!("{} output is not {} JSON", scanner, format))?;
+    Ok(match format {
+        ScannerFormat::CargoAudit => parse_cargo_audit(scanner, &value),
+        ScannerFormat::Semgrep => parse_semgrep(scanner, &value),
+        ScannerFormat::Sarif => parse_sarif(scanner, &value),
+    })
+}
+
+fn parse_cargo_audit(scanner: &str, value: &Value) -> Vec<Finding> {
+    let advisory = |entry: &Value, severity: Severity| {
+        let advisory = &entry["advisory"];
+        let package = entry["package"]["name"]
+            .as_str()
+            .or(advisory["package"].as_str())
+            .unwrap_or("unknown");
+        let version = entry["package"]["version"].as_str().unwrap_or("");
+        let severity = advisory["cvss"]
+            .as_f64()
+            .or_else(|| advisory["cvss"]["score"].as_f64())
+            .map(Severity::from_cvss)
+            .unwrap_or(severity);
+        Finding::new(
+            scanner,
+            advisory["id"].as_str().unwrap_or("advisory"),
+            severity,
+            format!("{} {}: {}", package, version, advisory["title"].as_str().unwrap_or("")),
+            Some("Cargo.lock".to_string()),
+            None,
+        )
+    };
+
+    let mut findings: Vec<Finding> = value["vulnerabilities"]["list"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|entry| advisory(entry, Severity::High))
+        .collect();
+    // Warnings (unmaintained, yanked, unsound) are grouped by kind
+    if let Some(warnings) = value["warnings"].as_object() {
+        for entry in warnings.values().filter_map(Value::as_array).flatten() {
+            if entry["advisory"].is_object() {
+                findings.push(advisory(entry, Severity::Low));
+            } else {
+                let package = entry["package"]["name"].as_str().unwrap_or("unknown");
+                let kind = entry["kind"].as_str().unwrap_or("warning");
+                findings.push(Finding::new(
+                    scanner,
+                    kind,
+                    Severity::Low,
+                    format!("{} is {}", package, kind),
+                    Some("Cargo.lock".to_string()),
+                    None,
+                ));
+            }
+        }
+    }
+    findings
+}
+
+fn parse_semgrep(scanner: &str, value: &Value) -> Vec<Finding> {
+    value["results"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|result| {
+            let severity = match result["extra"]["severity"].as_str().unwrap_or("") {
+                "ERROR" => Severity::High,
+                "WARNING" => Severity::Medium,
+                _ => Severity::Low,
+            };
+            Finding::new(
+                scanner,
+                result["check_id"].as_str().unwrap_or("semgrep"),
+                severity,
+                result["extra"]["message"].as_str().unwrap_or("").trim(),
+                result["path"].as_str().map(String::from),
+                result["start"]["line"].as_u64(),
+            )
+        })
+        .collect()
+}
+
+fn parse_sarif(scanner: &str, value: &Value) -> Vec<Finding> {
+    value["runs"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|run| run["results"].as_array())
+        .flatten()
+        .map(|result| {
+            let location = &result["locations"][0]["physicalLocation"];
+            Finding::new(
+                scanner,
+                result["ruleId"].as_str().unwrap_or("sarif"),
+                Severity::from_sarif_level(result["level"].as_str().unwrap_or("warning")),
+                result["message"]["text"].as_str().unwrap_or(""),
+                location["artifactLocation"]["uri"].as_str().map(String::from),
+                location["region"]["startLine"].as_u64(),
+            )
+        })
+        .collect()
+}
+
+/// Counts of findings by triage state
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TriageCounts {
+    pub pending: usize,
+    pub accepted: usize,
+    pub fixed: usize,
+    pub dismissed: usize,
+}
+
+impl std::fmt::Display for TriageCounts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} pending, {} fixed, {} accepted, {} dismissed",
+            self.pending, self.fixed, self.accepted, self.dismissed
+        )
+    }
+}
+
+/// All findings of a security review, persisted in the worktree
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FindingStore {
+    /// Scanners that have run at least once
+    #[serde(default)]
+    pub scanned: Vec<String>,
+    #[serde(default)]
+    pub findings: Vec<Finding>,
+}
+
+impl FindingStore {
+    /// Path of the findings file in `worktree`
+    pub fn path(worktree: &Path) -> PathBuf {
+        worktree.join(SECURITY_DIR).join(FINDINGS_FILE)
+    }
+
+    /// Load the store from `worktree` (empty if no scan has run)
+    pub fn load(worktree: &Path) -> Result<Self> {
+        let path = Self::path(worktree);
+        debug!(?path, "FindingStore::load: called");
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Write the findings and the SARIF report into `worktree`
+    pub fn save(&self, worktree: &Path) -> Result<()> {
+        let dir = worktree.join(SECURITY_DIR);
+        debug!(?dir, findings = self.findings.len(), "FindingStore::save: called");
+        std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        std::fs::write(dir.join(FINDINGS_FILE), serde_json::to_string_pretty(self)?)?;
+        std::fs::write(
+            dir.join(REPORT_FILE),
+            serde_json::to_string_pretty(&self.render_sarif())?,
+        )?;
+        Ok(())
+    }
+
+    /// Replace `scanner`'s findings with a fresh scan, keeping earlier triage
+    ///
+    /// A finding marked fixed that is still reported goes back to pending; an
+    /// untriaged finding the scanner no longer reports counts as fixed.
+    pub fn merge_scan(&mut self, scanner: &str, fresh: Vec<Finding>) {
+        debug!(%scanner, fresh = fresh.len(), "FindingStore::merge_scan: called");
+        let (previous, others): (Vec<Finding>, Vec<Finding>) = std::mem::take(&mut self.findings)
+            .into_iter()
+            .partition(|f| f.scanner == scanner);
+        self.findings = others;
+
+        for mut finding in fresh {
+            if let Some(old) = previous.iter().find(|old| old.id == finding.id) {
+                finding.triage = old.triage.clone().filter(|t| t.verdict != Verdict::Fixed);
+            }
+            if !self.findings.iter().any(|f| f.id == finding.id) {
+                self.findings.push(finding);
+            }
+        }
+        for mut old in previous {
+            if self.findings.iter().any(|f| f.id == old.id) {
+                continue;
+            }
+            if old.triage.is_none() {
+                old.triage = Some(Triage {
+                    verdict: Verdict::Fixed,
+                    justification: format!("No longer reported by {}", scanner),
+                });
+            }
+            self.findings.push(old);
+        }
+        if !self.scanned.iter().any(|s| s == scanner) {
+            self.scanned.push(scanner.to_string());
+        }
+    }
+
+    /// Record the triage of finding `id`
+    pub fn triage(&mut self, id: &str, verdict: Verdict, justification: &str) -> Result<(), String> {
+        debug!(%id, %verdict, "FindingStore::triage: called");
+        let finding = self
+            .findings
+            .iter_mut()
+            .find(|f| f.id == id)
+            .ok_or_else(|| format!("No finding with ID '{}'", id))?;
+        finding.triage = Some(Triage {
+            verdict,
+            justification: justification.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Findings still waiting for triage, most severe first
+    pub fn pending(&self) -> Vec<&Finding> {
+        let mut pending: Vec<&Finding> = self.findings.iter().filter(|f| f.triage.is_none()).collect();
+        pending.sort_by_key(|f| std::cmp::Reverse(f.severity));
+        pending
+    }
+
+    /// Count findings by triage state
+    pub fn counts(&self) -> TriageCounts {
+        let mut counts = TriageCounts::default();
+        for finding in &self.findings {
+            match finding.triage.as_ref().map(|t| t.verdict) {
+                None => counts.pending += 1,
+                Some(Verdict::Accepted) => counts.accepted += 1,
+                Some(Verdict::Fixed) => counts.fixed += 1,
+                Some(Verdict::Dismissed) => counts.dismissed += 1,
+            }
+        }
+        counts
+    }
+
+    /// Pending findings as prompt text, one per line
+    pub fn format_pending(&self) -> String {
+        self.pending()
+            .iter()
+            .map(|f| {
+                let location = f.location().map(|l| format!(" at {}", l)).unwrap_or_default();
+                format!(
+                    "- [{}] {} ({}, {}){}: {}",
+                    f.id, f.rule_id, f.scanner, f.severity, location, f.message
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// SARIF 2.1.0 with one run per scanner; triage is carried as result suppressions
+    pub fn render_sarif(&self) -> Value {
+        debug!(findings = self.findings.len(), "FindingStore::render_sarif: called");
+        let counts = self.counts();
+        let runs: Vec<Value> = self
+            .scanned
+            .iter()
+            .map(|scanner| {
+                let findings: Vec<&Finding> = self.findings.iter().filter(|f| &f.scanner == scanner).collect();
+                let mut rules: Vec<&str> = findings.iter().map(|f| f.rule_id.as_str()).collect();
+                rules.sort_unstable();
+                rules.dedup();
+                json!({
+                    "tool": {
+                        "driver": {
+                            "name": scanner,
+                            "rules": rules.iter().map(|id| json!({ "id": id })).collect::<Vec<_>>(),
+                        }
+                    },
+                    "results": findings.iter().map(|f| sarif_result(f)).collect::<Vec<_>>(),
+                    "properties": {
+                        "triagedBy": "taskdaemon",
+                    },
+                })
+            })
+            .collect();
+
+        json!({
+            "$schema": SARIF_SCHEMA,
+            "version": "2.1.0",
+            "runs": runs,
+            "properties": {
+                "pendingFindings": counts.pending,
+                "accepted": counts.accepted,
+                "fixed": counts.fixed,
+                "dismissed": counts.dismissed,
+            },
+        })
+    }
+}
+
+fn sarif_result(finding: &Finding) -> Value {
+    let mut result = json!({
+        "ruleId": finding.rule_id,
+        "level": finding.severity.sarif_level(),
+        "message": { "text": finding.message },
+        "partialFingerprints": { "taskdaemonFindingId": finding.id },
+        "properties": {
+            "severity": finding.severity.to_string(),
+            "triage": finding.triage.as_ref().map(|t| t.verdict.to_string()).unwrap_or_else(|| "pending".to_string()),
+        },
+    });
+    if let Some(path) = &finding.path {
+        let mut location = json!({ "artifactLocation": { "uri": path } });
+        if let Some(line) = finding.line {
+            location["region"] = json!({ "startLine": line });
+        }
+        result["locations"] = json!([{ "physicalLocation": location }]);
+    }
+    // Accepted and dismissed findings stay in the report as suppressed results
+    if let Some(triage) = &finding.triage {
+        let status = match triage.verdict {
+            Verdict::Fixed => None,
+            Verdict::Accepted => Some("accepted"),
+            Verdict::Dismissed => Some("rejected"),
+        };
+        if let Some(status) = status {
+            result["suppressions"] = json!([{
+                "kind": "external",
+                "status": status,
+                "justification": triage.justification,
+            }]);
+        } else {
+            result["baselineState"] = json!("absent");
+            result["properties"]["justification"] = json!(triage.justification);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const CARGO_AUDIT: &str = r#"{
+        "vulnerabilities": {
+            "found": true,
+            "count": 1,
+            "list": [{
+                "advisory": { "id": "RUSTSEC-2024-0001", "package": "smallvec", "title": "Buffer overflow", "cvss": null },
+                "package": { "name": "smallvec", "version": "1.6.0" }
+            }]
+        },
+        "warnings": {
+            "unmaintained": [{
+                "kind": "unmaintained",
+                "package": { "name": "atty", "version": "0.2.14" },
+                "advisory": { "id": "RUSTSEC-2021-0145", "title": "atty is unmaintained" }
+            }]
+        }
+    }"#;
+
+    const SEMGREP: &str = r#"{
+        "results": [{
+            "check_id": "rust.lang.security.unsafe-usage",
+            "path": "src/lib.rs",
+            "start": { "line": 42, "col": 5 },
+            "extra": { "message": "Unsafe block", "severity": "WARNING" }
+        }],
+        "errors": []
+    }"#;
+
+    #[test]
+    fn test_parse_cargo_audit() {
+        let findings = parse_findings("cargo-audit", ScannerFormat::CargoAudit, CARGO_AUDIT).unwrap();
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].rule_id, "RUSTSEC-2024-0001");
+        assert_eq!(findings[0].severity, Severity::High);
+        assert!(findings[0].message.contains("smallvec 1.6.0"));
+        assert_eq!(findings[1].severity, Severity::Low);
+        assert!(findings[0].id.starts_with("cargo-audit-"));
+    }
+
+    #[test]
+    fn test_parse_semgrep_and_sarif() {
+        let findings = parse_findings("semgrep", ScannerFormat::Semgrep, SEMGREP).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].location().as_deref(), Some("src/lib.rs:42"));
+        assert_eq!(findings[0].severity, Severity::Medium);
+
+        let mut store = FindingStore::default();
+        store.merge_scan("semgrep", findings.clone());
+        let reparsed = parse_findings("semgrep", ScannerFormat::Sarif, &store.render_sarif().to_string()).unwrap();
+        assert_eq!(reparsed, findings);
+
+        assert!(parse_findings("semgrep", ScannerFormat::Semgrep, "not json").is_err());
+    }
+
+    #[test]
+    fn test_merge_scan_keeps_triage_and_reopens_unfixed() {
+        let mut store = FindingStore::default();
+        let findings = parse_findings("cargo-audit", ScannerFormat::CargoAudit, CARGO_AUDIT).unwrap();
+        store.merge_scan("cargo-audit", findings.clone());
+        assert_eq!(store.counts().pending, 2);
+
+        store
+            .triage(&findings[0].id, Verdict::Fixed, "Bumped smallvec")
+            .unwrap();
+        store
+            .triage(&findings[1].id, Verdict::Accepted, "Only used in tests")
+            .unwrap();
+        assert!(store.triage("nope", Verdict::Fixed, "").is_err());
+        assert_eq!(store.counts().pending, 0);
+
+        // The "fixed" advisory is still reported, so it is pending again
+        store.merge_scan("cargo-audit", findings.clone());
+        assert_eq!(store.pending().len(), 1);
+        assert_eq!(store.pending()[0].id, findings[0].id);
+        assert_eq!(store.counts().accepted, 1);
+
+        // Nothing reported any more: the untriaged one counts as fixed
+        store.merge_scan("cargo-audit", vec![]);
+        let counts = store.counts();
+        assert_eq!((counts.pending, counts.fixed, counts.accepted), (0, 1, 1));
+    }
+
+    #[test]
+    fn test_save_writes_sarif_report() {
+        let temp = tempdir().unwrap();
+        let mut store = FindingStore::default();
+        store.merge_scan(
+            "semgrep",
+            parse_findings("semgrep", ScannerFormat::Semgrep, SEMGREP).unwrap(),
+        );
+        let id = store.findings[0].id.clone();
+        store.triage(&id, Verdict::Dismissed, "Reviewed: FFI boundary").unwrap();
+        store.save(temp.path()).unwrap();
+
+        assert_eq!(FindingStore::load(temp.path()).unwrap(), store);
+        let report = std::fs::read_to_string(temp.path().join(SECURITY_DIR).join(REPORT_FILE)).unwrap();
+        assert!(report.contains("\"pendingFindings\": 0"));
+        let sarif: Value = serde_json::from_str(&report).unwrap();
+        let result = &sarif["runs"][0]["results"][0];
+        assert_eq!(result["suppressions"][0]["status"], "rejected");
+        assert_eq!(result["suppressions"][0]["justification"], "Reviewed: FFI boundary");
+    }
+}
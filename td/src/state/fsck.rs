@@ -0,0 +1,454 @@
+//! Consistency check between the event log and the TaskStore (`td fsck`)
+//!
+//! Every execution's events are persisted under the runs directory
+//! independently of its TaskStore record, so a crash between the two writes
+//! leaves them disagreeing. fsck replays each execution's events into the
+//! state they imply, reports where the stored record differs and, when asked,
+//! repairs the record to match the log.
+
+use std::path::Path;
+
+use serde::Serialize;
+use tracing::{debug, info, warn};
+
+use crate::domain::{LoopExecution, LoopExecutionStatus};
+use crate::events::{Event, read_execution_events};
+use crate::resources::DAEMON_EVENT_ID;
+
+use super::StateManager;
+
+/// Where an execution's event log says it got to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReplayedStatus {
+    /// Started and has not finished since
+    Running,
+    /// Finished with validation passing (merge or cascade still to record)
+    Succeeded,
+    /// Finished without passing (error, budget, timeout or interruption)
+    Failed,
+    /// Stopped for making no progress and waiting for a human
+    Escalated,
+    /// Paused for making no progress
+    Paused,
+}
+
+impl std::fmt::Display for ReplayedStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Running => "running",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+            Self::Escalated => "escalated",
+            Self::Paused => "paused",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// State rebuilt by replaying an execution's events in order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplayedExecution {
+    /// Status after the last lifecycle event (None if the loop never started)
+    pub status: Option<ReplayedStatus>,
+    /// Highest iteration any event mentions
+    pub iteration: u32,
+}
+
+impl ReplayedExecution {
+    /// Fold a log's events into the state they describe
+    pub fn replay<'a>(events: impl IntoIterator<Item = &'a Event>) -> Self {
+        let mut replayed = Self::default();
+        for event in events {
+            match event {
+                Event::LoopStarted { .. } => replayed.status = Some(ReplayedStatus::Running),
+                Event::LoopCompleted {
+                    success,
+                    total_iterations,
+                    ..
+                } => {
+                    replayed.status = Some(if *success {
+                        ReplayedStatus::Succeeded
+                    } else {
+                        ReplayedStatus::Failed
+                    });
+                    replayed.iteration = replayed.iteration.max(*total_iterations);
+                }
+                Event::LoopStuck { iteration, action, .. } => {
+                    replayed.iteration = replayed.iteration.max(*iteration);
+                    replayed.status = Some(match action.as_str() {
+                        "escalate" => ReplayedStatus::Escalated,
+                        "steer" => ReplayedStatus::Running,
+                        _ => ReplayedStatus::Paused,
+                    });
+                }
+                Event::IterationStarted { iteration, .. } | Event::IterationCompleted { iteration, .. } => {
+                    replayed.iteration = replayed.iteration.max(*iteration);
+                }
+                _ => {}
+            }
+        }
+        replayed
+    }
+}
+
+/// Kind of disagreement between an execution's record and its event log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Drift {
+    /// The log shows the loop ended but the record still has it pending or running
+    Status {
+        stored: LoopExecutionStatus,
+        replayed: ReplayedStatus,
+        repair_to: LoopExecutionStatus,
+    },
+    /// The log mentions a later iteration than the record
+    Iteration { stored: u32, replayed: u32 },
+    /// The record says running, the log agrees, but no daemon is running it
+    Interrupted,
+    /// An event log exists for an execution the TaskStore doesn't have
+    OrphanedLog,
+}
+
+/// One discrepancy found
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Discrepancy {
+    pub exec_id: String,
+    #[serde(flatten)]
+    pub drift: Drift,
+    /// Whether the record was updated to match the log
+    pub repaired: bool,
+}
+
+impl Discrepancy {
+    /// One-line description
+    pub fn describe(&self) -> String {
+        match &self.drift {
+            Drift::Status {
+                stored,
+                replayed,
+                repair_to,
+            } => format!(
+                "status is {} but the event log ends {} (repair: {})",
+                stored, replayed, repair_to
+            ),
+            Drift::Iteration { stored, replayed } => {
+                format!("iteration is {} but the event log reaches {}", stored, replayed)
+            }
+            Drift::Interrupted => "running with no daemon (repair: paused)".to_string(),
+            Drift::OrphanedLog => "event log without a TaskStore record".to_string(),
+        }
+    }
+
+    /// Whether fsck knows how to repair it (orphaned logs are only reported)
+    fn is_repairable(&self) -> bool {
+        !matches!(self.drift, Drift::OrphanedLog)
+    }
+}
+
+/// Result of a check
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FsckReport {
+    /// Executions whose records were compared with their logs
+    pub checked: usize,
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+impl FsckReport {
+    /// True if nothing disagreed
+    pub fn is_clean(&self) -> bool {
+        self.discrepancies.is_empty()
+    }
+
+    /// Discrepancies that were repaired
+    pub fn repaired(&self) -> usize {
+        self.discrepancies.iter().filter(|d| d.repaired).count()
+    }
+
+    /// Human-readable report with a summary line
+    pub fn render_text(&self) -> String {
+        let mut out = String::new();
+        for d in &self.discrepancies {
+            let mark = if d.repaired { "fixed" } else { "found" };
+            out.push_str(&format!("{:<6} {:<40} {}\n", mark, d.exec_id, d.describe()));
+        }
+        out.push_str(&format!(
+            "{} executions checked: {} discrepancies, {} repaired\n",
+            self.checked,
+            self.discrepancies.len(),
+            self.repaired()
+        ));
+        out
+    }
+}
+
+/// Compare every execution's record with its event log under `runs_dir`
+///
+/// `daemon_running` says whether a daemon may be running executions right
+/// now; without one, a record and log that both say "running" mean the run
+/// was interrupted. With `repair`, every repairable discrepancy is written
+/// back to the TaskStore.
+pub async fn fsck(
+    state: &StateManager,
+    runs_dir: &Path,
+    daemon_running: bool,
+    repair: bool,
+) -> eyre::Result<FsckReport> {
+    debug!(?runs_dir, daemon_running, repair, "fsck: called");
+    let executions = state
+        .list_executions(None, None)
+        .await
+        .map_err(|e| eyre::eyre!("Failed to list executions: {}", e))?;
+
+    let mut report = FsckReport {
+        checked: executions.len(),
+        ..Default::default()
+    };
+    for exec in &executions {
+        let events: Vec<Event> = read_execution_events(runs_dir, &exec.id)?
+            .into_iter()
+            .map(|entry| entry.event)
+            .collect();
+        if events.is_empty() {
+            debug!(exec_id = %exec.id, "fsck: no event log");
+            continue;
+        }
+        let replayed = ReplayedExecution::replay(&events);
+        for drift in compare(exec, &replayed, daemon_running) {
+            report.discrepancies.push(Discrepancy {
+                exec_id: exec.id.clone(),
+                drift,
+                repaired: false,
+            });
+        }
+    }
+
+    // Logs left behind by executions that were deleted (or never stored)
+    if runs_dir.is_dir() {
+        let known: std::collections::HashSet<&str> = executions.iter().map(|e| e.id.as_str()).collect();
+        let mut orphans: Vec<String> = std::fs::read_dir(runs_dir)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| name != DAEMON_EVENT_ID && !known.contains(name.as_str()))
+            .collect();
+        orphans.sort();
+        report
+            .discrepancies
+            .extend(orphans.into_iter().map(|exec_id| Discrepancy {
+                exec_id,
+                drift: Drift::OrphanedLog,
+                repaired: false,
+            }));
+    }
+
+    if repair {
+        for discrepancy in report.discrepancies.iter_mut().filter(|d| d.is_repairable()) {
+            match apply_repair(state, discrepancy).await {
+                Ok(()) => discrepancy.repaired = true,
+                Err(e) => warn!(exec_id = %discrepancy.exec_id, error = %e, "fsck: repair failed"),
+            }
+        }
+    }
+
+    if !report.is_clean() {
+        info!(
+            checked = report.checked,
+            discrepancies = report.discrepancies.len(),
+            repaired = report.repaired(),
+            "fsck found discrepancies between the event log and the TaskStore"
+        );
+    }
+    Ok(report)
+}
+
+/// Discrepancies between one record and its replayed log
+fn compare(exec: &LoopExecution, replayed: &ReplayedExecution, daemon_running: bool) -> Vec<Drift> {
+    let mut drifts = Vec::new();
+    let pending_or_active = exec.status == LoopExecutionStatus::Pending || exec.is_active();
+
+    match replayed.status {
+        Some(ReplayedStatus::Running) if exec.is_active() && !daemon_running => drifts.push(Drift::Interrupted),
+        Some(status @ (ReplayedStatus::Succeeded | ReplayedStatus::Failed | ReplayedStatus::Escalated | ReplayedStatus::Paused))
+            if pending_or_active =>
+        {
+            let repair_to = match status {
+                // Validation passed but the merge/cascade never ran: resuming finishes it
+                ReplayedStatus::Succeeded | ReplayedStatus::Escalated => LoopExecutionStatus::Blocked,
+                ReplayedStatus::Paused => LoopExecutionStatus::Paused,
+                _ => LoopExecutionStatus::Failed,
+            };
+            drifts.push(Drift::Status {
+                stored: exec.status,
+                replayed: status,
+                repair_to,
+            });
+        }
+        _ => {}
+    }
+
+    if replayed.iteration > exec.iteration {
+        drifts.push(Drift::Iteration {
+            stored: exec.iteration,
+            replayed: replayed.iteration,
+        });
+    }
+    drifts
+}
+
+/// Write one repair back to the execution's record
+async fn apply_repair(state: &StateManager, discrepancy: &Discrepancy) -> eyre::Result<()> {
+    debug!(exec_id = %discrepancy.exec_id, drift = ?discrepancy.drift, "apply_repair: called");
+    let drift = discrepancy.drift.clone();
+    state
+        .modify_execution(&discrepancy.exec_id, |exec| match &drift {
+            Drift::Status {
+                replayed, repair_to, ..
+            } => {
+                exec.set_status(*repair_to);
+                exec.set_error(match replayed {
+                    ReplayedStatus::Succeeded => "fsck: loop finished but its result was not recorded; resume to merge",
+                    ReplayedStatus::Escalated => "fsck: loop was stuck and escalated before its status was recorded",
+                    ReplayedStatus::Paused => "fsck: loop was stuck and paused before its status was recorded",
+                    _ => "fsck: loop failed before its status was recorded",
+                });
+            }
+            Drift::Iteration { replayed, .. } => exec.iteration = *replayed,
+            Drift::Interrupted => {
+                exec.set_status(LoopExecutionStatus::Paused);
+                exec.set_error("fsck: execution was interrupted");
+            }
+            Drift::OrphanedLog => {}
+        })
+        .await
+        .map_err(|e| eyre::eyre!("Failed to repair execution {}: {}", discrepancy.exec_id, e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventLogger;
+    use tempfile::tempdir;
+
+    fn log(runs: &Path, events: &[Event]) {
+        let mut logger = EventLogger::new(runs);
+        for event in events {
+            logger.write_event(event).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fsck_reports_and_repairs_drift() {
+        let temp = tempdir().unwrap();
+        let runs = temp.path().join("runs");
+        let state = StateManager::spawn(temp.path()).unwrap();
+
+        // Crashed after the loop failed but before the record was updated
+        let mut failed = LoopExecution::with_id("exec-failed", "ralph");
+        failed.set_status(LoopExecutionStatus::Running);
+        failed.iteration = 1;
+        state.create_execution(failed).await.unwrap();
+        // Consistent: finished and recorded
+        let mut done = LoopExecution::with_id("exec-done", "ralph");
+        done.set_status(LoopExecutionStatus::Complete);
+        done.iteration = 1;
+        state.create_execution(done).await.unwrap();
+
+        let started = |id: &str| Event::LoopStarted {
+            execution_id: id.to_string(),
+            loop_type: "ralph".to_string(),
+            task_description: "task".to_string(),
+        };
+        log(
+            &runs,
+            &[
+                started("exec-failed"),
+                Event::IterationStarted {
+                    execution_id: "exec-failed".to_string(),
+                    iteration: 3,
+                },
+                Event::LoopCompleted {
+                    execution_id: "exec-failed".to_string(),
+                    success: false,
+                    total_iterations: 3,
+                },
+                started("exec-done"),
+                Event::LoopCompleted {
+                    execution_id: "exec-done".to_string(),
+                    success: true,
+                    total_iterations: 1,
+                },
+                started("exec-deleted"),
+            ],
+        );
+
+        let report = fsck(&state, &runs, false, false).await.unwrap();
+        assert_eq!(report.checked, 2);
+        let kinds: Vec<(&str, &Drift)> = report
+            .discrepancies
+            .iter()
+            .map(|d| (d.exec_id.as_str(), &d.drift))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                (
+                    "exec-failed",
+                    &Drift::Status {
+                        stored: LoopExecutionStatus::Running,
+                        replayed: ReplayedStatus::Failed,
+                        repair_to: LoopExecutionStatus::Failed,
+                    }
+                ),
+                ("exec-failed", &Drift::Iteration { stored: 1, replayed: 3 }),
+                ("exec-deleted", &Drift::OrphanedLog),
+            ]
+        );
+        assert_eq!(report.repaired(), 0);
+
+        let report = fsck(&state, &runs, false, true).await.unwrap();
+        assert_eq!(report.repaired(), 2);
+        let repaired = state.get_execution("exec-failed").await.unwrap().unwrap();
+        assert_eq!(repaired.status, LoopExecutionStatus::Failed);
+        assert_eq!(repaired.iteration, 3);
+
+        // Only the orphaned log remains, and it is never "repaired"
+        let report = fsck(&state, &runs, false, true).await.unwrap();
+        assert_eq!(report.discrepancies.len(), 1);
+        assert_eq!(report.repaired(), 0);
+
+        state.shutdown().await.unwrap();
+    }
+
+    #[test]
+    fn test_replay_follows_reruns_and_stuck_actions() {
+        let id = || "e".to_string();
+        let events = [
+            Event::LoopStarted {
+                execution_id: id(),
+                loop_type: "ralph".to_string(),
+                task_description: String::new(),
+            },
+            Event::LoopCompleted {
+                execution_id: id(),
+                success: false,
+                total_iterations: 2,
+            },
+            Event::LoopStarted {
+                execution_id: id(),
+                loop_type: "ralph".to_string(),
+                task_description: String::new(),
+            },
+            Event::LoopStuck {
+                execution_id: id(),
+                iteration: 5,
+                unchanged_iterations: 3,
+                action: "escalate".to_string(),
+            },
+        ];
+        let replayed = ReplayedExecution::replay(&events);
+        assert_eq!(replayed.status, Some(ReplayedStatus::Escalated));
+        assert_eq!(replayed.iteration, 5);
+    }
+}
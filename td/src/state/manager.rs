@@ -2,14 +2,23 @@
 //!
 //! Processes commands via channels for thread-safe access to persistent state.
 
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use tokio::sync::mpsc;
 use tracing::{debug, info};
 
-use crate::domain::{Filter, FilterOp, IndexValue, IterationLog, Loop, LoopExecution, LoopExecutionStatus, Store};
+use crate::config::{DedupConfig, DedupMode};
+use crate::dedup::{Duplicate, find_duplicate};
+use crate::domain::{
+    Batch, CherryPick, DailyRollup, Filter, FilterOp, IndexValue, IterationLog, IterationLogFilter, Loop, LoopExecution,
+    LoopExecutionStatus, MetricsSnapshot, PLAN_TYPE, Plan, ReplSession, RunOverride, SPEC_TYPE, Spec, Store,
+    Submitter, ValidationOutcome, WakeCondition,
+};
 use crate::ipc::DaemonClient;
+use taskstore::now_ms;
 
 use super::messages::{StateCommand, StateError, StateResponse};
+use super::transaction::{StateTransaction, TxOp};
 
 /// Aggregated metrics from the daemon's state
 #[derive(Debug, Default, serde::Serialize)]
@@ -28,10 +37,55 @@ pub struct DaemonMetrics {
     pub failed: u64,
     /// Paused loops
     pub paused: u64,
+    /// Loops parked until a wake condition holds
+    pub parked: u64,
     /// Stopped loops
     pub stopped: u64,
     /// Total iterations across all loops
     pub total_iterations: u64,
+    /// Re-runs next to the executions they were cloned from
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reruns: Vec<RerunComparison>,
+}
+
+/// A re-run (`td exec rerun`) and the execution it was cloned from
+#[derive(Debug, serde::Serialize)]
+pub struct RerunComparison {
+    /// The execution that was cloned
+    pub original: RunStats,
+    /// The clone
+    pub rerun: RunStats,
+}
+
+/// Outcome and cost of one execution, for comparing re-runs
+#[derive(Debug, serde::Serialize)]
+pub struct RunStats {
+    /// Execution ID
+    pub id: String,
+    /// Current status
+    pub status: LoopExecutionStatus,
+    /// Model override, if the run had one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Iterations run so far
+    pub iterations: u32,
+    /// Input plus output tokens
+    pub tokens: u64,
+    /// Total validation time in milliseconds
+    pub duration_ms: u64,
+}
+
+impl From<&LoopExecution> for RunStats {
+    fn from(exec: &LoopExecution) -> Self {
+        Self {
+            id: exec.id.clone(),
+            status: exec.status,
+            model: exec.model.clone(),
+            iterations: exec.iteration,
+            tokens: exec.total_tokens(),
+            duration_ms: exec.total_duration_ms,
+        }
+    }
 }
 
 /// Event broadcast when state changes that TUI should react to
@@ -92,12 +146,27 @@ pub enum StateEvent {
         success: bool,
         total_iterations: u32,
     },
+    /// A query was rejected because it would deadlock
+    DeadlockDetected { execution_id: String, cycle: Vec<String> },
+    /// A path an execution edits is locked by another execution
+    PathConflict {
+        execution_id: String,
+        path: String,
+        held_by: String,
+    },
+    /// The daemon stopped picking up executions because a resource limit is exceeded
+    ResourcePressure { exceeded: Vec<String> },
+    /// The provider circuit breaker opened or closed (resuming `resumed` executions)
+    ProviderCircuit { open: bool, resumed: usize },
 }
 
+/// How often [`StateManager::modify_execution`] re-reads after a conflict before giving up
+const MODIFY_ATTEMPTS: u32 = 5;
+
 /// Path to the state change notification file
 /// This file contains a monotonically increasing counter that's bumped on every state change.
-/// External processes can poll this file to detect when they should refresh.
-fn state_notify_path() -> std::path::PathBuf {
+/// External processes watch (or poll) this file to detect when they should refresh.
+pub(super) fn state_notify_path() -> std::path::PathBuf {
     dirs::data_local_dir()
         .unwrap_or_else(|| std::path::PathBuf::from("."))
         .join("taskdaemon")
@@ -133,6 +202,8 @@ pub struct StateManager {
     tx: mpsc::Sender<StateCommand>,
     /// Broadcast sender for state change notifications
     event_tx: tokio::sync::broadcast::Sender<StateEvent>,
+    /// TaskStore directory
+    store_path: PathBuf,
 }
 
 impl StateManager {
@@ -151,6 +222,12 @@ impl StateManager {
             exec_count, iter_log_count, "Rebuilt indexes for Loop, LoopExecution, and IterationLog records"
         );
 
+        // Give plan/spec Loop records written before typed records existed their Plan/Spec
+        let migrated = migrate_typed_records(&mut store)?;
+        if migrated > 0 {
+            info!(migrated, "Created typed Plan/Spec records from generic Loop records");
+        }
+
         let (tx, rx) = mpsc::channel(256);
 
         // Broadcast channel for state change notifications (TUI subscribes)
@@ -161,7 +238,16 @@ impl StateManager {
 
         info!("StateManager spawned");
 
-        Ok(Self { tx, event_tx })
+        Ok(Self {
+            tx,
+            event_tx,
+            store_path: store_path.as_ref().to_path_buf(),
+        })
+    }
+
+    /// TaskStore directory this manager owns
+    pub fn store_path(&self) -> &Path {
+        &self.store_path
     }
 
     /// Subscribe to state change events (for instant TUI updates)
@@ -288,6 +374,117 @@ impl StateManager {
         self.list_loops(Some(loop_type.to_string()), None, None).await
     }
 
+    // === Typed Plan/Spec operations ===
+    //
+    // Plans and specs share their ID with a `plan`/`spec` Loop record. Saving a
+    // typed record copies title, status, file and priority (plus deps and
+    // phases for specs) onto that Loop, and updating the Loop copies them back,
+    // so the cascade and typed callers always agree.
+
+    /// Create or replace a Plan
+    pub async fn save_plan(&self, plan: Plan) -> StateResponse<()> {
+        debug!(plan_id = %plan.id, "save_plan: called");
+        let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+        self.tx
+            .send(StateCommand::SavePlan { plan, reply: reply_tx })
+            .await
+            .map_err(|_| StateError::ChannelError)?;
+        reply_rx.await.map_err(|_| StateError::ChannelError)?
+    }
+
+    /// Get a Plan by ID
+    pub async fn get_plan(&self, id: &str) -> StateResponse<Option<Plan>> {
+        debug!(%id, "get_plan: called");
+        let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+        self.tx
+            .send(StateCommand::GetPlan {
+                id: id.to_string(),
+                reply: reply_tx,
+            })
+            .await
+            .map_err(|_| StateError::ChannelError)?;
+        reply_rx.await.map_err(|_| StateError::ChannelError)?
+    }
+
+    /// List Plans, optionally by status, newest first
+    pub async fn list_plans(&self, status_filter: Option<String>) -> StateResponse<Vec<Plan>> {
+        debug!(?status_filter, "list_plans: called");
+        self.query_plans(status_filter, None).await
+    }
+
+    /// The Plan written by an execution, if any
+    pub async fn get_plan_for_execution(&self, exec_id: &str) -> StateResponse<Option<Plan>> {
+        debug!(%exec_id, "get_plan_for_execution: called");
+        Ok(self
+            .query_plans(None, Some(exec_id.to_string()))
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    async fn query_plans(&self, status_filter: Option<String>, exec_filter: Option<String>) -> StateResponse<Vec<Plan>> {
+        let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+        self.tx
+            .send(StateCommand::ListPlans {
+                status_filter,
+                exec_filter,
+                reply: reply_tx,
+            })
+            .await
+            .map_err(|_| StateError::ChannelError)?;
+        reply_rx.await.map_err(|_| StateError::ChannelError)?
+    }
+
+    /// Create or replace a Spec, linking it from its plan
+    pub async fn save_spec(&self, spec: Spec) -> StateResponse<()> {
+        debug!(spec_id = %spec.id, plan_id = ?spec.plan_id, "save_spec: called");
+        let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+        self.tx
+            .send(StateCommand::SaveSpec { spec, reply: reply_tx })
+            .await
+            .map_err(|_| StateError::ChannelError)?;
+        reply_rx.await.map_err(|_| StateError::ChannelError)?
+    }
+
+    /// Get a Spec by ID
+    pub async fn get_spec(&self, id: &str) -> StateResponse<Option<Spec>> {
+        debug!(%id, "get_spec: called");
+        let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+        self.tx
+            .send(StateCommand::GetSpec {
+                id: id.to_string(),
+                reply: reply_tx,
+            })
+            .await
+            .map_err(|_| StateError::ChannelError)?;
+        reply_rx.await.map_err(|_| StateError::ChannelError)?
+    }
+
+    /// List Specs, optionally by plan and status, oldest first
+    pub async fn list_specs(
+        &self,
+        plan_filter: Option<String>,
+        status_filter: Option<String>,
+    ) -> StateResponse<Vec<Spec>> {
+        debug!(?plan_filter, ?status_filter, "list_specs: called");
+        let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+        self.tx
+            .send(StateCommand::ListSpecs {
+                plan_filter,
+                status_filter,
+                reply: reply_tx,
+            })
+            .await
+            .map_err(|_| StateError::ChannelError)?;
+        reply_rx.await.map_err(|_| StateError::ChannelError)?
+    }
+
+    /// Specs decomposed from a plan
+    pub async fn list_specs_for_plan(&self, plan_id: &str) -> StateResponse<Vec<Spec>> {
+        debug!(%plan_id, "list_specs_for_plan: called");
+        self.list_specs(Some(plan_id.to_string()), None).await
+    }
+
     // === LoopExecution operations ===
 
     /// Create a new LoopExecution
@@ -326,6 +523,46 @@ impl StateManager {
         result
     }
 
+    /// Create an execution and, if it is pending, tell the daemon to pick it up
+    ///
+    /// Used by out-of-process submitters (e.g. `td exec submit`), which can't rely on
+    /// the in-process `ExecutionPending` broadcast reaching the daemon.
+    pub async fn submit_execution(&self, execution: LoopExecution) -> StateResponse<String> {
+        debug!(execution_id = %execution.id, "submit_execution: called");
+        let is_pending = execution.status == LoopExecutionStatus::Pending;
+        let id = self.create_execution(execution).await?;
+        if is_pending {
+            debug!(%id, "submit_execution: notifying daemon");
+            self.notify_daemon_pending(&id).await;
+        }
+        Ok(id)
+    }
+
+    /// Submit an execution unless it duplicates a recent unfinished one (see [`crate::dedup`])
+    ///
+    /// A duplicate fails with [`StateError::Duplicate`] when `config.mode`
+    /// blocks it; otherwise the execution is created and the duplicate it
+    /// resembles is returned with the new ID.
+    pub async fn submit_execution_checked(
+        &self,
+        execution: LoopExecution,
+        config: &DedupConfig,
+    ) -> StateResponse<(String, Option<Duplicate>)> {
+        debug!(execution_id = %execution.id, "submit_execution_checked: called");
+        let duplicate = if config.mode == DedupMode::Off {
+            None
+        } else {
+            let candidates = self.list_executions(None, Some(execution.loop_type.clone())).await?;
+            find_duplicate(&execution, &candidates, config, now_ms())
+        };
+        if let Some(duplicate) = duplicate.as_ref().filter(|d| d.blocked) {
+            debug!(existing = %duplicate.existing_id, "submit_execution_checked: blocked");
+            return Err(StateError::Duplicate(duplicate.clone()));
+        }
+        let id = self.submit_execution(execution).await?;
+        Ok((id, duplicate))
+    }
+
     /// Get a LoopExecution by ID
     pub async fn get_execution(&self, id: &str) -> StateResponse<Option<LoopExecution>> {
         debug!(%id, "get_execution: called");
@@ -340,8 +577,13 @@ impl StateManager {
         reply_rx.await.map_err(|_| StateError::ChannelError)?
     }
 
-    /// Update a LoopExecution
-    pub async fn update_execution(&self, execution: LoopExecution) -> StateResponse<()> {
+    /// Update a LoopExecution if nobody else has since the caller read it
+    ///
+    /// Compare-and-swap on `revision`: the update is stored only while the
+    /// stored revision still equals the caller's, and fails with
+    /// [`StateError::Conflict`] otherwise. Returns the new revision. Callers
+    /// that can simply re-apply their change should use [`Self::modify_execution`].
+    pub async fn update_execution(&self, execution: LoopExecution) -> StateResponse<u64> {
         debug!(execution_id = %execution.id, status = ?execution.status, "update_execution: called");
         let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
         self.tx
@@ -361,6 +603,36 @@ impl StateManager {
         result
     }
 
+    /// Read an execution, apply `change` to it and store it, retrying on conflicts
+    ///
+    /// `change` runs again on a fresh read whenever another writer got in
+    /// between, so it must be safe to repeat. Returns the stored execution.
+    pub async fn modify_execution<F>(&self, id: &str, mut change: F) -> StateResponse<LoopExecution>
+    where
+        F: FnMut(&mut LoopExecution),
+    {
+        debug!(%id, "modify_execution: called");
+        let mut attempt = 1;
+        loop {
+            let mut execution = self
+                .get_execution(id)
+                .await?
+                .ok_or_else(|| StateError::NotFound(format!("Execution {}", id)))?;
+            change(&mut execution);
+            match self.update_execution(execution.clone()).await {
+                Ok(revision) => {
+                    execution.revision = revision;
+                    return Ok(execution);
+                }
+                Err(e) if e.is_conflict() && attempt < MODIFY_ATTEMPTS => {
+                    debug!(%id, attempt, error = %e, "modify_execution: conflict, retrying with a fresh read");
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
     /// List LoopExecutions with optional filters
     pub async fn list_executions(
         &self,
@@ -410,6 +682,60 @@ impl StateManager {
         reply_rx.await.map_err(|_| StateError::ChannelError)?
     }
 
+    // === Transactions ===
+
+    /// Apply several Loop/LoopExecution writes atomically
+    ///
+    /// `build` queues the writes on a [`StateTransaction`]; they reach the actor
+    /// as one command and persist all together or not at all. Created
+    /// executions are announced (and pending ones handed to the LoopManager)
+    /// only after the whole transaction has committed.
+    pub async fn transaction<F, R>(&self, build: F) -> StateResponse<R>
+    where
+        F: FnOnce(&mut StateTransaction) -> R,
+    {
+        let mut tx = StateTransaction::new();
+        let value = build(&mut tx);
+        debug!(op_count = tx.ops().len(), "transaction: called");
+        if tx.is_empty() {
+            return Ok(value);
+        }
+
+        let ops = tx.into_ops();
+        let created: Vec<(String, String, bool)> = ops
+            .iter()
+            .filter_map(|op| match op {
+                TxOp::CreateExecution(exec) => Some((
+                    exec.id.clone(),
+                    exec.loop_type.clone(),
+                    exec.status == LoopExecutionStatus::Pending,
+                )),
+                _ => None,
+            })
+            .collect();
+
+        let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+        self.tx
+            .send(StateCommand::Transaction { ops, reply: reply_tx })
+            .await
+            .map_err(|_| StateError::ChannelError)?;
+        let written = reply_rx.await.map_err(|_| StateError::ChannelError)??;
+        debug!(written, "transaction: committed");
+
+        for (id, loop_type, is_pending) in created {
+            let _ = self.event_tx.send(StateEvent::ExecutionCreated {
+                id: id.clone(),
+                loop_type,
+            });
+            if is_pending {
+                let _ = self.event_tx.send(StateEvent::ExecutionPending { id });
+            }
+        }
+        notify_state_change();
+
+        Ok(value)
+    }
+
     // === IterationLog operations ===
 
     /// Create a new IterationLog
@@ -453,6 +779,18 @@ impl StateManager {
         reply_rx.await.map_err(|_| StateError::ChannelError)?
     }
 
+    /// List IterationLogs matching a filter (by execution, creation time range and outcome),
+    /// oldest first
+    pub async fn query_iteration_logs(&self, filter: IterationLogFilter) -> StateResponse<Vec<IterationLog>> {
+        debug!(?filter, "query_iteration_logs: called");
+        let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+        self.tx
+            .send(StateCommand::QueryIterationLogs { filter, reply: reply_tx })
+            .await
+            .map_err(|_| StateError::ChannelError)?;
+        reply_rx.await.map_err(|_| StateError::ChannelError)?
+    }
+
     /// Get a specific IterationLog by ID
     pub async fn get_iteration_log(&self, id: &str) -> StateResponse<Option<IterationLog>> {
         debug!(%id, "get_iteration_log: called");
@@ -481,6 +819,82 @@ impl StateManager {
         reply_rx.await.map_err(|_| StateError::ChannelError)?
     }
 
+    // === ReplSession operations ===
+
+    /// Create or replace a ReplSession
+    pub async fn save_repl_session(&self, session: ReplSession) -> StateResponse<()> {
+        debug!(session_id = %session.id, "save_repl_session: called");
+        let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+        self.tx
+            .send(StateCommand::SaveReplSession {
+                session,
+                reply: reply_tx,
+            })
+            .await
+            .map_err(|_| StateError::ChannelError)?;
+        reply_rx.await.map_err(|_| StateError::ChannelError)?
+    }
+
+    /// Get a ReplSession by ID
+    pub async fn get_repl_session(&self, id: &str) -> StateResponse<Option<ReplSession>> {
+        debug!(%id, "get_repl_session: called");
+        let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+        self.tx
+            .send(StateCommand::GetReplSession {
+                id: id.to_string(),
+                reply: reply_tx,
+            })
+            .await
+            .map_err(|_| StateError::ChannelError)?;
+        reply_rx.await.map_err(|_| StateError::ChannelError)?
+    }
+
+    /// List ReplSessions, most recently updated first
+    pub async fn list_repl_sessions(&self) -> StateResponse<Vec<ReplSession>> {
+        debug!("list_repl_sessions: called");
+        let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+        self.tx
+            .send(StateCommand::ListReplSessions { reply: reply_tx })
+            .await
+            .map_err(|_| StateError::ChannelError)?;
+        reply_rx.await.map_err(|_| StateError::ChannelError)?
+    }
+
+    // === Metrics history operations ===
+
+    /// Save a finished execution's metrics and recompute the affected daily rollups
+    pub async fn save_metrics_snapshot(&self, snapshot: MetricsSnapshot) -> StateResponse<()> {
+        debug!(exec_id = %snapshot.id, day = %snapshot.day, "save_metrics_snapshot: called");
+        let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+        self.tx
+            .send(StateCommand::SaveMetricsSnapshot {
+                snapshot,
+                reply: reply_tx,
+            })
+            .await
+            .map_err(|_| StateError::ChannelError)?;
+        reply_rx.await.map_err(|_| StateError::ChannelError)?
+    }
+
+    /// List daily rollups, optionally for one loop type and from a day (YYYY-MM-DD) on, oldest first
+    pub async fn list_daily_rollups(
+        &self,
+        loop_type_filter: Option<String>,
+        since_day: Option<String>,
+    ) -> StateResponse<Vec<DailyRollup>> {
+        debug!(?loop_type_filter, ?since_day, "list_daily_rollups: called");
+        let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+        self.tx
+            .send(StateCommand::ListDailyRollups {
+                loop_type_filter,
+                since_day,
+                reply: reply_tx,
+            })
+            .await
+            .map_err(|_| StateError::ChannelError)?;
+        reply_rx.await.map_err(|_| StateError::ChannelError)?
+    }
+
     /// Sync the store from JSONL files
     pub async fn sync(&self) -> StateResponse<()> {
         debug!("sync: called");
@@ -521,7 +935,18 @@ impl StateManager {
 
         let mut metrics = DaemonMetrics::default();
 
-        for exec in executions {
+        let by_id: HashMap<&str, &LoopExecution> = executions.iter().map(|e| (e.id.as_str(), e)).collect();
+        for exec in &executions {
+            if let Some(original) = exec.rerun_of.as_deref().and_then(|id| by_id.get(id)) {
+                debug!(rerun = %exec.id, original = %original.id, "get_metrics: found re-run");
+                metrics.reruns.push(RerunComparison {
+                    original: RunStats::from(*original),
+                    rerun: RunStats::from(exec),
+                });
+            }
+        }
+
+        for exec in &executions {
             metrics.total_executions += 1;
             match exec.status {
                 LoopExecutionStatus::Draft => {
@@ -544,10 +969,14 @@ impl StateManager {
                     debug!("get_metrics: status is Failed");
                     metrics.failed += 1;
                 }
-                LoopExecutionStatus::Paused => {
-                    debug!("get_metrics: status is Paused");
+                LoopExecutionStatus::Paused | LoopExecutionStatus::WaitingProvider => {
+                    debug!("get_metrics: status is Paused or WaitingProvider");
                     metrics.paused += 1;
                 }
+                LoopExecutionStatus::Parked => {
+                    debug!("get_metrics: status is Parked");
+                    metrics.parked += 1;
+                }
                 LoopExecutionStatus::Stopped => {
                     debug!("get_metrics: status is Stopped");
                     metrics.stopped += 1;
@@ -593,7 +1022,7 @@ impl StateManager {
 
         debug!("cancel_execution: setting status to Stopped");
         execution.set_status(LoopExecutionStatus::Stopped);
-        self.update_execution(execution).await
+        self.update_execution(execution).await.map(|_| ())
     }
 
     /// Pause a running execution
@@ -611,7 +1040,7 @@ impl StateManager {
 
         debug!("pause_execution: setting status to Paused");
         execution.set_status(LoopExecutionStatus::Paused);
-        self.update_execution(execution).await
+        self.update_execution(execution).await.map(|_| ())
     }
 
     /// Resume a paused execution
@@ -630,9 +1059,10 @@ impl StateManager {
         }
 
         debug!("resume_execution: setting status to Running");
+        execution.wake_conditions.clear();
         execution.set_status(LoopExecutionStatus::Running);
         let exec_id = execution.id.clone();
-        let result = self.update_execution(execution).await;
+        let result = self.update_execution(execution).await.map(|_| ());
 
         // Notify daemon via IPC for immediate pickup (fire-and-forget)
         if result.is_ok() {
@@ -642,6 +1072,38 @@ impl StateManager {
         result
     }
 
+    /// Approve the merge of an execution held by its risk score, then resume it
+    pub async fn approve_execution(&self, id: &str, approver: Option<Submitter>) -> StateResponse<()> {
+        debug!(%id, ?approver, "approve_execution: called");
+        let mut execution = self
+            .get_execution(id)
+            .await?
+            .ok_or_else(|| StateError::NotFound(format!("Execution {}", id)))?;
+
+        let held = execution.risk.as_ref().is_some_and(|risk| risk.holds_merge());
+        if execution.status != LoopExecutionStatus::Blocked || !held {
+            debug!("approve_execution: execution not awaiting approval");
+            return Err(StateError::StoreError(
+                "Can only approve blocked executions awaiting risk approval".to_string(),
+            ));
+        }
+
+        debug!("approve_execution: recording approval, setting status to Running");
+        if let Some(risk) = execution.risk.as_mut() {
+            risk.approve(approver);
+        }
+        execution.clear_error();
+        execution.set_status(LoopExecutionStatus::Running);
+        let exec_id = execution.id.clone();
+        let result = self.update_execution(execution).await.map(|_| ());
+
+        if result.is_ok() {
+            self.notify_daemon_resumed(&exec_id).await;
+        }
+
+        result
+    }
+
     /// Start a draft execution (transitions Draft -> Pending, daemon picks it up)
     pub async fn start_draft(&self, id: &str) -> StateResponse<()> {
         debug!(%id, "start_draft: called");
@@ -658,7 +1120,7 @@ impl StateManager {
         debug!("start_draft: marking execution as ready");
         execution.mark_ready();
         let exec_id = execution.id.clone();
-        let result = self.update_execution(execution).await;
+        let result = self.update_execution(execution).await.map(|_| ());
 
         // Notify daemon via IPC for immediate pickup (fire-and-forget)
         // Also send in-process event for same-process daemon
@@ -686,7 +1148,7 @@ impl StateManager {
         debug!("activate_draft: setting status to Pending for LoopManager pickup");
         execution.set_status(LoopExecutionStatus::Pending);
         let exec_id = execution.id.clone();
-        let result = self.update_execution(execution).await;
+        let result = self.update_execution(execution).await.map(|_| ());
 
         // Notify LoopManager that work is ready for immediate pickup
         // Both in-process event (for same-process daemon) and IPC (for separate daemon)
@@ -697,56 +1159,183 @@ impl StateManager {
 
         result
     }
-}
 
-/// The actor loop that owns the Store and processes commands
-async fn actor_loop(mut store: Store, mut rx: mpsc::Receiver<StateCommand>) {
-    debug!("actor_loop: called");
-    debug!("StateManager actor started");
+    /// Park an execution until one of `conditions` holds (daemon wakes it to Pending)
+    pub async fn park_execution(&self, id: &str, conditions: Vec<WakeCondition>) -> StateResponse<()> {
+        debug!(%id, count = conditions.len(), "park_execution: called");
+        let mut execution = self
+            .get_execution(id)
+            .await?
+            .ok_or_else(|| StateError::NotFound(format!("Execution {}", id)))?;
 
-    while let Some(cmd) = rx.recv().await {
-        match cmd {
-            // Loop operations (generic work units)
-            StateCommand::CreateLoop { record, reply } => {
-                debug!(record_id = %record.id, "actor_loop: CreateLoop command");
-                let result = store.create(record).map_err(|e| StateError::StoreError(e.to_string()));
-                let _ = reply.send(result);
-            }
+        if execution.is_terminal() || execution.is_draft() {
+            debug!("park_execution: execution is terminal or draft, cannot park");
+            return Err(StateError::StoreError(
+                "Cannot park a draft or terminal execution".to_string(),
+            ));
+        }
+        if conditions.is_empty() {
+            debug!("park_execution: no wake conditions");
+            return Err(StateError::StoreError(
+                "At least one wake condition is required".to_string(),
+            ));
+        }
 
-            StateCommand::GetLoop { id, reply } => {
-                debug!(%id, "actor_loop: GetLoop command");
-                let result: StateResponse<Option<Loop>> =
-                    store.get(&id).map_err(|e| StateError::StoreError(e.to_string()));
-                let _ = reply.send(result);
-            }
+        debug!("park_execution: setting status to Parked");
+        execution.park(conditions);
+        self.update_execution(execution).await.map(|_| ())
+    }
 
-            StateCommand::UpdateLoop { record, reply } => {
-                debug!(record_id = %record.id, "actor_loop: UpdateLoop command");
-                let result = store.update(record).map_err(|e| StateError::StoreError(e.to_string()));
-                let _ = reply.send(result);
-            }
+    /// Wake a parked execution (transitions Parked -> Pending, daemon picks it up)
+    pub async fn wake_execution(&self, id: &str) -> StateResponse<()> {
+        debug!(%id, "wake_execution: called");
+        let mut execution = self
+            .get_execution(id)
+            .await?
+            .ok_or_else(|| StateError::NotFound(format!("Execution {}", id)))?;
 
-            StateCommand::ListLoops {
-                type_filter,
-                status_filter,
-                parent_filter,
-                reply,
-            } => {
-                debug!(
-                    ?type_filter,
-                    ?status_filter,
-                    ?parent_filter,
-                    "actor_loop: ListLoops command"
-                );
-                let mut filters = Vec::new();
-                if let Some(loop_type) = type_filter {
-                    debug!(%loop_type, "actor_loop: ListLoops adding type filter");
-                    filters.push(Filter {
-                        field: "type".to_string(),
-                        op: FilterOp::Eq,
-                        value: IndexValue::String(loop_type),
-                    });
-                }
+        if !execution.wake() {
+            debug!("wake_execution: execution is not parked");
+            return Err(StateError::StoreError("Can only wake parked executions".to_string()));
+        }
+
+        let exec_id = execution.id.clone();
+        let result = self.update_execution(execution).await.map(|_| ());
+
+        // Same pickup path as start_draft: in-process event plus IPC
+        if result.is_ok() {
+            let _ = self.event_tx.send(StateEvent::ExecutionPending { id: exec_id.clone() });
+            self.notify_daemon_pending(&exec_id).await;
+        }
+
+        result
+    }
+
+    /// Continue an execution from `iteration` once its worktree was restored to that snapshot (-> Pending)
+    pub async fn rollback_execution(&self, id: &str, iteration: u32) -> StateResponse<()> {
+        debug!(%id, iteration, "rollback_execution: called");
+        let mut execution = self
+            .get_execution(id)
+            .await?
+            .ok_or_else(|| StateError::NotFound(format!("Execution {}", id)))?;
+
+        if !execution.roll_back_to(iteration) {
+            debug!("rollback_execution: execution cannot be rolled back");
+            return Err(StateError::StoreError(
+                "Can only roll back paused, blocked, parked, waiting, failed or stopped executions".to_string(),
+            ));
+        }
+
+        let exec_id = execution.id.clone();
+        let result = self.update_execution(execution).await.map(|_| ());
+
+        if result.is_ok() {
+            let _ = self.event_tx.send(StateEvent::ExecutionPending { id: exec_id.clone() });
+            self.notify_daemon_pending(&exec_id).await;
+        }
+
+        result
+    }
+
+    /// Record commits cherry-picked from an execution's branch
+    pub async fn record_cherry_pick(&self, id: &str, pick: CherryPick) -> StateResponse<()> {
+        debug!(%id, branch = %pick.branch, "record_cherry_pick: called");
+        self.modify_execution(id, |execution| execution.record_cherry_pick(pick.clone()))
+            .await
+            .map(|_| ())
+    }
+
+    /// Add and remove an execution's tags, returning the resulting tag list
+    pub async fn tag_execution(&self, id: &str, add: &[String], remove: &[String]) -> StateResponse<Vec<String>> {
+        debug!(%id, ?add, ?remove, "tag_execution: called");
+        let execution = self
+            .modify_execution(id, |execution| {
+                execution.add_tags(add);
+                execution.remove_tags(remove);
+            })
+            .await?;
+        Ok(execution.tags)
+    }
+
+    /// Change an unfinished execution's model or iteration cap (`td exec set`)
+    ///
+    /// A running loop applies the change at its next iteration boundary.
+    pub async fn set_overrides(&self, id: &str, changes: &[RunOverride]) -> StateResponse<LoopExecution> {
+        debug!(%id, ?changes, "set_overrides: called");
+        let execution = self
+            .get_execution(id)
+            .await?
+            .ok_or_else(|| StateError::NotFound(format!("Execution {}", id)))?;
+        if execution.is_terminal() {
+            debug!(status = %execution.status, "set_overrides: execution finished");
+            return Err(StateError::StoreError(format!(
+                "Execution {} is {}; overrides only apply to unfinished executions",
+                id, execution.status
+            )));
+        }
+        self.modify_execution(id, |execution| {
+            for change in changes {
+                execution.apply_override(change);
+            }
+        })
+        .await
+    }
+}
+
+/// The actor loop that owns the Store and processes commands
+async fn actor_loop(mut store: Store, mut rx: mpsc::Receiver<StateCommand>) {
+    debug!("actor_loop: called");
+    debug!("StateManager actor started");
+
+    while let Some(cmd) = rx.recv().await {
+        match cmd {
+            // Loop operations (generic work units)
+            StateCommand::CreateLoop { record, reply } => {
+                debug!(record_id = %record.id, "actor_loop: CreateLoop command");
+                let result = store
+                    .create(record.clone())
+                    .and_then(|id| sync_typed_record(&mut store, &record).map(|_| id))
+                    .map_err(|e| StateError::StoreError(e.to_string()));
+                let _ = reply.send(result);
+            }
+
+            StateCommand::GetLoop { id, reply } => {
+                debug!(%id, "actor_loop: GetLoop command");
+                let result: StateResponse<Option<Loop>> =
+                    store.get(&id).map_err(|e| StateError::StoreError(e.to_string()));
+                let _ = reply.send(result);
+            }
+
+            StateCommand::UpdateLoop { record, reply } => {
+                debug!(record_id = %record.id, "actor_loop: UpdateLoop command");
+                let result = store
+                    .update(record.clone())
+                    .and_then(|_| sync_typed_record(&mut store, &record))
+                    .map_err(|e| StateError::StoreError(e.to_string()));
+                let _ = reply.send(result);
+            }
+
+            StateCommand::ListLoops {
+                type_filter,
+                status_filter,
+                parent_filter,
+                reply,
+            } => {
+                debug!(
+                    ?type_filter,
+                    ?status_filter,
+                    ?parent_filter,
+                    "actor_loop: ListLoops command"
+                );
+                let mut filters = Vec::new();
+                if let Some(loop_type) = type_filter {
+                    debug!(%loop_type, "actor_loop: ListLoops adding type filter");
+                    filters.push(Filter {
+                        field: "type".to_string(),
+                        op: FilterOp::Eq,
+                        value: IndexValue::String(loop_type),
+                    });
+                }
                 if let Some(status) = status_filter {
                     debug!(%status, "actor_loop: ListLoops adding status filter");
                     filters.push(Filter {
@@ -769,6 +1358,88 @@ async fn actor_loop(mut store: Store, mut rx: mpsc::Receiver<StateCommand>) {
                 let _ = reply.send(result);
             }
 
+            StateCommand::SavePlan { plan, reply } => {
+                debug!(plan_id = %plan.id, "actor_loop: SavePlan command");
+                let result = save_plan(&mut store, plan).map_err(|e| StateError::StoreError(e.to_string()));
+                let _ = reply.send(result);
+            }
+
+            StateCommand::GetPlan { id, reply } => {
+                debug!(%id, "actor_loop: GetPlan command");
+                let result: StateResponse<Option<Plan>> =
+                    store.get(&id).map_err(|e| StateError::StoreError(e.to_string()));
+                let _ = reply.send(result);
+            }
+
+            StateCommand::ListPlans {
+                status_filter,
+                exec_filter,
+                reply,
+            } => {
+                debug!(?status_filter, ?exec_filter, "actor_loop: ListPlans command");
+                let mut filters = Vec::new();
+                if let Some(status) = status_filter {
+                    filters.push(Filter {
+                        field: "status".to_string(),
+                        op: FilterOp::Eq,
+                        value: IndexValue::String(status),
+                    });
+                }
+                if let Some(exec_id) = exec_filter {
+                    filters.push(Filter {
+                        field: "exec_id".to_string(),
+                        op: FilterOp::Eq,
+                        value: IndexValue::String(exec_id),
+                    });
+                }
+                let result: StateResponse<Vec<Plan>> =
+                    store.list(&filters).map_err(|e| StateError::StoreError(e.to_string()));
+                let _ = reply.send(result);
+            }
+
+            StateCommand::SaveSpec { spec, reply } => {
+                debug!(spec_id = %spec.id, "actor_loop: SaveSpec command");
+                let result = save_spec(&mut store, spec).map_err(|e| StateError::StoreError(e.to_string()));
+                let _ = reply.send(result);
+            }
+
+            StateCommand::GetSpec { id, reply } => {
+                debug!(%id, "actor_loop: GetSpec command");
+                let result: StateResponse<Option<Spec>> =
+                    store.get(&id).map_err(|e| StateError::StoreError(e.to_string()));
+                let _ = reply.send(result);
+            }
+
+            StateCommand::ListSpecs {
+                plan_filter,
+                status_filter,
+                reply,
+            } => {
+                debug!(?plan_filter, ?status_filter, "actor_loop: ListSpecs command");
+                let mut filters = Vec::new();
+                if let Some(plan_id) = plan_filter {
+                    filters.push(Filter {
+                        field: "plan_id".to_string(),
+                        op: FilterOp::Eq,
+                        value: IndexValue::String(plan_id),
+                    });
+                }
+                if let Some(status) = status_filter {
+                    filters.push(Filter {
+                        field: "status".to_string(),
+                        op: FilterOp::Eq,
+                        value: IndexValue::String(status),
+                    });
+                }
+                let result: StateResponse<Vec<Spec>> =
+                    store.list(&filters).map_err(|e| StateError::StoreError(e.to_string()));
+                let result = result.map(|mut specs| {
+                    specs.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+                    specs
+                });
+                let _ = reply.send(result);
+            }
+
             StateCommand::CreateExecution { execution, reply } => {
                 debug!(execution_id = %execution.id, "actor_loop: CreateExecution command");
                 let result = store
@@ -784,11 +1455,15 @@ async fn actor_loop(mut store: Store, mut rx: mpsc::Receiver<StateCommand>) {
                 let _ = reply.send(result);
             }
 
-            StateCommand::UpdateExecution { execution, reply } => {
-                debug!(execution_id = %execution.id, "actor_loop: UpdateExecution command");
-                let result = store
-                    .update(execution)
-                    .map_err(|e| StateError::StoreError(e.to_string()));
+            StateCommand::UpdateExecution { mut execution, reply } => {
+                debug!(execution_id = %execution.id, revision = execution.revision, "actor_loop: UpdateExecution command");
+                let result = next_revision(&store, &execution).and_then(|revision| {
+                    execution.revision = revision;
+                    store
+                        .update(execution)
+                        .map(|_| revision)
+                        .map_err(|e| StateError::StoreError(e.to_string()))
+                });
                 let _ = reply.send(result);
             }
 
@@ -825,6 +1500,7 @@ async fn actor_loop(mut store: Store, mut rx: mpsc::Receiver<StateCommand>) {
                 debug!(%id, "actor_loop: DeleteLoop command");
                 let result = store
                     .delete::<Loop>(&id)
+                    .and_then(|_| delete_typed_record(&mut store, &id))
                     .map_err(|e| StateError::StoreError(e.to_string()));
                 let _ = reply.send(result);
             }
@@ -843,6 +1519,12 @@ async fn actor_loop(mut store: Store, mut rx: mpsc::Receiver<StateCommand>) {
                 let _ = reply.send(result);
             }
 
+            StateCommand::Transaction { ops, reply } => {
+                debug!(op_count = ops.len(), "actor_loop: Transaction command");
+                let result = apply_transaction(&mut store, &ops).map_err(|e| StateError::StoreError(e.to_string()));
+                let _ = reply.send(result);
+            }
+
             StateCommand::GetGeneric {
                 collection: _,
                 id: _,
@@ -877,6 +1559,36 @@ async fn actor_loop(mut store: Store, mut rx: mpsc::Receiver<StateCommand>) {
                 let _ = reply.send(result);
             }
 
+            StateCommand::QueryIterationLogs { filter, reply } => {
+                debug!(?filter, "actor_loop: QueryIterationLogs command");
+                let mut filters = Vec::new();
+                if let Some(execution_id) = &filter.execution_id {
+                    filters.push(Filter {
+                        field: "execution_id".to_string(),
+                        op: FilterOp::Eq,
+                        value: IndexValue::String(execution_id.clone()),
+                    });
+                }
+                if filter.outcome == Some(ValidationOutcome::Passed) {
+                    filters.push(Filter {
+                        field: "exit_code".to_string(),
+                        op: FilterOp::Eq,
+                        value: IndexValue::Int(0),
+                    });
+                }
+                // Time range and the remaining outcomes are not indexed; apply them here
+                let result: StateResponse<Vec<IterationLog>> =
+                    store.list(&filters).map_err(|e| StateError::StoreError(e.to_string()));
+                let result = result.map(|mut logs| {
+                    logs.retain(|l| filter.matches(l));
+                    logs.sort_by(|a, b| {
+                        (a.created_at, &a.execution_id, a.iteration).cmp(&(b.created_at, &b.execution_id, b.iteration))
+                    });
+                    logs
+                });
+                let _ = reply.send(result);
+            }
+
             StateCommand::GetIterationLog { id, reply } => {
                 debug!(%id, "actor_loop: GetIterationLog command");
                 let result: StateResponse<Option<IterationLog>> =
@@ -892,6 +1604,69 @@ async fn actor_loop(mut store: Store, mut rx: mpsc::Receiver<StateCommand>) {
                 let _ = reply.send(result);
             }
 
+            // ReplSession operations
+            StateCommand::SaveReplSession { session, reply } => {
+                debug!(session_id = %session.id, "actor_loop: SaveReplSession command");
+                let result = store.update(session).map_err(|e| StateError::StoreError(e.to_string()));
+                let _ = reply.send(result);
+            }
+
+            StateCommand::GetReplSession { id, reply } => {
+                debug!(%id, "actor_loop: GetReplSession command");
+                let result: StateResponse<Option<ReplSession>> =
+                    store.get(&id).map_err(|e| StateError::StoreError(e.to_string()));
+                let _ = reply.send(result);
+            }
+
+            StateCommand::ListReplSessions { reply } => {
+                debug!("actor_loop: ListReplSessions command");
+                let result: StateResponse<Vec<ReplSession>> =
+                    store.list(&[]).map_err(|e| StateError::StoreError(e.to_string()));
+                // Most recent first
+                let result = result.map(|mut sessions| {
+                    sessions.sort_by_key(|s| std::cmp::Reverse(s.updated_at));
+                    sessions
+                });
+                let _ = reply.send(result);
+            }
+
+            StateCommand::SaveMetricsSnapshot { snapshot, reply } => {
+                debug!(exec_id = %snapshot.id, "actor_loop: SaveMetricsSnapshot command");
+                let result =
+                    save_metrics_snapshot(&mut store, snapshot).map_err(|e| StateError::StoreError(e.to_string()));
+                let _ = reply.send(result);
+            }
+
+            StateCommand::ListDailyRollups {
+                loop_type_filter,
+                since_day,
+                reply,
+            } => {
+                debug!(?loop_type_filter, ?since_day, "actor_loop: ListDailyRollups command");
+                let mut filters = Vec::new();
+                if let Some(loop_type) = loop_type_filter {
+                    filters.push(Filter {
+                        field: "loop_type".to_string(),
+                        op: FilterOp::Eq,
+                        value: IndexValue::String(loop_type),
+                    });
+                }
+                if let Some(day) = since_day {
+                    filters.push(Filter {
+                        field: "day".to_string(),
+                        op: FilterOp::Gte,
+                        value: IndexValue::String(day),
+                    });
+                }
+                let result: StateResponse<Vec<DailyRollup>> =
+                    store.list(&filters).map_err(|e| StateError::StoreError(e.to_string()));
+                let result = result.map(|mut rollups| {
+                    rollups.sort_by(|a, b| a.day.cmp(&b.day).then_with(|| a.loop_type.cmp(&b.loop_type)));
+                    rollups
+                });
+                let _ = reply.send(result);
+            }
+
             StateCommand::Sync { reply } => {
                 debug!("actor_loop: Sync command");
                 let result = store.sync().map_err(|e| StateError::StoreError(e.to_string()));
@@ -913,6 +1688,26 @@ async fn actor_loop(mut store: Store, mut rx: mpsc::Receiver<StateCommand>) {
                     debug!(count = c, "actor_loop: RebuildIndexes IterationLog indexes rebuilt");
                     count += c;
                 }
+                if let Ok(c) = store.rebuild_indexes::<ReplSession>() {
+                    debug!(count = c, "actor_loop: RebuildIndexes ReplSession indexes rebuilt");
+                    count += c;
+                }
+                if let Ok(c) = store.rebuild_indexes::<MetricsSnapshot>() {
+                    debug!(count = c, "actor_loop: RebuildIndexes MetricsSnapshot indexes rebuilt");
+                    count += c;
+                }
+                if let Ok(c) = store.rebuild_indexes::<DailyRollup>() {
+                    debug!(count = c, "actor_loop: RebuildIndexes DailyRollup indexes rebuilt");
+                    count += c;
+                }
+                if let Ok(c) = store.rebuild_indexes::<Plan>() {
+                    debug!(count = c, "actor_loop: RebuildIndexes Plan indexes rebuilt");
+                    count += c;
+                }
+                if let Ok(c) = store.rebuild_indexes::<Spec>() {
+                    debug!(count = c, "actor_loop: RebuildIndexes Spec indexes rebuilt");
+                    count += c;
+                }
                 let _ = reply.send(Ok(count));
             }
 
@@ -927,6 +1722,242 @@ async fn actor_loop(mut store: Store, mut rx: mpsc::Receiver<StateCommand>) {
     debug!("StateManager actor stopped");
 }
 
+/// Keep the typed Plan/Spec of a `plan`/`spec` Loop record in step with it
+///
+/// Creates the typed record the first time; afterwards only the shared fields
+/// are copied, so typed-only fields (acceptance, links) survive.
+fn sync_typed_record(store: &mut Store, record: &Loop) -> eyre::Result<()> {
+    let mut batch = Batch::new();
+    stage_typed_record(store, record, &mut batch)?;
+    store.apply_batch(&batch)?;
+    Ok(())
+}
+
+/// Stage the typed Plan/Spec writes that follow a `plan`/`spec` Loop record
+fn stage_typed_record(store: &Store, record: &Loop, batch: &mut Batch) -> eyre::Result<()> {
+    match record.r#type.as_str() {
+        PLAN_TYPE => {
+            debug!(record_id = %record.id, "stage_typed_record: plan");
+            let plan = match store.get::<Plan>(&record.id)? {
+                Some(mut plan) => {
+                    plan.sync_from_loop(record);
+                    plan
+                }
+                None => Plan::from_loop(record),
+            };
+            batch.put(&plan)
+        }
+        SPEC_TYPE => {
+            debug!(record_id = %record.id, "stage_typed_record: spec");
+            let spec = match store.get::<Spec>(&record.id)? {
+                Some(mut spec) => {
+                    spec.sync_from_loop(record);
+                    spec
+                }
+                None => Spec::from_loop(record),
+            };
+            stage_link_spec(store, &spec, batch)?;
+            batch.put(&spec)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Drop the typed record sharing a deleted Loop record's ID
+fn delete_typed_record(store: &mut Store, id: &str) -> eyre::Result<()> {
+    let mut batch = Batch::new();
+    stage_typed_delete(store, id, &mut batch)?;
+    store.apply_batch(&batch)?;
+    Ok(())
+}
+
+/// Stage deleting whichever typed record shares a Loop record's ID
+fn stage_typed_delete(store: &Store, id: &str, batch: &mut Batch) -> eyre::Result<()> {
+    if store.get::<Plan>(id)?.is_some() {
+        batch.delete::<Plan>(id);
+    }
+    if store.get::<Spec>(id)?.is_some() {
+        batch.delete::<Spec>(id);
+    }
+    Ok(())
+}
+
+/// Save a Plan and copy its shared fields onto its Loop record
+fn save_plan(store: &mut Store, plan: Plan) -> eyre::Result<()> {
+    let mut batch = Batch::new();
+    if let Some(mut record) = store.get::<Loop>(&plan.id)? {
+        debug!(plan_id = %plan.id, "save_plan: mirroring onto Loop record");
+        record.title = plan.title.clone();
+        record.status = plan.status.clone();
+        record.file = plan.file.clone();
+        record.priority = plan.priority;
+        record.updated_at = plan.updated_at;
+        batch.put(&record)?;
+    }
+    batch.put(&plan)?;
+    store.apply_batch(&batch)?;
+    Ok(())
+}
+
+/// Save a Spec, link it from its plan, and copy its shared fields onto its Loop record
+fn save_spec(store: &mut Store, spec: Spec) -> eyre::Result<()> {
+    let mut batch = Batch::new();
+    stage_link_spec(store, &spec, &mut batch)?;
+    if let Some(mut record) = store.get::<Loop>(&spec.id)? {
+        debug!(spec_id = %spec.id, "save_spec: mirroring onto Loop record");
+        record.title = spec.title.clone();
+        record.status = spec.status.clone();
+        record.file = spec.file.clone();
+        record.deps = spec.deps.clone();
+        record.phases = spec.phases.clone();
+        record.priority = spec.priority;
+        record.updated_at = spec.updated_at;
+        batch.put(&record)?;
+    }
+    batch.put(&spec)?;
+    store.apply_batch(&batch)?;
+    Ok(())
+}
+
+/// Stage adding a spec to its plan's `spec_ids` (no-op without a known plan)
+fn stage_link_spec(store: &Store, spec: &Spec, batch: &mut Batch) -> eyre::Result<()> {
+    let Some(plan_id) = &spec.plan_id else {
+        return Ok(());
+    };
+    if let Some(mut plan) = store.get::<Plan>(plan_id)?
+        && !plan.spec_ids.contains(&spec.id)
+    {
+        debug!(%plan_id, spec_id = %spec.id, "stage_link_spec: linking spec to plan");
+        plan.add_spec(&spec.id);
+        batch.put(&plan)?;
+    }
+    Ok(())
+}
+
+/// Revision an execution update will be stored with
+///
+/// Fails with a conflict when the stored execution has moved past the
+/// revision the update was read at. Updating an execution that isn't stored
+/// yet creates it.
+fn next_revision(store: &Store, execution: &LoopExecution) -> StateResponse<u64> {
+    let stored = store
+        .get::<LoopExecution>(&execution.id)
+        .map_err(|e| StateError::StoreError(e.to_string()))?;
+    match stored {
+        Some(stored) if stored.revision != execution.revision => {
+            debug!(execution_id = %execution.id, expected = execution.revision, actual = stored.revision, "next_revision: conflict");
+            Err(StateError::Conflict {
+                id: execution.id.clone(),
+                expected: execution.revision,
+                actual: stored.revision,
+            })
+        }
+        Some(stored) => Ok(stored.revision + 1),
+        None => Ok(execution.revision),
+    }
+}
+
+/// Apply a transaction's writes as one TaskStore batch
+///
+/// Follow-on writes (typed Plan/Spec records, IterationLogs of a deleted
+/// execution) are staged from what is already stored and land in the same
+/// batch. Returns the number of records written.
+fn apply_transaction(store: &mut Store, ops: &[TxOp]) -> eyre::Result<usize> {
+    debug!(op_count = ops.len(), "apply_transaction: called");
+    let mut batch = Batch::new();
+    for op in ops {
+        match op {
+            TxOp::PutLoop(record) => {
+                batch.put(record)?;
+                stage_typed_record(store, record, &mut batch)?;
+            }
+            TxOp::DeleteLoop(id) => {
+                batch.delete::<Loop>(id);
+                stage_typed_delete(store, id, &mut batch)?;
+            }
+            TxOp::CreateExecution(execution) => {
+                batch.put(execution)?;
+            }
+            TxOp::UpdateExecution(execution) => {
+                let mut execution = execution.clone();
+                execution.revision = next_revision(store, &execution)?;
+                batch.put(&execution)?;
+            }
+            TxOp::DeleteExecution(id) => {
+                let logs: Vec<IterationLog> = store.list(&[Filter {
+                    field: "execution_id".to_string(),
+                    op: FilterOp::Eq,
+                    value: IndexValue::String(id.clone()),
+                }])?;
+                debug!(%id, log_count = logs.len(), "apply_transaction: deleting execution with its logs");
+                for log in &logs {
+                    batch.delete::<IterationLog>(&log.id);
+                }
+                batch.delete::<LoopExecution>(id);
+            }
+        }
+    }
+    store.apply_batch(&batch)
+}
+
+/// Create typed records for `plan`/`spec` Loop records that have none yet
+///
+/// Plans go first so specs can link to them. Returns how many were created.
+fn migrate_typed_records(store: &mut Store) -> eyre::Result<usize> {
+    debug!("migrate_typed_records: called");
+    let mut records: Vec<Loop> = store.list(&[])?;
+    records.retain(|r| r.r#type == PLAN_TYPE || r.r#type == SPEC_TYPE);
+    records.sort_by_key(|r| (r.r#type != PLAN_TYPE, r.created_at));
+
+    let mut created = 0;
+    for record in &records {
+        let exists = match record.r#type.as_str() {
+            PLAN_TYPE => store.get::<Plan>(&record.id)?.is_some(),
+            _ => store.get::<Spec>(&record.id)?.is_some(),
+        };
+        if !exists {
+            debug!(record_id = %record.id, r#type = %record.r#type, "migrate_typed_records: creating typed record");
+            sync_typed_record(store, record)?;
+            created += 1;
+        }
+    }
+    Ok(created)
+}
+
+/// Upsert a snapshot, then recompute the rollups of the day it lands on and,
+/// if the execution was snapshotted before on another day, of that day too
+fn save_metrics_snapshot(store: &mut Store, snapshot: MetricsSnapshot) -> eyre::Result<()> {
+    debug!(exec_id = %snapshot.id, day = %snapshot.day, "save_metrics_snapshot: called");
+    let previous: Option<MetricsSnapshot> = store.get(&snapshot.id)?;
+    let mut affected = vec![(snapshot.day.clone(), snapshot.loop_type.clone())];
+    if let Some(previous) = previous
+        && previous.day != snapshot.day
+    {
+        debug!(previous_day = %previous.day, "save_metrics_snapshot: execution moved days");
+        affected.push((previous.day, previous.loop_type));
+    }
+    store.update(snapshot)?;
+
+    for (day, loop_type) in affected {
+        let filters = vec![
+            Filter {
+                field: "day".to_string(),
+                op: FilterOp::Eq,
+                value: IndexValue::String(day.clone()),
+            },
+            Filter {
+                field: "loop_type".to_string(),
+                op: FilterOp::Eq,
+                value: IndexValue::String(loop_type.clone()),
+            },
+        ];
+        let snapshots: Vec<MetricsSnapshot> = store.list(&filters)?;
+        debug!(%day, %loop_type, count = snapshots.len(), "save_metrics_snapshot: recomputing rollup");
+        store.update(DailyRollup::from_snapshots(&day, &loop_type, &snapshots))?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -985,6 +2016,74 @@ mod tests {
         manager.shutdown().await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_spawn_migrates_plan_and_spec_loops_to_typed_records() {
+        let temp = tempdir().unwrap();
+        {
+            // Records written before typed Plan/Spec existed
+            let mut store = Store::open(temp.path()).unwrap();
+            store.create(Loop::with_id("plan-1", PLAN_TYPE, "Auth")).unwrap();
+            let mut spec = Loop::with_id("spec-1", SPEC_TYPE, "Schema").with_parent("plan-1");
+            spec.add_phase(crate::domain::Phase::new("Phase 1", "Tables"));
+            store.create(spec).unwrap();
+            store.create(Loop::with_id("other-1", "ralph", "Fix it")).unwrap();
+        }
+
+        let manager = StateManager::spawn(temp.path()).unwrap();
+        let plan = manager.get_plan("plan-1").await.unwrap().unwrap();
+        assert_eq!(plan.title, "Auth");
+        assert_eq!(plan.spec_ids, ["spec-1"]);
+        let specs = manager.list_specs_for_plan("plan-1").await.unwrap();
+        assert_eq!(specs.len(), 1);
+        assert_eq!(specs[0].phases_progress(), "0/1");
+        assert!(manager.get_plan("other-1").await.unwrap().is_none());
+        assert!(manager.get_spec("other-1").await.unwrap().is_none());
+
+        // Typed updates reach the Loop record the cascade reads
+        let mut spec = specs[0].clone();
+        spec.status = crate::domain::LoopStatus::Complete;
+        manager.save_spec(spec).await.unwrap();
+        let record = manager.get_loop_required("spec-1").await.unwrap();
+        assert_eq!(record.status, crate::domain::LoopStatus::Complete);
+
+        manager.delete_loop("plan-1").await.unwrap();
+        assert!(manager.get_plan("plan-1").await.unwrap().is_none());
+
+        manager.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_update_execution_rejects_stale_revision() {
+        let temp = tempdir().unwrap();
+        let manager = StateManager::spawn(temp.path()).unwrap();
+        manager
+            .create_execution(LoopExecution::with_id("cas-exec", "ralph"))
+            .await
+            .unwrap();
+
+        // Two writers read the same revision; the second to write loses
+        let mut tui = manager.get_execution("cas-exec").await.unwrap().unwrap();
+        let mut cli = tui.clone();
+        tui.set_status(LoopExecutionStatus::Paused);
+        assert_eq!(manager.update_execution(tui).await.unwrap(), 1);
+        cli.add_tags(&["urgent".to_string()]);
+        let err = manager.update_execution(cli).await.unwrap_err();
+        assert!(err.is_conflict());
+        assert!(matches!(err, StateError::Conflict { expected: 0, actual: 1, .. }));
+
+        // Retrying on a fresh read keeps both changes
+        let exec = manager
+            .modify_execution("cas-exec", |exec| exec.add_tags(&["urgent".to_string()]))
+            .await
+            .unwrap();
+        assert_eq!(exec.revision, 2);
+        let stored = manager.get_execution("cas-exec").await.unwrap().unwrap();
+        assert_eq!(stored.status, LoopExecutionStatus::Paused);
+        assert_eq!(stored.tags, ["urgent"]);
+
+        manager.shutdown().await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_state_manager_get_nonexistent() {
         let temp = tempdir().unwrap();
@@ -1071,6 +2170,177 @@ mod tests {
         manager.shutdown().await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_get_metrics_compares_reruns() {
+        let temp = tempdir().unwrap();
+        let manager = StateManager::spawn(temp.path()).unwrap();
+
+        let mut original = LoopExecution::with_id("orig-exec", "ralph");
+        original.set_status(crate::domain::LoopExecutionStatus::Failed);
+        original.iteration = 10;
+        original.add_iteration_metrics(1000, 500, 60_000);
+        let mut rerun = original.rerun(1);
+        rerun.model = Some("anthropic/claude-opus-4".to_string());
+        let rerun_id = rerun.id.clone();
+        manager.create_execution(original).await.unwrap();
+        manager.create_execution(rerun).await.unwrap();
+        manager.create_execution(LoopExecution::with_id("other-exec", "ralph")).await.unwrap();
+
+        let metrics = manager.get_metrics().await.unwrap();
+        assert_eq!(metrics.reruns.len(), 1);
+        let comparison = &metrics.reruns[0];
+        assert_eq!(comparison.original.id, "orig-exec");
+        assert_eq!(comparison.original.iterations, 10);
+        assert_eq!(comparison.original.tokens, 1500);
+        assert_eq!(comparison.rerun.id, rerun_id);
+        assert_eq!(comparison.rerun.model.as_deref(), Some("anthropic/claude-opus-4"));
+        assert_eq!(comparison.rerun.status, crate::domain::LoopExecutionStatus::Pending);
+
+        manager.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_submit_execution_checked_detects_duplicates() {
+        let temp = tempdir().unwrap();
+        let manager = StateManager::spawn(temp.path()).unwrap();
+        let draft = |id: &str| {
+            let mut exec = LoopExecution::with_id(id, "ralph").with_context_value("task", "Fix the login redirect");
+            exec.set_status(crate::domain::LoopExecutionStatus::Draft);
+            exec
+        };
+
+        let warn = DedupConfig::default();
+        let (_, duplicate) = manager.submit_execution_checked(draft("first"), &warn).await.unwrap();
+        assert!(duplicate.is_none());
+        let (id, duplicate) = manager.submit_execution_checked(draft("second"), &warn).await.unwrap();
+        assert_eq!(id, "second");
+        assert_eq!(duplicate.unwrap().existing_id, "first");
+
+        let block = DedupConfig {
+            mode: DedupMode::Block,
+            ..warn
+        };
+        let result = manager.submit_execution_checked(draft("third"), &block).await;
+        assert!(matches!(result, Err(StateError::Duplicate(d)) if d.blocked));
+        assert!(manager.get_execution("third").await.unwrap().is_none());
+
+        manager.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_set_overrides() {
+        let temp = tempdir().unwrap();
+        let manager = StateManager::spawn(temp.path()).unwrap();
+        manager
+            .create_execution(LoopExecution::with_id("live-exec", "ralph"))
+            .await
+            .unwrap();
+
+        let changes = vec![
+            RunOverride::Model(Some("anthropic/claude-opus-4".to_string())),
+            RunOverride::MaxIterations(Some(20)),
+        ];
+        let updated = manager.set_overrides("live-exec", &changes).await.unwrap();
+        assert_eq!(updated.model.as_deref(), Some("anthropic/claude-opus-4"));
+        assert_eq!(updated.max_iterations, Some(20));
+
+        let mut done = manager.get_execution("live-exec").await.unwrap().unwrap();
+        done.set_status(LoopExecutionStatus::Complete);
+        manager.update_execution(done).await.unwrap();
+        assert!(manager.set_overrides("live-exec", &changes).await.is_err());
+        assert!(manager.set_overrides("missing", &changes).await.is_err());
+
+        manager.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_park_and_wake_execution() {
+        let temp = tempdir().unwrap();
+        let manager = StateManager::spawn(temp.path()).unwrap();
+        let mut events = manager.subscribe_events();
+
+        let mut exec = LoopExecution::with_id("park-exec", "phase");
+        exec.set_status(crate::domain::LoopExecutionStatus::Running);
+        manager.create_execution(exec).await.unwrap();
+
+        // Parking needs at least one condition
+        assert!(manager.park_execution("park-exec", Vec::new()).await.is_err());
+
+        manager
+            .park_execution("park-exec", vec![WakeCondition::file_exists("ready.flag")])
+            .await
+            .unwrap();
+        let parked = manager.get_execution("park-exec").await.unwrap().unwrap();
+        assert_eq!(parked.status, crate::domain::LoopExecutionStatus::Parked);
+        assert_eq!(parked.wake_conditions.len(), 1);
+        assert_eq!(manager.get_metrics().await.unwrap().parked, 1);
+
+        manager.wake_execution("park-exec").await.unwrap();
+        let woken = manager.get_execution("park-exec").await.unwrap().unwrap();
+        assert_eq!(woken.status, crate::domain::LoopExecutionStatus::Pending);
+        assert!(woken.wake_conditions.is_empty());
+
+        // Waking notifies the daemon like any other pending transition
+        let mut saw_pending = false;
+        while let Ok(event) = events.try_recv() {
+            saw_pending |= matches!(event, StateEvent::ExecutionPending { ref id } if id == "park-exec");
+        }
+        assert!(saw_pending);
+
+        // Only parked executions can be woken
+        assert!(manager.wake_execution("park-exec").await.is_err());
+
+        manager.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_tag_execution() {
+        let temp = tempdir().unwrap();
+        let manager = StateManager::spawn(temp.path()).unwrap();
+        manager
+            .create_execution(LoopExecution::with_id("tag-exec", "ralph"))
+            .await
+            .unwrap();
+
+        let tags = manager
+            .tag_execution("tag-exec", &["backend".to_string(), "urgent".to_string()], &[])
+            .await
+            .unwrap();
+        assert_eq!(tags, ["backend", "urgent"]);
+
+        manager
+            .tag_execution("tag-exec", &[], &["urgent".to_string()])
+            .await
+            .unwrap();
+        let exec = manager.get_execution("tag-exec").await.unwrap().unwrap();
+        assert_eq!(exec.tags, ["backend"]);
+        assert!(manager.tag_execution("missing", &[], &[]).await.is_err());
+
+        manager.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rollback_execution() {
+        let temp = tempdir().unwrap();
+        let manager = StateManager::spawn(temp.path()).unwrap();
+
+        let mut exec = LoopExecution::with_id("rollback-exec", "phase");
+        exec.set_status(crate::domain::LoopExecutionStatus::Running);
+        exec.iteration = 6;
+        manager.create_execution(exec).await.unwrap();
+
+        // A running execution still owns its worktree
+        assert!(manager.rollback_execution("rollback-exec", 2).await.is_err());
+
+        manager.pause_execution("rollback-exec").await.unwrap();
+        manager.rollback_execution("rollback-exec", 2).await.unwrap();
+        let rolled_back = manager.get_execution("rollback-exec").await.unwrap().unwrap();
+        assert_eq!(rolled_back.status, crate::domain::LoopExecutionStatus::Pending);
+        assert_eq!(rolled_back.iteration, 2);
+
+        manager.shutdown().await.unwrap();
+    }
+
     // === NEGATIVE TESTS: start_draft ===
 
     #[tokio::test]
@@ -1156,6 +2426,45 @@ mod tests {
         manager.shutdown().await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_approve_execution_held_by_risk() {
+        let temp = tempdir().unwrap();
+        let manager = StateManager::spawn(temp.path()).unwrap();
+
+        let factor = crate::domain::RiskFactor {
+            reason: "1 file in **/migrations/**".to_string(),
+            points: 40,
+        };
+        let mut exec = LoopExecution::with_id("risky-exec", "phase");
+        exec.set_status(crate::domain::LoopExecutionStatus::Blocked);
+        exec.set_error("Risk score 40/30, awaiting approval");
+        exec.set_risk(crate::domain::RiskAssessment::new(vec![factor], 30, 1, 12));
+        manager.create_execution(exec).await.unwrap();
+
+        // A blocked execution without a risk hold is not approvable
+        let mut other = LoopExecution::with_id("blocked-exec", "phase");
+        other.set_status(crate::domain::LoopExecutionStatus::Blocked);
+        manager.create_execution(other).await.unwrap();
+        assert!(manager.approve_execution("blocked-exec", None).await.is_err());
+
+        let approver = Submitter {
+            user: "alice".to_string(),
+            name: None,
+        };
+        manager.approve_execution("risky-exec", Some(approver)).await.unwrap();
+        let exec = manager.get_execution("risky-exec").await.unwrap().unwrap();
+        assert_eq!(exec.status, crate::domain::LoopExecutionStatus::Running);
+        assert!(exec.last_error.is_none());
+        let risk = exec.risk.unwrap();
+        assert!(!risk.holds_merge());
+        assert_eq!(risk.approved_by.unwrap().user, "alice");
+
+        // Already approved
+        assert!(manager.approve_execution("risky-exec", None).await.is_err());
+
+        manager.shutdown().await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_start_draft_fails_for_paused_execution() {
         let temp = tempdir().unwrap();
@@ -1253,6 +2562,43 @@ mod tests {
         manager.shutdown().await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_query_iteration_logs() {
+        let temp = tempdir().unwrap();
+        let manager = StateManager::spawn(temp.path()).unwrap();
+
+        for (exec_id, iteration, exit_code, created_at) in
+            [("exec-a", 1, 1, 1_000), ("exec-a", 2, 0, 2_000), ("exec-b", 1, -1, 1_500), ("exec-b", 2, 0, 3_000)]
+        {
+            let log = IterationLog::new(exec_id, iteration)
+                .with_created_at(created_at)
+                .with_exit_code(exit_code);
+            manager.create_iteration_log(log).await.unwrap();
+        }
+
+        let ids = |logs: Vec<IterationLog>| logs.into_iter().map(|l| l.id).collect::<Vec<_>>();
+
+        let all = manager.query_iteration_logs(IterationLogFilter::default()).await.unwrap();
+        assert_eq!(
+            ids(all),
+            vec!["exec-a-iter-1", "exec-b-iter-1", "exec-a-iter-2", "exec-b-iter-2"]
+        );
+
+        let passed = manager
+            .query_iteration_logs(IterationLogFilter::default().with_outcome(Some(ValidationOutcome::Passed)))
+            .await
+            .unwrap();
+        assert_eq!(ids(passed), vec!["exec-a-iter-2", "exec-b-iter-2"]);
+
+        let filter = IterationLogFilter::for_execution("exec-b").with_time_range(Some(1_000), Some(2_000));
+        assert_eq!(ids(manager.query_iteration_logs(filter).await.unwrap()), vec!["exec-b-iter-1"]);
+
+        let errors = IterationLogFilter::default().with_outcome(Some(ValidationOutcome::Error));
+        assert_eq!(ids(manager.query_iteration_logs(errors).await.unwrap()), vec!["exec-b-iter-1"]);
+
+        manager.shutdown().await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_delete_iteration_logs() {
         let temp = tempdir().unwrap();
@@ -1312,6 +2658,47 @@ mod tests {
         manager.shutdown().await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_transaction_applies_all_writes_or_none() {
+        let temp = tempdir().unwrap();
+        let manager = StateManager::spawn(temp.path()).unwrap();
+        let mut events = manager.subscribe_events();
+
+        let plan = Loop::with_id("tx-plan", PLAN_TYPE, "Plan");
+        manager.create_loop(plan.clone()).await.unwrap();
+
+        let mut parent = plan.clone();
+        parent.set_status(crate::domain::LoopStatus::InProgress);
+        let child = LoopExecution::with_id("tx-child", "spec");
+        let count = manager
+            .transaction(|tx| {
+                tx.update_loop(parent.clone()).create_execution(child.clone());
+                tx.ops().len()
+            })
+            .await
+            .unwrap();
+        assert_eq!(count, 2);
+        assert!(manager.get_execution("tx-child").await.unwrap().is_some());
+        let stored_plan = manager.get_plan("tx-plan").await.unwrap().unwrap();
+        assert_eq!(stored_plan.status, crate::domain::LoopStatus::InProgress);
+        assert!(matches!(
+            events.try_recv(),
+            Ok(StateEvent::ExecutionCreated { ref id, .. }) if id == "tx-child"
+        ));
+
+        // A bad write anywhere means none of the others land either
+        let result = manager
+            .transaction(|tx| {
+                tx.create_execution(LoopExecution::with_id("tx-other", "spec"))
+                    .create_execution(LoopExecution::with_id(" ", "spec"));
+            })
+            .await;
+        assert!(result.is_err());
+        assert!(manager.get_execution("tx-other").await.unwrap().is_none());
+
+        manager.shutdown().await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_iteration_log_created_event() {
         let temp = tempdir().unwrap();
@@ -1345,4 +2732,83 @@ mod tests {
 
         manager.shutdown().await.unwrap();
     }
+
+    // === ReplSession tests ===
+
+    #[tokio::test]
+    async fn test_repl_session_save_and_list() {
+        let temp = tempdir().unwrap();
+        let manager = StateManager::spawn(temp.path()).unwrap();
+
+        let mut older = ReplSession::new("chat");
+        older.updated_at = 1;
+        let mut newer = ReplSession::new("plan");
+        newer.updated_at = 2;
+        manager.save_repl_session(older.clone()).await.unwrap();
+        manager.save_repl_session(newer.clone()).await.unwrap();
+
+        // Saving again replaces rather than duplicates
+        older.title = "renamed".to_string();
+        older.updated_at = 3;
+        manager.save_repl_session(older.clone()).await.unwrap();
+
+        let sessions = manager.list_repl_sessions().await.unwrap();
+        let ids: Vec<_> = sessions.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec![older.id.as_str(), newer.id.as_str()]);
+
+        let retrieved = manager.get_repl_session(&older.id).await.unwrap().unwrap();
+        assert_eq!(retrieved.title, "renamed");
+
+        manager.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_metrics_snapshot_rollups() {
+        let temp = tempdir().unwrap();
+        let manager = StateManager::spawn(temp.path()).unwrap();
+
+        let mut exec = LoopExecution::with_id("metrics-a", "implement");
+        exec.iteration = 3;
+        let a = MetricsSnapshot::from_execution(&exec, "complete", "claude-sonnet-4");
+        let day = a.day.clone();
+        manager.save_metrics_snapshot(a).await.unwrap();
+
+        exec = LoopExecution::with_id("metrics-b", "implement");
+        exec.iteration = 5;
+        let b = MetricsSnapshot::from_execution(&exec, "failed", "claude-sonnet-4");
+        manager.save_metrics_snapshot(b.clone()).await.unwrap();
+
+        // Re-saving the same execution replaces it rather than double counting
+        manager.save_metrics_snapshot(b).await.unwrap();
+
+        let rollups = manager
+            .list_daily_rollups(Some("implement".to_string()), Some(day.clone()))
+            .await
+            .unwrap();
+        assert_eq!(rollups.len(), 1);
+        assert_eq!(rollups[0].runs, 2);
+        assert_eq!(rollups[0].complete, 1);
+        assert_eq!(rollups[0].total_iterations, 8);
+
+        // Moving a snapshot to another day recomputes both days
+        let moved = MetricsSnapshot {
+            day: "2000-01-01".to_string(),
+            ..MetricsSnapshot::from_execution(&LoopExecution::with_id("metrics-a", "implement"), "complete", "")
+        };
+        manager.save_metrics_snapshot(moved).await.unwrap();
+        let rollups = manager.list_daily_rollups(None, None).await.unwrap();
+        assert_eq!(rollups.len(), 2);
+        assert_eq!(rollups[0].day, "2000-01-01");
+        assert_eq!(rollups[1].runs, 1);
+
+        assert!(
+            manager
+                .list_daily_rollups(Some("plan".to_string()), None)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        manager.shutdown().await.unwrap();
+    }
 }
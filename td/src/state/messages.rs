@@ -6,7 +6,10 @@ use serde_json::Value;
 use thiserror::Error;
 use tokio::sync::oneshot;
 
-use crate::domain::{IterationLog, Loop, LoopExecution};
+use crate::dedup::Duplicate;
+use crate::domain::{DailyRollup, IterationLog, IterationLogFilter, Loop, LoopExecution, MetricsSnapshot, Plan, ReplSession, Spec};
+
+use super::transaction::TxOp;
 
 /// Errors from state operations
 #[derive(Debug, Error)]
@@ -22,6 +25,19 @@ pub enum StateError {
 
     #[error("Channel error")]
     ChannelError,
+
+    #[error("Revision conflict on {id}: expected revision {expected}, stored revision is {actual}")]
+    Conflict { id: String, expected: u64, actual: u64 },
+
+    #[error("{0}")]
+    Duplicate(Duplicate),
+}
+
+impl StateError {
+    /// True if an update lost a race with another writer (re-read and retry)
+    pub fn is_conflict(&self) -> bool {
+        matches!(self, Self::Conflict { .. })
+    }
 }
 
 /// Response from state operations
@@ -50,6 +66,34 @@ pub enum StateCommand {
         reply: oneshot::Sender<StateResponse<Vec<Loop>>>,
     },
 
+    // Typed Plan/Spec operations
+    SavePlan {
+        plan: Plan,
+        reply: oneshot::Sender<StateResponse<()>>,
+    },
+    GetPlan {
+        id: String,
+        reply: oneshot::Sender<StateResponse<Option<Plan>>>,
+    },
+    ListPlans {
+        status_filter: Option<String>,
+        exec_filter: Option<String>,
+        reply: oneshot::Sender<StateResponse<Vec<Plan>>>,
+    },
+    SaveSpec {
+        spec: Spec,
+        reply: oneshot::Sender<StateResponse<()>>,
+    },
+    GetSpec {
+        id: String,
+        reply: oneshot::Sender<StateResponse<Option<Spec>>>,
+    },
+    ListSpecs {
+        plan_filter: Option<String>,
+        status_filter: Option<String>,
+        reply: oneshot::Sender<StateResponse<Vec<Spec>>>,
+    },
+
     // LoopExecution operations
     CreateExecution {
         execution: LoopExecution,
@@ -61,7 +105,7 @@ pub enum StateCommand {
     },
     UpdateExecution {
         execution: LoopExecution,
-        reply: oneshot::Sender<StateResponse<()>>,
+        reply: oneshot::Sender<StateResponse<u64>>,
     },
     ListExecutions {
         status_filter: Option<String>,
@@ -79,6 +123,12 @@ pub enum StateCommand {
         reply: oneshot::Sender<StateResponse<()>>,
     },
 
+    // Transactions
+    Transaction {
+        ops: Vec<TxOp>,
+        reply: oneshot::Sender<StateResponse<usize>>,
+    },
+
     // Generic operations
     GetGeneric {
         collection: String,
@@ -95,6 +145,10 @@ pub enum StateCommand {
         execution_id: String,
         reply: oneshot::Sender<StateResponse<Vec<IterationLog>>>,
     },
+    QueryIterationLogs {
+        filter: IterationLogFilter,
+        reply: oneshot::Sender<StateResponse<Vec<IterationLog>>>,
+    },
     GetIterationLog {
         id: String,
         reply: oneshot::Sender<StateResponse<Option<IterationLog>>>,
@@ -104,6 +158,30 @@ pub enum StateCommand {
         reply: oneshot::Sender<StateResponse<usize>>,
     },
 
+    // ReplSession operations
+    SaveReplSession {
+        session: ReplSession,
+        reply: oneshot::Sender<StateResponse<()>>,
+    },
+    GetReplSession {
+        id: String,
+        reply: oneshot::Sender<StateResponse<Option<ReplSession>>>,
+    },
+    ListReplSessions {
+        reply: oneshot::Sender<StateResponse<Vec<ReplSession>>>,
+    },
+
+    // Metrics history operations
+    SaveMetricsSnapshot {
+        snapshot: MetricsSnapshot,
+        reply: oneshot::Sender<StateResponse<()>>,
+    },
+    ListDailyRollups {
+        loop_type_filter: Option<String>,
+        since_day: Option<String>,
+        reply: oneshot::Sender<StateResponse<Vec<DailyRollup>>>,
+    },
+
     // Sync operations
     Sync {
         reply: oneshot::Sender<StateResponse<()>>,
@@ -1,12 +1,19 @@
 //! State management with actor pattern
 //!
 //! StateManager owns the TaskStore and processes messages via channels,
-//! providing thread-safe access to persistent state.
+//! providing thread-safe access to persistent state. Other processes learn of
+//! changes through [`StateWatcher`].
 
+mod fsck;
 mod manager;
 mod messages;
 mod recovery;
+mod transaction;
+mod watch;
 
-pub use manager::{DaemonMetrics, StateEvent, StateManager, read_state_version};
+pub use fsck::{Discrepancy, Drift, FsckReport, ReplayedExecution, ReplayedStatus, fsck};
+pub use manager::{DaemonMetrics, RerunComparison, RunStats, StateEvent, StateManager, read_state_version};
 pub use messages::{StateCommand, StateError, StateResponse};
 pub use recovery::{RecoveryStats, recover, scan_for_recovery};
+pub use transaction::{StateTransaction, TxOp};
+pub use watch::StateWatcher;
@@ -0,0 +1,96 @@
+//! Multi-record transactions
+//!
+//! A [`StateTransaction`] collects Loop and LoopExecution writes on the
+//! caller's side; [`super::StateManager::transaction`] then hands them to the
+//! actor as one command, which applies them as a single TaskStore batch.
+//! Nothing else runs between the writes and either all of them persist or
+//! none do.
+
+use tracing::debug;
+
+use crate::domain::{Loop, LoopExecution};
+
+/// A write queued in a transaction
+#[derive(Debug, Clone)]
+pub enum TxOp {
+    /// Create or replace a Loop record (typed Plan/Spec records follow it)
+    PutLoop(Loop),
+    /// Delete a Loop record and its typed record
+    DeleteLoop(String),
+    /// Create a LoopExecution
+    CreateExecution(LoopExecution),
+    /// Replace a LoopExecution
+    UpdateExecution(LoopExecution),
+    /// Delete a LoopExecution and its IterationLogs
+    DeleteExecution(String),
+}
+
+/// Writes applied together by [`super::StateManager::transaction`]
+#[derive(Debug, Default)]
+pub struct StateTransaction {
+    ops: Vec<TxOp>,
+}
+
+impl StateTransaction {
+    /// Create an empty transaction
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue creating a Loop record
+    pub fn create_loop(&mut self, record: Loop) -> &mut Self {
+        debug!(record_id = %record.id, "StateTransaction::create_loop: called");
+        self.ops.push(TxOp::PutLoop(record));
+        self
+    }
+
+    /// Queue updating a Loop record
+    pub fn update_loop(&mut self, record: Loop) -> &mut Self {
+        debug!(record_id = %record.id, "StateTransaction::update_loop: called");
+        self.ops.push(TxOp::PutLoop(record));
+        self
+    }
+
+    /// Queue deleting a Loop record
+    pub fn delete_loop(&mut self, id: &str) -> &mut Self {
+        debug!(%id, "StateTransaction::delete_loop: called");
+        self.ops.push(TxOp::DeleteLoop(id.to_string()));
+        self
+    }
+
+    /// Queue creating a LoopExecution
+    pub fn create_execution(&mut self, execution: LoopExecution) -> &mut Self {
+        debug!(execution_id = %execution.id, "StateTransaction::create_execution: called");
+        self.ops.push(TxOp::CreateExecution(execution));
+        self
+    }
+
+    /// Queue updating a LoopExecution
+    pub fn update_execution(&mut self, execution: LoopExecution) -> &mut Self {
+        debug!(execution_id = %execution.id, "StateTransaction::update_execution: called");
+        self.ops.push(TxOp::UpdateExecution(execution));
+        self
+    }
+
+    /// Queue deleting a LoopExecution
+    pub fn delete_execution(&mut self, id: &str) -> &mut Self {
+        debug!(%id, "StateTransaction::delete_execution: called");
+        self.ops.push(TxOp::DeleteExecution(id.to_string()));
+        self
+    }
+
+    /// Queued writes in the order they will be applied
+    pub fn ops(&self) -> &[TxOp] {
+        &self.ops
+    }
+
+    /// True if nothing has been queued
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Take the queued writes
+    pub fn into_ops(self) -> Vec<TxOp> {
+        self.ops
+    }
+}
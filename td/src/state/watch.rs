@@ -0,0 +1,206 @@
+//! Filesystem notifications for cross-process state changes
+//!
+//! Every state change bumps the `.state_version` file, and other processes
+//! used to poll it. A [`StateWatcher`] instead watches that file and the
+//! TaskStore's `.jsonl` files with inotify and wakes its owner as soon as one
+//! is written, so the TUI and the daemon react within milliseconds. Where
+//! inotify is not available (macOS, or when the watch cannot be set up)
+//! [`StateWatcher::changed`] never fires and callers keep polling.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Notify;
+use tracing::{debug, warn};
+
+/// Least time between two wake-ups, so a burst of writes is one refresh
+const MIN_WAKE_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Wakes its owner when the shared state changes on disk
+pub struct StateWatcher {
+    notify: Arc<Notify>,
+    /// The task reading notifications (None: not event-driven)
+    task: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl StateWatcher {
+    /// Watch the state-version file and the TaskStore at `store_path`
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub fn spawn(store_path: &Path) -> Self {
+        debug!(store_path = %store_path.display(), "StateWatcher::spawn: called");
+        let notify = Arc::new(Notify::new());
+        let version_path = super::manager::state_notify_path();
+        let task = match platform::watch(&version_path, store_path, notify.clone()) {
+            Ok(task) => {
+                debug!("StateWatcher::spawn: watching for changes");
+                Some(task)
+            }
+            Err(e) => {
+                warn!(error = %e, "File change notifications unavailable, polling for state changes");
+                None
+            }
+        };
+        Self { notify, task }
+    }
+
+    /// A watcher that never fires (callers poll)
+    pub fn polling() -> Self {
+        Self {
+            notify: Arc::new(Notify::new()),
+            task: None,
+        }
+    }
+
+    /// Whether changes are reported as they happen (false: callers must poll)
+    pub fn is_event_driven(&self) -> bool {
+        self.task.is_some()
+    }
+
+    /// Wait for the next change; never returns when not event-driven
+    pub async fn changed(&self) {
+        if self.task.is_none() {
+            return std::future::pending().await;
+        }
+        self.notify.notified().await;
+        // Let the rest of a burst land, then swallow the wake-up it left behind
+        tokio::time::sleep(MIN_WAKE_INTERVAL).await;
+        let _ = tokio::time::timeout(Duration::ZERO, self.notify.notified()).await;
+    }
+}
+
+impl Drop for StateWatcher {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::os::fd::{AsFd, AsRawFd, RawFd};
+    use std::path::Path;
+    use std::sync::Arc;
+
+    use nix::sys::inotify::{AddWatchFlags, InitFlags, Inotify};
+    use tokio::io::unix::AsyncFd;
+    use tokio::sync::Notify;
+    use tracing::{debug, trace, warn};
+
+    /// `Inotify` as the raw fd `AsyncFd` wants
+    struct InotifyFd(Inotify);
+
+    impl AsRawFd for InotifyFd {
+        fn as_raw_fd(&self) -> RawFd {
+            self.0.as_fd().as_raw_fd()
+        }
+    }
+
+    /// Watch the directories holding `version_path` and the TaskStore, waking `notify` on changes
+    pub fn watch(
+        version_path: &Path,
+        store_path: &Path,
+        notify: Arc<Notify>,
+    ) -> std::io::Result<tokio::task::JoinHandle<()>> {
+        let version_dir = version_path.parent().unwrap_or(Path::new("."));
+        std::fs::create_dir_all(version_dir)?;
+        let version_name = version_path.file_name().map(|name| name.to_os_string());
+
+        let inotify = Inotify::init(InitFlags::IN_NONBLOCK | InitFlags::IN_CLOEXEC)?;
+        let flags = AddWatchFlags::IN_CLOSE_WRITE | AddWatchFlags::IN_MOVED_TO;
+        let version_wd = inotify.add_watch(version_dir, flags)?;
+        let store_wd = inotify.add_watch(store_path, flags)?;
+        let fd = AsyncFd::new(InotifyFd(inotify))?;
+
+        Ok(tokio::spawn(async move {
+            loop {
+                let mut guard = match fd.readable().await {
+                    Ok(guard) => guard,
+                    Err(e) => {
+                        warn!(error = %e, "State watcher stopped");
+                        return;
+                    }
+                };
+                let events = match fd.get_ref().0.read_events() {
+                    Ok(events) => events,
+                    Err(nix::errno::Errno::EAGAIN) => {
+                        guard.clear_ready();
+                        continue;
+                    }
+                    Err(e) => {
+                        warn!(error = %e, "State watcher stopped");
+                        return;
+                    }
+                };
+                let relevant = events.iter().any(|event| {
+                    let name = event.name.as_deref();
+                    if event.wd == version_wd {
+                        name == version_name.as_deref()
+                    } else {
+                        event.wd == store_wd
+                            && name.is_some_and(|name| Path::new(name).extension().is_some_and(|ext| ext == "jsonl"))
+                    }
+                });
+                trace!(count = events.len(), relevant, "StateWatcher: events");
+                if relevant {
+                    debug!("StateWatcher: state changed on disk");
+                    notify.notify_one();
+                }
+            }
+        }))
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod platform {
+    use std::path::Path;
+    use std::sync::Arc;
+
+    use tokio::sync::Notify;
+
+    /// No file notifications on this platform; the caller falls back to polling
+    pub fn watch(
+        _version_path: &Path,
+        _store_path: &Path,
+        _notify: Arc<Notify>,
+    ) -> std::io::Result<tokio::task::JoinHandle<()>> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "no file change notifications on this platform",
+        ))
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_watcher_wakes_on_store_writes() {
+        let temp = tempdir().unwrap();
+        let version = temp.path().join("version").join(".state_version");
+        let notify = Arc::new(Notify::new());
+        let watcher = StateWatcher {
+            notify: notify.clone(),
+            task: Some(platform::watch(&version, temp.path(), notify).unwrap()),
+        };
+        assert!(watcher.is_event_driven());
+
+        // Other files are ignored
+        std::fs::write(temp.path().join("taskstore.db"), "x").unwrap();
+        assert!(tokio::time::timeout(Duration::from_millis(200), watcher.changed()).await.is_err());
+
+        std::fs::write(temp.path().join("loop_executions.jsonl"), "{}\n").unwrap();
+        tokio::time::timeout(Duration::from_secs(5), watcher.changed()).await.unwrap();
+
+        std::fs::write(&version, "7").unwrap();
+        tokio::time::timeout(Duration::from_secs(5), watcher.changed()).await.unwrap();
+
+        let polling = StateWatcher::polling();
+        assert!(!polling.is_event_driven());
+        assert!(tokio::time::timeout(Duration::from_millis(50), polling.changed()).await.is_err());
+    }
+}
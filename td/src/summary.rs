@@ -0,0 +1,460 @@
+//! Global summary of executions
+//!
+//! Aggregates the TaskStore execution records and their event logs
+//! (`~/.taskdaemon/runs/{id}/events.jsonl`) into one overview: per-loop-type
+//! success rate, mean iterations, mean cost and p95 duration, the most recent
+//! failures with their reasons, queue depth, and rate-limit pressure. Shown by
+//! `td summary` and the TUI summary screen.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use tracing::debug;
+
+use crate::domain::{LoopExecution, LoopExecutionStatus};
+use crate::events::{Event, EventLogEntry};
+use crate::llm::TokenUsage;
+
+/// Number of recent failures listed
+pub const RECENT_FAILURES: usize = 5;
+
+/// Window over which rate-limit pressure is measured
+pub const RATE_LIMIT_WINDOW_MINUTES: i64 = 60;
+
+/// Aggregates for one loop type
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TypeSummary {
+    pub loop_type: String,
+    pub total: usize,
+    pub active: usize,
+    pub complete: usize,
+    pub failed: usize,
+    /// complete / (complete + failed); None until something has finished
+    pub success_rate: Option<f64>,
+    /// Mean iterations of finished executions
+    pub mean_iterations: Option<f64>,
+    /// Mean estimated cost of finished executions
+    pub mean_cost_usd: Option<f64>,
+    /// 95th percentile wall-clock duration of finished executions
+    pub p95_duration_ms: Option<u64>,
+}
+
+/// A failed execution and why it failed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FailureSummary {
+    pub id: String,
+    pub loop_type: String,
+    pub reason: String,
+    /// Last update of the record (ms since epoch)
+    pub failed_at: i64,
+}
+
+/// Rate limiting seen in the last [`RATE_LIMIT_WINDOW_MINUTES`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RateLimitPressure {
+    pub hits: usize,
+    /// Executions that were rate limited at least once
+    pub executions: usize,
+    /// Total time spent waiting for retries
+    pub wait_ms: u64,
+}
+
+/// Overview of all executions
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Summary {
+    pub types: Vec<TypeSummary>,
+    pub recent_failures: Vec<FailureSummary>,
+    /// Pending executions waiting for a slot
+    pub queue_depth: usize,
+    pub running: usize,
+    pub parked: usize,
+    pub rate_limits: RateLimitPressure,
+}
+
+/// Per-execution figures taken from its event log
+#[derive(Debug, Default)]
+struct LoggedRun {
+    started_at: Option<DateTime<Utc>>,
+    completed_at: Option<DateTime<Utc>>,
+    input_tokens: u64,
+    output_tokens: u64,
+    last_error: Option<String>,
+}
+
+impl LoggedRun {
+    fn from_events(entries: &[EventLogEntry]) -> Self {
+        let mut run = Self::default();
+        for entry in entries {
+            match &entry.event {
+                Event::LoopStarted { .. } => {
+                    run.started_at.get_or_insert(entry.timestamp);
+                }
+                Event::LoopCompleted { .. } => run.completed_at = Some(entry.timestamp),
+                Event::ResponseCompleted {
+                    input_tokens,
+                    output_tokens,
+                    ..
+                } => {
+                    run.input_tokens += input_tokens;
+                    run.output_tokens += output_tokens;
+                }
+                Event::Error { context, message, .. } => run.last_error = Some(format!("{}: {}", context, message)),
+                _ => {}
+            }
+        }
+        run
+    }
+
+    fn duration_ms(&self) -> Option<u64> {
+        let (start, end) = (self.started_at?, self.completed_at?);
+        u64::try_from((end - start).num_milliseconds()).ok()
+    }
+}
+
+/// Nearest-rank 95th percentile
+fn p95(values: &mut [u64]) -> Option<u64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let rank = (values.len() * 95).div_ceil(100);
+    Some(values[rank.max(1) - 1])
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+impl Summary {
+    /// Aggregate `executions` and their event logs (keyed by execution ID)
+    ///
+    /// `model` prices tokens; `now` anchors the rate-limit window.
+    pub fn compute(
+        executions: &[LoopExecution],
+        events: &HashMap<String, Vec<EventLogEntry>>,
+        model: &str,
+        now: DateTime<Utc>,
+    ) -> Self {
+        debug!(executions = executions.len(), logs = events.len(), %model, "Summary::compute: called");
+        let mut by_type: BTreeMap<&str, Vec<&LoopExecution>> = BTreeMap::new();
+        for exec in executions {
+            by_type.entry(exec.loop_type.as_str()).or_default().push(exec);
+        }
+
+        let logged: HashMap<&str, LoggedRun> = events
+            .iter()
+            .map(|(id, entries)| (id.as_str(), LoggedRun::from_events(entries)))
+            .collect();
+        let empty = LoggedRun::default();
+        let logged_for = |id: &str| logged.get(id).unwrap_or(&empty);
+
+        let types = by_type
+            .into_iter()
+            .map(|(loop_type, execs)| {
+                let count = |status: LoopExecutionStatus| execs.iter().filter(|e| e.status == status).count();
+                let complete = count(LoopExecutionStatus::Complete);
+                let failed = count(LoopExecutionStatus::Failed);
+                let active = execs.iter().filter(|e| !e.is_terminal()).count();
+
+                let finished: Vec<_> = execs
+                    .iter()
+                    .filter(|e| matches!(e.status, LoopExecutionStatus::Complete | LoopExecutionStatus::Failed))
+                    .collect();
+                let iterations: Vec<f64> = finished.iter().map(|e| e.iteration as f64).collect();
+                let costs: Vec<f64> = finished
+                    .iter()
+                    .map(|e| {
+                        let run = logged_for(&e.id);
+                        TokenUsage {
+                            input_tokens: run.input_tokens.max(e.total_input_tokens),
+                            output_tokens: run.output_tokens.max(e.total_output_tokens),
+                            ..Default::default()
+                        }
+                        .cost_usd(model)
+                    })
+                    .collect();
+                let mut durations: Vec<u64> = finished
+                    .iter()
+                    .filter_map(|e| {
+                        logged_for(&e.id)
+                            .duration_ms()
+                            .or((e.total_duration_ms > 0).then_some(e.total_duration_ms))
+                    })
+                    .collect();
+
+                TypeSummary {
+                    loop_type: loop_type.to_string(),
+                    total: execs.len(),
+                    active,
+                    complete,
+                    failed,
+                    success_rate: (!finished.is_empty()).then(|| complete as f64 / finished.len() as f64),
+                    mean_iterations: mean(&iterations),
+                    mean_cost_usd: mean(&costs),
+                    p95_duration_ms: p95(&mut durations),
+                }
+            })
+            .collect();
+
+        let mut failures: Vec<_> = executions
+            .iter()
+            .filter(|e| e.status == LoopExecutionStatus::Failed)
+            .collect();
+        failures.sort_by_key(|e| std::cmp::Reverse(e.updated_at));
+        let recent_failures = failures
+            .into_iter()
+            .take(RECENT_FAILURES)
+            .map(|e| FailureSummary {
+                id: e.id.clone(),
+                loop_type: e.loop_type.clone(),
+                reason: e
+                    .last_error
+                    .clone()
+                    .or_else(|| logged_for(&e.id).last_error.clone())
+                    .unwrap_or_else(|| "unknown".to_string()),
+                failed_at: e.updated_at,
+            })
+            .collect();
+
+        let window_start = now - Duration::minutes(RATE_LIMIT_WINDOW_MINUTES);
+        let mut rate_limits = RateLimitPressure::default();
+        for entries in events.values() {
+            let mut hit = false;
+            for entry in entries.iter().filter(|e| e.timestamp >= window_start) {
+                if let Event::RateLimited { retry_after_ms, .. } = entry.event {
+                    rate_limits.hits += 1;
+                    rate_limits.wait_ms += retry_after_ms;
+                    hit = true;
+                }
+            }
+            if hit {
+                rate_limits.executions += 1;
+            }
+        }
+
+        let count = |status: LoopExecutionStatus| executions.iter().filter(|e| e.status == status).count();
+        let summary = Self {
+            types,
+            recent_failures,
+            queue_depth: count(LoopExecutionStatus::Pending),
+            running: count(LoopExecutionStatus::Running),
+            parked: count(LoopExecutionStatus::Parked),
+            rate_limits,
+        };
+        debug!(
+            types = summary.types.len(),
+            failures = summary.recent_failures.len(),
+            "Summary::compute: aggregated"
+        );
+        summary
+    }
+
+    /// Plain-text rendering for `td summary`
+    pub fn to_text(&self) -> String {
+        debug!("Summary::to_text: called");
+        let mut out = String::new();
+        let _ = writeln!(out, "TaskDaemon Summary");
+        let _ = writeln!(out, "------------------");
+        let _ = writeln!(
+            out,
+            "Queue: {} pending, {} running, {} parked",
+            self.queue_depth, self.running, self.parked
+        );
+        let _ = writeln!(
+            out,
+            "Rate limits (last {}m): {} hits across {} executions, {} waiting",
+            RATE_LIMIT_WINDOW_MINUTES,
+            self.rate_limits.hits,
+            self.rate_limits.executions,
+            format_duration_ms(self.rate_limits.wait_ms)
+        );
+        let _ = writeln!(out);
+
+        if self.types.is_empty() {
+            let _ = writeln!(out, "No executions yet.");
+            return out;
+        }
+
+        let _ = writeln!(
+            out,
+            "{:<16} {:>6} {:>7} {:>8} {:>10} {:>10} {:>10}",
+            "TYPE", "TOTAL", "ACTIVE", "SUCCESS", "MEAN ITER", "MEAN COST", "P95 TIME"
+        );
+        for t in &self.types {
+            let _ = writeln!(
+                out,
+                "{:<16} {:>6} {:>7} {:>8} {:>10} {:>10} {:>10}",
+                t.loop_type,
+                t.total,
+                t.active,
+                t.success_rate.map_or("-".to_string(), |r| format!("{:.0}%", r * 100.0)),
+                t.mean_iterations.map_or("-".to_string(), |i| format!("{:.1}", i)),
+                t.mean_cost_usd.map_or("-".to_string(), |c| format!("${:.2}", c)),
+                t.p95_duration_ms.map_or("-".to_string(), format_duration_ms),
+            );
+        }
+
+        if !self.recent_failures.is_empty() {
+            let _ = writeln!(out);
+            let _ = writeln!(out, "Recent failures:");
+            for f in &self.recent_failures {
+                let _ = writeln!(out, "  {} [{}]: {}", f.id, f.loop_type, f.reason);
+            }
+        }
+        out
+    }
+}
+
+/// Format milliseconds as "45s", "1m 15s", or "2h 30m"
+pub fn format_duration_ms(ms: u64) -> String {
+    let secs = ms / 1000;
+    let (mins, hours) = (secs / 60, secs / 3600);
+    if hours > 0 {
+        format!("{}h {}m", hours, mins % 60)
+    } else if mins > 0 {
+        format!("{}m {}s", mins, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(event: Event, offset_secs: i64) -> EventLogEntry {
+        EventLogEntry {
+            timestamp: DateTime::<Utc>::from_timestamp(1_700_000_000 + offset_secs, 0).unwrap(),
+            event,
+        }
+    }
+
+    fn exec(id: &str, loop_type: &str, status: LoopExecutionStatus, iteration: u32) -> LoopExecution {
+        let mut exec = LoopExecution::with_id(id, loop_type);
+        exec.status = status;
+        exec.iteration = iteration;
+        exec
+    }
+
+    fn run_events(id: &str, duration_secs: i64) -> Vec<EventLogEntry> {
+        vec![
+            entry(
+                Event::LoopStarted {
+                    execution_id: id.to_string(),
+                    loop_type: "phase".to_string(),
+                    task_description: String::new(),
+                },
+                0,
+            ),
+            entry(
+                Event::ResponseCompleted {
+                    execution_id: id.to_string(),
+                    iteration: 1,
+                    response_summary: String::new(),
+                    input_tokens: 1_000_000,
+                    output_tokens: 0,
+                    has_tool_calls: false,
+                    served_by: None,
+                },
+                1,
+            ),
+            entry(
+                Event::LoopCompleted {
+                    execution_id: id.to_string(),
+                    success: true,
+                    total_iterations: 1,
+                },
+                duration_secs,
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_p95_nearest_rank() {
+        assert_eq!(p95(&mut []), None);
+        assert_eq!(p95(&mut [7]), Some(7));
+        let mut values: Vec<u64> = (1..=20).rev().collect();
+        assert_eq!(p95(&mut values), Some(19));
+    }
+
+    #[test]
+    fn test_summary_aggregates_types_failures_and_queue() {
+        let mut failed = exec("f1", "phase", LoopExecutionStatus::Failed, 10);
+        failed.updated_at = 2;
+        let mut older = exec("f0", "phase", LoopExecutionStatus::Failed, 10);
+        older.updated_at = 1;
+        let executions = vec![
+            exec("c1", "phase", LoopExecutionStatus::Complete, 2),
+            exec("c2", "phase", LoopExecutionStatus::Complete, 4),
+            failed,
+            older,
+            exec("p1", "phase", LoopExecutionStatus::Pending, 0),
+            exec("r1", "spec", LoopExecutionStatus::Running, 3),
+        ];
+        let mut events = HashMap::new();
+        events.insert("c1".to_string(), run_events("c1", 60));
+        events.insert("c2".to_string(), run_events("c2", 120));
+        let mut f1_events = run_events("f1", 30);
+        f1_events.push(entry(
+            Event::Error {
+                execution_id: "f1".to_string(),
+                context: "validation".to_string(),
+                message: "tests failed".to_string(),
+                code: None,
+            },
+            31,
+        ));
+        events.insert("f1".to_string(), f1_events);
+
+        let summary = Summary::compute(&executions, &events, "unknown-model", Utc::now());
+        assert_eq!(summary.queue_depth, 1);
+        assert_eq!(summary.running, 1);
+        assert_eq!(summary.types.len(), 2);
+
+        let phase = &summary.types[0];
+        assert_eq!(phase.loop_type, "phase");
+        assert_eq!((phase.total, phase.active, phase.complete, phase.failed), (5, 1, 2, 2));
+        assert_eq!(phase.success_rate, Some(0.5));
+        assert_eq!(phase.mean_iterations, Some(6.5));
+        assert_eq!(phase.p95_duration_ms, Some(120_000));
+        assert!(phase.mean_cost_usd.is_some());
+
+        let spec = &summary.types[1];
+        assert_eq!(spec.success_rate, None);
+        assert_eq!(spec.p95_duration_ms, None);
+
+        let ids: Vec<_> = summary.recent_failures.iter().map(|f| f.id.as_str()).collect();
+        assert_eq!(ids, vec!["f1", "f0"]);
+        assert_eq!(summary.recent_failures[0].reason, "validation: tests failed");
+        assert_eq!(summary.recent_failures[1].reason, "unknown");
+    }
+
+    #[test]
+    fn test_rate_limit_pressure_uses_window() {
+        let executions = vec![exec("r1", "phase", LoopExecutionStatus::Running, 1)];
+        let limited = |offset_secs| {
+            entry(
+                Event::RateLimited {
+                    execution_id: "r1".to_string(),
+                    iteration: 1,
+                    retry_after_ms: 30_000,
+                },
+                offset_secs,
+            )
+        };
+        let mut events = HashMap::new();
+        events.insert("r1".to_string(), vec![limited(0), limited(3000), limited(3500)]);
+
+        let now = DateTime::<Utc>::from_timestamp(1_700_000_000 + 3601, 0).unwrap();
+        let summary = Summary::compute(&executions, &events, "unknown-model", now);
+        assert_eq!(summary.rate_limits.hits, 2);
+        assert_eq!(summary.rate_limits.executions, 1);
+        assert_eq!(summary.rate_limits.wait_ms, 60_000);
+
+        let text = summary.to_text();
+        assert!(text.contains("2 hits across 1 executions, 1m 0s waiting"));
+        assert!(text.contains("phase"));
+    }
+}
@@ -0,0 +1,565 @@
+//! Execution timelines
+//!
+//! Turns an execution's event log (`~/.taskdaemon/runs/{id}/events.jsonl`)
+//! into timed spans — iterations, LLM calls, tool calls and validation runs —
+//! and renders them as a Mermaid Gantt chart or a standalone HTML page for
+//! `td exec timeline`. Both include a breakdown of where the time went, e.g.
+//! how much of the run was spent waiting on `cargo build`.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use chrono::{DateTime, Utc};
+use tracing::debug;
+
+use crate::events::{Event, EventLogEntry, IterationOutcome};
+use crate::report::{escape_html, format_duration_ms};
+
+/// Output format for `td exec timeline`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimelineFormat {
+    #[default]
+    Html,
+    Mermaid,
+}
+
+impl std::str::FromStr for TimelineFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        debug!(%s, "TimelineFormat::from_str: called");
+        match s.to_lowercase().as_str() {
+            "html" => Ok(Self::Html),
+            "mermaid" | "mmd" => Ok(Self::Mermaid),
+            _ => Err(format!("Unknown timeline format: {}. Use: html or mermaid", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for TimelineFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Html => write!(f, "html"),
+            Self::Mermaid => write!(f, "mermaid"),
+        }
+    }
+}
+
+/// What a span measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SpanKind {
+    Iteration,
+    Llm,
+    Tool,
+    Validation,
+}
+
+impl SpanKind {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Iteration => "iteration",
+            Self::Llm => "llm",
+            Self::Tool => "tool",
+            Self::Validation => "validation",
+        }
+    }
+}
+
+/// One timed activity
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub kind: SpanKind,
+    /// Iteration number, tool name, validation command, or "LLM call"
+    pub name: String,
+    pub iteration: u32,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Tool call failed, validation exited non-zero, or the iteration errored
+    pub failed: bool,
+    /// No completion was logged; the span ends at the last event
+    pub unfinished: bool,
+}
+
+impl Span {
+    pub fn duration_ms(&self) -> u64 {
+        (self.end - self.start).num_milliseconds().max(0) as u64
+    }
+}
+
+/// Time spent on one activity (a tool, a validation command, LLM calls)
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeShare {
+    pub kind: SpanKind,
+    pub name: String,
+    pub count: u32,
+    pub total_ms: u64,
+    /// Share of the execution's wall-clock time
+    pub percent: f64,
+}
+
+/// Timed spans of one execution
+#[derive(Debug, Clone)]
+pub struct Timeline {
+    pub execution_id: String,
+    /// Spans ordered by start time
+    pub spans: Vec<Span>,
+}
+
+impl Timeline {
+    /// Build the timeline from an execution's logged events
+    pub fn from_events(execution_id: impl Into<String>, entries: &[EventLogEntry]) -> Self {
+        let execution_id = execution_id.into();
+        debug!(%execution_id, events = entries.len(), "Timeline::from_events: called");
+        let mut spans = Vec::new();
+        let mut iterations: BTreeMap<u32, DateTime<Utc>> = BTreeMap::new();
+        let mut prompts: BTreeMap<u32, DateTime<Utc>> = BTreeMap::new();
+        let mut validations: BTreeMap<u32, String> = BTreeMap::new();
+
+        for entry in entries {
+            let at = entry.timestamp;
+            match &entry.event {
+                Event::IterationStarted { iteration, .. } => {
+                    iterations.insert(*iteration, at);
+                }
+                Event::IterationCompleted { iteration, outcome, .. } => {
+                    let start = iterations.remove(iteration).unwrap_or(at);
+                    spans.push(Span {
+                        kind: SpanKind::Iteration,
+                        name: format!("Iteration {}", iteration),
+                        iteration: *iteration,
+                        start,
+                        end: at,
+                        failed: matches!(
+                            outcome,
+                            IterationOutcome::ToolError { .. }
+                                | IterationOutcome::LlmError { .. }
+                                | IterationOutcome::TimedOut { .. }
+                        ),
+                        unfinished: false,
+                    });
+                }
+                Event::PromptSent { iteration, .. } => {
+                    prompts.insert(*iteration, at);
+                }
+                Event::ResponseCompleted { iteration, .. } => {
+                    let start = prompts.remove(iteration).unwrap_or(at);
+                    spans.push(Span {
+                        kind: SpanKind::Llm,
+                        name: "LLM call".to_string(),
+                        iteration: *iteration,
+                        start,
+                        end: at,
+                        failed: false,
+                        unfinished: false,
+                    });
+                }
+                Event::ToolCallCompleted {
+                    iteration,
+                    tool_name,
+                    success,
+                    duration_ms,
+                    ..
+                } => spans.push(Span {
+                    kind: SpanKind::Tool,
+                    name: tool_name.clone(),
+                    iteration: *iteration,
+                    start: at - chrono::Duration::milliseconds(*duration_ms as i64),
+                    end: at,
+                    failed: !success,
+                    unfinished: false,
+                }),
+                Event::ValidationStarted { iteration, command, .. } => {
+                    validations.insert(*iteration, command.clone());
+                }
+                Event::ValidationCompleted {
+                    iteration,
+                    exit_code,
+                    duration_ms,
+                    ..
+                } => spans.push(Span {
+                    kind: SpanKind::Validation,
+                    name: validations
+                        .remove(iteration)
+                        .unwrap_or_else(|| "validation".to_string()),
+                    iteration: *iteration,
+                    start: at - chrono::Duration::milliseconds(*duration_ms as i64),
+                    end: at,
+                    failed: *exit_code != 0,
+                    unfinished: false,
+                }),
+                _ => {}
+            }
+        }
+
+        // Whatever is still open ran until the log ends
+        if let Some(last) = entries.iter().map(|e| e.timestamp).max() {
+            for (iteration, start) in iterations {
+                spans.push(Span {
+                    kind: SpanKind::Iteration,
+                    name: format!("Iteration {}", iteration),
+                    iteration,
+                    start,
+                    end: last,
+                    failed: false,
+                    unfinished: true,
+                });
+            }
+            for (iteration, start) in prompts {
+                spans.push(Span {
+                    kind: SpanKind::Llm,
+                    name: "LLM call".to_string(),
+                    iteration,
+                    start,
+                    end: last,
+                    failed: false,
+                    unfinished: true,
+                });
+            }
+        }
+
+        spans.sort_by(|a, b| a.start.cmp(&b.start).then(a.kind.cmp(&b.kind)));
+        debug!(spans = spans.len(), "Timeline::from_events: built");
+        Self { execution_id, spans }
+    }
+
+    /// Earliest span start
+    pub fn start(&self) -> Option<DateTime<Utc>> {
+        self.spans.iter().map(|s| s.start).min()
+    }
+
+    /// Latest span end
+    pub fn end(&self) -> Option<DateTime<Utc>> {
+        self.spans.iter().map(|s| s.end).max()
+    }
+
+    /// Wall-clock time from the first span to the last
+    pub fn wall_ms(&self) -> u64 {
+        match (self.start(), self.end()) {
+            (Some(start), Some(end)) => (end - start).num_milliseconds().max(0) as u64,
+            _ => 0,
+        }
+    }
+
+    /// Time per LLM/tool/validation activity, largest first
+    pub fn breakdown(&self) -> Vec<TimeShare> {
+        let wall = self.wall_ms();
+        let mut shares: BTreeMap<(SpanKind, &str), (u32, u64)> = BTreeMap::new();
+        for span in self.spans.iter().filter(|s| s.kind != SpanKind::Iteration) {
+            let share = shares.entry((span.kind, span.name.as_str())).or_default();
+            share.0 += 1;
+            share.1 += span.duration_ms();
+        }
+        let mut shares: Vec<TimeShare> = shares
+            .into_iter()
+            .map(|((kind, name), (count, total_ms))| TimeShare {
+                kind,
+                name: name.to_string(),
+                count,
+                total_ms,
+                percent: if wall == 0 {
+                    0.0
+                } else {
+                    total_ms as f64 * 100.0 / wall as f64
+                },
+            })
+            .collect();
+        shares.sort_by(|a, b| b.total_ms.cmp(&a.total_ms).then_with(|| a.name.cmp(&b.name)));
+        shares
+    }
+
+    /// Render in the requested format
+    pub fn render(&self, format: TimelineFormat) -> String {
+        debug!(%format, "Timeline::render: called");
+        match format {
+            TimelineFormat::Html => self.to_html(),
+            TimelineFormat::Mermaid => self.to_mermaid(),
+        }
+    }
+
+    /// Render as a Mermaid Gantt chart, one section per iteration
+    pub fn to_mermaid(&self) -> String {
+        debug!(exec_id = %self.execution_id, "Timeline::to_mermaid: called");
+        let mut out = String::new();
+        let _ = writeln!(out, "gantt");
+        let _ = writeln!(out, "    title Execution {}", mermaid_text(&self.execution_id));
+        let _ = writeln!(out, "    dateFormat x");
+        let _ = writeln!(out, "    axisFormat %H:%M:%S");
+
+        let mut section = None;
+        for span in &self.spans {
+            if section != Some(span.iteration) {
+                section = Some(span.iteration);
+                let _ = writeln!(out, "    section Iteration {}", span.iteration);
+            }
+            let tag = if span.failed {
+                "crit, "
+            } else if span.unfinished {
+                "active, "
+            } else if span.kind == SpanKind::Iteration {
+                "done, "
+            } else {
+                ""
+            };
+            let start = span.start.timestamp_millis();
+            // Zero-length bars don't render
+            let end = span.end.timestamp_millis().max(start + 1);
+            let _ = writeln!(
+                out,
+                "    {} ({}) :{}{}, {}",
+                mermaid_text(&span.name),
+                format_duration_ms(span.duration_ms()),
+                tag,
+                start,
+                end
+            );
+        }
+
+        let breakdown = self.breakdown();
+        if !breakdown.is_empty() {
+            let _ = writeln!(
+                out,
+                "%% Where the time went ({} total)",
+                format_duration_ms(self.wall_ms())
+            );
+            for share in breakdown {
+                let _ = writeln!(
+                    out,
+                    "%%   {:>5.1}%  {:>8}  {} {} x{}",
+                    share.percent,
+                    format_duration_ms(share.total_ms),
+                    share.kind.label(),
+                    share.name,
+                    share.count
+                );
+            }
+        }
+        out
+    }
+
+    /// Render as a standalone HTML page with one bar per span
+    pub fn to_html(&self) -> String {
+        debug!(exec_id = %self.execution_id, "Timeline::to_html: called");
+        let mut out = String::new();
+        let title = escape_html(&self.execution_id);
+        let _ = writeln!(
+            out,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Execution Timeline: {title}</title>\n\
+             <style>body{{font-family:sans-serif;max-width:1200px;margin:2em auto}}\
+             table{{border-collapse:collapse;width:100%}}td,th{{border:1px solid #ccc;padding:2px 8px;text-align:left;white-space:nowrap}}\
+             td.track{{width:60%;position:relative}}.bar{{height:12px;min-width:2px;border-radius:2px}}\
+             .iteration{{background:#9ab}}.llm{{background:#68c}}.tool{{background:#c96}}.validation{{background:#6a6}}\
+             .failed{{background:#c44}}.unfinished{{opacity:.5}}</style>\n\
+             </head>\n<body>\n<h1>Execution Timeline: {title}</h1>"
+        );
+
+        let (Some(start), wall) = (self.start(), self.wall_ms()) else {
+            let _ = writeln!(out, "<p><em>No events logged.</em></p>\n</body>\n</html>");
+            return out;
+        };
+        let _ = writeln!(
+            out,
+            "<p>Started {} · {} wall clock</p>",
+            start.format("%Y-%m-%d %H:%M:%S UTC"),
+            format_duration_ms(wall)
+        );
+
+        let _ = writeln!(
+            out,
+            "<h2>Where the Time Went</h2>\n<table>\n<tr><th>Activity</th><th>Kind</th><th>Count</th><th>Total</th><th>Share</th></tr>"
+        );
+        for share in self.breakdown() {
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{:.1}%</td></tr>",
+                escape_html(&share.name),
+                share.kind.label(),
+                share.count,
+                format_duration_ms(share.total_ms),
+                share.percent
+            );
+        }
+        let _ = writeln!(out, "</table>");
+
+        let _ = writeln!(
+            out,
+            "<h2>Timeline</h2>\n<table>\n<tr><th>Span</th><th>Start</th><th>Duration</th><th></th></tr>"
+        );
+        let scale = wall.max(1) as f64;
+        for span in &self.spans {
+            let offset = (span.start - start).num_milliseconds().max(0) as u64;
+            let mut classes = span.kind.label().to_string();
+            if span.failed {
+                classes.push_str(" failed");
+            }
+            if span.unfinished {
+                classes.push_str(" unfinished");
+            }
+            let name = if span.kind == SpanKind::Iteration {
+                format!("<strong>{}</strong>", escape_html(&span.name))
+            } else {
+                format!("&nbsp;&nbsp;{}", escape_html(&span.name))
+            };
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td>+{}</td><td>{}</td><td class=\"track\">\
+                 <div class=\"bar {}\" style=\"margin-left:{:.2}%;width:{:.2}%\"></div></td></tr>",
+                name,
+                format_duration_ms(offset),
+                format_duration_ms(span.duration_ms()),
+                classes,
+                offset as f64 * 100.0 / scale,
+                span.duration_ms() as f64 * 100.0 / scale
+            );
+        }
+        let _ = writeln!(out, "</table>\n</body>\n</html>");
+        out
+    }
+}
+
+/// Strip characters Mermaid treats as syntax in task names
+fn mermaid_text(s: &str) -> String {
+    s.chars()
+        .map(|c| if matches!(c, ':' | ';' | '#' | '\n') { ' ' } else { c })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(event: Event, offset_ms: i64) -> EventLogEntry {
+        EventLogEntry {
+            timestamp: DateTime::<Utc>::from_timestamp_millis(1_700_000_000_000 + offset_ms).unwrap(),
+            event,
+        }
+    }
+
+    fn sample_events() -> Vec<EventLogEntry> {
+        let id = "exec-1".to_string();
+        vec![
+            entry(
+                Event::IterationStarted {
+                    execution_id: id.clone(),
+                    iteration: 1,
+                },
+                0,
+            ),
+            entry(
+                Event::PromptSent {
+                    execution_id: id.clone(),
+                    iteration: 1,
+                    prompt_summary: String::new(),
+                    token_count: 0,
+                },
+                0,
+            ),
+            entry(
+                Event::ResponseCompleted {
+                    execution_id: id.clone(),
+                    iteration: 1,
+                    response_summary: String::new(),
+                    input_tokens: 100,
+                    output_tokens: 10,
+                    has_tool_calls: true,
+                    served_by: None,
+                },
+                1_000,
+            ),
+            entry(
+                Event::ToolCallCompleted {
+                    execution_id: id.clone(),
+                    iteration: 1,
+                    tool_name: "run_command".to_string(),
+                    success: false,
+                    result_summary: String::new(),
+                    duration_ms: 1_000,
+                },
+                2_000,
+            ),
+            entry(
+                Event::ValidationStarted {
+                    execution_id: id.clone(),
+                    iteration: 1,
+                    command: "cargo build".to_string(),
+                },
+                2_000,
+            ),
+            entry(
+                Event::ValidationCompleted {
+                    execution_id: id.clone(),
+                    iteration: 1,
+                    exit_code: 0,
+                    duration_ms: 8_000,
+                },
+                10_000,
+            ),
+            entry(
+                Event::IterationCompleted {
+                    execution_id: id.clone(),
+                    iteration: 1,
+                    outcome: IterationOutcome::ValidationPassed,
+                },
+                10_000,
+            ),
+            entry(
+                Event::IterationStarted {
+                    execution_id: id.clone(),
+                    iteration: 2,
+                },
+                10_000,
+            ),
+            entry(
+                Event::PromptSent {
+                    execution_id: id,
+                    iteration: 2,
+                    prompt_summary: String::new(),
+                    token_count: 0,
+                },
+                10_000,
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_timeline_spans_and_breakdown() {
+        let timeline = Timeline::from_events("exec-1", &sample_events());
+        assert_eq!(timeline.spans.len(), 6);
+        assert_eq!(timeline.wall_ms(), 10_000);
+
+        let tool = timeline.spans.iter().find(|s| s.kind == SpanKind::Tool).unwrap();
+        assert_eq!((tool.duration_ms(), tool.failed), (1_000, true));
+        let unfinished: Vec<_> = timeline.spans.iter().filter(|s| s.unfinished).collect();
+        assert_eq!(unfinished.len(), 2);
+        assert!(unfinished.iter().all(|s| s.iteration == 2));
+
+        let breakdown = timeline.breakdown();
+        assert_eq!(breakdown[0].name, "cargo build");
+        assert_eq!(breakdown[0].kind, SpanKind::Validation);
+        assert!((breakdown[0].percent - 80.0).abs() < 0.01);
+        assert_eq!(breakdown.len(), 3);
+    }
+
+    #[test]
+    fn test_timeline_renders_mermaid_and_html() {
+        let timeline = Timeline::from_events("exec-1", &sample_events());
+
+        let mermaid = timeline.render(TimelineFormat::Mermaid);
+        assert!(mermaid.starts_with("gantt\n"));
+        assert!(mermaid.contains("    section Iteration 2\n"));
+        assert!(mermaid.contains("    cargo build (8.0s) :1700000002000, 1700000010000\n"));
+        assert!(mermaid.contains("    run_command (1.0s) :crit, 1700000001000, 1700000002000\n"));
+        assert!(mermaid.contains("80.0%"));
+
+        let html = timeline.render(TimelineFormat::Html);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("class=\"bar validation\" style=\"margin-left:20.00%;width:80.00%\""));
+        assert!(html.contains("bar tool failed"));
+
+        assert_eq!("MMD".parse::<TimelineFormat>().unwrap(), TimelineFormat::Mermaid);
+        assert!("svg".parse::<TimelineFormat>().is_err());
+        let empty = Timeline::from_events("exec-2", &[]);
+        assert!(empty.to_html().contains("No events logged"));
+    }
+}
@@ -64,7 +64,7 @@ impl Tool for FetchTool {
         })
     }
 
-    async fn execute(&self, input: Value, _ctx: &ToolContext) -> ToolResult {
+    async fn execute(&self, input: Value, ctx: &ToolContext) -> ToolResult {
         debug!(?input, "FetchTool::execute: called");
         let url = match input["url"].as_str() {
             Some(u) => {
@@ -85,12 +85,31 @@ impl Tool for FetchTool {
 
         debug!("FetchTool::execute: URL protocol validated");
 
+        if let Err(e) = ctx.network.check_url("fetch", url) {
+            debug!(%e, "FetchTool::execute: blocked by network policy");
+            return ToolResult::error(e);
+        }
+
         let prompt = input["prompt"].as_str();
         debug!(has_prompt = %prompt.is_some(), "FetchTool::execute: prompt parameter");
 
         // Fetch the content with timeout
+        // Redirects are checked against the network policy too
+        let network = ctx.network.clone();
+        let redirects = reqwest::redirect::Policy::custom(move |attempt| {
+            let host = attempt.url().host_str().unwrap_or_default().to_string();
+            let target = attempt.url().to_string();
+            if attempt.previous().len() >= 10 {
+                attempt.error("too many redirects")
+            } else if network.check("fetch", &host, &target) {
+                attempt.follow()
+            } else {
+                attempt.error(format!("Blocked by network policy: {} is not an allowed host", host))
+            }
+        });
         let client = reqwest::Client::builder()
             .timeout(std::time::Duration::from_secs(30))
+            .redirect(redirects)
             .user_agent("TaskDaemon/0.1 (fetch tool)")
             .build()
             .unwrap_or_default();
@@ -162,7 +181,7 @@ impl Tool for FetchTool {
         if let (Some(prompt_text), Some(llm)) = (prompt, &self.llm_client) {
             debug!("FetchTool::execute: summarizing with LLM");
             return self
-                .summarize_with_llm(llm, &content, prompt_text, url, _ctx.max_tokens)
+                .summarize_with_llm(llm, &content, prompt_text, url, ctx.max_tokens)
                 .await;
         }
 
@@ -297,6 +316,26 @@ mod tests {
         assert!(result.content.contains("http"));
     }
 
+    #[tokio::test]
+    async fn test_fetch_blocked_by_network_policy() {
+        let temp = tempdir().unwrap();
+        let policy = crate::tools::NetworkPolicy {
+            allow: vec!["docs.rs".to_string()],
+            deny: Vec::new(),
+        };
+        let ctx = ToolContext::new(temp.path().to_path_buf(), "test".to_string())
+            .with_network(crate::tools::NetworkGuard::new(policy));
+
+        let input = serde_json::json!({"url": "https://example.com/page"});
+        let result = FetchTool::new().execute(input, &ctx).await;
+
+        assert!(result.is_error);
+        assert!(result.content.contains("Blocked by network policy"));
+        let log = ctx.network.take_log();
+        assert_eq!(log.len(), 1);
+        assert_eq!((log[0].tool.as_str(), log[0].allowed), ("fetch", false));
+    }
+
     #[tokio::test]
     async fn test_fetch_missing_url() {
         let temp = tempdir().unwrap();
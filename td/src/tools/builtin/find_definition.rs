@@ -0,0 +1,102 @@
+//! find_definition tool - go-to-definition via the project's language server
+
+use async_trait::async_trait;
+use serde_json::Value;
+use tracing::debug;
+
+use crate::tools::lsp::{collect_locations, format_locations, resolve_position};
+use crate::tools::{Tool, ToolContext, ToolResult};
+
+/// Jump to the definition of a symbol using LSP
+pub struct FindDefinitionTool;
+
+#[async_trait]
+impl Tool for FindDefinitionTool {
+    fn name(&self) -> &'static str {
+        "find_definition"
+    }
+
+    fn description(&self) -> &'static str {
+        "Find where a symbol is defined using the project's language server. \
+         Give the file and line where the symbol is used, plus the symbol name."
+    }
+
+    fn input_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "File containing a use of the symbol (relative to worktree)"
+                },
+                "line": {
+                    "type": "integer",
+                    "description": "1-based line number of the usage"
+                },
+                "symbol": {
+                    "type": "string",
+                    "description": "Symbol name on that line (used to locate the column)"
+                },
+                "column": {
+                    "type": "integer",
+                    "description": "1-based column (alternative to symbol)"
+                }
+            },
+            "required": ["path", "line"]
+        })
+    }
+
+    async fn execute(&self, input: Value, ctx: &ToolContext) -> ToolResult {
+        debug!(?input, "FindDefinitionTool::execute: called");
+        let Some(lsp) = &ctx.lsp else {
+            debug!("FindDefinitionTool::execute: no LSP session in context");
+            return ToolResult::error("Language server not available in this context. Use grep instead.");
+        };
+
+        let position = match resolve_position(&input, ctx) {
+            Ok(p) => p,
+            Err(e) => {
+                debug!(%e, "FindDefinitionTool::execute: invalid position");
+                return ToolResult::error(e);
+            }
+        };
+
+        match lsp
+            .document_request(&position.path, "textDocument/definition", position.params())
+            .await
+        {
+            Ok(result) => {
+                let locations = collect_locations(&result);
+                debug!(count = locations.len(), "FindDefinitionTool::execute: got locations");
+                if locations.is_empty() {
+                    ToolResult::success("No definition found.")
+                } else {
+                    ToolResult::success(format_locations(&locations, &ctx.worktree, 20))
+                }
+            }
+            Err(e) => {
+                debug!(%e, "FindDefinitionTool::execute: request failed");
+                ToolResult::error(format!("find_definition failed: {}", e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_find_definition_without_session() {
+        let temp = tempdir().unwrap();
+        let ctx = ToolContext::new(temp.path().to_path_buf(), "test".to_string());
+
+        let result = FindDefinitionTool
+            .execute(serde_json::json!({"path": "lib.rs", "line": 1}), &ctx)
+            .await;
+
+        assert!(result.is_error);
+        assert!(result.content.contains("not available"));
+    }
+}
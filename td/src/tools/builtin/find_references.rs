@@ -0,0 +1,120 @@
+//! find_references tool - list all references to a symbol via LSP
+
+use async_trait::async_trait;
+use serde_json::Value;
+use tracing::debug;
+
+use crate::tools::lsp::{collect_locations, format_locations, resolve_position};
+use crate::tools::{Tool, ToolContext, ToolResult};
+
+/// Find every reference to a symbol using LSP
+pub struct FindReferencesTool;
+
+#[async_trait]
+impl Tool for FindReferencesTool {
+    fn name(&self) -> &'static str {
+        "find_references"
+    }
+
+    fn description(&self) -> &'static str {
+        "Find all references to a symbol using the project's language server. \
+         Give the file and line where the symbol appears, plus the symbol name."
+    }
+
+    fn input_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "File containing the symbol (relative to worktree)"
+                },
+                "line": {
+                    "type": "integer",
+                    "description": "1-based line number where the symbol appears"
+                },
+                "symbol": {
+                    "type": "string",
+                    "description": "Symbol name on that line (used to locate the column)"
+                },
+                "column": {
+                    "type": "integer",
+                    "description": "1-based column (alternative to symbol)"
+                },
+                "include_declaration": {
+                    "type": "boolean",
+                    "description": "Include the declaration itself (default: true)"
+                },
+                "max_results": {
+                    "type": "integer",
+                    "description": "Maximum number of references to return (default: 50)"
+                }
+            },
+            "required": ["path", "line"]
+        })
+    }
+
+    async fn execute(&self, input: Value, ctx: &ToolContext) -> ToolResult {
+        debug!(?input, "FindReferencesTool::execute: called");
+        let Some(lsp) = &ctx.lsp else {
+            debug!("FindReferencesTool::execute: no LSP session in context");
+            return ToolResult::error("Language server not available in this context. Use grep instead.");
+        };
+
+        let position = match resolve_position(&input, ctx) {
+            Ok(p) => p,
+            Err(e) => {
+                debug!(%e, "FindReferencesTool::execute: invalid position");
+                return ToolResult::error(e);
+            }
+        };
+
+        let include_declaration = input["include_declaration"].as_bool().unwrap_or(true);
+        let max_results = input["max_results"].as_u64().unwrap_or(50) as usize;
+
+        let mut params = position.params();
+        params["context"] = serde_json::json!({ "includeDeclaration": include_declaration });
+
+        match lsp
+            .document_request(&position.path, "textDocument/references", params)
+            .await
+        {
+            Ok(result) => {
+                let locations = collect_locations(&result);
+                debug!(count = locations.len(), "FindReferencesTool::execute: got locations");
+                if locations.is_empty() {
+                    ToolResult::success("No references found.")
+                } else {
+                    ToolResult::success(format!(
+                        "{} reference(s):\n{}",
+                        locations.len(),
+                        format_locations(&locations, &ctx.worktree, max_results)
+                    ))
+                }
+            }
+            Err(e) => {
+                debug!(%e, "FindReferencesTool::execute: request failed");
+                ToolResult::error(format!("find_references failed: {}", e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_find_references_without_session() {
+        let temp = tempdir().unwrap();
+        let ctx = ToolContext::new(temp.path().to_path_buf(), "test".to_string());
+
+        let result = FindReferencesTool
+            .execute(serde_json::json!({"path": "lib.rs", "line": 1}), &ctx)
+            .await;
+
+        assert!(result.is_error);
+        assert!(result.content.contains("not available"));
+    }
+}
@@ -0,0 +1,60 @@
+//! finish_review tool - complete the review with an overall summary
+
+use async_trait::async_trait;
+use serde_json::Value;
+use tracing::debug;
+
+use crate::review::Review;
+use crate::tools::{Tool, ToolContext, ToolResult};
+
+/// Mark the `review` loop's review complete
+pub struct FinishReviewTool;
+
+#[async_trait]
+impl Tool for FinishReviewTool {
+    fn name(&self) -> &'static str {
+        "finish_review"
+    }
+
+    fn description(&self) -> &'static str {
+        "Finish the review once every comment is recorded. The summary gives the overall assessment: \
+         whether the diff implements the plan and what most needs attention. No comments can be added after."
+    }
+
+    fn input_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "summary": {
+                    "type": "string",
+                    "description": "Overall assessment of the diff against the plan"
+                }
+            },
+            "required": ["summary"]
+        })
+    }
+
+    async fn execute(&self, input: Value, ctx: &ToolContext) -> ToolResult {
+        debug!(?input, "FinishReviewTool::execute: called");
+        let summary = input["summary"].as_str().unwrap_or("").trim();
+        if summary.is_empty() {
+            return ToolResult::error("summary is required");
+        }
+
+        let mut review = match Review::load(&ctx.worktree) {
+            Ok(Some(review)) => review,
+            Ok(None) => return ToolResult::error("No review in progress"),
+            Err(e) => return ToolResult::error(format!("Failed to load review: {:#}", e)),
+        };
+        if review.complete {
+            return ToolResult::error("The review is already finished");
+        }
+        review.finish(summary);
+        if let Err(e) = review.save(&ctx.worktree) {
+            return ToolResult::error(format!("Failed to save review: {:#}", e));
+        }
+
+        debug!(comments = review.comments.len(), "FinishReviewTool::execute: finished");
+        ToolResult::success(format!("Review finished: {}", review.counts()))
+    }
+}
@@ -30,7 +30,7 @@ impl Tool for GlobTool {
                 },
                 "path": {
                     "type": "string",
-                    "description": "Base directory (default: worktree root)"
+                    "description": "Base directory (default: worktree root; @mount for a read-only mount)"
                 }
             },
             "required": ["pattern"]
@@ -53,7 +53,7 @@ impl Tool for GlobTool {
         let base = input["path"].as_str().unwrap_or(".");
         debug!(%base, "GlobTool::execute: base path");
 
-        let base_path = match ctx.validate_path(Path::new(base)) {
+        let base_path = match ctx.validate_read_path(Path::new(base)) {
             Ok(p) => {
                 debug!(?p, "GlobTool::execute: base path validated");
                 p
@@ -82,15 +82,8 @@ impl Tool for GlobTool {
                 debug!("GlobTool::execute: glob successful");
                 paths
                     .filter_map(|r| r.ok())
-                    .filter(|p| {
-                        // Sandbox check - ensure path is within worktree
-                        p.starts_with(&ctx.worktree)
-                    })
-                    .filter_map(|p| {
-                        p.strip_prefix(&ctx.worktree)
-                            .ok()
-                            .map(|rel| rel.to_string_lossy().to_string())
-                    })
+                    // Sandbox check - only paths within the worktree or a read-only mount
+                    .filter_map(|p| ctx.display_path(&p))
                     .take(1000) // Limit results to prevent huge outputs
                     .collect()
             }
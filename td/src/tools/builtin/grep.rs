@@ -37,7 +37,7 @@ impl Tool for GrepTool {
                 },
                 "path": {
                     "type": "string",
-                    "description": "Path to search in (relative to worktree, default: '.')",
+                    "description": "Path to search in (relative to worktree or @mount/path, default: '.')",
                     "default": "."
                 },
                 "file_pattern": {
@@ -86,8 +86,8 @@ impl Tool for GrepTool {
 
         debug!(%path, ?file_pattern, %context_lines, %case_insensitive, %max_results, "GrepTool::execute: parameters parsed");
 
-        // Validate path is within worktree
-        let search_path = match ctx.validate_path(Path::new(path)) {
+        // Validate path is within the worktree or a read-only mount
+        let search_path = match ctx.validate_read_path(Path::new(path)) {
             Ok(p) => {
                 debug!(?p, "GrepTool::execute: search path validated");
                 p
@@ -174,11 +174,9 @@ impl Tool for GrepTool {
             let max = max_results;
 
             // Get relative path for display
-            let display_path = file_path
-                .strip_prefix(&ctx.worktree)
-                .unwrap_or(&file_path)
-                .to_string_lossy()
-                .to_string();
+            let display_path = ctx
+                .display_path(&file_path)
+                .unwrap_or_else(|| file_path.to_string_lossy().to_string());
 
             let search_result = searcher.search_path(
                 &matcher,
@@ -382,6 +380,25 @@ mod tests {
         assert!(result.content.contains("Invalid regex"));
     }
 
+    #[tokio::test]
+    async fn test_grep_read_only_mount() {
+        let temp = tempdir().unwrap();
+        let reference = tempdir().unwrap();
+        fs::write(reference.path().join("api.rs"), "pub fn hello() {}").await.unwrap();
+        let ctx = ToolContext::new(temp.path().to_path_buf(), "test-exec".to_string()).with_read_only_mounts(
+            std::collections::BTreeMap::from([("refs".to_string(), reference.path().to_path_buf())]),
+        );
+
+        let result = GrepTool.execute(json!({"pattern": "hello", "path": "@refs"}), &ctx).await;
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.contains("@refs/api.rs:1:pub fn hello"));
+
+        // Without the mount the same directory is outside the sandbox
+        let ctx = ToolContext::new(temp.path().to_path_buf(), "test-exec".to_string());
+        let input = json!({"pattern": "hello", "path": reference.path().to_str().unwrap()});
+        assert!(GrepTool.execute(input, &ctx).await.is_error);
+    }
+
     #[test]
     fn test_format_results() {
         let results = vec![
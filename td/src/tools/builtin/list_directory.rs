@@ -26,7 +26,7 @@ impl Tool for ListDirectoryTool {
             "properties": {
                 "path": {
                     "type": "string",
-                    "description": "Directory path relative to worktree (default: .)"
+                    "description": "Directory path relative to worktree or @mount/path (default: .)"
                 }
             }
         })
@@ -37,7 +37,7 @@ impl Tool for ListDirectoryTool {
         let path = input["path"].as_str().unwrap_or(".");
         debug!(%path, "ListDirectoryTool::execute: path parameter");
 
-        let full_path = match ctx.validate_path(Path::new(path)) {
+        let full_path = match ctx.validate_read_path(Path::new(path)) {
             Ok(p) => {
                 debug!(?p, "ListDirectoryTool::execute: path validated");
                 p
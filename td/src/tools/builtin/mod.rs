@@ -4,32 +4,50 @@ mod complete_task;
 mod edit_file;
 mod explore;
 mod fetch;
+mod find_definition;
+mod find_references;
+mod finish_review;
 mod glob;
 mod grep;
 mod list_directory;
+mod outdated_deps;
 mod query;
 mod read_file;
 mod read_only_bash;
+mod review_comment;
 mod run_command;
 mod search;
+mod security_scan;
 mod share;
+mod symbol_outline;
 mod todo;
 mod tree;
+mod triage_finding;
+mod view_image;
 mod write_file;
 
 pub use complete_task::CompleteTaskTool;
 pub use edit_file::EditFileTool;
 pub use explore::ExploreTool;
 pub use fetch::FetchTool;
+pub use find_definition::FindDefinitionTool;
+pub use find_references::FindReferencesTool;
+pub use finish_review::FinishReviewTool;
 pub use glob::GlobTool;
 pub use grep::GrepTool;
 pub use list_directory::ListDirectoryTool;
+pub use outdated_deps::OutdatedDepsTool;
 pub use query::QueryTool;
 pub use read_file::ReadFileTool;
 pub use read_only_bash::ReadOnlyBashTool;
+pub use review_comment::ReviewCommentTool;
 pub use run_command::RunCommandTool;
 pub use search::SearchTool;
+pub use security_scan::SecurityScanTool;
 pub use share::ShareTool;
+pub use symbol_outline::SymbolOutlineTool;
 pub use todo::TodoTool;
 pub use tree::TreeTool;
+pub use triage_finding::TriageFindingTool;
+pub use view_image::ViewImageTool;
 pub use write_file::WriteFileTool;
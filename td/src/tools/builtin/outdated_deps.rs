@@ -0,0 +1,174 @@
+//! outdated_deps tool - list outdated dependencies and save the ones to upgrade
+
+use async_trait::async_trait;
+use serde_json::Value;
+use tracing::debug;
+
+use crate::deps::{DEPS_DIR, Ecosystem, OUTDATED_FILE, parse_outdated, save_outdated};
+use crate::tools::{Tool, ToolContext, ToolResult, combined_output, run_shell};
+
+/// Exit code `sh` uses for a command that isn't installed
+const COMMAND_NOT_FOUND: i32 = 127;
+
+/// Wall clock for one ecosystem's outdated command (it may fetch the registry index)
+const OUTDATED_TIMEOUT_MS: u64 = 600_000;
+
+/// Characters of command output shown when it can't be parsed
+const OUTPUT_TAIL_CHARS: usize = 2000;
+
+/// Run `cargo outdated` / `npm outdated` and save the dependencies to upgrade
+pub struct OutdatedDepsTool;
+
+#[async_trait]
+impl Tool for OutdatedDepsTool {
+    fn name(&self) -> &'static str {
+        "outdated_deps"
+    }
+
+    fn description(&self) -> &'static str {
+        "List direct dependencies with newer releases (cargo outdated, npm outdated) for every manifest in the \
+         worktree root, and save the ones to upgrade. Each saved dependency gets its own upgrade loop. \
+         Call again with `exclude` to drop dependencies that must not be upgraded."
+    }
+
+    fn input_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "exclude": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Dependency names to leave alone (pinned, known-incompatible, ...)"
+                },
+                "ecosystems": {
+                    "type": "array",
+                    "items": { "type": "string", "enum": ["cargo", "npm"] },
+                    "description": "Only check these ecosystems (default: every one with a manifest)"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, input: Value, ctx: &ToolContext) -> ToolResult {
+        debug!(?input, "OutdatedDepsTool::execute: called");
+        let names = |key: &str| -> Vec<String> {
+            input[key]
+                .as_array()
+                .map(|items| items.iter().filter_map(Value::as_str).map(String::from).collect())
+                .unwrap_or_default()
+        };
+        let exclude = names("exclude");
+        let wanted = names("ecosystems");
+
+        let ecosystems: Vec<Ecosystem> = Ecosystem::detect(&ctx.worktree)
+            .into_iter()
+            .filter(|e| wanted.is_empty() || wanted.contains(&e.to_string()))
+            .collect();
+        if ecosystems.is_empty() {
+            return ToolResult::error("No Cargo.toml or package.json found in the worktree root");
+        }
+
+        let mut lines = Vec::new();
+        let mut deps = Vec::new();
+        let mut ran = 0;
+        for ecosystem in ecosystems {
+            let command = ecosystem.outdated_command();
+            debug!(%ecosystem, %command, "OutdatedDepsTool::execute: running");
+            let output = match run_shell(
+                command,
+                &ctx.worktree,
+                &ctx.env,
+                OUTDATED_TIMEOUT_MS,
+                &ctx.resource_limits,
+            )
+            .await
+            {
+                Ok(output) => output,
+                Err(e) => {
+                    lines.push(format!("{}: failed to run: {}", ecosystem, e));
+                    continue;
+                }
+            };
+            if output.status.code() == Some(COMMAND_NOT_FOUND) {
+                lines.push(format!("{}: skipped, `{}` is not installed", ecosystem, command));
+                continue;
+            }
+
+            // npm outdated exits 1 when something is outdated; only unparseable output is an error
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            match parse_outdated(ecosystem, &stdout) {
+                Ok(found) => {
+                    lines.push(format!("{}: {} outdated", ecosystem, found.len()));
+                    deps.extend(found);
+                    ran += 1;
+                }
+                Err(e) => {
+                    let output = combined_output(&output);
+                    let skip = output.chars().count().saturating_sub(OUTPUT_TAIL_CHARS);
+                    let tail: String = output.chars().skip(skip).collect();
+                    lines.push(format!("{}: {:#}\n{}", ecosystem, e, tail));
+                }
+            }
+        }
+
+        if ran == 0 {
+            debug!("OutdatedDepsTool::execute: no ecosystem produced results");
+            return ToolResult::error(format!("Could not list outdated dependencies:\n{}", lines.join("\n")));
+        }
+
+        deps.retain(|dep| !exclude.contains(&dep.name));
+        if let Err(e) = save_outdated(&ctx.worktree, &deps) {
+            return ToolResult::error(format!("Failed to save outdated dependencies: {:#}", e));
+        }
+        debug!(count = deps.len(), "OutdatedDepsTool::execute: saved");
+
+        let mut message = lines.join("\n");
+        if deps.is_empty() {
+            message.push_str("\n\nNothing to upgrade.");
+        } else {
+            message.push_str(&format!("\n\nTo upgrade ({}/{}):\n", DEPS_DIR, OUTDATED_FILE));
+            for dep in &deps {
+                message.push_str(&format!(
+                    "- {} ({}, {}): {} -> {}\n",
+                    dep.name, dep.ecosystem, dep.kind, dep.current, dep.latest
+                ));
+            }
+        }
+        if !exclude.is_empty() {
+            message.push_str(&format!("\nExcluded: {}", exclude.join(", ")));
+        }
+        ToolResult::success(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deps::load_outdated;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_outdated_deps_requires_a_manifest() {
+        let temp = tempdir().unwrap();
+        let ctx = ToolContext::new(temp.path().to_path_buf(), "test".to_string());
+
+        let result = OutdatedDepsTool.execute(serde_json::json!({}), &ctx).await;
+
+        assert!(result.is_error);
+        assert!(load_outdated(temp.path()).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_outdated_deps_skips_other_ecosystems() {
+        let temp = tempdir().unwrap();
+        std::fs::write(temp.path().join("package.json"), "{}").unwrap();
+        let ctx = ToolContext::new(temp.path().to_path_buf(), "test".to_string());
+
+        let result = OutdatedDepsTool
+            .execute(serde_json::json!({ "ecosystems": ["cargo"] }), &ctx)
+            .await;
+
+        assert!(result.is_error);
+        assert!(result.content.contains("No Cargo.toml"));
+    }
+}
@@ -5,6 +5,7 @@ use serde_json::Value;
 use std::path::Path;
 use tracing::debug;
 
+use crate::progress::content_hash;
 use crate::tools::{Tool, ToolContext, ToolResult};
 
 /// Read a file's contents with line numbers
@@ -26,7 +27,7 @@ impl Tool for ReadFileTool {
             "properties": {
                 "path": {
                     "type": "string",
-                    "description": "File path relative to worktree"
+                    "description": "File path relative to worktree (or @mount/path in a read-only mount)"
                 },
                 "offset": {
                     "type": "integer",
@@ -35,6 +36,10 @@ impl Tool for ReadFileTool {
                 "limit": {
                     "type": "integer",
                     "description": "Max lines to read (default: 2000)"
+                },
+                "full": {
+                    "type": "boolean",
+                    "description": "Return the contents even if the file is unchanged since an earlier iteration read it"
                 }
             },
             "required": ["path"]
@@ -58,7 +63,7 @@ impl Tool for ReadFileTool {
         let limit = input["limit"].as_u64().unwrap_or(2000) as usize;
         debug!(%offset, %limit, "ReadFileTool::execute: offset and limit parameters");
 
-        let full_path = match ctx.validate_path(Path::new(path)) {
+        let full_path = match ctx.validate_read_path(Path::new(path)) {
             Ok(p) => {
                 debug!(?p, "ReadFileTool::execute: path validated");
                 p
@@ -80,6 +85,26 @@ impl Tool for ReadFileTool {
             }
         };
 
+        // A whole file already read in an earlier iteration comes back as its digest
+        let whole_file = offset <= 1 && content.lines().count() <= limit;
+        if whole_file && let Some(exploration) = &ctx.exploration {
+            let shown = ctx.display_path(&full_path).unwrap_or_else(|| path.to_string());
+            let mut cache = exploration.lock().await;
+            if !input["full"].as_bool().unwrap_or(false)
+                && let Some(digest) = cache.unchanged(&full_path, &content_hash(&content))
+            {
+                debug!(iteration = digest.iteration, "ReadFileTool::execute: unchanged since an earlier read");
+                return ToolResult::success(format!(
+                    "{} is unchanged since iteration {} read it. Pass \"full\": true to see the contents \
+                     (required before editing it).\n\n{}",
+                    shown,
+                    digest.iteration,
+                    digest.render()
+                ));
+            }
+            cache.record(&shown, &full_path, &content);
+        }
+
         // Track read for edit validation
         debug!("ReadFileTool::execute: tracking read for edit validation");
         ctx.track_read(&full_path).await;
@@ -178,6 +203,43 @@ mod tests {
         assert!(result.content.contains("Failed to read"));
     }
 
+    #[tokio::test]
+    async fn test_read_file_unchanged_since_earlier_iteration() {
+        use crate::progress::{ExplorationCache, ExplorationCacheConfig};
+        use std::sync::Arc;
+
+        let temp = tempdir().unwrap();
+        fs::write(temp.path().join("lib.rs"), "//! The library\npub fn run() {}\n").unwrap();
+        let cache = Arc::new(tokio::sync::Mutex::new(ExplorationCache::new(
+            &ExplorationCacheConfig::default(),
+        )));
+        let tool = &ReadFileTool;
+        let read = |input: Value| {
+            let ctx = ToolContext::new(temp.path().to_path_buf(), "test".to_string()).with_exploration(cache.clone());
+            async move {
+                let result = tool.execute(input, &ctx).await;
+                (result, ctx.was_read(Path::new("lib.rs")).await)
+            }
+        };
+
+        cache.lock().await.begin_iteration(1);
+        let (result, tracked) = read(serde_json::json!({"path": "lib.rs"})).await;
+        assert!(result.content.contains("pub fn run"));
+        assert!(tracked);
+
+        // Next iteration: the digest instead of the contents, and no edit permission
+        cache.lock().await.begin_iteration(2);
+        let (result, tracked) = read(serde_json::json!({"path": "lib.rs"})).await;
+        assert!(!result.is_error);
+        assert!(result.content.starts_with("lib.rs is unchanged since iteration 1 read it."));
+        assert!(result.content.contains("The library\n  2: pub fn run() {}"));
+        assert!(!tracked);
+
+        let (result, tracked) = read(serde_json::json!({"path": "lib.rs", "full": true})).await;
+        assert!(result.content.contains("     1│//! The library"));
+        assert!(tracked);
+    }
+
     #[tokio::test]
     async fn test_read_file_tracks_read() {
         let temp = tempdir().unwrap();
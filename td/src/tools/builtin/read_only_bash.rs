@@ -5,10 +5,9 @@
 
 use async_trait::async_trait;
 use serde_json::Value;
-use std::time::Duration;
 use tracing::debug;
 
-use crate::tools::{Tool, ToolContext, ToolResult};
+use crate::tools::{Tool, ToolContext, ToolError, ToolResult, combined_output, run_shell};
 
 /// Blocked commands and patterns for read-only mode
 const BLOCKED_COMMANDS: &[&str] = &[
@@ -169,44 +168,22 @@ impl Tool for ReadOnlyBashTool {
         debug!(%timeout_ms, "ReadOnlyBashTool::execute: timeout_ms value");
 
         debug!("ReadOnlyBashTool::execute: spawning command");
-        let output = match tokio::time::timeout(
-            Duration::from_millis(timeout_ms),
-            tokio::process::Command::new("sh")
-                .arg("-c")
-                .arg(command)
-                .current_dir(&ctx.worktree)
-                .output(),
-        )
-        .await
-        {
-            Ok(Ok(output)) => {
+        let output = match run_shell(command, &ctx.worktree, &ctx.env, timeout_ms, &ctx.resource_limits).await {
+            Ok(output) => {
                 debug!(status = ?output.status, "ReadOnlyBashTool::execute: command completed");
                 output
             }
-            Ok(Err(e)) => {
+            Err(ToolError::Io(e)) => {
                 debug!(%e, "ReadOnlyBashTool::execute: failed to execute command");
                 return ToolResult::error(format!("Failed to execute command: {}", e));
             }
-            Err(_) => {
-                debug!("ReadOnlyBashTool::execute: command timed out");
-                return ToolResult::error(format!("Command timed out after {}ms", timeout_ms));
+            Err(e) => {
+                debug!(%e, "ReadOnlyBashTool::execute: command stopped");
+                return e.into();
             }
         };
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        debug!(stdout_len = %stdout.len(), stderr_len = %stderr.len(), "ReadOnlyBashTool::execute: output lengths");
-
-        let result = if stdout.is_empty() && !stderr.is_empty() {
-            debug!("ReadOnlyBashTool::execute: using stderr only");
-            stderr.to_string()
-        } else if stderr.is_empty() {
-            debug!("ReadOnlyBashTool::execute: using stdout only");
-            stdout.to_string()
-        } else {
-            debug!("ReadOnlyBashTool::execute: combining stdout and stderr");
-            format!("{}\n\nSTDERR:\n{}", stdout, stderr)
-        };
+        let result = combined_output(&output);
 
         // Truncate long output (slightly smaller limit for exploration)
         let truncated = if result.len() > 20_000 {
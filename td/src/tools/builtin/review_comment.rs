@@ -0,0 +1,129 @@
+//! review_comment tool - record a comment on the diff under review
+
+use async_trait::async_trait;
+use serde_json::Value;
+use tracing::debug;
+
+use crate::review::{CommentSeverity, Review, ReviewComment};
+use crate::tools::{Tool, ToolContext, ToolResult};
+
+/// Record one comment of a `review` loop
+pub struct ReviewCommentTool;
+
+#[async_trait]
+impl Tool for ReviewCommentTool {
+    fn name(&self) -> &'static str {
+        "review_comment"
+    }
+
+    fn description(&self) -> &'static str {
+        "Comment on a line range of the diff under review. Use blocking only for problems that must be fixed \
+         before merging (bugs, missing plan requirements, broken tests); warning, suggestion or nit otherwise."
+    }
+
+    fn input_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "file": {
+                    "type": "string",
+                    "description": "File path relative to the repository root, as shown in the diff"
+                },
+                "start_line": {
+                    "type": "integer",
+                    "description": "First line commented on (1-based, new version of the file)"
+                },
+                "end_line": {
+                    "type": "integer",
+                    "description": "Last line commented on (default: start_line)"
+                },
+                "severity": {
+                    "type": "string",
+                    "enum": ["nit", "suggestion", "warning", "blocking"],
+                    "description": "How much the comment matters"
+                },
+                "comment": {
+                    "type": "string",
+                    "description": "What is wrong and why"
+                },
+                "suggestion": {
+                    "type": "string",
+                    "description": "How to fix it (optional)"
+                }
+            },
+            "required": ["file", "start_line", "severity", "comment"]
+        })
+    }
+
+    async fn execute(&self, input: Value, ctx: &ToolContext) -> ToolResult {
+        debug!(?input, "ReviewCommentTool::execute: called");
+        let severity: CommentSeverity = match input["severity"].as_str().unwrap_or("").parse() {
+            Ok(severity) => severity,
+            Err(e) => return ToolResult::error(e),
+        };
+        let comment = input["comment"].as_str().unwrap_or("").trim();
+        if comment.is_empty() {
+            return ToolResult::error("comment is required");
+        }
+        let Some(start_line) = input["start_line"].as_u64() else {
+            return ToolResult::error("start_line is required");
+        };
+        let end_line = input["end_line"].as_u64().unwrap_or(start_line);
+
+        let mut review = match Review::load(&ctx.worktree) {
+            Ok(Some(review)) => review,
+            Ok(None) => return ToolResult::error("No review in progress"),
+            Err(e) => return ToolResult::error(format!("Failed to load review: {:#}", e)),
+        };
+        let comment = ReviewComment {
+            file: input["file"].as_str().unwrap_or("").trim().to_string(),
+            start_line: start_line as u32,
+            end_line: end_line as u32,
+            severity,
+            comment: comment.to_string(),
+            suggestion: input["suggestion"]
+                .as_str()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(String::from),
+        };
+        let location = comment.location();
+        if let Err(e) = review.add_comment(comment) {
+            return ToolResult::error(e);
+        }
+        if let Err(e) = review.save(&ctx.worktree) {
+            return ToolResult::error(format!("Failed to save review: {:#}", e));
+        }
+
+        debug!(%location, %severity, "ReviewCommentTool::execute: recorded");
+        ToolResult::success(format!("Commented on {} ({}). Review: {}", location, severity, review.counts()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_review_comment() {
+        let temp = tempdir().unwrap();
+        let ctx = ToolContext::new(temp.path().to_path_buf(), "test".to_string());
+        let input = serde_json::json!({
+            "file": "src/lib.rs", "start_line": 10, "end_line": 12, "severity": "blocking",
+            "comment": "Panics on empty input", "suggestion": "Return None"
+        });
+        assert!(ReviewCommentTool.execute(input.clone(), &ctx).await.is_error);
+
+        Review::new("exec-1", "taskdaemon/exec-1", "abc").save(temp.path()).unwrap();
+        let bad = serde_json::json!({ "file": "src/lib.rs", "start_line": 1, "severity": "major", "comment": "x" });
+        assert!(ReviewCommentTool.execute(bad, &ctx).await.is_error);
+
+        let result = ReviewCommentTool.execute(input, &ctx).await;
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.contains("src/lib.rs:10-12"));
+        let review = Review::load(temp.path()).unwrap().unwrap();
+        assert_eq!(review.blocking().len(), 1);
+        assert_eq!(review.comments[0].suggestion.as_deref(), Some("Return None"));
+    }
+}
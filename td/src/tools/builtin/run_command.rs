@@ -2,10 +2,9 @@
 
 use async_trait::async_trait;
 use serde_json::Value;
-use std::time::Duration;
 use tracing::debug;
 
-use crate::tools::{Tool, ToolContext, ToolResult};
+use crate::tools::{Tool, ToolContext, ToolError, ToolResult, combined_output, run_shell};
 
 /// Execute a shell command in the worktree
 pub struct RunCommandTool;
@@ -54,44 +53,22 @@ impl Tool for RunCommandTool {
         debug!(%timeout_ms, "RunCommandTool::execute: timeout_ms value");
 
         debug!("RunCommandTool::execute: spawning command");
-        let output = match tokio::time::timeout(
-            Duration::from_millis(timeout_ms),
-            tokio::process::Command::new("sh")
-                .arg("-c")
-                .arg(command)
-                .current_dir(&ctx.worktree)
-                .output(),
-        )
-        .await
-        {
-            Ok(Ok(output)) => {
+        let output = match run_shell(command, &ctx.worktree, &ctx.env, timeout_ms, &ctx.resource_limits).await {
+            Ok(output) => {
                 debug!(status = ?output.status, "RunCommandTool::execute: command completed");
                 output
             }
-            Ok(Err(e)) => {
+            Err(ToolError::Io(e)) => {
                 debug!(%e, "RunCommandTool::execute: failed to execute command");
                 return ToolResult::error(format!("Failed to execute command: {}", e));
             }
-            Err(_) => {
-                debug!("RunCommandTool::execute: command timed out");
-                return ToolResult::error(format!("Command timed out after {}ms", timeout_ms));
+            Err(e) => {
+                debug!(%e, "RunCommandTool::execute: command stopped");
+                return e.into();
             }
         };
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        debug!(stdout_len = %stdout.len(), stderr_len = %stderr.len(), "RunCommandTool::execute: output lengths");
-
-        let result = if stdout.is_empty() && !stderr.is_empty() {
-            debug!("RunCommandTool::execute: using stderr only");
-            stderr.to_string()
-        } else if stderr.is_empty() {
-            debug!("RunCommandTool::execute: using stdout only");
-            stdout.to_string()
-        } else {
-            debug!("RunCommandTool::execute: combining stdout and stderr");
-            format!("{}\n\nSTDERR:\n{}", stdout, stderr)
-        };
+        let result = combined_output(&output);
 
         // Truncate long output
         let truncated = if result.len() > 30_000 {
@@ -146,6 +123,25 @@ mod tests {
         assert!(result.content.contains(temp.path().to_str().unwrap()) || !result.content.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_run_command_resource_limit() {
+        let temp = tempdir().unwrap();
+        let ctx = ToolContext::new(temp.path().to_path_buf(), "test".to_string()).with_resource_limits(
+            crate::tools::ResourceLimits {
+                timeout_ms: Some(200),
+                ..Default::default()
+            },
+        );
+        let tool = RunCommandTool;
+
+        let result = tool.execute(serde_json::json!({"command": "sleep 5"}), &ctx).await;
+
+        assert!(result.is_error);
+        let violation = result.violation.expect("violation should be reported");
+        assert_eq!(violation.kind, crate::tools::ResourceKind::WallClock);
+        assert!(result.content.contains("wall-clock limit of 200ms"));
+    }
+
     #[tokio::test]
     async fn test_run_command_failure() {
         let temp = tempdir().unwrap();
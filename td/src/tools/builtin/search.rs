@@ -82,7 +82,7 @@ impl Tool for SearchTool {
         })
     }
 
-    async fn execute(&self, input: Value, _ctx: &ToolContext) -> ToolResult {
+    async fn execute(&self, input: Value, ctx: &ToolContext) -> ToolResult {
         debug!(?input, "SearchTool::execute: called");
         let query = match input["query"].as_str() {
             Some(q) => {
@@ -112,6 +112,11 @@ impl Tool for SearchTool {
             }
         };
 
+        if let Some(Err(e)) = provider_url(&config.provider).map(|url| ctx.network.check_url("search", url)) {
+            debug!(%e, "SearchTool::execute: blocked by network policy");
+            return ToolResult::error(e);
+        }
+
         // Execute search based on provider
         debug!(provider = %config.provider, "SearchTool::execute: executing search");
         match config.provider.as_str() {
@@ -135,6 +140,20 @@ impl Tool for SearchTool {
     }
 }
 
+/// Endpoint a search provider is queried at
+fn provider_url(provider: &str) -> Option<&'static str> {
+    match provider {
+        "tavily" => Some(TAVILY_URL),
+        "brave" => Some(BRAVE_URL),
+        "serpapi" => Some(SERPAPI_URL),
+        _ => None,
+    }
+}
+
+const TAVILY_URL: &str = "https://api.tavily.com/search";
+const BRAVE_URL: &str = "https://api.search.brave.com/res/v1/web/search";
+const SERPAPI_URL: &str = "https://serpapi.com/search";
+
 /// Search using Tavily API
 async fn search_tavily(query: &str, max_results: usize, api_key: &str) -> ToolResult {
     debug!(%query, %max_results, "search_tavily: called");
@@ -151,7 +170,7 @@ async fn search_tavily(query: &str, max_results: usize, api_key: &str) -> ToolRe
     });
 
     debug!("search_tavily: sending request");
-    let response = match client.post("https://api.tavily.com/search").json(&body).send().await {
+    let response = match client.post(TAVILY_URL).json(&body).send().await {
         Ok(r) => {
             debug!(status = %r.status(), "search_tavily: received response");
             r
@@ -214,7 +233,7 @@ async fn search_brave(query: &str, max_results: usize, api_key: &str) -> ToolRes
 
     debug!("search_brave: sending request");
     let response = match client
-        .get("https://api.search.brave.com/res/v1/web/search")
+        .get(BRAVE_URL)
         .header("X-Subscription-Token", api_key)
         .query(&[("q", query), ("count", &max_results.to_string())])
         .send()
@@ -282,7 +301,7 @@ async fn search_serpapi(query: &str, max_results: usize, api_key: &str) -> ToolR
 
     debug!("search_serpapi: sending request");
     let response = match client
-        .get("https://serpapi.com/search")
+        .get(SERPAPI_URL)
         .query(&[
             ("q", query),
             ("api_key", api_key),
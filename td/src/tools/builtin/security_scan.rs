@@ -0,0 +1,177 @@
+//! security_scan tool - run static-analysis scanners and record their findings
+
+use async_trait::async_trait;
+use serde_json::Value;
+use tracing::debug;
+
+use crate::security::{FindingStore, SECURITY_DIR, parse_findings};
+use crate::tools::{Tool, ToolContext, ToolResult, combined_output, run_shell};
+
+/// Exit code `sh` uses for a command that isn't installed
+const COMMAND_NOT_FOUND: i32 = 127;
+
+/// Characters of scanner output shown when it can't be parsed
+const OUTPUT_TAIL_CHARS: usize = 2000;
+
+/// Run the loop type's scanners and merge their findings into the finding store
+pub struct SecurityScanTool;
+
+#[async_trait]
+impl Tool for SecurityScanTool {
+    fn name(&self) -> &'static str {
+        "security_scan"
+    }
+
+    fn description(&self) -> &'static str {
+        "Run the configured static-analysis scanners (e.g. cargo-audit, semgrep) in the worktree and record \
+         their findings. Re-run after fixing to confirm fixes. Returns the findings still waiting for triage."
+    }
+
+    fn input_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "scanners": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Names of the scanners to run (default: all configured)"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, input: Value, ctx: &ToolContext) -> ToolResult {
+        debug!(?input, "SecurityScanTool::execute: called");
+        if ctx.scanners.is_empty() {
+            return ToolResult::error("No scanners are configured for this loop type (`scanners` in its YAML)");
+        }
+        let wanted: Option<Vec<&str>> = input["scanners"]
+            .as_array()
+            .map(|names| names.iter().filter_map(Value::as_str).collect());
+        let specs: Vec<_> = ctx
+            .scanners
+            .iter()
+            .filter(|spec| wanted.as_ref().is_none_or(|names| names.contains(&spec.name.as_str())))
+            .collect();
+        if specs.is_empty() {
+            let known: Vec<&str> = ctx.scanners.iter().map(|s| s.name.as_str()).collect();
+            return ToolResult::error(format!("No such scanner. Configured: {}", known.join(", ")));
+        }
+
+        let mut store = match FindingStore::load(&ctx.worktree) {
+            Ok(store) => store,
+            Err(e) => return ToolResult::error(format!("Failed to load findings: {:#}", e)),
+        };
+
+        let mut lines = Vec::new();
+        let mut ran = 0;
+        for spec in specs {
+            debug!(scanner = %spec.name, command = %spec.command, "SecurityScanTool::execute: running scanner");
+            let output = match run_shell(
+                &spec.command,
+                &ctx.worktree,
+                &ctx.env,
+                spec.timeout_ms,
+                &ctx.resource_limits,
+            )
+            .await
+            {
+                Ok(output) => output,
+                Err(e) => {
+                    lines.push(format!("{}: failed to run: {}", spec.name, e));
+                    continue;
+                }
+            };
+            if output.status.code() == Some(COMMAND_NOT_FOUND) {
+                debug!(scanner = %spec.name, "SecurityScanTool::execute: scanner not installed");
+                lines.push(format!("{}: skipped, `{}` is not installed", spec.name, spec.command));
+                continue;
+            }
+
+            // Scanners exit non-zero when they find something; only unparseable output is an error
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            match parse_findings(&spec.name, spec.format, &stdout) {
+                Ok(findings) => {
+                    lines.push(format!("{}: {} findings", spec.name, findings.len()));
+                    store.merge_scan(&spec.name, findings);
+                    ran += 1;
+                }
+                Err(e) => {
+                    let output = combined_output(&output);
+                    let skip = output.chars().count().saturating_sub(OUTPUT_TAIL_CHARS);
+                    let tail: String = output.chars().skip(skip).collect();
+                    lines.push(format!("{}: {:#}\n{}", spec.name, e, tail));
+                }
+            }
+        }
+
+        if ran == 0 {
+            debug!("SecurityScanTool::execute: no scanner produced results");
+            return ToolResult::error(format!("No scanner produced results:\n{}", lines.join("\n")));
+        }
+        if let Err(e) = store.save(&ctx.worktree) {
+            return ToolResult::error(format!("Failed to save findings: {:#}", e));
+        }
+
+        let pending = store.format_pending();
+        ToolResult::success(format!(
+            "{}\n\nTotal: {} (report: {}/)\n\n{}",
+            lines.join("\n"),
+            store.counts(),
+            SECURITY_DIR,
+            if pending.is_empty() {
+                "No findings are waiting for triage.".to_string()
+            } else {
+                format!("Waiting for triage:\n{}", pending)
+            }
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::{ScannerFormat, ScannerSpec};
+    use tempfile::tempdir;
+
+    fn scanner(name: &str, command: &str) -> ScannerSpec {
+        ScannerSpec {
+            name: name.to_string(),
+            command: command.to_string(),
+            format: ScannerFormat::Semgrep,
+            timeout_ms: 10_000,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_security_scan_records_findings() {
+        let temp = tempdir().unwrap();
+        let report = r#"{"results":[{"check_id":"hardcoded-secret","path":"src/main.rs","start":{"line":3},"extra":{"message":"Hardcoded key","severity":"ERROR"}}]}"#;
+        std::fs::write(temp.path().join("out.json"), report).unwrap();
+        let ctx = ToolContext::new(temp.path().to_path_buf(), "test".to_string()).with_scanners(vec![
+            scanner("semgrep", "cat out.json; exit 1"),
+            scanner("missing", "definitely-not-a-scanner --json"),
+        ]);
+
+        let result = SecurityScanTool.execute(serde_json::json!({}), &ctx).await;
+
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.contains("semgrep: 1 findings"));
+        assert!(result.content.contains("missing: skipped"));
+        assert!(result.content.contains("hardcoded-secret"));
+        assert_eq!(FindingStore::load(temp.path()).unwrap().counts().pending, 1);
+    }
+
+    #[tokio::test]
+    async fn test_security_scan_requires_a_result() {
+        let temp = tempdir().unwrap();
+        let ctx = ToolContext::new(temp.path().to_path_buf(), "test".to_string());
+        assert!(SecurityScanTool.execute(serde_json::json!({}), &ctx).await.is_error);
+
+        let ctx = ctx.with_scanners(vec![scanner("broken", "echo oops")]);
+        let result = SecurityScanTool.execute(serde_json::json!({}), &ctx).await;
+        assert!(result.is_error);
+        assert!(result.content.contains("oops"));
+        assert!(!temp.path().join(SECURITY_DIR).exists());
+    }
+}
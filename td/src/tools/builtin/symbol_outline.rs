@@ -0,0 +1,185 @@
+//! symbol_outline tool - structural outline of a file via LSP
+
+use std::path::Path;
+
+use async_trait::async_trait;
+use serde_json::Value;
+use tracing::debug;
+
+use crate::tools::lsp::path_to_uri;
+use crate::tools::{Tool, ToolContext, ToolResult};
+
+/// List the symbols (types, functions, fields, ...) declared in a file
+pub struct SymbolOutlineTool;
+
+#[async_trait]
+impl Tool for SymbolOutlineTool {
+    fn name(&self) -> &'static str {
+        "symbol_outline"
+    }
+
+    fn description(&self) -> &'static str {
+        "Show an outline of the symbols declared in a file (types, functions, methods) with line numbers, \
+         using the project's language server. Cheaper than reading the whole file."
+    }
+
+    fn input_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "File to outline (relative to worktree)"
+                }
+            },
+            "required": ["path"]
+        })
+    }
+
+    async fn execute(&self, input: Value, ctx: &ToolContext) -> ToolResult {
+        debug!(?input, "SymbolOutlineTool::execute: called");
+        let Some(lsp) = &ctx.lsp else {
+            debug!("SymbolOutlineTool::execute: no LSP session in context");
+            return ToolResult::error("Language server not available in this context. Use read or grep instead.");
+        };
+
+        let Some(path) = input["path"].as_str() else {
+            debug!("SymbolOutlineTool::execute: missing path parameter");
+            return ToolResult::error("path is required");
+        };
+
+        let abs_path = match ctx.validate_path(Path::new(path)) {
+            Ok(p) => p,
+            Err(e) => {
+                debug!(%e, "SymbolOutlineTool::execute: path validation failed");
+                return ToolResult::error(e.to_string());
+            }
+        };
+
+        let params = serde_json::json!({ "textDocument": { "uri": path_to_uri(&abs_path) } });
+        match lsp
+            .document_request(&abs_path, "textDocument/documentSymbol", params)
+            .await
+        {
+            Ok(result) => {
+                let outline = format_outline(&result);
+                if outline.is_empty() {
+                    debug!("SymbolOutlineTool::execute: no symbols");
+                    ToolResult::success("No symbols found.")
+                } else {
+                    ToolResult::success(outline)
+                }
+            }
+            Err(e) => {
+                debug!(%e, "SymbolOutlineTool::execute: request failed");
+                ToolResult::error(format!("symbol_outline failed: {}", e))
+            }
+        }
+    }
+}
+
+/// Human-readable name for an LSP SymbolKind
+fn symbol_kind_name(kind: u64) -> &'static str {
+    match kind {
+        1 => "file",
+        2 => "module",
+        3 => "namespace",
+        4 => "package",
+        5 => "class",
+        6 => "method",
+        7 => "property",
+        8 => "field",
+        9 => "constructor",
+        10 => "enum",
+        11 => "interface",
+        12 => "function",
+        13 => "variable",
+        14 => "constant",
+        22 => "enum-member",
+        23 => "struct",
+        24 => "event",
+        25 => "operator",
+        26 => "type-parameter",
+        _ => "symbol",
+    }
+}
+
+/// Render a documentSymbol response (hierarchical DocumentSymbol[] or flat SymbolInformation[])
+fn format_outline(result: &Value) -> String {
+    let mut lines = Vec::new();
+    if let Some(symbols) = result.as_array() {
+        for symbol in symbols {
+            format_symbol(symbol, 0, &mut lines);
+        }
+    }
+    lines.join("\n")
+}
+
+fn format_symbol(symbol: &Value, depth: usize, lines: &mut Vec<String>) {
+    let name = symbol["name"].as_str().unwrap_or("?");
+    let kind = symbol_kind_name(symbol["kind"].as_u64().unwrap_or(0));
+    // DocumentSymbol has range; SymbolInformation has location.range
+    let range = symbol.get("range").unwrap_or(&symbol["location"]["range"]);
+    let start = range["start"]["line"].as_u64().map(|l| l + 1).unwrap_or(0);
+    let end = range["end"]["line"].as_u64().map(|l| l + 1).unwrap_or(start);
+
+    let container = symbol["containerName"]
+        .as_str()
+        .filter(|c| !c.is_empty())
+        .map(|c| format!(" (in {})", c))
+        .unwrap_or_default();
+    let span = if end > start { format!("{}-{}", start, end) } else { start.to_string() };
+    lines.push(format!(
+        "{}{} {} [{}]{}",
+        "  ".repeat(depth),
+        kind,
+        name,
+        span,
+        container
+    ));
+
+    if let Some(children) = symbol["children"].as_array() {
+        for child in children {
+            format_symbol(child, depth + 1, lines);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_format_hierarchical_outline() {
+        let result = json!([{
+            "name": "Foo",
+            "kind": 23,
+            "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 3, "character": 1 } },
+            "children": [{
+                "name": "bar",
+                "kind": 8,
+                "range": { "start": { "line": 1, "character": 4 }, "end": { "line": 1, "character": 12 } }
+            }]
+        }]);
+
+        let outline = format_outline(&result);
+        assert_eq!(outline, "struct Foo [1-4]\n  field bar [2]");
+    }
+
+    #[test]
+    fn test_format_flat_outline() {
+        let result = json!([{
+            "name": "run",
+            "kind": 12,
+            "containerName": "app",
+            "location": {
+                "uri": "file:///tmp/app.py",
+                "range": { "start": { "line": 9, "character": 0 }, "end": { "line": 20, "character": 0 } }
+            }
+        }]);
+
+        assert_eq!(format_outline(&result), "function run [10-21] (in app)");
+        assert!(format_outline(&Value::Null).is_empty());
+    }
+}
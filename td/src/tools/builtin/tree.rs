@@ -48,7 +48,7 @@ impl Tool for TreeTool {
         let show_hidden = input["show_hidden"].as_bool().unwrap_or(false);
         debug!(%path, %depth, %show_hidden, "TreeTool::execute: parameters");
 
-        let full_path = match ctx.validate_path(Path::new(path)) {
+        let full_path = match ctx.validate_read_path(Path::new(path)) {
             Ok(p) => {
                 debug!(?p, "TreeTool::execute: path validated");
                 p
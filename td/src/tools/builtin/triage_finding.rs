@@ -0,0 +1,110 @@
+//! triage_finding tool - record a verdict on a security finding
+
+use async_trait::async_trait;
+use serde_json::Value;
+use tracing::debug;
+
+use crate::security::{FindingStore, Verdict};
+use crate::tools::{Tool, ToolContext, ToolResult};
+
+/// Record the triage of one finding reported by `security_scan`
+pub struct TriageFindingTool;
+
+#[async_trait]
+impl Tool for TriageFindingTool {
+    fn name(&self) -> &'static str {
+        "triage_finding"
+    }
+
+    fn description(&self) -> &'static str {
+        "Record your verdict on a security finding: fixed (you changed the code), accepted (a real issue \
+         whose risk is accepted) or dismissed (not an issue). Every finding must be triaged."
+    }
+
+    fn input_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "id": {
+                    "type": "string",
+                    "description": "Finding ID as listed by security_scan (e.g. semgrep-1a2b3c4d)"
+                },
+                "verdict": {
+                    "type": "string",
+                    "enum": ["fixed", "accepted", "dismissed"],
+                    "description": "Triage outcome"
+                },
+                "justification": {
+                    "type": "string",
+                    "description": "The fix made, why the risk is acceptable, or why it is not an issue"
+                }
+            },
+            "required": ["id", "verdict", "justification"]
+        })
+    }
+
+    async fn execute(&self, input: Value, ctx: &ToolContext) -> ToolResult {
+        debug!(?input, "TriageFindingTool::execute: called");
+        let Some(id) = input["id"].as_str() else {
+            return ToolResult::error("id is required");
+        };
+        let verdict: Verdict = match input["verdict"].as_str().unwrap_or("").parse() {
+            Ok(verdict) => verdict,
+            Err(e) => return ToolResult::error(e),
+        };
+        let justification = input["justification"].as_str().unwrap_or("").trim();
+        if justification.is_empty() {
+            return ToolResult::error("justification is required");
+        }
+
+        let mut store = match FindingStore::load(&ctx.worktree) {
+            Ok(store) => store,
+            Err(e) => return ToolResult::error(format!("Failed to load findings: {:#}", e)),
+        };
+        if let Err(e) = store.triage(id, verdict, justification) {
+            return ToolResult::error(format!("{}. Run security_scan to list findings.", e));
+        }
+        if let Err(e) = store.save(&ctx.worktree) {
+            return ToolResult::error(format!("Failed to save findings: {:#}", e));
+        }
+
+        let counts = store.counts();
+        debug!(%id, %verdict, pending = counts.pending, "TriageFindingTool::execute: recorded");
+        let mut message = format!("Marked {} as {}. Findings: {}", id, verdict, counts);
+        if verdict == Verdict::Fixed {
+            message.push_str("\nRun security_scan again to confirm the fix.");
+        }
+        ToolResult::success(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::{Finding, Severity};
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_triage_finding() {
+        let temp = tempdir().unwrap();
+        let finding = Finding::new("semgrep", "weak-hash", Severity::Medium, "MD5 used", None, None);
+        let id = finding.id.clone();
+        let mut store = FindingStore::default();
+        store.merge_scan("semgrep", vec![finding]);
+        store.save(temp.path()).unwrap();
+        let ctx = ToolContext::new(temp.path().to_path_buf(), "test".to_string());
+
+        let missing = serde_json::json!({ "id": id, "verdict": "dismissed", "justification": " " });
+        assert!(TriageFindingTool.execute(missing, &ctx).await.is_error);
+        let unknown = serde_json::json!({ "id": "semgrep-0", "verdict": "fixed", "justification": "x" });
+        assert!(TriageFindingTool.execute(unknown, &ctx).await.is_error);
+
+        let input =
+            serde_json::json!({ "id": id, "verdict": "dismissed", "justification": "Checksum, not a password" });
+        let result = TriageFindingTool.execute(input, &ctx).await;
+
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.contains("0 pending"));
+        assert_eq!(FindingStore::load(temp.path()).unwrap().counts().dismissed, 1);
+    }
+}
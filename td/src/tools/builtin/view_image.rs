@@ -0,0 +1,114 @@
+//! view_image tool - show the model an image from the worktree
+
+use async_trait::async_trait;
+use serde_json::Value;
+use std::path::Path;
+use tracing::debug;
+
+use crate::llm::{ContentBlock, MAX_IMAGE_BYTES};
+use crate::tools::{Tool, ToolContext, ToolResult};
+
+/// Load a PNG/JPEG/GIF/WebP from the worktree into the conversation
+pub struct ViewImageTool;
+
+#[async_trait]
+impl Tool for ViewImageTool {
+    fn name(&self) -> &'static str {
+        "view_image"
+    }
+
+    fn description(&self) -> &'static str {
+        "Look at an image file (PNG, JPEG, GIF or WebP, up to 5MB), e.g. a UI screenshot. The image is added to the conversation."
+    }
+
+    fn input_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Image path relative to worktree"
+                }
+            },
+            "required": ["path"]
+        })
+    }
+
+    async fn execute(&self, input: Value, ctx: &ToolContext) -> ToolResult {
+        debug!(?input, "ViewImageTool::execute: called");
+        let Some(path) = input["path"].as_str() else {
+            debug!("ViewImageTool::execute: missing path parameter");
+            return ToolResult::error("path is required");
+        };
+
+        let full_path = match ctx.validate_path(Path::new(path)) {
+            Ok(p) => p,
+            Err(e) => {
+                debug!(%e, "ViewImageTool::execute: path validation failed");
+                return ToolResult::error(e.to_string());
+            }
+        };
+
+        let loaded = tokio::task::spawn_blocking(move || ContentBlock::image_from_path(&full_path, MAX_IMAGE_BYTES))
+            .await
+            .unwrap_or_else(|e| Err(format!("Failed to load image: {}", e)));
+        match loaded {
+            Ok(image) => {
+                let summary = match &image {
+                    ContentBlock::Image { media_type, data } => {
+                        format!("Loaded {} ({}, {} bytes base64)", path, media_type, data.len())
+                    }
+                    _ => format!("Loaded {}", path),
+                };
+                debug!(%summary, "ViewImageTool::execute: image loaded");
+                ToolResult::with_image(summary, image)
+            }
+            Err(e) => {
+                debug!(%e, "ViewImageTool::execute: failed to load image");
+                ToolResult::error(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    const PNG_HEADER: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+    #[tokio::test]
+    async fn test_view_image_attaches_image() {
+        let temp = tempdir().unwrap();
+        fs::write(temp.path().join("shot.png"), PNG_HEADER).unwrap();
+        let ctx = ToolContext::new(temp.path().to_path_buf(), "test-exec".to_string());
+
+        let result = ViewImageTool
+            .execute(serde_json::json!({ "path": "shot.png" }), &ctx)
+            .await;
+
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.contains("image/png"));
+        assert!(matches!(&result.images[..], [ContentBlock::Image { media_type, .. }] if media_type == "image/png"));
+    }
+
+    #[tokio::test]
+    async fn test_view_image_rejects_non_images_and_escapes() {
+        let temp = tempdir().unwrap();
+        fs::write(temp.path().join("notes.txt"), "hello").unwrap();
+        let ctx = ToolContext::new(temp.path().to_path_buf(), "test-exec".to_string());
+
+        let result = ViewImageTool
+            .execute(serde_json::json!({ "path": "notes.txt" }), &ctx)
+            .await;
+        assert!(result.is_error);
+        assert!(result.images.is_empty());
+
+        let result = ViewImageTool
+            .execute(serde_json::json!({ "path": "../outside.png" }), &ctx)
+            .await;
+        assert!(result.is_error);
+    }
+}
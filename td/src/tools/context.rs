@@ -1,14 +1,16 @@
 //! ToolContext - execution context for tools
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tracing::debug;
 
 use crate::coordinator::CoordinatorHandle;
+use crate::progress::ExplorationCacheRef;
+use crate::security::ScannerSpec;
 
-use super::ToolError;
+use super::{LspSessionRef, NetworkGuard, ResourceLimits, ToolError};
 
 /// Configuration for spawning explore tasks
 #[derive(Debug, Clone)]
@@ -73,14 +75,14 @@ impl Thoroughness {
 }
 
 impl std::str::FromStr for Thoroughness {
-    type Err = ();
+    type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
-            "quick" => Ok(Self::Quick),
+            "quick" | "low" => Ok(Self::Quick),
             "medium" => Ok(Self::Medium),
-            "thorough" => Ok(Self::Thorough),
-            _ => Err(()),
+            "thorough" | "high" => Ok(Self::Thorough),
+            _ => Err(format!("Unknown thoroughness: {}. Use: quick, medium or thorough", s)),
         }
     }
 }
@@ -133,6 +135,29 @@ pub struct ToolContext {
     /// Optional callback for spawning explore tasks
     /// Set to None in explore tasks to prevent nested explores
     pub explore_spawner: Option<ExploreSpawnerRef>,
+
+    /// Optional language server session for code intelligence tools
+    /// Owned by the execution so the server survives across iterations
+    pub lsp: Option<LspSessionRef>,
+
+    /// Limits applied to commands spawned by tools (from the loop type)
+    pub resource_limits: ResourceLimits,
+
+    /// Extra environment for commands spawned by tools (e.g. a shared CARGO_TARGET_DIR)
+    pub env: Vec<(String, String)>,
+
+    /// Static-analysis commands run by `security_scan` (from the loop type)
+    pub scanners: Vec<ScannerSpec>,
+
+    /// Extra roots the read-only tools may read, by mount name (from the loop type)
+    /// Reachable by absolute path or as `@name/...`; writes stay confined to the worktree
+    pub read_only_mounts: BTreeMap<String, PathBuf>,
+
+    /// Outbound network policy, and the requests checked against it
+    pub network: NetworkGuard,
+
+    /// Files read in earlier iterations, so unchanged ones need not be returned again
+    pub exploration: Option<ExplorationCacheRef>,
 }
 
 /// Default max tokens when not specified
@@ -150,6 +175,13 @@ impl ToolContext {
             coordinator: None,
             max_tokens: DEFAULT_MAX_TOKENS,
             explore_spawner: None,
+            lsp: None,
+            resource_limits: ResourceLimits::default(),
+            env: Vec::new(),
+            scanners: Vec::new(),
+            read_only_mounts: BTreeMap::new(),
+            network: NetworkGuard::default(),
+            exploration: None,
         }
     }
 
@@ -164,6 +196,13 @@ impl ToolContext {
             coordinator: None,
             max_tokens,
             explore_spawner: None,
+            lsp: None,
+            resource_limits: ResourceLimits::default(),
+            env: Vec::new(),
+            scanners: Vec::new(),
+            read_only_mounts: BTreeMap::new(),
+            network: NetworkGuard::default(),
+            exploration: None,
         }
     }
 
@@ -178,6 +217,13 @@ impl ToolContext {
             coordinator: None,
             max_tokens: DEFAULT_MAX_TOKENS,
             explore_spawner: None,
+            lsp: None,
+            resource_limits: ResourceLimits::default(),
+            env: Vec::new(),
+            scanners: Vec::new(),
+            read_only_mounts: BTreeMap::new(),
+            network: NetworkGuard::default(),
+            exploration: None,
         }
     }
 
@@ -192,6 +238,13 @@ impl ToolContext {
             coordinator: Some(coordinator),
             max_tokens: DEFAULT_MAX_TOKENS,
             explore_spawner: None,
+            lsp: None,
+            resource_limits: ResourceLimits::default(),
+            env: Vec::new(),
+            scanners: Vec::new(),
+            read_only_mounts: BTreeMap::new(),
+            network: NetworkGuard::default(),
+            exploration: None,
         }
     }
 
@@ -211,6 +264,13 @@ impl ToolContext {
             coordinator: Some(coordinator),
             max_tokens,
             explore_spawner: None,
+            lsp: None,
+            resource_limits: ResourceLimits::default(),
+            env: Vec::new(),
+            scanners: Vec::new(),
+            read_only_mounts: BTreeMap::new(),
+            network: NetworkGuard::default(),
+            exploration: None,
         }
     }
 
@@ -221,6 +281,55 @@ impl ToolContext {
         self
     }
 
+    /// Builder method to set the language server session
+    pub fn with_lsp(mut self, lsp: LspSessionRef) -> Self {
+        debug!(%self.exec_id, "ToolContext::with_lsp: called");
+        self.lsp = Some(lsp);
+        self
+    }
+
+    /// Builder method to set the resource limits for spawned commands
+    pub fn with_resource_limits(mut self, limits: ResourceLimits) -> Self {
+        debug!(%self.exec_id, ?limits, "ToolContext::with_resource_limits: called");
+        self.resource_limits = limits;
+        self
+    }
+
+    /// Set extra environment variables for commands spawned by tools
+    pub fn with_env(mut self, env: Vec<(String, String)>) -> Self {
+        debug!(%self.exec_id, ?env, "ToolContext::with_env: called");
+        self.env = env;
+        self
+    }
+
+    /// Set the scanners available to `security_scan`
+    pub fn with_scanners(mut self, scanners: Vec<ScannerSpec>) -> Self {
+        debug!(%self.exec_id, count = scanners.len(), "ToolContext::with_scanners: called");
+        self.scanners = scanners;
+        self
+    }
+
+    /// Set the extra roots read-only tools may read
+    pub fn with_read_only_mounts(mut self, mounts: BTreeMap<String, PathBuf>) -> Self {
+        debug!(%self.exec_id, ?mounts, "ToolContext::with_read_only_mounts: called");
+        self.read_only_mounts = mounts;
+        self
+    }
+
+    /// Set the outbound network policy (shared with the execution's proxy)
+    pub fn with_network(mut self, network: NetworkGuard) -> Self {
+        debug!(%self.exec_id, policy = ?network.policy(), "ToolContext::with_network: called");
+        self.network = network;
+        self
+    }
+
+    /// Set the execution's exploration cache
+    pub fn with_exploration(mut self, exploration: ExplorationCacheRef) -> Self {
+        debug!(%self.exec_id, "ToolContext::with_exploration: called");
+        self.exploration = Some(exploration);
+        self
+    }
+
     /// Track that a file was read (enables edit validation)
     pub async fn track_read(&self, path: &Path) {
         debug!(?path, "ToolContext::track_read: called");
@@ -244,10 +353,24 @@ impl ToolContext {
         read_files.clear();
     }
 
+    /// Files read so far (shipped to a tool worker with each call)
+    pub(crate) async fn reads(&self) -> Vec<PathBuf> {
+        self.read_files.lock().await.iter().cloned().collect()
+    }
+
+    /// Replace the read tracking with what a tool worker reported back
+    pub(crate) async fn set_reads(&self, paths: Vec<PathBuf>) {
+        debug!(count = paths.len(), "ToolContext::set_reads: called");
+        *self.read_files.lock().await = paths.into_iter().collect();
+    }
+
     /// Normalize a path relative to worktree
     fn normalize_path(&self, path: &Path) -> PathBuf {
         debug!(?path, "ToolContext::normalize_path: called");
-        if path.is_absolute() {
+        if let Some(mounted) = self.resolve_mount(path) {
+            debug!(?mounted, "ToolContext::normalize_path: path is in a read-only mount");
+            mounted
+        } else if path.is_absolute() {
             debug!("ToolContext::normalize_path: path is absolute");
             path.to_path_buf()
         } else {
@@ -256,6 +379,60 @@ impl ToolContext {
         }
     }
 
+    /// Resolve a virtual `@name/...` path to its read-only mount
+    fn resolve_mount(&self, path: &Path) -> Option<PathBuf> {
+        let rest = path.to_str()?.strip_prefix('@')?;
+        let (name, rel) = rest.split_once('/').unwrap_or((rest, ""));
+        let root = self.read_only_mounts.get(name)?;
+        Some(if rel.is_empty() { root.clone() } else { root.join(rel) })
+    }
+
+    /// Validate path for reading: within the worktree or a read-only mount
+    ///
+    /// Used by the tools that only read (read, glob, grep, list, tree); tools
+    /// that write keep using [`validate_path`](Self::validate_path).
+    pub fn validate_read_path(&self, path: &Path) -> Result<PathBuf, ToolError> {
+        debug!(?path, "ToolContext::validate_read_path: called");
+        let in_worktree = self.validate_path(path);
+        if in_worktree.is_ok() || !self.sandbox_enabled || self.read_only_mounts.is_empty() {
+            return in_worktree;
+        }
+
+        let normalized = self.normalize_path(path);
+        let canonical = normalized.canonicalize().unwrap_or(normalized);
+        let mounted = self.read_only_mounts.values().any(|root| {
+            let root = root.canonicalize().unwrap_or_else(|_| root.clone());
+            canonical.starts_with(&root)
+        });
+        if mounted {
+            debug!("ToolContext::validate_read_path: path is within a read-only mount");
+            Ok(canonical)
+        } else {
+            debug!("ToolContext::validate_read_path: sandbox violation detected");
+            in_worktree
+        }
+    }
+
+    /// How a tool shows `path` to the model: relative to the worktree, `@name/...`
+    /// inside a read-only mount, or None when it is outside both
+    pub fn display_path(&self, path: &Path) -> Option<String> {
+        let roots = std::iter::once((None, &self.worktree))
+            .chain(self.read_only_mounts.iter().map(|(name, root)| (Some(name), root)));
+        for (name, root) in roots {
+            let canonical = root.canonicalize().unwrap_or_else(|_| root.clone());
+            let Some(rel) = path.strip_prefix(root).or_else(|_| path.strip_prefix(&canonical)).ok() else {
+                continue;
+            };
+            let rel = rel.to_string_lossy();
+            return Some(match name {
+                None => rel.to_string(),
+                Some(name) if rel.is_empty() => format!("@{}", name),
+                Some(name) => format!("@{}/{}", name, rel),
+            });
+        }
+        None
+    }
+
     /// Validate path is within worktree (sandbox enforcement)
     pub fn validate_path(&self, path: &Path) -> Result<PathBuf, ToolError> {
         debug!(?path, "ToolContext::validate_path: called");
@@ -310,6 +487,9 @@ impl std::fmt::Debug for ToolContext {
             .field("worktree", &self.worktree)
             .field("exec_id", &self.exec_id)
             .field("sandbox_enabled", &self.sandbox_enabled)
+            .field("resource_limits", &self.resource_limits)
+            .field("read_only_mounts", &self.read_only_mounts)
+            .field("network", self.network.policy())
             .finish()
     }
 }
@@ -397,4 +577,29 @@ mod tests {
         let result = ctx.validate_path(Path::new("new_file.txt"));
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_read_only_mounts() {
+        let temp = tempdir().unwrap();
+        let reference = tempdir().unwrap();
+        fs::write(reference.path().join("lib.rs"), "pub fn x() {}").unwrap();
+        let mounts = BTreeMap::from([("refs".to_string(), reference.path().to_path_buf())]);
+        let ctx = ToolContext::new(temp.path().to_path_buf(), "test-exec".to_string()).with_read_only_mounts(mounts);
+
+        // Readable by virtual and absolute path
+        let virtual_path = ctx.validate_read_path(Path::new("@refs/lib.rs")).unwrap();
+        assert_eq!(virtual_path, reference.path().join("lib.rs").canonicalize().unwrap());
+        assert!(ctx.validate_read_path(&reference.path().join("lib.rs")).is_ok());
+        assert_eq!(ctx.display_path(&virtual_path).as_deref(), Some("@refs/lib.rs"));
+        assert_eq!(ctx.display_path(&temp.path().join("a.rs")).as_deref(), Some("a.rs"));
+
+        // Never writable, and other paths stay outside the sandbox
+        assert!(ctx.validate_path(Path::new("@refs/lib.rs")).is_err());
+        assert!(ctx.validate_path(&reference.path().join("lib.rs")).is_err());
+        assert!(ctx.validate_read_path(Path::new("/etc/passwd")).is_err());
+        // An unknown mount name is just a worktree-relative path
+        let unknown = ctx.validate_read_path(Path::new("@other/lib.rs")).unwrap();
+        assert!(unknown.ends_with("@other/lib.rs"));
+        assert_eq!(ctx.display_path(Path::new("/etc/passwd")), None);
+    }
 }
@@ -3,6 +3,8 @@
 use std::path::PathBuf;
 use thiserror::Error;
 
+use super::ResourceViolation;
+
 /// Errors that can occur during tool execution
 #[derive(Debug, Error)]
 pub enum ToolError {
@@ -22,9 +24,19 @@ pub enum ToolError {
     #[error("Command timed out after {timeout_ms}ms")]
     CommandTimeout { timeout_ms: u64 },
 
+    #[error("Resource limit exceeded: {violation}")]
+    ResourceLimitExceeded {
+        violation: ResourceViolation,
+        /// Output the command produced before it was stopped
+        output: String,
+    },
+
     #[error("Tool not found: {name}")]
     UnknownTool { name: String },
 
+    #[error("Tool already registered: {name}")]
+    DuplicateTool { name: String },
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -62,4 +74,14 @@ mod tests {
         assert!(msg.contains("5"));
         assert!(msg.contains("replace_all"));
     }
+
+    #[test]
+    fn test_resource_limit_exceeded_message() {
+        let err = ToolError::ResourceLimitExceeded {
+            violation: ResourceViolation::new(crate::tools::ResourceKind::Memory, 512),
+            output: String::new(),
+        };
+
+        assert_eq!(err.to_string(), "Resource limit exceeded: memory limit of 512MB");
+    }
 }
@@ -1,15 +1,20 @@
 //! ToolExecutor - manages tool execution for a loop or task
 
-use std::collections::HashMap;
-use tracing::debug;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tracing::{debug, warn};
 
+use crate::config::ToolWorkerConfig;
 use crate::llm::{ToolCall, ToolDefinition};
 
 use super::builtin::{
-    CompleteTaskTool, EditFileTool, ExploreTool, FetchTool, GlobTool, GrepTool, ListDirectoryTool, QueryTool,
-    ReadFileTool, ReadOnlyBashTool, RunCommandTool, SearchTool, ShareTool, TodoTool, TreeTool, WriteFileTool,
+    CompleteTaskTool, EditFileTool, ExploreTool, FetchTool, FindDefinitionTool, FindReferencesTool, FinishReviewTool,
+    GlobTool, GrepTool, ListDirectoryTool, OutdatedDepsTool, QueryTool, ReadFileTool, ReadOnlyBashTool,
+    ReviewCommentTool, RunCommandTool, SearchTool, SecurityScanTool, ShareTool, SymbolOutlineTool, TodoTool, TreeTool,
+    TriageFindingTool, ViewImageTool, WriteFileTool,
 };
-use super::{Tool, ToolContext, ToolResult};
+use super::worker::{ISOLATED_TOOLS, ToolWorker};
+use super::{Tool, ToolContext, ToolError, ToolResult};
 
 /// Tool profiles define which tools are available for different task types
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -24,6 +29,11 @@ pub enum ToolProfile {
 /// Manages tool execution for a loop
 pub struct ToolExecutor {
     tools: HashMap<String, Box<dyn Tool>>,
+    profile: ToolProfile,
+    /// Names still bound to the profile's built-in tools
+    builtin: HashSet<String>,
+    /// Runs isolated built-ins out of process when set
+    worker: Option<ToolWorker>,
 }
 
 impl ToolExecutor {
@@ -46,6 +56,7 @@ impl ToolExecutor {
                 tools.insert("list".into(), Box::new(ListDirectoryTool));
                 tools.insert("glob".into(), Box::new(GlobTool));
                 tools.insert("grep".into(), Box::new(GrepTool));
+                tools.insert("view_image".into(), Box::new(ViewImageTool));
 
                 // Command execution (full access)
                 tools.insert("bash".into(), Box::new(RunCommandTool));
@@ -56,6 +67,22 @@ impl ToolExecutor {
                 tools.insert("fetch".into(), Box::new(FetchTool::new()));
                 tools.insert("search".into(), Box::new(SearchTool));
 
+                // Code intelligence (requires lsp session in context)
+                tools.insert("find_definition".into(), Box::new(FindDefinitionTool));
+                tools.insert("find_references".into(), Box::new(FindReferencesTool));
+                tools.insert("symbol_outline".into(), Box::new(SymbolOutlineTool));
+
+                // Security review (scanners come from the loop type)
+                tools.insert("security_scan".into(), Box::new(SecurityScanTool));
+                tools.insert("triage_finding".into(), Box::new(TriageFindingTool));
+
+                // Dependency upgrades
+                tools.insert("outdated_deps".into(), Box::new(OutdatedDepsTool));
+
+                // Diff review
+                tools.insert("review_comment".into(), Box::new(ReviewCommentTool));
+                tools.insert("finish_review".into(), Box::new(FinishReviewTool));
+
                 // Task completion
                 tools.insert("complete_task".into(), Box::new(CompleteTaskTool));
 
@@ -73,6 +100,7 @@ impl ToolExecutor {
                 tools.insert("glob".into(), Box::new(GlobTool));
                 tools.insert("grep".into(), Box::new(GrepTool));
                 tools.insert("tree".into(), Box::new(TreeTool));
+                tools.insert("view_image".into(), Box::new(ViewImageTool));
 
                 // Read-only bash (blocks write commands)
                 tools.insert("bash".into(), Box::new(ReadOnlyBashTool));
@@ -88,7 +116,13 @@ impl ToolExecutor {
             }
         }
 
-        Self { tools }
+        let builtin = tools.keys().cloned().collect();
+        Self {
+            tools,
+            profile,
+            builtin,
+            worker: None,
+        }
     }
 
     /// Create executor with read-only tools (for exploration)
@@ -99,15 +133,53 @@ impl ToolExecutor {
     /// Create an empty executor (for testing)
     pub fn empty() -> Self {
         debug!("ToolExecutor::empty: called");
-        Self { tools: HashMap::new() }
+        Self {
+            tools: HashMap::new(),
+            profile: ToolProfile::Full,
+            builtin: HashSet::new(),
+            worker: None,
+        }
+    }
+
+    /// Run the file and command built-ins in `worker` instead of this process
+    ///
+    /// Tools that need daemon state, and any added with
+    /// [`add_tool`](Self::add_tool) or [`register_tool`](Self::register_tool),
+    /// keep running here.
+    pub fn use_worker(&mut self, config: &ToolWorkerConfig) -> eyre::Result<()> {
+        debug!(?config, "ToolExecutor::use_worker: called");
+        self.worker = Some(ToolWorker::new(self.profile, config)?);
+        Ok(())
+    }
+
+    /// The tool worker, if tools run out of process
+    pub fn worker(&self) -> Option<&ToolWorker> {
+        self.worker.as_ref()
     }
 
     /// Add a tool to the executor
     pub fn add_tool(&mut self, tool: Box<dyn Tool>) {
         debug!(tool_name = %tool.name(), "ToolExecutor::add_tool: called");
+        self.builtin.remove(tool.name());
         self.tools.insert(tool.name().to_string(), tool);
     }
 
+    /// Add a tool from outside the built-in set, refusing to replace one
+    ///
+    /// Unlike [`add_tool`](Self::add_tool) this fails if the name is taken,
+    /// so a plugin cannot shadow `bash` or `write`.
+    pub fn register_tool(&mut self, tool: Box<dyn Tool>) -> Result<(), ToolError> {
+        debug!(tool_name = %tool.name(), "ToolExecutor::register_tool: called");
+        if self.tools.contains_key(tool.name()) {
+            debug!("ToolExecutor::register_tool: name already taken");
+            return Err(ToolError::DuplicateTool {
+                name: tool.name().to_string(),
+            });
+        }
+        self.tools.insert(tool.name().to_string(), tool);
+        Ok(())
+    }
+
     /// Get tool definitions for LLM
     pub fn definitions(&self) -> Vec<ToolDefinition> {
         debug!("ToolExecutor::definitions: called");
@@ -140,6 +212,13 @@ impl ToolExecutor {
         debug!(tool_name = %tool_call.name, tool_id = %tool_call.id, "ToolExecutor::execute: called");
         match self.tools.get(&tool_call.name) {
             Some(tool) => {
+                if let Some(worker) = &self.worker
+                    && self.builtin.contains(&tool_call.name)
+                    && ISOLATED_TOOLS.contains(&tool_call.name.as_str())
+                {
+                    debug!("ToolExecutor::execute: tool found, executing in worker");
+                    return worker.execute(tool_call, ctx).await;
+                }
                 debug!("ToolExecutor::execute: tool found, executing");
                 tool.execute(tool_call.input.clone(), ctx).await
             }
@@ -150,6 +229,29 @@ impl ToolExecutor {
         }
     }
 
+    /// Execute a tool call, abandoning it once `limit` elapses
+    ///
+    /// Returns None if the call timed out. The call's future is dropped, which
+    /// kills any command it spawned.
+    pub async fn execute_within(
+        &self,
+        tool_call: &ToolCall,
+        ctx: &ToolContext,
+        limit: Option<Duration>,
+    ) -> Option<ToolResult> {
+        debug!(tool_name = %tool_call.name, tool_id = %tool_call.id, ?limit, "ToolExecutor::execute_within: called");
+        let Some(limit) = limit else {
+            return Some(self.execute(tool_call, ctx).await);
+        };
+        match tokio::time::timeout(limit, self.execute(tool_call, ctx)).await {
+            Ok(result) => Some(result),
+            Err(_) => {
+                warn!(tool_name = %tool_call.name, ?limit, "Tool call timed out");
+                None
+            }
+        }
+    }
+
     /// Execute multiple tool calls
     pub async fn execute_all(&self, tool_calls: &[ToolCall], ctx: &ToolContext) -> Vec<(String, ToolResult)> {
         debug!(count = %tool_calls.len(), "ToolExecutor::execute_all: called");
@@ -203,6 +305,16 @@ mod tests {
         assert!(executor.has_tool("glob"));
     }
 
+    #[test]
+    fn test_standard_executor_has_code_intelligence_tools() {
+        let executor = ToolExecutor::standard();
+
+        assert!(executor.has_tool("find_definition"));
+        assert!(executor.has_tool("find_references"));
+        assert!(executor.has_tool("symbol_outline"));
+        assert!(!ToolExecutor::read_only().has_tool("find_definition"));
+    }
+
     #[test]
     fn test_definitions_returns_all_tools() {
         let executor = ToolExecutor::standard();
@@ -238,4 +350,36 @@ mod tests {
         assert!(result.is_error);
         assert!(result.content.contains("Unknown tool"));
     }
+
+    #[tokio::test]
+    async fn test_execute_within_abandons_hung_call() {
+        let executor = ToolExecutor::standard();
+        let temp = tempdir().unwrap();
+        let ctx = ToolContext::new(temp.path().to_path_buf(), "test".to_string());
+
+        let call = ToolCall {
+            id: "call_1".to_string(),
+            name: "bash".to_string(),
+            input: serde_json::json!({"command": "sleep 30"}),
+        };
+
+        let started = std::time::Instant::now();
+        let result = executor
+            .execute_within(&call, &ctx, Some(Duration::from_millis(200)))
+            .await;
+        assert!(result.is_none());
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_register_tool_refuses_to_shadow() {
+        let mut executor = ToolExecutor::standard();
+
+        let err = executor.register_tool(Box::new(ReadOnlyBashTool)).unwrap_err();
+        assert!(matches!(err, ToolError::DuplicateTool { ref name } if name == "bash"));
+
+        let mut executor = ToolExecutor::empty();
+        executor.register_tool(Box::new(ReadOnlyBashTool)).unwrap();
+        assert!(executor.has_tool("bash"));
+    }
 }
@@ -0,0 +1,391 @@
+//! Resource limits for commands spawned by tools
+//!
+//! A runaway `cargo build` in one worktree can starve every other loop. Loop
+//! types can cap the commands their tools spawn (`resource-limits` in YAML):
+//! CPU time and address space are applied as rlimits through the shell's
+//! `ulimit` builtin, so they hold for each process the command starts, and a
+//! wall-clock timeout is enforced everywhere by killing the command's whole
+//! process group. A command that trips a limit is reported as
+//! `ToolError::ResourceLimitExceeded` and carried back on the `ToolResult`.
+
+use std::path::Path;
+use std::process::{Output, Stdio};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use super::ToolError;
+
+/// Exit status used by shells for a child killed by signal N (128 + N)
+const SHELL_SIGNAL_BASE: i32 = 128;
+
+/// SIGKILL - sent when the hard CPU limit is reached (or by the OOM killer)
+const SIGKILL: i32 = 9;
+
+/// SIGSEGV / SIGABRT - typical deaths of a process that failed to allocate
+const SIGSEGV: i32 = 11;
+const SIGABRT: i32 = 6;
+
+/// SIGXCPU - sent when the soft CPU limit is reached
+const SIGXCPU: i32 = 24;
+
+/// Stderr fragments that indicate an allocation failure under `memory-mb`
+const OOM_MARKERS: &[&str] = &[
+    "cannot allocate memory",
+    "memory allocation of",
+    "out of memory",
+    "memoryerror",
+    "bad_alloc",
+];
+
+/// Which limit a command ran into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ResourceKind {
+    /// CPU time (`cpu-secs`)
+    Cpu,
+    /// Address space (`memory-mb`)
+    Memory,
+    /// Wall-clock time (`timeout-ms`)
+    WallClock,
+}
+
+impl std::fmt::Display for ResourceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cpu => write!(f, "cpu"),
+            Self::Memory => write!(f, "memory"),
+            Self::WallClock => write!(f, "wall-clock"),
+        }
+    }
+}
+
+/// A command exceeded one of its loop type's resource limits
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceViolation {
+    /// Limit that was exceeded
+    pub kind: ResourceKind,
+    /// Configured limit, in the unit of its kind (seconds, MB, milliseconds)
+    pub limit: u64,
+}
+
+impl ResourceViolation {
+    /// Create a violation of the given limit
+    pub fn new(kind: ResourceKind, limit: u64) -> Self {
+        debug!(%kind, limit, "ResourceViolation::new: called");
+        Self { kind, limit }
+    }
+}
+
+impl std::fmt::Display for ResourceViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            ResourceKind::Cpu => write!(f, "cpu limit of {}s", self.limit),
+            ResourceKind::Memory => write!(f, "memory limit of {}MB", self.limit),
+            ResourceKind::WallClock => write!(f, "wall-clock limit of {}ms", self.limit),
+        }
+    }
+}
+
+/// Per-loop-type limits for spawned commands (`resource-limits` in YAML)
+///
+/// Unset fields are unlimited. CPU and memory limits are rlimits and so apply
+/// to each process separately, not to the command's process tree as a whole.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct ResourceLimits {
+    /// CPU seconds per process (SIGXCPU at the limit, SIGKILL a second later)
+    pub cpu_secs: Option<u64>,
+
+    /// Address space per process in megabytes
+    pub memory_mb: Option<u64>,
+
+    /// Wall-clock cap in milliseconds; also caps the tool's own `timeout_ms`
+    pub timeout_ms: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// Whether no limit is configured
+    pub fn is_unlimited(&self) -> bool {
+        self.cpu_secs.is_none() && self.memory_mb.is_none() && self.timeout_ms.is_none()
+    }
+
+    /// Fill limits this set leaves unset from `parent`
+    pub fn merged_with(&self, parent: &ResourceLimits) -> ResourceLimits {
+        debug!(?self, ?parent, "ResourceLimits::merged_with: called");
+        ResourceLimits {
+            cpu_secs: self.cpu_secs.or(parent.cpu_secs),
+            memory_mb: self.memory_mb.or(parent.memory_mb),
+            timeout_ms: self.timeout_ms.or(parent.timeout_ms),
+        }
+    }
+
+    /// Effective wall-clock timeout for a command, and whether the limit (not the
+    /// tool's requested timeout) is what bounds it
+    pub fn wall_clock_ms(&self, requested_ms: u64) -> (u64, bool) {
+        debug!(requested_ms, timeout_ms = ?self.timeout_ms, "ResourceLimits::wall_clock_ms: called");
+        match self.timeout_ms {
+            Some(limit) if limit < requested_ms => (limit, true),
+            _ => (requested_ms, false),
+        }
+    }
+
+    /// Shell prelude that applies the CPU and memory rlimits, if any are set
+    fn ulimit_prelude(&self) -> Option<String> {
+        debug!(cpu_secs = ?self.cpu_secs, memory_mb = ?self.memory_mb, "ResourceLimits::ulimit_prelude: called");
+        let mut steps = Vec::new();
+        if let Some(secs) = self.cpu_secs {
+            // Soft limit first: a hard limit below the current soft one is rejected
+            steps.push(format!("ulimit -S -t {}", secs));
+            steps.push(format!("ulimit -H -t {}", secs + 1));
+        }
+        if let Some(mb) = self.memory_mb {
+            steps.push(format!("ulimit -v {}", mb * 1024));
+        }
+        if steps.is_empty() {
+            debug!("ResourceLimits::ulimit_prelude: no rlimits");
+            return None;
+        }
+        Some(steps.join(" && "))
+    }
+
+    /// Work out whether a finished command was stopped by one of the limits
+    fn classify(&self, output: &Output) -> Option<ResourceViolation> {
+        debug!(status = ?output.status, "ResourceLimits::classify: called");
+        let signal = termination_signal(output);
+
+        if let Some(secs) = self.cpu_secs
+            && matches!(signal, Some(SIGXCPU) | Some(SIGKILL))
+        {
+            debug!(?signal, "ResourceLimits::classify: cpu limit");
+            return Some(ResourceViolation::new(ResourceKind::Cpu, secs));
+        }
+
+        if let Some(mb) = self.memory_mb
+            && !output.status.success()
+        {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_lowercase();
+            if matches!(signal, Some(SIGKILL) | Some(SIGSEGV) | Some(SIGABRT))
+                || OOM_MARKERS.iter().any(|marker| stderr.contains(marker))
+            {
+                debug!(?signal, "ResourceLimits::classify: memory limit");
+                return Some(ResourceViolation::new(ResourceKind::Memory, mb));
+            }
+        }
+
+        debug!("ResourceLimits::classify: within limits");
+        None
+    }
+}
+
+/// Signal that ended the command, either directly or as reported by `sh`
+fn termination_signal(output: &Output) -> Option<i32> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = output.status.signal() {
+            return Some(signal);
+        }
+    }
+    output
+        .status
+        .code()
+        .filter(|code| *code > SHELL_SIGNAL_BASE)
+        .map(|code| code - SHELL_SIGNAL_BASE)
+}
+
+/// Kill every process in the command's process group
+fn kill_process_group(pid: Option<u32>) {
+    debug!(?pid, "kill_process_group: called");
+    #[cfg(unix)]
+    if let Some(pid) = pid {
+        use nix::sys::signal::{Signal, killpg};
+        use nix::unistd::Pid;
+
+        if let Err(e) = killpg(Pid::from_raw(pid as i32), Signal::SIGKILL) {
+            debug!(%e, "kill_process_group: killpg failed");
+        }
+    }
+}
+
+/// Combine stdout and stderr the way the bash tools report them
+pub fn combined_output(output: &Output) -> String {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    debug!(stdout_len = %stdout.len(), stderr_len = %stderr.len(), "combined_output: called");
+    if stdout.is_empty() && !stderr.is_empty() {
+        stderr.to_string()
+    } else if stderr.is_empty() {
+        stdout.to_string()
+    } else {
+        format!("{}\n\nSTDERR:\n{}", stdout, stderr)
+    }
+}
+
+/// Run `command` with `sh -c` in `cwd` under `limits`
+///
+/// `requested_timeout_ms` is the tool's own timeout; a lower `timeout-ms` limit
+/// takes precedence and is reported as a violation rather than a plain timeout.
+pub async fn run_shell(
+    command: &str,
+    cwd: &Path,
+    env: &[(String, String)],
+    requested_timeout_ms: u64,
+    limits: &ResourceLimits,
+) -> Result<Output, ToolError> {
+    debug!(%command, ?cwd, requested_timeout_ms, ?limits, "run_shell: called");
+    let (timeout_ms, capped) = limits.wall_clock_ms(requested_timeout_ms);
+
+    let mut cmd = tokio::process::Command::new("sh");
+    match limits.ulimit_prelude() {
+        Some(prelude) => {
+            debug!(%prelude, "run_shell: applying rlimits");
+            cmd.arg("-c")
+                .arg(format!("{} && exec sh -c \"$1\"", prelude))
+                .arg("sh")
+                .arg(command);
+        }
+        None => {
+            cmd.arg("-c").arg(command);
+        }
+    }
+    cmd.current_dir(cwd)
+        .envs(env.iter().cloned())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    #[cfg(unix)]
+    cmd.process_group(0);
+
+    let child = cmd.spawn()?;
+    let pid = child.id();
+    match tokio::time::timeout(Duration::from_millis(timeout_ms), child.wait_with_output()).await {
+        Ok(Ok(output)) => match limits.classify(&output) {
+            Some(violation) => {
+                debug!(%violation, "run_shell: resource limit exceeded");
+                Err(ToolError::ResourceLimitExceeded {
+                    violation,
+                    output: combined_output(&output),
+                })
+            }
+            None => {
+                debug!(status = ?output.status, "run_shell: command completed");
+                Ok(output)
+            }
+        },
+        Ok(Err(e)) => {
+            debug!(%e, "run_shell: failed waiting for command");
+            Err(ToolError::Io(e))
+        }
+        Err(_) => {
+            debug!(timeout_ms, capped, "run_shell: command timed out");
+            kill_process_group(pid);
+            if capped {
+                Err(ToolError::ResourceLimitExceeded {
+                    violation: ResourceViolation::new(ResourceKind::WallClock, timeout_ms),
+                    output: String::new(),
+                })
+            } else {
+                Err(ToolError::CommandTimeout { timeout_ms })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_resource_limits_yaml() {
+        let yaml = "cpu-secs: 30\nmemory-mb: 2048\n";
+        let limits: ResourceLimits = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(limits.cpu_secs, Some(30));
+        assert_eq!(limits.memory_mb, Some(2048));
+        assert_eq!(limits.timeout_ms, None);
+        assert!(!limits.is_unlimited());
+        assert!(ResourceLimits::default().is_unlimited());
+    }
+
+    #[test]
+    fn test_merged_with_and_wall_clock() {
+        let parent = ResourceLimits {
+            cpu_secs: Some(10),
+            memory_mb: None,
+            timeout_ms: Some(5_000),
+        };
+        let child = ResourceLimits {
+            cpu_secs: Some(20),
+            ..Default::default()
+        };
+        let merged = child.merged_with(&parent);
+        assert_eq!(merged.cpu_secs, Some(20));
+        assert_eq!(merged.timeout_ms, Some(5_000));
+
+        assert_eq!(merged.wall_clock_ms(120_000), (5_000, true));
+        assert_eq!(merged.wall_clock_ms(1_000), (1_000, false));
+        assert_eq!(ResourceLimits::default().wall_clock_ms(1_000), (1_000, false));
+    }
+
+    #[tokio::test]
+    async fn test_run_shell_cpu_limit() {
+        let temp = tempdir().unwrap();
+        let limits = ResourceLimits {
+            cpu_secs: Some(1),
+            ..Default::default()
+        };
+
+        let result = run_shell("while :; do :; done", temp.path(), &[], 30_000, &limits).await;
+
+        match result {
+            Err(ToolError::ResourceLimitExceeded { violation, .. }) => {
+                assert_eq!(violation, ResourceViolation::new(ResourceKind::Cpu, 1));
+            }
+            other => panic!("expected cpu violation, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_shell_wall_clock_limit() {
+        let temp = tempdir().unwrap();
+        let limits = ResourceLimits {
+            timeout_ms: Some(200),
+            ..Default::default()
+        };
+
+        let result = run_shell("sleep 5", temp.path(), &[], 120_000, &limits).await;
+        assert!(matches!(
+            result,
+            Err(ToolError::ResourceLimitExceeded {
+                violation: ResourceViolation {
+                    kind: ResourceKind::WallClock,
+                    limit: 200
+                },
+                ..
+            })
+        ));
+
+        // The tool's own shorter timeout is still a plain timeout
+        let result = run_shell("sleep 5", temp.path(), &[], 100, &limits).await;
+        assert!(matches!(result, Err(ToolError::CommandTimeout { timeout_ms: 100 })));
+    }
+
+    #[tokio::test]
+    async fn test_run_shell_within_limits() {
+        let temp = tempdir().unwrap();
+        let limits = ResourceLimits {
+            cpu_secs: Some(10),
+            memory_mb: Some(512),
+            timeout_ms: Some(10_000),
+        };
+
+        let output = run_shell("echo ok; exit 3", temp.path(), &[], 10_000, &limits)
+            .await
+            .unwrap();
+        assert_eq!(output.status.code(), Some(3));
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "ok");
+    }
+}
@@ -0,0 +1,678 @@
+//! Language server integration for code intelligence tools
+//!
+//! An `LspSession` lazily spawns the project's language server (rust-analyzer,
+//! gopls, ...) inside a worktree and keeps it alive for the lifetime of the
+//! execution, so go-to-definition style queries don't pay indexing cost on
+//! every call. The session is shared across iterations via `ToolContext`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::Duration;
+
+use serde_json::{Value, json};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::{Mutex, oneshot};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use super::ToolContext;
+
+/// Default timeout for a single LSP request
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Pending request table shared between the client and its reader task
+type PendingMap = Arc<std::sync::Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>;
+
+/// How to launch a language server for a project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LspServerSpec {
+    /// Executable name or path
+    pub command: String,
+
+    /// Arguments passed to the executable
+    pub args: Vec<String>,
+
+    /// Default language id for opened documents
+    pub language_id: String,
+}
+
+impl LspServerSpec {
+    fn new(command: &str, args: &[&str], language_id: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            language_id: language_id.to_string(),
+        }
+    }
+
+    /// Detect the language server for a project from its marker files
+    pub fn detect(root: &Path) -> Option<Self> {
+        debug!(?root, "LspServerSpec::detect: called");
+        let spec = if root.join("Cargo.toml").exists() {
+            Self::new("rust-analyzer", &[], "rust")
+        } else if root.join("go.mod").exists() {
+            Self::new("gopls", &[], "go")
+        } else if root.join("tsconfig.json").exists() || root.join("package.json").exists() {
+            Self::new("typescript-language-server", &["--stdio"], "typescript")
+        } else if root.join("pyproject.toml").exists()
+            || root.join("setup.py").exists()
+            || root.join("requirements.txt").exists()
+        {
+            Self::new("pylsp", &[], "python")
+        } else {
+            debug!("LspServerSpec::detect: no known project markers");
+            return None;
+        };
+        debug!(command = %spec.command, "LspServerSpec::detect: detected server");
+        Some(spec)
+    }
+}
+
+/// Map a file extension to an LSP language id
+fn language_id_for(path: &Path, fallback: &str) -> String {
+    let id = match path.extension().and_then(|e| e.to_str()) {
+        Some("rs") => "rust",
+        Some("go") => "go",
+        Some("ts") => "typescript",
+        Some("tsx") => "typescriptreact",
+        Some("js") => "javascript",
+        Some("jsx") => "javascriptreact",
+        Some("py") => "python",
+        _ => fallback,
+    };
+    id.to_string()
+}
+
+/// Convert an absolute path to a `file://` URI
+pub fn path_to_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+/// Convert a `file://` URI back to a path (None for other schemes)
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?;
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(hex) = encoded.get(i + 1..i + 3)
+            && let Ok(b) = u8::from_str_radix(hex, 16)
+        {
+            decoded.push(b);
+            i += 3;
+            continue;
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    Some(PathBuf::from(String::from_utf8_lossy(&decoded).into_owned()))
+}
+
+/// Convert a character column (0-based, in chars) to an LSP UTF-16 offset
+pub fn utf16_column(line: &str, char_col: usize) -> u32 {
+    line.chars().take(char_col).map(|c| c.len_utf16() as u32).sum()
+}
+
+/// Encode a JSON-RPC message with LSP base protocol framing
+fn encode_message(body: &Value) -> Vec<u8> {
+    let payload = body.to_string();
+    let mut out = format!("Content-Length: {}\r\n\r\n", payload.len()).into_bytes();
+    out.extend_from_slice(payload.as_bytes());
+    out
+}
+
+/// Read one framed JSON-RPC message (None on EOF)
+async fn read_message<R: AsyncRead + Unpin>(reader: &mut BufReader<R>) -> std::io::Result<Option<Value>> {
+    let mut content_length: Option<usize> = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).await? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(len) = header.strip_prefix("Content-Length:") {
+            content_length = len.trim().parse().ok();
+        }
+    }
+
+    let len = content_length
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "missing Content-Length header"))?;
+    let mut body = vec![0u8; len];
+    reader.read_exact(&mut body).await?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+async fn write_message<W: AsyncWrite + Unpin>(writer: &mut W, body: &Value) -> std::io::Result<()> {
+    writer.write_all(&encode_message(body)).await?;
+    writer.flush().await
+}
+
+/// A running language server connection
+pub struct LspClient {
+    root: PathBuf,
+    spec: LspServerSpec,
+    child: Child,
+    stdin: Arc<Mutex<ChildStdin>>,
+    pending: PendingMap,
+    next_id: AtomicI64,
+    reader: JoinHandle<()>,
+    /// Open documents: path -> (version, text last sent to the server)
+    open_docs: HashMap<PathBuf, (i32, String)>,
+    request_timeout: Duration,
+}
+
+impl LspClient {
+    /// Spawn the server and perform the initialize handshake
+    pub async fn start(root: &Path, spec: LspServerSpec) -> eyre::Result<Self> {
+        debug!(?root, command = %spec.command, "LspClient::start: called");
+        let mut child = Command::new(&spec.command)
+            .args(&spec.args)
+            .current_dir(root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| eyre::eyre!("Failed to start language server '{}': {}", spec.command, e))?;
+
+        let stdin = Arc::new(Mutex::new(child.stdin.take().ok_or_else(|| eyre::eyre!("No stdin"))?));
+        let stdout = child.stdout.take().ok_or_else(|| eyre::eyre!("No stdout"))?;
+        let pending: PendingMap = Arc::new(std::sync::Mutex::new(HashMap::new()));
+
+        let reader = tokio::spawn(Self::read_loop(BufReader::new(stdout), stdin.clone(), pending.clone()));
+
+        let client = Self {
+            root: root.to_path_buf(),
+            spec,
+            child,
+            stdin,
+            pending,
+            next_id: AtomicI64::new(1),
+            reader,
+            open_docs: HashMap::new(),
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+        };
+
+        let root_uri = path_to_uri(root);
+        let init_params = json!({
+            "processId": std::process::id(),
+            "rootUri": root_uri,
+            "workspaceFolders": [{ "uri": root_uri, "name": "worktree" }],
+            "capabilities": {
+                "textDocument": {
+                    "definition": { "linkSupport": false },
+                    "references": {},
+                    "documentSymbol": { "hierarchicalDocumentSymbolSupport": true }
+                }
+            }
+        });
+        client.request("initialize", init_params).await?;
+        client.notify("initialized", json!({})).await?;
+
+        info!(command = %client.spec.command, root = ?client.root, "Language server started");
+        Ok(client)
+    }
+
+    /// Dispatch responses to waiting requests and answer server-initiated requests
+    async fn read_loop<R: AsyncRead + Unpin>(
+        mut reader: BufReader<R>,
+        stdin: Arc<Mutex<ChildStdin>>,
+        pending: PendingMap,
+    ) {
+        debug!("LspClient::read_loop: started");
+        loop {
+            let msg = match read_message(&mut reader).await {
+                Ok(Some(msg)) => msg,
+                Ok(None) => {
+                    debug!("LspClient::read_loop: server closed stdout");
+                    break;
+                }
+                Err(e) => {
+                    warn!(error = %e, "LspClient::read_loop: failed to read message");
+                    break;
+                }
+            };
+
+            let id = msg.get("id").cloned();
+            let method = msg.get("method").and_then(|m| m.as_str());
+            match (id, method) {
+                (Some(id), Some(method)) => {
+                    // Server -> client request (workspace/configuration, progress create, ...).
+                    // We don't support any of them; reply with null so the server doesn't stall.
+                    debug!(%method, "LspClient::read_loop: answering server request");
+                    let reply = json!({ "jsonrpc": "2.0", "id": id, "result": Value::Null });
+                    let mut stdin = stdin.lock().await;
+                    if let Err(e) = write_message(&mut *stdin, &reply).await {
+                        warn!(error = %e, "LspClient::read_loop: failed to reply to server request");
+                    }
+                }
+                (Some(id), None) => {
+                    let Some(id) = id.as_i64() else { continue };
+                    let result = match msg.get("error") {
+                        Some(err) => Err(err
+                            .get("message")
+                            .and_then(|m| m.as_str())
+                            .unwrap_or("unknown error")
+                            .to_string()),
+                        None => Ok(msg.get("result").cloned().unwrap_or(Value::Null)),
+                    };
+                    let sender = pending.lock().expect("pending mutex poisoned").remove(&id);
+                    if let Some(sender) = sender {
+                        let _ = sender.send(result);
+                    }
+                }
+                _ => {
+                    // Notifications (diagnostics, progress, logs) are ignored
+                }
+            }
+        }
+
+        // Fail any requests still waiting on the dead server
+        pending.lock().expect("pending mutex poisoned").clear();
+    }
+
+    /// Send a request and wait for its result
+    pub async fn request(&self, method: &str, params: Value) -> eyre::Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        debug!(%method, id, "LspClient::request: called");
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().expect("pending mutex poisoned").insert(id, tx);
+
+        let msg = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        {
+            let mut stdin = self.stdin.lock().await;
+            write_message(&mut *stdin, &msg).await?;
+        }
+
+        match tokio::time::timeout(self.request_timeout, rx).await {
+            Ok(Ok(Ok(result))) => Ok(result),
+            Ok(Ok(Err(message))) => Err(eyre::eyre!("{} failed: {}", method, message)),
+            Ok(Err(_)) => Err(eyre::eyre!("Language server exited during {}", method)),
+            Err(_) => {
+                self.pending.lock().expect("pending mutex poisoned").remove(&id);
+                Err(eyre::eyre!(
+                    "{} timed out after {}s (server may still be indexing)",
+                    method,
+                    self.request_timeout.as_secs()
+                ))
+            }
+        }
+    }
+
+    /// Send a notification (no response expected)
+    pub async fn notify(&self, method: &str, params: Value) -> eyre::Result<()> {
+        debug!(%method, "LspClient::notify: called");
+        let msg = json!({ "jsonrpc": "2.0", "method": method, "params": params });
+        let mut stdin = self.stdin.lock().await;
+        write_message(&mut *stdin, &msg).await?;
+        Ok(())
+    }
+
+    /// Make sure the server has the current on-disk content of a file
+    ///
+    /// Loops edit files between queries, so the document is re-sent whenever
+    /// its content differs from what the server last saw.
+    pub async fn sync_document(&mut self, path: &Path) -> eyre::Result<String> {
+        debug!(?path, "LspClient::sync_document: called");
+        let text = tokio::fs::read_to_string(path).await?;
+        let uri = path_to_uri(path);
+
+        match self.open_docs.get(path) {
+            Some((_, sent)) if *sent == text => {
+                debug!("LspClient::sync_document: document unchanged");
+            }
+            Some((version, _)) => {
+                let version = version + 1;
+                debug!(version, "LspClient::sync_document: sending didChange");
+                self.notify(
+                    "textDocument/didChange",
+                    json!({
+                        "textDocument": { "uri": uri, "version": version },
+                        "contentChanges": [{ "text": text }]
+                    }),
+                )
+                .await?;
+                self.open_docs.insert(path.to_path_buf(), (version, text.clone()));
+            }
+            None => {
+                debug!("LspClient::sync_document: sending didOpen");
+                self.notify(
+                    "textDocument/didOpen",
+                    json!({
+                        "textDocument": {
+                            "uri": uri,
+                            "languageId": language_id_for(path, &self.spec.language_id),
+                            "version": 1,
+                            "text": text
+                        }
+                    }),
+                )
+                .await?;
+                self.open_docs.insert(path.to_path_buf(), (1, text.clone()));
+            }
+        }
+
+        Ok(text)
+    }
+
+    /// Politely shut the server down, killing it if it doesn't comply
+    pub async fn shutdown(mut self) {
+        debug!(command = %self.spec.command, "LspClient::shutdown: called");
+        self.request_timeout = Duration::from_secs(5);
+        if self.request("shutdown", Value::Null).await.is_ok() {
+            let _ = self.notify("exit", Value::Null).await;
+        }
+        if tokio::time::timeout(Duration::from_secs(2), self.child.wait())
+            .await
+            .is_err()
+        {
+            debug!("LspClient::shutdown: server did not exit, killing");
+            let _ = self.child.kill().await;
+        }
+        self.reader.abort();
+    }
+}
+
+/// Per-execution language server session
+///
+/// The server is started on first use and reused until `shutdown()`.
+pub struct LspSession {
+    root: PathBuf,
+    spec: Option<LspServerSpec>,
+    client: Mutex<Option<LspClient>>,
+}
+
+/// Shared handle to an execution's LSP session
+pub type LspSessionRef = Arc<LspSession>;
+
+impl LspSession {
+    /// Create a session for a worktree, detecting the server from project files
+    pub fn new(root: PathBuf) -> Self {
+        debug!(?root, "LspSession::new: called");
+        let spec = LspServerSpec::detect(&root);
+        Self {
+            root,
+            spec,
+            client: Mutex::new(None),
+        }
+    }
+
+    /// Create a session with an explicit server
+    pub fn with_spec(root: PathBuf, spec: LspServerSpec) -> Self {
+        debug!(?root, command = %spec.command, "LspSession::with_spec: called");
+        Self {
+            root,
+            spec: Some(spec),
+            client: Mutex::new(None),
+        }
+    }
+
+    /// Worktree root the server is running in
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Whether a server is currently running
+    pub async fn is_running(&self) -> bool {
+        self.client.lock().await.is_some()
+    }
+
+    /// Run a document query: syncs `path`, then sends `method` with `params`
+    pub async fn document_request(&self, path: &Path, method: &str, params: Value) -> eyre::Result<Value> {
+        debug!(?path, %method, "LspSession::document_request: called");
+        let mut guard = self.client.lock().await;
+        if guard.is_none() {
+            let spec = self
+                .spec
+                .clone()
+                .ok_or_else(|| eyre::eyre!("No language server known for this project"))?;
+            *guard = Some(LspClient::start(&self.root, spec).await?);
+        }
+        let client = guard.as_mut().expect("client initialized above");
+        client.sync_document(path).await?;
+        client.request(method, params).await
+    }
+
+    /// Stop the language server if it was started
+    pub async fn shutdown(&self) {
+        debug!(root = ?self.root, "LspSession::shutdown: called");
+        if let Some(client) = self.client.lock().await.take() {
+            client.shutdown().await;
+        }
+    }
+}
+
+impl std::fmt::Debug for LspSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LspSession")
+            .field("root", &self.root)
+            .field("spec", &self.spec)
+            .finish()
+    }
+}
+
+/// A resolved position inside a document, ready to send to the server
+#[derive(Debug, Clone)]
+pub(crate) struct DocumentPosition {
+    /// Absolute path of the document
+    pub path: PathBuf,
+    /// 0-based line
+    pub line: u32,
+    /// 0-based UTF-16 column
+    pub character: u32,
+}
+
+impl DocumentPosition {
+    /// LSP `TextDocumentPositionParams` for this position
+    pub fn params(&self) -> Value {
+        json!({
+            "textDocument": { "uri": path_to_uri(&self.path) },
+            "position": { "line": self.line, "character": self.character }
+        })
+    }
+}
+
+/// Resolve tool input (`path`, 1-based `line`, optional `symbol` or `column`) to a position
+///
+/// When `symbol` is given the column is the first occurrence of it on the line,
+/// which is how an LLM naturally refers to an identifier.
+pub(crate) fn resolve_position(input: &Value, ctx: &ToolContext) -> Result<DocumentPosition, String> {
+    debug!(?input, "resolve_position: called");
+    let path = input["path"].as_str().ok_or("path is required")?;
+    let line = input["line"].as_u64().ok_or("line is required (1-based)")?;
+    if line == 0 {
+        return Err("line is 1-based".to_string());
+    }
+
+    let abs_path = ctx.validate_path(Path::new(path)).map_err(|e| e.to_string())?;
+    let text = std::fs::read_to_string(&abs_path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let line_text = text
+        .lines()
+        .nth((line - 1) as usize)
+        .ok_or_else(|| format!("{} has fewer than {} lines", path, line))?;
+
+    let char_col = if let Some(column) = input["column"].as_u64() {
+        column.saturating_sub(1) as usize
+    } else if let Some(symbol) = input["symbol"].as_str() {
+        let byte_idx = line_text
+            .find(symbol)
+            .ok_or_else(|| format!("Symbol '{}' not found on line {} of {}", symbol, line, path))?;
+        line_text[..byte_idx].chars().count()
+    } else {
+        line_text.chars().take_while(|c| c.is_whitespace()).count()
+    };
+
+    Ok(DocumentPosition {
+        path: abs_path,
+        line: (line - 1) as u32,
+        character: utf16_column(line_text, char_col),
+    })
+}
+
+/// Normalize a definition/references result (Location, Location[], LocationLink[]) to (path, line, character)
+pub(crate) fn collect_locations(result: &Value) -> Vec<(PathBuf, u32, u32)> {
+    let items: Vec<&Value> = match result {
+        Value::Array(items) => items.iter().collect(),
+        Value::Object(_) => vec![result],
+        _ => vec![],
+    };
+
+    items
+        .into_iter()
+        .filter_map(|item| {
+            // LocationLink uses targetUri/targetSelectionRange, Location uses uri/range
+            let uri = item.get("uri").or_else(|| item.get("targetUri"))?.as_str()?;
+            let range = item.get("range").or_else(|| item.get("targetSelectionRange"))?;
+            let line = range["start"]["line"].as_u64()? as u32;
+            let character = range["start"]["character"].as_u64()? as u32;
+            Some((uri_to_path(uri)?, line, character))
+        })
+        .collect()
+}
+
+/// Render locations as `path:line:col: source line`, relative to the worktree
+pub(crate) fn format_locations(locations: &[(PathBuf, u32, u32)], root: &Path, max_results: usize) -> String {
+    let mut file_cache: HashMap<&PathBuf, Vec<String>> = HashMap::new();
+    let mut lines = Vec::new();
+
+    for (path, line, character) in locations.iter().take(max_results) {
+        let source = file_cache
+            .entry(path)
+            .or_insert_with(|| {
+                std::fs::read_to_string(path)
+                    .map(|t| t.lines().map(str::to_string).collect())
+                    .unwrap_or_default()
+            })
+            .get(*line as usize)
+            .map(|l| l.trim().to_string())
+            .unwrap_or_default();
+        let display = path.strip_prefix(root).unwrap_or(path).display();
+        lines.push(format!("{}:{}:{}: {}", display, line + 1, character + 1, source));
+    }
+
+    if locations.len() > max_results {
+        lines.push(format!("... ({} more not shown)", locations.len() - max_results));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_detect_rust_project() {
+        let temp = tempdir().unwrap();
+        std::fs::write(temp.path().join("Cargo.toml"), "[package]").unwrap();
+
+        let spec = LspServerSpec::detect(temp.path()).unwrap();
+        assert_eq!(spec.command, "rust-analyzer");
+        assert_eq!(spec.language_id, "rust");
+    }
+
+    #[test]
+    fn test_detect_unknown_project() {
+        let temp = tempdir().unwrap();
+        assert!(LspServerSpec::detect(temp.path()).is_none());
+    }
+
+    #[test]
+    fn test_uri_roundtrip() {
+        let path = PathBuf::from("/tmp/work tree/src/main.rs");
+        let uri = path_to_uri(&path);
+        assert_eq!(uri, "file:///tmp/work%20tree/src/main.rs");
+        assert_eq!(uri_to_path(&uri).unwrap(), path);
+        assert!(uri_to_path("https://example.com").is_none());
+    }
+
+    #[test]
+    fn test_utf16_column() {
+        assert_eq!(utf16_column("let x = 1;", 4), 4);
+        // '😀' is two UTF-16 code units
+        assert_eq!(utf16_column("😀 foo", 2), 3);
+    }
+
+    #[tokio::test]
+    async fn test_message_framing_roundtrip() {
+        let msg = json!({ "jsonrpc": "2.0", "id": 1, "result": { "ok": true } });
+        let mut bytes = encode_message(&msg);
+        bytes.extend(encode_message(&json!({ "jsonrpc": "2.0", "method": "x" })));
+
+        let mut reader = BufReader::new(&bytes[..]);
+        assert_eq!(read_message(&mut reader).await.unwrap().unwrap(), msg);
+        assert_eq!(read_message(&mut reader).await.unwrap().unwrap()["method"], "x");
+        assert!(read_message(&mut reader).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_session_without_server_errors() {
+        let temp = tempdir().unwrap();
+        let file = temp.path().join("notes.txt");
+        std::fs::write(&file, "hello").unwrap();
+
+        let session = LspSession::new(temp.path().to_path_buf());
+        let result = session
+            .document_request(&file, "textDocument/documentSymbol", json!({}))
+            .await;
+        assert!(result.is_err());
+        assert!(!session.is_running().await);
+    }
+
+    #[test]
+    fn test_resolve_position_by_symbol() {
+        let temp = tempdir().unwrap();
+        std::fs::write(
+            temp.path().join("lib.rs"),
+            "fn main() {\n    let value = compute();\n}\n",
+        )
+        .unwrap();
+        let ctx = ToolContext::new(temp.path().to_path_buf(), "test".to_string());
+
+        let pos = resolve_position(&json!({"path": "lib.rs", "line": 2, "symbol": "compute"}), &ctx).unwrap();
+        assert_eq!(pos.line, 1);
+        assert_eq!(pos.character, 16);
+
+        let err = resolve_position(&json!({"path": "lib.rs", "line": 2, "symbol": "missing"}), &ctx).unwrap_err();
+        assert!(err.contains("not found"));
+    }
+
+    #[test]
+    fn test_collect_and_format_locations() {
+        let temp = tempdir().unwrap();
+        let file = temp.path().join("a.rs");
+        std::fs::write(&file, "struct Foo;\nimpl Foo {}\n").unwrap();
+        let uri = path_to_uri(&file);
+
+        let result = json!([
+            { "uri": uri, "range": { "start": { "line": 0, "character": 7 }, "end": { "line": 0, "character": 10 } } },
+            { "targetUri": uri, "targetSelectionRange": { "start": { "line": 1, "character": 5 }, "end": { "line": 1, "character": 8 } } }
+        ]);
+        let locations = collect_locations(&result);
+        assert_eq!(locations.len(), 2);
+
+        let output = format_locations(&locations, temp.path(), 10);
+        assert!(output.contains("a.rs:1:8: struct Foo;"));
+        assert!(output.contains("a.rs:2:6: impl Foo {}"));
+
+        let truncated = format_locations(&locations, temp.path(), 1);
+        assert!(truncated.contains("1 more not shown"));
+    }
+}
@@ -3,15 +3,34 @@
 //! Tools provide file system access, command execution, and coordination
 //! capabilities to Ralph loops. Each loop gets a `ToolContext` scoped to
 //! its git worktree - tools cannot escape the worktree sandbox.
+//!
+//! Tools from outside the built-in set come from library users
+//! ([`ToolExecutor::register_tool`], `DaemonBuilder::with_tool`) or, with the
+//! `wasm` feature, from WebAssembly modules found under `tools.wasm.paths`.
+//! With `tools.worker` the file and command built-ins run in a per-execution
+//! worker process (see [`ToolWorker`]). Outbound requests are checked against
+//! the loop type's [`NetworkPolicy`].
 
 mod context;
 mod error;
 mod executor;
+mod limits;
+mod lsp;
+mod network;
 mod traits;
+#[cfg(feature = "wasm")]
+pub(crate) mod wasm;
+mod worker;
 
 pub mod builtin;
 
 pub use context::{ExploreConfig, ExploreSpawner, ExploreSpawnerRef, Thoroughness, ToolContext};
 pub use error::ToolError;
 pub use executor::{ToolExecutor, ToolProfile};
+pub use limits::{ResourceKind, ResourceLimits, ResourceViolation, combined_output, run_shell};
+pub use lsp::{LspClient, LspServerSpec, LspSession, LspSessionRef};
+pub use network::{NetworkAccess, NetworkGuard, NetworkPolicy, NetworkProxy};
 pub use traits::{Tool, ToolResult};
+#[cfg(feature = "wasm")]
+pub use wasm::{WasmTool, load_wasm_tools};
+pub use worker::{ToolWorker, limit_memory, serve_worker};
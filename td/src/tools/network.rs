@@ -0,0 +1,366 @@
+//! Outbound network policy for tools
+//!
+//! A loop type's `network` section lists the hosts its tools may reach
+//! (`allow`) and the ones they may not (`deny`, which wins). Patterns are a
+//! host name, `*.example.com` for its subdomains, or `*` for everything; an
+//! empty `allow` list allows every host not denied. `fetch` and `search` check
+//! the policy before each request. Commands spawned by tools cannot be checked
+//! directly, so while a policy restricts anything each execution runs a
+//! [`NetworkProxy`] on localhost and commands get it as `HTTP_PROXY` /
+//! `HTTPS_PROXY`; programs that ignore the proxy variables are not covered.
+//! Every request, allowed or blocked, is recorded in the execution's
+//! [`NetworkGuard`] and emitted as a `NetworkRequest` event.
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, warn};
+
+/// Largest request head the proxy reads
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+
+/// Tool name proxied requests are recorded under
+const PROXY_TOOL: &str = "command";
+
+/// Hosts tools may and may not reach (`network` in loop YAML)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct NetworkPolicy {
+    /// Hosts allowed (empty: every host not denied)
+    pub allow: Vec<String>,
+    /// Hosts never allowed
+    pub deny: Vec<String>,
+}
+
+impl NetworkPolicy {
+    /// Whether the policy blocks anything
+    pub fn is_restricted(&self) -> bool {
+        !self.allow.is_empty() || !self.deny.is_empty()
+    }
+
+    /// Whether `host` may be reached
+    pub fn allows(&self, host: &str) -> bool {
+        let host = host.trim_end_matches('.').to_lowercase();
+        if self.deny.iter().any(|pattern| host_matches(pattern, &host)) {
+            return false;
+        }
+        self.allow.is_empty() || self.allow.iter().any(|pattern| host_matches(pattern, &host))
+    }
+}
+
+/// Whether `host` (lowercase) matches `pattern`: a host, `*.domain` or `*`
+fn host_matches(pattern: &str, host: &str) -> bool {
+    let pattern = pattern.trim().to_lowercase();
+    match pattern.strip_prefix("*.") {
+        Some(domain) => host.ends_with(&format!(".{}", domain)),
+        None => pattern == "*" || pattern == host,
+    }
+}
+
+/// One outbound request a tool made or tried to make
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkAccess {
+    /// Tool that made it (`command` for requests through the proxy)
+    pub tool: String,
+    /// Host the request was for
+    pub host: String,
+    /// URL, or host:port for tunnelled connections
+    pub target: String,
+    /// Whether the policy let it through
+    pub allowed: bool,
+}
+
+/// An execution's policy and the requests checked against it
+#[derive(Debug, Clone, Default)]
+pub struct NetworkGuard {
+    policy: Arc<NetworkPolicy>,
+    log: Arc<Mutex<Vec<NetworkAccess>>>,
+}
+
+impl NetworkGuard {
+    /// Check requests against `policy`
+    pub fn new(policy: NetworkPolicy) -> Self {
+        debug!(?policy, "NetworkGuard::new: called");
+        Self {
+            policy: Arc::new(policy),
+            log: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// The policy requests are checked against
+    pub fn policy(&self) -> &NetworkPolicy {
+        &self.policy
+    }
+
+    /// Check and record a request by `tool` to `host`; true if it may go ahead
+    pub fn check(&self, tool: &str, host: &str, target: &str) -> bool {
+        let allowed = self.policy.allows(host);
+        debug!(%tool, %host, allowed, "NetworkGuard::check: called");
+        self.record(vec![NetworkAccess {
+            tool: tool.to_string(),
+            host: host.to_string(),
+            target: target.to_string(),
+            allowed,
+        }]);
+        allowed
+    }
+
+    /// Check a URL; the error explains why it may not be fetched
+    pub fn check_url(&self, tool: &str, url: &str) -> Result<(), String> {
+        let host = match reqwest::Url::parse(url).ok().and_then(|u| u.host_str().map(String::from)) {
+            Some(host) => host,
+            None => return Err(format!("Invalid URL: {}", url)),
+        };
+        if self.check(tool, &host, url) {
+            Ok(())
+        } else {
+            Err(format!("Blocked by network policy: {} is not an allowed host", host))
+        }
+    }
+
+    /// Add requests checked elsewhere (by a tool worker)
+    pub fn record(&self, accesses: Vec<NetworkAccess>) {
+        if let Ok(mut log) = self.log.lock() {
+            log.extend(accesses);
+        }
+    }
+
+    /// Requests recorded since the last call
+    pub fn take_log(&self) -> Vec<NetworkAccess> {
+        self.log.lock().map(|mut log| std::mem::take(&mut *log)).unwrap_or_default()
+    }
+}
+
+/// A localhost HTTP proxy that applies a [`NetworkGuard`] to spawned commands
+///
+/// Handles `CONNECT host:port` (HTTPS and other tunnels) and absolute-form
+/// plain HTTP requests. Stops when dropped.
+pub struct NetworkProxy {
+    addr: SocketAddr,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl NetworkProxy {
+    /// Start a proxy on an ephemeral localhost port
+    pub async fn start(guard: NetworkGuard) -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        debug!(%addr, "NetworkProxy::start: listening");
+        let task = tokio::spawn(async move {
+            loop {
+                let stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        warn!(error = %e, "Network proxy failed to accept a connection");
+                        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                        continue;
+                    }
+                };
+                let guard = guard.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = serve_connection(stream, &guard).await {
+                        debug!(error = %e, "NetworkProxy: connection ended with error");
+                    }
+                });
+            }
+        });
+        Ok(Self { addr, task })
+    }
+
+    /// Address the proxy listens on
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Environment that points commands at the proxy
+    pub fn env(&self) -> Vec<(String, String)> {
+        let url = format!("http://{}", self.addr);
+        ["HTTP_PROXY", "HTTPS_PROXY", "ALL_PROXY", "http_proxy", "https_proxy", "all_proxy"]
+            .into_iter()
+            .map(|name| (name.to_string(), url.clone()))
+            .chain([("NO_PROXY".to_string(), String::new()), ("no_proxy".to_string(), String::new())])
+            .collect()
+    }
+}
+
+impl Drop for NetworkProxy {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Proxy one client connection
+async fn serve_connection(mut client: TcpStream, guard: &NetworkGuard) -> std::io::Result<()> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 4096];
+    let end = loop {
+        let n = client.read(&mut buf).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        head.extend_from_slice(&buf[..n]);
+        if let Some(pos) = head.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        if head.len() > MAX_HEAD_BYTES {
+            return respond(&mut client, "431 Request Header Fields Too Large", "").await;
+        }
+    };
+
+    let text = String::from_utf8_lossy(&head[..end]).to_string();
+    let mut parts = text.lines().next().unwrap_or_default().split_whitespace();
+    let (method, target) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+
+    let (host, port) = if method.eq_ignore_ascii_case("CONNECT") {
+        match target.rsplit_once(':').map(|(h, p)| (h, p.parse::<u16>())) {
+            Some((host, Ok(port))) => (host.trim_matches(['[', ']']).to_string(), port),
+            _ => return respond(&mut client, "400 Bad Request", "Expected CONNECT host:port").await,
+        }
+    } else {
+        match reqwest::Url::parse(target) {
+            Ok(url) if url.host_str().is_some() => (
+                url.host_str().unwrap_or_default().to_string(),
+                url.port_or_known_default().unwrap_or(80),
+            ),
+            _ => return respond(&mut client, "400 Bad Request", "Expected an absolute URL").await,
+        }
+    };
+
+    if !guard.check(PROXY_TOOL, &host, target) {
+        warn!(%host, "Blocked outbound connection by network policy");
+        let body = format!("Blocked by network policy: {} is not an allowed host\n", host);
+        return respond(&mut client, "403 Forbidden", &body).await;
+    }
+
+    let mut upstream = match TcpStream::connect((host.as_str(), port)).await {
+        Ok(upstream) => upstream,
+        Err(e) => return respond(&mut client, "502 Bad Gateway", &format!("{}\n", e)).await,
+    };
+    if method.eq_ignore_ascii_case("CONNECT") {
+        client.write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n").await?;
+        upstream.write_all(&head[end..]).await?;
+    } else {
+        // One request per connection, so a reused connection cannot reach another host unchecked
+        upstream.write_all(close_connection(&text).as_bytes()).await?;
+        upstream.write_all(&head[end..]).await?;
+    }
+    tokio::io::copy_bidirectional(&mut client, &mut upstream).await?;
+    Ok(())
+}
+
+/// `head` with any Connection / Proxy-Connection headers replaced by `Connection: close`
+fn close_connection(head: &str) -> String {
+    let mut lines: Vec<&str> = head
+        .trim_end_matches("\r\n")
+        .split("\r\n")
+        .filter(|line| {
+            let name = line.split(':').next().unwrap_or_default().trim().to_lowercase();
+            name != "connection" && name != "proxy-connection"
+        })
+        .collect();
+    lines.push("Connection: close");
+    format!("{}\r\n\r\n", lines.join("\r\n"))
+}
+
+async fn respond(client: &mut TcpStream, status: &str, body: &str) -> std::io::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    client.write_all(response.as_bytes()).await?;
+    client.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(allow: &[&str], deny: &[&str]) -> NetworkPolicy {
+        NetworkPolicy {
+            allow: allow.iter().map(|s| s.to_string()).collect(),
+            deny: deny.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_policy_allows() {
+        let open = NetworkPolicy::default();
+        assert!(!open.is_restricted());
+        assert!(open.allows("example.com"));
+
+        let restricted = policy(&["crates.io", "*.github.com"], &["gist.github.com"]);
+        assert!(restricted.allows("crates.io"));
+        assert!(restricted.allows("API.GitHub.com."));
+        assert!(!restricted.allows("github.com"));
+        assert!(!restricted.allows("gist.github.com"));
+        assert!(!restricted.allows("evil.io"));
+
+        let deny_only = policy(&[], &["*.internal", "metadata.google.internal"]);
+        assert!(deny_only.allows("example.com"));
+        assert!(!deny_only.allows("db.internal"));
+    }
+
+    #[test]
+    fn test_guard_records_checks() {
+        let guard = NetworkGuard::new(policy(&["docs.rs"], &[]));
+        assert!(guard.check_url("fetch", "https://docs.rs/tokio").is_ok());
+        let err = guard.check_url("fetch", "https://example.com/").unwrap_err();
+        assert!(err.contains("example.com is not an allowed host"));
+        assert!(guard.check_url("fetch", "not a url").is_err());
+
+        let log = guard.take_log();
+        assert_eq!(log.len(), 2);
+        assert!(log[0].allowed && !log[1].allowed);
+        assert_eq!(log[1].target, "https://example.com/");
+        assert!(guard.take_log().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_proxy_blocks_denied_hosts() {
+        let guard = NetworkGuard::new(policy(&["127.0.0.1"], &[]));
+        let proxy = NetworkProxy::start(guard.clone()).await.unwrap();
+
+        // A local upstream the policy allows
+        let upstream = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_addr = upstream.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = upstream.accept().await.unwrap();
+            stream.write_all(b"pong").await.unwrap();
+        });
+
+        let mut client = TcpStream::connect(proxy.addr()).await.unwrap();
+        let connect = format!("CONNECT {} HTTP/1.1\r\nHost: {}\r\n\r\n", upstream_addr, upstream_addr);
+        client.write_all(connect.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.ends_with("pong"));
+
+        let mut client = TcpStream::connect(proxy.addr()).await.unwrap();
+        client
+            .write_all(b"GET http://example.com/ HTTP/1.1\r\nHost: example.com\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 403"));
+
+        let log = guard.take_log();
+        assert_eq!(log.len(), 2);
+        assert!(log[0].allowed);
+        assert_eq!(log[1].host, "example.com");
+        assert!(!log[1].allowed);
+        assert!(proxy.env().contains(&("HTTPS_PROXY".to_string(), format!("http://{}", proxy.addr()))));
+    }
+
+    #[test]
+    fn test_close_connection() {
+        let head = "GET http://a/ HTTP/1.1\r\nHost: a\r\nProxy-Connection: keep-alive\r\n\r\n";
+        assert_eq!(close_connection(head), "GET http://a/ HTTP/1.1\r\nHost: a\r\nConnection: close\r\n\r\n");
+    }
+}
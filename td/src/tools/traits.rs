@@ -5,6 +5,8 @@ use serde_json::Value;
 use tracing::debug;
 
 use super::context::ToolContext;
+use super::{ResourceViolation, ToolError};
+use crate::llm::ContentBlock;
 
 /// A tool that can be called by the LLM
 #[async_trait]
@@ -22,11 +24,35 @@ pub trait Tool: Send + Sync {
     async fn execute(&self, input: Value, ctx: &ToolContext) -> ToolResult;
 }
 
+/// Shared tools, so one instance can serve every loop (see `TaskManager::with_tools`)
+#[async_trait]
+impl Tool for std::sync::Arc<dyn Tool> {
+    fn name(&self) -> &'static str {
+        (**self).name()
+    }
+
+    fn description(&self) -> &'static str {
+        (**self).description()
+    }
+
+    fn input_schema(&self) -> Value {
+        (**self).input_schema()
+    }
+
+    async fn execute(&self, input: Value, ctx: &ToolContext) -> ToolResult {
+        (**self).execute(input, ctx).await
+    }
+}
+
 /// Result of a tool execution
 #[derive(Debug, Clone)]
 pub struct ToolResult {
     pub content: String,
     pub is_error: bool,
+    /// Set when a spawned command was stopped by a resource limit
+    pub violation: Option<ResourceViolation>,
+    /// Images to show the model after the tool results
+    pub images: Vec<ContentBlock>,
 }
 
 impl ToolResult {
@@ -36,6 +62,17 @@ impl ToolResult {
         Self {
             content: content.into(),
             is_error: false,
+            violation: None,
+            images: Vec::new(),
+        }
+    }
+
+    /// Create a successful result that also shows the model an image
+    pub fn with_image(content: impl Into<String>, image: ContentBlock) -> Self {
+        debug!("ToolResult::with_image: called");
+        Self {
+            images: vec![image],
+            ..Self::success(content)
         }
     }
 
@@ -45,6 +82,27 @@ impl ToolResult {
         Self {
             content: content.into(),
             is_error: true,
+            violation: None,
+            images: Vec::new(),
+        }
+    }
+}
+
+impl From<ToolError> for ToolResult {
+    fn from(err: ToolError) -> Self {
+        debug!(%err, "ToolResult::from: called");
+        match err {
+            ToolError::ResourceLimitExceeded { violation, output } => {
+                let message = format!("Resource limit exceeded: {}", violation);
+                let content = if output.is_empty() { message } else { format!("{}\n{}", message, output) };
+                Self {
+                    content,
+                    is_error: true,
+                    violation: Some(violation),
+                    images: Vec::new(),
+                }
+            }
+            other => Self::error(other.to_string()),
         }
     }
 }
@@ -66,4 +124,23 @@ mod tests {
         assert!(result.is_error);
         assert_eq!(result.content, "File not found");
     }
+
+    #[test]
+    fn test_tool_result_from_resource_limit() {
+        let violation = ResourceViolation::new(crate::tools::ResourceKind::Cpu, 30);
+        let result = ToolResult::from(ToolError::ResourceLimitExceeded {
+            violation: violation.clone(),
+            output: "compiling...".to_string(),
+        });
+        assert!(result.is_error);
+        assert_eq!(result.violation, Some(violation));
+        assert_eq!(
+            result.content,
+            "Resource limit exceeded: cpu limit of 30s\ncompiling..."
+        );
+
+        let result = ToolResult::from(ToolError::CommandTimeout { timeout_ms: 100 });
+        assert!(result.violation.is_none());
+        assert_eq!(result.content, "Command timed out after 100ms");
+    }
 }
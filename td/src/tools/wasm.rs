@@ -0,0 +1,341 @@
+//! Sandboxed WebAssembly tool plugins
+//!
+//! A plugin is a core WebAssembly module that imports nothing, so it gets no
+//! filesystem, network, clock or environment, only the call's input. Each call
+//! runs in a fresh instance, with a fuel budget capping the instructions it
+//! executes and a cap on the memory it can grow. The module exports:
+//!
+//! - `memory`: its linear memory
+//! - `td_alloc(len: i32) -> i32`: reserve `len` bytes for the input
+//! - `td_describe() -> i64`: JSON `{"name", "description", "input_schema"}`
+//! - `td_call(ptr: i32, len: i32) -> i64`: handle the JSON input at `ptr`
+//!
+//! The `i64` results point at the output as `ptr << 32 | len`. `td_call`
+//! returns `{"content": "...", "is_error": false}`; output that is not such an
+//! object is passed to the model as plain text.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use eyre::{Context, Result, bail, eyre};
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::{debug, info};
+use wasmi::core::TrapCode;
+use wasmi::{Engine, Instance, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+use crate::config::WasmToolsConfig;
+use crate::tools::{Tool, ToolContext, ToolResult};
+
+/// Longest name the LLM APIs accept for a tool
+const MAX_NAME_LEN: usize = 64;
+
+/// What `td_describe` returns
+#[derive(Debug, Deserialize)]
+struct Description {
+    name: String,
+    description: String,
+    #[serde(default = "empty_schema")]
+    input_schema: Value,
+}
+
+fn empty_schema() -> Value {
+    serde_json::json!({ "type": "object" })
+}
+
+/// What `td_call` returns, when it returns JSON
+#[derive(Debug, Deserialize)]
+struct CallOutput {
+    content: String,
+    #[serde(default)]
+    is_error: bool,
+}
+
+/// A compiled module and the limits its calls run under
+///
+/// Shared with WebAssembly policies (see [`crate::policy`]).
+pub(crate) struct Plugin {
+    pub(crate) path: PathBuf,
+    engine: Engine,
+    module: Module,
+    pub(crate) fuel: u64,
+    max_memory: usize,
+}
+
+impl Plugin {
+    /// Compile the module at `path`, refusing one that imports anything
+    pub(crate) fn compile(path: &Path, fuel: u64, max_memory_mb: u32) -> Result<Self> {
+        debug!(?path, "Plugin::compile: called");
+        let bytes = std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+
+        let mut engine_config = wasmi::Config::default();
+        engine_config.consume_fuel(true);
+        let engine = Engine::new(&engine_config);
+        let module = Module::new(&engine, &bytes).map_err(|e| eyre!("{}: invalid module: {}", path.display(), e))?;
+
+        let imports: Vec<String> = module
+            .imports()
+            .map(|i| format!("{}::{}", i.module(), i.name()))
+            .collect();
+        if !imports.is_empty() {
+            bail!(
+                "{}: plugins may not import anything, found {}",
+                path.display(),
+                imports.join(", ")
+            );
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            engine,
+            module,
+            fuel,
+            max_memory: max_memory_mb as usize * 1024 * 1024,
+        })
+    }
+
+    /// Fresh instance with a full fuel tank
+    fn instantiate(&self) -> Result<(Store<StoreLimits>, Instance, Memory), wasmi::Error> {
+        let limits = StoreLimitsBuilder::new().memory_size(self.max_memory).build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store.set_fuel(self.fuel)?;
+        let instance = Linker::new(&self.engine)
+            .instantiate(&mut store, &self.module)?
+            .start(&mut store)?;
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| wasmi::Error::new("module does not export `memory`"))?;
+        Ok((store, instance, memory))
+    }
+
+    fn describe(&self) -> Result<Vec<u8>, wasmi::Error> {
+        let (mut store, instance, memory) = self.instantiate()?;
+        let packed = instance
+            .get_typed_func::<(), i64>(&store, "td_describe")?
+            .call(&mut store, ())?;
+        read_packed(&store, memory, packed)
+    }
+
+    /// Copy `input` into a fresh instance and call `export(ptr, len)` on it
+    pub(crate) fn call(&self, export: &str, input: &[u8]) -> Result<Vec<u8>, wasmi::Error> {
+        let (mut store, instance, memory) = self.instantiate()?;
+        let len = i32::try_from(input.len()).map_err(|_| wasmi::Error::new("input too large"))?;
+        let ptr = instance
+            .get_typed_func::<i32, i32>(&store, "td_alloc")?
+            .call(&mut store, len)?;
+        memory
+            .write(&mut store, ptr as u32 as usize, input)
+            .map_err(|e| wasmi::Error::new(format!("writing input: {}", e)))?;
+        let packed = instance
+            .get_typed_func::<(i32, i32), i64>(&store, export)?
+            .call(&mut store, (ptr, len))?;
+        read_packed(&store, memory, packed)
+    }
+}
+
+/// Whether a call stopped because it used up its fuel
+pub(crate) fn out_of_fuel(e: &wasmi::Error) -> bool {
+    e.as_trap_code() == Some(TrapCode::OutOfFuel)
+}
+
+/// Copy out the bytes an `i64` result points at
+fn read_packed(store: &Store<StoreLimits>, memory: Memory, packed: i64) -> Result<Vec<u8>, wasmi::Error> {
+    let ptr = (packed as u64 >> 32) as usize;
+    let len = (packed as u64 & 0xffff_ffff) as usize;
+    memory
+        .data(store)
+        .get(ptr..ptr + len)
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| wasmi::Error::new(format!("output {}+{} is outside memory", ptr, len)))
+}
+
+/// A tool backed by a WebAssembly module
+pub struct WasmTool {
+    name: &'static str,
+    description: &'static str,
+    input_schema: Value,
+    plugin: Arc<Plugin>,
+}
+
+impl WasmTool {
+    /// Compile the module at `path` and ask it to describe itself
+    ///
+    /// Name and description are leaked to satisfy `Tool`'s `&'static str`;
+    /// plugins are loaded once per daemon.
+    pub fn load(path: &Path, config: &WasmToolsConfig) -> Result<Self> {
+        debug!(?path, "WasmTool::load: called");
+        let plugin = Plugin::compile(path, config.fuel, config.max_memory_mb)?;
+        let described = plugin
+            .describe()
+            .map_err(|e| eyre!("{}: td_describe failed: {}", path.display(), e))?;
+        let description: Description = serde_json::from_slice(&described)
+            .with_context(|| format!("{}: td_describe did not return a description", path.display()))?;
+
+        let name = &description.name;
+        if name.is_empty()
+            || name.len() > MAX_NAME_LEN
+            || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            bail!("{}: invalid tool name {:?}", path.display(), name);
+        }
+
+        debug!(%name, "WasmTool::load: loaded");
+        Ok(Self {
+            name: Box::leak(description.name.into_boxed_str()),
+            description: Box::leak(description.description.into_boxed_str()),
+            input_schema: description.input_schema,
+            plugin: Arc::new(plugin),
+        })
+    }
+}
+
+#[async_trait]
+impl Tool for WasmTool {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+
+    fn input_schema(&self) -> Value {
+        self.input_schema.clone()
+    }
+
+    async fn execute(&self, input: Value, _ctx: &ToolContext) -> ToolResult {
+        debug!(tool = self.name, path = ?self.plugin.path, "WasmTool::execute: called");
+        let plugin = self.plugin.clone();
+        let input = input.to_string().into_bytes();
+        let output = match tokio::task::spawn_blocking(move || plugin.call("td_call", &input)).await {
+            Ok(output) => output,
+            Err(e) => return ToolResult::error(format!("Plugin task failed: {}", e)),
+        };
+
+        match output {
+            Ok(bytes) => match serde_json::from_slice::<CallOutput>(&bytes) {
+                Ok(CallOutput { content, is_error: false }) => ToolResult::success(content),
+                Ok(CallOutput { content, is_error: true }) => ToolResult::error(content),
+                Err(_) => ToolResult::success(String::from_utf8_lossy(&bytes)),
+            },
+            Err(e) if out_of_fuel(&e) => {
+                debug!(tool = self.name, "WasmTool::execute: out of fuel");
+                ToolResult::error(format!("{} ran out of fuel ({} instructions)", self.name, self.plugin.fuel))
+            }
+            Err(e) => ToolResult::error(format!("{} failed: {}", self.name, e)),
+        }
+    }
+}
+
+/// Load every module `config` finds, failing on the first one that is broken
+pub fn load_wasm_tools(config: &WasmToolsConfig) -> Result<Vec<WasmTool>> {
+    debug!("load_wasm_tools: called");
+    let mut tools = Vec::new();
+    for path in config.module_files() {
+        let tool = WasmTool::load(&path, config)?;
+        info!(tool = tool.name, path = %path.display(), "Loaded WASM tool");
+        tools.push(tool);
+    }
+    Ok(tools)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Returns the input as its output
+    const ECHO: &str = "(i64.or (i64.shl (i64.extend_i32_u (local.get 0)) (i64.const 32)) (i64.extend_i32_u (local.get 1)))";
+
+    /// Never returns
+    const SPIN: &str = "(loop $l (br $l)) (unreachable)";
+
+    fn module(dir: &TempDir, file: &str, describe: &str, call_body: &str) -> PathBuf {
+        let data: String = describe.bytes().map(|b| format!("\\{:02x}", b)).collect();
+        let wat = format!(
+            r#"(module
+  (memory (export "memory") 1)
+  (global $heap (mut i32) (i32.const 1024))
+  (data (i32.const 0) "{data}")
+  (func (export "td_alloc") (param i32) (result i32)
+    (global.get $heap)
+    (global.set $heap (i32.add (global.get $heap) (local.get 0))))
+  (func (export "td_describe") (result i64) (i64.const {len}))
+  (func (export "td_call") (param i32 i32) (result i64) {call_body}))"#,
+            len = describe.len(),
+        );
+        let path = dir.path().join(file);
+        std::fs::write(&path, wat::parse_str(&wat).unwrap()).unwrap();
+        path
+    }
+
+    fn ctx(dir: &TempDir) -> ToolContext {
+        ToolContext::new(dir.path().to_path_buf(), "test".to_string())
+    }
+
+    #[tokio::test]
+    async fn test_load_and_call() {
+        let dir = TempDir::new().unwrap();
+        let describe = r#"{"name":"echo","description":"Echo the input","input_schema":{"type":"object"}}"#;
+        let path = module(&dir, "echo.wasm", describe, ECHO);
+        let config = WasmToolsConfig {
+            paths: vec![dir.path().display().to_string()],
+            ..Default::default()
+        };
+
+        let tools = load_wasm_tools(&config).unwrap();
+        assert_eq!(tools.len(), 1);
+        let tool = &tools[0];
+        assert_eq!(tool.name(), "echo");
+        assert_eq!(tool.description(), "Echo the input");
+        assert_eq!(tool.plugin.path, path);
+
+        let result = tool
+            .execute(serde_json::json!({"content": "hello", "is_error": false}), &ctx(&dir))
+            .await;
+        assert!(!result.is_error);
+        assert_eq!(result.content, "hello");
+
+        let result = tool.execute(serde_json::json!({"content": "bad", "is_error": true}), &ctx(&dir)).await;
+        assert!(result.is_error);
+        assert_eq!(result.content, "bad");
+
+        // Not a CallOutput: passed through as text
+        let result = tool.execute(serde_json::json!([1, 2]), &ctx(&dir)).await;
+        assert!(!result.is_error);
+        assert_eq!(result.content, "[1,2]");
+    }
+
+    #[tokio::test]
+    async fn test_call_runs_out_of_fuel() {
+        let dir = TempDir::new().unwrap();
+        let path = module(&dir, "spin.wasm", r#"{"name":"spin","description":"Loops forever"}"#, SPIN);
+        let config = WasmToolsConfig {
+            fuel: 100_000,
+            ..Default::default()
+        };
+
+        let tool = WasmTool::load(&path, &config).unwrap();
+        let result = tool.execute(serde_json::json!({}), &ctx(&dir)).await;
+        assert!(result.is_error);
+        assert!(result.content.contains("ran out of fuel"), "{}", result.content);
+    }
+
+    #[test]
+    fn test_load_rejects_imports_and_bad_names() {
+        let dir = TempDir::new().unwrap();
+        let config = WasmToolsConfig::default();
+
+        let path = dir.path().join("imports.wasm");
+        let wat = r#"(module (import "wasi_snapshot_preview1" "fd_write" (func (param i32 i32 i32 i32) (result i32))))"#;
+        std::fs::write(&path, wat::parse_str(wat).unwrap()).unwrap();
+        let err = WasmTool::load(&path, &config).err().unwrap().to_string();
+        assert!(err.contains("wasi_snapshot_preview1::fd_write"), "{}", err);
+
+        let path = module(&dir, "bad.wasm", r#"{"name":"no spaces","description":""}"#, ECHO);
+        let err = WasmTool::load(&path, &config).err().unwrap().to_string();
+        assert!(err.contains("invalid tool name"), "{}", err);
+    }
+}
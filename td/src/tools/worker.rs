@@ -0,0 +1,444 @@
+//! Tool worker processes
+//!
+//! With `tools.worker.enabled`, each execution's [`ToolExecutor`] runs its
+//! file and command tools in a `td tool-worker` child instead of the daemon.
+//! A tool that panics or runs the worker out of memory then fails only the
+//! call that was running; the next call starts a fresh worker.
+//!
+//! The protocol is one JSON request and one JSON response per call, each
+//! framed by a 4-byte big-endian length. Workers are stateless: read tracking
+//! travels with every request and comes back with the response, so a restart
+//! loses nothing. Tools that need daemon state (coordination, explore, LSP,
+//! todo lists) and custom tools always run in the daemon.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use eyre::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+
+use crate::config::ToolWorkerConfig;
+use crate::llm::{ContentBlock, ToolCall};
+use crate::progress::ExplorationCache;
+use crate::security::ScannerSpec;
+
+use super::{
+    NetworkAccess, NetworkGuard, NetworkPolicy, ResourceLimits, ResourceViolation, ToolContext, ToolExecutor, ToolProfile,
+    ToolResult,
+};
+
+/// Built-in tools that only need the worktree, so they can run in a worker
+pub(crate) const ISOLATED_TOOLS: &[&str] = &[
+    "read",
+    "write",
+    "edit",
+    "list",
+    "glob",
+    "grep",
+    "view_image",
+    "bash",
+    "tree",
+    "fetch",
+    "search",
+    "security_scan",
+    "triage_finding",
+    "outdated_deps",
+    "review_comment",
+    "finish_review",
+];
+
+/// Largest frame either side accepts
+const MAX_FRAME_BYTES: usize = 64 * 1024 * 1024;
+
+/// How long a worker that stopped answering gets to exit before it is killed
+const EXIT_GRACE: Duration = Duration::from_secs(1);
+
+/// The parts of a [`ToolContext`] a worker can use
+#[derive(Debug, Serialize, Deserialize)]
+struct WorkerContext {
+    worktree: PathBuf,
+    exec_id: String,
+    sandbox_enabled: bool,
+    max_tokens: u32,
+    resource_limits: ResourceLimits,
+    env: Vec<(String, String)>,
+    scanners: Vec<ScannerSpec>,
+    read_only_mounts: BTreeMap<String, PathBuf>,
+    network: NetworkPolicy,
+    read_files: Vec<PathBuf>,
+    /// Only shipped with `read` calls, the one tool that consults it
+    #[serde(default)]
+    exploration: Option<ExplorationCache>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct WorkerRequest {
+    call: ToolCall,
+    ctx: WorkerContext,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct WorkerResponse {
+    content: String,
+    is_error: bool,
+    violation: Option<ResourceViolation>,
+    images: Vec<ContentBlock>,
+    read_files: Vec<PathBuf>,
+    network: Vec<NetworkAccess>,
+    #[serde(default)]
+    exploration: Option<ExplorationCache>,
+}
+
+/// Read one frame; None on a clean end of stream
+async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Option<Vec<u8>>> {
+    let mut len = [0u8; 4];
+    match reader.read_exact(&mut len).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME_BYTES {
+        bail!("frame of {} bytes exceeds the {} byte limit", len, MAX_FRAME_BYTES);
+    }
+    let mut buf = vec![0; len];
+    reader.read_exact(&mut buf).await?;
+    Ok(Some(buf))
+}
+
+async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, payload: &[u8]) -> Result<()> {
+    if payload.len() > MAX_FRAME_BYTES {
+        bail!("frame of {} bytes exceeds the {} byte limit", payload.len(), MAX_FRAME_BYTES);
+    }
+    writer.write_all(&(payload.len() as u32).to_be_bytes()).await?;
+    writer.write_all(payload).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Cap this process's address space (the worker applies `--memory-mb` to itself)
+pub fn limit_memory(mb: u64) -> Result<()> {
+    debug!(mb, "limit_memory: called");
+    let bytes = mb.saturating_mul(1024 * 1024);
+    nix::sys::resource::setrlimit(nix::sys::resource::Resource::RLIMIT_AS, bytes, bytes)
+        .context("Failed to set the worker memory limit")?;
+    Ok(())
+}
+
+/// Serve tool calls from `reader` until it closes (the `td tool-worker` side)
+pub async fn serve_worker<R, W>(profile: ToolProfile, mut reader: R, mut writer: W) -> Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    debug!(?profile, "serve_worker: called");
+    let executor = ToolExecutor::with_profile(profile);
+    while let Some(frame) = read_frame(&mut reader).await? {
+        let request: WorkerRequest = serde_json::from_slice(&frame).context("Invalid worker request")?;
+        let WorkerContext {
+            worktree,
+            exec_id,
+            sandbox_enabled,
+            max_tokens,
+            resource_limits,
+            env,
+            scanners,
+            read_only_mounts,
+            network,
+            read_files,
+            exploration,
+        } = request.ctx;
+        let mut ctx = ToolContext::with_max_tokens(worktree, exec_id, max_tokens)
+            .with_resource_limits(resource_limits)
+            .with_env(env)
+            .with_scanners(scanners)
+            .with_read_only_mounts(read_only_mounts)
+            .with_network(NetworkGuard::new(network));
+        ctx.sandbox_enabled = sandbox_enabled;
+        ctx.set_reads(read_files).await;
+        if let Some(exploration) = exploration {
+            ctx = ctx.with_exploration(Arc::new(Mutex::new(exploration)));
+        }
+
+        debug!(tool = %request.call.name, "serve_worker: executing");
+        let result = executor.execute(&request.call, &ctx).await;
+        let response = WorkerResponse {
+            content: result.content,
+            is_error: result.is_error,
+            violation: result.violation,
+            images: result.images,
+            read_files: ctx.reads().await,
+            network: ctx.network.take_log(),
+            exploration: match &ctx.exploration {
+                Some(exploration) => Some(exploration.lock().await.clone()),
+                None => None,
+            },
+        };
+        write_frame(&mut writer, &serde_json::to_vec(&response)?).await?;
+    }
+    debug!("serve_worker: input closed");
+    Ok(())
+}
+
+/// A running worker
+struct WorkerProcess {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: ChildStdout,
+    /// A request was sent and its response not yet read (the call was abandoned)
+    busy: bool,
+}
+
+impl WorkerProcess {
+    async fn round_trip(&mut self, request: &[u8]) -> Result<WorkerResponse> {
+        self.busy = true;
+        write_frame(&mut self.stdin, request).await?;
+        let frame = read_frame(&mut self.stdout)
+            .await?
+            .ok_or_else(|| eyre::eyre!("worker closed its output"))?;
+        self.busy = false;
+        Ok(serde_json::from_slice(&frame)?)
+    }
+
+    /// Reap the worker and describe how it ended
+    async fn reap(mut self) -> String {
+        let status = match tokio::time::timeout(EXIT_GRACE, self.child.wait()).await {
+            Ok(status) => status,
+            Err(_) => {
+                let _ = self.child.start_kill();
+                self.child.wait().await
+            }
+        };
+        match status {
+            Ok(status) => status.to_string(),
+            Err(e) => format!("unknown status: {}", e),
+        }
+    }
+}
+
+/// Runs an executor's isolated tools in a `td tool-worker` child process
+pub struct ToolWorker {
+    program: PathBuf,
+    args: Vec<String>,
+    process: Mutex<Option<WorkerProcess>>,
+    restarts: AtomicU32,
+}
+
+impl ToolWorker {
+    /// Worker for an executor with `profile`; the process starts on the first call
+    pub fn new(profile: ToolProfile, config: &ToolWorkerConfig) -> Result<Self> {
+        debug!(?profile, ?config, "ToolWorker::new: called");
+        let program = match &config.program {
+            Some(program) => program.clone(),
+            None => std::env::current_exe().context("Failed to locate the td executable for tool workers")?,
+        };
+        let mut args = vec!["tool-worker".to_string()];
+        if profile == ToolProfile::ReadOnly {
+            args.push("--read-only".to_string());
+        }
+        if let Some(mb) = config.memory_mb {
+            args.push("--memory-mb".to_string());
+            args.push(mb.to_string());
+        }
+        Ok(Self::with_command(program, args))
+    }
+
+    /// Worker started as `program args...`
+    pub(crate) fn with_command(program: PathBuf, args: Vec<String>) -> Self {
+        Self {
+            program,
+            args,
+            process: Mutex::new(None),
+            restarts: AtomicU32::new(0),
+        }
+    }
+
+    /// Times a worker died and was replaced
+    pub fn restarts(&self) -> u32 {
+        self.restarts.load(Ordering::Relaxed)
+    }
+
+    fn spawn(&self) -> Result<WorkerProcess> {
+        debug!(program = ?self.program, args = ?self.args, "ToolWorker::spawn: called");
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start tool worker {}", self.program.display()))?;
+        let stdin = child.stdin.take().expect("piped stdin");
+        let stdout = child.stdout.take().expect("piped stdout");
+        info!(pid = ?child.id(), "Started tool worker");
+        Ok(WorkerProcess {
+            child,
+            stdin,
+            stdout,
+            busy: false,
+        })
+    }
+
+    /// Run `call` in the worker, starting or replacing it as needed
+    pub async fn execute(&self, call: &ToolCall, ctx: &ToolContext) -> ToolResult {
+        debug!(tool = %call.name, exec_id = %ctx.exec_id, "ToolWorker::execute: called");
+        let request = WorkerRequest {
+            call: call.clone(),
+            ctx: WorkerContext {
+                worktree: ctx.worktree.clone(),
+                exec_id: ctx.exec_id.clone(),
+                sandbox_enabled: ctx.sandbox_enabled,
+                max_tokens: ctx.max_tokens,
+                resource_limits: ctx.resource_limits.clone(),
+                env: ctx.env.clone(),
+                scanners: ctx.scanners.clone(),
+                read_only_mounts: ctx.read_only_mounts.clone(),
+                network: ctx.network.policy().clone(),
+                read_files: ctx.reads().await,
+                exploration: match &ctx.exploration {
+                    Some(exploration) if call.name == "read" => Some(exploration.lock().await.clone()),
+                    _ => None,
+                },
+            },
+        };
+        let request = match serde_json::to_vec(&request) {
+            Ok(request) => request,
+            Err(e) => return ToolResult::error(format!("Failed to encode tool call: {}", e)),
+        };
+
+        let mut slot = self.process.lock().await;
+        // A call abandoned mid-exchange (watchdog timeout) leaves the stream out of step
+        if slot.as_ref().is_some_and(|p| p.busy) {
+            debug!("ToolWorker::execute: replacing worker left busy by an abandoned call");
+            *slot = None;
+        }
+        if slot.is_none() {
+            match self.spawn() {
+                Ok(process) => *slot = Some(process),
+                Err(e) => return ToolResult::error(format!("{:#}", e)),
+            }
+        }
+
+        let process = slot.as_mut().expect("worker just started");
+        match process.round_trip(&request).await {
+            Ok(response) => {
+                ctx.set_reads(response.read_files).await;
+                ctx.network.record(response.network);
+                if let (Some(exploration), Some(updated)) = (&ctx.exploration, response.exploration) {
+                    *exploration.lock().await = updated;
+                }
+                ToolResult {
+                    content: response.content,
+                    is_error: response.is_error,
+                    violation: response.violation,
+                    images: response.images,
+                }
+            }
+            Err(e) => {
+                let status = slot.take().expect("worker present").reap().await;
+                self.restarts.fetch_add(1, Ordering::Relaxed);
+                warn!(tool = %call.name, exec_id = %ctx.exec_id, error = %e, %status, "Tool worker crashed");
+                ToolResult::error(format!(
+                    "Tool worker crashed while running {} ({}); the next call starts a new worker",
+                    call.name, status
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn call(name: &str, input: serde_json::Value) -> ToolCall {
+        ToolCall {
+            id: "call_1".to_string(),
+            name: name.to_string(),
+            input,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_serve_worker_round_trip_carries_reads() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(temp.path().join("a.txt"), "hello\n").unwrap();
+        let ctx = ToolContext::new(temp.path().to_path_buf(), "exec-1".to_string());
+
+        let (parent, child) = tokio::io::duplex(64 * 1024);
+        let (child_read, child_write) = tokio::io::split(child);
+        let server = tokio::spawn(serve_worker(ToolProfile::Full, child_read, child_write));
+        let (mut parent_read, mut parent_write) = tokio::io::split(parent);
+
+        let request = WorkerRequest {
+            call: call("read", serde_json::json!({"path": "a.txt"})),
+            ctx: WorkerContext {
+                worktree: ctx.worktree.clone(),
+                exec_id: ctx.exec_id.clone(),
+                sandbox_enabled: true,
+                max_tokens: ctx.max_tokens,
+                resource_limits: ResourceLimits::default(),
+                env: Vec::new(),
+                scanners: Vec::new(),
+                read_only_mounts: BTreeMap::new(),
+                network: NetworkPolicy::default(),
+                read_files: Vec::new(),
+                exploration: None,
+            },
+        };
+        write_frame(&mut parent_write, &serde_json::to_vec(&request).unwrap())
+            .await
+            .unwrap();
+        let frame = read_frame(&mut parent_read).await.unwrap().unwrap();
+        let response: WorkerResponse = serde_json::from_slice(&frame).unwrap();
+        assert!(!response.is_error, "{}", response.content);
+        assert!(response.content.contains("hello"));
+        assert_eq!(response.read_files, vec![temp.path().join("a.txt")]);
+
+        // Closing the parent's end is how the daemon stops a worker
+        drop((parent_read, parent_write));
+        server.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_crashed_worker_fails_the_call_and_restarts() {
+        let temp = TempDir::new().unwrap();
+        let ctx = ToolContext::new(temp.path().to_path_buf(), "exec-1".to_string());
+        // Exits without answering, like a worker that panicked mid-call
+        let worker = ToolWorker::with_command(
+            PathBuf::from("sh"),
+            vec!["-c".to_string(), "head -c 4 >/dev/null; exit 3".to_string()],
+        );
+
+        let result = worker.execute(&call("read", serde_json::json!({"path": "a.txt"})), &ctx).await;
+        assert!(result.is_error);
+        assert!(result.content.contains("Tool worker crashed while running read"), "{}", result.content);
+        assert!(result.content.contains("exit status: 3"), "{}", result.content);
+        assert_eq!(worker.restarts(), 1);
+
+        let result = worker.execute(&call("read", serde_json::json!({"path": "a.txt"})), &ctx).await;
+        assert!(result.content.contains("Tool worker crashed"), "{}", result.content);
+        assert_eq!(worker.restarts(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_missing_worker_program_is_a_tool_error() {
+        let temp = TempDir::new().unwrap();
+        let ctx = ToolContext::new(temp.path().to_path_buf(), "exec-1".to_string());
+        let worker = ToolWorker::with_command(PathBuf::from("/nonexistent/td"), Vec::new());
+
+        let result = worker.execute(&call("read", serde_json::json!({})), &ctx).await;
+        assert!(result.is_error);
+        assert!(result.content.contains("Failed to start tool worker"), "{}", result.content);
+        assert_eq!(worker.restarts(), 0);
+    }
+}
@@ -6,10 +6,12 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use tracing::{debug, info, trace, warn};
 
+use super::commands::{CommandKind, Completion, render_prompt};
 use super::state::{
-    AppState, ConfirmAction, ConfirmDialog, InteractionMode, PendingAction, PlanCreateRequest, ReplMessage, ReplMode,
-    TopLevelPane, View, current_pane,
+    AppState, CommandRequest, ConfirmAction, ConfirmDialog, ExecutionItem, InteractionMode, PendingAction,
+    PlanCreateRequest, ReplMessage, ReplMode, SessionRequest, TopLevelPane, View, current_pane,
 };
+use crate::bulk::BulkAction;
 
 /// TUI application
 #[derive(Debug)]
@@ -55,7 +57,23 @@ impl App {
         match &self.state.interaction_mode {
             InteractionMode::Normal => {
                 debug!("App::handle_key: Normal mode");
-                self.handle_normal_key(key)
+                // Remapped keys are translated to the defaults handle_normal_key matches on
+                let in_repl = matches!(self.state.current_view, View::Repl);
+                match self.state.keymap.translate(key, in_repl) {
+                    Some(key) => self.handle_normal_key(key),
+                    None => {
+                        debug!("App::handle_key: key unbound by keymap");
+                        // A freed default key is still plain text in the REPL
+                        if let KeyCode::Char(c) = key.code
+                            && in_repl
+                            && !self.state.repl_streaming
+                            && !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+                        {
+                            self.start_repl_input(c);
+                        }
+                        false
+                    }
+                }
             }
             InteractionMode::Filter(_) => {
                 debug!("App::handle_key: Filter mode");
@@ -152,10 +170,13 @@ impl App {
                     // Scroll up in REPL view
                     let max = self.state.repl_max_scroll;
                     self.state.repl_scroll_up(1, max);
-                } else if matches!(self.state.current_view, View::Describe { .. }) {
+                } else if matches!(self.state.current_view, View::Describe { .. } | View::Compare { .. }) {
                     debug!("App::handle_normal_key: scroll up in Describe");
                     // Scroll up in Describe view
                     self.state.describe_scroll_up(1);
+                } else if matches!(self.state.current_view, View::Logs { .. }) {
+                    debug!("App::handle_normal_key: select prev iteration in Logs");
+                    self.state.logs_select_iteration(false);
                 } else if matches!(self.state.current_view, View::Loops) {
                     debug!("App::handle_normal_key: select prev in Loops tree");
                     // Tree navigation for Loops view
@@ -172,10 +193,13 @@ impl App {
                     // Scroll down in REPL view
                     let max = self.state.repl_max_scroll;
                     self.state.repl_scroll_down(1, max);
-                } else if matches!(self.state.current_view, View::Describe { .. }) {
+                } else if matches!(self.state.current_view, View::Describe { .. } | View::Compare { .. }) {
                     debug!("App::handle_normal_key: scroll down in Describe");
                     // Scroll down in Describe view
                     self.state.describe_scroll_down(1);
+                } else if matches!(self.state.current_view, View::Logs { .. }) {
+                    debug!("App::handle_normal_key: select next iteration in Logs");
+                    self.state.logs_select_iteration(true);
                 } else if matches!(self.state.current_view, View::Loops) {
                     debug!("App::handle_normal_key: select next in Loops tree");
                     // Tree navigation for Loops view
@@ -194,7 +218,7 @@ impl App {
                     debug!("App::handle_normal_key: page up in REPL");
                     let max = self.state.repl_max_scroll;
                     self.state.repl_scroll_up(10, max);
-                } else if matches!(self.state.current_view, View::Describe { .. }) {
+                } else if matches!(self.state.current_view, View::Describe { .. } | View::Compare { .. }) {
                     debug!("App::handle_normal_key: page up in Describe");
                     self.state.describe_scroll_up(10);
                 }
@@ -205,7 +229,7 @@ impl App {
                     debug!("App::handle_normal_key: page down in REPL");
                     let max = self.state.repl_max_scroll;
                     self.state.repl_scroll_down(10, max);
-                } else if matches!(self.state.current_view, View::Describe { .. }) {
+                } else if matches!(self.state.current_view, View::Describe { .. } | View::Compare { .. }) {
                     debug!("App::handle_normal_key: page down in Describe");
                     self.state.describe_scroll_down(10);
                 }
@@ -216,7 +240,7 @@ impl App {
                     debug!("App::handle_normal_key: scroll to top in REPL");
                     // Scroll to top
                     self.state.repl_scroll = Some(0);
-                } else if matches!(self.state.current_view, View::Describe { .. }) {
+                } else if matches!(self.state.current_view, View::Describe { .. } | View::Compare { .. }) {
                     debug!("App::handle_normal_key: scroll to top in Describe");
                     self.state.describe_scroll_to_top();
                 } else if matches!(self.state.current_view, View::Loops) {
@@ -233,7 +257,7 @@ impl App {
                     debug!("App::handle_normal_key: scroll to bottom in REPL");
                     // Scroll to bottom (auto-scroll mode)
                     self.state.repl_scroll_to_bottom();
-                } else if matches!(self.state.current_view, View::Describe { .. }) {
+                } else if matches!(self.state.current_view, View::Describe { .. } | View::Compare { .. }) {
                     debug!("App::handle_normal_key: scroll to bottom in Describe");
                     // Scroll to bottom in Describe view
                     self.state.describe_scroll = self.state.describe_max_scroll;
@@ -318,6 +342,26 @@ impl App {
                 // Describe selected item
                 self.handle_describe();
             }
+            (KeyCode::Char(' '), _) if matches!(self.state.current_view, View::Executions) => {
+                debug!("App::handle_normal_key: space - toggle mark");
+                self.handle_toggle_mark();
+            }
+            (KeyCode::Char('='), _) if matches!(self.state.current_view, View::Executions) => {
+                debug!("App::handle_normal_key: = - compare marked");
+                self.handle_compare();
+            }
+            (KeyCode::Char('x'), _)
+                if matches!(self.state.current_view, View::Executions) && !self.state.marked_executions.is_empty() =>
+            {
+                debug!("App::handle_normal_key: x - cancel marked");
+                self.handle_bulk(BulkAction::Cancel);
+            }
+            (KeyCode::Char('D'), _)
+                if matches!(self.state.current_view, View::Executions) && !self.state.marked_executions.is_empty() =>
+            {
+                debug!("App::handle_normal_key: D - delete marked");
+                self.handle_bulk(BulkAction::Delete);
+            }
             (KeyCode::Char('x'), _) if matches!(self.state.current_view, View::Executions | View::Loops) => {
                 debug!("App::handle_normal_key: x - cancel");
                 // Cancel selected execution
@@ -334,6 +378,12 @@ impl App {
                 // Delete selected execution
                 self.handle_delete();
             }
+            (KeyCode::Char('c'), KeyModifiers::NONE)
+                if matches!(self.state.current_view, View::Executions | View::Loops) =>
+            {
+                debug!("App::handle_normal_key: c - cherry-pick");
+                self.handle_cherry_pick();
+            }
 
             // === New task ===
             (KeyCode::Char('n'), _) if matches!(self.state.current_view, View::Executions) => {
@@ -366,6 +416,33 @@ impl App {
                 self.handle_show_logs_describe();
             }
 
+            // === Split-pane layout ===
+            (KeyCode::Char('p'), KeyModifiers::NONE)
+                if matches!(
+                    self.state.current_view,
+                    View::Executions | View::Loops | View::Describe { .. } | View::Logs { .. }
+                ) =>
+            {
+                debug!("App::handle_normal_key: p - toggle pin");
+                self.handle_toggle_pin();
+            }
+            (KeyCode::Char('u'), KeyModifiers::CONTROL) => {
+                debug!("App::handle_normal_key: Ctrl+u - unpin");
+                self.state.unpin();
+            }
+            (KeyCode::Char('w'), KeyModifiers::CONTROL) => {
+                debug!("App::handle_normal_key: Ctrl+w - cycle split");
+                self.state.cycle_split();
+            }
+            (KeyCode::Left, KeyModifiers::CONTROL) => {
+                debug!("App::handle_normal_key: Ctrl+Left - shrink primary pane");
+                self.state.resize_split(-5);
+            }
+            (KeyCode::Right, KeyModifiers::CONTROL) => {
+                debug!("App::handle_normal_key: Ctrl+Right - grow primary pane");
+                self.state.resize_split(5);
+            }
+
             // === REPL view specific: toggle tool output expansion ===
             (KeyCode::Char('o'), KeyModifiers::NONE) if matches!(self.state.current_view, View::Repl) => {
                 debug!("App::handle_normal_key: o - toggle tool expansion in REPL");
@@ -381,9 +458,7 @@ impl App {
                 if matches!(self.state.current_view, View::Repl) && !self.state.repl_streaming =>
             {
                 debug!(%c, "App::handle_normal_key: char starts REPL input");
-                self.state.repl_input.push(c);
-                self.state.repl_cursor_pos = self.state.repl_input.len();
-                self.state.interaction_mode = InteractionMode::ReplInput;
+                self.start_repl_input(c);
             }
 
             _ => {
@@ -394,6 +469,14 @@ impl App {
         false
     }
 
+    /// Enter REPL input mode with the typed character
+    fn start_repl_input(&mut self, c: char) {
+        debug!(%c, "App::start_repl_input: called");
+        self.state.repl_input.push(c);
+        self.state.repl_cursor_pos = self.state.repl_input.len();
+        self.state.interaction_mode = InteractionMode::ReplInput;
+    }
+
     /// Navigate to the previous top-level pane
     fn navigate_prev_top_level_view(&mut self) {
         debug!("App::navigate_prev_top_level_view: called");
@@ -470,6 +553,10 @@ impl App {
                     });
                 }
             }
+            View::Logs { .. } => {
+                debug!("App::handle_drill_down: in Logs view - toggle iteration");
+                self.state.toggle_logs_iteration();
+            }
             _ => {
                 debug!("App::handle_drill_down: no action for current view");
             }
@@ -486,6 +573,13 @@ impl App {
             return;
         }
 
+        // Then drop bulk-action marks
+        if !self.state.marked_executions.is_empty() {
+            debug!("App::handle_escape: clearing marks");
+            self.state.marked_executions.clear();
+            return;
+        }
+
         // Then try to pop view stack
         if self.state.pop_view() {
             debug!("App::handle_escape: popped view from stack");
@@ -529,6 +623,19 @@ impl App {
         }
     }
 
+    /// Pin the selected (or described/logged) execution to the secondary pane
+    fn handle_toggle_pin(&mut self) {
+        debug!("App::handle_toggle_pin: called");
+        let id = match &self.state.current_view {
+            View::Describe { target_id, .. } | View::Logs { target_id } => Some(target_id.clone()),
+            _ => self.state.selected_item_id(),
+        };
+        match id {
+            Some(id) => self.state.toggle_pin(&id),
+            None => debug!("App::handle_toggle_pin: no item selected"),
+        }
+    }
+
     /// Handle cancel action
     fn handle_cancel(&mut self) {
         debug!("App::handle_cancel: called");
@@ -559,6 +666,68 @@ impl App {
         }
     }
 
+    /// Mark or unmark the selected execution for a bulk action, then move down
+    fn handle_toggle_mark(&mut self) {
+        debug!("App::handle_toggle_mark: called");
+        let filtered = self.state.filtered_executions();
+        let count = filtered.len();
+        let Some(id) = filtered
+            .get(self.state.executions_selection.selected_index)
+            .map(|item| item.id.clone())
+        else {
+            debug!("App::handle_toggle_mark: no selection");
+            return;
+        };
+
+        if !self.state.marked_executions.remove(&id) {
+            self.state.marked_executions.insert(id);
+        }
+        self.state.executions_selection.select_next(count);
+    }
+
+    /// Open the Compare view for the two marked executions
+    fn handle_compare(&mut self) {
+        debug!(marked = self.state.marked_executions.len(), "App::handle_compare: called");
+        let mut marked = self.state.marked_executions.iter();
+        let (Some(left), Some(right), None) = (marked.next(), marked.next(), marked.next()) else {
+            self.state.set_error("Mark exactly two executions to compare (space marks)");
+            return;
+        };
+        let (left, right) = (left.clone(), right.clone());
+        self.state.comparison = None;
+        self.state.describe_scroll = 0;
+        self.state.push_view(View::Compare { left, right });
+    }
+
+    /// Confirm `action` on the marked executions it applies to
+    fn handle_bulk(&mut self, action: BulkAction) {
+        debug!(%action, marked = self.state.marked_executions.len(), "App::handle_bulk: called");
+        if self.state.marked_executions.is_empty() {
+            self.state
+                .set_error(format!("No executions marked to {} (space marks)", action));
+            return;
+        }
+
+        let (ids, skipped): (Vec<&ExecutionItem>, Vec<&ExecutionItem>) = self
+            .state
+            .executions
+            .iter()
+            .filter(|item| self.state.marked_executions.contains(&item.id))
+            .partition(|item| action.applies_to_status(&item.status));
+        if ids.is_empty() {
+            debug!("App::handle_bulk: action applies to none of the marked executions");
+            self.state.set_error(format!(
+                "None of the marked executions can be {}",
+                action.past_tense().to_lowercase()
+            ));
+            return;
+        }
+
+        let ids = ids.into_iter().map(|item| item.id.clone()).collect();
+        let skipped = skipped.len();
+        self.state.interaction_mode = InteractionMode::Confirm(ConfirmDialog::bulk(action, ids, skipped));
+    }
+
     /// Handle pause action
     fn handle_pause(&mut self) {
         debug!("App::handle_pause: called");
@@ -609,7 +778,7 @@ impl App {
         };
 
         if let Some(item) = selected
-            && item.status == "paused"
+            && matches!(item.status.as_str(), "paused" | "parked" | "waiting_provider")
         {
             debug!(%item.id, "App::handle_resume: showing resume confirm dialog");
             self.state.interaction_mode = InteractionMode::Confirm(ConfirmDialog::new(
@@ -649,6 +818,30 @@ impl App {
         }
     }
 
+    /// Handle cherry-pick action (the execution's commits onto a new branch)
+    fn handle_cherry_pick(&mut self) {
+        debug!("App::handle_cherry_pick: called");
+        let selected = if matches!(self.state.current_view, View::Loops) {
+            self.state.loops_tree.selected_node().map(|n| n.item.clone())
+        } else {
+            let filtered = self.state.filtered_executions();
+            filtered
+                .get(self.state.executions_selection.selected_index)
+                .copied()
+                .cloned()
+        };
+
+        if let Some(item) = selected
+            && item.status != "draft"
+        {
+            debug!(%item.id, "App::handle_cherry_pick: showing cherry-pick confirm dialog");
+            self.state.interaction_mode =
+                InteractionMode::Confirm(ConfirmDialog::cherry_pick(item.id.clone(), &item.name));
+        } else {
+            debug!("App::handle_cherry_pick: draft or no selection");
+        }
+    }
+
     /// Handle start draft action (transitions Draft -> Pending)
     fn handle_start_draft(&mut self) {
         debug!("App::handle_start_draft: called");
@@ -720,8 +913,8 @@ impl App {
                     info!("Setting PauseLoop action (running -> paused)");
                     PendingAction::PauseLoop(item.id.clone())
                 }
-                "paused" => {
-                    info!("Setting ResumeLoop action (paused -> running)");
+                "paused" | "parked" | "waiting_provider" => {
+                    info!("Setting ResumeLoop action ({} -> running)", item.status);
                     PendingAction::ResumeLoop(item.id.clone())
                 }
                 status => {
@@ -767,8 +960,8 @@ impl App {
                         info!("Setting PauseLoop action (running -> paused)");
                         PendingAction::PauseLoop(item.id.clone())
                     }
-                    "paused" => {
-                        info!("Setting ResumeLoop action (paused -> running)");
+                    "paused" | "parked" | "waiting_provider" => {
+                        info!("Setting ResumeLoop action ({} -> running)", item.status);
                         PendingAction::ResumeLoop(item.id.clone())
                     }
                     status => {
@@ -874,6 +1067,7 @@ impl App {
     /// Handle key in REPL input mode
     fn handle_repl_input_key(&mut self, key: KeyEvent) -> bool {
         debug!(?key, "App::handle_repl_input_key: called");
+        self.state.repl_completions.clear();
         match key.code {
             KeyCode::Esc => {
                 debug!("App::handle_repl_input_key: Esc - cancel input");
@@ -927,7 +1121,12 @@ impl App {
                 self.state.repl_input.insert(self.state.repl_cursor_pos, c);
                 self.state.repl_cursor_pos += c.len_utf8();
             }
-            // Tab cycles through views (exit input mode first)
+            // Tab completes slash commands
+            KeyCode::Tab if self.state.repl_input.starts_with('/') => {
+                debug!("App::handle_repl_input_key: Tab - complete command");
+                self.complete_repl_command();
+            }
+            // Otherwise Tab cycles through views (exit input mode first)
             KeyCode::Tab => {
                 debug!("App::handle_repl_input_key: Tab - next view");
                 self.state.interaction_mode = InteractionMode::Normal;
@@ -1024,21 +1223,28 @@ impl App {
         // Queue the plan creation request
         self.state.pending_plan_create = Some(PlanCreateRequest {
             messages: self.state.repl_history.clone(),
+            submitter: None,
         });
     }
 
     /// Handle REPL slash commands
     fn handle_repl_slash_command(&mut self, input: &str) {
         debug!(%input, "App::handle_repl_slash_command: called");
-        let parts: Vec<&str> = input.split_whitespace().collect();
-        let cmd = parts.first().copied().unwrap_or("");
+        let (kind, args) = match self.state.commands.parse(input) {
+            Ok((command, args)) => (command.kind.clone(), args),
+            Err(e) => {
+                debug!(%e, "App::handle_repl_slash_command: parse failed");
+                self.state.set_error(e);
+                return;
+            }
+        };
 
-        match cmd {
-            "/help" | "/h" => {
+        match kind {
+            CommandKind::Help => {
                 debug!("App::handle_repl_slash_command: help command");
                 self.state.interaction_mode = InteractionMode::Help;
             }
-            "/quit" | "/q" | "/exit" => {
+            CommandKind::Quit => {
                 debug!("App::handle_repl_slash_command: quit command");
                 if self.state.executions_active > 0 {
                     debug!("App::handle_repl_slash_command: showing quit confirm");
@@ -1048,22 +1254,41 @@ impl App {
                     self.state.should_quit = true;
                 }
             }
-            "/clear" | "/c" => {
+            CommandKind::Clear => {
                 debug!("App::handle_repl_slash_command: clear command");
                 self.state.repl_history.clear();
                 self.state.repl_response_buffer.clear();
                 self.state.repl_scroll = None; // Reset to auto-scroll
+                self.state.pending_session = Some(SessionRequest::New);
+            }
+            CommandKind::Sessions => {
+                debug!("App::handle_repl_slash_command: sessions command");
+                self.state.pending_session = Some(SessionRequest::List);
+            }
+            CommandKind::Resume => {
+                debug!("App::handle_repl_slash_command: resume command");
+                if self.state.repl_streaming {
+                    debug!("App::handle_repl_slash_command: cannot resume while streaming");
+                    self.state.set_error("Cannot resume while a response is streaming");
+                } else {
+                    self.state.pending_session = Some(SessionRequest::Resume(args[0].clone()));
+                }
             }
-            "/create" => {
+            CommandKind::Create => {
                 debug!("App::handle_repl_slash_command: create command");
                 self.handle_create_plan_command();
             }
-            "/executions" | "/exec" => {
+            CommandKind::Executions => {
                 debug!("App::handle_repl_slash_command: executions command");
                 self.state.current_view = View::Executions;
                 self.state.view_stack.clear();
             }
-            "/records" | "/rec" => {
+            CommandKind::Summary => {
+                debug!("App::handle_repl_slash_command: summary command");
+                self.state.current_view = View::Summary;
+                self.state.view_stack.clear();
+            }
+            CommandKind::Records => {
                 debug!("App::handle_repl_slash_command: records command");
                 self.state.current_view = View::Records {
                     type_filter: None,
@@ -1071,9 +1296,73 @@ impl App {
                 };
                 self.state.view_stack.clear();
             }
-            _ => {
-                debug!(%cmd, "App::handle_repl_slash_command: unknown command");
-                self.state.set_error(format!("Unknown command: {}", cmd));
+            CommandKind::Model => {
+                debug!("App::handle_repl_slash_command: model command");
+                if self.state.repl_streaming && !args.is_empty() {
+                    debug!("App::handle_repl_slash_command: cannot switch model while streaming");
+                    self.state
+                        .set_error("Cannot switch models while a response is streaming");
+                } else {
+                    self.state.pending_command = Some(CommandRequest::Model(args.first().cloned()));
+                }
+            }
+            CommandKind::Tools => {
+                debug!("App::handle_repl_slash_command: tools command");
+                let request = match args.as_slice() {
+                    [] => Some(CommandRequest::ListTools),
+                    [state, name] if state == "on" || state == "off" => Some(CommandRequest::SetTool {
+                        name: name.clone(),
+                        enabled: state == "on",
+                    }),
+                    _ => None,
+                };
+                match request {
+                    Some(request) => self.state.pending_command = Some(request),
+                    None => self.state.set_error("Usage: /tools [on|off <name>]"),
+                }
+            }
+            CommandKind::Context => {
+                debug!("App::handle_repl_slash_command: context command");
+                let request = match args.as_slice() {
+                    [sub, pattern] if sub == "ingest" => Some(CommandRequest::IngestContext(pattern.clone())),
+                    [sub] if sub == "list" => Some(CommandRequest::ListContext),
+                    [sub] if sub == "clear" => Some(CommandRequest::ClearContext),
+                    _ => None,
+                };
+                match request {
+                    Some(request) => self.state.pending_command = Some(request),
+                    None => self.state.set_error("Usage: /context ingest <glob> | list | clear"),
+                }
+            }
+            CommandKind::Cost => {
+                debug!("App::handle_repl_slash_command: cost command");
+                let summary = self.state.cost_summary();
+                self.state.repl_history.push(ReplMessage::assistant(summary));
+                self.state.repl_scroll = None;
+            }
+            CommandKind::Prompt { template } => {
+                debug!("App::handle_repl_slash_command: prompt command");
+                match render_prompt(&template, &args) {
+                    Ok(prompt) => self.state.pending_repl_submit = Some(prompt),
+                    Err(e) => self.state.set_error(e),
+                }
+            }
+        }
+    }
+
+    /// Complete the slash command being typed (Tab in REPL input)
+    fn complete_repl_command(&mut self) {
+        debug!(input = %self.state.repl_input, "App::complete_repl_command: called");
+        match self.state.commands.complete(&self.state.repl_input) {
+            Completion::Replace(text) => {
+                self.state.repl_cursor_pos = text.len();
+                self.state.repl_input = text;
+            }
+            Completion::Candidates(candidates) => {
+                self.state.repl_completions = candidates;
+            }
+            Completion::None => {
+                debug!("App::complete_repl_command: no completion");
             }
         }
     }
@@ -1154,6 +1443,14 @@ impl App {
                             debug!(%id, "App::handle_confirm_key: activate draft confirmed");
                             self.state.pending_action = Some(PendingAction::ActivateDraft(id.clone()));
                         }
+                        ConfirmAction::CherryPick(id) => {
+                            debug!(%id, "App::handle_confirm_key: cherry-pick confirmed");
+                            self.state.pending_action = Some(PendingAction::CherryPick(id.clone()));
+                        }
+                        ConfirmAction::Bulk(action, ids) => {
+                            debug!(%action, count = ids.len(), "App::handle_confirm_key: bulk action confirmed");
+                            self.state.pending_action = Some(PendingAction::Bulk(*action, ids.clone()));
+                        }
                     }
                 } else {
                     debug!("App::handle_confirm_key: user did not confirm");
@@ -1242,6 +1539,45 @@ impl App {
                 self.state.interaction_mode = InteractionMode::Help;
             }
 
+            // Bulk actions on the marked executions
+            "pause" | "resume" | "cancel" | "delete" => {
+                let action = match command {
+                    "pause" => BulkAction::Pause,
+                    "resume" => BulkAction::Resume,
+                    "cancel" => BulkAction::Cancel,
+                    _ => BulkAction::Delete,
+                };
+                debug!(%action, "App::execute_command: bulk command");
+                self.handle_bulk(action);
+            }
+
+            // Apply a saved execution filter (no name clears the filter)
+            "filter" => {
+                debug!(args = ?&parts[1..], "App::execute_command: filter command");
+                let Some(name) = parts.get(1) else {
+                    self.state.filter_text.clear();
+                    return;
+                };
+                match self.state.saved_filters.get(*name).cloned() {
+                    Some(query) => {
+                        debug!(%name, %query, "App::execute_command: applying saved filter");
+                        if !matches!(self.state.current_view, View::Executions) {
+                            self.state.navigate_to(View::Executions);
+                        }
+                        self.state.filter_text = query;
+                    }
+                    None => {
+                        debug!(%name, "App::execute_command: unknown saved filter");
+                        let names: Vec<&str> = self.state.saved_filters.keys().map(String::as_str).collect();
+                        self.state.set_error(if names.is_empty() {
+                            format!("Unknown filter: {} (no saved filters in config)", name)
+                        } else {
+                            format!("Unknown filter: {} (saved: {})", name, names.join(", "))
+                        });
+                    }
+                }
+            }
+
             _ => {
                 debug!(%command, "App::execute_command: unknown command");
                 self.state.set_error(format!("Unknown command: {}", command));
@@ -1253,7 +1589,6 @@ impl App {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::tui::state::ExecutionItem;
 
     #[test]
     fn test_app_new() {
@@ -1360,6 +1695,112 @@ mod tests {
         assert!(app.state().error_message.as_ref().unwrap().contains("Unknown command"));
     }
 
+    #[test]
+    fn test_execute_command_saved_filter() {
+        let mut app = App::new();
+        app.state_mut()
+            .saved_filters
+            .insert("failing-backend".to_string(), "status:failed tag:backend".to_string());
+        let mut tagged = make_execution_item("exec-1", "failed", None);
+        tagged.tags = vec!["backend".to_string()];
+        app.state_mut().executions = vec![
+            tagged,
+            make_execution_item("exec-2", "failed", None),
+            make_execution_item("exec-3", "running", None),
+        ];
+
+        app.execute_command("filter failing-backend".to_string());
+        assert!(matches!(app.state().current_view, View::Executions));
+        let ids: Vec<&str> = app
+            .state()
+            .filtered_executions()
+            .iter()
+            .map(|e| e.id.as_str())
+            .collect();
+        assert_eq!(ids, ["exec-1"]);
+
+        app.execute_command("filter missing".to_string());
+        assert!(app.state().error_message.as_ref().unwrap().contains("failing-backend"));
+
+        app.execute_command("filter".to_string());
+        assert_eq!(app.state().filtered_executions().len(), 3);
+    }
+
+    #[test]
+    fn test_user_filter() {
+        let mut mine = make_execution_item("exec-1", "running", None);
+        mine.submitted_by = Some(crate::domain::Submitter {
+            user: "alice".to_string(),
+            name: None,
+        });
+        mine.mine = true;
+        let other = make_execution_item("exec-2", "running", None);
+
+        assert!(mine.matches_filter("user:me"));
+        assert!(mine.matches_filter("user:Alice status:running"));
+        assert!(!other.matches_filter("user:me"));
+        assert!(!other.matches_filter("user:alice"));
+    }
+
+    #[test]
+    fn test_bulk_cancel_marked_executions() {
+        let mut app = App::new();
+        app.state_mut().current_view = View::Executions;
+        app.state_mut().executions = vec![
+            make_execution_item("exec-1", "running", None),
+            make_execution_item("exec-2", "complete", None),
+            make_execution_item("exec-3", "pending", None),
+        ];
+
+        // Space marks the selection and moves down
+        for _ in 0..3 {
+            app.handle_key(KeyEvent::from(KeyCode::Char(' ')));
+        }
+        assert_eq!(app.state().marked_executions.len(), 3);
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('x')));
+        let InteractionMode::Confirm(dialog) = &app.state().interaction_mode else {
+            panic!("expected a confirm dialog");
+        };
+        assert!(dialog.message.contains("1 marked can't be cancelled"));
+        let ConfirmAction::Bulk(action, ids) = &dialog.action else {
+            panic!("expected a bulk action");
+        };
+        assert_eq!(*action, BulkAction::Cancel);
+        assert_eq!(ids, &["exec-1".to_string(), "exec-3".to_string()]);
+
+        // Esc closes the dialog, a second Esc drops the marks
+        app.handle_key(KeyEvent::from(KeyCode::Esc));
+        app.handle_key(KeyEvent::from(KeyCode::Esc));
+        assert!(app.state().marked_executions.is_empty());
+    }
+
+    #[test]
+    fn test_compare_marked_executions() {
+        let mut app = App::new();
+        app.state_mut().current_view = View::Executions;
+        app.state_mut().executions = vec![
+            make_execution_item("exec-1", "failed", None),
+            make_execution_item("exec-2", "complete", None),
+        ];
+
+        // One mark is not enough
+        app.handle_key(KeyEvent::from(KeyCode::Char(' ')));
+        app.handle_key(KeyEvent::from(KeyCode::Char('=')));
+        assert!(matches!(app.state().current_view, View::Executions));
+        assert!(app.state().error_message.is_some());
+
+        app.handle_key(KeyEvent::from(KeyCode::Char(' ')));
+        app.handle_key(KeyEvent::from(KeyCode::Char('=')));
+        assert_eq!(
+            app.state().current_view,
+            View::Compare {
+                left: "exec-1".to_string(),
+                right: "exec-2".to_string()
+            }
+        );
+    }
+
     // === Helper to create test ExecutionItem ===
     fn make_execution_item(id: &str, status: &str, parent: Option<&str>) -> ExecutionItem {
         ExecutionItem {
@@ -1374,9 +1815,24 @@ mod tests {
             artifact_id: None,
             artifact_file: None,
             artifact_status: None,
+            deps: Vec::new(),
+            tags: Vec::new(),
+            submitted_by: None,
+            mine: false,
         }
     }
 
+    #[test]
+    fn test_dependency_names() {
+        let mut app = App::new();
+        let schema = make_execution_item("schema", "complete", Some("spec-1"));
+        let mut api = make_execution_item("api", "pending", Some("spec-2"));
+        api.deps = vec!["schema".to_string(), "gone".to_string()];
+        app.state_mut().executions = vec![schema, api.clone()];
+
+        assert_eq!(app.state().dependency_names(&api), vec!["Test schema", "gone"]);
+    }
+
     // === POSITIVE TESTS: handle_start_draft ===
 
     #[test]
@@ -1681,4 +2137,129 @@ mod tests {
         // Selection should be None
         assert!(app.state().loops_tree.selected_id().is_none());
     }
+
+    #[test]
+    fn test_pin_selected_execution_and_cycle_split() {
+        use crate::config::SplitMode;
+
+        let mut app = App::new();
+        app.state_mut().current_view = View::Loops;
+        let items = vec![make_execution_item("run-1", "running", None)];
+        app.state_mut().loops_tree.build_from_items(items);
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('p')));
+        assert_eq!(app.state().pinned_execution.as_deref(), Some("run-1"));
+        assert_eq!(app.state().layout.split, SplitMode::Horizontal);
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('w'), KeyModifiers::CONTROL));
+        assert_eq!(app.state().layout.split, SplitMode::Vertical);
+
+        // Pin survives leaving the list, Ctrl+u removes it
+        app.state_mut().current_view = View::Repl;
+        app.handle_key(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL));
+        assert_eq!(app.state().pinned_execution, None);
+        assert!(app.state().repl_input.is_empty());
+    }
+
+    #[test]
+    fn test_remapped_keys() {
+        use super::super::keymap::KeyMap;
+
+        let mut app = App::new();
+        let keys = [("quit".to_string(), "Q".to_string())].into_iter().collect();
+        app.state_mut().keymap = KeyMap::from_config(&keys).unwrap();
+
+        // The freed default key types text in the REPL instead of quitting
+        app.state_mut().current_view = View::Repl;
+        app.handle_key(KeyEvent::from(KeyCode::Char('q')));
+        assert!(!app.state().should_quit);
+        assert_eq!(app.state().repl_input, "q");
+
+        app.state_mut().interaction_mode = InteractionMode::Normal;
+        app.handle_key(KeyEvent::new(KeyCode::Char('Q'), KeyModifiers::SHIFT));
+        assert!(app.state().should_quit);
+    }
+
+    #[test]
+    fn test_session_slash_commands() {
+        use super::super::state::SessionRequest;
+
+        let mut app = App::new();
+        app.handle_repl_slash_command("/resume 1a2b3c");
+        assert_eq!(
+            app.state_mut().pending_session.take(),
+            Some(SessionRequest::Resume("1a2b3c".to_string()))
+        );
+
+        app.handle_repl_slash_command("/resume");
+        assert_eq!(app.state().pending_session, None);
+        assert!(app.state().error_message.is_some());
+
+        app.state_mut().repl_history.push(ReplMessage::user("hi"));
+        app.handle_repl_slash_command("/clear");
+        assert!(app.state().repl_history.is_empty());
+        assert_eq!(app.state().pending_session, Some(SessionRequest::New));
+    }
+
+    #[test]
+    fn test_registry_slash_commands() {
+        use super::super::commands::SlashCommand;
+
+        let mut app = App::new();
+        app.handle_repl_slash_command("/tools off bash");
+        assert_eq!(
+            app.state_mut().pending_command.take(),
+            Some(CommandRequest::SetTool {
+                name: "bash".to_string(),
+                enabled: false
+            })
+        );
+
+        app.handle_repl_slash_command(r#"/context ingest "src/**/*.rs""#);
+        assert_eq!(
+            app.state_mut().pending_command.take(),
+            Some(CommandRequest::IngestContext("src/**/*.rs".to_string()))
+        );
+
+        app.handle_repl_slash_command("/context bogus");
+        assert_eq!(app.state().pending_command, None);
+        assert!(app.state().error_message.is_some());
+
+        app.handle_repl_slash_command("/cost");
+        assert!(
+            app.state()
+                .repl_history
+                .last()
+                .unwrap()
+                .content
+                .contains("Input tokens: 0")
+        );
+
+        app.state_mut()
+            .commands
+            .register(SlashCommand::prompt("review", "Review a file", "Review {{args}}"))
+            .unwrap();
+        app.handle_repl_slash_command("/review src/lib.rs");
+        assert_eq!(app.state().pending_repl_submit.as_deref(), Some("Review src/lib.rs"));
+    }
+
+    #[test]
+    fn test_tab_completes_slash_commands() {
+        let mut app = App::new();
+        app.state_mut().current_view = View::Repl;
+        for c in "/mo".chars() {
+            app.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+        app.handle_key(KeyEvent::from(KeyCode::Tab));
+        assert_eq!(app.state().repl_input, "/model ");
+        assert_eq!(app.state().repl_cursor_pos, "/model ".len());
+        assert_eq!(app.state().current_view, View::Repl);
+
+        app.state_mut().repl_input = "/re".to_string();
+        app.state_mut().repl_cursor_pos = 3;
+        app.handle_key(KeyEvent::from(KeyCode::Tab));
+        assert_eq!(app.state().repl_completions, vec!["/resume", "/records"]);
+        app.handle_key(KeyEvent::from(KeyCode::Char('s')));
+        assert!(app.state().repl_completions.is_empty());
+    }
 }
@@ -0,0 +1,489 @@
+//! REPL slash commands
+//!
+//! The CommandRegistry holds every `/command` the REPL understands: name,
+//! aliases, usage line, help text, and how many arguments it takes. Input is
+//! parsed against the registry before dispatch, and Tab completes command
+//! names and fixed sub-command arguments.
+//!
+//! Built-in commands are handled by the App (or queued for the runner).
+//! Loop types can contribute prompt commands through `repl-commands` in
+//! their YAML; other callers can `register` commands directly.
+
+use handlebars::Handlebars;
+use serde_json::json;
+use tracing::{debug, warn};
+
+use crate::r#loop::{LoopLoader, ReplCommandDef};
+
+/// What a slash command does when invoked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandKind {
+    Help,
+    Quit,
+    Clear,
+    Sessions,
+    Resume,
+    Create,
+    Executions,
+    Summary,
+    Records,
+    Model,
+    Tools,
+    Context,
+    Cost,
+    /// Render a Handlebars template with the arguments and send it to the LLM
+    Prompt {
+        template: String,
+    },
+}
+
+/// A registered slash command
+#[derive(Debug, Clone)]
+pub struct SlashCommand {
+    /// Name without the leading slash
+    pub name: String,
+    /// Alternative names (without slash)
+    pub aliases: Vec<String>,
+    /// Usage line shown in help and argument errors
+    pub usage: String,
+    /// One-line help text
+    pub help: String,
+    /// Minimum number of arguments
+    pub min_args: usize,
+    /// Maximum number of arguments (None = unlimited)
+    pub max_args: Option<usize>,
+    /// Completions for the first argument
+    pub choices: Vec<String>,
+    /// Handler
+    pub kind: CommandKind,
+}
+
+impl SlashCommand {
+    /// Create a command that takes no arguments
+    pub fn new(name: impl Into<String>, help: impl Into<String>, kind: CommandKind) -> Self {
+        let name = name.into();
+        debug!(%name, "SlashCommand::new: called");
+        Self {
+            usage: format!("/{}", name),
+            name,
+            aliases: Vec::new(),
+            help: help.into(),
+            min_args: 0,
+            max_args: Some(0),
+            choices: Vec::new(),
+            kind,
+        }
+    }
+
+    /// Create a prompt command (any number of arguments, bound to `{{args}}`)
+    pub fn prompt(name: impl Into<String>, help: impl Into<String>, template: impl Into<String>) -> Self {
+        let command = Self::new(
+            name,
+            help,
+            CommandKind::Prompt {
+                template: template.into(),
+            },
+        )
+        .with_args(0, None);
+        debug!(name = %command.name, "SlashCommand::prompt: called");
+        let usage = format!("/{} [args...]", command.name);
+        command.with_usage(usage)
+    }
+
+    /// Builder: add aliases
+    pub fn with_aliases(mut self, aliases: &[&str]) -> Self {
+        debug!(name = %self.name, ?aliases, "SlashCommand::with_aliases: called");
+        self.aliases = aliases.iter().map(|a| a.to_string()).collect();
+        self
+    }
+
+    /// Builder: set the usage line
+    pub fn with_usage(mut self, usage: impl Into<String>) -> Self {
+        self.usage = usage.into();
+        debug!(name = %self.name, usage = %self.usage, "SlashCommand::with_usage: called");
+        self
+    }
+
+    /// Builder: set the accepted argument count
+    pub fn with_args(mut self, min: usize, max: Option<usize>) -> Self {
+        debug!(name = %self.name, min, ?max, "SlashCommand::with_args: called");
+        self.min_args = min;
+        self.max_args = max;
+        self
+    }
+
+    /// Builder: set completions for the first argument
+    pub fn with_choices(mut self, choices: &[&str]) -> Self {
+        debug!(name = %self.name, ?choices, "SlashCommand::with_choices: called");
+        self.choices = choices.iter().map(|c| c.to_string()).collect();
+        self
+    }
+
+    /// Whether `name` is this command's name or one of its aliases
+    pub fn matches(&self, name: &str) -> bool {
+        self.name == name || self.aliases.iter().any(|a| a == name)
+    }
+
+    /// Check the argument count against the command's limits
+    fn check_args(&self, args: &[String]) -> Result<(), String> {
+        debug!(name = %self.name, count = args.len(), "SlashCommand::check_args: called");
+        let too_few = args.len() < self.min_args;
+        let too_many = self.max_args.is_some_and(|max| args.len() > max);
+        if too_few || too_many {
+            return Err(format!("Usage: {}", self.usage));
+        }
+        Ok(())
+    }
+}
+
+impl From<&ReplCommandDef> for SlashCommand {
+    fn from(def: &ReplCommandDef) -> Self {
+        let command = Self::prompt(&def.name, &def.description, &def.prompt);
+        match &def.usage {
+            Some(usage) => command.with_usage(usage),
+            None => command,
+        }
+    }
+}
+
+/// Result of Tab completion
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Completion {
+    /// Nothing to complete
+    None,
+    /// Replace the input with this text
+    Replace(String),
+    /// Several candidates share no longer prefix
+    Candidates(Vec<String>),
+}
+
+/// All slash commands known to the REPL
+#[derive(Debug, Clone)]
+pub struct CommandRegistry {
+    commands: Vec<SlashCommand>,
+}
+
+impl Default for CommandRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl CommandRegistry {
+    /// Create a registry with the built-in commands
+    pub fn builtin() -> Self {
+        debug!("CommandRegistry::builtin: called");
+        let commands = vec![
+            SlashCommand::new("help", "Show keyboard shortcuts and commands", CommandKind::Help).with_aliases(&["h"]),
+            SlashCommand::new("quit", "Quit taskdaemon", CommandKind::Quit).with_aliases(&["q", "exit"]),
+            SlashCommand::new(
+                "clear",
+                "Clear conversation and start a new session",
+                CommandKind::Clear,
+            )
+            .with_aliases(&["c"]),
+            SlashCommand::new("sessions", "List saved sessions", CommandKind::Sessions),
+            SlashCommand::new("resume", "Resume a saved session (see /sessions)", CommandKind::Resume)
+                .with_usage("/resume <session-id>")
+                .with_args(1, Some(1)),
+            SlashCommand::new(
+                "create",
+                "Create plan from conversation (Rule of Five)",
+                CommandKind::Create,
+            ),
+            SlashCommand::new("executions", "Go to the Executions view", CommandKind::Executions)
+                .with_aliases(&["exec"]),
+            SlashCommand::new("summary", "Go to the Summary view", CommandKind::Summary),
+            SlashCommand::new("records", "Go to the Records view", CommandKind::Records).with_aliases(&["rec"]),
+            SlashCommand::new("model", "Show or switch the model (provider/model)", CommandKind::Model)
+                .with_usage("/model [name]")
+                .with_args(0, Some(1)),
+            SlashCommand::new("tools", "List tools or enable/disable one", CommandKind::Tools)
+                .with_usage("/tools [on|off <name>]")
+                .with_args(0, Some(2))
+                .with_choices(&["on", "off"]),
+            SlashCommand::new("context", "Add files to the conversation context", CommandKind::Context)
+                .with_usage("/context ingest <glob> | list | clear")
+                .with_args(1, Some(2))
+                .with_choices(&["ingest", "list", "clear"]),
+            SlashCommand::new("cost", "Show token usage and cost for this session", CommandKind::Cost),
+        ];
+        Self { commands }
+    }
+
+    /// Register a command
+    ///
+    /// Fails if the name or an alias is already taken.
+    pub fn register(&mut self, command: SlashCommand) -> Result<(), String> {
+        debug!(name = %command.name, "CommandRegistry::register: called");
+        if command.name.is_empty() || command.name.contains(|c: char| c.is_whitespace() || c == '/') {
+            return Err(format!("Invalid command name '{}'", command.name));
+        }
+        let names = std::iter::once(&command.name).chain(command.aliases.iter());
+        for name in names {
+            if self.get(name).is_some() {
+                debug!(%name, "CommandRegistry::register: name taken");
+                return Err(format!("Command /{} is already registered", name));
+            }
+        }
+        self.commands.push(command);
+        Ok(())
+    }
+
+    /// Register the `repl-commands` of every loaded loop type
+    ///
+    /// Commands that clash with an existing name are skipped with a warning.
+    /// Returns the number of commands registered.
+    pub fn register_loop_types(&mut self, loader: &LoopLoader) -> usize {
+        debug!(types = loader.len(), "CommandRegistry::register_loop_types: called");
+        let mut types: Vec<_> = loader.iter().collect();
+        types.sort_by_key(|(name, _)| *name);
+
+        let mut registered = 0;
+        for (type_name, loop_type) in types {
+            for def in &loop_type.repl_commands {
+                // Inherited commands appear in every child type; register once
+                if self.get(&def.name).is_some_and(|c| {
+                    c.kind
+                        == CommandKind::Prompt {
+                            template: def.prompt.clone(),
+                        }
+                }) {
+                    continue;
+                }
+                match self.register(SlashCommand::from(def)) {
+                    Ok(()) => registered += 1,
+                    Err(e) => warn!(loop_type = %type_name, error = %e, "Skipping REPL command"),
+                }
+            }
+        }
+        debug!(registered, "CommandRegistry::register_loop_types: complete");
+        registered
+    }
+
+    /// Look up a command by name or alias (without slash)
+    pub fn get(&self, name: &str) -> Option<&SlashCommand> {
+        self.commands.iter().find(|c| c.matches(name))
+    }
+
+    /// All commands in registration order
+    pub fn iter(&self) -> impl Iterator<Item = &SlashCommand> {
+        self.commands.iter()
+    }
+
+    /// Parse `/name args...` into the command and its arguments
+    pub fn parse(&self, input: &str) -> Result<(&SlashCommand, Vec<String>), String> {
+        debug!(%input, "CommandRegistry::parse: called");
+        let input = input.trim();
+        let body = input
+            .strip_prefix('/')
+            .ok_or_else(|| format!("Not a command: {}", input))?;
+        let (name, rest) = body.split_once(char::is_whitespace).unwrap_or((body, ""));
+        let command = self
+            .get(name)
+            .ok_or_else(|| format!("Unknown command: /{} (see /help)", name))?;
+        let args = split_args(rest)?;
+        command.check_args(&args)?;
+        Ok((command, args))
+    }
+
+    /// Complete a partially typed command name or first argument
+    pub fn complete(&self, input: &str) -> Completion {
+        debug!(%input, "CommandRegistry::complete: called");
+        let Some(body) = input.strip_prefix('/') else {
+            return Completion::None;
+        };
+
+        match body.split_once(' ') {
+            None => {
+                let names: Vec<&str> = self.commands.iter().map(|c| c.name.as_str()).collect();
+                complete_word(body, &names, |word| format!("/{}", word))
+            }
+            Some((name, arg)) => {
+                if arg.contains(char::is_whitespace) {
+                    return Completion::None;
+                }
+                let Some(command) = self.get(name) else {
+                    return Completion::None;
+                };
+                let choices: Vec<&str> = command.choices.iter().map(String::as_str).collect();
+                complete_word(arg, &choices, |word| format!("/{} {}", name, word))
+            }
+        }
+    }
+}
+
+/// Complete `typed` against `options`, formatting results with `format`
+///
+/// A unique match is completed with a trailing space; several matches are
+/// extended to their longest common prefix, or returned as candidates when
+/// that prefix is what was already typed.
+fn complete_word(typed: &str, options: &[&str], format: impl Fn(&str) -> String) -> Completion {
+    let matches: Vec<&str> = options.iter().copied().filter(|o| o.starts_with(typed)).collect();
+    match matches.as_slice() {
+        [] => Completion::None,
+        [only] => Completion::Replace(format!("{} ", format(only))),
+        [first, rest @ ..] => {
+            let common = rest.iter().fold(first.len(), |len, other| {
+                first
+                    .bytes()
+                    .zip(other.bytes())
+                    .take(len)
+                    .take_while(|(a, b)| a == b)
+                    .count()
+            });
+            if common > typed.len() {
+                Completion::Replace(format(&first[..common]))
+            } else {
+                Completion::Candidates(matches.iter().map(|m| format(m)).collect())
+            }
+        }
+    }
+}
+
+/// Split command arguments on whitespace, keeping double-quoted strings together
+pub fn split_args(input: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_token = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_token = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_token {
+                    args.push(std::mem::take(&mut current));
+                    has_token = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_token = true;
+            }
+        }
+    }
+
+    if in_quotes {
+        return Err("Unterminated quote in command arguments".to_string());
+    }
+    if has_token {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+/// Render a prompt command's template with its arguments
+///
+/// `{{args}}` is all arguments joined by spaces; `{{argv.[0]}}` etc. index them.
+pub fn render_prompt(template: &str, args: &[String]) -> Result<String, String> {
+    debug!(arg_count = args.len(), "render_prompt: called");
+    let mut hbs = Handlebars::new();
+    hbs.register_escape_fn(handlebars::no_escape);
+    let context = json!({ "args": args.join(" "), "argv": args });
+    hbs.render_template(template, &context)
+        .map_err(|e| format!("Failed to render command prompt: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_builtin_commands() {
+        let registry = CommandRegistry::builtin();
+
+        let (command, args) = registry.parse("/resume abc123").unwrap();
+        assert_eq!(command.kind, CommandKind::Resume);
+        assert_eq!(args, vec!["abc123"]);
+
+        let (command, _) = registry.parse("/q").unwrap();
+        assert_eq!(command.kind, CommandKind::Quit);
+
+        assert_eq!(registry.parse("/resume").unwrap_err(), "Usage: /resume <session-id>");
+        assert!(
+            registry
+                .parse("/nope")
+                .unwrap_err()
+                .starts_with("Unknown command: /nope")
+        );
+    }
+
+    #[test]
+    fn test_split_args_quotes() {
+        assert_eq!(
+            split_args(r#"ingest "src/**/*.rs"  extra"#).unwrap(),
+            vec!["ingest", "src/**/*.rs", "extra"]
+        );
+        assert_eq!(split_args(r#"say """#).unwrap(), vec!["say", ""]);
+        assert!(split_args(r#""open"#).is_err());
+    }
+
+    #[test]
+    fn test_complete_command_names() {
+        let registry = CommandRegistry::builtin();
+
+        assert_eq!(registry.complete("/cos"), Completion::Replace("/cost ".to_string()));
+        // "re" matches records and resume; the common prefix is already typed
+        assert_eq!(
+            registry.complete("/re"),
+            Completion::Candidates(vec!["/resume".to_string(), "/records".to_string()])
+        );
+        // Several commands start with "c" and share nothing more
+        assert!(matches!(registry.complete("/c"), Completion::Candidates(_)));
+        assert_eq!(registry.complete("/se"), Completion::Replace("/sessions ".to_string()));
+        assert_eq!(registry.complete("/zzz"), Completion::None);
+        assert_eq!(registry.complete("hello"), Completion::None);
+    }
+
+    #[test]
+    fn test_complete_arguments() {
+        let registry = CommandRegistry::builtin();
+
+        assert_eq!(
+            registry.complete("/context in"),
+            Completion::Replace("/context ingest ".to_string())
+        );
+        assert_eq!(
+            registry.complete("/tools o"),
+            Completion::Candidates(vec!["/tools on".to_string(), "/tools off".to_string()])
+        );
+        assert_eq!(
+            registry.complete("/tools of"),
+            Completion::Replace("/tools off ".to_string())
+        );
+        assert_eq!(registry.complete("/tools off x"), Completion::None);
+    }
+
+    #[test]
+    fn test_register_rejects_duplicates() {
+        let mut registry = CommandRegistry::builtin();
+
+        let review = SlashCommand::prompt("review", "Review a file", "Review {{args}}");
+        registry.register(review.clone()).unwrap();
+        assert!(registry.register(review).is_err());
+        assert!(
+            registry
+                .register(SlashCommand::prompt("x", "", "").with_aliases(&["q"]))
+                .is_err()
+        );
+
+        let (command, args) = registry.parse("/review src/main.rs").unwrap();
+        let CommandKind::Prompt { template } = &command.kind else {
+            panic!("expected prompt command");
+        };
+        assert_eq!(render_prompt(template, &args).unwrap(), "Review src/main.rs");
+    }
+
+    #[test]
+    fn test_render_prompt_argv() {
+        let args = vec!["a.rs".to_string(), "<b>".to_string()];
+        assert_eq!(
+            render_prompt("Compare {{argv.[0]}} with {{argv.[1]}}", &args).unwrap(),
+            "Compare a.rs with <b>"
+        );
+    }
+}
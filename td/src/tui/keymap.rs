@@ -0,0 +1,443 @@
+//! Configurable key bindings
+//!
+//! App dispatches on the default (vim-ish) keys. A KeyMap sits in front of
+//! that dispatch and translates remapped keys back to the default key of
+//! their action, so `tui.keys: { quit: Q }` makes `Q` quit and frees `q`.
+//!
+//! Actions that share a default key across views (e.g. `l` opens logs in
+//! lists and expands nodes in the Loops tree) move together.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use tracing::debug;
+
+/// A remappable TUI action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Quit,
+    Help,
+    Filter,
+    Command,
+    NextView,
+    PrevView,
+    Chat,
+    Plan,
+    Loops,
+    Down,
+    Up,
+    Top,
+    Bottom,
+    Select,
+    Back,
+    Collapse,
+    Logs,
+    Output,
+    Describe,
+    ToggleState,
+    Cancel,
+    Delete,
+    CherryPick,
+    Mark,
+    Compare,
+    NewTask,
+    Follow,
+    Pin,
+    Unpin,
+    CycleSplit,
+    GrowPane,
+    ShrinkPane,
+}
+
+impl Action {
+    /// All actions, in help order
+    pub const ALL: &'static [Action] = &[
+        Self::Quit,
+        Self::Help,
+        Self::Filter,
+        Self::Command,
+        Self::NextView,
+        Self::PrevView,
+        Self::Chat,
+        Self::Plan,
+        Self::Loops,
+        Self::Down,
+        Self::Up,
+        Self::Top,
+        Self::Bottom,
+        Self::Select,
+        Self::Back,
+        Self::Collapse,
+        Self::Logs,
+        Self::Output,
+        Self::Describe,
+        Self::ToggleState,
+        Self::Cancel,
+        Self::Delete,
+        Self::CherryPick,
+        Self::Mark,
+        Self::Compare,
+        Self::NewTask,
+        Self::Follow,
+        Self::Pin,
+        Self::Unpin,
+        Self::CycleSplit,
+        Self::GrowPane,
+        Self::ShrinkPane,
+    ];
+
+    /// Config name (`tui.keys.<name>`)
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Quit => "quit",
+            Self::Help => "help",
+            Self::Filter => "filter",
+            Self::Command => "command",
+            Self::NextView => "next-view",
+            Self::PrevView => "prev-view",
+            Self::Chat => "chat",
+            Self::Plan => "plan",
+            Self::Loops => "loops",
+            Self::Down => "down",
+            Self::Up => "up",
+            Self::Top => "top",
+            Self::Bottom => "bottom",
+            Self::Select => "select",
+            Self::Back => "back",
+            Self::Collapse => "collapse",
+            Self::Logs => "logs",
+            Self::Output => "output",
+            Self::Describe => "describe",
+            Self::ToggleState => "toggle-state",
+            Self::Cancel => "cancel",
+            Self::Delete => "delete",
+            Self::CherryPick => "cherry-pick",
+            Self::Mark => "mark",
+            Self::Compare => "compare",
+            Self::NewTask => "new-task",
+            Self::Follow => "follow",
+            Self::Pin => "pin",
+            Self::Unpin => "unpin",
+            Self::CycleSplit => "cycle-split",
+            Self::GrowPane => "grow-pane",
+            Self::ShrinkPane => "shrink-pane",
+        }
+    }
+
+    /// Look up an action by config name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|a| a.name() == name)
+    }
+
+    /// The key App dispatches on for this action
+    pub fn default_key(self) -> Key {
+        let (code, modifiers) = match self {
+            Self::Quit => (KeyCode::Char('q'), KeyModifiers::NONE),
+            Self::Help => (KeyCode::Char('?'), KeyModifiers::NONE),
+            Self::Filter => (KeyCode::Char('/'), KeyModifiers::NONE),
+            Self::Command => (KeyCode::Char(':'), KeyModifiers::NONE),
+            Self::NextView => (KeyCode::Tab, KeyModifiers::NONE),
+            Self::PrevView => (KeyCode::BackTab, KeyModifiers::NONE),
+            Self::Chat => (KeyCode::Char('C'), KeyModifiers::NONE),
+            Self::Plan => (KeyCode::Char('P'), KeyModifiers::NONE),
+            Self::Loops => (KeyCode::Char('L'), KeyModifiers::NONE),
+            Self::Down => (KeyCode::Char('j'), KeyModifiers::NONE),
+            Self::Up => (KeyCode::Char('k'), KeyModifiers::NONE),
+            Self::Top => (KeyCode::Char('g'), KeyModifiers::NONE),
+            Self::Bottom => (KeyCode::Char('G'), KeyModifiers::NONE),
+            Self::Select => (KeyCode::Enter, KeyModifiers::NONE),
+            Self::Back => (KeyCode::Esc, KeyModifiers::NONE),
+            Self::Collapse => (KeyCode::Char('h'), KeyModifiers::NONE),
+            Self::Logs => (KeyCode::Char('l'), KeyModifiers::NONE),
+            Self::Output => (KeyCode::Char('o'), KeyModifiers::NONE),
+            Self::Describe => (KeyCode::Char('d'), KeyModifiers::NONE),
+            Self::ToggleState => (KeyCode::Char('s'), KeyModifiers::NONE),
+            Self::Cancel => (KeyCode::Char('x'), KeyModifiers::NONE),
+            Self::Delete => (KeyCode::Char('D'), KeyModifiers::NONE),
+            Self::CherryPick => (KeyCode::Char('c'), KeyModifiers::NONE),
+            Self::Mark => (KeyCode::Char(' '), KeyModifiers::NONE),
+            Self::Compare => (KeyCode::Char('='), KeyModifiers::NONE),
+            Self::NewTask => (KeyCode::Char('n'), KeyModifiers::NONE),
+            Self::Follow => (KeyCode::Char('f'), KeyModifiers::NONE),
+            Self::Pin => (KeyCode::Char('p'), KeyModifiers::NONE),
+            Self::Unpin => (KeyCode::Char('u'), KeyModifiers::CONTROL),
+            Self::CycleSplit => (KeyCode::Char('w'), KeyModifiers::CONTROL),
+            Self::GrowPane => (KeyCode::Right, KeyModifiers::CONTROL),
+            Self::ShrinkPane => (KeyCode::Left, KeyModifiers::CONTROL),
+        };
+        Key { code, modifiers }
+    }
+
+    /// Whether the action also fires from the REPL view (where plain keys type text)
+    pub fn is_global(self) -> bool {
+        matches!(
+            self,
+            Self::Quit
+                | Self::Help
+                | Self::NextView
+                | Self::PrevView
+                | Self::Chat
+                | Self::Plan
+                | Self::Loops
+                | Self::Unpin
+                | Self::CycleSplit
+                | Self::GrowPane
+                | Self::ShrinkPane
+        )
+    }
+}
+
+/// A key with modifiers, normalized so `G` and `Shift+G` compare equal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl Key {
+    /// Normalize a key event (Shift is implied by the character itself)
+    pub fn from_event(event: KeyEvent) -> Self {
+        let mut modifiers = event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        if matches!(event.code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self {
+            code: event.code,
+            modifiers,
+        }
+    }
+
+    /// Parse a key spec: `q`, `G`, `ctrl-w`, `alt+x`, `tab`, `shift-tab`, `enter`, `f2`, `ctrl-left`
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        debug!(%spec, "Key::parse: called");
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = spec.trim();
+        loop {
+            let lower = rest.to_lowercase();
+            let stripped = ["ctrl-", "ctrl+", "alt-", "alt+", "shift-", "shift+"]
+                .iter()
+                .find(|p| lower.starts_with(*p) && rest.len() > p.len());
+            let Some(prefix) = stripped else { break };
+            match &prefix[..prefix.len() - 1] {
+                "ctrl" => modifiers |= KeyModifiers::CONTROL,
+                "alt" => modifiers |= KeyModifiers::ALT,
+                _ => modifiers |= KeyModifiers::SHIFT,
+            }
+            rest = &rest[prefix.len()..];
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => {
+                KeyCode::Char(if modifiers.contains(KeyModifiers::SHIFT) { c.to_ascii_uppercase() } else { c })
+            }
+            _ => match rest.to_lowercase().as_str() {
+                "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "space" => KeyCode::Char(' '),
+                "backspace" => KeyCode::Backspace,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                other => match other.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n) if (1..=12).contains(&n) => KeyCode::F(n),
+                    _ => return Err(format!("Unrecognized key '{}'", spec)),
+                },
+            },
+        };
+        Ok(Self::from_event(KeyEvent::new(code, modifiers)))
+    }
+
+    /// Short label for footers and help (e.g. `q`, `Ctrl+w`, `Tab`)
+    pub fn label(&self) -> String {
+        let mut label = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            label.push_str("Ctrl+");
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            label.push_str("Alt+");
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            label.push_str("Shift+");
+        }
+        match self.code {
+            KeyCode::Char(' ') => label.push_str("Space"),
+            KeyCode::Char(c) => label.push(c),
+            KeyCode::Tab => label.push_str("Tab"),
+            KeyCode::BackTab => label.push_str("Shift+Tab"),
+            KeyCode::Enter => label.push_str("Enter"),
+            KeyCode::Esc => label.push_str("Esc"),
+            KeyCode::Backspace => label.push_str("Backspace"),
+            KeyCode::Up => label.push('↑'),
+            KeyCode::Down => label.push('↓'),
+            KeyCode::Left => label.push('←'),
+            KeyCode::Right => label.push('→'),
+            KeyCode::Home => label.push_str("Home"),
+            KeyCode::End => label.push_str("End"),
+            KeyCode::PageUp => label.push_str("PgUp"),
+            KeyCode::PageDown => label.push_str("PgDn"),
+            KeyCode::F(n) => label.push_str(&format!("F{}", n)),
+            other => label.push_str(&format!("{:?}", other)),
+        }
+        label
+    }
+}
+
+/// Translation from user keys to the default keys App dispatches on
+#[derive(Debug, Clone, Default)]
+pub struct KeyMap {
+    /// Remapped key -> action
+    remapped: HashMap<Key, Action>,
+    /// Default keys that were remapped away and are now unbound
+    unbound: HashSet<Key>,
+    /// Effective key per remapped action (for labels)
+    bindings: HashMap<Action, Key>,
+}
+
+impl KeyMap {
+    /// Build from `tui.keys` (action name -> key spec)
+    pub fn from_config(keys: &BTreeMap<String, String>) -> Result<Self, String> {
+        debug!(count = keys.len(), "KeyMap::from_config: called");
+        let mut map = Self::default();
+        let mut taken: HashMap<Key, Action> = HashMap::new();
+
+        for (name, spec) in keys {
+            let action = Action::from_name(name).ok_or_else(|| format!("Unknown TUI action '{}'", name))?;
+            let key = Key::parse(spec)?;
+            if let Some(other) = taken.insert(key, action) {
+                return Err(format!(
+                    "Key '{}' is bound to both {} and {}",
+                    spec,
+                    other.name(),
+                    action.name()
+                ));
+            }
+            if key != action.default_key() {
+                map.remapped.insert(key, action);
+                map.unbound.insert(action.default_key());
+                map.bindings.insert(action, key);
+            }
+        }
+
+        // A default key stays unbound unless the user gave it a new action,
+        // and two actions may not end up on the same key
+        for (key, action) in &map.remapped {
+            map.unbound.remove(key);
+            if let Some(owner) = Action::ALL
+                .iter()
+                .find(|a| a.default_key() == *key && !map.bindings.contains_key(a))
+            {
+                return Err(format!(
+                    "Key '{}' for {} is already used by {}; remap {} too",
+                    key.label(),
+                    action.name(),
+                    owner.name(),
+                    owner.name()
+                ));
+            }
+        }
+        Ok(map)
+    }
+
+    /// Translate a key press to the key App dispatches on
+    ///
+    /// Returns None if the key was unbound by remapping. With `global_only`
+    /// (REPL view), only global actions are translated so typed text passes
+    /// through.
+    pub fn translate(&self, event: KeyEvent, global_only: bool) -> Option<KeyEvent> {
+        let key = Key::from_event(event);
+        if let Some(action) = self.remapped.get(&key) {
+            if global_only && !action.is_global() {
+                debug!(
+                    ?key,
+                    ?action,
+                    "KeyMap::translate: non-global action in REPL, passing through"
+                );
+                return Some(event);
+            }
+            let target = action.default_key();
+            debug!(?key, ?action, "KeyMap::translate: remapped");
+            return Some(KeyEvent::new(target.code, target.modifiers));
+        }
+        if self.unbound.contains(&key) {
+            debug!(?key, "KeyMap::translate: key unbound by remapping");
+            return None;
+        }
+        Some(event)
+    }
+
+    /// Label of the key currently bound to an action
+    pub fn label(&self, action: Action) -> String {
+        self.bindings
+            .get(&action)
+            .copied()
+            .unwrap_or_else(|| action.default_key())
+            .label()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs.iter().map(|(a, k)| (a.to_string(), k.to_string())).collect()
+    }
+
+    #[test]
+    fn test_parse_key_specs() {
+        assert_eq!(Key::parse("q").unwrap(), Action::Quit.default_key());
+        assert_eq!(Key::parse("ctrl-w").unwrap(), Action::CycleSplit.default_key());
+        assert_eq!(Key::parse("Ctrl+Right").unwrap(), Action::GrowPane.default_key());
+        assert_eq!(Key::parse("shift-tab").unwrap(), Action::PrevView.default_key());
+        assert_eq!(Key::parse("shift-g").unwrap(), Action::Bottom.default_key());
+        assert_eq!(Key::parse("f2").unwrap().code, KeyCode::F(2));
+        assert!(Key::parse("hyper-q").is_err());
+    }
+
+    #[test]
+    fn test_remap_translates_and_unbinds_default() {
+        let map = KeyMap::from_config(&keys(&[("quit", "Q")])).unwrap();
+
+        let translated = map.translate(KeyEvent::new(KeyCode::Char('Q'), KeyModifiers::SHIFT), false);
+        assert_eq!(translated.map(|k| k.code), Some(KeyCode::Char('q')));
+        assert!(map.translate(KeyEvent::from(KeyCode::Char('q')), false).is_none());
+        assert_eq!(map.label(Action::Quit), "Q");
+        assert_eq!(map.label(Action::Help), "?");
+    }
+
+    #[test]
+    fn test_swap_keys() {
+        let map = KeyMap::from_config(&keys(&[("down", "k"), ("up", "j")])).unwrap();
+
+        let down = map.translate(KeyEvent::from(KeyCode::Char('k')), false).unwrap();
+        assert_eq!(down.code, KeyCode::Char('j'));
+        let up = map.translate(KeyEvent::from(KeyCode::Char('j')), false).unwrap();
+        assert_eq!(up.code, KeyCode::Char('k'));
+    }
+
+    #[test]
+    fn test_repl_passes_through_non_global() {
+        let map = KeyMap::from_config(&keys(&[("pin", "z")])).unwrap();
+
+        let typed = map.translate(KeyEvent::from(KeyCode::Char('z')), true).unwrap();
+        assert_eq!(typed.code, KeyCode::Char('z'));
+        let pinned = map.translate(KeyEvent::from(KeyCode::Char('z')), false).unwrap();
+        assert_eq!(pinned.code, KeyCode::Char('p'));
+    }
+
+    #[test]
+    fn test_invalid_bindings() {
+        assert!(KeyMap::from_config(&keys(&[("teleport", "t")])).is_err());
+        assert!(KeyMap::from_config(&keys(&[("quit", "x")])).is_err()); // x is cancel
+        assert!(KeyMap::from_config(&keys(&[("quit", "z"), ("help", "z")])).is_err());
+    }
+}
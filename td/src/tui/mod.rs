@@ -5,22 +5,34 @@
 //! - Navigation with vim-style keybindings
 //! - Command mode for quick actions (:plans, :specs, :loops)
 //! - Filter mode for instant search (/)
+//! - Configurable color themes and key bindings (`tui:` config section)
+//!
+//! `td exec watch` uses the same widgets for a compact view of one execution.
 
-use tracing::debug;
+use tracing::{debug, warn};
 
 mod app;
+mod commands;
 mod conversation_log;
 mod events;
+mod keymap;
 mod runner;
 pub mod state;
+mod theme;
 pub mod tree;
 mod views;
+mod watch;
 
 pub use app::App;
+pub use commands::{CommandKind, CommandRegistry, SlashCommand};
 pub use events::{Event, EventHandler};
+pub use keymap::{Action, KeyMap};
 pub use runner::TuiRunner;
 pub use state::{AppState, InteractionMode, ReplMessage, ReplRole, TopLevelPane, View, current_pane};
+pub use theme::Theme;
+pub use watch::{ValidationState, WatchView, watch};
 
+use std::collections::HashMap;
 use std::io::{self, Stdout};
 use std::sync::Arc;
 
@@ -31,8 +43,9 @@ use eyre::Result;
 use ratatui::Terminal;
 use ratatui::backend::CrosstermBackend;
 
-use crate::config::DebugConfig;
+use crate::config::Config;
 use crate::llm::LlmClient;
+use crate::r#loop::LoopLoader;
 use crate::state::StateManager;
 
 /// Terminal type alias
@@ -72,17 +85,21 @@ pub async fn run(terminal: Tui) -> Result<()> {
 /// Run the TUI with StateManager connection for live data
 pub async fn run_with_state(state_manager: StateManager) -> Result<()> {
     debug!("run_with_state: called");
-    run_with_state_and_llm(state_manager, None, 16384, DebugConfig::default()).await
+    run_with_state_and_llm(state_manager, None, 16384, &Config::default()).await
 }
 
 /// Run the TUI with StateManager and optional LLM client for REPL
+///
+/// Layout changes are persisted to `config.source` (or the default config
+/// location when None). REPL slash commands declared by loop types are
+/// registered alongside the built-ins.
 pub async fn run_with_state_and_llm(
     state_manager: StateManager,
     llm_client: Option<Arc<dyn LlmClient>>,
     max_tokens: u32,
-    debug_config: DebugConfig,
+    config: &Config,
 ) -> Result<()> {
-    debug!(?debug_config, max_tokens, "run_with_state_and_llm: called");
+    debug!(debug_config = ?config.debug, max_tokens, "run_with_state_and_llm: called");
     // Session separator for easier log reading
     tracing::info!("********************************************************************************");
     tracing::info!("TUI session starting");
@@ -99,19 +116,52 @@ pub async fn run_with_state_and_llm(
     }
     let _guard = TerminalGuard;
 
-    let mut runner = if let Some(llm) = llm_client {
+    let tui_config = &config.tui;
+    let runner = if let Some(llm) = llm_client {
         debug!("run_with_state_and_llm: using LLM client");
         TuiRunner::with_llm_client(
             terminal,
             Some(state_manager),
             llm,
             max_tokens,
-            debug_config.log_conversations,
+            config.debug.log_conversations,
         )
     } else {
         debug!("run_with_state_and_llm: no LLM client");
         TuiRunner::with_state_manager(terminal, state_manager)
     };
+    let theme = Theme::load(&tui_config.theme, tui_config.themes_dir.as_deref()).unwrap_or_else(|e| {
+        warn!(error = %e, "run_with_state_and_llm: failed to load theme, using default");
+        Theme::default()
+    });
+    let keymap = KeyMap::from_config(&tui_config.keys).unwrap_or_else(|e| {
+        warn!(error = %e, "run_with_state_and_llm: invalid key bindings, using defaults");
+        KeyMap::default()
+    });
+    let mut commands = CommandRegistry::builtin();
+    let mut loop_max_iterations = HashMap::new();
+    match LoopLoader::new(&config.loops) {
+        Ok(loader) => {
+            let registered = commands.register_loop_types(&loader);
+            debug!(registered, "run_with_state_and_llm: registered loop-type commands");
+            loop_max_iterations = loader
+                .iter()
+                .map(|(name, loop_type)| (name.to_string(), loop_type.max_iterations))
+                .collect();
+        }
+        Err(e) => warn!(error = %e, "run_with_state_and_llm: failed to load loop types for REPL commands"),
+    }
+    let mut runner = runner
+        .with_layout(tui_config.layout, config.source.clone())
+        .with_appearance(theme, keymap)
+        .with_notifications(config.notifications.clone())
+        .with_dedup(config.dedup.clone())
+        .with_submitter(config.user.submitter())
+        .with_session_restore(tui_config.restore_session)
+        .with_saved_filters(tui_config.filters.clone())
+        .with_llm_config(config.llm.clone())
+        .with_loop_max_iterations(loop_max_iterations)
+        .with_commands(commands);
     runner.run().await
 }
 
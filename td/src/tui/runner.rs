@@ -7,6 +7,7 @@
 //! - Rendering at ~30 FPS
 //! - Processing REPL input with LLM streaming
 
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -16,27 +17,65 @@ use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
 use tracing::{debug, info, trace, warn};
 
-use crate::events::{Event as LoopEvent, EventBus, replay_execution_events};
+use crate::bulk::apply_all;
+use crate::compare::ExecutionComparison;
+use crate::config::{DedupConfig, LayoutConfig, LlmConfig, NotificationsConfig, save_tui_layout};
+use crate::domain::{CherryPick, ReplSession, SessionMessage, Submitter};
+use crate::events::{
+    BufferedSubscriber, DEFAULT_CHANNEL_CAPACITY, Event as LoopEvent, EventBus, EventLogEntry, OverflowPolicy,
+    TryRecvError as EventTryRecvError, default_runs_dir, read_execution_events, replay_execution_events,
+};
+use crate::ipc::DaemonClient;
 use crate::llm::{
     CompletionRequest, ContentBlock, LlmClient, Message, StopReason, StreamChunk, ToolCall, ToolDefinition,
+    create_client_from_resolved,
 };
-use crate::state::{StateEvent, StateManager, read_state_version};
+use crate::notify::{Notifier, ring_bell};
+use crate::state::{StateEvent, StateManager, StateWatcher, read_state_version};
+use crate::summary::Summary;
 use crate::tools::{ToolContext, ToolExecutor};
+use crate::worktree::{CherryPickResult, cherry_pick_to_branch};
 
 use super::Tui;
 use super::app::App;
+use super::commands::CommandRegistry;
 use super::conversation_log::ConversationLogger;
 use super::events::{Event, EventHandler};
+use super::keymap::KeyMap;
 use super::state::{
-    DaemonStatus, DescribeData, ExecutionInfo, ExecutionItem, LogEntry, PendingAction, PlanCreateRequest, RecordItem,
-    ReplMessage, ReplMode, ReplRole, View,
+    CommandRequest, DaemonStatus, DescribeData, ExecutionInfo, ExecutionItem, LogEntry, PendingAction,
+    PlanCreateRequest, RecordItem, ReplMessage, ReplMode, ReplRole, SessionRequest, View, event_iteration,
 };
+use super::theme::Theme;
 use super::views;
 use crate::daemon::DaemonManager;
 
 /// How often to refresh data from StateManager (250ms for responsive updates)
 const DATA_REFRESH_INTERVAL: Duration = Duration::from_millis(250);
 
+/// Fallback refresh interval while file notifications report changes as they happen
+const WATCHED_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often the writer token is renewed (well inside the daemon's lease)
+const WRITER_RENEW_INTERVAL: Duration = Duration::from_secs(20);
+
+/// Model assumed for cost estimation when no LLM config is provided
+const DEFAULT_REPL_MODEL: &str = "claude-sonnet-4";
+
+/// Tools offered to the LLM in the REPL (before `/tools off`)
+const REPL_TOOLS: &[&str] = &["read", "write", "edit", "list", "glob", "grep", "bash"];
+
+/// Maximum total size of files added with `/context ingest`
+const MAX_CONTEXT_BYTES: usize = 256 * 1024;
+
+/// A file added to the REPL context
+#[derive(Debug, Clone)]
+struct ContextFile {
+    /// Path relative to the worktree
+    path: String,
+    content: String,
+}
+
 /// Result from the background LLM task
 #[derive(Debug)]
 enum LlmTaskResult {
@@ -76,6 +115,18 @@ pub struct TuiRunner {
     worktree: PathBuf,
     /// LLM conversation history (separate from display history)
     repl_conversation: Vec<Message>,
+    /// Persisted session the REPL is writing to (None until the first save)
+    repl_session: Option<ReplSession>,
+    /// Restore the most recent session on startup
+    restore_session: bool,
+    /// LLM config for `/model` switching (None = switching unavailable)
+    llm_config: Option<LlmConfig>,
+    /// Current model ("provider/model"), used for `/model` and cost estimation
+    model: String,
+    /// REPL tools turned off with `/tools off`
+    disabled_tools: HashSet<String>,
+    /// Files added with `/context ingest`, appended to the system prompt
+    context_files: Vec<ContextFile>,
     /// System prompt for Chat mode REPL
     chat_system_prompt: String,
     /// System prompt for Plan mode REPL
@@ -100,16 +151,41 @@ pub struct TuiRunner {
     state_event_rx: Option<tokio::sync::broadcast::Receiver<StateEvent>>,
     /// Last known state version (for cross-process change detection)
     last_state_version: u64,
+    /// File notifications for cross-process changes (polling until `run` starts it)
+    state_watcher: StateWatcher,
 
     // === Event bus integration ===
     /// Event bus for observability events
     event_bus: Option<Arc<EventBus>>,
     /// Receiver for event bus events
-    event_bus_rx: Option<tokio::sync::broadcast::Receiver<LoopEvent>>,
+    event_bus_rx: Option<BufferedSubscriber>,
 
     // === Logs view state ===
     /// Track which execution's logs we've loaded to avoid reloading on every refresh
     logs_loaded_for: Option<String>,
+
+    // === Layout persistence ===
+    /// Config file to persist layout changes to (None = default location)
+    config_source: Option<PathBuf>,
+
+    // === Notifications ===
+    /// Rings the terminal bell on execution status transitions (None = bell disabled)
+    notifier: Option<Notifier>,
+
+    /// Duplicate detection for tasks created from the TUI
+    dedup: DedupConfig,
+
+    /// Recorded on executions created from the TUI; also picks out "mine" in the list
+    submitter: Option<Submitter>,
+
+    /// Each loop type's own max-iterations, shown next to overrides in Describe
+    loop_max_iterations: HashMap<String, u32>,
+
+    // === Daemon writer token ===
+    /// Writer token held for this TUI (None = no daemon, or read-only)
+    writer_token: Option<String>,
+    /// When the writer token was last claimed or renewed
+    last_writer_claim: Option<Instant>,
 }
 
 /// Progress updates from plan creation background task
@@ -149,6 +225,12 @@ impl TuiRunner {
             tool_executor: ToolExecutor::standard(),
             worktree,
             repl_conversation: Vec::new(),
+            repl_session: None,
+            restore_session: false,
+            llm_config: None,
+            model: DEFAULT_REPL_MODEL.to_string(),
+            disabled_tools: HashSet::new(),
+            context_files: Vec::new(),
             chat_system_prompt,
             plan_system_prompt,
             stream_rx: None,
@@ -159,9 +241,17 @@ impl TuiRunner {
             plan_task: None,
             state_event_rx: None,
             last_state_version: 0,
+            state_watcher: StateWatcher::polling(),
             event_bus: None,
             event_bus_rx: None,
             logs_loaded_for: None,
+            config_source: None,
+            notifier: None,
+            dedup: DedupConfig::default(),
+            submitter: None,
+            loop_max_iterations: HashMap::new(),
+            writer_token: None,
+            last_writer_claim: None,
         }
     }
 
@@ -187,6 +277,12 @@ impl TuiRunner {
             tool_executor: ToolExecutor::standard(),
             worktree,
             repl_conversation: Vec::new(),
+            repl_session: None,
+            restore_session: false,
+            llm_config: None,
+            model: DEFAULT_REPL_MODEL.to_string(),
+            disabled_tools: HashSet::new(),
+            context_files: Vec::new(),
             chat_system_prompt,
             plan_system_prompt,
             stream_rx: None,
@@ -197,9 +293,17 @@ impl TuiRunner {
             plan_task: None,
             state_event_rx,
             last_state_version: read_state_version(),
+            state_watcher: StateWatcher::polling(),
             event_bus: None,
             event_bus_rx: None,
             logs_loaded_for: None,
+            config_source: None,
+            notifier: None,
+            dedup: DedupConfig::default(),
+            submitter: None,
+            loop_max_iterations: HashMap::new(),
+            writer_token: None,
+            last_writer_claim: None,
         }
     }
 
@@ -242,6 +346,12 @@ impl TuiRunner {
             tool_executor: ToolExecutor::standard(),
             worktree,
             repl_conversation: Vec::new(),
+            repl_session: None,
+            restore_session: false,
+            llm_config: None,
+            model: DEFAULT_REPL_MODEL.to_string(),
+            disabled_tools: HashSet::new(),
+            context_files: Vec::new(),
             chat_system_prompt,
             plan_system_prompt,
             stream_rx: None,
@@ -252,9 +362,17 @@ impl TuiRunner {
             plan_task: None,
             state_event_rx,
             last_state_version: read_state_version(),
+            state_watcher: StateWatcher::polling(),
             event_bus: None,
             event_bus_rx: None,
             logs_loaded_for: None,
+            config_source: None,
+            notifier: None,
+            dedup: DedupConfig::default(),
+            submitter: None,
+            loop_max_iterations: HashMap::new(),
+            writer_token: None,
+            last_writer_claim: None,
         }
     }
 
@@ -323,7 +441,9 @@ Working directory: {}"#,
     pub fn with_event_bus(mut self, event_bus: Arc<EventBus>) -> Self {
         debug!("TuiRunner::with_event_bus: called");
         // Subscribe to event bus
-        self.event_bus_rx = Some(event_bus.subscribe());
+        // A slow frame should cost streamed tokens, not lifecycle events
+        self.event_bus_rx =
+            Some(event_bus.subscribe_buffered(DEFAULT_CHANNEL_CAPACITY, OverflowPolicy::DropTokenEventsFirst));
         self.event_bus = Some(event_bus);
         self
     }
@@ -335,10 +455,10 @@ Working directory: {}"#,
         self.event_bus.as_ref().map(|bus| bus.emitter_for(execution_id))
     }
 
-    /// Get the current system prompt based on REPL mode
-    fn current_system_prompt(&self) -> &str {
+    /// Get the current system prompt based on REPL mode, plus any ingested context
+    fn current_system_prompt(&self) -> String {
         debug!(repl_mode = ?self.app.state().repl_mode, "TuiRunner::current_system_prompt: called");
-        match self.app.state().repl_mode {
+        let base = match self.app.state().repl_mode {
             ReplMode::Chat => {
                 debug!("TuiRunner::current_system_prompt: returning chat prompt");
                 &self.chat_system_prompt
@@ -347,36 +467,126 @@ Working directory: {}"#,
                 debug!("TuiRunner::current_system_prompt: returning plan prompt");
                 &self.plan_system_prompt
             }
+        };
+        if self.context_files.is_empty() {
+            return base.clone();
         }
+
+        let mut prompt = format!(
+            "{}\n\n## Ingested Context\n\nThe user added these files to the conversation:\n",
+            base
+        );
+        for file in &self.context_files {
+            prompt.push_str(&format!("\n### {}\n```\n{}\n```\n", file.path, file.content));
+        }
+        prompt
     }
 
-    /// Get tool definitions for the REPL
+    /// Get tool definitions for the REPL (minus tools disabled with `/tools off`)
     fn get_tool_definitions(&self) -> Vec<ToolDefinition> {
-        debug!("TuiRunner::get_tool_definitions: called");
-        let tool_names = vec![
-            "read".to_string(),
-            "write".to_string(),
-            "edit".to_string(),
-            "list".to_string(),
-            "glob".to_string(),
-            "grep".to_string(),
-            "bash".to_string(),
-        ];
+        debug!(disabled = ?self.disabled_tools, "TuiRunner::get_tool_definitions: called");
+        let tool_names: Vec<String> = REPL_TOOLS
+            .iter()
+            .filter(|name| !self.disabled_tools.contains(**name))
+            .map(|name| name.to_string())
+            .collect();
 
         let definitions = self.tool_executor.definitions_for(&tool_names);
         debug!(count = definitions.len(), "TuiRunner::get_tool_definitions: returning");
         definitions
     }
 
+    /// Apply layout preferences and remember where to persist changes
+    pub fn with_layout(mut self, layout: LayoutConfig, config_source: Option<PathBuf>) -> Self {
+        debug!(?layout, ?config_source, "TuiRunner::with_layout: called");
+        self.app.state_mut().layout = layout;
+        self.config_source = config_source;
+        self
+    }
+
+    /// Ring the terminal bell on configured execution events
+    pub fn with_notifications(mut self, config: NotificationsConfig) -> Self {
+        debug!(bell = config.bell, "TuiRunner::with_notifications: called");
+        self.notifier = config.bell.then(|| Notifier::new(config));
+        self
+    }
+
+    /// Check new tasks for duplicates of unfinished executions
+    pub fn with_dedup(mut self, config: DedupConfig) -> Self {
+        debug!(?config, "TuiRunner::with_dedup: called");
+        self.dedup = config;
+        self
+    }
+
+    /// Attribute executions created from the TUI to `submitter`
+    pub fn with_submitter(mut self, submitter: Option<Submitter>) -> Self {
+        debug!(?submitter, "TuiRunner::with_submitter: called");
+        self.submitter = submitter;
+        self
+    }
+
+    /// Set each loop type's max-iterations, shown in Describe next to an execution's override
+    pub fn with_loop_max_iterations(mut self, max_iterations: HashMap<String, u32>) -> Self {
+        debug!(types = max_iterations.len(), "TuiRunner::with_loop_max_iterations: called");
+        self.loop_max_iterations = max_iterations;
+        self
+    }
+
+    /// Make saved execution filters available as `:filter <name>`
+    pub fn with_saved_filters(mut self, filters: BTreeMap<String, String>) -> Self {
+        debug!(count = filters.len(), "TuiRunner::with_saved_filters: called");
+        self.app.state_mut().saved_filters = filters;
+        self
+    }
+
+    /// Restore the most recently updated REPL session on startup
+    pub fn with_session_restore(mut self, restore: bool) -> Self {
+        debug!(restore, "TuiRunner::with_session_restore: called");
+        self.restore_session = restore;
+        self
+    }
+
+    /// Enable `/model` switching between the models in `config`
+    pub fn with_llm_config(mut self, config: LlmConfig) -> Self {
+        debug!(default = %config.default, "TuiRunner::with_llm_config: called");
+        self.model = config.default.clone();
+        self.llm_config = Some(config);
+        self
+    }
+
+    /// Use `commands` as the REPL slash-command registry
+    pub fn with_commands(mut self, commands: CommandRegistry) -> Self {
+        debug!("TuiRunner::with_commands: called");
+        self.app.state_mut().commands = commands;
+        self
+    }
+
+    /// Apply a color theme and key remapping
+    pub fn with_appearance(mut self, theme: Theme, keymap: KeyMap) -> Self {
+        debug!("TuiRunner::with_appearance: called");
+        let state = self.app.state_mut();
+        state.theme = theme;
+        state.keymap = keymap;
+        self
+    }
+
     /// Run the TUI main loop
     pub async fn run(&mut self) -> Result<()> {
         debug!("TuiRunner::run: called");
         // Fetch initial data if we have a state manager
-        if self.state_manager.is_some() {
+        if let Some(state_manager) = &self.state_manager {
             debug!("TuiRunner::run: state manager present, refreshing data");
+            self.state_watcher = StateWatcher::spawn(state_manager.store_path());
             self.refresh_data().await?;
         }
 
+        if self.restore_session {
+            debug!("TuiRunner::run: restoring last REPL session");
+            self.restore_latest_session().await;
+        }
+
+        self.claim_writer().await;
+
         debug!("TuiRunner::run: entering main loop");
         loop {
             // Process stream chunks for immediate display
@@ -406,6 +616,10 @@ Working directory: {}"#,
                         }
                     }
                 }
+                // Another process changed the state on disk
+                _ = self.state_watcher.changed() => {
+                    self.handle_state_file_change(true).await?;
+                }
                 // Handle plan progress messages immediately when they arrive
                 Some(progress) = async {
                     if let Some(rx) = &mut self.plan_progress_rx {
@@ -425,10 +639,85 @@ Working directory: {}"#,
             }
         }
 
+        self.release_writer().await;
         debug!("TuiRunner::run: exiting");
         Ok(())
     }
 
+    /// Refresh if another process bumped the state version (or, with `watched`, wrote the TaskStore)
+    async fn handle_state_file_change(&mut self, watched: bool) -> Result<()> {
+        let current_version = read_state_version();
+        if watched || current_version != self.last_state_version {
+            debug!(
+                old = self.last_state_version,
+                new = current_version,
+                "TuiRunner::handle_state_file_change: state version changed, refreshing"
+            );
+            self.last_state_version = current_version;
+            if self.state_manager.is_some() {
+                self.refresh_data().await?;
+                self.last_refresh = Instant::now();
+            }
+        }
+        Ok(())
+    }
+
+    /// Claim or renew the daemon's writer token
+    ///
+    /// Only one TUI (or CLI write) may change the daemon at a time. If another
+    /// holds the token this TUI goes read-only and keeps retrying, so it takes
+    /// over once the other one exits. Without a daemon nothing is gated.
+    async fn claim_writer(&mut self) {
+        self.last_writer_claim = Some(Instant::now());
+        let client = DaemonClient::new();
+        if !client.socket_exists() {
+            trace!("TuiRunner::claim_writer: no daemon socket");
+            self.writer_token = None;
+            self.app.state_mut().read_only = None;
+            return;
+        }
+        let holder = format!("tui-{}", std::process::id());
+        match client.claim_writer(&holder).await {
+            Ok(Ok(token)) => {
+                if self.app.state().read_only.is_some() {
+                    info!("Writer token acquired, leaving read-only mode");
+                }
+                self.writer_token = Some(token);
+                self.app.state_mut().read_only = None;
+            }
+            Ok(Err(message)) => {
+                debug!(%message, "TuiRunner::claim_writer: refused, read-only");
+                self.writer_token = None;
+                self.app.state_mut().read_only = Some(message);
+            }
+            Err(e) => {
+                debug!(error = %e, "TuiRunner::claim_writer: daemon unreachable");
+                self.writer_token = None;
+                self.app.state_mut().read_only = None;
+            }
+        }
+    }
+
+    /// Give the writer token back on exit so another TUI can take over
+    async fn release_writer(&mut self) {
+        if let Some(token) = self.writer_token.take() {
+            debug!("TuiRunner::release_writer: releasing writer token");
+            if let Err(e) = DaemonClient::new().release_writer(&token).await {
+                debug!(error = %e, "TuiRunner::release_writer: release failed");
+            }
+        }
+    }
+
+    /// Refuse a state-changing request while read-only; true if refused
+    fn refuse_if_read_only(&mut self, what: &str) -> bool {
+        let Some(reason) = self.app.state().read_only.clone() else {
+            return false;
+        };
+        debug!(%what, %reason, "TuiRunner::refuse_if_read_only: refused");
+        self.app.state_mut().set_error(format!("Cannot {}: {}", what, reason));
+        true
+    }
+
     /// Handle a single plan progress message
     fn handle_plan_progress(&mut self, progress: PlanProgress) {
         debug!(?progress, "TuiRunner::handle_plan_progress: called");
@@ -459,6 +748,10 @@ Working directory: {}"#,
                     "\n\n---\nPlan created: {} ({})\nView in Executions tab (Tab to switch).",
                     title, exec_id
                 )));
+                if let Some(session) = &mut self.repl_session {
+                    session.add_plan(&exec_id);
+                }
+                self.app.state_mut().repl_session_dirty = true;
                 // Clear plan creating flag
                 self.app.state_mut().plan_creating = false;
                 self.plan_progress_rx = None;
@@ -495,11 +788,21 @@ Working directory: {}"#,
         // Check for LLM task results
         self.process_llm_results().await;
 
+        // Keep the writer token (or retry taking it over while read-only)
+        if self
+            .last_writer_claim
+            .is_none_or(|t| t.elapsed() >= WRITER_RENEW_INTERVAL)
+        {
+            self.claim_writer().await;
+        }
+
         // Check for pending task to start
         if let Some(task) = self.app.state_mut().pending_task.take() {
             debug!(%task, "TuiRunner::handle_tick: pending task");
-            info!("Starting task: {}", task);
-            self.start_task(&task).await;
+            if !self.refuse_if_read_only("start a task") {
+                info!("Starting task: {}", task);
+                self.start_task(&task).await;
+            }
         }
 
         // Check for pending plan creation - spawn background task
@@ -508,18 +811,44 @@ Working directory: {}"#,
                 message_count = request.messages.len(),
                 "TuiRunner::handle_tick: pending plan create"
             );
-            info!("Creating plan from {} messages", request.messages.len());
-            self.start_plan_creation(request);
+            if !self.refuse_if_read_only("create a plan") {
+                info!("Creating plan from {} messages", request.messages.len());
+                self.start_plan_creation(request);
+            }
         }
 
         // Process plan creation progress
         self.process_plan_progress().await;
 
+        // Session commands (/sessions, /resume, /clear)
+        if let Some(request) = self.app.state_mut().pending_session.take() {
+            debug!(?request, "TuiRunner::handle_tick: pending session request");
+            self.handle_session_request(request).await;
+        }
+
+        // REPL commands that need the runner (/model, /tools, /context)
+        if let Some(request) = self.app.state_mut().pending_command.take() {
+            debug!(?request, "TuiRunner::handle_tick: pending command request");
+            self.handle_command_request(request);
+        }
+
+        // Persist the REPL session after each completed response
+        if std::mem::take(&mut self.app.state_mut().repl_session_dirty) {
+            self.save_repl_session().await;
+        }
+
+        // Persist layout changes made via keybindings
+        if std::mem::take(&mut self.app.state_mut().layout_dirty) {
+            self.save_layout();
+        }
+
         // Check for pending action (cancel/pause/resume/start draft)
         if let Some(action) = self.app.state_mut().pending_action.take() {
             debug!(?action, "TuiRunner::handle_tick: pending action");
-            info!("Executing action: {:?}", action);
-            self.execute_action(action).await;
+            if !self.refuse_if_read_only("change executions") {
+                info!("Executing action: {:?}", action);
+                self.execute_action(action).await;
+            }
         }
 
         // Process event bus events (streaming validation output, etc.)
@@ -528,23 +857,18 @@ Working directory: {}"#,
         // Check for state change events (instant refresh on new executions - same process)
         self.process_state_events().await?;
 
-        // Check for cross-process state changes via notification file
-        let current_version = read_state_version();
-        if current_version != self.last_state_version {
-            debug!(
-                old = self.last_state_version,
-                new = current_version,
-                "TuiRunner::handle_tick: state version changed, refreshing"
-            );
-            self.last_state_version = current_version;
-            if self.state_manager.is_some() {
-                self.refresh_data().await?;
-                self.last_refresh = Instant::now();
-            }
+        // Check for cross-process state changes via notification file (the watcher reports them as they happen)
+        if !self.state_watcher.is_event_driven() {
+            self.handle_state_file_change(false).await?;
         }
 
         // Refresh data if interval has elapsed (fallback polling)
-        if self.state_manager.is_some() && self.last_refresh.elapsed() >= DATA_REFRESH_INTERVAL {
+        let refresh_interval = if self.state_watcher.is_event_driven() {
+            WATCHED_REFRESH_INTERVAL
+        } else {
+            DATA_REFRESH_INTERVAL
+        };
+        if self.state_manager.is_some() && self.last_refresh.elapsed() >= refresh_interval {
             debug!("TuiRunner::handle_tick: refreshing data");
             self.refresh_data().await?;
             self.last_refresh = Instant::now();
@@ -565,34 +889,14 @@ Working directory: {}"#,
                         if let View::Logs { ref target_id } = self.app.state().current_view
                             && event.execution_id() == target_id
                         {
-                            // Convert event to log entry for display
-                            let log_entry = LogEntry {
-                                iteration: match &event {
-                                    LoopEvent::IterationStarted { iteration, .. }
-                                    | LoopEvent::IterationCompleted { iteration, .. }
-                                    | LoopEvent::PromptSent { iteration, .. }
-                                    | LoopEvent::TokenReceived { iteration, .. }
-                                    | LoopEvent::ResponseCompleted { iteration, .. }
-                                    | LoopEvent::ToolCallStarted { iteration, .. }
-                                    | LoopEvent::ToolCallCompleted { iteration, .. }
-                                    | LoopEvent::ValidationStarted { iteration, .. }
-                                    | LoopEvent::ValidationOutput { iteration, .. }
-                                    | LoopEvent::ValidationCompleted { iteration, .. } => *iteration,
-                                    _ => 0,
-                                },
-                                text: format_event_for_display(&event),
-                                is_error: matches!(event, LoopEvent::Error { .. }),
-                                is_stdout: matches!(event, LoopEvent::ValidationOutput { is_stderr: false, .. }),
-                            };
-                            self.app.state_mut().logs.push(log_entry);
+                            let state = self.app.state_mut();
+                            state.apply_log_event(&event, chrono::Utc::now());
+                            state.logs.push(log_entry_for_event(&event));
                         }
                     }
-                    Err(tokio::sync::broadcast::error::TryRecvError::Empty) => break,
-                    Err(tokio::sync::broadcast::error::TryRecvError::Lagged(n)) => {
-                        warn!("Event bus lagged, missed {} events", n);
-                    }
-                    Err(tokio::sync::broadcast::error::TryRecvError::Closed) => {
-                        debug!("Event bus channel closed");
+                    Err(EventTryRecvError::Empty) => break,
+                    Err(EventTryRecvError::Closed) => {
+                        debug!(dropped = rx.stats().dropped, "Event bus channel closed");
                         self.event_bus_rx = None;
                         break;
                     }
@@ -814,6 +1118,7 @@ Working directory: {}"#,
         // Execute tools and collect results
         let ctx = ToolContext::new_unsandboxed(self.worktree.clone(), "repl".to_string());
         let mut result_blocks: Vec<ContentBlock> = Vec::new();
+        let mut image_blocks: Vec<ContentBlock> = Vec::new();
 
         for tc in &tool_calls {
             info!("Executing tool: {} (id={})", tc.name, tc.id);
@@ -853,7 +1158,10 @@ Working directory: {}"#,
                 .push(ReplMessage::tool_result_with_args(&tc.name, tool_args, &result.content));
 
             result_blocks.push(ContentBlock::tool_result(&tc.id, &result.content, result.is_error));
+            image_blocks.extend(result.images);
         }
+        // Images must follow every tool result in the message
+        result_blocks.extend(image_blocks);
 
         info!(
             "All {} tools executed, adding results to conversation",
@@ -919,7 +1227,8 @@ Working directory: {}"#,
         // Set streaming state with fun word and start time
         self.app.state_mut().repl_streaming = true;
         self.app.state_mut().repl_response_buffer.clear();
-        self.app.state_mut().start_streaming("claude-sonnet-4"); // Default model for cost estimation
+        let model = self.model.clone();
+        self.app.state_mut().start_streaming(&model);
 
         // Create channel for streaming chunks
         let (stream_tx, stream_rx) = mpsc::channel::<StreamChunk>(100);
@@ -931,7 +1240,7 @@ Working directory: {}"#,
 
         // Build request (use current system prompt based on mode)
         let request = CompletionRequest {
-            system_prompt: self.current_system_prompt().to_string(),
+            system_prompt: self.current_system_prompt(),
             messages: self.repl_conversation.clone(),
             tools: self.get_tool_definitions(),
             max_tokens: self.max_tokens,
@@ -986,7 +1295,8 @@ Working directory: {}"#,
         // Set streaming state with fun word and start time
         self.app.state_mut().repl_streaming = true;
         self.app.state_mut().repl_response_buffer.clear();
-        self.app.state_mut().start_streaming("claude-sonnet-4"); // Default model for cost estimation
+        let model = self.model.clone();
+        self.app.state_mut().start_streaming(&model);
 
         // Create channel for streaming chunks
         let (stream_tx, stream_rx) = mpsc::channel::<StreamChunk>(100);
@@ -998,7 +1308,7 @@ Working directory: {}"#,
 
         // Build request with current conversation (includes tool results)
         let request = CompletionRequest {
-            system_prompt: self.current_system_prompt().to_string(),
+            system_prompt: self.current_system_prompt(),
             messages: self.repl_conversation.clone(),
             tools: self.get_tool_definitions(),
             max_tokens: self.max_tokens,
@@ -1063,16 +1373,19 @@ Working directory: {}"#,
             .unwrap_or_else(|| "New Task".to_string());
 
         // Create a plan execution - use the short title for the ID slug
-        let mut execution = crate::domain::LoopExecution::new("plan", &title);
+        let mut execution = crate::domain::LoopExecution::new("plan", &title).with_submitter(self.submitter.clone());
         execution.set_title(title);
         // Store the original task in context
         execution.set_context(serde_json::json!({ "user-request": task }));
         // New plans start as draft - user must approve before they run
         execution.set_status(crate::domain::LoopExecutionStatus::Draft);
 
-        match state_manager.create_execution(execution).await {
-            Ok(id) => {
+        match state_manager.submit_execution_checked(execution, &self.dedup).await {
+            Ok((id, duplicate)) => {
                 debug!("Created plan loop {}", id);
+                if let Some(duplicate) = duplicate {
+                    self.app.state_mut().set_error(duplicate.to_string());
+                }
                 // Force a refresh to show the new loop
                 self.last_refresh = Instant::now() - DATA_REFRESH_INTERVAL;
             }
@@ -1084,7 +1397,7 @@ Working directory: {}"#,
     }
 
     /// Start plan creation in a background task (non-blocking)
-    fn start_plan_creation(&mut self, request: PlanCreateRequest) {
+    fn start_plan_creation(&mut self, mut request: PlanCreateRequest) {
         debug!(
             message_count = request.messages.len(),
             "TuiRunner::start_plan_creation: called"
@@ -1128,13 +1441,32 @@ Working directory: {}"#,
         let (progress_tx, progress_rx) = mpsc::channel::<PlanProgress>(100);
         self.plan_progress_rx = Some(progress_rx);
 
+        // Link the plan to the session it came from so the planning
+        // conversation can be resumed later
+        let mode = self.app.state().repl_mode.name();
+        let session_id = self
+            .repl_session
+            .get_or_insert_with(|| ReplSession::new(mode))
+            .id
+            .clone();
+
         // Clone what we need for the background task
+        request.submitter = self.submitter.clone();
         let worktree = self.worktree.clone();
         let max_tokens = self.max_tokens;
 
         // Spawn background task
         self.plan_task = Some(tokio::spawn(async move {
-            Self::run_plan_creation(request, llm, state_manager, worktree, max_tokens, progress_tx).await;
+            Self::run_plan_creation(
+                request,
+                session_id,
+                llm,
+                state_manager,
+                worktree,
+                max_tokens,
+                progress_tx,
+            )
+            .await;
         }));
 
         info!("Plan creation background task spawned");
@@ -1330,6 +1662,38 @@ Working directory: {}"#,
                     self.app.state_mut().clear_live_output(execution_id);
                     should_refresh = true;
                 }
+                StateEvent::DeadlockDetected { execution_id, cycle } => {
+                    debug!(%execution_id, ?cycle, "process_state_events: deadlock detected");
+                    self.app
+                        .state_mut()
+                        .set_error(format!("Deadlock detected: {}", cycle.join(" → ")));
+                }
+                StateEvent::PathConflict {
+                    execution_id,
+                    path,
+                    held_by,
+                } => {
+                    debug!(%execution_id, %path, %held_by, "process_state_events: path conflict");
+                    self.app
+                        .state_mut()
+                        .set_error(format!("{}: {} is locked by {}", execution_id, path, held_by));
+                }
+                StateEvent::ResourcePressure { exceeded } => {
+                    debug!(?exceeded, "process_state_events: resource pressure");
+                    self.app.state_mut().set_error(format!(
+                        "Daemon under resource pressure, not picking up executions: {}",
+                        exceeded.join(", ")
+                    ));
+                }
+                StateEvent::ProviderCircuit { open, resumed } => {
+                    debug!(open, resumed, "process_state_events: provider circuit");
+                    let message = if *open {
+                        "Provider failing, executions waiting for it to recover".to_string()
+                    } else {
+                        format!("Provider recovered, resumed {} execution(s)", resumed)
+                    };
+                    self.app.state_mut().set_error(message);
+                }
             }
         }
 
@@ -1373,6 +1737,7 @@ Working directory: {}"#,
     /// Uses consolidated Rule of Five prompt - LLM self-reviews in a single call
     async fn run_plan_creation(
         request: PlanCreateRequest,
+        session_id: String,
         llm: Arc<dyn LlmClient>,
         state_manager: StateManager,
         worktree: PathBuf,
@@ -1381,6 +1746,7 @@ Working directory: {}"#,
     ) {
         debug!(
             message_count = request.messages.len(),
+            %session_id,
             "TuiRunner::run_plan_creation: called"
         );
         info!("=== run_plan_creation START (Rule of Five) ===");
@@ -1468,12 +1834,13 @@ Working directory: {}"#,
         info!("Extracted final plan: {} chars", final_plan.len());
 
         // Create the plan execution with Draft status
-        let mut execution = crate::domain::LoopExecution::new("plan", &title);
+        let mut execution = crate::domain::LoopExecution::new("plan", &title).with_submitter(request.submitter.clone());
         execution.set_title(title.clone());
         execution.set_status(crate::domain::LoopExecutionStatus::Draft);
 
         execution.set_context(serde_json::json!({
-            "user-request": conversation_text
+            "user-request": conversation_text,
+            "planning-session": session_id
         }));
 
         // Create the execution record
@@ -1753,6 +2120,25 @@ Working directory: {}"#,
                     }
                 }
             }
+            PendingAction::Bulk(action, ids) => {
+                debug!(%action, count = ids.len(), "TuiRunner::execute_action: Bulk");
+                let outcome = apply_all(state_manager, action, &ids).await;
+                self.app.state_mut().marked_executions.clear();
+                self.last_refresh = Instant::now() - DATA_REFRESH_INTERVAL;
+                if !outcome.failed.is_empty() {
+                    warn!(%action, failed = outcome.failed.len(), "Bulk action failed for some executions");
+                    let (id, error) = &outcome.failed[0];
+                    self.app.state_mut().set_error(format!(
+                        "{} {} of {}; {} failed ({}: {})",
+                        action.past_tense(),
+                        outcome.done.len(),
+                        ids.len(),
+                        outcome.failed.len(),
+                        id,
+                        error
+                    ));
+                }
+            }
             PendingAction::ActivateDraft(id) => {
                 debug!("Activating draft: {}", id);
                 match state_manager.activate_draft(&id).await {
@@ -1767,6 +2153,351 @@ Working directory: {}"#,
                     }
                 }
             }
+            PendingAction::CherryPick(id) => {
+                debug!("Cherry-picking commits of execution: {}", id);
+                let repo_root = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+                let branch = format!("picked/{}", id);
+                match cherry_pick_to_branch(&repo_root, &id, None, &branch, "main").await {
+                    Ok(CherryPickResult::Success { branch, commits }) => {
+                        debug!("Picked {} commits of {} onto {}", commits.len(), id, branch);
+                        let pick = CherryPick {
+                            branch,
+                            base: "main".to_string(),
+                            commits,
+                            created_at: taskstore::now_ms(),
+                        };
+                        if let Err(e) = state_manager.record_cherry_pick(&id, pick).await {
+                            warn!("Failed to record cherry-pick: {}", e);
+                            self.app
+                                .state_mut()
+                                .set_error(format!("Branch created, but failed to record it: {}", e));
+                        }
+                        self.last_refresh = Instant::now() - DATA_REFRESH_INTERVAL;
+                        self.app.state_mut().describe_data = None; // Force reload
+                    }
+                    Ok(CherryPickResult::Conflict { commit, .. }) => {
+                        warn!("Cherry-pick of {} conflicted at {}", id, commit);
+                        self.app.state_mut().set_error(format!(
+                            "Commit {} does not apply cleanly onto main; no branch created",
+                            commit
+                        ));
+                    }
+                    Err(e) => {
+                        warn!("Failed to cherry-pick: {}", e);
+                        self.app.state_mut().set_error(format!("Failed to cherry-pick: {}", e));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Persist the current REPL session (display history, conversation, usage)
+    async fn save_repl_session(&mut self) {
+        debug!("TuiRunner::save_repl_session: called");
+        let Some(state_manager) = &self.state_manager else {
+            debug!("TuiRunner::save_repl_session: no state manager");
+            return;
+        };
+
+        let state = self.app.state();
+        let mode = state.repl_mode.name();
+        let mut session = self.repl_session.take().unwrap_or_else(|| ReplSession::new(mode));
+        session.update(
+            mode,
+            state.repl_history.iter().map(SessionMessage::from).collect(),
+            self.repl_conversation.clone(),
+        );
+        let session = session.with_usage(
+            state.session_input_tokens,
+            state.session_output_tokens,
+            state.session_cost_usd,
+        );
+
+        if let Err(e) = state_manager.save_repl_session(session.clone()).await {
+            warn!("Failed to save REPL session: {}", e);
+        }
+        self.repl_session = Some(session);
+    }
+
+    /// Handle a queued session command
+    async fn handle_session_request(&mut self, request: SessionRequest) {
+        debug!(?request, "TuiRunner::handle_session_request: called");
+        if request == SessionRequest::New {
+            debug!("TuiRunner::handle_session_request: starting new session");
+            self.repl_session = None;
+            self.repl_conversation.clear();
+            self.context_files.clear();
+            let state = self.app.state_mut();
+            state.session_input_tokens = 0;
+            state.session_output_tokens = 0;
+            state.session_cost_usd = 0.0;
+            return;
+        }
+
+        let Some(state_manager) = &self.state_manager else {
+            debug!("TuiRunner::handle_session_request: no state manager");
+            self.app
+                .state_mut()
+                .set_error("Sessions are unavailable without a state store");
+            return;
+        };
+        let sessions = match state_manager.list_repl_sessions().await {
+            Ok(sessions) => sessions,
+            Err(e) => {
+                warn!("Failed to list REPL sessions: {}", e);
+                self.app
+                    .state_mut()
+                    .set_error(format!("Failed to list sessions: {}", e));
+                return;
+            }
+        };
+
+        match request {
+            SessionRequest::List => {
+                let current = self.repl_session.as_ref().map(|s| s.id.as_str());
+                let listing = format_session_list(&sessions, current);
+                let state = self.app.state_mut();
+                state.repl_history.push(ReplMessage::assistant(listing));
+                state.repl_scroll = None;
+            }
+            SessionRequest::Resume(query) => {
+                let mut matches: Vec<ReplSession> = sessions.into_iter().filter(|s| s.id.contains(&query)).collect();
+                if let Some(exact) = matches.iter().position(|s| s.id == query) {
+                    matches = vec![matches.swap_remove(exact)];
+                }
+                match matches.len() {
+                    1 => {
+                        let session = matches.remove(0);
+                        info!("Resuming REPL session {}", session.id);
+                        self.apply_repl_session(session);
+                    }
+                    0 => {
+                        debug!(%query, "TuiRunner::handle_session_request: no match");
+                        self.app
+                            .state_mut()
+                            .set_error(format!("No session matching '{}'", query));
+                    }
+                    n => {
+                        debug!(%query, n, "TuiRunner::handle_session_request: ambiguous");
+                        self.app
+                            .state_mut()
+                            .set_error(format!("'{}' matches {} sessions; use more of the ID", query, n));
+                    }
+                }
+            }
+            SessionRequest::New => unreachable!("handled above"),
+        }
+    }
+
+    /// Handle a queued REPL command (/model, /tools, /context)
+    fn handle_command_request(&mut self, request: CommandRequest) {
+        debug!(?request, "TuiRunner::handle_command_request: called");
+        let result = match request {
+            CommandRequest::Model(None) => Ok(self.describe_models()),
+            CommandRequest::Model(Some(name)) => self.switch_model(&name),
+            CommandRequest::ListTools => Ok(self.describe_tools()),
+            CommandRequest::SetTool { name, enabled } => self.set_tool_enabled(&name, enabled),
+            CommandRequest::IngestContext(pattern) => self.ingest_context(&pattern),
+            CommandRequest::ListContext => Ok(self.describe_context()),
+            CommandRequest::ClearContext => {
+                let count = self.context_files.len();
+                self.context_files.clear();
+                Ok(format!("Removed {} file(s) from context.", count))
+            }
+        };
+
+        let state = self.app.state_mut();
+        match result {
+            Ok(message) => {
+                state.repl_history.push(ReplMessage::assistant(message));
+                state.repl_scroll = None;
+            }
+            Err(e) => state.set_error(e),
+        }
+    }
+
+    /// Current model and the models `/model` can switch to
+    fn describe_models(&self) -> String {
+        debug!("TuiRunner::describe_models: called");
+        let mut out = format!("Current model: {}\n", self.model);
+        match &self.llm_config {
+            Some(config) => {
+                out.push_str("Available (/model <name>):\n");
+                for spec in config.available_models() {
+                    let marker = if spec == self.model { "*" } else { " " };
+                    out.push_str(&format!("{} {}\n", marker, spec));
+                }
+            }
+            None => out.push_str("Model switching is unavailable (no LLM config).\n"),
+        }
+        out
+    }
+
+    /// Switch the REPL to another configured model
+    ///
+    /// The conversation carries over; only subsequent requests use the new model.
+    fn switch_model(&mut self, name: &str) -> Result<String, String> {
+        debug!(%name, "TuiRunner::switch_model: called");
+        let config = self
+            .llm_config
+            .as_ref()
+            .ok_or("Model switching is unavailable (no LLM config)")?;
+        let resolved = config.resolve_model_name(name).map_err(|e| e.to_string())?;
+        let client = create_client_from_resolved(&resolved).map_err(|e| e.to_string())?;
+
+        self.llm_client = Some(client);
+        self.max_tokens = resolved.max_tokens;
+        self.model = format!("{}/{}", resolved.provider, resolved.model);
+        info!("REPL switched to model {}", self.model);
+        Ok(format!("Switched to {}.", self.model))
+    }
+
+    /// REPL tools and whether each is enabled
+    fn describe_tools(&self) -> String {
+        debug!("TuiRunner::describe_tools: called");
+        let mut out = String::from("REPL tools (/tools on|off <name>):\n");
+        for name in REPL_TOOLS {
+            let status = if self.disabled_tools.contains(*name) { "off" } else { "on" };
+            out.push_str(&format!("  {:<6} {}\n", name, status));
+        }
+        out
+    }
+
+    /// Enable or disable a REPL tool
+    fn set_tool_enabled(&mut self, name: &str, enabled: bool) -> Result<String, String> {
+        debug!(%name, enabled, "TuiRunner::set_tool_enabled: called");
+        if !REPL_TOOLS.contains(&name) {
+            return Err(format!("Unknown tool '{}'. Available: {}", name, REPL_TOOLS.join(", ")));
+        }
+        if enabled {
+            self.disabled_tools.remove(name);
+        } else {
+            self.disabled_tools.insert(name.to_string());
+        }
+        Ok(format!(
+            "Tool '{}' {}.",
+            name,
+            if enabled { "enabled" } else { "disabled" }
+        ))
+    }
+
+    /// Add files matching `pattern` (relative to the worktree) to the context
+    ///
+    /// Files already in the context are refreshed. Non-UTF-8 files are skipped,
+    /// and ingestion stops once the context reaches MAX_CONTEXT_BYTES.
+    fn ingest_context(&mut self, pattern: &str) -> Result<String, String> {
+        debug!(%pattern, "TuiRunner::ingest_context: called");
+        let full_pattern = self.worktree.join(pattern);
+        let paths = glob::glob(&full_pattern.to_string_lossy()).map_err(|e| format!("Invalid glob: {}", e))?;
+
+        let mut added = 0;
+        let mut skipped = 0;
+        let mut truncated = false;
+        for path in paths.filter_map(|p| p.ok()).filter(|p| p.is_file()) {
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                debug!(?path, "TuiRunner::ingest_context: skipping unreadable file");
+                skipped += 1;
+                continue;
+            };
+            let relative = path
+                .strip_prefix(&self.worktree)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .to_string();
+            self.context_files.retain(|f| f.path != relative);
+
+            let used: usize = self.context_files.iter().map(|f| f.content.len()).sum();
+            if used + content.len() > MAX_CONTEXT_BYTES {
+                debug!(?path, used, "TuiRunner::ingest_context: context budget exhausted");
+                truncated = true;
+                break;
+            }
+            self.context_files.push(ContextFile {
+                path: relative,
+                content,
+            });
+            added += 1;
+        }
+
+        if added == 0 && !truncated {
+            return Err(format!("No readable files match '{}'", pattern));
+        }
+        let mut message = format!(
+            "Added {} file(s) to context ({} total).",
+            added,
+            self.context_files.len()
+        );
+        if skipped > 0 {
+            message.push_str(&format!(" Skipped {} unreadable file(s).", skipped));
+        }
+        if truncated {
+            message.push_str(&format!(
+                " Stopped at the {} KB context limit.",
+                MAX_CONTEXT_BYTES / 1024
+            ));
+        }
+        Ok(message)
+    }
+
+    /// Files currently in the context
+    fn describe_context(&self) -> String {
+        debug!("TuiRunner::describe_context: called");
+        if self.context_files.is_empty() {
+            return "No files in context. Use /context ingest <glob>.".to_string();
+        }
+        let mut out = String::from("Context files:\n");
+        for file in &self.context_files {
+            out.push_str(&format!("  {} ({} bytes)\n", file.path, file.content.len()));
+        }
+        out
+    }
+
+    /// Restore the most recently updated session (startup)
+    async fn restore_latest_session(&mut self) {
+        debug!("TuiRunner::restore_latest_session: called");
+        let Some(state_manager) = &self.state_manager else {
+            debug!("TuiRunner::restore_latest_session: no state manager");
+            return;
+        };
+        match state_manager.list_repl_sessions().await {
+            Ok(sessions) => {
+                if let Some(session) = sessions.into_iter().next() {
+                    info!("Restoring REPL session {}", session.id);
+                    self.apply_repl_session(session);
+                }
+            }
+            Err(e) => warn!("Failed to restore REPL session: {}", e),
+        }
+    }
+
+    /// Replace the REPL state with a saved session
+    fn apply_repl_session(&mut self, session: ReplSession) {
+        debug!(session_id = %session.id, "TuiRunner::apply_repl_session: called");
+        let state = self.app.state_mut();
+        state.repl_history = session.messages.iter().cloned().map(ReplMessage::from).collect();
+        state.repl_mode = ReplMode::from_name(&session.mode);
+        state.repl_response_buffer.clear();
+        state.repl_scroll = None;
+        state.session_input_tokens = session.input_tokens;
+        state.session_output_tokens = session.output_tokens;
+        state.session_cost_usd = session.cost_usd;
+        self.repl_conversation = session.conversation.clone();
+        self.repl_session = Some(session);
+    }
+
+    /// Write the current layout preferences to the config file
+    fn save_layout(&mut self) {
+        let layout = self.app.state().layout;
+        debug!(?layout, "TuiRunner::save_layout: called");
+        match save_tui_layout(self.config_source.as_deref(), &layout) {
+            Ok(path) => {
+                debug!(?path, "TuiRunner::save_layout: saved");
+                self.config_source = Some(path);
+            }
+            Err(e) => {
+                warn!("Failed to save layout preferences: {}", e);
+                self.app.state_mut().set_error(format!("Failed to save layout: {}", e));
+            }
         }
     }
 
@@ -1829,6 +2560,7 @@ Working directory: {}"#,
             DaemonStatus::Disconnected
         };
         self.app.state_mut().daemon_status = daemon_status;
+        self.app.state_mut().maintenance = daemon.maintenance().is_some();
 
         let state_manager = match &self.state_manager {
             Some(sm) => {
@@ -1844,6 +2576,22 @@ Working directory: {}"#,
         // Sync Loop records and build exec_id -> artifact map
         let mut artifacts_by_exec_id: std::collections::HashMap<String, RecordItem> = std::collections::HashMap::new();
 
+        // Typed plan/spec records add acceptance criteria and decomposition links
+        let plans: std::collections::HashMap<String, crate::domain::Plan> = state_manager
+            .list_plans(None)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|p| (p.id.clone(), p))
+            .collect();
+        let specs: std::collections::HashMap<String, crate::domain::Spec> = state_manager
+            .list_specs(None, None)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|s| (s.id.clone(), s))
+            .collect();
+
         match state_manager.list_loops(None, None, None).await {
             Ok(loops) => {
                 let items: Vec<RecordItem> = loops
@@ -1859,17 +2607,37 @@ Working directory: {}"#,
                         // Extract exec_id from context if present
                         let exec_id = l.context.get("exec_id").and_then(|v| v.as_str()).map(|s| s.to_string());
 
+                        let (children_count, criteria, links) = match (plans.get(&l.id), specs.get(&l.id)) {
+                            (Some(plan), _) => (
+                                plan.spec_ids.len(),
+                                plan.acceptance.len().to_string(),
+                                format!("{} specs", plan.spec_ids.len()),
+                            ),
+                            (None, Some(spec)) => (
+                                0,
+                                spec.acceptance.len().to_string(),
+                                spec.plan_id
+                                    .as_ref()
+                                    .and_then(|id| plans.get(id))
+                                    .map(|plan| format!("⇡ {}", plan.title))
+                                    .unwrap_or_else(|| "-".to_string()),
+                            ),
+                            (None, None) => (0, "-".to_string(), "-".to_string()),
+                        };
+
                         RecordItem {
                             id: l.id.clone(),
                             title: l.title.clone(),
                             loop_type: l.r#type.clone(),
                             status: l.status.to_string(),
                             parent_id: l.parent.clone(),
-                            children_count: 0, // TODO: count children
+                            children_count,
                             phases_progress,
                             created: format_time_ago(l.created_at),
                             exec_id,
                             file: l.file.clone(),
+                            criteria,
+                            links,
                         }
                     })
                     .collect();
@@ -1893,6 +2661,13 @@ Working directory: {}"#,
         // Sync loop executions and link to artifacts
         match state_manager.list_executions(None, None).await {
             Ok(executions) => {
+                if let Some(notifier) = self.notifier.as_mut()
+                    && !notifier.observe(&executions).is_empty()
+                {
+                    debug!("TuiRunner::refresh_data: ringing bell for execution events");
+                    ring_bell();
+                }
+
                 let items: Vec<ExecutionItem> = executions
                     .iter()
                     .map(|e| {
@@ -1938,6 +2713,13 @@ Working directory: {}"#,
                             artifact_id: artifact.map(|a| a.id.clone()),
                             artifact_file: artifact.and_then(|a| a.file.clone()),
                             artifact_status: artifact.map(|a| a.status.clone()),
+                            deps: e.deps.clone(),
+                            tags: e.tags.clone(),
+                            mine: match (&e.submitted_by, &self.submitter) {
+                                (Some(by), Some(me)) => by.user == me.user,
+                                _ => false,
+                            },
+                            submitted_by: e.submitted_by.clone(),
                         }
                     })
                     .collect();
@@ -1996,54 +2778,30 @@ Working directory: {}"#,
         }
 
         match view {
-            View::Logs { ref target_id } => {
-                // Only load historical events ONCE when first entering this view
-                // Live updates come via event bus subscription in process_event_bus_events()
-                if self.logs_loaded_for.as_deref() != Some(target_id.as_str()) {
-                    debug!(%target_id, "TuiRunner::load_view_data: first load for logs view");
-                    self.logs_loaded_for = Some(target_id.clone());
-
-                    // Load persisted events from JSONL file (historical data)
-                    match replay_execution_events(target_id) {
-                        Ok(events) if !events.is_empty() => {
-                            debug!(%target_id, event_count = events.len(), "TuiRunner::load_view_data: loaded events from JSONL");
-                            let entries: Vec<LogEntry> = events
-                                .iter()
-                                .map(|event| {
-                                    let iteration = match event {
-                                        LoopEvent::IterationStarted { iteration, .. }
-                                        | LoopEvent::IterationCompleted { iteration, .. }
-                                        | LoopEvent::PromptSent { iteration, .. }
-                                        | LoopEvent::TokenReceived { iteration, .. }
-                                        | LoopEvent::ResponseCompleted { iteration, .. }
-                                        | LoopEvent::ToolCallStarted { iteration, .. }
-                                        | LoopEvent::ToolCallCompleted { iteration, .. }
-                                        | LoopEvent::ValidationStarted { iteration, .. }
-                                        | LoopEvent::ValidationOutput { iteration, .. }
-                                        | LoopEvent::ValidationCompleted { iteration, .. } => *iteration,
-                                        _ => 0,
-                                    };
-                                    LogEntry {
-                                        iteration,
-                                        text: format_event_for_display(event),
-                                        is_error: matches!(event, LoopEvent::Error { .. }),
-                                        is_stdout: matches!(
-                                            event,
-                                            LoopEvent::ValidationOutput { is_stderr: false, .. }
-                                        ),
-                                    }
-                                })
-                                .collect();
-                            self.app.state_mut().logs = entries;
-                        }
-                        _ => {
-                            // Clear logs if no events found
-                            debug!(%target_id, "TuiRunner::load_view_data: no events found");
-                            self.app.state_mut().logs.clear();
-                        }
-                    }
+            // Only load historical events ONCE when first entering this view
+            // Live updates come via event bus subscription in process_event_bus_events()
+            View::Logs { ref target_id } if self.logs_loaded_for.as_deref() != Some(target_id.as_str()) => {
+                debug!(%target_id, "TuiRunner::load_view_data: first load for logs view");
+                self.logs_loaded_for = Some(target_id.clone());
+
+                let model = self.llm_config.as_ref().map(|c| c.default.clone()).unwrap_or_default();
+                self.app.state_mut().reset_logs(model);
+
+                // Load persisted events from JSONL file (historical data)
+                let entries = default_runs_dir()
+                    .and_then(|runs_dir| read_execution_events(runs_dir, target_id))
+                    .unwrap_or_default();
+                debug!(%target_id, event_count = entries.len(), "TuiRunner::load_view_data: loaded events from JSONL");
+                let state = self.app.state_mut();
+                for entry in &entries {
+                    state.apply_log_event(&entry.event, entry.timestamp);
+                    state.logs.push(log_entry_for_event(&entry.event));
+                }
+
+                // Iteration logs fill in totals the event log no longer has
+                if let Ok(iteration_logs) = state_manager.list_iteration_logs(target_id).await {
+                    self.app.state_mut().apply_iteration_logs(&iteration_logs);
                 }
-                // Live events are added via process_event_bus_events() - no polling!
             }
             View::Describe {
                 ref target_id,
@@ -2058,6 +2816,9 @@ Working directory: {}"#,
                         "-".to_string()
                     };
 
+                    let default_model = self.llm_config.as_ref().map(|c| c.default.as_str());
+                    let type_max_iterations = self.loop_max_iterations.get(&exec.loop_type).copied();
+
                     // Load plan content from disk if it exists
                     let plan_path = self.worktree.join(".taskdaemon/plans").join(&exec.id).join("plan.md");
                     let plan_content = std::fs::read_to_string(&plan_path).ok();
@@ -2073,15 +2834,14 @@ Working directory: {}"#,
                         parent_id: exec.parent.clone(),
                         created: format_timestamp(exec.created_at),
                         updated: format_timestamp(exec.updated_at),
-                        fields: if let Some(ref err) = exec.last_error {
-                            vec![("Last Error".to_string(), err.clone())]
-                        } else {
-                            vec![]
-                        },
+                        fields: execution_describe_fields(&exec, default_model, type_max_iterations),
                         children: vec![],
                         execution: Some(ExecutionInfo {
                             id: exec.id.clone(),
-                            iteration: format!("{}/10", exec.iteration),
+                            iteration: match exec.max_iterations.or(type_max_iterations) {
+                                Some(max) => format!("{}/{}", exec.iteration, max),
+                                None => exec.iteration.to_string(),
+                            },
                             duration,
                             progress: exec.progress.lines().last().unwrap_or("").to_string(),
                         }),
@@ -2090,6 +2850,8 @@ Working directory: {}"#,
                         artifact_path: exec.artifact_path.clone(),
                         artifact_status: exec.artifact_status.clone(),
                         worktree: exec.worktree.clone(),
+                        locked_paths: exec.locked_paths.clone(),
+                        acceptance: exec.acceptance.clone(),
                         total_input_tokens: exec.total_input_tokens,
                         total_output_tokens: exec.total_output_tokens,
                         total_duration_ms: exec.total_duration_ms,
@@ -2115,6 +2877,8 @@ Working directory: {}"#,
                         artifact_path: None,
                         artifact_status: None,
                         worktree: None, // Loop records don't have worktree
+                        locked_paths: Vec::new(),
+                        acceptance: Vec::new(),
                         total_input_tokens: 0,
                         total_output_tokens: 0,
                         total_duration_ms: 0,
@@ -2173,6 +2937,50 @@ Working directory: {}"#,
 
                 self.app.state_mut().describe_data = data;
             }
+            View::Compare { ref left, ref right }
+                if self.app.state().comparison.as_ref().is_none_or(|c| {
+                    c.left.execution.id != *left || c.right.execution.id != *right
+                }) =>
+            {
+                // Reports and diffs are expensive to build: load once per pair
+                let (Some(left_exec), Some(right_exec)) =
+                    (state_manager.get_execution(left).await?, state_manager.get_execution(right).await?)
+                else {
+                    self.app.state_mut().set_error("Execution to compare no longer exists");
+                    self.app.state_mut().pop_view();
+                    return Ok(());
+                };
+                let runs_dir = default_runs_dir()?;
+                let model = self.llm_config.as_ref().map(|c| c.default.as_str()).unwrap_or_default();
+                let comparison = ExecutionComparison::load(left_exec, right_exec, &runs_dir, model).await;
+                debug!(files = comparison.files.len(), "TuiRunner::load_view_data: loaded comparison");
+                self.app.state_mut().comparison = Some(comparison);
+            }
+            View::Summary => {
+                let executions = state_manager.list_executions(None, None).await?;
+                let events: HashMap<String, Vec<EventLogEntry>> = match dirs::home_dir() {
+                    Some(home) => {
+                        let runs_dir = home.join(".taskdaemon").join("runs");
+                        executions
+                            .iter()
+                            .map(|e| {
+                                (
+                                    e.id.clone(),
+                                    read_execution_events(&runs_dir, &e.id).unwrap_or_default(),
+                                )
+                            })
+                            .collect()
+                    }
+                    None => HashMap::new(),
+                };
+                let model = self.llm_config.as_ref().map(|c| c.default.as_str()).unwrap_or_default();
+                let summary = Summary::compute(&executions, &events, model, chrono::Utc::now());
+                debug!(
+                    types = summary.types.len(),
+                    "TuiRunner::load_view_data: computed summary"
+                );
+                self.app.state_mut().summary = Some(summary);
+            }
             _ => {}
         }
 
@@ -2221,6 +3029,109 @@ fn format_duration(created_at_ms: i64) -> String {
     format!("{}:{:02}", mins, secs)
 }
 
+/// Extra describe fields for an execution: dependencies, planning session, last error
+fn execution_describe_fields(
+    exec: &crate::domain::LoopExecution,
+    default_model: Option<&str>,
+    type_max_iterations: Option<u32>,
+) -> Vec<(String, String)> {
+    debug!(id = %exec.id, "execution_describe_fields: called");
+    let mut fields = Vec::new();
+    // Effective settings, with the default they replace (`td exec set`)
+    let model = match (&exec.model, default_model) {
+        (Some(model), Some(default)) => format!("{} (override; default {})", model, default),
+        (Some(model), None) => format!("{} (override)", model),
+        (None, Some(default)) => format!("{} (default)", default),
+        (None, None) => "default".to_string(),
+    };
+    fields.push(("Model".to_string(), model));
+    let max_iterations = match (exec.max_iterations, type_max_iterations) {
+        (Some(max), Some(default)) => format!("{} (override; loop type {})", max, default),
+        (Some(max), None) => format!("{} (override)", max),
+        (None, Some(default)) => format!("{} (loop type)", default),
+        (None, None) => "loop type default".to_string(),
+    };
+    fields.push(("Max Iter".to_string(), max_iterations));
+    if let Some(submitter) = &exec.submitted_by {
+        fields.push(("Submitted By".to_string(), submitter.to_string()));
+    }
+    if let Some(risk) = &exec.risk {
+        let factors: Vec<String> = risk.factors.iter().map(|f| format!("{} +{}", f.reason, f.points)).collect();
+        let risk = if factors.is_empty() {
+            risk.summary()
+        } else {
+            format!("{}: {}", risk.summary(), factors.join(", "))
+        };
+        fields.push(("Risk".to_string(), risk));
+    }
+    if !exec.tags.is_empty() {
+        fields.push(("Tags".to_string(), exec.tags.join(", ")));
+    }
+    if !exec.deps.is_empty() {
+        fields.push(("Depends On".to_string(), exec.deps.join(", ")));
+    }
+    if let Some(session) = exec.context.get("planning-session").and_then(|v| v.as_str()) {
+        fields.push((
+            "Planning Session".to_string(),
+            format!("{} (/resume {})", session, session),
+        ));
+    }
+    if !exec.wake_conditions.is_empty() {
+        let conditions: Vec<String> = exec.wake_conditions.iter().map(|c| c.to_string()).collect();
+        fields.push(("Wake On".to_string(), conditions.join(" | ")));
+    }
+    if !exec.cherry_picks.is_empty() {
+        let picks: Vec<String> = exec
+            .cherry_picks
+            .iter()
+            .map(|p| format!("{} ({} commits)", p.branch, p.commits.len()))
+            .collect();
+        fields.push(("Cherry-Picked To".to_string(), picks.join(", ")));
+    }
+    if let Some(ref err) = exec.last_error {
+        fields.push(("Last Error".to_string(), err.clone()));
+    }
+    if let Some(ref code) = exec.error_code {
+        fields.push(("Error Code".to_string(), code.clone()));
+    }
+    fields
+}
+
+/// Maximum sessions shown by `/sessions`
+const MAX_LISTED_SESSIONS: usize = 20;
+
+/// Render the `/sessions` listing (current session marked with `*`)
+fn format_session_list(sessions: &[ReplSession], current: Option<&str>) -> String {
+    debug!(count = sessions.len(), ?current, "format_session_list: called");
+    if sessions.is_empty() {
+        return "No saved sessions.".to_string();
+    }
+
+    let mut out = String::from("Saved sessions (newest first) - /resume <id> to continue:\n");
+    for session in sessions.iter().take(MAX_LISTED_SESSIONS) {
+        let marker = if Some(session.id.as_str()) == current { "*" } else { " " };
+        let title = if session.title.is_empty() { "(untitled)" } else { &session.title };
+        let plans = match session.plan_ids.len() {
+            0 => String::new(),
+            n => format!("  [{} plan{}]", n, if n == 1 { "" } else { "s" }),
+        };
+        out.push_str(&format!(
+            "{} {}  {}  {:>3} msgs  {}  {}{}\n",
+            marker,
+            session.id,
+            session.mode,
+            session.messages.len(),
+            format_timestamp(session.updated_at),
+            title,
+            plans
+        ));
+    }
+    if sessions.len() > MAX_LISTED_SESSIONS {
+        out.push_str(&format!("  ... and {} older\n", sessions.len() - MAX_LISTED_SESSIONS));
+    }
+    out
+}
+
 /// Format a timestamp as ISO date string in local timezone
 fn format_timestamp(timestamp_ms: i64) -> String {
     use chrono::{Local, TimeZone};
@@ -2260,6 +3171,23 @@ fn clean_title(raw: &str) -> String {
     words.join(" ")
 }
 
+/// Convert a LoopEvent into a Logs view entry
+fn log_entry_for_event(event: &LoopEvent) -> LogEntry {
+    LogEntry {
+        iteration: event_iteration(event).unwrap_or(0),
+        text: format_event_for_display(event),
+        is_error: matches!(
+            event,
+            LoopEvent::Error { .. }
+                | LoopEvent::ResourceLimitExceeded { .. }
+                | LoopEvent::NetworkRequest { allowed: false, .. }
+                | LoopEvent::DeadlockDetected { .. }
+                | LoopEvent::PathConflict { .. }
+        ),
+        is_stdout: matches!(event, LoopEvent::ValidationOutput { is_stderr: false, .. }),
+    }
+}
+
 /// Format a LoopEvent for display in the Logs view
 fn format_event_for_display(event: &LoopEvent) -> String {
     match event {
@@ -2274,6 +3202,22 @@ fn format_event_for_display(event: &LoopEvent) -> String {
             total_phases,
             ..
         } => format!("Phase {}/{} started: {}", phase_index + 1, total_phases, phase_name),
+        LoopEvent::PhaseCompleted {
+            phase_name,
+            iterations_used,
+            budget,
+            carried_over,
+            ..
+        } => {
+            if *carried_over > 0 {
+                format!(
+                    "Phase {} completed: {}/{} iterations, {} carried over",
+                    phase_name, iterations_used, budget, carried_over
+                )
+            } else {
+                format!("Phase {} completed: {}/{} iterations", phase_name, iterations_used, budget)
+            }
+        }
         LoopEvent::IterationStarted { iteration, .. } => format!("Iteration {} started", iteration),
         LoopEvent::IterationCompleted { iteration, outcome, .. } => {
             format!("Iteration {} completed: {:?}", iteration, outcome)
@@ -2289,6 +3233,15 @@ fn format_event_for_display(event: &LoopEvent) -> String {
                 format!("Loop failed after {} iterations", total_iterations)
             }
         }
+        LoopEvent::LoopStuck {
+            unchanged_iterations,
+            action,
+            ..
+        } => format!(
+            "Loop stuck: no progress for {} iterations ({})",
+            unchanged_iterations, action
+        ),
+        LoopEvent::OverrideChanged { setting, from, to, .. } => format!("Override: {} {} → {}", setting, from, to),
         LoopEvent::PromptSent {
             prompt_summary,
             token_count,
@@ -2323,6 +3276,38 @@ fn format_event_for_display(event: &LoopEvent) -> String {
             let status = if *success { "✓" } else { "✗" };
             format!("{} {} ({}ms): {}", status, tool_name, duration_ms, result_summary)
         }
+        LoopEvent::ResourceLimitExceeded {
+            tool_name,
+            resource,
+            limit,
+            ..
+        } => {
+            let unit = match resource.as_str() {
+                "cpu" => "s",
+                "memory" => "MB",
+                _ => "ms",
+            };
+            format!("✗ {} exceeded {} limit of {}{}", tool_name, resource, limit, unit)
+        }
+        LoopEvent::NetworkRequest {
+            tool_name,
+            target,
+            allowed,
+            ..
+        } => {
+            let status = if *allowed { "→" } else { "✗ blocked" };
+            format!("{} {} {}", status, tool_name, target)
+        }
+        LoopEvent::RateLimited { retry_after_ms, .. } => format!("Rate limited, retrying in {}ms", retry_after_ms),
+        LoopEvent::DeadlockDetected { cycle, .. } => format!("✗ Deadlock detected: {}", cycle.join(" → ")),
+        LoopEvent::PathConflict { path, held_by, .. } => format!("⚠ {} is locked by {}", path, held_by),
+        LoopEvent::ResourcePressure { exceeded, .. } => {
+            format!("⚠ Resource pressure, pickups paused: {}", exceeded.join(", "))
+        }
+        LoopEvent::ProviderCircuit { open: true, .. } => "⚠ Provider failing, circuit breaker open".to_string(),
+        LoopEvent::ProviderCircuit { resumed, .. } => {
+            format!("✓ Provider recovered, resumed {} execution(s)", resumed)
+        }
         LoopEvent::ValidationStarted { command, .. } => format!("Validation: {}", command),
         LoopEvent::ValidationOutput { line, is_stderr, .. } => {
             if *is_stderr {
@@ -2371,6 +3356,20 @@ mod tests {
         assert_eq!(format_time_ago(now - 3 * 24 * 60 * 60 * 1000), "3d ago");
     }
 
+    #[test]
+    fn test_execution_describe_fields_show_overrides() {
+        let mut exec = crate::domain::LoopExecution::with_id("exec-1", "ralph");
+        let fields = execution_describe_fields(&exec, Some("anthropic/claude-sonnet-4"), Some(10));
+        assert_eq!(fields[0].1, "anthropic/claude-sonnet-4 (default)");
+        assert_eq!(fields[1].1, "10 (loop type)");
+
+        exec.model = Some("anthropic/claude-opus-4".to_string());
+        exec.max_iterations = Some(20);
+        let fields = execution_describe_fields(&exec, Some("anthropic/claude-sonnet-4"), Some(10));
+        assert_eq!(fields[0].1, "anthropic/claude-opus-4 (override; default anthropic/claude-sonnet-4)");
+        assert_eq!(fields[1].1, "20 (override; loop type 10)");
+    }
+
     #[test]
     fn test_format_duration() {
         let now = taskstore::now_ms();
@@ -5,12 +5,24 @@
 //!
 //! Views are dynamic based on loaded loop types from YAML configuration.
 
+use std::collections::{BTreeMap, BTreeSet};
 use std::time::Instant;
 
+use chrono::{DateTime, Utc};
 use rand::seq::IndexedRandom;
-use tracing::debug;
+use tracing::{debug, trace};
 
+use super::commands::CommandRegistry;
+use super::keymap::KeyMap;
+use super::theme::Theme;
 use super::tree::LoopTree;
+use crate::bulk::BulkAction;
+use crate::compare::ExecutionComparison;
+use crate::config::{LayoutConfig, SplitMode};
+use crate::domain::{AcceptanceCheck, IterationLog, SessionMessage, SessionRole, Submitter};
+use crate::events::{Event as LoopEvent, IterationOutcome};
+use crate::llm::TokenUsage;
+use crate::summary::Summary;
 
 /// Fun words for the streaming status indicator (Claude Code style)
 pub const STREAMING_WORDS: &[&str] = &[
@@ -36,6 +48,8 @@ pub enum View {
     Loops,
     /// All running executions (`:executions`) - legacy flat view
     Executions,
+    /// Overview of all executions (`:summary`)
+    Summary,
     /// Loop records filtered by type (`:records` or `:<type>` e.g., `:plan`)
     Records {
         /// Filter to specific loop type (None = all records)
@@ -51,6 +65,8 @@ pub enum View {
         /// The loop type of the target (for context)
         target_type: String,
     },
+    /// Two marked executions side by side (`=` key)
+    Compare { left: String, right: String },
 }
 
 /// Top-level panes for Tab cycling (in order): Chat, Plan, Loops
@@ -117,12 +133,14 @@ impl View {
             Self::Repl => "REPL".to_string(),
             Self::Loops => "Loops".to_string(),
             Self::Executions => "Executions".to_string(),
+            Self::Summary => "Summary".to_string(),
             Self::Records {
                 type_filter: Some(t), ..
             } => format!("Records ({})", t),
             Self::Records { type_filter: None, .. } => "Records".to_string(),
             Self::Logs { .. } => "Logs".to_string(),
             Self::Describe { .. } => "Describe".to_string(),
+            Self::Compare { .. } => "Compare".to_string(),
         }
     }
 
@@ -132,6 +150,7 @@ impl View {
     /// - `repl` - show the interactive REPL
     /// - `loops` - show hierarchical loop tree
     /// - `executions` - show flat execution list (legacy)
+    /// - `summary` - show the overview of all executions
     /// - `records` or `all` - show all Loop records (deprecated)
     ///
     /// Dynamic commands (based on loaded loop types):
@@ -145,6 +164,8 @@ impl View {
             "loops" => Some(Self::Loops),
             // Legacy flat execution list
             "executions" => Some(Self::Executions),
+            // Overview of all executions
+            "summary" => Some(Self::Summary),
             // All records (deprecated)
             "records" | "all" => Some(Self::Records {
                 type_filter: None,
@@ -259,6 +280,23 @@ impl ConfirmDialog {
         Self::new(ConfirmAction::PauseLoop(id), format!("Pause {}?", name))
     }
 
+    pub fn cherry_pick(id: String, name: &str) -> Self {
+        let message = format!("Cherry-pick {}'s commits onto a new branch picked/{}?", name, id);
+        Self::new(ConfirmAction::CherryPick(id), message)
+    }
+
+    pub fn bulk(action: BulkAction, ids: Vec<String>, skipped: usize) -> Self {
+        let mut message = format!("{} {} marked execution(s)?", action.label(), ids.len());
+        if skipped > 0 {
+            message.push_str(&format!(
+                " ({} marked can't be {})",
+                skipped,
+                action.past_tense().to_lowercase()
+            ));
+        }
+        Self::new(ConfirmAction::Bulk(action, ids), message)
+    }
+
     pub fn delete_execution(id: String, name: &str) -> Self {
         Self::new(
             ConfirmAction::DeleteExecution(id),
@@ -277,6 +315,10 @@ pub enum ConfirmAction {
     DeleteExecution(String),
     /// Activate a draft - goes directly to Running (no pending state)
     ActivateDraft(String),
+    /// Cherry-pick all of an execution's commits onto a new branch off main
+    CherryPick(String),
+    /// Apply one action to every listed execution (marked in the Executions view)
+    Bulk(BulkAction, Vec<String>),
 }
 
 /// Action pending execution by the runner
@@ -288,6 +330,38 @@ pub enum PendingAction {
     DeleteExecution(String),
     /// Activate a draft - goes directly to Running (no pending state)
     ActivateDraft(String),
+    /// Cherry-pick all of an execution's commits onto a new branch off main
+    CherryPick(String),
+    /// Apply one action to every listed execution
+    Bulk(BulkAction, Vec<String>),
+}
+
+/// REPL session command queued for the runner (needs the StateManager)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionRequest {
+    /// List saved sessions (`/sessions`)
+    List,
+    /// Restore a saved session by ID or unique ID fragment (`/resume <id>`)
+    Resume(String),
+    /// Start a fresh session (`/clear`)
+    New,
+}
+
+/// REPL command queued for the runner (needs the LLM config, tools, or worktree)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandRequest {
+    /// Show the current model, or switch to another (`/model [name]`)
+    Model(Option<String>),
+    /// List REPL tools and whether they are enabled (`/tools`)
+    ListTools,
+    /// Enable or disable a REPL tool (`/tools on|off <name>`)
+    SetTool { name: String, enabled: bool },
+    /// Add files matching a glob to the conversation context (`/context ingest <glob>`)
+    IngestContext(String),
+    /// List ingested context files (`/context list`)
+    ListContext,
+    /// Drop all ingested context (`/context clear`)
+    ClearContext,
 }
 
 /// Request to create a plan from the current conversation
@@ -295,6 +369,8 @@ pub enum PendingAction {
 pub struct PlanCreateRequest {
     /// The conversation messages to summarize
     pub messages: Vec<ReplMessage>,
+    /// Who the plan execution is attributed to (filled in by the runner)
+    pub submitter: Option<Submitter>,
 }
 
 /// REPL mode (Chat vs Plan)
@@ -307,6 +383,24 @@ pub enum ReplMode {
     Plan,
 }
 
+impl ReplMode {
+    /// Name used in persisted sessions
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Chat => "chat",
+            Self::Plan => "plan",
+        }
+    }
+
+    /// Parse a persisted mode name (unknown names fall back to Chat)
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "plan" => Self::Plan,
+            _ => Self::Chat,
+        }
+    }
+}
+
 /// REPL message role
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ReplRole {
@@ -410,6 +504,43 @@ impl ReplMessage {
     }
 }
 
+impl From<&ReplMessage> for SessionMessage {
+    fn from(msg: &ReplMessage) -> Self {
+        let role = match &msg.role {
+            ReplRole::User => SessionRole::User,
+            ReplRole::Assistant => SessionRole::Assistant,
+            ReplRole::ToolResult { tool_name } => SessionRole::ToolResult {
+                tool_name: tool_name.clone(),
+            },
+            ReplRole::Error => SessionRole::Error,
+        };
+        Self {
+            role,
+            content: msg.content.clone(),
+            tool_args: msg.tool_args.clone(),
+            timestamp: msg.timestamp,
+        }
+    }
+}
+
+impl From<SessionMessage> for ReplMessage {
+    fn from(msg: SessionMessage) -> Self {
+        let role = match msg.role {
+            SessionRole::User => ReplRole::User,
+            SessionRole::Assistant => ReplRole::Assistant,
+            SessionRole::ToolResult { tool_name } => ReplRole::ToolResult { tool_name },
+            SessionRole::Error => ReplRole::Error,
+        };
+        Self {
+            role,
+            content: msg.content,
+            timestamp: msg.timestamp,
+            tool_args: msg.tool_args,
+            expanded: false,
+        }
+    }
+}
+
 /// Selection state for list views
 #[derive(Debug, Default, Clone)]
 pub struct SelectionState {
@@ -461,12 +592,20 @@ pub struct AppState {
     pub interaction_mode: InteractionMode,
     /// Current filter text (for / filtering)
     pub filter_text: String,
+    /// Named execution filters from config (`:filter <name>`)
+    pub saved_filters: BTreeMap<String, String>,
+    /// Executions marked for a bulk action (space in the Executions view)
+    pub marked_executions: BTreeSet<String>,
     /// Should the app quit
     pub should_quit: bool,
     /// Last error message
     pub error_message: Option<String>,
     /// Daemon connection status
     pub daemon_status: DaemonStatus,
+    /// Daemon is in maintenance mode (`td daemon maintenance on`)
+    pub maintenance: bool,
+    /// Another client holds the daemon's writer token (why we are read-only)
+    pub read_only: Option<String>,
 
     // === Cached data for display ===
     /// Loop records (filtered by current view)
@@ -479,6 +618,10 @@ pub struct AppState {
     pub logs: Vec<LogEntry>,
     /// Describe data for current target
     pub describe_data: Option<DescribeData>,
+    /// Overview shown in the Summary view
+    pub summary: Option<Summary>,
+    /// Executions shown in the Compare view
+    pub comparison: Option<ExecutionComparison>,
 
     // === Selection state per view ===
     pub records_selection: SelectionState,
@@ -499,6 +642,14 @@ pub struct AppState {
     // === Logs view state ===
     pub logs_follow: bool,
     pub logs_scroll: usize,
+    /// Per-iteration totals shown as group headers
+    pub logs_iterations: BTreeMap<u32, IterationSummary>,
+    /// Iterations whose lines are hidden under their header
+    pub logs_collapsed: BTreeSet<u32>,
+    /// Iteration header selected with j/k (Enter collapses/expands it)
+    pub logs_selected: Option<u32>,
+    /// Model used to price iteration token usage
+    pub logs_model: String,
 
     // === Describe view state ===
     pub describe_scroll: usize,
@@ -536,6 +687,16 @@ pub struct AppState {
     pub pending_plan_create: Option<PlanCreateRequest>,
     /// Is plan creation currently in progress? (used to block double-execution)
     pub plan_creating: bool,
+    /// Pending session command (list/resume/new)
+    pub pending_session: Option<SessionRequest>,
+    /// Session changed and should be persisted
+    pub repl_session_dirty: bool,
+    /// Slash commands available in the REPL
+    pub commands: CommandRegistry,
+    /// Pending command for the runner (/model, /tools, /context)
+    pub pending_command: Option<CommandRequest>,
+    /// Completion candidates shown after an ambiguous Tab
+    pub repl_completions: Vec<String>,
 
     // === Streaming status (Claude Code style) ===
     /// Fun word for streaming indicator (e.g., "Pondering", "Orbiting")
@@ -561,6 +722,20 @@ pub struct AppState {
     /// Live output buffers keyed by execution_id
     /// Each buffer contains the streaming output for a loop execution
     pub live_output: std::collections::HashMap<String, LiveOutputBuffer>,
+
+    // === Split-pane layout ===
+    /// Layout preferences (from config, changed via keybindings)
+    pub layout: LayoutConfig,
+    /// Execution shown live in the secondary pane
+    pub pinned_execution: Option<String>,
+    /// Layout changed and should be persisted to config
+    pub layout_dirty: bool,
+
+    // === Appearance ===
+    /// Colors used by all views
+    pub theme: Theme,
+    /// Key remapping applied before dispatch
+    pub keymap: KeyMap,
 }
 
 /// Buffer for live streaming output from a loop execution
@@ -581,14 +756,20 @@ impl Default for AppState {
             view_stack: Vec::new(),
             interaction_mode: InteractionMode::default(),
             filter_text: String::new(),
+            saved_filters: BTreeMap::new(),
+            marked_executions: BTreeSet::new(),
             should_quit: false,
             error_message: None,
             daemon_status: DaemonStatus::default(),
+            maintenance: false,
+            read_only: None,
             records: Vec::new(),
             executions: Vec::new(),
             loops_tree: LoopTree::new(),
             logs: Vec::new(),
             describe_data: None,
+            summary: None,
+            comparison: None,
             records_selection: SelectionState::default(),
             executions_selection: SelectionState::default(),
             loops_scroll: 0,
@@ -600,6 +781,10 @@ impl Default for AppState {
             available_types: Vec::new(),
             logs_follow: true,
             logs_scroll: 0,
+            logs_iterations: BTreeMap::new(),
+            logs_collapsed: BTreeSet::new(),
+            logs_selected: None,
+            logs_model: String::new(),
             describe_scroll: 0,
             describe_max_scroll: 0,
             describe_show_output: false,
@@ -618,6 +803,11 @@ impl Default for AppState {
             repl_max_scroll: 0,
             pending_plan_create: None,
             plan_creating: false,
+            pending_session: None,
+            repl_session_dirty: false,
+            commands: CommandRegistry::default(),
+            pending_command: None,
+            repl_completions: Vec::new(),
             // Streaming status
             streaming_word: String::new(),
             streaming_start: None,
@@ -630,6 +820,13 @@ impl Default for AppState {
             current_model: String::new(),
             // Live output
             live_output: std::collections::HashMap::new(),
+            // Split-pane layout
+            layout: LayoutConfig::default(),
+            pinned_execution: None,
+            layout_dirty: false,
+            // Appearance
+            theme: Theme::default(),
+            keymap: KeyMap::default(),
         }
     }
 }
@@ -744,8 +941,8 @@ impl AppState {
             View::Loops => self.loops_tree.visible_len(),
             View::Records { .. } => self.filtered_records().len(),
             View::Executions => self.filtered_executions().len(),
-            View::Logs { .. } => self.logs.len(),
-            View::Describe { .. } => 0,
+            View::Logs { .. } => self.logs_rows().len(),
+            View::Describe { .. } | View::Compare { .. } | View::Summary => 0,
         }
     }
 
@@ -892,11 +1089,103 @@ impl AppState {
         self.describe_scroll = 0;
     }
 
+    /// Clear logs and iteration summaries before loading a new target
+    pub fn reset_logs(&mut self, model: impl Into<String>) {
+        debug!("AppState::reset_logs: called");
+        self.logs.clear();
+        self.logs_iterations.clear();
+        self.logs_collapsed.clear();
+        self.logs_selected = None;
+        self.logs_model = model.into();
+    }
+
+    /// Fold an execution event into its iteration's summary
+    pub fn apply_log_event(&mut self, event: &LoopEvent, at: DateTime<Utc>) {
+        trace!("AppState::apply_log_event: called");
+        if let Some(iteration) = event_iteration(event) {
+            self.logs_iterations.entry(iteration).or_default().apply(event, at);
+        }
+    }
+
+    /// Fill summary gaps from persisted iteration logs
+    pub fn apply_iteration_logs(&mut self, logs: &[IterationLog]) {
+        debug!(count = logs.len(), "AppState::apply_iteration_logs: called");
+        for log in logs {
+            self.logs_iterations.entry(log.iteration).or_default().fill_from_log(log);
+        }
+    }
+
+    /// Logs view rows: each iteration's header followed by its lines (unless collapsed)
+    pub fn logs_rows(&self) -> Vec<LogRow<'_>> {
+        let mut groups: BTreeMap<u32, Vec<&LogEntry>> = BTreeMap::new();
+        for entry in self.logs.iter().filter(|e| e.iteration > 0) {
+            groups.entry(entry.iteration).or_default().push(entry);
+        }
+
+        let mut rows = Vec::with_capacity(self.logs.len() + groups.len());
+        for entry in &self.logs {
+            if entry.iteration == 0 {
+                rows.push(LogRow::Entry(entry));
+                continue;
+            }
+            let Some(lines) = groups.remove(&entry.iteration) else {
+                continue;
+            };
+            rows.push(LogRow::Header(entry.iteration));
+            if !self.logs_collapsed.contains(&entry.iteration) {
+                rows.extend(lines.into_iter().map(LogRow::Entry));
+            }
+        }
+        rows
+    }
+
+    /// Select the next (or previous) iteration header and scroll to it
+    pub fn logs_select_iteration(&mut self, forward: bool) {
+        debug!(forward, ?self.logs_selected, "AppState::logs_select_iteration: called");
+        let rows = self.logs_rows();
+        let headers: Vec<(usize, u32)> = rows
+            .iter()
+            .enumerate()
+            .filter_map(|(i, row)| match row {
+                LogRow::Header(iteration) => Some((i, *iteration)),
+                LogRow::Entry(_) => None,
+            })
+            .collect();
+        let current = self
+            .logs_selected
+            .and_then(|sel| headers.iter().position(|(_, iteration)| *iteration == sel));
+        let next = match (current, forward) {
+            (None, true) => 0,
+            (None, false) => headers.len().saturating_sub(1),
+            (Some(i), true) => (i + 1).min(headers.len().saturating_sub(1)),
+            (Some(i), false) => i.saturating_sub(1),
+        };
+        if let Some((row, iteration)) = headers.get(next) {
+            self.logs_selected = Some(*iteration);
+            self.logs_scroll = *row;
+            self.logs_follow = false;
+        }
+    }
+
+    /// Collapse or expand the selected iteration (the latest one if none is selected)
+    pub fn toggle_logs_iteration(&mut self) {
+        let target = self
+            .logs_selected
+            .or_else(|| self.logs.iter().map(|e| e.iteration).filter(|i| *i > 0).max());
+        debug!(?target, "AppState::toggle_logs_iteration: called");
+        if let Some(iteration) = target {
+            self.logs_selected = Some(iteration);
+            if !self.logs_collapsed.remove(&iteration) {
+                self.logs_collapsed.insert(iteration);
+            }
+        }
+    }
+
     /// Tick - called on each frame update
     pub fn tick(&mut self) {
         // Update logs scroll if following
         if self.logs_follow && !self.logs.is_empty() {
-            self.logs_scroll = self.logs.len().saturating_sub(1);
+            self.logs_scroll = self.logs_rows().len().saturating_sub(1);
         }
 
         // Scroll is handled in render with viewport awareness
@@ -928,18 +1217,30 @@ impl AppState {
         if self.filter_text.is_empty() {
             self.executions.iter().collect()
         } else {
-            let filter = self.filter_text.to_lowercase();
             self.executions
                 .iter()
-                .filter(|e| {
-                    e.name.to_lowercase().contains(&filter)
-                        || e.id.to_lowercase().contains(&filter)
-                        || e.loop_type.to_lowercase().contains(&filter)
-                })
+                .filter(|e| e.matches_filter(&self.filter_text))
                 .collect()
         }
     }
 
+    /// Display names of the executions `item` depends on
+    ///
+    /// Dependencies that aren't loaded fall back to their ID.
+    pub fn dependency_names(&self, item: &ExecutionItem) -> Vec<String> {
+        debug!(id = %item.id, deps = item.deps.len(), "AppState::dependency_names: called");
+        item.deps
+            .iter()
+            .map(|dep| {
+                self.executions
+                    .iter()
+                    .find(|e| &e.id == dep)
+                    .map(|e| e.name.clone())
+                    .unwrap_or_else(|| dep.clone())
+            })
+            .collect()
+    }
+
     /// Toggle expand/collapse for the most recent collapsible tool result
     pub fn toggle_tool_expansion(&mut self) {
         debug!("AppState::toggle_tool_expansion: called");
@@ -994,6 +1295,68 @@ impl AppState {
         self.live_output.remove(execution_id);
     }
 
+    /// Pin an execution to the secondary pane, or unpin it if already pinned
+    ///
+    /// Pinning turns the split on if it was off.
+    pub fn toggle_pin(&mut self, execution_id: &str) {
+        debug!(%execution_id, ?self.pinned_execution, "AppState::toggle_pin: called");
+        if self.pinned_execution.as_deref() == Some(execution_id) {
+            debug!("AppState::toggle_pin: unpinning");
+            self.pinned_execution = None;
+            return;
+        }
+        self.pinned_execution = Some(execution_id.to_string());
+        if self.layout.split == SplitMode::Off {
+            debug!("AppState::toggle_pin: enabling split");
+            self.layout.split = SplitMode::Horizontal;
+            self.layout_dirty = true;
+        }
+    }
+
+    /// Remove the pinned execution (the split stays as configured)
+    pub fn unpin(&mut self) {
+        debug!(?self.pinned_execution, "AppState::unpin: called");
+        self.pinned_execution = None;
+    }
+
+    /// Cycle the split mode (off -> side by side -> stacked -> off)
+    pub fn cycle_split(&mut self) {
+        debug!(?self.layout.split, "AppState::cycle_split: called");
+        self.layout.split = self.layout.split.next();
+        self.layout_dirty = true;
+    }
+
+    /// Grow (positive) or shrink (negative) the primary pane
+    pub fn resize_split(&mut self, delta: i16) {
+        debug!(delta, "AppState::resize_split: called");
+        if self.layout.split == SplitMode::Off {
+            debug!("AppState::resize_split: split is off");
+            return;
+        }
+        self.layout.resize(delta);
+        self.layout_dirty = true;
+    }
+
+    /// Token usage and cost summary for `/cost`
+    pub fn cost_summary(&self) -> String {
+        debug!("AppState::cost_summary: called");
+        let model = if self.current_model.is_empty() {
+            "(no requests yet)"
+        } else {
+            &self.current_model
+        };
+        let (input_price, output_price) = model_pricing(&self.current_model);
+        format!(
+            "Session usage:\n  Model: {}\n  Input tokens: {}\n  Output tokens: {}\n  Estimated cost: ${:.4}\n  Pricing: ${}/M input, ${}/M output",
+            model,
+            self.session_input_tokens,
+            self.session_output_tokens,
+            self.session_cost_usd,
+            input_price,
+            output_price
+        )
+    }
+
     /// Finish a request and accumulate session totals
     pub fn finish_request(&mut self, input_tokens: u64, output_tokens: u64) {
         debug!(input_tokens, output_tokens, "AppState::finish_request: called");
@@ -1001,12 +1364,7 @@ impl AppState {
         self.session_output_tokens += output_tokens;
 
         // Calculate cost based on current model
-        let (input_price, output_price) = match self.current_model.as_str() {
-            m if m.contains("opus") => (15.0, 75.0),
-            m if m.contains("sonnet") => (3.0, 15.0),
-            m if m.contains("haiku") => (0.25, 1.25),
-            _ => (3.0, 15.0), // Default to sonnet pricing
-        };
+        let (input_price, output_price) = model_pricing(&self.current_model);
 
         let input_cost = (input_tokens as f64 / 1_000_000.0) * input_price;
         let output_cost = (output_tokens as f64 / 1_000_000.0) * output_price;
@@ -1015,6 +1373,7 @@ impl AppState {
             session_cost_usd = self.session_cost_usd,
             "AppState::finish_request: updated cost"
         );
+        self.repl_session_dirty = true;
 
         // Clear streaming state
         self.streaming_start = None;
@@ -1023,6 +1382,16 @@ impl AppState {
     }
 }
 
+/// Price per million (input, output) tokens, by model family
+fn model_pricing(model: &str) -> (f64, f64) {
+    match model {
+        m if m.contains("opus") => (15.0, 75.0),
+        m if m.contains("sonnet") => (3.0, 15.0),
+        m if m.contains("haiku") => (0.25, 1.25),
+        _ => (3.0, 15.0), // Default to sonnet pricing
+    }
+}
+
 /// Cached Loop record item for display
 #[derive(Debug, Clone)]
 pub struct RecordItem {
@@ -1038,6 +1407,10 @@ pub struct RecordItem {
     pub exec_id: Option<String>,
     /// Output file path (e.g., "plan.md", "spec.md")
     pub file: Option<String>,
+    /// Acceptance criteria count of a plan or spec ("-" for other records)
+    pub criteria: String,
+    /// Decomposition links: "3 specs" for a plan, the plan's title for a spec
+    pub links: String,
 }
 
 /// Cached loop execution item for display
@@ -1057,6 +1430,198 @@ pub struct ExecutionItem {
     pub artifact_file: Option<String>,
     /// Status of the artifact Loop record
     pub artifact_status: Option<String>,
+    /// Executions this one depends on (dependency edges)
+    pub deps: Vec<String>,
+    /// Labels set with `td exec tag`
+    pub tags: Vec<String>,
+    /// Who submitted the execution
+    pub submitted_by: Option<Submitter>,
+    /// Whether this TUI's user submitted it
+    pub mine: bool,
+}
+
+impl ExecutionItem {
+    /// Whether this item matches a filter query
+    ///
+    /// Words must all match. `tag:`, `status:`, `type:` and `user:` words
+    /// compare against that field (`user:me` matches your own executions);
+    /// other words match the name, ID or loop type.
+    pub fn matches_filter(&self, query: &str) -> bool {
+        query.split_whitespace().all(|word| {
+            let word = word.to_lowercase();
+            if let Some(user) = word.strip_prefix("user:") {
+                if user == "me" {
+                    self.mine
+                } else {
+                    self.submitted_by.as_ref().is_some_and(|by| by.is(user))
+                }
+            } else if let Some(tag) = word.strip_prefix("tag:") {
+                self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+            } else if let Some(status) = word.strip_prefix("status:") {
+                self.status.eq_ignore_ascii_case(status)
+            } else if let Some(loop_type) = word.strip_prefix("type:") {
+                self.loop_type.eq_ignore_ascii_case(loop_type)
+            } else {
+                self.name.to_lowercase().contains(&word)
+                    || self.id.to_lowercase().contains(&word)
+                    || self.loop_type.to_lowercase().contains(&word)
+            }
+        })
+    }
+}
+
+/// A row in the Logs view
+#[derive(Debug, Clone, Copy)]
+pub enum LogRow<'a> {
+    /// Iteration group header
+    Header(u32),
+    /// A log line
+    Entry(&'a LogEntry),
+}
+
+/// Iteration number an execution event belongs to, if any
+pub fn event_iteration(event: &LoopEvent) -> Option<u32> {
+    match event {
+        LoopEvent::IterationStarted { iteration, .. }
+        | LoopEvent::IterationCompleted { iteration, .. }
+        | LoopEvent::PromptSent { iteration, .. }
+        | LoopEvent::TokenReceived { iteration, .. }
+        | LoopEvent::ResponseCompleted { iteration, .. }
+        | LoopEvent::ToolCallStarted { iteration, .. }
+        | LoopEvent::ToolCallCompleted { iteration, .. }
+        | LoopEvent::ResourceLimitExceeded { iteration, .. }
+        | LoopEvent::NetworkRequest { iteration, .. }
+        | LoopEvent::RateLimited { iteration, .. }
+        | LoopEvent::ValidationStarted { iteration, .. }
+        | LoopEvent::ValidationOutput { iteration, .. }
+        | LoopEvent::ValidationCompleted { iteration, .. } => Some(*iteration),
+        _ => None,
+    }
+}
+
+/// Cost and latency totals for one iteration, shown as its Logs view header
+#[derive(Debug, Clone, Default)]
+pub struct IterationSummary {
+    pub started_at: Option<DateTime<Utc>>,
+    pub duration_ms: Option<u64>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub tool_calls: usize,
+    pub tool_failures: usize,
+    /// Validation exit code and duration, once validation has run
+    pub validation: Option<(i32, u64)>,
+    pub outcome: Option<IterationOutcome>,
+}
+
+impl IterationSummary {
+    /// Fold one event (recorded at `at`) into the totals
+    pub fn apply(&mut self, event: &LoopEvent, at: DateTime<Utc>) {
+        match event {
+            LoopEvent::IterationStarted { .. } => self.started_at = Some(at),
+            LoopEvent::IterationCompleted { outcome, .. } => {
+                if let Some(started) = self.started_at {
+                    self.duration_ms = Some((at - started).num_milliseconds().max(0) as u64);
+                }
+                self.outcome = Some(outcome.clone());
+            }
+            LoopEvent::ResponseCompleted {
+                input_tokens,
+                output_tokens,
+                ..
+            } => {
+                self.input_tokens += input_tokens;
+                self.output_tokens += output_tokens;
+            }
+            LoopEvent::ToolCallCompleted { success, .. } => {
+                self.tool_calls += 1;
+                if !success {
+                    self.tool_failures += 1;
+                }
+            }
+            LoopEvent::ValidationCompleted {
+                exit_code, duration_ms, ..
+            } => self.validation = Some((*exit_code, *duration_ms)),
+            _ => {}
+        }
+    }
+
+    /// Fill anything the event log lacked (e.g. compacted or missing events) from the persisted iteration log
+    pub fn fill_from_log(&mut self, log: &IterationLog) {
+        if self.input_tokens == 0 && self.output_tokens == 0 {
+            self.input_tokens = log.llm_input_tokens.unwrap_or(0);
+            self.output_tokens = log.llm_output_tokens.unwrap_or(0);
+        }
+        if self.tool_calls == 0 {
+            self.tool_calls = log.tool_calls.len();
+            self.tool_failures = log.tool_calls.iter().filter(|c| c.is_error).count();
+        }
+        if self.validation.is_none() && !log.validation_command.is_empty() {
+            self.validation = Some((log.exit_code, log.duration_ms));
+        }
+    }
+
+    /// Estimated cost in USD for this iteration's tokens
+    pub fn cost_usd(&self, model: &str) -> f64 {
+        TokenUsage {
+            input_tokens: self.input_tokens,
+            output_tokens: self.output_tokens,
+            ..Default::default()
+        }
+        .cost_usd(model)
+    }
+
+    /// Human-readable validation result
+    pub fn validation_label(&self) -> String {
+        match (&self.outcome, self.validation) {
+            (Some(IterationOutcome::ValidationPassed), _) => "validation passed".to_string(),
+            (Some(IterationOutcome::ValidationFailed { exit_code }), _) => {
+                format!("validation failed (exit {})", exit_code)
+            }
+            (Some(IterationOutcome::MaxTurnsReached), _) => "max turns reached".to_string(),
+            (Some(IterationOutcome::ToolError { tool, .. }), _) => format!("tool error ({})", tool),
+            (Some(IterationOutcome::LlmError { .. }), _) => "LLM error".to_string(),
+            (Some(IterationOutcome::TimedOut { .. }), _) => "timed out".to_string(),
+            (None, Some((0, _))) => "validation passed".to_string(),
+            (None, Some((code, _))) => format!("validation failed (exit {})", code),
+            (None, None) => "running".to_string(),
+        }
+    }
+
+    /// Whether the iteration ended well (None while still running)
+    pub fn passed(&self) -> Option<bool> {
+        match (&self.outcome, self.validation) {
+            (Some(outcome), _) => Some(matches!(outcome, IterationOutcome::ValidationPassed)),
+            (None, Some((code, _))) => Some(code == 0),
+            (None, None) => None,
+        }
+    }
+
+    /// Header text: duration, tokens, cost, tool calls and validation outcome
+    pub fn header(&self, iteration: u32, model: &str) -> String {
+        let duration = self
+            .duration_ms
+            .map(crate::summary::format_duration_ms)
+            .unwrap_or_else(|| "-".to_string());
+        let tools = if self.tool_failures > 0 {
+            format!("{} tools ({} failed)", self.tool_calls, self.tool_failures)
+        } else {
+            format!("{} tools", self.tool_calls)
+        };
+        let validation = match self.validation {
+            Some((_, ms)) => format!("{} in {}", self.validation_label(), crate::summary::format_duration_ms(ms)),
+            None => self.validation_label(),
+        };
+        format!(
+            "Iteration {} · {} · {} in / {} out tokens · ${:.4} · {} · {}",
+            iteration,
+            duration,
+            self.input_tokens,
+            self.output_tokens,
+            self.cost_usd(model),
+            tools,
+            validation
+        )
+    }
 }
 
 /// Log entry for the logs view
@@ -1094,6 +1659,10 @@ pub struct DescribeData {
     pub artifact_status: Option<String>,
     /// Git worktree path where execution runs
     pub worktree: Option<String>,
+    /// Paths the execution holds advisory locks on
+    pub locked_paths: Vec<String>,
+    /// Acceptance criteria and their latest status
+    pub acceptance: Vec<AcceptanceCheck>,
     /// Total LLM input tokens consumed
     pub total_input_tokens: u64,
     /// Total LLM output tokens generated
@@ -1124,6 +1693,7 @@ mod tests {
         ));
         // :loops now maps to View::Loops (tree view)
         assert!(matches!(View::from_command("loops", &types), Some(View::Loops)));
+        assert!(matches!(View::from_command("summary", &types), Some(View::Summary)));
         // :executions still maps to legacy flat view
         assert!(matches!(
             View::from_command("executions", &types),
@@ -1246,4 +1816,138 @@ mod tests {
         assert_eq!(TopLevelPane::Loops.prev(), TopLevelPane::Plan);
         assert_eq!(TopLevelPane::Plan.prev(), TopLevelPane::Chat);
     }
+
+    #[test]
+    fn test_toggle_pin_enables_split() {
+        let mut state = AppState::new();
+        assert_eq!(state.layout.split, SplitMode::Off);
+
+        state.toggle_pin("exec-1");
+        assert_eq!(state.pinned_execution.as_deref(), Some("exec-1"));
+        assert_eq!(state.layout.split, SplitMode::Horizontal);
+        assert!(state.layout_dirty);
+
+        state.toggle_pin("exec-2");
+        assert_eq!(state.pinned_execution.as_deref(), Some("exec-2"));
+
+        state.toggle_pin("exec-2");
+        assert_eq!(state.pinned_execution, None);
+        assert_eq!(state.layout.split, SplitMode::Horizontal);
+    }
+
+    fn log_line(iteration: u32, text: &str) -> LogEntry {
+        LogEntry {
+            iteration,
+            text: text.to_string(),
+            is_error: false,
+            is_stdout: false,
+        }
+    }
+
+    #[test]
+    fn test_iteration_summary_from_events() {
+        let start = DateTime::<Utc>::from_timestamp(1_700_000_000, 0).unwrap();
+        let id = "exec-1".to_string();
+        let mut state = AppState::new();
+        let events = [
+            (LoopEvent::IterationStarted { execution_id: id.clone(), iteration: 1 }, 0),
+            (
+                LoopEvent::ResponseCompleted {
+                    execution_id: id.clone(),
+                    iteration: 1,
+                    response_summary: String::new(),
+                    input_tokens: 1000,
+                    output_tokens: 200,
+                    has_tool_calls: true,
+                    served_by: None,
+                },
+                5,
+            ),
+            (
+                LoopEvent::ToolCallCompleted {
+                    execution_id: id.clone(),
+                    iteration: 1,
+                    tool_name: "bash".to_string(),
+                    success: false,
+                    result_summary: String::new(),
+                    duration_ms: 10,
+                },
+                6,
+            ),
+            (
+                LoopEvent::ValidationCompleted {
+                    execution_id: id.clone(),
+                    iteration: 1,
+                    exit_code: 2,
+                    duration_ms: 3000,
+                },
+                70,
+            ),
+            (
+                LoopEvent::IterationCompleted {
+                    execution_id: id.clone(),
+                    iteration: 1,
+                    outcome: IterationOutcome::ValidationFailed { exit_code: 2 },
+                },
+                75,
+            ),
+        ];
+        for (event, offset) in &events {
+            state.apply_log_event(event, start + chrono::Duration::seconds(*offset));
+        }
+
+        let summary = &state.logs_iterations[&1];
+        assert_eq!(summary.duration_ms, Some(75_000));
+        assert_eq!((summary.input_tokens, summary.output_tokens), (1000, 200));
+        assert_eq!((summary.tool_calls, summary.tool_failures), (1, 1));
+        assert_eq!(summary.passed(), Some(false));
+        let header = summary.header(1, "claude-sonnet-4");
+        assert!(header.starts_with("Iteration 1 · 1m 15s · 1000 in / 200 out tokens · $"));
+        assert!(header.ends_with("1 tools (1 failed) · validation failed (exit 2) in 3s"));
+    }
+
+    #[test]
+    fn test_iteration_summary_fills_from_iteration_log() {
+        let mut log = IterationLog::new("exec-1", 2);
+        log.validation_command = "otto ci".to_string();
+        log.llm_input_tokens = Some(500);
+        log.llm_output_tokens = Some(50);
+
+        let mut state = AppState::new();
+        state.apply_iteration_logs(&[log]);
+        let summary = &state.logs_iterations[&2];
+        assert_eq!((summary.input_tokens, summary.output_tokens), (500, 50));
+        assert_eq!(summary.validation_label(), "validation passed");
+    }
+
+    #[test]
+    fn test_logs_rows_group_and_collapse() {
+        let mut state = AppState::new();
+        state.logs = vec![
+            log_line(1, "a"),
+            log_line(2, "b"),
+            log_line(1, "late"),
+            log_line(0, "loop done"),
+        ];
+
+        let rows = state.logs_rows();
+        assert_eq!(rows.len(), 6);
+        assert!(matches!(rows[0], LogRow::Header(1)));
+        assert!(matches!(rows[2], LogRow::Entry(e) if e.text == "late"));
+        assert!(matches!(rows[3], LogRow::Header(2)));
+
+        // Enter with nothing selected collapses the latest iteration
+        state.toggle_logs_iteration();
+        assert!(state.logs_collapsed.contains(&2));
+        assert_eq!(state.logs_rows().len(), 5);
+
+        state.logs_select_iteration(false);
+        assert_eq!(state.logs_selected, Some(1));
+        assert_eq!(state.logs_scroll, 0);
+        assert!(!state.logs_follow);
+        state.toggle_logs_iteration();
+        assert_eq!(state.logs_rows().len(), 3);
+        state.toggle_logs_iteration();
+        assert_eq!(state.logs_rows().len(), 5);
+    }
 }
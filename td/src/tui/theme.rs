@@ -0,0 +1,357 @@
+//! TUI color themes
+//!
+//! Every color the views draw with comes from a Theme. Built-in themes cover
+//! dark and light terminals; custom themes are TOML files in the themes
+//! directory (`~/.config/taskdaemon/themes/<name>.toml` by default) that
+//! override any subset of a base theme:
+//!
+//! ```toml
+//! extends = "light"
+//!
+//! [colors]
+//! running = "#007a3d"
+//! header = "blue"
+//! ```
+//!
+//! Colors accept names (`cyan`, `dark-gray`), `#rrggbb`, or a 256-color index.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use eyre::{Context, Result};
+use ratatui::style::Color;
+use serde::Deserialize;
+use tracing::debug;
+
+/// Names of the built-in themes
+pub const BUILTIN_THEMES: &[&str] = &["default", "light", "high-contrast"];
+
+/// Semantic colors used across all views
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    // === Status colors ===
+    pub running: Color,
+    pub pending: Color,
+    pub complete: Color,
+    pub failed: Color,
+    pub blocked: Color,
+    pub draft: Color,
+    pub paused: Color,
+    pub stopped: Color,
+    pub rebasing: Color,
+    pub unknown: Color,
+
+    // === Chrome ===
+    /// Borders and titles
+    pub header: Color,
+    /// Keybinding hints
+    pub keybind: Color,
+    /// Background of the selected row
+    pub selected_bg: Color,
+    /// Secondary text
+    pub dim: Color,
+    /// Primary text
+    pub text: Color,
+    /// Paths, file names, stdout markers
+    pub accent: Color,
+    /// Emphasis (mode indicators)
+    pub highlight: Color,
+    /// Positive values (input tokens, live markers)
+    pub success: Color,
+    /// Cautionary values (cost, truncation)
+    pub warning: Color,
+    /// Negative values (output tokens, errors)
+    pub error: Color,
+    /// Background of popups and overlays
+    pub popup_bg: Color,
+    /// Text drawn on top of a colored background
+    pub inverse_text: Color,
+
+    // === REPL ===
+    pub repl_user: Color,
+    pub repl_assistant: Color,
+    pub repl_tool: Color,
+    pub repl_error: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    /// k9s-inspired theme for dark terminals (the default)
+    pub fn dark() -> Self {
+        Self {
+            running: Color::Rgb(0, 255, 127),  // Spring green
+            pending: Color::Rgb(255, 215, 0),  // Gold
+            complete: Color::Rgb(50, 205, 50), // Lime green
+            failed: Color::Rgb(220, 20, 60),   // Crimson
+            blocked: Color::Rgb(255, 69, 0),   // Orange red
+            draft: Color::Rgb(255, 255, 0),    // Yellow - awaiting approval
+            paused: Color::Yellow,
+            stopped: Color::DarkGray,
+            rebasing: Color::Magenta,
+            unknown: Color::Gray,
+            header: Color::Rgb(0, 255, 255),  // Cyan
+            keybind: Color::Rgb(0, 255, 255), // Cyan
+            selected_bg: Color::Rgb(40, 40, 40),
+            dim: Color::DarkGray,
+            text: Color::White,
+            accent: Color::Cyan,
+            highlight: Color::Magenta,
+            success: Color::Green,
+            warning: Color::Yellow,
+            error: Color::Red,
+            popup_bg: Color::Black,
+            inverse_text: Color::Black,
+            repl_user: Color::Rgb(0, 255, 127),        // Green
+            repl_assistant: Color::Rgb(100, 149, 237), // Cornflower blue
+            repl_tool: Color::Rgb(255, 215, 0),        // Gold
+            repl_error: Color::Rgb(220, 20, 60),       // Crimson
+        }
+    }
+
+    /// Darker, saturated colors that stay readable on light backgrounds
+    pub fn light() -> Self {
+        Self {
+            running: Color::Rgb(0, 128, 64),
+            pending: Color::Rgb(176, 112, 0),
+            complete: Color::Rgb(0, 112, 0),
+            failed: Color::Rgb(180, 0, 30),
+            blocked: Color::Rgb(200, 60, 0),
+            draft: Color::Rgb(140, 110, 0),
+            paused: Color::Rgb(150, 100, 0),
+            stopped: Color::Rgb(110, 110, 110),
+            rebasing: Color::Rgb(140, 0, 140),
+            unknown: Color::Rgb(90, 90, 90),
+            header: Color::Rgb(0, 90, 160),
+            keybind: Color::Rgb(0, 90, 160),
+            selected_bg: Color::Rgb(220, 225, 235),
+            dim: Color::Rgb(120, 120, 120),
+            text: Color::Black,
+            accent: Color::Rgb(0, 110, 140),
+            highlight: Color::Rgb(140, 0, 140),
+            success: Color::Rgb(0, 120, 0),
+            warning: Color::Rgb(160, 100, 0),
+            error: Color::Rgb(180, 0, 30),
+            popup_bg: Color::Rgb(245, 245, 245),
+            inverse_text: Color::White,
+            repl_user: Color::Rgb(0, 120, 60),
+            repl_assistant: Color::Rgb(30, 70, 170),
+            repl_tool: Color::Rgb(150, 95, 0),
+            repl_error: Color::Rgb(180, 0, 30),
+        }
+    }
+
+    /// Basic 16-color palette with maximum contrast (no RGB required)
+    pub fn high_contrast() -> Self {
+        Self {
+            running: Color::LightGreen,
+            pending: Color::LightYellow,
+            complete: Color::Green,
+            failed: Color::LightRed,
+            blocked: Color::Red,
+            draft: Color::Yellow,
+            paused: Color::Yellow,
+            stopped: Color::Gray,
+            rebasing: Color::LightMagenta,
+            unknown: Color::White,
+            header: Color::White,
+            keybind: Color::LightCyan,
+            selected_bg: Color::Blue,
+            dim: Color::Gray,
+            text: Color::White,
+            accent: Color::LightCyan,
+            highlight: Color::LightMagenta,
+            success: Color::LightGreen,
+            warning: Color::LightYellow,
+            error: Color::LightRed,
+            popup_bg: Color::Black,
+            inverse_text: Color::Black,
+            repl_user: Color::LightGreen,
+            repl_assistant: Color::LightCyan,
+            repl_tool: Color::LightYellow,
+            repl_error: Color::LightRed,
+        }
+    }
+
+    /// Look up a built-in theme by name
+    pub fn builtin(name: &str) -> Option<Self> {
+        debug!(%name, "Theme::builtin: called");
+        match name {
+            "default" | "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "high-contrast" => Some(Self::high_contrast()),
+            _ => None,
+        }
+    }
+
+    /// Default directory for custom theme files
+    pub fn default_themes_dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|d| d.join("taskdaemon").join("themes"))
+    }
+
+    /// Load a theme by name: built-in, or `<themes_dir>/<name>.toml`
+    pub fn load(name: &str, themes_dir: Option<&Path>) -> Result<Self> {
+        debug!(%name, ?themes_dir, "Theme::load: called");
+        if let Some(theme) = Self::builtin(name) {
+            debug!("Theme::load: using built-in theme");
+            return Ok(theme);
+        }
+
+        let dir = themes_dir
+            .map(Path::to_path_buf)
+            .or_else(Self::default_themes_dir)
+            .ok_or_else(|| eyre::eyre!("Could not determine themes directory"))?;
+        let path = dir.join(format!("{}.toml", name));
+        let content = std::fs::read_to_string(&path).with_context(|| {
+            format!(
+                "Unknown theme '{}': not built in ({}) and {} not readable",
+                name,
+                BUILTIN_THEMES.join(", "),
+                path.display()
+            )
+        })?;
+        Self::from_toml(&content).with_context(|| format!("Invalid theme file {}", path.display()))
+    }
+
+    /// Parse a theme file; unspecified colors come from its base theme
+    pub fn from_toml(content: &str) -> Result<Self> {
+        debug!(content_len = content.len(), "Theme::from_toml: called");
+        let file: ThemeFile = toml::from_str(content).context("Failed to parse theme TOML")?;
+
+        let base = file.extends.as_deref().unwrap_or("default");
+        let mut theme = Self::builtin(base).ok_or_else(|| {
+            eyre::eyre!(
+                "Theme extends unknown built-in '{}' (available: {})",
+                base,
+                BUILTIN_THEMES.join(", ")
+            )
+        })?;
+
+        for (key, value) in &file.colors {
+            let color = Color::from_str(value).map_err(|_| eyre::eyre!("Invalid color for {}: '{}'", key, value))?;
+            let slot = theme
+                .slot_mut(key)
+                .ok_or_else(|| eyre::eyre!("Unknown theme color '{}'", key))?;
+            *slot = color;
+        }
+        Ok(theme)
+    }
+
+    /// Color for a loop/record status string
+    pub fn status_color(&self, status: &str) -> Color {
+        match status {
+            "running" | "in_progress" => self.running,
+            "pending" | "ready" => self.pending,
+            "complete" | "completed" => self.complete,
+            "failed" => self.failed,
+            "blocked" => self.blocked,
+            "paused" | "parked" | "waiting_provider" => self.paused,
+            "stopped" | "cancelled" => self.stopped,
+            "rebasing" => self.rebasing,
+            "draft" => self.draft,
+            _ => self.unknown,
+        }
+    }
+
+    /// Mutable access to a color by its TOML key (kebab-case or snake_case)
+    fn slot_mut(&mut self, key: &str) -> Option<&mut Color> {
+        let slot = match key.replace('-', "_").as_str() {
+            "running" => &mut self.running,
+            "pending" => &mut self.pending,
+            "complete" => &mut self.complete,
+            "failed" => &mut self.failed,
+            "blocked" => &mut self.blocked,
+            "draft" => &mut self.draft,
+            "paused" => &mut self.paused,
+            "stopped" => &mut self.stopped,
+            "rebasing" => &mut self.rebasing,
+            "unknown" => &mut self.unknown,
+            "header" => &mut self.header,
+            "keybind" => &mut self.keybind,
+            "selected_bg" => &mut self.selected_bg,
+            "dim" => &mut self.dim,
+            "text" => &mut self.text,
+            "accent" => &mut self.accent,
+            "highlight" => &mut self.highlight,
+            "success" => &mut self.success,
+            "warning" => &mut self.warning,
+            "error" => &mut self.error,
+            "popup_bg" => &mut self.popup_bg,
+            "inverse_text" => &mut self.inverse_text,
+            "repl_user" => &mut self.repl_user,
+            "repl_assistant" => &mut self.repl_assistant,
+            "repl_tool" => &mut self.repl_tool,
+            "repl_error" => &mut self.repl_error,
+            _ => return None,
+        };
+        Some(slot)
+    }
+}
+
+/// On-disk theme format
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeFile {
+    /// Built-in theme to start from (default: "default")
+    extends: Option<String>,
+    /// Color overrides keyed by slot name
+    #[serde(default)]
+    colors: BTreeMap<String, String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_themes() {
+        for name in BUILTIN_THEMES {
+            assert!(Theme::builtin(name).is_some(), "missing built-in theme {}", name);
+        }
+        assert_eq!(Theme::builtin("dark"), Some(Theme::default()));
+        assert!(Theme::builtin("solarized").is_none());
+    }
+
+    #[test]
+    fn test_theme_from_toml_overrides_base() {
+        let theme = Theme::from_toml(
+            r##"
+extends = "light"
+
+[colors]
+running = "#112233"
+header = "blue"
+selected-bg = "dark-gray"
+"##,
+        )
+        .unwrap();
+
+        assert_eq!(theme.running, Color::Rgb(0x11, 0x22, 0x33));
+        assert_eq!(theme.header, Color::Blue);
+        assert_eq!(theme.selected_bg, Color::DarkGray);
+        assert_eq!(theme.failed, Theme::light().failed);
+    }
+
+    #[test]
+    fn test_theme_from_toml_errors() {
+        assert!(Theme::from_toml("[colors]\nrunning = \"not-a-color\"").is_err());
+        assert!(Theme::from_toml("[colors]\nsparkles = \"red\"").is_err());
+        assert!(Theme::from_toml("extends = \"nope\"").is_err());
+    }
+
+    #[test]
+    fn test_theme_load_from_dir() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("mine.toml"), "[colors]\ndim = \"240\"").unwrap();
+
+        let theme = Theme::load("mine", Some(temp.path())).unwrap();
+        assert_eq!(theme.dim, Color::Indexed(240));
+        assert!(Theme::load("missing", Some(temp.path())).is_err());
+        assert_eq!(Theme::load("light", Some(temp.path())).unwrap(), Theme::light());
+    }
+}
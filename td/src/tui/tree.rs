@@ -451,6 +451,10 @@ mod tests {
             artifact_id: None,
             artifact_file: None,
             artifact_status: None,
+            deps: Vec::new(),
+            tags: Vec::new(),
+            submitted_by: None,
+            mine: false,
         }
     }
 
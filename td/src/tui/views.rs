@@ -5,57 +5,22 @@
 
 use ratatui::Frame;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
-use ratatui::style::{Color, Modifier, Style};
+use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Clear, Paragraph, Row, Table, Wrap};
 use tracing::trace;
 
-use super::state::{AppState, ConfirmDialog, DaemonStatus, InteractionMode, ReplMode, ReplRole, View};
+use super::keymap::Action;
+use super::state::{AppState, ConfirmDialog, DaemonStatus, InteractionMode, LogRow, ReplMode, ReplRole, View};
+use super::theme::Theme;
 use super::tree::LoopTree;
-
-/// Status colors (k9s-inspired)
-mod colors {
-    use ratatui::style::Color;
-
-    pub const RUNNING: Color = Color::Rgb(0, 255, 127); // Spring green
-    pub const PENDING: Color = Color::Rgb(255, 215, 0); // Gold
-    pub const COMPLETE: Color = Color::Rgb(50, 205, 50); // Lime green
-    pub const FAILED: Color = Color::Rgb(220, 20, 60); // Crimson
-    pub const BLOCKED: Color = Color::Rgb(255, 69, 0); // Orange red
-    pub const DRAFT: Color = Color::Rgb(255, 255, 0); // Yellow - awaiting approval
-    pub const HEADER: Color = Color::Rgb(0, 255, 255); // Cyan
-    pub const KEYBIND: Color = Color::Rgb(0, 255, 255); // Cyan
-    pub const SELECTED_BG: Color = Color::Rgb(40, 40, 40);
-    pub const DIM: Color = Color::DarkGray;
-
-    // REPL message colors
-    pub const REPL_USER: Color = Color::Rgb(0, 255, 127); // Green
-    pub const REPL_ASSISTANT: Color = Color::Rgb(100, 149, 237); // Cornflower blue
-    pub const REPL_TOOL: Color = Color::Rgb(255, 215, 0); // Gold
-    pub const REPL_ERROR: Color = Color::Rgb(220, 20, 60); // Crimson
-}
-
-/// Get color for a status string
-fn status_color(status: &str) -> Color {
-    trace!(%status, "status_color: called");
-    match status {
-        "running" => colors::RUNNING,
-        "pending" => colors::PENDING,
-        "complete" | "completed" => colors::COMPLETE,
-        "failed" => colors::FAILED,
-        "blocked" => colors::BLOCKED,
-        "paused" => Color::Yellow,
-        "stopped" | "cancelled" => Color::DarkGray,
-        "rebasing" => Color::Magenta,
-        "in_progress" => colors::RUNNING,
-        "ready" => colors::PENDING,
-        "draft" => colors::DRAFT,
-        _ => Color::Gray,
-    }
-}
+use crate::compare::{DeltaLine, PatchDelta};
+use crate::config::{LayoutConfig, SplitMode};
+use crate::domain::CriterionStatus;
+use crate::summary::RATE_LIMIT_WINDOW_MINUTES;
 
 /// Get status icon
-fn status_icon(status: &str) -> &'static str {
+pub(super) fn status_icon(status: &str) -> &'static str {
     trace!(%status, "status_icon: called");
     match status {
         "running" | "in_progress" => "●",
@@ -65,6 +30,8 @@ fn status_icon(status: &str) -> &'static str {
         "failed" => "✗",
         "cancelled" | "stopped" => "⊘",
         "paused" => "◑",
+        "parked" => "◔",
+        "waiting_provider" => "◷",
         "rebasing" => "↻",
         "draft" => "◌",
         _ => " ",
@@ -87,14 +54,23 @@ pub fn render(state: &mut AppState, frame: &mut Frame) {
     // Render header (breadcrumb + metrics)
     render_header(state, frame, chunks[0]);
 
+    // Split off the pinned execution pane when the layout calls for it
+    let (main_area, pinned_area) = split_main_area(&state.layout, chunks[1]);
+
     // Render main content based on current view
     match &state.current_view {
-        View::Repl => render_repl_view(state, frame, chunks[1]),
-        View::Loops => render_loops_tree(state, frame, chunks[1]),
-        View::Records { .. } => render_records_table(state, frame, chunks[1]),
-        View::Executions => render_executions_table(state, frame, chunks[1]),
-        View::Logs { .. } => render_logs_view(state, frame, chunks[1]),
-        View::Describe { .. } => render_describe_view(state, frame, chunks[1]),
+        View::Repl => render_repl_view(state, frame, main_area),
+        View::Loops => render_loops_tree(state, frame, main_area),
+        View::Records { .. } => render_records_table(state, frame, main_area),
+        View::Executions => render_executions_table(state, frame, main_area),
+        View::Logs { .. } => render_logs_view(state, frame, main_area),
+        View::Describe { .. } => render_describe_view(state, frame, main_area),
+        View::Summary => render_summary_view(state, frame, main_area),
+        View::Compare { .. } => render_compare_view(state, frame, main_area),
+    }
+
+    if let Some(area) = pinned_area {
+        render_pinned_pane(state, frame, area);
     }
 
     // Render footer (context-sensitive keybinds or input)
@@ -102,20 +78,108 @@ pub fn render(state: &mut AppState, frame: &mut Frame) {
 
     // Render overlays
     match &state.interaction_mode {
-        InteractionMode::Help => render_help_overlay(frame, frame.area()),
-        InteractionMode::Confirm(dialog) => render_confirm_dialog(dialog, frame, frame.area()),
+        InteractionMode::Help => render_help_overlay(state, frame, frame.area()),
+        InteractionMode::Confirm(dialog) => render_confirm_dialog(&state.theme, dialog, frame, frame.area()),
         _ => {}
     }
 }
 
+/// Minimum main-area size for a split; below this the pinned pane is hidden
+const MIN_SPLIT_WIDTH: u16 = 60;
+const MIN_SPLIT_HEIGHT: u16 = 16;
+
+/// Split the main area into (primary, pinned) according to the layout
+fn split_main_area(layout: &LayoutConfig, area: Rect) -> (Rect, Option<Rect>) {
+    trace!(?layout, ?area, "split_main_area: called");
+    let direction = match layout.split {
+        SplitMode::Off => return (area, None),
+        SplitMode::Horizontal if area.width >= MIN_SPLIT_WIDTH => Direction::Horizontal,
+        SplitMode::Vertical if area.height >= MIN_SPLIT_HEIGHT => Direction::Vertical,
+        _ => return (area, None),
+    };
+
+    let primary = layout.primary_percent();
+    let chunks = Layout::default()
+        .direction(direction)
+        .constraints([Constraint::Percentage(primary), Constraint::Percentage(100 - primary)])
+        .split(area);
+    (chunks[0], Some(chunks[1]))
+}
+
+/// Render the pinned execution's live output in the secondary pane
+fn render_pinned_pane(state: &AppState, frame: &mut Frame, area: Rect) {
+    trace!(?state.pinned_execution, "render_pinned_pane: called");
+    let theme = state.theme;
+    let Some(id) = state.pinned_execution.as_deref() else {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Pinned ")
+            .border_style(Style::default().fg(theme.dim));
+        frame.render_widget(block, area);
+        render_empty_message(
+            &theme,
+            frame,
+            area,
+            &format!(
+                "No execution pinned. Press {} on a loop to pin it.",
+                state.keymap.label(Action::Pin)
+            ),
+        );
+        return;
+    };
+
+    let exec = state.executions.iter().find(|e| e.id == id);
+    let name = exec.map(|e| e.name.as_str()).unwrap_or(id);
+    let status = exec.map(|e| e.status.as_str()).unwrap_or("unknown");
+    let iteration = exec.map(|e| e.iteration.as_str()).unwrap_or("-");
+    let title = format!(
+        " Pinned: {} {} {} · iter {} ",
+        truncate_str(name, 30),
+        status_icon(status),
+        status,
+        iteration
+    );
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(Style::default().fg(theme.status_color(status)));
+    let inner = block.inner(area);
+
+    let live = state.get_live_output(id).filter(|b| !b.content.is_empty());
+    let lines: Vec<Line> = match live {
+        Some(buf) => buf.content.lines().map(|l| Line::from(l.to_string())).collect(),
+        None => {
+            let message = match exec {
+                Some(e) if e.status == "running" => "Waiting for output...".to_string(),
+                Some(e) if !e.progress.is_empty() => e.progress.clone(),
+                Some(_) => format!("No live output ({})", status),
+                None => "Execution not found".to_string(),
+            };
+            vec![Line::from(Span::styled(message, Style::default().fg(theme.dim)))]
+        }
+    };
+
+    // Follow the tail: scroll so the last wrapped line sits at the bottom
+    let width = inner.width.max(1) as usize;
+    let wrapped_height: usize = lines.iter().map(|l| l.width().max(1).div_ceil(width)).sum();
+    let scroll = wrapped_height.saturating_sub(inner.height as usize);
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false })
+        .scroll((scroll.min(u16::MAX as usize) as u16, 0));
+    frame.render_widget(paragraph, area);
+}
+
 /// Render header with view tabs and metrics
 fn render_header(state: &AppState, frame: &mut Frame, area: Rect) {
     trace!("render_header: called");
+    let theme = state.theme;
     // Daemon status indicator (colored dot before TaskDaemon)
     let (indicator, indicator_color) = match state.daemon_status {
-        DaemonStatus::Connected => ("●", Color::Green),
-        DaemonStatus::VersionMismatch => ("●", Color::Yellow),
-        DaemonStatus::Disconnected => ("●", Color::Red),
+        DaemonStatus::Connected => ("●", theme.success),
+        DaemonStatus::VersionMismatch => ("●", theme.warning),
+        DaemonStatus::Disconnected => ("●", theme.error),
     };
 
     // Build left side: indicator + TaskDaemon + view tabs
@@ -124,11 +188,43 @@ fn render_header(state: &AppState, frame: &mut Frame, area: Rect) {
         Span::styled(indicator, Style::default().fg(indicator_color)),
         Span::styled(
             " TaskDaemon",
-            Style::default().fg(colors::HEADER).add_modifier(Modifier::BOLD),
+            Style::default().fg(theme.header).add_modifier(Modifier::BOLD),
         ),
         Span::raw(" │ "),
     ];
 
+    // Maintenance banner: nothing is picked up until `td daemon maintenance off`
+    if state.maintenance {
+        left_spans.push(Span::styled(
+            "MAINTENANCE",
+            Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
+        ));
+        left_spans.push(Span::raw(" │ "));
+    }
+
+    // Provider banner: executions wait for the provider circuit breaker to close
+    let waiting_provider = state
+        .executions
+        .iter()
+        .filter(|e| e.status == "waiting_provider")
+        .count();
+    if waiting_provider > 0 {
+        left_spans.push(Span::styled(
+            format!("WAITING FOR PROVIDER ({})", waiting_provider),
+            Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
+        ));
+        left_spans.push(Span::raw(" │ "));
+    }
+
+    // Read-only banner: another TUI/CLI holds the daemon's writer token
+    if state.read_only.is_some() {
+        left_spans.push(Span::styled(
+            "READ-ONLY",
+            Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
+        ));
+        left_spans.push(Span::raw(" │ "));
+    }
+
     // First view tab: Chat|Plan (special handling)
     let is_repl_view = matches!(state.current_view, View::Repl);
     if is_repl_view {
@@ -136,18 +232,18 @@ fn render_header(state: &AppState, frame: &mut Frame, area: Rect) {
         if state.repl_mode == ReplMode::Chat {
             left_spans.push(Span::styled(
                 "Chat",
-                Style::default().fg(colors::HEADER).add_modifier(Modifier::BOLD),
+                Style::default().fg(theme.header).add_modifier(Modifier::BOLD),
             ));
-            left_spans.push(Span::styled("|Plan", Style::default().fg(colors::DIM)));
+            left_spans.push(Span::styled("|Plan", Style::default().fg(theme.dim)));
         } else {
-            left_spans.push(Span::styled("Chat|", Style::default().fg(colors::DIM)));
+            left_spans.push(Span::styled("Chat|", Style::default().fg(theme.dim)));
             left_spans.push(Span::styled(
                 "Plan",
-                Style::default().fg(colors::HEADER).add_modifier(Modifier::BOLD),
+                Style::default().fg(theme.header).add_modifier(Modifier::BOLD),
             ));
         }
     } else {
-        left_spans.push(Span::styled("Chat|Plan", Style::default().fg(colors::DIM)));
+        left_spans.push(Span::styled("Chat|Plan", Style::default().fg(theme.dim)));
     }
 
     // Remaining view tab: Loops
@@ -160,23 +256,23 @@ fn render_header(state: &AppState, frame: &mut Frame, area: Rect) {
     )];
 
     for (name, is_active) in other_tabs.iter() {
-        left_spans.push(Span::styled(" · ", Style::default().fg(colors::DIM)));
+        left_spans.push(Span::styled(" · ", Style::default().fg(theme.dim)));
         if *is_active {
             left_spans.push(Span::styled(
                 *name,
-                Style::default().fg(colors::HEADER).add_modifier(Modifier::BOLD),
+                Style::default().fg(theme.header).add_modifier(Modifier::BOLD),
             ));
         } else {
-            left_spans.push(Span::styled(*name, Style::default().fg(colors::DIM)));
+            left_spans.push(Span::styled(*name, Style::default().fg(theme.dim)));
         }
     }
 
     // Add filter indicator if active
     if !state.filter_text.is_empty() {
-        left_spans.push(Span::styled(" │ ", Style::default().fg(colors::DIM)));
+        left_spans.push(Span::styled(" │ ", Style::default().fg(theme.dim)));
         left_spans.push(Span::styled(
             format!("/{}", &state.filter_text),
-            Style::default().fg(Color::Magenta),
+            Style::default().fg(theme.highlight),
         ));
     }
 
@@ -236,24 +332,24 @@ fn render_header(state: &AppState, frame: &mut Frame, area: Rect) {
     // Add right-side metrics with colors
     for (i, part) in right_parts.iter().enumerate() {
         if i > 0 {
-            spans.push(Span::styled(" │ ", Style::default().fg(colors::DIM)));
+            spans.push(Span::styled(" │ ", Style::default().fg(theme.dim)));
         }
         let color = if part.contains("active") {
-            colors::RUNNING
+            theme.running
         } else if part.contains("drafts") {
-            colors::DRAFT
+            theme.draft
         } else if part.contains("done") {
-            colors::COMPLETE
+            theme.complete
         } else if part.contains("failed") {
-            colors::FAILED
+            theme.failed
         } else if part.starts_with('↑') {
-            Color::Green // Input tokens - cheap
+            theme.success // Input tokens - cheap
         } else if part.starts_with('↓') {
-            Color::Red // Output tokens - expensive
+            theme.error // Output tokens - expensive
         } else if part.starts_with('$') {
-            Color::Yellow // Cost
+            theme.warning // Cost
         } else {
-            Color::White
+            theme.text
         };
         spans.push(Span::styled(part.clone(), Style::default().fg(color)));
     }
@@ -285,6 +381,7 @@ fn calculate_input_height(input: &str, width: u16) -> u16 {
 /// Render REPL view with conversation history and input (unified single border)
 fn render_repl_view(state: &mut AppState, frame: &mut Frame, area: Rect) {
     trace!(?state.repl_mode, "render_repl_view: called");
+    let theme = state.theme;
     // Title changes based on REPL mode
     let title = match state.repl_mode {
         ReplMode::Chat => " Chat ",
@@ -295,7 +392,7 @@ fn render_repl_view(state: &mut AppState, frame: &mut Frame, area: Rect) {
     let block = Block::default()
         .borders(Borders::ALL)
         .title(title)
-        .border_style(Style::default().fg(colors::HEADER));
+        .border_style(Style::default().fg(theme.header));
 
     let inner = block.inner(area);
 
@@ -349,6 +446,7 @@ fn tool_summary(tool_name: &str, content: &str) -> Option<String> {
 /// Render REPL history content (no borders)
 fn render_repl_history(state: &mut AppState, frame: &mut Frame, area: Rect) {
     trace!(history_len = state.repl_history.len(), "render_repl_history: called");
+    let theme = state.theme;
     use super::state::{COLLAPSE_PREVIEW_LINES, COLLAPSE_THRESHOLD};
 
     let mut lines: Vec<Line> = Vec::new();
@@ -360,16 +458,13 @@ fn render_repl_history(state: &mut AppState, frame: &mut Frame, area: Rect) {
                 for (i, content_line) in msg.content.lines().enumerate() {
                     if i == 0 {
                         lines.push(Line::from(vec![
-                            Span::styled(
-                                "> ",
-                                Style::default().fg(colors::REPL_USER).add_modifier(Modifier::BOLD),
-                            ),
-                            Span::styled(content_line, Style::default().fg(colors::REPL_USER)),
+                            Span::styled("> ", Style::default().fg(theme.repl_user).add_modifier(Modifier::BOLD)),
+                            Span::styled(content_line, Style::default().fg(theme.repl_user)),
                         ]));
                     } else {
                         lines.push(Line::from(vec![
                             Span::raw("  "),
-                            Span::styled(content_line, Style::default().fg(colors::REPL_USER)),
+                            Span::styled(content_line, Style::default().fg(theme.repl_user)),
                         ]));
                     }
                 }
@@ -395,7 +490,7 @@ fn render_repl_history(state: &mut AppState, frame: &mut Frame, area: Rect) {
                 // Header line
                 lines.push(Line::from(vec![Span::styled(
                     header,
-                    Style::default().fg(colors::REPL_TOOL),
+                    Style::default().fg(theme.repl_tool),
                 )]));
 
                 let line_count = msg.line_count();
@@ -407,24 +502,24 @@ fn render_repl_history(state: &mut AppState, frame: &mut Frame, area: Rect) {
                     if let Some(summary) = tool_summary(tool_name, &msg.content) {
                         // Show summary line
                         lines.push(Line::from(vec![
-                            Span::styled("└ ", Style::default().fg(colors::DIM)),
-                            Span::styled(summary, Style::default().fg(colors::DIM)),
-                            Span::styled(" (ctrl+o to expand)", Style::default().fg(colors::DIM)),
+                            Span::styled("└ ", Style::default().fg(theme.dim)),
+                            Span::styled(summary, Style::default().fg(theme.dim)),
+                            Span::styled(" (ctrl+o to expand)", Style::default().fg(theme.dim)),
                         ]));
                     } else {
                         // Show preview lines
                         for (i, content_line) in msg.content.lines().take(COLLAPSE_PREVIEW_LINES).enumerate() {
                             let prefix = if i == 0 { "└ " } else { "  " };
                             lines.push(Line::from(vec![
-                                Span::styled(prefix, Style::default().fg(colors::DIM)),
-                                Span::styled(content_line, Style::default().fg(colors::DIM)),
+                                Span::styled(prefix, Style::default().fg(theme.dim)),
+                                Span::styled(content_line, Style::default().fg(theme.dim)),
                             ]));
                         }
                         // Show collapse indicator
                         let hidden = line_count - COLLAPSE_PREVIEW_LINES;
                         lines.push(Line::from(vec![Span::styled(
                             format!("  … +{} lines (ctrl+o to expand)", hidden),
-                            Style::default().fg(colors::DIM),
+                            Style::default().fg(theme.dim),
                         )]));
                     }
                 } else {
@@ -432,8 +527,8 @@ fn render_repl_history(state: &mut AppState, frame: &mut Frame, area: Rect) {
                     for (i, content_line) in msg.content.lines().enumerate() {
                         let prefix = if i == 0 { "└ " } else { "  " };
                         lines.push(Line::from(vec![
-                            Span::styled(prefix, Style::default().fg(colors::DIM)),
-                            Span::styled(content_line, Style::default().fg(colors::DIM)),
+                            Span::styled(prefix, Style::default().fg(theme.dim)),
+                            Span::styled(content_line, Style::default().fg(theme.dim)),
                         ]));
                     }
                 }
@@ -443,16 +538,13 @@ fn render_repl_history(state: &mut AppState, frame: &mut Frame, area: Rect) {
                 for (i, content_line) in msg.content.lines().enumerate() {
                     if i == 0 {
                         lines.push(Line::from(vec![
-                            Span::styled(
-                                "! ",
-                                Style::default().fg(colors::REPL_ERROR).add_modifier(Modifier::BOLD),
-                            ),
-                            Span::styled(content_line, Style::default().fg(colors::REPL_ERROR)),
+                            Span::styled("! ", Style::default().fg(theme.repl_error).add_modifier(Modifier::BOLD)),
+                            Span::styled(content_line, Style::default().fg(theme.repl_error)),
                         ]));
                     } else {
                         lines.push(Line::from(vec![
                             Span::raw("  "),
-                            Span::styled(content_line, Style::default().fg(colors::REPL_ERROR)),
+                            Span::styled(content_line, Style::default().fg(theme.repl_error)),
                         ]));
                     }
                 }
@@ -469,13 +561,13 @@ fn render_repl_history(state: &mut AppState, frame: &mut Frame, area: Rect) {
             for (i, content_line) in state.repl_response_buffer.lines().enumerate() {
                 if i == 0 {
                     lines.push(Line::from(vec![
-                        Span::styled("  ", Style::default().fg(colors::REPL_ASSISTANT)),
-                        Span::styled(content_line, Style::default().fg(Color::White)),
+                        Span::styled("  ", Style::default().fg(theme.repl_assistant)),
+                        Span::styled(content_line, Style::default().fg(theme.text)),
                     ]));
                 } else {
                     lines.push(Line::from(vec![
                         Span::raw("  "),
-                        Span::styled(content_line, Style::default().fg(Color::White)),
+                        Span::styled(content_line, Style::default().fg(theme.text)),
                     ]));
                 }
             }
@@ -504,7 +596,7 @@ fn render_repl_history(state: &mut AppState, frame: &mut Frame, area: Rect) {
             word, elapsed, input_str, output_str
         );
 
-        lines.push(Line::from(vec![Span::styled(status, Style::default().fg(colors::DIM))]));
+        lines.push(Line::from(vec![Span::styled(status, Style::default().fg(theme.dim))]));
     }
 
     // Show welcome message if empty (varies by mode)
@@ -528,12 +620,12 @@ fn render_repl_history(state: &mut AppState, frame: &mut Frame, area: Rect) {
 
         lines.push(Line::from(vec![Span::styled(
             welcome_title,
-            Style::default().fg(colors::HEADER).add_modifier(Modifier::BOLD),
+            Style::default().fg(theme.header).add_modifier(Modifier::BOLD),
         )]));
         lines.push(Line::from(""));
         lines.push(Line::from(vec![Span::styled(
             welcome_desc,
-            Style::default().fg(colors::DIM),
+            Style::default().fg(theme.dim),
         )]));
     }
 
@@ -576,10 +668,11 @@ fn render_repl_input(state: &AppState, frame: &mut Frame, area: Rect) {
         streaming = state.repl_streaming,
         "render_repl_input: called"
     );
+    let theme = state.theme;
     let input_style = if state.repl_streaming {
-        Style::default().fg(colors::DIM)
+        Style::default().fg(theme.dim)
     } else {
-        Style::default().fg(Color::White)
+        Style::default().fg(theme.text)
     };
 
     // Split input at cursor position for rendering cursor in the middle
@@ -588,7 +681,7 @@ fn render_repl_input(state: &AppState, frame: &mut Frame, area: Rect) {
 
     let mut spans = vec![Span::styled(
         "> ",
-        Style::default().fg(colors::REPL_USER).add_modifier(Modifier::BOLD),
+        Style::default().fg(theme.repl_user).add_modifier(Modifier::BOLD),
     )];
 
     // Text before cursor
@@ -609,8 +702,8 @@ fn render_repl_input(state: &AppState, frame: &mut Frame, area: Rect) {
                 spans.push(Span::styled(
                     c.to_string(),
                     Style::default()
-                        .fg(Color::Black)
-                        .bg(Color::White)
+                        .fg(theme.inverse_text)
+                        .bg(theme.text)
                         .add_modifier(Modifier::SLOW_BLINK),
                 ));
                 // Rest of the text after cursor
@@ -627,6 +720,14 @@ fn render_repl_input(state: &AppState, frame: &mut Frame, area: Rect) {
         }
     }
 
+    // Candidates from an ambiguous Tab completion
+    if !state.repl_completions.is_empty() {
+        spans.push(Span::styled(
+            format!("  {}", state.repl_completions.join("  ")),
+            Style::default().fg(theme.dim),
+        ));
+    }
+
     let input_content = Line::from(spans);
     let input = Paragraph::new(input_content).wrap(Wrap { trim: false });
 
@@ -636,6 +737,7 @@ fn render_repl_input(state: &AppState, frame: &mut Frame, area: Rect) {
 /// Render Records table (generic Loop records)
 fn render_records_table(state: &AppState, frame: &mut Frame, area: Rect) {
     trace!("render_records_table: called");
+    let theme = state.theme;
     let filtered = state.filtered_records();
     let selected_idx = state.records_selection.selected_index;
 
@@ -667,7 +769,7 @@ fn render_records_table(state: &AppState, frame: &mut Frame, area: Rect) {
         .enumerate()
         .map(|(i, record)| {
             let row_style = if i == selected_idx {
-                Style::default().bg(colors::SELECTED_BG)
+                Style::default().bg(theme.selected_bg)
             } else {
                 Style::default()
             };
@@ -677,6 +779,8 @@ fn render_records_table(state: &AppState, frame: &mut Frame, area: Rect) {
                 record.loop_type.clone(),
                 record.status.clone(),
                 record.phases_progress.clone(),
+                record.criteria.clone(),
+                record.links.clone(),
                 record.created.clone(),
             ])
             .style(row_style)
@@ -688,31 +792,34 @@ fn render_records_table(state: &AppState, frame: &mut Frame, area: Rect) {
         Constraint::Length(12), // TYPE
         Constraint::Length(12), // STATUS
         Constraint::Length(8),  // PHASES
+        Constraint::Length(10), // CRITERIA
+        Constraint::Length(24), // LINKS
         Constraint::Length(12), // CREATED
     ];
 
     let table = Table::new(rows, widths)
         .header(
-            Row::new(vec!["NAME", "TYPE", "STATUS", "PHASES", "CREATED"])
-                .style(Style::default().add_modifier(Modifier::BOLD).fg(colors::HEADER)),
+            Row::new(vec!["NAME", "TYPE", "STATUS", "PHASES", "CRITERIA", "LINKS", "CREATED"])
+                .style(Style::default().add_modifier(Modifier::BOLD).fg(theme.header)),
         )
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .title(title)
-                .border_style(Style::default().fg(colors::HEADER)),
+                .border_style(Style::default().fg(theme.header)),
         );
 
     frame.render_widget(table, area);
 
     if filtered.is_empty() {
-        render_empty_message(frame, area, "No records found.");
+        render_empty_message(&theme, frame, area, "No records found.");
     }
 }
 
 /// Render Executions table (running LoopExecutions)
 fn render_executions_table(state: &AppState, frame: &mut Frame, area: Rect) {
     trace!("render_executions_table: called");
+    let theme = state.theme;
     let filtered = state.filtered_executions();
     let selected_idx = state.executions_selection.selected_index;
 
@@ -721,14 +828,41 @@ fn render_executions_table(state: &AppState, frame: &mut Frame, area: Rect) {
         .enumerate()
         .map(|(i, exec_item)| {
             let row_style = if i == selected_idx {
-                Style::default().bg(colors::SELECTED_BG)
+                Style::default().bg(theme.selected_bg)
             } else {
                 Style::default()
             };
 
+            // Dependency edges, e.g. "⇠ add-schema, add-api"
+            let deps = state.dependency_names(exec_item);
+            let deps_suffix = if deps.is_empty() {
+                String::new()
+            } else {
+                format!("  ⇠ {}", deps.join(", "))
+            };
+
+            let tags_suffix: String = exec_item.tags.iter().map(|tag| format!(" #{}", tag)).collect();
+
+            let mark = if state.marked_executions.contains(&exec_item.id) {
+                "▣ "
+            } else {
+                ""
+            };
+
             Row::new(vec![
-                format!("{} {}", status_icon(&exec_item.status), &exec_item.name),
+                format!(
+                    "{}{} {}{}{}",
+                    mark,
+                    status_icon(&exec_item.status),
+                    &exec_item.name,
+                    tags_suffix,
+                    deps_suffix
+                ),
                 exec_item.loop_type.clone(),
+                exec_item
+                    .submitted_by
+                    .as_ref()
+                    .map_or_else(|| "-".to_string(), |by| by.user.clone()),
                 exec_item.iteration.clone(),
                 exec_item.status.clone(),
                 exec_item.duration.clone(),
@@ -740,6 +874,7 @@ fn render_executions_table(state: &AppState, frame: &mut Frame, area: Rect) {
     let widths = [
         Constraint::Percentage(50), // NAME - take more space for slug/title
         Constraint::Length(8),      // TYPE
+        Constraint::Length(10),     // USER
         Constraint::Length(6),      // ITER
         Constraint::Length(10),     // STATUS
         Constraint::Length(10),     // DURATION
@@ -747,26 +882,187 @@ fn render_executions_table(state: &AppState, frame: &mut Frame, area: Rect) {
 
     let table = Table::new(rows, widths)
         .header(
-            Row::new(vec!["NAME", "TYPE", "ITER", "STATUS", "DURATION"])
-                .style(Style::default().add_modifier(Modifier::BOLD).fg(colors::HEADER)),
+            Row::new(vec!["NAME", "TYPE", "USER", "ITER", "STATUS", "DURATION"])
+                .style(Style::default().add_modifier(Modifier::BOLD).fg(theme.header)),
         )
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title(format!(" Executions ({}) ", filtered.len()))
-                .border_style(Style::default().fg(colors::HEADER)),
+                .title(if state.marked_executions.is_empty() {
+                    format!(" Executions ({}) ", filtered.len())
+                } else {
+                    format!(
+                        " Executions ({}, {} marked) ",
+                        filtered.len(),
+                        state.marked_executions.len()
+                    )
+                })
+                .border_style(Style::default().fg(theme.header)),
         );
 
     frame.render_widget(table, area);
 
     if filtered.is_empty() {
-        render_empty_message(frame, area, "No running executions. Press [n] to create a new task.");
+        render_empty_message(
+            &theme,
+            frame,
+            area,
+            &format!(
+                "No running executions. Press [{}] to create a new task.",
+                state.keymap.label(Action::NewTask)
+            ),
+        );
+    }
+}
+
+/// Render Summary view: queue and rate limits, per-type table, recent failures
+fn render_summary_view(state: &AppState, frame: &mut Frame, area: Rect) {
+    trace!("render_summary_view: called");
+    let theme = state.theme;
+    let Some(summary) = &state.summary else {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Summary ")
+            .border_style(Style::default().fg(theme.header));
+        frame.render_widget(block, area);
+        render_empty_message(&theme, frame, area, "Loading summary...");
+        return;
+    };
+
+    let failures_height = if summary.recent_failures.is_empty() {
+        0
+    } else {
+        summary.recent_failures.len() as u16 + 2
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(4),               // Queue + rate limits
+            Constraint::Min(4),                  // Per-type table
+            Constraint::Length(failures_height), // Recent failures
+        ])
+        .split(area);
+
+    let pressure_style = if summary.rate_limits.hits > 0 {
+        Style::default().fg(theme.warning)
+    } else {
+        Style::default().fg(theme.dim)
+    };
+    let overview = Paragraph::new(vec![
+        Line::from(vec![
+            Span::styled("Queue: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::styled(
+                format!("{} pending", summary.queue_depth),
+                Style::default().fg(theme.pending),
+            ),
+            Span::raw(", "),
+            Span::styled(
+                format!("{} running", summary.running),
+                Style::default().fg(theme.running),
+            ),
+            Span::raw(format!(", {} parked", summary.parked)),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                format!("Rate limits ({}m): ", RATE_LIMIT_WINDOW_MINUTES),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!(
+                    "{} hits across {} executions, {} waiting",
+                    summary.rate_limits.hits,
+                    summary.rate_limits.executions,
+                    format_duration_ms(summary.rate_limits.wait_ms)
+                ),
+                pressure_style,
+            ),
+        ]),
+    ])
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Summary ")
+            .border_style(Style::default().fg(theme.header)),
+    );
+    frame.render_widget(overview, chunks[0]);
+
+    let rows: Vec<Row> = summary
+        .types
+        .iter()
+        .map(|t| {
+            Row::new(vec![
+                t.loop_type.clone(),
+                t.total.to_string(),
+                t.active.to_string(),
+                t.success_rate.map_or("-".to_string(), |r| format!("{:.0}%", r * 100.0)),
+                t.mean_iterations.map_or("-".to_string(), |i| format!("{:.1}", i)),
+                t.mean_cost_usd.map_or("-".to_string(), |c| format!("${:.2}", c)),
+                t.p95_duration_ms.map_or("-".to_string(), format_duration_ms),
+            ])
+        })
+        .collect();
+    let widths = [
+        Constraint::Min(12),    // TYPE
+        Constraint::Length(6),  // TOTAL
+        Constraint::Length(7),  // ACTIVE
+        Constraint::Length(8),  // SUCCESS
+        Constraint::Length(10), // MEAN ITER
+        Constraint::Length(10), // MEAN COST
+        Constraint::Length(10), // P95 TIME
+    ];
+    let table = Table::new(rows, widths)
+        .header(
+            Row::new(vec![
+                "TYPE",
+                "TOTAL",
+                "ACTIVE",
+                "SUCCESS",
+                "MEAN ITER",
+                "MEAN COST",
+                "P95 TIME",
+            ])
+            .style(Style::default().add_modifier(Modifier::BOLD).fg(theme.header)),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" Loop Types ({}) ", summary.types.len()))
+                .border_style(Style::default().fg(theme.header)),
+        );
+    frame.render_widget(table, chunks[1]);
+    if summary.types.is_empty() {
+        render_empty_message(&theme, frame, chunks[1], "No executions yet.");
+    }
+
+    if !summary.recent_failures.is_empty() {
+        let lines: Vec<Line> = summary
+            .recent_failures
+            .iter()
+            .map(|f| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("✗ {} ", truncate_str(&f.id, 40)),
+                        Style::default().fg(theme.failed),
+                    ),
+                    Span::styled(format!("[{}] ", f.loop_type), Style::default().fg(theme.dim)),
+                    Span::raw(f.reason.clone()),
+                ])
+            })
+            .collect();
+        let failures = Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Recent Failures ")
+                .border_style(Style::default().fg(theme.header)),
+        );
+        frame.render_widget(failures, chunks[2]);
     }
 }
 
 /// Render hierarchical Loops tree view
 fn render_loops_tree(state: &AppState, frame: &mut Frame, area: Rect) {
     trace!("render_loops_tree: called");
+    let theme = state.theme;
     let tree = &state.loops_tree;
     let selected_id = tree.selected_id();
 
@@ -796,6 +1092,7 @@ fn render_loops_tree(state: &AppState, frame: &mut Frame, area: Rect) {
                 "plan" => "Plan: ",
                 "spec" => "Spec: ",
                 "phase" => "Phase: ",
+                "explore" => "Explore: ",
                 "ralph" => "",
                 _ => "",
             };
@@ -809,24 +1106,33 @@ fn render_loops_tree(state: &AppState, frame: &mut Frame, area: Rect) {
 
             // Build the line
             let style = if is_selected {
-                Style::default().bg(colors::SELECTED_BG)
+                Style::default().bg(theme.selected_bg)
             } else {
                 Style::default()
             };
 
-            let exec_status_color = status_color(&node.item.status);
+            let exec_status_color = theme.status_color(&node.item.status);
 
             // Build base spans
             let mut spans = vec![
-                Span::styled(prefix, Style::default().fg(colors::DIM)),
-                Span::styled(expand_icon, Style::default().fg(colors::DIM)),
+                Span::styled(prefix, Style::default().fg(theme.dim)),
+                Span::styled(expand_icon, Style::default().fg(theme.dim)),
                 Span::styled(status_icon_str, Style::default().fg(exec_status_color)),
                 Span::raw(" "),
-                Span::styled(type_indicator, Style::default().fg(colors::DIM)),
+                Span::styled(type_indicator, Style::default().fg(theme.dim)),
                 Span::styled(&node.item.name, style),
-                Span::styled(progress, Style::default().fg(colors::DIM)),
+                Span::styled(progress, Style::default().fg(theme.dim)),
             ];
 
+            // Dependency edges (siblings this node waits on)
+            let deps = state.dependency_names(&node.item);
+            if !deps.is_empty() {
+                spans.push(Span::styled(
+                    format!(" ⇠ {}", deps.join(", ")),
+                    Style::default().fg(theme.dim),
+                ));
+            }
+
             // Add artifact info if present (e.g., "→ plan.md ✓")
             if let Some(ref artifact_file) = node.item.artifact_file {
                 // Extract just the filename from the path
@@ -837,13 +1143,16 @@ fn render_loops_tree(state: &AppState, frame: &mut Frame, area: Rect) {
 
                 // Get artifact status icon and color
                 let (artifact_icon, artifact_color) = if let Some(ref status) = node.item.artifact_status {
-                    (status_icon(status), status_color(status))
+                    (status_icon(status), theme.status_color(status))
                 } else {
-                    ("○", Color::DarkGray)
+                    ("○", theme.dim)
                 };
 
-                spans.push(Span::styled(" → ", Style::default().fg(colors::DIM)));
-                spans.push(Span::styled(format!("{} ", filename), Style::default().fg(Color::Cyan)));
+                spans.push(Span::styled(" → ", Style::default().fg(theme.dim)));
+                spans.push(Span::styled(
+                    format!("{} ", filename),
+                    Style::default().fg(theme.accent),
+                ));
                 spans.push(Span::styled(artifact_icon, Style::default().fg(artifact_color)));
             }
 
@@ -867,7 +1176,7 @@ fn render_loops_tree(state: &AppState, frame: &mut Frame, area: Rect) {
             Block::default()
                 .borders(Borders::ALL)
                 .title(format!(" Loops ({}) ", tree.len()))
-                .border_style(Style::default().fg(colors::HEADER)),
+                .border_style(Style::default().fg(theme.header)),
         )
         .scroll((scroll_offset as u16, 0));
 
@@ -875,6 +1184,7 @@ fn render_loops_tree(state: &AppState, frame: &mut Frame, area: Rect) {
 
     if tree.is_empty() {
         render_empty_message(
+            &theme,
             frame,
             area,
             "No loops yet. Use the Plan pane (Tab) to create a new Plan.",
@@ -938,34 +1248,54 @@ fn build_tree_prefix(tree: &LoopTree, id: &str, depth: usize) -> String {
 /// Render Logs view
 fn render_logs_view(state: &AppState, frame: &mut Frame, area: Rect) {
     trace!("render_logs_view: called");
+    let theme = state.theme;
     let target_id = if let View::Logs { target_id } = &state.current_view {
         target_id.clone()
     } else {
         return;
     };
 
-    // First, add stored logs from completed iterations
+    // Stored logs, grouped under a header per iteration
     let mut display_lines: Vec<Line> = state
-        .logs
-        .iter()
-        .map(|entry| {
-            let prefix_style = if entry.is_error {
-                Style::default().fg(colors::FAILED)
-            } else if entry.is_stdout {
-                Style::default().fg(Color::Cyan)
-            } else {
-                Style::default().fg(colors::DIM)
-            };
+        .logs_rows()
+        .into_iter()
+        .map(|row| match row {
+            LogRow::Header(iteration) => {
+                let summary = state.logs_iterations.get(&iteration).cloned().unwrap_or_default();
+                let marker = if state.logs_collapsed.contains(&iteration) { "▶ " } else { "▼ " };
+                let color = match summary.passed() {
+                    Some(true) => theme.success,
+                    Some(false) => theme.failed,
+                    None => theme.running,
+                };
+                let mut style = Style::default().fg(color).add_modifier(Modifier::BOLD);
+                if state.logs_selected == Some(iteration) {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                Line::from(Span::styled(
+                    format!("{}{}", marker, summary.header(iteration, &state.logs_model)),
+                    style,
+                ))
+            }
+            LogRow::Entry(entry) => {
+                let prefix_style = if entry.is_error {
+                    Style::default().fg(theme.failed)
+                } else if entry.is_stdout {
+                    Style::default().fg(theme.accent)
+                } else {
+                    Style::default().fg(theme.dim)
+                };
 
-            let prefix = if entry.is_error {
-                format!("[iter {}] ERROR: ", entry.iteration)
-            } else if entry.is_stdout {
-                format!("[iter {}] STDOUT: ", entry.iteration)
-            } else {
-                format!("[iter {}] ", entry.iteration)
-            };
+                let prefix = if entry.is_error {
+                    format!("  [iter {}] ERROR: ", entry.iteration)
+                } else if entry.is_stdout {
+                    format!("  [iter {}] STDOUT: ", entry.iteration)
+                } else {
+                    format!("  [iter {}] ", entry.iteration)
+                };
 
-            Line::from(vec![Span::styled(prefix, prefix_style), Span::raw(&entry.text)])
+                Line::from(vec![Span::styled(prefix, prefix_style), Span::raw(&entry.text)])
+            }
         })
         .collect();
 
@@ -975,12 +1305,12 @@ fn render_logs_view(state: &AppState, frame: &mut Frame, area: Rect) {
             display_lines.push(Line::from(""));
             display_lines.push(Line::from(Span::styled(
                 "── Live Output ──",
-                Style::default().fg(Color::Green).add_modifier(Modifier::BOLD),
+                Style::default().fg(theme.success).add_modifier(Modifier::BOLD),
             )));
         }
         for line in live_buf.content.lines() {
             display_lines.push(Line::from(vec![
-                Span::styled("[live] ", Style::default().fg(Color::Green)),
+                Span::styled("[live] ", Style::default().fg(theme.success)),
                 Span::raw(line.to_string()),
             ]));
         }
@@ -1009,7 +1339,7 @@ fn render_logs_view(state: &AppState, frame: &mut Frame, area: Rect) {
             Block::default()
                 .borders(Borders::ALL)
                 .title(title)
-                .border_style(Style::default().fg(colors::HEADER)),
+                .border_style(Style::default().fg(theme.header)),
         )
         .wrap(Wrap { trim: false })
         .scroll((state.logs_scroll as u16, 0));
@@ -1017,17 +1347,18 @@ fn render_logs_view(state: &AppState, frame: &mut Frame, area: Rect) {
     frame.render_widget(logs, area);
 
     if display_lines.is_empty() {
-        render_empty_message(frame, area, "No logs yet.");
+        render_empty_message(&theme, frame, area, "No logs yet.");
     }
 }
 
 /// Render Describe view with scroll support
 fn render_describe_view(state: &mut AppState, frame: &mut Frame, area: Rect) {
     trace!("render_describe_view: called");
+    let theme = state.theme;
     let data = match &state.describe_data {
         Some(d) => d,
         None => {
-            render_empty_message(frame, area, "Loading...");
+            render_empty_message(&theme, frame, area, "Loading...");
             return;
         }
     };
@@ -1043,7 +1374,7 @@ fn render_describe_view(state: &mut AppState, frame: &mut Frame, area: Rect) {
         ]),
         Line::from(vec![
             Span::styled("Status:      ", Style::default().add_modifier(Modifier::BOLD)),
-            Span::styled(&data.status, Style::default().fg(status_color(&data.status))),
+            Span::styled(&data.status, Style::default().fg(theme.status_color(&data.status))),
         ]),
     ];
 
@@ -1101,18 +1432,18 @@ fn render_describe_view(state: &mut AppState, frame: &mut Frame, area: Rect) {
                 };
                 lines.push(Line::from(vec![
                     Span::raw("  Live:      "),
-                    Span::styled(format!("{} lines", line_count), Style::default().fg(Color::Green)),
+                    Span::styled(format!("{} lines", line_count), Style::default().fg(theme.success)),
                 ]));
                 if !truncated.is_empty() {
                     lines.push(Line::from(vec![
                         Span::raw("  Latest:    "),
-                        Span::styled(truncated, Style::default().fg(Color::Yellow)),
+                        Span::styled(truncated, Style::default().fg(theme.warning)),
                     ]));
                 }
             } else {
                 lines.push(Line::from(vec![
                     Span::raw("  Live:      "),
-                    Span::styled("waiting for output...", Style::default().fg(Color::DarkGray)),
+                    Span::styled("waiting for output...", Style::default().fg(theme.dim)),
                 ]));
             }
         } else if !exec.progress.is_empty() {
@@ -1131,7 +1462,7 @@ fn render_describe_view(state: &mut AppState, frame: &mut Frame, area: Rect) {
             // Show the full artifact path
             lines.push(Line::from(vec![
                 Span::raw("  Path:   "),
-                Span::styled(path, Style::default().fg(Color::Cyan)),
+                Span::styled(path, Style::default().fg(theme.accent)),
             ]));
         }
         if let Some(ref status) = data.artifact_status {
@@ -1139,7 +1470,7 @@ fn render_describe_view(state: &mut AppState, frame: &mut Frame, area: Rect) {
                 Span::raw("  Status: "),
                 Span::styled(
                     format!("{} {}", status_icon(status), status),
-                    Style::default().fg(status_color(status)),
+                    Style::default().fg(theme.status_color(status)),
                 ),
             ]));
         }
@@ -1154,10 +1485,58 @@ fn render_describe_view(state: &mut AppState, frame: &mut Frame, area: Rect) {
         )]));
         lines.push(Line::from(vec![
             Span::raw("  Path:   "),
-            Span::styled(worktree, Style::default().fg(Color::Cyan)),
+            Span::styled(worktree, Style::default().fg(theme.accent)),
         ]));
     }
 
+    // Path locks section (other loops are warned before editing these)
+    if !data.locked_paths.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![Span::styled(
+            "Path locks:",
+            Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+        )]));
+        for path in &data.locked_paths {
+            lines.push(Line::from(vec![
+                Span::raw("  "),
+                Span::styled(path, Style::default().fg(theme.warning)),
+            ]));
+        }
+    }
+
+    // Acceptance criteria section (re-checked every iteration)
+    if !data.acceptance.is_empty() {
+        lines.push(Line::from(""));
+        let met = data
+            .acceptance
+            .iter()
+            .filter(|c| c.status == CriterionStatus::Passed)
+            .count();
+        lines.push(Line::from(vec![Span::styled(
+            format!("Acceptance ({}/{}):", met, data.acceptance.len()),
+            Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+        )]));
+        for check in &data.acceptance {
+            let (marker, color) = match check.status {
+                CriterionStatus::Passed => ("✓", theme.success),
+                CriterionStatus::Failed => ("✗", theme.error),
+                CriterionStatus::Pending => ("·", theme.dim),
+            };
+            let mut spans = vec![
+                Span::raw("  "),
+                Span::styled(marker, Style::default().fg(color)),
+                Span::raw(format!(" {}", check.criterion)),
+            ];
+            if let Some(detail) = &check.detail {
+                spans.push(Span::styled(
+                    format!("  {}", detail.lines().last().unwrap_or("")),
+                    Style::default().fg(theme.dim),
+                ));
+            }
+            lines.push(Line::from(spans));
+        }
+    }
+
     // Aggregate Metrics section (only show if there's activity)
     if data.total_input_tokens > 0 || data.total_output_tokens > 0 || data.total_duration_ms > 0 {
         lines.push(Line::from(""));
@@ -1170,10 +1549,13 @@ fn render_describe_view(state: &mut AppState, frame: &mut Frame, area: Rect) {
             Span::raw("  Tokens:   "),
             Span::styled(
                 format_tokens(data.total_input_tokens),
-                Style::default().fg(Color::Green),
+                Style::default().fg(theme.success),
             ),
             Span::raw(" in / "),
-            Span::styled(format_tokens(data.total_output_tokens), Style::default().fg(Color::Red)),
+            Span::styled(
+                format_tokens(data.total_output_tokens),
+                Style::default().fg(theme.error),
+            ),
             Span::raw(" out ("),
             Span::raw(format_tokens(total_tokens)),
             Span::raw(" total)"),
@@ -1208,7 +1590,7 @@ fn render_describe_view(state: &mut AppState, frame: &mut Frame, area: Rect) {
             if live_content.is_empty() {
                 lines.push(Line::from(vec![Span::styled(
                     "(Waiting for output...)",
-                    Style::default().fg(Color::Yellow),
+                    Style::default().fg(theme.warning),
                 )]));
             } else {
                 for line in live_content.lines() {
@@ -1223,7 +1605,7 @@ fn render_describe_view(state: &mut AppState, frame: &mut Frame, area: Rect) {
         } else {
             lines.push(Line::from(vec![Span::styled(
                 "(No output yet)",
-                Style::default().fg(Color::DarkGray),
+                Style::default().fg(theme.dim),
             )]));
         }
     } else if let Some(ref plan) = data.plan_content {
@@ -1270,7 +1652,7 @@ fn render_describe_view(state: &mut AppState, frame: &mut Frame, area: Rect) {
             Block::default()
                 .borders(Borders::ALL)
                 .title(title)
-                .border_style(Style::default().fg(colors::HEADER)),
+                .border_style(Style::default().fg(theme.header)),
         )
         .wrap(Wrap { trim: true })
         .scroll((scroll as u16, 0));
@@ -1278,69 +1660,168 @@ fn render_describe_view(state: &mut AppState, frame: &mut Frame, area: Rect) {
     frame.render_widget(describe, area);
 }
 
+/// Render two executions side by side, then the diff of their changes
+fn render_compare_view(state: &mut AppState, frame: &mut Frame, area: Rect) {
+    trace!("render_compare_view: called");
+    let theme = state.theme;
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Compare ")
+        .border_style(Style::default().fg(theme.header));
+    let Some(comparison) = &state.comparison else {
+        frame.render_widget(block, area);
+        render_empty_message(&theme, frame, area, "Loading comparison...");
+        return;
+    };
+
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let mut lines = vec![
+        Line::from(vec![Span::styled("A: ", bold), Span::raw(comparison.left.execution.id.clone())]),
+        Line::from(vec![Span::styled("B: ", bold), Span::raw(comparison.right.execution.id.clone())]),
+    ];
+    if !comparison.same_task() {
+        lines.push(Line::from(Span::styled(
+            "These executions do not look like runs of the same task",
+            Style::default().fg(theme.warning),
+        )));
+    }
+    lines.push(Line::from(""));
+
+    let rows = comparison.rows();
+    let width = rows.iter().map(|(_, left, _)| left.chars().count()).max().unwrap_or(0);
+    for (label, left, right) in rows {
+        let changed = if left == right { Style::default() } else { Style::default().fg(theme.highlight) };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:<12}", label), bold),
+            Span::raw(format!("{:<width$}  ", left)),
+            Span::styled(right, changed),
+        ]));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled("Changes", bold.add_modifier(Modifier::UNDERLINED))));
+    if comparison.files.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "Neither execution changed any files",
+            Style::default().fg(theme.dim),
+        )));
+    }
+    for file in &comparison.files {
+        let (mark, style) = match &file.delta {
+            PatchDelta::Same => ("=", Style::default().fg(theme.dim)),
+            PatchDelta::OnlyLeft => ("A", Style::default().fg(theme.warning)),
+            PatchDelta::OnlyRight => ("B", Style::default().fg(theme.warning)),
+            PatchDelta::Differs(_) => ("~", Style::default().fg(theme.highlight)),
+        };
+        lines.push(Line::from(Span::styled(format!("{} {}", mark, file.path), style)));
+        if let PatchDelta::Differs(delta) = &file.delta {
+            for line in delta {
+                let (side, text) = match line {
+                    DeltaLine::Left(text) => ("A", text),
+                    DeltaLine::Right(text) => ("B", text),
+                };
+                let color = if text.starts_with('+') { theme.success } else { theme.error };
+                lines.push(Line::from(vec![
+                    Span::styled(format!("    {} ", side), bold),
+                    Span::styled(text.clone(), Style::default().fg(color)),
+                ]));
+            }
+        }
+    }
+
+    let viewport_height = area.height.saturating_sub(2) as usize;
+    let max_scroll = lines.len().saturating_sub(viewport_height);
+    state.describe_max_scroll = max_scroll;
+    let scroll = state.describe_scroll.min(max_scroll);
+
+    let paragraph = Paragraph::new(lines).block(block).scroll((scroll as u16, 0));
+    frame.render_widget(paragraph, area);
+}
+
 /// Render footer with context-sensitive keybinds
 fn render_footer(state: &AppState, frame: &mut Frame, area: Rect) {
     trace!(?state.interaction_mode, "render_footer: called");
+    let theme = state.theme;
+    let key = |action: Action| format!("[{}]", state.keymap.label(action));
     let content = match &state.interaction_mode {
         InteractionMode::Filter(text) => Line::from(vec![
-            Span::styled("/", Style::default().fg(colors::KEYBIND)),
+            Span::styled("/", Style::default().fg(theme.keybind)),
             Span::raw(text),
             Span::styled("_", Style::default().add_modifier(Modifier::SLOW_BLINK)),
         ]),
         InteractionMode::Command(text) => Line::from(vec![
-            Span::styled(":", Style::default().fg(colors::KEYBIND)),
+            Span::styled(":", Style::default().fg(theme.keybind)),
             Span::raw(text),
             Span::styled("_", Style::default().add_modifier(Modifier::SLOW_BLINK)),
         ]),
         InteractionMode::TaskInput(text) => Line::from(vec![
             Span::styled(
                 "New Task: ",
-                Style::default().fg(colors::KEYBIND).add_modifier(Modifier::BOLD),
+                Style::default().fg(theme.keybind).add_modifier(Modifier::BOLD),
             ),
             Span::raw(text),
             Span::styled("_", Style::default().add_modifier(Modifier::SLOW_BLINK)),
-            Span::styled("  (Enter to create, Esc to cancel)", Style::default().fg(colors::DIM)),
+            Span::styled("  (Enter to create, Esc to cancel)", Style::default().fg(theme.dim)),
         ]),
         _ => {
             // Show error or context-sensitive keybinds
             if let Some(ref error) = state.error_message {
                 Line::from(Span::styled(
                     format!(" Error: {}", error),
-                    Style::default().fg(colors::FAILED),
+                    Style::default().fg(theme.failed),
                 ))
             } else {
                 // Show keybinds based on current view
                 let keybinds = match &state.current_view {
                     View::Repl => {
                         if state.repl_mode == ReplMode::Plan {
-                            vec![("[Enter]", "Send"), ("/create", "Create Plan"), ("/clear", "Clear")]
+                            vec![
+                                ("[Enter]".to_string(), "Send"),
+                                ("/create".to_string(), "Create Plan"),
+                                ("/clear".to_string(), "Clear"),
+                            ]
                         } else {
-                            vec![("[Enter]", "Send"), ("/clear", "Clear")]
+                            vec![("[Enter]".to_string(), "Send"), ("/clear".to_string(), "Clear")]
                         }
                     }
                     View::Loops => vec![
-                        ("[Enter]", "Describe"),
-                        ("[s]", "State"),
-                        ("[o]", "Output"),
-                        ("[L]", "Logs"),
-                        ("[x]", "Cancel"),
+                        (key(Action::Select), "Describe"),
+                        (key(Action::ToggleState), "State"),
+                        (key(Action::Output), "Output"),
+                        (key(Action::Loops), "Logs"),
+                        (key(Action::Pin), "Pin"),
+                        (key(Action::Cancel), "Cancel"),
                     ],
                     View::Records { .. } => vec![
-                        ("[Enter]", "Children"),
-                        ("[d]", "Describe"),
-                        ("[l]", "Logs"),
-                        ("[Esc]", "Back"),
+                        (key(Action::Select), "Children"),
+                        (key(Action::Describe), "Describe"),
+                        (key(Action::Logs), "Logs"),
+                        (key(Action::Back), "Back"),
                     ],
                     View::Executions => vec![
-                        ("[n]", "New Task"),
-                        ("[d]", "Describe"),
-                        ("[l]", "Logs"),
-                        ("[x]", "Cancel"),
-                        ("[D]", "Delete"),
+                        (key(Action::NewTask), "New Task"),
+                        (key(Action::Describe), "Describe"),
+                        (key(Action::Logs), "Logs"),
+                        (key(Action::Pin), "Pin"),
+                        (key(Action::Cancel), "Cancel"),
+                        (key(Action::Delete), "Delete"),
+                        (key(Action::CherryPick), "Pick"),
                     ],
-                    View::Logs { .. } => vec![("[Esc]", "Back"), ("[f]", "Follow")],
+                    View::Logs { .. } => vec![
+                        (key(Action::Down), "Next Iter"),
+                        (key(Action::Select), "Collapse"),
+                        (key(Action::Back), "Back"),
+                        (key(Action::Follow), "Follow"),
+                    ],
+                    View::Summary => vec![(key(Action::Back), "Back")],
+                    View::Compare { .. } => vec![(key(Action::Down), "Scroll"), (key(Action::Back), "Back")],
                     View::Describe { .. } => {
-                        vec![("[Esc]", "Back"), ("[s]", "State"), ("[o]", "Output"), ("[l]", "Logs")]
+                        vec![
+                            (key(Action::Back), "Back"),
+                            (key(Action::ToggleState), "State"),
+                            (key(Action::Output), "Output"),
+                            (key(Action::Logs), "Logs"),
+                        ]
                     }
                 };
 
@@ -1349,7 +1830,7 @@ fn render_footer(state: &AppState, frame: &mut Frame, area: Rect) {
                 for (key, action) in keybinds {
                     left_spans.push(Span::styled(
                         key,
-                        Style::default().fg(colors::KEYBIND).add_modifier(Modifier::BOLD),
+                        Style::default().fg(theme.keybind).add_modifier(Modifier::BOLD),
                     ));
                     left_spans.push(Span::raw(format!(" {} ", action)));
                 }
@@ -1357,13 +1838,19 @@ fn render_footer(state: &AppState, frame: &mut Frame, area: Rect) {
                 // Right side: Views, Help, Quit (left-justified grouping)
                 let right_line = Line::from(vec![
                     Span::styled(
-                        "[Tab]",
-                        Style::default().fg(colors::KEYBIND).add_modifier(Modifier::BOLD),
+                        key(Action::NextView),
+                        Style::default().fg(theme.keybind).add_modifier(Modifier::BOLD),
                     ),
                     Span::raw(" Views "),
-                    Span::styled("[?]", Style::default().fg(colors::KEYBIND).add_modifier(Modifier::BOLD)),
+                    Span::styled(
+                        key(Action::Help),
+                        Style::default().fg(theme.keybind).add_modifier(Modifier::BOLD),
+                    ),
                     Span::raw(" Help "),
-                    Span::styled("[q]", Style::default().fg(colors::KEYBIND).add_modifier(Modifier::BOLD)),
+                    Span::styled(
+                        key(Action::Quit),
+                        Style::default().fg(theme.keybind).add_modifier(Modifier::BOLD),
+                    ),
                     Span::raw(" Quit "),
                 ]);
 
@@ -1392,74 +1879,124 @@ fn render_footer(state: &AppState, frame: &mut Frame, area: Rect) {
 }
 
 /// Render help overlay
-fn render_help_overlay(frame: &mut Frame, area: Rect) {
+fn render_help_overlay(state: &AppState, frame: &mut Frame, area: Rect) {
     trace!("render_help_overlay: called");
+    let theme = &state.theme;
+    let key = |action: Action| state.keymap.label(action);
     let popup_area = centered_rect(60, 70, area);
     frame.render_widget(Clear, popup_area);
 
-    let help_text = vec![
+    let mut help_text = vec![
         Line::from(vec![Span::styled(
             "Keyboard Shortcuts",
             Style::default()
                 .add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
-                .fg(colors::HEADER),
+                .fg(theme.header),
         )]),
         Line::from(""),
         Line::from(vec![Span::styled(
             "Global",
             Style::default().add_modifier(Modifier::BOLD),
         )]),
-        key_line("Tab", "Cycle views (Chat/Plan → Executions → Records)"),
-        key_line(":", "Command mode (:records, :executions, :<type>)"),
-        key_line("/", "Filter current view"),
-        key_line("?", "Toggle help"),
-        key_line("q", "Quit"),
-        key_line("Esc", "Back / Clear filter"),
+        key_line(
+            theme,
+            &key(Action::NextView),
+            "Cycle views (Chat/Plan → Executions → Records)",
+        ),
+        key_line(
+            theme,
+            &key(Action::Command),
+            "Command mode (:records, :executions, :summary, :<type>)",
+        ),
+        key_line(
+            theme,
+            &key(Action::Filter),
+            "Filter current view (words, tag:, status:, type:)",
+        ),
+        key_line(theme, ":filter", "Apply a saved filter (:filter <name>)"),
+        key_line(theme, &key(Action::Help), "Toggle help"),
+        key_line(theme, &key(Action::Quit), "Quit"),
+        key_line(theme, &key(Action::Back), "Back / Clear filter"),
         Line::from(""),
         Line::from(vec![Span::styled(
             "Chat/Plan View",
             Style::default().add_modifier(Modifier::BOLD),
         )]),
-        key_line("Enter", "Send message (Chat) or create plan (Plan)"),
-        key_line("/create", "Create plan from conversation (Rule of Five)"),
-        key_line("/clear", "Clear conversation history"),
-        key_line("o", "Toggle tool output expand/collapse"),
+        key_line(theme, "Enter", "Send message (Chat) or create plan (Plan)"),
+        key_line(theme, "Tab", "Complete /command (when typing one)"),
+        key_line(theme, "o", "Toggle tool output expand/collapse"),
         Line::from(""),
         Line::from(vec![Span::styled(
             "Navigation",
             Style::default().add_modifier(Modifier::BOLD),
         )]),
-        key_line("j/↓", "Move down"),
-        key_line("k/↑", "Move up"),
-        key_line("g", "Go to top"),
-        key_line("G", "Go to bottom"),
-        key_line("Enter", "Drill into selected"),
+        key_line(theme, &format!("{}/↓", key(Action::Down)), "Move down"),
+        key_line(theme, &format!("{}/↑", key(Action::Up)), "Move up"),
+        key_line(theme, &key(Action::Top), "Go to top"),
+        key_line(theme, &key(Action::Bottom), "Go to bottom"),
+        key_line(theme, &key(Action::Select), "Drill into selected"),
         Line::from(""),
         Line::from(vec![Span::styled(
             "Actions",
             Style::default().add_modifier(Modifier::BOLD),
         )]),
-        key_line("l", "View logs/progress"),
-        key_line("d", "Describe (full details)"),
-        key_line("x", "Cancel selected"),
-        key_line("p", "Pause selected"),
-        key_line("r", "Resume selected"),
-        key_line("s", "Start draft (begin execution)"),
-        key_line("D", "Delete selected"),
+        key_line(theme, &key(Action::Logs), "View logs/progress"),
+        key_line(theme, &key(Action::Describe), "Describe (full details)"),
+        key_line(theme, &key(Action::Cancel), "Cancel selected"),
+        key_line(theme, &key(Action::Pin), "Pin/unpin to side pane"),
+        key_line(theme, "r", "Resume selected"),
+        key_line(theme, &key(Action::ToggleState), "Start draft (begin execution)"),
+        key_line(theme, &key(Action::Delete), "Delete selected"),
+        key_line(theme, &key(Action::CherryPick), "Cherry-pick commits onto picked/<id>"),
+        key_line(
+            theme,
+            &key(Action::Mark),
+            "Mark for bulk action (x/D or :pause/:resume/:cancel/:delete)",
+        ),
+        key_line(theme, &key(Action::Compare), "Compare the two marked executions"),
         Line::from(""),
         Line::from(vec![Span::styled(
             "Logs View",
             Style::default().add_modifier(Modifier::BOLD),
         )]),
-        key_line("f", "Toggle follow mode"),
+        key_line(theme, &key(Action::Down), "Select next iteration (Up: previous)"),
+        key_line(theme, &key(Action::Select), "Collapse/expand selected iteration"),
+        key_line(theme, &key(Action::Follow), "Toggle follow mode"),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "Layout",
+            Style::default().add_modifier(Modifier::BOLD),
+        )]),
+        key_line(
+            theme,
+            &key(Action::CycleSplit),
+            "Cycle split (off/side-by-side/stacked)",
+        ),
+        key_line(
+            theme,
+            &format!("{}/{}", key(Action::ShrinkPane), key(Action::GrowPane)),
+            "Resize split",
+        ),
+        key_line(theme, &key(Action::Unpin), "Unpin execution"),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "REPL Commands",
+            Style::default().add_modifier(Modifier::BOLD),
+        )]),
     ];
+    help_text.extend(
+        state
+            .commands
+            .iter()
+            .map(|command| key_line(theme, &command.usage, &command.help)),
+    );
 
     let help = Paragraph::new(help_text)
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .title(" Help (? to close) ")
-                .style(Style::default().bg(Color::Black)),
+                .style(Style::default().bg(theme.popup_bg)),
         )
         .wrap(Wrap { trim: true });
 
@@ -1467,36 +2004,36 @@ fn render_help_overlay(frame: &mut Frame, area: Rect) {
 }
 
 /// Helper to create a key binding line
-fn key_line<'a>(key: &'a str, desc: &'a str) -> Line<'a> {
+fn key_line<'a>(theme: &Theme, key: &str, desc: &'a str) -> Line<'a> {
     Line::from(vec![
         Span::raw("  "),
-        Span::styled(format!("{:<12}", key), Style::default().fg(colors::KEYBIND)),
+        Span::styled(format!("{:<12}", key), Style::default().fg(theme.keybind)),
         Span::raw(desc),
     ])
 }
 
 /// Render confirmation dialog
-fn render_confirm_dialog(dialog: &ConfirmDialog, frame: &mut Frame, area: Rect) {
+fn render_confirm_dialog(theme: &Theme, dialog: &ConfirmDialog, frame: &mut Frame, area: Rect) {
     trace!("render_confirm_dialog: called");
     let popup_area = centered_rect(50, 20, area);
     frame.render_widget(Clear, popup_area);
 
     let yes_style = if dialog.selected_button {
         Style::default()
-            .fg(Color::Black)
-            .bg(Color::Green)
+            .fg(theme.inverse_text)
+            .bg(theme.success)
             .add_modifier(Modifier::BOLD)
     } else {
-        Style::default().fg(Color::Green)
+        Style::default().fg(theme.success)
     };
 
     let no_style = if !dialog.selected_button {
         Style::default()
-            .fg(Color::Black)
-            .bg(Color::Red)
+            .fg(theme.inverse_text)
+            .bg(theme.error)
             .add_modifier(Modifier::BOLD)
     } else {
-        Style::default().fg(Color::Red)
+        Style::default().fg(theme.error)
     };
 
     let content = vec![
@@ -1512,7 +2049,7 @@ fn render_confirm_dialog(dialog: &ConfirmDialog, frame: &mut Frame, area: Rect)
         Line::from(""),
         Line::from(vec![Span::styled(
             "  Tab/←→: switch  Enter: confirm  Esc: cancel",
-            Style::default().fg(Color::DarkGray),
+            Style::default().fg(theme.dim),
         )]),
     ];
 
@@ -1521,7 +2058,7 @@ fn render_confirm_dialog(dialog: &ConfirmDialog, frame: &mut Frame, area: Rect)
             Block::default()
                 .borders(Borders::ALL)
                 .title(" Confirm ")
-                .style(Style::default().bg(Color::Black)),
+                .style(Style::default().bg(theme.popup_bg)),
         )
         .alignment(ratatui::layout::Alignment::Center);
 
@@ -1529,7 +2066,7 @@ fn render_confirm_dialog(dialog: &ConfirmDialog, frame: &mut Frame, area: Rect)
 }
 
 /// Render empty state message
-fn render_empty_message(frame: &mut Frame, area: Rect, message: &str) {
+fn render_empty_message(theme: &Theme, frame: &mut Frame, area: Rect, message: &str) {
     trace!(%message, "render_empty_message: called");
     let inner = area.inner(ratatui::layout::Margin {
         horizontal: 2,
@@ -1537,7 +2074,7 @@ fn render_empty_message(frame: &mut Frame, area: Rect, message: &str) {
     });
 
     let empty = Paragraph::new(message)
-        .style(Style::default().fg(Color::DarkGray))
+        .style(Style::default().fg(theme.dim))
         .alignment(ratatui::layout::Alignment::Center);
 
     frame.render_widget(empty, inner);
@@ -1572,7 +2109,7 @@ fn truncate_str(s: &str, max_len: usize) -> &str {
 }
 
 /// Format token count for display (e.g., "1.2K", "3.5M")
-fn format_tokens(count: u64) -> String {
+pub(super) fn format_tokens(count: u64) -> String {
     trace!(count, "format_tokens: called");
     if count >= 1_000_000 {
         format!("{:.1}M", count as f64 / 1_000_000.0)
@@ -1595,7 +2132,7 @@ fn format_streaming_duration(d: std::time::Duration) -> String {
 }
 
 /// Format duration from milliseconds for display (e.g., "45s", "1m 15s", "2h 30m")
-fn format_duration_ms(ms: u64) -> String {
+pub(super) fn format_duration_ms(ms: u64) -> String {
     trace!(ms, "format_duration_ms: called");
     let secs = ms / 1000;
     let mins = secs / 60;
@@ -0,0 +1,486 @@
+//! Compact live view of a single execution (`td exec watch`)
+//!
+//! One screen for a small terminal: status, iteration and phase, token
+//! counts, the latest validation run and the tail of the execution's output
+//! (the same text `td exec logs` prints). The view follows the daemon's live
+//! events, polls the stored execution for its status, and exits when the
+//! execution terminates (or on q, Esc or Ctrl+C).
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use eyre::Result;
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph};
+use tracing::{debug, trace};
+
+use super::events::{Event as TermEvent, EventHandler};
+use super::theme::Theme;
+use super::views::{format_duration_ms, format_tokens, status_icon};
+use crate::domain::{LoopExecution, LoopExecutionStatus};
+use crate::events::Event;
+use crate::ipc::EventStream;
+use crate::logs::{FollowEnd, LogPrinter, STATUS_POLL_INTERVAL, spawn_reader};
+use crate::state::StateManager;
+
+/// Output lines kept for the tail
+const MAX_TAIL_LINES: usize = 500;
+
+/// How often the screen is redrawn when nothing happens
+const TICK_RATE: Duration = Duration::from_millis(250);
+
+/// The execution's most recent validation run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationState {
+    /// No validation has run yet
+    NotRun,
+    /// The command is running
+    Running { command: String },
+    /// The command exited
+    Finished {
+        command: String,
+        exit_code: i32,
+        duration_ms: u64,
+    },
+}
+
+/// What the watch screen shows, built from the execution and its events
+pub struct WatchView {
+    exec: LoopExecution,
+    max_iterations: Option<u32>,
+    iteration: u32,
+    phase: Option<String>,
+    validation: ValidationState,
+    printer: LogPrinter,
+    /// Output lines, oldest first; the last one is still being written
+    tail: VecDeque<String>,
+}
+
+impl WatchView {
+    /// Watch `exec`, whose loop stops after `max_iterations` (None: unknown)
+    pub fn new(exec: LoopExecution, max_iterations: Option<u32>) -> Self {
+        debug!(exec_id = %exec.id, ?max_iterations, "WatchView::new: called");
+        Self {
+            iteration: exec.iteration,
+            max_iterations: exec.max_iterations.or(max_iterations),
+            exec,
+            phase: None,
+            validation: ValidationState::NotRun,
+            printer: LogPrinter::new(),
+            tail: VecDeque::new(),
+        }
+    }
+
+    pub fn exec(&self) -> &LoopExecution {
+        &self.exec
+    }
+
+    pub fn validation(&self) -> &ValidationState {
+        &self.validation
+    }
+
+    /// Take in one of the execution's events (logged or live)
+    pub fn apply(&mut self, event: &Event) {
+        trace!(event = event.event_type(), "WatchView::apply: called");
+        match event {
+            Event::IterationStarted { iteration, .. } => self.iteration = self.iteration.max(*iteration),
+            Event::PhaseStarted {
+                phase_name,
+                phase_index,
+                total_phases,
+                ..
+            } => self.phase = Some(format!("{} ({}/{})", phase_name, phase_index + 1, total_phases)),
+            Event::ValidationStarted { command, .. } => {
+                self.validation = ValidationState::Running {
+                    command: command.clone(),
+                }
+            }
+            Event::ValidationCompleted {
+                exit_code, duration_ms, ..
+            } => {
+                let command = match &self.validation {
+                    ValidationState::Running { command } => command.clone(),
+                    _ => String::new(),
+                };
+                self.validation = ValidationState::Finished {
+                    command,
+                    exit_code: *exit_code,
+                    duration_ms: *duration_ms,
+                };
+            }
+            Event::LoopCompleted { success, .. } => {
+                let status = if *success {
+                    LoopExecutionStatus::Complete
+                } else {
+                    LoopExecutionStatus::Failed
+                };
+                self.exec.set_status(status);
+            }
+            _ => {}
+        }
+        if let Some(text) = self.printer.render(event) {
+            self.push_output(&text);
+        }
+    }
+
+    /// Take in the stored execution (status, iteration, token counts)
+    pub fn update(&mut self, exec: LoopExecution) {
+        debug!(exec_id = %exec.id, status = %exec.status, "WatchView::update: called");
+        self.iteration = self.iteration.max(exec.iteration);
+        if exec.max_iterations.is_some() {
+            self.max_iterations = exec.max_iterations;
+        }
+        // A LoopCompleted event may arrive before the stored status catches up
+        if !self.exec.is_terminal() || exec.is_terminal() {
+            self.exec = exec;
+        }
+    }
+
+    /// Append printed output to the tail, continuing its unfinished last line
+    fn push_output(&mut self, text: &str) {
+        let mut parts = text.split('\n');
+        if let Some(first) = parts.next() {
+            match self.tail.back_mut() {
+                Some(last) => last.push_str(first),
+                None => self.tail.push_back(first.to_string()),
+            }
+        }
+        self.tail.extend(parts.map(String::from));
+        while self.tail.len() > MAX_TAIL_LINES {
+            self.tail.pop_front();
+        }
+    }
+
+    /// The last `height` rows of output, wrapped at `width` characters
+    pub fn tail_rows(&self, width: usize, height: usize) -> Vec<String> {
+        trace!(width, height, "WatchView::tail_rows: called");
+        let width = width.max(1);
+        let mut lines: Vec<&String> = self.tail.iter().collect();
+        // The unfinished line is empty right after a newline
+        if lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+
+        let mut rows = Vec::new();
+        for line in lines.into_iter().rev() {
+            let chars: Vec<char> = line.chars().collect();
+            let mut wrapped: Vec<String> = chars.chunks(width).map(|chunk| chunk.iter().collect()).collect();
+            if wrapped.is_empty() {
+                wrapped.push(String::new());
+            }
+            for row in wrapped.into_iter().rev() {
+                if rows.len() == height {
+                    rows.reverse();
+                    return rows;
+                }
+                rows.push(row);
+            }
+        }
+        rows.reverse();
+        rows
+    }
+
+    /// Draw the view: a summary block, the output tail and a key hint
+    pub fn render(&self, frame: &mut Frame, theme: &Theme) {
+        trace!(exec_id = %self.exec.id, "WatchView::render: called");
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(6), // Summary
+                Constraint::Min(3),    // Output tail
+                Constraint::Length(1), // Keys
+            ])
+            .split(frame.area());
+
+        let status = self.exec.status.to_string();
+        let iteration = match self.max_iterations {
+            Some(max) => format!("iteration {}/{}", self.iteration, max),
+            None => format!("iteration {}", self.iteration),
+        };
+        let mut first = vec![
+            Span::styled(
+                format!("{} {}", status_icon(&status), status),
+                Style::default()
+                    .fg(theme.status_color(&status))
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(format!("  {}", iteration), Style::default().fg(theme.text)),
+        ];
+        if let Some(phase) = &self.phase {
+            first.push(Span::styled(format!("  phase {}", phase), Style::default().fg(theme.accent)));
+        }
+
+        let second = match (&self.exec.last_error, &self.exec.title) {
+            (Some(error), _) if self.exec.status == LoopExecutionStatus::Failed => {
+                Line::from(Span::styled(format!("error: {}", error), Style::default().fg(theme.error)))
+            }
+            (_, Some(title)) => Line::from(Span::styled(title.clone(), Style::default().fg(theme.dim))),
+            _ => Line::from(""),
+        };
+
+        let validation = match &self.validation {
+            ValidationState::NotRun => Span::styled("not run yet", Style::default().fg(theme.dim)),
+            ValidationState::Running { command } => {
+                Span::styled(format!("running: {}", command), Style::default().fg(theme.running))
+            }
+            ValidationState::Finished {
+                exit_code: 0,
+                duration_ms,
+                ..
+            } => Span::styled(
+                format!("✓ passed ({})", format_duration_ms(*duration_ms)),
+                Style::default().fg(theme.success),
+            ),
+            ValidationState::Finished {
+                exit_code, duration_ms, ..
+            } => Span::styled(
+                format!("✗ failed, exit {} ({})", exit_code, format_duration_ms(*duration_ms)),
+                Style::default().fg(theme.failed),
+            ),
+        };
+
+        let summary = vec![
+            Line::from(first),
+            second,
+            Line::from(vec![Span::styled("validation ", Style::default().fg(theme.dim)), validation]),
+            Line::from(Span::styled(
+                format!(
+                    "tokens {} in / {} out  ·  {}",
+                    format_tokens(self.exec.total_input_tokens),
+                    format_tokens(self.exec.total_output_tokens),
+                    format_duration_ms(self.exec.total_duration_ms)
+                ),
+                Style::default().fg(theme.dim),
+            )),
+        ];
+        let title = format!(" {} · {} ", self.exec.id, self.exec.loop_type);
+        frame.render_widget(
+            Paragraph::new(summary).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(title)
+                    .border_style(Style::default().fg(theme.header)),
+            ),
+            chunks[0],
+        );
+
+        let area = chunks[1];
+        let rows = self.tail_rows(area.width.saturating_sub(2) as usize, area.height.saturating_sub(2) as usize);
+        let output: Vec<Line> = rows.into_iter().map(|row| Line::from(Span::raw(row))).collect();
+        frame.render_widget(
+            Paragraph::new(output).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Output ")
+                    .border_style(Style::default().fg(theme.header)),
+            ),
+            area,
+        );
+
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::styled(" q", Style::default().fg(theme.keybind)),
+                Span::styled(" quit", Style::default().fg(theme.dim)),
+            ])),
+            chunks[2],
+        );
+    }
+}
+
+/// Whether a key ends the watch (q, Esc, Ctrl+C; raw mode turns Ctrl+C into a key)
+fn is_quit(key: &KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => true,
+        KeyCode::Char('c') => key.modifiers.contains(KeyModifiers::CONTROL),
+        _ => false,
+    }
+}
+
+/// Show `view` full screen, following `stream` (None: status polling only)
+///
+/// Returns when the execution terminates, the daemon closes the stream or
+/// the user quits. The terminal is restored before returning.
+pub async fn watch(
+    state: &StateManager,
+    mut view: WatchView,
+    stream: Option<EventStream>,
+    theme: &Theme,
+) -> Result<FollowEnd> {
+    debug!(exec_id = %view.exec.id, live = stream.is_some(), "watch: called");
+    let mut terminal = super::init()?;
+
+    // Restore the terminal on every way out, errors included
+    struct TerminalGuard;
+    impl Drop for TerminalGuard {
+        fn drop(&mut self) {
+            let _ = super::restore();
+        }
+    }
+    let _guard = TerminalGuard;
+
+    let mut keys = EventHandler::new(TICK_RATE);
+    let (reader, mut rx) = match stream {
+        Some(stream) => {
+            let (reader, rx) = spawn_reader(stream);
+            (Some(reader), Some(rx))
+        }
+        None => (None, None),
+    };
+    let mut poll = tokio::time::interval(STATUS_POLL_INTERVAL);
+
+    let end = loop {
+        terminal.draw(|frame| view.render(frame, theme))?;
+        if view.exec.is_terminal() {
+            debug!(exec_id = %view.exec.id, status = %view.exec.status, "watch: execution finished");
+            break FollowEnd::Finished(view.exec.status);
+        }
+
+        tokio::select! {
+            next = async {
+                match rx.as_mut() {
+                    Some(rx) => rx.recv().await,
+                    None => std::future::pending().await,
+                }
+            } => match next {
+                Some(Ok(Some(event))) => view.apply(&event),
+                Some(Err(e)) => {
+                    reader.iter().for_each(|r| r.abort());
+                    return Err(e);
+                }
+                Some(Ok(None)) | None => {
+                    debug!(exec_id = %view.exec.id, "watch: daemon closed the stream");
+                    break FollowEnd::Disconnected;
+                }
+            },
+            _ = poll.tick() => {
+                if let Some(exec) = state.get_execution(&view.exec.id).await? {
+                    view.update(exec);
+                }
+            }
+            key = keys.next() => {
+                if let TermEvent::Key(key) = key?
+                    && is_quit(&key)
+                {
+                    debug!(exec_id = %view.exec.id, "watch: quit");
+                    break FollowEnd::Interrupted;
+                }
+            }
+        }
+    };
+    reader.iter().for_each(|r| r.abort());
+    Ok(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::IterationOutcome;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    fn view() -> WatchView {
+        let mut exec = LoopExecution::new("phase", "Add a parser");
+        exec.set_status(LoopExecutionStatus::Running);
+        WatchView::new(exec, Some(10))
+    }
+
+    fn events(id: &str) -> Vec<Event> {
+        vec![
+            Event::IterationStarted {
+                execution_id: id.to_string(),
+                iteration: 3,
+            },
+            Event::TokenReceived {
+                execution_id: id.to_string(),
+                iteration: 3,
+                token: "Fixing the ".to_string(),
+            },
+            Event::TokenReceived {
+                execution_id: id.to_string(),
+                iteration: 3,
+                token: "parser".to_string(),
+            },
+            Event::ValidationStarted {
+                execution_id: id.to_string(),
+                iteration: 3,
+                command: "cargo test".to_string(),
+            },
+            Event::ValidationCompleted {
+                execution_id: id.to_string(),
+                iteration: 3,
+                exit_code: 1,
+                duration_ms: 2500,
+            },
+            Event::IterationCompleted {
+                execution_id: id.to_string(),
+                iteration: 3,
+                outcome: IterationOutcome::ValidationFailed { exit_code: 1 },
+            },
+        ]
+    }
+
+    #[test]
+    fn test_apply_events() {
+        let mut view = view();
+        let id = view.exec().id.clone();
+        for event in events(&id) {
+            view.apply(&event);
+        }
+
+        assert_eq!(view.iteration, 3);
+        assert_eq!(
+            view.validation(),
+            &ValidationState::Finished {
+                command: "cargo test".to_string(),
+                exit_code: 1,
+                duration_ms: 2500
+            }
+        );
+        assert_eq!(
+            view.tail_rows(80, 3),
+            vec!["$ cargo test", "$ exit 1 (2500ms)", "-- iteration 3: validation failed (exit 1)"]
+        );
+        // Long lines wrap, and the rows kept are the last ones
+        assert_eq!(view.tail_rows(6, 2), vec!["led (e", "xit 1)"]);
+
+        view.apply(&Event::LoopCompleted {
+            execution_id: id,
+            success: false,
+            total_iterations: 3,
+        });
+        assert_eq!(view.exec().status, LoopExecutionStatus::Failed);
+        // A stale stored status does not undo the completion
+        let mut stale = view.exec().clone();
+        stale.set_status(LoopExecutionStatus::Running);
+        view.update(stale);
+        assert!(view.exec().is_terminal());
+    }
+
+    #[test]
+    fn test_render() {
+        let mut view = view();
+        let id = view.exec().id.clone();
+        for event in events(&id) {
+            view.apply(&event);
+        }
+
+        let mut terminal = Terminal::new(TestBackend::new(60, 12)).unwrap();
+        terminal.draw(|frame| view.render(frame, &Theme::default())).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .chunks(60)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>() + "\n")
+            .collect();
+
+        assert!(screen.contains("● running  iteration 3/10"), "{}", screen);
+        assert!(screen.contains("validation ✗ failed, exit 1 (2s)"), "{}", screen);
+        assert!(screen.contains("-- iteration 3: validation failed (exit 1)"), "{}", screen);
+        assert!(screen.contains(" q quit"), "{}", screen);
+    }
+}
@@ -0,0 +1,327 @@
+//! Multi-persona debate over a plan
+//!
+//! For plans that matter, a loop type can declare a `debate`: once the plan
+//! loop's draft passes validation, model personas (by default an architect and
+//! a skeptic) take turns arguing over it for a number of rounds, each seeing
+//! the task, the draft and everything said so far. A judge pass then weighs
+//! the debate and writes the final plan. The whole exchange is kept as a
+//! [`DebateTranscript`] and written next to the plan.
+
+use std::sync::Arc;
+
+use eyre::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::llm::{CompletionRequest, LlmClient, Message};
+
+const DEFAULT_JUDGE_PROMPT: &str = "You are the judge of a debate about a plan. Weigh each participant's \
+     arguments on their merits, not on who argued last. Keep what the draft got right, fix what the debate \
+     showed to be wrong or missing, and drop objections that did not hold up.";
+
+/// A debate participant
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Persona {
+    /// Name shown in the transcript, unique within the debate
+    pub name: String,
+
+    /// Who the persona is and what it argues for (its system prompt)
+    pub prompt: String,
+}
+
+impl Persona {
+    fn new(name: &str, prompt: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            prompt: prompt.to_string(),
+        }
+    }
+}
+
+/// Debate settings (`debate` in loop YAML)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct DebateConfig {
+    /// Participants, in speaking order
+    pub personas: Vec<Persona>,
+
+    /// Rounds in which every persona speaks once
+    pub rounds: u32,
+
+    /// System prompt of the judge that writes the final plan
+    pub judge: String,
+
+    /// Response limit for each turn and for the judge
+    pub max_tokens: u32,
+
+    /// Transcript file name, written next to the plan
+    pub transcript: String,
+}
+
+impl Default for DebateConfig {
+    fn default() -> Self {
+        Self {
+            personas: vec![
+                Persona::new(
+                    "architect",
+                    "You are the architect. Defend and strengthen the plan: argue for the structure, interfaces \
+                     and sequencing that best solve the task, and concede points that are genuinely right.",
+                ),
+                Persona::new(
+                    "skeptic",
+                    "You are the skeptic. Challenge the plan: find wrong or unstated assumptions, missing \
+                     requirements, risks, edge cases and simpler alternatives. Be specific.",
+                ),
+            ],
+            rounds: 2,
+            judge: DEFAULT_JUDGE_PROMPT.to_string(),
+            max_tokens: 4096,
+            transcript: "debate.md".to_string(),
+        }
+    }
+}
+
+impl DebateConfig {
+    /// Check the debate is runnable: two or more uniquely named personas, at least one round
+    pub fn validate(&self) -> Result<(), String> {
+        debug!(personas = self.personas.len(), rounds = self.rounds, "DebateConfig::validate: called");
+        if self.personas.len() < 2 {
+            return Err("a debate needs at least two personas".to_string());
+        }
+        let mut seen = std::collections::HashSet::new();
+        for persona in &self.personas {
+            if persona.name.trim().is_empty() {
+                return Err("debate persona without a name".to_string());
+            }
+            if !seen.insert(persona.name.as_str()) {
+                return Err(format!("duplicate debate persona '{}'", persona.name));
+            }
+        }
+        if self.rounds == 0 {
+            return Err("rounds must be at least 1".to_string());
+        }
+        if self.transcript.trim().is_empty() || self.transcript.contains('/') {
+            return Err("transcript must be a file name".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// One persona's contribution to a round
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DebateTurn {
+    /// 1-based round number
+    pub round: u32,
+    pub persona: String,
+    pub text: String,
+}
+
+/// Everything said in a debate, and the plan the judge wrote from it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DebateTranscript {
+    pub turns: Vec<DebateTurn>,
+    pub final_plan: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+impl DebateTranscript {
+    /// The turns so far as prompt text
+    fn render_turns(&self) -> String {
+        let mut text = String::new();
+        for turn in &self.turns {
+            text.push_str(&format!("### {} (round {})\n{}\n\n", turn.persona, turn.round, turn.text.trim()));
+        }
+        text
+    }
+
+    /// The transcript as a markdown document
+    pub fn render_markdown(&self) -> String {
+        debug!(turns = self.turns.len(), "DebateTranscript::render_markdown: called");
+        let mut text = String::from("# Plan Debate\n");
+        let mut round = 0;
+        for turn in &self.turns {
+            if turn.round != round {
+                round = turn.round;
+                text.push_str(&format!("\n## Round {}\n", round));
+            }
+            text.push_str(&format!("\n### {}\n\n{}\n", turn.persona, turn.text.trim()));
+        }
+        text.push_str(&format!("\n## Judge: Final Plan\n\n{}\n", self.final_plan.trim()));
+        text.push_str(&format!(
+            "\n---\nTokens: {} in, {} out\n",
+            self.input_tokens, self.output_tokens
+        ));
+        text
+    }
+}
+
+/// Runs a debate over a draft plan
+pub struct Debate {
+    llm: Arc<dyn LlmClient>,
+    config: DebateConfig,
+}
+
+impl Debate {
+    pub fn new(llm: Arc<dyn LlmClient>, config: DebateConfig) -> Self {
+        debug!(personas = config.personas.len(), rounds = config.rounds, "Debate::new: called");
+        Self { llm, config }
+    }
+
+    /// Debate `draft` (the plan for `task`) and have the judge write the final plan
+    pub async fn run(&self, task: &str, draft: &str) -> Result<DebateTranscript> {
+        debug!(task_len = task.len(), draft_len = draft.len(), "Debate::run: called");
+        let mut transcript = DebateTranscript::default();
+        let context = format!("## Task\n{}\n\n## Draft Plan\n{}\n", task.trim(), draft.trim());
+
+        for round in 1..=self.config.rounds {
+            for persona in &self.config.personas {
+                debug!(round, persona = %persona.name, "Debate::run: turn");
+                let prompt = format!(
+                    "{}\n## Debate So Far\n{}\nThis is round {} of {}. As {}, respond to the points made so far \
+                     and make your own case about the plan. Do not rewrite the plan.",
+                    context,
+                    if transcript.turns.is_empty() {
+                        "(you speak first)\n".to_string()
+                    } else {
+                        transcript.render_turns()
+                    },
+                    round,
+                    self.config.rounds,
+                    persona.name
+                );
+                let text = self
+                    .ask(&persona.prompt, prompt, &mut transcript)
+                    .await
+                    .with_context(|| format!("Debate turn of '{}' in round {} failed", persona.name, round))?;
+                transcript.turns.push(DebateTurn {
+                    round,
+                    persona: persona.name.clone(),
+                    text,
+                });
+            }
+        }
+
+        let prompt = format!(
+            "{}\n## Debate\n{}\nWrite the complete final plan in markdown, in the draft's structure. \
+             Respond with only the plan.",
+            context,
+            transcript.render_turns()
+        );
+        let plan = self
+            .ask(&self.config.judge, prompt, &mut transcript)
+            .await
+            .context("Debate judge pass failed")?;
+        transcript.final_plan = strip_fence(&plan).to_string();
+        debug!(turns = transcript.turns.len(), "Debate::run: complete");
+        Ok(transcript)
+    }
+
+    /// One completion, counting its tokens against the transcript
+    async fn ask(&self, system_prompt: &str, prompt: String, transcript: &mut DebateTranscript) -> Result<String> {
+        let request = CompletionRequest {
+            system_prompt: system_prompt.to_string(),
+            messages: vec![Message::user(prompt)],
+            max_tokens: self.config.max_tokens,
+            tools: vec![],
+        };
+        let response = self.llm.complete(request).await?;
+        transcript.input_tokens += response.usage.input_tokens;
+        transcript.output_tokens += response.usage.output_tokens;
+        response
+            .content
+            .filter(|c| !c.trim().is_empty())
+            .ok_or_else(|| eyre::eyre!("Response had no content"))
+    }
+}
+
+/// Drop a code fence wrapped around the whole response
+fn strip_fence(text: &str) -> &str {
+    let text = text.trim();
+    if let Some(rest) = text.strip_prefix("```")
+        && let Some(body) = rest.strip_suffix("```")
+        && let Some((_, body)) = body.split_once('\n')
+    {
+        return body.trim();
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::client::mock::MockLlmClient;
+    use crate::llm::{CompletionResponse, StopReason, TokenUsage};
+
+    fn response(content: &str) -> CompletionResponse {
+        CompletionResponse {
+            content: Some(content.to_string()),
+            tool_calls: vec![],
+            stop_reason: StopReason::EndTurn,
+            usage: TokenUsage {
+                input_tokens: 100,
+                output_tokens: 10,
+                ..Default::default()
+            },
+            served_by: None,
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(DebateConfig::default().validate().is_ok());
+
+        let mut config = DebateConfig::default();
+        config.personas.truncate(1);
+        assert!(config.validate().unwrap_err().contains("two personas"));
+
+        let mut config = DebateConfig::default();
+        config.personas[1].name = "architect".to_string();
+        assert!(config.validate().unwrap_err().contains("duplicate"));
+
+        let config = DebateConfig {
+            rounds: 0,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_debate_rounds_and_judge() {
+        let llm = Arc::new(MockLlmClient::new(vec![
+            response("Split the parser into its own module."),
+            response("The draft never says how errors are reported."),
+            response("Agreed, return a typed error."),
+            response("Then the CLI needs exit codes too."),
+            response("```markdown\n# Plan\n- parser module\n- typed errors and exit codes\n```"),
+        ]));
+        let debate = Debate::new(llm, DebateConfig::default());
+
+        let transcript = debate.run("Add a config parser", "# Plan\n- parser").await.unwrap();
+
+        let speakers: Vec<(u32, &str)> = transcript.turns.iter().map(|t| (t.round, t.persona.as_str())).collect();
+        assert_eq!(
+            speakers,
+            vec![(1, "architect"), (1, "skeptic"), (2, "architect"), (2, "skeptic")]
+        );
+        assert_eq!(transcript.final_plan, "# Plan\n- parser module\n- typed errors and exit codes");
+        assert_eq!((transcript.input_tokens, transcript.output_tokens), (500, 50));
+
+        let markdown = transcript.render_markdown();
+        assert!(markdown.contains("## Round 2\n\n### architect\n\nAgreed, return a typed error."));
+        assert!(markdown.contains("## Judge: Final Plan\n\n# Plan\n- parser module"));
+    }
+
+    #[tokio::test]
+    async fn test_debate_fails_without_judge() {
+        let llm = Arc::new(MockLlmClient::new(vec![response("a"), response("b")]));
+        let config = DebateConfig {
+            rounds: 1,
+            ..Default::default()
+        };
+
+        let err = Debate::new(llm, config).run("task", "draft").await.unwrap_err();
+        assert!(format!("{:#}", err).contains("judge"));
+    }
+}
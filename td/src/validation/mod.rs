@@ -1,7 +1,15 @@
 //! Validation module for plan refinement
 //!
-//! Implements the Rule of Five methodology for systematic plan review and improvement.
+//! Implements the Rule of Five methodology for systematic plan review and improvement,
+//! the configurable review pipelines loop types declare in their `review` section,
+//! and the persona debate a `debate` section holds over a finished plan.
 
+mod debate;
 mod rule_of_five;
 
-pub use rule_of_five::{PassResult, PlanRefinementContext, ReviewPass};
+pub use debate::{Debate, DebateConfig, DebateTranscript, DebateTurn, Persona};
+
+pub use rule_of_five::{
+    Convergence, PassResult, PlanRefinementContext, ReviewPass, ReviewPassConfig, ReviewPipeline, ReviewProgress,
+    ReviewStep,
+};
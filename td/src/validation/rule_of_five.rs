@@ -5,8 +5,15 @@
 //! different perspective.
 //!
 //! Task size guidelines: Small features: 2-3 passes. Large/critical: 4-5 passes.
+//!
+//! The passes a loop runs are configurable: a loop type's `review` section
+//! declares an ordered [`ReviewPipeline`] of named passes, each with its own
+//! prompt and convergence criteria, and the engine walks it with
+//! [`ReviewProgress`].
 
 use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
 use tracing::debug;
 
 /// Rule of Five pass definitions
@@ -132,6 +139,17 @@ impl ReviewPass {
         }
     }
 
+    /// Pass name as used in review pipelines (`draft`, `edge-cases`, ...)
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Draft => "draft",
+            Self::Correctness => "correctness",
+            Self::Clarity => "clarity",
+            Self::EdgeCases => "edge-cases",
+            Self::Excellence => "excellence",
+        }
+    }
+
     /// Get the numeric value (1-5)
     pub fn number(&self) -> u8 {
         debug!(?self, "ReviewPass::number: called");
@@ -364,6 +382,179 @@ impl PlanRefinementContext {
     }
 }
 
+/// When a configured review pass counts as converged
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct Convergence {
+    /// Command that must exit 0 (Handlebars over the execution context); unset: the loop's validation
+    pub command: Option<String>,
+
+    /// Also require an iteration that wrote no files (the review found nothing to fix)
+    pub unchanged: bool,
+
+    /// Move on after this many iterations even if not converged (0: no cap)
+    pub max_iterations: u32,
+}
+
+/// A named pass of a review pipeline
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewPassConfig {
+    /// Pass name, unique within the pipeline (e.g. `correctness`, `security`)
+    pub name: String,
+
+    /// One-line summary of what the pass checks
+    #[serde(default)]
+    pub description: String,
+
+    /// Instructions for this pass (Handlebars, exposed as `{{review-instructions}}`)
+    pub prompt: String,
+
+    /// When the pass is done
+    #[serde(default)]
+    pub converge: Convergence,
+}
+
+/// Ordered review passes a loop works through (`review` in loop YAML)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ReviewPipeline {
+    /// Passes in the order they run (empty: no review)
+    #[serde(default)]
+    pub passes: Vec<ReviewPassConfig>,
+
+    /// Finish early once this many passes in a row converge on their first iteration (0: run every pass)
+    #[serde(default)]
+    pub stop_after_clean: u32,
+}
+
+impl ReviewPipeline {
+    /// Check the pipeline is runnable: named passes, no duplicate names
+    pub fn validate(&self) -> Result<(), String> {
+        debug!(passes = self.passes.len(), "ReviewPipeline::validate: called");
+        let mut seen = std::collections::HashSet::new();
+        for pass in &self.passes {
+            if pass.name.trim().is_empty() {
+                return Err("review pass without a name".to_string());
+            }
+            if !seen.insert(pass.name.as_str()) {
+                return Err(format!("duplicate review pass '{}'", pass.name));
+            }
+        }
+        Ok(())
+    }
+
+    /// The classic five passes, built from [`ReviewPass`]
+    pub fn rule_of_five() -> Self {
+        debug!("ReviewPipeline::rule_of_five: called");
+        let passes = (1..=5)
+            .filter_map(ReviewPass::from_number)
+            .map(|pass| ReviewPassConfig {
+                name: pass.name().to_string(),
+                description: pass.description().to_string(),
+                prompt: pass.instructions().to_string(),
+                converge: Convergence {
+                    command: Some(format!(".taskdaemon/validators/{}", pass.validation_command())),
+                    ..Default::default()
+                },
+            })
+            .collect();
+        Self {
+            passes,
+            stop_after_clean: 2,
+        }
+    }
+}
+
+/// What recording an iteration did to the pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewStep {
+    /// The current pass needs another iteration
+    Stay,
+    /// Moved on to the next pass (`forced` when the pass hit its iteration cap)
+    Advanced { forced: bool },
+    /// Every pass is done, or enough passes in a row were clean
+    Complete,
+}
+
+/// Position of a running loop within its review pipeline
+#[derive(Debug, Clone)]
+pub struct ReviewProgress {
+    pipeline: ReviewPipeline,
+    current: usize,
+    pass_iterations: u32,
+    clean_streak: u32,
+}
+
+impl ReviewProgress {
+    /// Start at the first pass; None when the pipeline has no passes
+    pub fn new(pipeline: ReviewPipeline) -> Option<Self> {
+        debug!(passes = pipeline.passes.len(), "ReviewProgress::new: called");
+        if pipeline.passes.is_empty() {
+            return None;
+        }
+        Some(Self {
+            pipeline,
+            current: 0,
+            pass_iterations: 0,
+            clean_streak: 0,
+        })
+    }
+
+    /// The pass currently running (None once complete)
+    pub fn current(&self) -> Option<&ReviewPassConfig> {
+        self.pipeline.passes.get(self.current)
+    }
+
+    /// 1-based number of the current pass
+    pub fn number(&self) -> usize {
+        self.current + 1
+    }
+
+    /// Number of passes in the pipeline
+    pub fn total(&self) -> usize {
+        self.pipeline.passes.len()
+    }
+
+    /// Whether the pipeline has finished
+    pub fn is_complete(&self) -> bool {
+        self.current >= self.pipeline.passes.len()
+    }
+
+    /// Record one iteration of the current pass
+    pub fn record(&mut self, converged: bool) -> ReviewStep {
+        let Some(pass) = self.current() else {
+            return ReviewStep::Complete;
+        };
+        debug!(pass = %pass.name, converged, iterations = self.pass_iterations, "ReviewProgress::record: called");
+        let cap = pass.converge.max_iterations;
+        self.pass_iterations += 1;
+
+        let forced = !converged && cap > 0 && self.pass_iterations >= cap;
+        if !converged && !forced {
+            return ReviewStep::Stay;
+        }
+
+        let clean = converged && self.pass_iterations == 1;
+        self.clean_streak = if clean { self.clean_streak + 1 } else { 0 };
+        self.current += 1;
+        self.pass_iterations = 0;
+
+        let stop = self.pipeline.stop_after_clean;
+        if stop > 0 && self.clean_streak >= stop {
+            debug!(
+                clean_streak = self.clean_streak,
+                "ReviewProgress::record: stable, stopping early"
+            );
+            self.current = self.pipeline.passes.len();
+        }
+        if self.is_complete() {
+            ReviewStep::Complete
+        } else {
+            ReviewStep::Advanced { forced }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -473,4 +664,75 @@ mod tests {
             assert!(!pass.validation_command().is_empty());
         }
     }
+
+    fn pipeline_of(names: &[&str], stop_after_clean: u32) -> ReviewPipeline {
+        ReviewPipeline {
+            passes: names
+                .iter()
+                .map(|name| ReviewPassConfig {
+                    name: name.to_string(),
+                    description: String::new(),
+                    prompt: format!("Review for {}", name),
+                    converge: Convergence {
+                        max_iterations: 2,
+                        ..Default::default()
+                    },
+                })
+                .collect(),
+            stop_after_clean,
+        }
+    }
+
+    #[test]
+    fn test_review_progress_walks_passes_in_order() {
+        let mut progress = ReviewProgress::new(pipeline_of(&["draft", "security", "clarity"], 0)).unwrap();
+        assert_eq!(progress.current().unwrap().name, "draft");
+
+        assert_eq!(progress.record(false), ReviewStep::Stay);
+        assert_eq!(progress.record(true), ReviewStep::Advanced { forced: false });
+        assert_eq!(progress.current().unwrap().name, "security");
+        assert_eq!(progress.number(), 2);
+
+        // The iteration cap moves on without convergence
+        assert_eq!(progress.record(false), ReviewStep::Stay);
+        assert_eq!(progress.record(false), ReviewStep::Advanced { forced: true });
+
+        assert_eq!(progress.record(true), ReviewStep::Complete);
+        assert!(progress.is_complete());
+        assert!(progress.current().is_none());
+    }
+
+    #[test]
+    fn test_review_progress_stops_after_clean_passes() {
+        let mut progress = ReviewProgress::new(pipeline_of(&["a", "b", "c", "d"], 2)).unwrap();
+        progress.record(false);
+        progress.record(true);
+        // "a" needed two iterations, so only "b" and "c" count as clean
+        assert_eq!(progress.record(true), ReviewStep::Advanced { forced: false });
+        assert_eq!(progress.record(true), ReviewStep::Complete);
+    }
+
+    #[test]
+    fn test_review_pipeline_from_yaml() {
+        let yaml = r#"
+stop-after-clean: 2
+passes:
+  - name: security
+    prompt: "Look for injection and secrets"
+    converge:
+      command: "cargo audit"
+      unchanged: true
+      max-iterations: 3
+"#;
+        let pipeline: ReviewPipeline = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(pipeline.passes.len(), 1);
+        assert_eq!(pipeline.passes[0].converge.command.as_deref(), Some("cargo audit"));
+        assert!(pipeline.passes[0].converge.unchanged);
+        assert_eq!(pipeline.passes[0].converge.max_iterations, 3);
+        assert!(pipeline.validate().is_ok());
+
+        assert!(ReviewProgress::new(ReviewPipeline::default()).is_none());
+        assert!(pipeline_of(&["a", "a"], 0).validate().is_err());
+        assert_eq!(ReviewPipeline::rule_of_five().passes.len(), 5);
+    }
 }
@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use tracing::debug;
 
+use crate::coordinator::{MainUpdated, Topic};
+
 /// Configuration for the MainWatcher
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WatcherConfig {
@@ -50,7 +52,7 @@ fn default_fetch_enabled() -> bool {
 
 fn default_event_type() -> String {
     debug!("default_event_type: called");
-    "main_updated".to_string()
+    MainUpdated::NAME.to_string()
 }
 
 impl Default for WatcherConfig {
@@ -61,7 +63,7 @@ impl Default for WatcherConfig {
             main_branch: "main".to_string(),
             remote: "origin".to_string(),
             fetch_enabled: true,
-            event_type: "main_updated".to_string(),
+            event_type: MainUpdated::NAME.to_string(),
         }
     }
 }
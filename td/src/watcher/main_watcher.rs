@@ -4,13 +4,12 @@ use std::path::PathBuf;
 use std::process::Stdio;
 
 use eyre::{Result, eyre};
-use serde_json::json;
 use tokio::process::Command;
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 
 use super::config::WatcherConfig;
-use crate::coordinator::CoordRequest;
+use crate::coordinator::{CoordRequest, MainUpdated};
 
 /// The MainWatcher monitors the main branch for updates and alerts all loops
 pub struct MainWatcher {
@@ -118,15 +117,16 @@ impl MainWatcher {
             );
 
             // Alert all loops via coordinator
+            let update = MainUpdated {
+                sha: current_sha.clone(),
+                old_sha: Some(last_sha.clone()),
+                branch: self.config.main_branch.clone(),
+            };
             self.coordinator_tx
                 .send(CoordRequest::Alert {
                     from_exec_id: "_main_watcher".to_string(),
                     event_type: self.config.event_type.clone(),
-                    data: json!({
-                        "old_sha": last_sha,
-                        "new_sha": &current_sha,
-                        "branch": &self.config.main_branch,
-                    }),
+                    data: serde_json::to_value(&update)?,
                 })
                 .await
                 .map_err(|_| eyre!("Coordinator channel closed"))?;
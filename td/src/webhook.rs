@@ -0,0 +1,580 @@
+//! HTTP trigger endpoint
+//!
+//! External systems (issue trackers, chatbots, CI) start loops by POSTing to a
+//! route configured under `webhooks`:
+//!
+//! ```text
+//! POST /hooks/issues
+//! X-TaskDaemon-Signature: sha256=<hex HMAC-SHA256 of the body with the route secret>
+//!
+//! {"loop_type": "ralph", "task": "Fix the login redirect", "context": {"issue": "412"}, "user": "alice"}
+//! ```
+//!
+//! A valid request creates a pending execution, which the daemon picks up like
+//! any other, and answers `201` with its ID. Unsigned or wrongly signed
+//! requests get `401`, and each route accepts at most `rate-limit` requests a
+//! minute (`429` with `Retry-After` beyond that).
+//!
+//! The server speaks just enough HTTP/1.1 for webhooks: one request per
+//! connection, `Content-Length` bodies only. Put it behind a reverse proxy for
+//! TLS.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use eyre::{Context, Result, bail};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, info, warn};
+
+use crate::batch::task_execution;
+use crate::chat::ChatBridge;
+use crate::config::{DedupConfig, WebhookConfig, WebhookRoute};
+use crate::domain::{LoopExecution, Submitter};
+use crate::r#loop::LoopLoader;
+use crate::state::{StateError, StateManager};
+
+/// Header carrying the body signature: `sha256=<hex digest>`
+pub const SIGNATURE_HEADER: &str = "x-taskdaemon-signature";
+
+/// Tag added to every execution created through a webhook
+pub const WEBHOOK_TAG: &str = "webhook";
+
+/// Longest accepted request line plus headers
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+
+/// How long a client may take to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Length of a rate-limit window
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Body of a webhook request
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookRequest {
+    /// Loop type to run
+    #[serde(alias = "loop-type")]
+    pub loop_type: String,
+
+    /// Task description, available to prompts as `{{task}}`
+    pub task: String,
+
+    /// Extra context values for the execution
+    #[serde(default)]
+    pub context: serde_json::Map<String, serde_json::Value>,
+
+    /// Who asked for the task, recorded as the execution's submitter
+    #[serde(default)]
+    pub user: Option<String>,
+}
+
+/// A parsed HTTP request
+#[derive(Debug, Clone, Default)]
+pub struct HttpRequest {
+    pub method: String,
+    /// Path without the query string
+    pub path: String,
+    /// Header values by lowercased name
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+/// A response to send back
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: serde_json::Value,
+    /// Seconds until the client may retry (sent as `Retry-After`)
+    pub retry_after: Option<u64>,
+}
+
+impl HttpResponse {
+    pub(crate) fn json(status: u16, body: serde_json::Value) -> Self {
+        Self {
+            status,
+            body,
+            retry_after: None,
+        }
+    }
+
+    pub(crate) fn error(status: u16, message: impl Into<String>) -> Self {
+        Self::json(status, serde_json::json!({ "error": message.into() }))
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            201 => "Created",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            403 => "Forbidden",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            409 => "Conflict",
+            413 => "Payload Too Large",
+            422 => "Unprocessable Entity",
+            429 => "Too Many Requests",
+            _ => "Internal Server Error",
+        }
+    }
+
+    /// Serialize as an HTTP/1.1 response that closes the connection
+    fn to_bytes(&self) -> Vec<u8> {
+        let body = self.body.to_string();
+        let mut head = format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+            self.status,
+            self.reason(),
+            body.len()
+        );
+        if let Some(secs) = self.retry_after {
+            head.push_str(&format!("Retry-After: {}\r\n", secs));
+        }
+        head.push_str("\r\n");
+        let mut bytes = head.into_bytes();
+        bytes.extend_from_slice(body.as_bytes());
+        bytes
+    }
+}
+
+/// HMAC-SHA256 of `message` under `key` (RFC 2104)
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    const BLOCK: usize = 64;
+    let mut block = [0u8; BLOCK];
+    if key.len() > BLOCK {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let mut inner = Sha256::new();
+    inner.update(block.map(|b| b ^ 0x36));
+    inner.update(message);
+    let mut outer = Sha256::new();
+    outer.update(block.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+/// Signature header value for `body`: `sha256=<hex>`
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    format!("sha256={}", to_hex(&hmac_sha256(secret.as_bytes(), body)))
+}
+
+/// Whether `signature` is the correct signature of `body` (constant time)
+pub fn verify(secret: &str, body: &[u8], signature: &str) -> bool {
+    constant_time_eq(&sign(secret, body), &signature.trim().to_ascii_lowercase())
+}
+
+/// Lowercase hex encoding
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Compare two strings without exiting early on the first difference
+pub(crate) fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Fixed-window request counter for one route
+#[derive(Debug)]
+struct RateLimiter {
+    per_minute: u32,
+    window_start: Option<Instant>,
+    count: u32,
+}
+
+impl RateLimiter {
+    fn new(per_minute: u32) -> Self {
+        Self {
+            per_minute,
+            window_start: None,
+            count: 0,
+        }
+    }
+
+    /// Count a request; Err(wait) if the window is full
+    fn check(&mut self, now: Instant) -> std::result::Result<(), Duration> {
+        if self.per_minute == 0 {
+            return Ok(());
+        }
+        let start = match self.window_start {
+            Some(start) if now.duration_since(start) < RATE_WINDOW => start,
+            _ => {
+                self.count = 0;
+                *self.window_start.insert(now)
+            }
+        };
+        if self.count >= self.per_minute {
+            return Err(RATE_WINDOW.saturating_sub(now.duration_since(start)));
+        }
+        self.count += 1;
+        Ok(())
+    }
+}
+
+/// A configured route with its resolved secret
+#[derive(Debug)]
+struct Route {
+    config: WebhookRoute,
+    secret: String,
+    limiter: Mutex<RateLimiter>,
+}
+
+/// Serves the configured webhook routes
+pub struct WebhookServer {
+    routes: Vec<Route>,
+    max_body_bytes: usize,
+    state: StateManager,
+    loader: Arc<RwLock<LoopLoader>>,
+    chat: Option<Arc<ChatBridge>>,
+    dedup: DedupConfig,
+}
+
+impl WebhookServer {
+    /// Build the server, reading every route's secret
+    pub fn new(config: &WebhookConfig, state: StateManager, loader: Arc<RwLock<LoopLoader>>) -> Result<Self> {
+        debug!(routes = config.routes.len(), "WebhookServer::new: called");
+        let mut routes: Vec<Route> = Vec::with_capacity(config.routes.len());
+        for route in &config.routes {
+            if !route.path.starts_with('/') {
+                bail!("Webhook route path must start with '/': {}", route.path);
+            }
+            if routes.iter().any(|r| r.config.path == route.path) {
+                bail!("Duplicate webhook route: {}", route.path);
+            }
+            routes.push(Route {
+                config: route.clone(),
+                secret: route.secret()?,
+                limiter: Mutex::new(RateLimiter::new(route.rate_limit)),
+            });
+        }
+        Ok(Self {
+            routes,
+            max_body_bytes: config.max_body_bytes,
+            state,
+            loader,
+            chat: None,
+            dedup: DedupConfig::default(),
+        })
+    }
+
+    /// Route chat commands (`chat.command-path`) to `bridge`
+    pub fn with_chat(mut self, bridge: Arc<ChatBridge>) -> Self {
+        self.chat = Some(bridge);
+        self
+    }
+
+    /// Check created executions for duplicates of unfinished ones
+    pub fn with_dedup(mut self, config: DedupConfig) -> Self {
+        self.dedup = config;
+        self
+    }
+
+    /// Accept connections until the task is aborted
+    pub async fn serve(self: Arc<Self>, listener: TcpListener) {
+        if let Ok(addr) = listener.local_addr() {
+            info!(%addr, routes = self.routes.len(), "Webhook server listening");
+        }
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!(error = %e, "Webhook accept failed");
+                    continue;
+                }
+            };
+            debug!(%peer, "WebhookServer::serve: connection");
+            let server = self.clone();
+            tokio::spawn(async move {
+                if let Err(e) = server.handle_connection(stream).await {
+                    debug!(%peer, error = %e, "WebhookServer::serve: connection failed");
+                }
+            });
+        }
+    }
+
+    async fn handle_connection(&self, mut stream: TcpStream) -> Result<()> {
+        let response = match tokio::time::timeout(READ_TIMEOUT, read_request(&mut stream, self.max_body_bytes)).await {
+            Ok(Ok(Ok(request))) => self.handle(&request).await,
+            Ok(Ok(Err(response))) => response,
+            Ok(Err(e)) => return Err(e),
+            Err(_) => bail!("Timed out reading request"),
+        };
+        stream.write_all(&response.to_bytes()).await?;
+        stream.shutdown().await?;
+        Ok(())
+    }
+
+    /// Answer one request
+    pub async fn handle(&self, request: &HttpRequest) -> HttpResponse {
+        debug!(method = %request.method, path = %request.path, "WebhookServer::handle: called");
+        if let Some(chat) = &self.chat
+            && chat.command_path() == Some(request.path.as_str())
+        {
+            return chat.handle(request).await;
+        }
+        let Some(route) = self.routes.iter().find(|r| r.config.path == request.path) else {
+            return HttpResponse::error(404, format!("No webhook route {}", request.path));
+        };
+        if request.method != "POST" {
+            return HttpResponse::error(405, "Only POST is accepted");
+        }
+
+        // Count every attempt, so guessing signatures is rate limited too
+        let limited = route
+            .limiter
+            .lock()
+            .expect("rate limiter poisoned")
+            .check(Instant::now());
+        if let Err(wait) = limited {
+            warn!(path = %request.path, "Webhook rate limit exceeded");
+            let mut response = HttpResponse::error(429, "Rate limit exceeded");
+            response.retry_after = Some(wait.as_secs().max(1));
+            return response;
+        }
+
+        let signature = request.headers.get(SIGNATURE_HEADER).map(String::as_str).unwrap_or("");
+        if !verify(&route.secret, &request.body, signature) {
+            warn!(path = %request.path, "Webhook signature rejected");
+            return HttpResponse::error(401, "Missing or invalid signature");
+        }
+
+        let payload: WebhookRequest = match serde_json::from_slice(&request.body) {
+            Ok(payload) => payload,
+            Err(e) => return HttpResponse::error(400, format!("Invalid body: {}", e)),
+        };
+        if payload.task.trim().is_empty() {
+            return HttpResponse::error(400, "Empty task");
+        }
+        if !route.config.loop_types.is_empty() && !route.config.loop_types.contains(&payload.loop_type) {
+            return HttpResponse::error(
+                403,
+                format!(
+                    "Loop type '{}' is not allowed on {}",
+                    payload.loop_type, route.config.path
+                ),
+            );
+        }
+
+        let exec = match self.build_execution(&route.config, &payload) {
+            Ok(exec) => exec,
+            Err(problems) => {
+                return HttpResponse::json(
+                    422,
+                    serde_json::json!({ "error": "Invalid request", "problems": problems }),
+                );
+            }
+        };
+        match self.state.submit_execution_checked(exec, &self.dedup).await {
+            Ok((id, duplicate)) => {
+                info!(%id, loop_type = %payload.loop_type, path = %route.config.path, "Webhook created execution");
+                let mut body = serde_json::json!({ "id": id, "loop_type": payload.loop_type });
+                if let Some(duplicate) = duplicate {
+                    body["duplicate-of"] = duplicate.existing_id.into();
+                }
+                HttpResponse::json(201, body)
+            }
+            Err(StateError::Duplicate(duplicate)) => {
+                info!(existing = %duplicate.existing_id, path = %route.config.path, "Webhook refused duplicate task");
+                HttpResponse::json(
+                    409,
+                    serde_json::json!({ "error": duplicate.to_string(), "duplicate-of": duplicate.existing_id }),
+                )
+            }
+            Err(e) => {
+                warn!(error = %e, "Webhook failed to create execution");
+                HttpResponse::error(500, format!("Failed to create execution: {}", e))
+            }
+        }
+    }
+
+    /// Pending execution for a request, checked against its loop type's variables
+    fn build_execution(
+        &self,
+        route: &WebhookRoute,
+        payload: &WebhookRequest,
+    ) -> std::result::Result<LoopExecution, Vec<String>> {
+        let loader = self.loader.read().expect("loop loader poisoned");
+        let mut tags = vec![WEBHOOK_TAG.to_string()];
+        tags.extend(route.tags.iter().cloned());
+        let submitter = payload.user.as_ref().map(|user| Submitter {
+            user: user.clone(),
+            name: None,
+        });
+        task_execution(&loader, &payload.loop_type, &payload.task, payload.context.clone(), &tags)
+            .map(|exec| exec.with_submitter(submitter))
+    }
+}
+
+/// Read one request; Ok(Err(response)) for requests to reject without routing
+async fn read_request(
+    stream: &mut TcpStream,
+    max_body_bytes: usize,
+) -> Result<std::result::Result<HttpRequest, HttpResponse>> {
+    let mut reader = BufReader::new(stream);
+    let mut request = HttpRequest::default();
+    let mut head_bytes = 0;
+
+    let mut line = String::new();
+    head_bytes += reader
+        .read_line(&mut line)
+        .await
+        .context("Failed to read request line")?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Ok(Err(HttpResponse::error(400, "Malformed request line")));
+    };
+    request.method = method.to_string();
+    request.path = target.split('?').next().unwrap_or(target).to_string();
+
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line).await.context("Failed to read header")?;
+        head_bytes += read;
+        if head_bytes > MAX_HEAD_BYTES {
+            return Ok(Err(HttpResponse::error(400, "Headers too large")));
+        }
+        let header = line.trim_end();
+        if read == 0 || header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            request
+                .headers
+                .insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let length = match request.headers.get("content-length").map(|v| v.parse::<usize>()) {
+        Some(Ok(length)) => length,
+        Some(Err(_)) => return Ok(Err(HttpResponse::error(400, "Invalid Content-Length"))),
+        None => 0,
+    };
+    if length > max_body_bytes {
+        return Ok(Err(HttpResponse::error(413, "Body too large")));
+    }
+    request.body = vec![0; length];
+    reader
+        .read_exact(&mut request.body)
+        .await
+        .context("Failed to read body")?;
+    Ok(Ok(request))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LoopsConfig;
+    use crate::domain::LoopExecutionStatus;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_signature_and_rate_limit() {
+        // RFC 4231 test case 2
+        let digest = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(
+            to_hex(&digest),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+
+        let signature = sign("s3cret", b"{}");
+        assert!(verify("s3cret", b"{}", &signature));
+        assert!(verify(
+            "s3cret",
+            b"{}",
+            &signature.to_uppercase().replace("SHA256", "sha256")
+        ));
+        assert!(!verify("s3cret", b"{ }", &signature));
+        assert!(!verify("other", b"{}", &signature));
+        assert!(!verify("s3cret", b"{}", ""));
+
+        let start = Instant::now();
+        let mut limiter = RateLimiter::new(2);
+        assert!(limiter.check(start).is_ok());
+        assert!(limiter.check(start + Duration::from_secs(1)).is_ok());
+        assert_eq!(
+            limiter.check(start + Duration::from_secs(20)),
+            Err(Duration::from_secs(40))
+        );
+        assert!(limiter.check(start + Duration::from_secs(61)).is_ok());
+        assert!(RateLimiter::new(0).check(start).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_webhook_creates_pending_execution() {
+        let temp = tempdir().unwrap();
+        let state = StateManager::spawn(temp.path()).unwrap();
+        let loader = LoopLoader::new(&LoopsConfig {
+            paths: vec!["builtin".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+        let secret_file = temp.path().join("secret");
+        std::fs::write(&secret_file, "s3cret\n").unwrap();
+        let config = WebhookConfig {
+            routes: vec![WebhookRoute {
+                path: "/hooks/issues".to_string(),
+                secret_env: None,
+                secret_file: Some(secret_file.display().to_string()),
+                loop_types: vec!["ralph".to_string()],
+                rate_limit: 3,
+                tags: vec!["Issues".to_string()],
+            }],
+            ..Default::default()
+        };
+        let server = Arc::new(WebhookServer::new(&config, state.clone(), Arc::new(RwLock::new(loader))).unwrap());
+        let request = |body: &str, signature: Option<String>| HttpRequest {
+            method: "POST".to_string(),
+            path: "/hooks/issues".to_string(),
+            headers: signature
+                .map(|s| HashMap::from([(SIGNATURE_HEADER.to_string(), s)]))
+                .unwrap_or_default(),
+            body: body.as_bytes().to_vec(),
+        };
+
+        let body =
+            r#"{"loop_type": "ralph", "task": "Fix the login redirect", "context": {"issue": "412"}, "user": "alice"}"#;
+        assert_eq!(server.handle(&request(body, None)).await.status, 401);
+        let forbidden = r#"{"loop_type": "plan", "task": "Plan it"}"#;
+        let response = server
+            .handle(&request(forbidden, Some(sign("s3cret", forbidden.as_bytes()))))
+            .await;
+        assert_eq!(response.status, 403);
+
+        // A real connection, to exercise the HTTP parsing
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let serving = tokio::spawn(server.clone().serve(listener));
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let raw = format!(
+            "POST /hooks/issues?source=tracker HTTP/1.1\r\nHost: localhost\r\nX-TaskDaemon-Signature: {}\r\nContent-Length: {}\r\n\r\n{}",
+            sign("s3cret", body.as_bytes()),
+            body.len(),
+            body
+        );
+        stream.write_all(raw.as_bytes()).await.unwrap();
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).await.unwrap();
+        serving.abort();
+        assert!(reply.starts_with("HTTP/1.1 201 Created\r\n"), "{}", reply);
+        let json: serde_json::Value = serde_json::from_str(reply.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        let id = json["id"].as_str().unwrap();
+
+        let exec = state.get_execution(id).await.unwrap().unwrap();
+        assert_eq!(exec.status, LoopExecutionStatus::Pending);
+        assert_eq!(exec.context["task"], "Fix the login redirect");
+        assert_eq!(exec.context["issue"], "412");
+        assert_eq!(exec.tags, ["webhook", "issues"]);
+        assert!(exec.submitted_by_user("alice"));
+
+        // Three requests per minute, the rejected ones included
+        let response = server.handle(&request(body, None)).await;
+        assert_eq!(response.status, 429);
+        assert!(response.retry_after.is_some());
+
+        state.shutdown().await.unwrap();
+    }
+}
@@ -0,0 +1,600 @@
+//! Conventional-commit messages and changelog entries for merges
+//!
+//! A loop type with a `merge` section gets a conventional-commit message
+//! (`feat(scope): subject`) for its merge instead of `Merge spec: ...`, and
+//! optionally squashes the branch and appends an entry to a changelog. The
+//! type, scope, subject and body come from the LLM, given the execution title,
+//! the branch's commits and its diff summary; without an LLM (or if it fails)
+//! they are derived from the title and the changed files. Both the message and
+//! the changelog entry are Handlebars templates over those values.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use eyre::{Context, Result};
+use handlebars::Handlebars;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tokio::process::Command;
+use tracing::{debug, warn};
+
+use crate::llm::{CompletionRequest, LlmClient, Message};
+
+/// Conventional-commit types the generated message may use
+pub const COMMIT_TYPES: &[&str] = &[
+    "feat", "fix", "docs", "style", "refactor", "perf", "test", "build", "ci", "chore", "revert",
+];
+
+/// Default commit message template
+pub const DEFAULT_COMMIT_TEMPLATE: &str =
+    "{{type}}{{#if scope}}({{scope}}){{/if}}{{#if breaking}}!{{/if}}: {{subject}}{{#if body}}\n\n{{body}}{{/if}}";
+
+/// Default changelog entry template (one list item)
+pub const DEFAULT_CHANGELOG_TEMPLATE: &str = "- **{{type}}{{#if scope}}({{scope}}){{/if}}**: {{subject}}";
+
+/// Longest commit header before the subject is cut
+const MAX_HEADER_CHARS: usize = 72;
+
+/// Changed files listed in the LLM prompt
+const MAX_PROMPT_FILES: usize = 50;
+
+/// How an execution's branch is merged (`merge` in loop YAML)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct MergeConfig {
+    /// Squash the branch into a single commit instead of a merge commit
+    pub squash: bool,
+
+    /// Ask the LLM for the type, scope and body (default: true)
+    #[serde(default = "default_true")]
+    pub llm: bool,
+
+    /// Commit type when the LLM is off or unsure (default: inferred from the files)
+    #[serde(rename = "type")]
+    pub commit_type: Option<String>,
+
+    /// Fixed scope for every message (default: chosen by the LLM, or none)
+    pub scope: Option<String>,
+
+    /// Handlebars template for the commit message
+    pub commit_template: Option<String>,
+
+    /// Changelog to append an entry to, relative to the repo root (unset: none)
+    pub changelog: Option<String>,
+
+    /// Handlebars template for the changelog entry
+    pub changelog_template: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Lines changed in one file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileChange {
+    pub path: String,
+    pub insertions: u64,
+    pub deletions: u64,
+}
+
+/// What changed on an execution's branch relative to main
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiffSummary {
+    /// Changed paths
+    pub files: Vec<String>,
+    pub insertions: u64,
+    pub deletions: u64,
+    /// Per-file counts, in `files` order (binary files count zero lines)
+    pub changes: Vec<FileChange>,
+    /// Subjects of the branch's commits, oldest first
+    pub commits: Vec<String>,
+}
+
+impl DiffSummary {
+    /// Summarize `worktree`'s branch against `base`
+    pub async fn collect(worktree: &Path, base: &str) -> Result<Self> {
+        debug!(?worktree, %base, "DiffSummary::collect: called");
+        let range = format!("{}...HEAD", base);
+        let numstat = git_stdout(worktree, &["diff", "--numstat", &range]).await?;
+        let mut summary = Self::default();
+        for line in numstat.lines() {
+            let mut fields = line.splitn(3, '\t');
+            let (Some(added), Some(removed), Some(path)) = (fields.next(), fields.next(), fields.next()) else {
+                continue;
+            };
+            // Binary files report "-"
+            let change = FileChange {
+                path: path.to_string(),
+                insertions: added.parse::<u64>().unwrap_or(0),
+                deletions: removed.parse::<u64>().unwrap_or(0),
+            };
+            summary.insertions += change.insertions;
+            summary.deletions += change.deletions;
+            summary.files.push(path.to_string());
+            summary.changes.push(change);
+        }
+        let log = git_stdout(
+            worktree,
+            &["log", "--reverse", "--format=%s", &format!("{}..HEAD", base)],
+        )
+        .await?;
+        summary.commits = log.lines().map(String::from).collect();
+        Ok(summary)
+    }
+
+    /// One-line stat, like `git diff --shortstat`
+    pub fn stat(&self) -> String {
+        format!(
+            "{} files changed, {} insertions(+), {} deletions(-)",
+            self.files.len(),
+            self.insertions,
+            self.deletions
+        )
+    }
+}
+
+async fn git_stdout(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .await
+        .with_context(|| format!("Failed to run git {}", args.join(" ")))?;
+    if !output.status.success() {
+        eyre::bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// The parts of a conventional commit
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitParts {
+    #[serde(rename = "type")]
+    pub commit_type: String,
+    #[serde(default)]
+    pub scope: Option<String>,
+    pub subject: String,
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub breaking: bool,
+}
+
+impl CommitParts {
+    /// Derive the parts from the execution title and the changed files
+    pub fn infer(config: &MergeConfig, title: &str, diff: &DiffSummary) -> Self {
+        debug!(%title, files = diff.files.len(), "CommitParts::infer: called");
+        let lower = title.to_lowercase();
+        let all = |pred: fn(&str) -> bool| !diff.files.is_empty() && diff.files.iter().all(|f| pred(f));
+        let commit_type = if all(is_doc) {
+            "docs"
+        } else if all(is_test) {
+            "test"
+        } else if all(is_ci) {
+            "ci"
+        } else if all(is_build) {
+            "build"
+        } else if ["fix", "bug", "repair", "correct"].iter().any(|w| lower.starts_with(w)) {
+            "fix"
+        } else if lower.starts_with("refactor") {
+            "refactor"
+        } else {
+            config.commit_type.as_deref().unwrap_or("feat")
+        };
+        Self {
+            commit_type: commit_type.to_string(),
+            scope: config.scope.clone(),
+            subject: title.to_string(),
+            body: None,
+            breaking: false,
+        }
+    }
+
+    /// Clean up the LLM's (or title's) parts so the header is a valid conventional commit
+    fn normalize(mut self, config: &MergeConfig, fallback: &CommitParts) -> Self {
+        self.commit_type = self.commit_type.trim().to_lowercase();
+        if !COMMIT_TYPES.contains(&self.commit_type.as_str()) {
+            self.commit_type = fallback.commit_type.clone();
+        }
+        if config.scope.is_some() {
+            self.scope = config.scope.clone();
+        }
+        self.scope = self
+            .scope
+            .map(|s| s.trim().to_lowercase().replace(' ', "-"))
+            .filter(|s| !s.is_empty());
+
+        let mut subject = self
+            .subject
+            .lines()
+            .next()
+            .unwrap_or("")
+            .trim()
+            .trim_end_matches('.')
+            .to_string();
+        if subject.is_empty() {
+            subject = fallback.subject.clone();
+        }
+        // Conventional subjects start lowercase, unless it's an acronym or identifier
+        let mut chars = subject.chars();
+        if let (Some(first), Some(second)) = (chars.next(), chars.next())
+            && first.is_uppercase()
+            && !second.is_uppercase()
+        {
+            subject = first.to_lowercase().chain(subject.chars().skip(1)).collect();
+        }
+        let prefix = self.commit_type.len() + self.scope.as_ref().map_or(0, |s| s.len() + 2) + 3;
+        let room = MAX_HEADER_CHARS.saturating_sub(prefix);
+        if subject.chars().count() > room {
+            subject = subject
+                .chars()
+                .take(room.saturating_sub(3))
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+                + "...";
+        }
+        self.subject = subject;
+        self.body = self.body.map(|b| b.trim().to_string()).filter(|b| !b.is_empty());
+        self
+    }
+}
+
+fn is_doc(path: &str) -> bool {
+    path.ends_with(".md") || path.ends_with(".rst") || path.ends_with(".txt") || path.starts_with("docs/")
+}
+
+fn is_test(path: &str) -> bool {
+    path.starts_with("tests/")
+        || path.contains("/tests/")
+        || path.contains("_test.")
+        || path.contains(".test.")
+        || path.contains(".spec.")
+}
+
+fn is_ci(path: &str) -> bool {
+    path.starts_with(".github/") || path.starts_with(".gitlab-ci") || path.starts_with(".circleci/")
+}
+
+fn is_build(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    matches!(
+        name,
+        "Cargo.toml" | "Cargo.lock" | "package.json" | "package-lock.json" | "yarn.lock" | "pnpm-lock.yaml"
+    )
+}
+
+/// Ask the LLM for the commit parts (None if it fails or answers garbage)
+async fn parts_from_llm(
+    llm: &Arc<dyn LlmClient>,
+    title: &str,
+    loop_type: &str,
+    diff: &DiffSummary,
+) -> Option<CommitParts> {
+    debug!(%title, "parts_from_llm: called");
+    let system_prompt = format!(
+        "Write a conventional commit for the change described below. Output ONLY a JSON object: \
+         {{\"type\": one of {}, \"scope\": short lowercase area or null, \"subject\": imperative summary \
+         under 60 characters without a trailing period, \"body\": 1-3 sentences on what changed and why or null, \
+         \"breaking\": true only if existing users must change something}}",
+        COMMIT_TYPES.join("/")
+    );
+    let mut files: Vec<&str> = diff.files.iter().take(MAX_PROMPT_FILES).map(String::as_str).collect();
+    if diff.files.len() > MAX_PROMPT_FILES {
+        files.push("...");
+    }
+    let text = format!(
+        "Title: {}\nLoop type: {}\nDiff: {}\n\nCommits:\n{}\n\nFiles:\n{}",
+        title,
+        loop_type,
+        diff.stat(),
+        diff.commits.join("\n"),
+        files.join("\n")
+    );
+    let request = CompletionRequest {
+        system_prompt,
+        messages: vec![Message::user(text)],
+        max_tokens: 400,
+        tools: vec![],
+    };
+
+    let content = match llm.complete(request).await {
+        Ok(response) => response.content?,
+        Err(e) => {
+            debug!(error = %e, "parts_from_llm: LLM call failed");
+            return None;
+        }
+    };
+    // Tolerate a fenced or chatty answer around the object
+    let json = &content[content.find('{')?..=content.rfind('}')?];
+    match serde_json::from_str(json) {
+        Ok(parts) => Some(parts),
+        Err(e) => {
+            debug!(error = %e, "parts_from_llm: unparseable answer");
+            None
+        }
+    }
+}
+
+/// A generated merge commit message and changelog entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeMessage {
+    /// Full commit message (header, blank line, body)
+    pub commit: String,
+
+    /// Squash the branch instead of creating a merge commit
+    pub squash: bool,
+
+    /// Changelog path (relative to the repo root) and the entry to add
+    pub changelog: Option<(String, String)>,
+}
+
+impl MergeMessage {
+    /// Generate the message for merging `worktree`'s branch into `base`
+    ///
+    /// `llm` is consulted only if the config allows it; any failure falls back
+    /// to parts inferred from the title and diff.
+    pub async fn generate(
+        config: &MergeConfig,
+        llm: Option<&Arc<dyn LlmClient>>,
+        worktree: &Path,
+        base: &str,
+        title: &str,
+        context: &Value,
+    ) -> Result<Self> {
+        debug!(?worktree, %title, "MergeMessage::generate: called");
+        let diff = DiffSummary::collect(worktree, base).await.unwrap_or_else(|e| {
+            warn!(error = %e, "Failed to summarize branch diff for the merge message");
+            DiffSummary::default()
+        });
+        let fallback = CommitParts::infer(config, title, &diff);
+        let loop_type = context.get("loop-type").and_then(Value::as_str).unwrap_or("");
+        let parts = match llm {
+            Some(llm) if config.llm => parts_from_llm(llm, title, loop_type, &diff).await,
+            _ => None,
+        };
+        let parts = parts.unwrap_or_else(|| fallback.clone()).normalize(config, &fallback);
+        Self::render(config, &parts, &diff, title, context)
+    }
+
+    /// Render the templates over the parts, the diff and the execution context
+    pub fn render(
+        config: &MergeConfig,
+        parts: &CommitParts,
+        diff: &DiffSummary,
+        title: &str,
+        context: &Value,
+    ) -> Result<Self> {
+        let mut values = match context {
+            Value::Object(map) => map.clone(),
+            _ => serde_json::Map::new(),
+        };
+        values.extend(
+            json!({
+                "type": parts.commit_type,
+                "scope": parts.scope,
+                "subject": parts.subject,
+                "body": parts.body,
+                "breaking": parts.breaking,
+                "title": title,
+                "files-changed": diff.files.len(),
+                "insertions": diff.insertions,
+                "deletions": diff.deletions,
+                "diff-stat": diff.stat(),
+                "date": chrono::Local::now().format("%Y-%m-%d").to_string(),
+            })
+            .as_object()
+            .cloned()
+            .unwrap_or_default(),
+        );
+        let values = Value::Object(values);
+
+        let mut hbs = Handlebars::new();
+        hbs.register_escape_fn(handlebars::no_escape);
+        let template = config.commit_template.as_deref().unwrap_or(DEFAULT_COMMIT_TEMPLATE);
+        let commit = hbs
+            .render_template(template, &values)
+            .context("Invalid merge commit-template")?
+            .trim()
+            .to_string();
+        let changelog = match &config.changelog {
+            Some(path) => {
+                let template = config
+                    .changelog_template
+                    .as_deref()
+                    .unwrap_or(DEFAULT_CHANGELOG_TEMPLATE);
+                let entry = hbs
+                    .render_template(template, &values)
+                    .context("Invalid merge changelog-template")?
+                    .trim_end()
+                    .to_string();
+                Some((path.clone(), entry))
+            }
+            None => None,
+        };
+        Ok(Self {
+            commit,
+            squash: config.squash,
+            changelog,
+        })
+    }
+}
+
+/// Heading that collects entries not yet released
+const UNRELEASED_HEADING: &str = "## Unreleased";
+
+/// Add `entry` to the top of a changelog's unreleased section
+///
+/// The section (and the file) are created when missing; a Keep a Changelog
+/// style `## [Unreleased]` heading is recognized too.
+pub fn add_changelog_entry(content: &str, entry: &str) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let is_unreleased = |line: &str| {
+        let heading = line.trim().to_lowercase();
+        heading == "## unreleased" || heading == "## [unreleased]"
+    };
+
+    let mut out: Vec<String> = Vec::new();
+    if let Some(idx) = lines.iter().position(|l| is_unreleased(l)) {
+        out.extend(lines[..=idx].iter().map(|l| l.to_string()));
+        out.push(String::new());
+        out.push(entry.to_string());
+        let rest = &lines[idx + 1..];
+        let rest = &rest[rest.iter().take_while(|l| l.trim().is_empty()).count()..];
+        if rest.first().is_some_and(|l| l.starts_with("## ")) {
+            out.push(String::new());
+        }
+        out.extend(rest.iter().map(|l| l.to_string()));
+    } else {
+        // New section above the first release, or after the title
+        let insert_at = lines.iter().position(|l| l.starts_with("## ")).unwrap_or(lines.len());
+        if lines.is_empty() {
+            out.push("# Changelog".to_string());
+            out.push(String::new());
+        } else {
+            out.extend(lines[..insert_at].iter().map(|l| l.to_string()));
+            while out.last().is_some_and(|l| l.trim().is_empty()) {
+                out.pop();
+            }
+            out.push(String::new());
+        }
+        out.push(UNRELEASED_HEADING.to_string());
+        out.push(String::new());
+        out.push(entry.to_string());
+        if insert_at < lines.len() {
+            out.push(String::new());
+            out.extend(lines[insert_at..].iter().map(|l| l.to_string()));
+        }
+    }
+    let mut result = out.join("\n");
+    result.push('\n');
+    result
+}
+
+/// Add `entry` to the changelog at `path`, creating it if needed
+pub async fn append_changelog(path: &Path, entry: &str) -> Result<()> {
+    debug!(?path, "append_changelog: called");
+    let content = match tokio::fs::read_to_string(path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    tokio::fs::write(path, add_changelog_entry(&content, entry))
+        .await
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff(files: &[&str]) -> DiffSummary {
+        DiffSummary {
+            files: files.iter().map(|f| f.to_string()).collect(),
+            insertions: 10,
+            deletions: 2,
+            changes: vec![],
+            commits: vec![],
+        }
+    }
+
+    #[test]
+    fn test_infer_commit_type() {
+        let config = MergeConfig::default();
+        let infer = |title: &str, files: &[&str]| CommitParts::infer(&config, title, &diff(files)).commit_type;
+
+        assert_eq!(infer("Add OAuth login", &["src/auth.rs", "README.md"]), "feat");
+        assert_eq!(infer("Fix token refresh", &["src/auth.rs"]), "fix");
+        assert_eq!(infer("Explain config", &["README.md", "docs/config.md"]), "docs");
+        assert_eq!(infer("Cover parser", &["tests/parser.rs"]), "test");
+        assert_eq!(infer("Upgrade serde", &["Cargo.toml", "Cargo.lock"]), "build");
+    }
+
+    #[test]
+    fn test_normalize_parts() {
+        let config = MergeConfig::default();
+        let fallback = CommitParts::infer(&config, "Add login", &diff(&["src/auth.rs"]));
+        let parts = CommitParts {
+            commit_type: "Feature".to_string(),
+            scope: Some("Auth API".to_string()),
+            subject: "Add OAuth login flow.\nextra line".to_string(),
+            body: Some("  ".to_string()),
+            breaking: false,
+        }
+        .normalize(&config, &fallback);
+
+        assert_eq!(parts.commit_type, "feat");
+        assert_eq!(parts.scope.as_deref(), Some("auth-api"));
+        assert_eq!(parts.subject, "add OAuth login flow");
+        assert!(parts.body.is_none());
+
+        let long = CommitParts {
+            subject: "x".repeat(100),
+            ..fallback.clone()
+        }
+        .normalize(&config, &fallback);
+        assert!(long.subject.ends_with("...") && long.subject.len() + "feat: ".len() <= MAX_HEADER_CHARS);
+    }
+
+    #[test]
+    fn test_render_message_and_entry() {
+        let config = MergeConfig {
+            changelog: Some("CHANGELOG.md".to_string()),
+            changelog_template: Some("- {{subject}} ({{exec-id}})".to_string()),
+            ..Default::default()
+        };
+        let parts = CommitParts {
+            commit_type: "fix".to_string(),
+            scope: Some("auth".to_string()),
+            subject: "refresh expired tokens".to_string(),
+            body: Some("Tokens were used after expiry.".to_string()),
+            breaking: true,
+        };
+
+        let message = MergeMessage::render(
+            &config,
+            &parts,
+            &diff(&["src/auth.rs"]),
+            "Fix token refresh",
+            &json!({ "exec-id": "abc123" }),
+        )
+        .unwrap();
+
+        assert_eq!(
+            message.commit,
+            "fix(auth)!: refresh expired tokens\n\nTokens were used after expiry."
+        );
+        assert_eq!(
+            message.changelog,
+            Some((
+                "CHANGELOG.md".to_string(),
+                "- refresh expired tokens (abc123)".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_add_changelog_entry() {
+        assert_eq!(
+            add_changelog_entry("", "- feat: a"),
+            "# Changelog\n\n## Unreleased\n\n- feat: a\n"
+        );
+
+        let existing = "# Changelog\n\n## [Unreleased]\n\n- fix: b\n\n## 1.0.0\n\n- feat: c\n";
+        assert_eq!(
+            add_changelog_entry(existing, "- feat: a"),
+            "# Changelog\n\n## [Unreleased]\n\n- feat: a\n- fix: b\n\n## 1.0.0\n\n- feat: c\n"
+        );
+
+        let released = "# Changelog\n\nAll notable changes.\n\n## 1.0.0\n\n- feat: c\n";
+        assert_eq!(
+            add_changelog_entry(released, "- feat: a"),
+            "# Changelog\n\nAll notable changes.\n\n## Unreleased\n\n- feat: a\n\n## 1.0.0\n\n- feat: c\n"
+        );
+    }
+}
@@ -0,0 +1,322 @@
+//! Worktree disk usage and cleanup policy
+//!
+//! Worktrees of finished executions (complete, failed, stopped) are kept for
+//! `git.prune-after-days` and then pruned; with the default of 0 they are
+//! removed as soon as the loop ends. Independently, when all worktrees
+//! together exceed `git.disk-quota-gb`, finished worktrees are pruned oldest
+//! first until usage is back under the quota. Worktrees of executions that
+//! may still run (pending, running, paused, parked, ...) are never pruned.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tracing::debug;
+
+use crate::config::GitConfig;
+use crate::domain::{LoopExecution, LoopExecutionStatus};
+
+const BYTES_PER_GB: u64 = 1024 * 1024 * 1024;
+const MS_PER_DAY: u64 = 24 * 60 * 60 * 1000;
+
+/// Disk usage of one worktree
+#[derive(Debug, Clone)]
+pub struct WorktreeUsage {
+    /// Execution ID (the worktree's directory name)
+    pub exec_id: String,
+    /// Path to the worktree
+    pub path: PathBuf,
+    /// Total size of the files in it, including build output
+    pub size_bytes: u64,
+    /// Last modification of the worktree directory (Unix milliseconds)
+    pub modified_ms: i64,
+}
+
+/// Whether a worktree's execution can still use it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorktreeState {
+    /// The execution may still run; never pruned
+    Active,
+    /// The execution finished (or no longer exists) at this time (Unix milliseconds)
+    Finished { since_ms: i64 },
+}
+
+impl WorktreeState {
+    /// State of the worktree belonging to `exec` (None: no execution record)
+    pub fn of(exec: Option<&LoopExecution>, usage: &WorktreeUsage) -> Self {
+        match exec {
+            Some(exec) => match exec.status {
+                LoopExecutionStatus::Complete | LoopExecutionStatus::Failed | LoopExecutionStatus::Stopped => {
+                    Self::Finished {
+                        since_ms: exec.updated_at,
+                    }
+                }
+                _ => Self::Active,
+            },
+            None => Self::Finished {
+                since_ms: usage.modified_ms,
+            },
+        }
+    }
+}
+
+/// Pair each worktree with the state of its execution
+pub fn classify(usage: Vec<WorktreeUsage>, executions: &[LoopExecution]) -> Vec<(WorktreeUsage, WorktreeState)> {
+    debug!(
+        worktrees = usage.len(),
+        executions = executions.len(),
+        "classify: called"
+    );
+    let by_id: HashMap<&str, &LoopExecution> = executions.iter().map(|e| (e.id.as_str(), e)).collect();
+    usage
+        .into_iter()
+        .map(|u| {
+            let state = WorktreeState::of(by_id.get(u.exec_id.as_str()).copied(), &u);
+            (u, state)
+        })
+        .collect()
+}
+
+/// When finished worktrees are pruned
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PrunePolicy {
+    /// How long a finished worktree is kept (None: remove it when the loop ends)
+    pub keep_finished: Option<Duration>,
+    /// Combined size of all worktrees above which the oldest finished ones are pruned
+    pub quota_bytes: Option<u64>,
+}
+
+impl PrunePolicy {
+    /// Policy from `git.prune-after-days` and `git.disk-quota-gb` (0 disables each)
+    pub fn from_config(git: &GitConfig) -> Self {
+        debug!(
+            git.prune_after_days,
+            git.disk_quota_gb, "PrunePolicy::from_config: called"
+        );
+        Self {
+            keep_finished: (git.prune_after_days > 0)
+                .then(|| Duration::from_millis(u64::from(git.prune_after_days) * MS_PER_DAY)),
+            quota_bytes: (git.disk_quota_gb > 0).then(|| u64::from(git.disk_quota_gb) * BYTES_PER_GB),
+        }
+    }
+
+    /// Whether worktrees are removed as soon as their loop ends
+    pub fn removes_on_finish(&self) -> bool {
+        self.keep_finished.is_none()
+    }
+}
+
+/// Why a worktree is pruned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PruneReason {
+    /// Finished longer ago than the retention period
+    Expired,
+    /// Removed to bring total usage under the quota
+    OverQuota,
+}
+
+impl std::fmt::Display for PruneReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Expired => write!(f, "expired"),
+            Self::OverQuota => write!(f, "over quota"),
+        }
+    }
+}
+
+/// A worktree selected for pruning
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PruneCandidate {
+    pub exec_id: String,
+    pub size_bytes: u64,
+    pub reason: PruneReason,
+}
+
+/// Choose the worktrees `policy` prunes at `now_ms`
+pub fn plan_prune(
+    entries: &[(WorktreeUsage, WorktreeState)],
+    policy: &PrunePolicy,
+    now_ms: i64,
+) -> Vec<PruneCandidate> {
+    debug!(count = entries.len(), ?policy, "plan_prune: called");
+    let keep_ms = policy.keep_finished.map_or(0, |d| d.as_millis() as i64);
+
+    let mut finished: Vec<(&WorktreeUsage, i64)> = entries
+        .iter()
+        .filter_map(|(usage, state)| match state {
+            WorktreeState::Finished { since_ms } => Some((usage, *since_ms)),
+            WorktreeState::Active => None,
+        })
+        .collect();
+    finished.sort_by_key(|(_, since_ms)| *since_ms);
+
+    let mut total: u64 = entries.iter().map(|(usage, _)| usage.size_bytes).sum();
+    let mut pruned = Vec::new();
+    for (usage, since_ms) in finished {
+        let reason = if now_ms - since_ms >= keep_ms {
+            PruneReason::Expired
+        } else if policy.quota_bytes.is_some_and(|quota| total > quota) {
+            PruneReason::OverQuota
+        } else {
+            continue;
+        };
+        total = total.saturating_sub(usage.size_bytes);
+        pruned.push(PruneCandidate {
+            exec_id: usage.exec_id.clone(),
+            size_bytes: usage.size_bytes,
+            reason,
+        });
+    }
+    pruned
+}
+
+/// Total size of the files under `path` (symlinks are not followed)
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Modification time of `path` in Unix milliseconds (0 if unknown)
+pub fn modified_ms(path: &Path) -> i64 {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_millis() as i64)
+}
+
+/// Current time in Unix milliseconds
+pub fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as i64)
+}
+
+/// Human-readable size, e.g. "1.5 GB"
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const DAY_MS: i64 = MS_PER_DAY as i64;
+
+    fn entry(exec_id: &str, size_bytes: u64, state: WorktreeState) -> (WorktreeUsage, WorktreeState) {
+        let usage = WorktreeUsage {
+            exec_id: exec_id.to_string(),
+            path: PathBuf::from("/tmp/worktrees").join(exec_id),
+            size_bytes,
+            modified_ms: 0,
+        };
+        (usage, state)
+    }
+
+    fn finished(days_ago: i64) -> WorktreeState {
+        WorktreeState::Finished {
+            since_ms: 100 * DAY_MS - days_ago * DAY_MS,
+        }
+    }
+
+    fn ids(plan: &[PruneCandidate]) -> Vec<(&str, PruneReason)> {
+        plan.iter().map(|c| (c.exec_id.as_str(), c.reason)).collect()
+    }
+
+    #[test]
+    fn test_plan_prune_expires_old_finished_worktrees() {
+        let policy = PrunePolicy {
+            keep_finished: Some(Duration::from_millis(7 * MS_PER_DAY)),
+            quota_bytes: None,
+        };
+        let entries = vec![
+            entry("old", 10, finished(10)),
+            entry("recent", 10, finished(2)),
+            entry("running", 10, WorktreeState::Active),
+        ];
+
+        let plan = plan_prune(&entries, &policy, 100 * DAY_MS);
+        assert_eq!(ids(&plan), vec![("old", PruneReason::Expired)]);
+
+        let immediate = PrunePolicy::default();
+        assert!(immediate.removes_on_finish());
+        assert_eq!(plan_prune(&entries, &immediate, 100 * DAY_MS).len(), 2);
+    }
+
+    #[test]
+    fn test_plan_prune_quota_removes_oldest_finished_first() {
+        let policy = PrunePolicy {
+            keep_finished: Some(Duration::from_millis(30 * MS_PER_DAY)),
+            quota_bytes: Some(100),
+        };
+        let entries = vec![
+            entry("newest", 40, finished(1)),
+            entry("oldest", 40, finished(5)),
+            entry("middle", 40, finished(3)),
+            entry("active", 60, WorktreeState::Active),
+        ];
+
+        let plan = plan_prune(&entries, &policy, 100 * DAY_MS);
+        assert_eq!(
+            ids(&plan),
+            vec![("oldest", PruneReason::OverQuota), ("middle", PruneReason::OverQuota)]
+        );
+
+        // Active worktrees alone over quota: nothing left to prune
+        let entries = vec![entry("active", 500, WorktreeState::Active)];
+        assert!(plan_prune(&entries, &policy, 100 * DAY_MS).is_empty());
+    }
+
+    #[test]
+    fn test_policy_from_config() {
+        let git = GitConfig {
+            prune_after_days: 3,
+            disk_quota_gb: 2,
+            ..Default::default()
+        };
+        let policy = PrunePolicy::from_config(&git);
+        assert_eq!(policy.keep_finished, Some(Duration::from_secs(3 * 24 * 3600)));
+        assert_eq!(policy.quota_bytes, Some(2 * BYTES_PER_GB));
+
+        let git = GitConfig {
+            prune_after_days: 0,
+            disk_quota_gb: 0,
+            ..Default::default()
+        };
+        assert_eq!(PrunePolicy::from_config(&git), PrunePolicy::default());
+    }
+
+    #[test]
+    fn test_dir_size_and_format() {
+        let temp = tempdir().unwrap();
+        std::fs::create_dir_all(temp.path().join("target/debug")).unwrap();
+        std::fs::write(temp.path().join("a.txt"), vec![0u8; 100]).unwrap();
+        std::fs::write(temp.path().join("target/debug/bin"), vec![0u8; 2048]).unwrap();
+
+        assert_eq!(dir_size(temp.path()), 2148);
+        assert_eq!(dir_size(&temp.path().join("missing")), 0);
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(3 * BYTES_PER_GB), "3.0 GB");
+    }
+}
@@ -5,6 +5,9 @@ use std::path::{Path, PathBuf};
 use tokio::process::Command;
 use tracing::{debug, info, warn};
 
+use super::cleanup::{PruneCandidate, WorktreeUsage, dir_size, format_size, modified_ms};
+use super::snapshot::delete_snapshots;
+
 /// Error types for worktree operations
 #[derive(Debug, thiserror::Error)]
 pub enum WorktreeError {
@@ -139,6 +142,21 @@ impl WorktreeManager {
         })
     }
 
+    /// Reuse the worktree of a resumed execution, or create one
+    pub async fn open_or_create(&self, exec_id: &str) -> Result<WorktreeInfo, WorktreeError> {
+        debug!(%exec_id, "WorktreeManager::open_or_create: called");
+        if !self.exists(exec_id) {
+            return self.create(exec_id).await;
+        }
+        self.validate(exec_id).await?;
+        debug!(%exec_id, "WorktreeManager::open_or_create: reusing existing worktree");
+        Ok(WorktreeInfo {
+            exec_id: exec_id.to_string(),
+            path: self.worktree_path(exec_id),
+            branch: format!("{}/{}", self.config.branch_prefix, exec_id),
+        })
+    }
+
     /// Remove a worktree
     pub async fn remove(&self, exec_id: &str) -> Result<(), WorktreeError> {
         debug!(%exec_id, "WorktreeManager::remove: called");
@@ -183,6 +201,10 @@ impl WorktreeManager {
             .await;
         debug!("WorktreeManager::remove: branch deletion attempted");
 
+        if let Err(e) = delete_snapshots(&self.config.repo_root, exec_id).await {
+            warn!("Failed to delete snapshots of {}: {}", exec_id, e);
+        }
+
         info!("Removed worktree for {}", exec_id);
 
         Ok(())
@@ -427,11 +449,59 @@ impl WorktreeManager {
         debug!(cleaned, "WorktreeManager::cleanup_orphaned: completed");
         Ok(cleaned)
     }
+
+    /// Disk usage of every worktree
+    pub async fn usage(&self) -> Result<Vec<WorktreeUsage>> {
+        debug!("WorktreeManager::usage: called");
+        let worktrees = self.list().await?;
+        let usage = tokio::task::spawn_blocking(move || {
+            worktrees
+                .into_iter()
+                .map(|wt| WorktreeUsage {
+                    size_bytes: dir_size(&wt.path),
+                    modified_ms: modified_ms(&wt.path),
+                    exec_id: wt.exec_id,
+                    path: wt.path,
+                })
+                .collect::<Vec<_>>()
+        })
+        .await
+        .context("Failed to measure worktree usage")?;
+
+        debug!(count = usage.len(), "WorktreeManager::usage: measured worktrees");
+        Ok(usage)
+    }
+
+    /// Remove the planned worktrees, returning the bytes freed
+    pub async fn prune(&self, candidates: &[PruneCandidate]) -> u64 {
+        debug!(count = candidates.len(), "WorktreeManager::prune: called");
+        let mut freed = 0;
+        for candidate in candidates {
+            match self.remove(&candidate.exec_id).await {
+                Ok(()) => {
+                    info!(
+                        "Pruned worktree {} ({}, {})",
+                        candidate.exec_id,
+                        candidate.reason,
+                        format_size(candidate.size_bytes)
+                    );
+                    freed += candidate.size_bytes;
+                }
+                Err(e) => {
+                    debug!(exec_id = %candidate.exec_id, "WorktreeManager::prune: removal failed");
+                    warn!("Failed to prune worktree {}: {}", candidate.exec_id, e);
+                }
+            }
+        }
+        debug!(freed, "WorktreeManager::prune: completed");
+        freed
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::worktree::PruneReason;
     use tempfile::tempdir;
 
     async fn setup_git_repo(dir: &Path) {
@@ -562,6 +632,10 @@ mod tests {
         manager.create("exec-123").await.unwrap();
         assert!(manager.exists("exec-123"));
 
+        // A resumed execution reuses its worktree instead of failing to re-add it
+        let reopened = manager.open_or_create("exec-123").await.unwrap();
+        assert_eq!(reopened.path, manager.worktree_path("exec-123"));
+
         manager.remove("exec-123").await.unwrap();
         assert!(!manager.exists("exec-123"));
     }
@@ -614,4 +688,41 @@ mod tests {
         // Cleanup remaining
         manager.remove("exec-2").await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_usage_and_prune() {
+        let repo_dir = tempdir().unwrap();
+        let worktree_dir = tempdir().unwrap();
+
+        setup_git_repo(repo_dir.path()).await;
+
+        let config = WorktreeConfig {
+            base_dir: worktree_dir.path().to_path_buf(),
+            repo_root: repo_dir.path().to_path_buf(),
+            min_disk_space_gb: 1,
+            branch_prefix: "test".to_string(),
+        };
+
+        let manager = WorktreeManager::new(config);
+        let info = manager.create("exec-big").await.unwrap();
+        manager.create("exec-small").await.unwrap();
+        std::fs::create_dir_all(info.path.join("target")).unwrap();
+        std::fs::write(info.path.join("target/artifact"), vec![0u8; 64 * 1024]).unwrap();
+
+        let usage = manager.usage().await.unwrap();
+        let size = |id: &str| usage.iter().find(|u| u.exec_id == id).unwrap().size_bytes;
+        assert!(size("exec-big") >= 64 * 1024);
+        assert!(size("exec-small") < 64 * 1024);
+
+        let candidates = vec![PruneCandidate {
+            exec_id: "exec-big".to_string(),
+            size_bytes: size("exec-big"),
+            reason: PruneReason::OverQuota,
+        }];
+        assert_eq!(manager.prune(&candidates).await, size("exec-big"));
+        assert!(!manager.exists("exec-big"));
+        assert!(manager.exists("exec-small"));
+
+        manager.remove("exec-small").await.unwrap();
+    }
 }
@@ -1,13 +1,16 @@
 //! Git merge operations for completed specs
 //!
-//! Handles merging completed worktree branches back to main.
+//! Handles merging completed worktree branches back to main, and picking
+//! individual commits from a loop's branch onto a new branch.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use eyre::{Result, bail};
+use eyre::{Context, Result, bail};
 use tokio::process::Command;
 use tracing::{debug, info, warn};
 
+use super::changelog::{MergeMessage, append_changelog};
+
 /// Result of a merge operation
 #[derive(Debug, Clone)]
 pub enum MergeResult {
@@ -64,20 +67,66 @@ impl MergeResult {
     }
 }
 
+/// Commit anything left uncommitted in a worktree before its branch is merged
+pub async fn commit_pending(worktree_path: &Path, spec_title: &str) -> Result<()> {
+    debug!(?worktree_path, %spec_title, "commit_pending: called");
+    let status = Command::new("git")
+        .args(["status", "--porcelain"])
+        .current_dir(worktree_path)
+        .output()
+        .await?;
+
+    if !status.stdout.is_empty() {
+        debug!("commit_pending: uncommitted changes found, auto-committing");
+        info!("Auto-committing uncommitted changes in worktree");
+
+        // Stage all changes
+        Command::new("git")
+            .args(["add", "-A"])
+            .current_dir(worktree_path)
+            .output()
+            .await?;
+
+        // Commit
+        let commit_msg = format!("WIP: Auto-commit before merge for {}", spec_title);
+        let commit_output = Command::new("git")
+            .args(["commit", "-m", &commit_msg])
+            .current_dir(worktree_path)
+            .output()
+            .await?;
+
+        if !commit_output.status.success() {
+            debug!("commit_pending: auto-commit failed");
+            let stderr = String::from_utf8_lossy(&commit_output.stderr);
+            warn!("Auto-commit failed: {}", stderr);
+            // Continue anyway - might be nothing to commit
+        } else {
+            debug!("commit_pending: auto-commit succeeded");
+        }
+    } else {
+        debug!("commit_pending: no uncommitted changes");
+    }
+    Ok(())
+}
+
 /// Merge a completed spec's worktree branch to main
 ///
 /// This function:
 /// 1. Auto-commits any uncommitted changes in the worktree
 /// 2. Switches to main in the repo root
 /// 3. Pulls latest main
-/// 4. Merges the feature branch with --no-ff
+/// 4. Merges the feature branch with --no-ff, or squashes it when `message` asks to
 /// 5. Pushes to remote
 ///
+/// With a generated `message` the merge commit gets its conventional-commit
+/// message and the changelog entry is committed together with the merge.
+///
 /// # Arguments
 /// * `repo_root` - Path to the main repository
 /// * `worktree_path` - Path to the worktree
 /// * `exec_id` - Execution ID (used for branch name)
 /// * `spec_title` - Title of the spec (used in commit message)
+/// * `message` - Generated commit message and changelog entry (None: `Merge spec: {title}`)
 ///
 /// # Returns
 /// * `Ok(MergeResult::Success)` if merge completed successfully
@@ -89,6 +138,7 @@ pub async fn merge_to_main(
     worktree_path: &Path,
     exec_id: &str,
     spec_title: &str,
+    message: Option<&MergeMessage>,
 ) -> Result<MergeResult> {
     debug!(?repo_root, ?worktree_path, %exec_id, %spec_title, "merge_to_main: called");
     let branch_name = format!("taskdaemon/{}", exec_id);
@@ -101,42 +151,7 @@ pub async fn merge_to_main(
     );
 
     // 1. Ensure all changes are committed in worktree
-    let status = Command::new("git")
-        .args(["status", "--porcelain"])
-        .current_dir(worktree_path)
-        .output()
-        .await?;
-
-    if !status.stdout.is_empty() {
-        debug!("merge_to_main: uncommitted changes found, auto-committing");
-        info!("Auto-committing uncommitted changes in worktree");
-
-        // Stage all changes
-        Command::new("git")
-            .args(["add", "-A"])
-            .current_dir(worktree_path)
-            .output()
-            .await?;
-
-        // Commit
-        let commit_msg = format!("WIP: Auto-commit before merge for {}", spec_title);
-        let commit_output = Command::new("git")
-            .args(["commit", "-m", &commit_msg])
-            .current_dir(worktree_path)
-            .output()
-            .await?;
-
-        if !commit_output.status.success() {
-            debug!("merge_to_main: auto-commit failed");
-            let stderr = String::from_utf8_lossy(&commit_output.stderr);
-            warn!("Auto-commit failed: {}", stderr);
-            // Continue anyway - might be nothing to commit
-        } else {
-            debug!("merge_to_main: auto-commit succeeded");
-        }
-    } else {
-        debug!("merge_to_main: no uncommitted changes");
-    }
+    commit_pending(worktree_path, spec_title).await?;
 
     // 2. Switch to main in repo root
     debug!("merge_to_main: checking out main branch");
@@ -170,18 +185,24 @@ pub async fn merge_to_main(
         debug!("merge_to_main: pull succeeded");
     }
 
-    // 4. Merge the feature branch with no-ff
+    // 4. Merge the feature branch with no-ff (or squash it)
     debug!("merge_to_main: merging feature branch");
     let merge_msg = format!("Merge spec: {}", spec_title);
+    let merge_args: Vec<&str> = match message {
+        Some(message) if message.squash => vec!["merge", "--squash", &branch_name],
+        Some(_) => vec!["merge", "--no-ff", "--no-commit", &branch_name],
+        None => vec!["merge", "--no-ff", &branch_name, "-m", &merge_msg],
+    };
     let merge_output = Command::new("git")
-        .args(["merge", "--no-ff", &branch_name, "-m", &merge_msg])
+        .args(&merge_args)
         .current_dir(repo_root)
         .output()
         .await?;
 
     if !merge_output.status.success() {
         let stderr = String::from_utf8_lossy(&merge_output.stderr);
-        if stderr.contains("CONFLICT") {
+        let stdout = String::from_utf8_lossy(&merge_output.stdout);
+        if stderr.contains("CONFLICT") || stdout.contains("CONFLICT") {
             debug!("merge_to_main: merge conflict detected");
             warn!("Merge conflict detected for {}", exec_id);
             return Ok(MergeResult::Conflict {
@@ -193,6 +214,11 @@ pub async fn merge_to_main(
     }
     debug!("merge_to_main: merge succeeded");
 
+    // 4b. Changelog entry and the generated message, committed with the merge
+    if let Some(message) = message {
+        commit_merge(repo_root, message).await?;
+    }
+
     info!(
         exec_id = %exec_id,
         "Merge completed, pushing to remote"
@@ -225,6 +251,221 @@ pub async fn merge_to_main(
     Ok(MergeResult::Success)
 }
 
+/// Add the changelog entry and commit a `--no-commit` or `--squash` merge
+async fn commit_merge(repo_root: &Path, message: &MergeMessage) -> Result<()> {
+    debug!(?repo_root, squash = message.squash, "commit_merge: called");
+    if let Some((path, entry)) = &message.changelog {
+        append_changelog(&repo_root.join(path), entry).await?;
+        git(repo_root, &["add", "--", path]).await?;
+    }
+    let output = Command::new("git")
+        .args(["commit", "-m", &message.commit])
+        .current_dir(repo_root)
+        .output()
+        .await?;
+    if !output.status.success() {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        // A squash of a branch with nothing new leaves nothing to commit
+        if stdout.contains("nothing to commit") {
+            debug!("commit_merge: nothing to commit");
+            return Ok(());
+        }
+        bail!(
+            "Failed to commit merge: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    debug!("commit_merge: committed");
+    Ok(())
+}
+
+/// Result of a cherry-pick onto a new branch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CherryPickResult {
+    /// All commits applied; `commits` are the picked source commits in order
+    Success { branch: String, commits: Vec<String> },
+    /// A commit did not apply cleanly; nothing was kept
+    Conflict { commit: String, message: String },
+}
+
+/// Run git in `dir`, returning trimmed stdout
+async fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    debug!(?dir, ?args, "merge git: called");
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .await
+        .with_context(|| format!("Failed to run git {}", args.join(" ")))?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Resolve the commits to pick from `exec_branch`
+///
+/// `spec` is a comma-separated list of commits and git ranges (`a..b` excludes
+/// `a`, as in `git log`); None picks everything on the branch that isn't on
+/// `base`. Merge commits in ranges are skipped, and every commit must be on
+/// the execution's branch but not already on `base`.
+async fn resolve_commits(repo_root: &Path, exec_branch: &str, base: &str, spec: Option<&str>) -> Result<Vec<String>> {
+    debug!(%exec_branch, %base, ?spec, "resolve_commits: called");
+    let default_range = format!("{}..{}", base, exec_branch);
+    let items: Vec<&str> = match spec {
+        Some(spec) => spec.split(',').map(str::trim).filter(|s| !s.is_empty()).collect(),
+        None => vec![default_range.as_str()],
+    };
+
+    let mut commits: Vec<String> = Vec::new();
+    for item in items {
+        let resolved = if item.contains("..") {
+            git(repo_root, &["rev-list", "--reverse", "--no-merges", item]).await?
+        } else {
+            git(repo_root, &["rev-parse", "--verify", &format!("{}^{{commit}}", item)]).await?
+        };
+        for commit in resolved.lines() {
+            if !commits.iter().any(|c| c == commit) {
+                commits.push(commit.to_string());
+            }
+        }
+    }
+    if commits.is_empty() {
+        bail!("No commits to pick from {}", exec_branch);
+    }
+
+    for commit in &commits {
+        if !is_ancestor(repo_root, commit, exec_branch).await? {
+            bail!("Commit {} is not on {}", commit, exec_branch);
+        }
+        if is_ancestor(repo_root, commit, base).await? {
+            bail!("Commit {} is already on {}", commit, base);
+        }
+    }
+    Ok(commits)
+}
+
+/// Whether `commit` is reachable from `branch`
+async fn is_ancestor(repo_root: &Path, commit: &str, branch: &str) -> Result<bool> {
+    let status = Command::new("git")
+        .args(["merge-base", "--is-ancestor", commit, branch])
+        .current_dir(repo_root)
+        .status()
+        .await?;
+    Ok(status.success())
+}
+
+/// Remove the scratch worktree used for picking, and the new branch if the pick failed
+async fn discard_pick_worktree(repo_root: &Path, path: &Path, failed_branch: Option<&str>) {
+    debug!(?path, ?failed_branch, "discard_pick_worktree: called");
+    let path = path.to_string_lossy();
+    if let Err(e) = git(repo_root, &["worktree", "remove", "--force", &path]).await {
+        warn!("Failed to remove cherry-pick worktree: {}", e);
+    }
+    if let Some(branch) = failed_branch
+        && let Err(e) = git(repo_root, &["branch", "-D", branch]).await
+    {
+        warn!("Failed to delete branch {}: {}", branch, e);
+    }
+}
+
+/// Cherry-pick commits from an execution's branch onto a new branch off `base`
+///
+/// The picks happen in a scratch worktree, so neither the repo root's checkout
+/// nor the execution's worktree is touched. Picked commits carry a
+/// "(cherry picked from commit ...)" trailer. On a conflict the pick is
+/// aborted and the new branch deleted.
+///
+/// # Arguments
+/// * `repo_root` - Path to the main repository
+/// * `exec_id` - Execution whose branch (`taskdaemon/<exec_id>`) to pick from
+/// * `commits` - Commits and ranges to pick (see `resolve_commits`); None for all
+/// * `branch` - Name of the branch to create (must not exist)
+/// * `base` - Branch or commit to start the new branch from
+pub async fn cherry_pick_to_branch(
+    repo_root: &Path,
+    exec_id: &str,
+    commits: Option<&str>,
+    branch: &str,
+    base: &str,
+) -> Result<CherryPickResult> {
+    debug!(?repo_root, %exec_id, ?commits, %branch, %base, "cherry_pick_to_branch: called");
+    let exec_branch = format!("taskdaemon/{}", exec_id);
+    if git(
+        repo_root,
+        &[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("refs/heads/{}", exec_branch),
+        ],
+    )
+    .await
+    .is_err()
+    {
+        bail!("Branch {} not found (was its worktree pruned?)", exec_branch);
+    }
+    if git(
+        repo_root,
+        &["rev-parse", "--verify", "--quiet", &format!("refs/heads/{}", branch)],
+    )
+    .await
+    .is_ok()
+    {
+        bail!("Branch {} already exists", branch);
+    }
+
+    let picks = resolve_commits(repo_root, &exec_branch, base, commits).await?;
+    info!(
+        exec_id = %exec_id,
+        branch = %branch,
+        count = picks.len(),
+        "Cherry-picking commits onto new branch"
+    );
+
+    let scratch: PathBuf = std::env::temp_dir().join(format!("taskdaemon-pick-{}-{}", exec_id, std::process::id()));
+    let scratch_str = scratch.to_string_lossy().to_string();
+    git(
+        repo_root,
+        &["worktree", "add", "--quiet", "-b", branch, &scratch_str, base],
+    )
+    .await?;
+
+    let mut args = vec!["cherry-pick", "-x"];
+    args.extend(picks.iter().map(String::as_str));
+    let output = Command::new("git").args(&args).current_dir(&scratch).output().await?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let failed = git(&scratch, &["rev-parse", "--verify", "--quiet", "CHERRY_PICK_HEAD"])
+            .await
+            .ok();
+        let _ = git(&scratch, &["cherry-pick", "--abort"]).await;
+        discard_pick_worktree(repo_root, &scratch, Some(branch)).await;
+        let Some(commit) = failed else {
+            debug!("cherry_pick_to_branch: cherry-pick failed");
+            bail!("Cherry-pick failed: {}", stderr.trim());
+        };
+        debug!(%commit, "cherry_pick_to_branch: conflict");
+        warn!("Cherry-pick conflict for {} at {}", exec_id, commit);
+        return Ok(CherryPickResult::Conflict {
+            commit,
+            message: stderr,
+        });
+    }
+
+    discard_pick_worktree(repo_root, &scratch, None).await;
+    info!(exec_id = %exec_id, branch = %branch, "Cherry-pick completed");
+    Ok(CherryPickResult::Success {
+        branch: branch.to_string(),
+        commits: picks,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -311,8 +552,135 @@ mod tests {
         setup_git_repo(repo_dir.path()).await;
 
         // Try to merge a branch that doesn't exist - should fail
-        let result = merge_to_main(repo_dir.path(), worktree_dir.path(), "nonexistent", "Test Spec").await;
+        let result = merge_to_main(repo_dir.path(), worktree_dir.path(), "nonexistent", "Test Spec", None).await;
 
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_squash_merge_with_message_and_changelog() {
+        let repo_dir = tempdir().unwrap();
+        let repo = repo_dir.path();
+        setup_git_repo(repo).await;
+        git(repo, &["branch", "-M", "main"]).await.unwrap();
+        let worktree = repo.join("wt");
+        git(repo, &["worktree", "add", "--quiet", "-b", "taskdaemon/exec-1", "wt"])
+            .await
+            .unwrap();
+        std::fs::write(worktree.join("greeting.txt"), "hello").unwrap();
+        git(&worktree, &["add", "-A"]).await.unwrap();
+        git(&worktree, &["commit", "--quiet", "-m", "wip"]).await.unwrap();
+        // Left uncommitted: the merge commits it first
+        std::fs::write(worktree.join("farewell.txt"), "bye").unwrap();
+
+        let message = MergeMessage {
+            commit: "feat(greet): add greeting\n\nSays hello.".to_string(),
+            squash: true,
+            changelog: Some(("CHANGELOG.md".to_string(), "- feat: add greeting".to_string())),
+        };
+        let result = merge_to_main(repo, &worktree, "exec-1", "Add greeting", Some(&message))
+            .await
+            .unwrap();
+
+        // No remote to push to, but the merge itself is done
+        assert!(matches!(result, MergeResult::PushFailed { .. }));
+        assert_eq!(git(repo, &["log", "-1", "--format=%B"]).await.unwrap(), message.commit);
+        assert_eq!(
+            git(repo, &["log", "-1", "--format=%P"])
+                .await
+                .unwrap()
+                .split(' ')
+                .count(),
+            1
+        );
+        assert!(repo.join("farewell.txt").exists());
+        let changelog = std::fs::read_to_string(repo.join("CHANGELOG.md")).unwrap();
+        assert!(changelog.contains("## Unreleased\n\n- feat: add greeting"));
+        assert_eq!(
+            git(repo, &["status", "--porcelain", "--untracked-files=no"])
+                .await
+                .unwrap(),
+            ""
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cherry_pick_to_branch() {
+        let repo_dir = tempdir().unwrap();
+        let repo = repo_dir.path();
+        setup_git_repo(repo).await;
+
+        // The loop's branch: a.txt, then b.txt, then an edit to a.txt
+        git(repo, &["checkout", "--quiet", "-b", "taskdaemon/exec-1"])
+            .await
+            .unwrap();
+        let mut loop_commits = Vec::new();
+        for (file, content) in [("a.txt", "a1"), ("b.txt", "b1"), ("a.txt", "a2")] {
+            std::fs::write(repo.join(file), content).unwrap();
+            git(repo, &["add", "-A"]).await.unwrap();
+            git(repo, &["commit", "--quiet", "-m", file]).await.unwrap();
+            loop_commits.push(git(repo, &["rev-parse", "HEAD"]).await.unwrap());
+        }
+        git(repo, &["checkout", "--quiet", "-"]).await.unwrap();
+
+        // Just the second commit
+        let result = cherry_pick_to_branch(repo, "exec-1", Some(&loop_commits[1][..8]), "picked/b", "HEAD")
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            CherryPickResult::Success {
+                branch: "picked/b".into(),
+                commits: vec![loop_commits[1].clone()],
+            }
+        );
+        let files = git(repo, &["ls-tree", "--name-only", "picked/b"]).await.unwrap();
+        assert_eq!(files, "b.txt");
+        let message = git(repo, &["log", "-1", "--format=%B", "picked/b"]).await.unwrap();
+        assert!(message.contains(&format!("cherry picked from commit {}", loop_commits[1])));
+        assert!(git(repo, &["status", "--porcelain"]).await.unwrap().is_empty());
+
+        // Everything on the branch by default; existing branches are refused
+        let all = cherry_pick_to_branch(repo, "exec-1", None, "picked/all", "HEAD")
+            .await
+            .unwrap();
+        assert!(matches!(all, CherryPickResult::Success { commits, .. } if commits == loop_commits));
+        assert!(
+            cherry_pick_to_branch(repo, "exec-1", None, "picked/all", "HEAD")
+                .await
+                .is_err()
+        );
+
+        // The a.txt edit without the commit creating it conflicts; nothing is kept
+        let range = format!("{}..{}", loop_commits[1], loop_commits[2]);
+        let conflict = cherry_pick_to_branch(repo, "exec-1", Some(&range), "picked/a", "HEAD")
+            .await
+            .unwrap();
+        assert!(matches!(conflict, CherryPickResult::Conflict { ref commit, .. } if *commit == loop_commits[2]));
+        assert!(
+            git(repo, &["rev-parse", "--verify", "--quiet", "refs/heads/picked/a"])
+                .await
+                .is_err()
+        );
+
+        // Commits must come from the execution's branch and not be on the base already
+        std::fs::write(repo.join("c.txt"), "c").unwrap();
+        git(repo, &["add", "-A"]).await.unwrap();
+        git(repo, &["commit", "--quiet", "-m", "c.txt"]).await.unwrap();
+        assert!(
+            cherry_pick_to_branch(repo, "exec-1", Some("HEAD"), "picked/x", "HEAD")
+                .await
+                .is_err()
+        );
+        assert!(
+            cherry_pick_to_branch(repo, "exec-1", Some("HEAD~1"), "picked/x", "HEAD")
+                .await
+                .is_err()
+        );
+        assert!(
+            cherry_pick_to_branch(repo, "missing", None, "picked/y", "HEAD")
+                .await
+                .is_err()
+        );
+    }
 }
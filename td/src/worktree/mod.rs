@@ -3,8 +3,20 @@
 //! Each Ralph loop executes in its own git worktree on a feature branch,
 //! enabling parallel work without file conflicts.
 
+mod changelog;
+mod cleanup;
 mod manager;
 mod merge;
+mod snapshot;
 
+pub use changelog::{
+    COMMIT_TYPES, CommitParts, DEFAULT_CHANGELOG_TEMPLATE, DEFAULT_COMMIT_TEMPLATE, DiffSummary, FileChange,
+    MergeConfig, MergeMessage, add_changelog_entry, append_changelog,
+};
+pub use cleanup::{
+    PruneCandidate, PrunePolicy, PruneReason, WorktreeState, WorktreeUsage, classify, dir_size, format_size, now_ms,
+    plan_prune,
+};
 pub use manager::{WorktreeConfig, WorktreeError, WorktreeInfo, WorktreeManager};
-pub use merge::{MergeResult, merge_to_main};
+pub use merge::{CherryPickResult, MergeResult, cherry_pick_to_branch, commit_pending, merge_to_main};
+pub use snapshot::{create_snapshot, delete_snapshots, list_snapshots, restore_snapshot, snapshot_ref};
@@ -0,0 +1,201 @@
+//! Per-iteration worktree snapshots
+//!
+//! After each iteration the engine records the worktree's files (tracked and
+//! untracked, not ignored ones such as `target/`) as a commit referenced by
+//! `refs/taskdaemon/snapshots/<exec-id>/<iteration>`. The commit's parent is
+//! the worktree's HEAD at the time. HEAD, the index and the branch are left
+//! alone, so snapshots never show up in the loop's diff or in what gets merged.
+//!
+//! Restoring a snapshot resets HEAD to that parent and the files to the
+//! snapshot, leaving the changes uncommitted just as they were.
+
+use std::path::Path;
+
+use eyre::{Context, Result, bail};
+use tokio::process::Command;
+use tracing::{debug, info};
+
+/// Namespace of snapshot refs (shared by all worktrees of the repository)
+const SNAPSHOT_REF_PREFIX: &str = "refs/taskdaemon/snapshots";
+
+/// Identity for snapshot commits, so they work without a configured user
+const SNAPSHOT_IDENTITY: [(&str, &str); 4] = [
+    ("GIT_AUTHOR_NAME", "taskdaemon"),
+    ("GIT_AUTHOR_EMAIL", "taskdaemon@localhost"),
+    ("GIT_COMMITTER_NAME", "taskdaemon"),
+    ("GIT_COMMITTER_EMAIL", "taskdaemon@localhost"),
+];
+
+/// Ref holding the snapshot of `exec_id` after `iteration`
+pub fn snapshot_ref(exec_id: &str, iteration: u32) -> String {
+    format!("{}/{}/{}", SNAPSHOT_REF_PREFIX, exec_id, iteration)
+}
+
+/// Run git in `dir`, returning trimmed stdout
+async fn git(dir: &Path, args: &[&str], env: &[(&str, &str)]) -> Result<String> {
+    debug!(?dir, ?args, "snapshot git: called");
+    let output = Command::new("git")
+        .args(args)
+        .envs(env.iter().copied())
+        .current_dir(dir)
+        .output()
+        .await
+        .with_context(|| format!("Failed to run git {}", args.join(" ")))?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Record the worktree's current files as the snapshot for `iteration`
+///
+/// Returns the snapshot commit. An existing snapshot for the same iteration
+/// (from an earlier run of the execution) is replaced.
+pub async fn create_snapshot(worktree: &Path, exec_id: &str, iteration: u32) -> Result<String> {
+    debug!(?worktree, %exec_id, iteration, "create_snapshot: called");
+    let git_dir = git(worktree, &["rev-parse", "--absolute-git-dir"], &[]).await?;
+    let git_dir = Path::new(&git_dir);
+
+    // Stage everything into a scratch index, seeded from the real one so unchanged files are not rehashed
+    let index = git_dir.join("taskdaemon-snapshot.index");
+    if tokio::fs::copy(git_dir.join("index"), &index).await.is_err() {
+        debug!("create_snapshot: no index to seed from");
+    }
+    let index_env = [("GIT_INDEX_FILE", index.to_str().unwrap_or_default())];
+    let tree = async {
+        git(worktree, &["add", "-A"], &index_env).await?;
+        git(worktree, &["write-tree"], &index_env).await
+    }
+    .await;
+    let _ = tokio::fs::remove_file(&index).await;
+    let tree = tree?;
+
+    let head = git(worktree, &["rev-parse", "HEAD"], &[]).await?;
+    let message = format!("taskdaemon snapshot: {} iteration {}", exec_id, iteration);
+    let commit = git(
+        worktree,
+        &["commit-tree", &tree, "-p", &head, "-m", &message],
+        &SNAPSHOT_IDENTITY,
+    )
+    .await?;
+    git(
+        worktree,
+        &["update-ref", &snapshot_ref(exec_id, iteration), &commit],
+        &[],
+    )
+    .await?;
+
+    debug!(%commit, iteration, "create_snapshot: recorded");
+    Ok(commit)
+}
+
+/// Iterations of `exec_id` that have a snapshot, ascending
+pub async fn list_snapshots(repo: &Path, exec_id: &str) -> Result<Vec<u32>> {
+    debug!(?repo, %exec_id, "list_snapshots: called");
+    let prefix = format!("{}/{}/", SNAPSHOT_REF_PREFIX, exec_id);
+    let refs = git(repo, &["for-each-ref", "--format=%(refname)", &prefix], &[]).await?;
+    let mut iterations: Vec<u32> = refs
+        .lines()
+        .filter_map(|name| name.strip_prefix(&prefix)?.parse().ok())
+        .collect();
+    iterations.sort_unstable();
+    Ok(iterations)
+}
+
+/// Reset the worktree to the snapshot taken after `iteration`
+///
+/// Files not ignored by git are replaced by the snapshot's; ignored build
+/// output is kept.
+pub async fn restore_snapshot(worktree: &Path, exec_id: &str, iteration: u32) -> Result<()> {
+    debug!(?worktree, %exec_id, iteration, "restore_snapshot: called");
+    let name = format!("{}^{{commit}}", snapshot_ref(exec_id, iteration));
+    let Ok(commit) = git(worktree, &["rev-parse", "--verify", "--quiet", &name], &[]).await else {
+        bail!("No snapshot of {} for iteration {}", exec_id, iteration);
+    };
+    let parent = git(worktree, &["rev-parse", &format!("{}^", commit)], &[]).await?;
+
+    git(worktree, &["reset", "--quiet", "--hard", &parent], &[]).await?;
+    git(worktree, &["clean", "--quiet", "-fd"], &[]).await?;
+    // Check out the snapshot's files (removing ones it doesn't have), then unstage them
+    git(worktree, &["read-tree", "-u", "--reset", &commit], &[]).await?;
+    git(worktree, &["reset", "--quiet"], &[]).await?;
+
+    info!("Restored worktree of {} to iteration {}", exec_id, iteration);
+    Ok(())
+}
+
+/// Delete all snapshots of `exec_id`
+pub async fn delete_snapshots(repo: &Path, exec_id: &str) -> Result<usize> {
+    debug!(?repo, %exec_id, "delete_snapshots: called");
+    let iterations = list_snapshots(repo, exec_id).await?;
+    for iteration in &iterations {
+        git(repo, &["update-ref", "-d", &snapshot_ref(exec_id, *iteration)], &[]).await?;
+    }
+    debug!(count = iterations.len(), "delete_snapshots: deleted");
+    Ok(iterations.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    async fn setup_git_repo(dir: &Path) {
+        for args in [
+            &["init"][..],
+            &["config", "user.email", "test@test.com"][..],
+            &["config", "user.name", "Test"][..],
+        ] {
+            git(dir, args, &[]).await.unwrap();
+        }
+        fs::write(dir.join(".gitignore"), "target/\n").unwrap();
+        fs::write(dir.join("lib.rs"), "fn original() {}\n").unwrap();
+        git(dir, &["add", "-A"], &[]).await.unwrap();
+        git(dir, &["commit", "-m", "initial"], &[]).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_and_restore() {
+        let repo = tempdir().unwrap();
+        let dir = repo.path();
+        setup_git_repo(dir).await;
+        let head = git(dir, &["rev-parse", "HEAD"], &[]).await.unwrap();
+
+        // Iteration 1: edit a tracked file, add an untracked one
+        fs::write(dir.join("lib.rs"), "fn good() {}\n").unwrap();
+        fs::write(dir.join("new.rs"), "fn helper() {}\n").unwrap();
+        create_snapshot(dir, "exec-1", 1).await.unwrap();
+
+        // Snapshots leave HEAD and the uncommitted changes alone
+        assert_eq!(git(dir, &["rev-parse", "HEAD"], &[]).await.unwrap(), head);
+        let status = git(dir, &["status", "--porcelain"], &[]).await.unwrap();
+        assert!(status.contains("M lib.rs") && status.contains("?? new.rs"));
+
+        // Iteration 2 makes things worse
+        fs::write(dir.join("lib.rs"), "fn broken( {}\n").unwrap();
+        fs::remove_file(dir.join("new.rs")).unwrap();
+        fs::write(dir.join("junk.rs"), "junk").unwrap();
+        fs::create_dir_all(dir.join("target")).unwrap();
+        fs::write(dir.join("target/cache"), "build output").unwrap();
+        create_snapshot(dir, "exec-1", 2).await.unwrap();
+        assert_eq!(list_snapshots(dir, "exec-1").await.unwrap(), vec![1, 2]);
+
+        restore_snapshot(dir, "exec-1", 1).await.unwrap();
+        assert_eq!(fs::read_to_string(dir.join("lib.rs")).unwrap(), "fn good() {}\n");
+        assert_eq!(fs::read_to_string(dir.join("new.rs")).unwrap(), "fn helper() {}\n");
+        assert!(!dir.join("junk.rs").exists());
+        assert!(dir.join("target/cache").exists(), "ignored files are kept");
+        assert_eq!(git(dir, &["rev-parse", "HEAD"], &[]).await.unwrap(), head);
+        let status = git(dir, &["status", "--porcelain"], &[]).await.unwrap();
+        assert!(status.contains("M lib.rs") && status.contains("?? new.rs"));
+
+        assert!(restore_snapshot(dir, "exec-1", 7).await.is_err());
+        assert_eq!(delete_snapshots(dir, "exec-1").await.unwrap(), 2);
+        assert!(list_snapshots(dir, "exec-1").await.unwrap().is_empty());
+    }
+}
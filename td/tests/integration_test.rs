@@ -94,6 +94,8 @@ async fn test_scheduler_basic_flow() {
         max_requests_per_window: 100,
         rate_window_secs: 1,
         default_priority: Priority::Normal,
+        adaptive: true,
+        ..Default::default()
     };
     let scheduler = Arc::new(Scheduler::new(config));
 
@@ -119,6 +121,8 @@ async fn test_scheduler_sequential_requests() {
         max_requests_per_window: 100,
         rate_window_secs: 60,
         default_priority: Priority::Normal,
+        adaptive: true,
+        ..Default::default()
     };
     let scheduler = Arc::new(Scheduler::new(config));
 
@@ -2135,3 +2139,46 @@ async fn test_tool_context_without_explore_spawner() {
     // Default context should not have explore spawner (prevents nested explores)
     assert!(ctx.explore_spawner.is_none());
 }
+
+#[tokio::test]
+async fn test_tools_run_in_worker_process() {
+    use taskdaemon::config::ToolWorkerConfig;
+    use taskdaemon::llm::ToolCall;
+    use taskdaemon::tools::ToolContext;
+    use taskdaemon::{ToolExecutor, ToolProfile};
+
+    let temp = TempDir::new().unwrap();
+    std::fs::write(temp.path().join("notes.txt"), "alpha\n").unwrap();
+    let ctx = ToolContext::new(temp.path().to_path_buf(), "worker-exec".to_string());
+
+    let mut executor = ToolExecutor::with_profile(ToolProfile::Full);
+    executor
+        .use_worker(&ToolWorkerConfig {
+            enabled: true,
+            program: Some(env!("CARGO_BIN_EXE_td").into()),
+            memory_mb: None,
+        })
+        .unwrap();
+    let call = |name: &str, input: serde_json::Value| ToolCall {
+        id: format!("call-{}", name),
+        name: name.to_string(),
+        input,
+    };
+
+    // The worker reports the read back, so the edit (also in the worker) is allowed
+    let read = executor.execute(&call("read", serde_json::json!({"path": "notes.txt"})), &ctx).await;
+    assert!(!read.is_error, "{}", read.content);
+    let edit = executor
+        .execute(
+            &call("edit", serde_json::json!({"path": "notes.txt", "old_string": "alpha", "new_string": "beta"})),
+            &ctx,
+        )
+        .await;
+    assert!(!edit.is_error, "{}", edit.content);
+    assert_eq!(std::fs::read_to_string(temp.path().join("notes.txt")).unwrap(), "beta\n");
+
+    let bash = executor.execute(&call("bash", serde_json::json!({"command": "echo $PPID"})), &ctx).await;
+    assert!(!bash.is_error, "{}", bash.content);
+    assert_ne!(bash.content.trim(), std::process::id().to_string());
+    assert_eq!(executor.worker().unwrap().restarts(), 0);
+}
@@ -0,0 +1,89 @@
+// Batched writes applied all-or-nothing
+
+use crate::record::{IndexValue, Record};
+use eyre::{Context, Result};
+use std::collections::HashMap;
+
+/// One write in a batch
+#[derive(Debug, Clone)]
+pub enum BatchOp {
+    /// Create or replace a record
+    Put {
+        collection: &'static str,
+        id: String,
+        data: serde_json::Value,
+        updated_at: i64,
+        indexes: HashMap<String, IndexValue>,
+    },
+    /// Delete a record
+    Delete { collection: &'static str, id: String },
+}
+
+impl BatchOp {
+    /// Collection the op writes to
+    pub fn collection(&self) -> &'static str {
+        match self {
+            Self::Put { collection, .. } | Self::Delete { collection, .. } => collection,
+        }
+    }
+
+    /// ID of the record the op writes
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Put { id, .. } | Self::Delete { id, .. } => id,
+        }
+    }
+}
+
+/// Writes across any number of records and collections, applied together by
+/// [`crate::Store::apply_batch`]
+///
+/// Ops are applied in the order they were added, so a later put of the same
+/// record wins.
+#[derive(Debug, Clone, Default)]
+pub struct Batch {
+    ops: Vec<BatchOp>,
+}
+
+impl Batch {
+    /// Create an empty batch
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a create-or-replace of a record
+    pub fn put<T: Record>(&mut self, record: &T) -> Result<()> {
+        let data = serde_json::to_value(record).context("Failed to serialize record")?;
+        self.ops.push(BatchOp::Put {
+            collection: T::collection_name(),
+            id: record.id().to_string(),
+            data,
+            updated_at: record.updated_at(),
+            indexes: record.indexed_fields(),
+        });
+        Ok(())
+    }
+
+    /// Add a delete of a record
+    pub fn delete<T: Record>(&mut self, id: &str) {
+        self.ops.push(BatchOp::Delete {
+            collection: T::collection_name(),
+            id: id.to_string(),
+        });
+    }
+
+    /// Ops in the order they will be applied
+    pub fn ops(&self) -> &[BatchOp] {
+        &self.ops
+    }
+
+    /// Number of ops in the batch
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// True if the batch has no ops
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+}
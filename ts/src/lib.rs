@@ -1,11 +1,13 @@
 // TaskStore - Generic persistent state management with SQLite+JSONL+Git
 
+pub mod batch;
 pub mod filter;
 pub mod jsonl;
 pub mod record;
 pub mod store;
 
 // Re-export main types for convenience
+pub use batch::{Batch, BatchOp};
 pub use filter::{Filter, FilterOp};
 pub use record::{IndexValue, Record};
 pub use store::{Store, now_ms};
@@ -1,5 +1,6 @@
 // Generic store implementation using JSONL + SQLite
 
+use crate::batch::{Batch, BatchOp};
 use crate::filter::{Filter, FilterOp};
 use crate::jsonl;
 use crate::record::{IndexValue, Record};
@@ -285,6 +286,65 @@ impl Store {
         Ok(count)
     }
 
+    /// Apply every op in a batch, or none of them
+    ///
+    /// All ops are validated and written to SQLite inside one transaction, and
+    /// the JSONL lines for each collection are appended with a single write
+    /// before the transaction commits. Any failure rolls the SQLite side back
+    /// and leaves the JSONL files untouched. Returns the number of ops applied.
+    pub fn apply_batch(&mut self, batch: &Batch) -> Result<usize> {
+        debug!(op_count = batch.len(), "apply_batch: called");
+        for op in batch.ops() {
+            Self::validate_collection_name(op.collection())?;
+            Self::validate_id(op.id())?;
+        }
+
+        let tx = self.db.transaction()?;
+        let mut lines: Vec<(&'static str, Vec<String>)> = Vec::new();
+        for op in batch.ops() {
+            let line = match op {
+                BatchOp::Put {
+                    collection,
+                    id,
+                    data,
+                    updated_at,
+                    indexes,
+                } => {
+                    let data_json = serde_json::to_string(data)?;
+                    tx.execute(
+                        "INSERT OR REPLACE INTO records (collection, id, data_json, updated_at)
+                         VALUES (?1, ?2, ?3, ?4)",
+                        rusqlite::params![collection, id, data_json, updated_at],
+                    )?;
+                    Self::update_indexes_tx(&tx, collection, id, indexes)?;
+                    data_json
+                }
+                BatchOp::Delete { collection, id } => {
+                    tx.execute(
+                        "DELETE FROM records WHERE collection = ?1 AND id = ?2",
+                        rusqlite::params![collection, id],
+                    )?;
+                    serde_json::to_string(&serde_json::json!({
+                        "id": id,
+                        "deleted": true,
+                        "updated_at": crate::now_ms(),
+                    }))?
+                }
+            };
+            match lines.iter_mut().find(|(c, _)| *c == op.collection()) {
+                Some((_, collection_lines)) => collection_lines.push(line),
+                None => lines.push((op.collection(), vec![line])),
+            }
+        }
+
+        for (collection, collection_lines) in &lines {
+            Self::append_jsonl_lines(&self.base_path, collection, collection_lines)?;
+        }
+        tx.commit()?;
+
+        Ok(batch.len())
+    }
+
     /// List records with optional filtering
     pub fn list<T: Record>(&self, filters: &[Filter]) -> Result<Vec<T>> {
         let collection = T::collection_name();
@@ -445,6 +505,29 @@ impl Store {
         Ok(())
     }
 
+    fn append_jsonl_lines(base_path: &Path, collection: &str, lines: &[String]) -> Result<()> {
+        let jsonl_path = base_path.join(format!("{}.jsonl", collection));
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&jsonl_path)
+            .context("Failed to open JSONL file for appending")?;
+
+        // Acquire exclusive lock before writing
+        file.lock_exclusive().context("Failed to acquire file lock")?;
+
+        // One write so readers never see part of the batch
+        let mut buf = lines.join("\n");
+        buf.push('\n');
+
+        use std::io::Write;
+        file.write_all(buf.as_bytes())?;
+        file.sync_all()?;
+
+        Ok(())
+    }
+
     fn update_indexes_tx(
         tx: &rusqlite::Transaction,
         collection: &str,
@@ -1046,6 +1129,53 @@ mod tests {
         assert_eq!(records[0].status, "active");
     }
 
+    #[test]
+    fn test_apply_batch_is_all_or_nothing() {
+        let temp = TempDir::new().unwrap();
+        let mut store = Store::open(temp.path()).unwrap();
+        let record = |id: &str, status: &str| TestRecord {
+            id: id.to_string(),
+            name: id.to_string(),
+            status: status.to_string(),
+            count: 1,
+            active: true,
+            updated_at: now_ms(),
+        };
+        store.create(record("old", "active")).unwrap();
+
+        let mut batch = Batch::new();
+        batch.put(&record("a", "active")).unwrap();
+        batch.put(&record("b", "done")).unwrap();
+        batch.delete::<TestRecord>("old");
+        assert_eq!(store.apply_batch(&batch).unwrap(), 3);
+
+        let ids: Vec<String> = store.list::<TestRecord>(&[]).unwrap().into_iter().map(|r| r.id).collect();
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&"a".to_string()) && ids.contains(&"b".to_string()));
+        let done: Vec<TestRecord> = store
+            .list(&[Filter {
+                field: "status".to_string(),
+                op: FilterOp::Eq,
+                value: IndexValue::String("done".to_string()),
+            }])
+            .unwrap();
+        assert_eq!(done.len(), 1);
+
+        // An invalid op anywhere in the batch means nothing is written
+        let jsonl_path = temp.path().join(".taskstore/test_records.jsonl");
+        let before = fs::read_to_string(&jsonl_path).unwrap();
+        let mut batch = Batch::new();
+        batch.put(&record("c", "active")).unwrap();
+        batch.put(&record("  ", "active")).unwrap();
+        assert!(store.apply_batch(&batch).is_err());
+        assert!(store.get::<TestRecord>("c").unwrap().is_none());
+        assert_eq!(fs::read_to_string(&jsonl_path).unwrap(), before);
+
+        // The JSONL side replays to the same state
+        store.sync().unwrap();
+        assert_eq!(store.list::<TestRecord>(&[]).unwrap().len(), 2);
+    }
+
     #[test]
     fn test_validation_collection_name() {
         // Valid